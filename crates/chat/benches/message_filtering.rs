@@ -1,7 +1,7 @@
 use chat::{
     types::*, ChatSource, MessageFilter, SourcesManager, ChatPattern,
 };
-use chrono::{DateTime, Utc, Duration};
+use chrono::{Utc, Duration};
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
 use std::time::Duration as StdDuration;
 
@@ -82,44 +82,17 @@ impl ChatSource for BenchmarkChatSource {
             ChatPattern::Multiple(_) => {
                 // Not implemented for benchmark
             }
+            ChatPattern::Label(_) => {
+                // Resolved by SourcesManager before reaching sources
+            }
+            ChatPattern::Topic { .. } => {
+                // Benchmark chats have no topics
+                messages.clear();
+            }
         }
 
-        // Apply time filters
-        if let Some(since) = filter.since {
-            messages.retain(|msg| msg.timestamp >= since);
-        }
-
-        if let Some(before) = filter.before {
-            messages.retain(|msg| msg.timestamp <= before);
-        }
-
-        // Apply sender filter
-        if let Some(ref sender) = filter.sender {
-            let sender_lower = sender.to_lowercase();
-            messages.retain(|msg| {
-                msg.sender
-                    .display_name
-                    .as_ref()
-                    .map(|name| name.to_lowercase().contains(&sender_lower))
-                    .unwrap_or(false)
-            });
-        }
-
-        // Apply search filter
-        if let Some(ref search) = filter.search {
-            let search_lower = search.to_lowercase();
-            messages.retain(|msg| match &msg.content {
-                MessageContent::Text(text) => text.to_lowercase().contains(&search_lower),
-                _ => false,
-            });
-        }
-
-        // Apply limit
-        if let Some(limit) = filter.limit {
-            messages.truncate(limit);
-        }
-
-        Ok(messages)
+        // Time, sender, search and limit follow the shared semantics
+        Ok(apply_filter(messages, &filter))
     }
 
     async fn subscribe(&self) -> anyhow::Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
@@ -149,6 +122,11 @@ fn generate_messages(count: usize) -> Vec<Message> {
             timestamp: base_time + Duration::seconds(i as i64),
             reply_to: None,
             edited: i % 10 == 0,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
         };
         messages.push(message);
     }
@@ -172,6 +150,7 @@ fn generate_chats(count: usize) -> Vec<Chat> {
                 ChatType::Channel
             },
             participant_count: Some((i % 50) + 2),
+            topic: None,
         };
         chats.push(chat);
     }
@@ -205,6 +184,12 @@ fn benchmark_query_all(c: &mut Criterion) {
                 search: None,
                 limit: None,
                 content_type: None,
+                mentions_me: false,
+                exact: false,
+                language: None,
+                include_system: false,
+                in_attachments: false,
+                separate_merged: false,
             };
 
             b.to_async(&runtime).iter(|| async {
@@ -247,6 +232,12 @@ fn benchmark_time_filter(c: &mut Criterion) {
                 search: None,
                 limit: None,
                 content_type: None,
+                mentions_me: false,
+                exact: false,
+                language: None,
+                include_system: false,
+                in_attachments: false,
+                separate_merged: false,
             };
 
             b.to_async(&runtime).iter(|| async {
@@ -289,6 +280,12 @@ fn benchmark_search_filter(c: &mut Criterion) {
                 search: Some("message".to_string()),
                 limit: None,
                 content_type: None,
+                mentions_me: false,
+                exact: false,
+                language: None,
+                include_system: false,
+                in_attachments: false,
+                separate_merged: false,
             };
 
             b.to_async(&runtime).iter(|| async {
@@ -331,6 +328,12 @@ fn benchmark_combined_filters(c: &mut Criterion) {
                 search: Some("message".to_string()),
                 limit: Some(100),
                 content_type: None,
+                mentions_me: false,
+                exact: false,
+                language: None,
+                include_system: false,
+                in_attachments: false,
+                separate_merged: false,
             };
 
             b.to_async(&runtime).iter(|| async {
@@ -373,6 +376,12 @@ fn benchmark_chat_pattern_matching(c: &mut Criterion) {
                 search: None,
                 limit: None,
                 content_type: None,
+                mentions_me: false,
+                exact: false,
+                language: None,
+                include_system: false,
+                in_attachments: false,
+                separate_merged: false,
             };
 
             b.to_async(&runtime).iter(|| async {
@@ -422,6 +431,12 @@ fn benchmark_cross_source_queries(c: &mut Criterion) {
                 search: None,
                 limit: None,
                 content_type: None,
+                mentions_me: false,
+                exact: false,
+                language: None,
+                include_system: false,
+                in_attachments: false,
+                separate_merged: false,
             };
 
             b.to_async(&runtime).iter(|| async {
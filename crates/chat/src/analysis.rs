@@ -0,0 +1,307 @@
+use chrono::{Datelike, TimeZone, Timelike};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::language;
+use crate::types::{ChatId, Message, MessageContent};
+
+/// Replies slower than this are treated as a new conversation, not a response
+const MAX_RESPONSE_SECS: i64 = 12 * 60 * 60;
+
+/// Message counts by weekday (Monday first) and hour of day
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityHeatmap {
+    pub counts: [[usize; 24]; 7],
+}
+
+impl ActivityHeatmap {
+    /// Build a heatmap with hours and weekdays in the given timezone
+    pub fn from_messages<Tz: TimeZone>(messages: &[&Message], tz: &Tz) -> Self {
+        let mut counts = [[0; 24]; 7];
+
+        for message in messages {
+            let local = message.timestamp.with_timezone(tz);
+            let weekday = local.weekday().num_days_from_monday() as usize;
+            counts[weekday][local.hour() as usize] += 1;
+        }
+
+        Self { counts }
+    }
+
+    /// Busiest (weekday, hour, count), if there is any activity
+    pub fn peak(&self) -> Option<(usize, usize, usize)> {
+        let mut peak = None;
+
+        for (day, hours) in self.counts.iter().enumerate() {
+            for (hour, &count) in hours.iter().enumerate() {
+                let best = peak.map(|(_, _, best)| best).unwrap_or(0);
+                if count > best {
+                    peak = Some((day, hour, count));
+                }
+            }
+        }
+
+        peak
+    }
+
+    /// Highest count in any cell
+    pub fn max(&self) -> usize {
+        self.counts.iter().flatten().copied().max().unwrap_or(0)
+    }
+}
+
+/// How quickly one participant replies to another
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseStats {
+    /// Participant who replied
+    pub responder: String,
+    /// Participant being replied to
+    pub to: String,
+    pub count: usize,
+    pub mean_secs: i64,
+    pub median_secs: i64,
+}
+
+/// Aggregate sentiment over a chat's English text messages, from [`lexicon_sentiment`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SentimentSummary {
+    /// Mean score in [-1, 1]
+    pub mean: f32,
+    pub positive: usize,
+    pub neutral: usize,
+    pub negative: usize,
+    /// Messages left unscored because they are in another language
+    pub skipped: usize,
+}
+
+/// Analysis results for a single chat
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatAnalysis {
+    pub chat_id: ChatId,
+    pub message_count: usize,
+    pub heatmap: ActivityHeatmap,
+    pub response_times: Vec<ResponseStats>,
+    pub sentiment: Option<SentimentSummary>,
+}
+
+/// Analyze messages per chat, with hours and weekdays in the given timezone
+pub fn analyze<Tz: TimeZone>(messages: &[Message], tz: &Tz, with_sentiment: bool) -> Vec<ChatAnalysis> {
+    let mut by_chat: HashMap<&ChatId, Vec<&Message>> = HashMap::new();
+    for message in messages {
+        by_chat.entry(&message.chat_id).or_default().push(message);
+    }
+
+    let mut analyses: Vec<ChatAnalysis> = by_chat
+        .into_iter()
+        .map(|(chat_id, mut chat_messages)| {
+            chat_messages.sort_by_key(|m| m.timestamp);
+
+            ChatAnalysis {
+                chat_id: chat_id.clone(),
+                message_count: chat_messages.len(),
+                heatmap: ActivityHeatmap::from_messages(&chat_messages, tz),
+                response_times: response_times(&chat_messages),
+                sentiment: with_sentiment.then(|| sentiment_summary(&chat_messages)),
+            }
+        })
+        .collect();
+
+    // Busiest chats first
+    analyses.sort_by(|a, b| b.message_count.cmp(&a.message_count).then_with(|| a.chat_id.as_str().cmp(b.chat_id.as_str())));
+    analyses
+}
+
+fn sender_name(message: &Message) -> String {
+    message.sender.display_name
+        .clone()
+        .or_else(|| message.sender.username.clone())
+        .unwrap_or_else(|| message.sender.id.to_string())
+}
+
+/// Response times between consecutive messages from different senders (messages sorted oldest first)
+pub fn response_times(messages: &[&Message]) -> Vec<ResponseStats> {
+    let mut samples: HashMap<(String, String), Vec<i64>> = HashMap::new();
+
+    for pair in messages.windows(2) {
+        let (previous, current) = (pair[0], pair[1]);
+        if previous.sender.id == current.sender.id {
+            continue;
+        }
+
+        let secs = (current.timestamp - previous.timestamp).num_seconds();
+        if (0..=MAX_RESPONSE_SECS).contains(&secs) {
+            samples
+                .entry((sender_name(current), sender_name(previous)))
+                .or_default()
+                .push(secs);
+        }
+    }
+
+    let mut stats: Vec<ResponseStats> = samples
+        .into_iter()
+        .map(|((responder, to), mut secs)| {
+            secs.sort_unstable();
+            ResponseStats {
+                responder,
+                to,
+                count: secs.len(),
+                mean_secs: secs.iter().sum::<i64>() / secs.len() as i64,
+                median_secs: secs[secs.len() / 2],
+            }
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.responder.cmp(&b.responder)));
+    stats
+}
+
+const POSITIVE_WORDS: &[&str] = &[
+    "good", "great", "thanks", "thank", "love", "nice", "awesome", "happy", "excellent",
+    "cool", "perfect", "glad", "amazing", "yes", "congrats", "fun", "wonderful", "best",
+];
+
+const NEGATIVE_WORDS: &[&str] = &[
+    "bad", "sad", "hate", "angry", "terrible", "awful", "sorry", "problem", "broken",
+    "annoying", "worst", "wrong", "fail", "failed", "no", "ugh", "upset", "sick",
+];
+
+/// Keyword-count heuristic scoring English text in [-1, 1]: positive minus negative
+/// lexicon words over all lexicon words found. Not a model; it misses negation, sarcasm
+/// and anything outside the two short word lists, and means nothing for other languages.
+pub fn lexicon_sentiment(text: &str) -> f32 {
+    let mut positive = 0;
+    let mut negative = 0;
+
+    for word in text.split(|c: char| !c.is_alphanumeric() && c != '\'') {
+        let word = word.to_lowercase();
+        if POSITIVE_WORDS.contains(&word.as_str()) {
+            positive += 1;
+        } else if NEGATIVE_WORDS.contains(&word.as_str()) {
+            negative += 1;
+        }
+    }
+
+    let total = positive + negative;
+    if total == 0 {
+        0.0
+    } else {
+        (positive as f32 - negative as f32) / total as f32
+    }
+}
+
+/// Score text messages that are English, or too short to tell
+fn sentiment_summary(messages: &[&Message]) -> SentimentSummary {
+    let mut scores = Vec::new();
+    let mut skipped = 0;
+    for message in messages {
+        let MessageContent::Text(text) = &message.content else {
+            continue;
+        };
+        let detected = message.language.as_deref().or_else(|| language::detect(text));
+        if detected.is_some_and(|language| language != "en") {
+            skipped += 1;
+            continue;
+        }
+        scores.push(lexicon_sentiment(text));
+    }
+
+    let mean = if scores.is_empty() {
+        0.0
+    } else {
+        scores.iter().sum::<f32>() / scores.len() as f32
+    };
+
+    SentimentSummary {
+        mean,
+        positive: scores.iter().filter(|&&s| s > 0.0).count(),
+        neutral: scores.iter().filter(|&&s| s == 0.0).count(),
+        negative: scores.iter().filter(|&&s| s < 0.0).count(),
+        skipped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MessageId, User, UserId};
+    use chrono::{Duration, Utc};
+
+    fn message(sender: &str, text: &str, timestamp: chrono::DateTime<Utc>) -> Message {
+        Message {
+            id: MessageId::new(timestamp.timestamp().to_string()),
+            chat_id: ChatId::new("chat"),
+            sender: User {
+                id: UserId::new(sender),
+                username: None,
+                display_name: Some(sender.to_string()),
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp,
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_heatmap_and_peak() {
+        // 2025-01-13 is a Monday
+        let monday_nine = Utc.with_ymd_and_hms(2025, 1, 13, 9, 0, 0).unwrap();
+        let messages = vec![
+            message("alice", "hi", monday_nine),
+            message("bob", "hey", monday_nine + Duration::minutes(5)),
+            message("alice", "later", monday_nine + Duration::days(1)),
+        ];
+
+        let analysis = analyze(&messages, &Utc, false);
+        assert_eq!(analysis.len(), 1);
+        assert_eq!(analysis[0].heatmap.counts[0][9], 2);
+        assert_eq!(analysis[0].heatmap.counts[1][9], 1);
+        assert_eq!(analysis[0].heatmap.peak(), Some((0, 9, 2)));
+        assert!(analysis[0].sentiment.is_none());
+    }
+
+    #[test]
+    fn test_response_times() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 13, 9, 0, 0).unwrap();
+        let messages = [
+            message("alice", "question?", start),
+            message("bob", "answer", start + Duration::seconds(60)),
+            message("bob", "more", start + Duration::seconds(90)),
+            message("alice", "ok", start + Duration::seconds(390)),
+        ];
+        let refs: Vec<&Message> = messages.iter().collect();
+
+        let stats = response_times(&refs);
+        assert_eq!(stats.len(), 2);
+        let alice = stats.iter().find(|s| s.responder == "alice").unwrap();
+        assert_eq!(alice.to, "bob");
+        assert_eq!(alice.mean_secs, 300);
+    }
+
+    #[test]
+    fn test_lexicon_sentiment() {
+        assert!(lexicon_sentiment("Thanks, that's great!") > 0.0);
+        assert!(lexicon_sentiment("this is broken and terrible") < 0.0);
+        assert_eq!(lexicon_sentiment("meeting at 5"), 0.0);
+    }
+
+    #[test]
+    fn test_sentiment_skips_other_languages() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 13, 9, 0, 0).unwrap();
+        let messages = vec![
+            message("alice", "Thanks, that's great!", start),
+            // "No" is in the lexicon, but this is Finnish
+            message("bob", "No niin, huomenna on palaveri toimistolla kello kymmenen, tuokaa kannettavat mukaan.", start),
+        ];
+
+        let sentiment = analyze(&messages, &Utc, true).remove(0).sentiment.unwrap();
+        assert_eq!((sentiment.positive, sentiment.neutral, sentiment.negative), (1, 0, 0));
+        assert_eq!(sentiment.skipped, 1);
+    }
+}
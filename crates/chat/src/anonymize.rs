@@ -0,0 +1,448 @@
+//! Anonymized exports, for sharing datasets without the people in them
+//!
+//! Every user and chat gets a pseudonym (`user-3f9a2c1d`, `chat-81be07aa`) from a hash of
+//! its ID with a salt kept on this machine, so the same person has the same pseudonym in
+//! every export but nobody without the salt can work back to them. Usernames and phone
+//! numbers are dropped, as are phone numbers, email addresses and @handles in message
+//! text; timestamps can be shifted by a stable offset per message. What each pseudonym
+//! stands for is written to a local mapping file, so an export can be read back later.
+
+use anyhow::{Context, Result};
+use chrono::Duration;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::types::{Chat, ChatId, ChatType, Message, MessageContent, SystemEvent, User, UserId};
+
+/// Hex digits of the hash kept in a pseudonym
+const PSEUDONYM_DIGITS: usize = 8;
+
+/// What a pseudonym stands for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Identity {
+    pub source: String,
+    /// User or chat ID at the source
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Pseudonym to identity, for the mapping file
+    mapping: BTreeMap<String, Identity>,
+    /// Lowercase usernames and names seen so far, to their pseudonyms
+    names: HashMap<String, String>,
+}
+
+/// Rewrites chats and messages with pseudonyms
+pub struct Anonymizer {
+    salt: String,
+    /// Timestamps move by up to this much either way
+    jitter: Option<Duration>,
+    state: Mutex<State>,
+}
+
+impl Anonymizer {
+    pub fn new(salt: impl Into<String>, jitter: Option<Duration>) -> Self {
+        Self {
+            salt: salt.into(),
+            jitter: jitter.filter(|jitter| jitter.num_seconds() > 0),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// An anonymizer with this machine's salt, created on first use
+    pub fn load(jitter: Option<Duration>) -> Result<Self> {
+        Ok(Self::new(load_salt(&default_dir()?.join("salt"))?, jitter))
+    }
+
+    /// Default location of the mapping file (`~/.config/chat/anonymize/mapping.json`)
+    pub fn mapping_path() -> Result<PathBuf> {
+        Ok(default_dir()?.join("mapping.json"))
+    }
+
+    /// Add the pseudonyms handed out to the mapping file at the default location
+    pub fn save_mapping(&self) -> Result<PathBuf> {
+        let path = Self::mapping_path()?;
+        self.save_mapping_to(&path)?;
+        Ok(path)
+    }
+
+    /// Add the pseudonyms handed out to a mapping file, keeping what it already holds
+    pub fn save_mapping_to(&self, path: &Path) -> Result<()> {
+        let mut mapping = load_mapping(path)?;
+        mapping.extend(self.state().mapping.clone());
+        write_private(path, &serde_json::to_string_pretty(&mapping)?)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn hash(&self, parts: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        for part in parts {
+            hasher.update([0]);
+            hasher.update(part.as_bytes());
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn pseudonym(&self, kind: &str, source: &str, id: &str) -> String {
+        format!("{}-{}", kind, &self.hash(&[kind, source, id])[..PSEUDONYM_DIGITS])
+    }
+
+    /// A user's pseudonym, remembering who it stands for
+    pub fn user(&self, source: &str, user: &User) -> User {
+        let pseudonym = self.pseudonym("user", source, user.id.as_str());
+        let mut state = self.state();
+        for name in [&user.username, &user.display_name].into_iter().flatten() {
+            state.names.insert(name.to_lowercase(), pseudonym.clone());
+        }
+        let identity = state.mapping.entry(pseudonym.clone()).or_insert_with(|| Identity {
+            source: source.to_string(),
+            id: user.id.to_string(),
+            name: None,
+            username: None,
+            phone: None,
+        });
+        identity.name = user.display_name.clone().or(identity.name.take());
+        identity.username = user.username.clone().or(identity.username.take());
+        identity.phone = user.phone_number.clone().or(identity.phone.take());
+
+        User {
+            id: UserId::new(&pseudonym),
+            username: None,
+            display_name: Some(pseudonym),
+            phone_number: None,
+        }
+    }
+
+    fn chat_id(&self, source: &str, id: &ChatId) -> ChatId {
+        let pseudonym = self.pseudonym("chat", source, id.as_str());
+        self.state().mapping.entry(pseudonym.clone()).or_insert_with(|| Identity {
+            source: source.to_string(),
+            id: id.to_string(),
+            name: None,
+            username: None,
+            phone: None,
+        });
+        ChatId::new(&pseudonym)
+    }
+
+    /// A chat under its pseudonym. Direct chats are named after a person, so they get
+    /// that person's pseudonym as title; group and channel titles are kept.
+    pub fn chat(&self, source: &str, chat: &Chat) -> Chat {
+        let id = self.chat_id(source, &chat.id);
+        if let Some(title) = &chat.title {
+            if let Some(identity) = self.state().mapping.get_mut(id.as_str()) {
+                identity.name = Some(title.clone());
+            }
+        }
+        let title = match chat.chat_type {
+            ChatType::Group | ChatType::Channel => chat.title.clone(),
+            ChatType::DirectMessage | ChatType::Unknown => Some(self.pseudonym("user", source, chat.id.as_str())),
+        };
+
+        let mut topic = chat.topic.clone();
+        if let Some(topic) = &mut topic {
+            topic.group_id = self.chat_id(source, &topic.group_id);
+        }
+        Chat {
+            id,
+            title,
+            chat_type: chat.chat_type.clone(),
+            participant_count: chat.participant_count,
+            topic,
+        }
+    }
+
+    /// Messages with pseudonyms for their senders, scrubbed text and, with jitter, shifted
+    /// timestamps. Senders are seen first, so @mentions of them get their pseudonyms.
+    pub fn messages(&self, source: &str, messages: &[Message]) -> Vec<Message> {
+        let senders: Vec<User> = messages.iter().map(|message| self.user(source, &message.sender)).collect();
+        messages
+            .iter()
+            .zip(senders)
+            .map(|(message, sender)| self.message(source, message, sender))
+            .collect()
+    }
+
+    fn message(&self, source: &str, message: &Message, sender: User) -> Message {
+        let text = |text: &String| self.text(text);
+        let content = match &message.content {
+            MessageContent::Text(body) => MessageContent::Text(text(body)),
+            MessageContent::Image { caption, url } => MessageContent::Image { caption: caption.as_ref().map(text), url: url.clone() },
+            MessageContent::Video { caption, url } => MessageContent::Video { caption: caption.as_ref().map(text), url: url.clone() },
+            MessageContent::Contact { .. } => MessageContent::Contact { name: "[contact]".to_string(), phone: None },
+            MessageContent::System(event) => MessageContent::System(self.event(event)),
+            other => other.clone(),
+        };
+
+        let mut timestamp = message.timestamp;
+        if let Some(jitter) = self.jitter {
+            let hash = self.hash(&["time", source, message.chat_id.as_str(), message.id.as_str()]);
+            let spread = jitter.num_seconds() as u64 * 2 + 1;
+            let offset = u64::from_str_radix(&hash[..12], 16).unwrap_or(0) % spread;
+            timestamp += Duration::seconds(offset as i64 - jitter.num_seconds());
+        }
+
+        Message {
+            chat_id: self.chat_id(source, &message.chat_id),
+            sender,
+            content,
+            timestamp,
+            media_text: message.media_text.as_ref().map(text),
+            // The source's own timestamp would undo the jitter
+            original_timestamp: None,
+            ..message.clone()
+        }
+    }
+
+    fn event(&self, event: &SystemEvent) -> SystemEvent {
+        // Events name users by display name, falling back to their ID
+        let name = |name: &String| {
+            self.state()
+                .names
+                .get(&name.to_lowercase())
+                .cloned()
+                .unwrap_or_else(|| format!("user-{}", &self.hash(&["name", name])[..PSEUDONYM_DIGITS]))
+        };
+        match event {
+            SystemEvent::Added { users } => SystemEvent::Added { users: users.iter().map(name).collect() },
+            SystemEvent::Removed { user } => SystemEvent::Removed { user: name(user) },
+            SystemEvent::Other { description } => SystemEvent::Other { description: self.text(description) },
+            other => other.clone(),
+        }
+    }
+
+    /// Text without phone numbers, email addresses and @handles; handles of users seen
+    /// so far become their pseudonyms
+    pub fn text(&self, text: &str) -> String {
+        let patterns = patterns();
+        let text = patterns.email.replace_all(text, "[email]");
+        let text = patterns.phone.replace_all(&text, "[phone]");
+        let state = self.state();
+        patterns
+            .handle
+            .replace_all(&text, |captures: &Captures| {
+                let handle = state.names.get(&captures[2].to_lowercase()).map(String::as_str).unwrap_or("user");
+                format!("{}@{}", &captures[1], handle)
+            })
+            .into_owned()
+    }
+}
+
+struct Patterns {
+    email: Regex,
+    phone: Regex,
+    handle: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let regex = |pattern: &str| Regex::new(pattern).expect("valid pattern");
+        Patterns {
+            email: regex(r"\b[\w.+-]+@[\w-]+(?:\.[\w-]+)+\b"),
+            // International numbers, and local ones long enough not to be dates or amounts
+            phone: regex(r"(?:\+|\b00)\d[\d ().-]{5,}\d|\b0\d{8,11}\b|\b\d{3}[ -]\d{3}[ -]\d{4}\b"),
+            handle: regex(r"(^|[^\w@])@(\w{3,32})"),
+        }
+    })
+}
+
+fn default_dir() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .context("Failed to get config directory")?
+        .join("chat")
+        .join("anonymize"))
+}
+
+/// Read the salt, or create one if there's none yet
+fn load_salt(path: &Path) -> Result<String> {
+    if path.exists() {
+        let salt = std::fs::read_to_string(path).context("Failed to read anonymization salt")?;
+        if !salt.trim().is_empty() {
+            return Ok(salt.trim().to_string());
+        }
+    }
+    let salt = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    write_private(path, &salt)?;
+    Ok(salt)
+}
+
+/// Read a mapping file, empty if there is none
+pub fn load_mapping(path: &Path) -> Result<BTreeMap<String, Identity>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = std::fs::read_to_string(path).context("Failed to read anonymization mapping")?;
+    serde_json::from_str(&contents).context("Failed to parse anonymization mapping")
+}
+
+/// Write a file only the current user can read
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).context("Failed to create anonymization directory")?;
+    }
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MessageId, TopicId};
+    use chrono::{TimeZone, Utc};
+
+    fn user(id: &str, username: &str, name: &str) -> User {
+        User {
+            id: UserId::new(id),
+            username: Some(username.to_string()),
+            display_name: Some(name.to_string()),
+            phone_number: Some("+358401234567".to_string()),
+        }
+    }
+
+    fn message(id: &str, sender: User, content: MessageContent) -> Message {
+        Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new("-100777"),
+            sender,
+            content,
+            timestamp: Utc.with_ymd_and_hms(2025, 3, 3, 12, 0, 0).unwrap(),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: Some(Utc.with_ymd_and_hms(2025, 3, 3, 11, 58, 0).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_pseudonyms_are_stable() {
+        let alice = user("42", "alice", "Alice Smith");
+        let first = Anonymizer::new("salt", None).user("telegram", &alice);
+        assert!(first.id.as_str().starts_with("user-"));
+        assert_eq!(first.display_name.as_deref(), Some(first.id.as_str()));
+        assert_eq!((first.username, first.phone_number), (None, None));
+
+        // The same salt gives the same pseudonym; another salt or source another one
+        assert_eq!(Anonymizer::new("salt", None).user("telegram", &alice).id, first.id);
+        assert_ne!(Anonymizer::new("other", None).user("telegram", &alice).id, first.id);
+        assert_ne!(Anonymizer::new("salt", None).user("signal", &alice).id, first.id);
+    }
+
+    #[test]
+    fn test_messages() {
+        let anonymizer = Anonymizer::new("salt", None);
+        let alice = user("42", "alice", "Alice Smith");
+        let bob = user("43", "bob_b", "Bob");
+        let messages = [
+            message(
+                "1",
+                alice.clone(),
+                MessageContent::Text("Ask @bob_b or @carol, call +358 40 123 4567 or mail a.smith@example.com on 2025-01-15".to_string()),
+            ),
+            message("2", bob.clone(), MessageContent::System(SystemEvent::Added { users: vec!["Alice Smith".to_string()] })),
+            message("3", bob, MessageContent::Contact { name: "Dave".to_string(), phone: Some("+1555".to_string()) }),
+        ];
+
+        let anonymized = anonymizer.messages("telegram", &messages);
+        let alice_id = anonymized[0].sender.id.to_string();
+        let bob_id = anonymized[1].sender.id.to_string();
+        let MessageContent::Text(text) = &anonymized[0].content else { panic!("not text") };
+        assert_eq!(text, &format!("Ask @{} or @user, call [phone] or mail [email] on 2025-01-15", bob_id));
+        let MessageContent::System(event) = &anonymized[1].content else { panic!("not an event") };
+        assert_eq!(event, &SystemEvent::Added { users: vec![alice_id.clone()] });
+        assert!(matches!(&anonymized[2].content, MessageContent::Contact { name, phone: None } if name == "[contact]"));
+        assert!(anonymized[0].chat_id.as_str().starts_with("chat-"));
+        assert_eq!(anonymized[0].chat_id, anonymized[2].chat_id);
+        assert_eq!(anonymized[0].timestamp, messages[0].timestamp);
+        assert_eq!(anonymized[0].original_timestamp, None);
+
+        // The mapping leads back to the original users and chat
+        let path = std::env::temp_dir().join(format!("anonymize-{}.json", uuid::Uuid::new_v4()));
+        anonymizer.save_mapping_to(&path).unwrap();
+        let mapping = load_mapping(&path).unwrap();
+        assert_eq!(mapping[&alice_id].id, "42");
+        assert_eq!(mapping[&alice_id].username.as_deref(), Some("alice"));
+        assert_eq!(mapping[&alice_id].phone.as_deref(), Some("+358401234567"));
+        assert_eq!(mapping[anonymized[0].chat_id.as_str()].id, "-100777");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let jitter = Duration::minutes(30);
+        let anonymizer = Anonymizer::new("salt", Some(jitter));
+        let messages: Vec<Message> = (0..20)
+            .map(|n| message(&n.to_string(), user("42", "alice", "Alice"), MessageContent::Text("hi".to_string())))
+            .collect();
+        let first = anonymizer.messages("telegram", &messages);
+        let again = Anonymizer::new("salt", Some(jitter)).messages("telegram", &messages);
+
+        for ((original, shifted), repeated) in messages.iter().zip(&first).zip(&again) {
+            assert!((shifted.timestamp - original.timestamp).abs() <= jitter);
+            assert_eq!(shifted.timestamp, repeated.timestamp);
+        }
+        assert!(first.iter().any(|message| message.timestamp != messages[0].timestamp));
+    }
+
+    #[test]
+    fn test_chat() {
+        let anonymizer = Anonymizer::new("salt", None);
+        let direct = Chat {
+            id: ChatId::new("42"),
+            title: Some("Alice Smith".to_string()),
+            chat_type: ChatType::DirectMessage,
+            participant_count: Some(2),
+            topic: None,
+        };
+        let anonymized = anonymizer.chat("telegram", &direct);
+        assert!(anonymized.id.as_str().starts_with("chat-"));
+        // A direct chat is titled with the other person's pseudonym
+        let alice = anonymizer.user("telegram", &user("42", "alice", "Alice Smith"));
+        assert_eq!(anonymized.title, alice.display_name);
+
+        let group = Chat {
+            id: ChatId::new("-100777"),
+            title: Some("Work".to_string()),
+            chat_type: ChatType::Group,
+            participant_count: Some(12),
+            topic: None,
+        };
+        let topic = group.forum_topic(TopicId::new("5"), "Releases");
+        let anonymized_group = anonymizer.chat("telegram", &group);
+        assert_eq!(anonymized_group.title.as_deref(), Some("Work"));
+        let anonymized_topic = anonymizer.chat("telegram", &topic);
+        assert_eq!(anonymized_topic.topic.unwrap().group_id, anonymized_group.id);
+    }
+
+    #[test]
+    fn test_salt() {
+        let path = std::env::temp_dir().join(format!("anonymize-salt-{}", uuid::Uuid::new_v4()));
+        let salt = load_salt(&path).unwrap();
+        assert_eq!(salt.len(), 64);
+        assert_eq!(load_salt(&path).unwrap(), salt);
+        let _ = std::fs::remove_file(path);
+    }
+}
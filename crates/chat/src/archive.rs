@@ -0,0 +1,383 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::types::Message;
+
+/// `prev_hash` of the very first record
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One archived message. Each record carries the hash of the record before it,
+/// so editing, removing or reordering any record breaks every hash after it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveRecord {
+    /// Position in the chain, starting at 0 and never reset across files
+    pub seq: u64,
+    pub archived_at: DateTime<Utc>,
+    pub source: String,
+    pub message: Message,
+    pub prev_hash: String,
+    /// SHA-256 over the record without this field
+    pub hash: String,
+}
+
+/// Append-only writer for the hash-chained message archive.
+///
+/// Records go to one JSONL file per UTC day (`2025-01-15.jsonl`). When a new day
+/// starts, the previous day's file is made read-only.
+pub struct ArchiveWriter {
+    dir: PathBuf,
+    seq: u64,
+    head: String,
+    current: Option<(NaiveDate, File)>,
+}
+
+impl ArchiveWriter {
+    /// Default archive directory (`~/.local/share/chat/archive-log`, platform data directory)
+    pub fn default_dir() -> Result<PathBuf> {
+        Ok(dirs::data_dir()
+            .context("Failed to get data directory")?
+            .join("chat")
+            .join("archive-log"))
+    }
+
+    /// Open an archive, continuing the chain from its last record
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create archive directory: {}", dir.display()))?;
+
+        let mut writer = Self {
+            dir,
+            seq: 0,
+            head: GENESIS_HASH.to_string(),
+            current: None,
+        };
+
+        if let Some(last_file) = archive_files(&writer.dir)?.pop() {
+            if let Some(record) = last_record(&last_file)? {
+                writer.seq = record.seq + 1;
+                writer.head = record.hash;
+            }
+        }
+
+        Ok(writer)
+    }
+
+    /// Hash of the newest record; record it elsewhere to detect truncation later
+    pub fn head(&self) -> &str {
+        &self.head
+    }
+
+    /// Number of records written so far
+    pub fn len(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seq == 0
+    }
+
+    /// Append a message to the chain and flush it to disk
+    pub fn append(&mut self, source: &str, message: &Message) -> Result<ArchiveRecord> {
+        let archived_at = Utc::now();
+        let mut body = serde_json::json!({
+            "seq": self.seq,
+            "archived_at": archived_at,
+            "source": source,
+            "message": message,
+            "prev_hash": self.head,
+        });
+        let hash = record_hash(&body);
+        body["hash"] = Value::String(hash.clone());
+
+        let file = self.file_for(archived_at.date_naive())?;
+        writeln!(file, "{}", serde_json::to_string(&body)?).context("Failed to write archive record")?;
+        file.sync_data().context("Failed to flush archive record")?;
+
+        let record = ArchiveRecord {
+            seq: self.seq,
+            archived_at,
+            source: source.to_string(),
+            message: message.clone(),
+            prev_hash: std::mem::replace(&mut self.head, hash.clone()),
+            hash,
+        };
+        self.seq += 1;
+
+        Ok(record)
+    }
+
+    /// The open file for a day, sealing the previous day's file on rollover
+    fn file_for(&mut self, date: NaiveDate) -> Result<&mut File> {
+        if self.current.as_ref().map(|(day, _)| *day) != Some(date) {
+            if let Some((day, file)) = self.current.take() {
+                drop(file);
+                seal(&day_file(&self.dir, day))?;
+            }
+
+            let path = day_file(&self.dir, date);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            self.current = Some((date, file));
+        }
+
+        Ok(&mut self.current.as_mut().expect("file opened above").1)
+    }
+}
+
+/// Where verification found the chain broken
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainError {
+    pub file: PathBuf,
+    /// 1-based line within the file
+    pub line: usize,
+    pub reason: String,
+}
+
+impl std::fmt::Display for ChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.file.display(), self.line, self.reason)
+    }
+}
+
+/// Result of checking an archive's hash chain
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    pub files: usize,
+    /// Records checked before the first error (all of them if the chain is intact)
+    pub records: u64,
+    /// Hash of the last valid record
+    pub head: String,
+    pub error: Option<ChainError>,
+}
+
+impl VerifyReport {
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Check every record's hash, its link to the previous record and the sequence numbers,
+/// stopping at the first break
+pub fn verify(dir: &Path) -> Result<VerifyReport> {
+    let files = archive_files(dir)?;
+    let mut report = VerifyReport {
+        files: files.len(),
+        records: 0,
+        head: GENESIS_HASH.to_string(),
+        error: None,
+    };
+
+    for path in &files {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+            if let Err(reason) = check_record(&line, report.records, &report.head) {
+                report.error = Some(ChainError {
+                    file: path.clone(),
+                    line: index + 1,
+                    reason,
+                });
+                return Ok(report);
+            }
+
+            let record: ArchiveRecord = serde_json::from_str(&line)?;
+            report.head = record.hash;
+            report.records += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Number of records `f` picks. Records that don't parse are skipped; `verify` reports them.
+pub fn count_matching(dir: &Path, mut f: impl FnMut(&ArchiveRecord) -> bool) -> Result<u64> {
+    let mut count = 0;
+    for path in archive_files(dir)? {
+        let file = File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        for line in BufReader::new(file).lines() {
+            let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+            if serde_json::from_str::<ArchiveRecord>(&line).is_ok_and(|record| f(&record)) {
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
+
+/// Validate one line against the expected position and previous hash
+fn check_record(line: &str, seq: u64, prev_hash: &str) -> std::result::Result<(), String> {
+    let mut body: Value = serde_json::from_str(line).map_err(|e| format!("not valid JSON: {}", e))?;
+    serde_json::from_value::<ArchiveRecord>(body.clone()).map_err(|e| format!("not an archive record: {}", e))?;
+
+    let stored = body
+        .as_object_mut()
+        .and_then(|object| object.remove("hash"))
+        .and_then(|hash| hash.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    if body["seq"].as_u64() != Some(seq) {
+        return Err(format!("expected record {}, found {} (records missing or reordered)", seq, body["seq"]));
+    }
+    if body["prev_hash"].as_str() != Some(prev_hash) {
+        return Err("prev_hash does not match the previous record".to_string());
+    }
+    if record_hash(&body) != stored {
+        return Err("hash mismatch (record was modified)".to_string());
+    }
+
+    Ok(())
+}
+
+/// SHA-256 of a record body in canonical JSON form, hex encoded
+fn record_hash(body: &Value) -> String {
+    let canonical = serde_json::to_string(body).expect("JSON values serialize");
+    format!("{:x}", Sha256::digest(canonical.as_bytes()))
+}
+
+fn day_file(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!("{}.jsonl", date.format("%Y-%m-%d")))
+}
+
+/// Archive files oldest first
+fn archive_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read archive directory: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "jsonl")
+                && path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").is_ok())
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+fn last_record(path: &Path) -> Result<Option<ArchiveRecord>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut last = None;
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            last = Some(line);
+        }
+    }
+
+    last.map(|line| {
+        serde_json::from_str(&line).with_context(|| format!("Corrupt last record in {}", path.display()))
+    })
+    .transpose()
+}
+
+/// Make a finished day's file read-only
+fn seal(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(path, permissions)
+        .with_context(|| format!("Failed to make {} read-only", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChatId, MessageContent, MessageId, User, UserId};
+
+    fn message(id: usize) -> Message {
+        Message {
+            id: MessageId::new(id.to_string()),
+            chat_id: ChatId::new("-100123"),
+            sender: User {
+                id: UserId::new("u"),
+                username: None,
+                display_name: Some("Antti".to_string()),
+                phone_number: None,
+            },
+            content: MessageContent::Text(format!("message {}", id)),
+            timestamp: Utc::now(),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("chat-archive-{}", uuid::Uuid::new_v4().simple()))
+    }
+
+    #[test]
+    fn test_chain_continues_after_reopen() {
+        let dir = temp_dir();
+
+        let mut writer = ArchiveWriter::open(&dir).unwrap();
+        let first = writer.append("telegram", &message(1)).unwrap();
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        writer.append("telegram", &message(2)).unwrap();
+        drop(writer);
+
+        let mut writer = ArchiveWriter::open(&dir).unwrap();
+        assert_eq!(writer.len(), 2);
+        let third = writer.append("whatsapp", &message(3)).unwrap();
+        assert_eq!(third.seq, 2);
+
+        let report = verify(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(report.is_valid(), "{:?}", report.error);
+        assert_eq!(report.records, 3);
+        assert_eq!(report.head, third.hash);
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let dir = temp_dir();
+
+        let mut writer = ArchiveWriter::open(&dir).unwrap();
+        for id in 0..3 {
+            writer.append("telegram", &message(id)).unwrap();
+        }
+        drop(writer);
+
+        let path = archive_files(&dir).unwrap().pop().unwrap();
+        let original = std::fs::read_to_string(&path).unwrap();
+
+        // Edited message text
+        std::fs::write(&path, original.replace("message 1", "message 9")).unwrap();
+        let report = verify(&dir).unwrap();
+        assert_eq!(report.records, 1);
+        let error = report.error.unwrap();
+        assert_eq!(error.line, 2);
+        assert!(error.reason.contains("hash mismatch"));
+
+        // Removed record
+        let lines: Vec<&str> = original.lines().collect();
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        let report = verify(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(report.error.unwrap().reason.contains("expected record 1"));
+    }
+}
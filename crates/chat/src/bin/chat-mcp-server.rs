@@ -3,7 +3,14 @@ use anyhow::Result;
 #[cfg(feature = "mcp")]
 use chat::mcp_server::ChatMcpServer;
 #[cfg(feature = "mcp")]
-use chat::SourcesManager;
+use chat::{LabelStore, MergedChats, MyAccounts, SourcesConfig, SourcesManager};
+
+/// Value of a `--name value` argument
+#[cfg(feature = "mcp")]
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1).cloned())
+}
 
 #[cfg(feature = "mcp")]
 #[tokio::main]
@@ -13,21 +20,71 @@ async fn main() -> Result<()> {
         .target(env_logger::Target::Stderr)
         .init();
 
+    // --connect ADDR: relay stdio to a server already listening there (e.g. the dashboard's)
+    if let Some(addr) = arg_value("--connect") {
+        return bridge(&addr).await;
+    }
+    let listen: Option<std::net::SocketAddr> = arg_value("--listen")
+        .map(|addr| addr.parse())
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --listen address: {}", e))?;
+
     eprintln!("Chat MCP Server v0.1.0");
     eprintln!("Protocol: Model Context Protocol (MCP)");
-    eprintln!("Transport: stdio (JSON-RPC)");
+    match listen {
+        Some(addr) => eprintln!("Transport: TCP {} (JSON-RPC, one message per line)", addr),
+        None => eprintln!("Transport: stdio (JSON-RPC)"),
+    }
     eprintln!();
 
     // Create sources manager
-    let manager = SourcesManager::new();
+    let manager = SourcesManager::with_labels(LabelStore::load()?);
+    manager.set_merged_chats(MergedChats::load()?)?;
+    manager.set_accounts(MyAccounts::load()?)?;
+
+    let config = SourcesConfig::load()?;
+
+    // --read-only or `read_only = true` in sources.toml: nothing is ever sent, drafted or marked read
+    let forced_read_only = std::env::args().any(|arg| arg == "--read-only");
+    let read_only = forced_read_only || config.read_only;
+    manager.set_read_only(read_only);
+    if read_only {
+        eprintln!("Mode: read-only");
+    }
 
     // Note: In a real implementation, this would load configured sources
     // For now, the server will report empty sources until they are configured
 
     // Create and run server
-    let server = ChatMcpServer::new(manager);
-    server.run_stdio().await?;
+    // Limits, time ranges and call quotas from the [mcp] section of sources.toml, reloaded
+    // with read_only whenever the file changes
+    let server = ChatMcpServer::new(manager)
+        .with_guardrails(config.mcp)
+        .with_policy_reload(SourcesConfig::default_path()?, forced_read_only);
+    match listen {
+        Some(addr) => server.run_tcp(addr).await?,
+        None => server.run_stdio().await?,
+    }
+
+    Ok(())
+}
+
+/// Copy stdin to the server at `addr` and its replies to stdout, for MCP clients that
+/// only launch stdio servers
+#[cfg(feature = "mcp")]
+async fn bridge(addr: &str) -> Result<()> {
+    use anyhow::Context;
+
+    let stream = tokio::net::TcpStream::connect(addr)
+        .await
+        .with_context(|| format!("No chat MCP server listening on {}", addr))?;
+    let (mut reader, mut writer) = stream.into_split();
 
+    let upstream = tokio::spawn(async move {
+        let _ = tokio::io::copy(&mut tokio::io::stdin(), &mut writer).await;
+    });
+    tokio::io::copy(&mut reader, &mut tokio::io::stdout()).await?;
+    upstream.abort();
     Ok(())
 }
 
@@ -0,0 +1,825 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+mod telegram;
+mod unified_commands;
+
+#[derive(Parser)]
+#[command(name = "chat")]
+#[command(about = "Unified CLI for reading messages from Telegram, WhatsApp, and Signal", long_about = None)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Enable verbose output
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Suppress output (quiet mode)
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Record source traffic to a fixture file
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Serve source responses from a fixture file recorded with --record
+    #[arg(long, global = true, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// Never send, draft or mark messages read (also `read_only = true` in sources.toml)
+    #[arg(long, global = true)]
+    read_only: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List all configured chat sources (with --verbose, their capabilities)
+    #[command(visible_alias = "source")]
+    Sources {
+        /// Keep running and print connection events (connected, disconnected,
+        /// auth expired, rate limited) as JSON lines
+        #[arg(long)]
+        watch: bool,
+
+        /// Seconds between connection checks with --watch
+        #[arg(long, default_value = "30", requires = "watch")]
+        interval: u64,
+
+        /// Seconds between keep-alive pings with --watch, which keep sessions alive and
+        /// notice when one has expired
+        #[arg(long, default_value = "300", requires = "watch")]
+        keep_alive: u64,
+    },
+
+    /// List chats from a source
+    Chats {
+        /// Source ID (telegram, signal, whatsapp)
+        source: String,
+
+        /// Filter by name pattern
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Filter by chat type (direct, group, channel)
+        #[arg(long)]
+        chat_type: Option<String>,
+
+        /// Filter by local label
+        #[arg(long)]
+        label: Option<String>,
+
+        /// Output format (text, json, csv, compact)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Get messages with filters
+    Messages {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:Antti", "telegram:#work", "*:*")
+        filter: String,
+
+        /// Time range - messages after this time (e.g., "7d", "2h", "2025-01-15")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Time range - messages before this time
+        #[arg(long)]
+        before: Option<String>,
+
+        /// Sender filter (name or ID pattern)
+        #[arg(long)]
+        sender: Option<String>,
+
+        /// Text search (substring, ignoring case and accents unless --exact)
+        #[arg(long)]
+        search: Option<String>,
+
+        /// Match --search only against text extracted from attachments (documents, image text, transcripts)
+        #[arg(long, requires = "search")]
+        in_attachments: bool,
+
+        /// Limit number of results
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Only messages mentioning me or replying to me (handles from ~/.config/chat/accounts.json)
+        #[arg(long)]
+        mentions_me: bool,
+
+        /// Match --search and --sender exactly (case- and accent-sensitive)
+        #[arg(long)]
+        exact: bool,
+
+        /// Only messages detected to be in this language (e.g., "en", "fi")
+        #[arg(long = "lang")]
+        lang: Option<String>,
+
+        /// Collapse forwarded/duplicate messages to their first occurrence
+        #[arg(long)]
+        dedupe: bool,
+
+        /// Only these content types, comma-separated (text, image, video, audio, file, sticker, location, contact, system)
+        #[arg(long = "type")]
+        content_type: Option<String>,
+
+        /// Also show system events: joins, leaves, removals, renames and pins
+        #[arg(long)]
+        include_system: bool,
+
+        /// Only show system events, e.g. to audit who was added to a group and when
+        #[arg(long, conflicts_with_all = ["content_type", "include_system"])]
+        only_system: bool,
+
+        /// Show voice note transcripts under audio messages (see `chat media process --transcribe`)
+        #[arg(long)]
+        with_transcripts: bool,
+
+        /// Output format (text, json, csv, compact)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Extract links shared in matching messages
+    Links {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:Antti", "telegram:#work", "*:*")
+        filter: String,
+
+        /// Time range - messages after this time (e.g., "7d", "2h", "2025-01-15")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Time range - messages before this time
+        #[arg(long)]
+        before: Option<String>,
+
+        /// Sender filter (name or ID pattern)
+        #[arg(long)]
+        sender: Option<String>,
+
+        /// Limit number of messages scanned
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Fetch page titles and descriptions (requires link-preview feature)
+        #[arg(long)]
+        fetch_titles: bool,
+
+        /// Maximum number of concurrent title fetches
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+
+        /// Output format (text, json, csv)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Analyze activity, response times and sentiment of matching chats
+    Analyze {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:Antti", "telegram:#work", "*:*")
+        filter: String,
+
+        /// Time range - messages after this time (e.g., "30d", "2025-01-15")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Time range - messages before this time
+        #[arg(long)]
+        before: Option<String>,
+
+        /// Limit number of messages analyzed
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Include a keyword-based sentiment estimate of English messages
+        #[arg(long)]
+        sentiment: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Split chats into topic segments with generated titles
+    Topics {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:Antti", "telegram:#work", "*:*")
+        filter: String,
+
+        /// Time range - messages after this time (e.g., "30d", "2025-01-15")
+        #[arg(long, default_value = "30d")]
+        since: String,
+
+        /// Time range - messages before this time
+        #[arg(long)]
+        before: Option<String>,
+
+        /// Limit number of messages segmented
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Output format (text, json, chunks)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// List my unsent drafts and scheduled messages
+    Drafts {
+        /// Source ID (telegram)
+        source: String,
+
+        /// Only chats matching this pattern (name, ID, or "Group/Topic")
+        #[arg(long)]
+        chat: Option<String>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Find duplicate/forwarded messages across chats
+    Dupes {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:*", "*:*")
+        #[arg(default_value = "*:*")]
+        filter: String,
+
+        /// Time range - messages after this time (e.g., "7d", "2025-01-15")
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Only group copies posted within this window of each other (e.g., "1d", "6h")
+        #[arg(long, default_value = "1d")]
+        window: String,
+
+        /// Limit number of messages scanned
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Pack recent and relevant messages into a compact transcript under a token budget
+    Context {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:Antti", "telegram:#work", "*:*")
+        filter: String,
+
+        /// Consider messages after this time (e.g., "7d", "2025-01-15")
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Prefer messages relevant to this query (requires embeddings feature)
+        #[arg(long)]
+        query: Option<String>,
+
+        /// Token budget for the transcript
+        #[arg(short, long, default_value = "2000")]
+        budget: usize,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Search messages by meaning using a local embedding index (requires embeddings feature)
+    SemanticSearch {
+        /// Natural-language query (e.g., "when did we agree on the API design?")
+        query: String,
+
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:*", "telegram:#work")
+        #[arg(long, default_value = "*:*")]
+        filter: String,
+
+        /// Index messages newer than this before searching (e.g., "90d", "2025-01-15")
+        #[arg(long, default_value = "90d")]
+        since: String,
+
+        /// Number of results to show
+        #[arg(short, long, default_value = "10")]
+        top: usize,
+
+        /// Ollama embedding model (OLLAMA_HOST selects the server)
+        #[arg(long, default_value = chat::embeddings::DEFAULT_MODEL)]
+        model: String,
+
+        /// Search the existing index without fetching new messages
+        #[arg(long)]
+        no_update: bool,
+
+        /// Only match text extracted from attachments (documents, image text, transcripts)
+        #[arg(long)]
+        in_attachments: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Sync new messages into a local archive, resuming from per-chat checkpoints
+    Sync {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:*", "telegram:#work")
+        #[arg(default_value = "*:*")]
+        filter: String,
+
+        /// How far back to go for chats without a checkpoint (e.g., "30d", "2025-01-15")
+        #[arg(long, default_value = "30d")]
+        since: String,
+
+        /// Directory for the per-chat JSONL archive (default: platform data dir)
+        #[arg(long)]
+        archive_dir: Option<PathBuf>,
+
+        /// Attempts per chat before giving up on transient errors
+        #[arg(long, default_value = "5")]
+        retries: u32,
+
+        /// Only re-run chats that failed permanently last time
+        #[arg(long)]
+        failed: bool,
+        /// Report matched chats and estimated cost without fetching messages
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Export matching chats into one dataset file for analysis, e.g. a SQLite database
+    Export {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:*", "telegram:#work")
+        #[arg(default_value = "*:*")]
+        filter: String,
+
+        /// File to write, replaced if it exists; a new directory for parquet
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Dataset format (sqlite, parquet)
+        #[arg(short, long, default_value = "sqlite")]
+        format: String,
+
+        /// Time range - messages after this time (e.g., "30d", "2025-01-15")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Time range - messages before this time
+        #[arg(long)]
+        before: Option<String>,
+
+        /// Maximum number of messages per chat
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Replace people with stable pseudonyms and drop phone numbers, emails and usernames
+        #[arg(long)]
+        anonymize: bool,
+
+        /// With --anonymize, shift each timestamp by up to this much either way (e.g. "30m")
+        #[arg(long, requires = "anonymize")]
+        jitter: Option<String>,
+        /// Report matched chats and estimated cost without fetching messages
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Export every matching chat to its own file, concurrently
+    ExportAll {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:*", "telegram:#work")
+        #[arg(long, default_value = "*:*")]
+        filter: String,
+
+        /// Directory to write exports and manifest.json into
+        #[arg(short, long)]
+        output_dir: PathBuf,
+
+        /// Output format (json, csv, text, compact)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+
+        /// Time range - messages after this time (e.g., "30d", "2025-01-15")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Time range - messages before this time
+        #[arg(long)]
+        before: Option<String>,
+
+        /// Maximum number of messages per chat
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Number of chats exported at the same time
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+
+        /// Request budget shared by all exports, in requests per second
+        #[arg(long, default_value = "2")]
+        rate: f64,
+
+        /// Continue an interrupted export, skipping chats the manifest marks complete
+        #[arg(long)]
+        resume: bool,
+
+        /// Replace people with stable pseudonyms and drop phone numbers, emails and usernames
+        #[arg(long)]
+        anonymize: bool,
+
+        /// With --anonymize, shift each timestamp by up to this much either way (e.g. "30m")
+        #[arg(long, requires = "anonymize")]
+        jitter: Option<String>,
+        /// Report matched chats and estimated cost without fetching messages
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Manage local chat labels
+    Labels {
+        #[command(subcommand)]
+        action: unified_commands::labels::LabelAction,
+    },
+
+    /// Treat chats as one conversation, e.g. a group and the group that replaced it
+    Merged {
+        #[command(subcommand)]
+        action: unified_commands::merged::MergedAction,
+    },
+
+    /// Resurface messages later as reminders
+    Remind {
+        #[command(subcommand)]
+        action: unified_commands::remind::RemindAction,
+    },
+
+    /// Bookmark messages to come back to
+    Bookmark {
+        #[command(subcommand)]
+        action: unified_commands::bookmark::BookmarkAction,
+    },
+
+    /// Link the accounts people use on each platform
+    Identities {
+        #[command(subcommand)]
+        action: unified_commands::identities::IdentityAction,
+    },
+
+    /// Notes and fields on chats and people, included in packed context
+    Note {
+        #[command(subcommand)]
+        action: unified_commands::note::NoteAction,
+    },
+
+    /// Canned replies with placeholders
+    Snippet {
+        #[command(subcommand)]
+        action: unified_commands::snippet::SnippetAction,
+    },
+
+    /// MCP server tools
+    Mcp {
+        #[command(subcommand)]
+        action: unified_commands::mcp::McpAction,
+    },
+
+    /// Tamper-evident archive of all incoming messages
+    Archive {
+        #[command(subcommand)]
+        action: unified_commands::archive::ArchiveAction,
+    },
+
+    /// Forget chats and people in local data
+    Cache {
+        #[command(subcommand)]
+        action: unified_commands::cache::CacheAction,
+    },
+
+    /// Extract text from image, voice note and document attachments for search and summaries
+    Media {
+        #[command(subcommand)]
+        action: unified_commands::media::MediaAction,
+    },
+
+    /// Run incoming messages through user-defined pipelines
+    Pipeline {
+        #[command(subcommand)]
+        action: unified_commands::pipeline::PipelineAction,
+    },
+
+    /// Summarize unread messages, urgent chats first
+    Catchup {
+        #[command(flatten)]
+        args: unified_commands::catchup::CatchupArgs,
+    },
+
+    /// Mark a chat's messages read, all of them or as far as one message
+    MarkRead {
+        /// The chat (format: source:pattern or source:chat_id); every matching chat is marked
+        chat: String,
+
+        /// Only as far as this message ID, keeping newer messages unread
+        #[arg(long)]
+        through: Option<String>,
+
+        /// How far back to look in chats never read before (e.g., "24h", "2025-01-15")
+        #[arg(long, default_value = "30d")]
+        since: String,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Find proposed meetings and other events ("let's meet Tuesday at 15:00") in messages
+    Events {
+        /// Chats to search (format: source:pattern, e.g., "telegram:#work"); all chats by default
+        filters: Vec<String>,
+
+        /// How far back to search (e.g., "7d", "2025-01-15")
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Also ask an LLM for events the rules miss (needs the 'summaries' feature)
+        #[arg(long)]
+        llm: bool,
+
+        /// Ollama model for --llm (default: llama3.2)
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Also write the events to this iCalendar file
+        #[arg(long)]
+        ics: Option<PathBuf>,
+
+        /// Output format (text, json, ics)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Find things people asked me to do ("can you send the report?") and track them
+    Todos {
+        /// Chats to scan (format: source:pattern, e.g., "telegram:#work"); all chats by default
+        filters: Vec<String>,
+
+        /// How far back to scan (e.g., "7d", "2025-01-15")
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Mark todos done by ID (or a unique ID prefix) instead of scanning
+        #[arg(long, value_name = "ID")]
+        done: Vec<String>,
+
+        /// Open done todos again by ID instead of scanning
+        #[arg(long, value_name = "ID")]
+        reopen: Vec<String>,
+
+        /// Also list done todos
+        #[arg(long)]
+        all: bool,
+
+        /// List the todos found before without fetching messages
+        #[arg(long)]
+        cached: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Statistics of a channel I admin: post frequency, views, top posts and growth
+    ChannelStats {
+        /// The channel (format: source:pattern, e.g., "telegram:My channel")
+        channel: String,
+
+        /// How far back to count posts (e.g., "30d", "2025-01-15")
+        #[arg(long, default_value = "30d")]
+        since: String,
+
+        /// Period growth is shown per (day, week, month)
+        #[arg(long, default_value = "week")]
+        by: String,
+
+        /// Number of top posts to list
+        #[arg(long, default_value_t = chat::channel_stats::DEFAULT_TOP)]
+        top: usize,
+
+        /// Also write the per-period numbers to this CSV file
+        #[arg(long)]
+        csv: Option<PathBuf>,
+
+        /// Also write all statistics to this JSON file
+        #[arg(long)]
+        json: Option<PathBuf>,
+
+        /// Output format (text, json, csv)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Send a text message to one chat, unless read-only mode is on
+    Send {
+        /// The chat (format: source:pattern or source:chat_id, e.g., "telegram:Alice")
+        chat: String,
+
+        /// Message text, or "-" to read it from stdin
+        text: String,
+
+        /// Send as a reply to this message ID
+        #[arg(long)]
+        reply_to: Option<String>,
+
+        /// Output format (text, json: the sent message)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Follow new messages from all streaming sources
+    Watch {
+        /// Only messages from this source
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Output format (text, json: one versioned event per line)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Update chat to the latest GitHub release, after checking its checksum
+    SelfUpdate {
+        /// Only check for a newer release and show what's new
+        #[arg(long)]
+        check: bool,
+
+        /// Install the latest release even if it isn't newer, e.g. to repair the binary
+        #[arg(long, conflicts_with = "check")]
+        force: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Telegram commands (legacy, use unified commands instead)
+    #[command(hide = false)]
+    Telegram {
+        #[command(subcommand)]
+        command: telegram::TelegramCommand,
+    },
+
+    /// WhatsApp commands (not yet implemented)
+    #[command(hide = true)]
+    Whatsapp {
+        #[command(subcommand)]
+        command: WhatsAppCommand,
+    },
+
+    /// Signal commands (not yet implemented)
+    #[command(hide = true)]
+    Signal {
+        #[command(subcommand)]
+        command: SignalCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum WhatsAppCommand {
+    /// Initialize WhatsApp connection
+    Init,
+    /// Show status (placeholder)
+    Status,
+}
+
+#[derive(Subcommand)]
+enum SignalCommand {
+    /// Initialize Signal connection
+    Init,
+    /// Show status (placeholder)
+    Status,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // Initialize logging based on verbosity
+    if cli.verbose {
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
+    } else if !cli.quiet {
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    }
+
+    if let Some(path) = cli.replay {
+        unified_commands::set_source_mode(unified_commands::SourceMode::Replay(path));
+    } else if let Some(path) = cli.record {
+        unified_commands::set_source_mode(unified_commands::SourceMode::Record(path));
+    }
+    if cli.read_only {
+        unified_commands::set_read_only();
+    }
+
+    let result = match cli.command {
+        Command::Sources { watch: false, .. } => unified_commands::sources::execute(cli.verbose).await,
+        Command::Sources { watch: true, interval, keep_alive } => {
+            unified_commands::sources::watch(interval, keep_alive).await
+        }
+        Command::Chats { source, name, chat_type, label, format } => {
+            unified_commands::chats::execute(source, name, chat_type, label, format).await
+        }
+        Command::Messages { filter, since, before, sender, search, in_attachments, limit, mentions_me, exact, lang, dedupe, content_type, include_system, only_system, with_transcripts, format } => {
+            unified_commands::messages::execute(filter, since, before, sender, search, in_attachments, limit, mentions_me, exact, lang, dedupe, content_type, include_system, only_system, with_transcripts, format).await
+        }
+        Command::Links { filter, since, before, sender, limit, fetch_titles, concurrency, format } => {
+            unified_commands::links::execute(filter, since, before, sender, limit, fetch_titles, concurrency, format).await
+        }
+        Command::Analyze { filter, since, before, limit, sentiment, format } => {
+            unified_commands::analyze::execute(filter, since, before, limit, sentiment, format).await
+        }
+        Command::Topics { filter, since, before, limit, format } => {
+            unified_commands::topics::execute(filter, since, before, limit, format).await
+        }
+        Command::Drafts { source, chat, format } => {
+            unified_commands::drafts::execute(source, chat, format).await
+        }
+        Command::Dupes { filter, since, window, limit, format } => {
+            unified_commands::dupes::execute(filter, since, window, limit, format).await
+        }
+        Command::Context { filter, since, query, budget, format } => {
+            unified_commands::context::execute(filter, since, query, budget, format).await
+        }
+        Command::SemanticSearch { query, filter, since, top, model, no_update, in_attachments, format } => {
+            unified_commands::semantic_search::execute(query, filter, since, top, model, no_update, in_attachments, format).await
+        }
+        Command::Sync { filter, since, archive_dir, retries, failed, dry_run } => {
+            unified_commands::sync::execute(filter, since, archive_dir, retries, failed, dry_run).await
+        }
+        Command::Export { filter, output, format, since, before, limit, anonymize, jitter, dry_run } => {
+            unified_commands::export::execute(filter, output, format, since, before, limit, anonymize, jitter, dry_run).await
+        }
+        Command::ExportAll { filter, output_dir, format, since, before, limit, concurrency, rate, resume, anonymize, jitter, dry_run } => {
+            unified_commands::export_all::execute(
+                filter, output_dir, format, since, before, limit, concurrency, rate, resume, anonymize, jitter, dry_run,
+            )
+            .await
+        }
+        Command::Labels { action } => unified_commands::labels::execute(action).await,
+        Command::Merged { action } => unified_commands::merged::execute(action).await,
+        Command::Snippet { action } => unified_commands::snippet::execute(action).await,
+        Command::Note { action } => unified_commands::note::execute(action).await,
+        Command::Remind { action } => unified_commands::remind::execute(action).await,
+        Command::Bookmark { action } => unified_commands::bookmark::execute(action).await,
+        Command::Identities { action } => unified_commands::identities::execute(action).await,
+        Command::Mcp { action } => unified_commands::mcp::execute(action).await,
+        Command::Archive { action } => unified_commands::archive::execute(action).await,
+        Command::Cache { action } => unified_commands::cache::execute(action).await,
+        Command::Media { action } => unified_commands::media::execute(action).await,
+        Command::Pipeline { action } => unified_commands::pipeline::execute(action).await,
+        Command::Catchup { args } => unified_commands::catchup::execute(args).await,
+        Command::MarkRead { chat, through, since, format } => {
+            unified_commands::mark_read::execute(chat, through, since, format).await
+        }
+        Command::Events { filters, since, llm, model, ics, format } => {
+            unified_commands::events::execute(filters, since, llm, model, ics, format).await
+        }
+        Command::Todos { filters, since, done, reopen, all, cached, format } => {
+            unified_commands::todos::execute(filters, since, done, reopen, all, cached, format).await
+        }
+        Command::ChannelStats { channel, since, by, top, csv, json, format } => {
+            unified_commands::channel_stats::execute(channel, since, by, top, csv, json, format).await
+        }
+        Command::Send { chat, text, reply_to, format } => {
+            unified_commands::send::execute(chat, text, reply_to, format).await
+        }
+        Command::Watch { source, format } => unified_commands::watch::execute(source, format).await,
+        Command::SelfUpdate { check, force, format } => {
+            unified_commands::self_update::execute(check, force, format).await
+        }
+        Command::Telegram { command } => telegram::execute(command).await,
+        Command::Whatsapp { command: _ } => {
+            use colored::Colorize;
+            println!("{}", "WhatsApp CLI is not yet implemented.".yellow());
+            println!("See the OpenSpec proposal: openspec/changes/add-whatsapp-cli/");
+            Ok(())
+        }
+        Command::Signal { command: _ } => {
+            use colored::Colorize;
+            println!("{}", "Signal CLI is not yet implemented.".yellow());
+            println!("See the OpenSpec proposal: openspec/changes/add-signal-cli/");
+            Ok(())
+        }
+    };
+
+    if let Err(e) = &result {
+        print_sign_in_hint(e);
+    }
+    result
+}
+
+/// Tell how to sign in again when a command failed because the session expired
+fn print_sign_in_hint(error: &anyhow::Error) {
+    use colored::Colorize;
+
+    let expired = matches!(chat::events::classify_error(error), Some(chat::SourceEventKind::AuthExpired { .. }));
+    let command = chat::login_command("telegram");
+    // Errors from the telegram commands already say what to run
+    if expired && !format!("{:#}", error).contains(&command) {
+        eprintln!("{}", "The Telegram session has expired or was revoked.".yellow());
+        eprintln!("Sign in again with {}", command.cyan());
+    }
+}
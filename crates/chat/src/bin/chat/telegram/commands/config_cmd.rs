@@ -1,7 +1,7 @@
 use anyhow::Result;
 use colored::Colorize;
 
-use crate::config::Config;
+use chat::cli::config::Config;
 
 pub async fn set(key: String, value: String) -> Result<()> {
     let mut config = Config::load()?;
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::cli::OutputFormat;
+use super::parse_time;
+
+#[cfg_attr(not(feature = "telegram"), allow(unused_variables))]
+pub async fn execute(
+    chat: String,
+    format: OutputFormat,
+    output: String,
+    since: Option<String>,
+    before: Option<String>,
+    limit: Option<usize>,
+    dry_run: bool,
+) -> Result<()> {
+    println!("{}", format!("Exporting messages from '{}'...", chat).bold());
+
+    let since_time = since.map(|s| parse_time(&s)).transpose()?;
+    let before_time = before.map(|b| parse_time(&b)).transpose()?;
+
+    if let Some(st) = since_time {
+        println!("  {}: {}", "Since".dimmed(), st.format("%Y-%m-%d %H:%M:%S"));
+    }
+    if let Some(bt) = before_time {
+        println!("  {}: {}", "Before".dimmed(), bt.format("%Y-%m-%d %H:%M:%S"));
+    }
+    if let Some(l) = limit {
+        println!("  {}: {}", "Limit".dimmed(), l);
+    }
+
+    #[cfg(feature = "telegram")]
+    {
+        use anyhow::Context;
+        use chat::cli::formatters;
+        use chat::commands::telegram::{self, HistoryFilter};
+
+        let (client, tasks) = telegram::connect().await?;
+
+        if dry_run {
+            let preview = telegram::preview_export(&client, &chat, limit).await;
+            tasks.shutdown().await;
+            print_preview(&chat, preview?, since_time.is_some() || before_time.is_some());
+            return Ok(());
+        }
+
+        let Some(found) = telegram::find_chat(&client, &chat).await? else {
+            println!();
+            println!("{}", format!("Chat not found: {}", chat).yellow());
+            println!("  Use {} to see available chats", "chat telegram list".cyan());
+            tasks.shutdown().await;
+            return Ok(());
+        };
+        println!("  {}: {} (ID: {})", "Chat".dimmed(), found.name, found.id);
+
+        let filter = HistoryFilter {
+            since: since_time,
+            before: before_time,
+            sender: None,
+            limit,
+        };
+        let messages = telegram::fetch_history(&client, &found, &filter, |count| {
+            use std::io::Write;
+            print!("\r  {}: {}", "Fetched".dimmed(), count);
+            let _ = std::io::stdout().flush();
+        })
+        .await?;
+
+        if !messages.is_empty() {
+            println!("\r  {}: {}", "Fetched".dimmed(), messages.len());
+        }
+
+        tasks.shutdown().await;
+
+        if messages.is_empty() {
+            println!();
+            println!("{}", "No messages found.".yellow());
+            return Ok(());
+        }
+
+        let formatted = formatters::format_messages(&messages, format)?;
+        std::fs::write(&output, &formatted)
+            .context("Failed to write output file")?;
+
+        println!();
+        println!("{}", format!("Exported {} messages to: {}", messages.len(), output).green());
+    }
+
+    #[cfg(not(feature = "telegram"))]
+    {
+        println!();
+        println!("{}", "Note:".yellow().bold());
+        println!("  The telegram feature is not enabled.");
+        println!("  Build with: cargo build --features telegram");
+    }
+
+    Ok(())
+}
+
+/// Print what an export would do
+#[cfg(feature = "telegram")]
+fn print_preview(chat: &str, preview: Option<chat::commands::telegram::ExportPreview>, time_filtered: bool) {
+    println!();
+    println!("{}", "Dry run - no messages will be fetched".bold());
+
+    let Some(preview) = preview else {
+        println!("  {}: 0", "Chats matched".dimmed());
+        println!();
+        println!("{}", format!("Chat not found: {}", chat).yellow());
+        println!("  Use {} to see available chats", "chat telegram list".cyan());
+        return;
+    };
+
+    println!("  {}: {}", "Chats matched".dimmed(), preview.matched.len());
+    if preview.matched.len() > 1 {
+        println!("  {}", "Only the first match is exported:".dimmed());
+    }
+    let first = &preview.matched[0];
+    println!("  {}: {} (ID: {})", "Chat".dimmed(), first.name, first.id);
+
+    let estimate = &preview.estimate;
+    println!("  {}: {}", "Messages in chat".dimmed(), preview.total);
+    if time_filtered {
+        println!("  {}: up to {} (--since/--before not applied to estimate)", "Estimated messages".dimmed(), estimate.messages);
+    } else {
+        println!("  {}: {}", "Estimated messages".dimmed(), estimate.messages);
+    }
+    println!("  {}: {}", "Estimated API calls".dimmed(), estimate.api_calls);
+    println!("  {}: {}", "Expected rate-limit delay".dimmed(), humantime::format_duration(estimate.delay));
+}
@@ -0,0 +1,86 @@
+use anyhow::Result;
+use colored::Colorize;
+
+pub async fn execute() -> Result<()> {
+    println!("{}", "Importing Telegram folders as labels...".dimmed());
+
+    #[cfg(feature = "telegram")]
+    {
+        use chat::{ChatId, LabelStore};
+        use grammers_client::grammers_tl_types as tl;
+
+        let (client, tasks) = chat::commands::telegram::connect().await?;
+
+        let filters = match client.invoke(&tl::functions::messages::GetDialogFilters {}).await {
+            Ok(tl::enums::messages::DialogFilters::Filters(f)) => f.filters,
+            Err(e) => {
+                tasks.shutdown().await;
+                return Err(anyhow::anyhow!("Failed to fetch chat folders: {}", e));
+            }
+        };
+
+        tasks.shutdown().await;
+
+        let mut store = LabelStore::load()?;
+        let mut folder_count = 0;
+        let mut assigned = 0;
+
+        for filter in filters {
+            // The default "All chats" entry and shared chat lists have no peer list to import
+            let folder = match filter {
+                tl::enums::DialogFilter::Filter(f) => f,
+                _ => continue,
+            };
+
+            let tl::enums::TextWithEntities::Entities(title) = folder.title;
+            let label = title.text;
+            let mut folder_chats = 0;
+
+            for peer in folder.pinned_peers.iter().chain(folder.include_peers.iter()) {
+                if let Some(dialog_id) = input_peer_dialog_id(peer) {
+                    if store.add("telegram", &ChatId::new(dialog_id.to_string()), &label) {
+                        assigned += 1;
+                    }
+                    folder_chats += 1;
+                }
+            }
+
+            folder_count += 1;
+            println!("  {} {} ({} chats)", "•".cyan(), label.bold(), folder_chats);
+        }
+
+        if folder_count == 0 {
+            println!();
+            println!("{}", "No chat folders found.".yellow());
+            return Ok(());
+        }
+
+        store.save()?;
+
+        println!();
+        println!("{}", format!("Imported {} folders, {} new label assignments", folder_count, assigned).green());
+    }
+
+    #[cfg(not(feature = "telegram"))]
+    {
+        println!();
+        println!("{}", "Note:".yellow().bold());
+        println!("  The telegram feature is not enabled.");
+        println!("  Build with: cargo build --features telegram");
+    }
+
+    Ok(())
+}
+
+/// Convert a folder's input peer into the Bot API dialog ID used as chat ID elsewhere
+#[cfg(feature = "telegram")]
+fn input_peer_dialog_id(peer: &grammers_client::grammers_tl_types::enums::InputPeer) -> Option<i64> {
+    use grammers_client::grammers_tl_types::enums::InputPeer;
+
+    match peer {
+        InputPeer::User(user) => Some(user.user_id),
+        InputPeer::Chat(chat) => Some(-chat.chat_id),
+        InputPeer::Channel(channel) => Some(-1_000_000_000_000 - channel.channel_id),
+        _ => None,
+    }
+}
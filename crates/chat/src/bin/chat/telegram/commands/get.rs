@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use chat::cli::OutputFormat;
+use super::parse_time;
+
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(not(feature = "telegram"), allow(unused_variables))]
+pub async fn execute(
+    chat: Option<String>,
+    id: Option<String>,
+    limit: usize,
+    since: Option<String>,
+    before: Option<String>,
+    after: Option<String>,
+    sender: Option<String>,
+    _message_type: Option<String>,
+    format: OutputFormat,
+    output: Option<String>,
+) -> Result<()> {
+    let chat_id = chat.or(id).context("Chat name or ID is required")?;
+
+    println!("{}", format!("Fetching messages from '{}'...", chat_id).dimmed());
+
+    let since_time = since.or(after).map(|s| parse_time(&s)).transpose()?;
+    let before_time = before.map(|b| parse_time(&b)).transpose()?;
+
+    if let Some(st) = since_time {
+        println!("  {}: {}", "Since".dimmed(), st.format("%Y-%m-%d %H:%M:%S"));
+    }
+    if let Some(bt) = before_time {
+        println!("  {}: {}", "Before".dimmed(), bt.format("%Y-%m-%d %H:%M:%S"));
+    }
+    if let Some(ref s) = sender {
+        println!("  {}: {}", "Sender filter".dimmed(), s);
+    }
+    println!("  {}: {}", "Limit".dimmed(), limit);
+
+    #[cfg(feature = "telegram")]
+    {
+        use chat::cli::formatters;
+        use chat::commands::telegram::{self, HistoryFilter};
+
+        let (client, tasks) = telegram::connect().await?;
+        let Some(found) = telegram::find_chat(&client, &chat_id).await? else {
+            println!();
+            println!("{}", format!("Chat not found: {}", chat_id).yellow());
+            println!("  Use {} to see available chats", "chat telegram list".cyan());
+            tasks.shutdown().await;
+            return Ok(());
+        };
+        let filter = HistoryFilter {
+            since: since_time,
+            before: before_time,
+            sender,
+            limit: Some(limit),
+        };
+        let messages = telegram::fetch_history(&client, &found, &filter, |_| {}).await?;
+        tasks.shutdown().await;
+
+        if messages.is_empty() {
+            println!();
+            println!("{}", "No messages found.".yellow());
+            return Ok(());
+        }
+
+        let formatted = formatters::format_messages(&messages, format)?;
+
+        if let Some(output_file) = output {
+            std::fs::write(&output_file, &formatted)
+                .context("Failed to write output file")?;
+            println!("{}", format!("Output written to: {}", output_file).green());
+        } else {
+            println!("{}", formatted);
+        }
+    }
+
+    #[cfg(not(feature = "telegram"))]
+    {
+        println!();
+        println!("{}", "Note:".yellow().bold());
+        println!("  The telegram feature is not enabled.");
+        println!("  Build with: cargo build --features telegram");
+    }
+
+    Ok(())
+}
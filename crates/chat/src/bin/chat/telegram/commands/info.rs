@@ -1,8 +1,9 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 
-use crate::cli::OutputFormat;
+use chat::cli::OutputFormat;
 
+#[cfg_attr(not(feature = "telegram"), allow(unused_variables))]
 pub async fn execute(
     chat: Option<String>,
     id: Option<String>,
@@ -14,9 +15,7 @@ pub async fn execute(
 
     #[cfg(feature = "telegram")]
     {
-        use super::client;
-
-        let (client, runner_handle) = client::create_client().await?;
+        let (client, tasks) = chat::commands::telegram::connect().await?;
 
         // Find the chat/dialog by searching through dialogs
         let mut dialogs = client.iter_dialogs();
@@ -64,7 +63,7 @@ pub async fn execute(
             }
         }
 
-        runner_handle.abort();
+        tasks.shutdown().await;
     }
 
     #[cfg(not(feature = "telegram"))]
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::io::{self, Write};
+
+use chat::cli::config::Config;
+
+pub async fn execute(
+    api_id: Option<i32>,
+    api_hash: Option<String>,
+    phone: Option<String>,
+    json: bool,
+) -> Result<()> {
+    let out = Reporter { json };
+    out.status("Initializing Telegram connection...");
+
+    let mut config = Config::load()?;
+
+    // Get or prompt for API credentials
+    let api_id = api_id
+        .or(config.api_id)
+        .context("API ID is required. Set via --api-id, config, or TELEGRAM_API_ID env var")?;
+
+    let api_hash = api_hash
+        .or(config.api_hash.clone())
+        .context("API Hash is required. Set via --api-hash, config, or TELEGRAM_API_HASH env var")?;
+
+    let phone = phone
+        .or(config.phone.clone())
+        .context("Phone number is required. Set via --phone flag")?;
+
+    // Save configuration
+    config.api_id = Some(api_id);
+    config.api_hash = Some(api_hash.clone());
+    config.phone = Some(phone.clone());
+    config.save()?;
+
+    if !json {
+        println!("{}", "Configuration saved.".green());
+    }
+
+    #[cfg(feature = "telegram")]
+    {
+        telegram_auth(api_id, &api_hash, &phone, &out).await?;
+    }
+
+    #[cfg(not(feature = "telegram"))]
+    {
+        println!("\n{}", "Note:".yellow().bold());
+        println!("  The telegram feature is not enabled.");
+        println!("  Build with: cargo build --features telegram");
+    }
+
+    Ok(())
+}
+
+/// Progress and prompts, for a terminal or, with `--json`, for a program driving the login
+struct Reporter {
+    json: bool,
+}
+
+#[cfg_attr(not(feature = "telegram"), allow(dead_code))]
+impl Reporter {
+    fn status(&self, text: &str) {
+        if !self.json {
+            println!("{}", text.bold());
+        }
+    }
+
+    /// Emit a JSON event line; nothing is printed in terminal mode
+    fn event(&self, event: serde_json::Value) -> Result<()> {
+        if self.json {
+            let mut stdout = io::stdout();
+            writeln!(stdout, "{}", event)?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Ask for a line of input, announcing it with a prompt or a JSON event
+    fn ask(&self, prompt: &str, event: serde_json::Value) -> Result<String> {
+        if self.json {
+            self.event(event)?;
+        } else {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+        }
+
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            anyhow::bail!("Login cancelled");
+        }
+        Ok(answer.trim().to_string())
+    }
+
+    fn signed_in(&self, name: Option<&str>, username: Option<&str>) -> Result<()> {
+        if !self.json {
+            println!("{}", "✓ Successfully signed in!".green().bold());
+            println!("  User: {}", name.unwrap_or("Unknown"));
+            if let Some(username) = username {
+                println!("  Username: @{}", username);
+            }
+        }
+        self.event(serde_json::json!({ "event": "signed_in", "user": name, "username": username }))
+    }
+}
+
+#[cfg(feature = "telegram")]
+async fn telegram_auth(api_id: i32, api_hash: &str, phone: &str, out: &Reporter) -> Result<()> {
+    use chat::{Supervisor, TelegramSession};
+    use grammers_client::Client;
+    use grammers_mtsender::SenderPool;
+
+    out.status("Connecting to Telegram...");
+
+    let session = TelegramSession::open_default()?;
+    let pool = session.sender_pool(api_id);
+    let client = Client::new(&pool);
+
+    // Start the network runner
+    let SenderPool { runner, .. } = pool;
+    let tasks = Supervisor::new();
+    tasks.spawn_once("runner", async move {
+        runner.run().await;
+        Ok::<_, anyhow::Error>(())
+    });
+
+    let result = sign_in(&client, api_hash, phone, out).await;
+    if result.is_ok() {
+        session.save()?;
+        if !out.json {
+            println!("  Session saved to {}", session.path().display());
+        }
+    }
+
+    tasks.shutdown().await;
+    result
+}
+
+#[cfg(feature = "telegram")]
+async fn sign_in(client: &grammers_client::Client, api_hash: &str, phone: &str, out: &Reporter) -> Result<()> {
+    use grammers_client::SignInError;
+
+    if client.is_authorized().await? {
+        if !out.json {
+            println!("{}", "✓ Already signed in!".green().bold());
+        }
+        let me = client.get_me().await.ok();
+        return out.signed_in(
+            me.as_ref().and_then(|me| me.first_name()),
+            me.as_ref().and_then(|me| me.username()),
+        );
+    }
+
+    out.status("Requesting authentication code...");
+    let token = client
+        .request_login_code(phone, api_hash)
+        .await
+        .context("Failed to request login code")?;
+
+    let code = out.ask(
+        "Enter the code you received: ",
+        serde_json::json!({ "event": "code_required" }),
+    )?;
+
+    out.status("Signing in...");
+
+    match client.sign_in(&token, &code).await {
+        Ok(_) => {}
+        Err(SignInError::PasswordRequired(password_token)) => {
+            let hint = password_token.hint().map(str::to_string);
+            let prompt = match &hint {
+                Some(hint) => format!("Two-factor authentication enabled. Hint: {}\nEnter your password: ", hint),
+                None => "Two-factor authentication enabled.\nEnter your password: ".to_string(),
+            };
+            let password = out.ask(&prompt, serde_json::json!({ "event": "password_required", "hint": hint }))?;
+
+            out.status("Checking password...");
+            client
+                .check_password(password_token, password.as_str())
+                .await
+                .context("Failed to sign in with password")?;
+        }
+        Err(e) => return Err(anyhow::anyhow!("Failed to sign in: {}", e)),
+    }
+
+    let me = client.get_me().await.ok();
+    out.signed_in(
+        me.as_ref().and_then(|me| me.first_name()),
+        me.as_ref().and_then(|me| me.username()),
+    )
+}
@@ -1,18 +1,18 @@
 use anyhow::Result;
 use colored::Colorize;
-use chat::{Chat, ChatId, ChatType};
 
-use crate::cli::{ChatTypeFilter, OutputFormat};
-use crate::formatters;
+use chat::cli::{ChatTypeFilter, OutputFormat};
 
+#[cfg_attr(not(feature = "telegram"), allow(unused_variables))]
 pub async fn execute(format: OutputFormat, chat_type: Option<ChatTypeFilter>) -> Result<()> {
     println!("{}", "Fetching chat list...".dimmed());
 
     #[cfg(feature = "telegram")]
     {
-        use super::client;
+        use chat::cli::formatters;
+        use chat::{Chat, ChatId, ChatType};
 
-        let (client, runner_handle) = client::create_client().await?;
+        let (client, tasks) = chat::commands::telegram::connect().await?;
 
         // Fetch all dialogs
         let mut chats = Vec::new();
@@ -21,7 +21,7 @@ pub async fn execute(format: OutputFormat, chat_type: Option<ChatTypeFilter>) ->
         while let Some(dialog) = dialogs.next().await? {
             let peer = dialog.peer();
             let name = peer.name().unwrap_or("Unknown");
-            let id = ChatId::new(&peer.id().bot_api_dialog_id().to_string());
+            let id = ChatId::new(peer.id().bot_api_dialog_id().to_string());
 
             // Note: grammers v0.8 API doesn't provide easy peer type discrimination
             // Setting all to Unknown for simplicity
@@ -30,11 +30,12 @@ pub async fn execute(format: OutputFormat, chat_type: Option<ChatTypeFilter>) ->
                 title: Some(name.to_string()),
                 chat_type: ChatType::Unknown,
                 participant_count: None,
+                topic: None,
             };
             chats.push(chat);
         }
 
-        runner_handle.abort();
+        tasks.shutdown().await;
 
         // Apply filter (all are Unknown type, so filter will match all or none)
         let filtered_chats: Vec<_> = if chat_type.is_some() {
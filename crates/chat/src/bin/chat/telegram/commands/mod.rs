@@ -1,6 +1,6 @@
-pub mod client;
 pub mod config_cmd;
 pub mod export;
+pub mod folders;
 pub mod get;
 pub mod info;
 pub mod init;
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::cli::OutputFormat;
+
+#[cfg_attr(not(feature = "telegram"), allow(unused_variables))]
+pub async fn execute(
+    chat: Option<String>,
+    term: String,
+    all: bool,
+    ignore_case: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    if all {
+        println!("{}", format!("Searching all chats for '{}'...", term).bold());
+    } else if let Some(ref chat_name) = chat {
+        println!("{}", format!("Searching '{}' for '{}'...", chat_name, term).bold());
+    } else {
+        anyhow::bail!("Either provide a chat name or use --all flag");
+    }
+
+    if ignore_case {
+        println!("  {}: enabled", "Case-insensitive".dimmed());
+    }
+
+    #[cfg(feature = "telegram")]
+    {
+        use chat::cli::formatters;
+        use chat::commands::telegram;
+
+        let (client, tasks) = telegram::connect().await?;
+
+        let messages = if all {
+            let results = telegram::search_all(&client, &term, ignore_case).await?;
+            println!("  {}: {} chats", "Searched".dimmed(), results.chats);
+            results.messages
+        } else {
+            let chat_id = chat.unwrap();
+            let Some(found) = telegram::find_chat(&client, &chat_id).await? else {
+                println!();
+                println!("{}", format!("Chat not found: {}", chat_id).yellow());
+                println!("  Use {} to see available chats", "chat telegram list".cyan());
+                tasks.shutdown().await;
+                return Ok(());
+            };
+            telegram::search_chat(&client, &found, &term, ignore_case).await?
+        };
+
+        tasks.shutdown().await;
+
+        if messages.is_empty() {
+            println!();
+            println!("{}", "No messages found matching the search term.".yellow());
+            return Ok(());
+        }
+
+        println!("  {}: {}", "Found".dimmed(), messages.len());
+        println!();
+
+        let formatted = formatters::format_messages(&messages, format)?;
+        println!("{}", formatted);
+    }
+
+    #[cfg(not(feature = "telegram"))]
+    {
+        println!();
+        println!("{}", "Note:".yellow().bold());
+        println!("  The telegram feature is not enabled.");
+        println!("  Build with: cargo build --features telegram");
+    }
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use colored::Colorize;
 
-use crate::config::Config;
+use chat::cli::config::Config;
 
 pub async fn execute() -> Result<()> {
     let config = Config::load()?;
@@ -10,13 +10,13 @@ pub async fn execute() -> Result<()> {
     println!("{}", "Telegram Connection Status".bold());
     println!();
 
-    if config.api_id.is_some() && config.api_hash.is_some() {
+    if let (Some(api_id), Some(_)) = (config.api_id, &config.api_hash) {
         println!("  {}: {}", "Configuration".bold(), "Present".green());
-        println!("  {}: {:?}", "API ID", config.api_id.unwrap());
-        println!("  {}: {}", "API Hash", "***".dimmed());
+        println!("  API ID: {:?}", api_id);
+        println!("  API Hash: {}", "***".dimmed());
 
         if let Some(phone) = &config.phone {
-            println!("  {}: {}", "Phone", phone);
+            println!("  Phone: {}", phone);
         }
     } else {
         println!("  {}: {}", "Configuration".bold(), "Not configured".red());
@@ -26,7 +26,7 @@ pub async fn execute() -> Result<()> {
     }
 
     println!();
-    println!("  {}: {:?}", "Session File", session_file);
+    println!("  Session File: {:?}", session_file);
 
     if !session_file.exists() {
         println!("  {}: {}", "Session Status".bold(), "Not found".yellow());
@@ -40,13 +40,11 @@ pub async fn execute() -> Result<()> {
     // Try to connect and check authorization
     #[cfg(feature = "telegram")]
     {
-        use super::client;
-
         println!();
         println!("  {}", "Checking connection...".dimmed());
 
-        match client::create_client().await {
-            Ok((client, runner_handle)) => {
+        match chat::commands::telegram::connect().await {
+            Ok((client, tasks)) => {
                 println!("  {}: {}", "Connection".bold(), "Authorized".green());
 
                 // Get user info
@@ -54,21 +52,21 @@ pub async fn execute() -> Result<()> {
                     Ok(me) => {
                         println!();
                         println!("  {}", "Account Information:".bold());
-                        println!("    {}: {}", "Name", me.first_name().unwrap_or("Unknown"));
+                        println!("    Name: {}", me.first_name().unwrap_or("Unknown"));
                         if let Some(last_name) = me.last_name() {
-                            println!("    {}: {}", "Last Name", last_name);
+                            println!("    Last Name: {}", last_name);
                         }
                         if let Some(username) = me.username() {
-                            println!("    {}: @{}", "Username", username);
+                            println!("    Username: @{}", username);
                         }
-                        println!("    {}: {}", "User ID", me.raw.id());
+                        println!("    User ID: {}", me.raw.id());
                     }
                     Err(e) => {
                         println!("  {}: Failed to get user info: {}", "Warning".yellow(), e);
                     }
                 }
 
-                runner_handle.abort();
+                tasks.shutdown().await;
             }
             Err(e) => {
                 println!("  {}: {}", "Connection".bold(), "Failed".red());
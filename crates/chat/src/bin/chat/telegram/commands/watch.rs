@@ -0,0 +1,82 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::cli::OutputFormat;
+
+#[cfg_attr(not(feature = "telegram"), allow(unused_variables))]
+pub async fn execute(chat: Option<String>, all: bool, format: OutputFormat) -> Result<()> {
+    // Status goes to stderr so stdout stays a clean NDJSON stream with --format json
+    if all {
+        eprintln!("{}", "Watching all chats for new messages...".bold());
+    } else if let Some(ref chat_name) = chat {
+        eprintln!("{}", format!("Watching '{}' for new messages...", chat_name).bold());
+    } else {
+        anyhow::bail!("Either provide a chat name or use --all flag");
+    }
+
+    eprintln!("{}", "Press Ctrl+C to stop watching.".dimmed());
+    eprintln!();
+
+    #[cfg(feature = "telegram")]
+    {
+        use chat::commands::telegram::MessageWatch;
+        use chat::{MessageContent, StreamEvent};
+
+        let mut watch = MessageWatch::start().await?;
+
+        if let Some(ref chat_id) = chat {
+            match watch.only(chat_id).await? {
+                Some(found) => eprintln!("{}", format!("Found chat: {} (ID: {})", found.name, found.id).green()),
+                None => {
+                    eprintln!();
+                    eprintln!("{}", format!("Chat not found: {}", chat_id).yellow());
+                    eprintln!("  Use {} to see available chats", "chat telegram list".cyan());
+                    watch.stop().await;
+                    return Ok(());
+                }
+            }
+        }
+
+        eprintln!();
+
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {
+                    eprintln!();
+                    eprintln!("{}", "Stopping watch...".yellow());
+                    break;
+                }
+                message = watch.next() => {
+                    let message = message?;
+                    match format {
+                        OutputFormat::Json => println!("{}", StreamEvent::message("telegram", message).to_line()?),
+                        _ => {
+                            let text = match &message.content {
+                                MessageContent::Text(text) => text.as_str(),
+                                _ => "",
+                            };
+                            println!(
+                                "[{}] {}: {}",
+                                message.timestamp.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+                                message.sender.display_name.as_deref().unwrap_or("Unknown").cyan(),
+                                text
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        watch.stop().await;
+    }
+
+    #[cfg(not(feature = "telegram"))]
+    {
+        eprintln!();
+        eprintln!("{}", "Note:".yellow().bold());
+        eprintln!("  The telegram feature is not enabled.");
+        eprintln!("  Build with: cargo build --features telegram");
+    }
+
+    Ok(())
+}
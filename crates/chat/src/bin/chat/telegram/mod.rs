@@ -2,7 +2,7 @@ use anyhow::Result;
 use clap::Subcommand;
 
 pub mod commands;
-use super::cli::*;
+use chat::cli::*;
 use commands::*;
 
 #[derive(Subcommand)]
@@ -20,6 +20,10 @@ pub enum TelegramCommand {
         /// Phone number (with country code)
         #[arg(long)]
         phone: Option<String>,
+
+        /// Report progress as JSON lines and read the code and password from stdin without prompts
+        #[arg(long)]
+        json: bool,
     },
 
     /// Check connection status
@@ -116,6 +120,10 @@ pub enum TelegramCommand {
         /// Maximum number of messages to export
         #[arg(short, long)]
         limit: Option<usize>,
+
+        /// Report matched chats and estimated cost without fetching messages
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Search messages by text content
@@ -153,6 +161,9 @@ pub enum TelegramCommand {
         format: OutputFormat,
     },
 
+    /// Import Telegram chat folders as local labels
+    ImportFolders,
+
     /// Manage configuration
     Config {
         #[command(subcommand)]
@@ -187,7 +198,8 @@ pub async fn execute(command: TelegramCommand) -> Result<()> {
             api_id,
             api_hash,
             phone,
-        } => init::execute(api_id, api_hash, phone).await,
+            json,
+        } => init::execute(api_id, api_hash, phone, json).await,
 
         TelegramCommand::Status => status::execute().await,
 
@@ -218,7 +230,8 @@ pub async fn execute(command: TelegramCommand) -> Result<()> {
             since,
             before,
             limit,
-        } => export::execute(chat, format, output, since, before, limit).await,
+            dry_run,
+        } => export::execute(chat, format, output, since, before, limit, dry_run).await,
 
         TelegramCommand::Search {
             term,
@@ -230,6 +243,8 @@ pub async fn execute(command: TelegramCommand) -> Result<()> {
 
         TelegramCommand::Info { chat, id, format } => info::execute(chat, id, format).await,
 
+        TelegramCommand::ImportFolders => folders::execute().await,
+
         TelegramCommand::Config { action } => match action {
             ConfigAction::Set { key, value } => config_cmd::set(key, value).await,
             ConfigAction::Get { key } => config_cmd::get(key).await,
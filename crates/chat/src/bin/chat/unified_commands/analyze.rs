@@ -0,0 +1,111 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::analysis;
+use chat::commands::{self, analysis::AnalysisQuery};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+pub async fn execute(
+    filter: String,
+    since: Option<String>,
+    before: Option<String>,
+    limit: Option<usize>,
+    sentiment: bool,
+    format: String,
+) -> Result<()> {
+    println!("{}", "Analyzing messages...".dimmed());
+
+    let manager = super::create_manager()?;
+    let query = AnalysisQuery { filter, since, before, sender: None, limit };
+    let analyses = commands::analysis::analyze(&manager, &query, &chrono::Local, sentiment).await?;
+
+    if analyses.is_empty() {
+        println!();
+        println!("{}", "No messages found.".yellow());
+        return Ok(());
+    }
+
+    match format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&analyses)?;
+            println!("{}", json);
+        }
+        _ => {
+            for chat in analyses {
+                println!();
+                println!("{} {} ({} messages)", "Chat".bold(), chat.chat_id.to_string().cyan(), chat.message_count);
+                println!();
+
+                print_heatmap(&chat.heatmap);
+
+                if let Some((day, hour, count)) = chat.heatmap.peak() {
+                    println!();
+                    println!("  Busiest: {} {:02}:00 ({} messages)", WEEKDAYS[day], hour, count);
+                }
+
+                if !chat.response_times.is_empty() {
+                    println!();
+                    println!("  {}", "Response times:".bold());
+                    for stats in chat.response_times.iter().take(10) {
+                        println!("    {} → {}: median {}, mean {} ({} replies)",
+                            stats.responder,
+                            stats.to,
+                            format_secs(stats.median_secs),
+                            format_secs(stats.mean_secs),
+                            stats.count
+                        );
+                    }
+                }
+
+                if let Some(sentiment) = &chat.sentiment {
+                    println!();
+                    println!("  {} {:+.2} ({} positive, {} neutral, {} negative)",
+                        "Sentiment:".bold(),
+                        sentiment.mean,
+                        sentiment.positive.to_string().green(),
+                        sentiment.neutral,
+                        sentiment.negative.to_string().red()
+                    );
+                    if sentiment.skipped > 0 {
+                        println!("  {} non-English messages not scored", sentiment.skipped.to_string().dimmed());
+                    }
+                }
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Render the hour × weekday heatmap as shaded blocks
+fn print_heatmap(heatmap: &analysis::ActivityHeatmap) {
+    const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+    let max = heatmap.max().max(1);
+
+    println!("       {}", "0     6     12    18   23".dimmed());
+    for (day, hours) in heatmap.counts.iter().enumerate() {
+        let row: String = hours
+            .iter()
+            .map(|&count| {
+                if count == 0 {
+                    '·'
+                } else {
+                    SHADES[(1 + count * 3 / max).min(4)]
+                }
+            })
+            .collect();
+        println!("  {}  {}", WEEKDAYS[day], row);
+    }
+}
+
+fn format_secs(secs: i64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+use std::path::PathBuf;
+
+use chat::archive::{self, ArchiveWriter};
+use chat::commands;
+use chat::forget::ForgetLog;
+
+#[derive(Subcommand)]
+pub enum ArchiveAction {
+    /// Subscribe to all streaming sources and append every message to the archive
+    Run {
+        /// Archive directory (default: ~/.local/share/chat/archive-log)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+    /// Check the archive's hash chain for modified, missing or reordered records
+    Verify {
+        /// Archive directory (default: ~/.local/share/chat/archive-log)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+}
+
+pub async fn execute(action: ArchiveAction) -> Result<()> {
+    match action {
+        ArchiveAction::Run { dir } => run(resolve_dir(dir)?).await,
+        ArchiveAction::Verify { dir, format } => verify(resolve_dir(dir)?, &format),
+    }
+}
+
+fn resolve_dir(dir: Option<PathBuf>) -> Result<PathBuf> {
+    match dir {
+        Some(dir) => Ok(dir),
+        None => ArchiveWriter::default_dir(),
+    }
+}
+
+async fn run(dir: PathBuf) -> Result<()> {
+    let mut writer = ArchiveWriter::open(&dir)?;
+    let forgotten = ForgetLog::load()?;
+    let manager = super::create_manager()?;
+
+    let (subscribed, mut rx) = super::subscribe_all(&manager, commands::archive::buffer_config()).await?;
+
+    if subscribed.is_empty() {
+        println!("{}", "No sources to archive.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{} {} to {} (Ctrl+C to stop)",
+        "Archiving".bold(),
+        subscribed.join(", "),
+        dir.display()
+    );
+
+    let stop = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    let report = commands::archive::run(&mut writer, &mut rx, &forgotten, stop).await?;
+
+    println!();
+    println!("Archived {} messages", report.archived);
+    println!("Head: {}", report.head.dimmed());
+
+    Ok(())
+}
+
+fn verify(dir: PathBuf, format: &str) -> Result<()> {
+    let report = archive::verify(&dir)?;
+
+    match format {
+        "json" => {
+            let json = serde_json::json!({
+                "valid": report.is_valid(),
+                "files": report.files,
+                "records": report.records,
+                "head": report.head,
+                "error": report.error.as_ref().map(|e| serde_json::json!({
+                    "file": e.file,
+                    "line": e.line,
+                    "reason": e.reason,
+                })),
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        _ => {
+            println!("{} {}", "Verifying".bold(), dir.display());
+            println!("  {} records in {} files", report.records, report.files);
+            match &report.error {
+                None => {
+                    println!("  {} chain intact", "✓".green());
+                    println!("  Head: {}", report.head.dimmed());
+                }
+                Some(error) => {
+                    println!("  {} {}", "✗".red(), error.to_string().red());
+                }
+            }
+        }
+    }
+
+    if !report.is_valid() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
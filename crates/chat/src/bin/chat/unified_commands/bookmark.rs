@@ -0,0 +1,140 @@
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+
+use chat::{MessageContent, MessageFilter, MessageId, filter_parser};
+use chat::bookmarks::{Bookmark, BookmarkStore};
+
+#[derive(Subcommand)]
+pub enum BookmarkAction {
+    /// Bookmark a message
+    Add {
+        /// Message reference (format: source:message_id, e.g., "telegram:12345")
+        message: String,
+
+        /// Chat containing the message, used to capture its content (name or ID)
+        #[arg(long)]
+        chat: Option<String>,
+
+        /// Note to keep with the bookmark
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// List bookmarks, newest first
+    List {
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Remove a bookmark
+    Remove {
+        /// Message reference (format: source:message_id)
+        message: String,
+    },
+}
+
+pub async fn execute(action: BookmarkAction) -> Result<()> {
+    let mut store = BookmarkStore::load()?;
+
+    match action {
+        BookmarkAction::Add { message, chat, note } => {
+            let (source, message_id) = parse_reference(&message)?;
+            let mut bookmark = Bookmark::new(source, message_id);
+            bookmark.note = note;
+
+            if let Some(chat) = chat {
+                capture_content(&mut bookmark, &chat).await?;
+            }
+
+            if store.add(bookmark) {
+                println!("{}", format!("Bookmarked {}", message).green());
+            } else {
+                println!("{}", format!("Updated the bookmark of {}", message).green());
+            }
+            store.save()?;
+        }
+        BookmarkAction::List { format } => {
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(store.list())?);
+                return Ok(());
+            }
+
+            if store.list().is_empty() {
+                println!("{}", "No bookmarks.".yellow());
+                println!();
+                println!("Use {} to add one", "chat bookmark add <source:message_id> --chat <chat>".cyan());
+                return Ok(());
+            }
+
+            println!();
+            println!("{}", "Bookmarks:".bold());
+            println!();
+
+            for bookmark in store.list() {
+                println!("  {} {} {}",
+                    bookmark.reference().cyan(),
+                    bookmark.created.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string().dimmed(),
+                    bookmark.summary()
+                );
+            }
+
+            println!();
+        }
+        BookmarkAction::Remove { message } => {
+            let (source, message_id) = parse_reference(&message)?;
+            match store.remove(source, &message_id) {
+                Some(_) => {
+                    store.save()?;
+                    println!("{}", format!("Removed the bookmark of {}", message).green());
+                }
+                None => println!("{}", format!("No bookmark for '{}'", message).yellow()),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_reference(message: &str) -> Result<(&str, MessageId)> {
+    message
+        .split_once(':')
+        .filter(|(source, id)| !source.is_empty() && !id.is_empty())
+        .map(|(source, id)| (source, MessageId::new(id)))
+        .ok_or_else(|| anyhow::anyhow!("Invalid message reference '{}'. Expected source:message_id", message))
+}
+
+/// Look up the referenced message in a chat and store its content with the bookmark
+async fn capture_content(bookmark: &mut Bookmark, chat: &str) -> Result<()> {
+    let (_, chat_pattern) = filter_parser::parse_source_filter(&format!("{}:{}", bookmark.source, chat))?;
+    let manager = super::create_manager()?;
+
+    let filter = MessageFilter {
+        chat: chat_pattern,
+        limit: Some(1000),
+        ..MessageFilter::new()
+    };
+
+    let messages = match manager.query_messages(Some(&bookmark.source), filter).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            println!("{}", format!("Warning: could not fetch message content: {}", e).yellow());
+            return Ok(());
+        }
+    };
+
+    match messages.into_iter().find(|m| m.id == bookmark.message_id) {
+        Some(message) => {
+            bookmark.chat_id = Some(message.chat_id);
+            bookmark.sender = message.sender.display_name;
+            bookmark.content = Some(match message.content {
+                MessageContent::Text(text) => text,
+                _ => "[Non-text content]".to_string(),
+            });
+        }
+        None => {
+            println!("{}", format!("Warning: message {} not found in '{}'", bookmark.message_id, chat).yellow());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use anyhow::Result;
+use chrono::Utc;
+use clap::{Args, Subcommand};
+use colored::Colorize;
+
+use chat::forget::{self, ForgetAction, ForgetLog, ForgetRecord, ForgetReport, ForgetTarget, LocalData};
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Remove a chat or person from local data: synced messages, derived attachment text, embeddings, todos, notes and labels
+    Forget {
+        #[command(flatten)]
+        target: TargetArgs,
+
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Let a forgotten chat or person back into local data
+    Unforget {
+        #[command(flatten)]
+        target: TargetArgs,
+    },
+    /// List forgotten chats and people, and the log of what was removed
+    Forgotten {
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+pub struct TargetArgs {
+    /// Chat as source:chat_id (e.g. telegram:-100123)
+    #[arg(long)]
+    chat: Option<String>,
+
+    /// Person as source:user_id (e.g. telegram:12345)
+    #[arg(long)]
+    user: Option<String>,
+}
+
+impl TargetArgs {
+    fn parse(&self) -> Result<ForgetTarget> {
+        match (&self.chat, &self.user) {
+            (Some(chat), _) => Ok(ForgetTarget::Chat(chat.parse()?)),
+            (None, Some(user)) => ForgetTarget::parse_user(user),
+            (None, None) => anyhow::bail!("Pass --chat or --user"),
+        }
+    }
+}
+
+pub async fn execute(action: CacheAction) -> Result<()> {
+    match action {
+        CacheAction::Forget { target, dry_run, format } => {
+            let target = target.parse()?;
+            let report = forget::forget(&LocalData::default_paths()?, &target, dry_run)?;
+            print_report(&target, &report, dry_run, &format)
+        }
+        CacheAction::Unforget { target } => {
+            let target = target.parse()?;
+            let path = ForgetLog::default_path()?;
+            let mut log = ForgetLog::load_from(&path)?;
+            if !log.is_forgotten(&target) {
+                println!("{}", format!("{} is not forgotten", target).yellow());
+                return Ok(());
+            }
+            log.append_to(&path, ForgetRecord {
+                at: Utc::now(),
+                action: ForgetAction::Unforget,
+                target: target.clone(),
+                removed: ForgetReport::default(),
+            })?;
+            println!("{}", format!("{} can be synced and indexed again", target).green());
+            Ok(())
+        }
+        CacheAction::Forgotten { format } => list(&format),
+    }
+}
+
+fn print_report(target: &ForgetTarget, report: &ForgetReport, dry_run: bool, format: &str) -> Result<()> {
+    match format {
+        "json" => {
+            let json = serde_json::json!({
+                "target": target,
+                "dry_run": dry_run,
+                "removed": report,
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        _ => {
+            let heading = if dry_run { "Would forget" } else { "Forgot" };
+            println!("{} {}", heading.bold(), target);
+            for (count, what) in [
+                (report.messages, "synced messages"),
+                (report.media_text, "attachment texts"),
+                (report.embeddings, "embedding index entries"),
+                (report.todos, "todos"),
+                (report.notes, "notes"),
+                (report.labels, "labels"),
+            ] {
+                if count > 0 {
+                    println!("  {} {}", count, what);
+                }
+            }
+            if report.is_empty() {
+                println!("  {}", "Nothing stored locally".dimmed());
+            }
+            if report.archived_records > 0 {
+                println!(
+                    "  {}",
+                    format!(
+                        "{} records kept in the tamper-evident archive; removing them would break its hash chain",
+                        report.archived_records
+                    )
+                    .yellow()
+                );
+            }
+            if dry_run {
+                println!("{}", "Dry run: nothing was removed.".dimmed());
+            } else {
+                println!("{}", "Logged; sync, archive, media and index runs will skip it from now on.".dimmed());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn list(format: &str) -> Result<()> {
+    let log = ForgetLog::load()?;
+
+    match format {
+        "json" => {
+            let json = serde_json::json!({
+                "forgotten": log.forgotten(),
+                "log": log.records(),
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        _ => {
+            if log.records().is_empty() {
+                println!("{}", "Nothing has been forgotten.".yellow());
+                return Ok(());
+            }
+            for record in log.records() {
+                let action = match record.action {
+                    ForgetAction::Forget => "forget".red(),
+                    ForgetAction::Unforget => "unforget".green(),
+                };
+                println!("{} {:<8} {}", record.at.format("%Y-%m-%d %H:%M").to_string().dimmed(), action, record.target);
+            }
+            println!();
+            println!("{} currently forgotten", log.forgotten().len());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashSet;
+
+use chat::catchup::{self, UnreadChat};
+use chat::{ChatPattern, GlobalChatId, Message, MessageFilter, ReadCursors, filter_parser};
+
+/// Unread messages fetched per chat at most
+const MAX_UNREAD: usize = 500;
+
+/// What `catchup --format json` prints
+#[derive(Serialize)]
+struct CatchUp {
+    summary: String,
+    /// Whether `summary` was written by the LLM rather than the plain digest
+    summarized: bool,
+    marked_read: bool,
+    chats: Vec<ChatUnread>,
+}
+
+#[derive(Serialize)]
+struct ChatUnread {
+    chat: String,
+    title: Option<String>,
+    unread: usize,
+    urgent: usize,
+    /// The unread messages, oldest first, with `--messages`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    messages: Vec<Message>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CatchupFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+pub struct CatchupArgs {
+    /// Chats to catch up on (format: source:pattern, e.g., "telegram:#work"); all chats by default
+    filters: Vec<String>,
+
+    /// How far back to look in chats never read before (e.g., "24h", "2025-01-15")
+    #[arg(long, default_value = "24h")]
+    since: String,
+
+    /// Move the read cursors past the summarized messages
+    #[arg(long)]
+    mark_read: bool,
+
+    /// Ollama model for the summary (default: llama3.2)
+    #[arg(long)]
+    model: Option<String>,
+
+    /// Print a plain digest instead of asking an LLM
+    #[arg(long)]
+    no_llm: bool,
+
+    /// Prompt budget in estimated tokens
+    #[arg(long, default_value_t = chat::catchup::DEFAULT_BUDGET)]
+    budget: usize,
+
+    /// Include the unread messages in JSON output, e.g. to triage them one by one
+    #[arg(long)]
+    messages: bool,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = CatchupFormat::Text)]
+    format: CatchupFormat,
+}
+
+pub async fn execute(args: CatchupArgs) -> Result<()> {
+    let CatchupArgs { filters, since, mark_read, model, no_llm, budget, messages, format } = args;
+    let default_since = filter_parser::parse_time_spec(&since)?;
+    let filters = if filters.is_empty() { vec!["*:*".to_string()] } else { filters };
+
+    let manager = super::create_manager()?;
+    let mut cursors = ReadCursors::load()?;
+
+    // Chats matched by more than one filter are caught up once, as are merged chats
+    let mut seen = HashSet::new();
+    let mut chats = Vec::new();
+    for filter in &filters {
+        let (source_id, chat_pattern) = filter_parser::parse_source_filter(filter)?;
+        for (source, chat) in chat::commands::chats::matching(&manager, source_id.as_deref(), &chat_pattern).await? {
+            let id = GlobalChatId::from_chat_id(&source, &manager.merged_into(&source, &chat.id)?);
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+
+            let since = cursors.cursor(&id).map(|c| c.timestamp).unwrap_or(default_since);
+            let msg_filter = MessageFilter {
+                chat: ChatPattern::Id(chat.id.clone()),
+                since: Some(since),
+                limit: Some(MAX_UNREAD),
+                ..MessageFilter::new()
+            };
+            let messages = match manager.query_messages(Some(&source), msg_filter).await {
+                Ok(messages) => messages,
+                Err(e) => {
+                    eprintln!("{}", format!("Skipping {}: {}", id, e).yellow());
+                    continue;
+                }
+            };
+
+            let unread = messages
+                .into_iter()
+                .filter(|m| !m.pending && cursors.is_unread(&id, m))
+                .collect();
+            chats.push(UnreadChat::new(id, chat.title.clone(), unread));
+        }
+    }
+    catchup::order(&mut chats);
+
+    let (summary, summarized) = if chats.is_empty() {
+        ("All caught up.".to_string(), false)
+    } else if no_llm {
+        (catchup::digest(&chats), false)
+    } else {
+        if format != CatchupFormat::Json {
+            eprintln!("{}", "Summarizing...".dimmed());
+        }
+        let model = model.as_deref().unwrap_or(catchup::DEFAULT_SUMMARY_MODEL);
+        match generate(&catchup::prompt(&chats, budget), model).await {
+            Ok(summary) => (summary, true),
+            Err(e) => {
+                eprintln!("{}", format!("Summary unavailable ({}), showing a digest instead", e).yellow());
+                (catchup::digest(&chats), false)
+            }
+        }
+    };
+
+    if mark_read {
+        for chat in &chats {
+            cursors.mark_read(chat.chat.clone(), &chat.messages);
+        }
+        cursors.save()?;
+    }
+
+    match format {
+        CatchupFormat::Json => {
+            let output = CatchUp {
+                summary,
+                summarized,
+                marked_read: mark_read,
+                chats: chats
+                    .iter()
+                    .map(|chat| ChatUnread {
+                        chat: chat.chat.to_string(),
+                        title: chat.title.clone(),
+                        unread: chat.messages.len(),
+                        urgent: chat.urgent,
+                        messages: if messages { chat.messages.clone() } else { Vec::new() },
+                    })
+                    .collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        CatchupFormat::Text => {
+            for chat in &chats {
+                let mut line = format!("{} {}", chat.name().bold(), format!("({} unread)", chat.messages.len()).dimmed());
+                if chat.urgent > 0 {
+                    line.push_str(&format!(" {}", format!("{} urgent", chat.urgent).red().bold()));
+                }
+                println!("{}", line);
+            }
+            if !chats.is_empty() {
+                println!();
+            }
+            println!("{}", summary);
+            if mark_read && !chats.is_empty() {
+                println!();
+                println!("{}", "Marked as read.".green());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Ask an Ollama model for a completion of `prompt`
+#[cfg(feature = "summaries")]
+pub(super) async fn generate(prompt: &str, model: &str) -> Result<String> {
+    use anyhow::Context;
+
+    let host = std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string());
+    let response: serde_json::Value = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .build()?
+        .post(format!("{}/api/generate", host))
+        .json(&serde_json::json!({ "model": model, "prompt": prompt, "stream": false }))
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach Ollama at {}", host))?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    response["response"]
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Ollama returned no response"))
+}
+
+#[cfg(not(feature = "summaries"))]
+pub(super) async fn generate(_prompt: &str, _model: &str) -> Result<String> {
+    anyhow::bail!("LLM summaries require the 'summaries' feature")
+}
@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use std::path::PathBuf;
+
+use chat::channel_stats::{self, ChannelStats, Period, SubscriberHistory, SubscriberSample};
+use chat::{GlobalChatId, filter_parser};
+
+pub async fn execute(
+    channel: String,
+    since: String,
+    by: String,
+    top: usize,
+    csv: Option<PathBuf>,
+    json: Option<PathBuf>,
+    format: String,
+) -> Result<()> {
+    let since = filter_parser::parse_time_spec(&since)?;
+    let period: Period = by.parse()?;
+    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&channel)?;
+    let source_id = source_id.context("Name the channel's source, e.g. telegram:\"My channel\"")?;
+
+    let manager = super::create_manager()?;
+    let mut chats = chat::commands::chats::matching(&manager, Some(&source_id), &chat_pattern).await?;
+    let (source, chat) = match chats.len() {
+        0 => anyhow::bail!("No chat matches '{}'", channel),
+        1 => chats.remove(0),
+        n => {
+            let names: Vec<&str> = chats.iter().map(|(_, chat)| chat.title.as_deref().unwrap_or(chat.id.as_str())).collect();
+            anyhow::bail!("'{}' matches {} chats ({}); name one channel", channel, n, names.join(", "))
+        }
+    };
+
+    if format == "text" {
+        eprintln!("{}", "Fetching posts...".dimmed());
+    }
+    let now = Utc::now();
+    let posts = manager.channel_posts(&source, &chat.id, since).await?;
+    let id = GlobalChatId::from_chat_id(&source, &chat.id);
+
+    // Counts are only known as of now, so growth builds up from earlier runs
+    let mut history = SubscriberHistory::load()?;
+    if let Some(count) = posts.subscribers {
+        history.record(&id, SubscriberSample { at: now, count });
+        history.save()?;
+    }
+    let stats = channel_stats::compute(id.clone(), posts, history.samples(&id), since, now, period, top);
+
+    for (path, contents) in [
+        (&csv, channel_stats::periods_csv(&stats)),
+        (&json, serde_json::to_string_pretty(&stats)?),
+    ] {
+        if let Some(path) = path {
+            std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+            if format == "text" {
+                eprintln!("{}", format!("Wrote {}", path.display()).green());
+            }
+        }
+    }
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&stats)?),
+        "csv" => print!("{}", channel_stats::periods_csv(&stats)),
+        _ => print_text(&stats),
+    }
+
+    Ok(())
+}
+
+fn print_text(stats: &ChannelStats) {
+    let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_else(|| "-".to_string());
+
+    println!(
+        "{} ({})  since {}",
+        stats.title.as_deref().unwrap_or("Channel").bold(),
+        stats.chat,
+        stats.since.format("%Y-%m-%d")
+    );
+    let change = match stats.subscriber_change {
+        Some(change) => format!(" ({:+})", change),
+        None => String::new(),
+    };
+    println!("  {:<12} {}{}", "Subscribers", optional(stats.subscribers), change);
+    println!("  {:<12} {} ({:.1} a day)", "Posts", stats.posts, stats.posts_per_day);
+    match stats.total_views {
+        Some(total) => println!(
+            "  {:<12} {} total, {} average, {} median",
+            "Views",
+            total,
+            optional(stats.average_views),
+            optional(stats.median_views)
+        ),
+        None => println!("  {:<12} {}", "Views", "not counted by this source".dimmed()),
+    }
+
+    if !stats.top_posts.is_empty() {
+        println!();
+        println!("{}", "Top posts".bold());
+        for (rank, post) in stats.top_posts.iter().enumerate() {
+            println!(
+                "  {}. {}  {} views  {} interactions  {}",
+                rank + 1,
+                post.posted.format("%Y-%m-%d"),
+                optional(post.views).cyan(),
+                post.interactions(),
+                post.preview().dimmed()
+            );
+        }
+    }
+
+    println!();
+    println!("{}", format!("Per {}", stats.period).bold());
+    for period in &stats.periods {
+        println!(
+            "  {}  {:>4} posts  {:>9} views  {:>7} average  {:>8} subscribers",
+            period.start,
+            period.posts,
+            optional(period.views),
+            optional(period.average_views),
+            optional(period.subscribers)
+        );
+    }
+}
@@ -1,63 +1,33 @@
 use anyhow::Result;
 use colored::Colorize;
 
-use chat::{ChatFilter, ChatType, SourcesManager};
+use chat::commands::{self, chats::{ChatEntry, ChatQuery, UnknownSource}};
+use chat::{Chat, ChatType};
 
 pub async fn execute(
     source: String,
     name: Option<String>,
     chat_type: Option<String>,
+    label: Option<String>,
     format: String,
 ) -> Result<()> {
     println!("{}", format!("Listing chats from source '{}'...", source).dimmed());
 
-    // Create sources manager
-    let manager = SourcesManager::new();
+    let manager = super::create_manager()?;
+    let query = ChatQuery { name, chat_type, label };
 
-    // Check if source exists
-    if !manager.has_source(&source) {
-        println!();
-        println!("{}", format!("Source '{}' not found.", source).red());
-        println!();
-        println!("Available sources:");
-        let sources = manager.list_sources()?;
-        if sources.is_empty() {
-            println!("  {}", "No sources configured.".yellow());
-            println!();
-            println!("Run {} to configure Telegram", "chat telegram init".cyan());
-        } else {
-            for s in sources {
-                println!("  {} {}", "•".cyan(), s.id);
-            }
-        }
-        println!();
-        return Ok(());
-    }
-
-    // Build filter
-    let mut filter = ChatFilter::new();
-
-    if let Some(name_pattern) = name {
-        filter = filter.with_name(name_pattern);
-    }
-
-    if let Some(type_str) = chat_type {
-        let ct = match type_str.to_lowercase().as_str() {
-            "direct" | "dm" => ChatType::DirectMessage,
-            "group" => ChatType::Group,
-            "channel" => ChatType::Channel,
-            _ => {
-                println!("{}", format!("Invalid chat type '{}'. Expected: direct, group, channel", type_str).red());
+    let entries = match commands::chats::list(&manager, &source, query).await {
+        Ok(entries) => entries,
+        Err(e) => match e.downcast_ref::<UnknownSource>() {
+            Some(unknown) => {
+                print_unknown_source(unknown);
                 return Ok(());
             }
-        };
-        filter = filter.with_type(ct);
-    }
-
-    // List chats
-    let chats = manager.list_chats(&source, Some(filter)).await?;
+            None => return Err(e),
+        },
+    };
 
-    if chats.is_empty() {
+    if entries.is_empty() {
         println!();
         println!("{}", "No chats found.".yellow());
         return Ok(());
@@ -66,32 +36,33 @@ pub async fn execute(
     // Format output
     match format.as_str() {
         "json" => {
+            let chats: Vec<&Chat> = entries.iter().map(|entry| &entry.chat).collect();
             let json = serde_json::to_string_pretty(&chats)?;
             println!("{}", json);
         }
         "csv" => {
             println!("ID,Name,Type,Participants");
-            for chat in chats {
+            for ChatEntry { id, chat, .. } in &entries {
                 let name = chat.title.as_deref().unwrap_or("Unknown");
                 let chat_type = format!("{:?}", chat.chat_type);
                 let participants = chat.participant_count
                     .map(|c| c.to_string())
                     .unwrap_or_else(|| "N/A".to_string());
-                println!("{},{},{},{}", chat.id, name, chat_type, participants);
+                println!("{},{},{},{}", id, name, chat_type, participants);
             }
         }
         "compact" => {
-            for chat in chats {
+            for ChatEntry { id, chat, .. } in &entries {
                 let name = chat.title.as_deref().unwrap_or("Unknown");
-                println!("{} - {}", chat.id, name);
+                println!("{} - {}", id, name);
             }
         }
-        "text" | _ => {
+        _ => {
             println!();
-            println!("{} {} chats found:", chats.len(), source);
+            println!("{} {} chats found:", entries.len(), source);
             println!();
 
-            for chat in chats {
+            for ChatEntry { id, chat, labels } in &entries {
                 let name = chat.title.as_deref().unwrap_or("Unknown");
                 let type_str = match chat.chat_type {
                     ChatType::DirectMessage => "Direct".cyan(),
@@ -105,11 +76,15 @@ pub async fn execute(
                     name.bold(),
                     type_str
                 );
-                println!("    {}: {}", "ID".dimmed(), chat.id);
+                println!("    {}: {}", "ID".dimmed(), id);
 
                 if let Some(count) = chat.participant_count {
                     println!("    {}: {}", "Participants".dimmed(), count);
                 }
+
+                if !labels.is_empty() {
+                    println!("    {}: {}", "Labels".dimmed(), labels.join(", "));
+                }
             }
 
             println!();
@@ -118,3 +93,20 @@ pub async fn execute(
 
     Ok(())
 }
+
+fn print_unknown_source(unknown: &UnknownSource) {
+    println!();
+    println!("{}", format!("Source '{}' not found.", unknown.source).red());
+    println!();
+    println!("Available sources:");
+    if unknown.available.is_empty() {
+        println!("  {}", "No sources configured.".yellow());
+        println!();
+        println!("Run {} to configure Telegram", "chat telegram init".cyan());
+    } else {
+        for id in &unknown.available {
+            println!("  {} {}", "•".cyan(), id);
+        }
+    }
+    println!();
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::context::{ContextPacker, pack_context};
+use chat::{MessageFilter, NoteStore, filter_parser};
+
+pub async fn execute(
+    filter: String,
+    since: String,
+    query: Option<String>,
+    budget: usize,
+    format: String,
+) -> Result<()> {
+    // Parse source:pattern filter
+    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&filter)?;
+
+    // Create sources manager
+    let manager = super::create_manager()?;
+
+    let msg_filter = MessageFilter {
+        chat: chat_pattern,
+        since: Some(filter_parser::parse_time_spec(&since)?),
+        ..MessageFilter::new()
+    };
+    let packer = ContextPacker::new(budget).with_notes(NoteStore::load()?);
+
+    let packed = match query {
+        #[cfg(feature = "embeddings")]
+        Some(query) => {
+            use chat::context::ContextQuery;
+            use chat::embeddings::{DEFAULT_MODEL, EmbeddingIndex, OllamaEmbedder};
+
+            let embedder = OllamaEmbedder::from_env(DEFAULT_MODEL)?;
+            let mut index = EmbeddingIndex::load(DEFAULT_MODEL)?;
+            let context_query = ContextQuery {
+                text: &query,
+                embedder: &embedder,
+                index: &mut index,
+            };
+
+            let packed = pack_context(&manager, source_id.as_deref(), msg_filter, &packer, Some(context_query)).await?;
+            index.save()?;
+            packed
+        }
+        #[cfg(not(feature = "embeddings"))]
+        Some(_) => {
+            eprintln!("{}", "Note: --query requires the embeddings feature, packing recent messages only".yellow());
+            pack_context(&manager, source_id.as_deref(), msg_filter, &packer, None).await?
+        }
+        None => pack_context(&manager, source_id.as_deref(), msg_filter, &packer, None).await?,
+    };
+
+    match format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&packed)?;
+            println!("{}", json);
+        }
+        _ => {
+            print!("{}", packed.text);
+            eprintln!(
+                "{}",
+                format!(
+                    "{} messages, ~{}/{} tokens, {} omitted",
+                    packed.messages.len(),
+                    packed.tokens,
+                    packed.budget,
+                    packed.omitted
+                )
+                .dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
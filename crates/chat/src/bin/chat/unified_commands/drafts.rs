@@ -0,0 +1,64 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::HashMap;
+
+use chat::{ChatPattern, MessageContent, MessageFilter, filter_parser};
+
+pub async fn execute(source: String, chat: Option<String>, format: String) -> Result<()> {
+    // Create sources manager
+    let manager = super::create_manager()?;
+
+    let msg_filter = MessageFilter {
+        chat: match &chat {
+            Some(pattern) => filter_parser::parse_source_filter(&format!("{}:{}", source, pattern))?.1,
+            None => ChatPattern::All,
+        },
+        limit: None,
+        ..MessageFilter::new()
+    };
+
+    let messages = manager.pending_messages(&source, msg_filter).await?;
+
+    if messages.is_empty() {
+        println!("{}", "No drafts or scheduled messages.".yellow());
+        return Ok(());
+    }
+
+    match format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&messages)?;
+            println!("{}", json);
+        }
+        _ => {
+            let titles: HashMap<_, _> = manager
+                .list_chats(&source, None)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|chat| Some((chat.id, chat.title?)))
+                .collect();
+
+            for msg in &messages {
+                let title = titles.get(&msg.chat_id).cloned().unwrap_or_else(|| msg.chat_id.to_string());
+                let when = if msg.is_draft() {
+                    format!("draft, last edited {}", msg.timestamp.format("%Y-%m-%d %H:%M"))
+                } else {
+                    format!("scheduled for {}", msg.timestamp.format("%Y-%m-%d %H:%M"))
+                };
+
+                println!("{} {}", title.cyan().bold(), format!("({})", when).dimmed());
+                match &msg.content {
+                    MessageContent::Text(text) => {
+                        for line in text.lines() {
+                            println!("  {}", line);
+                        }
+                    }
+                    _ => println!("  {}", "[Non-text content]".dimmed()),
+                }
+                println!();
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::commands::{self, analysis::AnalysisQuery};
+
+pub async fn execute(
+    filter: String,
+    since: String,
+    window: String,
+    limit: Option<usize>,
+    format: String,
+) -> Result<()> {
+    println!("{}", "Looking for duplicate messages...".dimmed());
+
+    let manager = super::create_manager()?;
+    let query = AnalysisQuery { filter, since: Some(since), before: None, sender: None, limit };
+    let groups = commands::analysis::duplicates(&manager, &query, &window).await?;
+
+    if groups.is_empty() {
+        println!();
+        println!("{}", "No duplicate messages found.".green());
+        return Ok(());
+    }
+
+    match format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&groups)?;
+            println!("{}", json);
+        }
+        _ => {
+            println!();
+            println!("{} {} duplicate groups:", "Found".bold(), groups.len());
+
+            for group in groups {
+                println!();
+                println!("  {} {}",
+                    format!("{}x in {} chats", group.messages.len(), group.chat_count()).yellow().bold(),
+                    preview(&group.text).dimmed()
+                );
+                for occurrence in &group.messages {
+                    println!("    {} {} {}",
+                        occurrence.timestamp.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+                        occurrence.chat_id,
+                        occurrence.sender.as_deref().unwrap_or("")
+                    );
+                }
+            }
+
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// First line of the text, shortened for display
+fn preview(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("");
+    if line.chars().count() > 80 {
+        format!("{}…", line.chars().take(80).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
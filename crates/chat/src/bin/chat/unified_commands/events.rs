@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use chat::calendar::{self, FoundBy, ProposedEvent};
+use chat::{ChatPattern, GlobalChatId, MessageFilter, filter_parser};
+
+/// Messages searched per chat at most
+const MAX_MESSAGES: usize = 1000;
+
+pub async fn execute(
+    filters: Vec<String>,
+    since: String,
+    llm: bool,
+    model: Option<String>,
+    ics: Option<PathBuf>,
+    format: String,
+) -> Result<()> {
+    let since = filter_parser::parse_time_spec(&since)?;
+    let filters = if filters.is_empty() { vec!["*:*".to_string()] } else { filters };
+
+    let manager = super::create_manager()?;
+
+    // Chats matched by more than one filter are searched once, as are merged chats
+    let mut seen = HashSet::new();
+    let mut messages = Vec::new();
+    for filter in &filters {
+        let (source_id, chat_pattern) = filter_parser::parse_source_filter(filter)?;
+        for (source, chat) in chat::commands::chats::matching(&manager, source_id.as_deref(), &chat_pattern).await? {
+            let id = GlobalChatId::from_chat_id(&source, &manager.merged_into(&source, &chat.id)?);
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+
+            let msg_filter = MessageFilter {
+                chat: ChatPattern::Id(chat.id.clone()),
+                since: Some(since),
+                limit: Some(MAX_MESSAGES),
+                ..MessageFilter::new()
+            };
+            match manager.query_messages(Some(&source), msg_filter).await {
+                Ok(found) => messages.extend(found.into_iter().filter(|m| !m.pending).map(|m| (id.clone(), m))),
+                Err(e) => eprintln!("{}", format!("Skipping {}: {}", id, e).yellow()),
+            }
+        }
+    }
+    messages.sort_by_key(|(_, m)| m.timestamp);
+
+    let mut events: Vec<ProposedEvent> = messages.iter().filter_map(|(chat, m)| calendar::extract(chat, m)).collect();
+    if llm && !messages.is_empty() {
+        if format == "text" {
+            eprintln!("{}", "Asking the LLM...".dimmed());
+        }
+        let model = model.as_deref().unwrap_or(calendar::DEFAULT_EVENTS_MODEL);
+        let found = super::catchup::generate(&calendar::prompt(&messages, calendar::DEFAULT_BUDGET), model)
+            .await
+            .and_then(|reply| calendar::parse_reply(&reply, &messages));
+        match found {
+            Ok(found) => calendar::merge(&mut events, found),
+            Err(e) => eprintln!("{}", format!("LLM extraction unavailable ({}), showing rule matches only", e).yellow()),
+        }
+    }
+    events.sort_by_key(ProposedEvent::start);
+
+    if let Some(path) = &ics {
+        std::fs::write(path, calendar::to_ics(&events, Utc::now()))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        if format == "text" {
+            eprintln!("{}", format!("Wrote {} events to {}", events.len(), path.display()).green());
+        }
+    }
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&events)?),
+        "ics" => print!("{}", calendar::to_ics(&events, Utc::now())),
+        _ => {
+            if events.is_empty() {
+                println!("{}", "No events found.".dimmed());
+            }
+            for event in &events {
+                let when = match event.time {
+                    Some(time) => format!("{} {}", event.date.format("%a %Y-%m-%d"), time.format("%H:%M")),
+                    None => format!("{} all day", event.date.format("%a %Y-%m-%d")),
+                };
+                let by = if event.found_by == FoundBy::Llm { " (llm)" } else { "" };
+                println!(
+                    "{}  {}{}\n    {} in {}: {}",
+                    when.bold(),
+                    event.title,
+                    by.dimmed(),
+                    event.sender.cyan(),
+                    event.chat,
+                    event.text.replace('\n', " ").dimmed()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
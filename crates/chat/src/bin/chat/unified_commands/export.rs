@@ -0,0 +1,116 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use colored::Colorize;
+use std::path::PathBuf;
+
+use chat::anonymize::Anonymizer;
+use chat::cli::OutputFormat;
+use chat::commands;
+use chat::commands::export::{DatasetEvent, ExportRange};
+use chat::estimate::ExportEstimate;
+use chat::sync::default_archive_dir;
+use chat::filter_parser;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    filter: String,
+    output: PathBuf,
+    format: String,
+    since: Option<String>,
+    before: Option<String>,
+    limit: Option<usize>,
+    anonymize: bool,
+    jitter: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let output_format = OutputFormat::from_str(&format, true)
+        .ok()
+        .filter(|format| format.is_dataset())
+        .ok_or_else(|| anyhow::anyhow!("Unknown dataset format '{}'. Use sqlite or parquet", format))?;
+
+    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&filter)?;
+    let since = since.map(|s| filter_parser::parse_time_spec(&s)).transpose()?;
+    let before = before.map(|b| filter_parser::parse_time_spec(&b)).transpose()?;
+    let anonymizer = anonymizer(anonymize, jitter)?;
+
+    let manager = super::create_manager()?;
+    let chats = commands::chats::matching(&manager, source_id.as_deref(), &chat_pattern).await?;
+    if chats.is_empty() {
+        println!("{}", "No matching chats found.".yellow());
+        return Ok(());
+    }
+
+    if dry_run {
+        let archive_dir = default_archive_dir()?;
+        let page_size = commands::export_all::PAGE_SIZE;
+        let estimate = commands::export::estimate(&manager, &chats, &archive_dir, since, before, limit, page_size).await?;
+        print_estimate(&estimate, "not in the sync archive, and the source reports no count");
+        return Ok(());
+    }
+
+    let writer = commands::export::create_writer(output_format, &output)?;
+    let range = ExportRange { since, before, limit };
+    let total = commands::export::export_dataset(&manager, &chats, &range, anonymizer.as_ref(), writer, |event| match event {
+        DatasetEvent::Written { chat, messages } => println!(
+            "  {} {}",
+            chat.title.as_deref().unwrap_or(chat.id.as_str()),
+            format!("({} messages)", messages).dimmed()
+        ),
+        DatasetEvent::Skipped { source, chat, error } => {
+            eprintln!("{}", format!("Skipping {}:{}: {}", source, chat.id, error).yellow())
+        }
+    })
+    .await?;
+
+    println!();
+    println!(
+        "{}",
+        format!("Exported {} messages from {} chats to {}", total, chats.len(), output.display()).green()
+    );
+    if let Some(anonymizer) = &anonymizer {
+        let mapping = anonymizer.save_mapping()?;
+        println!("  {}: {}", "Pseudonym mapping (keep it private)".dimmed(), mapping.display());
+    }
+    Ok(())
+}
+
+/// The anonymizer for `--anonymize`, with its optional `--jitter`
+pub fn anonymizer(anonymize: bool, jitter: Option<String>) -> Result<Option<Anonymizer>> {
+    if !anonymize {
+        return Ok(None);
+    }
+    let jitter = jitter
+        .map(|jitter| {
+            let jitter = humantime::parse_duration(&jitter)
+                .map_err(|e| anyhow::anyhow!("Invalid jitter '{}': {}", jitter, e))?;
+            Ok::<_, anyhow::Error>(chrono::Duration::from_std(jitter)?)
+        })
+        .transpose()?;
+    Ok(Some(Anonymizer::load(jitter)?))
+}
+
+/// Print the outcome of a `--dry-run`, saying why some chats' sizes are unknown
+pub fn print_estimate(estimate: &ExportEstimate, unknown_because: &str) {
+    println!("{}", "Dry run - no messages will be fetched".bold());
+    println!("  {}: {}", "Chats matched".dimmed(), estimate.chats);
+    if estimate.unknown_chats == 0 {
+        println!("  {}: {}", "Estimated messages".dimmed(), estimate.messages);
+    } else if estimate.unknown_chats < estimate.chats {
+        println!(
+            "  {}: {} (excluding the {} chats of unknown size)",
+            "Estimated messages".dimmed(),
+            estimate.messages,
+            estimate.unknown_chats
+        );
+    }
+    if estimate.unknown_chats > 0 {
+        println!(
+            "  {}: {} ({}, counted as one request each)",
+            "Chats of unknown size".dimmed(),
+            estimate.unknown_chats,
+            unknown_because
+        );
+    }
+    println!("  {}: {}", "Estimated API calls".dimmed(), estimate.api_calls);
+    println!("  {}: {}", "Expected rate-limit delay".dimmed(), humantime::format_duration(estimate.delay));
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use colored::Colorize;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use chat::cli::OutputFormat;
+use chat::commands::export::ExportRange;
+use chat::commands::export_all::{self, ExportAllEvent, ExportAllOptions, ExportManifest};
+use chat::{Progress, filter_parser};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    filter: String,
+    output_dir: PathBuf,
+    format: String,
+    since: Option<String>,
+    before: Option<String>,
+    limit: Option<usize>,
+    concurrency: usize,
+    rate: f64,
+    resume: bool,
+    anonymize: bool,
+    jitter: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let output_format = OutputFormat::from_str(&format, true)
+        .map_err(|_| anyhow::anyhow!("Unknown format '{}'. Use json, csv, text or compact", format))?;
+    if output_format.is_dataset() {
+        anyhow::bail!("'{}' writes all chats into one file; use `chat export --format {}` instead", format, format);
+    }
+
+    // Parse source:pattern filter
+    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&filter)?;
+
+    let range = ExportRange {
+        since: since.map(|s| filter_parser::parse_time_spec(&s)).transpose()?,
+        before: before.map(|b| filter_parser::parse_time_spec(&b)).transpose()?,
+        limit,
+    };
+    let anonymizer = super::export::anonymizer(anonymize, jitter)?.map(Rc::new);
+
+    // Create sources manager
+    let manager = super::create_manager()?;
+
+    let manifest_path = ExportManifest::path(&output_dir);
+    let previous = if resume { ExportManifest::load(&manifest_path)? } else { None };
+
+    let targets = export_all::targets(&manager, source_id.as_deref(), &chat_pattern, anonymizer.as_deref()).await?;
+    if targets.is_empty() {
+        println!("{}", "No matching chats found.".yellow());
+        return Ok(());
+    }
+
+    let resuming = previous.is_some();
+    let plan = export_all::plan(targets, previous, output_format);
+    if resuming {
+        println!("{}", format!("Resuming: {} chats already complete", plan.carried.len()).dimmed());
+    }
+
+    if dry_run {
+        let archive_dir = chat::sync::default_archive_dir()?;
+        let estimate = export_all::estimate(&manager, &plan.targets, &archive_dir, &range, rate).await?;
+        super::export::print_estimate(&estimate, "not in the sync archive, and the source reports no count");
+        return Ok(());
+    }
+
+    println!("{}", format!("Exporting {} chats to {}...", plan.targets.len(), output_dir.display()).bold());
+
+    // First Ctrl+C lets running exports finish their current page
+    let cancel = Progress::new();
+    cancel.cancel_on_ctrl_c();
+
+    let bars = MultiProgress::new();
+    let overall = bars.add(ProgressBar::new(plan.targets.len() as u64));
+    overall.set_style(
+        ProgressStyle::with_template("{prefix:>12.bold} [{bar:30}] {pos}/{len} chats ({elapsed})")?
+            .progress_chars("=> "),
+    );
+    overall.set_prefix("Exporting");
+
+    let chat_style = ProgressStyle::with_template("{spinner} {prefix:30!} {pos:>7} messages {msg}")?;
+    let chat_bars: RefCell<HashMap<usize, ProgressBar>> = RefCell::new(HashMap::new());
+    let progress = overall.clone();
+    let on_event = move |event: ExportAllEvent<'_>| match event {
+        ExportAllEvent::Started { index, target } => {
+            let bar = bars.insert_before(&progress, ProgressBar::new_spinner());
+            bar.set_style(chat_style.clone());
+            bar.set_prefix(target.label());
+            bar.enable_steady_tick(std::time::Duration::from_millis(120));
+            chat_bars.borrow_mut().insert(index, bar);
+        }
+        ExportAllEvent::Fetching { index, count } => {
+            if let Some(bar) = chat_bars.borrow().get(&index) {
+                bar.set_message(format!("fetching page ({})", count));
+            }
+        }
+        ExportAllEvent::Fetched { index, new } => {
+            if let Some(bar) = chat_bars.borrow().get(&index) {
+                bar.inc(new as u64);
+                bar.set_message("");
+            }
+        }
+        ExportAllEvent::Finished { index, entry } => {
+            if let Some(bar) = chat_bars.borrow_mut().remove(&index) {
+                match &entry.error {
+                    Some(error) => bar.abandon_with_message(error.red().to_string()),
+                    None if entry.complete => bar.finish_with_message("done".green().to_string()),
+                    None => bar.abandon_with_message("interrupted".yellow().to_string()),
+                }
+            }
+            progress.inc(1);
+        }
+    };
+
+    let options = ExportAllOptions {
+        filter,
+        output_dir,
+        format: output_format,
+        range,
+        concurrency,
+        rate,
+    };
+    let manifest = export_all::export_all(&manager, plan, &options, anonymizer.clone(), &cancel, on_event).await?;
+    overall.finish();
+
+    let mapping = anonymizer.map(|anonymizer| anonymizer.save_mapping()).transpose()?;
+
+    println!();
+    println!(
+        "{}",
+        format!("Exported {} messages from {} chats", manifest.total_messages(), manifest.exported()).green()
+    );
+    if manifest.failed() > 0 {
+        println!("{}", format!("{} chats failed, see manifest for details", manifest.failed()).red());
+    }
+    if manifest.cancelled {
+        println!(
+            "{}",
+            format!("Interrupted with {} chats incomplete. Re-run with --resume to continue", manifest.incomplete())
+                .yellow()
+        );
+    }
+    println!("  {}: {}", "Manifest".dimmed(), manifest_path.display());
+    if let Some(mapping) = mapping {
+        println!("  {}: {}", "Pseudonym mapping (keep it private)".dimmed(), mapping.display());
+    }
+
+    Ok(())
+}
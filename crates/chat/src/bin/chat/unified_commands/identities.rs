@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use std::path::PathBuf;
+
+use chat::IdentityStore;
+use chat::identities::{ImportReport, Link};
+
+#[derive(Subcommand)]
+pub enum IdentityAction {
+    /// Link accounts to people from a CSV file with name, platform and id columns
+    Import {
+        /// CSV file with a header row naming its name, platform and id columns
+        file: PathBuf,
+
+        /// Move accounts already linked to someone else to the person in the file
+        #[arg(long)]
+        force: bool,
+
+        /// Report what would change without saving
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// List people and their accounts
+    List {
+        /// Output format (text, json, csv)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Link, unlink, rename or remove a person
+    Edit {
+        /// Person's name
+        name: String,
+
+        /// Link an account (format: platform:id, e.g. "telegram:12345"); repeatable
+        #[arg(long, value_name = "PLATFORM:ID")]
+        link: Vec<String>,
+
+        /// Unlink an account (format: platform:id); repeatable
+        #[arg(long, value_name = "PLATFORM:ID")]
+        unlink: Vec<String>,
+
+        /// New name; merges with the person of that name if there is one
+        #[arg(long)]
+        rename: Option<String>,
+
+        /// Remove the person and all their links
+        #[arg(long, conflicts_with_all = ["link", "unlink", "rename"])]
+        remove: bool,
+
+        /// Move accounts linked to someone else
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+pub async fn execute(action: IdentityAction) -> Result<()> {
+    let mut store = IdentityStore::load()?;
+
+    match action {
+        IdentityAction::Import { file, force, dry_run, format } => {
+            let text = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let report = store.import_csv(&text, force)?;
+            if !dry_run {
+                store.save()?;
+            }
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_report(&report, dry_run);
+            }
+        }
+        IdentityAction::List { format } => match format.as_str() {
+            "json" => {
+                let people: Vec<_> = store
+                    .people()
+                    .map(|(name, accounts)| serde_json::json!({ "name": name, "accounts": accounts }))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&people)?);
+            }
+            "csv" => print!("{}", store.to_csv()),
+            _ => {
+                if store.people().next().is_none() {
+                    println!("{}", "No identities.".yellow());
+                    println!();
+                    println!("Use {} or {} to add some",
+                        "chat identities import <file.csv>".cyan(),
+                        "chat identities edit <name> --link <platform:id>".cyan()
+                    );
+                    return Ok(());
+                }
+
+                println!();
+                println!("{}", "Identities:".bold());
+                println!();
+
+                for (name, accounts) in store.people() {
+                    println!("  {} {}", "•".cyan(), name.bold());
+                    for account in accounts {
+                        println!("      {}", account);
+                    }
+                }
+
+                println!();
+            }
+        },
+        IdentityAction::Edit { name, link, unlink, rename, remove, force } => {
+            if remove {
+                match store.remove(&name) {
+                    Some(accounts) => {
+                        store.save()?;
+                        println!("{}", format!("Removed {} and {} linked accounts", name, accounts.len()).green());
+                    }
+                    None => println!("{}", format!("No identity named '{}'", name).yellow()),
+                }
+                return Ok(());
+            }
+
+            // Nothing is saved unless every change applies
+            let link = parse_accounts(&link)?;
+            let unlink = parse_accounts(&unlink)?;
+
+            for account in &unlink {
+                if !store.accounts(&name).is_some_and(|accounts| accounts.contains(account)) {
+                    anyhow::bail!("{} is not linked to {}", account, name);
+                }
+                store.unlink(account);
+                println!("{}", format!("Unlinked {}", account).green());
+            }
+
+            for account in &link {
+                match store.link(&name, account, force) {
+                    Link::Added => println!("{}", format!("Linked {} to {}", account, name).green()),
+                    Link::Unchanged => println!("{}", format!("{} is already linked to {}", account, name).yellow()),
+                    Link::Moved(from) => println!("{}", format!("Moved {} from {} to {}", account, from, name).green()),
+                    Link::Conflict(owner) => anyhow::bail!(
+                        "{} is linked to {}; use --force to move it to {}",
+                        account, owner, name
+                    ),
+                }
+            }
+
+            if let Some(new_name) = rename {
+                if !store.rename(&name, &new_name) {
+                    anyhow::bail!("No identity named '{}' with linked accounts", name);
+                }
+                println!("{}", format!("Renamed {} to {}", name, new_name.trim()).green());
+            }
+
+            store.save()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_accounts(accounts: &[String]) -> Result<Vec<String>> {
+    accounts
+        .iter()
+        .map(|account| IdentityStore::parse_account(account).map_err(|e| anyhow::anyhow!(e)))
+        .collect()
+}
+
+fn print_report(report: &ImportReport, dry_run: bool) {
+    let prefix = if dry_run { "Would link" } else { "Linked" };
+    println!("{}", format!("{} {} accounts ({} already linked)", prefix, report.added, report.unchanged).green());
+    if report.moved > 0 {
+        println!("{}", format!("Moved {} accounts from someone else", report.moved).green());
+    }
+
+    if !report.conflicts.is_empty() {
+        println!();
+        println!("{}", format!("{} conflicts, skipped (use --force to move them):", report.conflicts.len()).yellow());
+        for conflict in &report.conflicts {
+            println!("  line {}: {} is linked to {}, not {}",
+                conflict.line, conflict.account.cyan(), conflict.linked_to, conflict.wanted
+            );
+        }
+    }
+
+    if !report.invalid.is_empty() {
+        println!();
+        println!("{}", format!("{} invalid rows, skipped:", report.invalid.len()).red());
+        for row in &report.invalid {
+            println!("  line {}: {}", row.line, row.reason);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+
+use chat::{GlobalChatId, LabelStore};
+
+#[derive(Subcommand)]
+pub enum LabelAction {
+    /// Assign a label to a chat
+    Add {
+        /// Source ID (telegram, signal, whatsapp)
+        source: String,
+        /// Chat ID (any known form, e.g. -100123 or channel:123)
+        chat_id: String,
+        /// Label name (e.g., work, family, noisy)
+        label: String,
+    },
+    /// Remove a label from a chat
+    Remove {
+        /// Source ID (telegram, signal, whatsapp)
+        source: String,
+        /// Chat ID
+        chat_id: String,
+        /// Label name
+        label: String,
+    },
+    /// List labelled chats
+    List {
+        /// Only show chats with this label
+        #[arg(long)]
+        label: Option<String>,
+    },
+}
+
+pub async fn execute(action: LabelAction) -> Result<()> {
+    let mut store = LabelStore::load()?;
+
+    match action {
+        LabelAction::Add { source, chat_id, label } => {
+            let id = GlobalChatId::new(source.as_str(), &chat_id);
+            if store.add(&id.source, &id.chat_id(), &label) {
+                store.save()?;
+                println!("{}", format!("Labelled {} as '{}'", id, label).green());
+            } else {
+                println!("{}", format!("{} already has label '{}'", id, label).yellow());
+            }
+        }
+        LabelAction::Remove { source, chat_id, label } => {
+            let id = GlobalChatId::new(source.as_str(), &chat_id);
+            if store.remove(&id.source, &id.chat_id(), &label) {
+                store.save()?;
+                println!("{}", format!("Removed label '{}' from {}", label, id).green());
+            } else {
+                println!("{}", format!("{} does not have label '{}'", id, label).yellow());
+            }
+        }
+        LabelAction::List { label } => {
+            let entries: Vec<_> = store
+                .entries()
+                .into_iter()
+                .filter(|(_, _, labels)| {
+                    label
+                        .as_ref()
+                        .map(|l| labels.contains(&l.to_lowercase()))
+                        .unwrap_or(true)
+                })
+                .collect();
+
+            if entries.is_empty() {
+                println!("{}", "No labelled chats.".yellow());
+                println!();
+                println!("Use {} to label a chat", "chat labels add <source> <chat_id> <label>".cyan());
+                return Ok(());
+            }
+
+            println!();
+            println!("{}", "Labelled Chats:".bold());
+            println!();
+
+            for (source, chat_id, labels) in entries {
+                println!("  {} {} [{}]",
+                    "•".cyan(),
+                    GlobalChatId::from_chat_id(&source, &chat_id),
+                    labels.join(", ").green()
+                );
+            }
+
+            println!();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use colored::Colorize;
+
+#[cfg(feature = "link-preview")]
+use chat::links;
+use chat::commands::{self, analysis::AnalysisQuery};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    filter: String,
+    since: Option<String>,
+    before: Option<String>,
+    sender: Option<String>,
+    limit: Option<usize>,
+    fetch_titles: bool,
+    concurrency: usize,
+    format: String,
+) -> Result<()> {
+    println!("{}", "Collecting links...".dimmed());
+
+    let manager = super::create_manager()?;
+    let query = AnalysisQuery { filter, since, before, sender, limit };
+    #[allow(unused_mut)]
+    let mut entries = commands::analysis::links(&manager, &query).await?;
+
+    if entries.is_empty() {
+        println!();
+        println!("{}", "No links found.".yellow());
+        return Ok(());
+    }
+
+    if fetch_titles {
+        #[cfg(feature = "link-preview")]
+        {
+            println!("{}", format!("Fetching titles for {} links...", entries.len()).dimmed());
+            links::fetch_previews(&mut entries, concurrency).await?;
+        }
+
+        #[cfg(not(feature = "link-preview"))]
+        {
+            let _ = concurrency;
+            println!("{}", "Note: title fetching requires the link-preview feature".yellow());
+            println!("  Build with: cargo build --features link-preview");
+        }
+    }
+
+    // Format output
+    match format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&entries)?;
+            println!("{}", json);
+        }
+        "csv" => {
+            println!("URL,Chat ID,Sender,First Seen,Count,Title");
+            for entry in entries {
+                let title = entry.title.as_deref().unwrap_or("").replace(',', ";");
+                println!("{},{},{},{},{},{}",
+                    entry.url,
+                    entry.chat_id,
+                    entry.sender.as_deref().unwrap_or(""),
+                    entry.first_seen.to_rfc3339(),
+                    entry.count,
+                    title
+                );
+            }
+        }
+        _ => {
+            println!();
+            println!("{} {} unique links:", "Found".bold(), entries.len());
+            println!();
+
+            for entry in entries {
+                let shared = if entry.count > 1 {
+                    format!(" (shared {}x)", entry.count)
+                } else {
+                    String::new()
+                };
+
+                println!("  {} {}{}", "•".cyan(), entry.url.underline(), shared.dimmed());
+                if let Some(title) = &entry.title {
+                    println!("    {}", title.bold());
+                }
+                if let Some(description) = &entry.description {
+                    println!("    {}", description.dimmed());
+                }
+                println!("    {} {} in {}",
+                    entry.first_seen.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+                    entry.sender.as_deref().unwrap_or("Unknown"),
+                    entry.chat_id
+                );
+            }
+
+            println!();
+        }
+    }
+
+    Ok(())
+}
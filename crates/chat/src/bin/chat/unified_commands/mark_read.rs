@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::HashSet;
+
+use chat::{ChatPattern, GlobalChatId, MessageFilter, MessageId, ReadCursors, filter_parser};
+
+/// Messages fetched per chat at most
+const MAX_UNREAD: usize = 5000;
+
+pub async fn execute(chat: String, through: Option<String>, since: String, format: String) -> Result<()> {
+    let default_since = filter_parser::parse_time_spec(&since)?;
+    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&chat)?;
+
+    let manager = super::create_manager()?;
+    let mut chats = chat::commands::chats::matching(&manager, source_id.as_deref(), &chat_pattern).await?;
+    if chats.is_empty() {
+        anyhow::bail!("No chat matches '{}'", chat);
+    }
+    if through.is_some() && chats.len() > 1 {
+        anyhow::bail!("--through needs exactly one chat, but '{}' matches {}", chat, chats.len());
+    }
+    let through = through.map(MessageId::new);
+
+    let mut cursors = ReadCursors::load()?;
+    let mut seen = HashSet::new();
+    let mut marked = Vec::new();
+    for (source, chat) in chats.drain(..) {
+        let id = GlobalChatId::from_chat_id(&source, &manager.merged_into(&source, &chat.id)?);
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+
+        let since = cursors.cursor(&id).map(|c| c.timestamp).unwrap_or(default_since);
+        let filter = MessageFilter {
+            chat: ChatPattern::Id(chat.id.clone()),
+            since: Some(since),
+            limit: Some(MAX_UNREAD),
+            ..MessageFilter::new()
+        };
+        let mut messages: Vec<_> = manager
+            .query_messages(Some(&source), filter)
+            .await?
+            .into_iter()
+            .filter(|m| !m.pending && cursors.is_unread(&id, m))
+            .collect();
+
+        // Only as far as the given message, keeping what came after it unread
+        if let Some(through) = &through {
+            let until = messages
+                .iter()
+                .find(|m| &m.id == through)
+                .map(|m| m.timestamp)
+                .with_context(|| format!("Message {} is not among the unread messages of {}", through, id))?;
+            messages.retain(|m| m.timestamp <= until);
+        }
+
+        let count = messages.len();
+        if cursors.mark_read(id.clone(), &messages) {
+            marked.push((id, count));
+        }
+    }
+    cursors.save()?;
+
+    match format.as_str() {
+        "json" => {
+            let output: Vec<_> = marked
+                .iter()
+                .map(|(id, count)| serde_json::json!({ "chat": id.to_string(), "marked": count }))
+                .collect();
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        _ => {
+            if marked.is_empty() {
+                println!("{}", "Nothing unread.".yellow());
+            }
+            for (id, count) in &marked {
+                println!("{}", format!("Marked {} messages read in {}", count, id).green());
+            }
+        }
+    }
+
+    Ok(())
+}
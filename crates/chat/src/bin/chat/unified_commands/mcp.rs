@@ -0,0 +1,307 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use serde_json::{Value, json};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chat::commands;
+use chat::mcp_selftest::{McpClient, RecordedCall, parse_argument};
+
+#[derive(Subcommand)]
+pub enum McpAction {
+    /// Start the MCP server and check that it answers correctly
+    Selftest {
+        /// Path to the chat-mcp-server binary (default: next to this binary, then PATH)
+        #[arg(long)]
+        server: Option<PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Call the MCP server's tools by hand: list them, then invoke them with prompted
+    /// arguments and pretty-printed results
+    Client {
+        /// Path to the chat-mcp-server binary (default: next to this binary, then PATH)
+        #[arg(long, conflicts_with = "connect")]
+        server: Option<PathBuf>,
+
+        /// Connect to a server listening on TCP (e.g. the dashboard's, 127.0.0.1:7411)
+        #[arg(long, value_name = "ADDR")]
+        connect: Option<String>,
+
+        /// Run the calls saved in FILE instead of prompting
+        #[arg(long, value_name = "FILE")]
+        script: Option<PathBuf>,
+
+        /// Append every call made to FILE, to replay later with --script
+        #[arg(long, value_name = "FILE")]
+        save: Option<PathBuf>,
+
+        /// Seconds to wait for each response
+        #[arg(long, default_value = "60")]
+        timeout: u64,
+    },
+}
+
+pub async fn execute(action: McpAction) -> Result<()> {
+    match action {
+        McpAction::Selftest { server, format } => selftest(server, &format),
+        McpAction::Client { server, connect, script, save, timeout } => {
+            client(server, connect, script, save, Duration::from_secs(timeout))
+        }
+    }
+}
+
+fn selftest(server: Option<PathBuf>, format: &str) -> Result<()> {
+    let report = commands::mcp::selftest(server)?;
+    let checks = &report.checks;
+    let failed = report.failed();
+
+    match format {
+        "json" => {
+            let json: Vec<_> = checks
+                .iter()
+                .map(|c| serde_json::json!({ "name": c.name, "passed": c.passed, "detail": c.detail }))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        _ => {
+            println!("{} {}", "Testing".bold(), report.server.display());
+            for check in checks {
+                let mark = if check.passed { "✓".green() } else { "✗".red() };
+                println!("  {} {} {}", mark, check.name, check.detail.dimmed());
+            }
+            println!();
+
+            if failed == 0 {
+                println!("{}", format!("All {} checks passed", checks.len()).green());
+            } else {
+                println!("{}", format!("{} of {} checks failed", failed, checks.len()).red().bold());
+            }
+        }
+    }
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn client(
+    server: Option<PathBuf>,
+    connect: Option<String>,
+    script: Option<PathBuf>,
+    save: Option<PathBuf>,
+    timeout: Duration,
+) -> Result<()> {
+    let client = match &connect {
+        Some(addr) => McpClient::connect(addr)?,
+        None => commands::mcp::spawn_server(&server.unwrap_or_else(commands::mcp::default_server_path))?,
+    };
+    let mut session = Session { client: client.with_timeout(timeout), tools: Vec::new(), save };
+
+    let info = session.client.initialize("chat-mcp-client")?;
+    eprintln!(
+        "{} {} {}",
+        "Connected to".dimmed(),
+        info["serverInfo"]["name"].as_str().unwrap_or("MCP server").bold(),
+        format!("(protocol {})", info["protocolVersion"].as_str().unwrap_or("?")).dimmed()
+    );
+    session.tools = session.client.list_tools()?;
+
+    match script {
+        Some(path) => session.run_script(&path),
+        None => {
+            session.print_tools();
+            eprintln!("{}", "Type a tool name to call it, or 'help'.".dimmed());
+            session.repl()
+        }
+    }
+}
+
+struct Session {
+    client: McpClient,
+    tools: Vec<Value>,
+    save: Option<PathBuf>,
+}
+
+impl Session {
+    fn repl(&mut self) -> Result<()> {
+        loop {
+            let Some(line) = prompt("mcp> ")? else {
+                return Ok(());
+            };
+            let (command, rest) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+            let rest = rest.trim();
+
+            let result = match command {
+                "" => Ok(()),
+                "quit" | "exit" => return Ok(()),
+                "help" => {
+                    print_help();
+                    Ok(())
+                }
+                "tools" => {
+                    self.print_tools();
+                    Ok(())
+                }
+                "describe" => self.describe(rest),
+                "call" => {
+                    let (tool, arguments) = rest.split_once(' ').unwrap_or((rest, ""));
+                    self.call(tool, arguments.trim())
+                }
+                "raw" => {
+                    let (method, params) = rest.split_once(' ').unwrap_or((rest, ""));
+                    self.raw(method, params.trim())
+                }
+                tool => self.call(tool, rest),
+            };
+            if let Err(e) = result {
+                println!("{}", format!("{:#}", e).red());
+            }
+        }
+    }
+
+    fn run_script(&mut self, path: &Path) -> Result<()> {
+        for call in commands::mcp::load_script(path)? {
+            println!("{} {} {}", "→".cyan(), call.tool.bold(), call.arguments.to_string().dimmed());
+            self.invoke(&call)?;
+        }
+        Ok(())
+    }
+
+    fn tool(&self, name: &str) -> Result<&Value> {
+        commands::mcp::find_tool(&self.tools, name)
+    }
+
+    fn print_tools(&self) {
+        println!("{}", "Tools:".bold());
+        for tool in &self.tools {
+            println!(
+                "  {} {}",
+                tool["name"].as_str().unwrap_or_default().cyan(),
+                tool["description"].as_str().unwrap_or_default().dimmed()
+            );
+        }
+    }
+
+    fn describe(&self, name: &str) -> Result<()> {
+        let tool = self.tool(name)?;
+        println!("{}", name.bold());
+        if let Some(description) = tool["description"].as_str() {
+            println!("  {}", description);
+        }
+        for argument in commands::mcp::arguments(&tool["inputSchema"]) {
+            println!("  {}", argument.describe());
+        }
+        Ok(())
+    }
+
+    /// Call a tool with the given JSON arguments, or prompt for each argument
+    fn call(&mut self, name: &str, arguments: &str) -> Result<()> {
+        let schema = self.tool(name)?["inputSchema"].clone();
+        let arguments = if arguments.is_empty() {
+            match prompt_arguments(&schema)? {
+                Some(arguments) => arguments,
+                None => return Ok(()),
+            }
+        } else {
+            serde_json::from_str(arguments).context("Arguments must be a JSON object")?
+        };
+
+        let call = RecordedCall { tool: name.to_string(), arguments };
+        self.invoke(&call)?;
+        self.record(&call)
+    }
+
+    fn invoke(&mut self, call: &RecordedCall) -> Result<()> {
+        let result = commands::mcp::call_tool(&mut self.client, call)?;
+        if result.is_error {
+            println!("{}", result.text.red());
+        } else {
+            println!("{}", result.text);
+        }
+        println!("{}", format!("({} ms)", result.elapsed.as_millis()).dimmed());
+        Ok(())
+    }
+
+    /// Send any JSON-RPC request, e.g. `raw resources/list`
+    fn raw(&mut self, method: &str, params: &str) -> Result<()> {
+        if method.is_empty() {
+            anyhow::bail!("Usage: raw METHOD [PARAMS_JSON]");
+        }
+        let params = if params.is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(params).context("Params must be JSON")?
+        };
+        let response = self.client.request(method, params)?;
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        Ok(())
+    }
+
+    fn record(&self, call: &RecordedCall) -> Result<()> {
+        match &self.save {
+            Some(path) => commands::mcp::save_call(path, call),
+            None => Ok(()),
+        }
+    }
+}
+
+fn print_help() {
+    println!("  tools               list tools");
+    println!("  describe TOOL       show a tool's arguments");
+    println!("  TOOL                call a tool, prompting for each argument");
+    println!("  TOOL {{...}}          call a tool with JSON arguments");
+    println!("  raw METHOD [JSON]   send any JSON-RPC request");
+    println!("  quit                leave");
+}
+
+/// Ask for every argument in a tool's input schema. Blank answers skip optional
+/// arguments; a blank required argument cancels the call (None).
+fn prompt_arguments(schema: &Value) -> Result<Option<Value>> {
+    let mut arguments = serde_json::Map::new();
+
+    for argument in commands::mcp::arguments(schema) {
+        let (field, property, required) = (argument.name, argument.property, argument.required);
+        println!("  {}", argument.describe().dimmed());
+        loop {
+            let Some(input) = prompt(&format!("  {}{}: ", field, if required { "*" } else { "" }))? else {
+                return Ok(None);
+            };
+            if input.is_empty() {
+                if required {
+                    println!("  {}", "Required; call cancelled".yellow());
+                    return Ok(None);
+                }
+                break;
+            }
+            match parse_argument(property, &input) {
+                Ok(value) => {
+                    arguments.insert(field.to_string(), value);
+                    break;
+                }
+                Err(e) => println!("  {}", e.to_string().red()),
+            }
+        }
+    }
+
+    Ok(Some(Value::Object(arguments)))
+}
+
+/// Read one trimmed line, None at end of input
+fn prompt(text: &str) -> Result<Option<String>> {
+    print!("{}", text);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
@@ -0,0 +1,267 @@
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+use std::path::PathBuf;
+
+use chat::commands;
+use chat::commands::media::{MediaEvent, MediaFilter, MediaPipeline};
+use chat::forget::ForgetLog;
+use chat::media_text::{MediaProcessor, MediaTextStore};
+use chat::{BufferConfig, SourcesManager, filter_parser};
+
+#[derive(Subcommand)]
+pub enum MediaAction {
+    /// Extract text from attachments (image OCR and captions, voice note transcripts, documents) so search and summaries see it
+    Process {
+        /// Source and chat filter (format: source:pattern, e.g., "telegram:*", "telegram:#work")
+        #[arg(default_value = "*:*")]
+        filter: String,
+
+        /// Process attachments newer than this (e.g., "7d", "2025-01-15")
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Read text from images with tesseract (requires the 'ocr' feature)
+        #[arg(long)]
+        ocr: bool,
+
+        /// Tesseract languages, e.g. "eng+fin"
+        #[arg(long)]
+        ocr_lang: Option<String>,
+
+        /// Describe images with an Ollama vision model (requires the 'vision' feature)
+        #[arg(long)]
+        caption: bool,
+
+        /// Vision model for captions (default: llava)
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Transcribe audio and voice notes with a local Whisper model (requires the 'whisper' feature)
+        #[arg(long)]
+        transcribe: bool,
+
+        /// Path of the whisper.cpp ggml model file
+        #[arg(long, env = "WHISPER_MODEL")]
+        whisper_model: Option<PathBuf>,
+
+        /// Spoken language for transcripts, e.g. "en"; detected when not given
+        #[arg(long)]
+        whisper_lang: Option<String>,
+
+        /// Extract the text of PDF, DOCX and plain text attachments (requires the 'documents' feature)
+        #[arg(long)]
+        documents: bool,
+
+        /// Process attachments again even if they already have derived text
+        #[arg(long)]
+        force: bool,
+
+        /// Keep running and process attachments of new messages as they arrive
+        #[arg(long)]
+        follow: bool,
+    },
+    /// Show derived text for the attachments of matching chats
+    Show {
+        /// Source and chat filter (format: source:pattern)
+        #[arg(default_value = "*:*")]
+        filter: String,
+
+        /// Show attachments newer than this
+        #[arg(long, default_value = "7d")]
+        since: String,
+    },
+}
+
+pub async fn execute(action: MediaAction) -> Result<()> {
+    match action {
+        MediaAction::Process {
+            filter,
+            since,
+            ocr,
+            ocr_lang,
+            caption,
+            model,
+            transcribe,
+            whisper_model,
+            whisper_lang,
+            documents,
+            force,
+            follow,
+        } => {
+            let mut processors = Vec::new();
+            if ocr {
+                processors.push(ocr_processor(ocr_lang)?);
+            }
+            if caption {
+                processors.push(captioner(model)?);
+            }
+            if transcribe {
+                processors.push(transcriber(whisper_model, whisper_lang)?);
+            }
+            if documents {
+                processors.push(document_extractor()?);
+            }
+            if processors.is_empty() {
+                anyhow::bail!("Nothing to do: pass --ocr, --caption, --transcribe and/or --documents");
+            }
+
+            let pipeline = MediaPipeline {
+                processors,
+                store_path: MediaTextStore::default_path()?,
+            };
+            let filter = media_filter(&filter, &since)?;
+            let manager = super::create_manager()?;
+            let forgotten = ForgetLog::load()?;
+
+            process(&pipeline, &manager, &filter, force, &forgotten).await?;
+            if follow {
+                follow_new(&pipeline, &manager, &filter, &forgotten).await?;
+            }
+            Ok(())
+        }
+        MediaAction::Show { filter, since } => show(filter, since).await,
+    }
+}
+
+fn media_filter(filter: &str, since: &str) -> Result<MediaFilter> {
+    let (source_id, chat) = filter_parser::parse_source_filter(filter)?;
+    Ok(MediaFilter {
+        source_id,
+        chat,
+        since: filter_parser::parse_time_spec(since)?,
+    })
+}
+
+#[cfg(feature = "ocr")]
+fn ocr_processor(languages: Option<String>) -> Result<Box<dyn MediaProcessor>> {
+    Ok(Box::new(chat::media_text::TesseractOcr { languages }))
+}
+
+#[cfg(not(feature = "ocr"))]
+fn ocr_processor(_languages: Option<String>) -> Result<Box<dyn MediaProcessor>> {
+    anyhow::bail!("OCR requires the 'ocr' feature")
+}
+
+#[cfg(feature = "vision")]
+fn captioner(model: Option<String>) -> Result<Box<dyn MediaProcessor>> {
+    let model = model.unwrap_or_else(|| chat::media_text::DEFAULT_VISION_MODEL.to_string());
+    Ok(Box::new(chat::media_text::OllamaCaptioner::from_env(model)))
+}
+
+#[cfg(not(feature = "vision"))]
+fn captioner(_model: Option<String>) -> Result<Box<dyn MediaProcessor>> {
+    anyhow::bail!("Captions require the 'vision' feature")
+}
+
+#[cfg(feature = "whisper")]
+fn transcriber(model: Option<PathBuf>, language: Option<String>) -> Result<Box<dyn MediaProcessor>> {
+    let Some(model) = model else {
+        anyhow::bail!("Transcripts need a Whisper model: pass --whisper-model or set WHISPER_MODEL");
+    };
+    if !model.exists() {
+        anyhow::bail!("Whisper model {} not found", model.display());
+    }
+    Ok(Box::new(chat::media_text::WhisperTranscriber { language, ..chat::media_text::WhisperTranscriber::new(model) }))
+}
+
+#[cfg(not(feature = "whisper"))]
+fn transcriber(_model: Option<PathBuf>, _language: Option<String>) -> Result<Box<dyn MediaProcessor>> {
+    anyhow::bail!("Transcripts require the 'whisper' feature")
+}
+
+#[cfg(feature = "documents")]
+fn document_extractor() -> Result<Box<dyn MediaProcessor>> {
+    Ok(Box::new(chat::media_text::DocumentExtractor))
+}
+
+#[cfg(not(feature = "documents"))]
+fn document_extractor() -> Result<Box<dyn MediaProcessor>> {
+    anyhow::bail!("Document text requires the 'documents' feature")
+}
+
+/// Print what processing reports: derived text on stdout, problems on stderr
+fn print_event(event: MediaEvent<'_>) {
+    match event {
+        MediaEvent::SourceFailed { source, error } => eprintln!("{}", format!("Skipping {}: {}", source, error).yellow()),
+        MediaEvent::Unavailable { message, error } => {
+            eprintln!("{}", format!("Attachment of message {} unavailable: {}", message.id, error).yellow())
+        }
+        MediaEvent::Extracted { source, message, derived } => println!(
+            "{} {} {}",
+            format!("{}:{}", source, message.chat_id).dimmed(),
+            format!("[{}]", derived.kind.name()).cyan(),
+            derived.text.lines().next().unwrap_or_default()
+        ),
+        MediaEvent::Failed { message, error } => {
+            eprintln!("{}", format!("Failed on message {}: {}", message.id, error).red())
+        }
+    }
+}
+
+async fn process(
+    pipeline: &MediaPipeline,
+    manager: &SourcesManager,
+    filter: &MediaFilter,
+    force: bool,
+    forgotten: &ForgetLog,
+) -> Result<()> {
+    let report = pipeline.process(manager, filter, force, forgotten, print_event).await?;
+
+    println!();
+    println!(
+        "{} {} attachments: {} texts extracted, {} without a file, {} failed",
+        "Checked".bold(),
+        report.checked,
+        report.extracted,
+        report.unavailable,
+        report.failed
+    );
+    if report.unavailable > 0 {
+        println!("{}", "Sources only provide files for some attachments; the rest can't be processed.".dimmed());
+    }
+    Ok(())
+}
+
+/// Background worker: process the attachments of new messages from every streaming source
+async fn follow_new(
+    pipeline: &MediaPipeline,
+    manager: &SourcesManager,
+    filter: &MediaFilter,
+    forgotten: &ForgetLog,
+) -> Result<()> {
+    let (subscribed, mut rx) = super::subscribe_all(manager, BufferConfig::default()).await?;
+    if subscribed.is_empty() {
+        eprintln!("{}", "No sources to listen to.".yellow());
+        return Ok(());
+    }
+    eprintln!("{} {} for new attachments (Ctrl+C to stop)", "Watching".bold(), subscribed.join(", "));
+
+    let stop = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    pipeline.follow(manager, filter, forgotten, &mut rx, stop, print_event).await
+}
+
+async fn show(filter: String, since: String) -> Result<()> {
+    let store = MediaTextStore::load()?;
+    let filter = media_filter(&filter, &since)?;
+    let manager = super::create_manager()?;
+    let forgotten = ForgetLog::load()?;
+
+    let texts = commands::media::derived_texts(&manager, &filter, &store, &forgotten, print_event).await?;
+    for (source, message, derived) in &texts {
+        println!(
+            "{} {} {} {}",
+            message.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string().dimmed(),
+            format!("{}:{}", source, message.chat_id).dimmed(),
+            format!("[{}]", derived.kind.name()).cyan(),
+            derived.text.replace('\n', " ")
+        );
+    }
+
+    if texts.is_empty() {
+        println!("{}", "No derived text. Run `chat media process --ocr`, `--transcribe` or `--documents` first.".yellow());
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+
+use chat::{GlobalChatId, MergedChats};
+
+#[derive(Subcommand)]
+pub enum MergedAction {
+    /// Merge chats into one conversation, or add chats to a merge
+    Add {
+        /// Name of the merge (e.g. "Team")
+        name: String,
+        /// Chats as source:chat_id, oldest first (e.g. telegram:-100111 telegram:-100222)
+        #[arg(required = true)]
+        chats: Vec<String>,
+    },
+    /// Remove a merge by name, or take one chat (source:chat_id) out of its merge
+    Remove {
+        /// Merge name or source:chat_id
+        target: String,
+    },
+    /// List merged chats
+    List,
+}
+
+pub async fn execute(action: MergedAction) -> Result<()> {
+    let mut store = MergedChats::load()?;
+
+    match action {
+        MergedAction::Add { name, chats } => {
+            let chats = chats
+                .iter()
+                .map(|chat| GlobalChatId::parse(chat, None))
+                .collect::<Result<Vec<_>>>()?;
+            let added = store.add(&name, &chats)?;
+            store.save()?;
+            if added > 0 {
+                println!("{}", format!("Merged {} chats into '{}'", added, name.trim()).green());
+            } else {
+                println!("{}", format!("'{}' already has those chats", name.trim()).yellow());
+            }
+        }
+        MergedAction::Remove { target } => {
+            let chat = GlobalChatId::parse(&target, None).ok().filter(|chat| store.merge_of(chat).is_some());
+            let removed = match &chat {
+                Some(chat) => store.remove_chat(chat),
+                None => store.remove(&target),
+            };
+            if removed {
+                store.save()?;
+                println!("{}", format!("Removed {}", target).green());
+            } else {
+                println!("{}", format!("No merge or merged chat '{}'", target).yellow());
+            }
+        }
+        MergedAction::List => {
+            if store.is_empty() {
+                println!("{}", "No merged chats.".yellow());
+                println!();
+                println!("Use {} to merge chats", "chat merged add <name> <source:chat_id>...".cyan());
+                return Ok(());
+            }
+
+            println!();
+            println!("{}", "Merged Chats:".bold());
+            println!();
+
+            for (name, chats) in store.entries() {
+                let chats: Vec<String> = chats.iter().map(ToString::to_string).collect();
+                println!("  {} {} [{}]", "•".cyan(), name.bold(), chats.join(" → ").green());
+            }
+
+            println!();
+        }
+    }
+
+    Ok(())
+}
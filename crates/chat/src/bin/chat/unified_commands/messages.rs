@@ -1,47 +1,53 @@
 use anyhow::Result;
 use colored::Colorize;
 
-use chat::{MessageFilter, SourcesManager, filter_parser};
+use chat::commands::{self, messages::MessageQuery};
+use chat::{filter_parser, ContentType};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn execute(
     filter: String,
     since: Option<String>,
     before: Option<String>,
     sender: Option<String>,
     search: Option<String>,
+    in_attachments: bool,
     limit: Option<usize>,
+    mentions_me: bool,
+    exact: bool,
+    language: Option<String>,
+    dedupe: bool,
+    content_type: Option<String>,
+    include_system: bool,
+    only_system: bool,
+    with_transcripts: bool,
     format: String,
 ) -> Result<()> {
-    println!("{}", "Querying messages...".dimmed());
-
-    // Parse source:pattern filter
-    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&filter)?;
+    let content_types = if only_system {
+        Some(vec![ContentType::System])
+    } else {
+        content_type.as_deref().map(filter_parser::parse_content_types).transpose()?
+    };
 
-    // Create sources manager
-    let manager = SourcesManager::new();
+    println!("{}", "Querying messages...".dimmed());
 
-    // Build message filter
-    let mut msg_filter = MessageFilter {
-        chat: chat_pattern,
-        since: None,
-        before: None,
+    let manager = super::create_manager()?;
+    let query = MessageQuery {
+        filter,
+        since,
+        before,
         sender,
         search,
+        in_attachments,
         limit,
-        content_type: None,
+        mentions_me,
+        exact,
+        language,
+        dedupe,
+        content_types,
+        include_system,
     };
-
-    // Parse time specifications
-    if let Some(since_spec) = since {
-        msg_filter.since = Some(filter_parser::parse_time_spec(&since_spec)?);
-    }
-
-    if let Some(before_spec) = before {
-        msg_filter.before = Some(filter_parser::parse_time_spec(&before_spec)?);
-    }
-
-    // Query messages
-    let messages = manager.query_messages(source_id.as_deref(), msg_filter).await?;
+    let messages = commands::messages::query(&manager, query).await?;
 
     if messages.is_empty() {
         println!();
@@ -59,8 +65,11 @@ pub async fn execute(
             println!("ID,Chat ID,Sender,Timestamp,Content");
             for msg in messages {
                 let sender_name = msg.sender.display_name.as_deref().unwrap_or("Unknown");
-                let content = match &msg.content {
-                    chat::MessageContent::Text(text) => text.replace('\n', " ").replace(',', ";"),
+                let content = match (&msg.content, &msg.media_text) {
+                    (chat::MessageContent::Text(text), _) => text.replace('\n', " ").replace(',', ";"),
+                    (chat::MessageContent::Audio { .. }, Some(transcript)) if with_transcripts => {
+                        transcript.replace(',', ";")
+                    }
                     _ => "[Non-text content]".to_string(),
                 };
                 println!("{},{},{},{},{}",
@@ -70,14 +79,15 @@ pub async fn execute(
         "compact" => {
             for msg in messages {
                 let sender_name = msg.sender.display_name.as_deref().unwrap_or("Unknown");
-                let content = match &msg.content {
-                    chat::MessageContent::Text(text) => text,
+                let content = match (&msg.content, &msg.media_text) {
+                    (chat::MessageContent::Text(text), _) => text,
+                    (chat::MessageContent::Audio { .. }, Some(transcript)) if with_transcripts => transcript,
                     _ => "[Non-text content]",
                 };
                 println!("[{}] {}: {}", msg.timestamp.format("%Y-%m-%d %H:%M:%S"), sender_name, content);
             }
         }
-        "text" | _ => {
+        _ => {
             println!();
             println!("{} {} messages found:", "Found".bold(), messages.len());
             println!();
@@ -86,10 +96,12 @@ pub async fn execute(
                 let sender_name = msg.sender.display_name.as_deref().unwrap_or("Unknown");
                 let timestamp = msg.timestamp.format("%Y-%m-%d %H:%M:%S").to_string().dimmed();
 
+                let mention = if msg.mentions_me { "@me".yellow().bold() } else { "".normal() };
+
                 println!("{} {} {}",
                     timestamp,
                     format!("{}:", sender_name).cyan().bold(),
-                    ""
+                    mention
                 );
 
                 match &msg.content {
@@ -106,6 +118,9 @@ pub async fn execute(
                     }
                     chat::MessageContent::Audio { .. } => {
                         println!("  {}", "[Audio]".yellow());
+                        if let Some(transcript) = msg.media_text.as_ref().filter(|_| with_transcripts) {
+                            println!("  {} {}", "Transcript:".dimmed(), transcript);
+                        }
                     }
                     chat::MessageContent::File { filename, .. } => {
                         println!("  {} {}", "[File]".yellow(), filename.as_deref().unwrap_or(""));
@@ -119,6 +134,9 @@ pub async fn execute(
                     chat::MessageContent::Contact { name, phone } => {
                         println!("  {} {} {}", "[Contact]".yellow(), name, phone.as_deref().unwrap_or(""));
                     }
+                    chat::MessageContent::System(event) => {
+                        println!("  {}", format!("[{}]", event.describe()).dimmed());
+                    }
                     chat::MessageContent::Unknown => {
                         println!("  {}", "[Unknown content]".dimmed());
                     }
@@ -0,0 +1,53 @@
+use anyhow::Result;
+
+use chat::{BufferConfig, Message, SourcesManager};
+use colored::Colorize;
+
+pub mod analyze;
+pub mod archive;
+pub mod bookmark;
+pub mod cache;
+pub mod catchup;
+pub mod channel_stats;
+pub mod sources;
+pub mod chats;
+pub mod context;
+pub mod drafts;
+pub mod dupes;
+pub mod events;
+pub mod export;
+pub mod export_all;
+pub mod identities;
+pub mod labels;
+pub mod links;
+pub mod mark_read;
+pub mod mcp;
+pub mod media;
+pub mod merged;
+pub mod messages;
+pub mod note;
+pub mod pipeline;
+pub mod remind;
+pub mod self_update;
+pub mod semantic_search;
+pub mod send;
+pub mod snippet;
+pub mod sync;
+pub mod todos;
+pub mod topics;
+pub mod watch;
+
+pub use chat::commands::{create_manager, set_read_only, set_source_mode, SourceMode};
+
+/// Subscribe to every connected streaming source, merging their messages into one channel
+/// of `(source_id, message)`. Returns the subscribed source IDs; others are reported and skipped.
+pub async fn subscribe_all(
+    manager: &SourcesManager,
+    config: BufferConfig,
+) -> Result<(Vec<String>, tokio::sync::mpsc::UnboundedReceiver<(String, Message)>)> {
+    let merged = chat::commands::subscribe_all(manager, config).await?;
+    for skipped in &merged.skipped {
+        eprintln!("{}", format!("Skipping {}: {}", skipped.id, skipped.reason).yellow());
+    }
+    Ok((merged.sources, merged.messages))
+}
@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+
+use chat::{GlobalChatId, Note, NoteStore};
+
+#[derive(Subcommand)]
+pub enum NoteAction {
+    /// Add to or change the note on a chat or person
+    Set {
+        /// Chat as source:chat_id (e.g. telegram:-100123), or source:user_id with --user
+        target: String,
+        /// The target is a person rather than a chat
+        #[arg(long)]
+        user: bool,
+        /// Replace the free text
+        #[arg(long)]
+        text: Option<String>,
+        /// Append a line to the free text
+        #[arg(long)]
+        append: Option<String>,
+        /// Field as key=value (e.g. birthday=05-01); an empty value removes the field
+        #[arg(long = "field", value_name = "KEY=VALUE")]
+        fields: Vec<String>,
+        /// Person's name, so questions naming them pick up the note
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Show the note on a chat or person
+    Show {
+        /// Chat as source:chat_id, or source:user_id with --user
+        target: String,
+        #[arg(long)]
+        user: bool,
+    },
+    /// Remove the note on a chat or person
+    Remove {
+        /// Chat as source:chat_id, or source:user_id with --user
+        target: String,
+        #[arg(long)]
+        user: bool,
+    },
+    /// List all notes
+    List {
+        /// Output format: text or json
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+}
+
+/// A chat or person a note belongs to
+enum Target {
+    Chat(GlobalChatId),
+    User { source: String, user_id: String },
+}
+
+impl Target {
+    fn parse(target: &str, user: bool) -> Result<Self> {
+        if !user {
+            return Ok(Target::Chat(target.parse()?));
+        }
+        let (source, user_id) = target
+            .split_once(':')
+            .with_context(|| format!("Person '{}' needs a source prefix, e.g. telegram:{}", target, target))?;
+        Ok(Target::User { source: source.to_string(), user_id: user_id.to_string() })
+    }
+
+    fn note<'a>(&self, store: &'a NoteStore) -> Option<&'a Note> {
+        match self {
+            Target::Chat(id) => store.chat(&id.source, &id.chat_id()),
+            Target::User { source, user_id } => store.user(source, user_id),
+        }
+    }
+
+    fn note_mut<'a>(&self, store: &'a mut NoteStore) -> &'a mut Note {
+        match self {
+            Target::Chat(id) => store.chat_mut(&id.source, &id.chat_id()),
+            Target::User { source, user_id } => store.user_mut(source, user_id),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Target::Chat(id) => id.to_string(),
+            Target::User { source, user_id } => format!("{}:{} (person)", source, user_id),
+        }
+    }
+}
+
+pub async fn execute(action: NoteAction) -> Result<()> {
+    let mut store = NoteStore::load()?;
+
+    match action {
+        NoteAction::Set { target, user, text, append, fields, name } => {
+            let target = Target::parse(&target, user)?;
+            let note = target.note_mut(&mut store);
+            if let Some(text) = text {
+                note.text = text;
+            }
+            if let Some(line) = append {
+                if !note.text.is_empty() {
+                    note.text.push('\n');
+                }
+                note.text.push_str(&line);
+            }
+            for field in fields {
+                let (key, value) = field
+                    .split_once('=')
+                    .with_context(|| format!("Expected KEY=VALUE, got '{}'", field))?;
+                note.set_field(key, value);
+            }
+            if let Some(name) = name {
+                note.name = Some(name).filter(|name| !name.trim().is_empty());
+            }
+            store.save()?;
+            println!("{}", format!("Updated the note on {}", target.label()).green());
+        }
+        NoteAction::Show { target, user } => {
+            let target = Target::parse(&target, user)?;
+            match target.note(&store).filter(|note| !note.is_empty()) {
+                Some(note) => print_note(&target.label(), note),
+                None => println!("{}", format!("No note on {}", target.label()).yellow()),
+            }
+        }
+        NoteAction::Remove { target, user } => {
+            let target = Target::parse(&target, user)?;
+            let removed = match &target {
+                Target::Chat(id) => store.remove_chat(&id.source, &id.chat_id()),
+                Target::User { source, user_id } => store.remove_user(source, user_id),
+            };
+            if removed {
+                store.save()?;
+                println!("{}", format!("Removed the note on {}", target.label()).green());
+            } else {
+                println!("{}", format!("No note on {}", target.label()).yellow());
+            }
+        }
+        NoteAction::List { format } => {
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&store)?);
+                return Ok(());
+            }
+
+            let chats: Vec<_> = store.chat_entries().collect();
+            let users: Vec<_> = store.user_entries().collect();
+            if chats.is_empty() && users.is_empty() {
+                println!("{}", "No notes.".yellow());
+                println!();
+                println!("Use {} to add one", "chat note set <source>:<chat_id> --text <text>".cyan());
+                return Ok(());
+            }
+
+            for (heading, entries) in [("Chats:", chats), ("People:", users)] {
+                if entries.is_empty() {
+                    continue;
+                }
+                println!();
+                println!("{}", heading.bold());
+                for (key, note) in entries {
+                    let label = match &note.name {
+                        Some(name) => format!("{} ({})", name, key),
+                        None => key.to_string(),
+                    };
+                    println!("  {} {} {}", "•".cyan(), label.green(), note.summary());
+                }
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+fn print_note(label: &str, note: &Note) {
+    println!("{}", label.bold());
+    if let Some(name) = &note.name {
+        println!("  {} {}", "name:".dimmed(), name);
+    }
+    for (key, value) in &note.fields {
+        println!("  {} {}", format!("{}:", key).dimmed(), value);
+    }
+    for line in note.text.lines() {
+        println!("  {}", line);
+    }
+    if let Some(updated) = note.updated {
+        println!("  {}", format!("updated {}", updated.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")).dimmed());
+    }
+}
@@ -0,0 +1,228 @@
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chat::pipeline::{Dispatch, Envelope, PipelineHost, Pipelines, Route, append_to_store};
+use chat::{BufferConfig, Chat, ChatId, OverflowPolicy, SourcesManager, StreamEvent};
+
+#[derive(Subcommand)]
+pub enum PipelineAction {
+    /// Validate the pipeline file and list its pipelines
+    Check {
+        /// Pipeline file (default: ~/.config/chat/pipelines.yaml)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Run incoming messages from all streaming sources through the pipelines
+    Run {
+        /// Pipeline file (default: ~/.config/chat/pipelines.yaml)
+        #[arg(long)]
+        file: Option<PathBuf>,
+
+        /// Print notify and tts dispatches as JSON lines on stdout for a host
+        /// process (the dashboard) to handle, instead of showing them here
+        #[arg(long)]
+        emit: bool,
+    },
+}
+
+pub async fn execute(action: PipelineAction) -> Result<()> {
+    match action {
+        PipelineAction::Check { file } => check(file),
+        PipelineAction::Run { file, emit } => run(file, emit).await,
+    }
+}
+
+fn load(file: Option<PathBuf>) -> Result<(PathBuf, Pipelines)> {
+    let path = match file {
+        Some(path) => path,
+        None => Pipelines::default_path()?,
+    };
+    if !path.exists() {
+        anyhow::bail!("Pipeline file not found: {}", path.display());
+    }
+
+    let pipelines = Pipelines::load_from(&path)?;
+    Ok((path, pipelines))
+}
+
+fn check(file: Option<PathBuf>) -> Result<()> {
+    let (path, pipelines) = load(file)?;
+
+    println!("{} {}", "✓".green(), path.display());
+    for name in pipelines.names() {
+        println!("  {}", name);
+    }
+    if pipelines.is_empty() {
+        println!("{}", "  No enabled pipelines.".yellow());
+    }
+
+    Ok(())
+}
+
+async fn run(file: Option<PathBuf>, emit: bool) -> Result<()> {
+    let (path, pipelines) = load(file)?;
+    if pipelines.is_empty() {
+        eprintln!("{}", format!("No enabled pipelines in {}", path.display()).yellow());
+        return Ok(());
+    }
+
+    let manager = super::create_manager()?;
+    let config = BufferConfig {
+        policy: OverflowPolicy::Block,
+        ..BufferConfig::default()
+    };
+
+    let (subscribed, mut rx) = super::subscribe_all(&manager, config).await?;
+    if subscribed.is_empty() {
+        eprintln!("{}", "No sources to listen to.".yellow());
+        return Ok(());
+    }
+
+    let chats = load_chats(&manager, &subscribed).await;
+    let labels = manager.labels()?;
+    let host = CliHost::new(emit)?;
+
+    eprintln!(
+        "{} {} on {} (Ctrl+C to stop)",
+        "Running".bold(),
+        pipelines.names().join(", "),
+        subscribed.join(", ")
+    );
+
+    loop {
+        tokio::select! {
+            received = rx.recv() => {
+                let Some((source, message)) = received else { break };
+                let envelope = Envelope {
+                    chat: chats.get(&(source.clone(), message.chat_id.clone())).cloned(),
+                    labels: labels.labels_for(&source, &message.chat_id),
+                    source,
+                    message,
+                };
+
+                let report = pipelines.process(&envelope, &host).await;
+                for (pipeline, error) in report.errors {
+                    eprintln!("{}", format!("Pipeline '{}' failed: {}", pipeline, error).red());
+                }
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Chats of the subscribed sources, for filtering by chat name
+async fn load_chats(manager: &SourcesManager, sources: &[String]) -> HashMap<(String, ChatId), Chat> {
+    let mut chats = HashMap::new();
+
+    for source in sources {
+        match manager.list_chats(source, None).await {
+            Ok(list) => {
+                for chat in list {
+                    chats.insert((source.clone(), chat.id.clone()), chat);
+                }
+            }
+            Err(e) => eprintln!(
+                "{}",
+                format!("Could not list {} chats, only chat IDs will match: {}", source, e).yellow()
+            ),
+        }
+    }
+
+    chats
+}
+
+/// Carries out dispatches from the command line
+struct CliHost {
+    emit: bool,
+    #[cfg(feature = "pipelines")]
+    client: reqwest::Client,
+    #[cfg(feature = "pipelines")]
+    ollama_host: String,
+}
+
+impl CliHost {
+    fn new(emit: bool) -> Result<Self> {
+        Ok(Self {
+            emit,
+            #[cfg(feature = "pipelines")]
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(60))
+                .build()?,
+            #[cfg(feature = "pipelines")]
+            ollama_host: std::env::var("OLLAMA_HOST")
+                .unwrap_or_else(|_| "http://localhost:11434".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl PipelineHost for CliHost {
+    #[cfg(feature = "pipelines")]
+    async fn translate(&self, text: &str, to: &str, model: &str) -> Result<String> {
+        use anyhow::Context;
+
+        let prompt = format!(
+            "Translate the following message into {}. Reply with the translation only.\n\n{}",
+            to, text
+        );
+        let response: serde_json::Value = self
+            .client
+            .post(format!("{}/api/generate", self.ollama_host))
+            .json(&serde_json::json!({ "model": model, "prompt": prompt, "stream": false }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach Ollama at {}", self.ollama_host))?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        response["response"]
+            .as_str()
+            .map(|s| s.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Ollama returned no translation"))
+    }
+
+    #[cfg(not(feature = "pipelines"))]
+    async fn translate(&self, _text: &str, _to: &str, _model: &str) -> Result<String> {
+        anyhow::bail!("translate stages require the 'pipelines' feature")
+    }
+
+    async fn dispatch(&self, dispatch: Dispatch) -> Result<()> {
+        match &dispatch.route {
+            Route::Store { path } => append_to_store(path, &dispatch),
+            Route::Webhook { url } => self.post_webhook(url, &dispatch).await,
+            Route::Notify { .. } | Route::Tts { .. } if self.emit => {
+                println!("{}", StreamEvent::dispatch(dispatch).to_line()?);
+                Ok(())
+            }
+            Route::Notify { .. } => {
+                println!("{} {}", format!("[{}]", dispatch.pipeline).cyan(), dispatch.text);
+                Ok(())
+            }
+            Route::Tts { .. } => {
+                println!("{} {} {}", format!("[{}]", dispatch.pipeline).cyan(), "🔊".dimmed(), dispatch.text);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl CliHost {
+    #[cfg(feature = "pipelines")]
+    async fn post_webhook(&self, url: &str, dispatch: &Dispatch) -> Result<()> {
+        self.client.post(url).json(&StreamEvent::dispatch(dispatch.clone())).send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "pipelines"))]
+    async fn post_webhook(&self, _url: &str, _dispatch: &Dispatch) -> Result<()> {
+        anyhow::bail!("webhook stages require the 'pipelines' feature")
+    }
+}
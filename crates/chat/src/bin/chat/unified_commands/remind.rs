@@ -0,0 +1,162 @@
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+
+use chat::{MessageContent, MessageFilter, MessageId, filter_parser};
+use chat::reminders::{Reminder, ReminderStore};
+
+#[derive(Subcommand)]
+pub enum RemindAction {
+    /// Remind me about a message later
+    Add {
+        /// Message reference (format: source:message_id, e.g., "telegram:12345")
+        message: String,
+
+        /// Delay before the reminder fires (e.g., "2d", "3h", "30m")
+        #[arg(long = "in", default_value = "1d")]
+        delay: String,
+
+        /// Chat containing the message, used to capture its content (name or ID)
+        #[arg(long)]
+        chat: Option<String>,
+
+        /// Note to show with the reminder
+        #[arg(long)]
+        note: Option<String>,
+
+        /// Also read the reminder aloud in the dashboard
+        #[arg(long)]
+        speak: bool,
+
+        /// Fire even during the dashboard's do-not-disturb hours
+        #[arg(long)]
+        critical: bool,
+    },
+    /// List pending reminders
+    List,
+    /// Push a reminder back
+    Snooze {
+        /// Reminder ID
+        id: String,
+
+        /// New delay from now (e.g., "1h", "1d")
+        #[arg(long = "for", default_value = "1h")]
+        delay: String,
+    },
+    /// Cancel a reminder
+    Cancel {
+        /// Reminder ID
+        id: String,
+    },
+}
+
+pub async fn execute(action: RemindAction) -> Result<()> {
+    let mut store = ReminderStore::load()?;
+
+    match action {
+        RemindAction::Add { message, delay, chat, note, speak, critical } => {
+            let (source, message_id) = message
+                .split_once(':')
+                .filter(|(source, id)| !source.is_empty() && !id.is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Invalid message reference '{}'. Expected source:message_id", message))?;
+
+            let delay = filter_parser::parse_duration_spec(&delay)?;
+            let mut reminder = Reminder::new(source, MessageId::new(message_id), delay);
+            reminder.note = note;
+            reminder.speak = speak;
+            reminder.critical = critical;
+
+            if let Some(chat) = chat {
+                capture_content(&mut reminder, &chat).await?;
+            }
+
+            println!("{}", format!("Reminder {} set for {}",
+                reminder.id,
+                reminder.due.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+            ).green());
+
+            store.add(reminder);
+            store.save()?;
+        }
+        RemindAction::List => {
+            if store.list().is_empty() {
+                println!("{}", "No pending reminders.".yellow());
+                println!();
+                println!("Use {} to add one", "chat remind add <source:message_id> --in 2d".cyan());
+                return Ok(());
+            }
+
+            println!();
+            println!("{}", "Pending Reminders:".bold());
+            println!();
+
+            for reminder in store.list() {
+                println!("  {} {} {}",
+                    reminder.id.cyan(),
+                    reminder.due.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string().dimmed(),
+                    reminder.summary()
+                );
+            }
+
+            println!();
+        }
+        RemindAction::Snooze { id, delay } => {
+            let delay = filter_parser::parse_duration_spec(&delay)?;
+            match store.snooze(&id, delay) {
+                Some(due) => {
+                    store.save()?;
+                    println!("{}", format!("Reminder {} snoozed until {}",
+                        id,
+                        due.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                    ).green());
+                }
+                None => println!("{}", format!("Reminder '{}' not found", id).yellow()),
+            }
+        }
+        RemindAction::Cancel { id } => match store.cancel(&id) {
+            Some(_) => {
+                store.save()?;
+                println!("{}", format!("Reminder {} cancelled", id).green());
+            }
+            None => println!("{}", format!("Reminder '{}' not found", id).yellow()),
+        },
+    }
+
+    Ok(())
+}
+
+/// Look up the referenced message in a chat and store its content with the reminder
+async fn capture_content(reminder: &mut Reminder, chat: &str) -> Result<()> {
+    let (_, chat_pattern) = filter_parser::parse_source_filter(&format!("{}:{}", reminder.source, chat))?;
+    let manager = super::create_manager()?;
+
+    let filter = MessageFilter {
+        chat: chat_pattern,
+        limit: Some(1000),
+        ..MessageFilter::new()
+    };
+
+    let messages = match manager.query_messages(Some(&reminder.source), filter).await {
+        Ok(messages) => messages,
+        Err(e) => {
+            println!("{}", format!("Warning: could not fetch message content: {}", e).yellow());
+            return Ok(());
+        }
+    };
+
+    match messages.into_iter().find(|m| m.id == reminder.message_id) {
+        Some(message) => {
+            reminder.chat_id = Some(message.chat_id);
+            reminder.sender = message.sender.display_name;
+            reminder.content = Some(match message.content {
+                MessageContent::Text(text) => text,
+                _ => "[Non-text content]".to_string(),
+            });
+        }
+        None => {
+            println!("{}", format!("Warning: message {} not found in '{}'", reminder.message_id, chat).yellow());
+        }
+    }
+
+    Ok(())
+}
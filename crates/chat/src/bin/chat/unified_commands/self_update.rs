@@ -0,0 +1,128 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::self_update::{UpdateCheck, Version};
+use chat::sources_config::SourcesConfig;
+
+/// Check GitHub for a newer release and, unless `check` is set, install it.
+/// `force` installs the latest release even when it isn't newer.
+pub async fn execute(check: bool, force: bool, format: String) -> Result<()> {
+    let settings = SourcesConfig::load()?.updates;
+    let off = if !cfg!(feature = "self-update") {
+        Some("This chat was built without the self-update feature")
+    } else if !settings.check {
+        Some("Update checks are off ([updates] check = false in sources.toml)")
+    } else {
+        None
+    };
+    if let Some(reason) = off.filter(|_| check) {
+        match format.as_str() {
+            "json" => println!("{}", serde_json::to_string(&UpdateCheck::disabled())?),
+            _ => println!("{}", reason),
+        }
+        return Ok(());
+    }
+
+    let release = fetch::latest_release(&settings.repository).await?;
+    let status = UpdateCheck::new(&release);
+    if format == "json" {
+        println!("{}", serde_json::to_string(&status)?);
+    } else {
+        print_status(&status);
+    }
+    if check || !(status.update_available || force) {
+        return Ok(());
+    }
+
+    let target = std::env::current_exe()?;
+    let tag = &release.tag_name;
+    eprintln!("{} {} to {}", "Installing".bold(), tag, target.display());
+    let bytes = fetch::verified_binary(&release).await?;
+    chat::self_update::install(&bytes, &target)?;
+    eprintln!("{} chat {} → {}", "Updated".green().bold(), Version::current(), tag);
+    Ok(())
+}
+
+fn print_status(status: &UpdateCheck) {
+    let latest = status.latest.as_deref().unwrap_or("unknown");
+    if status.update_available {
+        println!("{} {} → {}", "Update available:".green().bold(), status.current, latest);
+    } else {
+        println!("chat {} is up to date (latest release: {})", status.current, latest);
+    }
+    if status.update_available && !status.changelog.is_empty() {
+        println!("\n{}\n{}\n", "What's new".bold(), status.changelog);
+    }
+    if !status.url.is_empty() {
+        println!("{}", status.url.dimmed());
+    }
+}
+
+#[cfg(feature = "self-update")]
+mod fetch {
+    use anyhow::{Context, Result};
+    use chat::self_update::{self, Release, CHECKSUMS_ASSET};
+
+    fn client() -> Result<reqwest::Client> {
+        Ok(reqwest::Client::builder()
+            // GitHub rejects requests without one
+            .user_agent(concat!("chat/", env!("CARGO_PKG_VERSION")))
+            .timeout(std::time::Duration::from_secs(300))
+            .build()?)
+    }
+
+    pub async fn latest_release(repository: &str) -> Result<Release> {
+        client()?
+            .get(self_update::latest_release_url(repository))
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach GitHub for {} releases", repository))?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Unexpected release data from GitHub")
+    }
+
+    /// This platform's binary from `release`, checked against the release's checksums
+    pub async fn verified_binary(release: &Release) -> Result<Vec<u8>> {
+        let name = self_update::asset_name();
+        let asset = release
+            .asset(&name)
+            .with_context(|| format!("Release {} has no {} binary", release.tag_name, name))?;
+        let sums = release
+            .asset(CHECKSUMS_ASSET)
+            .with_context(|| format!("Release {} has no {}; not installing an unchecked binary", release.tag_name, CHECKSUMS_ASSET))?;
+
+        let client = client()?;
+        let sums = client.get(&sums.browser_download_url).send().await?.error_for_status()?.text().await?;
+        let expected = self_update::parse_checksums(&sums)
+            .remove(&name)
+            .with_context(|| format!("{} has no entry for {}", CHECKSUMS_ASSET, name))?;
+
+        let bytes = client
+            .get(&asset.browser_download_url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to download {}", name))?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        self_update::verify_checksum(&bytes, &expected).with_context(|| format!("Not installing {}", name))?;
+        Ok(bytes.to_vec())
+    }
+}
+
+#[cfg(not(feature = "self-update"))]
+mod fetch {
+    use anyhow::Result;
+    use chat::self_update::Release;
+
+    pub async fn latest_release(_repository: &str) -> Result<Release> {
+        anyhow::bail!("Self-update requires the 'self-update' feature")
+    }
+
+    pub async fn verified_binary(_release: &Release) -> Result<Vec<u8>> {
+        anyhow::bail!("Self-update requires the 'self-update' feature")
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use colored::Colorize;
+
+#[cfg(feature = "embeddings")]
+use chat::embeddings::{EmbeddingIndex, OllamaEmbedder};
+#[cfg(feature = "embeddings")]
+use chat::forget::ForgetLog;
+#[cfg(feature = "embeddings")]
+use chat::{ChatFilter, ChatId, ChatPattern, MessageFilter, SourcesManager, filter_parser};
+#[cfg(feature = "embeddings")]
+use std::collections::HashSet;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn execute(
+    query: String,
+    filter: String,
+    since: String,
+    top: usize,
+    model: String,
+    no_update: bool,
+    in_attachments: bool,
+    format: String,
+) -> Result<()> {
+    #[cfg(feature = "embeddings")]
+    {
+        search(query, filter, since, top, model, no_update, in_attachments, format).await
+    }
+
+    #[cfg(not(feature = "embeddings"))]
+    {
+        let _ = (query, filter, since, top, model, no_update, in_attachments, format);
+        println!("{}", "Note: semantic search requires the embeddings feature".yellow());
+        println!("  Build with: cargo build --features embeddings");
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embeddings")]
+#[allow(clippy::too_many_arguments)]
+async fn search(
+    query: String,
+    filter: String,
+    since: String,
+    top: usize,
+    model: String,
+    no_update: bool,
+    in_attachments: bool,
+    format: String,
+) -> Result<()> {
+    // Parse source:pattern filter
+    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&filter)?;
+
+    let embedder = OllamaEmbedder::from_env(model.as_str())?;
+    let mut index = EmbeddingIndex::load(&model)?;
+
+    // Create sources manager
+    let manager = super::create_manager()?;
+
+    let source_ids: Vec<String> = match &source_id {
+        Some(id) => vec![id.clone()],
+        None => manager.list_sources()?.into_iter().map(|s| s.id).collect(),
+    };
+
+    if !no_update {
+        println!("{}", "Updating embedding index...".dimmed());
+
+        let msg_filter = MessageFilter {
+            chat: chat_pattern.clone(),
+            since: Some(filter_parser::parse_time_spec(&since)?),
+            ..MessageFilter::new()
+        };
+
+        let forgotten = ForgetLog::load()?;
+        let mut added = 0;
+        for source in &source_ids {
+            let mut messages = manager.query_messages(Some(source), msg_filter.clone()).await?;
+            messages.retain(|m| !forgotten.covers(source, m));
+            added += index.index_messages(source, &messages, &embedder).await?;
+        }
+
+        if added > 0 {
+            index.save()?;
+            println!("{}", format!("Indexed {} new texts ({} total)", added, index.len()).dimmed());
+        }
+    }
+
+    let chats = matching_chats(&manager, &source_ids, &chat_pattern).await?;
+    let hits = index
+        .query(&embedder, &query, top, |entry| {
+            let in_scope = match &chats {
+                Some(chats) => chats.contains(&(entry.source.clone(), entry.chat_id.clone())),
+                None => source_ids.contains(&entry.source),
+            };
+            in_scope && (entry.attachment || !in_attachments)
+        })
+        .await?;
+
+    if hits.is_empty() {
+        println!();
+        println!("{}", "No matching messages found.".yellow());
+        return Ok(());
+    }
+
+    match format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&hits)?;
+            println!("{}", json);
+        }
+        _ => {
+            println!();
+            println!("{} {} results for \"{}\":", "Top".bold(), hits.len(), query);
+            println!();
+
+            for hit in &hits {
+                let message = &hit.message;
+                println!(
+                    "{} {} {} {}{}",
+                    format!("{:.3}", hit.score).green(),
+                    message.timestamp.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+                    format!("{}:{}", message.source, message.chat_id).cyan(),
+                    message.sender.as_deref().unwrap_or("unknown").bold(),
+                    if message.attachment { " (attachment)".dimmed() } else { "".normal() }
+                );
+                println!("      {}", message.text.replace('\n', " "));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Chats the filter selects per source, or `None` when it selects every chat
+#[cfg(feature = "embeddings")]
+async fn matching_chats(
+    manager: &SourcesManager,
+    source_ids: &[String],
+    pattern: &ChatPattern,
+) -> Result<Option<HashSet<(String, ChatId)>>> {
+    let chat_filter = match pattern {
+        ChatPattern::All => return Ok(None),
+        ChatPattern::Label(label) => Some(ChatFilter::new().with_label(label.clone())),
+        _ => None,
+    };
+
+    let mut chats = HashSet::new();
+    for source in source_ids {
+        for chat in manager.list_chats(source, chat_filter.clone()).await? {
+            if chat_filter.is_some() || pattern.matches(&chat) {
+                chats.insert((source.clone(), chat.id));
+            }
+        }
+    }
+
+    Ok(Some(chats))
+}
@@ -0,0 +1,45 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::io::Read;
+
+use chat::{filter_parser, ChatPattern, MessageId};
+
+pub async fn execute(chat: String, text: String, reply_to: Option<String>, format: String) -> Result<()> {
+    // "-" reads the text from stdin, for multi-line messages and scripts
+    let text = if text == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).context("Failed to read the message from stdin")?;
+        text
+    } else {
+        text
+    };
+
+    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&chat)?;
+    let source_id = source_id.context("Name the chat's source, e.g. telegram:\"Alice\"")?;
+
+    let manager = super::create_manager()?;
+    let chat_id = match chat_pattern {
+        ChatPattern::Id(id) => id,
+        pattern => {
+            let mut chats = chat::commands::chats::matching(&manager, Some(&source_id), &pattern).await?;
+            match chats.len() {
+                0 => anyhow::bail!("No chat matches '{}'", chat),
+                1 => chats.remove(0).1.id,
+                n => {
+                    let names: Vec<&str> = chats.iter().map(|(_, chat)| chat.title.as_deref().unwrap_or(chat.id.as_str())).collect();
+                    anyhow::bail!("'{}' matches {} chats ({}); name one chat", chat, n, names.join(", "))
+                }
+            }
+        }
+    };
+
+    let reply_to = reply_to.map(MessageId::new);
+    let message = manager.send_message(&source_id, &chat_id, text.trim_end(), reply_to.as_ref()).await?;
+
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string(&message)?),
+        _ => println!("{}", format!("Sent message {} to {}:{}", message.id, source_id, message.chat_id).green()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use std::collections::HashMap;
+
+use chat::snippets::{self, SnippetStore};
+
+#[derive(Subcommand)]
+pub enum SnippetAction {
+    /// Add a snippet, or replace the one with the same name
+    Add {
+        /// Snippet name (e.g., thanks, ooo)
+        name: String,
+        /// Text, with placeholders like {sender}, {chat}, {date} or {time}
+        text: String,
+    },
+    /// Remove a snippet
+    Remove {
+        /// Snippet name
+        name: String,
+    },
+    /// List snippets
+    List {
+        /// Output format: text or json
+        #[arg(long, default_value = "text")]
+        format: String,
+    },
+    /// Print a snippet with its placeholders filled in, ready to paste into a draft
+    Show {
+        /// Snippet name
+        name: String,
+        /// Placeholder value as key=value; {date} and {time} default to now
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
+    },
+}
+
+pub async fn execute(action: SnippetAction) -> Result<()> {
+    let mut store = SnippetStore::load()?;
+
+    match action {
+        SnippetAction::Add { name, text } => {
+            let replaced = store.set(&name, &text)?;
+            store.save()?;
+            let verb = if replaced { "Replaced" } else { "Added" };
+            println!("{}", format!("{} snippet '{}'", verb, name).green());
+        }
+        SnippetAction::Remove { name } => {
+            if store.remove(&name) {
+                store.save()?;
+                println!("{}", format!("Removed snippet '{}'", name).green());
+            } else {
+                println!("{}", format!("No snippet named '{}'", name).yellow());
+            }
+        }
+        SnippetAction::List { format } => {
+            let entries = store.entries();
+            if format == "json" {
+                let map: serde_json::Map<String, serde_json::Value> = entries
+                    .into_iter()
+                    .map(|(name, text)| (name, serde_json::Value::String(text)))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&map)?);
+                return Ok(());
+            }
+
+            if entries.is_empty() {
+                println!("{}", "No snippets.".yellow());
+                println!();
+                println!("Use {} to add one", "chat snippet add <name> <text>".cyan());
+                return Ok(());
+            }
+
+            println!();
+            println!("{}", "Snippets:".bold());
+            println!();
+
+            for (name, text) in entries {
+                let placeholders = snippets::placeholders(&text);
+                let first_line = text.lines().next().unwrap_or_default();
+                print!("  {} {} {}", "•".cyan(), name.green(), first_line);
+                if !placeholders.is_empty() {
+                    print!(" {}", format!("[{}]", placeholders.join(", ")).dimmed());
+                }
+                println!();
+            }
+
+            println!();
+        }
+        SnippetAction::Show { name, vars } => {
+            let mut values = default_values();
+            for var in vars {
+                let (key, value) = var
+                    .split_once('=')
+                    .with_context(|| format!("Expected KEY=VALUE, got '{}'", var))?;
+                values.insert(key.trim().to_string(), value.to_string());
+            }
+
+            let text = store
+                .render(&name, &values)
+                .with_context(|| format!("No snippet named '{}'", name))?;
+            println!("{}", text);
+        }
+    }
+
+    Ok(())
+}
+
+/// Values every snippet can use without passing them
+fn default_values() -> HashMap<String, String> {
+    let now = chrono::Local::now();
+    HashMap::from([
+        ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+        ("time".to_string(), now.format("%H:%M").to_string()),
+    ])
+}
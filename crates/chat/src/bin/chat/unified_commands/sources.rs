@@ -0,0 +1,110 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use chat::{commands, login_command, Capability, SourceEvent, SourceEventKind};
+
+pub async fn execute(verbose: bool) -> Result<()> {
+    println!("{}", "Listing configured chat sources...".dimmed());
+
+    let manager = super::create_manager()?;
+    manager.check_auth().await;
+    let sources = commands::sources::list(&manager)?;
+
+    if sources.is_empty() {
+        println!();
+        println!("{}", "No sources configured.".yellow());
+        println!();
+        println!("To configure a source, use:");
+        println!("  {} - Initialize Telegram", "chat telegram init".cyan());
+        println!("  {} - Initialize Signal (not yet implemented)", "chat signal init".dimmed());
+        println!("  {} - Initialize WhatsApp (not yet implemented)", "chat whatsapp init".dimmed());
+        return Ok(());
+    }
+
+    println!();
+    println!("{}", "Configured Sources:".bold());
+    println!();
+
+    for source in sources {
+        let status = if source.auth_required {
+            "Sign-in required".yellow()
+        } else if source.is_connected {
+            "Connected".green()
+        } else {
+            "Disconnected".red()
+        };
+
+        println!("  {} {} - {}",
+            "•".cyan(),
+            source.name.bold(),
+            status
+        );
+        println!("    {}: {}", "ID".dimmed(), source.id);
+        if source.auth_required {
+            println!("    The session has expired or was revoked. Run {} to sign in again.", login_command(&source.id).cyan());
+        }
+        for task in &source.tasks {
+            if !task.is_healthy() {
+                println!("    {} {}", "⚠".yellow(), task.describe().yellow());
+            } else if verbose {
+                println!("    {}: {}", "Task".dimmed(), task.describe());
+            }
+        }
+
+        if verbose {
+            for capability in Capability::ALL {
+                let mark = if source.capabilities.supports(capability) {
+                    "✓".green()
+                } else {
+                    "✗".red()
+                };
+                println!("    {} {}", mark, capability);
+            }
+        }
+    }
+
+    println!();
+
+    Ok(())
+}
+
+/// Print source connection events as JSON lines until interrupted, pinging sources
+/// every `keep_alive` seconds so their sessions stay alive and expiry is noticed
+pub async fn watch(interval: u64, keep_alive: u64) -> Result<()> {
+    let manager = super::create_manager()?;
+    manager.check_auth().await;
+    let mut events = manager.events().subscribe();
+
+    // Start with the current state of every source
+    for source in commands::sources::list(&manager)? {
+        let kind = if source.auth_required {
+            SourceEventKind::AuthExpired { reason: "session expired".to_string() }
+        } else if source.is_connected {
+            SourceEventKind::Connected
+        } else {
+            SourceEventKind::Disconnected { reason: None }
+        };
+        println!("{}", serde_json::to_string(&SourceEvent::new(source.id, kind))?);
+    }
+    let watcher = manager.watch_connections(Duration::from_secs(interval.max(1)));
+    let mut pings = tokio::time::interval(Duration::from_secs(keep_alive.max(1)));
+    pings.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = pings.tick() => manager.check_auth().await,
+            event = events.recv() => match event {
+                Ok(event) => println!("{}", serde_json::to_string(&event)?),
+                Err(RecvError::Lagged(missed)) => eprintln!("Warning: missed {} source events", missed),
+                Err(RecvError::Closed) => break,
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    watcher.abort();
+    manager.shutdown().await;
+    Ok(())
+}
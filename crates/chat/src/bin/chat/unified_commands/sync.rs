@@ -0,0 +1,113 @@
+use anyhow::Result;
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::PathBuf;
+
+use chat::commands;
+use chat::commands::sync::{SyncEvent, SyncOptions, PAGE_SIZE};
+use chat::forget::ForgetLog;
+use chat::sync::default_archive_dir;
+use chat::{Chat, Progress, RetryPolicy, SyncState, filter_parser};
+
+pub async fn execute(
+    filter: String,
+    since: String,
+    archive_dir: Option<PathBuf>,
+    retries: u32,
+    failed_only: bool,
+    dry_run: bool,
+) -> Result<()> {
+    // Parse source:pattern filter
+    let (source_id, chat_pattern) = filter_parser::parse_source_filter(&filter)?;
+    let options = SyncOptions {
+        archive_dir: match archive_dir {
+            Some(dir) => dir,
+            None => default_archive_dir()?,
+        },
+        state_path: SyncState::default_path()?,
+        since: filter_parser::parse_time_spec(&since)?,
+        policy: RetryPolicy {
+            max_attempts: retries.max(1),
+            ..RetryPolicy::default()
+        },
+    };
+
+    let mut state = SyncState::load_from(&options.state_path)?;
+    let forgotten = ForgetLog::load()?;
+
+    // Create sources manager
+    let manager = super::create_manager()?;
+
+    let targets =
+        commands::sync::targets(&manager, source_id.as_deref(), &chat_pattern, &state, &forgotten, failed_only).await?;
+    if targets.is_empty() {
+        println!("{}", "No matching chats to sync.".yellow());
+        return Ok(());
+    }
+
+    if dry_run {
+        print_preview(&targets, &state);
+        return Ok(());
+    }
+
+    // Ctrl+C stops after the current chat; its checkpoint is already saved
+    let cancel = Progress::new();
+    cancel.cancel_on_ctrl_c();
+
+    let bar = ProgressBar::new(targets.len() as u64);
+    bar.set_style(
+        ProgressStyle::with_template("{prefix:>10.bold} [{bar:30}] {pos}/{len} {wide_msg}")?.progress_chars("=> "),
+    );
+    bar.set_prefix("Syncing");
+
+    let report = commands::sync::sync(&manager, &targets, &mut state, &options, &forgotten, &cancel, |event| match event {
+        SyncEvent::Chat { id, title } => bar.set_message(title.map_or_else(|| id.to_string(), str::to_string)),
+        SyncEvent::Retry { id, attempt, error, delay } => bar.println(format!(
+            "  {} {} attempt {} failed: {} (retrying in {}s)",
+            "!".yellow(),
+            id,
+            attempt,
+            error,
+            delay.as_secs()
+        )),
+        SyncEvent::Done => bar.inc(1),
+    })
+    .await?;
+
+    bar.finish_and_clear();
+
+    println!(
+        "{}",
+        format!("Synced {} chats, {} new messages", report.synced, report.new_messages).green()
+    );
+    println!("  {}: {}", "Archive".dimmed(), options.archive_dir.display());
+
+    if report.skipped > 0 {
+        println!("{}", format!("Interrupted: {} chats not synced", report.skipped).yellow());
+    }
+
+    if !report.failures.is_empty() {
+        println!();
+        println!("{}", format!("{} chats failed permanently:", report.failures.len()).red().bold());
+        for failure in &report.failures {
+            println!(
+                "  {} {} - {}",
+                failure.chat.to_string().cyan(),
+                failure.title.as_deref().unwrap_or(""),
+                failure.error
+            );
+        }
+        println!();
+        println!("Re-run just these with: chat sync {} --failed", filter);
+    }
+
+    Ok(())
+}
+
+/// Print what a sync would fetch
+fn print_preview(targets: &[(String, Chat)], state: &SyncState) {
+    let preview = commands::export::preview_sync(targets, state, PAGE_SIZE);
+    super::export::print_estimate(&preview.estimate, "new messages aren't counted before fetching");
+    println!("  {}: {}", "Resuming from a checkpoint".dimmed(), preview.resuming);
+    println!("  {}: {}", "Starting from --since".dimmed(), targets.len() - preview.resuming);
+}
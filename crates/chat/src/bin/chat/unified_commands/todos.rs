@@ -0,0 +1,102 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::HashSet;
+
+use chat::forget::ForgetLog;
+use chat::todos;
+use chat::{ChatPattern, GlobalChatId, MessageFilter, MyAccounts, Todo, TodoStore, filter_parser};
+
+/// Messages searched per chat at most
+const MAX_MESSAGES: usize = 1000;
+
+pub async fn execute(
+    filters: Vec<String>,
+    since: String,
+    done: Vec<String>,
+    reopen: Vec<String>,
+    all: bool,
+    cached: bool,
+    format: String,
+) -> Result<()> {
+    let mut store = TodoStore::load()?;
+
+    // Marking todos done or open is local and needs no scan
+    if !done.is_empty() || !reopen.is_empty() {
+        for (ids, done) in [(&done, true), (&reopen, false)] {
+            for id in ids {
+                let todo = store.set_done(id, done)?;
+                let state = if done { "Done".green() } else { "Reopened".yellow() };
+                println!("{} {} {}", state, todo.id.dimmed(), todo.task);
+            }
+        }
+        return store.save();
+    }
+
+    let mut added = 0;
+    if !cached {
+        let since = filter_parser::parse_time_spec(&since)?;
+        let filters = if filters.is_empty() { vec!["*:*".to_string()] } else { filters };
+        let manager = super::create_manager()?;
+        let accounts = MyAccounts::load()?;
+        let forgotten = ForgetLog::load()?;
+
+        // Chats matched by more than one filter are scanned once, as are merged chats
+        let mut seen = HashSet::new();
+        for filter in &filters {
+            let (source_id, chat_pattern) = filter_parser::parse_source_filter(filter)?;
+            for (source, chat) in chat::commands::chats::matching(&manager, source_id.as_deref(), &chat_pattern).await? {
+                let id = GlobalChatId::from_chat_id(&source, &manager.merged_into(&source, &chat.id)?);
+                if !seen.insert(id.clone()) {
+                    continue;
+                }
+
+                let msg_filter = MessageFilter {
+                    chat: ChatPattern::Id(chat.id.clone()),
+                    since: Some(since),
+                    limit: Some(MAX_MESSAGES),
+                    ..MessageFilter::new()
+                };
+                let messages = match manager.query_messages(Some(&source), msg_filter).await {
+                    Ok(messages) => messages,
+                    Err(e) => {
+                        eprintln!("{}", format!("Skipping {}: {}", id, e).yellow());
+                        continue;
+                    }
+                };
+
+                let handles = accounts.handles_for(&source);
+                for message in messages.iter().filter(|m| !forgotten.covers(&source, m)) {
+                    if let Some(todo) = todos::extract(&id, chat.title.as_deref(), &chat.chat_type, message, handles) {
+                        added += usize::from(store.add(todo));
+                    }
+                }
+            }
+        }
+        store.save()?;
+    }
+
+    let listed: Vec<&Todo> = store.todos().iter().filter(|t| all || !t.is_done()).collect();
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&listed)?),
+        _ => {
+            if added > 0 {
+                println!("{}", format!("{} new", added).green());
+            }
+            if listed.is_empty() {
+                println!("{}", "Nothing to do.".dimmed());
+            }
+            for todo in listed {
+                let check = if todo.is_done() { "[x]".green() } else { "[ ]".normal() };
+                println!(
+                    "{} {} {}  {}",
+                    check,
+                    todo.id.dimmed(),
+                    todo.task.bold(),
+                    format!("{} in {}, {}", todo.sender, todo.chat_name(), todo.sent.format("%Y-%m-%d")).dimmed()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::commands::{self, analysis::AnalysisQuery};
+use chat::topics;
+
+pub async fn execute(
+    filter: String,
+    since: String,
+    before: Option<String>,
+    limit: Option<usize>,
+    format: String,
+) -> Result<()> {
+    println!("{}", "Segmenting topics...".dimmed());
+
+    let manager = super::create_manager()?;
+    let query = AnalysisQuery { filter, since: Some(since), before, sender: None, limit };
+    let (messages, segments) = commands::analysis::topics(&manager, &query).await?;
+
+    if messages.is_empty() {
+        println!();
+        println!("{}", "No messages found.".yellow());
+        return Ok(());
+    }
+
+    match format.as_str() {
+        "json" => {
+            let json = serde_json::to_string_pretty(&segments)?;
+            println!("{}", json);
+        }
+        "chunks" => {
+            for chunk in topics::context_chunks(&messages, &segments) {
+                println!("{}", chunk.text);
+            }
+        }
+        _ => {
+            println!();
+            println!("{} {} topics:", "Found".bold(), segments.len());
+
+            let mut current_chat = None;
+            for segment in &segments {
+                if current_chat != Some(&segment.chat_id) {
+                    println!();
+                    println!("{} {}", "Chat".bold(), segment.chat_id.to_string().cyan());
+                    current_chat = Some(&segment.chat_id);
+                }
+
+                println!("  {} {} {}",
+                    segment.start.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string().dimmed(),
+                    segment.title.bold(),
+                    format!("({} messages, {})", segment.message_count(), segment.participants.join(", ")).dimmed()
+                );
+            }
+
+            println!();
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use chat::{BufferConfig, MessageContent, StreamEvent};
+
+/// Follow new messages from every streaming source.
+/// With `--format json`, stdout is an NDJSON stream of `message` events.
+pub async fn execute(source: Option<String>, format: String) -> Result<()> {
+    let manager = super::create_manager()?;
+    let (subscribed, mut rx) = super::subscribe_all(&manager, BufferConfig::default()).await?;
+    if subscribed.is_empty() {
+        eprintln!("{}", "No sources to listen to.".yellow());
+        return Ok(());
+    }
+
+    eprintln!("{} {} (Ctrl+C to stop)", "Watching".bold(), subscribed.join(", "));
+
+    loop {
+        tokio::select! {
+            received = rx.recv() => {
+                let Some((id, message)) = received else { break };
+                if source.as_ref().is_some_and(|source| *source != id) {
+                    continue;
+                }
+
+                match format.as_str() {
+                    "json" => println!("{}", StreamEvent::message(id, message).to_line()?),
+                    _ => {
+                        let sender = message.sender.display_name.as_deref().unwrap_or("Unknown");
+                        let content = match &message.content {
+                            MessageContent::Text(text) => text.as_str(),
+                            _ => "[Non-text content]",
+                        };
+                        println!(
+                            "[{}] {} {}: {}",
+                            message.timestamp.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+                            format!("{}:{}", id, message.chat_id).dimmed(),
+                            sender.cyan(),
+                            content
+                        );
+                    }
+                }
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    Ok(())
+}
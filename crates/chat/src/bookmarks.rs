@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::types::{ChatId, MessageId};
+
+/// A message kept to come back to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub source: String,
+    pub message_id: MessageId,
+    #[serde(default)]
+    pub chat_id: Option<ChatId>,
+    /// Message text, captured when the bookmark was made
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub sender: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub created: DateTime<Utc>,
+}
+
+impl Bookmark {
+    pub fn new(source: impl Into<String>, message_id: MessageId) -> Self {
+        Self {
+            source: source.into(),
+            message_id,
+            chat_id: None,
+            content: None,
+            sender: None,
+            note: None,
+            created: Utc::now(),
+        }
+    }
+
+    /// `source:message_id`, as the CLI takes it
+    pub fn reference(&self) -> String {
+        format!("{}:{}", self.source, self.message_id)
+    }
+
+    /// One line for lists
+    pub fn summary(&self) -> String {
+        let mut text = match (&self.sender, &self.content) {
+            (Some(sender), Some(content)) => format!("{}: {}", sender, content),
+            (None, Some(content)) => content.clone(),
+            _ => format!("Message {} on {}", self.message_id, self.source),
+        };
+
+        if let Some(note) = &self.note {
+            text = format!("{} ({})", text, note);
+        }
+
+        text
+    }
+}
+
+/// Bookmarked messages (`~/.config/chat/bookmarks.json`), newest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookmarkStore {
+    #[serde(default)]
+    bookmarks: Vec<Bookmark>,
+}
+
+impl BookmarkStore {
+    /// Create an empty bookmark store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the bookmarks file (`~/.config/chat/bookmarks.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("bookmarks.json"))
+    }
+
+    /// Load bookmarks from the default location, returning an empty store if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load bookmarks from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read bookmarks file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse bookmarks file")
+    }
+
+    /// Save bookmarks to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save bookmarks to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create bookmarks directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize bookmarks")?;
+
+        std::fs::write(path, contents).context("Failed to write bookmarks file")
+    }
+
+    /// Add a bookmark, replacing one of the same message. Returns false if it replaced one.
+    pub fn add(&mut self, bookmark: Bookmark) -> bool {
+        let replaced = self.remove(&bookmark.source, &bookmark.message_id).is_some();
+        self.bookmarks.insert(0, bookmark);
+        !replaced
+    }
+
+    /// Remove the bookmark of a message. Returns the removed bookmark.
+    pub fn remove(&mut self, source: &str, message_id: &MessageId) -> Option<Bookmark> {
+        let index = self
+            .bookmarks
+            .iter()
+            .position(|b| b.source == source && &b.message_id == message_id)?;
+        Some(self.bookmarks.remove(index))
+    }
+
+    /// All bookmarks, newest first
+    pub fn list(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut store = BookmarkStore::new();
+        assert!(store.add(Bookmark::new("telegram", MessageId::new("1"))));
+        assert!(store.add(Bookmark::new("telegram", MessageId::new("2"))));
+
+        // Bookmarking a message again replaces it and moves it to the top
+        let mut again = Bookmark::new("telegram", MessageId::new("1"));
+        again.note = Some("reply".to_string());
+        assert!(!store.add(again));
+        assert_eq!(store.list().len(), 2);
+        assert_eq!(store.list()[0].note.as_deref(), Some("reply"));
+
+        assert!(store.remove("telegram", &MessageId::new("2")).is_some());
+        assert!(store.remove("signal", &MessageId::new("1")).is_none());
+        assert_eq!(store.list().len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut store = BookmarkStore::new();
+        let mut bookmark = Bookmark::new("telegram", MessageId::new("42"));
+        bookmark.chat_id = Some(ChatId::new("100"));
+        bookmark.content = Some("Flight is at 9".to_string());
+        store.add(bookmark);
+
+        let path = std::env::temp_dir().join(format!("chat-bookmarks-{}.json", uuid::Uuid::new_v4().simple()));
+        store.save_to(&path).unwrap();
+        let loaded = BookmarkStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.list(), store.list());
+        assert_eq!(loaded.list()[0].reference(), "telegram:42");
+    }
+}
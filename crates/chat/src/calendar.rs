@@ -0,0 +1,482 @@
+//! Date and time proposals in messages ("let's meet Tuesday at 15:00") as calendar events,
+//! found by rules or by an LLM, and written out as iCalendar for import into a calendar app
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+use crate::context::estimate_tokens;
+use crate::global_id::GlobalChatId;
+use crate::types::{Message, MessageContent, MessageId};
+
+/// Ollama model used to find events when none is given
+pub const DEFAULT_EVENTS_MODEL: &str = "llama3.2";
+
+/// Prompt budget in estimated tokens when none is given
+pub const DEFAULT_BUDGET: usize = 6000;
+
+/// Length of timed events; proposals rarely say when they end
+const EVENT_MINUTES: i64 = 60;
+
+/// Event titles are cut to this many characters
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Longer message bodies are cut to this many characters in prompts
+const MAX_MESSAGE_CHARS: usize = 400;
+
+const MONTHS: &str = "january|jan|february|feb|march|mar|april|apr|may|june|jun|july|jul|august|aug|september|sept|sep|october|oct|november|nov|december|dec";
+
+/// Words that make a date or time alone an event proposal
+const EVENT_WORDS: &str = "meet|meeting|call|lunch|dinner|breakfast|coffee|drinks|appointment|see you|catch up|sync|standup|party|interview|demo|session|let's|lets";
+
+/// How an event was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FoundBy {
+    Rules,
+    Llm,
+}
+
+/// A date, and maybe a time, mentioned in a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct When {
+    pub date: NaiveDate,
+    /// None for all-day events
+    pub time: Option<NaiveTime>,
+}
+
+/// An event proposed in a message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposedEvent {
+    pub chat: GlobalChatId,
+    pub message_id: MessageId,
+    pub sender: String,
+    pub title: String,
+    /// Local date of the event
+    pub date: NaiveDate,
+    /// Local start time; None for all-day events
+    pub time: Option<NaiveTime>,
+    /// The message that proposed it
+    pub text: String,
+    pub sent: DateTime<Utc>,
+    pub found_by: FoundBy,
+}
+
+impl ProposedEvent {
+    fn new(chat: &GlobalChatId, message: &Message, when: When, title: Option<String>, found_by: FoundBy) -> Self {
+        let text = message_text(message).unwrap_or_default().to_string();
+        Self {
+            chat: chat.clone(),
+            message_id: message.id.clone(),
+            sender: sender_name(message),
+            title: title.unwrap_or_else(|| shorten(&text, MAX_TITLE_CHARS)),
+            date: when.date,
+            time: when.time,
+            text,
+            sent: message.timestamp,
+            found_by,
+        }
+    }
+
+    /// Local start, midnight for all-day events
+    pub fn start(&self) -> NaiveDateTime {
+        self.date.and_time(self.time.unwrap_or(NaiveTime::MIN))
+    }
+
+    /// Same for every export of this event, so re-importing updates it instead of duplicating it
+    fn uid(&self) -> String {
+        let key = format!("{}\n{}\n{}", self.chat, self.message_id, self.start());
+        format!("{}@chat", &format!("{:x}", Sha256::digest(key.as_bytes()))[..16])
+    }
+}
+
+struct Patterns {
+    iso_date: Regex,
+    dotted_date: Regex,
+    day_month: Regex,
+    month_day: Regex,
+    relative: Regex,
+    clock: Regex,
+    meridiem: Regex,
+    at_hour: Regex,
+    event_word: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let regex = |pattern: &str| Regex::new(pattern).expect("valid pattern");
+        Patterns {
+            iso_date: regex(r"\b(\d{4})-(\d{1,2})-(\d{1,2})\b"),
+            dotted_date: regex(r"\b(\d{1,2})\.(\d{1,2})\.(\d{4}|\d{2})?"),
+            day_month: regex(&format!(r"\b(\d{{1,2}})(?:st|nd|rd|th)?(?: of)? ({})\b", MONTHS)),
+            month_day: regex(&format!(r"\b({}) (\d{{1,2}})(?:st|nd|rd|th)?\b", MONTHS)),
+            relative: regex(r"\b(today|tonight|tomorrow|monday|tuesday|wednesday|thursday|friday|saturday|sunday)\b"),
+            clock: regex(r"\b(\d{1,2}):(\d{2})\s*(am|pm)?\b"),
+            meridiem: regex(r"\b(\d{1,2})\s*(am|pm)\b"),
+            at_hour: regex(r"\bat (\d{1,2})\b"),
+            event_word: regex(&format!(r"\b(?:{})\b", EVENT_WORDS)),
+        }
+    })
+}
+
+/// Find a proposed date and time in `text`, sent at local time `sent`.
+///
+/// A date with a time is always a proposal; a date or a time alone only together with a
+/// word like "meet" or "call". Dates without a year and weekdays are the next ones after
+/// `sent`, a time alone is its next occurrence. Dates before `sent` are not proposals.
+pub fn find(text: &str, sent: NaiveDateTime) -> Option<When> {
+    let text = text.to_lowercase();
+    let date = find_date(&text, sent.date());
+    let time = find_time(&text);
+    let event_word = patterns().event_word.is_match(&text);
+
+    let date = match (date, time) {
+        (Some(date), Some(_)) => date,
+        (Some(date), None) if event_word => date,
+        (None, Some(time)) if event_word => {
+            if time > sent.time() { sent.date() } else { sent.date() + Duration::days(1) }
+        }
+        _ => return None,
+    };
+    (date >= sent.date()).then_some(When { date, time })
+}
+
+fn find_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let patterns = patterns();
+    let number = |s: &str| s.parse::<u32>().ok();
+
+    if let Some(caps) = patterns.iso_date.captures(text) {
+        return NaiveDate::from_ymd_opt(caps[1].parse().ok()?, number(&caps[2])?, number(&caps[3])?);
+    }
+    if let Some(caps) = patterns.dotted_date.captures(text) {
+        let year = caps.get(3).and_then(|y| y.as_str().parse::<i32>().ok()).map(|y| if y < 100 { y + 2000 } else { y });
+        if let Some(date) = day_of_year(today, year, number(&caps[2])?, number(&caps[1])?) {
+            return Some(date);
+        }
+    }
+    if let Some(caps) = patterns.day_month.captures(text) {
+        return day_of_year(today, None, month_number(&caps[2])?, number(&caps[1])?);
+    }
+    if let Some(caps) = patterns.month_day.captures(text) {
+        return day_of_year(today, None, month_number(&caps[1])?, number(&caps[2])?);
+    }
+
+    let word = patterns.relative.captures(text)?.get(1)?.as_str();
+    match word {
+        "today" | "tonight" => Some(today),
+        "tomorrow" => Some(today + Duration::days(1)),
+        weekday => {
+            let weekday: Weekday = weekday.parse().ok()?;
+            let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            Some(today + Duration::days(if ahead == 0 { 7 } else { ahead as i64 }))
+        }
+    }
+}
+
+/// A day of the given year, or without one the next such day from `today` on
+fn day_of_year(today: NaiveDate, year: Option<i32>, month: u32, day: u32) -> Option<NaiveDate> {
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+        None => {
+            let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+            if date < today { NaiveDate::from_ymd_opt(today.year() + 1, month, day) } else { Some(date) }
+        }
+    }
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    const PREFIXES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    PREFIXES.iter().position(|prefix| name.starts_with(prefix)).map(|i| i as u32 + 1)
+}
+
+fn find_time(text: &str) -> Option<NaiveTime> {
+    let patterns = patterns();
+    let number = |s: &str| s.parse::<u32>().ok();
+
+    if let Some(caps) = patterns.clock.captures(text) {
+        let hour = with_meridiem(number(&caps[1])?, caps.get(3).map(|m| m.as_str()))?;
+        return NaiveTime::from_hms_opt(hour, number(&caps[2])?, 0);
+    }
+    if let Some(caps) = patterns.meridiem.captures(text) {
+        return NaiveTime::from_hms_opt(with_meridiem(number(&caps[1])?, Some(&caps[2]))?, 0, 0);
+    }
+    if text.contains("noon") {
+        return NaiveTime::from_hms_opt(12, 0, 0);
+    }
+    // "at 3" means the afternoon; nobody proposes meeting at three in the night
+    let hour = number(&patterns.at_hour.captures(text)?[1])?;
+    let hour = if (1..=7).contains(&hour) { hour + 12 } else { hour };
+    NaiveTime::from_hms_opt(hour, 0, 0)
+}
+
+fn with_meridiem(hour: u32, meridiem: Option<&str>) -> Option<u32> {
+    match meridiem {
+        None => Some(hour),
+        Some(_) if !(1..=12).contains(&hour) => None,
+        Some("am") => Some(hour % 12),
+        Some(_) => Some(hour % 12 + 12),
+    }
+}
+
+/// The event proposed in a message, if any, found by rules
+pub fn extract(chat: &GlobalChatId, message: &Message) -> Option<ProposedEvent> {
+    let text = message_text(message)?;
+    let when = find(text, message.timestamp.with_timezone(&Local).naive_local())?;
+    Some(ProposedEvent::new(chat, message, when, None, FoundBy::Rules))
+}
+
+/// LLM prompt asking for the events proposed in `messages`, numbered by their position.
+/// The newest messages that fit in `budget` tokens are included.
+pub fn prompt(messages: &[(GlobalChatId, Message)], budget: usize) -> String {
+    let mut lines = Vec::new();
+    let mut used = 0;
+    for (i, (_, message)) in messages.iter().enumerate().rev() {
+        let Some(text) = message_text(message) else { continue };
+        let line = format!(
+            "[{}] {} {}: {}",
+            i,
+            message.timestamp.with_timezone(&Local).format("%a %Y-%m-%d %H:%M"),
+            sender_name(message),
+            shorten(&text.replace('\n', " "), MAX_MESSAGE_CHARS)
+        );
+        used += estimate_tokens(&line);
+        if used > budget && !lines.is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+    lines.reverse();
+
+    let mut prompt = String::from(
+        "Find the meetings, calls and other events proposed in these chat messages. Each line \
+         starts with the message number in brackets and when it was sent. Resolve relative dates \
+         like \"Tuesday\" or \"tomorrow\" against the time the message was sent. Reply with only \
+         a JSON array, one object per event: {\"message\": number, \"title\": short title, \
+         \"date\": \"YYYY-MM-DD\", \"time\": \"HH:MM\" or null}. Reply [] if there are none.\n\n",
+    );
+    for line in lines {
+        prompt.push_str(&line);
+        prompt.push('\n');
+    }
+    prompt
+}
+
+#[derive(Deserialize)]
+struct LlmEvent {
+    message: usize,
+    #[serde(default)]
+    title: Option<String>,
+    date: String,
+    #[serde(default)]
+    time: Option<String>,
+}
+
+/// Events in an LLM reply to [`prompt`]. Entries naming unknown messages or unreadable
+/// dates are dropped.
+pub fn parse_reply(reply: &str, messages: &[(GlobalChatId, Message)]) -> Result<Vec<ProposedEvent>> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => anyhow::bail!("No JSON array in the reply"),
+    };
+    let events: Vec<LlmEvent> = serde_json::from_str(json).context("Failed to parse the events in the reply")?;
+
+    Ok(events
+        .into_iter()
+        .filter_map(|event| {
+            let (chat, message) = messages.get(event.message)?;
+            let date = NaiveDate::parse_from_str(event.date.trim(), "%Y-%m-%d").ok()?;
+            let time = event.time.and_then(|time| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok());
+            let title = event.title.map(|title| title.trim().to_string()).filter(|title| !title.is_empty());
+            Some(ProposedEvent::new(chat, message, When { date, time }, title, FoundBy::Llm))
+        })
+        .collect())
+}
+
+/// Add `more` events to `events`, skipping ones already found for the same message and
+/// start, and sort them by start
+pub fn merge(events: &mut Vec<ProposedEvent>, more: Vec<ProposedEvent>) {
+    for event in more {
+        let known = events
+            .iter()
+            .any(|e| e.chat == event.chat && e.message_id == event.message_id && e.start() == event.start());
+        if !known {
+            events.push(event);
+        }
+    }
+    events.sort_by_key(|e| e.start());
+}
+
+/// An iCalendar file with the events. Times are floating: they stay at the same local
+/// time in whichever time zone the calendar is in.
+pub fn to_ics(events: &[ProposedEvent], stamp: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//chat//events//EN".to_string(),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid()));
+        lines.push(format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        match event.time {
+            Some(_) => {
+                let start = event.start();
+                lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+                lines.push(format!("DTEND:{}", (start + Duration::minutes(EVENT_MINUTES)).format("%Y%m%dT%H%M%S")));
+            }
+            None => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")));
+                lines.push(format!("DTEND;VALUE=DATE:{}", (event.date + Duration::days(1)).format("%Y%m%d")));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", escape(&event.title)));
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            escape(&format!("{}: {}\n\n{} ({})", event.sender, event.text, event.chat, event.sent.format("%Y-%m-%d %H:%M UTC")))
+        ));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect::<Vec<_>>().concat()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line into lines of at most 75 bytes, each ended by CRLF
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    match line.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+fn message_text(message: &Message) -> Option<&str> {
+    match &message.content {
+        MessageContent::Text(text) => Some(text),
+        MessageContent::Image { caption: Some(caption), .. } |
+        MessageContent::Video { caption: Some(caption), .. } => Some(caption),
+        _ => None,
+    }
+}
+
+fn sender_name(message: &Message) -> String {
+    message.sender.display_name
+        .clone()
+        .or_else(|| message.sender.username.clone())
+        .unwrap_or_else(|| message.sender.id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    /// Wednesday 2025-01-15 12:00
+    fn sent() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 15).unwrap().and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    fn when(text: &str) -> Option<(String, Option<String>)> {
+        find(text, sent()).map(|w| (w.date.to_string(), w.time.map(|t| t.format("%H:%M").to_string())))
+    }
+
+    fn at(date: &str, time: Option<&str>) -> Option<(String, Option<String>)> {
+        Some((date.to_string(), time.map(str::to_string)))
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(when("Let's meet Tuesday at 15:00"), at("2025-01-21", Some("15:00")));
+        assert_eq!(when("lunch tomorrow, 1pm?"), at("2025-01-16", Some("13:00")));
+        assert_eq!(when("Call on 3rd of February"), at("2025-02-03", None));
+        assert_eq!(when("Party on Jan 10 at 7"), at("2026-01-10", Some("19:00")));
+        assert_eq!(when("Termin 20.1. um 9:30"), at("2025-01-20", Some("09:30")));
+        assert_eq!(when("standup at 9:30 then"), at("2025-01-16", Some("09:30")));
+        assert_eq!(when("see you wednesday"), at("2025-01-22", None));
+        assert_eq!(when("review 2025-03-01 at 10am"), at("2025-03-01", Some("10:00")));
+    }
+
+    #[test]
+    fn test_not_proposals() {
+        assert_eq!(when("I'll send it tomorrow"), None);
+        assert_eq!(when("the build took 12:30 minutes"), None);
+        assert_eq!(when("the meeting was on 2024-12-01 at 10:00"), None);
+        assert_eq!(when("thanks!"), None);
+    }
+
+    fn messages() -> Vec<(GlobalChatId, Message)> {
+        let chat = GlobalChatId::new("telegram", "1");
+        vec![
+            (chat.clone(), MessageBuilder::new("1", "1").text("hi").build()),
+            (chat, MessageBuilder::new("2", "1").minute(1).text("dinner, friday at 7?").build()),
+        ]
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        let reply = r#"Sure! [{"message": 1, "title": "Dinner", "date": "2025-01-17", "time": "19:00"},
+            {"message": 9, "date": "2025-01-18"}, {"message": 0, "date": "soon"}]"#;
+        let events = parse_reply(reply, &messages()).unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].title, "Dinner");
+        assert_eq!(events[0].message_id.as_str(), "2");
+        assert_eq!(events[0].start().to_string(), "2025-01-17 19:00:00");
+        assert_eq!(events[0].found_by, FoundBy::Llm);
+        assert!(parse_reply("no events", &messages()).is_err());
+    }
+
+    #[test]
+    fn test_merge_and_ics() {
+        let messages = messages();
+        let (chat, message) = &messages[1];
+        let date = NaiveDate::from_ymd_opt(2025, 1, 17).unwrap();
+        let timed = When { date, time: NaiveTime::from_hms_opt(19, 0, 0) };
+
+        let mut events = vec![ProposedEvent::new(chat, message, timed, None, FoundBy::Rules)];
+        merge(
+            &mut events,
+            vec![
+                ProposedEvent::new(chat, message, timed, Some("Dinner".into()), FoundBy::Llm),
+                ProposedEvent::new(chat, message, When { date, time: None }, None, FoundBy::Llm),
+            ],
+        );
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].time, None);
+
+        let ics = to_ics(&events, Utc::now());
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250117\r\n"));
+        assert!(ics.contains("DTSTART:20250117T190000\r\nDTEND:20250117T200000\r\n"));
+        assert!(ics.contains("SUMMARY:dinner\\, friday at 7?\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    }
+}
@@ -0,0 +1,223 @@
+//! "Catch me up": the unread messages of a set of chats, urgent chats first, ready to be
+//! summarized by an LLM or read as a plain digest
+
+use serde::Serialize;
+
+use crate::context::estimate_tokens;
+use crate::global_id::GlobalChatId;
+use crate::text_fold;
+use crate::types::{Message, MessageContent};
+
+/// Ollama model used for summaries when none is given
+pub const DEFAULT_SUMMARY_MODEL: &str = "llama3.2";
+
+/// Prompt budget in estimated tokens when none is given
+pub const DEFAULT_BUDGET: usize = 6000;
+
+/// Words that make a message urgent, matched ignoring case and accents
+const URGENT_WORDS: &[&str] = &["urgent", "asap", "emergency", "immediately", "deadline", "kiire"];
+
+/// Longer message bodies are cut to this many characters in prompts
+const MAX_MESSAGE_CHARS: usize = 400;
+
+/// Unread messages of one chat
+#[derive(Debug, Clone, Serialize)]
+pub struct UnreadChat {
+    pub chat: GlobalChatId,
+    pub title: Option<String>,
+    /// Oldest first
+    pub messages: Vec<Message>,
+    /// How many of the messages are urgent
+    pub urgent: usize,
+}
+
+impl UnreadChat {
+    pub fn new(chat: GlobalChatId, title: Option<String>, mut messages: Vec<Message>) -> Self {
+        messages.sort_by_key(|m| m.timestamp);
+        let urgent = messages.iter().filter(|m| is_urgent(m)).count();
+        Self { chat, title, messages, urgent }
+    }
+
+    /// Chat title, falling back to its ID
+    pub fn name(&self) -> String {
+        self.title.clone().unwrap_or_else(|| self.chat.to_string())
+    }
+}
+
+/// Whether a message needs attention first: it mentions me or says it's urgent
+pub fn is_urgent(message: &Message) -> bool {
+    message.mentions_me
+        || message_text(message).is_some_and(|text| URGENT_WORDS.iter().any(|word| text_fold::contains(text, word, false)))
+}
+
+/// Drop chats without unread messages and put urgent chats first, then the most recently active
+pub fn order(chats: &mut Vec<UnreadChat>) {
+    chats.retain(|chat| !chat.messages.is_empty());
+    chats.sort_by(|a, b| {
+        (b.urgent > 0)
+            .cmp(&(a.urgent > 0))
+            .then_with(|| b.messages.last().map(|m| m.timestamp).cmp(&a.messages.last().map(|m| m.timestamp)))
+    });
+}
+
+/// LLM prompt asking for a summary grouped by chat, urgent items first.
+/// Each chat gets an equal share of `budget` tokens, filled with its newest messages.
+pub fn prompt(chats: &[UnreadChat], budget: usize) -> String {
+    let mut prompt = String::from(
+        "Catch me up on these chats. Summarize each chat in a few short sentences under its name, \
+         in the order given. Start with anything marked URGENT or that needs a reply from me. \
+         Reply in plain text suitable for reading aloud.\n\n",
+    );
+
+    let share = budget / chats.len().max(1);
+    for chat in chats {
+        let mut lines = Vec::new();
+        let mut used = 0;
+        for message in chat.messages.iter().rev() {
+            let line = transcript_line(message);
+            used += estimate_tokens(&line);
+            if used > share && !lines.is_empty() {
+                break;
+            }
+            lines.push(line);
+        }
+        lines.reverse();
+
+        let omitted = chat.messages.len() - lines.len();
+        prompt.push_str(&format!("## {} ({} unread", chat.name(), chat.messages.len()));
+        if chat.urgent > 0 {
+            prompt.push_str(&format!(", {} urgent", chat.urgent));
+        }
+        prompt.push_str(")\n");
+        if omitted > 0 {
+            prompt.push_str(&format!("[{} earlier messages omitted]\n", omitted));
+        }
+        for line in lines {
+            prompt.push_str(&line);
+            prompt.push('\n');
+        }
+        prompt.push('\n');
+    }
+
+    prompt
+}
+
+/// Plain digest for when no LLM is available: counts and the latest message per chat
+pub fn digest(chats: &[UnreadChat]) -> String {
+    chats
+        .iter()
+        .map(|chat| {
+            let mut line = format!("{}: {} unread", chat.name(), chat.messages.len());
+            if chat.urgent > 0 {
+                line.push_str(&format!(", {} urgent", chat.urgent));
+            }
+            if let Some(latest) = chat.messages.iter().rev().find(|m| is_urgent(m)).or(chat.messages.last()) {
+                line.push_str(&format!(". {} said: {}", sender_name(latest), body(latest)));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn transcript_line(message: &Message) -> String {
+    format!(
+        "[{}]{} {}: {}",
+        message.timestamp.format("%Y-%m-%d %H:%M"),
+        if is_urgent(message) { " URGENT" } else { "" },
+        sender_name(message),
+        body(message)
+    )
+}
+
+fn message_text(message: &Message) -> Option<&str> {
+    match &message.content {
+        MessageContent::Text(text) => Some(text),
+        MessageContent::Image { caption: Some(caption), .. } |
+        MessageContent::Video { caption: Some(caption), .. } => Some(caption),
+        _ => None,
+    }
+}
+
+fn body(message: &Message) -> String {
+    let mut text = message_text(message).unwrap_or("[media]").replace('\n', " ");
+    if let Some(media_text) = &message.media_text {
+        let label = match message.content {
+            MessageContent::Audio { .. } => "transcript",
+            MessageContent::File { .. } => "document text",
+            _ => "image text",
+        };
+        text.push_str(&format!(" ({}: {})", label, media_text.replace('\n', " ")));
+    }
+    match text.char_indices().nth(MAX_MESSAGE_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+fn sender_name(message: &Message) -> String {
+    message.sender.display_name
+        .clone()
+        .or_else(|| message.sender.username.clone())
+        .unwrap_or_else(|| message.sender.id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    fn chat(id: &str, title: &str, messages: Vec<Message>) -> UnreadChat {
+        UnreadChat::new(GlobalChatId::new("telegram", id), Some(title.to_string()), messages)
+    }
+
+    #[test]
+    fn test_urgent() {
+        assert!(is_urgent(&MessageBuilder::new("1", "a").text("Need this ASAP please").build()));
+        assert!(is_urgent(&MessageBuilder::new("1", "a").text("can you look?").mentions_me().build()));
+        assert!(!is_urgent(&MessageBuilder::new("1", "a").text("lunch?").build()));
+    }
+
+    #[test]
+    fn test_order() {
+        let mut chats = vec![
+            chat("1", "Quiet", vec![]),
+            chat("2", "Recent", vec![MessageBuilder::new("1", "2").minute(30).text("hi").build()]),
+            chat("3", "Older", vec![MessageBuilder::new("1", "3").minute(10).text("hello").build()]),
+            chat("4", "Urgent", vec![MessageBuilder::new("1", "4").minute(0).text("urgent: server down").build()]),
+        ];
+        order(&mut chats);
+
+        let names: Vec<_> = chats.iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["Urgent", "Recent", "Older"]);
+    }
+
+    #[test]
+    fn test_prompt_budget() {
+        let messages = (0..50)
+            .map(|i| MessageBuilder::new(i.to_string(), "1").minute(i).text(format!("message number {}", i)).build())
+            .collect();
+        let chats = vec![chat("1", "Work", messages)];
+
+        let prompt = prompt(&chats, 100);
+        assert!(prompt.contains("## Work (50 unread)"));
+        assert!(prompt.contains("message number 49"));
+        assert!(!prompt.contains("message number 0\n"));
+        assert!(prompt.contains("earlier messages omitted"));
+    }
+
+    #[test]
+    fn test_digest() {
+        let chats = vec![chat(
+            "1",
+            "Work",
+            vec![
+                MessageBuilder::new("1", "1").minute(0).text("deadline is today").build(),
+                MessageBuilder::new("2", "1").minute(1).text("thanks").build(),
+            ],
+        )];
+        let digest = digest(&chats);
+        assert!(digest.starts_with("Work: 2 unread, 1 urgent."));
+        assert!(digest.ends_with("said: deadline is today"));
+    }
+}
@@ -0,0 +1,446 @@
+//! Statistics of a channel I admin: how often it posts, how its posts are viewed, which
+//! posts did best and how it grows over time
+//!
+//! Sources that count views fetch [`ChannelPosts`]; everything else is worked out here.
+//! A source only knows the subscriber count as of now, so every run records it in a
+//! local [`SubscriberHistory`], and growth is read from the counts recorded before.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::global_id::GlobalChatId;
+use crate::types::MessageId;
+
+/// Top posts listed when no number is given
+pub const DEFAULT_TOP: usize = 5;
+
+/// Post texts are cut to this many characters in listings
+const MAX_TEXT_CHARS: usize = 80;
+
+/// A channel post with the counters the source reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Post {
+    pub id: MessageId,
+    pub posted: DateTime<Utc>,
+    pub text: String,
+    /// None where the source doesn't count them
+    pub views: Option<u64>,
+    pub forwards: Option<u64>,
+    pub replies: Option<u64>,
+    pub reactions: Option<u64>,
+}
+
+impl Post {
+    /// Forwards, replies and reactions together
+    pub fn interactions(&self) -> u64 {
+        [self.forwards, self.replies, self.reactions].into_iter().flatten().sum()
+    }
+
+    /// The text on one line, cut for listings
+    pub fn preview(&self) -> String {
+        let text = self.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        match text.char_indices().nth(MAX_TEXT_CHARS) {
+            Some((end, _)) => format!("{}…", &text[..end]),
+            None => text,
+        }
+    }
+}
+
+/// What a source knows about a channel right now
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChannelPosts {
+    pub title: Option<String>,
+    /// Current subscriber count, where the source tells
+    pub subscribers: Option<u64>,
+    /// Posts in the requested time range, in any order
+    pub posts: Vec<Post>,
+}
+
+/// Length of the periods growth is shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    /// First day of the period `date` is in; weeks start on Monday
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// First day of the period after the one starting on `start`
+    pub fn next(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => start + Duration::days(1),
+            Period::Week => start + Duration::days(7),
+            Period::Month => start
+                .checked_add_months(chrono::Months::new(1))
+                .unwrap_or(start + Duration::days(31)),
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "day" | "daily" => Ok(Period::Day),
+            "week" | "weekly" => Ok(Period::Week),
+            "month" | "monthly" => Ok(Period::Month),
+            _ => anyhow::bail!("Unknown period '{}'; use day, week or month", s),
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A subscriber count and when it was seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriberSample {
+    pub at: DateTime<Utc>,
+    pub count: u64,
+}
+
+/// One period of a channel's history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodStats {
+    /// First day of the period (UTC)
+    pub start: NaiveDate,
+    pub posts: usize,
+    /// Views of the posts made in the period
+    pub views: Option<u64>,
+    pub average_views: Option<u64>,
+    pub interactions: u64,
+    /// Last subscriber count recorded by the end of the period
+    pub subscribers: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelStats {
+    pub chat: GlobalChatId,
+    pub title: Option<String>,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub posts: usize,
+    pub posts_per_day: f64,
+    /// None when the source counts no views
+    pub total_views: Option<u64>,
+    pub average_views: Option<u64>,
+    pub median_views: Option<u64>,
+    pub subscribers: Option<u64>,
+    /// Change since the first count recorded in the time range
+    pub subscriber_change: Option<i64>,
+    /// Most viewed posts, or those with the most interactions without view counts
+    pub top_posts: Vec<Post>,
+    pub period: Period,
+    pub periods: Vec<PeriodStats>,
+}
+
+/// Work out a channel's statistics for posts from `since` to `until`. `history` is the
+/// subscriber counts recorded so far, including the current one.
+pub fn compute(
+    chat: GlobalChatId,
+    channel: ChannelPosts,
+    history: &[SubscriberSample],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    period: Period,
+    top: usize,
+) -> ChannelStats {
+    let mut posts: Vec<Post> = channel
+        .posts
+        .into_iter()
+        .filter(|post| post.posted >= since && post.posted <= until)
+        .collect();
+    posts.sort_by_key(|post| post.posted);
+
+    let days = ((until - since).num_seconds() as f64 / 86_400.0).max(1.0);
+    let mut views: Vec<u64> = posts.iter().filter_map(|post| post.views).collect();
+    views.sort_unstable();
+    let total_views = (!views.is_empty()).then(|| views.iter().sum::<u64>());
+
+    let samples: Vec<&SubscriberSample> = history.iter().filter(|sample| sample.at <= until).collect();
+    let subscribers = channel.subscribers.or_else(|| samples.last().map(|sample| sample.count));
+    let first = samples.iter().find(|sample| sample.at >= since).map(|sample| sample.count);
+    let subscriber_change = subscribers.zip(first).map(|(now, then)| now as i64 - then as i64);
+
+    let mut top_posts = posts.clone();
+    top_posts.sort_by(|a, b| {
+        b.views
+            .unwrap_or(0)
+            .cmp(&a.views.unwrap_or(0))
+            .then(b.interactions().cmp(&a.interactions()))
+            .then(b.posted.cmp(&a.posted))
+    });
+    top_posts.truncate(top);
+
+    ChannelStats {
+        chat,
+        title: channel.title,
+        since,
+        until,
+        posts: posts.len(),
+        posts_per_day: posts.len() as f64 / days,
+        total_views,
+        average_views: total_views.map(|total| total / views.len() as u64),
+        median_views: views.get(views.len() / 2).copied(),
+        subscribers,
+        subscriber_change,
+        top_posts,
+        period,
+        periods: periods(&posts, &samples, since, until, period),
+    }
+}
+
+fn periods(
+    posts: &[Post],
+    samples: &[&SubscriberSample],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    period: Period,
+) -> Vec<PeriodStats> {
+    let mut periods = Vec::new();
+    let mut start = period.start(since.date_naive());
+    while start <= until.date_naive() {
+        let next = period.next(start);
+        let in_period: Vec<&Post> = posts
+            .iter()
+            .filter(|post| post.posted.date_naive() >= start && post.posted.date_naive() < next)
+            .collect();
+        let counted: Vec<u64> = in_period.iter().filter_map(|post| post.views).collect();
+        let views = (!counted.is_empty()).then(|| counted.iter().sum::<u64>());
+        let subscribers = samples
+            .iter()
+            .rev()
+            .find(|sample| sample.at.date_naive() < next)
+            .map(|sample| sample.count);
+
+        periods.push(PeriodStats {
+            start,
+            posts: in_period.len(),
+            views,
+            average_views: views.map(|views| views / counted.len() as u64),
+            interactions: in_period.iter().map(|post| post.interactions()).sum(),
+            subscribers,
+        });
+        start = next;
+    }
+    periods
+}
+
+/// The periods as CSV, one row each
+pub fn periods_csv(stats: &ChannelStats) -> String {
+    let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+    let mut csv = String::from("period_start,posts,views,average_views,interactions,subscribers\n");
+    for period in &stats.periods {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            period.start,
+            period.posts,
+            optional(period.views),
+            optional(period.average_views),
+            period.interactions,
+            optional(period.subscribers)
+        ));
+    }
+    csv
+}
+
+/// Subscriber counts recorded per channel, so growth can be shown later
+///
+/// Stored at `~/.config/chat/channel_subscribers.json`, keyed by the canonical
+/// `source:chat_id` (see [`GlobalChatId`]). One count is kept per channel and day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubscriberHistory {
+    #[serde(default)]
+    channels: BTreeMap<String, Vec<SubscriberSample>>,
+}
+
+impl SubscriberHistory {
+    /// Default location of the history file
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("channel_subscribers.json"))
+    }
+
+    /// Load the history from the default location, empty if there is none
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path).context("Failed to read subscriber history")?;
+        serde_json::from_str(&contents).context("Failed to parse subscriber history")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let contents = serde_json::to_string_pretty(self).context("Failed to serialize subscriber history")?;
+        std::fs::write(path, contents).context("Failed to write subscriber history")
+    }
+
+    /// Record a count, replacing one recorded earlier the same day
+    pub fn record(&mut self, chat: &GlobalChatId, sample: SubscriberSample) {
+        let samples = self.channels.entry(chat.to_string()).or_default();
+        samples.retain(|old| old.at.date_naive() != sample.at.date_naive());
+        samples.push(sample);
+        samples.sort_by_key(|sample| sample.at);
+    }
+
+    /// Counts recorded for a channel, oldest first
+    pub fn samples(&self, chat: &GlobalChatId) -> &[SubscriberSample] {
+        self.channels.get(&chat.to_string()).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ChatId;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn post(id: u32, posted: DateTime<Utc>, views: Option<u64>, reactions: u64) -> Post {
+        Post {
+            id: MessageId::new(id.to_string()),
+            posted,
+            text: format!("Post {}", id),
+            views,
+            forwards: Some(1),
+            replies: None,
+            reactions: Some(reactions),
+        }
+    }
+
+    fn channel() -> GlobalChatId {
+        GlobalChatId::from_chat_id("telegram", &ChatId::new("-1001234"))
+    }
+
+    #[test]
+    fn test_compute() {
+        // Monday 3 March to Sunday 16 March: two weeks
+        let posts = ChannelPosts {
+            title: Some("News".to_string()),
+            subscribers: Some(1200),
+            posts: vec![
+                post(1, at(3, 9), Some(100), 2),
+                post(2, at(5, 9), Some(400), 1),
+                post(3, at(11, 9), Some(250), 9),
+                post(4, at(12, 9), Some(300), 0),
+                // Outside the range
+                post(5, at(20, 9), Some(9000), 0),
+            ],
+        };
+        let history = [
+            SubscriberSample { at: at(2, 12), count: 1000 },
+            SubscriberSample { at: at(4, 12), count: 1050 },
+            SubscriberSample { at: at(10, 12), count: 1150 },
+            SubscriberSample { at: at(16, 12), count: 1200 },
+        ];
+
+        let stats = compute(channel(), posts, &history, at(3, 0), at(17, 0), Period::Week, 2);
+        assert_eq!(stats.posts, 4);
+        assert!((stats.posts_per_day - 4.0 / 14.0).abs() < 1e-9);
+        assert_eq!(stats.total_views, Some(1050));
+        assert_eq!(stats.average_views, Some(262));
+        assert_eq!(stats.median_views, Some(300));
+        assert_eq!(stats.subscribers, Some(1200));
+        assert_eq!(stats.subscriber_change, Some(150));
+        let top: Vec<&str> = stats.top_posts.iter().map(|post| post.id.as_str()).collect();
+        assert_eq!(top, ["2", "4"]);
+
+        assert_eq!(stats.periods.len(), 3);
+        assert_eq!(stats.periods[0].start, NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+        assert_eq!((stats.periods[0].posts, stats.periods[0].views), (2, Some(500)));
+        assert_eq!(stats.periods[0].interactions, 5);
+        assert_eq!(stats.periods[0].subscribers, Some(1050));
+        assert_eq!(stats.periods[1].average_views, Some(275));
+        assert_eq!(stats.periods[1].subscribers, Some(1200));
+        assert_eq!((stats.periods[2].posts, stats.periods[2].views), (0, None));
+
+        let csv = periods_csv(&stats);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("period_start,posts,views,average_views,interactions,subscribers"));
+        assert_eq!(lines.next(), Some("2025-03-03,2,500,250,5,1050"));
+        assert_eq!(lines.last(), Some("2025-03-17,0,,,0,1200"));
+    }
+
+    #[test]
+    fn test_without_view_counts() {
+        let posts = ChannelPosts {
+            title: None,
+            subscribers: None,
+            posts: vec![post(1, at(3, 9), None, 1), post(2, at(4, 9), None, 7)],
+        };
+        let stats = compute(channel(), posts, &[], at(1, 0), at(31, 0), Period::Month, DEFAULT_TOP);
+        assert_eq!((stats.total_views, stats.average_views, stats.median_views), (None, None, None));
+        assert_eq!((stats.subscribers, stats.subscriber_change), (None, None));
+        // Ranked by interactions instead
+        assert_eq!(stats.top_posts[0].id.as_str(), "2");
+        assert_eq!(stats.periods.len(), 1);
+        assert_eq!(stats.periods[0].start, NaiveDate::from_ymd_opt(2025, 3, 1).unwrap());
+    }
+
+    #[test]
+    fn test_subscriber_history() {
+        let path = std::env::temp_dir().join(format!("channel-subscribers-{}.json", uuid::Uuid::new_v4()));
+        let mut history = SubscriberHistory::load_from(&path).unwrap();
+        history.record(&channel(), SubscriberSample { at: at(4, 9), count: 10 });
+        history.record(&channel(), SubscriberSample { at: at(3, 9), count: 8 });
+        // A later count the same day replaces the earlier one
+        history.record(&channel(), SubscriberSample { at: at(4, 18), count: 12 });
+        history.save_to(&path).unwrap();
+
+        let history = SubscriberHistory::load_from(&path).unwrap();
+        let counts: Vec<u64> = history.samples(&channel()).iter().map(|sample| sample.count).collect();
+        assert_eq!(counts, [8, 12]);
+        let other = GlobalChatId::from_chat_id("telegram", &ChatId::new("-1009"));
+        assert!(history.samples(&other).is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_period() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 30).unwrap();
+        assert_eq!(Period::Week.start(date), NaiveDate::from_ymd_opt(2025, 1, 27).unwrap());
+        assert_eq!(Period::Month.next(Period::Month.start(date)), NaiveDate::from_ymd_opt(2025, 2, 1).unwrap());
+        assert_eq!("Weekly".parse::<Period>().unwrap(), Period::Week);
+        assert!("year".parse::<Period>().is_err());
+    }
+}
@@ -1,9 +1,8 @@
 use anyhow::Result;
-use chat::{Chat, ChatType, Message, MessageContent};
+use crate::types::{Chat, ChatType, Message, MessageContent};
 use colored::Colorize;
-use serde_json;
 
-use crate::cli::OutputFormat;
+use super::OutputFormat;
 
 pub fn format_chats(chats: &[Chat], format: OutputFormat) -> Result<String> {
     match format {
@@ -13,6 +12,9 @@ pub fn format_chats(chats: &[Chat], format: OutputFormat) -> Result<String> {
         }
         OutputFormat::Csv => Ok(format_chats_csv(chats)),
         OutputFormat::Compact => Ok(format_chats_compact(chats)),
+        OutputFormat::Sqlite | OutputFormat::Parquet => {
+            anyhow::bail!("{:?} output is only available from `chat export`", format)
+        }
     }
 }
 
@@ -24,6 +26,9 @@ pub fn format_messages(messages: &[Message], format: OutputFormat) -> Result<Str
         }
         OutputFormat::Csv => Ok(format_messages_csv(messages)),
         OutputFormat::Compact => Ok(format_messages_compact(messages)),
+        OutputFormat::Sqlite | OutputFormat::Parquet => {
+            anyhow::bail!("{:?} output is only available from `chat export`", format)
+        }
     }
 }
 
@@ -124,6 +129,9 @@ fn format_messages_text(messages: &[Message]) -> String {
             }
             MessageContent::Audio { .. } => {
                 output.push_str(&"[Audio]".yellow().to_string());
+                if let Some(transcript) = &msg.media_text {
+                    output.push_str(&format!(" {}", transcript));
+                }
             }
             MessageContent::File { filename, .. } => {
                 output.push_str(&format!("[File: {}]", filename.as_deref().unwrap_or("unknown")).yellow().to_string());
@@ -141,6 +149,9 @@ fn format_messages_text(messages: &[Message]) -> String {
                     phone.as_ref().map(|p| format!(" ({})", p)).unwrap_or_default()
                 ).yellow().to_string());
             }
+            MessageContent::System(event) => {
+                output.push_str(&format!("[{}]", event.describe()).dimmed().to_string());
+            }
             MessageContent::Unknown => {
                 output.push_str(&"[Unknown message type]".red().to_string());
             }
@@ -165,13 +176,14 @@ fn format_messages_csv(messages: &[Message]) -> String {
             MessageContent::Text(text) => ("text", text.clone()),
             MessageContent::Image { caption, .. } => ("image", caption.clone().unwrap_or_default()),
             MessageContent::Video { caption, .. } => ("video", caption.clone().unwrap_or_default()),
-            MessageContent::Audio { .. } => ("audio", String::new()),
+            MessageContent::Audio { .. } => ("audio", msg.media_text.clone().unwrap_or_default()),
             MessageContent::File { filename, .. } => ("file", filename.clone().unwrap_or_default()),
             MessageContent::Sticker => ("sticker", String::new()),
             MessageContent::Location { latitude, longitude } => {
                 ("location", format!("{},{}", latitude, longitude))
             }
             MessageContent::Contact { name, .. } => ("contact", name.clone()),
+            MessageContent::System(event) => ("system", event.describe()),
             MessageContent::Unknown => ("unknown", String::new()),
         };
 
@@ -199,11 +211,15 @@ fn format_messages_compact(messages: &[Message]) -> String {
                 MessageContent::Text(text) => text.clone(),
                 MessageContent::Image { .. } => "[Image]".to_string(),
                 MessageContent::Video { .. } => "[Video]".to_string(),
-                MessageContent::Audio { .. } => "[Audio]".to_string(),
+                MessageContent::Audio { .. } => match &msg.media_text {
+                    Some(transcript) => format!("[Audio] {}", transcript),
+                    None => "[Audio]".to_string(),
+                },
                 MessageContent::File { .. } => "[File]".to_string(),
                 MessageContent::Sticker => "[Sticker]".to_string(),
                 MessageContent::Location { .. } => "[Location]".to_string(),
                 MessageContent::Contact { .. } => "[Contact]".to_string(),
+                MessageContent::System(event) => format!("[{}]", event.describe()),
                 MessageContent::Unknown => "[Unknown]".to_string(),
             };
 
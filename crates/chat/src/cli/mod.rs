@@ -1,6 +1,11 @@
+//! Output options and Telegram settings shared by the `chat` command-line tools
+
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
+pub mod config;
+pub mod formatters;
+
 #[derive(Debug, Clone, Copy, ValueEnum, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
@@ -12,6 +17,17 @@ pub enum OutputFormat {
     Csv,
     /// Compact single-line format
     Compact,
+    /// SQLite database with chats, users, messages and media tables (feature `sqlite`)
+    Sqlite,
+    /// Directory of Parquet files partitioned by source, chat and date (feature `arrow`)
+    Parquet,
+}
+
+impl OutputFormat {
+    /// Whether the output is a whole dataset written to a file rather than printable text
+    pub fn is_dataset(self) -> bool {
+        matches!(self, OutputFormat::Sqlite | OutputFormat::Parquet)
+    }
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -0,0 +1,219 @@
+//! One clock for messages from different sources
+//!
+//! Bridges stamp messages with their own clock, which can run minutes off, and often only
+//! to the second, so merged views show answers before questions. [`ClockSkew`] works out
+//! how far each source's clock is ahead and takes that off its timestamps, keeping what the
+//! source reported in `Message::original_timestamp`. [`newest_first`] orders messages that
+//! are still tied by source and then by ID, so every query lists them the same way.
+
+use chrono::{DateTime, Duration, Utc};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+
+use crate::types::{Message, MessageId};
+
+/// Offsets smaller than this are delivery delay and rounding, not a wrong clock
+const MIN_SKEW_MS: i64 = 2000;
+
+/// Samples kept per source; old ones age out so a fixed clock is noticed
+const MAX_SAMPLES: usize = 50;
+
+/// Samples needed before a source's timestamps are corrected
+const MIN_SAMPLES: usize = 3;
+
+/// How far one source's clock is ahead, from messages whose arrival time is known
+#[derive(Debug, Clone, Default)]
+pub struct SkewEstimate {
+    /// How far each message's timestamp was ahead of its arrival
+    samples: VecDeque<Duration>,
+}
+
+impl SkewEstimate {
+    /// Record a message stamped `timestamp` that had arrived by `received`
+    pub fn observe(&mut self, timestamp: DateTime<Utc>, received: DateTime<Utc>) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(timestamp - received);
+    }
+
+    /// How far the clock is ahead (negative when behind), once there are enough samples
+    /// and it is off by enough to matter.
+    ///
+    /// A message arrives after it was sent, so every sample is the skew less a delivery
+    /// delay, and the largest one, from the message delivered fastest, is closest to it.
+    /// Backlogs replayed after a reconnect only add samples with long delays.
+    pub fn skew(&self) -> Option<Duration> {
+        if self.samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        let ahead = *self.samples.iter().max()?;
+        (ahead.abs() >= Duration::milliseconds(MIN_SKEW_MS)).then_some(ahead)
+    }
+}
+
+/// Clock corrections per source: a fixed offset from sources.toml, otherwise an estimate
+#[derive(Debug, Clone, Default)]
+pub struct ClockSkew {
+    estimates: HashMap<String, SkewEstimate>,
+    fixed: HashMap<String, Duration>,
+}
+
+impl ClockSkew {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Correct a source by `offset`, how far its clock runs ahead, instead of estimating
+    /// it. `None` goes back to estimating.
+    pub fn set_fixed(&mut self, source_id: &str, offset: Option<Duration>) {
+        match offset {
+            Some(offset) => self.fixed.insert(source_id.to_string(), offset),
+            None => self.fixed.remove(source_id),
+        };
+    }
+
+    /// Record a message from a live stream as it arrives
+    pub fn observe(&mut self, source_id: &str, message: &Message, received: DateTime<Utc>) {
+        if message.pending {
+            return;
+        }
+        let timestamp = message.original_timestamp.unwrap_or(message.timestamp);
+        self.estimates.entry(source_id.to_string()).or_default().observe(timestamp, received);
+    }
+
+    /// Record queried messages dated after `now`. When they arrived isn't known, but it
+    /// was before now, so they still show a clock running ahead.
+    pub fn observe_future(&mut self, source_id: &str, messages: &[Message], now: DateTime<Utc>) {
+        for message in messages {
+            if message.original_timestamp.unwrap_or(message.timestamp) > now {
+                self.observe(source_id, message, now);
+            }
+        }
+    }
+
+    /// How far a source's clock is ahead, if it is off by enough to correct
+    pub fn skew(&self, source_id: &str) -> Option<Duration> {
+        match self.fixed.get(source_id) {
+            Some(offset) => (!offset.is_zero()).then_some(*offset),
+            None => self.estimates.get(source_id)?.skew(),
+        }
+    }
+
+    /// Take a source's skew off its message timestamps, keeping the reported time in
+    /// `original_timestamp`. Drafts and scheduled messages are left alone, and normalizing
+    /// twice changes nothing.
+    pub fn normalize(&self, source_id: &str, messages: &mut [Message]) {
+        let Some(skew) = self.skew(source_id) else {
+            return;
+        };
+        for message in messages.iter_mut().filter(|m| !m.pending) {
+            let original = message.original_timestamp.unwrap_or(message.timestamp);
+            message.timestamp = original - skew;
+            message.original_timestamp = Some(original);
+        }
+    }
+}
+
+/// Compare message IDs as numbers when both are, so "9" comes before "10". Numeric IDs
+/// come before others.
+pub fn compare_ids(a: &MessageId, b: &MessageId) -> Ordering {
+    match (a.as_str().parse::<u64>(), b.as_str().parse::<u64>()) {
+        (Ok(x), Ok(y)) => x.cmp(&y).then_with(|| a.as_str().cmp(b.as_str())),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.as_str().cmp(b.as_str()),
+    }
+}
+
+/// Order of merged views: newest first, and messages with the same timestamp by source,
+/// then by ID, both descending so the list reads back in time the same way throughout
+pub fn newest_first(a_source: &str, a: &Message, b_source: &str, b: &Message) -> Ordering {
+    (b.timestamp, b_source)
+        .cmp(&(a.timestamp, a_source))
+        .then_with(|| compare_ids(&b.id, &a.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_736_942_400 + seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn test_skew_estimate() {
+        let mut estimate = SkewEstimate::default();
+        estimate.observe(at(95), at(5));
+        estimate.observe(at(100), at(12));
+        assert_eq!(estimate.skew(), None);
+
+        // A replayed backlog arrives late and doesn't pull the estimate down
+        estimate.observe(at(0), at(600));
+        assert_eq!(estimate.skew(), Some(Duration::seconds(90)));
+
+        let mut close = SkewEstimate::default();
+        for delay in [1, 0, 1] {
+            close.observe(at(0), at(delay));
+        }
+        assert_eq!(close.skew(), None);
+
+        let mut behind = SkewEstimate::default();
+        for delay in [61, 60, 75] {
+            behind.observe(at(0), at(delay));
+        }
+        assert_eq!(behind.skew(), Some(Duration::seconds(-60)));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut clock = ClockSkew::new();
+        let now = Utc::now();
+        let ahead: Vec<Message> = (0..3)
+            .map(|i| MessageBuilder::new(i.to_string(), "1").at(now + Duration::seconds(300 + i)).build())
+            .collect();
+        clock.observe_future("bridge", &ahead, now);
+        assert_eq!(clock.skew("bridge"), Some(Duration::seconds(302)));
+        assert_eq!(clock.skew("telegram"), None);
+
+        let mut messages = vec![
+            MessageBuilder::new("4", "1").at(at(400)).build(),
+            MessageBuilder::new("5", "1").at(at(900)).pending().build(),
+        ];
+        clock.normalize("bridge", &mut messages);
+        assert_eq!(messages[0].timestamp, at(98));
+        assert_eq!(messages[0].original_timestamp, Some(at(400)));
+        assert_eq!(messages[1].timestamp, at(900));
+        assert_eq!(messages[1].original_timestamp, None);
+
+        clock.normalize("bridge", &mut messages);
+        assert_eq!(messages[0].timestamp, at(98));
+
+        // A fixed offset wins over the estimate, and zero turns correction off
+        clock.set_fixed("bridge", Some(Duration::seconds(100)));
+        clock.normalize("bridge", &mut messages);
+        assert_eq!(messages[0].timestamp, at(300));
+        clock.set_fixed("bridge", Some(Duration::zero()));
+        assert_eq!(clock.skew("bridge"), None);
+        clock.set_fixed("bridge", None);
+        assert_eq!(clock.skew("bridge"), Some(Duration::seconds(302)));
+    }
+
+    #[test]
+    fn test_newest_first() {
+        let message = |id: &str, seconds| MessageBuilder::new(id, "1").at(at(seconds)).build();
+        let mut merged = [
+            ("signal", message("9", 10)),
+            ("telegram", message("10", 10)),
+            ("signal", message("10", 10)),
+            ("telegram", message("9", 10)),
+            ("signal", message("abc", 20)),
+            ("signal", message("1", 20)),
+        ];
+        merged.sort_by(|(a_source, a), (b_source, b)| newest_first(a_source, a, b_source, b));
+        let order: Vec<String> = merged.iter().map(|(source, m)| format!("{}:{}", source, m.id.as_str())).collect();
+        assert_eq!(order, ["signal:abc", "signal:1", "telegram:10", "telegram:9", "signal:10", "signal:9"]);
+    }
+}
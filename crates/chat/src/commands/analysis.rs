@@ -0,0 +1,147 @@
+use anyhow::Result;
+use chrono::TimeZone;
+
+use crate::analysis::{self, ChatAnalysis};
+use crate::dupes::{DuplicateDetector, DuplicateGroup};
+use crate::links::{self, LinkEntry};
+use crate::topics::{TopicSegment, TopicSegmenter};
+use crate::{filter_parser, Message, MessageFilter, SourcesManager};
+
+/// Messages to look at, as selected by `chat analyze`, `chat topics`, `chat dupes` and `chat links`
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisQuery {
+    /// Source and chat filter, e.g. "telegram:Antti", "telegram:#work", "*:*"
+    pub filter: String,
+    /// Time spec, e.g. "7d", "2h", "2025-01-15"
+    pub since: Option<String>,
+    pub before: Option<String>,
+    pub sender: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl AnalysisQuery {
+    pub fn new(filter: impl Into<String>) -> Self {
+        Self {
+            filter: filter.into(),
+            ..Self::default()
+        }
+    }
+
+    async fn fetch(&self, manager: &SourcesManager, search: Option<&str>) -> Result<Vec<Message>> {
+        let (source_id, chat) = filter_parser::parse_source_filter(&self.filter)?;
+        let filter = MessageFilter {
+            chat,
+            since: self.since.as_deref().map(filter_parser::parse_time_spec).transpose()?,
+            before: self.before.as_deref().map(filter_parser::parse_time_spec).transpose()?,
+            sender: self.sender.clone(),
+            search: search.map(str::to_string),
+            limit: self.limit,
+            ..MessageFilter::new()
+        };
+        manager.query_messages(source_id.as_deref(), filter).await
+    }
+}
+
+/// Activity, response times and optionally sentiment per chat, with hours in `tz`
+pub async fn analyze<Tz: TimeZone>(
+    manager: &SourcesManager,
+    query: &AnalysisQuery,
+    tz: &Tz,
+    sentiment: bool,
+) -> Result<Vec<ChatAnalysis>> {
+    let messages = query.fetch(manager, None).await?;
+    Ok(analysis::analyze(&messages, tz, sentiment))
+}
+
+/// The messages and the topics they were segmented into
+pub async fn topics(manager: &SourcesManager, query: &AnalysisQuery) -> Result<(Vec<Message>, Vec<TopicSegment>)> {
+    let messages = query.fetch(manager, None).await?;
+    let segments = TopicSegmenter::new().segment(&messages);
+    Ok((messages, segments))
+}
+
+/// Messages posted more than once within `window` (a duration spec such as "24h")
+pub async fn duplicates(manager: &SourcesManager, query: &AnalysisQuery, window: &str) -> Result<Vec<DuplicateGroup>> {
+    let detector = DuplicateDetector::new().with_window(filter_parser::parse_duration_spec(window)?);
+    let messages = query.fetch(manager, None).await?;
+    Ok(detector.find_duplicates(&messages))
+}
+
+/// Unique links shared in the selected messages, without titles
+pub async fn links(manager: &SourcesManager, query: &AnalysisQuery) -> Result<Vec<LinkEntry>> {
+    let messages = query.fetch(manager, Some("http")).await?;
+    Ok(links::collect_links(&messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::{RecordedCall, SourceRecording};
+    use crate::types::{ChatId, MessageContent, MessageId, User, UserId};
+    use crate::ReplaySource;
+
+    fn message(id: &str, chat: &str, text: &str) -> Message {
+        Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new(chat),
+            sender: User {
+                id: UserId::new("u"),
+                username: None,
+                display_name: Some("Ann".to_string()),
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp: chrono::Utc::now(),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    fn manager_with(messages: Vec<Message>) -> SourcesManager {
+        let recording = SourceRecording {
+            source_name: "Mock".to_string(),
+            calls: vec![RecordedCall::GetMessages {
+                filter: MessageFilter::new(),
+                response: Ok(messages),
+            }],
+            ..SourceRecording::default()
+        };
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ReplaySource::new("mock", recording))).unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_and_links() {
+        let text = "Release 2.0 is out, grab it from https://example.com/downloads today";
+        let manager = manager_with(vec![message("1", "a", text), message("2", "b", text)]);
+        let query = AnalysisQuery::new("mock:*");
+
+        let groups = duplicates(&manager, &query, "24h").await.unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].chat_count(), 2);
+
+        let links = links(&manager, &query).await.unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].url, "https://example.com/downloads");
+    }
+
+    #[tokio::test]
+    async fn test_analyze_per_chat() {
+        let manager = manager_with(vec![message("1", "a", "hi"), message("2", "b", "hey")]);
+        let analyses = analyze(&manager, &AnalysisQuery::new("mock:*"), &chrono::Utc, false).await.unwrap();
+        assert_eq!(analyses.len(), 2);
+        assert!(analyses.iter().all(|chat| chat.message_count == 1 && chat.sentiment.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_bad_window() {
+        let manager = manager_with(Vec::new());
+        assert!(duplicates(&manager, &AnalysisQuery::new("mock:*"), "fortnight-ish").await.is_err());
+    }
+}
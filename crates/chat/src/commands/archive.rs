@@ -0,0 +1,78 @@
+use anyhow::Result;
+use std::future::Future;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::archive::ArchiveWriter;
+use crate::forget::ForgetLog;
+use crate::{BufferConfig, Message, OverflowPolicy};
+
+/// Subscription settings for archiving: never drop messages, a full buffer pauses the
+/// source instead
+pub fn buffer_config() -> BufferConfig {
+    BufferConfig {
+        policy: OverflowPolicy::Block,
+        ..BufferConfig::default()
+    }
+}
+
+/// Outcome of an archiving run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveReport {
+    /// Records appended by this run
+    pub archived: u64,
+    /// Hash of the newest record
+    pub head: String,
+}
+
+/// Append every `(source, message)` from `messages` to the archive, leaving out forgotten
+/// people's messages, until the channel closes or `stop` completes
+pub async fn run(
+    writer: &mut ArchiveWriter,
+    messages: &mut UnboundedReceiver<(String, Message)>,
+    forgotten: &ForgetLog,
+    stop: impl Future<Output = ()>,
+) -> Result<ArchiveReport> {
+    let start = writer.len();
+    tokio::pin!(stop);
+    loop {
+        tokio::select! {
+            received = messages.recv() => {
+                let Some((source, message)) = received else { break };
+                if forgotten.covers(&source, &message) {
+                    continue;
+                }
+                let record = writer.append(&source, &message)?;
+                log::debug!("Archived {}:{} as record {}", source, message.id.as_str(), record.seq);
+            }
+            _ = &mut stop => break,
+        }
+    }
+
+    Ok(ArchiveReport {
+        archived: writer.len() - start,
+        head: writer.head().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    #[tokio::test]
+    async fn test_run_until_closed() {
+        let dir = std::env::temp_dir().join(format!("chat-archive-run-{}", uuid::Uuid::new_v4().simple()));
+        let mut writer = ArchiveWriter::open(&dir).unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tx.send(("mock".to_string(), MessageBuilder::new("1", "c").build())).unwrap();
+        tx.send(("mock".to_string(), MessageBuilder::new("2", "c").build())).unwrap();
+        drop(tx);
+
+        let report = run(&mut writer, &mut rx, &ForgetLog::default(), std::future::pending()).await.unwrap();
+        assert_eq!(report.archived, 2);
+        assert_eq!(report.head, writer.head());
+        assert!(crate::archive::verify(&dir).unwrap().is_valid());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
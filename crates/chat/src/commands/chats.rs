@@ -0,0 +1,152 @@
+use anyhow::Result;
+use std::fmt;
+
+use crate::{Chat, ChatFilter, ChatPattern, ChatType, GlobalChatId, SourcesManager};
+
+/// Filters for listing a source's chats
+#[derive(Debug, Clone, Default)]
+pub struct ChatQuery {
+    /// Name pattern
+    pub name: Option<String>,
+    /// "direct", "group" or "channel"
+    pub chat_type: Option<String>,
+    /// Local label
+    pub label: Option<String>,
+}
+
+/// A chat with its global ID and local labels
+#[derive(Debug, Clone)]
+pub struct ChatEntry {
+    pub id: GlobalChatId,
+    pub chat: Chat,
+    pub labels: Vec<String>,
+}
+
+/// The requested source is not registered
+#[derive(Debug, Clone)]
+pub struct UnknownSource {
+    pub source: String,
+    /// IDs of the sources that are registered
+    pub available: Vec<String>,
+}
+
+impl fmt::Display for UnknownSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Source '{}' not found", self.source)
+    }
+}
+
+impl std::error::Error for UnknownSource {}
+
+/// Parse a chat type name as accepted by `chat chats --chat-type`
+pub fn parse_chat_type(name: &str) -> Result<ChatType> {
+    match name.to_lowercase().as_str() {
+        "direct" | "dm" => Ok(ChatType::DirectMessage),
+        "group" => Ok(ChatType::Group),
+        "channel" => Ok(ChatType::Channel),
+        _ => anyhow::bail!("Invalid chat type '{}'. Expected: direct, group, channel", name),
+    }
+}
+
+/// List a source's chats. Fails with [`UnknownSource`] if the source isn't registered.
+pub async fn list(manager: &SourcesManager, source: &str, query: ChatQuery) -> Result<Vec<ChatEntry>> {
+    if !manager.has_source(source) {
+        let available = manager.list_sources()?.into_iter().map(|s| s.id).collect();
+        return Err(UnknownSource { source: source.to_string(), available }.into());
+    }
+
+    let mut filter = ChatFilter::new();
+    if let Some(name) = query.name {
+        filter = filter.with_name(name);
+    }
+    if let Some(chat_type) = query.chat_type {
+        filter = filter.with_type(parse_chat_type(&chat_type)?);
+    }
+    if let Some(label) = query.label {
+        filter = filter.with_label(label);
+    }
+
+    let chats = manager.list_chats(source, Some(filter)).await?;
+    let labels = manager.labels()?;
+
+    Ok(chats
+        .into_iter()
+        .map(|chat| ChatEntry {
+            id: GlobalChatId::from_chat_id(source, &chat.id),
+            labels: labels.labels_for(source, &chat.id),
+            chat,
+        })
+        .collect())
+}
+
+/// Chats selected by a parsed `source:pattern` filter, with their source IDs.
+/// Without a source, every registered source is searched.
+pub async fn matching(
+    manager: &SourcesManager,
+    source_id: Option<&str>,
+    pattern: &ChatPattern,
+) -> Result<Vec<(String, Chat)>> {
+    let source_ids: Vec<String> = match source_id {
+        Some(id) => vec![id.to_string()],
+        None => manager.list_sources()?.into_iter().map(|s| s.id).collect(),
+    };
+
+    let chat_filter = match pattern {
+        ChatPattern::Label(label) => Some(ChatFilter::new().with_label(label.clone())),
+        _ => None,
+    };
+
+    let mut chats = Vec::new();
+    for source in source_ids {
+        for chat in manager.list_chats(&source, chat_filter.clone()).await? {
+            if chat_filter.is_some() || pattern.matches(&chat) {
+                chats.push((source.clone(), chat));
+            }
+        }
+    }
+
+    Ok(chats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unknown_source() {
+        let manager = SourcesManager::new();
+        let err = list(&manager, "nowhere", ChatQuery::default()).await.unwrap_err();
+        let unknown = err.downcast_ref::<UnknownSource>().unwrap();
+        assert_eq!(unknown.source, "nowhere");
+        assert!(unknown.available.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_matching() {
+        use crate::recording::{RecordedCall, SourceRecording};
+        use crate::test_utils::ChatBuilder;
+        use crate::ReplaySource;
+
+        let recording = SourceRecording {
+            source_name: "Mock".to_string(),
+            calls: vec![RecordedCall::ListChats {
+                filter: None,
+                response: Ok(vec![ChatBuilder::new("1").title("Work").build(), ChatBuilder::new("2").title("Home").build()]),
+            }],
+            ..SourceRecording::default()
+        };
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ReplaySource::new("mock", recording))).unwrap();
+
+        let chats = matching(&manager, None, &ChatPattern::Name("work".to_string())).await.unwrap();
+        assert_eq!(chats.len(), 1);
+        assert_eq!((chats[0].0.as_str(), chats[0].1.id.as_str()), ("mock", "1"));
+    }
+
+    #[test]
+    fn test_parse_chat_type() {
+        assert_eq!(parse_chat_type("DM").unwrap(), ChatType::DirectMessage);
+        assert_eq!(parse_chat_type("channel").unwrap(), ChatType::Channel);
+        assert!(parse_chat_type("forum").is_err());
+    }
+}
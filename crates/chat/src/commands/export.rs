@@ -0,0 +1,289 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::anonymize::Anonymizer;
+use crate::cli::OutputFormat;
+use crate::estimate::{archived_messages, estimate_export, ExportEstimate, RateLimit};
+use crate::sync::archive_file;
+use crate::{Chat, ChatPattern, GlobalChatId, Message, MessageFilter, SourcesManager, SyncState};
+
+/// Messages of each chat an export writes
+#[derive(Debug, Clone, Default)]
+pub struct ExportRange {
+    pub since: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+    /// Most recent messages per chat
+    pub limit: Option<usize>,
+}
+
+/// Destination of a dataset export, one file or directory for every chat
+pub trait DatasetWriter {
+    /// Write a chat and its messages, returning how many messages were new
+    fn add_chat(&mut self, source: &str, chat: &Chat, messages: &[Message]) -> Result<usize>;
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+#[cfg(feature = "sqlite")]
+impl DatasetWriter for crate::sqlite_export::SqliteExport {
+    fn add_chat(&mut self, source: &str, chat: &Chat, messages: &[Message]) -> Result<usize> {
+        crate::sqlite_export::SqliteExport::add_chat(self, source, chat, messages)
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        crate::sqlite_export::SqliteExport::finish(*self)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl DatasetWriter for crate::parquet_export::ParquetExport {
+    fn add_chat(&mut self, source: &str, chat: &Chat, messages: &[Message]) -> Result<usize> {
+        crate::parquet_export::ParquetExport::add_chat(self, source, chat, messages)
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer for a dataset `format` at `path`
+pub fn create_writer(format: OutputFormat, path: &Path) -> Result<Box<dyn DatasetWriter>> {
+    match format {
+        #[cfg(feature = "sqlite")]
+        OutputFormat::Sqlite => Ok(Box::new(crate::sqlite_export::SqliteExport::create(path)?)),
+        #[cfg(not(feature = "sqlite"))]
+        OutputFormat::Sqlite => {
+            let _ = path;
+            anyhow::bail!("SQLite export requires the 'sqlite' feature")
+        }
+        #[cfg(feature = "arrow")]
+        OutputFormat::Parquet => Ok(Box::new(crate::parquet_export::ParquetExport::create(path)?)),
+        #[cfg(not(feature = "arrow"))]
+        OutputFormat::Parquet => anyhow::bail!("Parquet export requires the 'arrow' feature"),
+        other => anyhow::bail!("{:?} is not a dataset format", other),
+    }
+}
+
+/// How one chat of a dataset export went
+#[derive(Debug)]
+pub enum DatasetEvent<'a> {
+    /// Written as `chat`, under its pseudonym when anonymizing
+    Written { chat: &'a Chat, messages: usize },
+    /// Left out because its messages couldn't be fetched
+    Skipped { source: &'a str, chat: &'a Chat, error: &'a anyhow::Error },
+}
+
+/// Write `chats` into one dataset, pseudonymized by `anonymizer` if given, and return
+/// how many messages were written
+pub async fn export_dataset(
+    manager: &SourcesManager,
+    chats: &[(String, Chat)],
+    range: &ExportRange,
+    anonymizer: Option<&Anonymizer>,
+    mut writer: Box<dyn DatasetWriter>,
+    mut on_event: impl FnMut(DatasetEvent<'_>),
+) -> Result<usize> {
+    let mut total = 0;
+    for (source, chat) in chats {
+        let filter = MessageFilter {
+            chat: ChatPattern::Id(chat.id.clone()),
+            since: range.since,
+            before: range.before,
+            limit: range.limit,
+            include_system: true,
+            in_attachments: false,
+            separate_merged: true,
+            ..MessageFilter::new()
+        };
+        let messages = match manager.query_messages(Some(source), filter).await {
+            Ok(messages) => messages,
+            Err(error) => {
+                on_event(DatasetEvent::Skipped { source, chat, error: &error });
+                continue;
+            }
+        };
+
+        let (chat, messages) = match anonymizer {
+            Some(anonymizer) => (anonymizer.chat(source, chat), anonymizer.messages(source, &messages)),
+            None => (chat.clone(), messages),
+        };
+        let added = writer.add_chat(source, &chat, &messages)?;
+        total += added;
+        on_event(DatasetEvent::Written { chat: &chat, messages: added });
+    }
+    writer.finish()?;
+    Ok(total)
+}
+
+/// What fetching `chats` would cost, sized from the sync archive in `archive_dir` where a
+/// chat was synced before, and from the history total its source reports otherwise. That
+/// total ignores `since` and `before`, so it's an upper bound. Chats neither can size are
+/// left unknown. Sources are fetched one after another, each listed once.
+pub async fn estimate(
+    manager: &SourcesManager,
+    chats: &[(String, Chat)],
+    archive_dir: &Path,
+    since: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+    page_size: usize,
+) -> Result<ExportEstimate> {
+    let mut sizes: BTreeMap<&str, Vec<Option<usize>>> = BTreeMap::new();
+    for (source, chat) in chats {
+        let path = archive_file(archive_dir, &GlobalChatId::from_chat_id(source, &chat.id));
+        let size = match archived_messages(&path, since, before)? {
+            Some(archived) => Some(archived),
+            None => manager.message_count(source, &chat.id).await.unwrap_or_else(|e| {
+                log::debug!("No message count for {}:{}: {}", source, chat.id, e);
+                None
+            }),
+        };
+        sizes.entry(source).or_default().push(size);
+    }
+
+    Ok(sizes
+        .into_iter()
+        .map(|(source, sizes)| estimate_export(&sizes, limit, page_size, 1, RateLimit::for_source(source)))
+        .sum())
+}
+
+/// What `chat sync` would do
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncPreview {
+    /// New messages can't be counted without asking the source, so every chat is of unknown size
+    pub estimate: ExportEstimate,
+    /// Chats continuing from a checkpoint; the rest start from `--since`
+    pub resuming: usize,
+}
+
+/// Preview syncing `chats` from their checkpoints in `state`
+pub fn preview_sync(chats: &[(String, Chat)], state: &SyncState, page_size: usize) -> SyncPreview {
+    let resuming = chats
+        .iter()
+        .filter(|(source, chat)| state.checkpoint(&GlobalChatId::from_chat_id(source, &chat.id)).is_some())
+        .count();
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for (source, _) in chats {
+        *counts.entry(source).or_default() += 1;
+    }
+    let estimate = counts
+        .into_iter()
+        .map(|(source, count)| estimate_export(&vec![None; count], None, page_size, 1, RateLimit::for_source(source)))
+        .sum();
+
+    SyncPreview { estimate, resuming }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::{RecordedCall, SourceRecording};
+    use crate::test_utils::{ChatBuilder, InMemorySource, MessageBuilder};
+    use crate::{Checkpoint, MessageId, ReplaySource};
+    use std::sync::{Arc, Mutex};
+
+    /// Records the chats and message counts it's given
+    struct Collect(Arc<Mutex<Vec<(String, usize)>>>);
+
+    impl DatasetWriter for Collect {
+        fn add_chat(&mut self, _source: &str, chat: &Chat, messages: &[Message]) -> Result<usize> {
+            self.0.lock().unwrap().push((chat.id.to_string(), messages.len()));
+            Ok(messages.len())
+        }
+
+        fn finish(self: Box<Self>) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_dataset() {
+        let recording = SourceRecording {
+            source_name: "Mock".to_string(),
+            calls: vec![RecordedCall::GetMessages {
+                filter: MessageFilter::new(),
+                response: Ok(vec![MessageBuilder::new("1", "a").build(), MessageBuilder::new("2", "a").build()]),
+            }],
+            ..SourceRecording::default()
+        };
+        let broken = SourceRecording {
+            source_name: "Broken".to_string(),
+            calls: vec![RecordedCall::GetMessages {
+                filter: MessageFilter::new(),
+                response: Err("offline".to_string()),
+            }],
+            ..SourceRecording::default()
+        };
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ReplaySource::new("mock", recording))).unwrap();
+        manager.register(Box::new(ReplaySource::new("broken", broken))).unwrap();
+
+        let chats = vec![
+            ("mock".to_string(), ChatBuilder::new("a").build()),
+            ("broken".to_string(), ChatBuilder::new("b").build()),
+        ];
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let mut skipped = Vec::new();
+        let writer = Box::new(Collect(Arc::clone(&written)));
+        let total = export_dataset(&manager, &chats, &ExportRange::default(), None, writer, |event| {
+            if let DatasetEvent::Skipped { chat, .. } = event {
+                skipped.push(chat.id.to_string());
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(total, 2);
+        assert_eq!(*written.lock().unwrap(), vec![("a".to_string(), 2)]);
+        assert_eq!(skipped, vec!["b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_estimate() {
+        let dir = std::env::temp_dir().join(format!("chat-export-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let synced = ChatBuilder::new("1").build();
+        let line = serde_json::json!({ "timestamp": Utc::now() }).to_string();
+        std::fs::write(archive_file(&dir, &GlobalChatId::from_chat_id("mem", &synced.id)), format!("{line}\n{line}\n")).unwrap();
+
+        // Chat 2 was never synced, so its size comes from the source
+        let manager = SourcesManager::new();
+        let source = InMemorySource::new("mem").with_messages(vec![
+            MessageBuilder::new("1", "2").build(),
+            MessageBuilder::new("2", "2").build(),
+            MessageBuilder::new("3", "2").build(),
+        ]);
+        manager.register(Box::new(source)).unwrap();
+
+        let chats = vec![
+            ("mem".to_string(), synced),
+            ("mem".to_string(), ChatBuilder::new("2").build()),
+            ("whatsapp".to_string(), ChatBuilder::new("3").build()),
+        ];
+        let estimate = estimate(&manager, &chats, &dir, None, None, None, 100).await.unwrap();
+        assert_eq!((estimate.chats, estimate.messages, estimate.unknown_chats), (3, 5, 1));
+        // Each source is listed once, then one page for each chat
+        assert_eq!(estimate.api_calls, 5);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_preview_sync() {
+        let chats = vec![
+            ("telegram".to_string(), ChatBuilder::new("1").build()),
+            ("telegram".to_string(), ChatBuilder::new("2").build()),
+        ];
+        let mut state = SyncState::new();
+        state.set_checkpoint(GlobalChatId::new("telegram", "1"), Checkpoint {
+            last_message_id: MessageId::new("9"),
+            last_timestamp: Utc::now(),
+            synced_at: Utc::now(),
+        });
+
+        let preview = preview_sync(&chats, &state, 100);
+        assert_eq!(preview.resuming, 1);
+        assert_eq!((preview.estimate.unknown_chats, preview.estimate.api_calls), (2, 3));
+    }
+}
@@ -0,0 +1,631 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::export::ExportRange;
+use crate::anonymize::Anonymizer;
+use crate::cli::formatters;
+use crate::cli::OutputFormat;
+use crate::estimate::ExportEstimate;
+use crate::{Chat, ChatPattern, Message, MessageFilter, Progress, ProgressEvent, SourcesManager};
+
+/// Messages requested per page; each page costs one request from the rate budget
+pub const PAGE_SIZE: usize = 100;
+
+/// Where and how `chat export-all` writes
+#[derive(Debug, Clone)]
+pub struct ExportAllOptions {
+    /// The source:pattern filter, recorded in the manifest
+    pub filter: String,
+    pub output_dir: PathBuf,
+    /// A per-chat format; the dataset formats go through `export::export_dataset`
+    pub format: OutputFormat,
+    pub range: ExportRange,
+    /// Chats exported at the same time
+    pub concurrency: usize,
+    /// Requests per second shared by all chats
+    pub rate: f64,
+}
+
+/// One chat to export
+#[derive(Debug, Clone)]
+pub struct ExportTarget {
+    pub source: String,
+    pub chat: Chat,
+    /// The chat as written out, under its pseudonym when anonymizing
+    pub written_as: Chat,
+    /// Where an interrupted export of this chat left off
+    pub resume: Option<ResumePoint>,
+}
+
+impl ExportTarget {
+    /// Name shown for the chat, its title or else its ID
+    pub fn label(&self) -> String {
+        self.written_as.title.clone().unwrap_or_else(|| self.written_as.id.to_string())
+    }
+}
+
+/// Partial export to continue from
+#[derive(Debug, Clone)]
+pub struct ResumePoint {
+    pub before: DateTime<Utc>,
+    /// Messages already written; only JSON exports can be read back and extended
+    pub messages: Vec<Message>,
+}
+
+/// Outcome of exporting one chat
+struct ChatExport {
+    messages: usize,
+    complete: bool,
+    resume_before: Option<DateTime<Utc>>,
+}
+
+/// Manifest entry describing what was written for a chat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub source: String,
+    pub chat_id: String,
+    pub title: Option<String>,
+    pub file: Option<String>,
+    pub messages: usize,
+    pub error: Option<String>,
+    /// False when the export was interrupted before reaching the end of the chat
+    #[serde(default = "default_complete")]
+    pub complete: bool,
+    /// Resume marker: older messages still need to be fetched from before this time
+    #[serde(default)]
+    pub resume_before: Option<DateTime<Utc>>,
+}
+
+impl ManifestEntry {
+    fn new(target: &ExportTarget) -> Self {
+        Self {
+            source: target.source.clone(),
+            chat_id: target.written_as.id.to_string(),
+            title: target.written_as.title.clone(),
+            file: None,
+            messages: 0,
+            error: None,
+            complete: false,
+            resume_before: None,
+        }
+    }
+
+    /// Whether a `--resume` run can leave this chat alone
+    pub fn is_done(&self) -> bool {
+        self.complete && self.error.is_none()
+    }
+}
+
+fn default_complete() -> bool {
+    true
+}
+
+/// Summary written to `manifest.json` in the output directory
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub filter: String,
+    pub format: OutputFormat,
+    pub since: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    /// Set when the export was interrupted with Ctrl+C
+    #[serde(default)]
+    pub cancelled: bool,
+    pub chats: Vec<ManifestEntry>,
+}
+
+impl ExportManifest {
+    /// Where the manifest of an export into `output_dir` lives
+    pub fn path(output_dir: &Path) -> PathBuf {
+        output_dir.join("manifest.json")
+    }
+
+    /// Read the manifest of a previous run, if there is one
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(path).context("Failed to read manifest")?;
+        let manifest = serde_json::from_str(&content).context("Failed to parse manifest")?;
+        Ok(Some(manifest))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?).context("Failed to write manifest")
+    }
+
+    /// Chats that got a file
+    pub fn exported(&self) -> usize {
+        self.chats.iter().filter(|e| e.file.is_some()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.chats.iter().filter(|e| e.error.is_some()).count()
+    }
+
+    /// Chats an interrupt left for `--resume`
+    pub fn incomplete(&self) -> usize {
+        self.chats.iter().filter(|e| !e.complete && e.error.is_none()).count()
+    }
+
+    pub fn total_messages(&self) -> usize {
+        self.chats.iter().map(|e| e.messages).sum()
+    }
+}
+
+/// Chats left to export, and the entries of those a previous run finished
+#[derive(Debug)]
+pub struct ExportPlan {
+    pub targets: Vec<ExportTarget>,
+    pub carried: Vec<ManifestEntry>,
+}
+
+/// What an export reports about each chat as it goes. `index` is the chat's position
+/// in the plan's targets.
+#[derive(Debug)]
+pub enum ExportAllEvent<'a> {
+    Started { index: usize, target: &'a ExportTarget },
+    /// A page is being fetched; `count` is how much of it has arrived
+    Fetching { index: usize, count: usize },
+    /// New messages came in, including those read back when resuming
+    Fetched { index: usize, new: usize },
+    /// Done with the chat: complete, interrupted (`complete` unset) or failed (`error` set)
+    Finished { index: usize, entry: &'a ManifestEntry },
+}
+
+/// Global request budget shared by all concurrent exports
+struct RateBudget {
+    interval: tokio::sync::Mutex<tokio::time::Interval>,
+}
+
+impl RateBudget {
+    fn new(requests_per_second: f64) -> Self {
+        let period = std::time::Duration::from_secs_f64(1.0 / requests_per_second.max(0.01));
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        Self {
+            interval: tokio::sync::Mutex::new(interval),
+        }
+    }
+
+    /// Wait until the next request may be sent
+    async fn acquire(&self) {
+        self.interval.lock().await.tick().await;
+    }
+}
+
+/// Resolve the source:pattern filter to concrete chats
+pub async fn targets(
+    manager: &SourcesManager,
+    source_id: Option<&str>,
+    pattern: &ChatPattern,
+    anonymizer: Option<&Anonymizer>,
+) -> Result<Vec<ExportTarget>> {
+    let targets = super::chats::matching(manager, source_id, pattern)
+        .await?
+        .into_iter()
+        .map(|(source, chat)| ExportTarget {
+            written_as: match anonymizer {
+                Some(anonymizer) => anonymizer.chat(&source, &chat),
+                None => chat.clone(),
+            },
+            source,
+            chat,
+            resume: None,
+        })
+        .collect();
+
+    Ok(targets)
+}
+
+/// Split `targets` by what a previous run did: chats it finished keep their manifest
+/// entries, interrupted JSON exports continue where they stopped, the rest start over
+pub fn plan(targets: Vec<ExportTarget>, previous: Option<ExportManifest>, format: OutputFormat) -> ExportPlan {
+    let Some(previous) = previous else {
+        return ExportPlan { targets, carried: Vec::new() };
+    };
+
+    let mut by_chat: HashMap<(String, String), ManifestEntry> = previous
+        .chats
+        .into_iter()
+        .map(|e| ((e.source.clone(), e.chat_id.clone()), e))
+        .collect();
+
+    let mut plan = ExportPlan { targets: Vec::new(), carried: Vec::new() };
+    for mut target in targets {
+        match by_chat.remove(&(target.source.clone(), target.written_as.id.to_string())) {
+            Some(entry) if entry.is_done() => plan.carried.push(entry),
+            Some(entry) => {
+                target.resume = resume_point(&entry, format);
+                plan.targets.push(target);
+            }
+            None => plan.targets.push(target),
+        }
+    }
+    plan
+}
+
+/// What exporting `targets` would cost, also waiting on the shared `rate` budget
+pub async fn estimate(
+    manager: &SourcesManager,
+    targets: &[ExportTarget],
+    archive_dir: &Path,
+    range: &ExportRange,
+    rate: f64,
+) -> Result<ExportEstimate> {
+    let chats: Vec<(String, Chat)> = targets.iter().map(|t| (t.source.clone(), t.chat.clone())).collect();
+    let mut estimate =
+        super::export::estimate(manager, &chats, archive_dir, range.since, range.before, range.limit, PAGE_SIZE)
+            .await?;
+    let budget = std::time::Duration::from_secs_f64(estimate.api_calls as f64 / rate.max(0.01));
+    estimate.delay = estimate.delay.max(budget);
+    Ok(estimate)
+}
+
+/// Export every chat of `plan` into its own file and write the manifest, carrying over
+/// the entries of chats a previous run finished. Cancelling `cancel` lets running exports
+/// finish their current page and write what they have; chats not started yet are left
+/// for `--resume`.
+pub async fn export_all(
+    manager: &SourcesManager,
+    plan: ExportPlan,
+    options: &ExportAllOptions,
+    anonymizer: Option<Rc<Anonymizer>>,
+    cancel: &Progress,
+    on_event: impl Fn(ExportAllEvent<'_>) + 'static,
+) -> Result<ExportManifest> {
+    std::fs::create_dir_all(&options.output_dir)
+        .with_context(|| format!("Failed to create output directory: {}", options.output_dir.display()))?;
+
+    let started = Utc::now();
+    let on_event: Rc<dyn Fn(ExportAllEvent<'_>)> = Rc::new(on_event);
+    let budget = Rc::new(RateBudget::new(options.rate));
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(options.concurrency.max(1)));
+    let file_extension = extension(options.format);
+
+    // The rate budget is shared through an Rc, so exports run as local tasks on this thread
+    let local = tokio::task::LocalSet::new();
+    let entries = local
+        .run_until(async {
+            let mut handles = Vec::new();
+            let mut skipped = Vec::new();
+
+            for (index, target) in plan.targets.into_iter().enumerate() {
+                let permit = Arc::clone(&semaphore).acquire_owned().await?;
+
+                // Chats not started before an interrupt are left for --resume
+                if cancel.is_cancelled() {
+                    let mut entry = ManifestEntry::new(&target);
+                    entry.resume_before = options.range.before;
+                    skipped.push(entry);
+                    continue;
+                }
+
+                on_event(ExportAllEvent::Started { index, target: &target });
+
+                let manager = manager.clone();
+                let budget = Rc::clone(&budget);
+                let cancel = cancel.clone();
+                let anonymizer = anonymizer.clone();
+                let on_event = Rc::clone(&on_event);
+                let format = options.format;
+                let range = options.range.clone();
+                let path = options.output_dir.join(file_name(&target, file_extension));
+
+                handles.push(tokio::task::spawn_local(async move {
+                    let _permit = permit;
+                    let result = export_chat(
+                        &manager,
+                        &budget,
+                        &target,
+                        &path,
+                        format,
+                        &range,
+                        anonymizer.as_deref(),
+                        &cancel,
+                        index,
+                        &*on_event,
+                    )
+                    .await;
+
+                    let mut entry = ManifestEntry::new(&target);
+                    match result {
+                        Ok(export) => {
+                            entry.file = (export.messages > 0).then(|| path.display().to_string());
+                            entry.messages = export.messages;
+                            entry.complete = export.complete;
+                            entry.resume_before = export.resume_before;
+                        }
+                        Err(e) => entry.error = Some(e.to_string()),
+                    }
+
+                    on_event(ExportAllEvent::Finished { index, entry: &entry });
+                    entry
+                }));
+            }
+
+            let mut entries = Vec::new();
+            for handle in handles {
+                entries.push(handle.await?);
+            }
+            entries.extend(skipped);
+            Ok::<_, anyhow::Error>(entries)
+        })
+        .await?;
+
+    let mut chats = plan.carried;
+    chats.extend(entries);
+    let manifest = ExportManifest {
+        filter: options.filter.clone(),
+        format: options.format,
+        since: options.range.since,
+        before: options.range.before,
+        started,
+        finished: Utc::now(),
+        cancelled: cancel.is_cancelled(),
+        chats,
+    };
+    manifest.save(&ExportManifest::path(&options.output_dir))?;
+    Ok(manifest)
+}
+
+/// Continue a partial JSON export where it stopped; other formats are exported again
+fn resume_point(entry: &ManifestEntry, format: OutputFormat) -> Option<ResumePoint> {
+    if !matches!(format, OutputFormat::Json) {
+        return None;
+    }
+
+    let before = entry.resume_before?;
+    let content = std::fs::read_to_string(entry.file.as_ref()?).ok()?;
+    let messages = serde_json::from_str(&content).ok()?;
+
+    Some(ResumePoint { before, messages })
+}
+
+/// Fetch a chat page by page within the rate budget and write it out.
+/// On cancellation the current page is finished and the partial result is written.
+#[allow(clippy::too_many_arguments)]
+async fn export_chat(
+    manager: &SourcesManager,
+    budget: &RateBudget,
+    target: &ExportTarget,
+    path: &Path,
+    format: OutputFormat,
+    range: &ExportRange,
+    anonymizer: Option<&Anonymizer>,
+    cancel: &Progress,
+    index: usize,
+    on_event: &dyn Fn(ExportAllEvent<'_>),
+) -> Result<ChatExport> {
+    let max = range.limit.unwrap_or(usize::MAX);
+    let mut filter = MessageFilter {
+        chat: ChatPattern::Id(target.chat.id.clone()),
+        since: range.since,
+        before: target.resume.as_ref().map(|r| r.before).or(range.before),
+        limit: Some(PAGE_SIZE.min(max)),
+        separate_merged: true,
+        ..MessageFilter::new()
+    };
+
+    let mut messages: Vec<Message> = target.resume.as_ref().map(|r| r.messages.clone()).unwrap_or_default();
+    // Messages read back from an anonymized export already are
+    let resumed = messages.len();
+    let mut seen: HashSet<_> = messages.iter().map(|m| m.id.clone()).collect();
+    on_event(ExportAllEvent::Fetched { index, new: resumed });
+
+    // Progress callbacks must be Send, so page progress comes back over a channel
+    let (progress, mut page_events) = cancel.channel();
+
+    let mut complete = false;
+    while !cancel.is_cancelled() {
+        budget.acquire().await;
+        let page = {
+            let fetch = manager.query_messages_with_progress(Some(&target.source), filter.clone(), &progress);
+            tokio::pin!(fetch);
+            loop {
+                tokio::select! {
+                    page = &mut fetch => break page?,
+                    Some(event) = page_events.recv() => {
+                        if let ProgressEvent::Fetched { count, .. } = event {
+                            on_event(ExportAllEvent::Fetching { index, count });
+                        }
+                    }
+                }
+            }
+        };
+        let fetched = page.len();
+        let oldest = page.iter().map(|m| m.timestamp).min();
+
+        // Pages overlap by one second so messages sharing a timestamp aren't lost
+        let new: Vec<_> = page.into_iter().filter(|m| seen.insert(m.id.clone())).collect();
+        on_event(ExportAllEvent::Fetched { index, new: new.len() });
+
+        let exhausted = new.is_empty() || fetched < PAGE_SIZE;
+        messages.extend(new);
+
+        // A page cut short by cancellation doesn't mean the chat is exhausted
+        if cancel.is_cancelled() {
+            break;
+        }
+        if exhausted || messages.len() >= max {
+            complete = true;
+            break;
+        }
+        filter.before = oldest.map(|t| t + Duration::seconds(1));
+    }
+
+    messages.truncate(max);
+    let resumed = resumed.min(messages.len());
+
+    // Fetched messages carry their real timestamps, resumed anonymized ones may be shifted
+    let resume_before = if complete {
+        None
+    } else {
+        messages[resumed..].iter().map(|m| m.timestamp).min().map(|t| t + Duration::seconds(1)).or(filter.before)
+    };
+
+    if !messages.is_empty() {
+        if let Some(anonymizer) = anonymizer {
+            let fetched = anonymizer.messages(&target.source, &messages[resumed..]);
+            messages.truncate(resumed);
+            messages.extend(fetched);
+        }
+        let formatted = formatters::format_messages(&messages, format)?;
+        std::fs::write(path, formatted)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    Ok(ChatExport {
+        messages: messages.len(),
+        complete,
+        resume_before,
+    })
+}
+
+fn extension(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Json => "json",
+        OutputFormat::Csv => "csv",
+        OutputFormat::Text | OutputFormat::Compact => "txt",
+        OutputFormat::Sqlite => "db",
+        OutputFormat::Parquet => "parquet",
+    }
+}
+
+/// `<source>_<chat id>_<title-slug>.<ext>`
+fn file_name(target: &ExportTarget, extension: &str) -> String {
+    let slug: String = target
+        .chat
+        .title
+        .as_deref()
+        .unwrap_or("")
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .chars()
+        .take(40)
+        .collect();
+
+    if slug.is_empty() {
+        format!("{}_{}.{}", target.source, target.chat.id, extension)
+    } else {
+        format!("{}_{}_{}.{}", target.source, target.chat.id, slug, extension)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::{RecordedCall, SourceRecording};
+    use crate::test_utils::{ChatBuilder, MessageBuilder};
+    use crate::ReplaySource;
+    use std::cell::RefCell;
+
+    fn target(id: &str, title: &str) -> ExportTarget {
+        let chat = ChatBuilder::new(id).title(title).build();
+        ExportTarget {
+            source: "mock".to_string(),
+            written_as: chat.clone(),
+            chat,
+            resume: None,
+        }
+    }
+
+    fn entry(chat_id: &str, complete: bool) -> ManifestEntry {
+        ManifestEntry {
+            complete,
+            ..ManifestEntry::new(&target(chat_id, ""))
+        }
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name(&target("42", "Team Chat!"), "json"), "mock_42_team-chat.json");
+        assert_eq!(file_name(&target("42", "?!"), "txt"), "mock_42.txt");
+    }
+
+    #[test]
+    fn test_plan_carries_finished_chats() {
+        let previous = ExportManifest {
+            filter: "mock:*".to_string(),
+            format: OutputFormat::Json,
+            since: None,
+            before: None,
+            started: Utc::now(),
+            finished: Utc::now(),
+            cancelled: true,
+            chats: vec![entry("a", true), entry("b", false)],
+        };
+        let targets = vec![target("a", "A"), target("b", "B"), target("c", "C")];
+
+        let plan = plan(targets, Some(previous), OutputFormat::Json);
+        let left: Vec<_> = plan.targets.iter().map(|t| t.chat.id.to_string()).collect();
+        assert_eq!(left, vec!["b", "c"]);
+        assert_eq!(plan.carried.len(), 1);
+        assert_eq!(plan.carried[0].chat_id, "a");
+    }
+
+    #[tokio::test]
+    async fn test_export_all_writes_files_and_manifest() {
+        let dir = std::env::temp_dir().join(format!("chat-export-all-{}", uuid::Uuid::new_v4().simple()));
+        let recording = SourceRecording {
+            source_name: "Mock".to_string(),
+            calls: vec![RecordedCall::GetMessages {
+                filter: MessageFilter::new(),
+                response: Ok(vec![
+                    MessageBuilder::new("1", "a").minute(1).build(),
+                    MessageBuilder::new("2", "a").minute(2).build(),
+                ]),
+            }],
+            ..SourceRecording::default()
+        };
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ReplaySource::new("mock", recording))).unwrap();
+
+        let options = ExportAllOptions {
+            filter: "mock:*".to_string(),
+            output_dir: dir.clone(),
+            format: OutputFormat::Json,
+            range: ExportRange::default(),
+            concurrency: 2,
+            rate: 100.0,
+        };
+        let plan = ExportPlan { targets: vec![target("a", "Team")], carried: vec![entry("old", true)] };
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&events);
+        let manifest = export_all(&manager, plan, &options, None, &Progress::new(), move |event| {
+            seen.borrow_mut().push(match event {
+                ExportAllEvent::Started { .. } => "started",
+                ExportAllEvent::Fetching { .. } => "fetching",
+                ExportAllEvent::Fetched { .. } => "fetched",
+                ExportAllEvent::Finished { .. } => "finished",
+            })
+        })
+        .await
+        .unwrap();
+
+        assert_eq!((manifest.exported(), manifest.failed(), manifest.total_messages()), (1, 0, 2));
+        assert_eq!(manifest.chats.len(), 2);
+        assert_eq!(events.borrow().first(), Some(&"started"));
+        assert_eq!(events.borrow().last(), Some(&"finished"));
+
+        let saved = ExportManifest::load(&ExportManifest::path(&dir)).unwrap().unwrap();
+        assert!(saved.chats.iter().all(ManifestEntry::is_done));
+        let written = std::fs::read_to_string(dir.join("mock_a_team.json")).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Message>>(&written).unwrap().len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crate::mcp_selftest::{format_tool_response, run_selftest, schema_type, Check, McpClient, RecordedCall};
+
+/// Outcome of `chat mcp selftest`
+#[derive(Debug)]
+pub struct SelftestReport {
+    /// The server binary that was tested
+    pub server: PathBuf,
+    pub checks: Vec<Check>,
+}
+
+impl SelftestReport {
+    pub fn failed(&self) -> usize {
+        self.checks.iter().filter(|c| !c.passed).count()
+    }
+}
+
+/// One argument of a tool's input schema
+#[derive(Debug, Clone, Copy)]
+pub struct ToolArgument<'a> {
+    pub name: &'a str,
+    pub property: &'a Value,
+    pub required: bool,
+}
+
+impl ToolArgument<'_> {
+    /// `name (type, required): description [one of ...]`
+    pub fn describe(&self) -> String {
+        let mut text = format!("{} ({}", self.name, schema_type(self.property).unwrap_or("any"));
+        if self.required {
+            text.push_str(", required");
+        }
+        text.push(')');
+        if let Some(description) = self.property["description"].as_str() {
+            text.push_str(&format!(": {}", description));
+        }
+        if let Some(allowed) = self.property["enum"].as_array() {
+            let names: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            text.push_str(&format!(" [one of {}]", names.join(", ")));
+        }
+        text
+    }
+}
+
+/// Outcome of one tool call
+#[derive(Debug, Clone)]
+pub struct ToolResult {
+    pub response: Value,
+    /// The response for reading, see `format_tool_response`
+    pub text: String,
+    /// Whether the call failed, as a JSON-RPC error or a tool error
+    pub is_error: bool,
+    pub elapsed: Duration,
+}
+
+/// `chat-mcp-server` next to the running binary, falling back to PATH
+pub fn default_server_path() -> PathBuf {
+    let name = format!("chat-mcp-server{}", std::env::consts::EXE_SUFFIX);
+
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&name)))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// Start the server binary at `server` as a child talking over stdio
+pub fn spawn_server(server: &Path) -> Result<McpClient> {
+    McpClient::spawn(Command::new(server)).with_context(|| {
+        format!(
+            "Failed to start {} (build it with: cargo build --features mcp --bin chat-mcp-server)",
+            server.display()
+        )
+    })
+}
+
+/// Start the server (default: `default_server_path`) and run the self-test against it
+pub fn selftest(server: Option<PathBuf>) -> Result<SelftestReport> {
+    let server = server.unwrap_or_else(default_server_path);
+    let mut client = spawn_server(&server)?;
+    let checks = run_selftest(&mut client);
+    Ok(SelftestReport { server, checks })
+}
+
+/// The tool called `name` among those from tools/list
+pub fn find_tool<'a>(tools: &'a [Value], name: &str) -> Result<&'a Value> {
+    tools
+        .iter()
+        .find(|tool| tool["name"] == name)
+        .with_context(|| format!("No tool named '{}'. Type 'tools' to list them", name))
+}
+
+/// Arguments of an input schema, in schema order
+pub fn arguments(schema: &Value) -> Vec<ToolArgument<'_>> {
+    let required = |name: &str| schema["required"].as_array().is_some_and(|required| required.iter().any(|r| r == name));
+    schema["properties"]
+        .as_object()
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(name, property)| ToolArgument { name, property, required: required(name) })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Make a tool call, timing it
+pub fn call_tool(client: &mut McpClient, call: &RecordedCall) -> Result<ToolResult> {
+    let started = Instant::now();
+    let response = client.call_tool(&call.tool, call.arguments.clone())?;
+    let elapsed = started.elapsed();
+
+    Ok(ToolResult {
+        text: format_tool_response(&response),
+        is_error: response.get("error").is_some() || response["result"]["isError"] == true,
+        response,
+        elapsed,
+    })
+}
+
+/// Calls saved with `save_call`, skipping blank lines
+pub fn load_script(path: &Path) -> Result<Vec<RecordedCall>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line).with_context(|| format!("{}:{}: not a saved call", path.display(), number + 1))
+        })
+        .collect()
+}
+
+/// Append a call to a script file, to replay later with `load_script`
+pub fn save_call(path: &Path, call: &RecordedCall) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(call)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_arguments() {
+        let schema = json!({
+            "properties": {
+                "chat": { "type": "string", "description": "Chat ID" },
+                "kind": { "type": ["string", "null"], "enum": ["group", "channel"] },
+            },
+            "required": ["chat"],
+        });
+        let described: Vec<String> = arguments(&schema).iter().map(ToolArgument::describe).collect();
+        assert_eq!(described, vec![
+            "chat (string, required): Chat ID".to_string(),
+            "kind (string) [one of \"group\", \"channel\"]".to_string(),
+        ]);
+        assert!(arguments(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_script_round_trip() {
+        let path = std::env::temp_dir().join(format!("chat-mcp-script-{}.jsonl", uuid::Uuid::new_v4().simple()));
+        let calls = vec![
+            RecordedCall { tool: "list_sources".to_string(), arguments: json!({}) },
+            RecordedCall { tool: "list_chats".to_string(), arguments: json!({ "source": "mock" }) },
+        ];
+        for call in &calls {
+            save_call(&path, call).unwrap();
+        }
+        assert_eq!(load_script(&path).unwrap(), calls);
+
+        std::fs::write(&path, "{\"tool\":\"a\"}\n\nnot json\n").unwrap();
+        let error = load_script(&path).unwrap_err().to_string();
+        assert!(error.ends_with(":3: not a saved call"), "{}", error);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
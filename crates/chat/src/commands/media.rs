@@ -0,0 +1,355 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::PathBuf;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::forget::ForgetLog;
+use crate::media_text::{self, DerivedText, MediaProcessor, MediaTextStore};
+use crate::{ChatPattern, ContentType, GlobalChatId, Message, MessageFilter, SourcesManager};
+
+/// Attachments `chat media` looks at
+#[derive(Debug, Clone)]
+pub struct MediaFilter {
+    /// One source, or every source when None
+    pub source_id: Option<String>,
+    pub chat: ChatPattern,
+    /// Attachments newer than this; following only sees new messages, so it ignores it
+    pub since: DateTime<Utc>,
+}
+
+/// What media processing reports as it goes
+#[derive(Debug)]
+pub enum MediaEvent<'a> {
+    /// A source couldn't be queried and is left out
+    SourceFailed { source: &'a str, error: &'a anyhow::Error },
+    /// The attachment's file couldn't be fetched
+    Unavailable { message: &'a Message, error: &'a anyhow::Error },
+    Extracted { source: &'a str, message: &'a Message, derived: &'a DerivedText },
+    /// A processor failed on the attachment
+    Failed { message: &'a Message, error: &'a anyhow::Error },
+}
+
+/// What happened to one message's attachment
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MessageOutcome {
+    pub extracted: usize,
+    pub unavailable: bool,
+    pub failed: usize,
+}
+
+/// Outcome of `chat media process`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProcessReport {
+    /// Attachments looked at
+    pub checked: usize,
+    /// Texts derived
+    pub extracted: usize,
+    /// Attachments whose file the source doesn't provide or couldn't be fetched
+    pub unavailable: usize,
+    pub failed: usize,
+}
+
+/// Processors run over attachments, storing what they derive in the store at `store_path`
+pub struct MediaPipeline {
+    pub processors: Vec<Box<dyn MediaProcessor>>,
+    pub store_path: PathBuf,
+}
+
+impl MediaPipeline {
+    /// Content types the processors handle
+    pub fn content_types(&self) -> Vec<ContentType> {
+        let mut types = Vec::new();
+        for content_type in self.processors.iter().map(|p| p.kind().content_type()) {
+            if !types.contains(&content_type) {
+                types.push(content_type);
+            }
+        }
+        types
+    }
+
+    /// Run the processors over matching attachments, skipping those already handled
+    /// unless `force` is set. The store is saved after every message that got text, so
+    /// an interrupted run keeps its work.
+    pub async fn process(
+        &self,
+        manager: &SourcesManager,
+        filter: &MediaFilter,
+        force: bool,
+        forgotten: &ForgetLog,
+        mut on_event: impl FnMut(MediaEvent<'_>),
+    ) -> Result<ProcessReport> {
+        let mut store = MediaTextStore::load_from(&self.store_path)?;
+        let messages = media_messages(manager, filter, self.content_types(), forgotten, &mut on_event).await?;
+
+        let mut report = ProcessReport { checked: messages.len(), ..ProcessReport::default() };
+        for (source, message) in &messages {
+            let outcome = self.process_message(&mut store, source, message, force, &mut on_event).await;
+            report.extracted += outcome.extracted;
+            report.failed += outcome.failed;
+            if outcome.unavailable {
+                report.unavailable += 1;
+            }
+            if outcome.extracted > 0 {
+                store.save_to(&self.store_path)?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Process the attachments of new messages from `messages` until the channel closes
+    /// or `stop` completes. Chats matching the filter are resolved up front, so chats
+    /// created while following aren't picked up.
+    pub async fn follow(
+        &self,
+        manager: &SourcesManager,
+        filter: &MediaFilter,
+        forgotten: &ForgetLog,
+        messages: &mut UnboundedReceiver<(String, Message)>,
+        stop: impl Future<Output = ()>,
+        mut on_event: impl FnMut(MediaEvent<'_>),
+    ) -> Result<()> {
+        let chats: Option<HashSet<GlobalChatId>> = match filter.chat {
+            ChatPattern::All => None,
+            _ => Some(
+                super::chats::matching(manager, filter.source_id.as_deref(), &filter.chat)
+                    .await?
+                    .into_iter()
+                    .map(|(source, chat)| GlobalChatId::from_chat_id(&source, &chat.id))
+                    .collect(),
+            ),
+        };
+
+        tokio::pin!(stop);
+        loop {
+            tokio::select! {
+                received = messages.recv() => {
+                    let Some((source, message)) = received else { break };
+                    let wanted = filter.source_id.as_ref().is_none_or(|id| *id == source)
+                        && chats.as_ref().is_none_or(|chats| chats.contains(&GlobalChatId::from_chat_id(&source, &message.chat_id)))
+                        && !forgotten.covers(&source, &message);
+                    if !wanted {
+                        continue;
+                    }
+
+                    // Reload so runs of `media process` in the meantime aren't overwritten
+                    let mut store = MediaTextStore::load_from(&self.store_path)?;
+                    if self.process_message(&mut store, &source, &message, false, &mut on_event).await.extracted > 0 {
+                        store.save_to(&self.store_path)?;
+                    }
+                }
+                _ = &mut stop => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run the processors that haven't handled the message yet, storing what they derive
+    async fn process_message(
+        &self,
+        store: &mut MediaTextStore,
+        source: &str,
+        message: &Message,
+        force: bool,
+        on_event: &mut dyn FnMut(MediaEvent<'_>),
+    ) -> MessageOutcome {
+        let mut outcome = MessageOutcome::default();
+        let pending: Vec<&dyn MediaProcessor> = self
+            .processors
+            .iter()
+            .map(|p| p.as_ref())
+            .filter(|p| p.accepts(message) && (force || !store.has(source, message, p.kind())))
+            .collect();
+        if pending.is_empty() {
+            return outcome;
+        }
+
+        let Some(location) = media_text::media_location(message) else {
+            outcome.unavailable = true;
+            return outcome;
+        };
+        let (path, _download) = match fetch_media(location).await {
+            Ok(file) => file,
+            Err(error) => {
+                on_event(MediaEvent::Unavailable { message, error: &error });
+                outcome.unavailable = true;
+                return outcome;
+            }
+        };
+
+        for processor in pending {
+            match processor.process(&path).await {
+                Ok(Some(derived)) => {
+                    on_event(MediaEvent::Extracted { source, message, derived: &derived });
+                    store.insert(source, message, derived);
+                    outcome.extracted += 1;
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    on_event(MediaEvent::Failed { message, error: &error });
+                    outcome.failed += 1;
+                }
+            }
+        }
+        outcome
+    }
+}
+
+/// Text derived so far for the attachments matching the filter, with their source and message
+pub async fn derived_texts(
+    manager: &SourcesManager,
+    filter: &MediaFilter,
+    store: &MediaTextStore,
+    forgotten: &ForgetLog,
+    mut on_event: impl FnMut(MediaEvent<'_>),
+) -> Result<Vec<(String, Message, DerivedText)>> {
+    let content_types = vec![ContentType::Image, ContentType::Audio, ContentType::File];
+    let messages = media_messages(manager, filter, content_types, forgotten, &mut on_event).await?;
+
+    let mut texts = Vec::new();
+    for (source, message) in messages {
+        for derived in store.get(&source, &message).to_vec() {
+            texts.push((source.clone(), message.clone(), derived));
+        }
+    }
+    Ok(texts)
+}
+
+/// Messages of the given content types matching a filter, with their source
+async fn media_messages(
+    manager: &SourcesManager,
+    filter: &MediaFilter,
+    content_types: Vec<ContentType>,
+    forgotten: &ForgetLog,
+    on_event: &mut dyn FnMut(MediaEvent<'_>),
+) -> Result<Vec<(String, Message)>> {
+    let source_ids: Vec<String> = match &filter.source_id {
+        Some(id) => vec![id.clone()],
+        None => manager.list_sources()?.into_iter().map(|s| s.id).collect(),
+    };
+
+    let mut found = Vec::new();
+    for source in source_ids {
+        let msg_filter = MessageFilter {
+            chat: filter.chat.clone(),
+            since: Some(filter.since),
+            content_type: Some(content_types.clone()),
+            ..MessageFilter::new()
+        };
+        match manager.query_messages(Some(&source), msg_filter).await {
+            Ok(messages) => found.extend(
+                messages.into_iter().filter(|m| !forgotten.covers(&source, m)).map(|m| (source.clone(), m)),
+            ),
+            Err(error) => on_event(MediaEvent::SourceFailed { source: &source, error: &error }),
+        }
+    }
+    Ok(found)
+}
+
+/// Deletes a downloaded attachment when dropped
+struct Download(PathBuf);
+
+impl Drop for Download {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.0).ok();
+    }
+}
+
+/// Path of the attachment's file, downloading remote files to a temporary file
+async fn fetch_media(location: &str) -> Result<(PathBuf, Option<Download>)> {
+    if let Some(path) = media_text::local_path(location) {
+        if !path.exists() {
+            anyhow::bail!("{} not found", path.display());
+        }
+        return Ok((path, None));
+    }
+    download(location).await
+}
+
+#[cfg(any(feature = "ocr", feature = "vision", feature = "whisper", feature = "documents"))]
+async fn download(url: &str) -> Result<(PathBuf, Option<Download>)> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        anyhow::bail!("can't fetch {}", url);
+    }
+
+    let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+    let path = std::env::temp_dir().join(format!("chat-media-{}", uuid::Uuid::new_v4().simple()));
+    tokio::fs::write(&path, &bytes).await?;
+    Ok((path.clone(), Some(Download(path))))
+}
+
+#[cfg(not(any(feature = "ocr", feature = "vision", feature = "whisper", feature = "documents")))]
+async fn download(url: &str) -> Result<(PathBuf, Option<Download>)> {
+    anyhow::bail!("can't fetch {} without the 'ocr', 'vision', 'whisper' or 'documents' feature", url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media_text::DerivedKind;
+    use crate::recording::{RecordedCall, SourceRecording};
+    use crate::test_utils::MessageBuilder;
+    use crate::{MessageContent, ReplaySource};
+    use std::path::Path;
+
+    /// Reads the file back as its document text
+    struct Cat;
+
+    #[async_trait::async_trait]
+    impl MediaProcessor for Cat {
+        fn kind(&self) -> DerivedKind {
+            DerivedKind::Document
+        }
+
+        async fn process(&self, file: &Path) -> Result<Option<DerivedText>> {
+            Ok(Some(DerivedText::new(DerivedKind::Document, std::fs::read_to_string(file)?, "cat")))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_and_show() {
+        let dir = std::env::temp_dir().join(format!("chat-media-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("notes.txt");
+        std::fs::write(&file, "meeting notes").unwrap();
+
+        let attachment = |id: &str, url: Option<String>| {
+            MessageBuilder::new(id, "c").content(MessageContent::File { filename: None, url }).build()
+        };
+        let recording = SourceRecording {
+            source_name: "Mock".to_string(),
+            calls: vec![RecordedCall::GetMessages {
+                filter: MessageFilter::new(),
+                response: Ok(vec![
+                    attachment("1", Some(file.display().to_string())),
+                    attachment("2", None),
+                    attachment("3", Some(dir.join("gone.txt").display().to_string())),
+                ]),
+            }],
+            ..SourceRecording::default()
+        };
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ReplaySource::new("mock", recording))).unwrap();
+
+        let pipeline = MediaPipeline {
+            processors: vec![Box::new(Cat)],
+            store_path: dir.join("media-text.json"),
+        };
+        let filter = MediaFilter {
+            source_id: None,
+            chat: ChatPattern::All,
+            since: Utc::now() - chrono::Duration::days(3650),
+        };
+        let forgotten = ForgetLog::default();
+        let report = pipeline.process(&manager, &filter, false, &forgotten, |_| {}).await.unwrap();
+        assert_eq!(report, ProcessReport { checked: 3, extracted: 1, unavailable: 2, failed: 0 });
+
+        let store = MediaTextStore::load_from(&pipeline.store_path).unwrap();
+        let texts = derived_texts(&manager, &filter, &store, &forgotten, |_| {}).await.unwrap();
+        assert_eq!(texts.len(), 1);
+        assert_eq!(texts[0].2.text, "meeting notes");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
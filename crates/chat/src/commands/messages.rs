@@ -0,0 +1,139 @@
+use anyhow::Result;
+
+use crate::dupes::DuplicateDetector;
+use crate::{filter_parser, ContentType, Message, MessageFilter, SourcesManager};
+
+/// Options for querying messages, as accepted by `chat messages`
+#[derive(Debug, Clone, Default)]
+pub struct MessageQuery {
+    /// Source and chat filter, e.g. "telegram:Antti", "telegram:#work", "*:*"
+    pub filter: String,
+    /// Time spec, e.g. "7d", "2h", "2025-01-15"
+    pub since: Option<String>,
+    pub before: Option<String>,
+    pub sender: Option<String>,
+    pub search: Option<String>,
+    /// Match `search` only against text extracted from attachments
+    pub in_attachments: bool,
+    pub limit: Option<usize>,
+    pub mentions_me: bool,
+    /// Match search and sender case- and accent-sensitively
+    pub exact: bool,
+    /// Only messages in this language, e.g. "en" or "fin"
+    pub language: Option<String>,
+    /// Collapse forwarded/duplicate messages to their first occurrence
+    pub dedupe: bool,
+    /// Only messages with one of these content types
+    pub content_types: Option<Vec<ContentType>>,
+    /// Also return system events (joins, leaves, renames); implied when asking for the system type
+    pub include_system: bool,
+}
+
+impl MessageQuery {
+    pub fn new(filter: impl Into<String>) -> Self {
+        Self {
+            filter: filter.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Query messages matching `query`, most recent first when several sources are queried
+pub async fn query(manager: &SourcesManager, query: MessageQuery) -> Result<Vec<Message>> {
+    let (source_id, chat) = filter_parser::parse_source_filter(&query.filter)?;
+    let include_system = query.include_system
+        || query.content_types.as_ref().is_some_and(|types| types.contains(&ContentType::System));
+
+    let filter = MessageFilter {
+        chat,
+        since: query.since.as_deref().map(filter_parser::parse_time_spec).transpose()?,
+        before: query.before.as_deref().map(filter_parser::parse_time_spec).transpose()?,
+        sender: query.sender,
+        search: query.search,
+        limit: query.limit,
+        content_type: query.content_types,
+        mentions_me: query.mentions_me,
+        exact: query.exact,
+        language: query.language,
+        include_system,
+        in_attachments: query.in_attachments,
+        separate_merged: false,
+    };
+
+    let messages = manager.query_messages(source_id.as_deref(), filter).await?;
+
+    Ok(if query.dedupe {
+        DuplicateDetector::new().collapse(messages)
+    } else {
+        messages
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::{RecordedCall, SourceRecording};
+    use crate::types::{ChatId, MessageContent, MessageId, User, UserId};
+    use crate::ReplaySource;
+
+    fn message(id: &str, chat: &str, text: &str) -> Message {
+        Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new(chat),
+            sender: User {
+                id: UserId::new("u"),
+                username: None,
+                display_name: Some("Ann".to_string()),
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp: chrono::Utc::now(),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    fn manager_with(messages: Vec<Message>) -> SourcesManager {
+        let recording = SourceRecording {
+            source_name: "Mock".to_string(),
+            calls: vec![RecordedCall::GetMessages {
+                filter: MessageFilter::new(),
+                response: Ok(messages),
+            }],
+            ..SourceRecording::default()
+        };
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ReplaySource::new("mock", recording))).unwrap();
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_query() {
+        let manager = manager_with(vec![message("1", "a", "hello")]);
+        let messages = query(&manager, MessageQuery::new("mock:*")).await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].id.as_str(), "1");
+    }
+
+    #[tokio::test]
+    async fn test_query_dedupe() {
+        let text = "Release 2.0 is out, grab it from the downloads page today";
+        let manager = manager_with(vec![message("1", "a", text), message("2", "b", text)]);
+        let query_with = |dedupe| MessageQuery { dedupe, ..MessageQuery::new("mock:*") };
+
+        assert_eq!(query(&manager, query_with(false)).await.unwrap().len(), 2);
+        assert_eq!(query(&manager, query_with(true)).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_bad_time_spec() {
+        let manager = manager_with(Vec::new());
+        let query_with = MessageQuery { since: Some("yesterday-ish".to_string()), ..MessageQuery::new("mock:*") };
+        assert!(query(&manager, query_with).await.is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Command logic behind the `chat` CLI, usable from other frontends and tests.
+//!
+//! Functions here return typed results and never print; the binary parses arguments and
+//! formats what comes back. This covers source setup, listing sources and chats, message
+//! queries, the analyses (`analyze`, `topics`, `dupes`, `links`), `sync`, `export`,
+//! `export-all`, `archive`, `media`, the `mcp` client and self-test, and the `chat telegram`
+//! commands that use the stored session directly. Long-running commands report progress
+//! through typed events passed to a callback.
+
+use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::{
+    BufferConfig, Fixture, LabelStore, MediaTextStore, MergedChats, Message, MyAccounts, Recorder, SimulatedSource,
+    SourcesConfig, SourcesManager,
+};
+
+pub mod analysis;
+pub mod archive;
+pub mod chats;
+pub mod export;
+pub mod export_all;
+pub mod mcp;
+pub mod media;
+pub mod messages;
+pub mod sources;
+pub mod sync;
+#[cfg(feature = "telegram")]
+pub mod telegram;
+
+/// How sources are backed for this invocation
+#[derive(Debug, Clone, Default)]
+pub enum SourceMode {
+    /// Talk to the configured sources
+    #[default]
+    Live,
+    /// Talk to the configured sources and record their traffic to a fixture file
+    Record(PathBuf),
+    /// Serve responses from a fixture file instead of the configured sources
+    Replay(PathBuf),
+}
+
+static SOURCE_MODE: OnceLock<SourceMode> = OnceLock::new();
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Set the source mode used by `create_manager`. Only the first call takes effect.
+pub fn set_source_mode(mode: SourceMode) {
+    let _ = SOURCE_MODE.set(mode);
+}
+
+/// Force read-only mode for managers from `create_manager` (the `--read-only` flag).
+/// Without it, `read_only` in sources.toml decides.
+pub fn set_read_only() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
+
+/// Create a sources manager with labels, accounts, source settings, read-only mode and the
+/// current source mode applied. The simulated sources in sources.toml are registered
+/// unless a fixture is replayed in place of the configured sources.
+pub fn create_manager() -> Result<SourcesManager> {
+    let config = SourcesConfig::load()?;
+    let simulated = config.simulated.clone();
+    let manager = SourcesManager::with_labels(LabelStore::load()?);
+    manager.set_merged_chats(MergedChats::load()?)?;
+    manager.set_accounts(MyAccounts::load()?)?;
+    manager.set_media_text(MediaTextStore::load()?)?;
+    manager.set_read_only(READ_ONLY.load(Ordering::Relaxed) || config.read_only);
+    manager.set_sources_config(config)?;
+
+    let register_simulated = || -> Result<()> {
+        for settings in &simulated {
+            manager.register(Box::new(SimulatedSource::new(settings)))?;
+        }
+        Ok(())
+    };
+
+    match SOURCE_MODE.get().cloned().unwrap_or_default() {
+        SourceMode::Live => register_simulated()?,
+        SourceMode::Record(path) => {
+            register_simulated()?;
+            let recorder = Recorder::new(path);
+            manager.wrap_sources(|source| recorder.wrap(source))?;
+        }
+        SourceMode::Replay(path) => {
+            for source in Fixture::load(&path)?.replay_sources() {
+                manager.register(Box::new(source))?;
+            }
+        }
+    }
+
+    Ok(manager)
+}
+
+/// A source left out of a merged subscription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedSource {
+    pub id: String,
+    pub reason: &'static str,
+}
+
+/// Messages from every streaming source, merged into one channel of `(source_id, message)`
+pub struct MergedSubscription {
+    /// Sources feeding the channel
+    pub sources: Vec<String>,
+    /// Sources that are disconnected or can't stream
+    pub skipped: Vec<SkippedSource>,
+    pub messages: UnboundedReceiver<(String, Message)>,
+}
+
+/// Subscribe to every connected streaming source. Each subscription buffers as many
+/// messages as its source's `subscription_buffer` setting allows.
+pub async fn subscribe_all(manager: &SourcesManager, config: BufferConfig) -> Result<MergedSubscription> {
+    let (tx, messages) = tokio::sync::mpsc::unbounded_channel();
+    let mut sources = Vec::new();
+    let mut skipped = Vec::new();
+
+    for source in manager.list_sources()? {
+        if !source.is_connected {
+            skipped.push(SkippedSource { id: source.id, reason: "not connected" });
+            continue;
+        }
+
+        let config = BufferConfig {
+            capacity: manager.source_settings(&source.id)?.subscription_buffer,
+            ..config
+        };
+        match manager.subscribe(&source.id, config).await? {
+            Some(subscription) => {
+                let tx = tx.clone();
+                let source_id = source.id.clone();
+                tokio::spawn(async move {
+                    while let Some(message) = subscription.recv().await {
+                        if tx.send((source_id.clone(), message)).is_err() {
+                            break;
+                        }
+                    }
+                });
+                sources.push(source.id);
+            }
+            None => skipped.push(SkippedSource { id: source.id, reason: "does not support streaming" }),
+        }
+    }
+
+    Ok(MergedSubscription { sources, skipped, messages })
+}
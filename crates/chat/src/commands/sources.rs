@@ -0,0 +1,8 @@
+use anyhow::Result;
+
+use crate::{SourceInfo, SourcesManager};
+
+/// Configured sources with their connection status and capabilities
+pub fn list(manager: &SourcesManager) -> Result<Vec<SourceInfo>> {
+    manager.list_sources()
+}
@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::forget::ForgetLog;
+use crate::sync::{archive_file, RetryError};
+use crate::{
+    Chat, ChatPattern, Checkpoint, GlobalChatId, Message, MessageFilter, Progress, RetryPolicy, SourcesManager,
+    SyncState,
+};
+
+/// Messages requested per page
+pub const PAGE_SIZE: usize = 100;
+
+/// Where and how `chat sync` runs
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Directory of the per-chat archive files
+    pub archive_dir: PathBuf,
+    /// Where the checkpoints are saved after every chat
+    pub state_path: PathBuf,
+    /// Start of chats without a checkpoint
+    pub since: DateTime<Utc>,
+    pub policy: RetryPolicy,
+}
+
+/// What a sync reports as it goes
+#[derive(Debug)]
+pub enum SyncEvent<'a> {
+    /// Started on a chat
+    Chat { id: &'a GlobalChatId, title: Option<&'a str> },
+    /// Fetching failed and is tried again after `delay`
+    Retry {
+        id: &'a GlobalChatId,
+        attempt: u32,
+        error: &'a anyhow::Error,
+        delay: std::time::Duration,
+    },
+    /// Done with the chat, whether it synced or failed
+    Done,
+}
+
+/// A chat that could not be synced
+#[derive(Debug)]
+pub struct SyncFailure {
+    pub chat: GlobalChatId,
+    pub title: Option<String>,
+    pub error: RetryError,
+}
+
+/// Outcome of a sync
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub synced: usize,
+    pub new_messages: usize,
+    pub failures: Vec<SyncFailure>,
+    /// Chats left when the sync was interrupted
+    pub skipped: usize,
+}
+
+/// Chats matching the filter that aren't forgotten; with `failed_only`, just those
+/// whose last sync failed
+pub async fn targets(
+    manager: &SourcesManager,
+    source_id: Option<&str>,
+    pattern: &ChatPattern,
+    state: &SyncState,
+    forgotten: &ForgetLog,
+    failed_only: bool,
+) -> Result<Vec<(String, Chat)>> {
+    let mut targets = super::chats::matching(manager, source_id, pattern).await?;
+    targets.retain(|(source, chat)| !forgotten.covers_chat(&GlobalChatId::from_chat_id(source, &chat.id)));
+    if failed_only {
+        let failed: HashSet<GlobalChatId> = state.failed().map(|(id, _)| id.clone()).collect();
+        targets.retain(|(source, chat)| failed.contains(&GlobalChatId::from_chat_id(source, &chat.id)));
+    }
+    Ok(targets)
+}
+
+/// Append what's new in `targets` since their checkpoints to the archive, moving the
+/// checkpoints along. Cancelling `cancel` stops after the current chat, without moving
+/// its checkpoint.
+pub async fn sync(
+    manager: &SourcesManager,
+    targets: &[(String, Chat)],
+    state: &mut SyncState,
+    options: &SyncOptions,
+    forgotten: &ForgetLog,
+    cancel: &Progress,
+    mut on_event: impl FnMut(SyncEvent<'_>),
+) -> Result<SyncReport> {
+    std::fs::create_dir_all(&options.archive_dir)
+        .with_context(|| format!("Failed to create archive directory: {}", options.archive_dir.display()))?;
+
+    let mut report = SyncReport::default();
+    for (source, chat) in targets {
+        if cancel.is_cancelled() {
+            break;
+        }
+
+        let id = GlobalChatId::from_chat_id(source, &chat.id);
+        on_event(SyncEvent::Chat { id: &id, title: chat.title.as_deref() });
+
+        let checkpoint = state.checkpoint(&id).cloned();
+        let since = checkpoint.as_ref().map(|c| c.last_timestamp).unwrap_or(options.since);
+
+        let result = options
+            .policy
+            .run(
+                || fetch_since(manager, source, chat, since, cancel),
+                |attempt, error, delay| on_event(SyncEvent::Retry { id: &id, attempt, error, delay }),
+            )
+            .await;
+
+        // A fetch cut short by Ctrl+C may have gaps, so it must not move the checkpoint
+        if cancel.is_cancelled() {
+            break;
+        }
+
+        match result {
+            Ok(messages) => {
+                // The checkpoint message itself comes back since `since` is inclusive
+                let messages: Vec<Message> = match &checkpoint {
+                    Some(c) => messages
+                        .into_iter()
+                        .filter(|m| m.timestamp >= c.last_timestamp && m.id != c.last_message_id)
+                        .collect(),
+                    None => messages,
+                };
+
+                // Forgotten people's messages stay out, but still move the checkpoint
+                let kept: Vec<Message> = messages.iter().filter(|m| !forgotten.covers(source, m)).cloned().collect();
+                append_archive(&archive_file(&options.archive_dir, &id), &kept)?;
+
+                // Newest message becomes the checkpoint; an empty sync keeps the old one
+                match messages.iter().max_by_key(|m| m.timestamp) {
+                    Some(newest) => state.set_checkpoint(id.clone(), Checkpoint {
+                        last_message_id: newest.id.clone(),
+                        last_timestamp: newest.timestamp,
+                        synced_at: Utc::now(),
+                    }),
+                    None => state.clear_failed(&id),
+                }
+                state.save_to(&options.state_path)?;
+
+                report.synced += 1;
+                report.new_messages += messages.len();
+            }
+            Err(error) => {
+                state.mark_failed(id.clone(), error.last_error.to_string(), error.attempts);
+                state.save_to(&options.state_path)?;
+
+                report.failures.push(SyncFailure {
+                    chat: id,
+                    title: chat.title.clone(),
+                    error,
+                });
+            }
+        }
+
+        on_event(SyncEvent::Done);
+    }
+
+    report.skipped = targets.len() - report.synced - report.failures.len();
+    Ok(report)
+}
+
+/// Fetch every message of a chat newer than `since`, page by page
+async fn fetch_since(
+    manager: &SourcesManager,
+    source: &str,
+    chat: &Chat,
+    since: DateTime<Utc>,
+    cancel: &Progress,
+) -> Result<Vec<Message>> {
+    let mut filter = MessageFilter {
+        chat: ChatPattern::Id(chat.id.clone()),
+        since: Some(since),
+        limit: Some(PAGE_SIZE),
+        separate_merged: true,
+        ..MessageFilter::new()
+    };
+
+    let mut seen = HashSet::new();
+    let mut messages = Vec::new();
+
+    loop {
+        let page = manager.query_messages_with_progress(Some(source), filter.clone(), cancel).await?;
+        let fetched = page.len();
+        let oldest = page.iter().map(|m| m.timestamp).min();
+
+        let new: Vec<_> = page.into_iter().filter(|m| seen.insert(m.id.clone())).collect();
+        if new.is_empty() {
+            break;
+        }
+        messages.extend(new);
+
+        if fetched < PAGE_SIZE {
+            break;
+        }
+        filter.before = oldest.map(|t| t + Duration::seconds(1));
+    }
+
+    Ok(messages)
+}
+
+/// Append messages oldest first, one JSON object per line
+fn append_archive(path: &Path, messages: &[Message]) -> Result<()> {
+    if messages.is_empty() {
+        return Ok(());
+    }
+
+    let mut sorted: Vec<&Message> = messages.iter().collect();
+    sorted.sort_by_key(|m| m.timestamp);
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    for message in sorted {
+        writeln!(file, "{}", serde_json::to_string(message)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recording::{RecordedCall, SourceRecording};
+    use crate::test_utils::{ChatBuilder, MessageBuilder};
+    use crate::ReplaySource;
+
+    #[tokio::test]
+    async fn test_sync_appends_and_checkpoints() {
+        let dir = std::env::temp_dir().join(format!("chat-sync-{}", uuid::Uuid::new_v4().simple()));
+        let messages = vec![MessageBuilder::new("1", "c").minute(1).build(), MessageBuilder::new("2", "c").minute(2).build()];
+        let recording = SourceRecording {
+            source_name: "Mock".to_string(),
+            calls: vec![RecordedCall::GetMessages {
+                filter: MessageFilter::new(),
+                response: Ok(messages),
+            }],
+            ..SourceRecording::default()
+        };
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ReplaySource::new("mock", recording))).unwrap();
+
+        let options = SyncOptions {
+            archive_dir: dir.join("archive"),
+            state_path: dir.join("state.json"),
+            since: Utc::now() - Duration::days(3650),
+            policy: RetryPolicy::default(),
+        };
+        let targets = vec![("mock".to_string(), ChatBuilder::new("c").build())];
+        let mut state = SyncState::new();
+        let mut chats = 0;
+        let report = sync(&manager, &targets, &mut state, &options, &ForgetLog::default(), &Progress::new(), |event| {
+            if let SyncEvent::Chat { .. } = event {
+                chats += 1;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!((report.synced, report.new_messages, report.skipped, chats), (1, 2, 0, 1));
+        let id = GlobalChatId::new("mock", "c");
+        assert_eq!(state.checkpoint(&id).unwrap().last_message_id, crate::MessageId::new("2"));
+        assert!(SyncState::load_from(&options.state_path).unwrap().checkpoint(&id).is_some());
+        let archived = std::fs::read_to_string(archive_file(&options.archive_dir, &id)).unwrap();
+        assert_eq!(archived.lines().count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,344 @@
+//! The `chat telegram` commands that talk to Telegram directly with the stored session
+//! rather than through a registered source: `get`, `search`, `export` and `watch`
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use grammers_client::client::updates::UpdateStream;
+use grammers_client::types::Peer;
+use grammers_client::{Client, Update, UpdatesConfiguration};
+use grammers_mtsender::SenderPool;
+
+use crate::cli::config::Config;
+use crate::estimate::{estimate_export, ExportEstimate, RateLimit};
+use crate::supervisor::{Supervisor, TaskState};
+use crate::telegram_session::TelegramSession;
+use crate::{ChatId, Message};
+
+/// Telegram returns at most this many messages per history request
+pub const MESSAGES_PER_REQUEST: usize = 100;
+
+/// Dialogs are listed in pages of this size
+pub const DIALOGS_PER_REQUEST: usize = 100;
+
+/// Messages looked through per chat by a search
+pub const SEARCH_DEPTH: usize = 1000;
+
+/// How often a watch checks that the connection is still up
+const HEALTH_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A chat picked by ID or by part of its name
+#[derive(Debug, Clone)]
+pub struct FoundChat {
+    pub id: i64,
+    pub name: String,
+    peer: Peer,
+}
+
+/// Which messages `get` and `export` keep from a chat's history
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+    /// Part of the sender's name, any case
+    pub sender: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// What to do with the next message of a history that comes newest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Keep,
+    Skip,
+    Stop,
+}
+
+impl HistoryFilter {
+    fn step(&self, message: &Message, kept: usize) -> Step {
+        if self.limit.is_some_and(|limit| kept >= limit) || self.since.is_some_and(|since| message.timestamp < since) {
+            return Step::Stop;
+        }
+        if self.before.is_some_and(|before| message.timestamp >= before) {
+            return Step::Skip;
+        }
+        if let Some(sender) = &self.sender {
+            let name = message.sender.display_name.as_deref().unwrap_or_default();
+            if !name.to_lowercase().contains(&sender.to_lowercase()) {
+                return Step::Skip;
+            }
+        }
+        Step::Keep
+    }
+}
+
+/// Whether `text` contains `term`
+fn contains_term(text: &str, term: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        text.to_lowercase().contains(&term.to_lowercase())
+    } else {
+        text.contains(term)
+    }
+}
+
+/// Whether a chat named `name` with the ID `id` is the one asked for by `chat`
+fn is_chat(chat: &str, id: i64, name: &str) -> bool {
+    id.to_string() == chat || name.to_lowercase().contains(&chat.to_lowercase())
+}
+
+/// Messages found by a search
+#[derive(Debug, Clone, Default)]
+pub struct SearchResults {
+    pub messages: Vec<Message>,
+    /// Chats with at least one match
+    pub chats: usize,
+}
+
+/// What `export --dry-run` found, from chat metadata and the history total only
+#[derive(Debug, Clone)]
+pub struct ExportPreview {
+    /// Every chat matching the name; only the first is exported
+    pub matched: Vec<FoundChat>,
+    /// Messages in the first match's history
+    pub total: usize,
+    pub estimate: ExportEstimate,
+}
+
+/// Connect with the stored session. Its network runner is watched by the returned
+/// supervisor; shut that down when done with the client.
+pub async fn connect() -> Result<(Client, Supervisor)> {
+    let (client, _, tasks) = connect_with_updates().await?;
+    Ok((client, tasks))
+}
+
+/// Connect as `connect` does, keeping the channel updates arrive on
+async fn connect_with_updates() -> Result<(
+    Client,
+    tokio::sync::mpsc::UnboundedReceiver<grammers_client::session::updates::UpdatesLike>,
+    Supervisor,
+)> {
+    let config = Config::load()?;
+    let api_id = config
+        .api_id
+        .context("API ID not configured. Run 'chat telegram init'")?;
+
+    let session = TelegramSession::open_default()?;
+    let pool = session.sender_pool(api_id);
+    let client = Client::new(&pool);
+
+    let SenderPool { runner, updates, .. } = pool;
+    let tasks = Supervisor::new();
+    tasks.spawn_once("runner", async move {
+        runner.run().await;
+        Ok::<_, anyhow::Error>(())
+    });
+
+    if !client.is_authorized().await? {
+        tasks.shutdown().await;
+        anyhow::bail!("Not authenticated. Run 'chat telegram init' to authenticate");
+    }
+    session.save()?;
+
+    Ok((client, updates, tasks))
+}
+
+/// The first chat whose ID is `chat` or whose name contains it
+pub async fn find_chat(client: &Client, chat: &str) -> Result<Option<FoundChat>> {
+    let mut dialogs = client.iter_dialogs();
+    while let Some(dialog) = dialogs.next().await? {
+        let found = found_chat(dialog.peer());
+        if is_chat(chat, found.id, &found.name) {
+            return Ok(Some(found));
+        }
+    }
+    Ok(None)
+}
+
+fn found_chat(peer: &Peer) -> FoundChat {
+    FoundChat {
+        id: peer.id().bot_api_dialog_id(),
+        name: peer.name().unwrap_or("Unknown").to_string(),
+        peer: peer.clone(),
+    }
+}
+
+/// Messages of `chat` kept by `filter`, newest first. `progress` is told how many were
+/// kept after every page.
+pub async fn fetch_history(
+    client: &Client,
+    chat: &FoundChat,
+    filter: &HistoryFilter,
+    mut progress: impl FnMut(usize),
+) -> Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    let mut history = client.iter_messages(&chat.peer);
+    while let Some(msg) = history.next().await? {
+        let message = crate::telegram_source::convert_message(&msg, &chat.peer);
+        match filter.step(&message, messages.len()) {
+            Step::Stop => break,
+            Step::Skip => continue,
+            Step::Keep => messages.push(message),
+        }
+        if messages.len() % MESSAGES_PER_REQUEST == 0 {
+            progress(messages.len());
+        }
+    }
+    Ok(messages)
+}
+
+/// Messages containing `term` among the last `SEARCH_DEPTH` of `chat`
+pub async fn search_chat(client: &Client, chat: &FoundChat, term: &str, ignore_case: bool) -> Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    let mut history = client.iter_messages(&chat.peer).limit(SEARCH_DEPTH);
+    while let Some(msg) = history.next().await? {
+        if contains_term(msg.text(), term, ignore_case) {
+            messages.push(crate::telegram_source::convert_message(&msg, &chat.peer));
+        }
+    }
+    Ok(messages)
+}
+
+/// Messages containing `term` in every chat
+pub async fn search_all(client: &Client, term: &str, ignore_case: bool) -> Result<SearchResults> {
+    let mut results = SearchResults::default();
+    let mut dialogs = client.iter_dialogs();
+    while let Some(dialog) = dialogs.next().await? {
+        let messages = search_chat(client, &found_chat(dialog.peer()), term, ignore_case).await?;
+        if !messages.is_empty() {
+            results.chats += 1;
+            results.messages.extend(messages);
+        }
+    }
+    Ok(results)
+}
+
+/// What exporting `chat` with at most `limit` messages would cost, or `None` when no
+/// chat matches. Only the history total is asked for, not the messages themselves.
+pub async fn preview_export(client: &Client, chat: &str, limit: Option<usize>) -> Result<Option<ExportPreview>> {
+    let mut dialogs = client.iter_dialogs();
+    let mut dialog_count: usize = 0;
+    let mut matched = Vec::new();
+    while let Some(dialog) = dialogs.next().await? {
+        dialog_count += 1;
+        let found = found_chat(dialog.peer());
+        if is_chat(chat, found.id, &found.name) {
+            matched.push(found);
+        }
+    }
+
+    let Some(first) = matched.first() else {
+        return Ok(None);
+    };
+    let total = client.iter_messages(&first.peer).total().await?;
+    let estimate = estimate_export(
+        &[Some(total)],
+        limit,
+        MESSAGES_PER_REQUEST,
+        dialog_count.div_ceil(DIALOGS_PER_REQUEST).max(1),
+        Some(RateLimit::TELEGRAM),
+    );
+
+    Ok(Some(ExportPreview { matched, total, estimate }))
+}
+
+/// New incoming messages, as they arrive
+pub struct MessageWatch {
+    client: Client,
+    updates: UpdateStream,
+    tasks: Supervisor,
+    chat: Option<i64>,
+    health: tokio::time::Interval,
+}
+
+impl MessageWatch {
+    /// Connect and start receiving messages from every chat
+    pub async fn start() -> Result<Self> {
+        let (client, updates, tasks) = connect_with_updates().await?;
+        let updates = client.stream_updates(
+            updates,
+            UpdatesConfiguration {
+                catch_up: false,
+                ..Default::default()
+            },
+        );
+        Ok(Self {
+            client,
+            updates,
+            tasks,
+            chat: None,
+            health: tokio::time::interval(HEALTH_CHECK_INTERVAL),
+        })
+    }
+
+    /// Only pass on messages from the chat `chat` picks, returning it, or `None` when
+    /// no chat matches
+    pub async fn only(&mut self, chat: &str) -> Result<Option<FoundChat>> {
+        let found = find_chat(&self.client, chat).await?;
+        self.chat = found.as_ref().map(|found| found.id);
+        Ok(found)
+    }
+
+    /// The next incoming message. Updates stop coming without an error when the
+    /// network runner dies, so that is checked for while waiting.
+    pub async fn next(&mut self) -> Result<Message> {
+        loop {
+            tokio::select! {
+                _ = self.health.tick() => {
+                    if let Some(status) = self.tasks.statuses().into_iter().find(|s| s.state != TaskState::Running) {
+                        anyhow::bail!("Lost the connection to Telegram ({})", status.describe());
+                    }
+                }
+                update = self.updates.next() => {
+                    let Update::NewMessage(message) = update? else {
+                        continue;
+                    };
+                    let chat_id = message.peer_id().bot_api_dialog_id();
+                    if message.outgoing() || self.chat.is_some_and(|chat| chat != chat_id) {
+                        continue;
+                    }
+                    return Ok(crate::telegram_source::convert_message_in(&message, ChatId::new(chat_id.to_string())));
+                }
+            }
+        }
+    }
+
+    /// Save the update state and disconnect
+    pub async fn stop(self) {
+        // While the runner is still up
+        self.updates.sync_update_state();
+        drop(self.updates);
+        self.tasks.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    #[test]
+    fn test_history_filter() {
+        let now = Utc::now();
+        let hours_ago = |hours| now - chrono::Duration::hours(hours);
+        let filter = HistoryFilter {
+            since: Some(hours_ago(10)),
+            before: Some(hours_ago(2)),
+            sender: Some("ann".to_string()),
+            limit: Some(2),
+        };
+        let from = |sender: &str, hours| MessageBuilder::new("1", "c").from(sender).at(hours_ago(hours)).build();
+
+        assert_eq!(filter.step(&from("ann", 1), 0), Step::Skip);
+        assert_eq!(filter.step(&from("ann", 5), 0), Step::Keep);
+        assert_eq!(filter.step(&from("bob", 5), 0), Step::Skip);
+        assert_eq!(filter.step(&from("ann", 5), 2), Step::Stop);
+        assert_eq!(filter.step(&from("ann", 11), 0), Step::Stop);
+    }
+
+    #[test]
+    fn test_matching() {
+        assert!(contains_term("Release Notes", "notes", true));
+        assert!(!contains_term("Release Notes", "notes", false));
+        assert!(is_chat("-100123", -100123, "Work"));
+        assert!(is_chat("wor", 5, "Work"));
+        assert!(!is_chat("home", 5, "Work"));
+    }
+}
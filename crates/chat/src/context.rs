@@ -0,0 +1,467 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::embeddings::{Embedder, EmbeddingIndex, MAX_ATTACHMENT_CHARS};
+use crate::notes::NoteStore;
+use crate::types::{ChatId, Message, MessageContent, MessageFilter, MessageId};
+use crate::SourcesManager;
+
+/// Relevant candidates considered per query before packing
+const RELEVANT_CANDIDATES: usize = 20;
+
+/// Most of the budget notes on people may take, as a fraction
+const PEOPLE_SHARE: f32 = 0.25;
+
+/// Rough token estimate (about four characters per token for English text),
+/// close enough for budgeting without shipping a tokenizer
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Why a message made it into the packed context
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Selection {
+    /// Among the most recent messages
+    Recent,
+    /// Semantically close to the query
+    Relevant,
+    /// Replied to by a selected message
+    Parent,
+}
+
+/// A selected message with its cost in the budget
+#[derive(Debug, Clone, Serialize)]
+pub struct PackedMessage {
+    pub chat_id: ChatId,
+    pub message_id: MessageId,
+    pub selection: Selection,
+    pub tokens: usize,
+}
+
+/// Messages packed under a token budget, rendered as a compact transcript
+#[derive(Debug, Clone, Serialize)]
+pub struct PackedContext {
+    pub text: String,
+    /// Estimated tokens used by `text`
+    pub tokens: usize,
+    pub budget: usize,
+    /// Selected messages in transcript order
+    pub messages: Vec<PackedMessage>,
+    /// Candidate messages left out for lack of budget
+    pub omitted: usize,
+}
+
+/// Selects the most useful messages for an LLM prompt under a token budget.
+///
+/// Relevant messages (with the messages they reply to) are taken first, up to
+/// `relevant_share` of the budget; the rest is filled with the newest messages.
+/// The result is rendered oldest first, grouped by chat. Notes on the chats, and on the
+/// people who wrote the messages or are named in the query, come with it.
+#[derive(Debug, Clone)]
+pub struct ContextPacker {
+    pub budget: usize,
+    /// Fraction of the budget reserved for relevant messages when any are given
+    pub relevant_share: f32,
+    /// Pull in the parent of each selected reply when it is available
+    pub include_parents: bool,
+    /// Longer message bodies are cut to this many characters
+    pub max_message_chars: usize,
+    /// Notes on chats and people (`chat note`)
+    pub notes: NoteStore,
+}
+
+impl ContextPacker {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            relevant_share: 0.5,
+            include_parents: true,
+            max_message_chars: 600,
+            notes: NoteStore::default(),
+        }
+    }
+
+    /// Include these notes on chats and people
+    pub fn with_notes(mut self, notes: NoteStore) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Pack `messages`, favoring `relevant` (most relevant first) when given
+    pub fn pack(&self, messages: &[Message], relevant: &[(ChatId, MessageId)]) -> PackedContext {
+        self.pack_about(messages, relevant, None)
+    }
+
+    /// Like [`pack`](Self::pack), also including notes on people named in `query`
+    pub fn pack_about(&self, messages: &[Message], relevant: &[(ChatId, MessageId)], query: Option<&str>) -> PackedContext {
+        let by_key: HashMap<(&ChatId, &MessageId), &Message> = messages
+            .iter()
+            .map(|m| ((&m.chat_id, &m.id), m))
+            .collect();
+
+        let mut selection = Selected::new(self, self.people_notes(messages, query));
+
+        let relevant_budget = if relevant.is_empty() {
+            0
+        } else {
+            (self.budget as f32 * self.relevant_share) as usize
+        };
+
+        for (chat_id, message_id) in relevant {
+            let Some(&message) = by_key.get(&(chat_id, message_id)) else {
+                continue;
+            };
+
+            if !selection.add(message, Selection::Relevant, relevant_budget) {
+                continue;
+            }
+
+            if self.include_parents {
+                let parent = message
+                    .reply_to
+                    .as_ref()
+                    .and_then(|id| by_key.get(&(&message.chat_id, id)));
+                if let Some(&parent) = parent {
+                    selection.add(parent, Selection::Parent, relevant_budget);
+                }
+            }
+        }
+
+        let mut newest_first: Vec<&Message> = messages.iter().collect();
+        newest_first.sort_by_key(|m| Reverse(m.timestamp));
+
+        for message in newest_first {
+            if !selection.add(message, Selection::Recent, self.budget) && selection.full(self.budget) {
+                break;
+            }
+
+            if self.include_parents {
+                let parent = message
+                    .reply_to
+                    .as_ref()
+                    .and_then(|id| by_key.get(&(&message.chat_id, id)));
+                if let Some(&parent) = parent {
+                    selection.add(parent, Selection::Parent, self.budget);
+                }
+            }
+        }
+
+        selection.finish(messages.len())
+    }
+
+    /// Notes on people named in `query` and on the senders of `messages`, within
+    /// `PEOPLE_SHARE` of the budget
+    fn people_notes(&self, messages: &[Message], query: Option<&str>) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let named = query.map(|query| self.notes.people_named_in(query)).unwrap_or_default();
+        for note in named {
+            lines.push(format!("> {}: {}\n", note.name.as_deref().unwrap_or("?"), note.summary()));
+        }
+        for message in messages {
+            if let Some(note) = self.notes.user_in_any_source(message.sender.id.as_str()) {
+                let name = note.name.clone().unwrap_or_else(|| sender_name(message));
+                lines.push(format!("> {}: {}\n", name, note.summary()));
+            }
+        }
+
+        let limit = (self.budget as f32 * PEOPLE_SHARE) as usize;
+        let mut text = String::new();
+        for line in lines {
+            if text.contains(&line) {
+                continue;
+            }
+            if estimate_tokens(&text) + estimate_tokens(&line) > limit {
+                break;
+            }
+            text.push_str(&line);
+        }
+        if text.is_empty() {
+            text
+        } else {
+            format!("# People\n{}", text)
+        }
+    }
+
+    /// Header starting a chat's messages, with the chat's notes
+    fn chat_header(&self, chat_id: &ChatId) -> String {
+        match self.notes.chat_in_any_source(chat_id) {
+            Some(note) => format!("# {}\n> {}\n", chat_id, note.summary()),
+            None => format!("# {}\n", chat_id),
+        }
+    }
+
+    fn render_line(&self, message: &Message) -> String {
+        let mut body = message_body(message).replace('\n', " ");
+        if body.chars().count() > self.max_message_chars {
+            body = body.chars().take(self.max_message_chars).collect::<String>() + "…";
+        }
+
+        let reply = if message.reply_to.is_some() { " ↩" } else { "" };
+
+        format!(
+            "[{}] {}{}: {}\n",
+            message.timestamp.format("%m-%d %H:%M"),
+            sender_name(message),
+            reply,
+            body
+        )
+    }
+}
+
+/// Selection state while packing
+struct Selected<'a, 'p> {
+    packer: &'p ContextPacker,
+    /// Notes on people, ahead of the messages
+    people: String,
+    chosen: Vec<(&'a Message, Selection, usize)>,
+    keys: HashSet<(&'a ChatId, &'a MessageId)>,
+    chats: HashSet<&'a ChatId>,
+    tokens: usize,
+}
+
+impl<'a, 'p> Selected<'a, 'p> {
+    fn new(packer: &'p ContextPacker, people: String) -> Self {
+        Self {
+            packer,
+            tokens: estimate_tokens(&people),
+            people,
+            chosen: Vec::new(),
+            keys: HashSet::new(),
+            chats: HashSet::new(),
+        }
+    }
+
+    /// Add a message if it fits within `limit`. Returns false if it was skipped.
+    fn add(&mut self, message: &'a Message, selection: Selection, limit: usize) -> bool {
+        let key = (&message.chat_id, &message.id);
+        if self.keys.contains(&key) {
+            return false;
+        }
+
+        let mut cost = estimate_tokens(&self.packer.render_line(message));
+        if !self.chats.contains(&message.chat_id) {
+            cost += estimate_tokens(&self.packer.chat_header(&message.chat_id));
+        }
+
+        if self.tokens + cost > limit {
+            return false;
+        }
+
+        self.tokens += cost;
+        self.keys.insert(key);
+        self.chats.insert(&message.chat_id);
+        self.chosen.push((message, selection, cost));
+        true
+    }
+
+    /// Whether even the smallest message would no longer fit
+    fn full(&self, limit: usize) -> bool {
+        self.tokens + 8 > limit
+    }
+
+    fn finish(mut self, candidates: usize) -> PackedContext {
+        let packer = self.packer;
+        self.chosen.sort_by(|a, b| {
+            a.0.chat_id
+                .as_str()
+                .cmp(b.0.chat_id.as_str())
+                .then_with(|| a.0.timestamp.cmp(&b.0.timestamp))
+        });
+
+        let mut text = std::mem::take(&mut self.people);
+        let mut current_chat: Option<&ChatId> = None;
+        let mut messages = Vec::new();
+
+        for (message, selection, tokens) in &self.chosen {
+            if current_chat != Some(&message.chat_id) {
+                text.push_str(&packer.chat_header(&message.chat_id));
+                current_chat = Some(&message.chat_id);
+            }
+
+            text.push_str(&packer.render_line(message));
+            messages.push(PackedMessage {
+                chat_id: message.chat_id.clone(),
+                message_id: message.id.clone(),
+                selection: *selection,
+                tokens: *tokens,
+            });
+        }
+
+        PackedContext {
+            tokens: estimate_tokens(&text),
+            text,
+            budget: packer.budget,
+            omitted: candidates - messages.len(),
+            messages,
+        }
+    }
+}
+
+/// Semantic ranking inputs for [`pack_context`]
+pub struct ContextQuery<'a> {
+    pub text: &'a str,
+    pub embedder: &'a dyn Embedder,
+    /// Updated with any fetched messages that aren't indexed yet
+    pub index: &'a mut EmbeddingIndex,
+}
+
+/// Fetch messages matching `filter` from one source (or all of them) and pack them
+/// under the packer's budget, favoring messages relevant to `query` when given
+pub async fn pack_context(
+    manager: &SourcesManager,
+    source_id: Option<&str>,
+    filter: MessageFilter,
+    packer: &ContextPacker,
+    query: Option<ContextQuery<'_>>,
+) -> Result<PackedContext> {
+    let Some(query) = query else {
+        let messages = manager.query_messages(source_id, filter).await?;
+        return Ok(packer.pack(&messages, &[]));
+    };
+
+    let source_ids: Vec<String> = match source_id {
+        Some(id) => vec![id.to_string()],
+        None => manager.list_sources()?.into_iter().map(|s| s.id).collect(),
+    };
+
+    let mut messages = Vec::new();
+    let mut fetched = HashSet::new();
+    for source in &source_ids {
+        let source_messages = manager.query_messages(Some(source), filter.clone()).await?;
+        query.index.index_messages(source, &source_messages, query.embedder).await?;
+
+        fetched.extend(source_messages.iter().map(|m| (source.clone(), m.chat_id.clone(), m.id.clone())));
+        messages.extend(source_messages);
+    }
+
+    let relevant: Vec<(ChatId, MessageId)> = query
+        .index
+        .query(query.embedder, query.text, RELEVANT_CANDIDATES, |entry| {
+            fetched.contains(&(entry.source.clone(), entry.chat_id.clone(), entry.message_id.clone()))
+        })
+        .await?
+        .into_iter()
+        .map(|hit| (hit.message.chat_id, hit.message.message_id))
+        .collect();
+
+    Ok(packer.pack_about(&messages, &relevant, Some(query.text)))
+}
+
+fn message_body(message: &Message) -> String {
+    match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Image { caption, .. } => match &message.media_text {
+            Some(text) => format!("[image] {} (image text: {})", caption.as_deref().unwrap_or(""), text.replace('\n', " ")),
+            None => format!("[image] {}", caption.as_deref().unwrap_or("")),
+        },
+        MessageContent::Video { caption, .. } => format!("[video] {}", caption.as_deref().unwrap_or("")),
+        MessageContent::Audio { .. } => match &message.media_text {
+            Some(text) => format!("[audio] (transcript: {})", text),
+            None => "[audio]".to_string(),
+        },
+        MessageContent::File { filename, .. } => match &message.media_text {
+            Some(text) => {
+                let end = text.char_indices().nth(MAX_ATTACHMENT_CHARS).map_or(text.len(), |(i, _)| i);
+                format!("[file] {} (document text: {})", filename.as_deref().unwrap_or(""), text[..end].replace('\n', " "))
+            }
+            None => format!("[file] {}", filename.as_deref().unwrap_or("")),
+        },
+        MessageContent::Sticker => "[sticker]".to_string(),
+        MessageContent::Location { latitude, longitude } => format!("[location] {}, {}", latitude, longitude),
+        MessageContent::Contact { name, .. } => format!("[contact] {}", name),
+        MessageContent::System(event) => format!("[system] {}", event.describe()),
+        MessageContent::Unknown => "[unknown]".to_string(),
+    }
+}
+
+fn sender_name(message: &Message) -> String {
+    message.sender.display_name
+        .clone()
+        .or_else(|| message.sender.username.clone())
+        .unwrap_or_else(|| message.sender.id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{User, UserId};
+    use chrono::{Duration, Utc};
+
+    fn message(id: &str, minutes: i64, text: &str, reply_to: Option<&str>) -> Message {
+        Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new("team"),
+            sender: User {
+                id: UserId::new("u"),
+                username: None,
+                display_name: Some("Alice".to_string()),
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp: Utc::now() - Duration::days(1) + Duration::minutes(minutes),
+            reply_to: reply_to.map(MessageId::new),
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    fn history() -> Vec<Message> {
+        (0..50)
+            .map(|i| message(&i.to_string(), i, &format!("status update number {} with some filler text", i), None))
+            .collect()
+    }
+
+    #[test]
+    fn test_recent_messages_fit_budget() {
+        let packed = ContextPacker::new(100).pack(&history(), &[]);
+
+        assert!(packed.tokens <= 100);
+        assert!(packed.omitted > 0);
+        assert!(packed.messages.iter().all(|m| m.selection == Selection::Recent));
+        // Newest messages are kept and rendered in chronological order
+        assert_eq!(packed.messages.last().unwrap().message_id.as_str(), "49");
+        assert!(packed.text.starts_with("# team\n"));
+    }
+
+    #[test]
+    fn test_relevant_and_parents_included() {
+        let mut messages = history();
+        messages.push(message("q", 3, "Should the API use cursor pagination?", None));
+        messages.push(message("a", 4, "Agreed, cursor pagination for the API", Some("q")));
+
+        let relevant = [(ChatId::new("team"), MessageId::new("a"))];
+        let packed = ContextPacker::new(120).pack(&messages, &relevant);
+
+        let selection_of = |id: &str| {
+            packed.messages.iter().find(|m| m.message_id.as_str() == id).map(|m| m.selection)
+        };
+        assert_eq!(selection_of("a"), Some(Selection::Relevant));
+        assert_eq!(selection_of("q"), Some(Selection::Parent));
+        assert!(packed.text.find("Should the API").unwrap() < packed.text.find("Agreed").unwrap());
+        assert!(packed.tokens <= 120);
+    }
+
+    #[test]
+    fn test_notes_included() {
+        let mut notes = NoteStore::new();
+        notes.chat_mut("telegram", &ChatId::new("team")).text = "Backend team".to_string();
+        notes.user_mut("telegram", "u").set_field("role", "tech lead");
+        let bo = notes.user_mut("telegram", "b");
+        bo.name = Some("Bo".to_string());
+        bo.text = "On leave until May".to_string();
+
+        let packer = ContextPacker::new(200).with_notes(notes);
+        let packed = packer.pack_about(&history(), &[], Some("When is Bo back?"));
+
+        assert!(packed.text.starts_with("# People\n> Bo: On leave until May\n> Alice: role: tech lead\n# team\n> Backend team\n"));
+        assert!(packed.tokens <= 200);
+        assert!(!packer.pack(&history(), &[]).text.contains("Bo:"));
+    }
+}
@@ -0,0 +1,286 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use crate::types::{ChatId, Message, MessageContent, MessageId};
+
+/// A message belonging to a duplicate group
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateRef {
+    pub chat_id: ChatId,
+    pub message_id: MessageId,
+    pub sender: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Near-identical messages, typically the same chain message forwarded around
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    /// Text of the first occurrence
+    pub text: String,
+    /// Occurrences, oldest first
+    pub messages: Vec<DuplicateRef>,
+}
+
+impl DuplicateGroup {
+    /// Number of distinct chats the message appeared in
+    pub fn chat_count(&self) -> usize {
+        self.messages.iter().map(|m| &m.chat_id).collect::<HashSet<_>>().len()
+    }
+}
+
+/// Near-duplicate detector using 64-bit simhash over word shingles
+#[derive(Debug, Clone)]
+pub struct DuplicateDetector {
+    /// Only group messages posted within this window of the previous occurrence
+    pub window: Duration,
+    /// Maximum differing simhash bits to still count as a duplicate
+    pub max_distance: u32,
+    /// Ignore short messages ("ok", "thanks") which are trivially identical
+    pub min_length: usize,
+}
+
+impl Default for DuplicateDetector {
+    fn default() -> Self {
+        Self {
+            window: Duration::days(1),
+            max_distance: 3,
+            min_length: 40,
+        }
+    }
+}
+
+struct Cluster {
+    fingerprint: u64,
+    last_seen: DateTime<Utc>,
+    group: DuplicateGroup,
+    /// Index of the first occurrence in the sorted input
+    first: usize,
+}
+
+impl DuplicateDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the time window
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    fn cluster(&self, messages: &[&Message]) -> (Vec<Cluster>, Vec<Option<usize>>) {
+        let mut clusters: Vec<Cluster> = Vec::new();
+        let mut assignment = vec![None; messages.len()];
+
+        for (i, message) in messages.iter().enumerate() {
+            let Some(text) = message_text(message) else {
+                continue;
+            };
+            let normalized = normalize(text);
+            if normalized.len() < self.min_length {
+                continue;
+            }
+
+            let fingerprint = simhash(&normalized);
+            let occurrence = DuplicateRef {
+                chat_id: message.chat_id.clone(),
+                message_id: message.id.clone(),
+                sender: message.sender.display_name.clone(),
+                timestamp: message.timestamp,
+            };
+
+            let existing = clusters.iter_mut().enumerate().find(|(_, c)| {
+                message.timestamp - c.last_seen <= self.window
+                    && (c.fingerprint ^ fingerprint).count_ones() <= self.max_distance
+            });
+
+            match existing {
+                Some((index, cluster)) => {
+                    cluster.last_seen = message.timestamp;
+                    cluster.group.messages.push(occurrence);
+                    assignment[i] = Some(index);
+                }
+                None => {
+                    assignment[i] = Some(clusters.len());
+                    clusters.push(Cluster {
+                        fingerprint,
+                        last_seen: message.timestamp,
+                        group: DuplicateGroup {
+                            text: text.to_string(),
+                            messages: vec![occurrence],
+                        },
+                        first: i,
+                    });
+                }
+            }
+        }
+
+        (clusters, assignment)
+    }
+
+    /// Find groups of near-duplicate messages, largest first
+    pub fn find_duplicates(&self, messages: &[Message]) -> Vec<DuplicateGroup> {
+        let sorted = sorted_oldest_first(messages);
+        let (clusters, _) = self.cluster(&sorted);
+
+        let mut groups: Vec<DuplicateGroup> = clusters
+            .into_iter()
+            .map(|c| c.group)
+            .filter(|g| g.messages.len() > 1)
+            .collect();
+
+        groups.sort_by_key(|g| Reverse(g.messages.len()));
+        groups
+    }
+
+    /// Keep only the first occurrence of each duplicate group, preserving input order
+    pub fn collapse(&self, messages: Vec<Message>) -> Vec<Message> {
+        let keep: HashSet<(ChatId, MessageId)> = {
+            let sorted = sorted_oldest_first(&messages);
+            let (clusters, assignment) = self.cluster(&sorted);
+
+            sorted
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| match assignment[*i] {
+                    Some(cluster) => clusters[cluster].first == *i,
+                    None => true,
+                })
+                .map(|(_, m)| (m.chat_id.clone(), m.id.clone()))
+                .collect()
+        };
+
+        messages
+            .into_iter()
+            .filter(|m| keep.contains(&(m.chat_id.clone(), m.id.clone())))
+            .collect()
+    }
+}
+
+fn sorted_oldest_first(messages: &[Message]) -> Vec<&Message> {
+    let mut sorted: Vec<&Message> = messages.iter().collect();
+    sorted.sort_by_key(|m| m.timestamp);
+    sorted
+}
+
+fn message_text(message: &Message) -> Option<&str> {
+    match &message.content {
+        MessageContent::Text(text) => Some(text),
+        MessageContent::Image { caption: Some(caption), .. } |
+        MessageContent::Video { caption: Some(caption), .. } => Some(caption),
+        _ => None,
+    }
+}
+
+/// Lowercase, strip punctuation and collapse whitespace
+pub fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 64-bit simhash over word 3-shingles of normalized text
+pub fn simhash(normalized: &str) -> u64 {
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    let mut weights = [0i32; 64];
+
+    let shingles: Vec<String> = if words.len() < 3 {
+        vec![words.join(" ")]
+    } else {
+        words.windows(3).map(|w| w.join(" ")).collect()
+    };
+
+    for shingle in &shingles {
+        let hash = fnv1a(shingle.as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, &w)| w > 0)
+        .fold(0, |acc, (bit, _)| acc | (1 << bit))
+}
+
+/// FNV-1a, used instead of the std hasher so fingerprints are stable across runs
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{User, UserId};
+
+    const CHAIN: &str = "Forward this message to ten friends or you will have bad luck for seven years!!";
+
+    fn message(id: &str, chat: &str, text: &str, minutes: i64) -> Message {
+        Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new(chat),
+            sender: User {
+                id: UserId::new("u"),
+                username: None,
+                display_name: None,
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp: Utc::now() - Duration::days(2) + Duration::minutes(minutes),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_and_simhash() {
+        assert_eq!(normalize("Hello,  WORLD!!"), "hello world");
+        assert_eq!(simhash(&normalize(CHAIN)), simhash(&normalize(&CHAIN.to_uppercase())));
+    }
+
+    #[test]
+    fn test_find_duplicates_across_chats() {
+        let messages = vec![
+            message("1", "a", CHAIN, 0),
+            message("2", "b", &format!("{} ", CHAIN.to_lowercase()), 30),
+            message("3", "c", "Completely unrelated message about the meeting agenda for tomorrow", 40),
+            message("4", "c", CHAIN, 60 * 48),
+        ];
+
+        let groups = DuplicateDetector::new().find_duplicates(&messages);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].messages.len(), 2);
+        assert_eq!(groups[0].chat_count(), 2);
+    }
+
+    #[test]
+    fn test_collapse_keeps_first_occurrence() {
+        let messages = vec![
+            message("2", "b", CHAIN, 30),
+            message("1", "a", CHAIN, 0),
+            message("3", "a", "short", 40),
+        ];
+
+        let collapsed = DuplicateDetector::new().collapse(messages);
+        let ids: Vec<&str> = collapsed.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "3"]);
+    }
+}
@@ -0,0 +1,547 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::types::{ChatId, Message, MessageContent, MessageId, UserId};
+
+/// Ollama embedding model used when none is configured
+pub const DEFAULT_MODEL: &str = "nomic-embed-text";
+
+/// Texts sent per embedding request
+pub const BATCH_SIZE: usize = 32;
+
+/// Characters of an attachment's text embedded at most
+pub const MAX_ATTACHMENT_CHARS: usize = 2000;
+
+/// Index file header, bumped when the layout changes
+const MAGIC: &[u8; 8] = b"CHATVEC1";
+
+/// Turns text into embedding vectors
+#[async_trait::async_trait]
+pub trait Embedder: Send + Sync {
+    /// Model name, stored with the index so vectors from different models never mix
+    fn model(&self) -> &str;
+
+    /// Embed a batch of texts, one vector per text
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Embedder backed by an Ollama server's `/api/embed` endpoint
+#[cfg(feature = "embeddings")]
+pub struct OllamaEmbedder {
+    client: reqwest::Client,
+    host: String,
+    model: String,
+}
+
+#[cfg(feature = "embeddings")]
+impl OllamaEmbedder {
+    /// Use `host` (e.g. http://localhost:11434) and `model`
+    pub fn new(host: impl Into<String>, model: impl Into<String>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(120))
+            .build()?;
+
+        Ok(Self {
+            client,
+            host: host.into().trim_end_matches('/').to_string(),
+            model: model.into(),
+        })
+    }
+
+    /// Host from `OLLAMA_HOST`, falling back to the local default
+    pub fn from_env(model: impl Into<String>) -> Result<Self> {
+        let host = std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string());
+        Self::new(host, model)
+    }
+}
+
+#[cfg(feature = "embeddings")]
+#[async_trait::async_trait]
+impl Embedder for OllamaEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        #[derive(Deserialize)]
+        struct EmbedResponse {
+            embeddings: Vec<Vec<f32>>,
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/api/embed", self.host))
+            .json(&serde_json::json!({ "model": self.model, "input": texts }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach Ollama at {}", self.host))?
+            .error_for_status()
+            .context("Ollama embedding request failed")?;
+
+        let body: EmbedResponse = response.json().await.context("Failed to parse Ollama response")?;
+        if body.embeddings.len() != texts.len() {
+            anyhow::bail!("Ollama returned {} embeddings for {} texts", body.embeddings.len(), texts.len());
+        }
+
+        Ok(body.embeddings)
+    }
+}
+
+/// Message metadata kept alongside its vector so results can be shown without refetching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedMessage {
+    pub source: String,
+    pub chat_id: ChatId,
+    pub message_id: MessageId,
+    pub sender: Option<String>,
+    /// Missing in entries indexed before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_id: Option<UserId>,
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+    /// Whether `text` was extracted from the message's attachment rather than written
+    #[serde(default)]
+    pub attachment: bool,
+}
+
+/// A search result with its cosine similarity to the query
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub score: f32,
+    pub message: IndexedMessage,
+}
+
+/// Flat vector index stored in a single binary file.
+///
+/// Search is an exact cosine scan, which stays fast for personal chat history
+/// (hundreds of thousands of messages) without an ANN structure.
+#[derive(Debug, Clone)]
+pub struct EmbeddingIndex {
+    model: String,
+    dim: usize,
+    entries: Vec<IndexedMessage>,
+    /// Unit-length vectors, parallel to `entries`
+    vectors: Vec<Vec<f32>>,
+    keys: HashSet<(String, ChatId, MessageId, bool)>,
+}
+
+impl EmbeddingIndex {
+    /// Create an empty index for a model
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            dim: 0,
+            entries: Vec::new(),
+            vectors: Vec::new(),
+            keys: HashSet::new(),
+        }
+    }
+
+    /// Default location of the index file (`~/.config/chat/embeddings.idx`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("embeddings.idx"))
+    }
+
+    /// Load the index for `model` from the default location
+    pub fn load(model: &str) -> Result<Self> {
+        Self::load_from(&Self::default_path()?, model)
+    }
+
+    /// Load an index file. A missing file, or one built with another model, yields an empty index.
+    pub fn load_from(path: &Path, model: &str) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new(model));
+        }
+
+        let mut reader = std::io::BufReader::new(
+            std::fs::File::open(path).context("Failed to open embedding index")?,
+        );
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            anyhow::bail!("Not an embedding index: {}", path.display());
+        }
+
+        let stored_model = String::from_utf8(read_block(&mut reader)?)?;
+        if stored_model != model {
+            return Ok(Self::new(model));
+        }
+
+        let dim = read_u32(&mut reader)? as usize;
+        let count = read_u32(&mut reader)? as usize;
+        let mut index = Self::new(model);
+        index.dim = dim;
+
+        for _ in 0..count {
+            let entry: IndexedMessage = serde_json::from_slice(&read_block(&mut reader)?)
+                .context("Corrupt embedding index entry")?;
+
+            let mut bytes = vec![0u8; dim * 4];
+            reader.read_exact(&mut bytes)?;
+            let vector = bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+
+            index.keys.insert(key(&entry));
+            index.entries.push(entry);
+            index.vectors.push(vector);
+        }
+
+        Ok(index)
+    }
+
+    /// Model an index file was built with, or None if there is no index
+    pub fn stored_model(path: &Path) -> Result<Option<String>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let mut reader = std::io::BufReader::new(
+            std::fs::File::open(path).context("Failed to open embedding index")?,
+        );
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            anyhow::bail!("Not an embedding index: {}", path.display());
+        }
+        Ok(Some(String::from_utf8(read_block(&mut reader)?)?))
+    }
+
+    /// Save the index to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save the index to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create index directory")?;
+        }
+
+        let mut writer = std::io::BufWriter::new(
+            std::fs::File::create(path).context("Failed to write embedding index")?,
+        );
+
+        writer.write_all(MAGIC)?;
+        write_block(&mut writer, self.model.as_bytes())?;
+        writer.write_all(&(self.dim as u32).to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+
+        for (entry, vector) in self.entries.iter().zip(&self.vectors) {
+            write_block(&mut writer, &serde_json::to_vec(entry)?)?;
+            for value in vector {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether a message from a source is already indexed
+    pub fn contains(&self, source: &str, message: &Message) -> bool {
+        self.keys.contains(&(source.to_string(), message.chat_id.clone(), message.id.clone(), false))
+    }
+
+    /// Whether the text extracted from a message's attachment is already indexed
+    pub fn contains_attachment(&self, source: &str, message: &Message) -> bool {
+        self.keys.contains(&(source.to_string(), message.chat_id.clone(), message.id.clone(), true))
+    }
+
+    /// Remove the entries `f` picks, returning them
+    pub fn remove_where(&mut self, mut f: impl FnMut(&IndexedMessage) -> bool) -> Vec<IndexedMessage> {
+        let mut removed = Vec::new();
+        let mut kept = Vec::new();
+        for (entry, vector) in self.entries.drain(..).zip(self.vectors.drain(..)) {
+            if f(&entry) {
+                self.keys.remove(&key(&entry));
+                removed.push(entry);
+            } else {
+                kept.push((entry, vector));
+            }
+        }
+        (self.entries, self.vectors) = kept.into_iter().unzip();
+        removed
+    }
+
+    /// Add a message vector. All vectors in an index must share one dimension.
+    pub fn insert(&mut self, entry: IndexedMessage, vector: Vec<f32>) -> Result<()> {
+        if self.dim == 0 {
+            self.dim = vector.len();
+        } else if vector.len() != self.dim {
+            anyhow::bail!("Embedding has {} dimensions, index uses {}", vector.len(), self.dim);
+        }
+
+        if !self.keys.insert(key(&entry)) {
+            return Ok(());
+        }
+
+        self.entries.push(entry);
+        self.vectors.push(normalized(vector));
+        Ok(())
+    }
+
+    /// Embed and add messages from a source that aren't indexed yet. Text extracted from
+    /// attachments gets its own entries, so a message is found by what it says or what it
+    /// carries. Returns the number of new entries.
+    pub async fn index_messages(&mut self, source: &str, messages: &[Message], embedder: &dyn Embedder) -> Result<usize> {
+        if embedder.model() != self.model {
+            anyhow::bail!("Index was built with '{}', not '{}'", self.model, embedder.model());
+        }
+
+        let entry = |m: &Message, text: &str, attachment: bool| {
+            let text = text.trim();
+            (!text.is_empty()).then(|| IndexedMessage {
+                source: source.to_string(),
+                chat_id: m.chat_id.clone(),
+                message_id: m.id.clone(),
+                sender: m.sender.display_name.clone(),
+                sender_id: Some(m.sender.id.clone()),
+                timestamp: m.timestamp,
+                text: text.to_string(),
+                attachment,
+            })
+        };
+        let written = messages
+            .iter()
+            .filter(|m| !self.contains(source, m))
+            .filter_map(|m| entry(m, message_text(m)?, false));
+        // Embedding models only read so far; the start of a document says what it is about
+        let attached = messages
+            .iter()
+            .filter(|m| !self.contains_attachment(source, m))
+            .filter_map(|m| {
+                let text = m.media_text.as_deref()?;
+                let end = text.char_indices().nth(MAX_ATTACHMENT_CHARS).map_or(text.len(), |(i, _)| i);
+                entry(m, &text[..end], true)
+            });
+        let pending: Vec<IndexedMessage> = written.chain(attached).collect();
+
+        let mut added = 0;
+        for batch in pending.chunks(BATCH_SIZE) {
+            let texts: Vec<String> = batch.iter().map(|e| e.text.clone()).collect();
+            let vectors = embedder.embed(&texts).await?;
+
+            for (entry, vector) in batch.iter().cloned().zip(vectors) {
+                self.insert(entry, vector)?;
+                added += 1;
+            }
+        }
+
+        Ok(added)
+    }
+
+    /// Best matches for a query vector among entries accepted by `filter`, highest score first
+    pub fn search<F>(&self, query: &[f32], top: usize, filter: F) -> Vec<SearchHit>
+    where
+        F: Fn(&IndexedMessage) -> bool,
+    {
+        if query.len() != self.dim {
+            return Vec::new();
+        }
+
+        let query = normalized(query.to_vec());
+        let mut hits: Vec<SearchHit> = self
+            .entries
+            .iter()
+            .zip(&self.vectors)
+            .filter(|(entry, _)| filter(entry))
+            .map(|(entry, vector)| SearchHit {
+                score: dot(&query, vector),
+                message: entry.clone(),
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(top);
+        hits
+    }
+
+    /// Embed a natural-language query and search for it
+    pub async fn query<F>(&self, embedder: &dyn Embedder, query: &str, top: usize, filter: F) -> Result<Vec<SearchHit>>
+    where
+        F: Fn(&IndexedMessage) -> bool,
+    {
+        let vector = embedder
+            .embed(&[query.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("Embedder returned no vector for the query"))?;
+
+        Ok(self.search(&vector, top, filter))
+    }
+}
+
+fn key(entry: &IndexedMessage) -> (String, ChatId, MessageId, bool) {
+    (entry.source.clone(), entry.chat_id.clone(), entry.message_id.clone(), entry.attachment)
+}
+
+fn message_text(message: &Message) -> Option<&str> {
+    match &message.content {
+        MessageContent::Text(text) => Some(text),
+        MessageContent::Image { caption: Some(caption), .. } |
+        MessageContent::Video { caption: Some(caption), .. } => Some(caption),
+        _ => None,
+    }
+}
+
+fn normalized(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = dot(&vector, &vector).sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_block(reader: &mut impl Read) -> Result<Vec<u8>> {
+    let len = read_u32(reader)? as usize;
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn write_block(writer: &mut impl Write, bytes: &[u8]) -> Result<()> {
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{User, UserId};
+
+    /// Bag-of-topics embedder: one dimension per keyword group
+    struct KeywordEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for KeywordEmbedder {
+        fn model(&self) -> &str {
+            "keywords"
+        }
+
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            const GROUPS: [&[&str]; 3] = [&["api", "design", "endpoint", "schema"], &["lunch", "pizza", "food"], &["deploy", "release"]];
+
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    GROUPS
+                        .iter()
+                        .map(|words| words.iter().filter(|w| text.contains(*w)).count() as f32)
+                        .collect()
+                })
+                .collect())
+        }
+    }
+
+    fn message(id: &str, text: &str) -> Message {
+        Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new("chat"),
+            sender: User {
+                id: UserId::new("u"),
+                username: None,
+                display_name: Some("Alice".to_string()),
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp: Utc::now(),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_index_and_query() {
+        let messages = vec![
+            message("1", "Let's settle the endpoint schema for the new API"),
+            message("2", "Pizza for lunch?"),
+            message("3", "Release is going out after the deploy check"),
+        ];
+
+        let mut index = EmbeddingIndex::new("keywords");
+        assert_eq!(index.index_messages("telegram", &messages, &KeywordEmbedder).await.unwrap(), 3);
+        assert_eq!(index.index_messages("telegram", &messages, &KeywordEmbedder).await.unwrap(), 0);
+
+        let hits = index.query(&KeywordEmbedder, "when did we agree on the API design?", 2, |_| true).await.unwrap();
+        assert_eq!(hits[0].message.message_id.as_str(), "1");
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let mut index = EmbeddingIndex::new("keywords");
+        index.index_messages("telegram", &[message("1", "api design review")], &KeywordEmbedder).await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("chat-index-{}.idx", uuid::Uuid::new_v4().simple()));
+        index.save_to(&path).unwrap();
+
+        let loaded = EmbeddingIndex::load_from(&path, "keywords").unwrap();
+        let other_model = EmbeddingIndex::load_from(&path, "other").unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.contains("telegram", &message("1", "")));
+        assert!(other_model.is_empty());
+
+        let hits = loaded.search(&[1.0, 0.0, 0.0], 5, |m| m.source == "telegram");
+        assert!((hits[0].score - 1.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_index_attachments() {
+        let mut report = message("1", "here you go");
+        report.content = MessageContent::File { filename: Some("release.pdf".to_string()), url: None };
+        let mut index = EmbeddingIndex::new("keywords");
+        assert_eq!(index.index_messages("telegram", &[report.clone()], &KeywordEmbedder).await.unwrap(), 0);
+
+        // The document's text is indexed once it has been extracted
+        report.media_text = Some("Release plan: deploy on Friday".to_string());
+        assert_eq!(index.index_messages("telegram", &[report.clone()], &KeywordEmbedder).await.unwrap(), 1);
+        assert!(index.contains_attachment("telegram", &report));
+        assert!(!index.contains("telegram", &report));
+
+        let hits = index.query(&KeywordEmbedder, "when is the deploy?", 5, |m| m.attachment).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message.message_id.as_str(), "1");
+        assert!(index.query(&KeywordEmbedder, "deploy", 5, |m| !m.attachment).await.unwrap().is_empty());
+    }
+}
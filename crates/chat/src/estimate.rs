@@ -0,0 +1,166 @@
+//! Cost estimates for `--dry-run` exports and syncs, made without fetching any messages
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::Duration;
+
+/// How a source lets requests through: a burst at full speed, then a steady rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub burst: usize,
+    pub per_second: f64,
+}
+
+impl RateLimit {
+    /// Telegram answers about 30 requests quickly, then starts replying with FLOOD_WAIT
+    pub const TELEGRAM: RateLimit = RateLimit {
+        burst: 30,
+        per_second: 1.0,
+    };
+
+    /// The limit a source is known to apply, if any
+    pub fn for_source(source: &str) -> Option<RateLimit> {
+        (source == "telegram").then_some(Self::TELEGRAM)
+    }
+
+    /// Time spent waiting on the limit while sending `requests`
+    pub fn delay(&self, requests: usize) -> Duration {
+        let throttled = requests.saturating_sub(self.burst);
+        Duration::from_secs_f64(throttled as f64 / self.per_second.max(0.01))
+    }
+}
+
+/// Rough cost of an export or sync
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportEstimate {
+    pub chats: usize,
+    /// Messages in the chats whose size is known
+    pub messages: usize,
+    /// Chats of unknown size, counted as a single request each
+    pub unknown_chats: usize,
+    pub api_calls: usize,
+    pub delay: Duration,
+}
+
+impl std::iter::Sum for ExportEstimate {
+    /// Sources are fetched one after another, so their delays add up
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, estimate| ExportEstimate {
+            chats: total.chats + estimate.chats,
+            messages: total.messages + estimate.messages,
+            unknown_chats: total.unknown_chats + estimate.unknown_chats,
+            api_calls: total.api_calls + estimate.api_calls,
+            delay: total.delay + estimate.delay,
+        })
+    }
+}
+
+/// Estimate fetching chats of the given sizes (`None` when unknown) in pages of
+/// `page_size`, at most `limit` messages per chat, after `listing_calls` requests
+/// to find the chats
+pub fn estimate_export(
+    sizes: &[Option<usize>],
+    limit: Option<usize>,
+    page_size: usize,
+    listing_calls: usize,
+    rate: Option<RateLimit>,
+) -> ExportEstimate {
+    let known: Vec<usize> = sizes
+        .iter()
+        .flatten()
+        .map(|&size| limit.map_or(size, |l| l.min(size)))
+        .collect();
+    let unknown_chats = sizes.len() - known.len();
+
+    // Even an empty chat takes one request to find out
+    let pages: usize = known.iter().map(|&n| n.div_ceil(page_size.max(1)).max(1)).sum();
+    let api_calls = listing_calls + unknown_chats + pages;
+
+    ExportEstimate {
+        chats: sizes.len(),
+        messages: known.iter().sum(),
+        unknown_chats,
+        api_calls,
+        delay: rate.map_or(Duration::ZERO, |rate| rate.delay(api_calls)),
+    }
+}
+
+/// Messages in a chat's sync archive file within the time range, or `None` when
+/// the chat was never synced
+pub fn archived_messages(
+    path: &Path,
+    since: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+) -> Result<Option<usize>> {
+    #[derive(Deserialize)]
+    struct Stamp {
+        timestamp: DateTime<Utc>,
+    }
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut count = 0;
+    for line in BufReader::new(file).lines() {
+        let Ok(stamp) = serde_json::from_str::<Stamp>(&line?) else {
+            continue;
+        };
+        if since.is_none_or(|since| stamp.timestamp >= since) && before.is_none_or(|before| stamp.timestamp < before) {
+            count += 1;
+        }
+    }
+
+    Ok(Some(count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_estimate_export() {
+        let estimate = estimate_export(&[Some(250), Some(0), None], Some(120), 100, 1, None);
+        assert_eq!(estimate.chats, 3);
+        assert_eq!(estimate.messages, 120);
+        assert_eq!(estimate.unknown_chats, 1);
+        // 1 listing + 2 pages + 1 for the empty chat + 1 for the unknown one
+        assert_eq!(estimate.api_calls, 5);
+        assert_eq!(estimate.delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_estimate_rate_limit_delay() {
+        let estimate = estimate_export(&[Some(4_000)], None, 100, 1, Some(RateLimit::TELEGRAM));
+        assert_eq!(estimate.api_calls, 41);
+        assert_eq!(estimate.delay, Duration::from_secs(11));
+
+        let quick = estimate_export(&[Some(500)], None, 100, 1, Some(RateLimit::TELEGRAM));
+        assert_eq!(quick.delay, Duration::ZERO);
+
+        let total: ExportEstimate = [estimate, quick].into_iter().sum();
+        assert_eq!((total.chats, total.messages, total.api_calls), (2, 4_500, 47));
+        assert_eq!(total.delay, Duration::from_secs(11));
+    }
+
+    #[test]
+    fn test_archived_messages() {
+        let path = std::env::temp_dir().join(format!("chat-estimate-{}.jsonl", uuid::Uuid::new_v4().simple()));
+        assert_eq!(archived_messages(&path, None, None).unwrap(), None);
+
+        let at = |day| Utc.with_ymd_and_hms(2025, 1, day, 12, 0, 0).unwrap();
+        let lines: Vec<String> = (1..=5)
+            .map(|day| serde_json::json!({ "id": day.to_string(), "timestamp": at(day) }).to_string())
+            .collect();
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        assert_eq!(archived_messages(&path, None, None).unwrap(), Some(5));
+        assert_eq!(archived_messages(&path, Some(at(2)), Some(at(5))).unwrap(), Some(3));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
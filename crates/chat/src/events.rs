@@ -0,0 +1,277 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Events kept for subscribers that fall behind
+const CAPACITY: usize = 64;
+
+/// What happened to a source's connection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SourceEventKind {
+    /// The source is connected and answering
+    Connected,
+    /// The source lost its connection or was removed
+    Disconnected {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// The source's session was revoked or has expired; signing in again is needed
+    AuthExpired { reason: String },
+    /// The service asked us to slow down
+    RateLimited {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_secs: Option<u64>,
+    },
+}
+
+/// A connection event for one source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceEvent {
+    pub source: String,
+    #[serde(flatten)]
+    pub kind: SourceEventKind,
+    pub at: DateTime<Utc>,
+}
+
+impl SourceEvent {
+    pub fn new(source: impl Into<String>, kind: SourceEventKind) -> Self {
+        Self { source: source.into(), kind, at: Utc::now() }
+    }
+
+    /// Whether the event needs the user's attention
+    pub fn is_problem(&self) -> bool {
+        !matches!(self.kind, SourceEventKind::Connected)
+    }
+
+    /// One-line description for logs and notifications
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            SourceEventKind::Connected => format!("{} connected", self.source),
+            SourceEventKind::Disconnected { reason: None } => format!("{} disconnected", self.source),
+            SourceEventKind::Disconnected { reason: Some(reason) } => {
+                format!("{} disconnected: {}", self.source, reason)
+            }
+            SourceEventKind::AuthExpired { .. } => {
+                format!("{} needs to sign in again, run '{}'", self.source, login_command(&self.source))
+            }
+            SourceEventKind::RateLimited { retry_after_secs: Some(secs) } => {
+                format!("{} is rate limited for {}s", self.source, secs)
+            }
+            SourceEventKind::RateLimited { retry_after_secs: None } => format!("{} is rate limited", self.source),
+        }
+    }
+}
+
+/// CLI command that signs a source in again
+pub fn login_command(source: &str) -> String {
+    format!("chat {} init", source)
+}
+
+/// Recognize connection problems in a source error.
+///
+/// Returns None for errors that say nothing about the connection, like an unknown chat.
+pub fn classify_error(error: &anyhow::Error) -> Option<SourceEventKind> {
+    let message = format!("{:#}", error);
+    let lower = message.to_lowercase();
+
+    if let Some(secs) = flood_wait_secs(&message) {
+        return Some(SourceEventKind::RateLimited { retry_after_secs: Some(secs) });
+    }
+    if ["too many requests", "rate limit", "flood"].iter().any(|p| lower.contains(p)) {
+        return Some(SourceEventKind::RateLimited { retry_after_secs: None });
+    }
+
+    const AUTH: [&str; 6] = [
+        "auth_key_unregistered",
+        "session_revoked",
+        "session_expired",
+        "user_deactivated",
+        "not authenticated",
+        "unauthorized",
+    ];
+    if AUTH.iter().any(|p| lower.contains(p)) {
+        return Some(SourceEventKind::AuthExpired { reason: first_line(&message) });
+    }
+
+    const CONNECTION: [&str; 6] = [
+        "not connected",
+        "connection",
+        "disconnected",
+        "timed out",
+        "broken pipe",
+        "network",
+    ];
+    if CONNECTION.iter().any(|p| lower.contains(p)) {
+        return Some(SourceEventKind::Disconnected { reason: Some(first_line(&message)) });
+    }
+
+    None
+}
+
+/// Seconds from a Telegram `FLOOD_WAIT_<n>` / `FLOOD_WAIT (n)` error
+fn flood_wait_secs(message: &str) -> Option<u64> {
+    let rest = &message[message.find("FLOOD_WAIT")? + "FLOOD_WAIT".len()..];
+    let digits: String = rest
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+fn first_line(message: &str) -> String {
+    message.lines().next().unwrap_or_default().to_string()
+}
+
+/// Broadcasts source connection events to any number of subscribers.
+///
+/// Clones share subscribers. Repeats of a source's current state (connected twice,
+/// auth expired on every query) are dropped so consumers can notify on every event.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<SourceEvent>,
+    states: Arc<Mutex<HashMap<String, SourceEventKind>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender, states: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// Receive events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<SourceEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish an event unless it repeats the source's current state.
+    /// Returns whether it was published.
+    pub fn emit(&self, source: &str, kind: SourceEventKind) -> bool {
+        let Ok(mut states) = self.states.lock() else {
+            return false;
+        };
+
+        if !matches!(kind, SourceEventKind::RateLimited { .. }) {
+            let repeated = states.get(source).is_some_and(|current| match (current, &kind) {
+                (SourceEventKind::Connected, SourceEventKind::Connected) => true,
+                (SourceEventKind::Disconnected { .. }, SourceEventKind::Disconnected { .. }) => true,
+                (SourceEventKind::AuthExpired { .. }, SourceEventKind::AuthExpired { .. }) => true,
+                // An expired session also shows up as "not connected"
+                (SourceEventKind::AuthExpired { .. }, SourceEventKind::Disconnected { .. }) => true,
+                _ => false,
+            });
+            if repeated {
+                return false;
+            }
+            states.insert(source.to_string(), kind.clone());
+        }
+        drop(states);
+
+        // No subscribers is fine
+        let _ = self.sender.send(SourceEvent::new(source, kind));
+        true
+    }
+
+    /// Record a source's observed connection state, publishing changes
+    pub fn set_connected(&self, source: &str, connected: bool) -> bool {
+        let kind = if connected {
+            SourceEventKind::Connected
+        } else {
+            SourceEventKind::Disconnected { reason: None }
+        };
+        self.emit(source, kind)
+    }
+
+    /// Publish the connection problem behind a source error, if there is one
+    pub fn report_error(&self, source: &str, error: &anyhow::Error) -> bool {
+        match classify_error(error) {
+            Some(kind) => self.emit(source, kind),
+            None => false,
+        }
+    }
+
+    /// Whether the source's session expired and nothing has worked since
+    pub fn auth_required(&self, source: &str) -> bool {
+        self.states
+            .lock()
+            .is_ok_and(|states| matches!(states.get(source), Some(SourceEventKind::AuthExpired { .. })))
+    }
+
+    /// Forget a source's state, so its next event is always published
+    pub fn forget(&self, source: &str) {
+        if let Ok(mut states) = self.states.lock() {
+            states.remove(source);
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.sender.receiver_count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_error() {
+        let rate = classify_error(&anyhow::anyhow!("rpc error 420: FLOOD_WAIT (31)"));
+        assert_eq!(rate, Some(SourceEventKind::RateLimited { retry_after_secs: Some(31) }));
+        let rate = classify_error(&anyhow::anyhow!("FLOOD_WAIT_12"));
+        assert_eq!(rate, Some(SourceEventKind::RateLimited { retry_after_secs: Some(12) }));
+
+        let auth = classify_error(&anyhow::anyhow!("rpc error 401: AUTH_KEY_UNREGISTERED"));
+        assert!(matches!(auth, Some(SourceEventKind::AuthExpired { .. })));
+
+        let down = classify_error(&anyhow::anyhow!("Source 'telegram' is not connected"));
+        assert!(matches!(down, Some(SourceEventKind::Disconnected { .. })));
+
+        assert_eq!(classify_error(&anyhow::anyhow!("Chat not found: 42")), None);
+    }
+
+    #[test]
+    fn test_repeats_are_dropped() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+
+        assert!(bus.set_connected("telegram", true));
+        assert!(!bus.set_connected("telegram", true));
+        assert!(bus.emit("telegram", SourceEventKind::AuthExpired { reason: "revoked".into() }));
+        assert!(bus.auth_required("telegram"));
+        assert!(!bus.set_connected("telegram", false));
+        assert!(bus.emit("telegram", SourceEventKind::RateLimited { retry_after_secs: None }));
+        assert!(bus.emit("telegram", SourceEventKind::RateLimited { retry_after_secs: None }));
+        assert!(bus.set_connected("telegram", true));
+        assert!(!bus.auth_required("telegram"));
+
+        let kinds: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok()).map(|e| e.kind).collect();
+        assert_eq!(kinds.len(), 5);
+        assert_eq!(kinds[0], SourceEventKind::Connected);
+        assert_eq!(kinds[4], SourceEventKind::Connected);
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = SourceEvent::new("telegram", SourceEventKind::RateLimited { retry_after_secs: Some(5) });
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["source"], "telegram");
+        assert_eq!(json["kind"], "rate_limited");
+        assert_eq!(json["retry_after_secs"], 5);
+
+        let parsed: SourceEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, event);
+    }
+}
@@ -1,13 +1,15 @@
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
 
-use crate::types::{ChatId, ChatPattern};
+use crate::types::{ChatId, ChatPattern, ContentType};
 
 /// Parse a source:pattern filter string
 /// Examples:
 /// - "telegram:Antti" -> source="telegram", pattern=Name("Antti")
 /// - "telegram:123456" -> source="telegram", pattern=Id(ChatId("123456"))
 /// - "telegram:*" -> source="telegram", pattern=All
+/// - "telegram:#work" -> source="telegram", pattern=Label("work")
+/// - "telegram:Rust/Jobs" -> source="telegram", pattern=Topic { group: Name("Rust"), topic: "Jobs" }
 /// - "*:*" -> source=None, pattern=All
 pub fn parse_source_filter(input: &str) -> Result<(Option<String>, ChatPattern)> {
     let parts: Vec<&str> = input.splitn(2, ':').collect();
@@ -39,10 +41,30 @@ pub fn parse_source_filter(input: &str) -> Result<(Option<String>, ChatPattern)>
 /// Examples:
 /// - "*" -> All
 /// - "123456" (numeric) -> Id(ChatId("123456"))
+/// - "#work" -> Label("work")
 /// - "Antti" -> Name("Antti")
+/// - "Rust/Jobs", "-100123/45" -> Topic of a forum group (topic name or ID after the slash)
 fn parse_chat_pattern(pattern: &str) -> Result<ChatPattern> {
+    if let Some((group, topic)) = pattern.split_once('/') {
+        if group.is_empty() || topic.is_empty() {
+            anyhow::bail!("Invalid topic pattern '{}'. Expected 'group/topic'", pattern);
+        }
+        if group.starts_with('#') {
+            anyhow::bail!("Topics can't be selected within a label: '{}'", pattern);
+        }
+        return Ok(ChatPattern::Topic {
+            group: Box::new(parse_chat_pattern(group)?),
+            topic: topic.to_string(),
+        });
+    }
+
     if pattern == "*" {
         Ok(ChatPattern::All)
+    } else if let Some(label) = pattern.strip_prefix('#') {
+        if label.is_empty() {
+            anyhow::bail!("Empty label in pattern '{}'", pattern);
+        }
+        Ok(ChatPattern::Label(label.to_string()))
     } else if pattern.chars().all(|c| c.is_ascii_digit() || c == '-') {
         // Numeric pattern is treated as ID
         Ok(ChatPattern::Id(ChatId::new(pattern)))
@@ -78,6 +100,35 @@ pub fn parse_time_spec(spec: &str) -> Result<DateTime<Utc>> {
     anyhow::bail!("Invalid time specification: {}. Expected format: '7d', '2h', '2025-01-15', or ISO 8601 datetime", spec)
 }
 
+/// Parse a duration specification (e.g., "2d", "3h", "30m", "1w")
+pub fn parse_duration_spec(spec: &str) -> Result<Duration> {
+    match parse_relative_time(spec) {
+        Some(duration) if duration > Duration::zero() => Ok(duration),
+        _ => anyhow::bail!("Invalid duration: {}. Expected format: '2d', '3h', '30m', or '1w'", spec),
+    }
+}
+
+/// Parse a comma-separated list of content types (e.g., "audio", "image,video")
+pub fn parse_content_types(spec: &str) -> Result<Vec<ContentType>> {
+    spec.split(',')
+        .map(|name| match name.trim().to_lowercase().as_str() {
+            "text" => Ok(ContentType::Text),
+            "image" | "photo" => Ok(ContentType::Image),
+            "video" => Ok(ContentType::Video),
+            "audio" | "voice" => Ok(ContentType::Audio),
+            "file" | "document" => Ok(ContentType::File),
+            "sticker" => Ok(ContentType::Sticker),
+            "location" => Ok(ContentType::Location),
+            "contact" => Ok(ContentType::Contact),
+            "system" => Ok(ContentType::System),
+            other => anyhow::bail!(
+                "Unknown content type: {}. Expected text, image, video, audio, file, sticker, location, contact or system",
+                other
+            ),
+        })
+        .collect()
+}
+
 /// Parse relative time specification (7d, 2h, 30m, 60s)
 fn parse_relative_time(spec: &str) -> Option<Duration> {
     let spec = spec.trim();
@@ -130,6 +181,33 @@ mod tests {
         assert_eq!(pattern, ChatPattern::All);
     }
 
+    #[test]
+    fn test_parse_source_filter_with_label() {
+        let (source, pattern) = parse_source_filter("telegram:#work").unwrap();
+        assert_eq!(source.as_deref(), Some("telegram"));
+        assert_eq!(pattern, ChatPattern::Label("work".to_string()));
+        assert!(parse_source_filter("telegram:#").is_err());
+    }
+
+    #[test]
+    fn test_parse_source_filter_with_topic() {
+        let (source, pattern) = parse_source_filter("telegram:Rust/Jobs").unwrap();
+        assert_eq!(source.as_deref(), Some("telegram"));
+        assert_eq!(pattern, ChatPattern::Topic {
+            group: Box::new(ChatPattern::Name("Rust".to_string())),
+            topic: "Jobs".to_string(),
+        });
+
+        let (_, pattern) = parse_source_filter("telegram:-100123/45").unwrap();
+        assert_eq!(pattern, ChatPattern::Topic {
+            group: Box::new(ChatPattern::Id(ChatId::new("-100123"))),
+            topic: "45".to_string(),
+        });
+
+        assert!(parse_source_filter("telegram:Rust/").is_err());
+        assert!(parse_source_filter("telegram:#work/Jobs").is_err());
+    }
+
     #[test]
     fn test_parse_source_filter_no_colon() {
         let (source, pattern) = parse_source_filter("Antti").unwrap();
@@ -175,6 +253,21 @@ mod tests {
         assert!((dt - expected).num_seconds().abs() < 2);
     }
 
+    #[test]
+    fn test_parse_duration_spec() {
+        assert_eq!(parse_duration_spec("2d").unwrap(), Duration::days(2));
+        assert_eq!(parse_duration_spec("30m").unwrap(), Duration::minutes(30));
+        assert!(parse_duration_spec("0h").is_err());
+        assert!(parse_duration_spec("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_content_types() {
+        assert_eq!(parse_content_types("audio").unwrap(), vec![ContentType::Audio]);
+        assert_eq!(parse_content_types("Image, video").unwrap(), vec![ContentType::Image, ContentType::Video]);
+        assert!(parse_content_types("gif").is_err());
+    }
+
     #[test]
     fn test_parse_time_spec_date_only() {
         let dt = parse_time_spec("2025-01-15").unwrap();
@@ -0,0 +1,536 @@
+//! Forgetting a chat or a person: their messages and everything derived from them are
+//! removed from local data, and a log of what was forgotten keeps them from coming back.
+//!
+//! Local data is what `chat sync` archived, text derived from attachments, the embedding
+//! index, todos, notes and labels. The tamper-evident archive (`chat archive`) can't be
+//! edited without breaking its hash chain, so its records are only counted.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::archive::{self, ArchiveWriter};
+use crate::embeddings::EmbeddingIndex;
+use crate::global_id::GlobalChatId;
+use crate::labels::LabelStore;
+use crate::media_text::MediaTextStore;
+use crate::notes::NoteStore;
+use crate::sync;
+use crate::todos::TodoStore;
+use crate::types::{Message, MessageId, UserId};
+
+/// A chat or a person to forget
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgetTarget {
+    Chat(GlobalChatId),
+    User { source: String, user_id: UserId },
+}
+
+impl ForgetTarget {
+    /// A person as `source:user_id`
+    pub fn parse_user(input: &str) -> Result<Self> {
+        let (source, user_id) = input
+            .trim()
+            .split_once(':')
+            .filter(|(source, user_id)| !source.is_empty() && !user_id.is_empty())
+            .with_context(|| format!("Person '{}' needs a source prefix, e.g. telegram:{}", input, input))?;
+        Ok(Self::User { source: source.to_lowercase(), user_id: UserId::new(user_id) })
+    }
+
+    pub fn source(&self) -> &str {
+        match self {
+            Self::Chat(chat) => &chat.source,
+            Self::User { source, .. } => source,
+        }
+    }
+
+    /// Whether a message from `source` is in the chat, or was sent by the person
+    pub fn matches(&self, source: &str, message: &Message) -> bool {
+        match self {
+            Self::Chat(chat) => *chat == GlobalChatId::from_chat_id(source, &message.chat_id),
+            Self::User { source: user_source, user_id } => {
+                user_source.eq_ignore_ascii_case(source) && message.sender.id == *user_id
+            }
+        }
+    }
+
+    /// Whether an index entry or todo about a message is the target's. Ones from before
+    /// sender IDs were recorded are matched by message, or failing that by sender name.
+    fn covers_entry(
+        &self,
+        chat: &GlobalChatId,
+        message_id: &MessageId,
+        sender_id: Option<&UserId>,
+        sender: Option<&str>,
+        written: &Written,
+    ) -> bool {
+        match self {
+            Self::Chat(id) => id == chat,
+            Self::User { source, user_id } => {
+                chat.source == *source
+                    && match sender_id {
+                        Some(id) => id == user_id,
+                        None => {
+                            written.messages.contains(&(chat.clone(), message_id.clone()))
+                                || sender.is_some_and(|name| written.names.contains(name))
+                        }
+                    }
+            }
+        }
+    }
+}
+
+impl fmt::Display for ForgetTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Chat(chat) => write!(f, "chat {}", chat),
+            Self::User { source, user_id } => write!(f, "person {}:{}", source, user_id.as_str()),
+        }
+    }
+}
+
+/// The target's messages found so far, to find derived data that doesn't say who sent it
+#[derive(Default)]
+struct Written {
+    messages: HashSet<(GlobalChatId, MessageId)>,
+    names: HashSet<String>,
+}
+
+impl Written {
+    fn add(&mut self, source: &str, message: &Message) {
+        self.messages.insert((GlobalChatId::from_chat_id(source, &message.chat_id), message.id.clone()));
+        self.names.extend(message.sender.display_name.iter().chain(&message.sender.username).cloned());
+    }
+}
+
+/// What forgetting a target removed, or would remove on a dry run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForgetReport {
+    /// Messages in the sync archive
+    pub messages: usize,
+    /// Messages with text derived from their attachments
+    pub media_text: usize,
+    /// Embedding index entries
+    pub embeddings: usize,
+    pub todos: usize,
+    pub notes: usize,
+    pub labels: usize,
+    /// Records in the tamper-evident archive, which are kept
+    pub archived_records: u64,
+}
+
+impl ForgetReport {
+    /// Whether nothing was removed
+    pub fn is_empty(&self) -> bool {
+        self.messages + self.media_text + self.embeddings + self.todos + self.notes + self.labels == 0
+    }
+}
+
+/// Where local data is kept
+#[derive(Debug, Clone)]
+pub struct LocalData {
+    pub sync_archive: PathBuf,
+    pub archive_log: PathBuf,
+    pub media_text: PathBuf,
+    pub embeddings: PathBuf,
+    pub todos: PathBuf,
+    pub notes: PathBuf,
+    pub labels: PathBuf,
+    pub forget_log: PathBuf,
+}
+
+impl LocalData {
+    /// The default location of each store
+    pub fn default_paths() -> Result<Self> {
+        Ok(Self {
+            sync_archive: sync::default_archive_dir()?,
+            archive_log: ArchiveWriter::default_dir()?,
+            media_text: MediaTextStore::default_path()?,
+            embeddings: EmbeddingIndex::default_path()?,
+            todos: TodoStore::default_path()?,
+            notes: NoteStore::default_path()?,
+            labels: LabelStore::default_path()?,
+            forget_log: ForgetLog::default_path()?,
+        })
+    }
+}
+
+/// Remove a target from local data and record it in the forget log. A dry run only
+/// reports what would be removed.
+pub fn forget(data: &LocalData, target: &ForgetTarget, dry_run: bool) -> Result<ForgetReport> {
+    let mut report = ForgetReport::default();
+    let mut written = Written::default();
+    let source = target.source().to_string();
+
+    for path in sync_archive_files(&data.sync_archive, target)? {
+        let removed = scrub_archive_file(&path, dry_run, |message| {
+            target.matches(&source, message)
+                && sync::archive_file(&data.sync_archive, &GlobalChatId::from_chat_id(&source, &message.chat_id)) == path
+        })?;
+        report.messages += removed.len();
+        for message in &removed {
+            written.add(&source, message);
+        }
+    }
+
+    if let Some(model) = EmbeddingIndex::stored_model(&data.embeddings)? {
+        let mut index = EmbeddingIndex::load_from(&data.embeddings, &model)?;
+        let removed = index.remove_where(|entry| {
+            let chat = GlobalChatId::from_chat_id(&entry.source, &entry.chat_id);
+            target.covers_entry(&chat, &entry.message_id, entry.sender_id.as_ref(), entry.sender.as_deref(), &written)
+        });
+        report.embeddings = removed.len();
+        written.messages.extend(
+            removed.into_iter().map(|entry| (GlobalChatId::from_chat_id(&entry.source, &entry.chat_id), entry.message_id)),
+        );
+        if !dry_run && report.embeddings > 0 {
+            index.save_to(&data.embeddings)?;
+        }
+    }
+
+    let mut todos = TodoStore::load_from(&data.todos)?;
+    let removed = todos.remove_where(|todo| {
+        target.covers_entry(&todo.chat, &todo.message_id, todo.sender_id.as_ref(), Some(todo.sender.as_str()), &written)
+    });
+    report.todos = removed.len();
+    written.messages.extend(removed.into_iter().map(|todo| (todo.chat, todo.message_id)));
+    if !dry_run && report.todos > 0 {
+        todos.save_to(&data.todos)?;
+    }
+
+    // Derived text is keyed by message, so it goes last, once the target's messages are known
+    let mut media_text = MediaTextStore::load_from(&data.media_text)?;
+    report.media_text = match target {
+        ForgetTarget::Chat(chat) => media_text.remove_chat(chat),
+        ForgetTarget::User { .. } => written.messages.iter().filter(|(chat, id)| media_text.remove(chat, id)).count(),
+    };
+    if !dry_run && report.media_text > 0 {
+        media_text.save_to(&data.media_text)?;
+    }
+
+    let mut notes = NoteStore::load_from(&data.notes)?;
+    let removed = match target {
+        ForgetTarget::Chat(chat) => notes.remove_chat(&chat.source, &chat.chat_id()),
+        ForgetTarget::User { source, user_id } => notes.remove_user(source, user_id.as_str()),
+    };
+    report.notes = usize::from(removed);
+    if !dry_run && removed {
+        notes.save_to(&data.notes)?;
+    }
+
+    if let ForgetTarget::Chat(chat) = target {
+        let mut labels = LabelStore::load_from(&data.labels)?;
+        report.labels = labels.remove_chat(&chat.source, &chat.chat_id());
+        if !dry_run && report.labels > 0 {
+            labels.save_to(&data.labels)?;
+        }
+    }
+
+    report.archived_records = archive::count_matching(&data.archive_log, |record| target.matches(&record.source, &record.message))?;
+
+    if !dry_run {
+        ForgetLog::load_from(&data.forget_log)?.append_to(&data.forget_log, ForgetRecord {
+            at: Utc::now(),
+            action: ForgetAction::Forget,
+            target: target.clone(),
+            removed: report.clone(),
+        })?;
+    }
+
+    Ok(report)
+}
+
+/// Sync archive files that can hold the target's messages: the chat's own file, or every
+/// file for a person
+fn sync_archive_files(dir: &Path, target: &ForgetTarget) -> Result<Vec<PathBuf>> {
+    if let ForgetTarget::Chat(chat) = target {
+        let path = sync::archive_file(dir, chat);
+        return Ok(if path.exists() { vec![path] } else { Vec::new() });
+    }
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read archive directory: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Drop the messages `f` picks from a sync archive file, returning them. Lines that aren't
+/// messages are kept, and a file left empty is deleted.
+fn scrub_archive_file(path: &Path, dry_run: bool, mut f: impl FnMut(&Message) -> bool) -> Result<Vec<Message>> {
+    let contents = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let mut kept = String::new();
+    let mut removed = Vec::new();
+    for line in contents.lines() {
+        match serde_json::from_str::<Message>(line) {
+            Ok(message) if f(&message) => removed.push(message),
+            _ => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+    }
+
+    if !dry_run && !removed.is_empty() {
+        if kept.trim().is_empty() {
+            std::fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        } else {
+            let tmp = path.with_extension("jsonl.tmp");
+            std::fs::write(&tmp, kept).with_context(|| format!("Failed to write {}", path.display()))?;
+            std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+    }
+
+    Ok(removed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgetAction {
+    Forget,
+    /// Let a forgotten target back into local data
+    Unforget,
+}
+
+/// One entry of the forget log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgetRecord {
+    pub at: DateTime<Utc>,
+    pub action: ForgetAction,
+    pub target: ForgetTarget,
+    /// What was removed; empty when unforgetting
+    #[serde(default)]
+    pub removed: ForgetReport,
+}
+
+/// Append-only log of forgotten chats and people (`<data dir>/chat/forget-log.jsonl`)
+///
+/// It holds IDs and counts, never message content. Sync, the archive, media processing,
+/// the embedding index and todo scans skip whatever it says is forgotten.
+#[derive(Debug, Clone, Default)]
+pub struct ForgetLog {
+    records: Vec<ForgetRecord>,
+    /// Targets whose latest record forgets them
+    forgotten: Vec<ForgetTarget>,
+}
+
+impl ForgetLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the log (`<data dir>/chat/forget-log.jsonl`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::data_dir()
+            .context("Failed to get data directory")?
+            .join("chat");
+        Ok(dir.join("forget-log.jsonl"))
+    }
+
+    /// Load the log from the default location, returning an empty log if none exists
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let mut log = Self::default();
+        if !path.exists() {
+            return Ok(log);
+        }
+
+        let contents = std::fs::read_to_string(path).context("Failed to read forget log")?;
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            log.push(serde_json::from_str(line).context("Failed to parse forget log")?);
+        }
+        Ok(log)
+    }
+
+    /// Add a record and append it to the log file
+    pub fn append_to(&mut self, path: &Path, record: ForgetRecord) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create forget log directory")?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context("Failed to open forget log")?;
+        writeln!(file, "{}", serde_json::to_string(&record)?).context("Failed to write forget log")?;
+
+        self.push(record);
+        Ok(())
+    }
+
+    fn push(&mut self, record: ForgetRecord) {
+        self.forgotten.retain(|target| *target != record.target);
+        if record.action == ForgetAction::Forget {
+            self.forgotten.push(record.target.clone());
+        }
+        self.records.push(record);
+    }
+
+    /// All records, oldest first
+    pub fn records(&self) -> &[ForgetRecord] {
+        &self.records
+    }
+
+    /// Chats and people forgotten and not let back in
+    pub fn forgotten(&self) -> &[ForgetTarget] {
+        &self.forgotten
+    }
+
+    pub fn is_forgotten(&self, target: &ForgetTarget) -> bool {
+        self.forgotten.contains(target)
+    }
+
+    /// Whether a message from `source` is in a forgotten chat or by a forgotten person
+    pub fn covers(&self, source: &str, message: &Message) -> bool {
+        self.forgotten.iter().any(|target| target.matches(source, message))
+    }
+
+    /// Whether a whole chat is forgotten
+    pub fn covers_chat(&self, chat: &GlobalChatId) -> bool {
+        self.forgotten.iter().any(|target| matches!(target, ForgetTarget::Chat(id) if id == chat))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::IndexedMessage;
+    use crate::media_text::{DerivedKind, DerivedText};
+    use crate::test_utils::MessageBuilder;
+
+    fn local_data() -> LocalData {
+        let dir = std::env::temp_dir().join(format!("chat-forget-{}", uuid::Uuid::new_v4().simple()));
+        LocalData {
+            sync_archive: dir.join("archive"),
+            archive_log: dir.join("archive-log"),
+            media_text: dir.join("media_text.json"),
+            embeddings: dir.join("embeddings.idx"),
+            todos: dir.join("todos.json"),
+            notes: dir.join("notes.json"),
+            labels: dir.join("labels.json"),
+            forget_log: dir.join("forget-log.jsonl"),
+        }
+    }
+
+    fn indexed(message: &Message, sender_id: Option<UserId>) -> IndexedMessage {
+        IndexedMessage {
+            source: "telegram".to_string(),
+            chat_id: message.chat_id.clone(),
+            message_id: message.id.clone(),
+            sender: message.sender.display_name.clone(),
+            sender_id,
+            timestamp: message.timestamp,
+            text: "hello".to_string(),
+            attachment: false,
+        }
+    }
+
+    #[test]
+    fn test_forget_user() {
+        let data = local_data();
+        let messages = [
+            MessageBuilder::new("1", "10").minute(1).from("bob").text("hi").build(),
+            MessageBuilder::new("2", "10").minute(2).from("ana").text("hello").build(),
+            MessageBuilder::new("3", "10").minute(3).from("bob").text("bye").build(),
+        ];
+        std::fs::create_dir_all(&data.sync_archive).unwrap();
+        let chat = GlobalChatId::new("telegram", "10");
+        let file = sync::archive_file(&data.sync_archive, &chat);
+        let lines: Vec<String> = messages.iter().map(|m| serde_json::to_string(m).unwrap()).collect();
+        std::fs::write(&file, lines.join("\n") + "\n").unwrap();
+
+        // Bob's second message was indexed before sender IDs were recorded
+        let mut index = EmbeddingIndex::new("test");
+        index.insert(indexed(&messages[0], Some(UserId::new("bob"))), vec![1.0, 0.0]).unwrap();
+        index.insert(indexed(&messages[1], Some(UserId::new("ana"))), vec![0.0, 1.0]).unwrap();
+        index.insert(indexed(&messages[2], None), vec![1.0, 1.0]).unwrap();
+        index.save_to(&data.embeddings).unwrap();
+
+        let mut media_text = MediaTextStore::new();
+        media_text.insert("telegram", &messages[2], DerivedText::new(DerivedKind::Ocr, "receipt", "tesseract"));
+        media_text.insert("telegram", &messages[1], DerivedText::new(DerivedKind::Ocr, "menu", "tesseract"));
+        media_text.save_to(&data.media_text).unwrap();
+
+        let mut notes = NoteStore::new();
+        notes.user_mut("telegram", "bob").text = "Met at the conference".to_string();
+        notes.save_to(&data.notes).unwrap();
+
+        let bob = ForgetTarget::parse_user("Telegram:bob").unwrap();
+        let expected = ForgetReport { messages: 2, media_text: 1, embeddings: 2, notes: 1, ..ForgetReport::default() };
+
+        // A dry run reports without touching anything
+        assert_eq!(forget(&data, &bob, true).unwrap(), expected);
+        assert_eq!(std::fs::read_to_string(&file).unwrap().lines().count(), 3);
+        assert!(!data.forget_log.exists());
+
+        assert_eq!(forget(&data, &bob, false).unwrap(), expected);
+        let kept = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(kept.lines().count(), 1);
+        assert!(kept.contains("hello"));
+        assert_eq!(EmbeddingIndex::load_from(&data.embeddings, "test").unwrap().len(), 1);
+        let media_text = MediaTextStore::load_from(&data.media_text).unwrap();
+        assert!(media_text.get("telegram", &messages[2]).is_empty());
+        assert_eq!(media_text.get("telegram", &messages[1]).len(), 1);
+        assert!(NoteStore::load_from(&data.notes).unwrap().user("telegram", "bob").is_none());
+
+        let log = ForgetLog::load_from(&data.forget_log).unwrap();
+        assert_eq!(log.records().len(), 1);
+        assert_eq!(log.records()[0].removed, expected);
+        assert!(log.covers("telegram", &messages[0]));
+        assert!(!log.covers("telegram", &messages[1]));
+        assert!(!log.covers("signal", &messages[0]));
+
+        // Nothing is left the second time, but it is still logged
+        assert!(forget(&data, &bob, false).unwrap().is_empty());
+        assert_eq!(ForgetLog::load_from(&data.forget_log).unwrap().records().len(), 2);
+    }
+
+    #[test]
+    fn test_forget_chat() {
+        let data = local_data();
+        let chat = GlobalChatId::new("telegram", "10");
+        let message = MessageBuilder::new("1", "10").from("bob").text("hi").build();
+        std::fs::create_dir_all(&data.sync_archive).unwrap();
+        let file = sync::archive_file(&data.sync_archive, &chat);
+        std::fs::write(&file, serde_json::to_string(&message).unwrap() + "\n").unwrap();
+
+        let mut labels = LabelStore::new();
+        labels.add("telegram", &chat.chat_id(), "work");
+        labels.add("telegram", &chat.chat_id(), "noisy");
+        labels.save_to(&data.labels).unwrap();
+
+        let target = ForgetTarget::Chat(chat.clone());
+        let report = forget(&data, &target, false).unwrap();
+        assert_eq!((report.messages, report.labels), (1, 2));
+        assert!(!file.exists());
+
+        let mut log = ForgetLog::load_from(&data.forget_log).unwrap();
+        assert!(log.covers_chat(&chat));
+        log.append_to(&data.forget_log, ForgetRecord {
+            at: Utc::now(),
+            action: ForgetAction::Unforget,
+            target: target.clone(),
+            removed: ForgetReport::default(),
+        })
+        .unwrap();
+        assert!(!log.is_forgotten(&target));
+        assert!(!ForgetLog::load_from(&data.forget_log).unwrap().covers("telegram", &message));
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::types::{Chat, ChatId};
+
+/// Telegram channel and supergroup dialog ids are the channel id prefixed with -100
+const TELEGRAM_CHANNEL_PREFIX: &str = "-100";
+
+/// Source-qualified chat identifier that stays stable across runs and id format changes.
+///
+/// The canonical form is `<source>:<platform_id>`. For Telegram the platform id is the
+/// bot-API dialog id (`123` users, `-123` basic groups, `-100123` channels); older forms
+/// such as `channel:123` or bare internal ids are normalized on construction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GlobalChatId {
+    pub source: String,
+    pub platform_id: String,
+}
+
+impl GlobalChatId {
+    /// Create a global id, normalizing the platform id for the source
+    pub fn new(source: impl Into<String>, platform_id: &str) -> Self {
+        let source = source.into().trim().to_lowercase();
+        let platform_id = normalize_platform_id(&source, platform_id);
+        Self { source, platform_id }
+    }
+
+    /// Global id for a source's chat
+    pub fn from_chat_id(source: &str, chat_id: &ChatId) -> Self {
+        Self::new(source, chat_id.as_str())
+    }
+
+    /// Parse `<source>:<id>`, or a bare id belonging to `default_source`
+    pub fn parse(input: &str, default_source: Option<&str>) -> Result<Self> {
+        let input = input.trim();
+
+        if let Some((source, id)) = input.split_once(':') {
+            if is_source_name(source) {
+                return Ok(Self::new(source, id));
+            }
+        }
+
+        match default_source {
+            Some(source) => Ok(Self::new(source, input)),
+            None => anyhow::bail!("Chat id '{}' needs a source prefix, e.g. telegram:{}", input, input),
+        }
+    }
+
+    /// The platform id as a source-level chat id
+    pub fn chat_id(&self) -> ChatId {
+        ChatId::new(self.platform_id.as_str())
+    }
+
+    /// Platform ids this id may refer to, canonical form first.
+    ///
+    /// A bare positive Telegram id is either a user's dialog id or a channel's internal id
+    /// from before dialog ids were used, so both readings are candidates.
+    pub fn candidates(&self) -> Vec<String> {
+        let mut candidates = vec![self.platform_id.clone()];
+
+        if self.source == "telegram" && self.platform_id.parse::<u64>().is_ok() {
+            candidates.push(format!("{}{}", TELEGRAM_CHANNEL_PREFIX, self.platform_id));
+            candidates.push(format!("-{}", self.platform_id));
+        }
+
+        candidates
+    }
+}
+
+impl fmt::Display for GlobalChatId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.source, self.platform_id)
+    }
+}
+
+impl FromStr for GlobalChatId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s, None)
+    }
+}
+
+impl Serialize for GlobalChatId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for GlobalChatId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Source names are lowercase identifiers; anything else before a colon is part of the id
+fn is_source_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && s.chars().any(|c| c.is_ascii_alphabetic())
+        && !matches!(s, "user" | "chat" | "channel")
+}
+
+/// Bring any historical form of a platform id into canonical form
+pub fn normalize_platform_id(source: &str, raw: &str) -> String {
+    let raw = raw.trim();
+
+    match source {
+        "telegram" => normalize_telegram(raw),
+        _ => raw.to_string(),
+    }
+}
+
+/// Telegram ids: `user:N`/`chat:N`/`channel:N` (peer references) and numeric forms
+/// with stray `+` or leading zeros all become bot-API dialog ids
+fn normalize_telegram(raw: &str) -> String {
+    // Forum topics are `<group>/<topic>`; only the group part has alternate forms
+    if let Some((group, topic)) = raw.split_once('/') {
+        return format!("{}/{}", normalize_telegram(group), topic.trim());
+    }
+
+    let digits = |s: &str| s.trim().trim_start_matches(['-', '+']).parse::<u64>().ok();
+
+    if let Some((kind, id)) = raw.split_once(':') {
+        if let Some(id) = digits(id) {
+            match kind.trim().to_lowercase().as_str() {
+                "user" => return id.to_string(),
+                "chat" => return format!("-{}", id),
+                "channel" => return format!("{}{}", TELEGRAM_CHANNEL_PREFIX, id),
+                _ => {}
+            }
+        }
+    }
+
+    match raw.parse::<i64>() {
+        Ok(id) => id.to_string(),
+        Err(_) => raw.to_string(),
+    }
+}
+
+/// Resolves user-supplied chat ids in any historical form against the chats a source knows
+#[derive(Debug, Clone, Default)]
+pub struct ChatIdResolver {
+    known: HashSet<GlobalChatId>,
+}
+
+impl ChatIdResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolver for a source's chat list
+    pub fn from_chats(source: &str, chats: &[Chat]) -> Self {
+        let mut resolver = Self::new();
+        for chat in chats {
+            resolver.add(GlobalChatId::from_chat_id(source, &chat.id));
+        }
+        resolver
+    }
+
+    pub fn add(&mut self, id: GlobalChatId) {
+        self.known.insert(id);
+    }
+
+    /// Resolve an id to a known chat, trying the canonical reading first.
+    /// Falls back to the normalized id when no known chat matches.
+    pub fn resolve(&self, input: &str, default_source: Option<&str>) -> Result<GlobalChatId> {
+        let id = GlobalChatId::parse(input, default_source)?;
+
+        let found = id
+            .candidates()
+            .into_iter()
+            .map(|candidate| GlobalChatId {
+                source: id.source.clone(),
+                platform_id: candidate,
+            })
+            .find(|candidate| self.known.contains(candidate));
+
+        Ok(found.unwrap_or(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_telegram_forms() {
+        assert_eq!(GlobalChatId::new("telegram", "channel:123").to_string(), "telegram:-100123");
+        assert_eq!(GlobalChatId::new("telegram", "chat:45").to_string(), "telegram:-45");
+        assert_eq!(GlobalChatId::new("telegram", "user:678").to_string(), "telegram:678");
+        assert_eq!(GlobalChatId::new("Telegram", " +0678 ").to_string(), "telegram:678");
+        assert_eq!(GlobalChatId::new("telegram", "channel:123/45").to_string(), "telegram:-100123/45");
+        assert_eq!(GlobalChatId::new("whatsapp", "Family Group").to_string(), "whatsapp:Family Group");
+    }
+
+    #[test]
+    fn test_parse_and_serde_roundtrip() {
+        let id: GlobalChatId = "telegram:channel:123".parse().unwrap();
+        assert_eq!(id.platform_id, "-100123");
+        assert_eq!(GlobalChatId::parse("-100123", Some("telegram")).unwrap(), id);
+        assert!(GlobalChatId::parse("-100123", None).is_err());
+
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"telegram:-100123\"");
+        let back: GlobalChatId = serde_json::from_str("\"telegram:channel:123\"").unwrap();
+        assert_eq!(back, id);
+    }
+
+    #[test]
+    fn test_resolver_disambiguates_bare_ids() {
+        let mut resolver = ChatIdResolver::new();
+        resolver.add(GlobalChatId::new("telegram", "-100123"));
+        resolver.add(GlobalChatId::new("telegram", "456"));
+
+        // Bare internal channel id from older versions
+        assert_eq!(resolver.resolve("123", Some("telegram")).unwrap().platform_id, "-100123");
+        // A known user id keeps its canonical reading
+        assert_eq!(resolver.resolve("telegram:456", None).unwrap().platform_id, "456");
+        // Unknown ids fall back to the normalized form
+        assert_eq!(resolver.resolve("789", Some("telegram")).unwrap().platform_id, "789");
+    }
+}
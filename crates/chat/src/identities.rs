@@ -0,0 +1,385 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Result of linking an account to a person
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    Added,
+    /// The account was already linked to this person
+    Unchanged,
+    /// The account belongs to someone else, named here; nothing was changed
+    Conflict(String),
+    /// The account was moved from the person named here
+    Moved(String),
+}
+
+/// A CSV row that could not be used
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RowError {
+    pub line: usize,
+    pub reason: String,
+}
+
+/// A CSV row linking an account that already belongs to someone else
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    pub line: usize,
+    /// `platform:id`
+    pub account: String,
+    /// Person the account is linked to
+    pub linked_to: String,
+    /// Person the row links it to
+    pub wanted: String,
+}
+
+/// What an import did, or would do in a dry run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportReport {
+    pub added: usize,
+    pub unchanged: usize,
+    /// Conflicting accounts moved to the person in the file (`--force`)
+    pub moved: usize,
+    pub invalid: Vec<RowError>,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// People and the accounts they use on each platform (`~/.config/chat/identities.json`)
+///
+/// Accounts are stored as `platform:id` with the platform lowercase, e.g.
+/// `telegram:12345` or `matrix:@ana:example.org`. An account belongs to at most one
+/// person, and a person without accounts is dropped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdentityStore {
+    #[serde(default)]
+    people: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl IdentityStore {
+    /// Create an empty identity store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the identities file (`~/.config/chat/identities.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("identities.json"))
+    }
+
+    /// Load identities from the default location, returning an empty store if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load identities from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read identities file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse identities file")
+    }
+
+    /// Save identities to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save identities to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create identities directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize identities")?;
+
+        std::fs::write(path, contents).context("Failed to write identities file")
+    }
+
+    /// `platform:id` for an account, checking both parts
+    pub fn account(platform: &str, id: &str) -> Result<String, String> {
+        let platform = platform.trim().to_lowercase();
+        let id = id.trim();
+        if platform.is_empty() {
+            return Err("platform is empty".to_string());
+        }
+        if !platform.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("platform '{}' may only contain letters, digits, '-' and '_'", platform));
+        }
+        if id.is_empty() {
+            return Err("id is empty".to_string());
+        }
+        if id.chars().any(char::is_control) {
+            return Err(format!("id '{}' contains control characters", id.escape_debug()));
+        }
+        Ok(format!("{}:{}", platform, id))
+    }
+
+    /// Parse `platform:id` as the CLI takes it
+    pub fn parse_account(account: &str) -> Result<String, String> {
+        match account.split_once(':') {
+            Some((platform, id)) => Self::account(platform, id),
+            None => Err(format!("'{}' is not of the form platform:id", account)),
+        }
+    }
+
+    /// Person an account belongs to
+    pub fn person_for(&self, platform: &str, id: &str) -> Option<&str> {
+        let account = Self::account(platform, id).ok()?;
+        self.owner(&account)
+    }
+
+    fn owner(&self, account: &str) -> Option<&str> {
+        self.people
+            .iter()
+            .find(|(_, accounts)| accounts.contains(account))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Link a `platform:id` account to a person. An account of someone else is only
+    /// moved with `force`.
+    pub fn link(&mut self, name: &str, account: &str, force: bool) -> Link {
+        let name = name.trim();
+        let result = match self.owner(account) {
+            Some(owner) if owner == name => return Link::Unchanged,
+            Some(owner) if !force => return Link::Conflict(owner.to_string()),
+            Some(owner) => {
+                let owner = owner.to_string();
+                self.unlink(account);
+                Link::Moved(owner)
+            }
+            None => Link::Added,
+        };
+
+        self.people.entry(name.to_string()).or_default().insert(account.to_string());
+        result
+    }
+
+    /// Unlink an account, returning the person it belonged to
+    pub fn unlink(&mut self, account: &str) -> Option<String> {
+        let owner = self.owner(account)?.to_string();
+        if let Some(accounts) = self.people.get_mut(&owner) {
+            accounts.remove(account);
+            if accounts.is_empty() {
+                self.people.remove(&owner);
+            }
+        }
+        Some(owner)
+    }
+
+    /// Rename a person, merging into one of the new name if there is one. Returns false
+    /// if there is no such person.
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        let to = to.trim();
+        if to.is_empty() {
+            return false;
+        }
+        match self.people.remove(from) {
+            Some(accounts) => {
+                self.people.entry(to.to_string()).or_default().extend(accounts);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove a person, returning their accounts
+    pub fn remove(&mut self, name: &str) -> Option<BTreeSet<String>> {
+        self.people.remove(name)
+    }
+
+    /// Accounts of a person
+    pub fn accounts(&self, name: &str) -> Option<&BTreeSet<String>> {
+        self.people.get(name)
+    }
+
+    /// Everyone with their accounts, sorted by name
+    pub fn people(&self) -> impl Iterator<Item = (&str, &BTreeSet<String>)> {
+        self.people.iter().map(|(name, accounts)| (name.as_str(), accounts))
+    }
+
+    /// Link accounts from CSV with `name`, `platform` and `id` columns, in any order
+    /// and with a header row naming them. Rows are applied in order, so two rows
+    /// giving one account to different people conflict as well. Fails only if the
+    /// header is missing; bad rows are reported and skipped.
+    pub fn import_csv(&mut self, text: &str, force: bool) -> Result<ImportReport> {
+        let mut lines = text
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+
+        let (header_line, header) = lines.next().context("The CSV file is empty")?;
+        let header = split_csv_line(header)
+            .map_err(|e| anyhow::anyhow!("Line {}: {}", header_line, e))?;
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|column| column.trim().eq_ignore_ascii_case(name))
+                .with_context(|| format!("The header row has no '{}' column (expected name, platform, id)", name))
+        };
+        let (name_column, platform_column, id_column) = (column("name")?, column("platform")?, column("id")?);
+
+        let mut report = ImportReport::default();
+        for (line, text) in lines {
+            let fields = match split_csv_line(text) {
+                Ok(fields) => fields,
+                Err(reason) => {
+                    report.invalid.push(RowError { line, reason });
+                    continue;
+                }
+            };
+            let field = |column: usize| fields.get(column).map(|field| field.trim()).unwrap_or_default();
+            let name = field(name_column);
+            if name.is_empty() {
+                report.invalid.push(RowError { line, reason: "name is empty".to_string() });
+                continue;
+            }
+            let account = match Self::account(field(platform_column), field(id_column)) {
+                Ok(account) => account,
+                Err(reason) => {
+                    report.invalid.push(RowError { line, reason });
+                    continue;
+                }
+            };
+
+            match self.link(name, &account, force) {
+                Link::Added => report.added += 1,
+                Link::Unchanged => report.unchanged += 1,
+                Link::Moved(_) => report.moved += 1,
+                Link::Conflict(linked_to) => report.conflicts.push(Conflict {
+                    line,
+                    account,
+                    linked_to,
+                    wanted: name.to_string(),
+                }),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// All links as CSV, in the form `import_csv` reads
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("name,platform,id\n");
+        for (name, accounts) in &self.people {
+            for account in accounts {
+                let (platform, id) = account.split_once(':').unwrap_or((account, ""));
+                output.push_str(&format!("{},{},{}\n", csv_field(name), platform, csv_field(id)));
+            }
+        }
+        output
+    }
+}
+
+/// Split a CSV line into fields, with `"` quoting fields that contain commas or quotes
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"']) || value.trim() != value {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_and_conflicts() {
+        let mut store = IdentityStore::new();
+        assert_eq!(store.link("Ana", "telegram:1", false), Link::Added);
+        assert_eq!(store.link("Ana", "telegram:1", false), Link::Unchanged);
+        assert_eq!(store.link("Ben", "telegram:1", false), Link::Conflict("Ana".to_string()));
+        assert_eq!(store.person_for("Telegram", " 1 "), Some("Ana"));
+
+        // Moving Ana's only account leaves no Ana
+        assert_eq!(store.link("Ben", "telegram:1", true), Link::Moved("Ana".to_string()));
+        assert!(store.accounts("Ana").is_none());
+
+        store.link("Ana", "signal:+4917012345", false);
+        assert!(store.rename("Ben", "Ana"));
+        assert_eq!(store.accounts("Ana").unwrap().len(), 2);
+        assert_eq!(store.unlink("telegram:1").as_deref(), Some("Ana"));
+        assert_eq!(store.unlink("telegram:1"), None);
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let mut store = IdentityStore::new();
+        store.link("Cy", "whatsapp:491701", false);
+
+        let csv = "\u{feff}Platform,ID,Name\n\
+                   telegram,100,Ana\n\
+                   # Ana again, with a quoted name\n\
+                   matrix,@ana:example.org,\"Ana\"\n\
+                   telegram,100,Ana\n\
+                   telegram,100,Ben\n\
+                   whatsapp,491701,\"Ben, Jr.\"\n\
+                   tele gram,5,Ben\n\
+                   signal,,Ben\n\
+                   signal,7,\n\
+                   signal,8,\"Ben\n";
+        let report = store.import_csv(csv, false).unwrap();
+
+        assert_eq!(report.added, 2);
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.conflicts.len(), 2);
+        assert_eq!(report.conflicts[0].line, 6);
+        assert_eq!(report.conflicts[0].linked_to, "Ana");
+        assert_eq!(report.conflicts[1].wanted, "Ben, Jr.");
+        let lines: Vec<_> = report.invalid.iter().map(|row| row.line).collect();
+        assert_eq!(lines, vec![8, 9, 10, 11]);
+        assert_eq!(store.person_for("matrix", "@ana:example.org"), Some("Ana"));
+
+        let forced = store.import_csv("name,platform,id\n\"Ben, Jr.\",whatsapp,491701\n", true).unwrap();
+        assert_eq!(forced.moved, 1);
+
+        // Export reads back the same
+        let mut copy = IdentityStore::new();
+        let report = copy.import_csv(&store.to_csv(), false).unwrap();
+        assert_eq!(report.added, 3);
+        assert!(report.invalid.is_empty());
+        assert_eq!(copy.people().collect::<Vec<_>>(), store.people().collect::<Vec<_>>());
+
+        assert!(IdentityStore::new().import_csv("who,where\nAna,telegram\n", false).is_err());
+    }
+}
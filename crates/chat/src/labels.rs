@@ -0,0 +1,231 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::global_id::GlobalChatId;
+use crate::types::ChatId;
+
+/// Locally stored chat labels (work, family, noisy)
+///
+/// Labels are keyed by the canonical `source:chat_id` (see [`GlobalChatId`]) so the
+/// same store can hold chats from every source. Label names are case-insensitive
+/// and stored lowercase.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LabelStore {
+    #[serde(default)]
+    chats: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl LabelStore {
+    /// Create an empty label store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the labels file (`~/.config/chat/labels.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("labels.json"))
+    }
+
+    /// Load labels from the default location, returning an empty store if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load labels from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read labels file")?;
+
+        let mut store: Self = serde_json::from_str(&contents).context("Failed to parse labels file")?;
+        store.normalize_keys();
+        Ok(store)
+    }
+
+    /// Rewrite keys stored with older chat id forms into canonical form
+    fn normalize_keys(&mut self) {
+        let chats = std::mem::take(&mut self.chats);
+
+        for (key, labels) in chats {
+            let key = match key.parse::<GlobalChatId>() {
+                Ok(id) => id.to_string(),
+                Err(_) => key,
+            };
+            self.chats.entry(key).or_default().extend(labels);
+        }
+    }
+
+    /// Save labels to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save labels to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create labels directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize labels")?;
+
+        std::fs::write(path, contents).context("Failed to write labels file")
+    }
+
+    fn key(source_id: &str, chat_id: &ChatId) -> String {
+        GlobalChatId::from_chat_id(source_id, chat_id).to_string()
+    }
+
+    pub(crate) fn normalize(label: &str) -> String {
+        label.trim().to_lowercase()
+    }
+
+    /// Assign a label to a chat. Returns false if the chat already had it.
+    pub fn add(&mut self, source_id: &str, chat_id: &ChatId, label: &str) -> bool {
+        let label = Self::normalize(label);
+        if label.is_empty() {
+            return false;
+        }
+
+        self.chats
+            .entry(Self::key(source_id, chat_id))
+            .or_default()
+            .insert(label)
+    }
+
+    /// Remove a label from a chat. Returns false if the chat did not have it.
+    pub fn remove(&mut self, source_id: &str, chat_id: &ChatId, label: &str) -> bool {
+        let key = Self::key(source_id, chat_id);
+        let removed = match self.chats.get_mut(&key) {
+            Some(labels) => labels.remove(&Self::normalize(label)),
+            None => false,
+        };
+
+        if self.chats.get(&key).is_some_and(|labels| labels.is_empty()) {
+            self.chats.remove(&key);
+        }
+
+        removed
+    }
+
+    /// Remove every label from a chat, returning how many it had
+    pub fn remove_chat(&mut self, source_id: &str, chat_id: &ChatId) -> usize {
+        self.chats.remove(&Self::key(source_id, chat_id)).map_or(0, |labels| labels.len())
+    }
+
+    /// Labels assigned to a chat, sorted alphabetically
+    pub fn labels_for(&self, source_id: &str, chat_id: &ChatId) -> Vec<String> {
+        self.chats
+            .get(&Self::key(source_id, chat_id))
+            .map(|labels| labels.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Check if a chat carries a label
+    pub fn has_label(&self, source_id: &str, chat_id: &ChatId, label: &str) -> bool {
+        self.chats
+            .get(&Self::key(source_id, chat_id))
+            .is_some_and(|labels| labels.contains(&Self::normalize(label)))
+    }
+
+    /// All chats from a source carrying a label
+    pub fn chats_with_label(&self, source_id: &str, label: &str) -> Vec<ChatId> {
+        let prefix = format!("{}:", source_id);
+        let label = Self::normalize(label);
+
+        self.chats
+            .iter()
+            .filter(|(_, labels)| labels.contains(&label))
+            .filter_map(|(key, _)| key.strip_prefix(&prefix))
+            .map(ChatId::new)
+            .collect()
+    }
+
+    /// Every label in use, sorted alphabetically
+    pub fn all_labels(&self) -> Vec<String> {
+        let labels: BTreeSet<&String> = self.chats.values().flatten().collect();
+        labels.into_iter().cloned().collect()
+    }
+
+    /// All labelled chats as (source_id, chat_id, labels)
+    pub fn entries(&self) -> Vec<(String, ChatId, Vec<String>)> {
+        self.chats
+            .iter()
+            .filter_map(|(key, labels)| {
+                let (source, chat) = key.split_once(':')?;
+                Some((
+                    source.to_string(),
+                    ChatId::new(chat),
+                    labels.iter().cloned().collect(),
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_query() {
+        let mut store = LabelStore::new();
+        let chat = ChatId::new("123");
+
+        assert!(store.add("telegram", &chat, "Work"));
+        assert!(!store.add("telegram", &chat, "work"));
+        assert!(store.has_label("telegram", &chat, "WORK"));
+        assert!(!store.has_label("whatsapp", &chat, "work"));
+        assert_eq!(store.labels_for("telegram", &chat), vec!["work".to_string()]);
+    }
+
+    #[test]
+    fn test_chats_with_label() {
+        let mut store = LabelStore::new();
+        store.add("telegram", &ChatId::new("1"), "family");
+        store.add("telegram", &ChatId::new("2"), "work");
+        store.add("whatsapp", &ChatId::new("3"), "family");
+
+        assert_eq!(store.chats_with_label("telegram", "family"), vec![ChatId::new("1")]);
+        assert_eq!(store.all_labels(), vec!["family".to_string(), "work".to_string()]);
+    }
+
+    #[test]
+    fn test_historical_ids_share_labels() {
+        let mut store = LabelStore::new();
+        store.add("telegram", &ChatId::new("channel:123"), "work");
+
+        assert!(store.has_label("telegram", &ChatId::new("-100123"), "work"));
+        assert_eq!(store.chats_with_label("telegram", "work"), vec![ChatId::new("-100123")]);
+
+        let legacy: LabelStore = serde_json::from_str(
+            r#"{"chats": {"telegram:channel:123": ["work"], "telegram:-100123": ["news"]}}"#,
+        ).unwrap();
+        let path = std::env::temp_dir().join(format!("labels-{}.json", uuid::Uuid::new_v4().simple()));
+        legacy.save_to(&path).unwrap();
+        let loaded = LabelStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.labels_for("telegram", &ChatId::new("-100123")), vec!["news", "work"]);
+    }
+
+    #[test]
+    fn test_remove_drops_empty_entries() {
+        let mut store = LabelStore::new();
+        let chat = ChatId::new("-100123");
+
+        store.add("telegram", &chat, "noisy");
+        assert!(store.remove("telegram", &chat, "noisy"));
+        assert!(!store.remove("telegram", &chat, "noisy"));
+        assert!(store.entries().is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+//! Language detection for message text, using whatlang
+
+use crate::types::{Message, MessageContent};
+
+/// ISO 639-3 codes whatlang reports, with the ISO 639-1 codes messages are tagged with
+const CODES: &[(&str, &str)] = &[
+    ("afr", "af"), ("aka", "ak"), ("amh", "am"), ("ara", "ar"), ("aze", "az"), ("bel", "be"),
+    ("ben", "bn"), ("bul", "bg"), ("cat", "ca"), ("ces", "cs"), ("cmn", "zh"), ("dan", "da"),
+    ("deu", "de"), ("ell", "el"), ("eng", "en"), ("epo", "eo"), ("est", "et"), ("fin", "fi"),
+    ("fra", "fr"), ("guj", "gu"), ("heb", "he"), ("hin", "hi"), ("hrv", "hr"), ("hun", "hu"),
+    ("hye", "hy"), ("ind", "id"), ("ita", "it"), ("jav", "jv"), ("jpn", "ja"), ("kan", "kn"),
+    ("kat", "ka"), ("khm", "km"), ("kor", "ko"), ("lat", "la"), ("lav", "lv"), ("lit", "lt"),
+    ("mal", "ml"), ("mar", "mr"), ("mkd", "mk"), ("mya", "my"), ("nep", "ne"), ("nld", "nl"),
+    ("nob", "nb"), ("ori", "or"), ("pan", "pa"), ("pes", "fa"), ("pol", "pl"), ("por", "pt"),
+    ("ron", "ro"), ("rus", "ru"), ("sin", "si"), ("slk", "sk"), ("slv", "sl"), ("sna", "sn"),
+    ("spa", "es"), ("srp", "sr"), ("swe", "sv"), ("tam", "ta"), ("tel", "te"), ("tgl", "tl"),
+    ("tha", "th"), ("tuk", "tk"), ("tur", "tr"), ("ukr", "uk"), ("urd", "ur"), ("uzb", "uz"),
+    ("vie", "vi"), ("yid", "yi"), ("zul", "zu"),
+];
+
+/// Normalize a language code as given on the command line ("fi", "FIN") to ISO 639-1
+pub fn normalize(code: &str) -> Option<&'static str> {
+    let code = code.trim().to_lowercase();
+    CODES
+        .iter()
+        .find(|(long, short)| *short == code || *long == code)
+        .map(|(_, short)| *short)
+}
+
+/// Detect the language of a text, as an ISO 639-1 code.
+/// Returns None when the text is too short or mixed for a reliable guess.
+pub fn detect(text: &str) -> Option<&'static str> {
+    let info = whatlang::detect(text)?;
+    if !info.is_reliable() {
+        return None;
+    }
+    normalize(info.lang().code())
+}
+
+/// Tag messages with the language of their text or caption, keeping tags sources set
+pub fn tag(messages: &mut [Message]) {
+    for message in messages.iter_mut().filter(|m| m.language.is_none()) {
+        let text = match &message.content {
+            MessageContent::Text(text) => text,
+            MessageContent::Image { caption: Some(caption), .. }
+            | MessageContent::Video { caption: Some(caption), .. } => caption,
+            _ => continue,
+        };
+        message.language = detect(text).map(str::to_string);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("fi"), Some("fi"));
+        assert_eq!(normalize("FIN"), Some("fi"));
+        assert_eq!(normalize(" en "), Some("en"));
+        assert_eq!(normalize("klingon"), None);
+    }
+
+    #[test]
+    fn test_tag() {
+        let mut messages = vec![
+            MessageBuilder::new("1", "group")
+                .text("Huomenna on palaveri toimistolla kello kymmenen, tuokaa kannettavat mukaan.")
+                .build(),
+            MessageBuilder::new("2", "group")
+                .text("Could you send me the report from yesterday's meeting? I need it before the call this afternoon.")
+                .build(),
+            MessageBuilder::new("3", "group").text("ok").build(),
+        ];
+        tag(&mut messages);
+
+        let languages: Vec<_> = messages.iter().map(|m| m.language.as_deref()).collect();
+        assert_eq!(languages, [Some("fi"), Some("en"), None]);
+    }
+}
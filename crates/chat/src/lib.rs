@@ -1,21 +1,94 @@
+pub mod analysis;
+pub mod anonymize;
+pub mod archive;
+pub mod bookmarks;
+pub mod calendar;
+pub mod catchup;
+pub mod channel_stats;
+pub mod cli;
+pub mod clock;
+pub mod commands;
+pub mod context;
+pub mod dupes;
+pub mod embeddings;
+pub mod estimate;
+pub mod events;
 pub mod filter_parser;
+pub mod forget;
+pub mod global_id;
+pub mod identities;
+pub mod labels;
+pub mod language;
+pub mod links;
 pub mod matrix_client;
+#[cfg(feature = "arrow")]
+pub mod parquet_export;
 #[cfg(feature = "mcp")]
 pub mod mcp_server;
+pub mod mcp_selftest;
+pub mod media_text;
+pub mod mentions;
+pub mod merge;
+pub mod merged_chats;
+pub mod notes;
+pub mod pipeline;
+pub mod progress;
+pub mod query_plan;
+pub mod read_cursors;
+pub mod recording;
+pub mod reminders;
+pub mod self_update;
+pub mod simulated_source;
+pub mod snippets;
+pub mod sources_config;
 pub mod sources_manager;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_export;
+pub mod stream_event;
+pub mod subscription;
+pub mod supervisor;
+pub mod sync;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 #[cfg(feature = "telegram")]
 pub mod telegram_client;
 #[cfg(feature = "telegram")]
+pub mod telegram_session;
+#[cfg(feature = "telegram")]
 pub mod telegram_source;
+pub mod text_fold;
+pub mod todos;
+pub mod topics;
 pub mod types;
 #[cfg(feature = "whatsapp")]
 pub mod whatsapp_source;
 
+pub use events::{login_command, EventBus, SourceEvent, SourceEventKind};
+pub use global_id::{ChatIdResolver, GlobalChatId};
+pub use identities::IdentityStore;
+pub use labels::LabelStore;
 pub use matrix_client::MatrixChatClient;
+pub use media_text::MediaTextStore;
+pub use mentions::MyAccounts;
+pub use merged_chats::MergedChats;
+pub use notes::{Note, NoteStore};
+pub use progress::{Progress, ProgressEvent};
+pub use read_cursors::{ReadCursor, ReadCursors};
+pub use recording::{Fixture, Recorder, RecordingSource, ReplaySource};
+pub use snippets::SnippetStore;
+pub use simulated_source::SimulatedSource;
+pub use sources_config::{McpSettings, SimulationSettings, SourceSettings, SourcesConfig};
 pub use sources_manager::SourcesManager;
+pub use stream_event::{EventKind, StreamEvent};
+pub use subscription::{BufferConfig, OverflowPolicy, Subscription, SubscriptionStats};
+pub use supervisor::{RestartPolicy, Supervisor, TaskState, TaskStatus};
+pub use sync::{Checkpoint, RetryPolicy, SyncState};
+pub use todos::{Todo, TodoStore};
 #[cfg(feature = "telegram")]
 pub use telegram_client::TelegramChatClient;
 #[cfg(feature = "telegram")]
+pub use telegram_session::TelegramSession;
+#[cfg(feature = "telegram")]
 pub use telegram_source::TelegramSource;
 #[cfg(feature = "whatsapp")]
 pub use whatsapp_source::{WhatsAppSource, WhatsAppConfig};
@@ -24,5 +97,6 @@ pub use types::{
     Chat, ChatClient, ChatClientConfig, ChatClientId, ChatClientStatus, ChatId, ChatPlatform,
     ChatType, Message, MessageContent, MessageFetchOptions, MessageId, User, UserId,
     // New unified API types
-    ChatFilter, ChatPattern, ChatSource, ContentType, MessageFilter, SourceInfo,
+    Capability, ChatFilter, ChatPattern, ChatSource, ContentType, FilterPushdown, MessageFilter, SourceCapabilities,
+    SourceInfo, SystemEvent, TopicId, TopicInfo, apply_filter,
 };
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::types::{ChatId, Message, MessageContent};
+
+/// A unique URL shared in one or more messages
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkEntry {
+    pub url: String,
+    /// Chat where the link was first shared
+    pub chat_id: ChatId,
+    /// Sender of the first message sharing the link
+    pub sender: Option<String>,
+    pub first_seen: DateTime<Utc>,
+    /// Number of times the link was shared
+    pub count: usize,
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Extract http(s) URLs from text, trimming trailing punctuation
+pub fn extract_urls(text: &str) -> Vec<String> {
+    text.split(|c: char| c.is_whitespace() || c == '<' || c == '>' || c == '"')
+        .filter_map(|token| {
+            let start = token.find("https://").or_else(|| token.find("http://"))?;
+            let url = token[start..].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '\'']);
+            let has_host = url.split_once("://").is_some_and(|(_, rest)| !rest.is_empty());
+            has_host.then(|| url.to_string())
+        })
+        .collect()
+}
+
+/// Collect unique links from messages, oldest first
+pub fn collect_links(messages: &[Message]) -> Vec<LinkEntry> {
+    let mut sorted: Vec<&Message> = messages.iter().collect();
+    sorted.sort_by_key(|m| m.timestamp);
+
+    let mut entries: Vec<LinkEntry> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for message in sorted {
+        let text = match &message.content {
+            MessageContent::Text(text) => text,
+            MessageContent::Image { caption: Some(caption), .. } |
+            MessageContent::Video { caption: Some(caption), .. } => caption,
+            _ => continue,
+        };
+
+        for url in extract_urls(text) {
+            if let Some(&i) = index.get(&url) {
+                entries[i].count += 1;
+                continue;
+            }
+
+            index.insert(url.clone(), entries.len());
+            entries.push(LinkEntry {
+                url,
+                chat_id: message.chat_id.clone(),
+                sender: message.sender.display_name.clone(),
+                first_seen: message.timestamp,
+                count: 1,
+                title: None,
+                description: None,
+            });
+        }
+    }
+
+    entries
+}
+
+/// Extract the page title and description from HTML, preferring OpenGraph metadata
+pub fn parse_page_meta(html: &str) -> (Option<String>, Option<String>) {
+    let title = meta_content(html, "og:title").or_else(|| {
+        let lower = html.to_ascii_lowercase();
+        let start = lower.find("<title")?;
+        let start = start + lower[start..].find('>')? + 1;
+        let end = start + lower[start..].find("</title>")?;
+        non_empty(&html[start..end])
+    });
+
+    let description = meta_content(html, "og:description")
+        .or_else(|| meta_content(html, "description"));
+
+    (title, description)
+}
+
+/// Find `<meta property|name="key" content="...">` and return its content
+fn meta_content(html: &str, key: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let patterns = [
+        format!("property=\"{}\"", key),
+        format!("name=\"{}\"", key),
+    ];
+
+    for pattern in &patterns {
+        let mut search_from = 0;
+        while let Some(pos) = lower[search_from..].find(pattern.as_str()) {
+            let pos = search_from + pos;
+            let tag_start = lower[..pos].rfind('<').unwrap_or(0);
+            let tag_end = pos + lower[pos..].find('>').unwrap_or(lower.len() - pos);
+            let tag = &html[tag_start..tag_end];
+
+            if let Some(content) = attribute(tag, "content") {
+                return non_empty(&content);
+            }
+            search_from = tag_end;
+        }
+    }
+
+    None
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower.find(&format!("{}=\"", name))? + name.len() + 2;
+    let end = start + tag[start..].find('"')?;
+    Some(tag[start..end].to_string())
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Fetch page titles and descriptions, running at most `concurrency` requests at once
+#[cfg(feature = "link-preview")]
+pub async fn fetch_previews(entries: &mut [LinkEntry], concurrency: usize) -> anyhow::Result<()> {
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .user_agent("chat-cli link preview")
+        .build()?;
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        let client = client.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let url = entry.url.clone();
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.ok()?;
+            let response = client.get(&url).send().await.ok()?;
+            let is_html = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.contains("html"));
+            if !is_html {
+                return None;
+            }
+            let html = response.text().await.ok()?;
+            Some((i, parse_page_meta(&html)))
+        });
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        if let Ok(Some((i, (title, description)))) = result {
+            entries[i].title = title;
+            entries[i].description = description;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MessageId, User, UserId};
+
+    fn text_message(id: &str, text: &str, minutes_ago: i64) -> Message {
+        Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new("chat"),
+            sender: User {
+                id: UserId::new("u1"),
+                username: None,
+                display_name: Some("Alice".to_string()),
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp: Utc::now() - chrono::Duration::minutes(minutes_ago),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_urls() {
+        let urls = extract_urls("see https://example.com/a, and (http://foo.org/b). not http://");
+        assert_eq!(urls, vec!["https://example.com/a", "http://foo.org/b"]);
+    }
+
+    #[test]
+    fn test_collect_links_dedupes() {
+        let messages = vec![
+            text_message("2", "again https://example.com", 5),
+            text_message("1", "look https://example.com and https://rust-lang.org", 10),
+        ];
+
+        let links = collect_links(&messages);
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].url, "https://example.com");
+        assert_eq!(links[0].count, 2);
+        assert_eq!(links[1].url, "https://rust-lang.org");
+    }
+
+    #[test]
+    fn test_parse_page_meta() {
+        let html = r#"<html><head><title> Plain
+            Title </title><meta property="og:description" content="A page"></head></html>"#;
+        let (title, description) = parse_page_meta(html);
+        assert_eq!(title.as_deref(), Some("Plain Title"));
+        assert_eq!(description.as_deref(), Some("A page"));
+
+        let html = r#"<meta content="ignored" name="x"><meta property="og:title" content="OG Title">"#;
+        assert_eq!(parse_page_meta(html).0.as_deref(), Some("OG Title"));
+    }
+}
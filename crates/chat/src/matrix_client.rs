@@ -10,6 +10,135 @@ pub struct MatrixConfig {
     pub homeserver_url: String,
     pub username: String,
     pub password: String,
+    /// Bridges whose puppets are shown as the platform users they stand for
+    #[serde(default = "BridgeConfig::defaults")]
+    pub bridges: Vec<BridgeConfig>,
+}
+
+/// A mautrix bridge, as far as telling its puppets apart goes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeConfig {
+    pub platform: ChatPlatform,
+    /// Localpart prefix of the bridge's puppets, "telegram_" for `@telegram_12345:example.org`
+    pub prefix: String,
+    /// What the bridge appends to puppet display names, like " (Telegram)"
+    #[serde(default)]
+    pub display_suffix: Option<String>,
+}
+
+impl BridgeConfig {
+    fn new(platform: ChatPlatform, prefix: &str, display_suffix: &str) -> Self {
+        Self {
+            platform,
+            prefix: prefix.to_string(),
+            display_suffix: Some(display_suffix.to_string()),
+        }
+    }
+
+    /// mautrix-telegram, -whatsapp and -signal with their default templates
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new(ChatPlatform::Telegram, "telegram_", " (Telegram)"),
+            Self::new(ChatPlatform::WhatsApp, "whatsapp_", " (WA)"),
+            Self::new(ChatPlatform::Signal, "signal_", " (Signal)"),
+        ]
+    }
+}
+
+/// The platform user behind a bridge puppet
+#[derive(Debug, Clone)]
+pub struct BridgedUser {
+    pub platform: ChatPlatform,
+    /// The user as the platform's own source identifies them
+    pub user: User,
+}
+
+impl BridgedUser {
+    /// Source ID of the platform
+    pub fn source(&self) -> &'static str {
+        match self.platform {
+            ChatPlatform::Telegram => "telegram",
+            ChatPlatform::WhatsApp => "whatsapp",
+            ChatPlatform::Signal => "signal",
+            ChatPlatform::Matrix => "matrix",
+        }
+    }
+
+    /// The person as `source:user_id`, the way notes and `chat cache forget` name them
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.source(), self.user.id)
+    }
+}
+
+/// Maps puppet user IDs like `@telegram_12345:example.org` back to the platform users
+/// they stand for, with the IDs the platforms' own sources give them
+#[derive(Debug, Clone)]
+pub struct PuppetResolver {
+    bridges: Vec<BridgeConfig>,
+}
+
+impl Default for PuppetResolver {
+    fn default() -> Self {
+        Self::new(BridgeConfig::defaults())
+    }
+}
+
+impl PuppetResolver {
+    pub fn new(bridges: Vec<BridgeConfig>) -> Self {
+        Self { bridges }
+    }
+
+    /// The platform user behind a Matrix user, if it is a puppet of a known bridge
+    pub fn resolve(&self, matrix_id: &str, display_name: Option<&str>) -> Option<BridgedUser> {
+        let localpart = matrix_id.strip_prefix('@')?.split(':').next()?;
+        let bridge = self.bridges.iter().find(|bridge| localpart.starts_with(&bridge.prefix))?;
+        let remote = &localpart[bridge.prefix.len()..];
+
+        let digits = |id: &str| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit());
+        let (id, phone_number) = match bridge.platform {
+            ChatPlatform::Telegram if digits(remote) => (remote.to_string(), None),
+            // Newer bridges name puppets after the hidden "lid" ID instead of the phone number
+            ChatPlatform::WhatsApp => match remote.strip_prefix("lid-") {
+                Some(lid) if digits(lid) => (format!("{}@lid", lid), None),
+                None if digits(remote) => (format!("{}@s.whatsapp.net", remote), Some(format!("+{}", remote))),
+                _ => return None,
+            },
+            ChatPlatform::Signal if uuid::Uuid::parse_str(remote).is_ok() => (remote.to_lowercase(), None),
+            _ => return None,
+        };
+
+        let display_name = display_name
+            .map(|name| match &bridge.display_suffix {
+                Some(suffix) => name.strip_suffix(suffix.as_str()).unwrap_or(name),
+                None => name,
+            })
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+
+        Some(BridgedUser {
+            platform: bridge.platform.clone(),
+            user: User {
+                id: UserId::new(id),
+                username: None,
+                display_name,
+                phone_number,
+            },
+        })
+    }
+
+    /// The sender of a Matrix event: the platform user behind a puppet, else the Matrix user
+    pub fn sender(&self, matrix_id: &str, display_name: Option<&str>) -> User {
+        match self.resolve(matrix_id, display_name) {
+            Some(bridged) => bridged.user,
+            None => User {
+                id: UserId::new(matrix_id),
+                username: None,
+                display_name: display_name.map(str::to_string),
+                phone_number: None,
+            },
+        }
+    }
 }
 
 /// Matrix-based chat client that works with mautrix bridges
@@ -17,8 +146,11 @@ pub struct MatrixConfig {
 /// through a single Matrix homeserver with configured bridges.
 pub struct MatrixChatClient {
     config: ChatClientConfig,
+    /// Read by the matrix_sdk client once it is wired in
+    #[allow(dead_code)]
     matrix_config: MatrixConfig,
     status: ChatClientStatus,
+    puppets: PuppetResolver,
     // In a real implementation, add:
     // client: Option<matrix_sdk::Client>,
 }
@@ -29,17 +161,18 @@ impl MatrixChatClient {
         let matrix_config: MatrixConfig = serde_json::from_value(config.config_data.clone())
             .map_err(|e| anyhow!("Invalid Matrix configuration: {}", e))?;
 
+        let puppets = PuppetResolver::new(matrix_config.bridges.clone());
         Ok(Self {
             config,
             matrix_config,
             status: ChatClientStatus::Disconnected,
+            puppets,
         })
     }
 
-    /// Parse Matrix config from the generic config
-    fn parse_config(config_data: &serde_json::Value) -> Result<MatrixConfig> {
-        serde_json::from_value(config_data.clone())
-            .map_err(|e| anyhow!("Failed to parse Matrix config: {}", e))
+    /// Resolves the senders of bridged messages to platform users
+    pub fn puppets(&self) -> &PuppetResolver {
+        &self.puppets
     }
 }
 
@@ -120,6 +253,7 @@ impl ChatClient for MatrixChatClient {
                     ChatType::Group
                 },
                 participant_count: Some(room.members(None).await?.len()),
+                topic: None,
             };
             chats.push(chat);
         }
@@ -133,8 +267,8 @@ impl ChatClient for MatrixChatClient {
 
     async fn get_messages(
         &self,
-        chat_id: &ChatId,
-        options: MessageFetchOptions,
+        _chat_id: &ChatId,
+        _options: MessageFetchOptions,
     ) -> Result<Vec<Message>> {
         // In a real implementation:
         // 1. Get the Matrix room by ID
@@ -160,7 +294,7 @@ impl ChatClient for MatrixChatClient {
 
         let mut messages = Vec::new();
         for event in messages_response.chunk {
-            if let Some(message) = convert_matrix_event_to_message(event, chat_id)? {
+            if let Some(message) = convert_matrix_event_to_message(event, chat_id, &self.puppets)? {
                 messages.push(message);
             }
         }
@@ -199,8 +333,9 @@ impl ChatClient for MatrixChatClient {
         let client = self.client.as_ref()
             .ok_or_else(|| anyhow!("Not connected"))?;
 
+        let puppets = self.puppets.clone();
         client.add_event_handler(move |event: SyncRoomMessageEvent, room: Room| async move {
-            if let Some(message) = convert_matrix_event_to_message(event, &room.room_id()) {
+            if let Some(message) = convert_matrix_event_to_message(event, &room.room_id(), &puppets) {
                 let _ = tx.send(message).await;
             }
         });
@@ -218,6 +353,7 @@ impl ChatClient for MatrixChatClient {
 fn convert_matrix_event_to_message(
     event: AnyMessageLikeEvent,
     chat_id: &ChatId,
+    puppets: &PuppetResolver,
 ) -> Result<Option<Message>> {
     match event {
         AnyMessageLikeEvent::RoomMessage(msg) => {
@@ -238,19 +374,71 @@ fn convert_matrix_event_to_message(
             Ok(Some(Message {
                 id: MessageId::new(msg.event_id.to_string()),
                 chat_id: chat_id.clone(),
-                sender: User {
-                    id: UserId::new(msg.sender.to_string()),
-                    username: None,
-                    display_name: None,
-                    phone_number: None,
-                },
+                // Bridged senders become the platform users behind their puppets;
+                // the display name comes from the room member event
+                sender: puppets.sender(msg.sender.as_str(), None),
                 content,
                 timestamp: Utc::now(), // Use actual timestamp from event
                 reply_to: None,
                 edited: false,
+                mentions_me: false,
+                pending: false,
+                language: None,
+                media_text: None,
+                original_timestamp: None,
             }))
         }
         _ => Ok(None),
     }
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_puppets() {
+        let puppets = PuppetResolver::default();
+
+        let telegram = puppets.resolve("@telegram_12345:example.org", Some("Alice (Telegram)")).unwrap();
+        assert_eq!(telegram.key(), "telegram:12345");
+        assert_eq!(telegram.user.display_name.as_deref(), Some("Alice"));
+
+        let whatsapp = puppets.resolve("@whatsapp_358401234567:example.org", Some("Bob (WA)")).unwrap();
+        assert_eq!(whatsapp.user.id, UserId::new("358401234567@s.whatsapp.net"));
+        assert_eq!(whatsapp.user.phone_number.as_deref(), Some("+358401234567"));
+        let lid = puppets.resolve("@whatsapp_lid-998877:example.org", None).unwrap();
+        assert_eq!((lid.key(), lid.user.display_name), ("whatsapp:998877@lid".to_string(), None));
+
+        let signal = puppets.resolve("@signal_3F2504E0-4F89-11D3-9A0C-0305E82C3301:example.org", Some("Carol (Signal)")).unwrap();
+        assert_eq!(signal.key(), "signal:3f2504e0-4f89-11d3-9a0c-0305e82c3301");
+    }
+
+    #[test]
+    fn test_non_puppets_stay_matrix_users() {
+        let puppets = PuppetResolver::default();
+        // Bridge bots and other Matrix users aren't puppets
+        assert!(puppets.resolve("@telegrambot:example.org", None).is_none());
+        assert!(puppets.resolve("@telegram_bot:example.org", None).is_none());
+        assert!(puppets.resolve("@alice:example.org", None).is_none());
+
+        let sender = puppets.sender("@alice:example.org", Some("Alice"));
+        assert_eq!(sender.id, UserId::new("@alice:example.org"));
+        assert_eq!(sender.display_name.as_deref(), Some("Alice"));
+    }
+
+    #[test]
+    fn test_configured_bridges() {
+        let config: MatrixConfig = serde_json::from_value(serde_json::json!({
+            "homeserver_url": "https://example.org",
+            "username": "me",
+            "password": "secret",
+            "bridges": [{ "platform": "Telegram", "prefix": "tg_" }],
+        }))
+        .unwrap();
+        let puppets = PuppetResolver::new(config.bridges);
+        assert_eq!(puppets.resolve("@tg_42:example.org", Some("Dave")).unwrap().key(), "telegram:42");
+        assert!(puppets.resolve("@telegram_42:example.org", None).is_none());
+    }
+}
@@ -0,0 +1,567 @@
+//! End-to-end checks for the MCP server.
+//!
+//! [`McpClient`] drives a `chat-mcp-server` subprocess over stdio, or a server
+//! listening on TCP, and [`run_selftest`] runs the handshake, every advertised
+//! tool and a set of JSON-RPC conformance checks against it. Used by
+//! `chat mcp selftest`, `chat mcp client` and by the integration tests.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::time::Duration;
+
+/// JSON-RPC error codes the server is expected to use
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// How long to wait for a response before treating the server as hung
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A JSON-RPC client talking to an MCP server subprocess over stdio, or to a server on TCP
+pub struct McpClient {
+    /// The server process, when this client started it
+    child: Option<Child>,
+    writer: Box<dyn Write + Send>,
+    lines: Receiver<String>,
+    timeout: Duration,
+    next_id: u64,
+}
+
+impl McpClient {
+    /// Spawn the server. stdin/stdout are piped; stderr is discarded.
+    pub fn spawn(mut command: Command) -> Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to start MCP server")?;
+
+        let stdin = child.stdin.take().context("Failed to open server stdin")?;
+        let stdout = child.stdout.take().context("Failed to open server stdout")?;
+
+        Ok(Self::new(Some(child), Box::new(stdin), stdout))
+    }
+
+    /// Connect to a server listening on TCP (`chat-mcp-server --listen`)
+    pub fn connect(addr: &str) -> Result<Self> {
+        let stream = TcpStream::connect(addr).with_context(|| format!("No MCP server listening on {}", addr))?;
+        let reader = stream.try_clone().context("Failed to read from MCP server")?;
+        Ok(Self::new(None, Box::new(stream), reader))
+    }
+
+    fn new(child: Option<Child>, writer: Box<dyn Write + Send>, reader: impl Read + Send + 'static) -> Self {
+        // Read on a thread so a silent server can be timed out
+        let (tx, lines) = channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else { break };
+                if !line.trim().is_empty() && tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            child,
+            writer,
+            lines,
+            timeout: DEFAULT_TIMEOUT,
+            next_id: 1,
+        }
+    }
+
+    /// Change how long to wait for each response
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Write one raw line to the server
+    pub fn send_raw(&mut self, line: &str) -> Result<()> {
+        writeln!(self.writer, "{}", line).context("Failed to write to MCP server")?;
+        self.writer.flush().context("Failed to write to MCP server")
+    }
+
+    /// Wait for the next response the server writes, parsed as JSON.
+    /// Notifications from the server, like source events, are skipped.
+    pub fn receive(&mut self) -> Result<Value> {
+        loop {
+            let value: Value = match self.lines.recv_timeout(self.timeout) {
+                Ok(line) => serde_json::from_str(&line)
+                    .with_context(|| format!("Server wrote invalid JSON: {}", line))?,
+                Err(RecvTimeoutError::Timeout) => bail!("No response within {}s", self.timeout.as_secs()),
+                Err(RecvTimeoutError::Disconnected) => bail!("MCP server closed its output"),
+            };
+
+            if value.get("method").is_none() || value.get("id").is_some() {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Send a request with a fresh numeric id and return the whole response
+    pub fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.request_with_id(json!(id), method, params)
+    }
+
+    /// Send a request with the given id and return the whole response
+    pub fn request_with_id(&mut self, id: Value, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        self.send_raw(&request.to_string())?;
+        self.receive()
+    }
+
+    /// Send a notification, which gets no response
+    pub fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        let notification = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        self.send_raw(&notification.to_string())
+    }
+
+    /// Call a tool and return the whole response
+    pub fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Value> {
+        self.request("tools/call", json!({ "name": name, "arguments": arguments }))
+    }
+
+    /// Run the MCP handshake as `client_name`, returning the server's `initialize` result
+    pub fn initialize(&mut self, client_name: &str) -> Result<Value> {
+        let response = self.request_with_id(
+            json!("init"),
+            "initialize",
+            json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": { "name": client_name, "version": env!("CARGO_PKG_VERSION") }
+            }),
+        )?;
+        let result = expect_result(&response, &json!("init"))?.clone();
+        self.notify("notifications/initialized", json!({}))?;
+        Ok(result)
+    }
+
+    /// The server's tools, each with its name, description and input schema
+    pub fn list_tools(&mut self) -> Result<Vec<Value>> {
+        let response = self.request_with_id(json!("tools"), "tools/list", json!({}))?;
+        let result = expect_result(&response, &json!("tools"))?;
+        Ok(result["tools"].as_array().context("Missing tools array")?.clone())
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// A tool call made with `chat mcp client`, saved one per line so a session can be replayed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// Parse what was typed for one tool argument according to its JSON schema property.
+/// Arrays take a JSON array or comma-separated strings; objects take JSON.
+pub fn parse_argument(property: &Value, input: &str) -> Result<Value> {
+    let input = input.trim();
+
+    let value = match schema_type(property) {
+        Some("integer") => json!(input.parse::<i64>().with_context(|| format!("'{}' is not a whole number", input))?),
+        Some("number") => json!(input.parse::<f64>().with_context(|| format!("'{}' is not a number", input))?),
+        Some("boolean") => match input.to_lowercase().as_str() {
+            "true" | "yes" | "y" => json!(true),
+            "false" | "no" | "n" => json!(false),
+            _ => bail!("Answer yes or no"),
+        },
+        Some("array") if input.starts_with('[') => serde_json::from_str(input).context("Invalid JSON array")?,
+        Some("array") => json!(input.split(',').map(str::trim).filter(|s| !s.is_empty()).collect::<Vec<_>>()),
+        Some("object") => serde_json::from_str(input).context("Invalid JSON object")?,
+        _ => json!(input),
+    };
+
+    if let Some(allowed) = property["enum"].as_array() {
+        if !allowed.contains(&value) {
+            let names: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            bail!("Expected one of {}", names.join(", "));
+        }
+    }
+
+    Ok(value)
+}
+
+/// A property's type, ignoring the `null` that optional fields add (`["integer", "null"]`)
+pub fn schema_type(property: &Value) -> Option<&str> {
+    match &property["type"] {
+        Value::String(kind) => Some(kind),
+        Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).find(|kind| *kind != "null"),
+        _ => None,
+    }
+}
+
+/// A tools/call response for reading: the error, or each text content item with JSON
+/// pretty-printed
+pub fn format_tool_response(response: &Value) -> String {
+    if let Some(error) = response.get("error") {
+        return format!(
+            "Error {}: {}",
+            error["code"].as_i64().unwrap_or_default(),
+            error["message"].as_str().unwrap_or_default()
+        );
+    }
+
+    let Some(content) = response["result"]["content"].as_array() else {
+        return serde_json::to_string_pretty(&response["result"]).unwrap_or_default();
+    };
+    content
+        .iter()
+        .map(|item| match item["text"].as_str() {
+            Some(text) => serde_json::from_str::<Value>(text)
+                .and_then(|json| serde_json::to_string_pretty(&json))
+                .unwrap_or_else(|_| text.to_string()),
+            None => serde_json::to_string_pretty(item).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Outcome of one self-test check
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn from_result(name: impl Into<String>, result: Result<String>) -> Self {
+        let (passed, detail) = match result {
+            Ok(detail) => (true, detail),
+            Err(e) => (false, format!("{:#}", e)),
+        };
+        Self {
+            name: name.into(),
+            passed,
+            detail,
+        }
+    }
+}
+
+/// Run the handshake, every advertised tool and the protocol checks.
+/// Checks keep going after a failure so the report shows everything that is wrong.
+pub fn run_selftest(client: &mut McpClient) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    checks.push(Check::from_result("initialize", check_initialize(client)));
+
+    let tools = match list_tools(client) {
+        Ok(tools) => {
+            let names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
+            checks.push(Check {
+                name: "tools/list".to_string(),
+                passed: true,
+                detail: names.join(", "),
+            });
+            tools
+        }
+        Err(e) => {
+            checks.push(Check::from_result("tools/list", Err(e)));
+            Vec::new()
+        }
+    };
+
+    for tool in &tools {
+        let name = tool["name"].as_str().unwrap_or_default();
+        checks.push(Check::from_result(
+            format!("{} (valid arguments)", name),
+            check_tool_valid(client, name, &tool["inputSchema"]),
+        ));
+        checks.push(Check::from_result(
+            format!("{} (invalid arguments)", name),
+            check_tool_invalid(client, name, &tool["inputSchema"]),
+        ));
+    }
+
+    checks.push(Check::from_result("unknown tool", check_unknown_tool(client)));
+    checks.push(Check::from_result("unknown method", check_unknown_method(client)));
+    checks.push(Check::from_result("id echoing", check_id_echo(client)));
+    checks.push(Check::from_result("parse error", check_parse_error(client)));
+    checks.push(Check::from_result("invalid request", check_invalid_request(client)));
+    checks.push(Check::from_result("notifications", check_notification(client)));
+    checks.push(Check::from_result("batch", check_batch(client)));
+    checks.push(Check::from_result("empty batch", check_empty_batch(client)));
+
+    checks
+}
+
+fn check_initialize(client: &mut McpClient) -> Result<String> {
+    let result = client.initialize("chat-selftest")?;
+
+    let version = result["protocolVersion"].as_str().context("Missing protocolVersion")?;
+    let server = result["serverInfo"]["name"].as_str().context("Missing serverInfo.name")?;
+    if !result["capabilities"]["tools"].is_object() {
+        bail!("Server does not advertise the tools capability");
+    }
+
+    Ok(format!("{} (protocol {})", server, version))
+}
+
+fn list_tools(client: &mut McpClient) -> Result<Vec<Value>> {
+    let tools = client.list_tools()?;
+
+    if tools.is_empty() {
+        bail!("Server lists no tools");
+    }
+    for tool in &tools {
+        let name = tool["name"].as_str().context("Tool without a name")?;
+        if tool["inputSchema"]["type"] != "object" {
+            bail!("Tool '{}' has no object inputSchema", name);
+        }
+    }
+
+    Ok(tools)
+}
+
+/// A valid call must get a text result. An internal error is tolerated since
+/// it usually means a source isn't configured, not that the server is broken.
+fn check_tool_valid(client: &mut McpClient, name: &str, schema: &Value) -> Result<String> {
+    let id = json!(format!("{}-valid", name));
+    let response = client.request_with_id(
+        id.clone(),
+        "tools/call",
+        json!({ "name": name, "arguments": sample_arguments(schema) }),
+    )?;
+    expect_id(&response, &id)?;
+
+    if let Some(error) = response.get("error") {
+        let code = error["code"].as_i64().unwrap_or_default();
+        let message = error["message"].as_str().unwrap_or_default();
+        if code == INTERNAL_ERROR {
+            return Ok(format!("accepted, but failed: {}", message));
+        }
+        bail!("Rejected valid arguments with {}: {}", code, message);
+    }
+
+    let content = response["result"]["content"].as_array().context("Result has no content array")?;
+    if !content.iter().all(|c| c["type"] == "text" && c["text"].is_string()) {
+        bail!("Result content is not text");
+    }
+    Ok("ok".to_string())
+}
+
+/// Arguments of the wrong shape, and calls missing required fields, must be invalid params
+fn check_tool_invalid(client: &mut McpClient, name: &str, schema: &Value) -> Result<String> {
+    let id = json!(format!("{}-wrong-shape", name));
+    let response = client.request_with_id(
+        id.clone(),
+        "tools/call",
+        json!({ "name": name, "arguments": "not an object" }),
+    )?;
+    expect_error(&response, &id, INVALID_PARAMS)?;
+
+    let required = schema["required"].as_array().map(Vec::len).unwrap_or(0);
+    if required > 0 {
+        let id = json!(format!("{}-missing-required", name));
+        let response = client.request_with_id(id.clone(), "tools/call", json!({ "name": name, "arguments": {} }))?;
+        expect_error(&response, &id, INVALID_PARAMS)?;
+    }
+
+    Ok("ok".to_string())
+}
+
+fn check_unknown_tool(client: &mut McpClient) -> Result<String> {
+    let response = client.request_with_id(
+        json!(900),
+        "tools/call",
+        json!({ "name": "no_such_tool", "arguments": {} }),
+    )?;
+    expect_error(&response, &json!(900), METHOD_NOT_FOUND)?;
+    Ok("ok".to_string())
+}
+
+fn check_unknown_method(client: &mut McpClient) -> Result<String> {
+    let response = client.request_with_id(json!(901), "no/such/method", json!({}))?;
+    expect_error(&response, &json!(901), METHOD_NOT_FOUND)?;
+    Ok("ok".to_string())
+}
+
+fn check_id_echo(client: &mut McpClient) -> Result<String> {
+    for id in [json!("a-string-id"), json!(0), json!(9007199254740991u64)] {
+        let response = client.request_with_id(id.clone(), "ping", json!({}))?;
+        expect_result(&response, &id)?;
+    }
+    Ok("ok".to_string())
+}
+
+fn check_parse_error(client: &mut McpClient) -> Result<String> {
+    client.send_raw("{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": ")?;
+    let response = client.receive()?;
+    expect_error(&response, &Value::Null, PARSE_ERROR)?;
+    Ok("ok".to_string())
+}
+
+fn check_invalid_request(client: &mut McpClient) -> Result<String> {
+    // No method
+    client.send_raw(&json!({ "jsonrpc": "2.0", "id": 902 }).to_string())?;
+    let response = client.receive()?;
+    expect_error(&response, &json!(902), INVALID_REQUEST)?;
+
+    // Wrong protocol version
+    client.send_raw(&json!({ "jsonrpc": "1.0", "id": 903, "method": "ping" }).to_string())?;
+    let response = client.receive()?;
+    expect_error(&response, &json!(903), INVALID_REQUEST)?;
+
+    Ok("ok".to_string())
+}
+
+/// Notifications, even for unknown methods, must not get a response: the next
+/// line the server writes has to be the reply to the following ping
+fn check_notification(client: &mut McpClient) -> Result<String> {
+    client.notify("notifications/cancelled", json!({ "requestId": 1 }))?;
+    client.notify("no/such/notification", json!({}))?;
+
+    let response = client.request_with_id(json!("after-notify"), "ping", json!({}))?;
+    expect_result(&response, &json!("after-notify"))?;
+    Ok("ok".to_string())
+}
+
+fn check_batch(client: &mut McpClient) -> Result<String> {
+    let batch = json!([
+        { "jsonrpc": "2.0", "id": "b1", "method": "ping" },
+        { "jsonrpc": "2.0", "method": "notifications/initialized" },
+        { "jsonrpc": "2.0", "id": "b2", "method": "no/such/method" },
+        { "jsonrpc": "2.0", "id": "b3", "method": "tools/list" }
+    ]);
+    client.send_raw(&batch.to_string())?;
+
+    let response = client.receive()?;
+    let responses = response.as_array().context("Batch response is not an array")?;
+    if responses.len() != 3 {
+        bail!("Expected 3 responses (notifications get none), got {}", responses.len());
+    }
+
+    let find = |id: &str| {
+        responses
+            .iter()
+            .find(|r| r["id"] == id)
+            .with_context(|| format!("No response for id '{}'", id))
+    };
+    expect_result(find("b1")?, &json!("b1"))?;
+    expect_error(find("b2")?, &json!("b2"), METHOD_NOT_FOUND)?;
+    expect_result(find("b3")?, &json!("b3"))?;
+
+    Ok("ok".to_string())
+}
+
+fn check_empty_batch(client: &mut McpClient) -> Result<String> {
+    client.send_raw("[]")?;
+    let response = client.receive()?;
+    expect_error(&response, &Value::Null, INVALID_REQUEST)?;
+    Ok("ok".to_string())
+}
+
+/// Arguments that satisfy a tool's input schema: required fields get placeholder values
+fn sample_arguments(schema: &Value) -> Value {
+    let mut arguments = serde_json::Map::new();
+
+    for field in schema["required"].as_array().into_iter().flatten() {
+        let Some(field) = field.as_str() else { continue };
+        let property = &schema["properties"][field];
+
+        let value = if let Some(first) = property["enum"].as_array().and_then(|e| e.first()) {
+            first.clone()
+        } else {
+            match property["type"].as_str() {
+                Some("integer") | Some("number") => json!(1),
+                Some("boolean") => json!(false),
+                Some("array") => json!([]),
+                Some("object") => json!({}),
+                _ if field == "chat" => json!("*"),
+                _ => json!("selftest"),
+            }
+        };
+        arguments.insert(field.to_string(), value);
+    }
+
+    Value::Object(arguments)
+}
+
+fn expect_id(response: &Value, id: &Value) -> Result<()> {
+    if response["jsonrpc"] != "2.0" {
+        bail!("Response is missing jsonrpc: \"2.0\"");
+    }
+    if response.get("id") != Some(id) {
+        bail!("Expected id {}, got {}", id, response.get("id").unwrap_or(&Value::Null));
+    }
+    Ok(())
+}
+
+fn expect_result<'a>(response: &'a Value, id: &Value) -> Result<&'a Value> {
+    expect_id(response, id)?;
+    if let Some(error) = response.get("error") {
+        bail!("Unexpected error: {}", error);
+    }
+    response.get("result").context("Response has neither result nor error")
+}
+
+fn expect_error(response: &Value, id: &Value, code: i64) -> Result<()> {
+    expect_id(response, id)?;
+    if response.get("result").is_some() {
+        bail!("Expected error {}, got a result", code);
+    }
+    let actual = response["error"]["code"].as_i64().context("Error has no code")?;
+    if actual != code {
+        bail!("Expected error {}, got {}: {}", code, actual, response["error"]["message"]);
+    }
+    if !response["error"]["message"].is_string() {
+        bail!("Error has no message");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_argument() {
+        let limit = json!({ "type": ["integer", "null"] });
+        assert_eq!(parse_argument(&limit, " 20 ").unwrap(), json!(20));
+        assert!(parse_argument(&limit, "ten").is_err());
+
+        let ids = json!({ "type": "array", "items": { "type": "string" } });
+        assert_eq!(parse_argument(&ids, "a, b,").unwrap(), json!(["a", "b"]));
+        assert_eq!(parse_argument(&ids, "[1, 2]").unwrap(), json!([1, 2]));
+
+        let flag = json!({ "type": "boolean" });
+        assert_eq!(parse_argument(&flag, "Yes").unwrap(), json!(true));
+
+        let kind = json!({ "type": "string", "enum": ["group", "channel"] });
+        assert_eq!(parse_argument(&kind, "group").unwrap(), json!("group"));
+        assert!(parse_argument(&kind, "dm").is_err());
+    }
+
+    #[test]
+    fn test_format_tool_response() {
+        let response = json!({ "result": { "content": [{ "type": "text", "text": "{\"sources\":[]}" }] } });
+        assert_eq!(format_tool_response(&response), "{\n  \"sources\": []\n}");
+
+        let response = json!({ "result": { "content": [{ "type": "text", "text": "plain" }] } });
+        assert_eq!(format_tool_response(&response), "plain");
+
+        let response = json!({ "error": { "code": -32602, "message": "missing field `chat`" } });
+        assert_eq!(format_tool_response(&response), "Error -32602: missing field `chat`");
+    }
+}
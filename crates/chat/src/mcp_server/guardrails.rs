@@ -0,0 +1,247 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::sources_config::McpSettings;
+
+/// Window per-minute quotas are counted over
+const QUOTA_WINDOW: Duration = Duration::from_secs(60);
+
+/// A tool call refused by a guardrail. Agents get it as the error's `data`, with what
+/// was asked for, what is allowed and how to narrow the request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum GuardrailError {
+    /// A parameter asks for more than the server allows
+    RequestTooLarge {
+        parameter: String,
+        requested: u64,
+        maximum: u64,
+        hint: String,
+    },
+    /// The tool's calls for this minute are used up
+    RateLimited {
+        tool: String,
+        calls_per_minute: u32,
+        retry_after_secs: u64,
+    },
+}
+
+impl std::fmt::Display for GuardrailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GuardrailError::RequestTooLarge { parameter, requested, maximum, hint } => write!(
+                f,
+                "Request too large: {} {} is over the maximum of {}; try narrowing. {}",
+                parameter, requested, maximum, hint
+            ),
+            GuardrailError::RateLimited { tool, calls_per_minute, retry_after_secs } => write!(
+                f,
+                "Too many {} calls: at most {} a minute; retry in {}s",
+                tool, calls_per_minute, retry_after_secs
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GuardrailError {}
+
+/// Default and maximum limits, the longest time range and per-tool call quotas,
+/// checked before a tool call reaches the sources. The settings can be replaced while
+/// the server runs; each check sees either the old or the new ones, never a mix.
+#[derive(Debug, Default)]
+pub struct Guardrails {
+    settings: RwLock<McpSettings>,
+    /// Recent call times per tool, oldest first
+    calls: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl Guardrails {
+    pub fn new(settings: McpSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The settings in force
+    pub fn settings(&self) -> McpSettings {
+        self.current().clone()
+    }
+
+    /// Put new settings in force. Calls already counted keep counting against the new
+    /// quotas. Returns false if nothing changed.
+    pub fn replace(&self, settings: McpSettings) -> bool {
+        let mut current = self.settings.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if *current == settings {
+            return false;
+        }
+        *current = settings;
+        true
+    }
+
+    fn current(&self) -> std::sync::RwLockReadGuard<'_, McpSettings> {
+        self.settings.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Count a call to `tool`, refusing it once the tool's quota for the last minute is used
+    pub fn check_call(&self, tool: &str, now: Instant) -> Result<(), GuardrailError> {
+        let Some(quota) = self.current().quota(tool) else {
+            return Ok(());
+        };
+        let mut calls = self.calls.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let recent = calls.entry(tool.to_string()).or_default();
+        while recent.front().is_some_and(|at| now.duration_since(*at) >= QUOTA_WINDOW) {
+            recent.pop_front();
+        }
+
+        if recent.len() >= quota as usize {
+            let oldest = recent.front().copied().unwrap_or(now);
+            let retry_after = QUOTA_WINDOW.saturating_sub(now.duration_since(oldest));
+            return Err(GuardrailError::RateLimited {
+                tool: tool.to_string(),
+                calls_per_minute: quota,
+                retry_after_secs: retry_after.as_secs().max(1),
+            });
+        }
+        recent.push_back(now);
+        Ok(())
+    }
+
+    /// The limit a call runs with: the default when it gives none, refused over the maximum
+    pub fn limit(&self, requested: Option<usize>) -> Result<usize, GuardrailError> {
+        let default_limit = self.current().default_limit;
+        self.check_limit(requested.unwrap_or(default_limit))
+    }
+
+    /// Refuse a limit over the maximum
+    pub fn check_limit(&self, limit: usize) -> Result<usize, GuardrailError> {
+        let max_limit = self.current().max_limit;
+        if limit > max_limit {
+            return Err(GuardrailError::RequestTooLarge {
+                parameter: "limit".to_string(),
+                requested: limit as u64,
+                maximum: max_limit as u64,
+                hint: format!(
+                    "Ask for at most {} messages, or narrow by chat, sender, search or a shorter since/before range and page through older messages with before.",
+                    max_limit
+                ),
+            });
+        }
+        Ok(limit)
+    }
+
+    /// Refuse a time range from `since` to `before` (or now) longer than the maximum span
+    pub fn check_range(
+        &self,
+        since: Option<DateTime<Utc>>,
+        before: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<(), GuardrailError> {
+        let Some(since) = since else {
+            return Ok(());
+        };
+        let days = (before.unwrap_or(now) - since).num_days();
+        let maximum = self.current().max_range_days;
+        if days > i64::from(maximum) {
+            return Err(GuardrailError::RequestTooLarge {
+                parameter: "time range (days)".to_string(),
+                requested: days as u64,
+                maximum: u64::from(maximum),
+                hint: format!(
+                    "Cover at most {} days per call, e.g. since='{}d', and move before back to reach older messages.",
+                    maximum, maximum
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guardrails() -> Guardrails {
+        Guardrails::new(McpSettings {
+            default_limit: 50,
+            max_limit: 200,
+            max_range_days: 30,
+            calls_per_minute: 2,
+            quotas: [("list_sources".to_string(), 0)].into_iter().collect(),
+        })
+    }
+
+    #[test]
+    fn test_limits() {
+        let guardrails = guardrails();
+        assert_eq!(guardrails.limit(None), Ok(50));
+        assert_eq!(guardrails.limit(Some(200)), Ok(200));
+
+        let error = guardrails.limit(Some(100_000)).unwrap_err();
+        assert!(matches!(&error, GuardrailError::RequestTooLarge { requested: 100_000, maximum: 200, .. }));
+        let data = serde_json::to_value(&error).unwrap();
+        assert_eq!(data["reason"], "request_too_large");
+        assert_eq!(data["parameter"], "limit");
+        assert!(error.to_string().contains("try narrowing"));
+    }
+
+    #[test]
+    fn test_range() {
+        let guardrails = guardrails();
+        let now = Utc::now();
+        let days = |n: i64| Some(now - chrono::Duration::days(n));
+
+        assert!(guardrails.check_range(None, None, now).is_ok());
+        assert!(guardrails.check_range(days(30), None, now).is_ok());
+        assert!(guardrails.check_range(days(400), days(380), now).is_ok());
+        assert!(matches!(
+            guardrails.check_range(days(90), None, now),
+            Err(GuardrailError::RequestTooLarge { requested: 90, maximum: 30, .. })
+        ));
+    }
+
+    #[test]
+    fn test_quotas() {
+        let guardrails = guardrails();
+        let start = Instant::now();
+
+        assert!(guardrails.check_call("get_messages", start).is_ok());
+        assert!(guardrails.check_call("get_messages", start + Duration::from_secs(10)).is_ok());
+        let error = guardrails.check_call("get_messages", start + Duration::from_secs(20)).unwrap_err();
+        assert_eq!(error, GuardrailError::RateLimited {
+            tool: "get_messages".to_string(),
+            calls_per_minute: 2,
+            retry_after_secs: 40,
+        });
+        // Quotas are per tool, and calls age out of the window
+        assert!(guardrails.check_call("get_context", start + Duration::from_secs(20)).is_ok());
+        assert!(guardrails.check_call("get_messages", start + Duration::from_secs(61)).is_ok());
+        // A quota of 0 means no quota
+        for _ in 0..10 {
+            assert!(guardrails.check_call("list_sources", start).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_replace() {
+        let guardrails = guardrails();
+        let start = Instant::now();
+        guardrails.check_call("get_messages", start).unwrap();
+        guardrails.check_call("get_messages", start).unwrap();
+        assert!(guardrails.check_call("get_messages", start).is_err());
+
+        let mut settings = guardrails.settings();
+        assert!(!guardrails.replace(settings.clone()));
+        settings.max_limit = 500;
+        settings.quotas.insert("get_messages".to_string(), 3);
+        assert!(guardrails.replace(settings));
+
+        assert_eq!(guardrails.limit(Some(500)), Ok(500));
+        // Calls made under the old quota still count
+        assert!(guardrails.check_call("get_messages", start).is_ok());
+        assert!(guardrails.check_call("get_messages", start).is_err());
+    }
+}
@@ -1,22 +1,36 @@
 use anyhow::Result;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::types::{ChatFilter, ChatType, MessageFilter};
+use crate::types::{ChatFilter, ChatType, ContentType, MessageFilter, SourceCapabilities};
 use crate::filter_parser;
 
+pub mod guardrails;
 pub mod server;
 pub mod tools;
 
+pub use guardrails::{GuardrailError, Guardrails};
 pub use server::ChatMcpServer;
 
 /// MCP tool names
 pub const TOOL_LIST_SOURCES: &str = "list_sources";
 pub const TOOL_LIST_CHATS: &str = "list_chats";
 pub const TOOL_GET_MESSAGES: &str = "get_messages";
+pub const TOOL_SEMANTIC_SEARCH: &str = "semantic_search";
+pub const TOOL_GET_CONTEXT: &str = "get_context";
+
+/// Token budget for get_context when the caller doesn't give one
+pub const DEFAULT_CONTEXT_BUDGET: usize = 2000;
+
+/// Resource template for topic-segmented chat history
+pub const TOPICS_URI_TEMPLATE: &str = "chat://{source}/{chat}/topics";
 
 /// Request/Response types for MCP tools
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListSourcesRequest {}
 
 #[derive(Debug, Serialize)]
@@ -29,15 +43,28 @@ pub struct SourceInfo {
     pub id: String,
     pub name: String,
     pub is_connected: bool,
+    /// The session expired; the user has to sign in again before the source works
+    pub auth_required: bool,
+    pub capabilities: SourceCapabilities,
+    /// Background tasks that are restarting or have failed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<crate::supervisor::TaskStatus>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListChatsRequest {
+    /// Source ID (telegram, signal, whatsapp)
     pub source: String,
+    /// Filter by name pattern (case-insensitive substring)
     #[serde(default)]
     pub name_pattern: Option<String>,
+    /// Filter by chat type (direct, group, channel)
     #[serde(default)]
+    #[schemars(schema_with = "chat_type_schema")]
     pub chat_type: Option<String>,
+    /// Only chats carrying this local label (e.g., 'work')
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -53,20 +80,77 @@ pub struct ChatInfo {
     pub participant_count: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetMessagesRequest {
+    /// Source ID (telegram, signal, whatsapp). Optional - queries all sources if not specified
+    #[serde(default)]
     pub source: Option<String>,
+    /// Chat identifier (name, ID, '#label', or pattern like 'Antti' or '*' for all)
     pub chat: String,
+    /// Messages after this time (e.g., '7d', '2h', '2025-01-15')
     #[serde(default)]
     pub since: Option<String>,
+    /// Messages before this time
     #[serde(default)]
     pub before: Option<String>,
+    /// Filter by sender name or ID
     #[serde(default)]
     pub sender: Option<String>,
+    /// Text search (substring, ignoring case and accents unless exact)
     #[serde(default)]
     pub search: Option<String>,
+    /// Match search only against text extracted from attachments: documents, image text and transcripts (default: false)
+    #[serde(default)]
+    pub in_attachments: bool,
+    /// Limit number of results (default: 100, at most the server's max_limit)
     #[serde(default)]
     pub limit: Option<usize>,
+    /// Only messages with one of these content types (e.g., ["image", "video"])
+    #[serde(default)]
+    pub content_type: Option<Vec<ContentKind>>,
+    /// Only return messages that mention me or reply to me (default: false)
+    #[serde(default)]
+    pub mentions_me: bool,
+    /// Match search and sender case- and accent-sensitively (default: false)
+    #[serde(default)]
+    pub exact: bool,
+    /// Only messages detected to be in this language, as an ISO 639 code (e.g., "en", "fi")
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// Also return system events such as joins, leaves and renames (default: false)
+    #[serde(default)]
+    pub include_system: bool,
+}
+
+/// Message content type as spelled in tool arguments
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+    Text,
+    Image,
+    Video,
+    Audio,
+    File,
+    Sticker,
+    Location,
+    Contact,
+    System,
+}
+
+impl From<ContentKind> for ContentType {
+    fn from(kind: ContentKind) -> Self {
+        match kind {
+            ContentKind::Text => ContentType::Text,
+            ContentKind::Image => ContentType::Image,
+            ContentKind::Video => ContentType::Video,
+            ContentKind::Audio => ContentType::Audio,
+            ContentKind::File => ContentType::File,
+            ContentKind::Sticker => ContentType::Sticker,
+            ContentKind::Location => ContentType::Location,
+            ContentKind::Contact => ContentType::Contact,
+            ContentKind::System => ContentType::System,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -82,7 +166,74 @@ pub struct MessageInfo {
     pub sender: SenderInfo,
     pub content: String,
     pub timestamp: String,
+    /// Timestamp as the source reported it, when its clock was off and `timestamp` was corrected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_timestamp: Option<String>,
     pub edited: bool,
+    pub mentions_me: bool,
+    /// A draft or scheduled message I haven't sent yet
+    pub pending: bool,
+    /// Detected language (ISO 639-1), when the text was long enough to tell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Text derived from an attachment: a voice note transcript, or OCR and captions of an image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_text: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SemanticSearchRequest {
+    /// Natural-language question or description of what to find
+    pub query: String,
+    /// Source ID (telegram, signal, whatsapp). Optional - searches all sources if not specified
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Chat identifier (name, ID, '#label', or '*' for all). Optional - defaults to all chats
+    #[serde(default)]
+    pub chat: Option<String>,
+    /// Index messages after this time before searching (default: '90d')
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Number of results (default: 10)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Embedding model (default: nomic-embed-text)
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SemanticSearchResponse {
+    pub results: Vec<ScoredMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoredMessage {
+    pub score: f32,
+    pub source: String,
+    pub chat_id: String,
+    pub message_id: String,
+    pub sender: Option<String>,
+    pub content: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetContextRequest {
+    /// Source ID (telegram, signal, whatsapp). Optional - uses all sources if not specified
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Chat identifier (name, ID, '#label', or '*' for all)
+    pub chat: String,
+    /// Consider messages after this time (default: '7d')
+    #[serde(default)]
+    pub since: Option<String>,
+    /// What the context is needed for; relevant messages are preferred (requires embeddings feature)
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Token budget for the transcript (default: 2000)
+    #[serde(default)]
+    pub budget: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -91,6 +242,42 @@ pub struct SenderInfo {
     pub display_name: Option<String>,
 }
 
+/// A parsed `chat://{source}/{chat}/topics[?since=30d]` resource URI
+#[derive(Debug, PartialEq)]
+pub struct TopicsResource {
+    pub source: String,
+    pub chat: String,
+    pub since: String,
+}
+
+impl TopicsResource {
+    /// Parse a topics resource URI
+    pub fn parse(uri: &str) -> Result<Self> {
+        let rest = uri
+            .strip_prefix("chat://")
+            .ok_or_else(|| anyhow::anyhow!("Unsupported resource URI '{}'", uri))?;
+
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let parts: Vec<&str> = path.split('/').collect();
+
+        let [source, chat, "topics"] = parts.as_slice() else {
+            anyhow::bail!("Unsupported resource URI '{}'. Expected {}", uri, TOPICS_URI_TEMPLATE);
+        };
+
+        let since = query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("since="))
+            .unwrap_or("30d")
+            .to_string();
+
+        Ok(Self {
+            source: source.to_string(),
+            chat: chat.to_string(),
+            since,
+        })
+    }
+}
+
 /// Convert internal types to MCP response types
 impl From<crate::types::SourceInfo> for SourceInfo {
     fn from(info: crate::types::SourceInfo) -> Self {
@@ -98,6 +285,23 @@ impl From<crate::types::SourceInfo> for SourceInfo {
             id: info.id,
             name: info.name,
             is_connected: info.is_connected,
+            auth_required: info.auth_required,
+            capabilities: info.capabilities,
+            tasks: info.tasks.into_iter().filter(|task| !task.is_healthy()).collect(),
+        }
+    }
+}
+
+impl From<crate::embeddings::SearchHit> for ScoredMessage {
+    fn from(hit: crate::embeddings::SearchHit) -> Self {
+        Self {
+            score: hit.score,
+            source: hit.message.source,
+            chat_id: hit.message.chat_id.to_string(),
+            message_id: hit.message.message_id.to_string(),
+            sender: hit.message.sender,
+            content: hit.message.text,
+            timestamp: hit.message.timestamp.to_rfc3339(),
         }
     }
 }
@@ -141,6 +345,7 @@ impl From<&crate::types::Message> for MessageInfo {
             crate::types::MessageContent::Contact { name, phone } => {
                 format!("[Contact] {} {}", name, phone.as_deref().unwrap_or(""))
             }
+            crate::types::MessageContent::System(event) => format!("[System] {}", event.describe()),
             crate::types::MessageContent::Unknown => "[Unknown]".to_string(),
         };
 
@@ -153,11 +358,47 @@ impl From<&crate::types::Message> for MessageInfo {
             },
             content,
             timestamp: msg.timestamp.to_rfc3339(),
+            original_timestamp: msg.original_timestamp.map(|t| t.to_rfc3339()),
             edited: msg.edited,
+            mentions_me: msg.mentions_me,
+            pending: msg.pending,
+            language: msg.language.clone(),
+            media_text: msg.media_text.clone(),
         }
     }
 }
 
+/// JSON schema for a tool's arguments, generated from its request type.
+/// Optional fields are left out of `required` rather than marked nullable.
+pub fn input_schema<T: JsonSchema>() -> Value {
+    let generator = SchemaSettings::draft07()
+        .with(|settings| {
+            settings.option_add_null_type = false;
+            settings.inline_subschemas = true;
+        })
+        .into_generator();
+
+    let mut schema = serde_json::to_value(generator.into_root_schema_for::<T>())
+        .expect("schema serializes to JSON");
+
+    if let Some(object) = schema.as_object_mut() {
+        object.remove("$schema");
+        object.remove("title");
+        object.entry("properties").or_insert_with(|| serde_json::json!({}));
+        object.entry("required").or_insert_with(|| serde_json::json!([]));
+    }
+
+    schema
+}
+
+fn chat_type_schema(_: &mut SchemaGenerator) -> Schema {
+    serde_json::from_value(serde_json::json!({
+        "type": "string",
+        "enum": ["direct", "group", "channel"]
+    }))
+    .expect("valid schema")
+}
+
 /// Helper function to parse chat type string
 pub fn parse_chat_type(type_str: &str) -> Result<ChatType> {
     match type_str.to_lowercase().as_str() {
@@ -184,6 +425,11 @@ pub fn build_chat_filter(req: &ListChatsRequest) -> Result<Option<ChatFilter>> {
         has_filters = true;
     }
 
+    if let Some(ref label) = req.label {
+        filter = filter.with_label(label);
+        has_filters = true;
+    }
+
     if has_filters {
         Ok(Some(filter))
     } else {
@@ -195,6 +441,8 @@ pub fn build_chat_filter(req: &ListChatsRequest) -> Result<Option<ChatFilter>> {
 pub async fn build_message_filter(req: &GetMessagesRequest) -> Result<MessageFilter> {
     // Parse chat pattern from the chat field
     let (_, chat_pattern) = filter_parser::parse_source_filter(&req.chat)?;
+    let include_system = req.include_system
+        || req.content_type.as_ref().is_some_and(|kinds| kinds.contains(&ContentKind::System));
 
     let mut filter = MessageFilter {
         chat: chat_pattern,
@@ -203,7 +451,16 @@ pub async fn build_message_filter(req: &GetMessagesRequest) -> Result<MessageFil
         sender: req.sender.clone(),
         search: req.search.clone(),
         limit: req.limit,
-        content_type: None,
+        content_type: req
+            .content_type
+            .as_ref()
+            .map(|kinds| kinds.iter().copied().map(ContentType::from).collect()),
+        mentions_me: req.mentions_me,
+        exact: req.exact,
+        language: req.lang.clone(),
+        include_system,
+        in_attachments: req.in_attachments,
+        separate_merged: false,
     };
 
     // Parse time specifications
@@ -240,19 +497,90 @@ mod tests {
             source: "telegram".to_string(),
             name_pattern: None,
             chat_type: None,
+            label: None,
         };
         let filter = build_chat_filter(&req).unwrap();
         assert!(filter.is_none());
     }
 
+    #[test]
+    fn test_build_chat_filter_with_label() {
+        let req = ListChatsRequest {
+            source: "telegram".to_string(),
+            name_pattern: None,
+            chat_type: None,
+            label: Some("work".to_string()),
+        };
+        let filter = build_chat_filter(&req).unwrap().unwrap();
+        assert_eq!(filter.label.as_deref(), Some("work"));
+    }
+
     #[test]
     fn test_build_chat_filter_with_name() {
         let req = ListChatsRequest {
             source: "telegram".to_string(),
             name_pattern: Some("Work".to_string()),
             chat_type: None,
+            label: None,
         };
         let filter = build_chat_filter(&req).unwrap();
         assert!(filter.is_some());
     }
+
+    /// Placeholder value matching a property schema
+    fn sample(property: &Value) -> Value {
+        if let Some(first) = property["enum"].as_array().and_then(|e| e.first()) {
+            return first.clone();
+        }
+        match property["type"].as_str() {
+            Some("integer") => serde_json::json!(5),
+            Some("boolean") => serde_json::json!(true),
+            Some("array") => serde_json::json!([sample(&property["items"])]),
+            _ => serde_json::json!("7d"),
+        }
+    }
+
+    /// Every schema property deserializes, and exactly the schema's required
+    /// fields are required by serde
+    fn assert_in_sync<T: JsonSchema + serde::de::DeserializeOwned>() {
+        let schema = input_schema::<T>();
+        let properties = schema["properties"].as_object().unwrap();
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r.as_str().unwrap())
+            .collect();
+
+        let full: serde_json::Map<String, Value> =
+            properties.iter().map(|(name, property)| (name.clone(), sample(property))).collect();
+        serde_json::from_value::<T>(Value::Object(full.clone()))
+            .unwrap_or_else(|e| panic!("schema properties don't deserialize: {}", e));
+
+        for name in properties.keys() {
+            let mut without = full.clone();
+            without.remove(name);
+            let parsed = serde_json::from_value::<T>(Value::Object(without));
+            assert_eq!(
+                parsed.is_err(),
+                required.contains(&name.as_str()),
+                "'{}' is required by one of serde and the schema but not the other",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_schemas_match_request_types() {
+        assert_in_sync::<ListSourcesRequest>();
+        assert_in_sync::<ListChatsRequest>();
+        assert_in_sync::<GetMessagesRequest>();
+        assert_in_sync::<SemanticSearchRequest>();
+        assert_in_sync::<GetContextRequest>();
+
+        let schema = input_schema::<GetMessagesRequest>();
+        assert_eq!(schema["required"], serde_json::json!(["chat"]));
+        assert_eq!(schema["properties"]["content_type"]["items"]["enum"][1], "image");
+        assert_eq!(input_schema::<ListChatsRequest>()["properties"]["chat_type"]["enum"][0], "direct");
+    }
 }
@@ -2,8 +2,17 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::io::{self, BufRead, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
 
-use crate::SourcesManager;
+use crate::{McpSettings, SourceEvent, SourcesConfig, SourcesManager};
 
 use super::tools::*;
 use super::*;
@@ -43,22 +52,123 @@ const ERROR_INVALID_REQUEST: i32 = -32600;
 const ERROR_METHOD_NOT_FOUND: i32 = -32601;
 const ERROR_INVALID_PARAMS: i32 = -32602;
 const ERROR_INTERNAL_ERROR: i32 = -32603;
+/// A tool's per-minute call quota is used up (server-defined range)
+const ERROR_RATE_LIMITED: i32 = -32000;
+
+/// How often source connections are checked for event notifications
+const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often a long-running server pings sources to keep sessions alive and notice expiry
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// How often the policy file is checked for changes
+const POLICY_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// File the tool policy (`read_only` and `[mcp]`) is reloaded from while the server runs
+#[derive(Debug, Clone)]
+struct PolicyFile {
+    path: PathBuf,
+    /// `--read-only` was given, so the file can't turn read-only mode off
+    forced_read_only: bool,
+}
 
 /// MCP Server implementation
+#[derive(Clone)]
 pub struct ChatMcpServer {
     manager: SourcesManager,
+    guardrails: Arc<Guardrails>,
+    policy_file: Option<PolicyFile>,
+    /// Server notifications for every connected client
+    notifications: broadcast::Sender<Value>,
 }
 
 impl ChatMcpServer {
-    /// Create a new MCP server
+    /// Create a new MCP server with the default guardrails
     pub fn new(manager: SourcesManager) -> Self {
-        Self { manager }
+        let (notifications, _) = broadcast::channel(16);
+        Self {
+            manager,
+            guardrails: Arc::new(Guardrails::default()),
+            policy_file: None,
+            notifications,
+        }
+    }
+
+    /// Use these limits and quotas (the `[mcp]` section of sources.toml) for tool calls
+    pub fn with_guardrails(mut self, settings: McpSettings) -> Self {
+        self.guardrails = Arc::new(Guardrails::new(settings));
+        self
+    }
+
+    /// Reload the tool policy from `path` (sources.toml) whenever it changes, without
+    /// dropping connected clients. With `forced_read_only` the server stays read-only
+    /// whatever the file says.
+    pub fn with_policy_reload(mut self, path: PathBuf, forced_read_only: bool) -> Self {
+        self.policy_file = Some(PolicyFile { path, forced_read_only });
+        self
+    }
+
+    /// Put a tool policy in force and, if it changed anything, tell connected clients
+    /// their capabilities changed. Returns whether it changed anything.
+    pub fn apply_policy(&self, settings: McpSettings, read_only: bool) -> bool {
+        let mut changed = self.guardrails.replace(settings);
+        if self.manager.is_read_only() != read_only {
+            self.manager.set_read_only(read_only);
+            changed = true;
+        }
+
+        if changed {
+            for notification in policy_notifications(&self.guardrails.settings(), read_only) {
+                // No receivers just means no client is connected
+                let _ = self.notifications.send(notification);
+            }
+        }
+        changed
+    }
+
+    /// Check the policy file for changes and apply them. A file that fails to load
+    /// leaves the current policy in force until it is fixed.
+    fn watch_policy(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let policy_file = self.policy_file.clone()?;
+        let server = self.clone();
+
+        Some(tokio::spawn(async move {
+            let mut modified = modified_time(&policy_file.path);
+            let mut ticks = tokio::time::interval(POLICY_CHECK_INTERVAL);
+            loop {
+                ticks.tick().await;
+                let now_modified = modified_time(&policy_file.path);
+                if now_modified == modified {
+                    continue;
+                }
+                modified = now_modified;
+
+                match SourcesConfig::load_from(&policy_file.path) {
+                    Ok(config) => {
+                        let read_only = policy_file.forced_read_only || config.read_only;
+                        if server.apply_policy(config.mcp, read_only) {
+                            log::info!("Reloaded MCP policy from {}", policy_file.path.display());
+                        }
+                    }
+                    Err(e) => log::warn!("Keeping the current MCP policy: {:#}", e),
+                }
+            }
+        }))
     }
 
     /// Run the server on stdio
     pub async fn run_stdio(&self) -> Result<()> {
         eprintln!("Chat MCP Server starting on stdio...");
-        eprintln!("Available tools: list_sources, list_chats, get_messages");
+        eprintln!("Available tools: list_sources, list_chats, get_messages, semantic_search, get_context");
+        eprintln!("Available resources: {}", TOPICS_URI_TEMPLATE);
+
+        let watcher = self.manager.watch_connections(CONNECTION_CHECK_INTERVAL);
+        let policy_watcher = self.watch_policy();
+        // Each writeln holds the stdout lock, so notifications never split a response
+        let forwarder = self.forward_events(|notification| {
+            let mut stdout = io::stdout();
+            writeln!(stdout, "{}", notification).and_then(|_| stdout.flush()).is_ok()
+        });
 
         let stdin = io::stdin();
         let mut stdout = io::stdout();
@@ -72,62 +182,225 @@ impl ChatMcpServer {
 
             eprintln!("Received request: {}", line);
 
-            let response = self.handle_request(&line).await;
-            let response_json = serde_json::to_string(&response)?;
+            // Notifications (and batches of only notifications) get no reply
+            if let Some(response) = self.handle_message(&line).await {
+                writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
+                stdout.flush()?;
+
+                eprintln!("Sent response");
+            }
+        }
+
+        watcher.abort();
+        forwarder.abort();
+        if let Some(policy_watcher) = policy_watcher {
+            policy_watcher.abort();
+        }
+        self.manager.shutdown().await;
+        Ok(())
+    }
+
+    /// Serve clients connecting to a local TCP port, one JSON-RPC message per line like
+    /// stdio. All clients share this server's sources, so a long-running host (the
+    /// dashboard) can give every agent chat access over one set of connections.
+    pub async fn run_tcp(&self, addr: SocketAddr) -> Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        eprintln!("Listening on {}", listener.local_addr()?);
+        let result = self.serve_tcp(listener).await;
+        self.manager.shutdown().await;
+        result
+    }
+
+    /// Serve clients connecting to a listener the caller has bound, as `run_tcp` does, but
+    /// leave the sources connected: they belong to the caller. The dashboard binds its port
+    /// up front so a taken port fails before it starts.
+    pub async fn serve_tcp(&self, listener: TcpListener) -> Result<()> {
+        let watcher = self.manager.watch_connections(CONNECTION_CHECK_INTERVAL);
+        let policy_watcher = self.watch_policy();
+        let manager = self.manager.clone();
+        let keep_alive = tokio::spawn(async move {
+            let mut pings = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+            loop {
+                pings.tick().await;
+                manager.check_auth().await;
+            }
+        });
+
+        let result = loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => break Result::<()>::Err(e.into()),
+            };
+            eprintln!("Client connected: {}", peer);
+
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.serve_connection(stream).await {
+                    eprintln!("Client {} failed: {}", peer, e);
+                }
+                eprintln!("Client disconnected: {}", peer);
+            });
+        };
+
+        keep_alive.abort();
+        watcher.abort();
+        if let Some(policy_watcher) = policy_watcher {
+            policy_watcher.abort();
+        }
+        result
+    }
+
+    async fn serve_connection(&self, stream: tokio::net::TcpStream) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
 
-            writeln!(stdout, "{}", response_json)?;
-            stdout.flush()?;
+        // Responses and notifications go through one queue so lines never interleave
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let write_task = tokio::spawn(async move {
+            while let Some(line) = rx.recv().await {
+                if writer.write_all(format!("{}\n", line).as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let notifications = tx.clone();
+        let forwarder = self.forward_events(move |notification| notifications.send(notification.to_string()).is_ok());
 
-            eprintln!("Sent response");
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_message(&line).await {
+                if tx.send(serde_json::to_string(&response)?).is_err() {
+                    break;
+                }
+            }
         }
 
+        forwarder.abort();
+        drop(tx);
+        let _ = write_task.await;
         Ok(())
     }
 
-    /// Handle a JSON-RPC request
-    async fn handle_request(&self, request_str: &str) -> JsonRpcResponse {
-        // Parse request
-        let request: JsonRpcRequest = match serde_json::from_str(request_str) {
+    /// Send source connection events to the client as log notifications, so it
+    /// notices disconnects and expired sessions without polling list_sources, along
+    /// with the server's own notifications such as policy changes.
+    /// `send` delivers one notification and returns false once the client is gone.
+    fn forward_events<F>(&self, mut send: F) -> tokio::task::JoinHandle<()>
+    where
+        F: FnMut(&Value) -> bool + Send + 'static,
+    {
+        let mut events = self.manager.events().subscribe();
+        let mut notifications = self.notifications.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                let notification = tokio::select! {
+                    event = events.recv() => match event {
+                        Ok(event) => source_event_notification(&event),
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    },
+                    notification = notifications.recv() => match notification {
+                        Ok(notification) => notification,
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+
+                if !send(&notification) {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Handle one line of input: a single request or a batch
+    async fn handle_message(&self, message: &str) -> Option<Value> {
+        let value: Value = match serde_json::from_str(message) {
+            Ok(value) => value,
+            Err(e) => {
+                let response = error_response(None, ERROR_PARSE_ERROR, format!("Parse error: {}", e));
+                return serde_json::to_value(response).ok();
+            }
+        };
+
+        match value {
+            Value::Array(batch) if batch.is_empty() => {
+                let response = error_response(None, ERROR_INVALID_REQUEST, "Invalid request: empty batch".to_string());
+                serde_json::to_value(response).ok()
+            }
+            Value::Array(batch) => {
+                let mut responses = Vec::new();
+                for item in batch {
+                    if let Some(response) = self.handle_request(item).await {
+                        responses.extend(serde_json::to_value(response).ok());
+                    }
+                }
+
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            value => self
+                .handle_request(value)
+                .await
+                .and_then(|response| serde_json::to_value(response).ok()),
+        }
+    }
+
+    /// Handle a JSON-RPC request, returning `None` for notifications
+    async fn handle_request(&self, value: Value) -> Option<JsonRpcResponse> {
+        // Echo the id even when the rest of the request is malformed
+        let id = value.get("id").cloned().filter(|id| id.is_string() || id.is_number());
+
+        let request: JsonRpcRequest = match serde_json::from_value(value) {
             Ok(req) => req,
             Err(e) => {
-                return JsonRpcResponse {
-                    jsonrpc: "2.0".to_string(),
-                    id: None,
-                    result: None,
-                    error: Some(JsonRpcError {
-                        code: ERROR_PARSE_ERROR,
-                        message: format!("Parse error: {}", e),
-                        data: None,
-                    }),
-                };
+                return Some(error_response(id, ERROR_INVALID_REQUEST, format!("Invalid request: {}", e)));
             }
         };
 
-        // Handle the method
-        match self.handle_method(&request).await {
-            Ok(result) => JsonRpcResponse {
+        if request.jsonrpc != "2.0" {
+            return Some(error_response(
+                id,
+                ERROR_INVALID_REQUEST,
+                format!("Invalid request: unsupported jsonrpc version '{}'", request.jsonrpc),
+            ));
+        }
+
+        let result = self.handle_method(&request).await;
+
+        // Notifications have no id and never get a response, even on error
+        let id = request.id?;
+
+        match result {
+            Ok(result) => Some(JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
-                id: request.id,
+                id: Some(id),
                 result: Some(result),
                 error: None,
-            },
+            }),
             Err(e) => {
+                // Refused calls carry what was asked for and how to narrow it, for agents to act on
+                if let Some(refusal) = e.downcast_ref::<GuardrailError>() {
+                    let code = match refusal {
+                        GuardrailError::RequestTooLarge { .. } => ERROR_INVALID_PARAMS,
+                        GuardrailError::RateLimited { .. } => ERROR_RATE_LIMITED,
+                    };
+                    let mut response = error_response(Some(id), code, refusal.to_string());
+                    if let Some(error) = response.error.as_mut() {
+                        error.data = serde_json::to_value(refusal).ok();
+                    }
+                    return Some(response);
+                }
+
                 let (code, message) = match e.downcast_ref::<MethodError>() {
                     Some(MethodError::MethodNotFound(msg)) => (ERROR_METHOD_NOT_FOUND, msg.clone()),
                     Some(MethodError::InvalidParams(msg)) => (ERROR_INVALID_PARAMS, msg.clone()),
                     None => (ERROR_INTERNAL_ERROR, e.to_string()),
                 };
 
-                JsonRpcResponse {
-                    jsonrpc: "2.0".to_string(),
-                    id: request.id,
-                    result: None,
-                    error: Some(JsonRpcError {
-                        code,
-                        message,
-                        data: None,
-                    }),
-                }
+                Some(error_response(Some(id), code, message))
             }
         }
     }
@@ -136,8 +409,13 @@ impl ChatMcpServer {
     async fn handle_method(&self, request: &JsonRpcRequest) -> Result<Value> {
         match request.method.as_str() {
             "initialize" => self.handle_initialize(request).await,
+            "ping" => Ok(json!({})),
+            method if method.starts_with("notifications/") => Ok(Value::Null),
             "tools/list" => self.handle_tools_list().await,
             "tools/call" => self.handle_tools_call(request).await,
+            "resources/list" => Ok(json!({ "resources": [] })),
+            "resources/templates/list" => self.handle_resource_templates_list().await,
+            "resources/read" => self.handle_resources_read(request).await,
             _ => Err(MethodError::MethodNotFound(format!(
                 "Method '{}' not found",
                 request.method
@@ -151,7 +429,9 @@ impl ChatMcpServer {
         Ok(json!({
             "protocolVersion": "2024-11-05",
             "capabilities": {
-                "tools": {}
+                "tools": { "listChanged": true },
+                "resources": {},
+                "logging": {}
             },
             "serverInfo": {
                 "name": "chat-mcp-server",
@@ -166,78 +446,69 @@ impl ChatMcpServer {
             "tools": [
                 {
                     "name": TOOL_LIST_SOURCES,
-                    "description": "List all configured chat sources (telegram, signal, whatsapp) with their connection status",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {},
-                        "required": []
-                    }
+                    "description": "List all configured chat sources (telegram, signal, whatsapp) with their connection status and supported capabilities",
+                    "inputSchema": input_schema::<ListSourcesRequest>()
                 },
                 {
                     "name": TOOL_LIST_CHATS,
                     "description": "List chats from a specific source with optional filtering by name pattern or chat type",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "source": {
-                                "type": "string",
-                                "description": "Source ID (telegram, signal, whatsapp)"
-                            },
-                            "name_pattern": {
-                                "type": "string",
-                                "description": "Filter by name pattern (case-insensitive substring)"
-                            },
-                            "chat_type": {
-                                "type": "string",
-                                "description": "Filter by chat type (direct, group, channel)",
-                                "enum": ["direct", "group", "channel"]
-                            }
-                        },
-                        "required": ["source"]
-                    }
+                    "inputSchema": input_schema::<ListChatsRequest>()
                 },
                 {
                     "name": TOOL_GET_MESSAGES,
                     "description": "Get messages from a chat with advanced filtering by time, sender, and content",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "source": {
-                                "type": "string",
-                                "description": "Source ID (telegram, signal, whatsapp). Optional - queries all sources if not specified"
-                            },
-                            "chat": {
-                                "type": "string",
-                                "description": "Chat identifier (name, ID, or pattern like 'Antti' or '*' for all)"
-                            },
-                            "since": {
-                                "type": "string",
-                                "description": "Messages after this time (e.g., '7d', '2h', '2025-01-15')"
-                            },
-                            "before": {
-                                "type": "string",
-                                "description": "Messages before this time"
-                            },
-                            "sender": {
-                                "type": "string",
-                                "description": "Filter by sender name or ID"
-                            },
-                            "search": {
-                                "type": "string",
-                                "description": "Text search (case-insensitive substring)"
-                            },
-                            "limit": {
-                                "type": "integer",
-                                "description": "Limit number of results (default: 100)"
-                            }
-                        },
-                        "required": ["chat"]
-                    }
+                    "inputSchema": input_schema::<GetMessagesRequest>()
+                },
+                {
+                    "name": TOOL_SEMANTIC_SEARCH,
+                    "description": "Search messages by meaning rather than exact words, e.g. 'when did we agree on the API design?'. Returns messages scored by similarity",
+                    "inputSchema": input_schema::<SemanticSearchRequest>()
+                },
+                {
+                    "name": TOOL_GET_CONTEXT,
+                    "description": "Get a compact transcript of the most useful messages that fits a token budget: messages relevant to an optional query (with the messages they reply to), then the most recent ones. Local notes on the chats, the senders and people named in the query come first",
+                    "inputSchema": input_schema::<GetContextRequest>()
                 }
             ]
         }))
     }
 
+    /// Handle resources/templates/list request
+    async fn handle_resource_templates_list(&self) -> Result<Value> {
+        Ok(json!({
+            "resourceTemplates": [
+                {
+                    "uriTemplate": TOPICS_URI_TEMPLATE,
+                    "name": "Chat topics",
+                    "description": "Chat history split into topic segments with generated titles and transcripts. Append ?since=7d to change the time range (default 30d)",
+                    "mimeType": "text/markdown"
+                }
+            ]
+        }))
+    }
+
+    /// Handle resources/read request
+    async fn handle_resources_read(&self, request: &JsonRpcRequest) -> Result<Value> {
+        let uri = request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("uri"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| MethodError::InvalidParams("Missing resource uri".to_string()))?;
+
+        let resource = TopicsResource::parse(uri)
+            .map_err(|e| MethodError::InvalidParams(e.to_string()))?;
+        let text = handle_read_topics(&resource, &self.manager, &self.guardrails).await?;
+
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "text/markdown",
+                "text": text
+            }]
+        }))
+    }
+
     /// Handle tools/call request
     async fn handle_tools_call(&self, request: &JsonRpcRequest) -> Result<Value> {
         let params = request.params.as_ref().ok_or_else(|| {
@@ -252,9 +523,11 @@ impl ChatMcpServer {
         let empty_args = json!({});
         let arguments = params.get("arguments").unwrap_or(&empty_args);
 
+        self.guardrails.check_call(tool_name, Instant::now())?;
+
         match tool_name {
             TOOL_LIST_SOURCES => {
-                let req: ListSourcesRequest = serde_json::from_value(arguments.clone())?;
+                let req: ListSourcesRequest = parse_arguments(arguments)?;
                 let response = handle_list_sources(req, &self.manager).await?;
                 Ok(json!({
                     "content": [{
@@ -264,7 +537,7 @@ impl ChatMcpServer {
                 }))
             }
             TOOL_LIST_CHATS => {
-                let req: ListChatsRequest = serde_json::from_value(arguments.clone())?;
+                let req: ListChatsRequest = parse_arguments(arguments)?;
                 let response = handle_list_chats(req, &self.manager).await?;
                 Ok(json!({
                     "content": [{
@@ -274,8 +547,28 @@ impl ChatMcpServer {
                 }))
             }
             TOOL_GET_MESSAGES => {
-                let req: GetMessagesRequest = serde_json::from_value(arguments.clone())?;
-                let response = handle_get_messages(req, &self.manager).await?;
+                let req: GetMessagesRequest = parse_arguments(arguments)?;
+                let response = handle_get_messages(req, &self.manager, &self.guardrails).await?;
+                Ok(json!({
+                    "content": [{
+                        "type": "text",
+                        "text": serde_json::to_string_pretty(&response)?
+                    }]
+                }))
+            }
+            TOOL_SEMANTIC_SEARCH => {
+                let req: SemanticSearchRequest = parse_arguments(arguments)?;
+                let response = handle_semantic_search(req, &self.manager, &self.guardrails).await?;
+                Ok(json!({
+                    "content": [{
+                        "type": "text",
+                        "text": serde_json::to_string_pretty(&response)?
+                    }]
+                }))
+            }
+            TOOL_GET_CONTEXT => {
+                let req: GetContextRequest = parse_arguments(arguments)?;
+                let response = handle_get_context(req, &self.manager, &self.guardrails).await?;
                 Ok(json!({
                     "content": [{
                         "type": "text",
@@ -292,6 +585,68 @@ impl ChatMcpServer {
     }
 }
 
+/// Deserialize tool arguments, reporting bad ones as invalid params
+fn parse_arguments<T: serde::de::DeserializeOwned>(arguments: &Value) -> Result<T> {
+    serde_json::from_value(arguments.clone())
+        .map_err(|e| MethodError::InvalidParams(format!("Invalid arguments: {}", e)).into())
+}
+
+/// Build a `notifications/message` notification for a source event
+fn source_event_notification(event: &SourceEvent) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/message",
+        "params": {
+            "level": if event.is_problem() { "warning" } else { "info" },
+            "logger": "chat.sources",
+            "data": event
+        }
+    })
+}
+
+/// Build the notifications telling clients the tool policy changed: the standard
+/// `notifications/tools/list_changed`, and a log message with the policy now in force
+fn policy_notifications(settings: &McpSettings, read_only: bool) -> [Value; 2] {
+    [
+        json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/tools/list_changed"
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": {
+                "level": "info",
+                "logger": "chat.policy",
+                "data": {
+                    "kind": "policy_changed",
+                    "read_only": read_only,
+                    "limits": settings
+                }
+            }
+        }),
+    ]
+}
+
+/// When a file was last modified, `None` if it doesn't exist
+fn modified_time(path: &Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Build an error response
+fn error_response(id: Option<Value>, code: i32, message: String) -> JsonRpcResponse {
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(JsonRpcError {
+            code,
+            message,
+            data: None,
+        }),
+    }
+}
+
 /// Custom error types for method handling
 #[derive(Debug)]
 enum MethodError {
@@ -309,3 +664,32 @@ impl std::fmt::Display for MethodError {
 }
 
 impl std::error::Error for MethodError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_policy_notifies_on_change() {
+        let server = ChatMcpServer::new(SourcesManager::new());
+        let mut notifications = server.notifications.subscribe();
+
+        assert!(!server.apply_policy(McpSettings::default(), false));
+        assert!(notifications.try_recv().is_err());
+
+        let settings = McpSettings {
+            max_limit: 50,
+            default_limit: 20,
+            ..McpSettings::default()
+        };
+        assert!(server.apply_policy(settings, true));
+        assert!(server.manager.is_read_only());
+        assert_eq!(server.guardrails.limit(None), Ok(20));
+
+        let list_changed = notifications.try_recv().unwrap();
+        assert_eq!(list_changed["method"], "notifications/tools/list_changed");
+        let message = notifications.try_recv().unwrap();
+        assert_eq!(message["params"]["data"]["kind"], "policy_changed");
+        assert_eq!(message["params"]["data"]["limits"]["max_limit"], 50);
+    }
+}
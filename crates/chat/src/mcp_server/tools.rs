@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::Utc;
 
 use crate::SourcesManager;
 
@@ -39,9 +40,12 @@ pub async fn handle_list_chats(
 pub async fn handle_get_messages(
     request: GetMessagesRequest,
     manager: &SourcesManager,
+    guardrails: &Guardrails,
 ) -> Result<GetMessagesResponse> {
     // Build message filter
-    let filter = build_message_filter(&request).await?;
+    let mut filter = build_message_filter(&request).await?;
+    filter.limit = Some(guardrails.limit(request.limit)?);
+    guardrails.check_range(filter.since, filter.before, Utc::now())?;
 
     // Query messages
     let messages = manager
@@ -54,6 +58,154 @@ pub async fn handle_get_messages(
     Ok(GetMessagesResponse { messages, total })
 }
 
+/// Handle resources/read for a topics resource, returning one markdown document
+/// with a section per topic segment
+pub async fn handle_read_topics(
+    resource: &TopicsResource,
+    manager: &SourcesManager,
+    guardrails: &Guardrails,
+) -> Result<String> {
+    let (source_id, chat_pattern) =
+        filter_parser::parse_source_filter(&format!("{}:{}", resource.source, resource.chat))?;
+
+    let filter = MessageFilter {
+        chat: chat_pattern,
+        since: Some(filter_parser::parse_time_spec(&resource.since)?),
+        ..MessageFilter::new()
+    };
+    guardrails.check_range(filter.since, None, Utc::now())?;
+
+    let messages = manager.query_messages(source_id.as_deref(), filter).await?;
+    let segments = crate::topics::TopicSegmenter::new().segment(&messages);
+    let chunks = crate::topics::context_chunks(&messages, &segments);
+
+    Ok(chunks
+        .into_iter()
+        .map(|chunk| chunk.text)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Handle semantic_search tool call: index recent messages of the selected chats,
+/// then return the closest matches to the query
+#[cfg(feature = "embeddings")]
+pub async fn handle_semantic_search(
+    request: SemanticSearchRequest,
+    manager: &SourcesManager,
+    guardrails: &Guardrails,
+) -> Result<SemanticSearchResponse> {
+    use crate::embeddings::{DEFAULT_MODEL, EmbeddingIndex, OllamaEmbedder};
+    use crate::types::ChatPattern;
+
+    let limit = guardrails.check_limit(request.limit.unwrap_or(10))?;
+    let model = request.model.as_deref().unwrap_or(DEFAULT_MODEL);
+    let embedder = OllamaEmbedder::from_env(model)?;
+    let mut index = EmbeddingIndex::load(model)?;
+
+    let source_ids: Vec<String> = match &request.source {
+        Some(id) => vec![id.clone()],
+        None => manager.list_sources()?.into_iter().map(|s| s.id).collect(),
+    };
+
+    let chat_pattern = match &request.chat {
+        Some(chat) => filter_parser::parse_source_filter(chat)?.1,
+        None => ChatPattern::All,
+    };
+
+    let filter = MessageFilter {
+        chat: chat_pattern.clone(),
+        since: Some(filter_parser::parse_time_spec(request.since.as_deref().unwrap_or("90d"))?),
+        ..MessageFilter::new()
+    };
+    guardrails.check_range(filter.since, None, Utc::now())?;
+
+    // Chats seen while indexing are the ones the pattern resolved to
+    let mut chats = std::collections::HashSet::new();
+    let mut added = 0;
+    for source in &source_ids {
+        let messages = manager.query_messages(Some(source), filter.clone()).await?;
+        chats.extend(messages.iter().map(|m| (source.clone(), m.chat_id.clone())));
+        added += index.index_messages(source, &messages, &embedder).await?;
+    }
+
+    if added > 0 {
+        index.save()?;
+    }
+
+    let hits = index
+        .query(&embedder, &request.query, limit, |entry| {
+            match chat_pattern {
+                ChatPattern::All => source_ids.contains(&entry.source),
+                _ => chats.contains(&(entry.source.clone(), entry.chat_id.clone())),
+            }
+        })
+        .await?;
+
+    Ok(SemanticSearchResponse {
+        results: hits.into_iter().map(ScoredMessage::from).collect(),
+    })
+}
+
+#[cfg(not(feature = "embeddings"))]
+pub async fn handle_semantic_search(
+    _request: SemanticSearchRequest,
+    _manager: &SourcesManager,
+    _guardrails: &Guardrails,
+) -> Result<SemanticSearchResponse> {
+    anyhow::bail!("semantic_search requires the server to be built with the embeddings feature")
+}
+
+/// Handle get_context tool call: pack recent, relevant and parent messages under a token budget
+pub async fn handle_get_context(
+    request: GetContextRequest,
+    manager: &SourcesManager,
+    guardrails: &Guardrails,
+) -> Result<crate::context::PackedContext> {
+    use crate::context::{ContextPacker, pack_context};
+
+    let (_, chat_pattern) = filter_parser::parse_source_filter(&request.chat)?;
+    let filter = MessageFilter {
+        chat: chat_pattern,
+        since: Some(filter_parser::parse_time_spec(request.since.as_deref().unwrap_or("7d"))?),
+        ..MessageFilter::new()
+    };
+    guardrails.check_range(filter.since, None, Utc::now())?;
+    let notes = crate::notes::NoteStore::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load notes: {}", e);
+        Default::default()
+    });
+    let packer = ContextPacker::new(request.budget.unwrap_or(DEFAULT_CONTEXT_BUDGET)).with_notes(notes);
+
+    #[cfg(feature = "embeddings")]
+    {
+        if let Some(query) = &request.query {
+            use crate::context::ContextQuery;
+            use crate::embeddings::{DEFAULT_MODEL, EmbeddingIndex, OllamaEmbedder};
+
+            let embedder = OllamaEmbedder::from_env(DEFAULT_MODEL)?;
+            let mut index = EmbeddingIndex::load(DEFAULT_MODEL)?;
+            let context_query = ContextQuery {
+                text: query,
+                embedder: &embedder,
+                index: &mut index,
+            };
+
+            let packed = pack_context(manager, request.source.as_deref(), filter, &packer, Some(context_query)).await?;
+            index.save()?;
+            return Ok(packed);
+        }
+    }
+
+    #[cfg(not(feature = "embeddings"))]
+    {
+        if request.query.is_some() {
+            log::warn!("get_context query ignored: server built without the embeddings feature");
+        }
+    }
+
+    pack_context(manager, request.source.as_deref(), filter, &packer, None).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,10 +226,25 @@ mod tests {
             source: "nonexistent".to_string(),
             name_pattern: None,
             chat_type: None,
+            label: None,
         };
 
         let result = handle_list_chats(request, &manager).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
+
+    #[test]
+    fn test_parse_topics_resource() {
+        let resource = TopicsResource::parse("chat://telegram/-100123/topics?since=7d").unwrap();
+        assert_eq!(resource, TopicsResource {
+            source: "telegram".to_string(),
+            chat: "-100123".to_string(),
+            since: "7d".to_string(),
+        });
+
+        assert_eq!(TopicsResource::parse("chat://telegram/#work/topics").unwrap().since, "30d");
+        assert!(TopicsResource::parse("chat://telegram/-100123").is_err());
+        assert!(TopicsResource::parse("file:///etc/passwd").is_err());
+    }
 }
@@ -0,0 +1,708 @@
+//! Text derived from media attachments: OCR of screenshots, captions from a vision model,
+//! transcripts of voice notes and the text of shared documents. Kept in a local store next to the messages, since
+//! sources don't provide it, and attached to messages as [`Message::media_text`] for
+//! search, exports and summaries.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::global_id::GlobalChatId;
+use crate::types::{ContentType, Message, MessageContent, MessageId};
+
+/// Vision model used for captions when none is configured
+pub const DEFAULT_VISION_MODEL: &str = "llava";
+
+/// File extensions of attachments whose text is extracted: PDF, Word and plain text
+pub const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "docx", "txt", "md", "csv"];
+
+/// Document text beyond this many characters is dropped, keeping the store and search fast
+pub const MAX_DOCUMENT_CHARS: usize = 100_000;
+
+/// How the text was derived, in the order texts are joined on a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivedKind {
+    /// A description of the image
+    Caption,
+    /// Text read from the image
+    Ocr,
+    /// What was said in an audio message
+    Transcript,
+    /// Text of a shared document
+    Document,
+}
+
+impl DerivedKind {
+    /// Content type of the messages this kind of text is derived from
+    pub fn content_type(self) -> ContentType {
+        match self {
+            DerivedKind::Caption | DerivedKind::Ocr => ContentType::Image,
+            DerivedKind::Transcript => ContentType::Audio,
+            DerivedKind::Document => ContentType::File,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DerivedKind::Caption => "caption",
+            DerivedKind::Ocr => "ocr",
+            DerivedKind::Transcript => "transcript",
+            DerivedKind::Document => "document",
+        }
+    }
+}
+
+/// Text derived from one attachment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerivedText {
+    pub kind: DerivedKind,
+    pub text: String,
+    /// What produced it ("tesseract", "ollama:llava", "whisper:ggml-base.en")
+    pub engine: String,
+    pub extracted_at: DateTime<Utc>,
+}
+
+impl DerivedText {
+    pub fn new(kind: DerivedKind, text: impl Into<String>, engine: impl Into<String>) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+            engine: engine.into(),
+            extracted_at: Utc::now(),
+        }
+    }
+}
+
+/// Derived text per message, keyed by `<global chat id>/<message id>`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MediaTextStore {
+    #[serde(default)]
+    messages: BTreeMap<String, Vec<DerivedText>>,
+}
+
+impl MediaTextStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the store (`<data dir>/chat/media_text.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::data_dir()
+            .context("Failed to get data directory")?
+            .join("chat");
+        Ok(dir.join("media_text.json"))
+    }
+
+    /// Load the store from the default location, returning an empty store if none exists
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read media text file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse media text file")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create media text directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize media text")?;
+
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, contents).context("Failed to write media text file")?;
+        std::fs::rename(&tmp, path).context("Failed to write media text file")
+    }
+
+    fn key(source_id: &str, message: &Message) -> String {
+        Self::message_key(&GlobalChatId::from_chat_id(source_id, &message.chat_id), &message.id)
+    }
+
+    fn message_key(chat: &GlobalChatId, message_id: &MessageId) -> String {
+        format!("{}/{}", chat, message_id)
+    }
+
+    /// Store derived text for a message, replacing earlier text of the same kind
+    pub fn insert(&mut self, source_id: &str, message: &Message, derived: DerivedText) {
+        let entries = self.messages.entry(Self::key(source_id, message)).or_default();
+        entries.retain(|entry| entry.kind != derived.kind);
+        entries.push(derived);
+    }
+
+    pub fn get(&self, source_id: &str, message: &Message) -> &[DerivedText] {
+        self.messages
+            .get(&Self::key(source_id, message))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Whether text of this kind was already derived for the message
+    pub fn has(&self, source_id: &str, message: &Message, kind: DerivedKind) -> bool {
+        self.get(source_id, message).iter().any(|entry| entry.kind == kind)
+    }
+
+    /// Remove the derived text of a message. Returns false if it had none.
+    pub fn remove(&mut self, chat: &GlobalChatId, message_id: &MessageId) -> bool {
+        self.messages.remove(&Self::message_key(chat, message_id)).is_some()
+    }
+
+    /// Remove the derived text of every message in a chat, returning how many messages had some
+    pub fn remove_chat(&mut self, chat: &GlobalChatId) -> usize {
+        let prefix = format!("{}/", chat);
+        let before = self.messages.len();
+        self.messages.retain(|key, _| !key.starts_with(&prefix));
+        before - self.messages.len()
+    }
+
+    /// Whether any message of the source has derived text
+    pub fn has_source(&self, source_id: &str) -> bool {
+        let prefix = format!("{}:", source_id);
+        self.messages.keys().any(|key| key.starts_with(&prefix))
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Set [`Message::media_text`] on messages with derived text: captions first, then OCR,
+    /// transcripts and document text
+    pub fn annotate(&self, source_id: &str, messages: &mut [Message]) {
+        if self.is_empty() {
+            return;
+        }
+
+        for message in messages.iter_mut().filter(|m| m.media_text.is_none()) {
+            let mut entries: Vec<&DerivedText> = self.get(source_id, message).iter().collect();
+            if entries.is_empty() {
+                continue;
+            }
+            entries.sort_by_key(|entry| entry.kind);
+            let text = entries.iter().map(|entry| entry.text.trim()).collect::<Vec<_>>().join("\n");
+            message.media_text = Some(text);
+        }
+    }
+}
+
+/// Where an image, audio or file message's file can be found, if the source gave one:
+/// a local path, a `file://` URL or an `http(s)://` URL to fetch
+pub fn media_location(message: &Message) -> Option<&str> {
+    match &message.content {
+        MessageContent::Image { url: Some(url), .. } |
+        MessageContent::Audio { url: Some(url) } |
+        MessageContent::File { url: Some(url), .. } if !url.is_empty() => Some(url),
+        _ => None,
+    }
+}
+
+/// Whether a file attachment is a document whose text can be extracted, judged by the
+/// extension of its file name or URL
+pub fn is_document(message: &Message) -> bool {
+    let MessageContent::File { filename, url } = &message.content else {
+        return false;
+    };
+    [filename.as_deref(), url.as_deref()].into_iter().flatten().any(|name| {
+        let name = name.split(['?', '#']).next().unwrap_or(name);
+        name.rsplit_once('.')
+            .is_some_and(|(_, ext)| DOCUMENT_EXTENSIONS.iter().any(|known| known.eq_ignore_ascii_case(ext)))
+    })
+}
+
+/// Local path of a media location, or None when it has to be downloaded
+pub fn local_path(location: &str) -> Option<PathBuf> {
+    if let Some(path) = location.strip_prefix("file://") {
+        Some(PathBuf::from(path))
+    } else if location.contains("://") {
+        None
+    } else {
+        Some(PathBuf::from(location))
+    }
+}
+
+/// Derives text from media files
+#[async_trait::async_trait]
+pub trait MediaProcessor: Send + Sync {
+    fn kind(&self) -> DerivedKind;
+
+    /// Whether the processor handles the message's attachment
+    fn accepts(&self, message: &Message) -> bool {
+        self.kind().content_type().matches(&message.content)
+    }
+
+    /// Text derived from the file, or None when there is nothing to report
+    async fn process(&self, file: &Path) -> Result<Option<DerivedText>>;
+}
+
+/// OCR through the `tesseract` command line tool
+#[cfg(feature = "ocr")]
+pub struct TesseractOcr {
+    /// Tesseract language codes ("eng+fin"); tesseract's default when None
+    pub languages: Option<String>,
+}
+
+#[cfg(feature = "ocr")]
+#[async_trait::async_trait]
+impl MediaProcessor for TesseractOcr {
+    fn kind(&self) -> DerivedKind {
+        DerivedKind::Ocr
+    }
+
+    async fn process(&self, image: &Path) -> Result<Option<DerivedText>> {
+        let mut command = tokio::process::Command::new("tesseract");
+        command.arg(image).arg("stdout");
+        if let Some(languages) = &self.languages {
+            command.args(["-l", languages]);
+        }
+
+        let output = command.output().await.context("Failed to run tesseract; is it installed?")?;
+        if !output.status.success() {
+            anyhow::bail!("tesseract failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let text = normalize_ocr(&String::from_utf8_lossy(&output.stdout));
+        Ok((!text.is_empty()).then(|| DerivedText::new(DerivedKind::Ocr, text, "tesseract")))
+    }
+}
+
+/// Captions from a local vision model served by Ollama
+#[cfg(feature = "vision")]
+pub struct OllamaCaptioner {
+    client: reqwest::Client,
+    host: String,
+    model: String,
+}
+
+#[cfg(feature = "vision")]
+impl OllamaCaptioner {
+    pub fn new(host: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            host: host.into(),
+            model: model.into(),
+        }
+    }
+
+    /// Host from `OLLAMA_HOST`, falling back to the local default
+    pub fn from_env(model: impl Into<String>) -> Self {
+        let host = std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string());
+        Self::new(host, model)
+    }
+}
+
+#[cfg(feature = "vision")]
+#[async_trait::async_trait]
+impl MediaProcessor for OllamaCaptioner {
+    fn kind(&self) -> DerivedKind {
+        DerivedKind::Caption
+    }
+
+    async fn process(&self, image: &Path) -> Result<Option<DerivedText>> {
+        use base64::Engine;
+
+        let bytes = tokio::fs::read(image)
+            .await
+            .with_context(|| format!("Failed to read {}", image.display()))?;
+        let prompt = "Describe this image in one or two sentences. \
+                      If it is a screenshot, say what app or content it shows.";
+
+        let response: serde_json::Value = self
+            .client
+            .post(format!("{}/api/generate", self.host))
+            .json(&serde_json::json!({
+                "model": self.model,
+                "prompt": prompt,
+                "images": [base64::engine::general_purpose::STANDARD.encode(bytes)],
+                "stream": false,
+            }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach Ollama at {}", self.host))?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let caption = response["response"].as_str().unwrap_or_default().trim();
+        Ok((!caption.is_empty()).then(|| {
+            DerivedText::new(DerivedKind::Caption, caption, format!("ollama:{}", self.model))
+        }))
+    }
+}
+
+/// Transcripts from a local Whisper model through the whisper.cpp command line tool.
+/// Audio is converted to 16 kHz mono WAV with `ffmpeg` first, since voice notes are
+/// usually Opus or AAC.
+#[cfg(feature = "whisper")]
+pub struct WhisperTranscriber {
+    /// Path of the ggml model file
+    pub model: PathBuf,
+    /// Spoken language ("en", "fi"); detected by Whisper when None
+    pub language: Option<String>,
+    /// whisper.cpp binary, `whisper-cli` by default
+    pub binary: String,
+}
+
+#[cfg(feature = "whisper")]
+impl WhisperTranscriber {
+    pub fn new(model: impl Into<PathBuf>) -> Self {
+        Self {
+            model: model.into(),
+            language: None,
+            binary: "whisper-cli".to_string(),
+        }
+    }
+
+    fn engine(&self) -> String {
+        let model = self.model.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+        format!("whisper:{}", model)
+    }
+}
+
+#[cfg(feature = "whisper")]
+#[async_trait::async_trait]
+impl MediaProcessor for WhisperTranscriber {
+    fn kind(&self) -> DerivedKind {
+        DerivedKind::Transcript
+    }
+
+    async fn process(&self, audio: &Path) -> Result<Option<DerivedText>> {
+        let wav = std::env::temp_dir().join(format!("chat-audio-{}.wav", uuid::Uuid::new_v4().simple()));
+        let converted = tokio::process::Command::new("ffmpeg")
+            .args(["-loglevel", "error", "-y", "-i"])
+            .arg(audio)
+            .args(["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"])
+            .arg(&wav)
+            .output()
+            .await
+            .context("Failed to run ffmpeg; is it installed?")?;
+        if !converted.status.success() {
+            std::fs::remove_file(&wav).ok();
+            anyhow::bail!("ffmpeg failed: {}", String::from_utf8_lossy(&converted.stderr).trim());
+        }
+
+        let mut command = tokio::process::Command::new(&self.binary);
+        command.arg("-m").arg(&self.model).arg("-f").arg(&wav).args(["--no-timestamps", "--no-prints"]);
+        if let Some(language) = &self.language {
+            command.args(["-l", language]);
+        }
+        let output = command.output().await;
+        std::fs::remove_file(&wav).ok();
+
+        let output = output.with_context(|| format!("Failed to run {}; is whisper.cpp installed?", self.binary))?;
+        if !output.status.success() {
+            anyhow::bail!("{} failed: {}", self.binary, String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let text = normalize_transcript(&String::from_utf8_lossy(&output.stdout));
+        Ok((!text.is_empty()).then(|| DerivedText::new(DerivedKind::Transcript, text, self.engine())))
+    }
+}
+
+/// Text of PDF, DOCX and plain text attachments. The format is told from the file's
+/// contents, since downloaded attachments have no file name.
+#[cfg(feature = "documents")]
+pub struct DocumentExtractor;
+
+#[cfg(feature = "documents")]
+#[async_trait::async_trait]
+impl MediaProcessor for DocumentExtractor {
+    fn kind(&self) -> DerivedKind {
+        DerivedKind::Document
+    }
+
+    fn accepts(&self, message: &Message) -> bool {
+        is_document(message)
+    }
+
+    async fn process(&self, file: &Path) -> Result<Option<DerivedText>> {
+        let bytes = tokio::fs::read(file)
+            .await
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+
+        let (raw, engine) = tokio::task::spawn_blocking(move || -> Result<(String, &'static str)> {
+            match DocumentFormat::sniff(&bytes) {
+                Some(DocumentFormat::Pdf) => Ok((pdf_extract::extract_text_from_mem(&bytes)?, "pdf-extract")),
+                Some(DocumentFormat::Docx) => {
+                    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+                    let mut xml = String::new();
+                    std::io::Read::read_to_string(
+                        &mut archive.by_name("word/document.xml").context("Not a DOCX file")?,
+                        &mut xml,
+                    )?;
+                    Ok((docx_text(&xml), "docx"))
+                }
+                Some(DocumentFormat::Text) => Ok((String::from_utf8(bytes)?, "text")),
+                None => anyhow::bail!("Unsupported document format"),
+            }
+        })
+        .await??;
+
+        let text = normalize_document(&raw);
+        Ok((!text.is_empty()).then(|| DerivedText::new(DerivedKind::Document, text, engine)))
+    }
+}
+
+/// Document formats text can be extracted from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Pdf,
+    /// Word document: a ZIP archive with `word/document.xml`
+    Docx,
+    /// UTF-8 text without NUL bytes
+    Text,
+}
+
+impl DocumentFormat {
+    /// Tell the format from the start of the file
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"%PDF-") {
+            Some(Self::Pdf)
+        } else if bytes.starts_with(b"PK\x03\x04") {
+            Some(Self::Docx)
+        } else if !bytes.contains(&0) && std::str::from_utf8(bytes).is_ok() {
+            Some(Self::Text)
+        } else {
+            None
+        }
+    }
+}
+
+/// Plain text of a DOCX `word/document.xml`: one line per paragraph, tabs and breaks kept
+pub fn docx_text(xml: &str) -> String {
+    let mut text = String::with_capacity(xml.len() / 4);
+    // Only the content of <w:t> runs is text; whitespace between elements is layout
+    let mut in_run = false;
+    let mut rest = xml;
+    while let Some(open) = rest.find('<') {
+        if in_run {
+            text.push_str(&unescape_xml(&rest[..open]));
+        }
+        let Some(close) = rest[open..].find('>') else {
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        let name = tag.trim_start_matches('/').split([' ', '/']).next().unwrap_or_default();
+        match name {
+            "w:t" => in_run = !tag.starts_with('/') && !tag.ends_with('/'),
+            "w:p" if tag.starts_with('/') => text.push('\n'),
+            "w:br" | "w:cr" => text.push('\n'),
+            "w:tab" => text.push('\t'),
+            _ => {}
+        }
+        rest = &rest[open + close + 1..];
+    }
+    text
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Tidy extracted document text: trim lines, collapse runs of blank lines and cut it
+/// to [`MAX_DOCUMENT_CHARS`]
+pub fn normalize_document(raw: &str) -> String {
+    let mut text = String::with_capacity(raw.len().min(MAX_DOCUMENT_CHARS));
+    let mut blank = false;
+    for line in raw.lines().map(|line| line.split_whitespace().collect::<Vec<_>>().join(" ")) {
+        if line.is_empty() {
+            blank = !text.is_empty();
+            continue;
+        }
+        if blank {
+            text.push('\n');
+            blank = false;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&line);
+    }
+    match text.char_indices().nth(MAX_DOCUMENT_CHARS) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text,
+    }
+}
+
+/// Collapse OCR output: trim lines, drop empty ones and stray single characters
+pub fn normalize_ocr(raw: &str) -> String {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| line.chars().filter(|c| c.is_alphanumeric()).count() > 1)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Join Whisper's output into one paragraph, dropping markers for silence and noise
+/// such as "[BLANK_AUDIO]" or "(music)"
+pub fn normalize_transcript(raw: &str) -> String {
+    let mut spoken = String::with_capacity(raw.len());
+    let mut depth = 0usize;
+    for c in raw.chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => spoken.push(c),
+            _ => {}
+        }
+    }
+    spoken.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    fn image(id: &str, url: Option<&str>) -> Message {
+        MessageBuilder::new(id, "100")
+            .content(MessageContent::Image { caption: None, url: url.map(str::to_string) })
+            .build()
+    }
+
+    fn voice_note(id: &str, url: Option<&str>) -> Message {
+        MessageBuilder::new(id, "100")
+            .content(MessageContent::Audio { url: url.map(str::to_string) })
+            .build()
+    }
+
+    #[test]
+    fn test_store_and_annotate() {
+        let mut store = MediaTextStore::new();
+        let screenshot = image("1", None);
+        store.insert("telegram", &screenshot, DerivedText::new(DerivedKind::Ocr, "Invoice 42 due Friday", "tesseract"));
+        store.insert("telegram", &screenshot, DerivedText::new(DerivedKind::Caption, "A screenshot of an invoice", "ollama:llava"));
+        // Re-running a processor replaces its earlier text
+        store.insert("telegram", &screenshot, DerivedText::new(DerivedKind::Ocr, "Invoice 42 due Friday!", "tesseract"));
+
+        assert!(store.has("telegram", &screenshot, DerivedKind::Ocr));
+        assert!(store.has_source("telegram"));
+        assert!(!store.has_source("whatsapp"));
+        assert_eq!(store.get("telegram", &screenshot).len(), 2);
+
+        let mut messages = vec![screenshot.clone(), image("2", None)];
+        store.annotate("telegram", &mut messages);
+        assert_eq!(
+            messages[0].media_text.as_deref(),
+            Some("A screenshot of an invoice\nInvoice 42 due Friday!")
+        );
+        assert_eq!(messages[1].media_text, None);
+
+        // Same message ID in another source is a different message
+        let mut other = vec![screenshot];
+        store.annotate("whatsapp", &mut other);
+        assert_eq!(other[0].media_text, None);
+    }
+
+    #[test]
+    fn test_transcripts() {
+        let mut store = MediaTextStore::new();
+        let note = voice_note("3", None);
+        store.insert("telegram", &note, DerivedText::new(DerivedKind::Transcript, "Running late, start without me", "whisper:ggml-base"));
+
+        let mut messages = vec![note];
+        store.annotate("telegram", &mut messages);
+        assert_eq!(messages[0].media_text.as_deref(), Some("Running late, start without me"));
+
+        assert_eq!(DerivedKind::Transcript.content_type(), ContentType::Audio);
+        assert!(DerivedKind::Transcript.content_type().matches(&messages[0].content));
+        assert!(!DerivedKind::Ocr.content_type().matches(&messages[0].content));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut store = MediaTextStore::new();
+        let screenshot = image("1", None);
+        store.insert("telegram", &screenshot, DerivedText::new(DerivedKind::Ocr, "Hello", "tesseract"));
+
+        let path = std::env::temp_dir().join(format!("chat-media-text-{}.json", uuid::Uuid::new_v4().simple()));
+        store.save_to(&path).unwrap();
+        let loaded = MediaTextStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.get("telegram", &screenshot), store.get("telegram", &screenshot));
+    }
+
+    #[test]
+    fn test_locations() {
+        assert_eq!(media_location(&image("1", None)), None);
+        assert_eq!(media_location(&image("1", Some("/tmp/a.png"))), Some("/tmp/a.png"));
+        assert_eq!(media_location(&voice_note("2", Some("/tmp/a.ogg"))), Some("/tmp/a.ogg"));
+        assert_eq!(media_location(&MessageBuilder::new("3", "100").text("/tmp/a.png").build()), None);
+
+        assert_eq!(local_path("/tmp/a.png"), Some(PathBuf::from("/tmp/a.png")));
+        assert_eq!(local_path("file:///tmp/a.png"), Some(PathBuf::from("/tmp/a.png")));
+        assert_eq!(local_path("https://example.com/a.png"), None);
+        assert_eq!(local_path("mxc://matrix.org/abc"), None);
+    }
+
+    fn file(id: &str, filename: Option<&str>, url: Option<&str>) -> Message {
+        MessageBuilder::new(id, "100")
+            .content(MessageContent::File { filename: filename.map(str::to_string), url: url.map(str::to_string) })
+            .build()
+    }
+
+    #[test]
+    fn test_documents() {
+        assert!(is_document(&file("1", Some("Q3 Report.PDF"), None)));
+        assert!(is_document(&file("2", None, Some("https://example.com/notes.docx?dl=1"))));
+        assert!(!is_document(&file("3", Some("photo.heic"), None)));
+        assert!(!is_document(&image("4", Some("/tmp/a.pdf"))));
+        assert_eq!(media_location(&file("5", Some("a.txt"), Some("/tmp/a.txt"))), Some("/tmp/a.txt"));
+        assert_eq!(DerivedKind::Document.content_type(), ContentType::File);
+
+        assert_eq!(DocumentFormat::sniff(b"%PDF-1.7\n..."), Some(DocumentFormat::Pdf));
+        assert_eq!(DocumentFormat::sniff(b"PK\x03\x04rest"), Some(DocumentFormat::Docx));
+        assert_eq!(DocumentFormat::sniff("Agenda: päivitys".as_bytes()), Some(DocumentFormat::Text));
+        assert_eq!(DocumentFormat::sniff(b"\x89PNG\r\n\x1a\n\0\0"), None);
+    }
+
+    #[test]
+    fn test_docx_text() {
+        let xml = r#"<?xml version="1.0"?><w:document><w:body>
+            <w:p><w:r><w:t>Budget</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve">Q3 &amp; Q4</w:t></w:r></w:p>
+            <w:p><w:r><w:t>Owner:</w:t><w:br/><w:t>Ana</w:t></w:r></w:p></w:body></w:document>"#;
+        assert_eq!(normalize_document(&docx_text(xml)), "Budget Q3 & Q4\nOwner:\nAna");
+    }
+
+    #[test]
+    fn test_normalize_document() {
+        assert_eq!(normalize_document("  Title \n\n\n\nBody  text\n\n"), "Title\n\nBody text");
+        assert_eq!(normalize_document(&"x".repeat(MAX_DOCUMENT_CHARS + 10)).len(), MAX_DOCUMENT_CHARS);
+    }
+
+    #[test]
+    fn test_normalize_ocr() {
+        assert_eq!(normalize_ocr("  Invoice 42 \n\n|\n  due Friday\n\u{c}"), "Invoice 42\ndue Friday");
+    }
+
+    #[test]
+    fn test_normalize_transcript() {
+        assert_eq!(normalize_transcript("[BLANK_AUDIO]\n"), "");
+        assert_eq!(
+            normalize_transcript(" Hi, it's me.\n (upbeat music)\n Call me back [inaudible] tonight.\n"),
+            "Hi, it's me. Call me back tonight."
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::types::{Message, MessageContent};
+
+/// My usernames on each platform, used to detect messages mentioning me
+///
+/// Stored as `{"handles": {"telegram": ["alice"], "whatsapp": ["Alice"]}}`.
+/// Handles are matched case-insensitively as `@handle` in message text.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MyAccounts {
+    #[serde(default)]
+    handles: BTreeMap<String, Vec<String>>,
+}
+
+impl MyAccounts {
+    /// Create an empty account mapping
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the accounts file (`~/.config/chat/accounts.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("accounts.json"))
+    }
+
+    /// Load accounts from the default location, returning an empty mapping if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load accounts from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read accounts file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse accounts file")
+    }
+
+    /// Register one of my handles on a source (leading '@' is optional)
+    pub fn add_handle(&mut self, source_id: &str, handle: &str) {
+        let handle = handle.trim().trim_start_matches('@').to_string();
+        if handle.is_empty() {
+            return;
+        }
+
+        let handles = self.handles.entry(source_id.to_string()).or_default();
+        if !handles.iter().any(|h| h.eq_ignore_ascii_case(&handle)) {
+            handles.push(handle);
+        }
+    }
+
+    /// My handles on a source
+    pub fn handles_for(&self, source_id: &str) -> &[String] {
+        self.handles.get(source_id).map(|h| h.as_slice()).unwrap_or(&[])
+    }
+
+    /// Check if a message from a source mentions one of my handles
+    pub fn is_mentioned(&self, source_id: &str, message: &Message) -> bool {
+        let text = match &message.content {
+            MessageContent::Text(text) => text,
+            MessageContent::Image { caption: Some(caption), .. } |
+            MessageContent::Video { caption: Some(caption), .. } => caption,
+            _ => return false,
+        };
+
+        self.handles_for(source_id)
+            .iter()
+            .any(|handle| text_mentions(text, handle))
+    }
+}
+
+/// Check if text contains `@handle` as a whole word (case-insensitive)
+pub fn text_mentions(text: &str, handle: &str) -> bool {
+    let text = text.to_lowercase();
+    let needle = format!("@{}", handle.trim_start_matches('@').to_lowercase());
+
+    text.match_indices(&needle).any(|(start, _)| {
+        let is_word_char = |c: Option<char>| matches!(c, Some(c) if c.is_alphanumeric() || c == '_');
+        !is_word_char(text[..start].chars().next_back())
+            && !is_word_char(text[start + needle.len()..].chars().next())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_mentions() {
+        assert!(text_mentions("hey @Alice, take a look", "alice"));
+        assert!(text_mentions("@alice", "@alice"));
+        assert!(!text_mentions("hey @alice_bot", "alice"));
+        assert!(!text_mentions("mail me at bob@alice.com", "alice"));
+        assert!(!text_mentions("hey alice", "alice"));
+    }
+
+    #[test]
+    fn test_add_handle_dedupes() {
+        let mut accounts = MyAccounts::new();
+        accounts.add_handle("telegram", "@alice");
+        accounts.add_handle("telegram", "Alice");
+
+        assert_eq!(accounts.handles_for("telegram"), &["alice".to_string()]);
+        assert!(accounts.handles_for("whatsapp").is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+//! Merging per-source results into one newest-first list
+//!
+//! Each source returns its messages newest first and at most the query's limit, so a
+//! query across sources only has to walk the fronts of those lists until the limit is
+//! reached instead of collecting and sorting everything.
+
+use std::iter::Peekable;
+
+use crate::clock;
+use crate::types::Message;
+
+/// Newest-first merge of per-source message streams, each already newest first.
+///
+/// Messages are pulled from a source only when they are next in line, so taking the
+/// first `n` reads at most `n` messages per source. There are only ever a few sources,
+/// so the next message is picked by comparing the front of each rather than through a
+/// heap.
+pub struct NewestFirst<I: Iterator<Item = Message>> {
+    sources: Vec<(String, Peekable<I>)>,
+}
+
+impl<I: Iterator<Item = Message>> NewestFirst<I> {
+    pub fn new(sources: impl IntoIterator<Item = (String, I)>) -> Self {
+        Self {
+            sources: sources.into_iter().map(|(source, messages)| (source, messages.peekable())).collect(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Message>> Iterator for NewestFirst<I> {
+    type Item = (String, Message);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut next: Option<(usize, &str, &Message)> = None;
+        for (index, (source, messages)) in self.sources.iter_mut().enumerate() {
+            let Some(message) = messages.peek() else {
+                continue;
+            };
+            if next.is_none_or(|(_, best_source, best)| {
+                clock::newest_first(source, message, best_source, best).is_lt()
+            }) {
+                next = Some((index, source, message));
+            }
+        }
+
+        let index = next?.0;
+        let (source, messages) = &mut self.sources[index];
+        messages.next().map(|message| (source.clone(), message))
+    }
+}
+
+/// Merge per-source lists, each newest first, into one newest-first list of at most
+/// `limit` messages
+pub fn newest_first(sources: Vec<(String, Vec<Message>)>, limit: Option<usize>) -> Vec<Message> {
+    let merged = NewestFirst::new(sources.into_iter().map(|(source, messages)| (source, messages.into_iter())));
+    merged.take(limit.unwrap_or(usize::MAX)).map(|(_, message)| message).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    fn messages(ids_and_minutes: &[(&str, i64)]) -> Vec<Message> {
+        ids_and_minutes.iter().map(|(id, minute)| MessageBuilder::new(*id, "1").minute(*minute).build()).collect()
+    }
+
+    #[test]
+    fn test_merge() {
+        let sources = vec![
+            ("telegram".to_string(), messages(&[("t3", 9), ("t2", 5), ("t1", 1)])),
+            ("signal".to_string(), messages(&[("s2", 7), ("s1", 5)])),
+            ("empty".to_string(), Vec::new()),
+        ];
+
+        let ids = |merged: Vec<Message>| merged.iter().map(|m| m.id.as_str().to_string()).collect::<Vec<_>>();
+        assert_eq!(ids(newest_first(sources.clone(), None)), ["t3", "s2", "t2", "s1", "t1"]);
+        assert_eq!(ids(newest_first(sources, Some(3))), ["t3", "s2", "t2"]);
+    }
+
+    #[test]
+    fn test_merge_is_lazy() {
+        // Endless sources still give a first page
+        let endless = |source: &str, start: i64, step: i64| {
+            let source = source.to_string();
+            let messages = (0..).map(move |i| MessageBuilder::new(i.to_string(), "1").minute(start - i * step).build());
+            (source, messages)
+        };
+        let merged: Vec<(String, Message)> = NewestFirst::new([endless("a", 100, 2), endless("b", 99, 2)]).take(4).collect();
+        let sources: Vec<&str> = merged.iter().map(|(source, _)| source.as_str()).collect();
+        assert_eq!(sources, ["a", "b", "a", "b"]);
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::global_id::GlobalChatId;
+use crate::types::ChatId;
+
+/// Chats merged into one conversation, such as a group recreated as "Team v2"
+///
+/// Each merge has a name and its chats oldest first, all from one source. Queries for
+/// any of them cover all of them, and their messages carry the chat ID of the newest,
+/// so filters, statistics and summaries see one continuous chat.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergedChats {
+    #[serde(default)]
+    merges: BTreeMap<String, Vec<GlobalChatId>>,
+}
+
+impl MergedChats {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the merges file (`~/.config/chat/merged_chats.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("merged_chats.json"))
+    }
+
+    /// Load merges from the default location, returning an empty store if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load merges from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read merged chats file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse merged chats file")
+    }
+
+    /// Save merges to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save merges to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create merged chats directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize merged chats")?;
+
+        std::fs::write(path, contents).context("Failed to write merged chats file")
+    }
+
+    /// Add chats, oldest first, to a merge, creating it if needed. Returns how many
+    /// were new to it.
+    pub fn add(&mut self, name: &str, chats: &[GlobalChatId]) -> Result<usize> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("A merge needs a name");
+        }
+
+        let existing = self.merges.get(name).map(Vec::as_slice).unwrap_or_default();
+        let source = existing.first().or(chats.first()).map(|chat| chat.source.clone());
+        for chat in chats {
+            if Some(&chat.source) != source.as_ref() {
+                anyhow::bail!("Merged chats must share a source; {} is not from {}", chat, source.unwrap_or_default());
+            }
+            if let Some(other) = self.merge_of(chat).filter(|other| *other != name) {
+                anyhow::bail!("{} is already merged into '{}'", chat, other);
+            }
+        }
+
+        let members = self.merges.entry(name.to_string()).or_default();
+        let mut added = 0;
+        for chat in chats {
+            if !members.contains(chat) {
+                members.push(chat.clone());
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Take a chat out of its merge, dropping merges left with fewer than two chats.
+    /// Returns false if it wasn't merged.
+    pub fn remove_chat(&mut self, chat: &GlobalChatId) -> bool {
+        let Some(name) = self.merge_of(chat).map(str::to_string) else {
+            return false;
+        };
+        let members = self.merges.entry(name.clone()).or_default();
+        members.retain(|member| member != chat);
+        if members.len() < 2 {
+            self.merges.remove(&name);
+        }
+        true
+    }
+
+    /// Remove a whole merge. Returns false if there was none by that name.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.merges.remove(name.trim()).is_some()
+    }
+
+    /// Name of the merge a chat belongs to
+    pub fn merge_of(&self, chat: &GlobalChatId) -> Option<&str> {
+        self.merges
+            .iter()
+            .find(|(_, members)| members.contains(chat))
+            .map(|(name, _)| name.as_str())
+    }
+
+    fn members(&self, source_id: &str, chat_id: &ChatId) -> Option<&[GlobalChatId]> {
+        let chat = GlobalChatId::from_chat_id(source_id, chat_id);
+        self.merges.values().find(|members| members.contains(&chat)).map(Vec::as_slice)
+    }
+
+    /// Every chat merged with these, the chats themselves included, in their order
+    /// followed by the others
+    pub fn expand(&self, source_id: &str, chat_ids: &[ChatId]) -> Vec<ChatId> {
+        let mut expanded: Vec<ChatId> = Vec::new();
+        for chat_id in chat_ids {
+            let members = self.members(source_id, chat_id).into_iter().flatten().map(GlobalChatId::chat_id);
+            for id in std::iter::once(chat_id.clone()).chain(members) {
+                if !expanded.contains(&id) {
+                    expanded.push(id);
+                }
+            }
+        }
+        expanded
+    }
+
+    /// The chat a merged chat's messages are shown under: the newest in its merge
+    pub fn primary(&self, source_id: &str, chat_id: &ChatId) -> Option<ChatId> {
+        self.members(source_id, chat_id)?.last().map(GlobalChatId::chat_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.merges.is_empty()
+    }
+
+    /// All merges as (name, chats oldest first)
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &[GlobalChatId])> {
+        self.merges.iter().map(|(name, members)| (name.as_str(), members.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(input: &str) -> GlobalChatId {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_expand_and_primary() {
+        let mut merged = MergedChats::new();
+        assert_eq!(merged.add("Team", &[id("telegram:-100111"), id("telegram:-100222")]).unwrap(), 2);
+        assert_eq!(merged.add("Team", &[id("telegram:-100222"), id("telegram:-100333")]).unwrap(), 1);
+
+        let old = ChatId::new("-100111");
+        assert_eq!(
+            merged.expand("telegram", &[old.clone(), ChatId::new("42")]),
+            vec![old.clone(), ChatId::new("-100222"), ChatId::new("-100333"), ChatId::new("42")]
+        );
+        assert_eq!(merged.primary("telegram", &old), Some(ChatId::new("-100333")));
+        // Older forms of a chat's ID find its merge too
+        assert_eq!(merged.primary("telegram", &ChatId::new("channel:111")), Some(ChatId::new("-100333")));
+        assert_eq!(merged.primary("whatsapp", &old), None);
+        assert_eq!(merged.merge_of(&id("telegram:-100222")), Some("Team"));
+    }
+
+    #[test]
+    fn test_add_checks_chats() {
+        let mut merged = MergedChats::new();
+        merged.add("Team", &[id("telegram:-100111"), id("telegram:-100222")]).unwrap();
+
+        assert!(merged.add("Team", &[id("whatsapp:123@g.us")]).is_err());
+        assert!(merged.add("Other", &[id("telegram:-100222"), id("telegram:-100444")]).is_err());
+        assert!(merged.add(" ", &[id("telegram:-100444")]).is_err());
+    }
+
+    #[test]
+    fn test_remove_chat_drops_single_chat_merges() {
+        let mut merged = MergedChats::new();
+        merged.add("Team", &[id("telegram:-100111"), id("telegram:-100222")]).unwrap();
+
+        assert!(merged.remove_chat(&id("telegram:-100111")));
+        assert!(!merged.remove_chat(&id("telegram:-100111")));
+        assert!(merged.is_empty());
+    }
+}
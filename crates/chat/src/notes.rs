@@ -0,0 +1,260 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::global_id::{normalize_platform_id, GlobalChatId};
+use crate::types::ChatId;
+
+/// What you know about a chat or a person: free text plus fields like "birthday"
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    /// Display name of the person, so questions that name them find the note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    /// Field names are stored lowercase
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    #[serde(default)]
+    pub updated: Option<DateTime<Utc>>,
+}
+
+impl Note {
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.fields.is_empty()
+    }
+
+    /// Set a field, or remove it when `value` is empty
+    pub fn set_field(&mut self, key: &str, value: &str) {
+        let key = key.trim().to_lowercase();
+        if value.trim().is_empty() {
+            self.fields.remove(&key);
+        } else {
+            self.fields.insert(key, value.trim().to_string());
+        }
+    }
+
+    /// One line for prompts: fields first, then the text
+    pub fn summary(&self) -> String {
+        let mut parts: Vec<String> = self.fields.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+        let text = self.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            parts.push(text);
+        }
+        parts.join("; ")
+    }
+}
+
+/// Local notes on chats and people
+///
+/// Chats are keyed by the canonical `source:chat_id` (see [`GlobalChatId`]), people by
+/// `source:user_id`. The context packer includes the notes of the chats and people in
+/// what it packs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteStore {
+    #[serde(default)]
+    chats: BTreeMap<String, Note>,
+    #[serde(default)]
+    users: BTreeMap<String, Note>,
+}
+
+impl NoteStore {
+    /// Create an empty note store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the notes file (`~/.config/chat/notes.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("notes.json"))
+    }
+
+    /// Load notes from the default location, returning an empty store if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load notes from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read notes file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse notes file")
+    }
+
+    /// Save notes to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save notes to a specific file, leaving out empty ones
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create notes directory")?;
+        }
+
+        let mut store = self.clone();
+        store.chats.retain(|_, note| !note.is_empty());
+        store.users.retain(|_, note| !note.is_empty());
+        let contents = serde_json::to_string_pretty(&store)
+            .context("Failed to serialize notes")?;
+
+        std::fs::write(path, contents).context("Failed to write notes file")
+    }
+
+    fn chat_key(source_id: &str, chat_id: &ChatId) -> String {
+        GlobalChatId::from_chat_id(source_id, chat_id).to_string()
+    }
+
+    fn user_key(source_id: &str, user_id: &str) -> String {
+        format!("{}:{}", source_id.trim().to_lowercase(), user_id.trim())
+    }
+
+    pub fn chat(&self, source_id: &str, chat_id: &ChatId) -> Option<&Note> {
+        self.chats.get(&Self::chat_key(source_id, chat_id))
+    }
+
+    /// A chat's note for editing, created if there is none. Marks it updated.
+    pub fn chat_mut(&mut self, source_id: &str, chat_id: &ChatId) -> &mut Note {
+        let note = self.chats.entry(Self::chat_key(source_id, chat_id)).or_default();
+        note.updated = Some(Utc::now());
+        note
+    }
+
+    pub fn user(&self, source_id: &str, user_id: &str) -> Option<&Note> {
+        self.users.get(&Self::user_key(source_id, user_id))
+    }
+
+    /// A person's note for editing, created if there is none. Marks it updated.
+    pub fn user_mut(&mut self, source_id: &str, user_id: &str) -> &mut Note {
+        let note = self.users.entry(Self::user_key(source_id, user_id)).or_default();
+        note.updated = Some(Utc::now());
+        note
+    }
+
+    /// Remove a chat's note. Returns false if it had none.
+    pub fn remove_chat(&mut self, source_id: &str, chat_id: &ChatId) -> bool {
+        self.chats.remove(&Self::chat_key(source_id, chat_id)).is_some()
+    }
+
+    /// Remove a person's note. Returns false if they had none.
+    pub fn remove_user(&mut self, source_id: &str, user_id: &str) -> bool {
+        self.users.remove(&Self::user_key(source_id, user_id)).is_some()
+    }
+
+    /// Chat notes as (`source:chat_id`, note)
+    pub fn chat_entries(&self) -> impl Iterator<Item = (&str, &Note)> {
+        self.chats.iter().filter(|(_, note)| !note.is_empty()).map(|(key, note)| (key.as_str(), note))
+    }
+
+    /// Person notes as (`source:user_id`, note)
+    pub fn user_entries(&self) -> impl Iterator<Item = (&str, &Note)> {
+        self.users.iter().filter(|(_, note)| !note.is_empty()).map(|(key, note)| (key.as_str(), note))
+    }
+
+    /// The note on a chat from any source, for messages that don't say which source
+    /// they came from
+    pub fn chat_in_any_source(&self, chat_id: &ChatId) -> Option<&Note> {
+        self.chat_entries().find_map(|(key, note)| {
+            let (source, platform_id) = key.split_once(':')?;
+            (platform_id == normalize_platform_id(source, chat_id.as_str())).then_some(note)
+        })
+    }
+
+    /// The note on a person from any source
+    pub fn user_in_any_source(&self, user_id: &str) -> Option<&Note> {
+        self.user_entries()
+            .find_map(|(key, note)| (key.split_once(':')?.1 == user_id).then_some(note))
+    }
+
+    /// Notes on people whose name appears in `text`, e.g. a question to an agent
+    pub fn people_named_in(&self, text: &str) -> Vec<&Note> {
+        let text = text.to_lowercase();
+        self.user_entries()
+            .map(|(_, note)| note)
+            .filter(|note| {
+                note.name
+                    .as_deref()
+                    .map(|name| name.trim().to_lowercase())
+                    .is_some_and(|name| !name.is_empty() && contains_word(&text, &name))
+            })
+            .collect()
+    }
+}
+
+/// Whether `needle` occurs in `haystack` and isn't part of a longer word
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_and_user_notes() {
+        let mut store = NoteStore::new();
+        let chat = ChatId::new("channel:123");
+
+        store.chat_mut("telegram", &chat).text = "Release coordination for Apollo".to_string();
+        store.chat_mut("telegram", &chat).set_field("Project", "apollo");
+        let ana = store.user_mut("telegram", "42");
+        ana.name = Some("Ana".to_string());
+        ana.set_field("birthday", "05-01");
+        store.user_mut("telegram", "43");
+
+        let note = store.chat("telegram", &ChatId::new("-100123")).unwrap();
+        assert_eq!(note.summary(), "project: apollo; Release coordination for Apollo");
+        assert!(store.chat_in_any_source(&ChatId::new("-100123")).is_some());
+        assert_eq!(store.user_in_any_source("42").unwrap().summary(), "birthday: 05-01");
+        assert_eq!(store.user_entries().count(), 1);
+
+        store.chat_mut("telegram", &chat).set_field("project", "");
+        assert!(store.chat("telegram", &chat).unwrap().fields.is_empty());
+        assert!(store.remove_user("telegram", "42"));
+        assert!(!store.remove_user("telegram", "42"));
+    }
+
+    #[test]
+    fn test_people_named_in() {
+        let mut store = NoteStore::new();
+        store.user_mut("telegram", "1").name = Some("Ana".to_string());
+        store.user_mut("telegram", "1").text = "Prefers calls".to_string();
+        store.user_mut("signal", "2").name = Some("Bo".to_string());
+        store.user_mut("signal", "2").text = "Manages the budget".to_string();
+
+        let named: Vec<_> = store.people_named_in("What did Ana say about the release?").iter().map(|n| n.summary()).collect();
+        assert_eq!(named, vec!["Prefers calls"]);
+        // "Bo" inside "both" is not a mention
+        assert!(store.people_named_in("both teams").is_empty());
+    }
+
+    #[test]
+    fn test_save_skips_empty_notes() {
+        let mut store = NoteStore::new();
+        store.chat_mut("telegram", &ChatId::new("1")).text = "Family".to_string();
+        store.chat_mut("telegram", &ChatId::new("2"));
+
+        let path = std::env::temp_dir().join(format!("notes-{}.json", uuid::Uuid::new_v4().simple()));
+        store.save_to(&path).unwrap();
+        let loaded = NoteStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.chat_entries().map(|(key, _)| key).collect::<Vec<_>>(), vec!["telegram:1"]);
+    }
+}
@@ -0,0 +1,202 @@
+//! Messages exported as a Parquet dataset, partitioned Hive-style by source, chat and
+//! day so DuckDB, Polars or pandas load it directly:
+//! `<dir>/source=telegram/chat=-1001234/date=2025-01-15/part-0.parquet`
+
+use anyhow::{Context, Result};
+use arrow::array::{ArrayRef, BooleanArray, StringArray, TimestampMillisecondArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::types::{Chat, Message, MessageContent};
+
+/// Writes each chat's messages into one Parquet file per UTC day.
+///
+/// The partition keys live in the directory names only, as Hive-partitioned readers
+/// expect; every file holds the columns of [`schema`].
+pub struct ParquetExport {
+    dir: PathBuf,
+    schema: SchemaRef,
+    files: usize,
+}
+
+impl ParquetExport {
+    /// Start a dataset in `dir`, which must not exist or be empty
+    pub fn create(dir: &Path) -> Result<Self> {
+        if dir.exists() && std::fs::read_dir(dir)?.next().is_some() {
+            anyhow::bail!("{} is not empty; Parquet datasets are written into a new directory", dir.display());
+        }
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            schema: schema(),
+            files: 0,
+        })
+    }
+
+    /// Write a chat's messages, returning how many were written
+    pub fn add_chat(&mut self, source: &str, chat: &Chat, messages: &[Message]) -> Result<usize> {
+        let mut days: BTreeMap<NaiveDate, Vec<&Message>> = BTreeMap::new();
+        for message in messages {
+            days.entry(message.timestamp.date_naive()).or_default().push(message);
+        }
+
+        for (date, messages) in days {
+            let partition = self
+                .dir
+                .join(format!("source={}", partition_value(source)))
+                .join(format!("chat={}", partition_value(chat.id.as_str())))
+                .join(format!("date={}", date));
+            std::fs::create_dir_all(&partition)?;
+
+            // A chat listed twice adds files rather than replacing the first
+            let part = std::fs::read_dir(&partition)?.count();
+            let path = partition.join(format!("part-{}.parquet", part));
+            self.write_file(&path, chat, &messages)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            self.files += 1;
+        }
+
+        Ok(messages.len())
+    }
+
+    /// Number of files written so far
+    pub fn files(&self) -> usize {
+        self.files
+    }
+
+    fn write_file(&self, path: &Path, chat: &Chat, messages: &[&Message]) -> Result<()> {
+        let strings = |value: fn(&Message) -> Option<String>| -> ArrayRef {
+            Arc::new(messages.iter().map(|m| value(m)).collect::<StringArray>())
+        };
+        let flags = |value: fn(&Message) -> bool| -> ArrayRef {
+            Arc::new(messages.iter().map(|m| Some(value(m))).collect::<BooleanArray>())
+        };
+
+        let columns: Vec<ArrayRef> = vec![
+            strings(|m| Some(m.id.to_string())),
+            Arc::new(
+                TimestampMillisecondArray::from(messages.iter().map(|m| m.timestamp.timestamp_millis()).collect::<Vec<_>>())
+                    .with_timezone("UTC"),
+            ),
+            Arc::new(messages.iter().map(|_| chat.title.clone()).collect::<StringArray>()),
+            strings(|m| Some(m.sender.id.to_string())),
+            strings(|m| m.sender.display_name.clone().or_else(|| m.sender.username.clone())),
+            strings(|m| Some(m.content.kind().to_string())),
+            strings(|m| message_text(&m.content)),
+            strings(|m| media_url(&m.content)),
+            strings(|m| m.reply_to.as_ref().map(|id| id.to_string())),
+            flags(|m| m.edited),
+            flags(|m| m.mentions_me),
+            flags(|m| m.pending),
+            strings(|m| m.language.clone()),
+            strings(|m| m.media_text.clone()),
+            Arc::new(
+                TimestampMillisecondArray::from(
+                    messages.iter().map(|m| m.original_timestamp.map(|t| t.timestamp_millis())).collect::<Vec<_>>(),
+                )
+                .with_timezone("UTC"),
+            ),
+        ];
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+
+        let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let file = std::fs::File::create(path)?;
+        let mut writer = ArrowWriter::try_new(file, self.schema.clone(), Some(properties))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Columns of every file in the dataset
+pub fn schema() -> SchemaRef {
+    let text = |name: &str, nullable: bool| Field::new(name, DataType::Utf8, nullable);
+    let flag = |name: &str| Field::new(name, DataType::Boolean, false);
+
+    Arc::new(Schema::new(vec![
+        text("message_id", false),
+        Field::new("timestamp", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false),
+        text("chat_title", true),
+        text("sender_id", false),
+        text("sender_name", true),
+        text("kind", false),
+        text("text", true),
+        text("url", true),
+        text("reply_to", true),
+        flag("edited"),
+        flag("mentions_me"),
+        flag("pending"),
+        text("language", true),
+        text("media_text", true),
+        Field::new("original_timestamp", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), true),
+    ]))
+}
+
+/// Directory-safe partition value; IDs keep their characters except separators
+fn partition_value(value: &str) -> String {
+    value.replace(['/', '\\', '='], "_")
+}
+
+/// Text written with a message: its text or caption, or what a system event was
+fn message_text(content: &MessageContent) -> Option<String> {
+    match content {
+        MessageContent::Text(text) => Some(text.clone()),
+        MessageContent::Image { caption, .. } | MessageContent::Video { caption, .. } => caption.clone(),
+        MessageContent::Contact { name, .. } => Some(name.clone()),
+        MessageContent::Location { latitude, longitude } => Some(format!("{},{}", latitude, longitude)),
+        MessageContent::System(event) => Some(event.describe()),
+        _ => None,
+    }
+}
+
+fn media_url(content: &MessageContent) -> Option<String> {
+    match content {
+        MessageContent::Image { url, .. }
+        | MessageContent::Video { url, .. }
+        | MessageContent::Audio { url }
+        | MessageContent::File { url, .. } => url.clone(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ChatBuilder, MessageBuilder};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[test]
+    fn test_partitioned_export() {
+        let dir = std::env::temp_dir().join(format!("chat-parquet-{}", uuid::Uuid::new_v4().simple()));
+        let mut export = ParquetExport::create(&dir).unwrap();
+
+        let chat = ChatBuilder::new("-100/1").title("Work").build();
+        let messages = vec![
+            MessageBuilder::new("1", "-100/1").from("alice").minute(0).text("hello").build(),
+            MessageBuilder::new("2", "-100/1").from("bob").minute(1).text("hi").build(),
+            MessageBuilder::new("3", "-100/1").from("alice").minute(60 * 24).text("next day").build(),
+        ];
+        assert_eq!(export.add_chat("telegram", &chat, &messages).unwrap(), 3);
+        assert_eq!(export.files(), 2);
+
+        let file = dir.join("source=telegram/chat=-100_1/date=2025-01-15/part-0.parquet");
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&file).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        assert_eq!(rows, 2);
+        assert!(dir.join("source=telegram/chat=-100_1/date=2025-01-16/part-0.parquet").exists());
+
+        assert!(ParquetExport::create(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,731 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::filter_parser;
+use crate::labels::LabelStore;
+use crate::stream_event::StreamEvent;
+use crate::types::{Chat, ChatPattern, ChatType, Message, MessageContent};
+
+/// Replacement text for redacted matches when none is configured
+pub const DEFAULT_REDACTION: &str = "[redacted]";
+
+/// Ollama model used by `translate` stages when none is configured
+pub const DEFAULT_TRANSLATE_MODEL: &str = "llama3.2";
+
+/// Pipeline file as written by the user (`~/.config/chat/pipelines.yaml`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineFile {
+    #[serde(default)]
+    pub pipelines: Vec<PipelineConfig>,
+}
+
+/// One named chain of stages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfig {
+    pub name: String,
+    /// Sources this pipeline receives messages from (default: all)
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Written as `- filter: {...}` rather than serde_yaml's `- !filter {...}`
+    #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+    pub stages: Vec<StageConfig>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A pipeline stage. Stages run in order; a failed filter stops the message,
+/// transforms change it for the stages after them, and routes deliver it as it is at that point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageConfig {
+    Filter(FilterStage),
+    Redact(RedactStage),
+    Translate(TranslateStage),
+    Notify(NotifyStage),
+    Webhook(WebhookStage),
+    Tts(TtsStage),
+    Store(StoreStage),
+}
+
+impl StageConfig {
+    fn kind(&self) -> &'static str {
+        match self {
+            StageConfig::Filter(_) => "filter",
+            StageConfig::Redact(_) => "redact",
+            StageConfig::Translate(_) => "translate",
+            StageConfig::Notify(_) => "notify",
+            StageConfig::Webhook(_) => "webhook",
+            StageConfig::Tts(_) => "tts",
+            StageConfig::Store(_) => "store",
+        }
+    }
+}
+
+/// Keep only messages matching every given criterion
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FilterStage {
+    /// Chat filter in `source:pattern` form (e.g. "telegram:#work", "*:Antti")
+    pub chat: Option<String>,
+    /// Sender name or username (partial match, case-insensitive)
+    pub sender: Option<String>,
+    /// Text the message must contain (case-insensitive)
+    pub contains: Option<String>,
+    /// Regular expression the message text must match
+    pub matches: Option<String>,
+    /// Only messages that mention me (true) or don't (false)
+    pub mentions_me: Option<bool>,
+}
+
+/// Replace regular expression matches in the message text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedactStage {
+    pub patterns: Vec<String>,
+    #[serde(default = "default_redaction")]
+    pub replacement: String,
+}
+
+fn default_redaction() -> String {
+    DEFAULT_REDACTION.to_string()
+}
+
+/// Translate the message text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TranslateStage {
+    /// Target language (e.g. "en", "Finnish")
+    pub to: String,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// Show the message as a notification
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyStage {
+    /// Notify even during do-not-disturb
+    #[serde(default)]
+    pub critical: bool,
+}
+
+/// POST the message as JSON to a URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookStage {
+    pub url: String,
+}
+
+/// Read the message aloud
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TtsStage {
+    #[serde(default)]
+    pub voice: Option<String>,
+}
+
+/// Append the message to a JSONL file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StoreStage {
+    pub path: PathBuf,
+}
+
+/// Where a route stage delivers a message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "route", rename_all = "snake_case")]
+pub enum Route {
+    Notify { critical: bool },
+    Webhook { url: String },
+    Tts { voice: Option<String> },
+    Store { path: PathBuf },
+}
+
+/// A message delivered by a route stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dispatch {
+    pub pipeline: String,
+    pub source: String,
+    #[serde(flatten)]
+    pub route: Route,
+    /// Chat key for per-chat settings (`source:chat_id`)
+    pub chat: String,
+    pub chat_title: Option<String>,
+    /// One-line rendering of the message for notifications and speech
+    pub text: String,
+    pub message: Message,
+}
+
+/// A received message with the context pipelines can filter on
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    pub source: String,
+    /// The chat, if the runner could resolve it
+    pub chat: Option<Chat>,
+    /// Local labels of the chat
+    pub labels: Vec<String>,
+    pub message: Message,
+}
+
+/// Performs the side effects pipelines ask for
+#[async_trait::async_trait]
+pub trait PipelineHost: Send + Sync {
+    /// Translate text into a target language
+    async fn translate(&self, text: &str, to: &str, model: &str) -> Result<String>;
+
+    /// Deliver a message to a route
+    async fn dispatch(&self, dispatch: Dispatch) -> Result<()>;
+}
+
+/// Outcome of running one message through all pipelines
+#[derive(Debug, Default)]
+pub struct ProcessReport {
+    /// Pipelines the message passed all filters of
+    pub matched: Vec<String>,
+    pub dispatched: usize,
+    /// Failures as `(pipeline, error)`; a failure stops only its own pipeline
+    pub errors: Vec<(String, String)>,
+}
+
+/// Validated, ready-to-run pipelines
+#[derive(Debug, Clone, Default)]
+pub struct Pipelines {
+    pipelines: Vec<Pipeline>,
+}
+
+#[derive(Debug, Clone)]
+struct Pipeline {
+    name: String,
+    sources: Vec<String>,
+    stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone)]
+enum Stage {
+    Filter(Filter),
+    Redact { patterns: Vec<Regex>, replacement: String },
+    Translate { to: String, model: String },
+    Route(Route),
+}
+
+#[derive(Debug, Clone)]
+struct Filter {
+    chat: Option<(Option<String>, ChatPattern)>,
+    sender: Option<String>,
+    contains: Option<String>,
+    matches: Option<Regex>,
+    mentions_me: Option<bool>,
+}
+
+impl Pipelines {
+    /// Default pipeline file path (`~/.config/chat/pipelines.yaml`)
+    pub fn default_path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Failed to get config directory")?;
+        Ok(config_dir.join("chat").join("pipelines.yaml"))
+    }
+
+    /// Load from the default path; no file means no pipelines
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load and validate a pipeline file; a missing file means no pipelines
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pipeline file: {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid pipeline file: {}", path.display()))
+    }
+
+    /// Parse and validate pipeline YAML
+    pub fn parse(yaml: &str) -> Result<Self> {
+        let file: PipelineFile = serde_yaml::from_str(yaml)?;
+        Self::from_config(&file)
+    }
+
+    /// Validate a pipeline file, reporting every problem at once
+    pub fn from_config(file: &PipelineFile) -> Result<Self> {
+        let mut errors = Vec::new();
+        let mut names = HashSet::new();
+        let mut pipelines = Vec::new();
+
+        for config in &file.pipelines {
+            let name = config.name.trim();
+            if name.is_empty() {
+                errors.push("pipeline without a name".to_string());
+                continue;
+            }
+            if !names.insert(name.to_string()) {
+                errors.push(format!("pipeline '{}' is defined more than once", name));
+            }
+            if config.stages.is_empty() {
+                errors.push(format!("pipeline '{}' has no stages", name));
+            } else if !config.stages.iter().any(|stage| {
+                matches!(
+                    stage,
+                    StageConfig::Notify(_) | StageConfig::Webhook(_) | StageConfig::Tts(_) | StageConfig::Store(_)
+                )
+            }) {
+                errors.push(format!(
+                    "pipeline '{}' never routes messages anywhere (add a notify, webhook, tts or store stage)",
+                    name
+                ));
+            }
+
+            let mut stages = Vec::new();
+            for (index, stage) in config.stages.iter().enumerate() {
+                match compile_stage(stage) {
+                    Ok(stage) => stages.push(stage),
+                    Err(e) => errors.push(format!("pipeline '{}' stage {} ({}): {}", name, index + 1, stage.kind(), e)),
+                }
+            }
+
+            if config.enabled {
+                pipelines.push(Pipeline {
+                    name: name.to_string(),
+                    sources: config.sources.clone(),
+                    stages,
+                });
+            }
+        }
+
+        if !errors.is_empty() {
+            anyhow::bail!("{}", errors.join("\n"));
+        }
+
+        Ok(Self { pipelines })
+    }
+
+    /// Names of the enabled pipelines
+    pub fn names(&self) -> Vec<&str> {
+        self.pipelines.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// Sources any pipeline listens to; None means all sources
+    pub fn sources(&self) -> Option<HashSet<String>> {
+        let mut sources = HashSet::new();
+        for pipeline in &self.pipelines {
+            if pipeline.sources.is_empty() {
+                return None;
+            }
+            sources.extend(pipeline.sources.iter().cloned());
+        }
+        Some(sources)
+    }
+
+    /// Run a message through every pipeline that listens to its source
+    pub async fn process(&self, envelope: &Envelope, host: &dyn PipelineHost) -> ProcessReport {
+        let mut report = ProcessReport::default();
+
+        for pipeline in &self.pipelines {
+            if !pipeline.sources.is_empty() && !pipeline.sources.contains(&envelope.source) {
+                continue;
+            }
+
+            match pipeline.run(envelope, host).await {
+                Ok(None) => {}
+                Ok(Some(dispatched)) => {
+                    report.matched.push(pipeline.name.clone());
+                    report.dispatched += dispatched;
+                }
+                Err(e) => report.errors.push((pipeline.name.clone(), format!("{:#}", e))),
+            }
+        }
+
+        report
+    }
+}
+
+impl Pipeline {
+    /// Number of dispatches, or None if a filter dropped the message
+    async fn run(&self, envelope: &Envelope, host: &dyn PipelineHost) -> Result<Option<usize>> {
+        let mut message = envelope.message.clone();
+        let mut dispatched = 0;
+
+        for stage in &self.stages {
+            match stage {
+                Stage::Filter(filter) => {
+                    if !filter.matches(envelope, &message) {
+                        return Ok(None);
+                    }
+                }
+                Stage::Redact { patterns, replacement } => {
+                    if let Some(text) = text_mut(&mut message.content) {
+                        for pattern in patterns {
+                            *text = pattern.replace_all(text, replacement.as_str()).into_owned();
+                        }
+                    }
+                }
+                Stage::Translate { to, model } => {
+                    if let Some(text) = text_mut(&mut message.content) {
+                        if !text.trim().is_empty() {
+                            *text = host
+                                .translate(text, to, model)
+                                .await
+                                .with_context(|| format!("Failed to translate to {}", to))?;
+                        }
+                    }
+                }
+                Stage::Route(route) => {
+                    host.dispatch(Dispatch {
+                        pipeline: self.name.clone(),
+                        source: envelope.source.clone(),
+                        route: route.clone(),
+                        chat: format!("{}:{}", envelope.source, message.chat_id.as_str()),
+                        chat_title: envelope.chat.as_ref().and_then(|c| c.title.clone()),
+                        text: summary(envelope, &message),
+                        message: message.clone(),
+                    })
+                    .await?;
+                    dispatched += 1;
+                }
+            }
+        }
+
+        Ok(Some(dispatched))
+    }
+}
+
+impl Filter {
+    fn matches(&self, envelope: &Envelope, message: &Message) -> bool {
+        if let Some((source, pattern)) = &self.chat {
+            if source.as_ref().is_some_and(|s| s != &envelope.source) {
+                return false;
+            }
+            let chat_match = match (pattern, &envelope.chat) {
+                (ChatPattern::Label(label), _) => envelope.labels.contains(&LabelStore::normalize(label)),
+                (pattern, Some(chat)) => pattern.matches(chat),
+                // Without the chat only its ID is known
+                (pattern, None) => pattern.matches(&Chat {
+                    id: message.chat_id.clone(),
+                    title: None,
+                    chat_type: ChatType::Unknown,
+                    participant_count: None,
+                    topic: None,
+                }),
+            };
+            if !chat_match {
+                return false;
+            }
+        }
+
+        if let Some(sender) = &self.sender {
+            let sender = sender.to_lowercase();
+            let names = [&message.sender.display_name, &message.sender.username];
+            if !names.iter().any(|name| name.as_ref().is_some_and(|n| n.to_lowercase().contains(&sender))) {
+                return false;
+            }
+        }
+
+        let text = message_text(message).unwrap_or_default();
+        if let Some(needle) = &self.contains {
+            if !text.to_lowercase().contains(&needle.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.matches {
+            if !regex.is_match(text) {
+                return false;
+            }
+        }
+
+        if let Some(mentions_me) = self.mentions_me {
+            if message.mentions_me != mentions_me {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn compile_stage(stage: &StageConfig) -> Result<Stage> {
+    Ok(match stage {
+        StageConfig::Filter(filter) => {
+            if filter.chat.is_none()
+                && filter.sender.is_none()
+                && filter.contains.is_none()
+                && filter.matches.is_none()
+                && filter.mentions_me.is_none()
+            {
+                anyhow::bail!("filter has no criteria");
+            }
+
+            Stage::Filter(Filter {
+                chat: filter.chat.as_deref().map(filter_parser::parse_source_filter).transpose()?,
+                sender: filter.sender.clone(),
+                contains: filter.contains.clone(),
+                matches: filter
+                    .matches
+                    .as_deref()
+                    .map(|pattern| Regex::new(pattern).with_context(|| format!("invalid pattern '{}'", pattern)))
+                    .transpose()?,
+                mentions_me: filter.mentions_me,
+            })
+        }
+        StageConfig::Redact(redact) => {
+            if redact.patterns.is_empty() {
+                anyhow::bail!("no patterns to redact");
+            }
+            let patterns = redact
+                .patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern).with_context(|| format!("invalid pattern '{}'", pattern)))
+                .collect::<Result<Vec<_>>>()?;
+
+            Stage::Redact {
+                patterns,
+                replacement: redact.replacement.clone(),
+            }
+        }
+        StageConfig::Translate(translate) => {
+            if translate.to.trim().is_empty() {
+                anyhow::bail!("no target language");
+            }
+            Stage::Translate {
+                to: translate.to.clone(),
+                model: translate.model.clone().unwrap_or_else(|| DEFAULT_TRANSLATE_MODEL.to_string()),
+            }
+        }
+        StageConfig::Notify(notify) => Stage::Route(Route::Notify { critical: notify.critical }),
+        StageConfig::Webhook(webhook) => {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                anyhow::bail!("url must start with http:// or https://");
+            }
+            Stage::Route(Route::Webhook { url: webhook.url.clone() })
+        }
+        StageConfig::Tts(tts) => Stage::Route(Route::Tts { voice: tts.voice.clone() }),
+        StageConfig::Store(store) => {
+            if store.path.as_os_str().is_empty() {
+                anyhow::bail!("no path");
+            }
+            Stage::Route(Route::Store { path: store.path.clone() })
+        }
+    })
+}
+
+/// Append a dispatched message to a JSONL file as a [`StreamEvent`], expanding a leading `~`
+pub fn append_to_store(path: &Path, dispatch: &Dispatch) -> Result<()> {
+    let path = match path.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().context("Failed to get home directory")?.join(rest),
+        Err(_) => path.to_path_buf(),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", StreamEvent::dispatch(dispatch.clone()).to_line()?)?;
+
+    Ok(())
+}
+
+/// "Sender in Chat: text" for notifications and speech
+fn summary(envelope: &Envelope, message: &Message) -> String {
+    let sender = message
+        .sender
+        .display_name
+        .as_deref()
+        .or(message.sender.username.as_deref())
+        .unwrap_or("Unknown");
+    let body = match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Image { caption, .. } => caption.clone().unwrap_or_else(|| "[image]".to_string()),
+        MessageContent::Video { caption, .. } => caption.clone().unwrap_or_else(|| "[video]".to_string()),
+        MessageContent::Audio { .. } => "[audio]".to_string(),
+        MessageContent::File { filename, .. } => format!("[file: {}]", filename.as_deref().unwrap_or("unnamed")),
+        MessageContent::Sticker => "[sticker]".to_string(),
+        MessageContent::Location { .. } => "[location]".to_string(),
+        MessageContent::Contact { name, .. } => format!("[contact: {}]", name),
+        MessageContent::System(event) => format!("[{}]", event.describe()),
+        MessageContent::Unknown => "[message]".to_string(),
+    };
+
+    match envelope.chat.as_ref().and_then(|c| c.title.as_deref()) {
+        Some(title) if title != sender => format!("{} in {}: {}", sender, title, body),
+        _ => format!("{}: {}", sender, body),
+    }
+}
+
+fn message_text(message: &Message) -> Option<&str> {
+    match &message.content {
+        MessageContent::Text(text) => Some(text),
+        MessageContent::Image { caption: Some(caption), .. } |
+        MessageContent::Video { caption: Some(caption), .. } => Some(caption),
+        _ => None,
+    }
+}
+
+fn text_mut(content: &mut MessageContent) -> Option<&mut String> {
+    match content {
+        MessageContent::Text(text) => Some(text),
+        MessageContent::Image { caption: Some(caption), .. } |
+        MessageContent::Video { caption: Some(caption), .. } => Some(caption),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChatId, MessageId, User, UserId};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockHost {
+        dispatched: Mutex<Vec<Dispatch>>,
+    }
+
+    #[async_trait::async_trait]
+    impl PipelineHost for MockHost {
+        async fn translate(&self, text: &str, to: &str, _model: &str) -> Result<String> {
+            Ok(format!("[{}] {}", to, text))
+        }
+
+        async fn dispatch(&self, dispatch: Dispatch) -> Result<()> {
+            self.dispatched.lock().unwrap().push(dispatch);
+            Ok(())
+        }
+    }
+
+    fn envelope(text: &str) -> Envelope {
+        Envelope {
+            source: "telegram".to_string(),
+            chat: Some(Chat {
+                id: ChatId::new("42"),
+                title: Some("Work".to_string()),
+                chat_type: ChatType::Group,
+                participant_count: None,
+                topic: None,
+            }),
+            labels: vec!["office".to_string()],
+            message: Message {
+                id: MessageId::new("1"),
+                chat_id: ChatId::new("42"),
+                sender: User {
+                    id: UserId::new("u"),
+                    username: None,
+                    display_name: Some("Antti".to_string()),
+                    phone_number: None,
+                },
+                content: MessageContent::Text(text.to_string()),
+                timestamp: chrono::Utc::now(),
+                reply_to: None,
+                edited: false,
+                mentions_me: false,
+                pending: false,
+                language: None,
+                media_text: None,
+                original_timestamp: None,
+            },
+        }
+    }
+
+    const YAML: &str = r#"
+pipelines:
+  - name: urgent
+    stages:
+      - filter:
+          chat: "telegram:#office"
+          contains: urgent
+      - redact:
+          patterns: ['\d{4}-\d{4}']
+      - notify: {}
+      - translate:
+          to: fi
+      - tts: {}
+"#;
+
+    #[tokio::test]
+    async fn test_stages_run_in_order() {
+        let pipelines = Pipelines::parse(YAML).unwrap();
+        let host = MockHost::default();
+
+        let report = pipelines.process(&envelope("URGENT: card 1234-5678"), &host).await;
+        assert_eq!(report.matched, vec!["urgent"]);
+        assert_eq!(report.dispatched, 2);
+
+        let dispatched = host.dispatched.lock().unwrap();
+        assert_eq!(dispatched[0].route, Route::Notify { critical: false });
+        assert_eq!(dispatched[0].text, "Antti in Work: URGENT: card [redacted]");
+        assert_eq!(dispatched[1].route, Route::Tts { voice: None });
+        assert_eq!(dispatched[1].text, "Antti in Work: [fi] URGENT: card [redacted]");
+        assert_eq!(dispatched[1].chat, "telegram:42");
+    }
+
+    #[tokio::test]
+    async fn test_filter_stops_message() {
+        let pipelines = Pipelines::parse(YAML).unwrap();
+        let host = MockHost::default();
+
+        let report = pipelines.process(&envelope("lunch?"), &host).await;
+        assert!(report.matched.is_empty());
+        assert!(host.dispatched.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_label_filter_ignores_case() {
+        let yaml = "pipelines:\n  - name: office\n    stages:\n      - filter:\n          chat: \"telegram:#Office\"\n      - notify: {}\n";
+        let pipelines = Pipelines::parse(yaml).unwrap();
+        let host = MockHost::default();
+
+        let report = pipelines.process(&envelope("lunch?"), &host).await;
+        assert_eq!(report.matched, vec!["office"]);
+        assert_eq!(host.dispatched.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_validation_reports_all_errors() {
+        let yaml = r#"
+pipelines:
+  - name: a
+    stages:
+      - filter: {}
+      - redact:
+          patterns: ['(']
+      - webhook:
+          url: ftp://example.com
+  - name: a
+    stages:
+      - translate:
+          to: en
+"#;
+        let error = Pipelines::parse(yaml).unwrap_err().to_string();
+
+        assert!(error.contains("stage 1 (filter): filter has no criteria"));
+        assert!(error.contains("stage 2 (redact): invalid pattern '('"));
+        assert!(error.contains("stage 3 (webhook)"));
+        assert!(error.contains("'a' is defined more than once"));
+        assert!(error.contains("never routes messages anywhere"));
+    }
+
+    #[test]
+    fn test_unknown_stage_rejected() {
+        assert!(Pipelines::parse("pipelines:\n  - name: x\n    stages:\n      - shout: {}\n").is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::types::ChatId;
+
+/// Progress of a long-running fetch
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// Started fetching messages from a source
+    SourceStarted { source: String },
+    /// Started fetching a chat
+    ChatStarted { source: String, chat_id: ChatId },
+    /// Messages fetched so far from a chat
+    Fetched { source: String, chat_id: ChatId, count: usize },
+    /// Finished a chat, either completely or at a cancellation point
+    ChatFinished { source: String, chat_id: ChatId, count: usize },
+    /// Finished a source
+    SourceFinished { source: String, count: usize },
+    /// Fetching stopped early because of a cancellation request
+    Cancelled { source: String },
+}
+
+type Callback = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// Progress reporting and cooperative cancellation for long operations.
+///
+/// Clones share the cancellation flag, so one Ctrl+C handler can stop every
+/// fetch started from the same `Progress`. Fetch loops check `is_cancelled`
+/// between batches and return what they have so far.
+#[derive(Clone, Default)]
+pub struct Progress {
+    callback: Option<Callback>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Progress")
+            .field("callback", &self.callback.is_some())
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl Progress {
+    /// Progress that reports nothing and is never cancelled unless asked to
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same cancellation flag, reporting events to `callback`
+    pub fn with_callback<F>(&self, callback: F) -> Self
+    where
+        F: Fn(ProgressEvent) + Send + Sync + 'static,
+    {
+        Self {
+            callback: Some(Arc::new(callback)),
+            cancelled: Arc::clone(&self.cancelled),
+        }
+    }
+
+    /// Same cancellation flag, delivering events over a channel
+    pub fn channel(&self) -> (Self, tokio::sync::mpsc::UnboundedReceiver<ProgressEvent>) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let progress = self.with_callback(move |event| {
+            let _ = tx.send(event);
+        });
+        (progress, rx)
+    }
+
+    /// Report an event
+    pub fn emit(&self, event: ProgressEvent) {
+        if let Some(callback) = &self.callback {
+            callback(event);
+        }
+    }
+
+    /// Ask running fetches to stop after their current batch
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Cancel on the first Ctrl+C; a second Ctrl+C exits immediately
+    pub fn cancel_on_ctrl_c(&self) {
+        let progress = self.clone();
+
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                eprintln!("\nInterrupted, finishing current batch... (Ctrl+C again to abort)");
+                progress.cancel();
+
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    std::process::exit(130);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_callbacks_share_cancellation() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+
+        let root = Progress::new();
+        let reporting = root.with_callback(move |event| recorded.lock().unwrap().push(event));
+
+        reporting.emit(ProgressEvent::SourceStarted { source: "telegram".to_string() });
+        root.emit(ProgressEvent::SourceStarted { source: "ignored".to_string() });
+        assert_eq!(events.lock().unwrap().len(), 1);
+
+        assert!(!reporting.is_cancelled());
+        root.cancel();
+        assert!(reporting.is_cancelled());
+    }
+}
@@ -0,0 +1,229 @@
+//! Deciding which parts of a message query each source handles itself
+//!
+//! Sources declare in [`SourceCapabilities::pushdown`] which criteria they apply while
+//! fetching: Telegram searches text on its servers, and a source backed by a database
+//! could turn every criterion into a WHERE clause. Whatever a source doesn't apply is left
+//! out of what it is asked for and checked by SourcesManager once messages arrive. The
+//! limit only goes to the source when nothing is left to check, since the source would
+//! otherwise cut its results before they are checked.
+
+use std::fmt;
+
+use crate::types::{ContentType, MessageFilter, SourceCapabilities};
+
+/// Content types that can carry text derived from an attachment
+pub const ATTACHMENT_TYPES: [ContentType; 3] = [ContentType::Image, ContentType::Audio, ContentType::File];
+
+/// A part of a [`MessageFilter`] that is applied either by the source or by SourcesManager
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    Search,
+    Sender,
+    TimeRange,
+    ContentType,
+    /// Mentions of my handles, only known once messages are annotated
+    Mentions,
+    /// Detected language, only known once messages are annotated
+    Language,
+    /// Search in text derived from attachments, which only SourcesManager has
+    AttachmentText,
+}
+
+impl Criterion {
+    /// Leave this criterion out of a filter
+    fn clear(self, filter: &mut MessageFilter) {
+        match self {
+            Criterion::Search => filter.search = None,
+            Criterion::Sender => filter.sender = None,
+            Criterion::TimeRange => {
+                filter.since = None;
+                filter.before = None;
+            }
+            Criterion::ContentType => filter.content_type = None,
+            Criterion::Mentions => filter.mentions_me = false,
+            Criterion::Language => filter.language = None,
+            Criterion::AttachmentText => {
+                filter.search = None;
+                filter.in_attachments = false;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Criterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Criterion::Search => "search",
+            Criterion::Sender => "sender",
+            Criterion::TimeRange => "time range",
+            Criterion::ContentType => "content type",
+            Criterion::Mentions => "mentions",
+            Criterion::Language => "language",
+            Criterion::AttachmentText => "attachment text",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How one source answers a query
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    /// What the source is asked for
+    pub source: MessageFilter,
+    /// What the messages it returns are held to. Mentions and language are left out, and
+    /// so is the limit when either is set: they are checked once messages are annotated.
+    pub check: MessageFilter,
+    /// Criteria the source applies
+    pub pushed: Vec<Criterion>,
+    /// Criteria SourcesManager applies after fetching
+    pub client: Vec<Criterion>,
+}
+
+impl QueryPlan {
+    /// Plan `filter` for a source with `capabilities`
+    pub fn new(capabilities: &SourceCapabilities, filter: &MessageFilter) -> Self {
+        let pushdown = capabilities.pushdown;
+        let criteria = [
+            (Criterion::Search, filter.search.is_some() && !filter.in_attachments, pushdown.search),
+            (Criterion::Sender, filter.sender.is_some(), pushdown.sender),
+            (Criterion::TimeRange, filter.since.is_some() || filter.before.is_some(), pushdown.time_range),
+            (Criterion::ContentType, filter.content_type.is_some(), pushdown.content_type),
+            (Criterion::Mentions, filter.mentions_me, false),
+            (Criterion::Language, filter.language.is_some(), false),
+            (Criterion::AttachmentText, filter.in_attachments, false),
+        ];
+
+        let mut source = filter.clone();
+        let mut pushed = Vec::new();
+        let mut client = Vec::new();
+        for (criterion, present, supported) in criteria {
+            if !present {
+                continue;
+            }
+            if supported {
+                pushed.push(criterion);
+            } else {
+                criterion.clear(&mut source);
+                client.push(criterion);
+            }
+        }
+
+        if filter.in_attachments {
+            // Only attachments can match, so only they are fetched
+            source.content_type = Some(
+                ATTACHMENT_TYPES
+                    .into_iter()
+                    .filter(|t| filter.content_type.as_ref().is_none_or(|types| types.contains(t)))
+                    .collect(),
+            );
+        }
+        if !client.is_empty() {
+            source.limit = None;
+        }
+
+        let mut check = filter.clone();
+        if filter.mentions_me || filter.language.is_some() {
+            check.limit = None;
+        }
+        Criterion::Mentions.clear(&mut check);
+        Criterion::Language.clear(&mut check);
+
+        Self { source, check, pushed, client }
+    }
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = |criteria: &[Criterion]| {
+            if criteria.is_empty() {
+                "nothing".to_string()
+            } else {
+                criteria.iter().map(Criterion::to_string).collect::<Vec<_>>().join(", ")
+            }
+        };
+        write!(f, "source applies {}, checked here: {}", names(&self.pushed), names(&self.client))?;
+        match (self.source.limit, self.check.limit) {
+            (Some(limit), _) => write!(f, ", source limit {}", limit),
+            (None, Some(limit)) => write!(f, ", limit {} applied here", limit),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FilterPushdown;
+
+    fn filter() -> MessageFilter {
+        MessageFilter {
+            search: Some("invoice".to_string()),
+            sender: Some("bob".to_string()),
+            since: Some(chrono::Utc::now()),
+            limit: Some(50),
+            ..MessageFilter::new()
+        }
+    }
+
+    #[test]
+    fn test_pushdown() {
+        let telegram = SourceCapabilities {
+            pushdown: FilterPushdown { search: true, sender: true, time_range: true, content_type: true },
+            ..SourceCapabilities::default()
+        };
+        let plan = QueryPlan::new(&telegram, &filter());
+        assert_eq!(plan.pushed, [Criterion::Search, Criterion::Sender, Criterion::TimeRange]);
+        assert!(plan.client.is_empty());
+        assert_eq!(plan.source.search.as_deref(), Some("invoice"));
+        assert_eq!(plan.source.limit, Some(50));
+        assert_eq!(plan.to_string(), "source applies search, sender, time range, checked here: nothing, source limit 50");
+    }
+
+    #[test]
+    fn test_client_side() {
+        let search_only = SourceCapabilities {
+            pushdown: FilterPushdown { search: true, ..FilterPushdown::default() },
+            ..SourceCapabilities::default()
+        };
+        let plan = QueryPlan::new(&search_only, &filter());
+        assert_eq!(plan.pushed, [Criterion::Search]);
+        assert_eq!(plan.client, [Criterion::Sender, Criterion::TimeRange]);
+        assert_eq!(plan.source.search.as_deref(), Some("invoice"));
+        assert!(plan.source.sender.is_none() && plan.source.since.is_none());
+        // The source can't cut to the limit before the sender is checked
+        assert_eq!(plan.source.limit, None);
+        assert_eq!(plan.check.sender.as_deref(), Some("bob"));
+        assert_eq!(plan.check.limit, Some(50));
+
+        let plain = QueryPlan::new(&SourceCapabilities::default(), &MessageFilter { limit: Some(5), ..MessageFilter::new() });
+        assert_eq!(plan.to_string(), "source applies search, checked here: sender, time range, limit 50 applied here");
+        assert_eq!(plain.source.limit, Some(5));
+    }
+
+    #[test]
+    fn test_annotations_and_attachments() {
+        let everything = SourceCapabilities {
+            pushdown: FilterPushdown { search: true, sender: true, time_range: true, content_type: true },
+            ..SourceCapabilities::default()
+        };
+        let filter = MessageFilter {
+            mentions_me: true,
+            language: Some("en".to_string()),
+            in_attachments: true,
+            search: Some("contract".to_string()),
+            content_type: Some(vec![ContentType::File, ContentType::Text]),
+            ..MessageFilter::new()
+        };
+        let plan = QueryPlan::new(&everything, &filter);
+        assert_eq!(plan.pushed, [Criterion::ContentType]);
+        assert_eq!(plan.client, [Criterion::Mentions, Criterion::Language, Criterion::AttachmentText]);
+        assert!(!plan.source.mentions_me && plan.source.language.is_none());
+        assert!(plan.source.search.is_none() && !plan.source.in_attachments);
+        assert_eq!(plan.source.content_type, Some(vec![ContentType::File]));
+
+        assert!(!plan.check.mentions_me && plan.check.language.is_none());
+        assert!(plan.check.in_attachments);
+        assert_eq!(plan.check.limit, None);
+        assert_eq!(plan.check.search.as_deref(), Some("contract"));
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::global_id::GlobalChatId;
+use crate::types::{Message, MessageId};
+
+/// How far a chat has been read
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadCursor {
+    /// Newest message marked read
+    pub message_id: MessageId,
+    pub timestamp: DateTime<Utc>,
+    pub read_at: DateTime<Utc>,
+}
+
+/// Per-chat read cursors, keyed by [`GlobalChatId`]. Messages newer than a chat's
+/// cursor are unread; chats without a cursor have not been read at all.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReadCursors {
+    #[serde(default)]
+    chats: BTreeMap<GlobalChatId, ReadCursor>,
+}
+
+impl ReadCursors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the cursor file (`~/.config/chat/read_cursors.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("read_cursors.json"))
+    }
+
+    /// Load cursors from the default location, returning no cursors if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read read cursor file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse read cursor file")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create read cursor directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize read cursors")?;
+
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, contents).context("Failed to write read cursor file")?;
+        std::fs::rename(&tmp, path).context("Failed to write read cursor file")
+    }
+
+    pub fn cursor(&self, chat: &GlobalChatId) -> Option<&ReadCursor> {
+        self.chats.get(chat)
+    }
+
+    /// Whether a message of the chat is newer than its cursor
+    pub fn is_unread(&self, chat: &GlobalChatId, message: &Message) -> bool {
+        match self.chats.get(chat) {
+            Some(cursor) => message.timestamp > cursor.timestamp,
+            None => true,
+        }
+    }
+
+    /// Move the chat's cursor to the newest of `messages`; never moves it back.
+    /// Returns whether the cursor moved.
+    pub fn mark_read(&mut self, chat: GlobalChatId, messages: &[Message]) -> bool {
+        let Some(newest) = messages.iter().filter(|m| !m.pending).max_by_key(|m| m.timestamp) else {
+            return false;
+        };
+
+        if !self.is_unread(&chat, newest) {
+            return false;
+        }
+
+        self.chats.insert(chat, ReadCursor {
+            message_id: newest.id.clone(),
+            timestamp: newest.timestamp,
+            read_at: Utc::now(),
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    #[test]
+    fn test_mark_read() {
+        let chat = GlobalChatId::new("telegram", "100");
+        let messages = vec![
+            MessageBuilder::new("1", "100").minute(0).build(),
+            MessageBuilder::new("2", "100").minute(5).build(),
+        ];
+
+        let mut cursors = ReadCursors::new();
+        assert!(cursors.is_unread(&chat, &messages[0]));
+
+        assert!(cursors.mark_read(chat.clone(), &messages));
+        assert_eq!(cursors.cursor(&chat).unwrap().message_id, MessageId::new("2"));
+        assert!(!cursors.is_unread(&chat, &messages[1]));
+
+        // An older batch doesn't move the cursor back
+        assert!(!cursors.mark_read(chat.clone(), &messages[..1]));
+        assert_eq!(cursors.cursor(&chat).unwrap().message_id, MessageId::new("2"));
+
+        let newer = MessageBuilder::new("3", "100").minute(10).build();
+        assert!(cursors.is_unread(&chat, &newer));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let chat = GlobalChatId::new("telegram", "100");
+        let mut cursors = ReadCursors::new();
+        cursors.mark_read(chat.clone(), &[MessageBuilder::new("1", "100").build()]);
+
+        let path = std::env::temp_dir().join(format!("chat-read-cursors-{}.json", uuid::Uuid::new_v4().simple()));
+        cursors.save_to(&path).unwrap();
+        let loaded = ReadCursors::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.cursor(&chat), cursors.cursor(&chat));
+    }
+}
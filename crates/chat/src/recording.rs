@@ -0,0 +1,490 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::channel_stats::ChannelPosts;
+use crate::progress::Progress;
+use crate::sources_config::SourceSettings;
+use crate::supervisor::TaskStatus;
+use crate::types::{Chat, ChatFilter, ChatId, ChatSource, Message, MessageFilter, MessageId, SourceCapabilities};
+
+/// A single recorded source call and its outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "call", rename_all = "snake_case")]
+pub enum RecordedCall {
+    ListChats {
+        filter: Option<ChatFilter>,
+        response: std::result::Result<Vec<Chat>, String>,
+    },
+    GetMessages {
+        filter: MessageFilter,
+        response: std::result::Result<Vec<Message>, String>,
+    },
+    SendMessage {
+        chat_id: ChatId,
+        text: String,
+        reply_to: Option<MessageId>,
+        response: std::result::Result<Message, String>,
+    },
+}
+
+/// Recorded traffic for one source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceRecording {
+    pub source_name: String,
+    #[serde(default)]
+    pub capabilities: SourceCapabilities,
+    pub calls: Vec<RecordedCall>,
+}
+
+/// Fixture file contents: recorded traffic keyed by source ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Fixture {
+    pub sources: BTreeMap<String, SourceRecording>,
+}
+
+impl Fixture {
+    /// Load a fixture file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse fixture: {}", path.display()))
+    }
+
+    /// Write the fixture file, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write fixture: {}", path.display()))
+    }
+
+    /// Replay sources for every recorded source
+    pub fn replay_sources(&self) -> Vec<ReplaySource> {
+        self.sources
+            .iter()
+            .map(|(id, recording)| ReplaySource::new(id.clone(), recording.clone()))
+            .collect()
+    }
+}
+
+/// Shared fixture writer used by all recording sources of a session
+#[derive(Clone)]
+pub struct Recorder {
+    path: PathBuf,
+    fixture: Arc<Mutex<Fixture>>,
+}
+
+impl Recorder {
+    /// Start a new recording, overwriting any existing fixture at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            fixture: Arc::new(Mutex::new(Fixture::default())),
+        }
+    }
+
+    /// Wrap a source so its traffic is recorded
+    pub fn wrap(&self, inner: Arc<dyn ChatSource>) -> Arc<dyn ChatSource> {
+        Arc::new(RecordingSource {
+            inner,
+            recorder: self.clone(),
+        })
+    }
+
+    /// Append a call and flush the fixture to disk
+    fn record(&self, source: &dyn ChatSource, call: RecordedCall) -> Result<()> {
+        let mut fixture = self.fixture.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+
+        let recording = fixture.sources.entry(source.source_id().to_string()).or_default();
+        recording.source_name = source.source_name().to_string();
+        recording.capabilities = source.capabilities();
+        recording.calls.push(call);
+
+        fixture.save(&self.path)
+    }
+}
+
+/// Source wrapper that passes calls through and records them to a fixture file
+pub struct RecordingSource {
+    inner: Arc<dyn ChatSource>,
+    recorder: Recorder,
+}
+
+#[async_trait::async_trait]
+impl ChatSource for RecordingSource {
+    fn source_id(&self) -> &str {
+        self.inner.source_id()
+    }
+
+    fn source_name(&self) -> &str {
+        self.inner.source_name()
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn configure(&mut self, settings: SourceSettings) {
+        // The wrapper holds the only reference to the source it wraps
+        if let Some(inner) = Arc::get_mut(&mut self.inner) {
+            inner.configure(settings)
+        }
+    }
+
+    fn tasks(&self) -> Vec<TaskStatus> {
+        self.inner.tasks()
+    }
+
+    async fn shutdown(&self) {
+        self.inner.shutdown().await
+    }
+
+    async fn keep_alive(&self) -> Result<()> {
+        self.inner.keep_alive().await
+    }
+
+    async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+        let result = self.inner.list_chats(filter.clone()).await;
+
+        self.recorder.record(self.inner.as_ref(), RecordedCall::ListChats {
+            filter,
+            response: result.as_ref().cloned().map_err(|e| e.to_string()),
+        })?;
+
+        result
+    }
+
+    async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+        self.get_messages_with_progress(filter, &Progress::new()).await
+    }
+
+    async fn get_messages_with_progress(&self, filter: MessageFilter, progress: &Progress) -> Result<Vec<Message>> {
+        let result = self.inner.get_messages_with_progress(filter.clone(), progress).await;
+
+        self.recorder.record(self.inner.as_ref(), RecordedCall::GetMessages {
+            filter,
+            response: result.as_ref().cloned().map_err(|e| e.to_string()),
+        })?;
+
+        result
+    }
+
+    async fn get_pending(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+        // Drafts are not recorded
+        self.inner.get_pending(filter).await
+    }
+
+    async fn message_count(&self, chat_id: &ChatId) -> Result<Option<usize>> {
+        // Counts are not recorded
+        self.inner.message_count(chat_id).await
+    }
+
+    async fn get_channel_posts(&self, chat_id: &ChatId, since: DateTime<Utc>) -> Result<ChannelPosts> {
+        // Channel statistics are not recorded
+        self.inner.get_channel_posts(chat_id, since).await
+    }
+
+    async fn send_message(&self, chat_id: &ChatId, text: &str, reply_to: Option<&MessageId>) -> Result<Message> {
+        let result = self.inner.send_message(chat_id, text, reply_to).await;
+
+        self.recorder.record(self.inner.as_ref(), RecordedCall::SendMessage {
+            chat_id: chat_id.clone(),
+            text: text.to_string(),
+            reply_to: reply_to.cloned(),
+            response: result.as_ref().cloned().map_err(|e| e.to_string()),
+        })?;
+
+        result
+    }
+
+    async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+        // Streams are not recorded
+        self.inner.subscribe().await
+    }
+}
+
+/// Source that serves recorded responses back deterministically.
+///
+/// Calls are matched on their exact filter first and answered in recorded order.
+/// Filters built from relative times ("7d") differ between runs, so a call with no
+/// exact match takes the next unused call of the same kind. Once exhausted, the
+/// last matching response is repeated.
+pub struct ReplaySource {
+    id: String,
+    recording: SourceRecording,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplaySource {
+    pub fn new(id: impl Into<String>, recording: SourceRecording) -> Self {
+        let used = vec![false; recording.calls.len()];
+        Self {
+            id: id.into(),
+            recording,
+            used: Mutex::new(used),
+        }
+    }
+
+    /// Pick the recorded call answering a request
+    fn take<K, F>(&self, same_kind: K, same_filter: F) -> Result<&RecordedCall>
+    where
+        K: Fn(&RecordedCall) -> bool,
+        F: Fn(&RecordedCall) -> bool,
+    {
+        let mut used = self.used.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+
+        let calls = &self.recording.calls;
+        let exact: Vec<usize> = (0..calls.len())
+            .filter(|&i| same_kind(&calls[i]) && same_filter(&calls[i]))
+            .collect();
+
+        let index = exact
+            .iter()
+            .copied()
+            .find(|&i| !used[i])
+            .or_else(|| (0..calls.len()).find(|&i| !used[i] && same_kind(&calls[i])))
+            .or_else(|| exact.last().copied())
+            .or_else(|| (0..calls.len()).rev().find(|&i| same_kind(&calls[i])))
+            .ok_or_else(|| anyhow::anyhow!("No recorded response for this request on source '{}'", self.id))?;
+
+        used[index] = true;
+        Ok(&calls[index])
+    }
+}
+
+/// Compare filters by their serialized form, since filter types don't implement PartialEq
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatSource for ReplaySource {
+    fn source_id(&self) -> &str {
+        &self.id
+    }
+
+    fn source_name(&self) -> &str {
+        &self.recording.source_name
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        // Streams, drafts and channel statistics are not recorded
+        SourceCapabilities {
+            streaming: false,
+            pending: false,
+            channel_stats: false,
+            ..self.recording.capabilities
+        }
+    }
+
+    async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+        let call = self.take(
+            |call| matches!(call, RecordedCall::ListChats { .. }),
+            |call| matches!(call, RecordedCall::ListChats { filter: recorded, .. } if same(recorded, &filter)),
+        )?;
+
+        match call {
+            RecordedCall::ListChats { response, .. } => response.clone().map_err(|e| anyhow::anyhow!(e)),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+        let call = self.take(
+            |call| matches!(call, RecordedCall::GetMessages { .. }),
+            |call| matches!(call, RecordedCall::GetMessages { filter: recorded, .. } if same(recorded, &filter)),
+        )?;
+
+        match call {
+            RecordedCall::GetMessages { response, .. } => response.clone().map_err(|e| anyhow::anyhow!(e)),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn send_message(&self, chat_id: &ChatId, text: &str, _reply_to: Option<&MessageId>) -> Result<Message> {
+        let call = self.take(
+            |call| matches!(call, RecordedCall::SendMessage { .. }),
+            |call| matches!(call, RecordedCall::SendMessage { chat_id: recorded, text: sent, .. } if recorded == chat_id && sent == text),
+        )?;
+
+        match call {
+            RecordedCall::SendMessage { response, .. } => response.clone().map_err(|e| anyhow::anyhow!(e)),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChatId, MessageContent, MessageId, User, UserId};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Source returning a different message on every call
+    struct CountingSource {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl ChatSource for CountingSource {
+        fn source_id(&self) -> &str {
+            "mock"
+        }
+
+        fn source_name(&self) -> &str {
+            "Mock Source"
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn list_chats(&self, _filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+            anyhow::bail!("not connected")
+        }
+
+        async fn get_messages(&self, _filter: MessageFilter) -> Result<Vec<Message>> {
+            let n = self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(vec![Message {
+                id: MessageId::new(n.to_string()),
+                chat_id: ChatId::new("chat"),
+                sender: User {
+                    id: UserId::new("u"),
+                    username: None,
+                    display_name: None,
+                    phone_number: None,
+                },
+                content: MessageContent::Text(format!("call {}", n)),
+                timestamp: chrono::Utc::now(),
+                reply_to: None,
+                edited: false,
+                mentions_me: false,
+                pending: false,
+                language: None,
+                media_text: None,
+                original_timestamp: None,
+            }])
+        }
+
+        async fn send_message(&self, chat_id: &ChatId, text: &str, reply_to: Option<&MessageId>) -> Result<Message> {
+            let mut sent = self.get_messages(MessageFilter::new()).await?.remove(0);
+            sent.chat_id = chat_id.clone();
+            sent.content = MessageContent::Text(text.to_string());
+            sent.reply_to = reply_to.cloned();
+            Ok(sent)
+        }
+
+        async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+            Ok(None)
+        }
+    }
+
+    fn fixture_path() -> PathBuf {
+        std::env::temp_dir().join(format!("chat-fixture-{}.json", uuid::Uuid::new_v4().simple()))
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let path = fixture_path();
+        let recorder = Recorder::new(&path);
+        let source = recorder.wrap(Arc::new(CountingSource { calls: AtomicUsize::new(0) }));
+
+        let limited = MessageFilter { limit: Some(1), ..MessageFilter::new() };
+        source.get_messages(MessageFilter::new()).await.unwrap();
+        source.get_messages(MessageFilter::new()).await.unwrap();
+        source.get_messages(limited.clone()).await.unwrap();
+        assert!(source.list_chats(None).await.is_err());
+
+        let fixture = Fixture::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let replay = fixture.replay_sources().pop().unwrap();
+        assert_eq!(replay.source_id(), "mock");
+        assert_eq!(replay.source_name(), "Mock Source");
+
+        let text = |messages: Vec<Message>| match &messages[0].content {
+            MessageContent::Text(text) => text.clone(),
+            _ => String::new(),
+        };
+        assert_eq!(text(replay.get_messages(limited).await.unwrap()), "call 2");
+        assert_eq!(text(replay.get_messages(MessageFilter::new()).await.unwrap()), "call 0");
+        assert_eq!(text(replay.get_messages(MessageFilter::new()).await.unwrap()), "call 1");
+        // Exhausted: repeat the last matching response
+        assert_eq!(text(replay.get_messages(MessageFilter::new()).await.unwrap()), "call 1");
+
+        let err = replay.list_chats(None).await.unwrap_err();
+        assert_eq!(err.to_string(), "not connected");
+    }
+
+    #[tokio::test]
+    async fn test_replay_falls_back_to_recorded_order() {
+        let path = fixture_path();
+        let recorder = Recorder::new(&path);
+        let source = recorder.wrap(Arc::new(CountingSource { calls: AtomicUsize::new(0) }));
+        source.get_messages(MessageFilter::new()).await.unwrap();
+        source.get_messages(MessageFilter::new()).await.unwrap();
+
+        let fixture = Fixture::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let replay = fixture.replay_sources().pop().unwrap();
+
+        // A filter that was never recorded is served the next call of the same kind
+        let since = MessageFilter { since: Some(chrono::Utc::now()), ..MessageFilter::new() };
+        let first = replay.get_messages(since).await.unwrap();
+        let second = replay.get_messages(MessageFilter::new()).await.unwrap();
+        assert_eq!(first[0].id.as_str(), "0");
+        assert_eq!(second[0].id.as_str(), "1");
+    }
+
+    #[tokio::test]
+    async fn test_record_sent_messages() {
+        let path = fixture_path();
+        let recorder = Recorder::new(&path);
+        let source = recorder.wrap(Arc::new(CountingSource { calls: AtomicUsize::new(0) }));
+
+        let chat = ChatId::new("chat");
+        let sent = source.send_message(&chat, "hello", Some(&MessageId::new("7"))).await.unwrap();
+        assert_eq!(sent.reply_to, Some(MessageId::new("7")));
+        assert!(matches!(&sent.content, MessageContent::Text(text) if text == "hello"));
+
+        let fixture = Fixture::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let calls = &fixture.sources["mock"].calls;
+        assert!(matches!(&calls[..], [RecordedCall::SendMessage { text, reply_to: Some(_), .. }] if text == "hello"));
+
+        let replay = fixture.replay_sources().pop().unwrap();
+        assert_eq!(replay.send_message(&chat, "hello", None).await.unwrap().id, sent.id);
+    }
+
+    #[tokio::test]
+    async fn test_replay_unrecorded_kind() {
+        let replay = ReplaySource::new("mock", SourceRecording::default());
+        assert!(replay.get_messages(MessageFilter::new()).await.is_err());
+        assert!(replay.list_chats(None).await.is_err());
+    }
+}
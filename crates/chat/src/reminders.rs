@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::types::{ChatId, MessageId};
+
+/// A message marked to resurface later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    /// Short reminder ID used by snooze/cancel
+    pub id: String,
+    pub source: String,
+    pub message_id: MessageId,
+    #[serde(default)]
+    pub chat_id: Option<ChatId>,
+    /// Original message text, captured when the reminder was created
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub sender: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub due: DateTime<Utc>,
+    pub created: DateTime<Utc>,
+    /// Also read the reminder aloud when it fires
+    #[serde(default)]
+    pub speak: bool,
+    /// Fire even during the dashboard's do-not-disturb hours
+    #[serde(default)]
+    pub critical: bool,
+}
+
+impl Reminder {
+    /// Create a reminder for a message, due after the given delay
+    pub fn new(source: impl Into<String>, message_id: MessageId, delay: Duration) -> Self {
+        let now = Utc::now();
+        let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+
+        Self {
+            id,
+            source: source.into(),
+            message_id,
+            chat_id: None,
+            content: None,
+            sender: None,
+            note: None,
+            due: now + delay,
+            created: now,
+            speak: false,
+            critical: false,
+        }
+    }
+
+    /// Text shown when the reminder fires
+    pub fn summary(&self) -> String {
+        let mut text = match (&self.sender, &self.content) {
+            (Some(sender), Some(content)) => format!("{}: {}", sender, content),
+            (None, Some(content)) => content.clone(),
+            _ => format!("Message {} on {}", self.message_id, self.source),
+        };
+
+        if let Some(note) = &self.note {
+            text = format!("{} ({})", text, note);
+        }
+
+        text
+    }
+}
+
+/// Pending reminders (`~/.config/chat/reminders.json`)
+///
+/// The chat CLI adds, snoozes and cancels reminders; the dashboard fires due
+/// reminders and removes them from the file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReminderStore {
+    #[serde(default)]
+    reminders: Vec<Reminder>,
+}
+
+impl ReminderStore {
+    /// Create an empty reminder store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the reminders file (`~/.config/chat/reminders.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("reminders.json"))
+    }
+
+    /// Load reminders from the default location, returning an empty store if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load reminders from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read reminders file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse reminders file")
+    }
+
+    /// Save reminders to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save reminders to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create reminders directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize reminders")?;
+
+        std::fs::write(path, contents).context("Failed to write reminders file")
+    }
+
+    /// Add a reminder
+    pub fn add(&mut self, reminder: Reminder) {
+        self.reminders.push(reminder);
+        self.reminders.sort_by_key(|r| r.due);
+    }
+
+    /// Cancel a reminder by ID. Returns the removed reminder.
+    pub fn cancel(&mut self, id: &str) -> Option<Reminder> {
+        let index = self.reminders.iter().position(|r| r.id == id)?;
+        Some(self.reminders.remove(index))
+    }
+
+    /// Push a reminder's due time to `delay` from now. Returns the new due time.
+    pub fn snooze(&mut self, id: &str, delay: Duration) -> Option<DateTime<Utc>> {
+        let reminder = self.reminders.iter_mut().find(|r| r.id == id)?;
+        reminder.due = Utc::now() + delay;
+        let due = reminder.due;
+        self.reminders.sort_by_key(|r| r.due);
+        Some(due)
+    }
+
+    /// All pending reminders, soonest first
+    pub fn list(&self) -> &[Reminder] {
+        &self.reminders
+    }
+
+    /// Remove and return reminders due at or before `now`
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Vec<Reminder> {
+        let (due, pending): (Vec<_>, Vec<_>) = self.reminders.drain(..).partition(|r| r.due <= now);
+        self.reminders = pending;
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snooze_and_cancel() {
+        let mut store = ReminderStore::new();
+        let reminder = Reminder::new("telegram", MessageId::new("42"), Duration::hours(1));
+        let id = reminder.id.clone();
+        store.add(reminder);
+
+        let due = store.snooze(&id, Duration::days(1)).unwrap();
+        assert!(due > Utc::now() + Duration::hours(23));
+        assert!(store.snooze("missing", Duration::days(1)).is_none());
+
+        assert!(store.cancel(&id).is_some());
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn test_take_due() {
+        let mut store = ReminderStore::new();
+        store.add(Reminder::new("telegram", MessageId::new("1"), Duration::seconds(-5)));
+        store.add(Reminder::new("telegram", MessageId::new("2"), Duration::hours(2)));
+
+        let due = store.take_due(Utc::now());
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].message_id, MessageId::new("1"));
+        assert_eq!(store.list().len(), 1);
+    }
+}
@@ -0,0 +1,250 @@
+//! Updating the `chat` binary from GitHub releases.
+//!
+//! A release carries one binary per platform, named by [`asset_name`], and a
+//! `SHA256SUMS` file listing their checksums. The binary is downloaded, checked against
+//! that list, written next to the running one and renamed over it, so a failed update
+//! never leaves a half-written binary behind.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Repository releases are looked up in unless `[updates] repository` says otherwise
+pub const DEFAULT_REPOSITORY: &str = "k0sti/dashboard";
+/// Release asset with the checksums of the other assets, in `sha256sum` format
+pub const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+/// A release as returned by the GitHub API
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+impl Release {
+    pub fn version(&self) -> Option<Version> {
+        Version::parse(&self.tag_name)
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+
+    pub fn changelog(&self) -> &str {
+        self.body.as_deref().map(str::trim).unwrap_or("")
+    }
+}
+
+/// `major.minor.patch`; anything after a `-` or `+` is ignored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(pub u64, pub u64, pub u64);
+
+impl Version {
+    /// Parse "1.2.3" or "v1.2.3"; missing parts count as 0
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches('v');
+        let core = text.split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|part| part.parse::<u64>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().transpose().ok()?.unwrap_or(0);
+        let patch = parts.next().transpose().ok()?.unwrap_or(0);
+        Some(Self(major, minor, patch))
+    }
+
+    /// The version of this build
+    pub fn current() -> Self {
+        Self::parse(env!("CARGO_PKG_VERSION")).expect("package version is semver")
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// What `chat self-update --check --format json` prints, for the dashboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    pub current: String,
+    pub latest: Option<String>,
+    pub update_available: bool,
+    /// Release notes of the latest release
+    #[serde(default)]
+    pub changelog: String,
+    #[serde(default)]
+    pub url: String,
+    /// Checking is turned off with `[updates] check = false`, or chat was built
+    /// without the `self-update` feature
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl UpdateCheck {
+    pub fn new(release: &Release) -> Self {
+        let current = Version::current();
+        let latest = release.version();
+        Self {
+            current: current.to_string(),
+            latest: latest.map(|version| version.to_string()),
+            update_available: latest.is_some_and(|latest| latest > current),
+            changelog: release.changelog().to_string(),
+            url: release.html_url.clone(),
+            disabled: false,
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            current: Version::current().to_string(),
+            latest: None,
+            update_available: false,
+            changelog: String::new(),
+            url: String::new(),
+            disabled: true,
+        }
+    }
+}
+
+pub fn latest_release_url(repository: &str) -> String {
+    format!("https://api.github.com/repos/{}/releases/latest", repository)
+}
+
+/// Release asset with the binary for this platform, e.g. "chat-x86_64-linux"
+pub fn asset_name() -> String {
+    format!(
+        "chat-{}-{}{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// Checksums by file name from `sha256sum` output; binary-mode `*name` lines included
+pub fn parse_checksums(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| {
+            let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+            let name = name.trim_start().trim_start_matches('*');
+            Some((name.to_string(), hash.to_ascii_lowercase()))
+        })
+        .collect()
+}
+
+pub fn verify_checksum(bytes: &[u8], expected: &str) -> Result<()> {
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        anyhow::bail!("Checksum mismatch: expected {}, got {}", expected.trim(), actual);
+    }
+    Ok(())
+}
+
+/// Where the new binary is written before it replaces `target`: the same directory,
+/// so the rename stays on one filesystem
+pub fn staging_path(target: &Path) -> PathBuf {
+    let name = target.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    target.with_file_name(format!(".{}.update", name))
+}
+
+/// Replace the binary at `target` with `bytes`
+pub fn install(bytes: &[u8], target: &Path) -> Result<()> {
+    let staged = staging_path(target);
+    std::fs::write(&staged, bytes).with_context(|| format!("Failed to write {}", staged.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+    // A running executable can't be replaced on Windows, but it can be renamed
+    #[cfg(windows)]
+    {
+        let old = target.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(target, &old).with_context(|| format!("Failed to move {} aside", target.display()))?;
+    }
+
+    if let Err(e) = std::fs::rename(&staged, target) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e).with_context(|| format!("Failed to replace {}", target.display()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version() {
+        assert_eq!(Version::parse("v1.2.3"), Some(Version(1, 2, 3)));
+        assert_eq!(Version::parse("0.4"), Some(Version(0, 4, 0)));
+        assert_eq!(Version::parse("2.0.0-rc.1"), Some(Version(2, 0, 0)));
+        assert_eq!(Version::parse("nightly"), None);
+        assert!(Version(0, 10, 0) > Version(0, 9, 12));
+        assert_eq!(Version(1, 0, 2).to_string(), "1.0.2");
+    }
+
+    #[test]
+    fn test_update_check() {
+        let release = |tag: &str| Release {
+            tag_name: tag.to_string(),
+            body: Some("  - Faster sync\n".to_string()),
+            html_url: String::new(),
+            assets: Vec::new(),
+        };
+        let newer = UpdateCheck::new(&release("v999.0.0"));
+        assert!(newer.update_available);
+        assert_eq!(newer.latest.as_deref(), Some("999.0.0"));
+        assert_eq!(newer.changelog, "- Faster sync");
+
+        assert!(!UpdateCheck::new(&release("v0.0.1")).update_available);
+        assert!(!UpdateCheck::new(&release("latest")).update_available);
+    }
+
+    #[test]
+    fn test_checksums() {
+        let sums = parse_checksums(
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9  chat-x86_64-linux\n\
+             2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824 *chat-aarch64-macos\n",
+        );
+        assert_eq!(sums.len(), 2);
+        assert_eq!(
+            sums["chat-aarch64-macos"],
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+
+        verify_checksum(b"hello world", &sums["chat-x86_64-linux"]).unwrap();
+        verify_checksum(b"hello", &sums["chat-aarch64-macos"]).unwrap();
+        assert!(verify_checksum(b"hello!", &sums["chat-aarch64-macos"]).is_err());
+    }
+
+    #[test]
+    fn test_install() {
+        let dir = std::env::temp_dir().join(format!("chat-self-update-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("chat");
+        std::fs::write(&target, b"old").unwrap();
+
+        install(b"new", &target).unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"new");
+        assert!(!staging_path(&target).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,596 @@
+//! A source of made-up chats and messages, for demos, UI work and load tests.
+//!
+//! Each `[[simulated]]` table in sources.toml adds one (see [`SimulationSettings`]). Its
+//! chats, people and history come from the seed, anchored at the time the source is
+//! made; once subscribed to it keeps writing at `messages_per_minute`, with random gaps
+//! between messages, and messages sent to it are answered.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::sources_config::{MediaMix, SimulationSettings};
+use crate::supervisor::{RestartPolicy, Supervisor, TaskStatus};
+use crate::types::{
+    apply_filter, Chat, ChatFilter, ChatId, ChatSource, ChatType, Message, MessageContent, MessageFilter, MessageId,
+    SourceCapabilities, User, UserId,
+};
+
+/// Messages each subscriber's channel holds before the generator waits for it
+const STREAM_BUFFER: usize = 1024;
+/// Live messages kept for `get_messages` on top of the history, so a long load test
+/// doesn't grow without bound
+const MAX_LIVE_MESSAGES: usize = 100_000;
+
+const GROUPS: [&str; 8] = [
+    "Weekend Hiking", "Book Club", "Dev Team", "Family", "Flat 4B", "Climbing Crew", "Release Planning", "Board Games",
+];
+const CHANNELS: [&str; 3] = ["Release Notes", "City News", "Deals & Discounts"];
+const TOPICS: [&str; 10] = [
+    "the release", "Saturday", "the new office", "dinner", "the flaky test", "the trip", "the budget", "the demo",
+    "the migration", "the party",
+];
+const LINES: [&str; 14] = [
+    "Has anyone looked at {}?",
+    "I'll handle {} tomorrow",
+    "Quick update on {}: it's going fine",
+    "Can we move {} to next week?",
+    "Did you see the news about {}?",
+    "Still waiting to hear back about {}",
+    "Sounds good to me",
+    "Haha, yes",
+    "On my way!",
+    "Let me check and get back to you",
+    "Thanks, that helps a lot",
+    "Who's in for {}?",
+    "Running a bit late, sorry",
+    "I think we should talk about {} before deciding",
+];
+const REPLIES: [&str; 6] = ["Agreed", "Good point", "Not sure about that", "👍", "Yes, let's do it", "Can you say more?"];
+const FILES: [&str; 5] = ["notes.pdf", "budget.xlsx", "slides.pptx", "photo.zip", "contract.docx"];
+
+/// Small, seedable random numbers (SplitMix64), so the same seed makes the same data
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// In `0..n`; 0 when `n` is
+    fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    /// In `[0, 1)`
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        self.unit() < probability
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// A chat and who writes in it, by index into the personas
+struct SimulatedChat {
+    chat: Chat,
+    members: Vec<usize>,
+    /// Latest message, and whether I wrote it, for replies to point at
+    last: Option<(MessageId, bool)>,
+}
+
+/// Everything the source made up so far
+struct Simulation {
+    personas: Vec<User>,
+    media: MediaMix,
+    chats: Vec<SimulatedChat>,
+    /// Oldest first
+    messages: Vec<Message>,
+    history_len: usize,
+    rng: Rng,
+    next_id: u64,
+    /// Chat the last live message went to; conversations tend to go on where they are
+    current: usize,
+    /// Chat where I wrote last, answered by the next live message
+    reply_due: Option<usize>,
+}
+
+fn me() -> User {
+    User { id: UserId::new("me"), username: None, display_name: Some("Me".to_string()), phone_number: None }
+}
+
+impl Simulation {
+    fn new(settings: &SimulationSettings, now: DateTime<Utc>) -> Self {
+        let mut rng = Rng(settings.seed);
+        let personas: Vec<User> = settings
+            .personas
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| User {
+                id: UserId::new(name.to_lowercase().replace(' ', "_")),
+                username: Some(name.to_lowercase().replace(' ', "_")),
+                display_name: Some(name.to_string()),
+                phone_number: None,
+            })
+            .collect();
+
+        let chats = (0..settings.chats).map(|index| Self::make_chat(index, personas.len(), &mut rng)).collect();
+        let mut simulation = Self {
+            personas,
+            media: settings.media,
+            chats,
+            messages: Vec::new(),
+            history_len: 0,
+            rng,
+            next_id: 1,
+            current: 0,
+            reply_due: None,
+        };
+
+        // Each chat's history, spread over the days before `now`
+        let span_secs = i64::from(settings.history_days) * 86_400;
+        for chat in 0..simulation.chats.len() {
+            let mut offsets: Vec<i64> = (0..settings.history_per_chat)
+                .map(|_| (simulation.rng.unit() * span_secs as f64) as i64)
+                .collect();
+            offsets.sort_unstable_by(|a, b| b.cmp(a));
+            for offset in offsets {
+                let message = simulation.write(chat, now - Duration::seconds(offset));
+                simulation.messages.push(message);
+            }
+        }
+        simulation.messages.sort_by_key(|m| m.timestamp);
+        simulation.history_len = simulation.messages.len();
+        simulation
+    }
+
+    /// Chat `index`: two direct chats, two groups and a channel in every five
+    fn make_chat(index: usize, personas: usize, rng: &mut Rng) -> SimulatedChat {
+        let id = ChatId::new((index + 1).to_string());
+        let round = index / 5;
+        // "Book Club", then "Book Club 2" once every name is taken
+        let numbered = |names: &[&str], n: usize| match n / names.len() {
+            0 => names[n % names.len()].to_string(),
+            lap => format!("{} {}", names[n % names.len()], lap + 1),
+        };
+
+        let (title, chat_type, members, participants) = match index % 5 {
+            0 | 1 => {
+                let persona = (round * 2 + index % 5) % personas;
+                (None, ChatType::DirectMessage, vec![persona], 2)
+            }
+            2 | 3 => {
+                let group = round * 2 + index % 5 - 2;
+                let mut members: Vec<usize> = (0..personas).collect();
+                let size = (3 + rng.below(4)).min(personas);
+                for i in 0..size {
+                    let j = i + rng.below(personas - i);
+                    members.swap(i, j);
+                }
+                members.truncate(size);
+                let participants = members.len() + 1;
+                (Some(numbered(&GROUPS[..], group)), ChatType::Group, members, participants)
+            }
+            _ => {
+                let author = rng.below(personas);
+                (Some(numbered(&CHANNELS[..], round)), ChatType::Channel, vec![author], 100 + rng.below(5000))
+            }
+        };
+
+        SimulatedChat {
+            chat: Chat { id, title, chat_type, participant_count: Some(participants), topic: None },
+            members,
+            last: None,
+        }
+    }
+
+    /// The chat's title, or the person a direct chat is with
+    fn title(&self, chat: usize) -> String {
+        let chat = &self.chats[chat];
+        chat.chat.title.clone().unwrap_or_else(|| {
+            chat.members
+                .first()
+                .and_then(|&persona| self.personas[persona].display_name.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    /// A new message in `chat` from one of its members, or now and then from me
+    fn write(&mut self, chat: usize, timestamp: DateTime<Utc>) -> Message {
+        let rng = &mut self.rng;
+        let mine_chance = match self.chats[chat].chat.chat_type {
+            ChatType::DirectMessage => 0.4,
+            ChatType::Channel => 0.0,
+            _ => 0.15,
+        };
+        let from_me = rng.chance(mine_chance);
+        let sender = if from_me { me() } else { self.personas[*rng.pick(&self.chats[chat].members)].clone() };
+
+        let replying = self.chats[chat].last.clone().filter(|_| rng.chance(0.2));
+        let text = match &replying {
+            Some(_) => rng.pick(&REPLIES).to_string(),
+            None => {
+                let topic = *rng.pick(&TOPICS);
+                rng.pick(&LINES).replace("{}", topic)
+            }
+        };
+        let content = media(rng, &self.media, text);
+        let mentions_me = !from_me && replying.as_ref().is_some_and(|(_, mine)| *mine);
+
+        let id = MessageId::new(self.next_id.to_string());
+        self.next_id += 1;
+        self.chats[chat].last = Some((id.clone(), from_me));
+        Message {
+            id,
+            chat_id: self.chats[chat].chat.id.clone(),
+            sender,
+            content,
+            timestamp,
+            reply_to: replying.map(|(id, _)| id),
+            edited: false,
+            mentions_me,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    /// The next live message: an answer to what I wrote, or more of the conversation
+    /// going on, or a message in another chat
+    fn next_live(&mut self, now: DateTime<Utc>) -> Message {
+        let answering = self.reply_due.take();
+        let chat = match answering {
+            Some(chat) => chat,
+            None if self.rng.chance(0.5) => self.current,
+            None => self.rng.below(self.chats.len()),
+        };
+        self.current = chat;
+
+        let mine = answering.and_then(|chat| self.chats[chat].last.clone()).map(|(id, _)| id);
+        let mut message = self.write(chat, now);
+        if message.sender.id.as_str() == "me" {
+            // Live messages come from the others; mine arrive through `send_message`
+            let persona = *self.rng.pick(&self.chats[chat].members);
+            message.sender = self.personas[persona].clone();
+            self.chats[chat].last = Some((message.id.clone(), false));
+        }
+        if let Some(mine) = mine {
+            message.reply_to = Some(mine);
+            message.mentions_me = true;
+        }
+        self.keep(message.clone());
+        message
+    }
+
+    /// Add a message, dropping the oldest live messages past `MAX_LIVE_MESSAGES`
+    fn keep(&mut self, message: Message) {
+        self.messages.push(message);
+        let limit = self.history_len + MAX_LIVE_MESSAGES;
+        if self.messages.len() > limit + MAX_LIVE_MESSAGES / 10 {
+            self.messages.drain(self.history_len..self.history_len + (self.messages.len() - limit));
+        }
+    }
+
+    fn chat_index(&self, chat_id: &ChatId) -> Option<usize> {
+        self.chats.iter().position(|chat| &chat.chat.id == chat_id)
+    }
+}
+
+/// Text, or now and then a photo, voice note or something else, as `mix` weighs them
+fn media(rng: &mut Rng, mix: &MediaMix, text: String) -> MessageContent {
+    let weights = [mix.text, mix.image, mix.video, mix.audio, mix.file, mix.sticker, mix.location];
+    let mut roll = rng.below(mix.total().max(1) as usize) as u32;
+    let kind = weights
+        .iter()
+        .position(|&weight| {
+            if roll < weight {
+                true
+            } else {
+                roll -= weight;
+                false
+            }
+        })
+        .unwrap_or(0);
+    let caption = |rng: &mut Rng| rng.chance(0.5).then(|| text.clone());
+
+    match kind {
+        1 => MessageContent::Image { caption: caption(rng), url: None },
+        2 => MessageContent::Video { caption: caption(rng), url: None },
+        3 => MessageContent::Audio { url: None },
+        4 => MessageContent::File { filename: Some(rng.pick(&FILES).to_string()), url: None },
+        5 => MessageContent::Sticker,
+        6 => MessageContent::Location {
+            latitude: 60.17 + (rng.unit() - 0.5) / 10.0,
+            longitude: 24.94 + (rng.unit() - 0.5) / 10.0,
+        },
+        _ => MessageContent::Text(text),
+    }
+}
+
+/// Chat source making up plausible chats and traffic (see the module docs)
+pub struct SimulatedSource {
+    id: String,
+    name: String,
+    messages_per_minute: u32,
+    simulation: Arc<Mutex<Simulation>>,
+    subscribers: Arc<Mutex<Vec<mpsc::Sender<Message>>>>,
+    generating: AtomicBool,
+    supervisor: Supervisor,
+}
+
+impl SimulatedSource {
+    pub fn new(settings: &SimulationSettings) -> Self {
+        Self {
+            id: settings.id.clone(),
+            name: settings.name.clone(),
+            messages_per_minute: settings.messages_per_minute,
+            simulation: Arc::new(Mutex::new(Simulation::new(settings, Utc::now()))),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            generating: AtomicBool::new(false),
+            supervisor: Supervisor::new(),
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Simulation>> {
+        self.simulation.lock().map_err(|e| anyhow::anyhow!("Failed to acquire simulation lock: {}", e))
+    }
+
+    /// Start writing live messages to the subscribers, unless it has started already
+    fn start_generator(&self) {
+        if self.generating.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let simulation = Arc::clone(&self.simulation);
+        let subscribers = Arc::clone(&self.subscribers);
+        let mean_gap = 60.0 / f64::from(self.messages_per_minute);
+        self.supervisor.spawn("generator", RestartPolicy::default(), move || {
+            Ok(generate(Arc::clone(&simulation), Arc::clone(&subscribers), mean_gap))
+        });
+    }
+}
+
+/// Write live messages `mean_gap_secs` apart on average, the gaps random as between
+/// people writing independently. Fast rates catch up several messages per wakeup.
+async fn generate(
+    simulation: Arc<Mutex<Simulation>>,
+    subscribers: Arc<Mutex<Vec<mpsc::Sender<Message>>>>,
+    mean_gap_secs: f64,
+) -> Result<()> {
+    let mut gaps = Rng(simulation.lock().map(|mut simulation| simulation.rng.next()).unwrap_or(1));
+    let mut next = tokio::time::Instant::now();
+    loop {
+        next += std::time::Duration::from_secs_f64(-(1.0 - gaps.unit()).ln() * mean_gap_secs);
+        tokio::time::sleep_until(next).await;
+
+        let message = simulation
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire simulation lock: {}", e))?
+            .next_live(Utc::now());
+        let senders = subscribers
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire subscriber lock: {}", e))?
+            .clone();
+        for sender in senders {
+            if sender.send(message.clone()).await.is_err() {
+                if let Ok(mut subscribers) = subscribers.lock() {
+                    subscribers.retain(|subscriber| !subscriber.same_channel(&sender));
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatSource for SimulatedSource {
+    fn source_id(&self) -> &str {
+        &self.id
+    }
+
+    fn source_name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            list_chats: true,
+            history: true,
+            streaming: self.messages_per_minute > 0,
+            media_download: false,
+            sending: true,
+            member_lists: false,
+            ..SourceCapabilities::default()
+        }
+    }
+
+    fn tasks(&self) -> Vec<TaskStatus> {
+        self.supervisor.statuses()
+    }
+
+    async fn shutdown(&self) {
+        self.supervisor.shutdown().await
+    }
+
+    async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+        let simulation = self.lock()?;
+        Ok(simulation
+            .chats
+            .iter()
+            .map(|chat| chat.chat.clone())
+            .filter(|chat| filter.as_ref().is_none_or(|f| f.matches(chat)))
+            .collect())
+    }
+
+    async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+        let simulation = self.lock()?;
+        let chats: Vec<&ChatId> = simulation
+            .chats
+            .iter()
+            .filter(|chat| filter.chat.matches(&chat.chat))
+            .map(|chat| &chat.chat.id)
+            .collect();
+        let in_chats = simulation.messages.iter().filter(|message| chats.contains(&&message.chat_id)).cloned();
+        Ok(apply_filter(in_chats, &filter))
+    }
+
+    async fn send_message(&self, chat_id: &ChatId, text: &str, reply_to: Option<&MessageId>) -> Result<Message> {
+        let message = {
+            let mut simulation = self.lock()?;
+            let chat = simulation
+                .chat_index(chat_id)
+                .ok_or_else(|| anyhow::anyhow!("No simulated chat with ID '{}'", chat_id))?;
+            if simulation.chats[chat].chat.chat_type == ChatType::Channel {
+                anyhow::bail!("Only the author can post in '{}'", simulation.title(chat));
+            }
+
+            let id = MessageId::new(simulation.next_id.to_string());
+            simulation.next_id += 1;
+            simulation.chats[chat].last = Some((id.clone(), true));
+            simulation.reply_due = Some(chat);
+            let message = Message {
+                id,
+                chat_id: chat_id.clone(),
+                sender: me(),
+                content: MessageContent::Text(text.to_string()),
+                timestamp: Utc::now(),
+                reply_to: reply_to.cloned(),
+                edited: false,
+                mentions_me: false,
+                pending: false,
+                language: None,
+                media_text: None,
+                original_timestamp: None,
+            };
+            simulation.keep(message.clone());
+            message
+        };
+        Ok(message)
+    }
+
+    async fn subscribe(&self) -> Result<Option<mpsc::Receiver<Message>>> {
+        if self.messages_per_minute == 0 {
+            return Ok(None);
+        }
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        self.subscribers
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire subscriber lock: {}", e))?
+            .push(tx);
+        self.start_generator();
+        Ok(Some(rx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(seed: u64) -> SimulationSettings {
+        SimulationSettings { seed, chats: 10, history_per_chat: 20, ..SimulationSettings::default() }
+    }
+
+    async fn all(source: &SimulatedSource) -> Vec<Message> {
+        source.get_messages(MessageFilter { limit: None, ..MessageFilter::new() }).await.unwrap()
+    }
+
+    fn summary(messages: &[Message]) -> Vec<(String, String, String)> {
+        messages
+            .iter()
+            .map(|m| (m.id.as_str().to_string(), m.sender.id.as_str().to_string(), m.content.kind().to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_same_seed_same_history() {
+        let first = SimulatedSource::new(&settings(7));
+        let second = SimulatedSource::new(&settings(7));
+        let other = SimulatedSource::new(&settings(8));
+
+        let chats = first.list_chats(None).await.unwrap();
+        assert_eq!(chats.len(), 10);
+        let types: Vec<ChatType> = chats.iter().take(5).map(|chat| chat.chat_type.clone()).collect();
+        assert_eq!(
+            types,
+            [ChatType::DirectMessage, ChatType::DirectMessage, ChatType::Group, ChatType::Group, ChatType::Channel]
+        );
+        assert_eq!(chats[2].title.as_deref(), Some("Weekend Hiking"));
+        assert_eq!(chats[7].title.as_deref(), Some("Dev Team"));
+
+        let messages = all(&first).await;
+        assert_eq!(messages.len(), 200);
+        assert_eq!(summary(&messages), summary(&all(&second).await));
+        assert_ne!(summary(&messages), summary(&all(&other).await));
+        assert!(messages.iter().any(|m| m.reply_to.is_some()));
+
+        // Nobody but the author writes in a channel
+        let channel = first.get_messages(MessageFilter::for_chat_id(chats[4].id.clone())).await.unwrap();
+        assert_eq!(channel.len(), 20);
+        assert!(channel.iter().all(|m| m.sender.id == channel[0].sender.id && m.sender.id.as_str() != "me"));
+    }
+
+    #[tokio::test]
+    async fn test_media_mix() {
+        let stickers = SimulationSettings {
+            media: MediaMix { text: 0, image: 0, video: 0, audio: 0, file: 0, sticker: 1, location: 0 },
+            ..settings(1)
+        };
+        let messages = SimulatedSource::new(&stickers).get_messages(MessageFilter::new()).await.unwrap();
+        assert!(messages.iter().all(|m| matches!(m.content, MessageContent::Sticker)));
+
+        let messages = SimulatedSource::new(&settings(1)).get_messages(MessageFilter::new()).await.unwrap();
+        let texts = messages.iter().filter(|m| matches!(m.content, MessageContent::Text(_))).count();
+        assert!(texts > messages.len() / 2 && texts < messages.len());
+    }
+
+    #[tokio::test]
+    async fn test_stream_and_replies() {
+        let source = SimulatedSource::new(&SimulationSettings { messages_per_minute: 600_000, ..settings(3) });
+        assert!(source.capabilities().streaming);
+
+        let chat = ChatId::new("1");
+        let sent = source.send_message(&chat, "Lunch?", None).await.unwrap();
+        let mut stream = source.subscribe().await.unwrap().unwrap();
+
+        // The first live message answers mine
+        let answer = tokio::time::timeout(std::time::Duration::from_secs(5), stream.recv()).await.unwrap().unwrap();
+        assert_eq!(answer.chat_id, chat);
+        assert_ne!(answer.sender.id.as_str(), "me");
+        assert_eq!(answer.reply_to.as_ref(), Some(&sent.id));
+        assert!(answer.mentions_me);
+
+        for _ in 0..50 {
+            tokio::time::timeout(std::time::Duration::from_secs(5), stream.recv()).await.unwrap().unwrap();
+        }
+        let history = source.get_messages(MessageFilter::for_chat_id(chat)).await.unwrap();
+        assert!(history.iter().any(|m| m.id == sent.id));
+        assert!(history.iter().any(|m| m.id == answer.id));
+        assert!(source.tasks().iter().all(|task| task.is_healthy()));
+
+        source.shutdown().await;
+        assert!(source.tasks().is_empty());
+
+        let quiet = SimulatedSource::new(&SimulationSettings { messages_per_minute: 0, ..settings(3) });
+        assert!(quiet.subscribe().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Canned replies, by name
+///
+/// A snippet's text may contain placeholders like `{sender}` or `{date}`, filled in
+/// with [`SnippetStore::render`]. Placeholders without a value are left as they are,
+/// so they stand out in the draft. Names are case-insensitive and stored lowercase.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnippetStore {
+    #[serde(default)]
+    snippets: BTreeMap<String, String>,
+}
+
+impl SnippetStore {
+    /// Create an empty snippet store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the snippets file (`~/.config/chat/snippets.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("snippets.json"))
+    }
+
+    /// Load snippets from the default location, returning an empty store if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load snippets from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read snippets file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse snippets file")
+    }
+
+    /// Save snippets to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save snippets to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create snippets directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize snippets")?;
+
+        std::fs::write(path, contents).context("Failed to write snippets file")
+    }
+
+    fn normalize(name: &str) -> String {
+        name.trim().to_lowercase()
+    }
+
+    /// Add or replace a snippet. Returns true if one with that name was replaced.
+    pub fn set(&mut self, name: &str, text: &str) -> Result<bool> {
+        let name = Self::normalize(name);
+        if name.is_empty() {
+            anyhow::bail!("Snippet name is empty");
+        }
+
+        Ok(self.snippets.insert(name, text.to_string()).is_some())
+    }
+
+    /// Remove a snippet. Returns false if there was none with that name.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.snippets.remove(&Self::normalize(name)).is_some()
+    }
+
+    /// Text of a snippet, placeholders unfilled
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.snippets.get(&Self::normalize(name)).map(String::as_str)
+    }
+
+    /// All snippets as (name, text), sorted by name
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.snippets
+            .iter()
+            .map(|(name, text)| (name.clone(), text.clone()))
+            .collect()
+    }
+
+    /// Fill in a snippet's placeholders; `None` if there is no such snippet
+    pub fn render(&self, name: &str, values: &HashMap<String, String>) -> Option<String> {
+        self.get(name).map(|text| render(text, values))
+    }
+}
+
+/// Placeholders in `text`, in order of first appearance
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for (_, name) in scan(text) {
+        if !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// `text` with `{name}` replaced by `values[name]` where there is one
+pub fn render(text: &str, values: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = 0;
+    for (start, name) in scan(text) {
+        if let Some(value) = values.get(name) {
+            rendered.push_str(&text[rest..start]);
+            rendered.push_str(value);
+            rest = start + name.len() + 2;
+        }
+    }
+    rendered.push_str(&text[rest..]);
+    rendered
+}
+
+/// `{name}` placeholders in `text` as (byte offset of `{`, name). Names are letters,
+/// digits, `_` and `-`, so JSON or code in a snippet is left alone.
+fn scan(text: &str) -> Vec<(usize, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find('{').map(|i| from + i) {
+        let after = &text[open + 1..];
+        let len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(after.len());
+        if len > 0 && after[len..].starts_with('}') {
+            found.push((open, &after[..len]));
+            from = open + len + 2;
+        } else {
+            from = open + 1;
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_remove() {
+        let mut store = SnippetStore::new();
+        assert!(!store.set("Thanks", "Thanks {sender}!").unwrap());
+        assert!(store.set("thanks", "Thank you, {sender}!").unwrap());
+        assert!(store.set("  ", "x").is_err());
+        assert_eq!(store.get("THANKS"), Some("Thank you, {sender}!"));
+
+        let path = std::env::temp_dir().join(format!("snippets-{}.json", uuid::Uuid::new_v4().simple()));
+        store.save_to(&path).unwrap();
+        let mut loaded = SnippetStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.entries(), vec![("thanks".to_string(), "Thank you, {sender}!".to_string())]);
+        assert!(loaded.remove("thanks"));
+        assert!(!loaded.remove("thanks"));
+    }
+
+    #[test]
+    fn test_render() {
+        let text = "Hi {sender}, {sender} - see you {date}. {\"json\": 1} {unknown} {}";
+        assert_eq!(placeholders(text), vec!["sender", "date", "unknown"]);
+
+        let values: HashMap<String, String> = [("sender", "Ana"), ("date", "Monday")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(render(text, &values), "Hi Ana, Ana - see you Monday. {\"json\": 1} {unknown} {}");
+        assert_eq!(render("{{sender}}", &values), "{Ana}");
+    }
+}
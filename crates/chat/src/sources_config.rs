@@ -0,0 +1,394 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Source settings (`~/.config/chat/sources.toml`): global switches plus
+/// per-source limits under `[sources.<id>]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcesConfig {
+    /// Never send, draft or mark anything read, whatever the sources support
+    #[serde(default)]
+    pub read_only: bool,
+    /// Limits per source ID; sources without an entry use the defaults
+    #[serde(default)]
+    pub sources: BTreeMap<String, SourceSettings>,
+    /// `chat self-update` settings, under `[updates]`
+    #[serde(default)]
+    pub updates: UpdateSettings,
+    /// Guardrails on MCP tool calls, under `[mcp]`
+    #[serde(default)]
+    pub mcp: McpSettings,
+    /// Sources of made-up chats and messages, one per `[[simulated]]` table
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub simulated: Vec<SimulationSettings>,
+}
+
+/// Where updates come from and whether to look for them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateSettings {
+    /// Look for new releases with `chat self-update --check` and in the dashboard;
+    /// `chat self-update` still installs when run by hand
+    pub check: bool,
+    /// GitHub repository the releases are published in, as "owner/name"
+    pub repository: String,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            check: true,
+            repository: crate::self_update::DEFAULT_REPOSITORY.to_string(),
+        }
+    }
+}
+
+/// How much one MCP tool call may ask for, so agents get told to narrow a request
+/// instead of pulling a whole history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct McpSettings {
+    /// Messages a call returns when it gives no limit
+    pub default_limit: usize,
+    /// Largest limit a call may ask for
+    pub max_limit: usize,
+    /// Longest time range a call may cover, in days
+    pub max_range_days: u32,
+    /// Calls each tool takes per minute; 0 turns the quota off
+    pub calls_per_minute: u32,
+    /// Per-tool quotas overriding `calls_per_minute`, under `[mcp.quotas]`
+    pub quotas: BTreeMap<String, u32>,
+}
+
+impl Default for McpSettings {
+    fn default() -> Self {
+        Self {
+            default_limit: 100,
+            max_limit: 1000,
+            max_range_days: 365,
+            calls_per_minute: 60,
+            quotas: BTreeMap::new(),
+        }
+    }
+}
+
+impl McpSettings {
+    /// Calls a tool takes per minute, `None` without a quota
+    pub fn quota(&self, tool: &str) -> Option<u32> {
+        Some(self.quotas.get(tool).copied().unwrap_or(self.calls_per_minute)).filter(|quota| *quota > 0)
+    }
+
+    /// Check the settings are usable
+    pub fn validate(&self) -> Result<()> {
+        if self.default_limit == 0 {
+            anyhow::bail!("default_limit must be at least 1");
+        }
+        if self.max_limit < self.default_limit {
+            anyhow::bail!("max_limit ({}) must be at least default_limit ({})", self.max_limit, self.default_limit);
+        }
+        if self.max_range_days == 0 {
+            anyhow::bail!("max_range_days must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+/// A simulated source: plausible chats, a history and an ongoing stream of messages
+/// between made-up people, for demos, UI work and load tests (see
+/// [`crate::simulated_source`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationSettings {
+    /// Source ID, unique among the sources
+    pub id: String,
+    pub name: String,
+    /// The same seed makes the same chats and history
+    pub seed: u64,
+    pub chats: usize,
+    /// Messages of history each chat starts with, spread over `history_days`
+    pub history_per_chat: usize,
+    pub history_days: u32,
+    /// Live messages a minute, across all chats; 0 turns streaming off
+    pub messages_per_minute: u32,
+    /// Names of the people writing
+    pub personas: Vec<String>,
+    /// How often each kind of message comes up, under `[simulated.media]`
+    pub media: MediaMix,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            id: "simulated".to_string(),
+            name: "Simulated".to_string(),
+            seed: 1,
+            chats: 12,
+            history_per_chat: 50,
+            history_days: 7,
+            messages_per_minute: 6,
+            personas: ["Alice", "Bob", "Carol", "Dave", "Erin", "Frank", "Grace", "Heidi"]
+                .map(String::from)
+                .to_vec(),
+            media: MediaMix::default(),
+        }
+    }
+}
+
+impl SimulationSettings {
+    /// Check the settings are usable
+    pub fn validate(&self) -> Result<()> {
+        if self.id.trim().is_empty() {
+            anyhow::bail!("id must not be empty");
+        }
+        if !(1..=10_000).contains(&self.chats) {
+            anyhow::bail!("chats must be between 1 and 10000, got {}", self.chats);
+        }
+        if self.chats.saturating_mul(self.history_per_chat) > 1_000_000 {
+            anyhow::bail!("chats × history_per_chat must be at most 1000000");
+        }
+        if self.history_days == 0 {
+            anyhow::bail!("history_days must be at least 1");
+        }
+        if self.messages_per_minute > 600_000 {
+            anyhow::bail!("messages_per_minute must be at most 600000, got {}", self.messages_per_minute);
+        }
+        if self.personas.iter().all(|persona| persona.trim().is_empty()) {
+            anyhow::bail!("personas must name at least one person");
+        }
+        if self.media.total() == 0 {
+            anyhow::bail!("media must give at least one kind of message a weight");
+        }
+        Ok(())
+    }
+}
+
+/// Relative weights of the kinds of message a simulated source writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MediaMix {
+    pub text: u32,
+    pub image: u32,
+    pub video: u32,
+    pub audio: u32,
+    pub file: u32,
+    pub sticker: u32,
+    pub location: u32,
+}
+
+impl Default for MediaMix {
+    fn default() -> Self {
+        Self { text: 85, image: 6, video: 1, audio: 3, file: 2, sticker: 2, location: 1 }
+    }
+}
+
+impl MediaMix {
+    pub fn total(&self) -> u32 {
+        self.text + self.image + self.video + self.audio + self.file + self.sticker + self.location
+    }
+}
+
+/// How much work a source may do per request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceSettings {
+    /// Messages fetched per chat when a query gives no limit
+    pub max_messages_per_chat: usize,
+    /// Seconds allowed for fetching one chat before giving up on it
+    pub timeout_secs: u64,
+    /// Chats fetched at the same time
+    pub max_parallel_fetches: usize,
+    /// Messages a live subscription buffers before its overflow policy applies
+    pub subscription_buffer: usize,
+    /// Seconds the source's clock runs ahead (negative when behind), taken off its
+    /// timestamps instead of the offset estimated from live messages; 0 turns correction off
+    pub clock_offset_secs: Option<i64>,
+}
+
+impl Default for SourceSettings {
+    fn default() -> Self {
+        Self {
+            max_messages_per_chat: 1000,
+            timeout_secs: 60,
+            max_parallel_fetches: 4,
+            subscription_buffer: 1024,
+            clock_offset_secs: None,
+        }
+    }
+}
+
+impl SourceSettings {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// The configured clock offset, if any
+    pub fn clock_offset(&self) -> Option<chrono::Duration> {
+        self.clock_offset_secs.map(chrono::Duration::seconds)
+    }
+
+    /// Check the settings are usable
+    pub fn validate(&self) -> Result<()> {
+        if self.max_messages_per_chat == 0 {
+            anyhow::bail!("max_messages_per_chat must be at least 1");
+        }
+        if !(1..=3600).contains(&self.timeout_secs) {
+            anyhow::bail!("timeout_secs must be between 1 and 3600, got {}", self.timeout_secs);
+        }
+        if !(1..=32).contains(&self.max_parallel_fetches) {
+            anyhow::bail!("max_parallel_fetches must be between 1 and 32, got {}", self.max_parallel_fetches);
+        }
+        if self.subscription_buffer == 0 {
+            anyhow::bail!("subscription_buffer must be at least 1");
+        }
+        if let Some(offset) = self.clock_offset_secs.filter(|offset| offset.abs() > 86_400) {
+            anyhow::bail!("clock_offset_secs must be within a day, got {}", offset);
+        }
+        Ok(())
+    }
+}
+
+impl SourcesConfig {
+    /// Default location of the sources config
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("sources.toml"))
+    }
+
+    /// Load from the default location, returning the defaults if there is no file
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Self = toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+        config.validate().with_context(|| format!("Invalid settings in {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Check every source's settings
+    pub fn validate(&self) -> Result<()> {
+        for (source_id, settings) in &self.sources {
+            settings.validate().with_context(|| format!("[sources.{}]", source_id))?;
+        }
+        self.mcp.validate().context("[mcp]")?;
+
+        let mut ids = std::collections::BTreeSet::new();
+        for simulation in &self.simulated {
+            simulation.validate().with_context(|| format!("[[simulated]] '{}'", simulation.id))?;
+            if !ids.insert(simulation.id.as_str()) {
+                anyhow::bail!("[[simulated]]: the source ID '{}' is used twice", simulation.id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Settings for a source, the defaults if it has none
+    pub fn source(&self, source_id: &str) -> SourceSettings {
+        self.sources.get(source_id).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config: SourcesConfig = toml::from_str("read_only = true\n").unwrap();
+        assert!(config.read_only);
+
+        let config: SourcesConfig = toml::from_str("").unwrap();
+        assert!(!config.read_only);
+        assert!(config.updates.check);
+
+        let config: SourcesConfig = toml::from_str("[updates]\ncheck = false\n").unwrap();
+        assert!(!config.updates.check);
+        assert_eq!(config.updates.repository, UpdateSettings::default().repository);
+
+        let missing = std::env::temp_dir().join("chat-sources-config-missing.toml");
+        assert_eq!(SourcesConfig::load_from(&missing).unwrap(), SourcesConfig::default());
+    }
+
+    #[test]
+    fn test_source_settings() {
+        let config: SourcesConfig = toml::from_str(
+            "[sources.telegram]\nmax_messages_per_chat = 200\ntimeout_secs = 15\n",
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let telegram = config.source("telegram");
+        assert_eq!(telegram.max_messages_per_chat, 200);
+        assert_eq!(telegram.timeout(), Duration::from_secs(15));
+        // Unset fields and unlisted sources keep the defaults
+        assert_eq!(telegram.max_parallel_fetches, SourceSettings::default().max_parallel_fetches);
+        assert_eq!(config.source("whatsapp"), SourceSettings::default());
+        assert_eq!(telegram.clock_offset(), None);
+
+        let config: SourcesConfig = toml::from_str("[sources.bridge]
+clock_offset_secs = -90
+").unwrap();
+        assert_eq!(config.source("bridge").clock_offset(), Some(chrono::Duration::seconds(-90)));
+    }
+
+    #[test]
+    fn test_mcp_settings() {
+        let config: SourcesConfig = toml::from_str("[mcp]\nmax_limit = 500\n\n[mcp.quotas]\nsemantic_search = 5\nlist_sources = 0\n").unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.mcp.max_limit, 500);
+        assert_eq!(config.mcp.default_limit, McpSettings::default().default_limit);
+        assert_eq!(config.mcp.quota("semantic_search"), Some(5));
+        assert_eq!(config.mcp.quota("get_messages"), Some(60));
+        assert_eq!(config.mcp.quota("list_sources"), None);
+
+        let config: SourcesConfig = toml::from_str("[mcp]\ndefault_limit = 200\nmax_limit = 100\n").unwrap();
+        assert!(format!("{:#}", config.validate().unwrap_err()).contains("[mcp]"));
+    }
+
+    #[test]
+    fn test_simulated() {
+        let config: SourcesConfig = toml::from_str("[[simulated]]\nseed = 7\n\n[simulated.media]\ntext = 1\nimage = 1\n\n[[simulated]]\nid = \"load\"\nmessages_per_minute = 6000\n").unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.simulated.len(), 2);
+        assert_eq!(config.simulated[0].id, "simulated");
+        assert_eq!(config.simulated[0].media, MediaMix { text: 1, image: 1, ..MediaMix::default() });
+        assert_eq!(config.simulated[1].messages_per_minute, 6000);
+        assert_eq!(config.simulated[1].personas, SimulationSettings::default().personas);
+
+        let config: SourcesConfig = toml::from_str("[[simulated]]\n\n[[simulated]]\nseed = 2\n").unwrap();
+        assert!(format!("{:#}", config.validate().unwrap_err()).contains("used twice"));
+
+        let config: SourcesConfig = toml::from_str("[[simulated]]\npersonas = []\n").unwrap();
+        assert!(format!("{:#}", config.validate().unwrap_err()).contains("personas"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let config: SourcesConfig = toml::from_str("[sources.telegram]\nmax_parallel_fetches = 0\n").unwrap();
+        let error = format!("{:#}", config.validate().unwrap_err());
+        assert!(error.contains("[sources.telegram]"));
+        assert!(error.contains("max_parallel_fetches"));
+
+        let config: SourcesConfig = toml::from_str("[sources.bridge]
+clock_offset_secs = 100000
+").unwrap();
+        assert!(config.validate().is_err());
+
+        let path = std::env::temp_dir().join(format!("chat-sources-config-{}.toml", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&path, "[sources.telegram]\ntimeout_secs = 0\n").unwrap();
+        let loaded = SourcesConfig::load_from(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(loaded.is_err());
+    }
+}
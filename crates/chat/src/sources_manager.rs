@@ -1,25 +1,358 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
-
-use crate::types::{ChatFilter, ChatSource, Message, MessageFilter, SourceInfo};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::channel_stats::ChannelPosts;
+use crate::clock::{self, ClockSkew};
+use crate::events::{login_command, EventBus, SourceEventKind};
+use crate::global_id::GlobalChatId;
+use crate::labels::LabelStore;
+use crate::language;
+use crate::media_text::MediaTextStore;
+use crate::merge;
+use crate::mentions::MyAccounts;
+use crate::merged_chats::MergedChats;
+use crate::progress::{Progress, ProgressEvent};
+use crate::query_plan::{Criterion, QueryPlan, ATTACHMENT_TYPES};
+use crate::sources_config::{SourceSettings, SourcesConfig};
+use crate::subscription::{BufferConfig, Subscription, SubscriptionMonitor, SubscriptionStats};
+use crate::types::{
+    apply_filter, Capability, ChatFilter, ChatId, ChatPattern, ChatSource, ContentType, Message, MessageFilter, MessageId,
+    SourceCapabilities, SourceInfo,
+};
 
 /// Manager for multiple chat sources
+///
+/// Sources are cloned out of the lock before anything is awaited on them, so no lock is
+/// held across an await and the manager's futures are `Send`.
 pub struct SourcesManager {
-    sources: Arc<RwLock<HashMap<String, Box<dyn ChatSource>>>>,
+    sources: Arc<RwLock<HashMap<String, Arc<dyn ChatSource>>>>,
+    labels: Arc<RwLock<LabelStore>>,
+    /// Chats queried and shown as one conversation
+    merged: Arc<RwLock<MergedChats>>,
+    accounts: Arc<RwLock<MyAccounts>>,
+    /// Text derived from image and audio attachments, attached to messages and searched
+    media_text: Arc<RwLock<MediaTextStore>>,
+    subscriptions: Arc<RwLock<Vec<(String, SubscriptionMonitor)>>>,
+    events: EventBus,
+    /// Writes are refused whatever the sources support
+    read_only: Arc<AtomicBool>,
+    /// Per-source limits handed to sources as they are registered
+    config: Arc<RwLock<SourcesConfig>>,
+    /// How far each source's clock is off, taken off its message timestamps
+    clock: Arc<RwLock<ClockSkew>>,
 }
 
 impl SourcesManager {
     /// Create a new empty sources manager
     pub fn new() -> Self {
+        Self::with_labels(LabelStore::new())
+    }
+
+    /// Create a new empty sources manager using the given chat labels
+    pub fn with_labels(labels: LabelStore) -> Self {
         Self {
             sources: Arc::new(RwLock::new(HashMap::new())),
+            labels: Arc::new(RwLock::new(labels)),
+            merged: Arc::new(RwLock::new(MergedChats::new())),
+            accounts: Arc::new(RwLock::new(MyAccounts::new())),
+            media_text: Arc::new(RwLock::new(MediaTextStore::new())),
+            subscriptions: Arc::new(RwLock::new(Vec::new())),
+            events: EventBus::new(),
+            read_only: Arc::new(AtomicBool::new(false)),
+            config: Arc::new(RwLock::new(SourcesConfig::default())),
+            clock: Arc::new(RwLock::new(ClockSkew::new())),
+        }
+    }
+
+    /// Replace the chat labels used for label filters
+    pub fn set_labels(&self, labels: LabelStore) -> Result<()> {
+        let mut current = self.labels.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        *current = labels;
+        Ok(())
+    }
+
+    /// Get a copy of the chat labels
+    pub fn labels(&self) -> Result<LabelStore> {
+        let labels = self.labels.read()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+        Ok(labels.clone())
+    }
+
+    /// Replace the chats merged into one conversation
+    pub fn set_merged_chats(&self, merged: MergedChats) -> Result<()> {
+        let mut current = self.merged.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        *current = merged;
+        Ok(())
+    }
+
+    /// The chat a chat's messages are shown under: the newest chat merged with it, or itself
+    pub fn merged_into(&self, source_id: &str, chat_id: &ChatId) -> Result<ChatId> {
+        let merged = self.merged.read()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+        Ok(merged.primary(source_id, chat_id).unwrap_or_else(|| chat_id.clone()))
+    }
+
+    /// Replace my per-source handles used for mention detection
+    pub fn set_accounts(&self, accounts: MyAccounts) -> Result<()> {
+        let mut current = self.accounts.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        *current = accounts;
+        Ok(())
+    }
+
+    /// Replace the per-source settings and hand them to every registered source
+    pub fn set_sources_config(&self, config: SourcesConfig) -> Result<()> {
+        let mut sources = self.sources.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        for (source_id, source) in sources.iter_mut() {
+            match Arc::get_mut(source) {
+                Some(source) => source.configure(config.source(source_id)),
+                None => log::warn!("Source '{}' is busy; its new settings apply once it is registered again", source_id),
+            }
+        }
+        drop(sources);
+
+        let mut current = self.config.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        let mut clock = self.clock.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        for source_id in current.sources.keys().chain(config.sources.keys()) {
+            clock.set_fixed(source_id, config.source(source_id).clock_offset());
+        }
+        *current = config;
+        Ok(())
+    }
+
+    /// How far a source's clock is ahead (negative when behind), when its timestamps
+    /// are being corrected
+    pub fn clock_skew(&self, source_id: &str) -> Result<Option<chrono::Duration>> {
+        let clock = self.clock.read()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+        Ok(clock.skew(source_id))
+    }
+
+    /// Settings of a source, the defaults if sources.toml has none for it
+    pub fn source_settings(&self, source_id: &str) -> Result<SourceSettings> {
+        let config = self.config.read()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+        Ok(config.source(source_id))
+    }
+
+    /// Replace the text derived from image and audio attachments
+    pub fn set_media_text(&self, store: MediaTextStore) -> Result<()> {
+        let mut current = self.media_text.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        *current = store;
+        Ok(())
+    }
+
+    /// Fetch messages from one source as planned and hold them to the plan's checks.
+    ///
+    /// Sources only search what senders wrote, so when the source searches and has images,
+    /// voice notes or documents with derived text, the attachments matching the rest of the
+    /// filter are fetched too and their derived text searched here.
+    ///
+    /// Timestamps are corrected for the source's clock skew, and messages sharing one are
+    /// ordered by ID.
+    async fn fetch(
+        &self,
+        source_id: &str,
+        source: &dyn ChatSource,
+        plan: &QueryPlan,
+        progress: &Progress,
+    ) -> Result<Vec<Message>> {
+        let mut messages = source.get_messages_with_progress(plan.source.clone(), progress).await?;
+
+        if let Some(media_filter) = self.media_search_filter(source_id, plan)? {
+            let mut seen: HashSet<(ChatId, MessageId)> =
+                messages.iter().map(|m| (m.chat_id.clone(), m.id.clone())).collect();
+            for attachment in source.get_messages_with_progress(media_filter, progress).await? {
+                if seen.insert((attachment.chat_id.clone(), attachment.id.clone())) {
+                    messages.push(attachment);
+                }
+            }
+        }
+
+        let media_text = self.media_text.read()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+        media_text.annotate(source_id, &mut messages);
+
+        let mut clock = self.clock.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        clock.observe_future(source_id, &messages, Utc::now());
+        clock.normalize(source_id, &mut messages);
+        messages.sort_by(|a, b| clock::newest_first(source_id, a, source_id, b));
+
+        // Check what the source left out, and hold every source to the same semantics
+        Ok(apply_filter(messages, &plan.check))
+    }
+
+    /// The filter for attachments whose derived text may match a search the source ran
+    /// itself, if one is needed. Without the search, or with `in_attachments`, the source
+    /// query already returns them.
+    fn media_search_filter(&self, source_id: &str, plan: &QueryPlan) -> Result<Option<MessageFilter>> {
+        if !plan.pushed.contains(&Criterion::Search) {
+            return Ok(None);
+        }
+        let content_types: Vec<ContentType> = ATTACHMENT_TYPES
+            .into_iter()
+            .filter(|t| plan.check.content_type.as_ref().is_none_or(|types| types.contains(t)))
+            .collect();
+        let media_text = self.media_text.read()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+        if content_types.is_empty() || !media_text.has_source(source_id) {
+            return Ok(None);
         }
+
+        // Derived text is attached after the source query, so sources can't match on it or
+        // cut to the limit before it is searched
+        Ok(Some(MessageFilter {
+            search: None,
+            in_attachments: false,
+            content_type: Some(content_types),
+            limit: None,
+            ..plan.source.clone()
+        }))
+    }
+
+    /// Turn read-only mode on or off. In read-only mode sources report no sending,
+    /// drafting or mark-read capabilities and `require` refuses them.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Relaxed);
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    fn effective_capabilities(&self, source: &dyn ChatSource) -> SourceCapabilities {
+        let capabilities = source.capabilities();
+        if self.is_read_only() {
+            capabilities.read_only()
+        } else {
+            capabilities
+        }
+    }
+
+    /// Error unless a source exists and supports `capability`, with writes refused in read-only mode.
+    /// Anything that sends, drafts or marks read must check this before calling the source.
+    pub fn require(&self, source_id: &str, capability: Capability) -> Result<()> {
+        if capability.is_write() && self.is_read_only() {
+            anyhow::bail!("Read-only mode: {} is disabled for source '{}'", capability, source_id);
+        }
+
+        let source = self.source(source_id)?;
+        self.effective_capabilities(source.as_ref()).require(source_id, capability)
+    }
+
+    /// A registered source, cloned out of the lock so the lock isn't held while it's awaited
+    fn source(&self, source_id: &str) -> Result<Arc<dyn ChatSource>> {
+        let sources = self.sources.read()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+        sources.get(source_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Source '{}' not found", source_id))
+    }
+
+    /// Plan a query for a source: labels become chat IDs, merged chats are queried together,
+    /// and the criteria the source can't apply itself, like mentions of my handles, are left to be checked here
+    fn plan(&self, source_id: &str, source: &dyn ChatSource, filter: &MessageFilter) -> Result<QueryPlan> {
+        let mut resolved = self.resolve_labels(source_id, filter)?;
+
+        // Sources only see canonical chat ids
+        let canonical = |id: &ChatId| GlobalChatId::from_chat_id(source_id, id).chat_id();
+        resolved.chat = match resolved.chat {
+            ChatPattern::Id(id) => ChatPattern::Id(canonical(&id)),
+            ChatPattern::Multiple(ids) => ChatPattern::Multiple(ids.iter().map(canonical).collect()),
+            ChatPattern::Topic { group, topic } => match *group {
+                ChatPattern::Id(id) => ChatPattern::Topic { group: Box::new(ChatPattern::Id(canonical(&id))), topic },
+                group => ChatPattern::Topic { group: Box::new(group), topic },
+            },
+            other => other,
+        };
+
+        if !filter.separate_merged {
+            let merged = self.merged.read()
+                .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+            resolved.chat = match resolved.chat {
+                ChatPattern::Id(id) => match merged.expand(source_id, std::slice::from_ref(&id)) {
+                    ids if ids.len() > 1 => ChatPattern::Multiple(ids),
+                    _ => ChatPattern::Id(id),
+                },
+                ChatPattern::Multiple(ids) => ChatPattern::Multiple(merged.expand(source_id, &ids)),
+                other => other,
+            };
+        }
+
+        let plan = QueryPlan::new(&source.capabilities(), &resolved);
+        log::debug!("Query plan for '{}': {}", source_id, plan);
+        Ok(plan)
+    }
+
+    /// Flag messages mentioning my handles and detect their language, move messages of
+    /// merged chats to the newest of them, then apply the mentions and language filters
+    /// and the limit
+    fn annotate(&self, source_id: &str, filter: &MessageFilter, mut messages: Vec<Message>) -> Result<Vec<Message>> {
+        if !filter.separate_merged {
+            let merged = self.merged.read()
+                .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+            if !merged.is_empty() {
+                for message in &mut messages {
+                    if let Some(primary) = merged.primary(source_id, &message.chat_id) {
+                        message.chat_id = primary;
+                    }
+                }
+            }
+        }
+
+        let accounts = self.accounts.read()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+
+        for message in &mut messages {
+            if !message.mentions_me && accounts.is_mentioned(source_id, message) {
+                message.mentions_me = true;
+            }
+        }
+
+        if filter.mentions_me {
+            messages.retain(|m| m.mentions_me);
+        }
+
+        language::tag(&mut messages);
+        if filter.language.is_some() {
+            messages.retain(|m| filter.matches_language(m));
+        }
+        if let Some(limit) = filter.limit {
+            messages.truncate(limit);
+        }
+
+        Ok(messages)
+    }
+
+    /// Resolve a label chat pattern into the concrete chat IDs for a source
+    fn resolve_labels(&self, source_id: &str, filter: &MessageFilter) -> Result<MessageFilter> {
+        let mut resolved = filter.clone();
+
+        if let ChatPattern::Label(label) = &filter.chat {
+            let labels = self.labels.read()
+                .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+            resolved.chat = ChatPattern::Multiple(labels.chats_with_label(source_id, label));
+        }
+
+        Ok(resolved)
     }
 
     /// Register a new source
-    pub fn register(&self, source: Box<dyn ChatSource>) -> Result<()> {
+    pub fn register(&self, mut source: Box<dyn ChatSource>) -> Result<()> {
         let source_id = source.source_id().to_string();
+        source.configure(self.source_settings(&source_id)?);
 
         let mut sources = self.sources.write()
             .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
@@ -28,7 +361,28 @@ impl SourcesManager {
             anyhow::bail!("Source '{}' is already registered", source_id);
         }
 
-        sources.insert(source_id, source);
+        let connected = source.is_connected();
+        sources.insert(source_id.clone(), Arc::from(source));
+        drop(sources);
+
+        self.events.set_connected(&source_id, connected);
+        Ok(())
+    }
+
+    /// Replace every registered source with a wrapped version of itself
+    pub fn wrap_sources<F>(&self, wrap: F) -> Result<()>
+    where
+        F: Fn(Arc<dyn ChatSource>) -> Arc<dyn ChatSource>,
+    {
+        let mut sources = self.sources.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+
+        let wrapped = sources
+            .drain()
+            .map(|(id, source)| (id, wrap(source)))
+            .collect();
+        *sources = wrapped;
+
         Ok(())
     }
 
@@ -40,10 +394,27 @@ impl SourcesManager {
         if sources.remove(source_id).is_none() {
             anyhow::bail!("Source '{}' not found", source_id);
         }
+        drop(sources);
 
+        self.events.emit(source_id, SourceEventKind::Disconnected { reason: Some("removed".to_string()) });
+        self.events.forget(source_id);
         Ok(())
     }
 
+    /// Unregister every source and stop its background tasks. Call it when done with
+    /// the manager, after whatever uses the sources (watchers, subscriptions) has stopped.
+    pub async fn shutdown(&self) {
+        let sources: Vec<_> = match self.sources.write() {
+            Ok(mut sources) => sources.drain().collect(),
+            Err(_) => return,
+        };
+
+        for (id, source) in sources {
+            source.shutdown().await;
+            self.events.forget(&id);
+        }
+    }
+
     /// Get a source by ID
     /// Note: This returns None rather than a reference to avoid lifetime issues
     /// For operations on a source, use the query methods instead
@@ -63,6 +434,9 @@ impl SourcesManager {
                 id: source.source_id().to_string(),
                 name: source.source_name().to_string(),
                 is_connected: source.is_connected(),
+                auth_required: self.events.auth_required(source.source_id()),
+                capabilities: self.effective_capabilities(source.as_ref()),
+                tasks: source.tasks(),
             })
             .collect();
 
@@ -74,72 +448,284 @@ impl SourcesManager {
 
     /// List chats from a specific source
     pub async fn list_chats(&self, source_id: &str, filter: Option<ChatFilter>) -> Result<Vec<crate::types::Chat>> {
-        let sources = self.sources.read()
-            .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+        let source = self.source(source_id)?;
 
-        let source = sources.get(source_id)
-            .ok_or_else(|| anyhow::anyhow!("Source '{}' not found", source_id))?;
+        self.require_usable(source_id, source.as_ref())?;
+        source.capabilities().require(source_id, Capability::ListChats)?;
 
-        if !source.is_connected() {
-            anyhow::bail!("Source '{}' is not connected", source_id);
+        let label = filter.as_ref().and_then(|f| f.label.clone());
+        let mut chats = source.list_chats(filter).await.inspect_err(|e| {
+            self.events.report_error(source_id, e);
+        })?;
+
+        if let Some(label) = label {
+            let labels = self.labels.read()
+                .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+            chats.retain(|chat| labels.has_label(source_id, &chat.id, &label));
         }
 
-        source.list_chats(filter).await
+        Ok(chats)
     }
 
     /// Query messages from a specific source
     pub async fn query_messages(&self, source_id: Option<&str>, filter: MessageFilter) -> Result<Vec<Message>> {
+        self.query_messages_with_progress(source_id, filter, &Progress::new()).await
+    }
+
+    /// Query messages, reporting progress. Once `progress` is cancelled, sources stop
+    /// after their current batch and the messages fetched so far are returned.
+    pub async fn query_messages_with_progress(
+        &self,
+        source_id: Option<&str>,
+        filter: MessageFilter,
+        progress: &Progress,
+    ) -> Result<Vec<Message>> {
         filter.validate()?;
 
         if let Some(id) = source_id {
             // Query specific source
-            let sources = self.sources.read()
-                .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+            let source = self.source(id)?;
 
-            let source = sources.get(id)
-                .ok_or_else(|| anyhow::anyhow!("Source '{}' not found", id))?;
+            self.require_usable(id, source.as_ref())?;
+            source.capabilities().require(id, Capability::History)?;
 
-            if !source.is_connected() {
-                anyhow::bail!("Source '{}' is not connected", id);
+            let plan = self.plan(id, source.as_ref(), &filter)?;
+            if matches!(&plan.source.chat, ChatPattern::Multiple(ids) if ids.is_empty()) {
+                return Ok(Vec::new());
             }
 
-            source.get_messages(filter).await
+            progress.emit(ProgressEvent::SourceStarted { source: id.to_string() });
+            let messages = self.fetch(id, source.as_ref(), &plan, progress).await.inspect_err(|e| {
+                self.events.report_error(id, e);
+            })?;
+            progress.emit(ProgressEvent::SourceFinished { source: id.to_string(), count: messages.len() });
+
+            self.annotate(id, &filter, messages)
         } else {
             // Query all sources
-            let source_ids: Vec<String> = {
+            let sources: Vec<(String, Arc<dyn ChatSource>)> = {
                 let sources = self.sources.read()
                     .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
-                sources.keys().cloned().collect()
+                sources.iter().map(|(id, source)| (id.clone(), Arc::clone(source))).collect()
             };
 
-            let mut all_messages = Vec::new();
+            // Each source's messages, newest first and cut to the limit
+            let mut per_source = Vec::new();
 
-            for id in source_ids {
-                let sources = self.sources.read()
-                    .map_err(|e| anyhow::anyhow!("Failed to acquire read lock: {}", e))?;
+            for (id, source) in sources {
+                if progress.is_cancelled() {
+                    break;
+                }
 
-                if let Some(source) = sources.get(&id) {
-                    if source.is_connected() {
-                        match source.get_messages(filter.clone()).await {
-                            Ok(mut messages) => all_messages.append(&mut messages),
-                            Err(e) => {
-                                eprintln!("Warning: Failed to query source '{}': {}", id, e);
-                            }
+                let plan = self.plan(&id, source.as_ref(), &filter)?;
+                if matches!(&plan.source.chat, ChatPattern::Multiple(ids) if ids.is_empty()) {
+                    continue;
+                }
+
+                if source.is_connected() && !self.events.auth_required(&id) && source.capabilities().history {
+                    progress.emit(ProgressEvent::SourceStarted { source: id.clone() });
+
+                    match self.fetch(&id, source.as_ref(), &plan, progress).await {
+                        Ok(messages) => {
+                            progress.emit(ProgressEvent::SourceFinished { source: id.clone(), count: messages.len() });
+                            per_source.push((id.clone(), self.annotate(&id, &filter, messages)?));
+                        }
+                        Err(e) => {
+                            self.events.report_error(&id, &e);
+                            eprintln!("Warning: Failed to query source '{}': {}", id, e);
                         }
                     }
                 }
             }
 
-            // Sort by timestamp (most recent first)
-            all_messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            // Most recent first across sources, ties in a fixed order, cut to the limit
+            Ok(merge::newest_first(per_source, filter.limit))
+        }
+    }
+
+    /// Get my drafts and scheduled messages from a source, newest first
+    pub async fn pending_messages(&self, source_id: &str, filter: MessageFilter) -> Result<Vec<Message>> {
+        let source = self.source(source_id)?;
+
+        self.require_usable(source_id, source.as_ref())?;
+        source.capabilities().require(source_id, Capability::Pending)?;
+
+        let plan = self.plan(source_id, source.as_ref(), &filter)?;
+        if matches!(&plan.source.chat, ChatPattern::Multiple(ids) if ids.is_empty()) {
+            return Ok(Vec::new());
+        }
+
+        // Drafts are few, so the source gets every criterion it can be held to
+        let mut messages = source.get_pending(plan.check).await?;
+        messages.sort_by_key(|m| std::cmp::Reverse(m.timestamp));
+        Ok(messages)
+    }
+
+    /// Number of messages in a chat's history, if the source reports it without fetching them
+    pub async fn message_count(&self, source_id: &str, chat_id: &ChatId) -> Result<Option<usize>> {
+        let source = self.source(source_id)?;
+
+        self.require_usable(source_id, source.as_ref())?;
+        source.message_count(chat_id).await
+    }
+
+    /// Get a channel's posts since `since` with view counts, and its subscriber count
+    pub async fn channel_posts(&self, source_id: &str, chat_id: &ChatId, since: chrono::DateTime<Utc>) -> Result<ChannelPosts> {
+        let source = self.source(source_id)?;
+
+        self.require_usable(source_id, source.as_ref())?;
+        source.capabilities().require(source_id, Capability::ChannelStats)?;
+        source.get_channel_posts(chat_id, since).await
+    }
+
+    /// Send a text message to a chat, optionally as a reply. Refused in read-only mode
+    /// and for sources that can't send.
+    pub async fn send_message(&self, source_id: &str, chat_id: &ChatId, text: &str, reply_to: Option<&MessageId>) -> Result<Message> {
+        if text.trim().is_empty() {
+            anyhow::bail!("Refusing to send an empty message");
+        }
+        self.require(source_id, Capability::Sending)?;
+
+        let source = self.source(source_id)?;
+
+        self.require_usable(source_id, source.as_ref())?;
+        let chat_id = GlobalChatId::from_chat_id(source_id, chat_id).chat_id();
+        source.send_message(&chat_id, text, reply_to).await
+    }
+
+    /// Subscribe to a source's message stream through a bounded buffer.
+    /// Returns None if the source does not support streaming.
+    pub async fn subscribe(&self, source_id: &str, config: BufferConfig) -> Result<Option<Subscription>> {
+        let receiver = {
+            let source = self.source(source_id)?;
+
+            self.require_usable(source_id, source.as_ref())?;
+
+            if !source.capabilities().streaming {
+                return Ok(None);
+            }
+
+            source.subscribe().await?
+        };
+
+        let Some(receiver) = receiver else {
+            return Ok(None);
+        };
 
-            // Apply limit if specified
-            if let Some(limit) = filter.limit {
-                all_messages.truncate(limit);
+        let subscription = Subscription::spawn(self.normalize_stream(source_id, receiver), config);
+
+        let mut subscriptions = self.subscriptions.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        subscriptions.push((source_id.to_string(), subscription.monitor()));
+
+        Ok(Some(subscription))
+    }
+
+    /// Learn a source's clock skew from its live messages as they arrive, and correct
+    /// their timestamps on the way to the subscriber
+    fn normalize_stream(&self, source_id: &str, mut receiver: mpsc::Receiver<Message>) -> mpsc::Receiver<Message> {
+        let (tx, normalized) = mpsc::channel(1);
+        let source_id = source_id.to_string();
+        let clock = Arc::clone(&self.clock);
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                let mut messages = [message];
+                if let Ok(mut clock) = clock.write() {
+                    clock.observe(&source_id, &messages[0], Utc::now());
+                    clock.normalize(&source_id, &mut messages);
+                }
+                let [message] = messages;
+                if tx.send(message).await.is_err() {
+                    break;
+                }
             }
+        });
+        normalized
+    }
+
+    /// Buffer metrics for every live subscription created through this manager
+    pub fn subscription_stats(&self) -> Result<Vec<(String, SubscriptionStats)>> {
+        let mut subscriptions = self.subscriptions.write()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        subscriptions.retain(|(_, monitor)| monitor.is_active());
 
-            Ok(all_messages)
+        Ok(subscriptions
+            .iter()
+            .map(|(source_id, monitor)| (source_id.clone(), monitor.stats()))
+            .collect())
+    }
+
+    /// Connection events for the registered sources
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Fail unless a source is connected and signed in
+    fn require_usable(&self, source_id: &str, source: &dyn ChatSource) -> Result<()> {
+        if self.events.auth_required(source_id) {
+            anyhow::bail!(
+                "Source '{}' needs to sign in again, its session has expired. Run '{}'",
+                source_id,
+                login_command(source_id)
+            );
+        }
+        if !source.is_connected() {
+            anyhow::bail!("Source '{}' is not connected", source_id);
         }
+        Ok(())
+    }
+
+    /// Compare every source's connection state with the last one seen, publishing changes.
+    /// Sources waiting for a new sign-in stay that way until `check_auth` sees them work.
+    pub fn check_connections(&self) {
+        let Ok(sources) = self.sources.read() else {
+            return;
+        };
+        for (id, source) in sources.iter() {
+            if !self.events.auth_required(id) {
+                self.events.set_connected(id, source.is_connected());
+            }
+        }
+    }
+
+    /// Ping every connected source, which keeps idle sessions alive. Sources whose session
+    /// has expired are marked as needing a new sign-in and an auth event is published;
+    /// those that answer again are marked connected.
+    pub async fn check_auth(&self) {
+        let sources: Vec<(String, Arc<dyn ChatSource>)> = match self.sources.read() {
+            Ok(sources) => sources.iter().map(|(id, source)| (id.clone(), Arc::clone(source))).collect(),
+            Err(_) => return,
+        };
+
+        for (id, source) in sources {
+            if !source.is_connected() {
+                continue;
+            }
+
+            match source.keep_alive().await {
+                Ok(()) => {
+                    if self.events.auth_required(&id) {
+                        self.events.emit(&id, SourceEventKind::Connected);
+                    }
+                }
+                Err(e) => {
+                    log::debug!("Keep-alive for '{}' failed: {:#}", id, e);
+                    self.events.report_error(&id, &e);
+                }
+            }
+        }
+    }
+
+    /// Check connections every `interval` in a background task
+    pub fn watch_connections(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                manager.check_connections();
+            }
+        })
     }
 
     /// Get number of registered sources
@@ -165,6 +751,15 @@ impl Clone for SourcesManager {
     fn clone(&self) -> Self {
         Self {
             sources: Arc::clone(&self.sources),
+            labels: Arc::clone(&self.labels),
+            merged: Arc::clone(&self.merged),
+            accounts: Arc::clone(&self.accounts),
+            media_text: Arc::clone(&self.media_text),
+            subscriptions: Arc::clone(&self.subscriptions),
+            events: self.events.clone(),
+            read_only: Arc::clone(&self.read_only),
+            config: Arc::clone(&self.config),
+            clock: Arc::clone(&self.clock),
         }
     }
 }
@@ -172,7 +767,7 @@ impl Clone for SourcesManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{Chat, ChatId, ChatType};
+    use crate::types::{Chat, ChatId, ChatType, MessageContent, MessageId, SourceCapabilities, User, UserId};
     use async_trait::async_trait;
 
     // Mock source for testing
@@ -202,11 +797,148 @@ mod tests {
                 title: Some("Test Chat".to_string()),
                 chat_type: ChatType::DirectMessage,
                 participant_count: Some(2),
+                topic: None,
             }])
         }
 
+        async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+            let messages = ["hi @me, can you check this?", "lunch?"]
+                .iter()
+                .enumerate()
+                .map(|(i, text)| Message {
+                    id: MessageId::new(i.to_string()),
+                    chat_id: ChatId::new("test-chat"),
+                    sender: User {
+                        id: UserId::new("other"),
+                        username: None,
+                        display_name: Some("Other".to_string()),
+                        phone_number: None,
+                    },
+                    content: MessageContent::Text(text.to_string()),
+                    timestamp: chrono::Utc::now(),
+                    reply_to: None,
+                    edited: false,
+                    mentions_me: false,
+                    pending: false,
+                    language: None,
+                    media_text: None,
+                    original_timestamp: None,
+                })
+                .filter(|m| filter.matches(m))
+                .collect();
+
+            Ok(messages)
+        }
+
+        async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+            Ok(None)
+        }
+    }
+
+    // Source that only supports listing chats
+    struct ListOnlySource;
+
+    #[async_trait]
+    impl ChatSource for ListOnlySource {
+        fn source_id(&self) -> &str {
+            "list-only"
+        }
+
+        fn source_name(&self) -> &str {
+            "List Only"
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        fn capabilities(&self) -> SourceCapabilities {
+            SourceCapabilities {
+                list_chats: true,
+                ..SourceCapabilities::none()
+            }
+        }
+
+        async fn list_chats(&self, _filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_messages(&self, _filter: MessageFilter) -> Result<Vec<Message>> {
+            anyhow::bail!("should not be called")
+        }
+
+        async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+            anyhow::bail!("should not be called")
+        }
+    }
+
+    // Source running a background task that keeps failing
+    struct SupervisedSource {
+        supervisor: crate::Supervisor,
+    }
+
+    #[async_trait]
+    impl ChatSource for SupervisedSource {
+        fn source_id(&self) -> &str {
+            "supervised"
+        }
+
+        fn source_name(&self) -> &str {
+            "Supervised"
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        fn tasks(&self) -> Vec<crate::TaskStatus> {
+            self.supervisor.statuses()
+        }
+
+        async fn shutdown(&self) {
+            self.supervisor.shutdown().await
+        }
+
+        async fn list_chats(&self, _filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_messages(&self, _filter: MessageFilter) -> Result<Vec<Message>> {
+            Ok(Vec::new())
+        }
+
+        async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+            Ok(None)
+        }
+    }
+
+    // Source whose session was revoked on the server
+    struct ExpiredSource;
+
+    #[async_trait]
+    impl ChatSource for ExpiredSource {
+        fn source_id(&self) -> &str {
+            "expired"
+        }
+
+        fn source_name(&self) -> &str {
+            "Expired"
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn keep_alive(&self) -> Result<()> {
+            anyhow::bail!("rpc error 401: AUTH_KEY_UNREGISTERED")
+        }
+
+        async fn list_chats(&self, _filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+            Ok(Vec::new())
+        }
+
         async fn get_messages(&self, _filter: MessageFilter) -> Result<Vec<Message>> {
-            Ok(vec![])
+            anyhow::bail!("rpc error 401: AUTH_KEY_UNREGISTERED")
         }
 
         async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
@@ -273,6 +1005,54 @@ mod tests {
         assert_eq!(manager.count(), 0);
     }
 
+    #[test]
+    fn test_connection_events() {
+        let manager = SourcesManager::new();
+        let mut events = manager.events().subscribe();
+
+        manager.register(Box::new(MockSource {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            connected: true,
+        })).unwrap();
+        manager.check_connections();
+        manager.unregister("test").unwrap();
+
+        let event = events.try_recv().unwrap();
+        assert_eq!((event.source.as_str(), event.kind), ("test", SourceEventKind::Connected));
+        let event = events.try_recv().unwrap();
+        assert!(matches!(event.kind, SourceEventKind::Disconnected { reason: Some(_) }));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_auth() {
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ExpiredSource)).unwrap();
+        manager.register(Box::new(MockSource {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            connected: true,
+        })).unwrap();
+        let mut events = manager.events().subscribe();
+
+        manager.check_auth().await;
+        manager.check_connections();
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.source, "expired");
+        assert!(matches!(event.kind, SourceEventKind::AuthExpired { .. }));
+        assert!(events.try_recv().is_err());
+
+        let sources = manager.list_sources().unwrap();
+        assert!(sources[0].auth_required);
+        assert!(!sources[1].auth_required);
+
+        let error = manager.list_chats("expired", None).await.unwrap_err();
+        assert!(error.to_string().contains("chat expired init"));
+        assert!(manager.list_chats("test", None).await.is_ok());
+    }
+
     #[test]
     fn test_list_sources() {
         let manager = SourcesManager::new();
@@ -300,6 +1080,32 @@ mod tests {
         assert!(!sources[0].is_connected);
     }
 
+    #[tokio::test]
+    async fn test_task_health_and_shutdown() {
+        let supervisor = crate::Supervisor::new();
+        supervisor.spawn("runner", crate::RestartPolicy::default(), || {
+            Ok(async { Err::<(), _>(anyhow::anyhow!("connection reset")) })
+        });
+        let manager = SourcesManager::new();
+        manager.register(Box::new(SupervisedSource { supervisor: supervisor.clone() })).unwrap();
+
+        let mut tasks = Vec::new();
+        for _ in 0..100 {
+            tasks = manager.list_sources().unwrap().remove(0).tasks;
+            if tasks[0].last_error.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(tasks[0].name, "runner");
+        assert!(matches!(tasks[0].state, crate::TaskState::Restarting { attempt: 1, .. }));
+        assert_eq!(tasks[0].last_error.as_deref(), Some("connection reset"));
+
+        manager.shutdown().await;
+        assert!(manager.is_empty());
+        assert!(supervisor.statuses().is_empty());
+    }
+
     #[tokio::test]
     async fn test_list_chats() {
         let manager = SourcesManager::new();
@@ -329,6 +1135,441 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_list_chats_by_label() {
+        let mut labels = LabelStore::new();
+        labels.add("test", &ChatId::new("test-chat"), "work");
+        let manager = SourcesManager::with_labels(labels);
+
+        manager.register(Box::new(MockSource {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            connected: true,
+        })).unwrap();
+
+        let work = manager.list_chats("test", Some(ChatFilter::new().with_label("work"))).await.unwrap();
+        assert_eq!(work.len(), 1);
+
+        let family = manager.list_chats("test", Some(ChatFilter::new().with_label("family"))).await.unwrap();
+        assert!(family.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_messages_mentions_me() {
+        let manager = SourcesManager::new();
+        let mut accounts = MyAccounts::new();
+        accounts.add_handle("test", "me");
+        manager.set_accounts(accounts).unwrap();
+
+        manager.register(Box::new(MockSource {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            connected: true,
+        })).unwrap();
+
+        let all = manager.query_messages(Some("test"), MessageFilter::new()).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all.iter().filter(|m| m.mentions_me).count(), 1);
+
+        let filter = MessageFilter {
+            mentions_me: true,
+            ..MessageFilter::new()
+        };
+        let mentions = manager.query_messages(None, filter).await.unwrap();
+        assert_eq!(mentions.len(), 1);
+        assert_eq!(mentions[0].id, MessageId::new("0"));
+    }
+
+    #[tokio::test]
+    async fn test_query_messages_language() {
+        use crate::test_utils::{InMemorySource, MessageBuilder};
+
+        let manager = SourcesManager::new();
+        manager.register(Box::new(InMemorySource::new("test").with_messages(vec![
+            MessageBuilder::new("1", "group")
+                .text("Huomenna on palaveri toimistolla kello kymmenen, tuokaa kannettavat mukaan.")
+                .minute(1)
+                .build(),
+            MessageBuilder::new("2", "group")
+                .text("Could you send me the report from yesterday's meeting? I need it before the call this afternoon.")
+                .minute(2)
+                .build(),
+        ]))).unwrap();
+
+        let all = manager.query_messages(Some("test"), MessageFilter::new()).await.unwrap();
+        assert_eq!(all[0].language.as_deref(), Some("en"));
+
+        for (code, id) in [("fi", "1"), ("eng", "2")] {
+            let filter = MessageFilter { language: Some(code.to_string()), ..MessageFilter::new() };
+            let messages = manager.query_messages(None, filter).await.unwrap();
+            assert_eq!(messages.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), [id]);
+        }
+
+        let unknown = MessageFilter { language: Some("xx".to_string()), ..MessageFilter::new() };
+        assert!(manager.query_messages(None, unknown).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_query_merged_chats() {
+        use crate::test_utils::{InMemorySource, MessageBuilder};
+
+        let manager = SourcesManager::new();
+        let mut merged = MergedChats::new();
+        merged.add("Team", &[GlobalChatId::new("test", "team"), GlobalChatId::new("test", "team-v2")]).unwrap();
+        manager.set_merged_chats(merged).unwrap();
+        manager.register(Box::new(InMemorySource::new("test").with_messages(vec![
+            MessageBuilder::new("1", "team").text("old").minute(1).build(),
+            MessageBuilder::new("2", "team-v2").text("new").minute(2).build(),
+            MessageBuilder::new("3", "other").text("elsewhere").minute(3).build(),
+        ]))).unwrap();
+
+        // Either chat finds both, under the newer chat's ID
+        let old = manager.query_messages(Some("test"), MessageFilter::for_chat_id(ChatId::new("team"))).await.unwrap();
+        assert_eq!(old.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), ["2", "1"]);
+        assert!(old.iter().all(|m| m.chat_id == ChatId::new("team-v2")));
+        let all = manager.query_messages(None, MessageFilter::new()).await.unwrap();
+        assert_eq!(all.iter().filter(|m| m.chat_id == ChatId::new("team-v2")).count(), 2);
+        assert_eq!(manager.merged_into("test", &ChatId::new("team")).unwrap(), ChatId::new("team-v2"));
+
+        let separate = MessageFilter { separate_merged: true, ..MessageFilter::for_chat_id(ChatId::new("team")) };
+        let old = manager.query_messages(Some("test"), separate).await.unwrap();
+        assert_eq!(old.len(), 1);
+        assert_eq!(old[0].chat_id, ChatId::new("team"));
+    }
+
+    #[tokio::test]
+    async fn test_search_image_text() {
+        use crate::media_text::{DerivedKind, DerivedText};
+        use crate::test_utils::{InMemorySource, MessageBuilder};
+        use crate::types::MessageContent;
+
+        let screenshot = MessageBuilder::new("1", "group")
+            .content(MessageContent::Image { caption: None, url: None })
+            .minute(1)
+            .build();
+        let manager = SourcesManager::new();
+        manager.register(Box::new(InMemorySource::new("test").with_messages(vec![
+            screenshot.clone(),
+            MessageBuilder::new("2", "group").text("Did you pay the invoice?").minute(2).build(),
+            MessageBuilder::new("3", "group").text("Lunch?").minute(3).build(),
+        ]))).unwrap();
+
+        let search = |term: &str| MessageFilter { search: Some(term.to_string()), ..MessageFilter::new() };
+        let ids = |messages: Vec<Message>| messages.iter().map(|m| m.id.as_str().to_string()).collect::<Vec<_>>();
+
+        assert_eq!(ids(manager.query_messages(Some("test"), search("invoice")).await.unwrap()), ["2"]);
+
+        let mut store = MediaTextStore::new();
+        store.insert("test", &screenshot, DerivedText::new(DerivedKind::Ocr, "INVOICE 42\nDue Friday", "tesseract"));
+        manager.set_media_text(store).unwrap();
+
+        let found = manager.query_messages(Some("test"), search("invoice")).await.unwrap();
+        assert_eq!(ids(found.clone()), ["2", "1"]);
+        assert_eq!(found[1].media_text.as_deref(), Some("INVOICE 42\nDue Friday"));
+        assert_eq!(ids(manager.query_messages(None, search("due friday")).await.unwrap()), ["1"]);
+
+        // Images found through their text still honor the other filters
+        let text_only = MessageFilter { content_type: Some(vec![ContentType::Text]), ..search("invoice") };
+        assert_eq!(ids(manager.query_messages(Some("test"), text_only).await.unwrap()), ["2"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_documents() {
+        use crate::media_text::{DerivedKind, DerivedText};
+        use crate::test_utils::{InMemorySource, MessageBuilder};
+        use crate::types::MessageContent;
+
+        let contract = MessageBuilder::new("1", "group")
+            .content(MessageContent::File { filename: Some("contract.pdf".to_string()), url: None })
+            .minute(1)
+            .build();
+        let manager = SourcesManager::new();
+        manager.register(Box::new(InMemorySource::new("test").with_messages(vec![
+            contract.clone(),
+            MessageBuilder::new("2", "group").text("Did you sign the contract?").minute(2).build(),
+        ]))).unwrap();
+
+        let mut store = MediaTextStore::new();
+        store.insert("test", &contract, DerivedText::new(DerivedKind::Document, "Service contract between ACME and Bob", "pdf"));
+        manager.set_media_text(store).unwrap();
+
+        let search = |term: &str| MessageFilter { search: Some(term.to_string()), ..MessageFilter::new() };
+        let ids = |messages: Vec<Message>| messages.iter().map(|m| m.id.as_str().to_string()).collect::<Vec<_>>();
+
+        assert_eq!(ids(manager.query_messages(Some("test"), search("contract")).await.unwrap()), ["2", "1"]);
+        let in_attachments = MessageFilter { in_attachments: true, ..search("contract") };
+        assert_eq!(ids(manager.query_messages(Some("test"), in_attachments).await.unwrap()), ["1"]);
+        let in_attachments = MessageFilter { in_attachments: true, ..search("sign") };
+        assert!(manager.query_messages(Some("test"), in_attachments).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_all_sources_limit() {
+        use crate::test_utils::{InMemorySource, MessageBuilder};
+        use std::sync::Mutex;
+
+        // Remembers the limit it was asked for
+        struct LimitSource(InMemorySource, Arc<Mutex<Vec<Option<usize>>>>);
+
+        #[async_trait]
+        impl ChatSource for LimitSource {
+            fn source_id(&self) -> &str {
+                self.0.source_id()
+            }
+
+            fn source_name(&self) -> &str {
+                self.0.source_name()
+            }
+
+            fn is_connected(&self) -> bool {
+                true
+            }
+
+            async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+                self.0.list_chats(filter).await
+            }
+
+            async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+                self.1.lock().unwrap().push(filter.limit);
+                self.0.get_messages(filter).await
+            }
+
+            async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+                Ok(None)
+            }
+        }
+
+        let limits = Arc::new(Mutex::new(Vec::new()));
+        let manager = SourcesManager::new();
+        for (source, offset) in [("a", 0), ("b", 1), ("c", 2)] {
+            let messages = (0..20).map(|i| MessageBuilder::new(i.to_string(), "1").minute(i * 3 + offset).build()).collect();
+            let source = LimitSource(InMemorySource::new(source).with_messages(messages), Arc::clone(&limits));
+            manager.register(Box::new(source)).unwrap();
+        }
+
+        let filter = MessageFilter { limit: Some(4), ..MessageFilter::new() };
+        let newest = manager.query_messages(None, filter).await.unwrap();
+        let order: Vec<(i64, &str)> = newest
+            .iter()
+            .map(|m| ((m.timestamp - crate::test_utils::base_time()).num_minutes(), m.id.as_str()))
+            .collect();
+        assert_eq!(order, [(59, "19"), (58, "19"), (57, "19"), (56, "18")]);
+        assert_eq!(*limits.lock().unwrap(), [Some(4); 3]);
+    }
+
+    #[tokio::test]
+    async fn test_query_pushdown() {
+        use crate::test_utils::{InMemorySource, MessageBuilder};
+        use crate::types::FilterPushdown;
+        use std::sync::Mutex;
+
+        // Remembers the filters it was asked for
+        struct RecordingSource(InMemorySource, Arc<Mutex<Vec<MessageFilter>>>);
+
+        #[async_trait]
+        impl ChatSource for RecordingSource {
+            fn source_id(&self) -> &str {
+                self.0.source_id()
+            }
+
+            fn source_name(&self) -> &str {
+                self.0.source_name()
+            }
+
+            fn is_connected(&self) -> bool {
+                true
+            }
+
+            fn capabilities(&self) -> SourceCapabilities {
+                self.0.capabilities()
+            }
+
+            async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+                self.0.list_chats(filter).await
+            }
+
+            async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+                self.1.lock().unwrap().push(filter.clone());
+                self.0.get_messages(filter).await
+            }
+
+            async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+                Ok(None)
+            }
+        }
+
+        let messages: Vec<Message> = (0..6)
+            .map(|i| {
+                let sender = if i % 2 == 0 { "bob" } else { "ana" };
+                MessageBuilder::new(i.to_string(), "1").minute(i).from(sender).text("invoice attached").build()
+            })
+            .collect();
+        let filter = MessageFilter {
+            search: Some("invoice".to_string()),
+            sender: Some("bob".to_string()),
+            limit: Some(2),
+            ..MessageFilter::new()
+        };
+        let ids = |messages: Vec<Message>| messages.iter().map(|m| m.id.as_str().to_string()).collect::<Vec<_>>();
+
+        // Searching on its side, the source still can't cut to the limit before the sender is checked
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let search_only = SourceCapabilities {
+            pushdown: FilterPushdown { search: true, ..FilterPushdown::default() },
+            ..SourceCapabilities::default()
+        };
+        let source = InMemorySource::new("search").with_capabilities(search_only).with_messages(messages.clone());
+        let manager = SourcesManager::new();
+        manager.register(Box::new(RecordingSource(source, Arc::clone(&requests)))).unwrap();
+        assert_eq!(ids(manager.query_messages(Some("search"), filter.clone()).await.unwrap()), ["4", "2"]);
+        let asked = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(asked.search.as_deref(), Some("invoice"));
+        assert_eq!((asked.sender, asked.limit), (None, None));
+
+        // Applying everything itself, the source gets the whole filter
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let everything = SourceCapabilities {
+            pushdown: FilterPushdown { search: true, sender: true, time_range: true, content_type: true },
+            ..SourceCapabilities::default()
+        };
+        let source = InMemorySource::new("full").with_capabilities(everything).with_messages(messages);
+        manager.register(Box::new(RecordingSource(source, Arc::clone(&requests)))).unwrap();
+        assert_eq!(ids(manager.query_messages(Some("full"), filter).await.unwrap()), ["4", "2"]);
+        let asked = requests.lock().unwrap().pop().unwrap();
+        assert_eq!((asked.sender.as_deref(), asked.limit), (Some("bob"), Some(2)));
+    }
+
+    #[tokio::test]
+    async fn test_clock_normalization() {
+        use crate::test_utils::{base_time, InMemorySource, MessageBuilder};
+
+        // The bridge's clock runs two minutes ahead and only stamps whole minutes
+        let manager = SourcesManager::new();
+        manager.register(Box::new(InMemorySource::new("bridge").with_messages(vec![
+            MessageBuilder::new("9", "1").minute(3).build(),
+            MessageBuilder::new("10", "1").minute(3).build(),
+        ]))).unwrap();
+        manager.register(Box::new(InMemorySource::new("telegram").with_messages(vec![
+            MessageBuilder::new("1", "1").minute(2).build(),
+            MessageBuilder::new("2", "1").minute(1).build(),
+        ]))).unwrap();
+        let config: SourcesConfig = toml::from_str("[sources.bridge]\nclock_offset_secs = 120\n").unwrap();
+        manager.set_sources_config(config).unwrap();
+        assert_eq!(manager.clock_skew("bridge").unwrap(), Some(chrono::Duration::seconds(120)));
+
+        let merged = manager.query_messages(None, MessageFilter::new()).await.unwrap();
+        let order: Vec<&str> = merged.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(order, ["1", "2", "10", "9"]);
+        assert_eq!(merged[2].timestamp, base_time() + chrono::Duration::minutes(1));
+        assert_eq!(merged[2].original_timestamp, Some(base_time() + chrono::Duration::minutes(3)));
+        assert_eq!(merged[1].original_timestamp, None);
+
+        // Live messages teach the manager a clock it wasn't told about
+        let (source, stream) = InMemorySource::new("live").streaming();
+        manager.register(Box::new(source)).unwrap();
+        let subscription = manager.subscribe("live", BufferConfig::default()).await.unwrap().unwrap();
+        let ahead = Utc::now() + chrono::Duration::minutes(5);
+        for id in ["1", "2", "3"] {
+            stream.send(MessageBuilder::new(id, "1").at(ahead).build()).await.unwrap();
+        }
+        let mut last = None;
+        for _ in 0..3 {
+            last = subscription.recv().await;
+        }
+        let last = last.unwrap();
+        assert_eq!(last.original_timestamp, Some(ahead));
+        assert!(last.timestamp <= Utc::now());
+        assert!(manager.clock_skew("live").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_source_settings() {
+        use std::sync::Mutex;
+
+        // Remembers the settings it was configured with
+        struct ConfiguredSource(Arc<Mutex<Option<SourceSettings>>>);
+
+        #[async_trait]
+        impl ChatSource for ConfiguredSource {
+            fn source_id(&self) -> &str {
+                "configured"
+            }
+
+            fn source_name(&self) -> &str {
+                "Configured"
+            }
+
+            fn is_connected(&self) -> bool {
+                true
+            }
+
+            fn configure(&mut self, settings: SourceSettings) {
+                *self.0.lock().unwrap() = Some(settings);
+            }
+
+            async fn list_chats(&self, _filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+                Ok(Vec::new())
+            }
+
+            async fn get_messages(&self, _filter: MessageFilter) -> Result<Vec<Message>> {
+                Ok(Vec::new())
+            }
+
+            async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+                Ok(None)
+            }
+        }
+
+        let applied = Arc::new(Mutex::new(None));
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ConfiguredSource(Arc::clone(&applied)))).unwrap();
+        assert_eq!(*applied.lock().unwrap(), Some(SourceSettings::default()));
+
+        let mut config = SourcesConfig::default();
+        let settings = SourceSettings { max_messages_per_chat: 50, ..SourceSettings::default() };
+        config.sources.insert("configured".to_string(), settings);
+        manager.set_sources_config(config).unwrap();
+
+        assert_eq!(*applied.lock().unwrap(), Some(settings));
+        assert_eq!(manager.source_settings("configured").unwrap(), settings);
+        assert_eq!(manager.source_settings("other").unwrap(), SourceSettings::default());
+    }
+
+    #[tokio::test]
+    async fn test_search_transcripts() {
+        use crate::media_text::{DerivedKind, DerivedText};
+        use crate::test_utils::{InMemorySource, MessageBuilder};
+        use crate::types::MessageContent;
+
+        let voice_note = MessageBuilder::new("1", "group")
+            .content(MessageContent::Audio { url: None })
+            .minute(1)
+            .build();
+        let manager = SourcesManager::new();
+        manager.register(Box::new(InMemorySource::new("test").with_messages(vec![
+            voice_note.clone(),
+            MessageBuilder::new("2", "group").text("See you at the station").minute(2).build(),
+        ]))).unwrap();
+
+        let mut store = MediaTextStore::new();
+        store.insert("test", &voice_note, DerivedText::new(DerivedKind::Transcript, "Meet me at the station at six", "whisper:ggml-base"));
+        manager.set_media_text(store).unwrap();
+
+        let search = MessageFilter { search: Some("station".to_string()), ..MessageFilter::new() };
+        let found = manager.query_messages(Some("test"), search.clone()).await.unwrap();
+        assert_eq!(found.len(), 2);
+
+        let audio = MessageFilter { content_type: Some(vec![ContentType::Audio]), ..search };
+        let found = manager.query_messages(Some("test"), audio).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].media_text.as_deref(), Some("Meet me at the station at six"));
+
+        // Transcripts are attached without a search too
+        let all_audio = MessageFilter { content_type: Some(vec![ContentType::Audio]), ..MessageFilter::new() };
+        let found = manager.query_messages(Some("test"), all_audio).await.unwrap();
+        assert_eq!(found[0].media_text.as_deref(), Some("Meet me at the station at six"));
+    }
+
     #[test]
     fn test_clone() {
         let manager1 = SourcesManager::new();
@@ -342,4 +1583,86 @@ mod tests {
         assert_eq!(manager2.count(), 1);
         assert!(manager2.has_source("test"));
     }
+
+    #[tokio::test]
+    async fn test_capability_errors() {
+        let manager = SourcesManager::new();
+        manager.register(Box::new(ListOnlySource)).unwrap();
+
+        let sources = manager.list_sources().unwrap();
+        assert_eq!(sources[0].capabilities.supported(), vec![Capability::ListChats]);
+        assert!(manager.require("list-only", Capability::ListChats).is_ok());
+        let err = manager.require("list-only", Capability::Sending).unwrap_err();
+        assert_eq!(err.to_string(), "Source 'list-only' does not support sending messages");
+
+        assert!(manager.list_chats("list-only", None).await.is_ok());
+
+        let err = manager.query_messages(Some("list-only"), MessageFilter::new()).await.unwrap_err();
+        assert_eq!(err.to_string(), "Source 'list-only' does not support message history");
+
+        // Sources without history are skipped when querying everything
+        let messages = manager.query_messages(None, MessageFilter::new()).await.unwrap();
+        assert!(messages.is_empty());
+
+        let subscription = manager.subscribe("list-only", BufferConfig::default()).await.unwrap();
+        assert!(subscription.is_none());
+    }
+
+    #[test]
+    fn test_read_only_mode() {
+        struct WritableSource;
+
+        #[async_trait]
+        impl ChatSource for WritableSource {
+            fn source_id(&self) -> &str {
+                "writable"
+            }
+
+            fn source_name(&self) -> &str {
+                "Writable"
+            }
+
+            fn is_connected(&self) -> bool {
+                true
+            }
+
+            fn capabilities(&self) -> SourceCapabilities {
+                SourceCapabilities {
+                    sending: true,
+                    drafting: true,
+                    mark_read: true,
+                    ..SourceCapabilities::default()
+                }
+            }
+
+            async fn list_chats(&self, _filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+                Ok(Vec::new())
+            }
+
+            async fn get_messages(&self, _filter: MessageFilter) -> Result<Vec<Message>> {
+                Ok(Vec::new())
+            }
+
+            async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
+                Ok(None)
+            }
+        }
+
+        let manager = SourcesManager::new();
+        manager.register(Box::new(WritableSource)).unwrap();
+        assert!(manager.require("writable", Capability::Sending).is_ok());
+
+        // Clones share the switch, so a manager handed to a server can't escape it
+        manager.clone().set_read_only(true);
+        assert!(manager.is_read_only());
+        for capability in [Capability::Sending, Capability::Drafting, Capability::MarkRead] {
+            let err = manager.require("writable", capability).unwrap_err();
+            assert!(err.to_string().starts_with("Read-only mode"), "{}", err);
+        }
+        assert!(manager.require("writable", Capability::History).is_ok());
+
+        let capabilities = manager.list_sources().unwrap()[0].capabilities;
+        assert!(!capabilities.sending && !capabilities.drafting && !capabilities.mark_read);
+        assert!(capabilities.history);
+    }
 }
@@ -0,0 +1,293 @@
+//! Messages exported to a normalized SQLite database for analysis with SQL tools
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+use crate::types::{Chat, ChatId, ChatType, Message, MessageContent, User};
+
+/// Tables, indexes and views of an export. Timestamps are RFC 3339 UTC text, so they
+/// sort and compare as strings; `messages.date` is the UTC day for grouping.
+const SCHEMA: &str = "
+CREATE TABLE chats (
+    id INTEGER PRIMARY KEY,
+    source TEXT NOT NULL,
+    chat_id TEXT NOT NULL,
+    title TEXT,
+    chat_type TEXT NOT NULL,
+    participant_count INTEGER,
+    UNIQUE (source, chat_id)
+);
+
+CREATE TABLE users (
+    id INTEGER PRIMARY KEY,
+    source TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    username TEXT,
+    display_name TEXT,
+    phone_number TEXT,
+    UNIQUE (source, user_id)
+);
+
+CREATE TABLE messages (
+    id INTEGER PRIMARY KEY,
+    chat INTEGER NOT NULL REFERENCES chats (id),
+    message_id TEXT NOT NULL,
+    sender INTEGER NOT NULL REFERENCES users (id),
+    timestamp TEXT NOT NULL,
+    date TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    text TEXT,
+    reply_to TEXT,
+    edited INTEGER NOT NULL,
+    mentions_me INTEGER NOT NULL,
+    pending INTEGER NOT NULL,
+    language TEXT,
+    media_text TEXT,
+    original_timestamp TEXT,
+    UNIQUE (chat, message_id)
+);
+
+CREATE TABLE media (
+    message INTEGER PRIMARY KEY REFERENCES messages (id),
+    kind TEXT NOT NULL,
+    url TEXT,
+    file_name TEXT,
+    caption TEXT,
+    latitude REAL,
+    longitude REAL,
+    contact_name TEXT,
+    contact_phone TEXT
+);
+
+CREATE TABLE reactions (
+    message INTEGER NOT NULL REFERENCES messages (id),
+    emoji TEXT NOT NULL,
+    user INTEGER REFERENCES users (id)
+);
+
+CREATE INDEX messages_chat_timestamp ON messages (chat, timestamp);
+CREATE INDEX messages_sender ON messages (sender);
+CREATE INDEX messages_date ON messages (date);
+CREATE INDEX reactions_message ON reactions (message);
+
+CREATE VIEW daily_counts AS
+SELECT chats.source, chats.chat_id, chats.title, messages.date, COUNT(*) AS messages
+FROM messages JOIN chats ON chats.id = messages.chat
+GROUP BY messages.chat, messages.date;
+
+CREATE VIEW top_senders AS
+SELECT users.source, users.user_id, users.display_name, COUNT(*) AS messages,
+       COUNT(DISTINCT messages.chat) AS chats,
+       MIN(messages.timestamp) AS first_message, MAX(messages.timestamp) AS last_message
+FROM messages JOIN users ON users.id = messages.sender
+WHERE messages.kind != 'system'
+GROUP BY messages.sender
+ORDER BY messages DESC;
+";
+
+/// Writes chats and their messages into a new SQLite database.
+///
+/// Everything goes into one transaction that `finish` commits, so an interrupted
+/// export leaves an empty database rather than a partial one.
+pub struct SqliteExport {
+    conn: Connection,
+}
+
+impl SqliteExport {
+    /// Create the database at `path`, replacing any file already there
+    pub fn create(path: &Path) -> Result<Self> {
+        if path.exists() {
+            std::fs::remove_file(path).with_context(|| format!("Failed to replace {}", path.display()))?;
+        }
+        let conn = Connection::open(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch("BEGIN;")?;
+        conn.execute_batch(SCHEMA).context("Failed to create the export schema")?;
+        Ok(Self { conn })
+    }
+
+    /// Add a chat and its messages. Returns how many messages were new; a message
+    /// exported twice is written once.
+    pub fn add_chat(&mut self, source: &str, chat: &Chat, messages: &[Message]) -> Result<usize> {
+        self.conn.execute(
+            "INSERT INTO chats (source, chat_id, title, chat_type, participant_count) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (source, chat_id) DO UPDATE SET title = excluded.title",
+            params![
+                source,
+                chat.id.as_str(),
+                chat.title,
+                chat_type_name(&chat.chat_type),
+                chat.participant_count.map(|count| count as i64),
+            ],
+        )?;
+        let chat_row = self.chat_row(source, &chat.id)?;
+
+        let mut added = 0;
+        for message in messages {
+            let sender = self.user_row(source, &message.sender)?;
+            let inserted = self.conn.execute(
+                "INSERT OR IGNORE INTO messages (chat, message_id, sender, timestamp, date, kind, text, reply_to,
+                 edited, mentions_me, pending, language, media_text, original_timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                params![
+                    chat_row,
+                    message.id.as_str(),
+                    sender,
+                    message.timestamp.to_rfc3339(),
+                    message.timestamp.date_naive().to_string(),
+                    message.content.kind(),
+                    message_text(&message.content),
+                    message.reply_to.as_ref().map(|id| id.as_str()),
+                    message.edited,
+                    message.mentions_me,
+                    message.pending,
+                    message.language,
+                    message.media_text,
+                    message.original_timestamp.map(|t| t.to_rfc3339()),
+                ],
+            )?;
+            if inserted == 0 {
+                continue;
+            }
+            added += 1;
+            self.insert_media(self.conn.last_insert_rowid(), &message.content)?;
+        }
+
+        Ok(added)
+    }
+
+    /// Commit the export
+    pub fn finish(self) -> Result<()> {
+        self.conn.execute_batch("COMMIT;").context("Failed to write the export")?;
+        Ok(())
+    }
+
+    fn chat_row(&self, source: &str, chat_id: &ChatId) -> Result<i64> {
+        Ok(self.conn.query_row(
+            "SELECT id FROM chats WHERE source = ?1 AND chat_id = ?2",
+            params![source, chat_id.as_str()],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Row of a user, added on first sight and filled in by later sightings
+    fn user_row(&self, source: &str, user: &User) -> Result<i64> {
+        let existing: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM users WHERE source = ?1 AND user_id = ?2",
+                params![source, user.id.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match existing {
+            Some(id) => {
+                self.conn.execute(
+                    "UPDATE users SET username = COALESCE(username, ?2), display_name = COALESCE(display_name, ?3),
+                     phone_number = COALESCE(phone_number, ?4) WHERE id = ?1",
+                    params![id, user.username, user.display_name, user.phone_number],
+                )?;
+                Ok(id)
+            }
+            None => {
+                self.conn.execute(
+                    "INSERT INTO users (source, user_id, username, display_name, phone_number) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![source, user.id.as_str(), user.username, user.display_name, user.phone_number],
+                )?;
+                Ok(self.conn.last_insert_rowid())
+            }
+        }
+    }
+
+    fn insert_media(&self, message: i64, content: &MessageContent) -> Result<()> {
+        let (url, file_name, caption, location, contact) = match content {
+            MessageContent::Image { caption, url } | MessageContent::Video { caption, url } => {
+                (url.as_deref(), None, caption.as_deref(), None, None)
+            }
+            MessageContent::Audio { url } => (url.as_deref(), None, None, None, None),
+            MessageContent::File { filename, url } => (url.as_deref(), filename.as_deref(), None, None, None),
+            MessageContent::Sticker => (None, None, None, None, None),
+            MessageContent::Location { latitude, longitude } => (None, None, None, Some((*latitude, *longitude)), None),
+            MessageContent::Contact { name, phone } => (None, None, None, None, Some((name.as_str(), phone.as_deref()))),
+            MessageContent::Text(_) | MessageContent::System(_) | MessageContent::Unknown => return Ok(()),
+        };
+
+        self.conn.execute(
+            "INSERT INTO media (message, kind, url, file_name, caption, latitude, longitude, contact_name, contact_phone)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                message,
+                content.kind(),
+                url,
+                file_name,
+                caption,
+                location.map(|(latitude, _)| latitude),
+                location.map(|(_, longitude)| longitude),
+                contact.map(|(name, _)| name),
+                contact.and_then(|(_, phone)| phone),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn chat_type_name(chat_type: &ChatType) -> &'static str {
+    match chat_type {
+        ChatType::DirectMessage => "dm",
+        ChatType::Group => "group",
+        ChatType::Channel => "channel",
+        ChatType::Unknown => "unknown",
+    }
+}
+
+/// Text written with a message: its text or caption, or what a system event was
+fn message_text(content: &MessageContent) -> Option<String> {
+    match content {
+        MessageContent::Text(text) => Some(text.clone()),
+        MessageContent::Image { caption, .. } | MessageContent::Video { caption, .. } => caption.clone(),
+        MessageContent::System(event) => Some(event.describe()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ChatBuilder, MessageBuilder};
+
+    #[test]
+    fn test_export() {
+        let mut export = SqliteExport::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let chat = ChatBuilder::new("1").title("Work").build();
+        let messages = vec![
+            MessageBuilder::new("1", "1").from("alice").minute(0).text("hello").build(),
+            MessageBuilder::new("2", "1").from("bob").minute(1).text("hi").build(),
+            MessageBuilder::new("3", "1")
+                .from("alice")
+                .minute(2)
+                .content(MessageContent::Image { caption: Some("lunch".into()), url: None })
+                .build(),
+        ];
+        assert_eq!(export.add_chat("telegram", &chat, &messages).unwrap(), 3);
+        assert_eq!(export.add_chat("telegram", &chat, &messages[..1]).unwrap(), 0);
+
+        let conn = &export.conn;
+        let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM chats"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM users"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM media WHERE kind = 'image'"), 1);
+        assert_eq!(count("SELECT messages FROM daily_counts"), 3);
+
+        let (top, messages): (String, i64) = conn
+            .query_row("SELECT display_name, messages FROM top_senders LIMIT 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((top.as_str(), messages), ("Alice", 2));
+
+        export.finish().unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+//! Versioned events for NDJSON output: `watch --format json`, `pipeline run --emit`,
+//! store files and webhook bodies all write one [`StreamEvent`] per line.
+//!
+//! Every line is an object with `type` and `schema_version`, plus the fields of that type:
+//!
+//! ```json
+//! {"type":"message","schema_version":1,"source":"telegram","message":{"id":"42",...}}
+//! {"type":"dispatch","schema_version":1,"source":"telegram","message":{...},"pipeline":"urgent","route":"notify",...}
+//! ```
+//!
+//! Consumers should ignore fields they don't know. Adding fields or event types keeps the
+//! version; renaming or removing fields, or changing their meaning, bumps it.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::Dispatch;
+use crate::types::Message;
+
+/// Version of the event schema written by this build
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// One line of NDJSON output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEvent {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub event: EventKind,
+}
+
+/// What happened, tagged by `type`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    /// A message arrived from a source
+    Message { source: String, message: Message },
+    /// A pipeline delivered a message to a route
+    Dispatch(Dispatch),
+}
+
+impl StreamEvent {
+    pub fn new(event: EventKind) -> Self {
+        Self { schema_version: SCHEMA_VERSION, event }
+    }
+
+    pub fn message(source: impl Into<String>, message: Message) -> Self {
+        Self::new(EventKind::Message { source: source.into(), message })
+    }
+
+    pub fn dispatch(dispatch: Dispatch) -> Self {
+        Self::new(EventKind::Dispatch(dispatch))
+    }
+
+    /// The event as one NDJSON line, without the newline
+    pub fn to_line(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Parse one NDJSON line, rejecting events from a newer schema
+    pub fn from_line(line: &str) -> Result<Self> {
+        let event: Self = serde_json::from_str(line)?;
+        if event.schema_version > SCHEMA_VERSION {
+            anyhow::bail!(
+                "Event schema version {} is newer than the supported version {}",
+                event.schema_version,
+                SCHEMA_VERSION
+            );
+        }
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Route;
+    use crate::test_utils::MessageBuilder;
+    use serde_json::{json, Value};
+
+    fn round_trip(event: &StreamEvent) -> Value {
+        let line = event.to_line().unwrap();
+        assert!(!line.contains('\n'));
+        let parsed = StreamEvent::from_line(&line).unwrap();
+        let value = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(value, serde_json::to_value(event).unwrap());
+        value
+    }
+
+    #[test]
+    fn test_message_event() {
+        let message = MessageBuilder::new("42", "100").text("Hello").build();
+        let value = round_trip(&StreamEvent::message("telegram", message));
+
+        // Field names consumers rely on
+        assert_eq!(value["type"], "message");
+        assert_eq!(value["schema_version"], 1);
+        assert_eq!(value["source"], "telegram");
+        assert_eq!(value["message"]["id"], "42");
+        assert_eq!(value["message"]["chat_id"], "100");
+        assert_eq!(value["message"]["content"], json!({ "Text": "Hello" }));
+        assert_eq!(value["message"]["timestamp"], "2025-01-15T12:00:00Z");
+    }
+
+    #[test]
+    fn test_dispatch_event() {
+        let dispatch = Dispatch {
+            pipeline: "urgent".to_string(),
+            source: "telegram".to_string(),
+            route: Route::Notify { critical: true },
+            chat: "telegram:100".to_string(),
+            chat_title: Some("Work".to_string()),
+            text: "alice in Work: Hello".to_string(),
+            message: MessageBuilder::new("42", "100").text("Hello").build(),
+        };
+        let value = round_trip(&StreamEvent::dispatch(dispatch));
+
+        assert_eq!(value["type"], "dispatch");
+        assert_eq!(value["schema_version"], 1);
+        assert_eq!(value["source"], "telegram");
+        assert_eq!(value["pipeline"], "urgent");
+        assert_eq!(value["route"], "notify");
+        assert_eq!(value["critical"], true);
+        assert_eq!(value["chat"], "telegram:100");
+        assert_eq!(value["message"]["id"], "42");
+    }
+
+    #[test]
+    fn test_from_line() {
+        // Unknown fields from newer builds of the same version are ignored
+        let line = r#"{"type":"message","schema_version":1,"source":"telegram","extra":true,
+            "message":{"id":"1","chat_id":"2","sender":{"id":"3","username":null,"display_name":null,
+            "phone_number":null},"content":"Sticker","timestamp":"2025-01-15T12:00:00Z",
+            "reply_to":null,"edited":false}}"#;
+        let event = StreamEvent::from_line(&line.replace('\n', "")).unwrap();
+        assert!(matches!(event.event, EventKind::Message { ref source, .. } if source == "telegram"));
+
+        let newer = line.replace("\"schema_version\":1", "\"schema_version\":2").replace('\n', "");
+        assert!(StreamEvent::from_line(&newer).is_err());
+        assert!(StreamEvent::from_line(r#"{"type":"unknown","schema_version":1}"#).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Notify};
+
+use crate::types::Message;
+
+/// What to do when a subscriber falls behind and its buffer is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Discard the oldest buffered message to make room
+    #[default]
+    DropOldest,
+    /// Discard the incoming message
+    DropNewest,
+    /// Stop reading from the source until the subscriber catches up
+    Block,
+}
+
+/// Buffer configuration for a single subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BufferConfig {
+    pub capacity: usize,
+    pub policy: OverflowPolicy,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            policy: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// Point-in-time metrics for a subscription
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SubscriptionStats {
+    /// Messages received from the source
+    pub received: u64,
+    /// Messages handed to the subscriber
+    pub delivered: u64,
+    /// Messages discarded because the buffer was full
+    pub dropped: u64,
+    /// Messages waiting in the buffer
+    pub lag: usize,
+    pub capacity: usize,
+}
+
+struct Shared {
+    buffer: Mutex<VecDeque<Message>>,
+    config: BufferConfig,
+    received: AtomicU64,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    closed: AtomicBool,
+    /// Cleared when the subscriber drops its Subscription
+    active: AtomicBool,
+    data_ready: Notify,
+    space_ready: Notify,
+}
+
+impl Shared {
+    fn stats(&self) -> SubscriptionStats {
+        SubscriptionStats {
+            received: self.received.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            lag: self.buffer.lock().map(|b| b.len()).unwrap_or(0),
+            capacity: self.config.capacity,
+        }
+    }
+
+    /// Buffer a message according to the overflow policy.
+    /// Returns the message back if the policy is Block and the buffer is full.
+    fn push(&self, message: Message) -> Option<Message> {
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buffer.len() >= self.config.capacity {
+            match self.config.policy {
+                OverflowPolicy::DropOldest => {
+                    buffer.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                OverflowPolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+                OverflowPolicy::Block => return Some(message),
+            }
+        }
+
+        buffer.push_back(message);
+        drop(buffer);
+        self.data_ready.notify_one();
+        None
+    }
+
+    fn pop(&self) -> Option<Message> {
+        let message = self.buffer.lock().unwrap_or_else(|e| e.into_inner()).pop_front()?;
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+        self.space_ready.notify_one();
+        Some(message)
+    }
+}
+
+/// Read-only view of a subscription's metrics, usable after the subscription is handed off
+#[derive(Clone)]
+pub struct SubscriptionMonitor {
+    shared: Arc<Shared>,
+}
+
+impl SubscriptionMonitor {
+    pub fn stats(&self) -> SubscriptionStats {
+        self.shared.stats()
+    }
+
+    /// Whether the subscriber still holds the subscription
+    pub fn is_active(&self) -> bool {
+        self.shared.active.load(Ordering::Relaxed)
+    }
+}
+
+/// A bounded, policy-driven buffer between a source stream and a (possibly slow) subscriber
+pub struct Subscription {
+    shared: Arc<Shared>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Subscription {
+    /// Start forwarding messages from a source receiver into a bounded buffer
+    pub fn spawn(mut rx: mpsc::Receiver<Message>, config: BufferConfig) -> Self {
+        let config = BufferConfig {
+            capacity: config.capacity.max(1),
+            ..config
+        };
+
+        let shared = Arc::new(Shared {
+            buffer: Mutex::new(VecDeque::with_capacity(config.capacity.min(1024))),
+            config,
+            received: AtomicU64::new(0),
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            active: AtomicBool::new(true),
+            data_ready: Notify::new(),
+            space_ready: Notify::new(),
+        });
+
+        let forward = Arc::clone(&shared);
+        let task = tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                forward.received.fetch_add(1, Ordering::Relaxed);
+
+                let mut pending = message;
+                while let Some(message) = forward.push(pending) {
+                    // Block policy: wait for the subscriber to free space
+                    forward.space_ready.notified().await;
+                    pending = message;
+                }
+            }
+
+            forward.closed.store(true, Ordering::Release);
+            forward.data_ready.notify_one();
+        });
+
+        Self { shared, task }
+    }
+
+    /// Receive the next message, waiting if the buffer is empty.
+    /// Returns None once the source stream has ended and the buffer is drained.
+    pub async fn recv(&self) -> Option<Message> {
+        loop {
+            if let Some(message) = self.shared.pop() {
+                return Some(message);
+            }
+            if self.shared.closed.load(Ordering::Acquire) {
+                return self.shared.pop();
+            }
+            self.shared.data_ready.notified().await;
+        }
+    }
+
+    /// Receive a buffered message without waiting
+    pub fn try_recv(&self) -> Option<Message> {
+        self.shared.pop()
+    }
+
+    /// Current metrics
+    pub fn stats(&self) -> SubscriptionStats {
+        self.shared.stats()
+    }
+
+    /// Monitor for reading metrics without holding the subscription
+    pub fn monitor(&self) -> SubscriptionMonitor {
+        SubscriptionMonitor {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.shared.active.store(false, Ordering::Relaxed);
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChatId, MessageContent, MessageId, User, UserId};
+
+    fn message(id: usize) -> Message {
+        Message {
+            id: MessageId::new(id.to_string()),
+            chat_id: ChatId::new("chat"),
+            sender: User {
+                id: UserId::new("u"),
+                username: None,
+                display_name: None,
+                phone_number: None,
+            },
+            content: MessageContent::Text(format!("message {}", id)),
+            timestamp: chrono::Utc::now(),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    async fn fill(policy: OverflowPolicy) -> Subscription {
+        let (tx, rx) = mpsc::channel(16);
+        let subscription = Subscription::spawn(rx, BufferConfig { capacity: 2, policy });
+
+        for i in 0..5 {
+            tx.send(message(i)).await.unwrap();
+        }
+        drop(tx);
+
+        // Let the forwarding task drain the channel
+        for _ in 0..100 {
+            if subscription.stats().received >= 3 && subscription.stats().lag == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        subscription
+    }
+
+    async fn drain(subscription: &Subscription) -> Vec<String> {
+        let mut ids = Vec::new();
+        while let Some(message) = subscription.recv().await {
+            ids.push(message.id.as_str().to_string());
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest() {
+        let subscription = fill(OverflowPolicy::DropOldest).await;
+        assert_eq!(subscription.stats().dropped, 3);
+        assert_eq!(drain(&subscription).await, vec!["3", "4"]);
+    }
+
+    #[tokio::test]
+    async fn test_drop_newest() {
+        let subscription = fill(OverflowPolicy::DropNewest).await;
+        assert_eq!(subscription.stats().dropped, 3);
+        assert_eq!(drain(&subscription).await, vec!["0", "1"]);
+    }
+
+    #[tokio::test]
+    async fn test_block_loses_nothing() {
+        let subscription = fill(OverflowPolicy::Block).await;
+        assert_eq!(subscription.stats().lag, 2);
+        assert_eq!(drain(&subscription).await, vec!["0", "1", "2", "3", "4"]);
+        assert_eq!(subscription.stats().dropped, 0);
+        assert_eq!(subscription.stats().delivered, 5);
+    }
+}
@@ -0,0 +1,367 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::sync::RetryPolicy;
+
+/// How long `shutdown` waits for a task to stop before aborting it
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// When a supervised task is started again after failing
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// Delay before each restart. `max_attempts` counts the runs in a row that may fail
+    /// before the task is given up.
+    pub backoff: RetryPolicy,
+    /// A run lasting this long was healthy, and starts the count of failures over
+    pub reset_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            backoff: RetryPolicy {
+                max_attempts: 10,
+                initial_delay: Duration::from_secs(1),
+                max_delay: Duration::from_secs(60),
+            },
+            reset_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Run once, reporting a failure without restarting
+    pub fn never() -> Self {
+        Self {
+            backoff: RetryPolicy { max_attempts: 1, ..RetryPolicy::default() },
+            reset_after: Duration::MAX,
+        }
+    }
+}
+
+/// What a supervised task is doing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// Failed, and starts again after a pause
+    Restarting { attempt: u32, retry_in_secs: u64 },
+    /// Finished, or was shut down
+    Stopped,
+    /// Failed more often in a row than its policy allows, and was given up
+    Failed,
+}
+
+/// Health of a supervised task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskStatus {
+    pub name: String,
+    #[serde(flatten)]
+    pub state: TaskState,
+    /// Restarts since the task was spawned
+    #[serde(default)]
+    pub restarts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// When the task entered its current state
+    pub since: DateTime<Utc>,
+}
+
+impl TaskStatus {
+    fn new(name: String) -> Self {
+        Self { name, state: TaskState::Running, restarts: 0, last_error: None, since: Utc::now() }
+    }
+
+    /// Running or finished, rather than failing
+    pub fn is_healthy(&self) -> bool {
+        matches!(self.state, TaskState::Running | TaskState::Stopped)
+    }
+
+    /// One line for status listings
+    pub fn describe(&self) -> String {
+        let state = match &self.state {
+            TaskState::Running => "running".to_string(),
+            TaskState::Restarting { attempt, retry_in_secs } => {
+                format!("restarting in {}s (attempt {})", retry_in_secs, attempt)
+            }
+            TaskState::Stopped => "stopped".to_string(),
+            TaskState::Failed => "failed, given up".to_string(),
+        };
+        let mut text = format!("{}: {}", self.name, state);
+        if self.restarts > 0 {
+            text.push_str(&format!(", {} restarts", self.restarts));
+        }
+        if let Some(error) = &self.last_error {
+            text.push_str(&format!(", last error: {}", error));
+        }
+        text
+    }
+}
+
+struct Task {
+    status: Arc<Mutex<TaskStatus>>,
+    stop: watch::Sender<bool>,
+    handle: JoinHandle<()>,
+}
+
+/// Runs background tasks, such as a source's network runner, restarting them with
+/// backoff when they fail or panic. Their health can be read at any time, and
+/// `shutdown` stops them newest first, so tasks started on top of others go before
+/// what they depend on. Dropping the supervisor stops its tasks too.
+#[derive(Clone, Default)]
+pub struct Supervisor {
+    tasks: Arc<Mutex<Vec<Task>>>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Supervise a task. `start` makes the future for each run; a run that returns an
+    /// error or panics is started again as `policy` allows, and one that returns `Ok`
+    /// has finished.
+    pub fn spawn<F, Fut>(&self, name: impl Into<String>, policy: RestartPolicy, start: F)
+    where
+        F: FnMut() -> Result<Fut> + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let status = Arc::new(Mutex::new(TaskStatus::new(name.into())));
+        let (stop, stopped) = watch::channel(false);
+        let handle = tokio::spawn(supervise(start, policy, Arc::clone(&status), stopped));
+
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.push(Task { status, stop, handle });
+        }
+    }
+
+    /// Supervise a future that runs once: its failure is reported but not restarted
+    pub fn spawn_once<Fut>(&self, name: impl Into<String>, future: Fut)
+    where
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let mut future = Some(future);
+        self.spawn(name, RestartPolicy::never(), move || future.take().context("The task already ran"));
+    }
+
+    /// Health of every task, oldest first
+    pub fn statuses(&self) -> Vec<TaskStatus> {
+        let Ok(tasks) = self.tasks.lock() else {
+            return Vec::new();
+        };
+        tasks
+            .iter()
+            .filter_map(|task| task.status.lock().ok().map(|status| status.clone()))
+            .collect()
+    }
+
+    /// State of the task with this name
+    pub fn state(&self, name: &str) -> Option<TaskState> {
+        self.statuses().into_iter().find(|status| status.name == name).map(|status| status.state)
+    }
+
+    /// Stop every task, newest first, waiting up to `SHUTDOWN_TIMEOUT` for each before
+    /// aborting it
+    pub async fn shutdown(&self) {
+        let tasks = match self.tasks.lock() {
+            Ok(mut tasks) => std::mem::take(&mut *tasks),
+            Err(_) => return,
+        };
+
+        for mut task in tasks.into_iter().rev() {
+            let _ = task.stop.send(true);
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut task.handle).await.is_err() {
+                let name = task.status.lock().map(|status| status.name.clone()).unwrap_or_default();
+                log::warn!("Task '{}' did not stop in time, aborting it", name);
+                task.handle.abort();
+            }
+        }
+    }
+}
+
+fn set_state(status: &Mutex<TaskStatus>, state: TaskState) {
+    if let Ok(mut status) = status.lock() {
+        status.state = state;
+        status.since = Utc::now();
+    }
+}
+
+/// Run and restart one task until it finishes, is given up or told to stop
+async fn supervise<F, Fut>(
+    mut start: F,
+    policy: RestartPolicy,
+    status: Arc<Mutex<TaskStatus>>,
+    mut stop: watch::Receiver<bool>,
+) where
+    F: FnMut() -> Result<Fut> + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let name = status.lock().map(|status| status.name.clone()).unwrap_or_default();
+    let mut failures = 0;
+
+    loop {
+        let error = match start() {
+            Ok(future) => {
+                set_state(&status, TaskState::Running);
+                let started = Instant::now();
+                let mut run = tokio::spawn(future);
+
+                let result = tokio::select! {
+                    result = &mut run => result,
+                    // Told to stop, or the supervisor is gone
+                    _ = stop.changed() => {
+                        run.abort();
+                        let _ = run.await;
+                        set_state(&status, TaskState::Stopped);
+                        return;
+                    }
+                };
+
+                if started.elapsed() >= policy.reset_after {
+                    failures = 0;
+                }
+                match result {
+                    Ok(Ok(())) => {
+                        set_state(&status, TaskState::Stopped);
+                        return;
+                    }
+                    Ok(Err(e)) => format!("{:#}", e),
+                    Err(e) if e.is_panic() => format!("panicked: {}", panic_message(e.into_panic())),
+                    Err(_) => {
+                        set_state(&status, TaskState::Stopped);
+                        return;
+                    }
+                }
+            }
+            Err(e) => format!("failed to start: {:#}", e),
+        };
+
+        failures += 1;
+        if let Ok(mut status) = status.lock() {
+            status.last_error = Some(error.clone());
+        }
+        if failures >= policy.backoff.max_attempts {
+            log::error!("Task '{}' failed {} times in a row, giving up: {}", name, failures, error);
+            set_state(&status, TaskState::Failed);
+            return;
+        }
+
+        let delay = policy.backoff.delay(failures);
+        log::warn!("Task '{}' failed, restarting in {:?}: {}", name, delay, error);
+        set_state(&status, TaskState::Restarting { attempt: failures, retry_in_secs: delay.as_secs() });
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = stop.changed() => {
+                set_state(&status, TaskState::Stopped);
+                return;
+            }
+        }
+        if let Ok(mut status) = status.lock() {
+            status.restarts += 1;
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map(|s| s.to_string()).unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn quick(max_attempts: u32) -> RestartPolicy {
+        RestartPolicy {
+            backoff: RetryPolicy {
+                max_attempts,
+                initial_delay: Duration::from_millis(5),
+                max_delay: Duration::from_millis(20),
+            },
+            reset_after: Duration::from_secs(60),
+        }
+    }
+
+    /// Wait until the named task's status satisfies `done`
+    async fn wait_for(supervisor: &Supervisor, done: impl Fn(&TaskStatus) -> bool) -> TaskStatus {
+        for _ in 0..200 {
+            if let Some(status) = supervisor.statuses().into_iter().find(|status| done(status)) {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("task never got there: {:?}", supervisor.statuses());
+    }
+
+    #[tokio::test]
+    async fn test_restarts_after_panics() {
+        let supervisor = Supervisor::new();
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&runs);
+        supervisor.spawn("runner", quick(5), move || {
+            let run = counter.fetch_add(1, Ordering::SeqCst);
+            Ok(async move {
+                if run < 2 {
+                    panic!("connection lost");
+                }
+                std::future::pending::<()>().await;
+                Ok::<_, anyhow::Error>(())
+            })
+        });
+
+        let status = wait_for(&supervisor, |status| status.restarts == 2 && status.state == TaskState::Running).await;
+        assert_eq!(status.last_error.as_deref(), Some("panicked: connection lost"));
+        assert!(status.is_healthy());
+
+        supervisor.shutdown().await;
+        assert!(supervisor.statuses().is_empty());
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up() {
+        let supervisor = Supervisor::new();
+        supervisor.spawn("sync", quick(3), || Ok(async { Err::<(), _>(anyhow::anyhow!("flood wait")) }));
+        supervisor.spawn_once("once", async { Ok::<_, anyhow::Error>(()) });
+
+        let status = wait_for(&supervisor, |status| status.state == TaskState::Failed).await;
+        assert_eq!(status.name, "sync");
+        assert_eq!(status.restarts, 2);
+        assert_eq!(status.last_error.as_deref(), Some("flood wait"));
+        assert!(!status.is_healthy());
+        assert_eq!(supervisor.state("once"), Some(TaskState::Stopped));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_order() {
+        struct Guard(&'static str, Arc<Mutex<Vec<&'static str>>>);
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                self.1.lock().unwrap().push(self.0);
+            }
+        }
+
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+        let supervisor = Supervisor::new();
+        for name in ["runner", "updates"] {
+            // Made outside the future, so it is dropped with it even if never polled
+            let guard = Guard(name, Arc::clone(&stopped));
+            supervisor.spawn_once(name, async move {
+                let _guard = guard;
+                std::future::pending::<()>().await;
+                Ok::<_, anyhow::Error>(())
+            });
+        }
+        supervisor.shutdown().await;
+        assert_eq!(*stopped.lock().unwrap(), vec!["updates", "runner"]);
+    }
+}
@@ -0,0 +1,318 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::global_id::GlobalChatId;
+use crate::types::MessageId;
+
+/// Where a chat's sync got to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Newest message written to the archive
+    pub last_message_id: MessageId,
+    pub last_timestamp: DateTime<Utc>,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// A chat whose last sync gave up after all retries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedSync {
+    pub error: String,
+    pub attempts: u32,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Per-chat sync checkpoints and permanent failures, keyed by [`GlobalChatId`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    #[serde(default)]
+    checkpoints: BTreeMap<GlobalChatId, Checkpoint>,
+    #[serde(default)]
+    failed: BTreeMap<GlobalChatId, FailedSync>,
+}
+
+impl SyncState {
+    /// Create an empty sync state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the sync state file (`~/.config/chat/sync.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("sync.json"))
+    }
+
+    /// Load sync state from the default location, returning an empty state if none exists
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load sync state from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read sync state file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse sync state file")
+    }
+
+    /// Save sync state to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save sync state to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create sync state directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize sync state")?;
+
+        // Write then rename so an interrupted save never leaves a truncated file
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, contents).context("Failed to write sync state file")?;
+        std::fs::rename(&tmp, path).context("Failed to write sync state file")
+    }
+
+    pub fn checkpoint(&self, chat: &GlobalChatId) -> Option<&Checkpoint> {
+        self.checkpoints.get(chat)
+    }
+
+    /// Record a successful sync, clearing any earlier failure
+    pub fn set_checkpoint(&mut self, chat: GlobalChatId, checkpoint: Checkpoint) {
+        self.failed.remove(&chat);
+        self.checkpoints.insert(chat, checkpoint);
+    }
+
+    /// Record that a chat failed after all retries
+    pub fn mark_failed(&mut self, chat: GlobalChatId, error: String, attempts: u32) {
+        self.failed.insert(chat, FailedSync {
+            error,
+            attempts,
+            failed_at: Utc::now(),
+        });
+    }
+
+    /// Clear a failure without touching the checkpoint
+    pub fn clear_failed(&mut self, chat: &GlobalChatId) {
+        self.failed.remove(chat);
+    }
+
+    /// Chats whose last sync failed permanently
+    pub fn failed(&self) -> impl Iterator<Item = (&GlobalChatId, &FailedSync)> {
+        self.failed.iter()
+    }
+}
+
+/// `~/.local/share/chat/archive` (platform data directory), where `chat sync` writes
+pub fn default_archive_dir() -> Result<PathBuf> {
+    Ok(dirs::data_dir()
+        .context("Failed to get data directory")?
+        .join("chat")
+        .join("archive"))
+}
+
+/// A chat's file in the sync archive: its messages as JSON lines, oldest first
+pub fn archive_file(dir: &Path, id: &GlobalChatId) -> PathBuf {
+    let name: String = id
+        .to_string()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    dir.join(format!("{}.jsonl", name))
+}
+
+/// Exponential backoff for transient source errors
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Error after a retried operation gave up
+#[derive(Debug)]
+pub struct RetryError {
+    pub attempts: u32,
+    pub last_error: anyhow::Error,
+}
+
+impl std::fmt::Display for RetryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (after {} attempts)", self.last_error, self.attempts)
+    }
+}
+
+impl std::error::Error for RetryError {}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (1-based), doubling each time up to `max_delay`
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Run `operation`, retrying transient errors with backoff.
+    /// `on_retry` is told about each failed attempt before the wait.
+    pub async fn run<T, F, Fut, R>(&self, mut operation: F, mut on_retry: R) -> std::result::Result<T, RetryError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+        R: FnMut(u32, &anyhow::Error, Duration),
+    {
+        let mut attempt = 1;
+
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt < self.max_attempts && is_transient(&error) => {
+                    let delay = self.delay(attempt);
+                    on_retry(attempt, &error, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(error) => return Err(RetryError { attempts: attempt, last_error: error }),
+            }
+        }
+    }
+}
+
+/// Whether an error looks like a network blip worth retrying rather than a
+/// permanent failure (missing chat, bad filter, auth)
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    for cause in error.chain() {
+        if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind::*;
+            if matches!(
+                io.kind(),
+                ConnectionReset | ConnectionAborted | ConnectionRefused | NotConnected | BrokenPipe | TimedOut
+                    | Interrupted | UnexpectedEof
+            ) {
+                return true;
+            }
+        }
+    }
+
+    const MARKERS: &[&str] = &[
+        "timed out", "timeout", "connection reset", "connection closed", "connection refused",
+        "broken pipe", "network", "temporarily", "try again", "flood_wait", "rpc_call_fail",
+        "dropped", "disconnected", "502", "503", "504",
+    ];
+
+    let message = format!("{:#}", error).to_lowercase();
+    MARKERS.iter().any(|marker| message.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn quick() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+        assert_eq!(policy.delay(20), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors() {
+        let calls = AtomicU32::new(0);
+        let counter = &calls;
+        let mut retries = 0;
+
+        let result = quick()
+            .run(
+                move || async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                        anyhow::bail!("Connection reset by peer")
+                    }
+                    Ok("synced")
+                },
+                |_, _, _| retries += 1,
+            )
+            .await;
+
+        assert_eq!(result.unwrap(), "synced");
+        assert_eq!(retries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_fail_fast() {
+        let calls = AtomicU32::new(0);
+        let counter = &calls;
+
+        let result: std::result::Result<(), _> = quick()
+            .run(
+                move || async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    anyhow::bail!("Source 'telegram' not found")
+                },
+                |_, _, _| {},
+            )
+            .await;
+
+        assert_eq!(result.unwrap_err().attempts, 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let exhausted: std::result::Result<(), _> = quick()
+            .run(|| async { anyhow::bail!("request timed out") }, |_, _, _| {})
+            .await;
+        assert_eq!(exhausted.unwrap_err().attempts, 4);
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let chat = GlobalChatId::new("telegram", "-100123");
+        let mut state = SyncState::new();
+        state.mark_failed(chat.clone(), "timeout".to_string(), 5);
+        assert_eq!(state.failed().count(), 1);
+
+        state.set_checkpoint(chat.clone(), Checkpoint {
+            last_message_id: MessageId::new("42"),
+            last_timestamp: Utc::now(),
+            synced_at: Utc::now(),
+        });
+
+        let path = std::env::temp_dir().join(format!("chat-sync-{}.json", uuid::Uuid::new_v4().simple()));
+        state.save_to(&path).unwrap();
+        let loaded = SyncState::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.failed().count(), 0);
+        assert_eq!(loaded.checkpoint(&chat).unwrap().last_message_id.as_str(), "42");
+    }
+}
@@ -17,6 +17,8 @@ pub struct TelegramConfig {
 /// Direct Telegram client using grammers or tdlib
 pub struct TelegramChatClient {
     config: ChatClientConfig,
+    /// Read by the grammers client once it is wired in
+    #[allow(dead_code)]
     telegram_config: TelegramConfig,
     status: ChatClientStatus,
     // In a real implementation, add:
@@ -134,18 +136,21 @@ impl ChatClient for TelegramChatClient {
                     title: Some(user.full_name()),
                     chat_type: ChatType::DirectMessage,
                     participant_count: Some(2),
+                    topic: None,
                 },
                 grammers_client::types::Chat::Group(group) => Chat {
                     id: ChatId::new(group.id().to_string()),
                     title: Some(group.title().to_string()),
                     chat_type: ChatType::Group,
                     participant_count: Some(group.participant_count()),
+                    topic: None,
                 },
                 grammers_client::types::Chat::Channel(channel) => Chat {
                     id: ChatId::new(channel.id().to_string()),
                     title: Some(channel.title().to_string()),
                     chat_type: ChatType::Channel,
                     participant_count: channel.participant_count(),
+                    topic: None,
                 },
             };
             chats.push(chat);
@@ -160,8 +165,8 @@ impl ChatClient for TelegramChatClient {
 
     async fn get_messages(
         &self,
-        chat_id: &ChatId,
-        options: MessageFetchOptions,
+        _chat_id: &ChatId,
+        _options: MessageFetchOptions,
     ) -> Result<Vec<Message>> {
         // In a real implementation using grammers:
         // 1. Parse chat_id to get the chat
@@ -301,6 +306,11 @@ fn convert_telegram_message(
             .unwrap_or_else(|| Utc::now()),
         reply_to: msg.reply_to_message_id().map(|id| MessageId::new(id.to_string())),
         edited: msg.edit_date().is_some(),
+        mentions_me: msg.mentioned(),
+        pending: false,
+        language: None,
+        media_text: None,
+        original_timestamp: None,
     }))
 }
 */
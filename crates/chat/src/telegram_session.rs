@@ -0,0 +1,98 @@
+#![allow(deprecated)] // TlSession is the only file-backed storage that doesn't need SQLite
+
+use anyhow::{Context, Result};
+use grammers_mtsender::SenderPool;
+use grammers_session::storages::TlSession;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::cli::config::Config;
+
+/// A Telegram login session stored on disk.
+///
+/// The CLI, the Telegram source and the dashboard's login all use the same file, so
+/// signing in once with any of them is enough.
+pub struct TelegramSession {
+    session: Arc<TlSession>,
+    path: PathBuf,
+}
+
+impl TelegramSession {
+    /// `session_path` from the Telegram config, or `~/.config/chat/telegram/session.dat`
+    pub fn default_path() -> Result<PathBuf> {
+        match Config::load()?.session_path {
+            Some(path) => Ok(PathBuf::from(path)),
+            None => Config::session_file(),
+        }
+    }
+
+    /// Open the session at the default path
+    pub fn open_default() -> Result<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    /// Open a session file, starting an empty session if it doesn't exist yet
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let session = if path.exists() {
+            TlSession::load_file(&path).with_context(|| format!("Failed to read session {}", path.display()))?
+        } else {
+            TlSession::new()
+        };
+
+        Ok(Self {
+            session: Arc::new(session),
+            path,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Sender pool for a client backed by this session
+    pub fn sender_pool(&self, api_id: i32) -> SenderPool {
+        SenderPool::new(Arc::clone(&self.session), api_id)
+    }
+
+    /// Write the session to disk, readable only by the current user
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, self.session.save())
+            .with_context(|| format!("Failed to write session {}", tmp.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+        }
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to save session {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_reopen() {
+        let path = std::env::temp_dir()
+            .join(format!("chat-session-{}", uuid::Uuid::new_v4().simple()))
+            .join("session.dat");
+
+        let session = TelegramSession::open(&path).unwrap();
+        assert!(!path.exists());
+        session.save().unwrap();
+        assert!(path.exists());
+
+        let reopened = TelegramSession::open(&path).unwrap();
+        assert_eq!(reopened.session.save(), session.session.save());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -1,11 +1,25 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
+use crate::channel_stats::ChannelPosts;
+use crate::global_id::GlobalChatId;
+use crate::progress::{Progress, ProgressEvent};
+use crate::sources_config::SourceSettings;
+use crate::supervisor::TaskStatus;
 use crate::types::{
-    Chat, ChatFilter, ChatId, ChatPattern, ChatSource, ChatType, Message,
-    MessageContent, MessageFilter, MessageId, User, UserId,
+    Chat, ChatFilter, ChatId, ChatPattern, ChatSource, ChatType, FilterPushdown, Message,
+    MessageContent, MessageFilter, MessageId, SourceCapabilities, SystemEvent, TopicId, User, UserId,
 };
 
+#[cfg(feature = "telegram")]
+use crate::channel_stats::Post;
+#[cfg(feature = "telegram")]
+use crate::types::apply_filter;
+#[cfg(feature = "telegram")]
+use futures::StreamExt;
+#[cfg(feature = "telegram")]
+use grammers_client::grammers_tl_types as tl;
 #[cfg(feature = "telegram")]
 use grammers_client::types::Peer;
 #[cfg(feature = "telegram")]
@@ -13,20 +27,40 @@ use grammers_client::Client;
 #[cfg(feature = "telegram")]
 use grammers_mtsender::SenderPool;
 #[cfg(feature = "telegram")]
-use grammers_session::storages::MemorySession;
+use grammers_session::defs::PeerRef;
+#[cfg(feature = "telegram")]
+use crate::supervisor::{RestartPolicy, Supervisor, TaskState};
+#[cfg(feature = "telegram")]
+use crate::telegram_session::TelegramSession;
+#[cfg(feature = "telegram")]
+use std::collections::{HashMap, HashSet};
 #[cfg(feature = "telegram")]
 use std::path::PathBuf;
 #[cfg(feature = "telegram")]
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+
+/// Messages Telegram returns per history request
+#[cfg(feature = "telegram")]
+const FETCH_BATCH: usize = 100;
+
+/// The General topic every forum has; its messages carry no topic reference
 #[cfg(feature = "telegram")]
-use tokio::task::JoinHandle;
+const GENERAL_TOPIC_ID: i32 = 1;
+
+/// Name of the supervised network runner task
+#[cfg(feature = "telegram")]
+const RUNNER_TASK: &str = "runner";
 
 /// Telegram chat source implementation
 pub struct TelegramSource {
+    /// Replaced when the runner is restarted, as a client can't outlive its runner
+    #[cfg(feature = "telegram")]
+    client: Arc<RwLock<Option<Client>>>,
+    /// Watches the network runner, restarting it with a new client when it fails
     #[cfg(feature = "telegram")]
-    client: Option<Client>,
+    supervisor: Supervisor,
     #[cfg(feature = "telegram")]
-    _runner_handle: Option<JoinHandle<()>>,
+    settings: SourceSettings,
 }
 
 impl TelegramSource {
@@ -34,35 +68,74 @@ impl TelegramSource {
     pub fn new() -> Self {
         Self {
             #[cfg(feature = "telegram")]
-            client: None,
+            client: Arc::new(RwLock::new(None)),
             #[cfg(feature = "telegram")]
-            _runner_handle: None,
+            supervisor: Supervisor::new(),
+            #[cfg(feature = "telegram")]
+            settings: SourceSettings::default(),
         }
     }
 
+    /// Create a source with limits other than the defaults
+    pub fn with_settings(settings: SourceSettings) -> Self {
+        let mut source = Self::new();
+        source.configure(settings);
+        source
+    }
+
     /// Connect to Telegram with the given API ID and session file path
     #[cfg(feature = "telegram")]
     pub async fn connect_with_session(&mut self, api_id: i32, session_path: PathBuf) -> Result<()> {
-        // Note: Using MemorySession (session won't persist across restarts)
-        // This avoids SQLite conflicts with WhatsApp storage
-        let session = Arc::new(MemorySession::default());
+        self.disconnect().await;
+        let session = Arc::new(TelegramSession::open(session_path)?);
 
         // Create sender pool and client
-        let pool = SenderPool::new(Arc::clone(&session), api_id);
-        let client = Client::new(&pool);
+        let pool = session.sender_pool(api_id);
+        self.set_client(Some(Client::new(&pool)))?;
 
-        // Start the network runner
-        let SenderPool { runner, .. } = pool;
-        let runner_handle = tokio::spawn(runner.run());
+        // Start the network runner. A restart needs a new pool, and with it a new client.
+        let mut first_pool = Some(pool);
+        let runner_session = Arc::clone(&session);
+        let client = Arc::clone(&self.client);
+        self.supervisor.spawn(RUNNER_TASK, RestartPolicy::default(), move || {
+            let pool = match first_pool.take() {
+                Some(pool) => pool,
+                None => {
+                    let pool = runner_session.sender_pool(api_id);
+                    *client.write().map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))? =
+                        Some(Client::new(&pool));
+                    pool
+                }
+            };
+            let SenderPool { runner, .. } = pool;
+            Ok(async move {
+                runner.run().await;
+                Err::<(), _>(anyhow::anyhow!("The network runner stopped"))
+            })
+        });
 
         // Check if authorized
-        if !client.is_authorized().await? {
-            anyhow::bail!("Not authenticated. Session is invalid.");
+        let authorized = self.client()?.is_authorized().await;
+        if !matches!(authorized, Ok(true)) {
+            self.disconnect().await;
+            authorized?;
+            anyhow::bail!("Not authenticated. Run 'chat telegram init' to sign in.");
         }
+        session.save()?;
+
+        Ok(())
+    }
 
-        self.client = Some(client);
-        self._runner_handle = Some(runner_handle);
+    /// Stop the network runner and drop the client
+    #[cfg(feature = "telegram")]
+    pub async fn disconnect(&self) {
+        self.supervisor.shutdown().await;
+        let _ = self.set_client(None);
+    }
 
+    #[cfg(feature = "telegram")]
+    fn set_client(&self, client: Option<Client>) -> Result<()> {
+        *self.client.write().map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))? = client;
         Ok(())
     }
 
@@ -72,13 +145,375 @@ impl TelegramSource {
         anyhow::bail!("Telegram feature is not enabled");
     }
 
-    /// Get the client reference
+    /// The current client; clients are cheap handles to the shared connection
     #[cfg(feature = "telegram")]
-    fn client(&self) -> Result<&Client> {
+    fn client(&self) -> Result<Client> {
         self.client
-            .as_ref()
+            .read()
+            .ok()
+            .and_then(|client| client.clone())
             .ok_or_else(|| anyhow::anyhow!("Not connected. Call connect_with_session() first."))
     }
+
+    /// Find the dialog peer for a chat ID, accepting older id forms
+    #[cfg(feature = "telegram")]
+    async fn find_peer(&self, chat_id: &ChatId) -> Result<Option<Peer>> {
+        let candidates = GlobalChatId::from_chat_id("telegram", chat_id).candidates();
+        let client = self.client()?;
+        let mut dialogs = client.iter_dialogs();
+        let mut fallback_peer: Option<Peer> = None;
+
+        while let Some(dialog) = dialogs.next().await? {
+            let peer = dialog.peer();
+            let peer_id = peer.id().bot_api_dialog_id().to_string();
+
+            if peer_id == candidates[0] {
+                return Ok(Some(peer.clone()));
+            }
+            if fallback_peer.is_none() && candidates.contains(&peer_id) {
+                fallback_peer = Some(peer.clone());
+            }
+        }
+
+        Ok(fallback_peer)
+    }
+
+    /// Posts of a broadcast channel newer than `since`, newest first, one per album
+    #[cfg(feature = "telegram")]
+    async fn fetch_channel_posts(&self, peer: &Peer, since: DateTime<Utc>) -> Result<Vec<Post>> {
+        let peer_ref = PeerRef::from(peer);
+
+        let mut offset_id = 0;
+        let mut albums = HashSet::new();
+        let mut posts = Vec::new();
+
+        'pages: loop {
+            let response = self
+                .client()?
+                .invoke(&tl::functions::messages::GetHistory {
+                    peer: peer_ref.into(),
+                    offset_id,
+                    offset_date: 0,
+                    add_offset: 0,
+                    limit: FETCH_BATCH as i32,
+                    max_id: 0,
+                    min_id: 0,
+                    hash: 0,
+                })
+                .await?;
+
+            let (raw_messages, _) = raw_page(response);
+            if raw_messages.is_empty() {
+                break;
+            }
+            offset_id = raw_messages.last().map(|m| m.id).unwrap_or(0);
+            let page_size = raw_messages.len();
+
+            for raw in &raw_messages {
+                let post = convert_post(raw);
+                if post.posted < since {
+                    break 'pages;
+                }
+                // Every message of an album carries the album's counts
+                if raw.grouped_id.is_some_and(|album| !albums.insert(album)) {
+                    continue;
+                }
+                posts.push(post);
+            }
+
+            if page_size < FETCH_BATCH {
+                break;
+            }
+        }
+
+        Ok(posts)
+    }
+
+    /// Topics of a forum group as child chats
+    #[cfg(feature = "telegram")]
+    async fn list_topics(&self, peer: &Peer, group: &Chat) -> Result<Vec<Chat>> {
+        let peer_ref = PeerRef::from(peer);
+
+        let mut topics = Vec::new();
+        let mut offset = (0, 0, 0);
+
+        loop {
+            let request = tl::functions::messages::GetForumTopics {
+                peer: peer_ref.into(),
+                q: None,
+                offset_date: offset.0,
+                offset_id: offset.1,
+                offset_topic: offset.2,
+                limit: FETCH_BATCH as i32,
+            };
+            let tl::enums::messages::ForumTopics::Topics(page) = self.client()?.invoke(&request).await?;
+
+            let mut last = None;
+            for topic in &page.topics {
+                if let tl::enums::ForumTopic::Topic(topic) = topic {
+                    topics.push(group.forum_topic(TopicId::new(topic.id.to_string()), topic.title.clone()));
+                    last = Some((topic.date, topic.top_message, topic.id));
+                }
+            }
+
+            match last {
+                Some(next) if page.topics.len() == FETCH_BATCH && topics.len() < page.count as usize => offset = next,
+                _ => break,
+            }
+        }
+
+        Ok(topics)
+    }
+
+    /// Fetch a topic's messages newest first. Telegram filters by topic server-side:
+    /// a topic is the reply thread of its first message, except General, whose
+    /// messages are the group history entries that belong to no other topic.
+    #[cfg(feature = "telegram")]
+    async fn fetch_topic_messages(
+        &self,
+        peer: &Peer,
+        chat_id: &ChatId,
+        topic_id: &TopicId,
+        filter: &MessageFilter,
+        progress: &Progress,
+    ) -> Result<Vec<Message>> {
+        let topic: i32 = topic_id
+            .as_str()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid topic ID '{}'", topic_id))?;
+        let peer_ref = PeerRef::from(peer);
+
+        let max_messages = filter.limit.unwrap_or(self.settings.max_messages_per_chat);
+        let offset_date = filter.before.map(|t| t.timestamp() as i32).unwrap_or(0);
+        let mut offset_id = 0;
+        let mut messages = Vec::new();
+
+        'pages: loop {
+            let response = if topic == GENERAL_TOPIC_ID {
+                self.client()?
+                    .invoke(&tl::functions::messages::GetHistory {
+                        peer: peer_ref.into(),
+                        offset_id,
+                        offset_date,
+                        add_offset: 0,
+                        limit: FETCH_BATCH as i32,
+                        max_id: 0,
+                        min_id: 0,
+                        hash: 0,
+                    })
+                    .await?
+            } else {
+                self.client()?
+                    .invoke(&tl::functions::messages::GetReplies {
+                        peer: peer_ref.into(),
+                        msg_id: topic,
+                        offset_id,
+                        offset_date,
+                        add_offset: 0,
+                        limit: FETCH_BATCH as i32,
+                        max_id: 0,
+                        min_id: 0,
+                        hash: 0,
+                    })
+                    .await?
+            };
+
+            let (raw_messages, names) = raw_page(response);
+            if raw_messages.is_empty() {
+                break;
+            }
+            offset_id = raw_messages.last().map(|m| m.id).unwrap_or(0);
+            let page_size = raw_messages.len();
+
+            for raw in &raw_messages {
+                if topic == GENERAL_TOPIC_ID && raw_topic(raw).is_some() {
+                    continue;
+                }
+
+                let message = convert_raw_message(raw, chat_id, &names);
+                if filter.since.is_some_and(|since| message.timestamp < since) {
+                    break 'pages;
+                }
+                if filter.matches(&message) {
+                    messages.push(message);
+                    if messages.len() >= max_messages {
+                        break 'pages;
+                    }
+                }
+            }
+
+            progress.emit(ProgressEvent::Fetched {
+                source: self.source_id().to_string(),
+                chat_id: chat_id.clone(),
+                count: messages.len(),
+            });
+            if page_size < FETCH_BATCH || progress.is_cancelled() {
+                break;
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Search one chat's messages on Telegram's servers, newest first, within the filter's
+    /// time range. Telegram matches words, so everything it returns is still held to
+    /// `MessageFilter::matches`.
+    #[cfg(feature = "telegram")]
+    async fn search_chat_messages(
+        &self,
+        peer: &Peer,
+        chat_id: &ChatId,
+        term: &str,
+        filter: &MessageFilter,
+        progress: &Progress,
+    ) -> Result<Vec<Message>> {
+        let peer_ref = PeerRef::from(peer);
+
+        let max_messages = filter.limit.unwrap_or(self.settings.max_messages_per_chat);
+        let min_date = filter.since.map(|t| t.timestamp() as i32).unwrap_or(0);
+        let max_date = filter.before.map(|t| t.timestamp() as i32).unwrap_or(0);
+        let mut offset_id = 0;
+        let mut messages = Vec::new();
+
+        'pages: loop {
+            let response = self
+                .client()?
+                .invoke(&tl::functions::messages::Search {
+                    peer: peer_ref.into(),
+                    q: term.to_string(),
+                    from_id: None,
+                    saved_peer_id: None,
+                    saved_reaction: None,
+                    top_msg_id: None,
+                    filter: tl::enums::MessagesFilter::InputMessagesFilterEmpty,
+                    min_date,
+                    max_date,
+                    offset_id,
+                    add_offset: 0,
+                    limit: FETCH_BATCH as i32,
+                    max_id: 0,
+                    min_id: 0,
+                    hash: 0,
+                })
+                .await?;
+
+            let (raw_messages, names) = raw_page(response);
+            if raw_messages.is_empty() {
+                break;
+            }
+            offset_id = raw_messages.last().map(|m| m.id).unwrap_or(0);
+            let page_size = raw_messages.len();
+
+            for raw in &raw_messages {
+                let message = convert_raw_message(raw, chat_id, &names);
+                if filter.matches(&message) {
+                    messages.push(message);
+                    if messages.len() >= max_messages {
+                        break 'pages;
+                    }
+                }
+            }
+
+            progress.emit(ProgressEvent::Fetched {
+                source: self.source_id().to_string(),
+                chat_id: chat_id.clone(),
+                count: messages.len(),
+            });
+            if page_size < FETCH_BATCH || progress.is_cancelled() {
+                break;
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Fetch one chat's messages, giving up with an error once the configured timeout passes
+    #[cfg(feature = "telegram")]
+    async fn fetch_chat_with_timeout(
+        &self,
+        chat_id: ChatId,
+        filter: &MessageFilter,
+        progress: &Progress,
+    ) -> Result<Vec<Message>> {
+        let timeout = self.settings.timeout();
+        match tokio::time::timeout(timeout, self.fetch_chat(&chat_id, filter, progress)).await {
+            Ok(result) => result,
+            Err(_) => anyhow::bail!(
+                "Timed out fetching Telegram chat {} after {}s (timeout_secs in sources.toml)",
+                chat_id,
+                timeout.as_secs()
+            ),
+        }
+    }
+
+    /// Fetch one chat's messages newest first, up to the filter's limit or the
+    /// configured per-chat maximum
+    #[cfg(feature = "telegram")]
+    async fn fetch_chat(&self, chat_id: &ChatId, filter: &MessageFilter, progress: &Progress) -> Result<Vec<Message>> {
+        if progress.is_cancelled() {
+            return Ok(Vec::new());
+        }
+
+        // Topics are fetched through their forum group
+        let topic = chat_id.split_topic();
+        let peer_chat_id = topic.as_ref().map(|(group, _)| group).unwrap_or(chat_id);
+        let Some(peer) = self.find_peer(peer_chat_id).await? else {
+            return Ok(Vec::new());
+        };
+
+        progress.emit(ProgressEvent::ChatStarted {
+            source: self.source_id().to_string(),
+            chat_id: chat_id.clone(),
+        });
+
+        let messages = match (&topic, filter.search.as_deref()) {
+            (Some((_, topic_id)), _) => self.fetch_topic_messages(&peer, chat_id, topic_id, filter, progress).await?,
+            (None, Some(term)) => self.search_chat_messages(&peer, chat_id, term, filter, progress).await?,
+            (None, None) => {
+                let mut messages = Vec::new();
+                let client = self.client()?;
+                let mut msg_iter = client.iter_messages(&peer);
+                let max_messages = filter.limit.unwrap_or(self.settings.max_messages_per_chat);
+
+                while let Some(msg) = msg_iter.next().await? {
+                    let message = convert_message(&msg, &peer);
+                    // History comes newest first, so nothing older is wanted
+                    if filter.since.is_some_and(|since| message.timestamp < since) {
+                        break;
+                    }
+
+                    // Apply filters
+                    if filter.matches(&message) {
+                        messages.push(message);
+
+                        if messages.len() >= max_messages {
+                            break;
+                        }
+
+                        // Report and honor cancellation once per fetched batch
+                        if messages.len() % FETCH_BATCH == 0 {
+                            progress.emit(ProgressEvent::Fetched {
+                                source: self.source_id().to_string(),
+                                chat_id: chat_id.clone(),
+                                count: messages.len(),
+                            });
+                            if progress.is_cancelled() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                messages
+            }
+        };
+
+        progress.emit(ProgressEvent::ChatFinished {
+            source: self.source_id().to_string(),
+            chat_id: chat_id.clone(),
+            count: messages.len(),
+        });
+        Ok(messages)
+    }
 }
 
 impl Default for TelegramSource {
@@ -100,7 +535,8 @@ impl ChatSource for TelegramSource {
     fn is_connected(&self) -> bool {
         #[cfg(feature = "telegram")]
         {
-            self.client.is_some()
+            self.client.read().is_ok_and(|client| client.is_some())
+                && self.supervisor.state(RUNNER_TASK) == Some(TaskState::Running)
         }
         #[cfg(not(feature = "telegram"))]
         {
@@ -108,6 +544,65 @@ impl ChatSource for TelegramSource {
         }
     }
 
+    fn configure(&mut self, settings: SourceSettings) {
+        #[cfg(feature = "telegram")]
+        {
+            self.settings = settings;
+        }
+        #[cfg(not(feature = "telegram"))]
+        {
+            let _ = settings;
+        }
+    }
+
+    fn tasks(&self) -> Vec<TaskStatus> {
+        #[cfg(feature = "telegram")]
+        {
+            self.supervisor.statuses()
+        }
+        #[cfg(not(feature = "telegram"))]
+        {
+            Vec::new()
+        }
+    }
+
+    async fn shutdown(&self) {
+        #[cfg(feature = "telegram")]
+        self.disconnect().await;
+    }
+
+    async fn keep_alive(&self) -> Result<()> {
+        #[cfg(feature = "telegram")]
+        {
+            // updates.getState: cheap, and fails with AUTH_KEY_UNREGISTERED once the session is gone
+            if !self.client()?.is_authorized().await? {
+                anyhow::bail!("Not authenticated: the session was revoked or has expired");
+            }
+            Ok(())
+        }
+        #[cfg(not(feature = "telegram"))]
+        {
+            Ok(())
+        }
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        // grammers can stream updates and download media, but neither is wired up yet
+        SourceCapabilities {
+            sending: true,
+            pending: true,
+            channel_stats: true,
+            // Text is searched on the server, the rest is checked while reading history
+            pushdown: FilterPushdown {
+                search: true,
+                sender: true,
+                time_range: true,
+                content_type: true,
+            },
+            ..SourceCapabilities::default()
+        }
+    }
+
     async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
         #[cfg(feature = "telegram")]
         {
@@ -117,16 +612,25 @@ impl ChatSource for TelegramSource {
 
             while let Some(dialog) = dialogs.next().await? {
                 let peer = dialog.peer();
-                let chat = convert_peer_to_chat(&peer);
+                let chat = convert_peer_to_chat(peer);
 
-                // Apply filter if provided
-                if let Some(ref filter) = filter {
-                    if !filter.matches(&chat) {
-                        continue;
+                // Forum topics are listed as child chats right after their group
+                let topics = if is_forum(peer) {
+                    self.list_topics(peer, &chat).await?
+                } else {
+                    Vec::new()
+                };
+
+                for chat in std::iter::once(chat).chain(topics) {
+                    // Apply filter if provided
+                    if let Some(ref filter) = filter {
+                        if !filter.matches(&chat) {
+                            continue;
+                        }
                     }
-                }
 
-                chats.push(chat);
+                    chats.push(chat);
+                }
             }
 
             Ok(chats)
@@ -138,6 +642,10 @@ impl ChatSource for TelegramSource {
     }
 
     async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+        self.get_messages_with_progress(filter, &Progress::new()).await
+    }
+
+    async fn get_messages_with_progress(&self, filter: MessageFilter, progress: &Progress) -> Result<Vec<Message>> {
         #[cfg(feature = "telegram")]
         {
             filter.validate()?;
@@ -158,7 +666,7 @@ impl ChatSource for TelegramSource {
                         let peer_name = peer.name().unwrap_or("");
 
                         if peer_name.to_lowercase().contains(&name.to_lowercase()) {
-                            let chat_id = ChatId::new(&peer.id().bot_api_dialog_id().to_string());
+                            let chat_id = ChatId::new(peer.id().bot_api_dialog_id().to_string());
                             matched_chats.push(chat_id);
                         }
                     }
@@ -172,65 +680,197 @@ impl ChatSource for TelegramSource {
 
                     while let Some(dialog) = dialogs.next().await? {
                         let peer = dialog.peer();
-                        let chat_id = ChatId::new(&peer.id().bot_api_dialog_id().to_string());
+                        let chat_id = ChatId::new(peer.id().bot_api_dialog_id().to_string());
                         all_chat_ids.push(chat_id);
                     }
 
                     all_chat_ids
                 }
                 ChatPattern::Multiple(ids) => ids.clone(),
-            };
-
-            // Query messages from each chat
-            for chat_id in chats_to_query {
-                // Find the peer for this chat ID
-                let mut dialogs = client.iter_dialogs();
-                let mut found_peer: Option<Peer> = None;
+                ChatPattern::Label(label) => {
+                    anyhow::bail!("Label '{}' must be resolved by SourcesManager", label);
+                }
+                ChatPattern::Topic { .. } => {
+                    // Find matching topics within matching forum groups
+                    let mut matched_topics = Vec::new();
+                    let mut dialogs = client.iter_dialogs();
 
-                while let Some(dialog) = dialogs.next().await? {
-                    let peer = dialog.peer();
-                    let peer_id = peer.id().bot_api_dialog_id().to_string();
+                    while let Some(dialog) = dialogs.next().await? {
+                        let peer = dialog.peer();
+                        if !is_forum(peer) {
+                            continue;
+                        }
 
-                    if peer_id == chat_id.as_str() {
-                        found_peer = Some(peer.clone());
-                        break;
+                        let group = convert_peer_to_chat(peer);
+                        for topic in self.list_topics(peer, &group).await? {
+                            if filter.chat.matches(&topic) {
+                                matched_topics.push(topic.id);
+                            }
+                        }
                     }
+
+                    matched_topics
                 }
+            };
 
-                if let Some(ref peer) = found_peer {
-                    // Fetch messages from this peer
-                    let mut msg_iter = client.iter_messages(peer);
-                    let max_messages = filter.limit.unwrap_or(1000);
-                    let mut count = 0;
+            // Query messages from each chat, a few at a time
+            let fetches = futures::stream::iter(chats_to_query)
+                .map(|chat_id| self.fetch_chat_with_timeout(chat_id, &filter, progress))
+                .buffer_unordered(self.settings.max_parallel_fetches);
+            futures::pin_mut!(fetches);
+            while let Some(messages) = fetches.next().await {
+                all_messages.extend(messages?);
+            }
+            if progress.is_cancelled() {
+                progress.emit(ProgressEvent::Cancelled { source: self.source_id().to_string() });
+            }
 
-                    while let Some(msg) = msg_iter.next().await? {
-                        let message = convert_message(&msg, peer);
+            // Most recent first, cut to the limit
+            Ok(apply_filter(all_messages, &filter))
+        }
+        #[cfg(not(feature = "telegram"))]
+        {
+            anyhow::bail!("Telegram feature is not enabled");
+        }
+    }
 
-                        // Apply filters
-                        if filter.matches(&message) {
-                            all_messages.push(message);
-                            count += 1;
+    async fn get_pending(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+        #[cfg(feature = "telegram")]
+        {
+            let client = self.client()?;
 
-                            if count >= max_messages {
-                                break;
-                            }
+            // Cloud drafts of all chats come in one request
+            let mut drafts = HashMap::new();
+            if let tl::enums::Updates::Updates(updates) = client.invoke(&tl::functions::messages::GetAllDrafts {}).await? {
+                for update in updates.updates {
+                    if let tl::enums::Update::DraftMessage(update) = update {
+                        if let tl::enums::DraftMessage::Message(draft) = update.draft {
+                            drafts.insert(raw_peer_dialog_id(&update.peer), draft);
                         }
                     }
                 }
             }
 
-            // Sort by timestamp (most recent first)
-            all_messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            let mut pending = Vec::new();
+            let mut dialogs = client.iter_dialogs();
 
-            // Apply limit
-            if let Some(limit) = filter.limit {
-                all_messages.truncate(limit);
+            while let Some(dialog) = dialogs.next().await? {
+                let peer = dialog.peer();
+                let chat = convert_peer_to_chat(peer);
+                if !filter.chat.matches(&chat) {
+                    continue;
+                }
+
+                if let Some(draft) = drafts.remove(&peer.id().bot_api_dialog_id()) {
+                    pending.push(convert_draft(&draft, &chat.id));
+                }
+
+                // Scheduled messages need a request per chat
+                let peer_ref = PeerRef::from(peer);
+                let response = client
+                    .invoke(&tl::functions::messages::GetScheduledHistory {
+                        peer: peer_ref.into(),
+                        hash: 0,
+                    })
+                    .await?;
+                let (raw_messages, names) = raw_page(response);
+                for raw in &raw_messages {
+                    let mut message = convert_raw_message(raw, &chat.id, &names);
+                    message.pending = true;
+                    pending.push(message);
+                }
             }
 
-            Ok(all_messages)
+            pending.retain(|message| filter.matches(message));
+            Ok(pending)
         }
         #[cfg(not(feature = "telegram"))]
         {
+            let _ = filter;
+            anyhow::bail!("Telegram feature is not enabled");
+        }
+    }
+
+    async fn message_count(&self, chat_id: &ChatId) -> Result<Option<usize>> {
+        #[cfg(feature = "telegram")]
+        {
+            let Some(peer) = self.find_peer(chat_id).await? else {
+                return Ok(None);
+            };
+            // One request for the history total, without fetching any messages
+            Ok(Some(self.client()?.iter_messages(&peer).total().await?))
+        }
+        #[cfg(not(feature = "telegram"))]
+        {
+            let _ = chat_id;
+            anyhow::bail!("Telegram feature is not enabled");
+        }
+    }
+
+    async fn get_channel_posts(&self, chat_id: &ChatId, since: DateTime<Utc>) -> Result<ChannelPosts> {
+        #[cfg(feature = "telegram")]
+        {
+            let peer = self
+                .find_peer(chat_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Chat '{}' not found", chat_id))?;
+            // Groups have no view counts; only broadcast channels do
+            let Peer::Channel(channel) = &peer else {
+                anyhow::bail!("'{}' is not a channel", peer.name().unwrap_or(chat_id.as_str()));
+            };
+
+            // The full channel has the subscriber count; the dialog's copy is often missing
+            let request = tl::functions::channels::GetFullChannel {
+                channel: tl::types::InputChannel {
+                    channel_id: channel.raw.id,
+                    access_hash: channel.raw.access_hash.unwrap_or(0),
+                }
+                .into(),
+            };
+            let full_count = match self.client()?.invoke(&request).await {
+                Ok(tl::enums::messages::ChatFull::Full(full)) => match full.full_chat {
+                    tl::enums::ChatFull::ChannelFull(full) => full.participants_count,
+                    _ => None,
+                },
+                Err(_) => None,
+            };
+
+            Ok(ChannelPosts {
+                title: peer.name().map(|name| name.to_string()),
+                subscribers: full_count.or(channel.raw.participants_count).map(|count| count.max(0) as u64),
+                posts: self.fetch_channel_posts(&peer, since).await?,
+            })
+        }
+        #[cfg(not(feature = "telegram"))]
+        {
+            let _ = (chat_id, since);
+            anyhow::bail!("Telegram feature is not enabled");
+        }
+    }
+
+    async fn send_message(&self, chat_id: &ChatId, text: &str, reply_to: Option<&MessageId>) -> Result<Message> {
+        #[cfg(feature = "telegram")]
+        {
+            use grammers_client::InputMessage;
+
+            let peer = self
+                .find_peer(chat_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Chat '{}' not found", chat_id))?;
+            let reply_to = reply_to
+                .map(|id| id.as_str().parse::<i32>())
+                .transpose()
+                .map_err(|_| anyhow::anyhow!("'{}' is not a Telegram message ID", reply_to.map(MessageId::as_str).unwrap_or_default()))?;
+
+            let sent = self
+                .client()?
+                .send_message(&peer, InputMessage::new().text(text).reply_to(reply_to))
+                .await?;
+            Ok(convert_message(&sent, &peer))
+        }
+        #[cfg(not(feature = "telegram"))]
+        {
+            let _ = (chat_id, text, reply_to);
             anyhow::bail!("Telegram feature is not enabled");
         }
     }
@@ -245,7 +885,7 @@ impl ChatSource for TelegramSource {
 // Helper functions for Telegram-specific conversions
 #[cfg(feature = "telegram")]
 fn convert_peer_to_chat(peer: &Peer) -> Chat {
-    let chat_id = ChatId::new(&peer.id().bot_api_dialog_id().to_string());
+    let chat_id = ChatId::new(peer.id().bot_api_dialog_id().to_string());
     let title = peer.name().map(|s| s.to_string());
 
     // Note: grammers v0.8 API doesn't provide easy peer type discrimination
@@ -257,13 +897,20 @@ fn convert_peer_to_chat(peer: &Peer) -> Chat {
         title,
         chat_type,
         participant_count: None, // grammers doesn't easily provide this
+        topic: None,
     }
 }
 
 #[cfg(feature = "telegram")]
-fn convert_message(msg: &grammers_client::types::Message, peer: &Peer) -> Message {
-    let id = MessageId::new(&msg.id().to_string());
-    let chat_id = ChatId::new(&peer.id().bot_api_dialog_id().to_string());
+pub(crate) fn convert_message(msg: &grammers_client::types::Message, peer: &Peer) -> Message {
+    convert_message_in(msg, ChatId::new(peer.id().bot_api_dialog_id().to_string()))
+}
+
+/// Convert a message of the chat `chat_id`, such as one from an update, whose chat isn't
+/// at hand as a peer
+#[cfg(feature = "telegram")]
+pub(crate) fn convert_message_in(msg: &grammers_client::types::Message, chat_id: ChatId) -> Message {
+    let id = MessageId::new(msg.id().to_string());
     let timestamp = msg.date();
 
     // Get sender info
@@ -279,7 +926,7 @@ fn convert_message(msg: &grammers_client::types::Message, peer: &Peer) -> Messag
         };
 
         User {
-            id: UserId::new(&sender_id.to_string()),
+            id: UserId::new(sender_id.to_string()),
             username: None,
             display_name: Some(display_name),
             phone_number: None,
@@ -294,7 +941,14 @@ fn convert_message(msg: &grammers_client::types::Message, peer: &Peer) -> Messag
     };
 
     // Extract message content
-    let content = if !msg.text().is_empty() {
+    let content = if let Some(action) = msg.action() {
+        // Service messages only carry user IDs; the sender is the one name we know
+        let sender_id = msg.sender().map(|peer| peer.id().bot_api_dialog_id());
+        let sender_name = sender.display_name.clone().unwrap_or_default();
+        MessageContent::System(convert_action(action, sender_id, |id| {
+            if Some(id) == sender_id { sender_name.clone() } else { id.to_string() }
+        }))
+    } else if !msg.text().is_empty() {
         MessageContent::Text(msg.text().to_string())
     } else if msg.media().is_some() {
         MessageContent::Unknown
@@ -304,7 +958,7 @@ fn convert_message(msg: &grammers_client::types::Message, peer: &Peer) -> Messag
 
     let reply_to = msg
         .reply_to_message_id()
-        .map(|id| MessageId::new(&id.to_string()));
+        .map(|id| MessageId::new(id.to_string()));
 
     Message {
         id,
@@ -314,6 +968,218 @@ fn convert_message(msg: &grammers_client::types::Message, peer: &Peer) -> Messag
         timestamp,
         reply_to,
         edited: msg.edit_date().is_some(),
+        mentions_me: msg.mentioned(),
+        pending: false,
+        language: None,
+        media_text: None,
+        original_timestamp: None,
+    }
+}
+
+/// System event of a service message. `name` names a user by ID.
+#[cfg(feature = "telegram")]
+fn convert_action(action: &tl::enums::MessageAction, sender_id: Option<i64>, name: impl Fn(i64) -> String) -> SystemEvent {
+    use tl::enums::MessageAction;
+
+    match action {
+        MessageAction::ChatAddUser(add) if add.users.iter().all(|id| Some(*id) == sender_id) => SystemEvent::Joined,
+        MessageAction::ChatAddUser(add) => SystemEvent::Added {
+            users: add.users.iter().map(|id| name(*id)).collect(),
+        },
+        MessageAction::ChatJoinedByLink(_) | MessageAction::ChatJoinedByRequest => SystemEvent::Joined,
+        MessageAction::ChatDeleteUser(delete) if Some(delete.user_id) == sender_id => SystemEvent::Left,
+        MessageAction::ChatDeleteUser(delete) => SystemEvent::Removed { user: name(delete.user_id) },
+        MessageAction::ChatCreate(create) => SystemEvent::Created { title: create.title.clone() },
+        MessageAction::ChannelCreate(create) => SystemEvent::Created { title: create.title.clone() },
+        MessageAction::ChatEditTitle(edit) => SystemEvent::TitleChanged { title: edit.title.clone() },
+        MessageAction::ChatEditPhoto(_) | MessageAction::ChatDeletePhoto => SystemEvent::PhotoChanged,
+        MessageAction::PinMessage => SystemEvent::Pinned,
+        other => {
+            // Debug output starts with the variant name, e.g. "GroupCall(..."
+            let debug = format!("{:?}", other);
+            let kind = debug.split(['(', ' ']).next().unwrap_or_default();
+            SystemEvent::Other { description: format!("service event {}", kind) }
+        }
+    }
+}
+
+/// Whether a dialog is a forum supergroup with topics
+#[cfg(feature = "telegram")]
+fn is_forum(peer: &Peer) -> bool {
+    match peer {
+        Peer::Group(group) => matches!(&group.raw, tl::enums::Chat::Channel(channel) if channel.forum),
+        _ => false,
+    }
+}
+
+/// Topic a raw forum message belongs to, if any other than General
+#[cfg(feature = "telegram")]
+fn raw_topic(raw: &tl::types::Message) -> Option<i32> {
+    match &raw.reply_to {
+        Some(tl::enums::MessageReplyHeader::Header(header)) if header.forum_topic => {
+            header.reply_to_top_id.or(header.reply_to_msg_id)
+        }
+        _ => None,
+    }
+}
+
+/// Messages of a raw history page plus the sender names it came with
+#[cfg(feature = "telegram")]
+fn raw_page(response: tl::enums::messages::Messages) -> (Vec<tl::types::Message>, HashMap<i64, String>) {
+    let (messages, users, chats) = match response {
+        tl::enums::messages::Messages::Messages(m) => (m.messages, m.users, m.chats),
+        tl::enums::messages::Messages::Slice(m) => (m.messages, m.users, m.chats),
+        tl::enums::messages::Messages::ChannelMessages(m) => (m.messages, m.users, m.chats),
+        tl::enums::messages::Messages::NotModified(_) => (Vec::new(), Vec::new(), Vec::new()),
+    };
+
+    let mut names = HashMap::new();
+    for user in users {
+        if let tl::enums::User::User(user) = user {
+            let name = [user.first_name, user.last_name].into_iter().flatten().collect::<Vec<_>>().join(" ");
+            names.insert(user.id, name);
+        }
+    }
+    for chat in chats {
+        match chat {
+            tl::enums::Chat::Chat(chat) => {
+                names.insert(chat.id, chat.title);
+            }
+            tl::enums::Chat::Channel(channel) => {
+                names.insert(channel.id, channel.title);
+            }
+            _ => {}
+        }
+    }
+
+    let messages = messages
+        .into_iter()
+        .filter_map(|message| match message {
+            tl::enums::Message::Message(message) => Some(message),
+            _ => None,
+        })
+        .collect();
+
+    (messages, names)
+}
+
+/// Bot-API dialog id of a raw peer
+#[cfg(feature = "telegram")]
+fn raw_peer_dialog_id(peer: &tl::enums::Peer) -> i64 {
+    match peer {
+        tl::enums::Peer::User(user) => user.user_id,
+        tl::enums::Peer::Chat(chat) => -chat.chat_id,
+        tl::enums::Peer::Channel(channel) => -1_000_000_000_000 - channel.channel_id,
+    }
+}
+
+/// Convert a cloud draft into a pending message from me
+#[cfg(feature = "telegram")]
+fn convert_draft(draft: &tl::types::DraftMessage, chat_id: &ChatId) -> Message {
+    let reply_to = match &draft.reply_to {
+        Some(tl::enums::InputReplyTo::Message(reply)) => Some(MessageId::new(reply.reply_to_msg_id.to_string())),
+        _ => None,
+    };
+
+    Message {
+        id: MessageId::new(crate::types::DRAFT_MESSAGE_ID),
+        chat_id: chat_id.clone(),
+        sender: User {
+            id: UserId::new("me"),
+            username: None,
+            display_name: Some("User".to_string()),
+            phone_number: None,
+        },
+        content: MessageContent::Text(draft.message.clone()),
+        timestamp: chrono::DateTime::from_timestamp(draft.date as i64, 0).unwrap_or_default(),
+        reply_to,
+        edited: false,
+        mentions_me: false,
+        pending: true,
+        language: None,
+        media_text: None,
+        original_timestamp: None,
+    }
+}
+
+/// Convert a raw message fetched outside grammers' message iterators
+#[cfg(feature = "telegram")]
+fn convert_raw_message(raw: &tl::types::Message, chat_id: &ChatId, names: &HashMap<i64, String>) -> Message {
+    let sender_id = match &raw.from_id {
+        Some(tl::enums::Peer::User(user)) => Some(user.user_id),
+        Some(tl::enums::Peer::Chat(chat)) => Some(chat.chat_id),
+        Some(tl::enums::Peer::Channel(channel)) => Some(channel.channel_id),
+        None => None,
+    };
+
+    let sender = User {
+        id: UserId::new(sender_id.map(|id| id.to_string()).unwrap_or_else(|| "unknown".to_string())),
+        username: None,
+        display_name: Some(if raw.out {
+            "User".to_string()
+        } else {
+            sender_id
+                .and_then(|id| names.get(&id).cloned())
+                .unwrap_or_else(|| "Unknown".to_string())
+        }),
+        phone_number: None,
+    };
+
+    let content = if !raw.message.is_empty() {
+        MessageContent::Text(raw.message.clone())
+    } else if raw.media.is_some() {
+        MessageContent::Unknown
+    } else {
+        MessageContent::Text("".to_string())
+    };
+
+    // Within a topic every message points at the topic's first message; only
+    // replies inside the thread point at something else
+    let reply_to = match &raw.reply_to {
+        Some(tl::enums::MessageReplyHeader::Header(header)) => header
+            .reply_to_msg_id
+            .filter(|_| !header.forum_topic || header.reply_to_top_id.is_some())
+            .map(|id| MessageId::new(id.to_string())),
+        _ => None,
+    };
+
+    Message {
+        id: MessageId::new(raw.id.to_string()),
+        chat_id: chat_id.clone(),
+        sender,
+        content,
+        timestamp: chrono::DateTime::from_timestamp(raw.date as i64, 0).unwrap_or_default(),
+        reply_to,
+        edited: raw.edit_date.is_some(),
+        mentions_me: raw.mentioned,
+        pending: false,
+        language: None,
+        media_text: None,
+        original_timestamp: None,
+    }
+}
+
+/// A channel post with its view, forward, reply and reaction counts
+#[cfg(feature = "telegram")]
+fn convert_post(raw: &tl::types::Message) -> Post {
+    let replies = raw.replies.as_ref().map(|tl::enums::MessageReplies::Replies(replies)| replies.replies);
+    let reactions = raw.reactions.as_ref().map(|tl::enums::MessageReactions::Reactions(reactions)| {
+        reactions
+            .results
+            .iter()
+            .map(|tl::enums::ReactionCount::Count(count)| count.count.max(0) as u64)
+            .sum()
+    });
+    let count = |n: Option<i32>| n.map(|n| n.max(0) as u64);
+
+    Post {
+        id: MessageId::new(raw.id.to_string()),
+        posted: DateTime::from_timestamp(raw.date as i64, 0).unwrap_or_default(),
+        text: raw.message.clone(),
+        views: count(raw.views),
+        forwards: count(raw.forwards),
+        replies: count(replies),
+        reactions,
     }
 }
 
@@ -0,0 +1,469 @@
+//! Builders, generators and an in-memory source for tests.
+//!
+//! Enabled by the `test-utils` feature, so downstream crates can add
+//! `chat = { ..., features = ["test-utils"] }` to their dev-dependencies.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use crate::types::{
+    apply_filter, Chat, ChatFilter, ChatId, ChatSource, ChatType, Message, MessageContent, MessageFilter, MessageId,
+    SourceCapabilities, TopicId, User, UserId,
+};
+
+/// Fixed point in time generated messages count from, so test output is stable
+pub fn base_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap()
+}
+
+/// A user with `id` as username and a capitalized display name
+pub fn user(id: &str) -> User {
+    let mut chars = id.chars();
+    let display_name = chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect::<String>());
+    User {
+        id: UserId::new(id),
+        username: Some(id.to_string()),
+        display_name,
+        phone_number: None,
+    }
+}
+
+/// Builder for [`Chat`]; a group without a title by default
+pub struct ChatBuilder {
+    chat: Chat,
+}
+
+impl ChatBuilder {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            chat: Chat {
+                id: ChatId::new(id),
+                title: None,
+                chat_type: ChatType::Group,
+                participant_count: None,
+                topic: None,
+            },
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.chat.title = Some(title.into());
+        self
+    }
+
+    pub fn chat_type(mut self, chat_type: ChatType) -> Self {
+        self.chat.chat_type = chat_type;
+        self
+    }
+
+    pub fn direct(self) -> Self {
+        self.chat_type(ChatType::DirectMessage)
+    }
+
+    pub fn channel(self) -> Self {
+        self.chat_type(ChatType::Channel)
+    }
+
+    pub fn participants(mut self, count: usize) -> Self {
+        self.chat.participant_count = Some(count);
+        self
+    }
+
+    pub fn build(self) -> Chat {
+        self.chat
+    }
+
+    /// The chat plus one child chat per topic, as a forum group is listed
+    pub fn forum(self, topics: &[(&str, &str)]) -> Vec<Chat> {
+        let group = self.chat;
+        let mut chats: Vec<Chat> = topics
+            .iter()
+            .map(|(id, name)| group.forum_topic(TopicId::new(*id), *name))
+            .collect();
+        chats.insert(0, group);
+        chats
+    }
+}
+
+/// Builder for [`Message`]; a text message from "alice" at [`base_time`] by default
+pub struct MessageBuilder {
+    message: Message,
+}
+
+impl MessageBuilder {
+    pub fn new(id: impl Into<String>, chat_id: impl Into<String>) -> Self {
+        Self {
+            message: Message {
+                id: MessageId::new(id),
+                chat_id: ChatId::new(chat_id),
+                sender: user("alice"),
+                content: MessageContent::Text(String::new()),
+                timestamp: base_time(),
+                reply_to: None,
+                edited: false,
+                mentions_me: false,
+                pending: false,
+                language: None,
+                media_text: None,
+                original_timestamp: None,
+            },
+        }
+    }
+
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.content(MessageContent::Text(text.into()))
+    }
+
+    pub fn content(mut self, content: MessageContent) -> Self {
+        self.message.content = content;
+        self
+    }
+
+    pub fn sender(mut self, sender: User) -> Self {
+        self.message.sender = sender;
+        self
+    }
+
+    /// Sender built with [`user`]
+    pub fn from(self, id: &str) -> Self {
+        self.sender(user(id))
+    }
+
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.message.timestamp = timestamp;
+        self
+    }
+
+    /// Timestamp `minutes` after [`base_time`]
+    pub fn minute(self, minutes: i64) -> Self {
+        self.at(base_time() + Duration::minutes(minutes))
+    }
+
+    pub fn reply_to(mut self, id: impl Into<String>) -> Self {
+        self.message.reply_to = Some(MessageId::new(id));
+        self
+    }
+
+    pub fn edited(mut self) -> Self {
+        self.message.edited = true;
+        self
+    }
+
+    pub fn mentions_me(mut self) -> Self {
+        self.message.mentions_me = true;
+        self
+    }
+
+    /// An unsent scheduled message
+    pub fn pending(mut self) -> Self {
+        self.message.pending = true;
+        self
+    }
+
+    pub fn build(self) -> Message {
+        self.message
+    }
+}
+
+/// Deterministic generator of chats and messages spread over them.
+///
+/// Message `i` goes to chat `i % chats`, is sent by user `i % senders`, and is
+/// `step` after message `i - 1`, starting at `start`.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    pub chats: usize,
+    pub senders: usize,
+    pub start: DateTime<Utc>,
+    pub step: Duration,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            chats: 10,
+            senders: 5,
+            start: base_time(),
+            step: Duration::seconds(1),
+        }
+    }
+}
+
+impl Generator {
+    /// Chats "0".."chats", titled "Chat N", cycling through direct, group and channel
+    pub fn chats(&self) -> Vec<Chat> {
+        (0..self.chats)
+            .map(|i| {
+                let chat_type = match i % 3 {
+                    0 => ChatType::DirectMessage,
+                    1 => ChatType::Group,
+                    _ => ChatType::Channel,
+                };
+                ChatBuilder::new(i.to_string())
+                    .title(format!("Chat {}", i))
+                    .chat_type(chat_type)
+                    .participants(i % 50 + 2)
+                    .build()
+            })
+            .collect()
+    }
+
+    /// `count` text messages, oldest first
+    pub fn messages(&self, count: usize) -> Vec<Message> {
+        (0..count)
+            .map(|i| {
+                MessageBuilder::new(i.to_string(), (i % self.chats.max(1)).to_string())
+                    .from(&format!("user{}", i % self.senders.max(1)))
+                    .text(format!("Test message {} with some content to search through", i))
+                    .at(self.start + self.step * i as i32)
+                    .build()
+            })
+            .collect()
+    }
+}
+
+/// Chat source serving chats and messages from memory.
+///
+/// Filtering follows the reference semantics in [`crate::types`]: `ChatPattern::matches`
+/// on the message's chat, then `MessageFilter::matches`, newest first, cut to the limit.
+pub struct InMemorySource {
+    id: String,
+    name: String,
+    connected: Arc<AtomicBool>,
+    capabilities: SourceCapabilities,
+    chats: Vec<Chat>,
+    messages: Vec<Message>,
+    pending: Vec<Message>,
+    stream: Mutex<Option<mpsc::Receiver<Message>>>,
+    sent: Arc<Mutex<Vec<Message>>>,
+}
+
+impl InMemorySource {
+    /// A connected source with the default (read-only) capabilities and no data
+    pub fn new(id: impl Into<String>) -> Self {
+        let id = id.into();
+        Self {
+            name: id.clone(),
+            id,
+            connected: Arc::new(AtomicBool::new(true)),
+            capabilities: SourceCapabilities::default(),
+            chats: Vec::new(),
+            messages: Vec::new(),
+            pending: Vec::new(),
+            stream: Mutex::new(None),
+            sent: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_chats(mut self, chats: Vec<Chat>) -> Self {
+        self.chats = chats;
+        self
+    }
+
+    pub fn with_messages(mut self, messages: Vec<Message>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Drafts and scheduled messages; turns on the pending capability
+    pub fn with_pending(mut self, pending: Vec<Message>) -> Self {
+        self.pending = pending;
+        self.capabilities.pending = true;
+        self
+    }
+
+    pub fn with_capabilities(mut self, capabilities: SourceCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    pub fn disconnected(self) -> Self {
+        self.connected.store(false, Ordering::Relaxed);
+        self
+    }
+
+    /// Switch for the connection state that keeps working after the source is registered
+    pub fn connection(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.connected)
+    }
+
+    /// Turn on streaming; messages sent on the returned channel arrive at the subscriber
+    pub fn streaming(mut self) -> (Self, mpsc::Sender<Message>) {
+        let (tx, rx) = mpsc::channel(64);
+        self.stream = Mutex::new(Some(rx));
+        self.capabilities.streaming = true;
+        (self, tx)
+    }
+
+    /// Turn on sending; sent messages are kept in the returned list, sent by "me"
+    pub fn sending(mut self) -> (Self, Arc<Mutex<Vec<Message>>>) {
+        self.capabilities.sending = true;
+        let sent = Arc::clone(&self.sent);
+        (self, sent)
+    }
+
+    /// The chat a message belongs to, or a bare chat when it isn't in the chat list
+    fn chat_of(&self, chat_id: &ChatId) -> Chat {
+        self.chats
+            .iter()
+            .find(|chat| &chat.id == chat_id)
+            .cloned()
+            .unwrap_or_else(|| ChatBuilder::new(chat_id.as_str()).build())
+    }
+
+    fn filter(&self, messages: &[Message], filter: &MessageFilter) -> Vec<Message> {
+        let in_chats = messages
+            .iter()
+            .filter(|message| filter.chat.matches(&self.chat_of(&message.chat_id)))
+            .cloned();
+        apply_filter(in_chats, filter)
+    }
+}
+
+#[async_trait::async_trait]
+impl ChatSource for InMemorySource {
+    fn source_id(&self) -> &str {
+        &self.id
+    }
+
+    fn source_name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        self.capabilities
+    }
+
+    async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
+        Ok(self
+            .chats
+            .iter()
+            .filter(|chat| filter.as_ref().is_none_or(|f| f.matches(chat)))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+        Ok(self.filter(&self.messages, &filter))
+    }
+
+    async fn get_pending(&self, filter: MessageFilter) -> Result<Vec<Message>> {
+        Ok(self.filter(&self.pending, &filter))
+    }
+
+    async fn message_count(&self, chat_id: &ChatId) -> Result<Option<usize>> {
+        Ok(Some(self.messages.iter().filter(|m| &m.chat_id == chat_id).count()))
+    }
+
+    async fn send_message(&self, chat_id: &ChatId, text: &str, reply_to: Option<&MessageId>) -> Result<Message> {
+        let mut sent = self.sent.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        let mut message = MessageBuilder::new(format!("sent-{}", sent.len() + 1), chat_id.as_str())
+            .from("me")
+            .text(text)
+            .at(Utc::now())
+            .build();
+        message.reply_to = reply_to.cloned();
+        sent.push(message.clone());
+        Ok(message)
+    }
+
+    async fn subscribe(&self) -> Result<Option<mpsc::Receiver<Message>>> {
+        let mut stream = self.stream.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire write lock: {}", e))?;
+        Ok(stream.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ChatPattern;
+    use crate::{BufferConfig, SourcesManager};
+
+    #[test]
+    fn test_builders() {
+        let message = MessageBuilder::new("1", "42").from("bob").text("hi").minute(5).reply_to("0").build();
+        assert_eq!(message.sender.display_name.as_deref(), Some("Bob"));
+        assert_eq!(message.timestamp, base_time() + Duration::minutes(5));
+        assert_eq!(message.reply_to, Some(MessageId::new("0")));
+
+        let forum = ChatBuilder::new("-100").title("Dev").forum(&[("7", "Releases")]);
+        assert_eq!(forum.len(), 2);
+        assert_eq!(forum[1].title.as_deref(), Some("Dev / Releases"));
+
+        let generated = Generator { chats: 3, ..Generator::default() }.messages(7);
+        assert_eq!(generated[4].chat_id, ChatId::new("1"));
+        assert_eq!(generated[6].timestamp - generated[0].timestamp, Duration::seconds(6));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_source() {
+        let generator = Generator::default();
+        let source = InMemorySource::new("mem")
+            .with_chats(generator.chats())
+            .with_messages(generator.messages(100))
+            .with_pending(vec![MessageBuilder::new("draft", "3").text("later").pending().build()]);
+        let connection = source.connection();
+        let (source, stream) = source.streaming();
+
+        let manager = SourcesManager::new();
+        manager.register(Box::new(source)).unwrap();
+
+        let filter = MessageFilter {
+            chat: ChatPattern::Name("chat 3".into()),
+            limit: Some(3),
+            ..MessageFilter::new()
+        };
+        let messages = manager.query_messages(Some("mem"), filter.clone()).await.unwrap();
+        assert_eq!(messages.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), ["93", "83", "73"]);
+
+        let pending = manager.pending_messages("mem", filter).await.unwrap();
+        assert_eq!(pending.len(), 1);
+
+        let chats = manager.list_chats("mem", Some(ChatFilter::new().with_type(ChatType::Channel))).await.unwrap();
+        assert_eq!(chats.len(), 3);
+
+        let subscription = manager.subscribe("mem", BufferConfig::default()).await.unwrap().unwrap();
+        stream.send(MessageBuilder::new("live", "1").text("new").build()).await.unwrap();
+        assert_eq!(subscription.recv().await.unwrap().id.as_str(), "live");
+
+        connection.store(false, Ordering::Relaxed);
+        assert!(manager.list_chats("mem", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sending() {
+        let manager = SourcesManager::new();
+        let (source, sent) = InMemorySource::new("mem").sending();
+        manager.register(Box::new(source)).unwrap();
+
+        let reply_to = MessageId::new("41");
+        let message = manager.send_message("mem", &ChatId::new("1"), "On it", Some(&reply_to)).await.unwrap();
+        assert_eq!(message.reply_to, Some(reply_to));
+        assert_eq!(sent.lock().unwrap().len(), 1);
+        assert!(manager.send_message("mem", &ChatId::new("1"), "  ", None).await.is_err());
+
+        manager.set_read_only(true);
+        let err = manager.send_message("mem", &ChatId::new("1"), "Again", None).await.unwrap_err();
+        assert!(err.to_string().starts_with("Read-only mode"), "{}", err);
+        assert_eq!(sent.lock().unwrap().len(), 1);
+
+        let manager = SourcesManager::new();
+        manager.register(Box::new(InMemorySource::new("mem"))).unwrap();
+        assert!(manager.send_message("mem", &ChatId::new("1"), "Hi", None).await.is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! Case- and accent-insensitive text matching for search and sender filters
+
+/// Letters that fold to another letter once their accents are dropped, by lowercase base.
+/// Covers the Latin, Greek and Cyrillic letters that decompose into a base and diacritics.
+const ACCENTED: &[(char, &str)] = &[
+    ('a', "àáâãäåāăąǎȧạảấầẩẫậắằẳẵặ"),
+    ('c', "çćĉċč"),
+    ('d', "ďđ"),
+    ('e', "èéêëēĕėęěẹẻẽếềểễệ"),
+    ('g', "ĝğġģǧ"),
+    ('h', "ĥħ"),
+    ('i', "ìíîïĩīĭįıǐỉị"),
+    ('j', "ĵ"),
+    ('k', "ķǩ"),
+    ('l', "ĺļľŀł"),
+    ('n', "ñńņňǹ"),
+    ('o', "òóôõöøōŏőǒơọỏốồổỗộớờởỡợ"),
+    ('r', "ŕŗř"),
+    ('s', "śŝşšș"),
+    ('t', "ţťŧț"),
+    ('u', "ùúûüũūŭůűųưǔǖǘǚǜụủứừửữự"),
+    ('w', "ŵẁẃẅ"),
+    ('y', "ýÿŷỳỵỷỹ"),
+    ('z', "źżž"),
+    ('α', "ά"),
+    ('ε', "έ"),
+    ('η', "ή"),
+    ('ι', "ίϊΐ"),
+    ('ο', "ό"),
+    ('σ', "ς"),
+    ('υ', "ύϋΰ"),
+    ('ω', "ώ"),
+    ('е', "ёѐ"),
+    ('и', "йѝ"),
+    ('і', "ї"),
+    ('у', "ў"),
+];
+
+/// Letters that fold to more than one letter
+const EXPANDED: &[(char, &str)] = &[('ß', "ss"), ('æ', "ae"), ('œ', "oe")];
+
+/// Fold text for comparison: lowercase it and drop accents, so "CAFÉ", "Café" and "cafe"
+/// all become "cafe". Combining marks are dropped too, so decomposed input folds the same.
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii() {
+            folded.push(c);
+        } else if ('\u{0300}'..='\u{036F}').contains(&c) {
+            // Combining diacritical mark
+        } else if let Some((_, expanded)) = EXPANDED.iter().find(|(letter, _)| *letter == c) {
+            folded.push_str(expanded);
+        } else if let Some((base, _)) = ACCENTED.iter().find(|(_, accented)| accented.contains(c)) {
+            folded.push(*base);
+        } else {
+            folded.push(c);
+        }
+    }
+    folded
+}
+
+/// Whether `haystack` contains `needle`, ignoring case and accents unless `exact`
+pub fn contains(haystack: &str, needle: &str, exact: bool) -> bool {
+    if exact {
+        haystack.contains(needle)
+    } else {
+        fold(haystack).contains(&fold(needle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_latin() {
+        assert_eq!(fold("CAFÉ"), "cafe");
+        assert_eq!(fold("Café crème"), "cafe creme");
+        // Decomposed: e followed by a combining acute accent
+        assert_eq!(fold("Cafe\u{0301}"), "cafe");
+        assert_eq!(fold("Straße"), "strasse");
+        assert_eq!(fold("Łódź"), "lodz");
+        assert_eq!(fold("Ærøskøbing"), "aeroskobing");
+        assert_eq!(fold("Tiếng Việt"), "tieng viet");
+    }
+
+    #[test]
+    fn test_fold_other_scripts() {
+        assert_eq!(fold("ΚΑΛΗΜΈΡΑ"), "καλημερα");
+        assert_eq!(fold("λόγος"), "λογοσ");
+        assert_eq!(fold("ЁЛКА"), "елка");
+        assert_eq!(fold("Привет"), "привет");
+        // Scripts without case or accents pass through
+        assert_eq!(fold("東京"), "東京");
+        assert_eq!(fold("مرحبا"), "مرحبا");
+    }
+
+    #[test]
+    fn test_contains() {
+        assert!(contains("Meet at the CAFÉ", "café", false));
+        assert!(contains("Meet at the cafe", "Café", false));
+        assert!(contains("Ёлка в парке", "елка", false));
+        assert!(!contains("Meet at the cafe", "café", true));
+        assert!(!contains("Meet at the CAFÉ", "café", true));
+        assert!(contains("Meet at the café", "café", true));
+    }
+}
@@ -0,0 +1,291 @@
+//! Action items for me found in messages ("can you send the report?"), kept locally so
+//! they can be marked done and handed to dashboard agents
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::global_id::GlobalChatId;
+use crate::types::{ChatType, Message, MessageContent, MessageId, UserId};
+
+/// Phrases that ask the reader to do something
+const REQUEST_PHRASES: &str = r"can you|could you|would you|will you|can u|could u|please|pls|plz|don'?t forget to|remember to|need you to|make sure to|make sure|todo:?|action item:?";
+
+/// Something someone asked me to do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Todo {
+    /// Short ID derived from the message, the same on every scan
+    pub id: String,
+    pub chat: GlobalChatId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_title: Option<String>,
+    pub message_id: MessageId,
+    pub sender: String,
+    /// Missing in todos found before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_id: Option<UserId>,
+    /// What to do, as asked: "Send the report"
+    pub task: String,
+    /// The whole message
+    pub text: String,
+    pub sent: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done: Option<DateTime<Utc>>,
+}
+
+impl Todo {
+    pub fn is_done(&self) -> bool {
+        self.done.is_some()
+    }
+
+    /// Chat title, falling back to its ID
+    pub fn chat_name(&self) -> String {
+        self.chat_title.clone().unwrap_or_else(|| self.chat.to_string())
+    }
+}
+
+fn request() -> &'static Regex {
+    static REQUEST: OnceLock<Regex> = OnceLock::new();
+    REQUEST.get_or_init(|| Regex::new(&format!(r"(?i)\b(?:{})(?:\s|[,:]|$)", REQUEST_PHRASES)).expect("valid pattern"))
+}
+
+/// The task asked for in `text`: the first sentence with a request, without the request
+/// phrase ("Could you please send the report?" becomes "Send the report")
+pub fn find_task(text: &str) -> Option<String> {
+    text.split_inclusive(['.', '!', '?', '\n']).find_map(|sentence| {
+        let found = request().find(sentence)?;
+        let mut rest = &sentence[found.end()..];
+        // "can you please ..." asks twice
+        while let Some(again) = request().find(rest).filter(|m| rest[..m.start()].trim().is_empty()) {
+            rest = &rest[again.end()..];
+        }
+
+        let task = rest.trim().trim_end_matches(['.', '!', '?', ',', ';', ':']).trim();
+        if task.chars().count() < 3 {
+            return None;
+        }
+        let mut chars = task.chars();
+        let first = chars.next()?;
+        Some(first.to_uppercase().chain(chars).collect())
+    })
+}
+
+/// Whether a message asks me for something: it is a request, not written by me (one of
+/// `my_handles`), and either in a direct chat or mentioning me
+pub fn extract(
+    chat: &GlobalChatId,
+    chat_title: Option<&str>,
+    chat_type: &ChatType,
+    message: &Message,
+    my_handles: &[String],
+) -> Option<Todo> {
+    if message.pending || !(message.mentions_me || *chat_type == ChatType::DirectMessage) {
+        return None;
+    }
+    let by_me = message.sender.username.as_deref().is_some_and(|name| {
+        my_handles.iter().any(|handle| handle.trim_start_matches('@').eq_ignore_ascii_case(name.trim_start_matches('@')))
+    });
+    if by_me {
+        return None;
+    }
+
+    let text = message_text(message)?;
+    let task = find_task(text)?;
+    let key = format!("{}\n{}", chat, message.id);
+    Some(Todo {
+        id: format!("{:x}", Sha256::digest(key.as_bytes()))[..8].to_string(),
+        chat: chat.clone(),
+        chat_title: chat_title.map(str::to_string),
+        message_id: message.id.clone(),
+        sender: sender_name(message),
+        sender_id: Some(message.sender.id.clone()),
+        task,
+        text: text.to_string(),
+        sent: message.timestamp,
+        done: None,
+    })
+}
+
+/// Todos found so far (`~/.config/chat/todos.json`)
+///
+/// `chat todos` adds new ones on every scan and marks them done; the dashboard reads the
+/// file to hand open todos to agents.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TodoStore {
+    #[serde(default)]
+    todos: Vec<Todo>,
+}
+
+impl TodoStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default location of the todos file (`~/.config/chat/todos.json`)
+    pub fn default_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat");
+        Ok(dir.join("todos.json"))
+    }
+
+    /// Load todos from the default location, returning an empty store if none exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load todos from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context("Failed to read todos file")?;
+
+        serde_json::from_str(&contents).context("Failed to parse todos file")
+    }
+
+    /// Save todos to the default location
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::default_path()?)
+    }
+
+    /// Save todos to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create todos directory")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .context("Failed to serialize todos")?;
+
+        std::fs::write(path, contents).context("Failed to write todos file")
+    }
+
+    /// Add a todo unless one for the same message is known, keeping the newest last.
+    /// Returns whether it was added.
+    pub fn add(&mut self, todo: Todo) -> bool {
+        if self.todos.iter().any(|t| t.id == todo.id) {
+            return false;
+        }
+        let at = self.todos.partition_point(|t| t.sent <= todo.sent);
+        self.todos.insert(at, todo);
+        true
+    }
+
+    /// Mark a todo done, or open again. `id` may be any unique prefix of a todo ID.
+    pub fn set_done(&mut self, id: &str, done: bool) -> Result<&Todo> {
+        let mut matches = self.todos.iter().enumerate().filter(|(_, t)| t.id.starts_with(id));
+        let index = match (matches.next(), matches.next()) {
+            (Some((index, _)), None) if !id.is_empty() => index,
+            (Some(_), Some(_)) => anyhow::bail!("More than one todo starts with '{}'", id),
+            _ => anyhow::bail!("No todo '{}'", id),
+        };
+
+        let todo = &mut self.todos[index];
+        todo.done = done.then(Utc::now);
+        Ok(todo)
+    }
+
+    /// Remove the todos `f` picks, returning them
+    pub fn remove_where(&mut self, mut f: impl FnMut(&Todo) -> bool) -> Vec<Todo> {
+        let (removed, kept) = std::mem::take(&mut self.todos).into_iter().partition(|t| f(t));
+        self.todos = kept;
+        removed
+    }
+
+    /// All todos, oldest first
+    pub fn todos(&self) -> &[Todo] {
+        &self.todos
+    }
+
+    /// Todos not done yet, oldest first
+    pub fn open(&self) -> impl Iterator<Item = &Todo> {
+        self.todos.iter().filter(|t| !t.is_done())
+    }
+}
+
+fn message_text(message: &Message) -> Option<&str> {
+    match &message.content {
+        MessageContent::Text(text) => Some(text),
+        MessageContent::Image { caption: Some(caption), .. } |
+        MessageContent::Video { caption: Some(caption), .. } => Some(caption),
+        _ => None,
+    }
+}
+
+fn sender_name(message: &Message) -> String {
+    message.sender.display_name
+        .clone()
+        .or_else(|| message.sender.username.clone())
+        .unwrap_or_else(|| message.sender.id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+
+    #[test]
+    fn test_find_task() {
+        assert_eq!(find_task("Hey! Can you send the report?").as_deref(), Some("Send the report"));
+        assert_eq!(find_task("could you please review PR 42 today.").as_deref(), Some("Review PR 42 today"));
+        assert_eq!(find_task("Don't forget to book the room").as_deref(), Some("Book the room"));
+        assert_eq!(find_task("TODO: update the slides\nthanks").as_deref(), Some("Update the slides"));
+        assert_eq!(find_task("thanks, please!"), None);
+        assert_eq!(find_task("pleased to meet you"), None);
+        assert_eq!(find_task("the report is done"), None);
+    }
+
+    #[test]
+    fn test_extract() {
+        let chat = GlobalChatId::new("telegram", "1");
+        let ask = MessageBuilder::new("1", "1").from("bob").text("can you send the report?").build();
+        let mine = MessageBuilder::new("2", "1").from("alice").text("can you check this?").build();
+        let handles = vec!["@Alice".to_string()];
+
+        let todo = extract(&chat, Some("Bob"), &ChatType::DirectMessage, &ask, &handles).unwrap();
+        assert_eq!(todo.task, "Send the report");
+        assert_eq!(todo.id.len(), 8);
+        assert_eq!(extract(&chat, None, &ChatType::DirectMessage, &ask, &handles).unwrap().id, todo.id);
+        assert!(extract(&chat, None, &ChatType::DirectMessage, &mine, &handles).is_none());
+        assert!(extract(&chat, None, &ChatType::Group, &ask, &handles).is_none());
+
+        let mention = MessageBuilder::new("3", "1").from("bob").text("@alice please fix the build").mentions_me().build();
+        assert_eq!(extract(&chat, None, &ChatType::Group, &mention, &handles).unwrap().task, "Fix the build");
+    }
+
+    #[test]
+    fn test_store() {
+        let chat = GlobalChatId::new("telegram", "1");
+        let mut store = TodoStore::new();
+        for (id, minute) in [("1", 5), ("2", 1)] {
+            let message = MessageBuilder::new(id, "1").minute(minute).text("please send it").build();
+            assert!(store.add(extract(&chat, None, &ChatType::DirectMessage, &message, &[]).unwrap()));
+        }
+        let first = store.todos()[0].clone();
+        assert_eq!(first.message_id.as_str(), "2");
+        assert!(!store.add(first.clone()));
+
+        assert!(store.set_done(&first.id[..4], true).unwrap().is_done());
+        assert_eq!(store.open().count(), 1);
+        assert!(store.set_done("zzz", true).is_err());
+        assert!(store.set_done("", true).is_err());
+
+        let path = std::env::temp_dir().join(format!("todos-{}.json", uuid::Uuid::new_v4().simple()));
+        store.save_to(&path).unwrap();
+        let loaded = TodoStore::load_from(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.todos(), store.todos());
+
+        let mut loaded = loaded;
+        loaded.set_done(&first.id, false).unwrap();
+        assert_eq!(loaded.open().count(), 2);
+    }
+}
@@ -0,0 +1,353 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::dupes::normalize;
+use crate::types::{ChatId, Message, MessageContent, MessageId};
+
+/// Words too common to say anything about a topic
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was",
+    "one", "our", "out", "has", "him", "his", "how", "its", "may", "new", "now", "see", "way",
+    "who", "did", "get", "got", "let", "say", "she", "too", "use", "yes", "yeah", "just", "that",
+    "this", "with", "have", "from", "they", "will", "would", "there", "their", "what", "about",
+    "which", "when", "your", "were", "been", "than", "then", "them", "some", "into", "could",
+    "also", "like", "only", "more", "very", "here", "even", "well", "back", "much", "should",
+    "think", "know", "want", "going", "really", "thanks", "okay", "http", "https", "www",
+];
+
+/// A contiguous run of messages about one topic
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicSegment {
+    pub chat_id: ChatId,
+    /// Generated from the segment's most frequent keywords
+    pub title: String,
+    pub keywords: Vec<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub participants: Vec<String>,
+    /// Messages in the segment, oldest first
+    pub message_ids: Vec<MessageId>,
+}
+
+impl TopicSegment {
+    pub fn message_count(&self) -> usize {
+        self.message_ids.len()
+    }
+}
+
+/// Heuristic topic segmentation based on time gaps, reply links and keyword overlap
+#[derive(Debug, Clone)]
+pub struct TopicSegmenter {
+    /// Silence longer than this always starts a new topic (unless the message is a reply into it)
+    pub max_gap: Duration,
+    /// A message with no keyword overlap starts a new topic only after this much silence
+    pub min_shift_gap: Duration,
+    /// Number of recent messages whose keywords describe the current topic
+    pub window: usize,
+    /// Messages need this many keywords before they can shift the topic
+    pub min_keywords: usize,
+    /// Segments shorter than this are merged into the previous one
+    pub min_messages: usize,
+}
+
+impl Default for TopicSegmenter {
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::hours(3),
+            min_shift_gap: Duration::minutes(10),
+            window: 8,
+            min_keywords: 3,
+            min_messages: 3,
+        }
+    }
+}
+
+struct Draft<'a> {
+    messages: Vec<&'a Message>,
+}
+
+impl TopicSegmenter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split messages into topic segments per chat, oldest segment first within each chat
+    pub fn segment(&self, messages: &[Message]) -> Vec<TopicSegment> {
+        let mut by_chat: HashMap<&ChatId, Vec<&Message>> = HashMap::new();
+        for message in messages {
+            by_chat.entry(&message.chat_id).or_default().push(message);
+        }
+
+        let mut chats: Vec<_> = by_chat.into_iter().collect();
+        chats.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+
+        chats
+            .into_iter()
+            .flat_map(|(chat_id, mut chat_messages)| {
+                chat_messages.sort_by_key(|m| m.timestamp);
+                self.segment_chat(&chat_messages)
+                    .into_iter()
+                    .map(|draft| finish(chat_id, &draft.messages))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn segment_chat<'a>(&self, messages: &[&'a Message]) -> Vec<Draft<'a>> {
+        let mut drafts: Vec<Draft<'a>> = Vec::new();
+        let mut current_ids: HashSet<&MessageId> = HashSet::new();
+
+        for &message in messages {
+            let Some(draft) = drafts.last_mut() else {
+                current_ids.insert(&message.id);
+                drafts.push(Draft { messages: vec![message] });
+                continue;
+            };
+
+            let last = draft.messages.last().map(|m| m.timestamp).unwrap_or(message.timestamp);
+            let gap = message.timestamp - last;
+            let replies_here = message.reply_to.as_ref().is_some_and(|id| current_ids.contains(id));
+
+            let shift = if replies_here {
+                false
+            } else if gap > self.max_gap {
+                true
+            } else {
+                let words = keywords(message);
+                words.len() >= self.min_keywords
+                    && gap > self.min_shift_gap
+                    && !self.recent_keywords(&draft.messages).iter().any(|w| words.contains(w))
+            };
+
+            if shift {
+                current_ids.clear();
+                drafts.push(Draft { messages: Vec::new() });
+            }
+
+            current_ids.insert(&message.id);
+            if let Some(draft) = drafts.last_mut() {
+                draft.messages.push(message);
+            }
+        }
+
+        // Fold tiny segments into their predecessor
+        let mut merged: Vec<Draft<'a>> = Vec::new();
+        for draft in drafts {
+            match merged.last_mut() {
+                Some(previous) if draft.messages.len() < self.min_messages => {
+                    previous.messages.extend(draft.messages);
+                }
+                _ => merged.push(draft),
+            }
+        }
+
+        merged
+    }
+
+    fn recent_keywords(&self, messages: &[&Message]) -> HashSet<String> {
+        messages
+            .iter()
+            .rev()
+            .take(self.window)
+            .flat_map(|m| keywords(m))
+            .collect()
+    }
+}
+
+fn message_text(message: &Message) -> Option<&str> {
+    match &message.content {
+        MessageContent::Text(text) => Some(text),
+        MessageContent::Image { caption: Some(caption), .. } |
+        MessageContent::Video { caption: Some(caption), .. } => Some(caption),
+        _ => None,
+    }
+}
+
+fn sender_name(message: &Message) -> String {
+    message.sender.display_name
+        .clone()
+        .or_else(|| message.sender.username.clone())
+        .unwrap_or_else(|| message.sender.id.to_string())
+}
+
+/// Distinct meaningful words of a message
+fn keywords(message: &Message) -> HashSet<String> {
+    let Some(text) = message_text(message) else {
+        return HashSet::new();
+    };
+
+    normalize(text)
+        .split_whitespace()
+        .filter(|w| w.chars().count() >= 3 && !w.chars().all(|c| c.is_ascii_digit()))
+        .filter(|w| !STOPWORDS.contains(w))
+        .map(|w| w.to_string())
+        .collect()
+}
+
+fn finish(chat_id: &ChatId, messages: &[&Message]) -> TopicSegment {
+    let mut frequency: HashMap<String, usize> = HashMap::new();
+    for message in messages {
+        for word in keywords(message) {
+            *frequency.entry(word).or_default() += 1;
+        }
+    }
+
+    let mut ranked: Vec<(String, usize)> = frequency.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let keywords: Vec<String> = ranked.into_iter().take(5).map(|(w, _)| w).collect();
+
+    let start = messages.first().map(|m| m.timestamp).unwrap_or_else(Utc::now);
+    let end = messages.last().map(|m| m.timestamp).unwrap_or(start);
+
+    let title = if keywords.is_empty() {
+        format!("Conversation on {}", start.format("%Y-%m-%d"))
+    } else {
+        keywords.iter().take(3).map(|w| capitalize(w)).collect::<Vec<_>>().join(", ")
+    };
+
+    let mut participants: Vec<String> = Vec::new();
+    for message in messages {
+        let name = sender_name(message);
+        if !participants.contains(&name) {
+            participants.push(name);
+        }
+    }
+
+    TopicSegment {
+        chat_id: chat_id.clone(),
+        title,
+        keywords,
+        start,
+        end,
+        participants,
+        message_ids: messages.iter().map(|m| m.id.clone()).collect(),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A segment rendered as a self-contained text block, for summarizers and MCP resources
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextChunk {
+    pub chat_id: ChatId,
+    pub title: String,
+    pub text: String,
+}
+
+/// Render each segment with its transcript
+pub fn context_chunks(messages: &[Message], segments: &[TopicSegment]) -> Vec<ContextChunk> {
+    let by_id: HashMap<(&ChatId, &MessageId), &Message> = messages
+        .iter()
+        .map(|m| ((&m.chat_id, &m.id), m))
+        .collect();
+
+    segments
+        .iter()
+        .map(|segment| {
+            let mut text = format!(
+                "## {}\n{} – {} · {} messages · {}\n\n",
+                segment.title,
+                segment.start.format("%Y-%m-%d %H:%M"),
+                segment.end.format("%Y-%m-%d %H:%M"),
+                segment.message_count(),
+                segment.participants.join(", ")
+            );
+
+            for id in &segment.message_ids {
+                if let Some(message) = by_id.get(&(&segment.chat_id, id)) {
+                    let body = message_text(message).unwrap_or("[media]");
+                    text.push_str(&format!(
+                        "[{}] {}: {}\n",
+                        message.timestamp.format("%H:%M"),
+                        sender_name(message),
+                        body.replace('\n', " ")
+                    ));
+                }
+            }
+
+            ContextChunk {
+                chat_id: segment.chat_id.clone(),
+                title: segment.title.clone(),
+                text,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{User, UserId};
+
+    fn message(id: &str, minutes: i64, text: &str, reply_to: Option<&str>) -> Message {
+        Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new("group"),
+            sender: User {
+                id: UserId::new("u"),
+                username: None,
+                display_name: Some(format!("user{}", id.len())),
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp: Utc::now() - Duration::days(1) + Duration::minutes(minutes),
+            reply_to: reply_to.map(MessageId::new),
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    fn deploy_and_dinner() -> Vec<Message> {
+        vec![
+            message("1", 0, "The staging deploy failed again on the database migration", None),
+            message("2", 2, "Rolling back the staging deploy now", None),
+            message("3", 5, "Migration script needs a database lock timeout", None),
+            message("4", 7, "ok", None),
+            message("5", 40, "Anyone up for dinner tonight? Pizza place downtown", None),
+            message("6", 42, "Pizza sounds great, dinner at seven", None),
+            message("7", 45, "Downtown pizza dinner works for me", None),
+        ]
+    }
+
+    #[test]
+    fn test_keyword_shift_starts_segment() {
+        let segments = TopicSegmenter::new().segment(&deploy_and_dinner());
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].message_count(), 4);
+        assert!(segments[0].keywords.contains(&"staging".to_string()));
+        assert!(segments[1].title.contains("Dinner") || segments[1].title.contains("Pizza"));
+    }
+
+    #[test]
+    fn test_reply_keeps_segment_across_gap() {
+        let mut messages = deploy_and_dinner();
+        messages.push(message("8", 60 * 5, "Fixed the staging migration lock, deploy is green", Some("7")));
+
+        let segments = TopicSegmenter::new().segment(&messages);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].message_count(), 4);
+    }
+
+    #[test]
+    fn test_context_chunks() {
+        let messages = deploy_and_dinner();
+        let segments = TopicSegmenter::new().segment(&messages);
+        let chunks = context_chunks(&messages, &segments);
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].text.starts_with(&format!("## {}", segments[0].title)));
+        assert!(chunks[0].text.contains("Rolling back the staging deploy now"));
+        assert!(!chunks[0].text.contains("Pizza"));
+    }
+}
@@ -4,6 +4,13 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use uuid::Uuid;
 
+use crate::channel_stats::ChannelPosts;
+use crate::language;
+use crate::progress::Progress;
+use crate::sources_config::SourceSettings;
+use crate::supervisor::TaskStatus;
+use crate::text_fold;
+
 /// Unique identifier for a chat client
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ChatClientId(Uuid);
@@ -97,6 +104,42 @@ impl From<&str> for ChatId {
     }
 }
 
+impl ChatId {
+    /// ID of a forum topic as a child chat of its group: `<group_id>/<topic_id>`
+    pub fn topic(group: &ChatId, topic: &TopicId) -> Self {
+        Self(format!("{}/{}", group.0, topic.0))
+    }
+
+    /// Split a topic chat ID into its group and topic; `None` for ordinary chats
+    pub fn split_topic(&self) -> Option<(ChatId, TopicId)> {
+        let (group, topic) = self.0.split_once('/')?;
+        if group.is_empty() || topic.is_empty() {
+            return None;
+        }
+        Some((ChatId::new(group), TopicId::new(topic)))
+    }
+}
+
+/// Identifier of a topic within a forum group
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TopicId(String);
+
+impl TopicId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TopicId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// Unique identifier for a user
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct UserId(String);
@@ -186,6 +229,37 @@ pub struct Chat {
     pub title: Option<String>,
     pub chat_type: ChatType,
     pub participant_count: Option<usize>,
+    /// Set when this chat is a topic of a forum group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<TopicInfo>,
+}
+
+/// A forum topic listed as a child chat of its group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicInfo {
+    pub group_id: ChatId,
+    pub group_title: Option<String>,
+    pub id: TopicId,
+    pub name: String,
+}
+
+impl Chat {
+    /// Child chat for a topic of this forum group
+    pub fn forum_topic(&self, id: TopicId, name: impl Into<String>) -> Chat {
+        let name = name.into();
+        Chat {
+            id: ChatId::topic(&self.id, &id),
+            title: Some(format!("{} / {}", self.title.as_deref().unwrap_or("?"), name)),
+            chat_type: self.chat_type.clone(),
+            participant_count: self.participant_count,
+            topic: Some(TopicInfo {
+                group_id: self.id.clone(),
+                group_title: self.title.clone(),
+                id,
+                name,
+            }),
+        }
+    }
 }
 
 /// Content type of a message
@@ -199,9 +273,78 @@ pub enum MessageContent {
     Sticker,
     Location { latitude: f64, longitude: f64 },
     Contact { name: String, phone: Option<String> },
+    /// Something that happened in a group, like a member joining; the sender did it
+    System(SystemEvent),
     Unknown,
 }
 
+impl MessageContent {
+    /// Lowercase name of the variant, as accepted by `--type`: "text", "image", ...
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MessageContent::Text(_) => "text",
+            MessageContent::Image { .. } => "image",
+            MessageContent::Video { .. } => "video",
+            MessageContent::Audio { .. } => "audio",
+            MessageContent::File { .. } => "file",
+            MessageContent::Sticker => "sticker",
+            MessageContent::Location { .. } => "location",
+            MessageContent::Contact { .. } => "contact",
+            MessageContent::System(_) => "system",
+            MessageContent::Unknown => "unknown",
+        }
+    }
+}
+
+/// A group event recorded in the chat history instead of a message someone wrote.
+/// Users are named by display name, falling back to their ID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SystemEvent {
+    /// The sender joined, through an invite link or by request
+    Joined,
+    /// The sender added these users
+    Added { users: Vec<String> },
+    /// The sender left
+    Left,
+    /// The sender removed this user
+    Removed { user: String },
+    /// The sender created the group
+    Created { title: String },
+    TitleChanged { title: String },
+    PhotoChanged,
+    /// The sender pinned a message
+    Pinned,
+    /// Any other event, described by the source
+    Other { description: String },
+}
+
+impl SystemEvent {
+    /// What the sender did, to follow their name: "added Alice and Bob"
+    pub fn describe(&self) -> String {
+        match self {
+            SystemEvent::Joined => "joined".to_string(),
+            SystemEvent::Added { users } => format!("added {}", join_names(users)),
+            SystemEvent::Left => "left".to_string(),
+            SystemEvent::Removed { user } => format!("removed {}", user),
+            SystemEvent::Created { title } => format!("created the group \"{}\"", title),
+            SystemEvent::TitleChanged { title } => format!("changed the title to \"{}\"", title),
+            SystemEvent::PhotoChanged => "changed the group photo".to_string(),
+            SystemEvent::Pinned => "pinned a message".to_string(),
+            SystemEvent::Other { description } => description.clone(),
+        }
+    }
+}
+
+/// "Alice", "Alice and Bob", "Alice, Bob and Carol"
+fn join_names(names: &[String]) -> String {
+    match names {
+        [] => "nobody".to_string(),
+        [name] => name.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
 /// A message in a chat
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
@@ -212,6 +355,33 @@ pub struct Message {
     pub timestamp: DateTime<Utc>,
     pub reply_to: Option<MessageId>,
     pub edited: bool,
+    /// Whether this message mentions me or replies to one of my messages
+    #[serde(default)]
+    pub mentions_me: bool,
+    /// Written by me but not sent yet: a draft or a scheduled message
+    #[serde(default)]
+    pub pending: bool,
+    /// Detected language of the text, as an ISO 639-1 code ("en", "fi")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Text derived from an attachment, not written by the sender: OCR or a caption of an
+    /// image, or the transcript of a voice note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_text: Option<String>,
+    /// Timestamp as the source reported it, set when its clock was off and `timestamp` was
+    /// corrected (see [`crate::clock`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_timestamp: Option<DateTime<Utc>>,
+}
+
+/// Message ID given to a chat's unsent draft; a chat has at most one
+pub const DRAFT_MESSAGE_ID: &str = "draft";
+
+impl Message {
+    /// Whether this is an unsent draft (as opposed to a scheduled message)
+    pub fn is_draft(&self) -> bool {
+        self.pending && self.id.as_str() == DRAFT_MESSAGE_ID
+    }
 }
 
 /// Options for fetching messages
@@ -283,6 +453,10 @@ pub enum ChatPattern {
     All,
     /// Multiple specific chats
     Multiple(Vec<ChatId>),
+    /// Chats carrying a local label (resolved to `Multiple` by `SourcesManager`)
+    Label(String),
+    /// Topic of a forum group: the group pattern plus a topic name (partial match) or ID
+    Topic { group: Box<ChatPattern>, topic: String },
 }
 
 impl ChatPattern {
@@ -299,8 +473,33 @@ impl ChatPattern {
             }
             ChatPattern::All => true,
             ChatPattern::Multiple(ids) => ids.contains(&chat.id),
+            // Chats don't carry labels themselves; see LabelStore
+            ChatPattern::Label(_) => false,
+            ChatPattern::Topic { group, topic } => match &chat.topic {
+                Some(info) => group.matches_group(info) && Self::matches_topic(topic, info),
+                None => false,
+            },
         }
     }
+
+    /// Check if this pattern matches the group a topic belongs to
+    fn matches_group(&self, info: &TopicInfo) -> bool {
+        let group = Chat {
+            id: info.group_id.clone(),
+            title: info.group_title.clone(),
+            chat_type: ChatType::Group,
+            participant_count: None,
+            topic: None,
+        };
+        self.matches(&group)
+    }
+
+    /// Check if a topic pattern (`*`, topic ID or name) matches a topic
+    pub fn matches_topic(pattern: &str, info: &TopicInfo) -> bool {
+        pattern == "*"
+            || info.id.as_str() == pattern
+            || info.name.to_lowercase().contains(&pattern.to_lowercase())
+    }
 }
 
 /// Content type filter for messages
@@ -314,22 +513,24 @@ pub enum ContentType {
     Sticker,
     Location,
     Contact,
+    System,
 }
 
 impl ContentType {
     /// Check if this content type matches message content
     pub fn matches(&self, content: &MessageContent) -> bool {
-        match (self, content) {
-            (ContentType::Text, MessageContent::Text(_)) => true,
-            (ContentType::Image, MessageContent::Image { .. }) => true,
-            (ContentType::Video, MessageContent::Video { .. }) => true,
-            (ContentType::Audio, MessageContent::Audio { .. }) => true,
-            (ContentType::File, MessageContent::File { .. }) => true,
-            (ContentType::Sticker, MessageContent::Sticker) => true,
-            (ContentType::Location, MessageContent::Location { .. }) => true,
-            (ContentType::Contact, MessageContent::Contact { .. }) => true,
-            _ => false,
-        }
+        matches!(
+            (self, content),
+            (ContentType::Text, MessageContent::Text(_))
+                | (ContentType::Image, MessageContent::Image { .. })
+                | (ContentType::Video, MessageContent::Video { .. })
+                | (ContentType::Audio, MessageContent::Audio { .. })
+                | (ContentType::File, MessageContent::File { .. })
+                | (ContentType::Sticker, MessageContent::Sticker)
+                | (ContentType::Location, MessageContent::Location { .. })
+                | (ContentType::Contact, MessageContent::Contact { .. })
+                | (ContentType::System, MessageContent::System(_))
+        )
     }
 }
 
@@ -344,12 +545,34 @@ pub struct MessageFilter {
     pub before: Option<DateTime<Utc>>,
     /// Sender filter (name or ID pattern)
     pub sender: Option<String>,
-    /// Text search (case-insensitive substring)
+    /// Text search (case- and accent-insensitive substring of the text, caption or
+    /// text derived from an attachment)
     pub search: Option<String>,
     /// Limit number of results
     pub limit: Option<usize>,
     /// Message content types
     pub content_type: Option<Vec<ContentType>>,
+    /// Only messages mentioning me or replying to me
+    #[serde(default)]
+    pub mentions_me: bool,
+    /// Match `search` and `sender` exactly: case- and accent-sensitive
+    #[serde(default)]
+    pub exact: bool,
+    /// Only messages detected to be in this language ("fi", "eng")
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Keep system events (joins, leaves, renames), which are left out by default.
+    /// Combine with `content_type: [System]` for system events only.
+    #[serde(default)]
+    pub include_system: bool,
+    /// Only messages with text derived from an attachment (a document's text, image text
+    /// or a transcript), with `search` matched against that text alone
+    #[serde(default)]
+    pub in_attachments: bool,
+    /// Query merged chats (see [`MergedChats`](crate::merged_chats::MergedChats)) each on
+    /// their own, under their own IDs, as sync and per-chat exports do
+    #[serde(default)]
+    pub separate_merged: bool,
 }
 
 impl Default for MessageFilter {
@@ -362,6 +585,12 @@ impl Default for MessageFilter {
             search: None,
             limit: Some(100),
             content_type: None,
+            mentions_me: false,
+            exact: false,
+            language: None,
+            include_system: false,
+            in_attachments: false,
+            separate_merged: false,
         }
     }
 }
@@ -404,11 +633,23 @@ impl MessageFilter {
             }
         }
 
+        // Check language
+        if let Some(code) = &self.language {
+            if language::normalize(code).is_none() {
+                anyhow::bail!("Unknown language code '{}', expected e.g. 'en' or 'fin'", code);
+            }
+        }
+
         Ok(())
     }
 
     /// Check if a message matches this filter
     pub fn matches(&self, message: &Message) -> bool {
+        // System events only when asked for
+        if !self.include_system && matches!(message.content, MessageContent::System(_)) {
+            return false;
+        }
+
         // Check time range
         if let Some(since) = &self.since {
             if &message.timestamp < since {
@@ -425,11 +666,11 @@ impl MessageFilter {
         if let Some(sender_pattern) = &self.sender {
             let sender_match = message.sender.display_name
                 .as_ref()
-                .map(|name| name.to_lowercase().contains(&sender_pattern.to_lowercase()))
+                .map(|name| text_fold::contains(name, sender_pattern, self.exact))
                 .unwrap_or(false)
                 || message.sender.username
                     .as_ref()
-                    .map(|username| username.to_lowercase().contains(&sender_pattern.to_lowercase()))
+                    .map(|username| text_fold::contains(username, sender_pattern, self.exact))
                     .unwrap_or(false);
 
             if !sender_match {
@@ -437,18 +678,34 @@ impl MessageFilter {
             }
         }
 
+        // Check attachment text
+        if self.in_attachments {
+            let Some(media_text) = &message.media_text else {
+                return false;
+            };
+            if self.search.as_ref().is_some_and(|term| !text_fold::contains(media_text, term, self.exact)) {
+                return false;
+            }
+        }
+
         // Check text search
-        if let Some(search_term) = &self.search {
+        if let Some(search_term) = self.search.as_ref().filter(|_| !self.in_attachments) {
             let text_match = match &message.content {
                 MessageContent::Text(text) => {
-                    text.to_lowercase().contains(&search_term.to_lowercase())
+                    text_fold::contains(text, search_term, self.exact)
                 }
                 MessageContent::Image { caption: Some(caption), .. } |
                 MessageContent::Video { caption: Some(caption), .. } => {
-                    caption.to_lowercase().contains(&search_term.to_lowercase())
+                    text_fold::contains(caption, search_term, self.exact)
+                }
+                MessageContent::System(event) => {
+                    text_fold::contains(&event.describe(), search_term, self.exact)
                 }
                 _ => false,
-            };
+            } || message
+                .media_text
+                .as_ref()
+                .is_some_and(|text| text_fold::contains(text, search_term, self.exact));
 
             if !text_match {
                 return false;
@@ -462,8 +719,48 @@ impl MessageFilter {
             }
         }
 
+        // Check mentions
+        if self.mentions_me && !message.mentions_me {
+            return false;
+        }
+
+        // Check language
+        if !self.matches_language(message) {
+            return false;
+        }
+
         true
     }
+
+    /// Check if a message is in the filter's language; untagged messages never are
+    pub fn matches_language(&self, message: &Message) -> bool {
+        match &self.language {
+            Some(code) => message.language.is_some() && message.language.as_deref() == language::normalize(code),
+            None => true,
+        }
+    }
+}
+
+/// Apply a message filter: the reference semantics every source and mock follows.
+///
+/// - `since` is inclusive and `before` exclusive
+/// - `sender` is a substring of the display name or username
+/// - `search` is a substring of the text, media caption or system event description
+/// - both ignore case and accents (see [`text_fold::fold`]) unless `exact` is set
+/// - `content_type` keeps messages of any listed type; `mentions_me` keeps flagged ones
+/// - `language` keeps messages tagged with that language (see [`language::tag`])
+/// - system events are dropped unless `include_system` is set
+/// - results are newest first, ties keeping their input order, then cut to `limit`
+///
+/// `chat` is not checked here: picking chats needs the source's chat list, so sources
+/// select them with [`ChatPattern::matches`] and pass the messages of the matching chats.
+pub fn apply_filter(messages: impl IntoIterator<Item = Message>, filter: &MessageFilter) -> Vec<Message> {
+    let mut matching: Vec<Message> = messages.into_iter().filter(|m| filter.matches(m)).collect();
+    matching.sort_by_key(|m| std::cmp::Reverse(m.timestamp));
+    if let Some(limit) = filter.limit {
+        matching.truncate(limit);
+    }
+    matching
 }
 
 /// Filter for listing chats
@@ -475,6 +772,9 @@ pub struct ChatFilter {
     pub name_pattern: Option<String>,
     /// Only include chats with recent activity
     pub active_since: Option<DateTime<Utc>>,
+    /// Only include chats carrying this local label
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
 impl ChatFilter {
@@ -495,6 +795,12 @@ impl ChatFilter {
         self
     }
 
+    /// Filter by local label
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
     /// Check if a chat matches this filter
     pub fn matches(&self, chat: &Chat) -> bool {
         // Check chat type
@@ -515,13 +821,184 @@ impl ChatFilter {
             }
         }
 
-        // Note: active_since requires additional data not in Chat struct
-        // This would need to be checked by the implementation
+        // Note: active_since and label require additional data not in Chat struct
+        // These need to be checked by the implementation (labels by SourcesManager)
 
         true
     }
 }
 
+/// An optional feature a chat source may support
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    ListChats,
+    History,
+    Streaming,
+    MediaDownload,
+    Sending,
+    MemberLists,
+    Pending,
+    Drafting,
+    MarkRead,
+    ChannelStats,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 10] = [
+        Capability::ListChats,
+        Capability::History,
+        Capability::Streaming,
+        Capability::MediaDownload,
+        Capability::Sending,
+        Capability::MemberLists,
+        Capability::Pending,
+        Capability::Drafting,
+        Capability::MarkRead,
+        Capability::ChannelStats,
+    ];
+
+    /// Whether using the capability changes anything on the account
+    pub fn is_write(self) -> bool {
+        matches!(self, Capability::Sending | Capability::Drafting | Capability::MarkRead)
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Capability::ListChats => "listing chats",
+            Capability::History => "message history",
+            Capability::Streaming => "streaming",
+            Capability::MediaDownload => "media download",
+            Capability::Sending => "sending messages",
+            Capability::MemberLists => "member lists",
+            Capability::Pending => "drafts and scheduled messages",
+            Capability::Drafting => "saving drafts",
+            Capability::MarkRead => "marking messages read",
+            Capability::ChannelStats => "channel statistics",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Features supported by a chat source.
+/// The default is a read-only source: chat listing and message history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceCapabilities {
+    /// `list_chats` returns real chats
+    pub list_chats: bool,
+    /// `get_messages` returns message history
+    pub history: bool,
+    /// `subscribe` returns a live message stream
+    pub streaming: bool,
+    /// Message media can be downloaded
+    pub media_download: bool,
+    /// Messages can be sent
+    pub sending: bool,
+    /// Chat members can be listed
+    pub member_lists: bool,
+    /// `get_pending` returns drafts and scheduled messages
+    #[serde(default)]
+    pub pending: bool,
+    /// Drafts can be saved
+    #[serde(default)]
+    pub drafting: bool,
+    /// Messages can be marked read
+    #[serde(default)]
+    pub mark_read: bool,
+    /// `get_channel_posts` returns posts with view counts, for channels I admin
+    #[serde(default)]
+    pub channel_stats: bool,
+    /// Filter criteria `get_messages` applies itself; the rest is checked by
+    /// SourcesManager (see [`crate::query_plan`])
+    #[serde(default)]
+    pub pushdown: FilterPushdown,
+}
+
+/// Criteria of a [`MessageFilter`] a source applies while fetching, on its server or as
+/// it reads history. By default a source applies none of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterPushdown {
+    /// Text search
+    pub search: bool,
+    /// Sender name or ID
+    pub sender: bool,
+    /// `since` and `before`
+    pub time_range: bool,
+    /// Content types
+    pub content_type: bool,
+}
+
+impl Default for SourceCapabilities {
+    fn default() -> Self {
+        Self {
+            list_chats: true,
+            history: true,
+            streaming: false,
+            media_download: false,
+            sending: false,
+            member_lists: false,
+            pending: false,
+            drafting: false,
+            mark_read: false,
+            channel_stats: false,
+            pushdown: FilterPushdown::default(),
+        }
+    }
+}
+
+impl SourceCapabilities {
+    /// A source supporting nothing
+    pub fn none() -> Self {
+        Self {
+            list_chats: false,
+            history: false,
+            ..Self::default()
+        }
+    }
+
+    /// Check whether a capability is supported
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::ListChats => self.list_chats,
+            Capability::History => self.history,
+            Capability::Streaming => self.streaming,
+            Capability::MediaDownload => self.media_download,
+            Capability::Sending => self.sending,
+            Capability::MemberLists => self.member_lists,
+            Capability::Pending => self.pending,
+            Capability::Drafting => self.drafting,
+            Capability::MarkRead => self.mark_read,
+            Capability::ChannelStats => self.channel_stats,
+        }
+    }
+
+    /// The same capabilities with everything that writes to the account switched off
+    pub fn read_only(self) -> Self {
+        Self {
+            sending: false,
+            drafting: false,
+            mark_read: false,
+            ..self
+        }
+    }
+
+    /// Error unless the capability is supported
+    pub fn require(&self, source_id: &str, capability: Capability) -> Result<()> {
+        if !self.supports(capability) {
+            anyhow::bail!("Source '{}' does not support {}", source_id, capability);
+        }
+        Ok(())
+    }
+
+    /// Supported capabilities, in declaration order
+    pub fn supported(&self) -> Vec<Capability> {
+        Capability::ALL.into_iter().filter(|c| self.supports(*c)).collect()
+    }
+}
+
 /// Information about a chat source
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceInfo {
@@ -531,6 +1008,15 @@ pub struct SourceInfo {
     pub name: String,
     /// Connection status
     pub is_connected: bool,
+    /// The session expired or was revoked; signing in again is needed
+    #[serde(default)]
+    pub auth_required: bool,
+    /// Supported features
+    #[serde(default)]
+    pub capabilities: SourceCapabilities,
+    /// Health of the source's background tasks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<TaskStatus>,
 }
 
 /// Unified chat source interface
@@ -545,13 +1031,312 @@ pub trait ChatSource: Send + Sync {
     /// Check if source is connected
     fn is_connected(&self) -> bool;
 
+    /// Features this source supports
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::default()
+    }
+
+    /// Apply this source's settings from sources.toml. Called when the source is
+    /// registered and whenever the settings change; sources without limits ignore them.
+    fn configure(&mut self, _settings: SourceSettings) {}
+
+    /// Health of the background tasks the source runs, such as its network runner
+    fn tasks(&self) -> Vec<TaskStatus> {
+        Vec::new()
+    }
+
+    /// Stop the source's background tasks; called once it is no longer needed
+    async fn shutdown(&self) {}
+
+    /// Ping the service so an idle session stays alive and an expired one is noticed.
+    /// Fails with the service's error once the session is no longer authorized; sources
+    /// without sessions keep the default.
+    async fn keep_alive(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// List all chats (conversations) from this source
     async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>>;
 
     /// Get messages matching filter
     async fn get_messages(&self, filter: MessageFilter) -> Result<Vec<Message>>;
 
+    /// Get messages matching filter, reporting progress and returning what has been
+    /// fetched so far once `progress` is cancelled. Sources that fetch in one request
+    /// keep the default, which reports nothing.
+    async fn get_messages_with_progress(&self, filter: MessageFilter, progress: &Progress) -> Result<Vec<Message>> {
+        let _ = progress;
+        self.get_messages(filter).await
+    }
+
+    /// Get my unsent drafts and scheduled messages in chats matching the filter,
+    /// flagged `pending`. Only called when `capabilities().pending` is set.
+    async fn get_pending(&self, _filter: MessageFilter) -> Result<Vec<Message>> {
+        Ok(Vec::new())
+    }
+
+    /// Number of messages in a chat's history, if the service reports it without
+    /// fetching them. Sizes `--dry-run` estimates for chats that were never synced.
+    async fn message_count(&self, _chat_id: &ChatId) -> Result<Option<usize>> {
+        Ok(None)
+    }
+
+    /// Get a channel's posts since `since` with their view counts, plus its current
+    /// subscriber count. Only called when `capabilities().channel_stats` is set.
+    async fn get_channel_posts(&self, chat_id: &ChatId, _since: DateTime<Utc>) -> Result<ChannelPosts> {
+        anyhow::bail!("No channel statistics for '{}'", chat_id)
+    }
+
+    /// Send a text message to a chat, optionally as a reply, returning it as sent.
+    /// Only called when `capabilities().sending` is set and the manager isn't read-only.
+    async fn send_message(&self, chat_id: &ChatId, _text: &str, _reply_to: Option<&MessageId>) -> Result<Message> {
+        anyhow::bail!("Sending to '{}' is not supported", chat_id)
+    }
+
     /// Subscribe to new messages (optional)
     /// Returns None if the source doesn't support streaming
     async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    /// Small deterministic generator so the property tests need no extra dependency
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            // xorshift64*
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+            &items[self.below(items.len() as u64) as usize]
+        }
+
+        fn chance(&mut self, percent: u64) -> bool {
+            self.below(100) < percent
+        }
+    }
+
+    const NAMES: [&str; 5] = ["Alice", "bob", "Carol Ann", "dave", "Zoë"];
+    const WORDS: [&str; 7] = ["Hello", "release", "WORLD", "lunch", "deploy", "Café", "ПРИВЕТ"];
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap()
+    }
+
+    fn random_message(rng: &mut Rng, id: usize) -> Message {
+        let name = *rng.pick(&NAMES);
+        let text = format!("{} {}", rng.pick(&WORDS), rng.pick(&WORDS));
+        let content = match rng.below(4) {
+            0 => MessageContent::Image { caption: rng.chance(50).then_some(text), url: None },
+            1 => MessageContent::Sticker,
+            _ => MessageContent::Text(text),
+        };
+        Message {
+            id: MessageId::new(id.to_string()),
+            chat_id: ChatId::new(rng.below(3).to_string()),
+            sender: User {
+                id: UserId::new(name),
+                username: rng.chance(50).then(|| name.to_lowercase()),
+                display_name: rng.chance(80).then(|| name.to_string()),
+                phone_number: None,
+            },
+            content,
+            // Minute resolution so boundaries and ties come up often
+            timestamp: start() + Duration::minutes(rng.below(30) as i64),
+            reply_to: None,
+            edited: false,
+            mentions_me: rng.chance(20),
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        }
+    }
+
+    fn random_filter(rng: &mut Rng) -> MessageFilter {
+        let time = |rng: &mut Rng| start() + Duration::minutes(rng.below(30) as i64);
+        MessageFilter {
+            chat: ChatPattern::All,
+            since: rng.chance(40).then(|| time(rng)),
+            before: rng.chance(40).then(|| time(rng)),
+            sender: rng.chance(30).then(|| rng.pick(&["al", "BOB", "ann", "zed", "zoe"]).to_string()),
+            search: rng.chance(30).then(|| rng.pick(&["hello", "World", "ease", "nothing", "cafe", "привет"]).to_string()),
+            limit: rng.chance(50).then(|| rng.below(10) as usize + 1),
+            content_type: rng.chance(20).then(|| vec![rng.pick(&[ContentType::Text, ContentType::Image]).clone()]),
+            mentions_me: rng.chance(15),
+            exact: rng.chance(20),
+            language: None,
+            include_system: false,
+            in_attachments: false,
+            separate_merged: false,
+        }
+    }
+
+    /// Properties defining `apply_filter`, checked over generated messages and filters
+    #[test]
+    fn test_apply_filter_properties() {
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+
+        for _ in 0..500 {
+            let messages: Vec<Message> = (0..rng.below(40) as usize).map(|i| random_message(&mut rng, i)).collect();
+            let filter = random_filter(&mut rng);
+            let result = apply_filter(messages.clone(), &filter);
+            let unlimited = apply_filter(messages.clone(), &MessageFilter { limit: None, ..filter.clone() });
+
+            // Sound: everything returned matches every criterion
+            for message in &result {
+                assert!(filter.since.is_none_or(|since| message.timestamp >= since));
+                assert!(filter.before.is_none_or(|before| message.timestamp < before));
+                assert!(!filter.mentions_me || message.mentions_me);
+                assert!(filter.matches(message));
+            }
+
+            // Complete: without a limit, nothing matching is dropped
+            let expected = messages.iter().filter(|m| filter.matches(m)).count();
+            assert_eq!(unlimited.len(), expected);
+
+            // Newest first, ties in input order
+            for pair in unlimited.windows(2) {
+                assert!(pair[0].timestamp >= pair[1].timestamp);
+                if pair[0].timestamp == pair[1].timestamp {
+                    let index = |m: &Message| m.id.as_str().parse::<usize>().unwrap();
+                    assert!(index(&pair[0]) < index(&pair[1]));
+                }
+            }
+
+            // The limit keeps the newest: a prefix of the unlimited result
+            let ids = |messages: &[Message]| messages.iter().map(|m| m.id.clone()).collect::<Vec<_>>();
+            let kept = filter.limit.unwrap_or(usize::MAX).min(unlimited.len());
+            assert_eq!(ids(&result), ids(&unlimited[..kept]));
+
+            // Idempotent
+            assert_eq!(ids(&apply_filter(result.clone(), &filter)), ids(&result));
+        }
+    }
+
+    #[test]
+    fn test_apply_filter_time_bounds() {
+        let message = |id: &str, minute: i64| Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new("1"),
+            sender: User { id: UserId::new("1"), username: None, display_name: None, phone_number: None },
+            content: MessageContent::Text(String::new()),
+            timestamp: start() + Duration::minutes(minute),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        };
+        let filter = MessageFilter {
+            since: Some(start() + Duration::minutes(1)),
+            before: Some(start() + Duration::minutes(3)),
+            limit: None,
+            ..MessageFilter::new()
+        };
+
+        let result = apply_filter([message("a", 0), message("b", 1), message("c", 2), message("d", 3)], &filter);
+        assert_eq!(result.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), ["c", "b"]);
+    }
+
+    #[test]
+    fn test_apply_filter_folds_case_and_accents() {
+        let message = |id: &str, sender: &str, text: &str| Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new("1"),
+            sender: User {
+                id: UserId::new(id),
+                username: None,
+                display_name: Some(sender.to_string()),
+                phone_number: None,
+            },
+            content: MessageContent::Text(text.to_string()),
+            timestamp: start(),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        };
+        let messages = [
+            message("a", "José", "Meet at the café"),
+            message("b", "Jose", "Meet at the CAFE"),
+            message("c", "Ёжик", "Встреча в кафе"),
+            message("d", "Ana", "Καλημέρα"),
+        ];
+        let ids = |filter: &MessageFilter| {
+            apply_filter(messages.clone(), filter).into_iter().map(|m| m.id.to_string()).collect::<Vec<_>>()
+        };
+
+        let search = |term: &str, exact: bool| MessageFilter { search: Some(term.into()), exact, ..MessageFilter::new() };
+        assert_eq!(ids(&search("Café", false)), ["a", "b"]);
+        assert_eq!(ids(&search("cafe", false)), ["a", "b"]);
+        assert_eq!(ids(&search("café", true)), ["a"]);
+        assert_eq!(ids(&search("КАФЕ", false)), ["c"]);
+        assert_eq!(ids(&search("καλημερα", false)), ["d"]);
+
+        let sender = |name: &str, exact: bool| MessageFilter { sender: Some(name.into()), exact, ..MessageFilter::new() };
+        assert_eq!(ids(&sender("jose", false)), ["a", "b"]);
+        assert_eq!(ids(&sender("Jose", true)), ["b"]);
+        assert_eq!(ids(&sender("ежик", false)), ["c"]);
+    }
+
+    #[test]
+    fn test_system_events() {
+        let message = |id: &str, content: MessageContent| Message {
+            id: MessageId::new(id),
+            chat_id: ChatId::new("1"),
+            sender: User {
+                id: UserId::new("7"),
+                username: None,
+                display_name: Some("Alice".to_string()),
+                phone_number: None,
+            },
+            content,
+            timestamp: start(),
+            reply_to: None,
+            edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
+        };
+        let added = SystemEvent::Added { users: vec!["Bob".into(), "Carol".into(), "Dave".into()] };
+        assert_eq!(added.describe(), "added Bob, Carol and Dave");
+        let messages = [
+            message("a", MessageContent::Text("welcome Bob".into())),
+            message("b", MessageContent::System(added)),
+            message("c", MessageContent::System(SystemEvent::Left)),
+        ];
+        let ids = |filter: &MessageFilter| {
+            apply_filter(messages.clone(), filter).into_iter().map(|m| m.id.to_string()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(&MessageFilter::new()), ["a"]);
+        assert_eq!(ids(&MessageFilter { include_system: true, ..MessageFilter::new() }), ["a", "b", "c"]);
+        let only = MessageFilter { include_system: true, content_type: Some(vec![ContentType::System]), ..MessageFilter::new() };
+        assert_eq!(ids(&only), ["b", "c"]);
+        let search = MessageFilter { include_system: true, search: Some("bob".into()), ..MessageFilter::new() };
+        assert_eq!(ids(&search), ["a", "b"]);
+
+        let json = serde_json::to_value(MessageContent::System(SystemEvent::Removed { user: "Bob".into() })).unwrap();
+        assert_eq!(json, serde_json::json!({ "System": { "kind": "removed", "user": "Bob" } }));
+    }
+}
@@ -114,6 +114,11 @@ impl WhatsAppSource {
             timestamp: chrono::Utc::now(),
             reply_to: None,
             edited: false,
+            mentions_me: false,
+            pending: false,
+            language: None,
+            media_text: None,
+            original_timestamp: None,
         })
     }
 }
@@ -132,6 +137,11 @@ impl ChatSource for WhatsAppSource {
         self.connected
     }
 
+    fn capabilities(&self) -> SourceCapabilities {
+        // Chat listing and history are placeholders until whatsapp-rust is integrated
+        SourceCapabilities::none()
+    }
+
     async fn list_chats(&self, filter: Option<ChatFilter>) -> Result<Vec<Chat>> {
         #[cfg(not(feature = "whatsapp"))]
         {
@@ -178,6 +188,12 @@ impl ChatSource for WhatsAppSource {
                 ChatPattern::Multiple(_) => {
                     bail!("Multiple chat patterns not yet supported for WhatsApp.");
                 }
+                ChatPattern::Label(label) => {
+                    bail!("Label '{}' must be resolved by SourcesManager", label);
+                }
+                ChatPattern::Topic { .. } => {
+                    bail!("WhatsApp groups have no topics.");
+                }
             };
 
             info!("Fetching messages from WhatsApp group: {}", group_name);
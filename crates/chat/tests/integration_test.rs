@@ -1,12 +1,13 @@
 use anyhow::Result;
 use chat::{
     types::*, ChatSource, MessageFilter, SourcesManager, ChatFilter, ChatPattern,
+    GlobalChatId, MergedChats,
 };
 use chrono::{DateTime, Utc, Duration};
 
 #[cfg(feature = "mcp")]
 use chat::mcp_server::{
-    GetMessagesRequest, ListChatsRequest, ListSourcesRequest,
+    GetMessagesRequest, Guardrails, ListChatsRequest, ListSourcesRequest,
     tools::*,
 };
 
@@ -106,48 +107,20 @@ impl ChatSource for MockChatSource {
             ChatPattern::All => {
                 // No filtering
             }
-            ChatPattern::Multiple(_) => {
-                // Not implemented for mock
+            ChatPattern::Multiple(ids) => {
+                messages.retain(|msg| ids.contains(&msg.chat_id));
+            }
+            ChatPattern::Label(_) => {
+                // Resolved by SourcesManager before reaching sources
+            }
+            ChatPattern::Topic { .. } => {
+                // Mock chats have no topics
+                messages.clear();
             }
         }
 
-        // Apply time filters
-        if let Some(since) = filter.since {
-            messages.retain(|msg| msg.timestamp >= since);
-        }
-
-        if let Some(before) = filter.before {
-            messages.retain(|msg| msg.timestamp <= before);
-        }
-
-        // Apply sender filter
-        if let Some(ref sender) = filter.sender {
-            let sender_lower = sender.to_lowercase();
-            messages.retain(|msg| {
-                msg.sender
-                    .display_name
-                    .as_ref()
-                    .map(|name| name.to_lowercase().contains(&sender_lower))
-                    .unwrap_or(false)
-                    || msg.sender.id.to_string() == *sender
-            });
-        }
-
-        // Apply search filter
-        if let Some(ref search) = filter.search {
-            let search_lower = search.to_lowercase();
-            messages.retain(|msg| match &msg.content {
-                MessageContent::Text(text) => text.to_lowercase().contains(&search_lower),
-                _ => false,
-            });
-        }
-
-        // Apply limit
-        if let Some(limit) = filter.limit {
-            messages.truncate(limit);
-        }
-
-        Ok(messages)
+        // Time, sender, search and limit follow the shared semantics
+        Ok(apply_filter(messages, &filter))
     }
 
     async fn subscribe(&self) -> Result<Option<tokio::sync::mpsc::Receiver<Message>>> {
@@ -177,6 +150,11 @@ fn create_message(
         timestamp,
         reply_to: None,
         edited: false,
+        mentions_me: false,
+        pending: false,
+        language: None,
+        media_text: None,
+        original_timestamp: None,
     }
 }
 
@@ -187,6 +165,7 @@ fn create_chat(id: i64, title: &str, chat_type: ChatType) -> Chat {
         title: Some(title.to_string()),
         chat_type,
         participant_count: None,
+        topic: None,
     }
 }
 
@@ -253,6 +232,12 @@ async fn test_cross_source_queries() -> Result<()> {
         search: None,
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let messages = manager.query_messages(None, filter).await?;
@@ -267,6 +252,12 @@ async fn test_cross_source_queries() -> Result<()> {
         search: None,
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let messages2 = manager.query_messages(Some("source1"), filter2).await?;
@@ -308,6 +299,12 @@ async fn test_message_filtering() -> Result<()> {
         search: Some("meeting".to_string()),
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let results = manager.query_messages(Some("test"), filter).await?;
@@ -322,6 +319,12 @@ async fn test_message_filtering() -> Result<()> {
         search: None,
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let results2 = manager.query_messages(Some("test"), filter2).await?;
@@ -336,6 +339,12 @@ async fn test_message_filtering() -> Result<()> {
         search: None,
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let results3 = manager.query_messages(Some("test"), filter3).await?;
@@ -350,6 +359,12 @@ async fn test_message_filtering() -> Result<()> {
         search: None,
         limit: Some(2),
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let results4 = manager.query_messages(Some("test"), filter4).await?;
@@ -391,6 +406,12 @@ async fn test_chat_pattern_matching() -> Result<()> {
         search: None,
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let results = manager.query_messages(Some("test"), filter).await?;
@@ -405,6 +426,12 @@ async fn test_chat_pattern_matching() -> Result<()> {
         search: None,
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let results2 = manager.query_messages(Some("test"), filter2).await?;
@@ -486,6 +513,7 @@ async fn test_mcp_list_chats() -> Result<()> {
         source: "telegram".to_string(),
         name_pattern: Some("Work".to_string()),
         chat_type: None,
+        label: None,
     };
 
     let response = handle_list_chats(request, &manager).await?;
@@ -524,9 +552,15 @@ async fn test_mcp_get_messages() -> Result<()> {
         sender: None,
         search: None,
         limit: Some(10),
+        content_type: None,
+        mentions_me: false,
+        exact: false,
+        lang: None,
+        include_system: false,
+        in_attachments: false,
     };
 
-    let response = handle_get_messages(request, &manager).await?;
+    let response = handle_get_messages(request, &manager, &Guardrails::default()).await?;
 
     assert_eq!(response.total, 2);
     assert_eq!(response.messages.len(), 2);
@@ -534,6 +568,59 @@ async fn test_mcp_get_messages() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_merged_chat_queries() -> Result<()> {
+    let manager = SourcesManager::new();
+
+    let now = Utc::now();
+
+    let mut merged = MergedChats::new();
+    merged.add("Work", &[GlobalChatId::new("test", "1"), GlobalChatId::new("test", "2")])?;
+    manager.set_merged_chats(merged)?;
+
+    let source = MockChatSource::new("test", "Test")
+        .with_chats(vec![
+            create_chat(1, "Work", ChatType::Group),
+            create_chat(2, "Work (new)", ChatType::Group),
+            create_chat(3, "Other", ChatType::Group),
+        ])
+        .with_messages(vec![
+            create_message(1, 1, 10, "Alice", "Old group", now - Duration::hours(2)),
+            create_message(2, 2, 10, "Alice", "New group", now - Duration::hours(1)),
+            create_message(3, 3, 11, "Bob", "Elsewhere", now),
+        ]);
+    manager.register(Box::new(source))?;
+
+    let filter = |separate_merged| MessageFilter {
+        chat: ChatPattern::Id(ChatId::new("1")),
+        since: None,
+        before: None,
+        sender: None,
+        search: None,
+        limit: None,
+        content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged,
+    };
+
+    // Merged chats are queried together and reported under the newer chat
+    let messages = manager.query_messages(Some("test"), filter(false)).await?;
+    assert_eq!(messages.len(), 2, "Should include both merged chats");
+    assert!(messages.iter().all(|m| m.chat_id == ChatId::new("2")));
+
+    // Keeping them separate only returns the requested chat
+    let messages = manager.query_messages(Some("test"), filter(true)).await?;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].chat_id, ChatId::new("1"));
+    assert_eq!(messages[0].id, MessageId::new("1"));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_error_handling_source_not_found() -> Result<()> {
     let manager = SourcesManager::new();
@@ -546,6 +633,12 @@ async fn test_error_handling_source_not_found() -> Result<()> {
         search: None,
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let result = manager.query_messages(Some("nonexistent"), filter).await;
@@ -570,6 +663,12 @@ async fn test_empty_results() -> Result<()> {
         search: None,
         limit: None,
         content_type: None,
+        mentions_me: false,
+        exact: false,
+        language: None,
+        include_system: false,
+        in_attachments: false,
+        separate_merged: false,
     };
 
     let messages = manager.query_messages(Some("test"), filter).await?;
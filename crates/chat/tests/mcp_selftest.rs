@@ -0,0 +1,55 @@
+//! Spawns the real chat-mcp-server and runs the self-test suite against it
+#![cfg(feature = "mcp")]
+
+use chat::mcp_selftest::{INVALID_PARAMS, McpClient, run_selftest};
+use serde_json::json;
+use std::process::Command;
+
+/// Server with an empty config directory, so no user labels or accounts leak in
+fn spawn_server() -> McpClient {
+    let home = std::env::temp_dir().join(format!("chat-mcp-selftest-{}", std::process::id()));
+    std::fs::create_dir_all(&home).unwrap();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_chat-mcp-server"));
+    command.env("HOME", &home).env("XDG_CONFIG_HOME", home.join(".config"));
+    McpClient::spawn(command).unwrap()
+}
+
+#[test]
+fn test_selftest_passes() {
+    let mut client = spawn_server();
+    let checks = run_selftest(&mut client);
+
+    let failures: Vec<String> = checks
+        .iter()
+        .filter(|c| !c.passed)
+        .map(|c| format!("{}: {}", c.name, c.detail))
+        .collect();
+    assert!(failures.is_empty(), "failed checks:\n{}", failures.join("\n"));
+
+    // Every advertised tool was exercised both ways
+    assert!(checks.iter().any(|c| c.name == "get_messages (invalid arguments)"));
+    assert!(checks.iter().any(|c| c.name == "list_sources (valid arguments)"));
+}
+
+#[test]
+fn test_list_sources_without_sources() {
+    let mut client = spawn_server();
+    client.request("initialize", json!({})).unwrap();
+
+    let response = client.call_tool("list_sources", json!({})).unwrap();
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    let sources: serde_json::Value = serde_json::from_str(text).unwrap();
+    assert_eq!(sources["sources"], json!([]));
+}
+
+#[test]
+fn test_wrong_argument_type_is_invalid_params() {
+    let mut client = spawn_server();
+
+    let response = client
+        .call_tool("get_messages", json!({ "chat": "*", "limit": "ten" }))
+        .unwrap();
+    assert_eq!(response["id"], json!(1));
+    assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+}
@@ -0,0 +1,42 @@
+#!/usr/bin/env bash
+# Build target/AgentDashboard-x86_64.AppImage with the bundled resources and, when they
+# have been built, the chat helpers (cd crates/chat && cargo build --release --features mcp,self-update).
+# Needs appimagetool on PATH: https://github.com/AppImage/appimagetool
+set -euo pipefail
+
+root="$(cd "$(dirname "$0")/../.." && pwd)"
+appdir="$root/target/AgentDashboard.AppDir"
+arch="$(uname -m)"
+
+cd "$root"
+cargo build --release
+
+rm -rf "$appdir"
+mkdir -p "$appdir/usr/bin" "$appdir/usr/share/agent-dashboard" "$appdir/usr/share/applications"
+install -m 755 target/release/agent-dashboard "$appdir/usr/bin/"
+for helper in chat chat-mcp-server; do
+    if [ -x "crates/chat/target/release/$helper" ]; then
+        install -m 755 "crates/chat/target/release/$helper" "$appdir/usr/bin/"
+    else
+        echo "Skipping $helper: not built, the AppImage will use the one on PATH" >&2
+    fi
+done
+cp -r resources/. "$appdir/usr/share/agent-dashboard/"
+
+for size in 32 128 256 512; do
+    icons="$appdir/usr/share/icons/hicolor/${size}x${size}/apps"
+    mkdir -p "$icons"
+    cp "resources/icons/icon-$size.png" "$icons/agent-dashboard.png"
+done
+cp resources/icons/icon-256.png "$appdir/agent-dashboard.png"
+cp packaging/linux/agent-dashboard.desktop "$appdir/"
+cp packaging/linux/agent-dashboard.desktop "$appdir/usr/share/applications/"
+
+cat > "$appdir/AppRun" <<'APPRUN'
+#!/bin/sh
+here="$(dirname "$(readlink -f "$0")")"
+exec "$here/usr/bin/agent-dashboard" "$@"
+APPRUN
+chmod 755 "$appdir/AppRun"
+
+ARCH="$arch" appimagetool "$appdir" "target/AgentDashboard-$arch.AppImage"
@@ -0,0 +1,290 @@
+//! Branching conversations with LLM agents.
+//!
+//! Editing a message sent to an agent, or asking it for another reply, forks the
+//! conversation there: the new branch shares everything before that message and goes
+//! on from the new one. Only one branch per agent is shown at a time. Branching needs
+//! an agent that is sent the whole conversation with each message, so it can be handed
+//! the conversation of whichever branch is shown.
+
+use super::AgentId;
+use crate::ui::chat::{ChatMessage, MessageDirection, MessageId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BranchId(Uuid);
+
+impl BranchId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for BranchId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for BranchId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Who said a turn of a conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+/// A message of the conversation handed to an agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turn {
+    pub role: Role,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Branch {
+    pub id: BranchId,
+    pub agent_id: AgentId,
+    /// Branch it was forked from; `None` for the agent's original conversation
+    pub parent: Option<BranchId>,
+    /// The message to the agent that this branch replaces
+    pub fork_at: MessageId,
+    /// The message it has in its place
+    pub prompt: MessageId,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where a conversation can be switched to another branch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternatives {
+    /// The conversation forked from (`None` for the original), then its branches oldest first
+    pub options: Vec<Option<BranchId>>,
+    /// Which of them is shown
+    pub current: usize,
+}
+
+/// Every branch, and which one is shown for each agent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Branches {
+    branches: Vec<Branch>,
+    /// Agents missing here show their original conversation
+    active: HashMap<AgentId, BranchId>,
+}
+
+impl Branches {
+    pub fn active(&self, agent: AgentId) -> Option<BranchId> {
+        self.active.get(&agent).copied()
+    }
+
+    fn get(&self, id: BranchId) -> Option<&Branch> {
+        self.branches.iter().find(|branch| branch.id == id)
+    }
+
+    /// The branch `message` starts, if it's one a branch has in place of another
+    fn started_by(&self, message: &ChatMessage) -> Option<&Branch> {
+        self.get(message.branch?).filter(|branch| branch.prompt == message.id)
+    }
+
+    /// The branches leading from the original conversation to the one shown for `agent`
+    fn path(&self, agent: AgentId) -> Vec<&Branch> {
+        let mut path = Vec::new();
+        let mut next = self.active(agent);
+        while let Some(branch) = next.and_then(|id| self.get(id)) {
+            path.push(branch);
+            next = branch.parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// The messages shown. For an agent that's the messages of the branch shown and of
+    /// the branches it came from, each up to where the next one forks off.
+    pub fn visible<'a>(&self, messages: &'a [ChatMessage]) -> Vec<&'a ChatMessage> {
+        if self.branches.is_empty() {
+            return messages.iter().collect();
+        }
+
+        // Per agent, its path and which branches on it have reached their fork point
+        let mut agents: HashMap<AgentId, (Vec<&Branch>, Vec<bool>)> = HashMap::new();
+        messages
+            .iter()
+            .filter(|message| {
+                let Some(agent) = message.agent_id else {
+                    return true;
+                };
+                let (path, forked) = agents.entry(agent).or_insert_with(|| {
+                    let path = self.path(agent);
+                    let forked = vec![false; path.len()];
+                    (path, forked)
+                });
+
+                // Level 0 is the original conversation, level n the nth branch on the path
+                let level = match message.branch {
+                    None => 0,
+                    Some(id) => match path.iter().position(|branch| branch.id == id) {
+                        Some(index) => index + 1,
+                        None => return false,
+                    },
+                };
+                if level < path.len() && path[level].fork_at == message.id {
+                    forked[level] = true;
+                }
+                level == path.len() || !forked[level]
+            })
+            .collect()
+    }
+
+    /// The conversation with `agent` as shown, up to `until` if given, to hand to it.
+    /// Broadcasts count as the user's; errors and tool calls are left out.
+    pub fn history(&self, messages: &[ChatMessage], agent: AgentId, until: Option<MessageId>) -> Vec<Turn> {
+        self.visible(messages)
+            .into_iter()
+            .take_while(|message| Some(message.id) != until)
+            .filter(|message| !message.metadata.is_error && !message.metadata.is_toolcall)
+            .filter_map(|message| {
+                let role = match message.direction {
+                    MessageDirection::Broadcast => Role::User,
+                    MessageDirection::ToAgent if message.agent_id == Some(agent) => Role::User,
+                    MessageDirection::FromAgent if message.agent_id == Some(agent) => Role::Assistant,
+                    _ => return None,
+                };
+                Some(Turn { role, text: message.content.clone() })
+            })
+            .collect()
+    }
+
+    /// Fork the conversation `message` belongs to, putting `prompt` in its place, and
+    /// show the new branch. Replacing a branch's first message adds another branch at
+    /// the same place rather than one inside it.
+    pub fn fork(&mut self, message: &ChatMessage, prompt: MessageId) -> Option<&Branch> {
+        let agent_id = message.agent_id?;
+        let (fork_at, parent) = match self.started_by(message) {
+            Some(branch) => (branch.fork_at, branch.parent),
+            None => (message.id, message.branch),
+        };
+        let branch = Branch { id: BranchId::new(), agent_id, parent, fork_at, prompt, created_at: Utc::now() };
+        self.active.insert(agent_id, branch.id);
+        self.branches.push(branch);
+        self.branches.last()
+    }
+
+    /// Show `branch` of `agent`'s conversation, or the original for `None`
+    pub fn switch(&mut self, agent: AgentId, branch: Option<BranchId>) {
+        match branch {
+            Some(branch) => self.active.insert(agent, branch),
+            None => self.active.remove(&agent),
+        };
+    }
+
+    /// The branches to switch between at `message`, when it's a message that has been
+    /// replaced or one in place of another
+    pub fn alternatives(&self, message: &ChatMessage) -> Option<Alternatives> {
+        let (fork_at, original) = match self.started_by(message) {
+            Some(branch) => (branch.fork_at, branch.parent),
+            None => (message.id, message.branch),
+        };
+        let mut options = vec![original];
+        options.extend(self.branches.iter().filter(|branch| branch.fork_at == fork_at).map(|branch| Some(branch.id)));
+        if options.len() == 1 {
+            return None;
+        }
+        let current = options.iter().position(|option| *option == message.branch).unwrap_or(0);
+        Some(Alternatives { options, current })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents<'a>(messages: &[&'a ChatMessage]) -> Vec<&'a str> {
+        messages.iter().map(|message| message.content.as_str()).collect()
+    }
+
+    /// Push a message, tagged with the agent's shown branch as the app does
+    fn say(messages: &mut Vec<ChatMessage>, branches: &Branches, mut message: ChatMessage) -> MessageId {
+        message.branch = message.agent_id.and_then(|agent| branches.active(agent));
+        let id = message.id;
+        messages.push(message);
+        id
+    }
+
+    #[test]
+    fn test_fork_and_switch() {
+        let agent = AgentId::new();
+        let other = AgentId::new();
+        let mut branches = Branches::default();
+        let mut messages = Vec::new();
+
+        say(&mut messages, &branches, ChatMessage::new_user_message("hi".into(), Some(agent)));
+        say(&mut messages, &branches, ChatMessage::new_agent_message(agent, "hello".into()));
+        let question = say(&mut messages, &branches, ChatMessage::new_user_message("2+2?".into(), Some(agent)));
+        say(&mut messages, &branches, ChatMessage::new_agent_message(agent, "5".into()));
+        say(&mut messages, &branches, ChatMessage::new_agent_message(other, "elsewhere".into()));
+        assert_eq!(branches.visible(&messages).len(), messages.len());
+
+        // Ask again, differently
+        let fork_message = messages.iter().find(|message| message.id == question).unwrap().clone();
+        let prompt = ChatMessage::new_user_message("What is 2+2?".into(), Some(agent));
+        let first = branches.fork(&fork_message, prompt.id).unwrap().id;
+        let prompt_id = say(&mut messages, &branches, prompt);
+        say(&mut messages, &branches, ChatMessage::new_agent_message(agent, "4".into()));
+        assert_eq!(contents(&branches.visible(&messages)), ["hi", "hello", "elsewhere", "What is 2+2?", "4"]);
+
+        let history = branches.history(&messages, agent, Some(prompt_id));
+        assert_eq!(history, [
+            Turn { role: Role::User, text: "hi".into() },
+            Turn { role: Role::Assistant, text: "hello".into() },
+        ]);
+
+        // Replacing the branch's prompt again adds a sibling, offered at the same place
+        let replaced = messages.iter().find(|message| message.id == prompt_id).unwrap().clone();
+        let again = ChatMessage::new_user_message("2 plus 2?".into(), Some(agent));
+        let second = branches.fork(&replaced, again.id).unwrap().id;
+        say(&mut messages, &branches, again);
+        assert_eq!(contents(&branches.visible(&messages)), ["hi", "hello", "elsewhere", "2 plus 2?"]);
+        let shown = branches.visible(&messages);
+        let alternatives = branches.alternatives(shown.last().unwrap()).unwrap();
+        assert_eq!(alternatives.options, [None, Some(first), Some(second)]);
+        assert_eq!(alternatives.current, 2);
+
+        branches.switch(agent, None);
+        let shown = branches.visible(&messages);
+        assert_eq!(contents(&shown), ["hi", "hello", "2+2?", "5", "elsewhere"]);
+        assert_eq!(branches.alternatives(shown[2]).unwrap().current, 0);
+        assert!(branches.alternatives(shown[0]).is_none());
+    }
+
+    #[test]
+    fn test_nested_branches() {
+        let agent = AgentId::new();
+        let mut branches = Branches::default();
+        let mut messages = Vec::new();
+
+        let a = say(&mut messages, &branches, ChatMessage::new_user_message("a".into(), Some(agent)));
+        let fork_a = messages[0].clone();
+        let b = ChatMessage::new_user_message("b".into(), Some(agent));
+        branches.fork(&fork_a, b.id);
+        say(&mut messages, &branches, b);
+        let c = say(&mut messages, &branches, ChatMessage::new_user_message("c".into(), Some(agent)));
+
+        let fork_c = messages.iter().find(|message| message.id == c).unwrap().clone();
+        let d = ChatMessage::new_user_message("d".into(), Some(agent));
+        let inner = branches.fork(&fork_c, d.id).unwrap().clone();
+        say(&mut messages, &branches, d);
+        assert_ne!(inner.parent, None);
+        assert_eq!(contents(&branches.visible(&messages)), ["b", "d"]);
+
+        branches.switch(agent, inner.parent);
+        assert_eq!(contents(&branches.visible(&messages)), ["b", "c"]);
+        assert!(branches.visible(&messages).iter().all(|message| message.id != a));
+    }
+}
@@ -0,0 +1,564 @@
+use super::types::{AgentConfig, AgentId, AgentType};
+use crate::mcp;
+use crate::sandbox::SandboxConfig;
+use crate::toolcall::process::RunningAgent;
+use crate::toolcall::protocol::{AgentState, Frame, Output};
+use anyhow::{Context, Result};
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
+use vte::{Params, Parser, Perform};
+
+/// A response ends when the CLI has been quiet this long, even without a prompt
+const IDLE_TIMEOUT: Duration = Duration::from_millis(2500);
+
+/// Settings shared by the coding CLI adapters, stored in `AgentConfig::config_data`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodingCliConfig {
+    /// Model to ask the CLI for; the CLI's default when empty
+    #[serde(default)]
+    pub model: String,
+    /// Directory the CLI works in
+    pub workspace: String,
+    /// Extra command-line arguments
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+impl Default for CodingCliConfig {
+    fn default() -> Self {
+        let workspace = dirs::home_dir()
+            .map(|home| home.display().to_string())
+            .unwrap_or_else(|| ".".to_string());
+        Self {
+            model: String::new(),
+            workspace,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// A coding assistant CLI the dashboard can drive in a terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodingCli {
+    ClaudeCode,
+    Codex,
+}
+
+impl CodingCli {
+    pub fn for_agent_type(agent_type: &AgentType) -> Option<Self> {
+        match agent_type {
+            AgentType::ClaudeCode => Some(Self::ClaudeCode),
+            AgentType::Codex => Some(Self::Codex),
+            _ => None,
+        }
+    }
+
+    pub fn agent_type(self) -> AgentType {
+        match self {
+            Self::ClaudeCode => AgentType::ClaudeCode,
+            Self::Codex => AgentType::Codex,
+        }
+    }
+
+    /// Program and arguments for the given settings, connecting the CLI to the
+    /// dashboard's chat MCP server when one is running at `chat_mcp`, and pointing it at
+    /// the agent's system prompt and memory in `context_file`
+    pub fn command(
+        self,
+        config: &CodingCliConfig,
+        chat_mcp: Option<&str>,
+        context_file: Option<&Path>,
+    ) -> (String, Vec<String>) {
+        let program = match self {
+            Self::ClaudeCode => "claude",
+            Self::Codex => "codex",
+        };
+
+        let mut args = Vec::new();
+        if !config.model.trim().is_empty() {
+            args.push("--model".to_string());
+            args.push(config.model.trim().to_string());
+        }
+        if let Some(address) = chat_mcp {
+            match self {
+                Self::ClaudeCode => {
+                    args.push("--mcp-config".to_string());
+                    args.push(mcp::client_config(address).to_string());
+                }
+                Self::Codex => {
+                    args.push("-c".to_string());
+                    args.push("mcp_servers.chat.command=\"chat-mcp-server\"".to_string());
+                    args.push("-c".to_string());
+                    args.push(format!("mcp_servers.chat.args=[\"--connect\", \"{}\"]", address));
+                }
+            }
+        }
+        args.extend(config.extra_args.iter().cloned());
+        if let Some(path) = context_file {
+            let instruction = format!(
+                "Your instructions and memory from the agent dashboard are in {}. Read that file before anything else and follow it.",
+                path.display()
+            );
+            match self {
+                Self::ClaudeCode => {
+                    args.push("--append-system-prompt".to_string());
+                    args.push(instruction);
+                }
+                // No option for it, so it's the first prompt
+                Self::Codex => args.push(instruction),
+            }
+        }
+
+        (program.to_string(), args)
+    }
+
+    /// Whether a screen line is the CLI's input prompt, which means it's waiting for us
+    fn is_prompt(self, line: &str) -> bool {
+        let line = line.trim_start_matches(['│', '┃', ' ']).trim_end_matches(['│', '┃', ' ']);
+        match self {
+            Self::ClaudeCode => line == ">" || line.starts_with("> "),
+            Self::Codex => line == "▌" || line.starts_with("▌ ") || line == "›" || line.starts_with("› "),
+        }
+    }
+
+    /// The tool name and input if a line announces a tool call
+    fn tool_call(self, line: &str) -> Option<(String, String)> {
+        let line = line.trim();
+        match self {
+            // "⏺ Bash(cargo test)" or "● Read(src/main.rs)"
+            Self::ClaudeCode => {
+                let rest = line.strip_prefix('⏺').or_else(|| line.strip_prefix('●'))?.trim_start();
+                let open = rest.find('(')?;
+                let name = &rest[..open];
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return None;
+                }
+                let input = rest[open + 1..].strip_suffix(')').unwrap_or(&rest[open + 1..]);
+                Some((name.to_string(), input.to_string()))
+            }
+            // "exec bash -lc 'ls'" or "⚡ Running ls"
+            Self::Codex => {
+                let command = line
+                    .strip_prefix("exec ")
+                    .or_else(|| line.strip_prefix("⚡ Running "))?;
+                Some(("shell".to_string(), command.trim().to_string()))
+            }
+        }
+    }
+
+    /// Lines that are screen decoration rather than output
+    fn is_chrome(self, line: &str) -> bool {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return false;
+        }
+        if trimmed.chars().all(|c| "─━│┃╭╮╰╯┌┐└┘═ ".contains(c)) {
+            return true;
+        }
+        const HINTS: [&str; 5] = [
+            "esc to interrupt",
+            "? for shortcuts",
+            "ctrl+c to",
+            "tokens used",
+            "context left",
+        ];
+        let lower = trimmed.to_lowercase();
+        HINTS.iter().any(|hint| lower.contains(hint))
+    }
+
+    /// Response text with the CLI's bullet markers removed
+    fn clean(self, line: &str) -> String {
+        let trimmed = line.trim_end();
+        let text = trimmed.trim_start();
+        match text.strip_prefix('⏺').or_else(|| text.strip_prefix('●')) {
+            Some(rest) if self == Self::ClaudeCode => rest.trim_start().to_string(),
+            _ => trimmed.to_string(),
+        }
+    }
+}
+
+/// A new agent config with the adapter's default settings
+pub fn preset(cli: CodingCli, name: String) -> AgentConfig {
+    AgentConfig {
+        id: AgentId::new(),
+        name,
+        agent_type: cli.agent_type(),
+        config_data: serde_json::to_value(CodingCliConfig::default()).unwrap_or_default(),
+        sandbox: SandboxConfig::default(),
+        context: Default::default(),
+    }
+}
+
+/// Finds responses and tool calls in a coding CLI's screen output.
+///
+/// A response starts when the user's message is sent and ends when the input prompt
+/// comes back or the CLI goes quiet. Lines in between become one chat message, apart
+/// from tool call announcements, which become `tool_call` frames.
+pub struct ResponseTracker {
+    cli: CodingCli,
+    /// The message being answered, so its echo isn't part of the reply
+    question: Option<String>,
+    lines: Vec<String>,
+    last_output: Instant,
+    next_call: u64,
+}
+
+impl ResponseTracker {
+    pub fn new(cli: CodingCli) -> Self {
+        Self {
+            cli,
+            question: None,
+            lines: Vec::new(),
+            last_output: Instant::now(),
+            next_call: 1,
+        }
+    }
+
+    pub fn is_responding(&self) -> bool {
+        self.question.is_some()
+    }
+
+    /// The user's message was sent
+    pub fn sent(&mut self, text: &str, now: Instant) -> Vec<Output> {
+        let mut outputs = self.finish();
+        self.question = Some(text.trim().to_string());
+        self.last_output = now;
+        outputs.push(Output::Frame(Frame::Status { state: AgentState::Busy, detail: None }));
+        outputs
+    }
+
+    /// A line of screen output
+    pub fn line(&mut self, line: &str, now: Instant) -> Vec<Output> {
+        self.last_output = now;
+
+        let Some(question) = &self.question else {
+            return Vec::new();
+        };
+        if self.cli.is_prompt(line) {
+            // The prompt line echoing our own message isn't the end of the reply
+            if line.contains(question.as_str()) && self.lines.is_empty() {
+                return Vec::new();
+            }
+            return self.finish();
+        }
+        if (line.trim().is_empty() && self.lines.is_empty()) || self.cli.is_chrome(line) || line.trim() == question {
+            return Vec::new();
+        }
+
+        if let Some((name, input)) = self.cli.tool_call(line) {
+            let id = self.next_call.to_string();
+            self.next_call += 1;
+            return vec![Output::Frame(Frame::ToolCall {
+                id,
+                name,
+                parameters: serde_json::json!({ "input": input }),
+            })];
+        }
+
+        self.lines.push(self.cli.clean(line));
+        Vec::new()
+    }
+
+    /// Time passed without output; ends a response after `IDLE_TIMEOUT`
+    pub fn tick(&mut self, now: Instant) -> Vec<Output> {
+        if self.is_responding() && now.duration_since(self.last_output) >= IDLE_TIMEOUT {
+            self.finish()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// End the current response, if there is one
+    pub fn finish(&mut self) -> Vec<Output> {
+        if self.question.take().is_none() {
+            return Vec::new();
+        }
+
+        let text = self.lines.join("\n").trim().to_string();
+        self.lines.clear();
+
+        let mut outputs = Vec::new();
+        if !text.is_empty() {
+            outputs.push(Output::Frame(Frame::Message { text }));
+        }
+        outputs.push(Output::Frame(Frame::Status { state: AgentState::Idle, detail: None }));
+        outputs
+    }
+}
+
+/// Collects printable text from terminal output, one line at a time
+#[derive(Default)]
+struct ScreenText {
+    current: String,
+    lines: Vec<String>,
+}
+
+impl ScreenText {
+    fn end_line(&mut self) {
+        let line = std::mem::take(&mut self.current);
+        if !line.trim().is_empty() || self.lines.last().is_some_and(|l| !l.trim().is_empty()) {
+            self.lines.push(line);
+        }
+    }
+}
+
+impl Perform for ScreenText {
+    fn print(&mut self, c: char) {
+        self.current.push(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => self.end_line(),
+            // A bare carriage return redraws the line; keep the last drawing
+            b'\r' if !self.current.is_empty() => self.end_line(),
+            b'\t' => self.current.push('\t'),
+            _ => {}
+        }
+    }
+
+    fn hook(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, _action: char) {}
+
+    fn put(&mut self, _byte: u8) {}
+
+    fn unhook(&mut self) {}
+
+    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {}
+
+    fn csi_dispatch(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, action: char) {
+        // Cursor moves to another row start a new line of text
+        if matches!(action, 'H' | 'f' | 'B' | 'E' | 'F' | 'A') && !self.current.is_empty() {
+            self.end_line();
+        }
+    }
+
+    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {}
+}
+
+enum Event {
+    Screen(Vec<u8>),
+    Sent(String),
+}
+
+/// A coding CLI running in a pseudo-terminal
+pub struct CodingCliAgent {
+    child: Box<dyn Child + Send + Sync>,
+    // Kept so the terminal stays open
+    _master: Box<dyn MasterPty + Send>,
+    writer: Mutex<Box<dyn Write + Send>>,
+    events: mpsc::Sender<Event>,
+    output: mpsc::Receiver<Output>,
+}
+
+impl CodingCliAgent {
+    pub fn spawn(
+        cli: CodingCli,
+        config: &CodingCliConfig,
+        sandbox: &SandboxConfig,
+        chat_mcp: Option<&str>,
+        context_file: Option<&Path>,
+        ctx: egui::Context,
+    ) -> Result<Self> {
+        let pair = native_pty_system()
+            .openpty(PtySize { rows: 50, cols: 160, pixel_width: 0, pixel_height: 0 })
+            .context("Failed to create PTY")?;
+
+        let (program, args) = cli.command(config, chat_mcp, context_file);
+        let launch = sandbox.wrap(&program, &args, Some(Path::new(&config.workspace)))?;
+        let mut cmd = CommandBuilder::new(&launch.program);
+        cmd.args(&launch.args);
+        cmd.cwd(&config.workspace);
+        launch.apply_env_pty(&mut cmd);
+        cmd.env("TERM", "xterm-256color");
+
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .with_context(|| format!("Failed to start '{}'", program))?;
+        let mut reader = pair.master.try_clone_reader().context("Failed to read PTY")?;
+        let writer = pair.master.take_writer().context("Failed to write PTY")?;
+
+        let (events, event_rx) = mpsc::channel();
+        let (output_tx, output) = mpsc::channel();
+
+        let screen = events.clone();
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 4096];
+            while let Ok(n) = reader.read(&mut buffer) {
+                if n == 0 || screen.send(Event::Screen(buffer[..n].to_vec())).is_err() {
+                    break;
+                }
+            }
+        });
+
+        std::thread::spawn(move || {
+            let mut parser = Parser::new();
+            let mut text = ScreenText::default();
+            let mut tracker = ResponseTracker::new(cli);
+
+            loop {
+                let outputs = match event_rx.recv_timeout(Duration::from_millis(250)) {
+                    Ok(Event::Screen(bytes)) => {
+                        for byte in bytes {
+                            parser.advance(&mut text, byte);
+                        }
+                        let now = Instant::now();
+                        text.lines.drain(..).flat_map(|line| tracker.line(&line, now)).collect()
+                    }
+                    Ok(Event::Sent(message)) => tracker.sent(&message, Instant::now()),
+                    Err(mpsc::RecvTimeoutError::Timeout) => tracker.tick(Instant::now()),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                };
+
+                if outputs.is_empty() {
+                    continue;
+                }
+                for output in outputs {
+                    if output_tx.send(output).is_err() {
+                        return;
+                    }
+                }
+                ctx.request_repaint();
+            }
+
+            for output in tracker.finish() {
+                let _ = output_tx.send(output);
+            }
+            ctx.request_repaint();
+        });
+
+        Ok(Self {
+            child,
+            _master: pair.master,
+            writer: Mutex::new(writer),
+            events,
+            output,
+        })
+    }
+}
+
+impl RunningAgent for CodingCliAgent {
+    fn send_message(&self, text: &str) -> Result<()> {
+        let mut writer = self.writer.lock().map_err(|_| anyhow::anyhow!("PTY writer lock poisoned"))?;
+        // Newlines would submit early; the CLIs take the message as one line
+        let line = text.replace(['\r', '\n'], " ");
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\r")?;
+        writer.flush()?;
+
+        let _ = self.events.send(Event::Sent(line));
+        Ok(())
+    }
+
+    fn take_output(&self) -> Vec<Output> {
+        self.output.try_iter().collect()
+    }
+
+    fn exited(&mut self) -> Option<String> {
+        self.child.try_wait().ok().flatten().map(|status| format!("{:?}", status))
+    }
+
+    fn pid(&self) -> Option<u32> {
+        self.child.process_id()
+    }
+}
+
+impl Drop for CodingCliAgent {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(outputs: &[Output]) -> Vec<String> {
+        outputs
+            .iter()
+            .filter_map(|output| match output {
+                Output::Frame(Frame::Message { text }) => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_command() {
+        let config = CodingCliConfig {
+            model: "opus".to_string(),
+            workspace: "/tmp".to_string(),
+            extra_args: vec!["--verbose".to_string()],
+        };
+        let (program, args) = CodingCli::ClaudeCode.command(&config, None, None);
+        assert_eq!(program, "claude");
+        assert_eq!(args, ["--model", "opus", "--verbose"]);
+
+        let (program, args) = CodingCli::Codex.command(&CodingCliConfig::default(), None, None);
+        assert_eq!(program, "codex");
+        assert!(args.is_empty());
+
+        // With the dashboard's chat MCP server running, both CLIs are pointed at it
+        let (_, args) = CodingCli::ClaudeCode.command(&config, Some("127.0.0.1:7411"), None);
+        assert_eq!(args[2], "--mcp-config");
+        assert!(args[3].contains("\"--connect\",\"127.0.0.1:7411\""));
+        assert_eq!(args.last().map(String::as_str), Some("--verbose"));
+
+        let (_, args) = CodingCli::Codex.command(&CodingCliConfig::default(), Some("127.0.0.1:7411"), None);
+        assert_eq!(args[3], "mcp_servers.chat.args=[\"--connect\", \"127.0.0.1:7411\"]");
+
+        // The context file goes in Claude Code's system prompt and Codex's first prompt
+        let context = Path::new("/tmp/context.md");
+        let (_, args) = CodingCli::ClaudeCode.command(&config, None, Some(context));
+        assert_eq!(args[3], "--append-system-prompt");
+        assert!(args[4].contains("/tmp/context.md"));
+        let (_, args) = CodingCli::Codex.command(&CodingCliConfig::default(), None, Some(context));
+        assert_eq!(args.len(), 1);
+        assert!(args[0].contains("/tmp/context.md"));
+    }
+
+    #[test]
+    fn test_claude_response() {
+        let now = Instant::now();
+        let mut tracker = ResponseTracker::new(CodingCli::ClaudeCode);
+        let mut outputs = tracker.sent("list the files", now);
+
+        for line in [
+            "│ > list the files │",
+            "list the files",
+            "⏺ Bash(ls)",
+            "  ⎿  Cargo.toml",
+            "⏺ There is one file: Cargo.toml.",
+            "✻ Thinking… (esc to interrupt)",
+            "╭──────────╮",
+            "│ >        │",
+        ] {
+            outputs.extend(tracker.line(line, now));
+        }
+
+        assert!(outputs.iter().any(|o| matches!(o, Output::Frame(Frame::ToolCall { name, .. }) if name == "Bash")));
+        assert_eq!(messages(&outputs), ["⎿  Cargo.toml\nThere is one file: Cargo.toml."]);
+        assert_eq!(outputs.last(), Some(&Output::Frame(Frame::Status { state: AgentState::Idle, detail: None })));
+        assert!(!tracker.is_responding());
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let now = Instant::now();
+        let mut tracker = ResponseTracker::new(CodingCli::Codex);
+        tracker.sent("hi", now);
+        tracker.line("exec bash -lc 'git status'", now);
+        tracker.line("Working tree clean.", now);
+
+        assert!(tracker.tick(now + Duration::from_millis(100)).is_empty());
+        let outputs = tracker.tick(now + IDLE_TIMEOUT);
+        assert_eq!(messages(&outputs), ["Working tree clean."]);
+
+        // Output with nobody asking is ignored
+        assert!(tracker.line("Welcome to Codex", now).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+//! What an agent is told ahead of any conversation: a system prompt, and facts it
+//! should remember across conversations. LLM agents get it with every request; agents
+//! that run as a process or coding CLI get a context file to read.
+
+use super::AgentId;
+use crate::config::AppConfig;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the context file's path, for process agents
+pub const CONTEXT_FILE_ENV: &str = "AGENT_CONTEXT_FILE";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub text: String,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentContext {
+    /// Instructions given before the conversation; none when empty
+    #[serde(default)]
+    pub system_prompt: String,
+    /// Facts to remember, oldest first
+    #[serde(default)]
+    pub memory: Vec<MemoryEntry>,
+}
+
+impl AgentContext {
+    pub fn is_empty(&self) -> bool {
+        self.system_prompt.trim().is_empty() && self.memory.is_empty()
+    }
+
+    /// Add a fact, unless it's blank or already remembered. Returns whether it was added.
+    pub fn remember(&mut self, text: &str) -> bool {
+        let text = text.trim();
+        if text.is_empty() || self.memory.iter().any(|entry| entry.text == text) {
+            return false;
+        }
+        self.memory.push(MemoryEntry { text: text.to_string(), added_at: Utc::now() });
+        true
+    }
+
+    /// The system prompt followed by the memory, as one system message
+    pub fn system_message(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut text = self.system_prompt.trim().to_string();
+        if !self.memory.is_empty() {
+            if !text.is_empty() {
+                text.push_str("\n\n");
+            }
+            text.push_str("Things to remember:");
+            for entry in &self.memory {
+                text.push_str("\n- ");
+                text.push_str(&entry.text);
+            }
+        }
+        Some(text)
+    }
+
+    /// The context file's text
+    pub fn markdown(&self) -> String {
+        let mut text = String::from("# System prompt\n\n");
+        text.push_str(self.system_prompt.trim());
+        text.push_str("\n\n# Memory\n\n");
+        for entry in &self.memory {
+            text.push_str(&format!("- {} (added {})\n", entry.text, entry.added_at.format("%Y-%m-%d")));
+        }
+        text
+    }
+}
+
+/// Where an agent's context file goes
+pub fn context_file(agent: AgentId) -> Result<PathBuf> {
+    Ok(AppConfig::config_dir()?.join("agents").join(agent.to_string()).join("context.md"))
+}
+
+/// Write `agent`'s context file, or remove it when there's no context. Returns its path
+/// when there is one.
+pub fn write_context_file(agent: AgentId, context: &AgentContext) -> Result<Option<PathBuf>> {
+    let path = context_file(agent)?;
+    Ok(write_to(&path, context)?.then_some(path))
+}
+
+fn write_to(path: &Path, context: &AgentContext) -> Result<bool> {
+    if context.is_empty() {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => return Ok(false),
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, context.markdown())?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_message() {
+        let mut context = AgentContext::default();
+        assert_eq!(context.system_message(), None);
+
+        assert!(context.remember(" Deploys go through staging "));
+        assert!(!context.remember("Deploys go through staging"));
+        assert!(!context.remember("  "));
+        assert_eq!(context.system_message().unwrap(), "Things to remember:\n- Deploys go through staging");
+
+        context.system_prompt = "You review Rust code.\n".to_string();
+        assert!(context.remember("Prefer anyhow errors"));
+        assert_eq!(
+            context.system_message().unwrap(),
+            "You review Rust code.\n\nThings to remember:\n- Deploys go through staging\n- Prefer anyhow errors"
+        );
+    }
+
+    #[test]
+    fn test_context_file() {
+        let path = std::env::temp_dir().join(format!("agent-{}", uuid::Uuid::new_v4())).join("context.md");
+        let mut context = AgentContext { system_prompt: "Be brief.".to_string(), memory: Vec::new() };
+        context.remember("The API lives in crates/api");
+
+        assert!(write_to(&path, &context).unwrap());
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("# System prompt\n\nBe brief.\n\n# Memory\n\n- The API lives in crates/api (added "));
+
+        assert!(!write_to(&path, &AgentContext::default()).unwrap());
+        assert!(!path.exists());
+        assert!(!write_to(&path, &AgentContext::default()).unwrap());
+        let _ = std::fs::remove_dir(path.parent().unwrap());
+    }
+}
@@ -1,4 +1,10 @@
+pub mod branch;
+pub mod coding_cli;
+pub mod context;
 pub mod ollama;
+pub mod process;
 pub mod types;
 
+pub use coding_cli::{CodingCli, CodingCliConfig};
+pub use process::ProcessAgentConfig;
 pub use types::{AgentConfig, AgentId, AgentType};
@@ -1,140 +1,208 @@
-use super::types::{Agent, AgentConfig, AgentId, AgentStatus};
-use anyhow::Result;
+use super::branch::{Role, Turn};
+use super::context::AgentContext;
+use crate::toolcall::process::RunningAgent;
+use crate::toolcall::protocol::{AgentState, Frame, Output};
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{mpsc, Arc, Mutex};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct OllamaConfig {
     pub host: String,
     pub model: String,
 }
 
-#[allow(dead_code)]
-pub struct OllamaAgent {
-    config: AgentConfig,
-    ollama_config: OllamaConfig,
-    status: Arc<RwLock<AgentStatus>>,
-    client: reqwest::Client,
-    conversation_history: Arc<RwLock<Vec<OllamaMessage>>>,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
 struct OllamaMessage {
     role: String,
     content: String,
 }
 
+impl From<Turn> for OllamaMessage {
+    fn from(turn: Turn) -> Self {
+        let role = match turn.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        Self { role: role.to_string(), content: turn.text }
+    }
+}
+
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
-struct OllamaChatRequest {
-    model: String,
+struct OllamaChatRequest<'a> {
+    model: &'a str,
     messages: Vec<OllamaMessage>,
     stream: bool,
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 struct OllamaChatResponse {
     message: OllamaMessage,
-    done: bool,
 }
 
-#[allow(dead_code)]
-impl OllamaAgent {
-    pub fn new(config: AgentConfig) -> Result<Self> {
-        let ollama_config: OllamaConfig = serde_json::from_value(config.config_data.clone())?;
-
-        Ok(Self {
-            config,
-            ollama_config,
-            status: Arc::new(RwLock::new(AgentStatus::Disconnected)),
-            client: reqwest::Client::new(),
-            conversation_history: Arc::new(RwLock::new(Vec::new())),
-        })
+/// The conversation sent with each message, and how many times it has been replaced
+#[derive(Default)]
+struct Conversation {
+    /// System prompt and memory, sent ahead of the messages
+    system: Option<String>,
+    messages: Vec<OllamaMessage>,
+    generation: u64,
+}
+
+impl Conversation {
+    fn request_messages(&self) -> Vec<OllamaMessage> {
+        let system = self.system.iter().map(|text| OllamaMessage { role: "system".to_string(), content: text.clone() });
+        system.chain(self.messages.iter().cloned()).collect()
     }
 }
 
-#[async_trait::async_trait]
-impl Agent for OllamaAgent {
-    async fn send_message(&self, msg: String) -> Result<()> {
-        let mut history = self.conversation_history.write().await;
+/// A chat with a model served by Ollama. The whole conversation is sent with each
+/// message, so it can be replaced to branch it.
+///
+/// Requests run one at a time on a thread of their own. A reply to a conversation
+/// replaced while it was on its way is dropped.
+pub struct OllamaChat {
+    conversation: Arc<Mutex<Conversation>>,
+    /// Asks the thread for a reply to the conversation of that generation
+    requests: mpsc::Sender<u64>,
+    output: mpsc::Receiver<Output>,
+}
 
-        history.push(OllamaMessage {
-            role: "user".to_string(),
-            content: msg,
+impl OllamaChat {
+    /// Start the request thread, repainting `ctx` when a reply comes in
+    pub fn start(config: OllamaConfig, context: &AgentContext, ctx: egui::Context) -> Self {
+        let conversation =
+            Arc::new(Mutex::new(Conversation { system: context.system_message(), ..Conversation::default() }));
+        let (requests, pending) = mpsc::channel::<u64>();
+        let (tx, output) = mpsc::channel();
+
+        let shared = Arc::clone(&conversation);
+        std::thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    log::error!("Failed to start Ollama runtime: {}", e);
+                    return;
+                }
+            };
+            let client = reqwest::Client::new();
+            let status = |state, detail| Output::Frame(Frame::Status { state, detail });
+
+            for generation in pending {
+                let messages = match shared.lock() {
+                    Ok(conversation) if conversation.generation == generation => conversation.request_messages(),
+                    _ => continue,
+                };
+                if tx.send(status(AgentState::Busy, None)).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+
+                let outputs = match runtime.block_on(chat(&client, &config, messages)) {
+                    Ok(reply) => {
+                        let text = reply.content.clone();
+                        match shared.lock() {
+                            Ok(mut conversation) if conversation.generation == generation => {
+                                conversation.messages.push(reply);
+                                vec![Output::Frame(Frame::Message { text }), status(AgentState::Idle, None)]
+                            }
+                            _ => vec![status(AgentState::Idle, None)],
+                        }
+                    }
+                    Err(e) => vec![status(AgentState::Error, Some(format!("{:#}", e)))],
+                };
+                for output in outputs {
+                    if tx.send(output).is_err() {
+                        return;
+                    }
+                }
+                ctx.request_repaint();
+            }
         });
 
-        let request = OllamaChatRequest {
-            model: self.ollama_config.model.clone(),
-            messages: history.clone(),
-            stream: false,
-        };
+        Self { conversation, requests, output }
+    }
+}
 
-        let url = format!("{}/api/chat", self.ollama_config.host);
-        let response = self.client.post(&url).json(&request).send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            return Err(anyhow::anyhow!(
-                "Ollama API error {}: {}",
-                status,
-                error_text
-            ));
-        }
+async fn chat(client: &reqwest::Client, config: &OllamaConfig, messages: Vec<OllamaMessage>) -> Result<OllamaMessage> {
+    let request = OllamaChatRequest { model: &config.model, messages, stream: false };
+    let url = format!("{}/api/chat", config.host.trim_end_matches('/'));
+    let response = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach Ollama at {}", config.host))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        anyhow::bail!("Ollama API error {}: {}", status, error_text);
+    }
 
-        let chat_response: OllamaChatResponse = response.json().await?;
-        history.push(chat_response.message);
+    let chat_response: OllamaChatResponse = response.json().await?;
+    Ok(chat_response.message)
+}
 
-        Ok(())
+impl RunningAgent for OllamaChat {
+    fn send_message(&self, text: &str) -> Result<()> {
+        let mut conversation = self.conversation.lock().map_err(|_| anyhow::anyhow!("Ollama conversation poisoned"))?;
+        conversation.messages.push(OllamaMessage { role: "user".to_string(), content: text.to_string() });
+        self.requests.send(conversation.generation).context("Ollama request thread has stopped")
     }
 
-    fn get_status(&self) -> AgentStatus {
-        // This is synchronous, so we can't await
-        // In a real implementation, we'd need a different approach
-        AgentStatus::Connected
+    fn take_output(&self) -> Vec<Output> {
+        self.output.try_iter().collect()
     }
 
-    fn get_id(&self) -> AgentId {
-        self.config.id
+    fn exited(&mut self) -> Option<String> {
+        None
     }
 
-    fn get_config(&self) -> &AgentConfig {
-        &self.config
+    fn pid(&self) -> Option<u32> {
+        None
     }
 
-    async fn connect(&mut self) -> Result<()> {
-        let mut status = self.status.write().await;
-        *status = AgentStatus::Connecting;
+    fn can_branch(&self) -> bool {
+        true
+    }
 
-        // Test connection by listing models
-        let url = format!("{}/api/tags", self.ollama_config.host);
-        let response = self.client.get(&url).send().await;
+    fn set_history(&self, history: Vec<Turn>) -> Result<()> {
+        let mut conversation = self.conversation.lock().map_err(|_| anyhow::anyhow!("Ollama conversation poisoned"))?;
+        conversation.messages = history.into_iter().map(OllamaMessage::from).collect();
+        conversation.generation += 1;
+        Ok(())
+    }
 
-        match response {
-            Ok(resp) if resp.status().is_success() => {
-                *status = AgentStatus::Connected;
-                Ok(())
-            }
-            Ok(resp) => {
-                let error = format!("Ollama connection failed: {}", resp.status());
-                *status = AgentStatus::Error(error.clone());
-                Err(anyhow::anyhow!(error))
-            }
-            Err(e) => {
-                let error = format!("Failed to connect to Ollama: {}", e);
-                *status = AgentStatus::Error(error.clone());
-                Err(anyhow::anyhow!(error))
-            }
+    fn set_context(&self, context: &AgentContext) {
+        if let Ok(mut conversation) = self.conversation.lock() {
+            conversation.system = context.system_message();
         }
     }
+}
 
-    async fn disconnect(&mut self) -> Result<()> {
-        let mut status = self.status.write().await;
-        *status = AgentStatus::Disconnected;
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_messages() {
+        // Nothing is sent, so nothing needs to listen there
+        let config = OllamaConfig { host: "http://127.0.0.1:9".to_string(), model: "test".to_string() };
+        let mut context = AgentContext::default();
+        context.remember("The user is called Sam");
+        let chat = OllamaChat::start(config, &context, egui::Context::default());
+        chat.set_history(vec![
+            Turn { role: Role::User, text: "hi".to_string() },
+            Turn { role: Role::Assistant, text: "hello".to_string() },
+        ])
+        .unwrap();
+
+        let conversation = chat.conversation.lock().unwrap();
+        assert_eq!(conversation.generation, 1);
+        let messages = conversation.request_messages();
+        let roles: Vec<&str> = messages.iter().map(|message| message.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant"]);
+        assert!(messages[0].content.ends_with("- The user is called Sam"));
     }
 }
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for an agent run as a subprocess speaking the frame protocol in `toolcall::protocol`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessAgentConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Directory to run in, usually the agent's git worktree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+}
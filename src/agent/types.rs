@@ -1,3 +1,5 @@
+use super::context::AgentContext;
+use crate::sandbox::SandboxConfig;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -27,12 +29,21 @@ impl fmt::Display for AgentId {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AgentType {
     Ollama,
+    /// A local program speaking the agent frame protocol over stdin/stdout
+    Process,
+    /// Claude Code CLI driven in a terminal
+    ClaudeCode,
+    /// Codex CLI driven in a terminal
+    Codex,
 }
 
 impl fmt::Display for AgentType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AgentType::Ollama => write!(f, "Ollama"),
+            AgentType::Process => write!(f, "Process"),
+            AgentType::ClaudeCode => write!(f, "Claude Code"),
+            AgentType::Codex => write!(f, "Codex"),
         }
     }
 }
@@ -52,6 +63,12 @@ pub struct AgentConfig {
     pub name: String,
     pub agent_type: AgentType,
     pub config_data: serde_json::Value,
+    /// Applies to agents the dashboard starts as a local process
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    /// System prompt and memory
+    #[serde(default)]
+    pub context: AgentContext,
 }
 
 #[async_trait::async_trait]
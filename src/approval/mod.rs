@@ -0,0 +1,392 @@
+use crate::agent::AgentId;
+use crate::toolcall::types::{ToolcallRequest, ToolcallResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Punctuation that means nothing to `sh -c` or `cmd /C` outside of quoting
+const PLAIN_PUNCTUATION: &str = "-_./:=,+@'\"";
+
+/// Whether `command` is only words and quotes. Anything else may chain, redirect,
+/// expand or escape (`&`, `;`, `|`, `$`, `%`, `^`, `\`, line breaks, ...), so an
+/// allowlist match never covers it.
+fn is_plain(command: &str) -> bool {
+    command
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == ' ' || c == '\t' || PLAIN_PUNCTUATION.contains(c))
+}
+
+/// How an agent's shell commands are approved
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ApprovalPolicy {
+    /// Ask for every command
+    #[default]
+    AlwaysAsk,
+    /// Run commands matching a pattern, ask for the rest. `*` matches any text.
+    Allowlist { patterns: Vec<String> },
+    /// Run everything without asking
+    FullAuto,
+}
+
+impl ApprovalPolicy {
+    /// Whether `command` may run without asking
+    pub fn allows(&self, command: &str) -> bool {
+        match self {
+            Self::AlwaysAsk => false,
+            Self::FullAuto => true,
+            Self::Allowlist { patterns } => {
+                let command = command.trim();
+                is_plain(command) && patterns.iter().any(|pattern| glob_match(pattern.trim(), command))
+            }
+        }
+    }
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Approval settings, stored in the app config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalConfig {
+    /// Policy per agent; agents without one always ask
+    #[serde(default)]
+    pub policies: HashMap<AgentId, ApprovalPolicy>,
+    /// Unanswered prompts are denied after this long
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    60
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            policies: HashMap::new(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+}
+
+impl ApprovalConfig {
+    pub fn policy(&self, agent: &AgentId) -> ApprovalPolicy {
+        self.policies.get(agent).cloned().unwrap_or_default()
+    }
+}
+
+/// How a command was decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    /// Allowed by the agent's policy
+    AutoApproved,
+    Approved,
+    Denied,
+    /// Nobody answered in time
+    TimedOut,
+}
+
+impl Decision {
+    pub fn allowed(self) -> bool {
+        matches!(self, Self::AutoApproved | Self::Approved)
+    }
+}
+
+/// One line of the approval audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub agent: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    pub decision: Decision,
+}
+
+/// A command waiting for the user's answer
+pub struct PendingApproval {
+    pub id: u64,
+    pub agent: AgentId,
+    pub agent_name: String,
+    pub command: String,
+    pub cwd: Option<String>,
+    pub requested_at: Instant,
+    pub timeout: Duration,
+    answer: mpsc::Sender<bool>,
+}
+
+impl PendingApproval {
+    /// Time left before the command is denied
+    pub fn remaining(&self) -> Duration {
+        self.timeout.saturating_sub(self.requested_at.elapsed())
+    }
+}
+
+/// Decides whether agents may run shell commands, asking the user when their policy says so.
+///
+/// Tool calls run on agent reader threads, which block in `request` until the dashboard
+/// answers through `answer` or the timeout passes.
+pub struct ApprovalGate {
+    config: Mutex<ApprovalConfig>,
+    pending: Mutex<Vec<PendingApproval>>,
+    next_id: Mutex<u64>,
+    log_file: Option<PathBuf>,
+    ctx: Option<egui::Context>,
+}
+
+impl ApprovalGate {
+    /// Gate writing its audit log to `approvals.log` in the config directory
+    pub fn new(config: ApprovalConfig, ctx: egui::Context) -> Arc<Self> {
+        let log_file = crate::config::AppConfig::config_dir().ok().map(|dir| dir.join("approvals.log"));
+        Arc::new(Self::with_log(config, log_file, Some(ctx)))
+    }
+
+    fn with_log(config: ApprovalConfig, log_file: Option<PathBuf>, ctx: Option<egui::Context>) -> Self {
+        Self {
+            config: Mutex::new(config),
+            pending: Mutex::new(Vec::new()),
+            next_id: Mutex::new(1),
+            log_file,
+            ctx,
+        }
+    }
+
+    /// Apply changed policies or timeout
+    pub fn set_config(&self, config: ApprovalConfig) {
+        if let Ok(mut current) = self.config.lock() {
+            *current = config;
+        }
+    }
+
+    /// Decide on a command, blocking until the user answers if needed
+    pub fn request(&self, agent: AgentId, agent_name: &str, command: &str, cwd: Option<&str>) -> Decision {
+        let (policy, timeout) = match self.config.lock() {
+            Ok(config) => (config.policy(&agent), Duration::from_secs(config.timeout_secs)),
+            Err(_) => (ApprovalPolicy::AlwaysAsk, Duration::ZERO),
+        };
+
+        let decision = if policy.allows(command) {
+            Decision::AutoApproved
+        } else {
+            let (tx, rx) = mpsc::channel();
+            let id = {
+                let mut next_id = self.next_id.lock().unwrap_or_else(|e| e.into_inner());
+                *next_id += 1;
+                *next_id - 1
+            };
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.push(PendingApproval {
+                    id,
+                    agent,
+                    agent_name: agent_name.to_string(),
+                    command: command.to_string(),
+                    cwd: cwd.map(str::to_string),
+                    requested_at: Instant::now(),
+                    timeout,
+                    answer: tx,
+                });
+            }
+            if let Some(ctx) = &self.ctx {
+                ctx.request_repaint();
+            }
+
+            let decision = match rx.recv_timeout(timeout) {
+                Ok(true) => Decision::Approved,
+                Ok(false) => Decision::Denied,
+                Err(_) => Decision::TimedOut,
+            };
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.retain(|p| p.id != id);
+            }
+            if let Some(ctx) = &self.ctx {
+                ctx.request_repaint();
+            }
+            decision
+        };
+
+        self.audit(AuditEntry {
+            at: Utc::now(),
+            agent: agent_name.to_string(),
+            command: command.to_string(),
+            cwd: cwd.map(str::to_string),
+            decision,
+        });
+        decision
+    }
+
+    /// Run `f` on the oldest waiting command, if any
+    pub fn with_oldest<R>(&self, f: impl FnOnce(&PendingApproval) -> R) -> Option<R> {
+        let pending = self.pending.lock().ok()?;
+        pending.first().map(f)
+    }
+
+    /// Number of commands waiting for an answer
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().map(|p| p.len()).unwrap_or(0)
+    }
+
+    /// Answer a waiting command
+    pub fn answer(&self, id: u64, approve: bool) {
+        if let Ok(mut pending) = self.pending.lock() {
+            if let Some(index) = pending.iter().position(|p| p.id == id) {
+                let _ = pending.remove(index).answer.send(approve);
+            }
+        }
+    }
+
+    fn audit(&self, entry: AuditEntry) {
+        log::info!("Shell command from {} {:?}: {}", entry.agent, entry.decision, entry.command);
+
+        let Some(path) = &self.log_file else {
+            return;
+        };
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", serde_json::to_string(&entry).unwrap_or_default()));
+        if let Err(e) = result {
+            log::error!("Failed to write approval log: {}", e);
+        }
+    }
+
+    /// The most recent audit log entries, newest first
+    pub fn recent_entries(&self, limit: usize) -> Vec<AuditEntry> {
+        let Some(contents) = self.log_file.as_ref().and_then(|path| std::fs::read_to_string(path).ok()) else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .take(limit)
+            .collect()
+    }
+}
+
+/// Approval checks for one agent's tool calls
+#[derive(Clone)]
+pub struct AgentApproval {
+    pub gate: Arc<ApprovalGate>,
+    pub agent: AgentId,
+    pub agent_name: String,
+    /// Directory the agent runs in, shown when a command doesn't name its own
+    pub cwd: Option<String>,
+}
+
+impl AgentApproval {
+    /// Ask about shell tool calls, returning the failed result to send back when the command may not run
+    pub fn check(&self, request: &ToolcallRequest) -> Option<ToolcallResult> {
+        if request.name != "shell" {
+            return None;
+        }
+        let command = request.parameters.get("command").and_then(|c| c.as_str()).unwrap_or_default();
+        let cwd = request
+            .parameters
+            .get("working_dir")
+            .and_then(|d| d.as_str())
+            .map(str::to_string)
+            .or_else(|| self.cwd.clone());
+
+        let error = match self.gate.request(self.agent, &self.agent_name, command, cwd.as_deref()) {
+            decision if decision.allowed() => return None,
+            Decision::TimedOut => "Command not approved in time",
+            _ => "Command denied by the user",
+        };
+        Some(ToolcallResult {
+            success: false,
+            output: String::new(),
+            error: Some(error.to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("git status", "git status"));
+        assert!(!glob_match("git status", "git status -s"));
+        assert!(glob_match("cargo *", "cargo test --workspace"));
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(glob_match("git * --oneline", "git log -5 --oneline"));
+        assert!(!glob_match("git * --oneline", "git log --stat"));
+    }
+
+    #[test]
+    fn test_policy() {
+        let allowlist = ApprovalPolicy::Allowlist { patterns: vec!["cargo *".into(), "ls".into()] };
+        assert!(allowlist.allows("cargo test"));
+        assert!(allowlist.allows("  ls "));
+        assert!(!allowlist.allows("rm -rf target"));
+        assert!(!allowlist.allows("cargo test; rm -rf ~"));
+        assert!(!allowlist.allows("cargo test $(curl evil)"));
+        assert!(allowlist.allows("cargo test -p chat --features=mcp 'a b'"));
+
+        let git = ApprovalPolicy::Allowlist { patterns: vec!["git status*".into()] };
+        assert!(git.allows("git status --short"));
+        assert!(!git.allows("git status & rm -rf ~"));
+        assert!(!git.allows("git status\r\nrm -rf ~"));
+        assert!(!git.allows("git status ^& del /q *"));
+        assert!(!git.allows("git status %PATH%"));
+        assert!(!git.allows("git status ${HOME}"));
+        assert!(!git.allows("git status \\foo"));
+
+        assert!(!ApprovalPolicy::AlwaysAsk.allows("ls"));
+        assert!(ApprovalPolicy::FullAuto.allows("rm -rf target"));
+    }
+
+    #[test]
+    fn test_request_answers_and_timeouts() {
+        let agent = AgentId::new();
+        let mut config = ApprovalConfig { timeout_secs: 5, ..Default::default() };
+        config.policies.insert(agent, ApprovalPolicy::Allowlist { patterns: vec!["ls".into()] });
+        let gate = Arc::new(ApprovalGate::with_log(config.clone(), None, None));
+
+        assert_eq!(gate.request(agent, "a", "ls", None), Decision::AutoApproved);
+
+        let asking = Arc::clone(&gate);
+        let handle = std::thread::spawn(move || asking.request(agent, "a", "make", Some("/tmp")));
+        let id = loop {
+            if let Some(id) = gate.with_oldest(|p| p.id) {
+                break id;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(gate.with_oldest(|p| p.command.clone()).as_deref(), Some("make"));
+        gate.answer(id, false);
+        assert_eq!(handle.join().unwrap(), Decision::Denied);
+        assert_eq!(gate.pending_count(), 0);
+
+        config.timeout_secs = 0;
+        gate.set_config(config);
+        assert_eq!(gate.request(agent, "a", "make", None), Decision::TimedOut);
+    }
+}
@@ -0,0 +1,512 @@
+use crate::agent::AgentId;
+use crate::sources::IncomingMessage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+fn default_prompt() -> String {
+    "You are answering {chat} for me. {sender} wrote:\n\n{text}\n\nReply with only the message to send back."
+        .to_string()
+}
+
+/// An agent answering platform chats for me. Incoming messages in the chosen chats go to
+/// the agent, and its replies wait in the outbox until I approve them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssistantConfig {
+    /// Safety switch: while off nothing is routed to the agent or sent
+    #[serde(default)]
+    pub enabled: bool,
+    /// The agent answering
+    #[serde(default)]
+    pub agent: Option<AgentId>,
+    /// Chats the agent answers, keyed by `source:chat_id` (e.g. "telegram:-100123")
+    #[serde(default)]
+    pub chats: BTreeSet<String>,
+    /// What the agent is sent; `{sender}`, `{chat}` and `{text}` are filled in
+    #[serde(default = "default_prompt")]
+    pub prompt: String,
+}
+
+impl Default for AssistantConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            agent: None,
+            chats: BTreeSet::new(),
+            prompt: default_prompt(),
+        }
+    }
+}
+
+impl AssistantConfig {
+    pub fn is_enabled(&self, chat: &str) -> bool {
+        self.chats.contains(chat)
+    }
+
+    /// Switch answering a chat on or off
+    pub fn set_enabled(&mut self, chat: &str, enabled: bool) {
+        if enabled {
+            self.chats.insert(chat.to_string());
+        } else {
+            self.chats.remove(chat);
+        }
+    }
+
+    /// Whether messages are being routed to an agent at all
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.agent.is_some() && !self.chats.is_empty()
+    }
+
+    /// Fill in the prompt for one message
+    pub fn render(&self, chat: &str, sender: &str, text: &str) -> String {
+        let sender = if sender.is_empty() { "someone" } else { sender };
+        self.prompt
+            .replace("{sender}", sender)
+            .replace("{chat}", chat)
+            .replace("{text}", text)
+    }
+}
+
+/// A message routed to the agent, waiting for its reply
+#[derive(Debug, Clone, PartialEq)]
+struct Routed {
+    /// The agent's reply starts with `[reply <marker>]`
+    marker: u64,
+    agent: AgentId,
+    chat: String,
+    message_id: Option<String>,
+    sender: String,
+    text: String,
+}
+
+/// An agent's reply waiting for approval
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutgoingReply {
+    pub id: u64,
+    pub agent: AgentId,
+    pub agent_name: String,
+    /// `source:chat_id` it goes to
+    pub chat: String,
+    /// The message it answers
+    pub reply_to: Option<String>,
+    pub sender: String,
+    pub incoming: String,
+    /// What the agent wrote
+    pub drafted: String,
+    /// What will be sent, edited in the outbox
+    pub text: String,
+}
+
+/// What happened, in the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// An incoming message was passed to the agent
+    Routed {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message_id: Option<String>,
+        sender: String,
+        text: String,
+    },
+    /// The agent's reply went to the outbox
+    Drafted { text: String },
+    /// The reply was sent on my behalf
+    Sent {
+        text: String,
+        /// ID of the sent message, as the source reported it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reply_to: Option<String>,
+        /// Changed from what the agent wrote
+        edited: bool,
+    },
+    /// Sending failed
+    Failed { text: String, error: String },
+    /// The reply was thrown away, by me or by the safety switch
+    Discarded { text: String },
+}
+
+/// One line of the assistant audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub chat: String,
+    pub agent: String,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+/// Answers whether a reply was sent, with the sent message's ID
+type Sending = mpsc::Receiver<Result<Option<String>, String>>;
+
+/// Routes messages to the assistant agent, keeps its replies until approved and sends
+/// them through the chat CLI, logging every step to `assistant.log`
+pub struct Assistant {
+    /// Messages passed to the agent, oldest first; its replies name the one they answer
+    waiting: VecDeque<Routed>,
+    /// Replies waiting for approval, oldest first
+    pub outbox: Vec<OutgoingReply>,
+    /// Replies being sent
+    sending: Vec<(OutgoingReply, Sending)>,
+    /// `source:chat_id:message_id` of messages sent for me, so they aren't answered when they come back
+    sent: HashSet<String>,
+    next_id: u64,
+    next_marker: u64,
+    log_file: Option<PathBuf>,
+}
+
+impl Default for Assistant {
+    fn default() -> Self {
+        let log_file = crate::config::AppConfig::config_dir().ok().map(|dir| dir.join("assistant.log"));
+        Self::with_log(log_file)
+    }
+}
+
+impl Assistant {
+    fn with_log(log_file: Option<PathBuf>) -> Self {
+        Self {
+            waiting: VecDeque::new(),
+            outbox: Vec::new(),
+            sending: Vec::new(),
+            sent: HashSet::new(),
+            next_id: 1,
+            next_marker: 1,
+            log_file,
+        }
+    }
+
+    /// The prompt to pass to the assistant agent for an incoming message, or None if it
+    /// isn't for the assistant. Routed messages wait for the agent's reply, which the
+    /// prompt asks to start with the message's marker.
+    pub fn route(&mut self, config: &AssistantConfig, message: &IncomingMessage) -> Option<(AgentId, String)> {
+        let agent = config.agent.filter(|_| config.enabled)?;
+        let chat = message.chat_key();
+        let text = message.body();
+        if !config.is_enabled(&chat) || text.trim().is_empty() {
+            return None;
+        }
+        let message_id = message.message["id"].as_str().map(str::to_string);
+        if message_id.as_ref().is_some_and(|id| self.sent.contains(&format!("{}:{}", chat, id))) {
+            return None;
+        }
+
+        let sender = message.sender();
+        let marker = self.next_marker;
+        self.next_marker += 1;
+        let prompt = format!(
+            "{}\n\nStart your reply with {} so it reaches the right chat.",
+            config.render(&chat, &sender, &text),
+            reply_marker(marker)
+        );
+        self.audit(
+            &chat,
+            &agent.to_string(),
+            AuditEvent::Routed { message_id: message_id.clone(), sender: sender.clone(), text: text.clone() },
+        );
+        self.waiting.push_back(Routed { marker, agent, chat, message_id, sender, text });
+        Some((agent, prompt))
+    }
+
+    /// Take an agent's reply into the outbox if it carries the marker of a message routed
+    /// to that agent. Other output, e.g. the agent talking to me, is left alone.
+    pub fn take_reply(&mut self, agent: AgentId, agent_name: &str, text: &str) -> bool {
+        let Some((marker, text)) = parse_reply_marker(text) else {
+            return false;
+        };
+        let Some(index) = self.waiting.iter().position(|routed| routed.agent == agent && routed.marker == marker) else {
+            return false;
+        };
+        let routed = self.waiting.remove(index).expect("index from position");
+        self.audit(&routed.chat, agent_name, AuditEvent::Drafted { text: text.clone() });
+
+        self.outbox.push(OutgoingReply {
+            id: self.next_id,
+            agent,
+            agent_name: agent_name.to_string(),
+            chat: routed.chat,
+            reply_to: routed.message_id,
+            sender: routed.sender,
+            incoming: routed.text,
+            drafted: text.clone(),
+            text,
+        });
+        self.next_id += 1;
+        true
+    }
+
+    /// Send an approved reply. Refused while the safety switch is off.
+    pub fn approve(&mut self, config: &AssistantConfig, id: u64) -> Result<(), String> {
+        if !config.enabled {
+            return Err("The assistant is switched off".to_string());
+        }
+        let index = self.outbox.iter().position(|reply| reply.id == id).ok_or("No such reply")?;
+        if self.outbox[index].text.trim().is_empty() {
+            return Err("The reply is empty".to_string());
+        }
+
+        let reply = self.outbox.remove(index);
+        let result = send(&reply.chat, reply.text.trim(), reply.reply_to.as_deref());
+        self.sending.push((reply, result));
+        Ok(())
+    }
+
+    /// Throw away a reply
+    pub fn discard(&mut self, id: u64) {
+        if let Some(index) = self.outbox.iter().position(|reply| reply.id == id) {
+            let reply = self.outbox.remove(index);
+            self.audit(&reply.chat, &reply.agent_name, AuditEvent::Discarded { text: reply.text });
+        }
+    }
+
+    /// Safety switch off: forget routed messages and throw away every reply not sent yet
+    pub fn stop(&mut self) {
+        self.waiting.clear();
+        for reply in std::mem::take(&mut self.outbox) {
+            self.audit(&reply.chat, &reply.agent_name, AuditEvent::Discarded { text: reply.text });
+        }
+    }
+
+    /// Replies whose sending finished since the last call, with the error if it failed
+    pub fn poll(&mut self) -> Vec<(OutgoingReply, Result<(), String>)> {
+        let mut finished = Vec::new();
+        let mut still_sending = Vec::new();
+        for (reply, result) in std::mem::take(&mut self.sending) {
+            match result.try_recv() {
+                Ok(outcome) => finished.push((reply, outcome)),
+                Err(mpsc::TryRecvError::Empty) => still_sending.push((reply, result)),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished.push((reply, Err("The chat CLI stopped before answering".to_string())))
+                }
+            }
+        }
+        self.sending = still_sending;
+
+        finished
+            .into_iter()
+            .map(|(reply, outcome)| {
+                let event = match &outcome {
+                    Ok(message_id) => {
+                        if let Some(id) = message_id {
+                            self.sent.insert(format!("{}:{}", reply.chat, id));
+                        }
+                        AuditEvent::Sent {
+                            text: reply.text.clone(),
+                            message_id: message_id.clone(),
+                            reply_to: reply.reply_to.clone(),
+                            edited: reply.text.trim() != reply.drafted.trim(),
+                        }
+                    }
+                    Err(error) => AuditEvent::Failed { text: reply.text.clone(), error: error.clone() },
+                };
+                self.audit(&reply.chat, &reply.agent_name, event);
+                (reply, outcome.map(|_| ()))
+            })
+            .collect()
+    }
+
+    /// Whether replies are being sent, so the UI keeps checking
+    pub fn is_sending(&self) -> bool {
+        !self.sending.is_empty()
+    }
+
+    fn audit(&self, chat: &str, agent: &str, event: AuditEvent) {
+        let entry = AuditEntry { at: Utc::now(), chat: chat.to_string(), agent: agent.to_string(), event };
+        log::info!("Assistant in {}: {:?}", entry.chat, entry.event);
+
+        let Some(path) = &self.log_file else {
+            return;
+        };
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", serde_json::to_string(&entry).unwrap_or_default()));
+        if let Err(e) = result {
+            log::error!("Failed to write assistant log: {}", e);
+        }
+    }
+
+    /// The most recent audit log entries, newest first
+    pub fn recent_entries(&self, limit: usize) -> Vec<AuditEntry> {
+        let Some(contents) = self.log_file.as_ref().and_then(|path| std::fs::read_to_string(path).ok()) else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .take(limit)
+            .collect()
+    }
+}
+
+/// What a reply to routed message `marker` starts with
+fn reply_marker(marker: u64) -> String {
+    format!("[reply {}]", marker)
+}
+
+/// The marker a reply carries and the reply without it
+fn parse_reply_marker(text: &str) -> Option<(u64, String)> {
+    let start = text.find("[reply ")?;
+    let rest = &text[start + "[reply ".len()..];
+    let end = rest.find(']')?;
+    let marker = rest[..end].trim().parse().ok()?;
+    let reply = format!("{}{}", &text[..start], &rest[end + 1..]);
+    Some((marker, reply.trim().to_string()))
+}
+
+/// Send a message through `chat send` in the background, returning the sent message's ID
+fn send(chat: &str, text: &str, reply_to: Option<&str>) -> Sending {
+    let mut args = vec!["send".to_string(), chat.to_string(), "-".to_string(), "--format".to_string(), "json".to_string()];
+    if let Some(id) = reply_to {
+        args.extend(["--reply-to".to_string(), id.to_string()]);
+    }
+    let text = text.to_string();
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let result = Command::new(crate::config::paths::helper("chat"))
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                // The text goes through stdin so it is never taken for an option
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(text.as_bytes())?;
+                }
+                child.wait_with_output()
+            })
+            .map_err(|e| format!("Failed to run chat CLI: {}", e))
+            .and_then(|output| {
+                if output.status.success() {
+                    let sent: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_default();
+                    Ok(sent["id"].as_str().map(str::to_string))
+                } else {
+                    Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+                }
+            });
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn incoming(chat_id: &str, id: &str, text: &str) -> IncomingMessage {
+        serde_json::from_value(serde_json::json!({
+            "type": "message",
+            "schema_version": 1,
+            "source": "telegram",
+            "message": {
+                "id": id,
+                "chat_id": chat_id,
+                "sender": { "id": "7", "username": "alice", "display_name": "Alice" },
+                "content": { "Text": text }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn config(agent: AgentId) -> AssistantConfig {
+        let mut config = AssistantConfig { enabled: true, agent: Some(agent), ..Default::default() };
+        config.set_enabled("telegram:1", true);
+        config
+    }
+
+    #[test]
+    fn test_route_only_enabled_chats() {
+        let agent = AgentId::new();
+        let mut config = config(agent);
+        let mut assistant = Assistant::with_log(None);
+
+        let (to, prompt) = assistant.route(&config, &incoming("1", "10", "Lunch?")).unwrap();
+        assert_eq!(to, agent);
+        assert!(prompt.contains("Alice wrote:\n\nLunch?"));
+        assert!(assistant.route(&config, &incoming("2", "11", "Hi")).is_none());
+        assert!(assistant.route(&config, &incoming("1", "12", "  ")).is_none());
+
+        // The safety switch stops routing
+        config.enabled = false;
+        assert!(assistant.route(&config, &incoming("1", "13", "Still there?")).is_none());
+    }
+
+    #[test]
+    fn test_replies_wait_for_approval() {
+        let agent = AgentId::new();
+        let config = config(agent);
+        let mut assistant = Assistant::with_log(None);
+
+        assert!(!assistant.take_reply(agent, "Helper", "[reply 1] Unprompted"));
+        let (_, prompt) = assistant.route(&config, &incoming("1", "10", "Lunch?")).unwrap();
+        assert!(prompt.ends_with("Start your reply with [reply 1] so it reaches the right chat."));
+        assert!(!assistant.take_reply(AgentId::new(), "Other", "[reply 1] Not for you"));
+        assert!(!assistant.take_reply(agent, "Helper", "Thinking about it"));
+        assert!(assistant.take_reply(agent, "Helper", "[reply 1] Sure, 12:30? "));
+
+        let reply = &assistant.outbox[0];
+        assert_eq!(reply.chat, "telegram:1");
+        assert_eq!(reply.reply_to.as_deref(), Some("10"));
+        assert_eq!(reply.text, "Sure, 12:30?");
+
+        let id = reply.id;
+        let off = AssistantConfig { enabled: false, ..config };
+        assert!(assistant.approve(&off, id).is_err());
+        assistant.discard(id);
+        assert!(assistant.outbox.is_empty());
+    }
+
+    #[test]
+    fn test_stop_drops_pending_replies() {
+        let agent = AgentId::new();
+        let config = config(agent);
+        let mut assistant = Assistant::with_log(None);
+
+        assistant.route(&config, &incoming("1", "10", "One")).unwrap();
+        assistant.route(&config, &incoming("1", "11", "Two")).unwrap();
+        assert!(assistant.take_reply(agent, "Helper", "[reply 1] Answer one"));
+        assistant.stop();
+
+        assert!(assistant.outbox.is_empty());
+        assert!(!assistant.take_reply(agent, "Helper", "[reply 2] Answer two"));
+    }
+
+    #[test]
+    fn test_replies_matched_by_marker() {
+        let agent = AgentId::new();
+        let config = config(agent);
+        let mut assistant = Assistant::with_log(None);
+
+        assistant.route(&config, &incoming("1", "10", "One")).unwrap();
+        assistant.route(&config, &incoming("1", "11", "Two")).unwrap();
+        // Answered out of order, each reply still goes to its own message
+        assert!(assistant.take_reply(agent, "Helper", "[reply 2] Answer two"));
+        assert!(assistant.take_reply(agent, "Helper", "Sure. [reply 1]"));
+        assert!(!assistant.take_reply(agent, "Helper", "[reply 1] Again"));
+
+        let answers: Vec<_> = assistant.outbox.iter().map(|reply| (reply.reply_to.as_deref(), reply.text.as_str())).collect();
+        assert_eq!(answers, [(Some("11"), "Answer two"), (Some("10"), "Sure.")]);
+    }
+
+    #[test]
+    fn test_audit_entry_format() {
+        let entry = AuditEntry {
+            at: Utc::now(),
+            chat: "telegram:1".to_string(),
+            agent: "Helper".to_string(),
+            event: AuditEvent::Sent { text: "Hi".to_string(), message_id: Some("99".to_string()), reply_to: None, edited: true },
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["event"], "sent");
+        assert_eq!(json["message_id"], "99");
+        assert_eq!(serde_json::from_value::<AuditEntry>(json).unwrap(), entry);
+    }
+}
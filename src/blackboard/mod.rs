@@ -0,0 +1,163 @@
+//! A key-value board the agents share, so what one agent finds out (a list of failing
+//! tests, say) is there for the others to read. Agents use it through the `blackboard`
+//! tool call; the Blackboard window shows it. Keys are grouped in namespaces, and an
+//! entry may be given a time to live, after which it's dropped. Nothing is kept across
+//! restarts.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Namespace of keys written without one
+pub const DEFAULT_NAMESPACE: &str = "shared";
+/// Largest value accepted, as JSON
+pub const MAX_VALUE_BYTES: usize = 64 * 1024;
+/// Entries kept at most; writing more fails until some are deleted or expire
+pub const MAX_ENTRIES: usize = 1000;
+
+pub type SharedBlackboard = Arc<Mutex<Blackboard>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Entry {
+    pub namespace: String,
+    pub key: String,
+    pub value: Value,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Entry {
+    fn expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Blackboard {
+    entries: BTreeMap<(String, String), Entry>,
+}
+
+impl Blackboard {
+    pub fn shared() -> SharedBlackboard {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    /// Write `value` under `key`, replacing what was there. With `ttl` the entry is
+    /// dropped that long from now.
+    pub fn set(&mut self, namespace: &str, key: &str, value: Value, ttl: Option<Duration>, now: DateTime<Utc>) -> Result<()> {
+        let (namespace, key) = (namespace.trim(), key.trim());
+        if namespace.is_empty() || key.is_empty() {
+            anyhow::bail!("Namespace and key can't be empty");
+        }
+        let size = serde_json::to_string(&value)?.len();
+        if size > MAX_VALUE_BYTES {
+            anyhow::bail!("Value is {} bytes, more than the {} allowed", size, MAX_VALUE_BYTES);
+        }
+        self.purge_expired(now);
+        let id = (namespace.to_string(), key.to_string());
+        if !self.entries.contains_key(&id) && self.entries.len() >= MAX_ENTRIES {
+            anyhow::bail!("The blackboard is full ({} entries)", MAX_ENTRIES);
+        }
+
+        // A TTL too long to count in is as good as none
+        let expires_at = ttl
+            .and_then(|ttl| chrono::Duration::from_std(ttl).ok())
+            .and_then(|ttl| now.checked_add_signed(ttl));
+        let entry = Entry { namespace: id.0.clone(), key: id.1.clone(), value, updated_at: now, expires_at };
+        self.entries.insert(id, entry);
+        Ok(())
+    }
+
+    pub fn get(&self, namespace: &str, key: &str, now: DateTime<Utc>) -> Option<&Entry> {
+        self.entries
+            .get(&(namespace.trim().to_string(), key.trim().to_string()))
+            .filter(|entry| !entry.expired(now))
+    }
+
+    /// Delete an entry, returning whether there was one
+    pub fn remove(&mut self, namespace: &str, key: &str) -> bool {
+        self.entries.remove(&(namespace.trim().to_string(), key.trim().to_string())).is_some()
+    }
+
+    /// Live entries, by namespace and key, in `namespace` if given and with keys starting with `prefix`
+    pub fn list(&self, namespace: Option<&str>, prefix: &str, now: DateTime<Utc>) -> Vec<&Entry> {
+        self.entries
+            .values()
+            .filter(|entry| namespace.is_none_or(|namespace| entry.namespace == namespace.trim()))
+            .filter(|entry| entry.key.starts_with(prefix) && !entry.expired(now))
+            .collect()
+    }
+
+    /// Namespaces with live entries, sorted
+    pub fn namespaces(&self, now: DateTime<Utc>) -> Vec<&str> {
+        let mut namespaces: Vec<&str> =
+            self.entries.values().filter(|entry| !entry.expired(now)).map(|entry| entry.namespace.as_str()).collect();
+        namespaces.dedup();
+        namespaces
+    }
+
+    /// Delete every entry in `namespace`, or every entry at all
+    pub fn clear(&mut self, namespace: Option<&str>) {
+        self.entries.retain(|(entry_namespace, _), _| namespace.is_some_and(|namespace| *entry_namespace != namespace));
+    }
+
+    pub fn purge_expired(&mut self, now: DateTime<Utc>) {
+        self.entries.retain(|_, entry| !entry.expired(now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_namespaces_and_ttl() {
+        let now = Utc::now();
+        let mut board = Blackboard::default();
+        board.set("ci", "failing_tests", json!(["parser::nested", "upload::retry"]), None, now).unwrap();
+        board.set("ci", "build", json!("green"), Some(Duration::from_secs(60)), now).unwrap();
+        board.set(DEFAULT_NAMESPACE, "build", json!("other"), None, now).unwrap();
+
+        assert_eq!(board.get("ci", "build", now).unwrap().value, json!("green"));
+        assert_eq!(board.get("shared", "build", now).unwrap().value, json!("other"));
+        assert_eq!(board.list(Some("ci"), "", now).len(), 2);
+        assert_eq!(board.list(None, "fail", now).len(), 1);
+        assert_eq!(board.namespaces(now), ["ci", "shared"]);
+
+        // Expired entries are gone before they're purged
+        let later = now + chrono::Duration::seconds(61);
+        assert!(board.get("ci", "build", later).is_none());
+        assert_eq!(board.list(Some("ci"), "", later).len(), 1);
+        board.purge_expired(later);
+        assert!(board.get("ci", "build", now).is_none());
+
+        assert!(board.remove("ci", "failing_tests"));
+        assert!(!board.remove("ci", "failing_tests"));
+        board.clear(None);
+        assert!(board.list(None, "", now).is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let now = Utc::now();
+        let mut board = Blackboard::default();
+        assert!(board.set(" ", "key", json!(1), None, now).is_err());
+        assert!(board.set("ns", "", json!(1), None, now).is_err());
+        assert!(board.set("ns", "big", json!("x".repeat(MAX_VALUE_BYTES)), None, now).is_err());
+
+        for n in 0..MAX_ENTRIES {
+            board.set("ns", &n.to_string(), json!(n), None, now).unwrap();
+        }
+        assert!(board.set("ns", "one more", json!(0), None, now).is_err());
+        // Replacing an entry still works when full
+        board.set("ns", "0", json!("replaced"), None, now).unwrap();
+        board.clear(Some("ns"));
+        assert!(board.list(None, "", now).is_empty());
+    }
+}
@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+/// Clipboard watcher settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardConfig {
+    /// Offer actions for new clipboard text
+    pub enabled: bool,
+    /// How often the clipboard is checked
+    pub poll_interval_ms: u64,
+    /// Longer clipboard text is ignored
+    pub max_length: usize,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_ms: 1000,
+            max_length: 20_000,
+        }
+    }
+}
+
+/// What the clipboard text looks like
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipKind {
+    Url,
+    Code,
+    Text,
+}
+
+/// Where clipboard text is best sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipTarget {
+    /// Ask the selected agent about it
+    Agent,
+    /// Look for it in chat history
+    ChatSearch,
+}
+
+/// A piece of text copied to the clipboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip {
+    pub text: String,
+    pub kind: ClipKind,
+}
+
+impl Clip {
+    pub fn new(text: String) -> Self {
+        let kind = detect(&text);
+        Self { text, kind }
+    }
+
+    /// Links were probably shared in a chat; code and longer text are questions for an agent
+    pub fn suggested_target(&self) -> ClipTarget {
+        match self.kind {
+            ClipKind::Url => ClipTarget::ChatSearch,
+            ClipKind::Code => ClipTarget::Agent,
+            ClipKind::Text if self.text.trim().lines().count() == 1 && self.text.trim().len() <= 80 => {
+                ClipTarget::ChatSearch
+            }
+            ClipKind::Text => ClipTarget::Agent,
+        }
+    }
+
+    /// Message for an agent, with code fenced
+    pub fn agent_message(&self) -> String {
+        match self.kind {
+            ClipKind::Code if !self.text.trim_start().starts_with("```") => {
+                format!("```\n{}\n```", self.text.trim_end())
+            }
+            _ => self.text.trim().to_string(),
+        }
+    }
+
+    /// Chat search query: the URL itself, or the first line of text
+    pub fn search_query(&self) -> String {
+        let line = self.text.trim().lines().next().unwrap_or_default().trim();
+        line.chars().take(100).collect()
+    }
+
+    /// Short preview for the clipboard bar
+    pub fn preview(&self) -> String {
+        let line = self.text.trim().lines().next().unwrap_or_default();
+        let mut preview: String = line.chars().take(60).collect();
+        let lines = self.text.trim().lines().count();
+        if preview.len() < line.len() || lines > 1 {
+            preview.push('…');
+        }
+        if lines > 1 {
+            preview.push_str(&format!(" ({} lines)", lines));
+        }
+        preview
+    }
+}
+
+/// Classify clipboard text as a URL, code or plain text
+pub fn detect(text: &str) -> ClipKind {
+    let trimmed = text.trim();
+
+    if !trimmed.contains(char::is_whitespace)
+        && (trimmed.starts_with("http://") || trimmed.starts_with("https://"))
+    {
+        return ClipKind::Url;
+    }
+
+    if trimmed.starts_with("```") {
+        return ClipKind::Code;
+    }
+
+    const KEYWORDS: [&str; 12] = [
+        "fn ", "let ", "const ", "def ", "class ", "import ", "function ", "#include", "pub ", "return ", "impl ", "=>",
+    ];
+    let lines: Vec<&str> = trimmed.lines().collect();
+    let code_lines = lines
+        .iter()
+        .filter(|line| {
+            let line = line.trim();
+            line.ends_with(';')
+                || line.ends_with('{')
+                || line == "}"
+                || KEYWORDS.iter().any(|keyword| line.starts_with(keyword))
+        })
+        .count();
+    let indented = lines.iter().filter(|line| line.starts_with("    ") || line.starts_with('\t')).count();
+
+    if lines.len() == 1 {
+        if code_lines == 1 && trimmed.contains(['(', '=', '{']) {
+            return ClipKind::Code;
+        }
+    } else if code_lines * 3 >= lines.len() || (code_lines > 0 && indented * 2 >= lines.len()) {
+        return ClipKind::Code;
+    }
+
+    ClipKind::Text
+}
+
+/// Polls the system clipboard in a background thread and reports new text
+pub struct ClipboardWatcher {
+    clips: mpsc::Receiver<Clip>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ClipboardWatcher {
+    /// Start watching; text already on the clipboard is not reported
+    pub fn start(config: &ClipboardConfig, ctx: egui::Context) -> anyhow::Result<Self> {
+        let mut clipboard = arboard::Clipboard::new()?;
+        let (tx, clips) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let interval = Duration::from_millis(config.poll_interval_ms.max(100));
+        let max_length = config.max_length;
+        let stopped = Arc::clone(&stop);
+
+        std::thread::spawn(move || {
+            let mut last = clipboard.get_text().ok();
+
+            while !stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(interval);
+
+                let Ok(text) = clipboard.get_text() else {
+                    continue;
+                };
+                if last.as_deref() == Some(text.as_str()) {
+                    continue;
+                }
+                last = Some(text.clone());
+
+                if text.trim().is_empty() || text.len() > max_length {
+                    continue;
+                }
+                if tx.send(Clip::new(text)).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+
+        Ok(Self { clips, stop })
+    }
+
+    /// The newest clip since the last call, if any
+    pub fn latest(&self) -> Option<Clip> {
+        self.clips.try_iter().last()
+    }
+}
+
+impl Drop for ClipboardWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_url() {
+        assert_eq!(detect("https://example.com/a?b=c\n"), ClipKind::Url);
+        assert_eq!(detect("see https://example.com"), ClipKind::Text);
+    }
+
+    #[test]
+    fn test_detect_code() {
+        assert_eq!(detect("fn main() {\n    println!(\"hi\");\n}"), ClipKind::Code);
+        assert_eq!(detect("let x = compute(1);"), ClipKind::Code);
+        assert_eq!(detect("```\nls -la\n```"), ClipKind::Code);
+        assert_eq!(detect("def f(x):\n    return x + 1"), ClipKind::Code);
+    }
+
+    #[test]
+    fn test_detect_text() {
+        assert_eq!(detect("Meeting moved to 3pm"), ClipKind::Text);
+        assert_eq!(detect("Hi all,\n\nthe release is out. Let me know how it goes.\nThanks"), ClipKind::Text);
+    }
+
+    #[test]
+    fn test_targets() {
+        assert_eq!(Clip::new("https://example.com".into()).suggested_target(), ClipTarget::ChatSearch);
+        assert_eq!(Clip::new("let x = f(1);".into()).suggested_target(), ClipTarget::Agent);
+        assert_eq!(Clip::new("Meeting moved".into()).suggested_target(), ClipTarget::ChatSearch);
+        assert_eq!(
+            Clip::new("A longer note\nspanning lines\nthat reads like prose".into()).suggested_target(),
+            ClipTarget::Agent
+        );
+        assert_eq!(Clip::new("let x = f(1);".into()).agent_message(), "```\nlet x = f(1);\n```");
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+fn default_template() -> String {
+    "Message from {sender}: {text}".to_string()
+}
+
+fn default_chat_interval_secs() -> u64 {
+    20
+}
+
+fn default_max_per_minute() -> usize {
+    6
+}
+
+/// Reading incoming platform messages aloud, for the chats that have it switched on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnounceConfig {
+    /// Chats to announce, keyed by `source:chat_id` (e.g. "telegram:-100123")
+    #[serde(default)]
+    pub chats: BTreeSet<String>,
+    /// What gets spoken; `{sender}`, `{chat}` and `{text}` are filled in
+    #[serde(default = "default_template")]
+    pub template: String,
+    /// Minimum time between two announcements from the same chat
+    #[serde(default = "default_chat_interval_secs")]
+    pub chat_interval_secs: u64,
+    /// Announcements allowed per minute across all chats
+    #[serde(default = "default_max_per_minute")]
+    pub max_per_minute: usize,
+}
+
+impl Default for AnnounceConfig {
+    fn default() -> Self {
+        Self {
+            chats: BTreeSet::new(),
+            template: default_template(),
+            chat_interval_secs: default_chat_interval_secs(),
+            max_per_minute: default_max_per_minute(),
+        }
+    }
+}
+
+impl AnnounceConfig {
+    pub fn is_enabled(&self, chat: &str) -> bool {
+        self.chats.contains(chat)
+    }
+
+    /// Switch announcements for a chat on or off
+    pub fn set_enabled(&mut self, chat: &str, enabled: bool) {
+        if enabled {
+            self.chats.insert(chat.to_string());
+        } else {
+            self.chats.remove(chat);
+        }
+    }
+
+    /// Fill in the template for one message
+    pub fn render(&self, chat: &str, sender: &str, text: &str) -> String {
+        let sender = if sender.is_empty() { "someone" } else { sender };
+        self.template
+            .replace("{sender}", sender)
+            .replace("{chat}", chat)
+            .replace("{text}", text)
+    }
+}
+
+/// Rate limits announcements so a busy group doesn't take over the speakers
+#[derive(Debug, Default)]
+pub struct Announcer {
+    last_by_chat: HashMap<String, Instant>,
+    recent: VecDeque<Instant>,
+}
+
+impl Announcer {
+    /// The text to speak for a message, or None if the chat isn't announced or
+    /// a rate limit holds it back
+    pub fn announce(
+        &mut self,
+        config: &AnnounceConfig,
+        chat: &str,
+        sender: &str,
+        text: &str,
+        now: Instant,
+    ) -> Option<String> {
+        if !config.is_enabled(chat) || text.trim().is_empty() {
+            return None;
+        }
+
+        let interval = Duration::from_secs(config.chat_interval_secs);
+        if self
+            .last_by_chat
+            .get(chat)
+            .is_some_and(|last| now.duration_since(*last) < interval)
+        {
+            return None;
+        }
+
+        while self
+            .recent
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= config.max_per_minute {
+            return None;
+        }
+
+        self.last_by_chat.insert(chat.to_string(), now);
+        self.recent.push_back(now);
+        Some(config.render(chat, sender, text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(chats: &[&str]) -> AnnounceConfig {
+        let mut config = AnnounceConfig::default();
+        for chat in chats {
+            config.set_enabled(chat, true);
+        }
+        config
+    }
+
+    #[test]
+    fn test_render() {
+        let mut config = config(&[]);
+        assert_eq!(config.render("telegram:1", "Alice", "Hi"), "Message from Alice: Hi");
+        assert_eq!(config.render("telegram:1", "", "Hi"), "Message from someone: Hi");
+
+        config.template = "{chat}. {text}".to_string();
+        assert_eq!(config.render("telegram:1", "Alice", "Hi"), "telegram:1. Hi");
+    }
+
+    #[test]
+    fn test_per_chat_interval() {
+        let config = config(&["telegram:1", "telegram:2"]);
+        let mut announcer = Announcer::default();
+        let start = Instant::now();
+
+        assert!(announcer.announce(&config, "telegram:3", "Bob", "Hi", start).is_none());
+        assert!(announcer.announce(&config, "telegram:1", "Alice", "Hi", start).is_some());
+        assert!(announcer.announce(&config, "telegram:1", "Alice", "Again", start + Duration::from_secs(5)).is_none());
+        assert!(announcer.announce(&config, "telegram:2", "Bob", "Hi", start + Duration::from_secs(5)).is_some());
+        assert!(announcer.announce(&config, "telegram:1", "Alice", "Later", start + Duration::from_secs(20)).is_some());
+    }
+
+    #[test]
+    fn test_max_per_minute() {
+        let mut config = config(&["telegram:1", "telegram:2", "telegram:3"]);
+        config.max_per_minute = 2;
+        let mut announcer = Announcer::default();
+        let start = Instant::now();
+
+        assert!(announcer.announce(&config, "telegram:1", "A", "Hi", start).is_some());
+        assert!(announcer.announce(&config, "telegram:2", "B", "Hi", start).is_some());
+        assert!(announcer.announce(&config, "telegram:3", "C", "Hi", start).is_none());
+        assert!(announcer.announce(&config, "telegram:3", "C", "Hi", start + Duration::from_secs(60)).is_some());
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Smallest and largest font scale offered
+pub const FONT_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+
+fn default_font_scale() -> f32 {
+    1.0
+}
+
+fn default_terminal_font_size() -> f32 {
+    14.0
+}
+
+/// Text size, the Term tab's font and contrast, applied as soon as they change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppearanceConfig {
+    /// Multiplies every text size in the UI
+    #[serde(default = "default_font_scale")]
+    pub font_scale: f32,
+    /// A .ttf or .otf file for the Term tab; egui's built-in monospace font when unset
+    #[serde(default)]
+    pub terminal_font: Option<PathBuf>,
+    /// Term tab text size in points, before `font_scale`
+    #[serde(default = "default_terminal_font_size")]
+    pub terminal_font_size: f32,
+    /// Black or white backgrounds, brighter text and thicker outlines
+    #[serde(default)]
+    pub high_contrast: bool,
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            font_scale: default_font_scale(),
+            terminal_font: None,
+            terminal_font_size: default_terminal_font_size(),
+            high_contrast: false,
+        }
+    }
+}
+
+impl AppearanceConfig {
+    /// `font_scale` kept within `FONT_SCALE_RANGE`, in case the file was edited by hand
+    pub fn scale(&self) -> f32 {
+        if self.font_scale.is_finite() {
+            self.font_scale.clamp(*FONT_SCALE_RANGE.start(), *FONT_SCALE_RANGE.end())
+        } else {
+            default_font_scale()
+        }
+    }
+
+    /// Term tab text size with the scale applied
+    pub fn terminal_size(&self) -> f32 {
+        self.terminal_font_size.clamp(6.0, 48.0) * self.scale()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale() {
+        let config: AppearanceConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, AppearanceConfig::default());
+        assert_eq!(config.terminal_size(), 14.0);
+
+        let config = AppearanceConfig { font_scale: 1.5, terminal_font_size: 12.0, ..config };
+        assert_eq!(config.terminal_size(), 18.0);
+        assert_eq!(AppearanceConfig { font_scale: 10.0, ..config.clone() }.scale(), 3.0);
+        assert_eq!(AppearanceConfig { font_scale: f32::NAN, ..config }.scale(), 1.0);
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::{DateTime, Datelike, Local, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A daily window during which alerts are held back.
+/// Windows whose end is before their start run past midnight (e.g. 22:00-07:00).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Days the window starts on; empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+}
+
+impl QuietHours {
+    fn applies_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// Whether `now` falls inside this window
+    pub fn contains(&self, now: DateTime<Local>) -> bool {
+        let time = now.time();
+        let today = now.weekday();
+
+        if self.start <= self.end {
+            self.applies_on(today) && time >= self.start && time < self.end
+        } else {
+            // Overnight: the late part belongs to today, the early part to yesterday's window
+            (self.applies_on(today) && time >= self.start)
+                || (self.applies_on(today.pred()) && time < self.end)
+        }
+    }
+}
+
+/// Why do-not-disturb is active
+#[derive(Debug, Clone, PartialEq)]
+pub enum DndStatus {
+    Off,
+    /// Switched on by hand from the status bar
+    Manual,
+    /// Inside quiet hours ending at the given time
+    QuietHours { until: NaiveTime },
+}
+
+impl DndStatus {
+    pub fn is_active(&self) -> bool {
+        !matches!(self, DndStatus::Off)
+    }
+}
+
+/// Do-not-disturb settings: while active, TTS auto-speak and alerts are
+/// suppressed (but still logged) unless the alert is marked critical
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DndConfig {
+    /// DND switched on regardless of quiet hours
+    #[serde(default)]
+    pub manual: bool,
+    /// Quiet hours that apply to everything
+    #[serde(default)]
+    pub quiet_hours: Vec<QuietHours>,
+    /// Extra quiet hours per chat, keyed by `source:chat_id` (e.g. "telegram:-100123")
+    #[serde(default)]
+    pub chats: BTreeMap<String, Vec<QuietHours>>,
+}
+
+impl DndConfig {
+    /// Global DND status, ignoring per-chat quiet hours
+    pub fn status(&self, now: DateTime<Local>) -> DndStatus {
+        if self.manual {
+            return DndStatus::Manual;
+        }
+
+        Self::window_status(&self.quiet_hours, now)
+    }
+
+    /// DND status for alerts about a chat
+    pub fn status_for_chat(&self, chat: Option<&str>, now: DateTime<Local>) -> DndStatus {
+        let global = self.status(now);
+        if global.is_active() {
+            return global;
+        }
+
+        chat.and_then(|chat| self.chats.get(chat))
+            .map(|windows| Self::window_status(windows, now))
+            .unwrap_or(DndStatus::Off)
+    }
+
+    /// Whether an alert should be held back; critical alerts always get through
+    pub fn suppresses(&self, chat: Option<&str>, critical: bool, now: DateTime<Local>) -> bool {
+        !critical && self.status_for_chat(chat, now).is_active()
+    }
+
+    fn window_status(windows: &[QuietHours], now: DateTime<Local>) -> DndStatus {
+        windows
+            .iter()
+            .find(|window| window.contains(now))
+            .map(|window| DndStatus::QuietHours { until: window.end })
+            .unwrap_or(DndStatus::Off)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        // 2025-01-06 is a Monday
+        Local.with_ymd_and_hms(2025, 1, day, hour, minute, 0).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn every_day(start: NaiveTime, end: NaiveTime) -> QuietHours {
+        QuietHours { start, end, days: Vec::new() }
+    }
+
+    #[test]
+    fn test_overnight_quiet_hours() {
+        let night = every_day(time(22, 0), time(7, 0));
+        assert!(night.contains(at(6, 23, 30)));
+        assert!(night.contains(at(7, 6, 59)));
+        assert!(!night.contains(at(7, 7, 0)));
+        assert!(!night.contains(at(7, 12, 0)));
+    }
+
+    #[test]
+    fn test_weekday_windows() {
+        let mut weeknights = every_day(time(22, 0), time(7, 0));
+        weeknights.days = vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+
+        // Friday night's window runs into Saturday morning
+        assert!(weeknights.contains(at(11, 6, 0)));
+        // Saturday night has no window, so neither does Sunday morning
+        assert!(!weeknights.contains(at(11, 23, 0)));
+        assert!(!weeknights.contains(at(12, 6, 0)));
+    }
+
+    #[test]
+    fn test_per_chat_and_critical() {
+        let mut dnd = DndConfig::default();
+        dnd.chats.insert("telegram:-100123".to_string(), vec![every_day(time(9, 0), time(17, 0))]);
+
+        let noon = at(8, 12, 0);
+        assert!(dnd.suppresses(Some("telegram:-100123"), false, noon));
+        assert!(!dnd.suppresses(Some("telegram:-100123"), true, noon));
+        assert!(!dnd.suppresses(Some("telegram:42"), false, noon));
+        assert_eq!(dnd.status(noon), DndStatus::Off);
+
+        dnd.manual = true;
+        assert_eq!(dnd.status(noon), DndStatus::Manual);
+        assert!(dnd.suppresses(None, false, noon));
+    }
+}
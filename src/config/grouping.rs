@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+fn default_chat_window_secs() -> u64 {
+    120
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_terminal_gap_secs() -> u64 {
+    5
+}
+
+/// How the chat log and the Term tab bundle related output into blocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupingConfig {
+    /// Messages from the same sender at most this far apart share one block with one
+    /// header; 0 shows every message on its own
+    #[serde(default = "default_chat_window_secs")]
+    pub chat_window_secs: u64,
+    /// Show blocks of several messages collapsed to their first until expanded
+    #[serde(default)]
+    pub collapse_chat_groups: bool,
+    /// Group Term tab output under the command that printed it, with a header to
+    /// collapse it by
+    #[serde(default = "default_true")]
+    pub terminal_by_command: bool,
+    /// Output that no command was entered for starts a new block after this long a
+    /// pause; 0 keeps it in one block
+    #[serde(default = "default_terminal_gap_secs")]
+    pub terminal_gap_secs: u64,
+}
+
+impl Default for GroupingConfig {
+    fn default() -> Self {
+        Self {
+            chat_window_secs: default_chat_window_secs(),
+            collapse_chat_groups: false,
+            terminal_by_command: default_true(),
+            terminal_gap_secs: default_terminal_gap_secs(),
+        }
+    }
+}
+
+impl GroupingConfig {
+    /// Longest gap within a block of chat messages, if they are grouped at all
+    pub fn chat_window(&self) -> Option<chrono::Duration> {
+        (self.chat_window_secs > 0).then(|| chrono::Duration::seconds(self.chat_window_secs.min(86_400) as i64))
+    }
+
+    /// Pause that starts a new block of command-less Term tab output
+    pub fn terminal_gap(&self) -> Option<Duration> {
+        (self.terminal_by_command && self.terminal_gap_secs > 0).then(|| Duration::from_secs(self.terminal_gap_secs))
+    }
+}
@@ -1,24 +1,83 @@
+pub mod announce;
+pub mod appearance;
+pub mod dnd;
+pub mod grouping;
+pub mod paths;
+
 use crate::agent::AgentConfig;
+use crate::approval::ApprovalConfig;
+use crate::assistant::AssistantConfig;
+use crate::clipboard::ClipboardConfig;
+use crate::crash::CrashConfig;
+use crate::mcp::McpServerConfig;
+use crate::monitor::MonitorConfig;
+use crate::scripting::ScriptLimits;
+use crate::sync_api::SyncApiConfig;
+use crate::terminal::TerminalConfig;
 use crate::tts::TTSConfig;
+use crate::voice::VoiceConfig;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub use announce::{AnnounceConfig, Announcer};
+pub use appearance::AppearanceConfig;
+pub use dnd::{DndConfig, DndStatus};
+pub use grouping::GroupingConfig;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppConfig {
     pub agents: Vec<AgentConfig>,
     #[serde(default)]
     pub tts: TTSConfig,
-}
-
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self {
-            agents: Vec::new(),
-            tts: TTSConfig::default(),
-        }
-    }
+    #[serde(default)]
+    pub dnd: DndConfig,
+    /// Run `chat pipeline run` while the dashboard is open
+    #[serde(default)]
+    pub pipelines_enabled: bool,
+    /// Per-script resource limits, by script name
+    #[serde(default)]
+    pub script_limits: BTreeMap<String, ScriptLimits>,
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+    /// Which shell commands agents may run without asking
+    #[serde(default)]
+    pub approvals: ApprovalConfig,
+    /// Chats whose incoming messages are read aloud
+    #[serde(default)]
+    pub announce: AnnounceConfig,
+    /// Agent answering chosen chats, with replies sent once approved
+    #[serde(default)]
+    pub assistant: AssistantConfig,
+    /// Chat MCP server shared by the agents the dashboard launches
+    #[serde(default)]
+    pub mcp_server: McpServerConfig,
+    /// Term tab history settings
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+    /// Spoken commands
+    #[serde(default)]
+    pub voice: VoiceConfig,
+    /// CPU and memory limits for agent and terminal processes
+    #[serde(default)]
+    pub monitor: MonitorConfig,
+    /// UI language code, e.g. "de"; the system language when unset
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Font scale, Term tab font and high contrast
+    #[serde(default)]
+    pub appearance: AppearanceConfig,
+    /// Bundling messages and Term tab output into blocks
+    #[serde(default)]
+    pub grouping: GroupingConfig,
+    /// Local crash reports, off unless turned on
+    #[serde(default)]
+    pub crash_reports: CrashConfig,
+    /// HTTP API a phone syncs cached chats through
+    #[serde(default)]
+    pub sync_api: SyncApiConfig,
 }
 
 impl AppConfig {
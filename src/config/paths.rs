@@ -0,0 +1,177 @@
+//! Where the dashboard finds its bundled resources and the helper programs it runs, so it
+//! behaves the same under `cargo run`, from a macOS .app, a Windows install or an AppImage
+
+use anyhow::{Context, Result};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use super::AppConfig;
+use crate::scripting::ScriptHost;
+use crate::tts::TTSConfig;
+
+/// Overrides where bundled resources are looked up
+pub const RESOURCES_ENV: &str = "AGENT_DASHBOARD_RESOURCES";
+
+/// Where bundles put their resources, relative to the running executable
+fn resource_candidates(exe: &Path) -> Vec<PathBuf> {
+    let Some(dir) = exe.parent() else {
+        return Vec::new();
+    };
+    vec![
+        // Agent Dashboard.app/Contents/MacOS/agent-dashboard
+        dir.join("../Resources/resources"),
+        // Windows installs keep them next to the executable
+        dir.join("resources"),
+        // AppImage and Linux packages: usr/bin/agent-dashboard
+        dir.join("../share/agent-dashboard"),
+    ]
+}
+
+/// Directory of bundled resources, falling back to the source tree's under `cargo run`
+pub fn resource_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(RESOURCES_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    let exe = std::env::current_exe().ok()?;
+    resource_candidates(&exe)
+        .into_iter()
+        .chain(std::iter::once(Path::new(env!("CARGO_MANIFEST_DIR")).join("resources")))
+        .find(|dir| dir.is_dir())
+}
+
+/// A helper program the dashboard or its agents run (`chat`, `chat-mcp-server`): the copy
+/// shipped next to the executable in a bundle, otherwise whatever is on PATH
+pub fn helper(name: &str) -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| bundled_helper(&exe, name))
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+fn bundled_helper(exe: &Path, name: &str) -> Option<PathBuf> {
+    let path = exe.parent()?.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX));
+    path.is_file().then_some(path)
+}
+
+/// Directories command-line tools usually live in. Apps started from Finder or a desktop
+/// launcher get a minimal PATH without them, and then agents and helpers aren't found.
+fn tool_dirs(home: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = home {
+        dirs.push(home.join(".local").join("bin"));
+        dirs.push(home.join(".cargo").join("bin"));
+    }
+    if cfg!(unix) {
+        dirs.extend(["/usr/local/bin", "/opt/homebrew/bin"].map(PathBuf::from));
+    }
+    dirs
+}
+
+/// `path` with the directories it doesn't have yet appended, or None if it has them all
+fn extended_path(path: &OsStr, extra: impl IntoIterator<Item = PathBuf>) -> Option<OsString> {
+    let mut dirs: Vec<PathBuf> = std::env::split_paths(path).collect();
+    let before = dirs.len();
+    for dir in extra {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    if dirs.len() == before {
+        return None;
+    }
+    std::env::join_paths(dirs).ok()
+}
+
+/// Copy the files under `from` into `to`, keeping any that already exist there.
+/// Returns how many were copied.
+fn copy_missing(from: &Path, to: &Path) -> Result<usize> {
+    let mut copied = 0;
+    for entry in std::fs::read_dir(from).with_context(|| format!("Failed to read {}", from.display()))? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            copied += copy_missing(&entry.path(), &target)?;
+        } else if !target.exists() {
+            std::fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {} to {}", entry.path().display(), target.display()))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// First-run layout of the config directory: the scripts and TTS model directories, plus
+/// the defaults bundled under `resources/config` that aren't there yet. `agents.json` is
+/// left to onboarding.
+pub fn scaffold() -> Result<()> {
+    let config_dir = AppConfig::config_dir()?;
+    std::fs::create_dir_all(ScriptHost::scripts_dir()?)?;
+    std::fs::create_dir_all(TTSConfig::default().model_directory)?;
+
+    if let Some(defaults) = resource_dir().map(|dir| dir.join("config")).filter(|dir| dir.is_dir()) {
+        let copied = copy_missing(&defaults, &config_dir)?;
+        if copied > 0 {
+            log::info!("Copied {} default files to {}", copied, config_dir.display());
+        }
+    }
+    Ok(())
+}
+
+/// Get the process ready before the UI starts. Call it before spawning threads, since it
+/// may change PATH.
+pub fn prepare() {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let extra = tool_dirs(dirs::home_dir().as_deref()).into_iter().filter(|dir| dir.is_dir());
+    if let Some(path) = extended_path(&path, extra) {
+        std::env::set_var("PATH", path);
+    }
+
+    if let Err(e) = scaffold() {
+        log::warn!("Failed to set up the config directory: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_candidates() {
+        let exe = Path::new("/Applications/Agent Dashboard.app/Contents/MacOS/agent-dashboard");
+        let candidates = resource_candidates(exe);
+        assert_eq!(
+            candidates[0],
+            Path::new("/Applications/Agent Dashboard.app/Contents/MacOS/../Resources/resources")
+        );
+        let appimage = resource_candidates(Path::new("/tmp/.mount_x/usr/bin/agent-dashboard"));
+        assert_eq!(appimage[2], Path::new("/tmp/.mount_x/usr/bin/../share/agent-dashboard"));
+        assert!(resource_candidates(Path::new("")).is_empty());
+    }
+
+    #[test]
+    fn test_extended_path() {
+        let path = std::env::join_paths(["/usr/bin", "/bin"]).unwrap();
+        let extended = extended_path(&path, [PathBuf::from("/bin"), PathBuf::from("/opt/homebrew/bin")]).unwrap();
+        let dirs: Vec<PathBuf> = std::env::split_paths(&extended).collect();
+        assert_eq!(dirs, ["/usr/bin", "/bin", "/opt/homebrew/bin"].map(PathBuf::from));
+        assert!(extended_path(&path, [PathBuf::from("/usr/bin")]).is_none());
+    }
+
+    #[test]
+    fn test_copy_missing() {
+        let root = std::env::temp_dir().join(format!("dashboard-paths-{}", uuid::Uuid::new_v4().simple()));
+        let (from, to) = (root.join("resources"), root.join("config"));
+        std::fs::create_dir_all(from.join("tts").join("models")).unwrap();
+        std::fs::write(from.join("tts").join("models").join("README.txt"), "models go here").unwrap();
+        std::fs::write(from.join("settings.json"), "{}").unwrap();
+        std::fs::create_dir_all(&to).unwrap();
+        std::fs::write(to.join("settings.json"), "{\"edited\": true}").unwrap();
+
+        assert_eq!(copy_missing(&from, &to).unwrap(), 1);
+        assert!(to.join("tts").join("models").join("README.txt").exists());
+        assert_eq!(std::fs::read_to_string(to.join("settings.json")).unwrap(), "{\"edited\": true}");
+        assert_eq!(copy_missing(&from, &to).unwrap(), 0);
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,368 @@
+//! Opt-in crash reports. A panic hook writes a bundle to the config directory with the
+//! panic, a backtrace, recent log lines and the config, all with secrets scrubbed. Nothing
+//! leaves the machine unless the user opens an issue from the prompt shown at next launch.
+
+use crate::config::AppConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// Where "Open issue" files reports
+pub const ISSUES_URL: &str = "https://github.com/k0sti/dashboard/issues/new";
+/// Log lines put in a report
+pub const MAX_LOG_LINES: usize = 500;
+/// Longest report put in an issue link; browsers and GitHub reject much longer URLs
+const MAX_ISSUE_BODY: usize = 6000;
+/// Left in a bundle once the user has dealt with it, so it isn't offered again
+const REVIEWED_MARKER: &str = "reviewed";
+
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CrashConfig {
+    /// Write a report when the dashboard panics
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Write a report on panic when reports are enabled, after the usual panic message
+pub fn install() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        match write_panic_report(info) {
+            Ok(dir) => eprintln!("Crash report saved to {}", dir.display()),
+            Err(e) => eprintln!("Failed to save a crash report: {}", e),
+        }
+    }));
+}
+
+fn write_panic_report(info: &std::panic::PanicHookInfo<'_>) -> Result<PathBuf> {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string());
+    let report = CrashReport::new(
+        message,
+        info.location().map(|location| location.to_string()),
+        std::backtrace::Backtrace::force_capture().to_string(),
+    );
+
+    let log = crate::logs::recent_text(MAX_LOG_LINES).unwrap_or_else(|| "(log unavailable)".to_string());
+    let config = AppConfig::load()
+        .and_then(|config| Ok(serde_json::to_value(config)?))
+        .unwrap_or_else(|e| serde_json::json!({ "error": e.to_string() }));
+
+    write_bundle(&reports_dir()?, &report, &log, config)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub at: DateTime<Utc>,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+impl CrashReport {
+    fn new(message: String, location: Option<String>, backtrace: String) -> Self {
+        Self {
+            at: Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+            message,
+            location,
+            backtrace,
+        }
+    }
+
+    /// The report as an issue body
+    pub fn markdown(&self) -> String {
+        format!(
+            "**Panic:** {}\n**At:** {}\n**Thread:** {}\n**Version:** {} ({} {})\n**Time:** {}\n\n<details><summary>Backtrace</summary>\n\n```\n{}\n```\n</details>\n",
+            self.message,
+            self.location.as_deref().unwrap_or("unknown"),
+            self.thread,
+            self.version,
+            self.os,
+            self.arch,
+            self.at.to_rfc3339(),
+            self.backtrace.trim_end(),
+        )
+    }
+
+    /// A link to a new issue with the report filled in, cut short if it's too long for a URL
+    pub fn issue_url(&self) -> String {
+        let mut body = self.markdown();
+        if body.len() > MAX_ISSUE_BODY {
+            let mut end = MAX_ISSUE_BODY;
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            body.truncate(end);
+            body.push_str("\n```\n</details>\n\n(Cut short; the full report is on the clipboard.)\n");
+        }
+        body.push_str("\n<!-- Drag log.txt and config.json from the report folder here to attach them -->\n");
+
+        let title = format!("Crash: {}", self.message.lines().next().unwrap_or_default());
+        reqwest::Url::parse_with_params(ISSUES_URL, [("title", title.as_str()), ("body", body.as_str())])
+            .map(String::from)
+            .unwrap_or_else(|_| ISSUES_URL.to_string())
+    }
+
+    fn scrubbed(mut self) -> Self {
+        self.message = scrub(&self.message);
+        self.location = self.location.as_deref().map(scrub);
+        self.backtrace = scrub(&self.backtrace);
+        self
+    }
+}
+
+/// A report written to disk, one directory per crash
+#[derive(Debug, Clone)]
+pub struct ReportBundle {
+    pub dir: PathBuf,
+    pub report: CrashReport,
+}
+
+impl ReportBundle {
+    /// Don't offer this report again
+    pub fn mark_reviewed(&self) -> Result<()> {
+        std::fs::write(self.dir.join(REVIEWED_MARKER), "")?;
+        Ok(())
+    }
+}
+
+/// Open a report folder in the system file manager, to attach files from it
+pub fn open_folder(dir: &Path) -> Result<()> {
+    let opener = match std::env::consts::OS {
+        "macos" => "open",
+        "windows" => "explorer",
+        _ => "xdg-open",
+    };
+    std::process::Command::new(opener)
+        .arg(dir)
+        .spawn()
+        .with_context(|| format!("Failed to run {}", opener))?;
+    Ok(())
+}
+
+pub fn reports_dir() -> Result<PathBuf> {
+    Ok(AppConfig::config_dir()?.join("crash-reports"))
+}
+
+/// Write `report.json`, `log.txt` and `config.json` to a new directory under `dir`,
+/// scrubbing them on the way
+pub fn write_bundle(dir: &Path, report: &CrashReport, log: &str, mut config: serde_json::Value) -> Result<PathBuf> {
+    let bundle = dir.join(report.at.format("%Y%m%d-%H%M%S%.3f").to_string());
+    std::fs::create_dir_all(&bundle).with_context(|| format!("Failed to create {}", bundle.display()))?;
+
+    redact_config(&mut config);
+    std::fs::write(bundle.join("report.json"), serde_json::to_vec_pretty(&report.clone().scrubbed())?)?;
+    std::fs::write(bundle.join("log.txt"), scrub(log))?;
+    std::fs::write(bundle.join("config.json"), serde_json::to_vec_pretty(&config)?)?;
+    Ok(bundle)
+}
+
+/// Reports not yet shown to the user, newest first
+pub fn pending(dir: &Path) -> Result<Vec<ReportBundle>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut bundles = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() || path.join(REVIEWED_MARKER).exists() {
+            continue;
+        }
+        let report = std::fs::read(path.join("report.json"))
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?));
+        match report {
+            Ok(report) => bundles.push(ReportBundle { dir: path, report }),
+            Err(e) => log::warn!("Skipping crash report {}: {}", path.display(), e),
+        }
+    }
+    bundles.sort_by_key(|bundle| std::cmp::Reverse(bundle.report.at));
+    Ok(bundles)
+}
+
+/// Config keys whose values are always secret
+const SECRET_KEYS: [&str; 9] =
+    ["password", "passwd", "secret", "token", "api_key", "apikey", "private_key", "authorization", "credential"];
+
+/// Redact values under secret-sounding keys, and scrub every other string
+fn redact_config(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_KEYS.iter().any(|secret| key.contains(secret)) && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_config(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_config),
+        serde_json::Value::String(text) => *text = scrub(text),
+        _ => {}
+    }
+}
+
+struct Scrubber {
+    /// `token=…`, `"password": "…"` and the like; the name is kept
+    assignment: Regex,
+    /// `Bearer …` and `Basic …` credentials
+    auth_header: Regex,
+    /// Tokens recognizable by their prefix (OpenAI, Anthropic, GitHub, Slack, AWS, Google,
+    /// GitLab) and Telegram bot tokens
+    known_token: Regex,
+    /// Long runs of letters and digits, checked for being opaque
+    long_word: Regex,
+    email: Regex,
+}
+
+fn scrubber() -> &'static Scrubber {
+    static SCRUBBER: OnceLock<Scrubber> = OnceLock::new();
+    SCRUBBER.get_or_init(|| Scrubber {
+        assignment: Regex::new(
+            r#"(?i)((?:password|passwd|secret|token|api[_-]?key|private[_-]?key|access[_-]?key|authorization)["']?\s*[:=]\s*["']?)([^\s"',;&]+)"#,
+        )
+        .unwrap(),
+        auth_header: Regex::new(r"(?i)\b(bearer|basic)\s+[A-Za-z0-9._~+/=-]{8,}").unwrap(),
+        known_token: Regex::new(
+            r"\b(?:sk-[A-Za-z0-9_-]{16,}|gh[pousr]_[A-Za-z0-9]{20,}|github_pat_[A-Za-z0-9_]{20,}|xox[abpr]-[A-Za-z0-9-]{10,}|AKIA[0-9A-Z]{16}|AIza[0-9A-Za-z_-]{35}|glpat-[0-9A-Za-z_-]{20,}|\d{8,10}:[A-Za-z0-9_-]{35})",
+        )
+        .unwrap(),
+        long_word: Regex::new(r"[A-Za-z0-9_+-]{32,}={0,2}").unwrap(),
+        email: Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap(),
+    })
+}
+
+/// Remove obvious secrets and personal details from text: credentials, API tokens, long
+/// opaque strings, email addresses, and the home directory (shown as `~`)
+pub fn scrub(text: &str) -> String {
+    let mut text = text.to_string();
+    if let Some(home) = dirs::home_dir().map(|home| home.display().to_string()).filter(|home| home.len() > 1) {
+        text = text.replace(&home, "~");
+    }
+
+    let scrubber = scrubber();
+    let text = scrubber.known_token.replace_all(&text, REDACTED);
+    let text = scrubber.auth_header.replace_all(&text, format!("$1 {}", REDACTED));
+    let text = scrubber.assignment.replace_all(&text, |captures: &regex::Captures| {
+        // Already handled as an auth header: `Authorization: Bearer [redacted]`
+        if matches!(captures[2].to_lowercase().as_str(), "bearer" | "basic") {
+            captures[0].to_string()
+        } else {
+            format!("{}{}", &captures[1], REDACTED)
+        }
+    });
+    let text = scrubber.long_word.replace_all(&text, |captures: &regex::Captures| {
+        let word = &captures[0];
+        // Identifiers are long too, but rarely mix in digits like keys and hashes do
+        let opaque = word.chars().any(|c| c.is_ascii_digit()) && word.chars().any(|c| c.is_ascii_alphabetic());
+        if opaque { REDACTED.to_string() } else { word.to_string() }
+    });
+    scrubber.email.replace_all(&text, "[email]").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrub() {
+        let log = "connecting with Authorization: Bearer abc.def.ghi123\n\
+                   OPENAI_API_KEY=sk-proj-abcdefghijklmnop1234 bot 123456789:AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw0\n\
+                   password: hunter2, mail me at jane.doe@example.com\n\
+                   hash 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08 in core::panicking::panic_fmt";
+        let scrubbed = scrub(log);
+        for secret in ["abc.def.ghi123", "sk-proj", "AAHdqTcv", "hunter2", "jane.doe", "9f86d081"] {
+            assert!(!scrubbed.contains(secret), "{} left in {}", secret, scrubbed);
+        }
+        assert!(scrubbed.contains("Bearer [redacted]"));
+        assert!(scrubbed.contains("password: [redacted]"));
+        assert!(scrubbed.contains("[email]"));
+        assert!(scrubbed.contains("core::panicking::panic_fmt"));
+
+        if let Some(home) = dirs::home_dir().filter(|home| home.as_os_str().len() > 1) {
+            let path = home.join("src").join("main.rs").display().to_string();
+            assert!(scrub(&path).starts_with('~'));
+        }
+    }
+
+    #[test]
+    fn test_redact_config() {
+        let mut config = serde_json::json!({
+            "agents": [{
+                "name": "Claude",
+                "config_data": { "env": { "ANTHROPIC_API_KEY": "anything", "LANG": "en" }, "model": "opus" }
+            }],
+            "telegram": { "bot_token": "x", "session_password": null },
+            "voice": { "hotkey": "F9" }
+        });
+        redact_config(&mut config);
+        assert_eq!(config["agents"][0]["config_data"]["env"]["ANTHROPIC_API_KEY"], REDACTED);
+        assert_eq!(config["agents"][0]["config_data"]["env"]["LANG"], "en");
+        assert_eq!(config["telegram"]["bot_token"], REDACTED);
+        assert!(config["telegram"]["session_password"].is_null());
+        assert_eq!(config["voice"]["hotkey"], "F9");
+    }
+
+    #[test]
+    fn test_bundle() {
+        let dir = std::env::temp_dir().join(format!("crash-reports-{}", uuid::Uuid::new_v4().simple()));
+        assert!(pending(&dir).unwrap().is_empty());
+
+        let report = CrashReport::new(
+            "token=s3cr3t in the wrong place".to_string(),
+            Some("src/ui/app.rs:42:5".to_string()),
+            "0: agent_dashboard::main".to_string(),
+        );
+        let bundle = write_bundle(&dir, &report, "INFO started", serde_json::json!({ "api_key": "k" })).unwrap();
+        assert!(bundle.join("log.txt").exists());
+        assert!(!std::fs::read_to_string(bundle.join("config.json")).unwrap().contains("\"k\""));
+
+        let pending_reports = pending(&dir).unwrap();
+        assert_eq!(pending_reports.len(), 1);
+        assert_eq!(pending_reports[0].report.message, "token=[redacted] in the wrong place");
+
+        let url = pending_reports[0].report.issue_url();
+        assert!(url.starts_with(ISSUES_URL));
+        assert!(!url.contains("s3cr3t"));
+
+        pending_reports[0].mark_reviewed().unwrap();
+        assert!(pending(&dir).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_long_report_fits_in_url() {
+        let report = CrashReport::new("boom".to_string(), None, "frame\n".repeat(5000));
+        assert!(report.issue_url().len() < MAX_ISSUE_BODY * 4);
+        assert!(report.markdown().len() > MAX_ISSUE_BODY);
+    }
+}
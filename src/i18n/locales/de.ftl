@@ -0,0 +1,465 @@
+# Hauptfenster
+app-title = Agenten-Dashboard
+tab-home = Start
+tab-chats = Chats
+tab-term = Terminal
+button-config = Einstellungen
+button-plans = Pläne
+button-tts = Sprachausgabe
+button-scripts = Skripte
+button-updates = Updates
+button-update-available = ⬆ Update
+button-logs = Protokolle
+button-blackboard = Tafel
+button-assistant = Assistent
+button-triage = Sichten
+triage-hover = Ungelesene Nachrichten einzeln mit Einzeltasten abarbeiten
+button-identities = Identitäten
+identities-hover = Konten einer Person auf verschiedenen Plattformen verknüpfen
+updates-hover = Nach einer neuen Version der chat-CLI suchen
+
+# Agentenliste
+agents-heading = Aktive Agenten
+agents-none = Keine aktiven Agenten
+agents-broadcast = 📢 An alle
+agent-sandboxed = In Sandbox: { $sandbox }
+
+# Chat
+chat-to = An:
+chat-to-all = Alle Agenten
+chat-to-none = Niemand
+chat-to-select = Niemand (Agent auswählen)
+chat-send = Senden
+chat-you = [Du]
+chat-you-all = [Du → Alle]
+chat-notice = [Hinweis]
+chat-agent = [Agent: { $agent }]
+chat-speak = Nachricht vorlesen
+chat-edit = Bearbeiten und erneut senden, in einem neuen Zweig
+chat-edit-send = In neuem Zweig senden
+chat-edit-cancel = Abbrechen
+chat-regenerate = Neue Antwort anfordern, in einem neuen Zweig
+chat-branches = Fassungen dieser Nachricht; jede setzt ihren eigenen Zweig der Unterhaltung fort
+chat-group-expand = ▸ { $count } weitere
+chat-group-collapse = ▴ Einklappen
+chat-tool-rerun = Werkzeug mit denselben Argumenten erneut ausführen
+chat-tool-arguments = Argumente
+chat-tool-result = Ergebnis
+chat-tool-no-result = Kein Ergebnis gemeldet
+
+# Statusleiste
+status-no-sources = Keine Quellen
+source-connected = verbunden
+source-disconnected = getrennt
+source-session-expired = Sitzung abgelaufen
+source-sign-in = Erneut anmelden
+status-lag = Rückstand { $lag }
+status-lag-hover = { $lag } Nachrichten gepuffert, { $dropped } verworfen
+mcp-starting = startet
+mcp-running = auf { $address }
+mcp-stopped = gestoppt: { $reason }
+mcp-hover = Chat-MCP-Server { $state }
+tts-playing = Sprachausgabe: spielt ({ $queued } in der Warteschlange)
+tts-queued = Sprachausgabe: { $queued } in der Warteschlange
+tts-idle = Sprachausgabe: bereit
+tts-off = Sprachausgabe: aus
+dnd-off = Nicht stören: aus
+dnd-off-hover = Klicken, um Hinweise und automatisches Vorlesen zurückzuhalten
+dnd-on = 🔕 Nicht stören
+dnd-on-hover = Nicht stören ist an, nur kritische Hinweise kommen durch. Klicken zum Ausschalten
+dnd-quiet-hours = 🔕 Ruhezeit
+dnd-quiet-hours-hover = Ruhezeit bis { $until }, nur kritische Hinweise kommen durch
+voice-ready = 🎙 bereit
+voice-waiting = 🎙 wartet auf das Aktivierungswort
+voice-listening = 🎙 hört zu…
+voice-transcribing = 🎙 transkribiert…
+voice-hover = Klicken oder { $hotkey } drücken, um einen Befehl zu geben
+status-agents = Agenten: { $count }
+status-unread = Ungelesen: { $count }
+status-dismiss = Klicken zum Schließen
+
+# Abgestürzte Sitzung wiederherstellen
+recovery-title = Letzte Sitzung wiederherstellen?
+recovery-body = Das Dashboard wurde nicht normal beendet. Die Sitzung wurde zuletzt um { $time } gesichert.
+recovery-note = Agenten und der Terminalbefehl werden neu gestartet; ihre frühere Ausgabe ist verloren
+recovery-restore = Wiederherstellen
+recovery-discard = Verwerfen
+
+# Absturzberichte
+crash-title = Das Dashboard ist abgestürzt
+crash-body = Am { $time } wurde ein Absturzbericht gespeichert:
+crash-more = { $count } weitere ungesehene Berichte von früheren Abstürzen
+crash-privacy = Geheimnisse und persönliche Angaben wurden aus dem Bericht entfernt. Er bleibt in { $path }, bis ein Issue eröffnet wird.
+crash-open-issue = Issue eröffnen
+crash-open-issue-hint = Öffnet ein GitHub-Issue mit dem Bericht, kopiert ihn in die Zwischenablage und zeigt den Ordner mit Log und Konfiguration zum Anhängen
+crash-show-folder = Ordner anzeigen
+crash-dismiss = Schließen
+
+# Updates der chat-CLI
+updates-title = Updates
+updates-disabled = Die Update-Suche ist aus: [updates] check = false in der sources.toml der chat-CLI, oder eine chat-CLI ohne self-update
+updates-available = chat { $latest } ist verfügbar (installiert: { $current })
+updates-release-page = Release-Seite
+updates-up-to-date = chat { $current } ist aktuell
+updates-not-checked = Noch nicht gesucht
+updates-restart = Laufende chat-Befehle neu starten, um die neue Version zu nutzen.
+updates-checking = Suche…
+updates-installing = Wird heruntergeladen und installiert…
+updates-check = Jetzt suchen
+updates-install = Installieren
+updates-install-hover = Führt chat self-update aus, das die Prüfsumme des Downloads prüft
+
+# Plan-Ansicht
+plans-empty = Keine aktiven Pläne
+plans-import-todos = Chat-Aufgaben importieren
+plans-import-todos-hint = Offene Aufgaben aus chat todos in den Plan des gewählten Agenten übernehmen
+plans-select-agent = Zuerst einen Agenten wählen
+plans-step-send = An den Agenten des Plans senden
+plans-step-done = Als erledigt markieren, auch in chat todos
+
+# Einstellungen
+settings-language = Sprache
+settings-language-system = Systemsprache
+settings-crash-reports = Absturzberichte speichern
+settings-crash-reports-hint = Berichte bleiben ohne Geheimnisse auf diesem Computer, bis ein Issue eröffnet wird
+settings-crash-reports-folder = Berichtsordner öffnen
+settings-title = Agenten-Einstellungen
+settings-agents = Eingerichtete Agenten
+settings-add-agent = Agent hinzufügen
+settings-add-process-agent = Prozess-Agent hinzufügen
+settings-add-process-agent-hint = Ein lokales Programm, das das Agenten-Frame-Protokoll über stdin/stdout spricht
+settings-add-coding-cli = Coding-CLI hinzufügen
+settings-setup-wizard = Einrichtungsassistent starten
+settings-pipelines = Nachrichten-Pipelines ausführen
+settings-pipelines-hint = Führt `chat pipeline run` mit ~/.config/chat/pipelines.yaml aus
+settings-clipboard = Zwischenablage beobachten
+settings-clipboard-hint = Kopierten Text einem Agenten schicken oder in Chats danach suchen
+settings-appearance = Darstellung
+settings-font-scale = Schriftgröße
+settings-terminal-font = Terminal-Schrift
+settings-terminal-font-builtin = Eingebaute Festbreitenschrift
+settings-terminal-font-hint = Pfad zu einer .ttf- oder .otf-Datei, geladen beim Verlassen des Felds
+settings-terminal-font-size = Terminal-Schriftgröße
+settings-high-contrast = Hoher Kontrast
+settings-shortcuts-hint = F1 zeigt die Tastenkürzel
+settings-approvals = Freigabe von Shell-Befehlen
+settings-approvals-timeout = Unbeantwortete Anfragen ablehnen nach
+settings-approvals-recent = Letzte Befehle
+settings-announce = Nachrichten vorlesen
+settings-announce-template = Vorlage
+settings-announce-template-hint = { "{sender}" }, { "{chat}" } und { "{text}" } werden eingesetzt
+settings-announce-chat-interval = Höchstens eine Nachricht pro Chat alle
+settings-announce-max = Höchstens
+settings-announce-per-minute = Nachrichten pro Minute
+settings-announce-none = Keine Chats werden vorgelesen. Mit 🔊 im Chats-Tab einschalten.
+settings-announce-stop = Nicht mehr vorlesen
+settings-mcp = Chat-MCP-Server
+settings-mcp-enabled = Chats für Agenten bereitstellen
+settings-mcp-enabled-hint = Das Dashboard stellt die Chat-Werkzeuge selbst bereit; danach gestartete Coding-CLI-Agenten erhalten sie
+settings-mcp-port = Port
+settings-mcp-read-only = Nur lesen
+settings-mcp-read-only-hint = Agenten können Chats lesen, aber nie senden, Entwürfe anlegen oder als gelesen markieren
+settings-mcp-starting = Startet…
+settings-mcp-listening = Lauscht auf { $address }
+settings-mcp-stopped = Gestoppt: { $reason }
+settings-mcp-restart = Server neu starten
+settings-mcp-restart-hint = Port- und Nur-lesen-Änderungen übernehmen
+settings-mcp-not-running = Läuft nicht
+settings-grouping = Nachrichten gruppieren
+settings-grouping-window = Chat-Nachrichten eines Absenders zusammenfassen innerhalb von
+settings-grouping-window-hint = 0 zeigt jede Nachricht einzeln
+settings-grouping-collapse = Chat-Gruppen auf ihre erste Nachricht eingeklappt zeigen
+settings-grouping-terminal = Ausgabe im Term-Tab nach Befehl gruppieren
+settings-grouping-terminal-gap = Sonstige Ausgabe in einem neuen Block beginnen nach einer Pause von
+settings-grouping-terminal-gap-hint = 0 lässt sie in einem Block
+settings-history = Terminal-Verlauf
+settings-history-remember = Merken:
+settings-history-per-command = Befehle pro Startbefehl
+settings-history-skip-secrets = Zeilen, die geheim aussehen, nicht merken
+settings-history-skip-secrets-hint = Zeilen, die mit einem Leerzeichen beginnen, werden nie gemerkt
+settings-history-secret-words = Eine Zeile ist geheim, wenn sie eines dieser Wörter enthält (eines pro Zeile, ohne Groß-/Kleinschreibung):
+settings-paste = Einfügen im Terminal
+settings-paste-confirm-lines = Einfügen bestätigen bei mehr als
+settings-paste-confirm-lines-hint = 0 fragt nie
+settings-paste-lines = Zeilen
+settings-paste-control-chars = Einfügen mit Steuerzeichen bestätigen
+settings-paste-control-chars-hint = Escape-Sequenzen im eingefügten Text können auf das Terminal wirken
+settings-paste-bracketed = Mehrzeilige Eingaben als Bracketed Paste senden
+settings-paste-bracketed-hint = Nur wenn das Programm es verlangt; Shells warten dann auf Enter, statt jede Zeile auszuführen
+settings-terminal-sandbox = Terminal-Sandbox
+settings-terminal-sandbox-note = Gilt ab dem nächsten Start des Startbefehls
+settings-limits = Ressourcengrenzen
+settings-limits-monitor = Agenten- und Terminalprozesse überwachen
+settings-limits-interval = Messen alle
+settings-limits-memory = Speicherbudget
+settings-limits-memory-hint = Pro Agent oder Terminal, mit Kindprozessen; 0 für keines
+settings-limits-over = Bei Überschreitung
+settings-limits-warn = Warnen
+settings-limits-kill = Prozess beenden
+settings-limits-cpu = Warnen über
+settings-limits-cpu-hint = Prozent eines Kerns, zwei Messungen in Folge; 0 warnt nie
+settings-voice = Sprachbefehle
+settings-voice-enabled = Auf Sprachbefehle hören
+settings-voice-enabled-hint = Nimmt mit SoX `rec` auf und transkribiert mit whisper.cpp
+settings-voice-hotkey = Tastenkürzel
+settings-voice-hotkey-hint = z. B. Ctrl+Shift+Space oder F9
+settings-voice-wake-word = Aktivierungswort
+settings-voice-wake-word-hint = Ständig auf Befehle hören, die damit beginnen; leer hört nur nach dem Tastenkürzel
+settings-voice-model = Whisper-Modell
+settings-voice-language = Sprache
+settings-voice-language-hint = z. B. en; leer erkennt sie automatisch
+settings-voice-hotkey-unknown = Tastenkürzel nicht erkannt
+settings-voice-examples = Probiere „ask <agent> to …“, „broadcast …“, „stop“, „read the last message“, „open chats“ oder „do not disturb on“ (Befehle auf Englisch)
+settings-apply = Übernehmen
+settings-telegram = Telegram-Konto
+settings-telegram-session = Sitzung, geteilt mit der chat-CLI: { $path }
+settings-telegram-signed-out = Nicht angemeldet
+settings-agent-delete = Löschen
+settings-agent-connect = Verbinden
+settings-agent-config = Konfiguration: { $config }
+settings-agent-context = Systemprompt und Gedächtnis ({ $count })
+settings-agent-sandbox = Sandbox
+settings-cli-model = Modell:
+settings-cli-model-default = Standard der CLI
+settings-cli-workspace = Arbeitsverzeichnis:
+settings-cli-extra-args = Weitere Argumente:
+settings-context-prompt-hint = Systemprompt: Anweisungen vor jedem Gespräch
+settings-context-forget = Vergessen
+settings-context-since = Gemerkt seit { $time }
+settings-context-new = Etwas zum Merken
+settings-context-remember = Merken
+settings-context-clear = Gedächtnis leeren
+settings-context-clear-hint = Alles Gemerkte vergessen
+settings-sandbox-enabled = In einer Sandbox ausführen
+settings-sandbox-enabled-hint = Startet nicht, statt ohne Einschränkung zu laufen, wenn die Sandbox nicht verfügbar ist
+settings-sandbox-workspace = Nur ins Arbeitsverzeichnis schreiben
+settings-sandbox-network = Netzwerkzugriff erlauben
+settings-sandbox-writable = Außerdem beschreibbar:
+settings-sandbox-read-only = Nur lesbar:
+settings-sandbox-env-only = Nur die Umgebung ist eingeschränkt; Dateien und Netzwerk bleiben offen
+settings-sandbox-clean-env = Saubere Umgebung
+settings-sandbox-clean-env-hint = Nur PATH, HOME, USER, LOGNAME, LANG, TERM, SHELL und TMPDIR sowie die Variablen unten
+settings-sandbox-keep-env = Variablen behalten:
+
+# Chats-Tab
+chats-unlabelled = Ohne Label
+chats-no-recent-messages = Keine Nachrichten in den letzten 30 Tagen
+chats-refresh = Aktualisieren
+chats-search-hint = Nachrichten suchen
+chats-search = Suchen
+chats-catchup = Auf den neuesten Stand bringen
+chats-catchup-ticked = Auf den neuesten Stand bringen ({ $count })
+chats-catchup-hint = Ungelesene Nachrichten der angehakten Chats zusammenfassen, oder aller Chats, wenn keiner angehakt ist
+chats-catchup-mark-read = Als gelesen markieren
+chats-labels-hint = Labels vergeben mit: chat labels add telegram <chat_id> <label>
+chats-catchup-heading = Zusammenfassung
+chats-catchup-urgent = { $chat }: { $count } dringend
+chats-catchup-unread = { $count } ungelesen
+chats-catchup-marked-read = als gelesen markiert
+chats-read-aloud = Vorlesen
+chats-save-audio = Als gesprochenes Audio speichern
+chats-close = Schließen
+chats-catchup-digest = Keine LLM-Zusammenfassung verfügbar, zeige eine Übersicht
+chats-search-results = { $count } Treffer für „{ $query }“
+chats-show-activity = Chat-Aktivität zeigen
+chats-none = Keine Chats geladen. Klicke auf Aktualisieren, um sie abzurufen.
+chats-catchup-include = In die Zusammenfassung aufnehmen
+chats-unknown = Unbekannt
+chats-announce-hint = Neue Nachrichten aus diesem Chat vorlesen
+chats-assist-hint = Den Assistenz-Agenten in diesem Chat antworten lassen; Antworten werden nach Freigabe gesendet
+chats-live = Live
+chats-activity = Aktivität
+chats-tail = Mitlesen
+chats-tail-hint = Neuen Nachrichten in diesem Chat folgen, sobald sie eintreffen
+chats-notes = Notizen
+chats-notes-on = Notizen zu { $name }
+chats-notes-back = Zurück zu den Notizen des Chats
+chats-tail-filter = Nach Absender oder Text filtern
+chats-tail-mentions = Nur Erwähnungen
+chats-tail-clear = Leeren
+chats-tail-following = Folge neuen Nachrichten
+chats-tail-paused = Angehalten, solange nach oben gescrollt ist
+chats-tail-jump = ⬇ { $count } neu
+chats-tail-new = Neue Nachrichten
+chats-note-on = Notiz zu { $name }
+chats-tail-waiting = Warte auf neue Nachrichten…
+chats-copy-reply = Antwort kopieren
+chats-last-30-days = Letzte 30 Tage
+chats-message-count = { $count } Nachrichten
+chats-response-times = Antwortzeiten (Median)
+chats-response-time = { $responder } → { $to }: { $time } ({ $count } Antworten)
+chats-sentiment = Stimmung { $mean }
+chats-sentiment-counts = { $positive } positiv · { $neutral } neutral · { $negative } negativ
+weekday-mon = Mo
+weekday-tue = Di
+weekday-wed = Mi
+weekday-thu = Do
+weekday-fri = Fr
+weekday-sat = Sa
+weekday-sun = So
+chats-heatmap-cell = { $day } { $hour }:00 — { $count } Nachrichten
+chats-sentiment-hint = Eine Zählung von Schlüsselwörtern in englischen Nachrichten, kein Modell; Nachrichten in anderen Sprachen werden ausgelassen
+
+# Einrichtung
+onboarding-title = Willkommen bei Agent Dashboard
+onboarding-skip-setup = Einrichtung überspringen
+onboarding-back = Zurück
+onboarding-finish = Fertigstellen
+onboarding-next = Weiter
+onboarding-telegram-required = Melde dich bei Telegram an oder überspringe es vorerst
+onboarding-welcome = Los geht’s mit der Einrichtung
+onboarding-welcome-intro = Das dauert nur eine Minute. Du wirst:
+onboarding-welcome-telegram = { "  " }1. Dein Telegram-Konto verbinden
+onboarding-welcome-voice = { "  " }2. Eine Stimme für gesprochene Antworten wählen
+onboarding-welcome-agent = { "  " }3. Deinen ersten Agenten anlegen
+onboarding-welcome-saving = Bis du auf Fertigstellen klickst, wird nichts gespeichert, außer der Telegram-Anmeldung selbst.
+onboarding-telegram = Telegram
+onboarding-skip-telegram = Telegram vorerst überspringen
+onboarding-voice = Stimme
+onboarding-read-aloud = Antworten der Agenten vorlesen
+onboarding-speed = Tempo
+onboarding-test-voice = 🔊 Stimme testen
+onboarding-tts-failed = TTS konnte nicht gestartet werden: { $error }
+onboarding-voices-location = Stimmen sind Piper-Modelle (.onnx + .json) in { $path }
+onboarding-agent = Erster Agent
+onboarding-skip-agent = Vorerst überspringen
+onboarding-agent-name = Name:
+onboarding-agent-host = Ollama-Host:
+onboarding-agent-model = Modell:
+onboarding-ready = Bereit
+onboarding-summary-telegram-skipped = Telegram: übersprungen
+onboarding-summary-telegram = Telegram: { $phone }
+onboarding-summary-voice = Stimme: { $voice } mit { $speed }x
+onboarding-summary-voice-off = Stimme: aus
+onboarding-summary-agent-skipped = Agent: übersprungen
+onboarding-summary-agent = Agent: { $name } ({ $model })
+onboarding-writes = Fertigstellen schreibt:
+
+# Telegram-Anmeldung
+telegram-create-app = Lege eine App an unter
+telegram-create-app-purpose = um eine API-ID und einen Hash zu erhalten.
+telegram-api-id = API-ID:
+telegram-api-hash = API-Hash:
+telegram-phone = Telefon:
+telegram-sign-in = Anmelden
+telegram-requesting-code = Fordere einen Anmeldecode an...
+telegram-enter-code = Gib den Code ein, den Telegram dir geschickt hat:
+telegram-submit = Senden
+telegram-password-hint = Passwort für die Zwei-Faktor-Anmeldung (Hinweis: { $hint }):
+telegram-password = Passwort für die Zwei-Faktor-Anmeldung:
+telegram-signing-in = Melde an...
+telegram-signed-in-as = ✓ Angemeldet als { $user }
+telegram-signed-in = ✓ Angemeldet
+telegram-sign-in-again = Erneut anmelden
+telegram-sign-in-failed = Anmeldung fehlgeschlagen: { $error }
+telegram-try-again = Erneut versuchen
+telegram-cancel = Abbrechen
+telegram-submit-failed = Antwort konnte nicht gesendet werden: { $error }
+telegram-start-failed = { $error }. Ist die chat-CLI installiert?
+telegram-reauth-title = Telegram-Sitzung abgelaufen
+telegram-reauth-reason = Telegram akzeptiert diese Sitzung nicht mehr. Sie wurde von einem anderen Gerät abgemeldet oder ist abgelaufen.
+telegram-reauth-impact = Chats, Agenten und der MCP-Server können Telegram erst wieder lesen, wenn du dich erneut anmeldest.
+telegram-later = Später
+telegram-reauth-done = Wieder bei Telegram angemeldet
+
+# Triage
+triage-step-read = ✔ gelesen
+triage-step-bookmarked = 🔖 gemerkt
+triage-step-reminded = ⏰ Erinnerung gesetzt
+triage-step-sent = 🤖 an Agent gesendet
+triage-key-next = weiter
+triage-key-previous = zurück
+triage-key-read = bis hier als gelesen markieren
+triage-key-read-chat = ganzen Chat als gelesen markieren
+triage-key-bookmark = merken
+triage-key-remind = morgen erinnern
+triage-key-summarize = Agent: Chat zusammenfassen
+triage-key-draft = Agent: Antwort entwerfen
+triage-key-next-chat = nächster Chat
+triage-action-failed = { $action } fehlgeschlagen: { $error }
+triage-since = Nie gelesene Chats seit
+triage-load = Ungelesene laden
+triage-reload = Neu laden
+triage-nothing-loaded = Nichts geladen.
+triage-progress = { $position } von { $total } · { $remaining } übrig
+triage-done = Alles erledigt
+triage-action-read = { $chat } als gelesen markieren
+triage-action-bookmark = Nachricht merken
+triage-action-remind = Erinnerung setzen
+triage-select-agent = Wähle zuerst einen laufenden Agenten in der Seitenleiste
+
+# Identitäten
+identities-import-checked = Würde { $added } Konten verknüpfen, { $unchanged } bereits verknüpft, { $moved } verschoben, { $conflicts } Konflikte, { $invalid } ungültige Zeilen
+identities-import-done = { $added } Konten verknüpft, { $unchanged } bereits verknüpft, { $moved } verschoben, { $conflicts } Konflikte, { $invalid } ungültige Zeilen
+identities-import-csv = CSV importieren
+identities-import-hint = Spalten name, platform und id, mit Kopfzeile
+identities-check = Prüfen
+identities-check-hint = Probelauf
+identities-import = Importieren
+identities-force = Konten verschieben, die mit jemand anderem verknüpft sind
+identities-conflict = Zeile { $line }: { $account } ist mit { $linked } verknüpft, nicht mit { $wanted }
+identities-invalid-row = Zeile { $line }: { $reason }
+identities-name = Name
+identities-link = Verknüpfen
+identities-filter = Filter
+identities-reload = Neu laden
+identities-empty = Noch keine Identitäten.
+identities-rename-hint = Umbenennen oder mit jemandem zusammenführen
+identities-remove-hint = Mit allen Verknüpfungen entfernen
+identities-link-hint = Weiteres Konto verknüpfen
+identities-unlink = Verknüpfung lösen
+
+# Protokoll
+logs-show = Zeigen
+logs-record = Aufzeichnen
+logs-record-hint = Info und höher wird immer aufgezeichnet. Ausführlichere Stufen werden ab ihrer Auswahl bis zum Neustart des Dashboards aufgezeichnet.
+logs-resume = ▶ Fortsetzen
+logs-pause = ⏸ Anhalten
+logs-clear = Leeren
+logs-export = Exportieren
+logs-export-hint = Die angezeigten Einträge in einer Datei speichern
+logs-modules = Module: agent_dashboard, -wgpu
+logs-modules-hint = Kommagetrennte Modulpräfixe, die gezeigt werden; ein vorangestelltes - blendet eines aus
+logs-search = Suchen
+logs-follow = Mitlaufen
+logs-count = { $shown } von { $total } Einträgen
+logs-exported = { $count } Protokolleinträge in { $path } gespeichert
+logs-export-failed = Export des Protokolls fehlgeschlagen: { $error }
+
+# Blackboard
+blackboard-namespace = Namensraum
+blackboard-all = Alle
+blackboard-clear = { $namespace } leeren
+blackboard-clear-all = Alles leeren
+blackboard-empty = Noch leer. Agenten schreiben hier mit dem Tool-Aufruf blackboard.
+blackboard-expires = läuft in { $seconds } s ab
+blackboard-copy = Wert kopieren
+blackboard-delete = Löschen
+blackboard-key = Schlüssel
+blackboard-value = Wert (Text oder JSON)
+blackboard-ttl = TTL s
+blackboard-ttl-hint = Sekunden bis zum Verwerfen; leer behält den Eintrag
+blackboard-set = Setzen
+
+# Assistent
+assistant-enabled = Chats für mich beantworten
+assistant-enabled-hint = Sicherheitsschalter: Solange er aus ist, wird nichts an den Agenten weitergegeben oder gesendet, und nicht gesendete Antworten werden verworfen
+assistant-agent = Agent
+assistant-no-agent = Keiner
+assistant-agent-stopped = Der Agent läuft nicht; verbinde ihn, damit Nachrichten beantwortet werden.
+assistant-no-chats = Noch keine. Schalte 🤖 neben einem Chat im Chats-Tab ein.
+assistant-stop = Beenden
+assistant-outbox = Warten auf Freigabe
+assistant-outbox-empty = Nichts zu senden.
+assistant-incoming = { $sender } in { $chat }: { $text }
+assistant-send = Senden
+assistant-off = Der Assistent ist ausgeschaltet
+assistant-discard = Verwerfen
+assistant-from = von { $agent }
+assistant-audit = Prüfprotokoll
+assistant-audit-routed = weitergegeben von { $sender }
+assistant-audit-drafted = entworfen
+assistant-audit-sent-edited = gesendet, bearbeitet
+assistant-audit-sent = gesendet
+assistant-audit-failed = fehlgeschlagen: { $error }
+assistant-audit-discarded = verworfen
@@ -0,0 +1,465 @@
+# Main window
+app-title = Agent Dashboard
+tab-home = Home
+tab-chats = Chats
+tab-term = Term
+button-config = Config
+button-plans = Plans
+button-tts = TTS
+button-scripts = Scripts
+button-updates = Updates
+button-update-available = ⬆ Update
+button-logs = Logs
+button-blackboard = Blackboard
+button-assistant = Assistant
+button-triage = Triage
+triage-hover = Step through unread messages with single-key actions
+button-identities = Identities
+identities-hover = Link the accounts people use on each platform
+updates-hover = Check for a new chat CLI release
+
+# Agents panel
+agents-heading = Active Agents
+agents-none = No active agents
+agents-broadcast = 📢 Broadcast
+agent-sandboxed = Sandboxed: { $sandbox }
+
+# Chat
+chat-to = To:
+chat-to-all = All agents
+chat-to-none = None
+chat-to-select = None (select an agent)
+chat-send = Send
+chat-you = [You]
+chat-you-all = [You → All]
+chat-notice = [Notice]
+chat-agent = [Agent: { $agent }]
+chat-speak = Speak this message
+chat-edit = Edit and send again, in a new branch
+chat-edit-send = Send in new branch
+chat-edit-cancel = Cancel
+chat-regenerate = Ask for another reply, in a new branch
+chat-branches = Versions of this message; each continues its own branch of the conversation
+chat-group-expand = ▸ { $count } more
+chat-group-collapse = ▴ Collapse
+chat-tool-rerun = Run this tool again with the same arguments
+chat-tool-arguments = Arguments
+chat-tool-result = Result
+chat-tool-no-result = No result reported
+
+# Status bar
+status-no-sources = No sources
+source-connected = connected
+source-disconnected = disconnected
+source-session-expired = session expired
+source-sign-in = Sign in again
+status-lag = lag { $lag }
+status-lag-hover = { $lag } messages buffered, { $dropped } dropped
+mcp-starting = starting
+mcp-running = on { $address }
+mcp-stopped = stopped: { $reason }
+mcp-hover = Chat MCP server { $state }
+tts-playing = TTS: playing ({ $queued } queued)
+tts-queued = TTS: { $queued } queued
+tts-idle = TTS: idle
+tts-off = TTS: off
+dnd-off = DND: off
+dnd-off-hover = Click to hold back alerts and auto-speak
+dnd-on = 🔕 DND
+dnd-on-hover = Do not disturb is on, only critical alerts get through. Click to turn off
+dnd-quiet-hours = 🔕 Quiet hours
+dnd-quiet-hours-hover = Quiet hours until { $until }, only critical alerts get through
+voice-ready = 🎙 ready
+voice-waiting = 🎙 waiting for wake word
+voice-listening = 🎙 listening…
+voice-transcribing = 🎙 transcribing…
+voice-hover = Click or press { $hotkey } to give a command
+status-agents = Agents: { $count }
+status-unread = Unread: { $count }
+status-dismiss = Click to dismiss
+
+# Restoring a crashed session
+recovery-title = Restore previous session?
+recovery-body = The dashboard didn't close normally. The session was last saved at { $time }.
+recovery-note = Agents and the terminal command are started again; their earlier output is gone
+recovery-restore = Restore
+recovery-discard = Discard
+
+# Crash reports
+crash-title = The dashboard crashed
+crash-body = A crash report was saved on { $time }:
+crash-more = { $count } more unseen reports from earlier crashes
+crash-privacy = Secrets and personal details were removed from the report. It stays in { $path } unless you open an issue.
+crash-open-issue = Open issue
+crash-open-issue-hint = Opens a GitHub issue with the report filled in, copies it to the clipboard and shows the folder with the log and config to attach
+crash-show-folder = Show folder
+crash-dismiss = Dismiss
+
+# chat CLI updates
+updates-title = Updates
+updates-disabled = Update checks are off: [updates] check = false in the chat CLI's sources.toml, or a chat CLI built without self-update
+updates-available = chat { $latest } is available (installed: { $current })
+updates-release-page = Release page
+updates-up-to-date = chat { $current } is up to date
+updates-not-checked = Not checked yet
+updates-restart = Restart running chat commands to use the new version.
+updates-checking = Checking…
+updates-installing = Downloading and installing…
+updates-check = Check now
+updates-install = Install
+updates-install-hover = Runs chat self-update, which verifies the download's checksum
+
+# Plans panel
+plans-empty = No active plans
+plans-import-todos = Import chat todos
+plans-import-todos-hint = Add the open todos found by chat todos to the selected agent's plan
+plans-select-agent = Select an agent first
+plans-step-send = Send to the plan's agent
+plans-step-done = Mark done, also in chat todos
+
+# Settings
+settings-language = Language
+settings-language-system = System default
+settings-crash-reports = Save crash reports
+settings-crash-reports-hint = Reports stay on this computer, with secrets removed, until you choose to open an issue
+settings-crash-reports-folder = Open reports folder
+settings-title = Agent Configuration
+settings-agents = Configured Agents
+settings-add-agent = Add Agent
+settings-add-process-agent = Add Process Agent
+settings-add-process-agent-hint = A local program speaking the agent frame protocol on stdin/stdout
+settings-add-coding-cli = Add Coding CLI
+settings-setup-wizard = Run setup wizard
+settings-pipelines = Run message pipelines
+settings-pipelines-hint = Runs `chat pipeline run` with ~/.config/chat/pipelines.yaml
+settings-clipboard = Watch clipboard
+settings-clipboard-hint = Offer to send copied text to an agent or search chats for it
+settings-appearance = Appearance
+settings-font-scale = Font scale
+settings-terminal-font = Terminal font
+settings-terminal-font-builtin = Built-in monospace
+settings-terminal-font-hint = Path to a .ttf or .otf file, loaded when you leave the field
+settings-terminal-font-size = Terminal font size
+settings-high-contrast = High contrast
+settings-shortcuts-hint = Press F1 for keyboard shortcuts
+settings-approvals = Shell command approvals
+settings-approvals-timeout = Deny unanswered prompts after
+settings-approvals-recent = Recent commands
+settings-announce = Read messages aloud
+settings-announce-template = Template
+settings-announce-template-hint = { "{sender}" }, { "{chat}" } and { "{text}" } are filled in
+settings-announce-chat-interval = At most one message per chat every
+settings-announce-max = At most
+settings-announce-per-minute = messages per minute
+settings-announce-none = No chats are read aloud. Switch them on with 🔊 in the Chats tab.
+settings-announce-stop = Stop reading aloud
+settings-mcp = Chat MCP server
+settings-mcp-enabled = Serve chats to agents
+settings-mcp-enabled-hint = Serves the chat tools from the dashboard itself; coding CLI agents started afterwards get them
+settings-mcp-port = Port
+settings-mcp-read-only = Read-only
+settings-mcp-read-only-hint = Agents can read chats but never send, draft or mark messages read
+settings-mcp-starting = Starting…
+settings-mcp-listening = Listening on { $address }
+settings-mcp-stopped = Stopped: { $reason }
+settings-mcp-restart = Restart server
+settings-mcp-restart-hint = Apply port and read-only changes
+settings-mcp-not-running = Not running
+settings-grouping = Message grouping
+settings-grouping-window = Group chat messages from one sender within
+settings-grouping-window-hint = 0 shows every message on its own
+settings-grouping-collapse = Show chat groups collapsed to their first message
+settings-grouping-terminal = Group Term tab output by command
+settings-grouping-terminal-gap = Start a new block of other output after a pause of
+settings-grouping-terminal-gap-hint = 0 keeps it in one block
+settings-history = Terminal history
+settings-history-remember = Remember
+settings-history-per-command = commands per startup command
+settings-history-skip-secrets = Don't remember lines that look secret
+settings-history-skip-secrets-hint = Lines starting with a space are never remembered
+settings-history-secret-words = A line is secret if it contains one of these (one per line, ignoring case):
+settings-paste = Terminal paste
+settings-paste-confirm-lines = Confirm pastes of more than
+settings-paste-confirm-lines-hint = 0 never asks
+settings-paste-lines = lines
+settings-paste-control-chars = Confirm pastes with control characters
+settings-paste-control-chars-hint = Escape sequences in pasted text can act on the terminal
+settings-paste-bracketed = Send multi-line input as a bracketed paste
+settings-paste-bracketed-hint = Only when the program asks for it; shells then wait for Enter instead of running each line
+settings-terminal-sandbox = Terminal sandbox
+settings-terminal-sandbox-note = Takes effect when the startup command is next run
+settings-limits = Resource limits
+settings-limits-monitor = Monitor agent and terminal processes
+settings-limits-interval = Sample every
+settings-limits-memory = Memory budget
+settings-limits-memory-hint = Per agent or terminal, child processes included; 0 for none
+settings-limits-over = Over budget
+settings-limits-warn = Warn
+settings-limits-kill = Kill the process
+settings-limits-cpu = Warn above
+settings-limits-cpu-hint = Percent of one core, for two samples in a row; 0 never warns
+settings-voice = Voice commands
+settings-voice-enabled = Listen for voice commands
+settings-voice-enabled-hint = Records with SoX `rec` and transcribes with whisper.cpp
+settings-voice-hotkey = Hotkey
+settings-voice-hotkey-hint = e.g. Ctrl+Shift+Space or F9
+settings-voice-wake-word = Wake word
+settings-voice-wake-word-hint = Listen all the time for commands starting with this; empty listens only on the hotkey
+settings-voice-model = Whisper model
+settings-voice-language = Language
+settings-voice-language-hint = e.g. en; empty detects it
+settings-voice-hotkey-unknown = Hotkey not recognized
+settings-voice-examples = Try "ask <agent> to …", "broadcast …", "stop", "read the last message", "open chats" or "do not disturb on"
+settings-apply = Apply
+settings-telegram = Telegram account
+settings-telegram-session = Session shared with the chat CLI: { $path }
+settings-telegram-signed-out = Not signed in
+settings-agent-delete = Delete
+settings-agent-connect = Connect
+settings-agent-config = Config: { $config }
+settings-agent-context = System prompt and memory ({ $count })
+settings-agent-sandbox = Sandbox
+settings-cli-model = Model:
+settings-cli-model-default = CLI default
+settings-cli-workspace = Workspace:
+settings-cli-extra-args = Extra arguments:
+settings-context-prompt-hint = System prompt: instructions given before every conversation
+settings-context-forget = Forget this
+settings-context-since = Remembered since { $time }
+settings-context-new = Something to remember
+settings-context-remember = Remember
+settings-context-clear = Clear memory
+settings-context-clear-hint = Forget everything remembered
+settings-sandbox-enabled = Run in a sandbox
+settings-sandbox-enabled-hint = Fails to start rather than running unconfined when the sandbox isn't available
+settings-sandbox-workspace = Only write to the working directory
+settings-sandbox-network = Allow network access
+settings-sandbox-writable = Also writable:
+settings-sandbox-read-only = Read-only:
+settings-sandbox-env-only = Only the environment is restricted; files and network stay open
+settings-sandbox-clean-env = Clean environment
+settings-sandbox-clean-env-hint = Only PATH, HOME, USER, LOGNAME, LANG, TERM, SHELL and TMPDIR, plus the variables below
+settings-sandbox-keep-env = Keep variables:
+
+# Chats tab
+chats-unlabelled = Unlabelled
+chats-no-recent-messages = No messages in the last 30 days
+chats-refresh = Refresh
+chats-search-hint = Search messages
+chats-search = Search
+chats-catchup = Catch me up
+chats-catchup-ticked = Catch me up ({ $count })
+chats-catchup-hint = Summarize unread messages of the ticked chats, or of all chats if none are ticked
+chats-catchup-mark-read = Mark read
+chats-labels-hint = Assign labels with: chat labels add telegram <chat_id> <label>
+chats-catchup-heading = Catch-up
+chats-catchup-urgent = { $chat }: { $count } urgent
+chats-catchup-unread = { $count } unread
+chats-catchup-marked-read = marked read
+chats-read-aloud = Read aloud
+chats-save-audio = Save as narrated audio
+chats-close = Close
+chats-catchup-digest = No LLM summary available, showing a digest
+chats-search-results = { $count } results for "{ $query }"
+chats-show-activity = Show chat activity
+chats-none = No chats loaded. Click Refresh to fetch chats.
+chats-catchup-include = Include in catch-up
+chats-unknown = Unknown
+chats-announce-hint = Read new messages from this chat aloud
+chats-assist-hint = Let the assistant agent answer this chat, with replies sent once approved
+chats-live = Live
+chats-activity = Activity
+chats-tail = Tail
+chats-tail-hint = Follow new messages in this chat as they arrive
+chats-notes = Notes
+chats-notes-on = Notes on { $name }
+chats-notes-back = Back to the chat's notes
+chats-tail-filter = Filter sender or text
+chats-tail-mentions = Mentions only
+chats-tail-clear = Clear
+chats-tail-following = Following new messages
+chats-tail-paused = Paused while scrolled up
+chats-tail-jump = ⬇ { $count } new
+chats-tail-new = New messages
+chats-note-on = Note on { $name }
+chats-tail-waiting = Waiting for new messages…
+chats-copy-reply = Copy a reply
+chats-last-30-days = Last 30 days
+chats-message-count = { $count } messages
+chats-response-times = Response times (median)
+chats-response-time = { $responder } → { $to }: { $time } ({ $count } replies)
+chats-sentiment = Sentiment { $mean }
+chats-sentiment-counts = { $positive } positive · { $neutral } neutral · { $negative } negative
+weekday-mon = Mon
+weekday-tue = Tue
+weekday-wed = Wed
+weekday-thu = Thu
+weekday-fri = Fri
+weekday-sat = Sat
+weekday-sun = Sun
+chats-heatmap-cell = { $day } { $hour }:00 — { $count } messages
+chats-sentiment-hint = A keyword count over English messages, not a model; messages in other languages are left out
+
+# Onboarding
+onboarding-title = Welcome to Agent Dashboard
+onboarding-skip-setup = Skip setup
+onboarding-back = Back
+onboarding-finish = Finish
+onboarding-next = Next
+onboarding-telegram-required = Sign in to Telegram, or skip it for now
+onboarding-welcome = Let's get you set up
+onboarding-welcome-intro = This takes a minute. You'll:
+onboarding-welcome-telegram = { "  " }1. Connect your Telegram account
+onboarding-welcome-voice = { "  " }2. Pick a voice for spoken replies
+onboarding-welcome-agent = { "  " }3. Create your first agent
+onboarding-welcome-saving = Nothing is saved until you press Finish, except the Telegram login itself.
+onboarding-telegram = Telegram
+onboarding-skip-telegram = Skip Telegram for now
+onboarding-voice = Voice
+onboarding-read-aloud = Read agent replies aloud
+onboarding-speed = Speed
+onboarding-test-voice = 🔊 Test voice
+onboarding-tts-failed = Failed to start TTS: { $error }
+onboarding-voices-location = Voices are Piper models (.onnx + .json) in { $path }
+onboarding-agent = First agent
+onboarding-skip-agent = Skip for now
+onboarding-agent-name = Name:
+onboarding-agent-host = Ollama host:
+onboarding-agent-model = Model:
+onboarding-ready = Ready
+onboarding-summary-telegram-skipped = Telegram: skipped
+onboarding-summary-telegram = Telegram: { $phone }
+onboarding-summary-voice = Voice: { $voice } at { $speed }x
+onboarding-summary-voice-off = Voice: off
+onboarding-summary-agent-skipped = Agent: skipped
+onboarding-summary-agent = Agent: { $name } ({ $model })
+onboarding-writes = Finish writes:
+
+# Telegram login
+telegram-create-app = Create an app at
+telegram-create-app-purpose = to get an API ID and hash.
+telegram-api-id = API ID:
+telegram-api-hash = API hash:
+telegram-phone = Phone:
+telegram-sign-in = Sign in
+telegram-requesting-code = Requesting a login code...
+telegram-enter-code = Enter the code Telegram sent you:
+telegram-submit = Submit
+telegram-password-hint = Two-factor password (hint: { $hint }):
+telegram-password = Two-factor password:
+telegram-signing-in = Signing in...
+telegram-signed-in-as = ✓ Signed in as { $user }
+telegram-signed-in = ✓ Signed in
+telegram-sign-in-again = Sign in again
+telegram-sign-in-failed = Sign-in failed: { $error }
+telegram-try-again = Try again
+telegram-cancel = Cancel
+telegram-submit-failed = Failed to send answer: { $error }
+telegram-start-failed = { $error }. Is the chat CLI installed?
+telegram-reauth-title = Telegram session expired
+telegram-reauth-reason = Telegram no longer accepts this session. It was signed out from another device or expired.
+telegram-reauth-impact = Chats, agents and the MCP server can't read Telegram until you sign in again.
+telegram-later = Later
+telegram-reauth-done = Signed in to Telegram again
+
+# Triage
+triage-step-read = ✔ read
+triage-step-bookmarked = 🔖 bookmarked
+triage-step-reminded = ⏰ reminder set
+triage-step-sent = 🤖 sent to agent
+triage-key-next = next
+triage-key-previous = previous
+triage-key-read = mark read up to here
+triage-key-read-chat = mark the whole chat read
+triage-key-bookmark = bookmark
+triage-key-remind = remind me tomorrow
+triage-key-summarize = agent: summarize the chat
+triage-key-draft = agent: draft a reply
+triage-key-next-chat = next chat
+triage-action-failed = Failed to { $action }: { $error }
+triage-since = Never-read chats since
+triage-load = Load unread
+triage-reload = Reload
+triage-nothing-loaded = Nothing loaded.
+triage-progress = { $position } of { $total } · { $remaining } left
+triage-done = All done
+triage-action-read = mark { $chat } read
+triage-action-bookmark = bookmark the message
+triage-action-remind = set a reminder
+triage-select-agent = Select a running agent in the sidebar first
+
+# Identities
+identities-import-checked = Would link { $added } accounts, { $unchanged } already linked, { $moved } moved, { $conflicts } conflicts, { $invalid } invalid rows
+identities-import-done = Linked { $added } accounts, { $unchanged } already linked, { $moved } moved, { $conflicts } conflicts, { $invalid } invalid rows
+identities-import-csv = Import CSV
+identities-import-hint = Columns name, platform and id, with a header row
+identities-check = Check
+identities-check-hint = Dry run
+identities-import = Import
+identities-force = Move accounts linked to someone else
+identities-conflict = Line { $line }: { $account } is linked to { $linked }, not { $wanted }
+identities-invalid-row = Line { $line }: { $reason }
+identities-name = Name
+identities-link = Link
+identities-filter = Filter
+identities-reload = Reload
+identities-empty = No identities yet.
+identities-rename-hint = Rename, or merge into someone
+identities-remove-hint = Remove with all links
+identities-link-hint = Link another account
+identities-unlink = Unlink
+
+# Logs
+logs-show = Show
+logs-record = Record
+logs-record-hint = Info and above are always recorded. More verbose levels are recorded from when they're picked until the dashboard restarts.
+logs-resume = ▶ Resume
+logs-pause = ⏸ Pause
+logs-clear = Clear
+logs-export = Export
+logs-export-hint = Save the records shown to a file
+logs-modules = Modules: agent_dashboard, -wgpu
+logs-modules-hint = Comma-separated module prefixes to show; put - in front of one to hide it
+logs-search = Search
+logs-follow = Follow
+logs-count = { $shown } of { $total } records
+logs-exported = Saved { $count } log records to { $path }
+logs-export-failed = Log export failed: { $error }
+
+# Blackboard
+blackboard-namespace = Namespace
+blackboard-all = All
+blackboard-clear = Clear { $namespace }
+blackboard-clear-all = Clear all
+blackboard-empty = Nothing yet. Agents write here with the blackboard tool call.
+blackboard-expires = expires in { $seconds }s
+blackboard-copy = Copy the value
+blackboard-delete = Delete
+blackboard-key = key
+blackboard-value = value (text or JSON)
+blackboard-ttl = TTL s
+blackboard-ttl-hint = Seconds until it's dropped; empty keeps it
+blackboard-set = Set
+
+# Assistant
+assistant-enabled = Answer chats for me
+assistant-enabled-hint = Safety switch: while off nothing is passed to the agent or sent, and unsent replies are thrown away
+assistant-agent = Agent
+assistant-no-agent = None
+assistant-agent-stopped = The agent isn't running; connect it to have messages answered.
+assistant-no-chats = None yet. Turn on 🤖 next to a chat in the Chats tab.
+assistant-stop = Stop
+assistant-outbox = Waiting for approval
+assistant-outbox-empty = Nothing to send.
+assistant-incoming = { $sender } in { $chat }: { $text }
+assistant-send = Send
+assistant-off = The assistant is switched off
+assistant-discard = Discard
+assistant-from = from { $agent }
+assistant-audit = Audit log
+assistant-audit-routed = passed on from { $sender }
+assistant-audit-drafted = drafted
+assistant-audit-sent-edited = sent, edited
+assistant-audit-sent = sent
+assistant-audit-failed = failed: { $error }
+assistant-audit-discarded = discarded
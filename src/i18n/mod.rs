@@ -0,0 +1,186 @@
+//! Translated UI text, from the Fluent files in `locales/`.
+//!
+//! Look strings up with `tr!("message-id")` or `tr!("message-id", name = value)`. A
+//! message missing from the chosen language falls back to English, and one missing
+//! from English shows its ID.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use unic_langid::LanguageIdentifier;
+
+/// Languages shipped, by code, with the name shown in the language setting
+pub const LANGUAGES: [(&str, &str); 2] = [("en", "English"), ("de", "Deutsch")];
+const FALLBACK: &str = "en";
+
+const SOURCES: [(&str, &str); 2] = [
+    ("en", include_str!("locales/en.ftl")),
+    ("de", include_str!("locales/de.ftl")),
+];
+
+static BUNDLES: OnceLock<HashMap<&'static str, FluentBundle<FluentResource>>> = OnceLock::new();
+static CURRENT: RwLock<&'static str> = RwLock::new(FALLBACK);
+
+fn bundles() -> &'static HashMap<&'static str, FluentBundle<FluentResource>> {
+    BUNDLES.get_or_init(|| {
+        SOURCES
+            .iter()
+            .map(|(code, source)| {
+                let language: LanguageIdentifier = code.parse().expect("locale code is valid");
+                let mut bundle = FluentBundle::new_concurrent(vec![language]);
+                // Isolation marks around arguments show up as boxes in egui's fonts
+                bundle.set_use_isolating(false);
+                let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+                    log::error!("Errors in the {} translations: {:?}", code, errors);
+                    resource
+                });
+                if let Err(errors) = bundle.add_resource(resource) {
+                    log::error!("Errors in the {} translations: {:?}", code, errors);
+                }
+                (*code, bundle)
+            })
+            .collect()
+    })
+}
+
+/// Use `language` for UI text, or the system language when `None`. Unknown
+/// languages fall back to English.
+pub fn set_language(language: Option<&str>) {
+    let wanted = language.map(str::to_string).or_else(system_language).unwrap_or_default();
+    let code = LANGUAGES.iter().map(|(code, _)| *code).find(|code| *code == wanted).unwrap_or(FALLBACK);
+    if let Ok(mut current) = CURRENT.write() {
+        *current = code;
+    }
+}
+
+/// e.g. "de" for `LANG=de_DE.UTF-8`
+fn system_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| value.split(['_', '.', '-']).next().map(str::to_lowercase))
+}
+
+pub fn current_language() -> &'static str {
+    CURRENT.read().map(|current| *current).unwrap_or(FALLBACK)
+}
+
+pub fn translate(id: &str) -> String {
+    format(id, None)
+}
+
+pub fn translate_args(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    format(id, Some(&fluent_args))
+}
+
+fn format(id: &str, args: Option<&FluentArgs>) -> String {
+    let bundles = bundles();
+    [current_language(), FALLBACK]
+        .iter()
+        .filter_map(|code| bundles.get(code))
+        .find_map(|bundle| {
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, args, &mut errors);
+            if !errors.is_empty() {
+                log::warn!("Formatting {}: {:?}", id, errors);
+            }
+            Some(text.into_owned())
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Translated text: `tr!("chat-send")`, `tr!("status-agents", count = 3)`
+macro_rules! tr {
+    ($id:literal) => {
+        $crate::i18n::translate($id)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate_args($id, &[$((stringify!($name), fluent_bundle::FluentValue::from($value))),+])
+    };
+}
+pub(crate) use tr;
+
+/// Which way text runs, going by its first strongly directional character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+pub fn direction(text: &str) -> Direction {
+    text.chars()
+        .find_map(|c| {
+            if is_rtl(c) {
+                Some(Direction::RightToLeft)
+            } else if c.is_alphabetic() {
+                Some(Direction::LeftToRight)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(Direction::LeftToRight)
+}
+
+/// Hebrew, Arabic, Syriac, Thaana, N'Ko and their presentation forms
+fn is_rtl(c: char) -> bool {
+    matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message_ids(source: &str) -> Vec<String> {
+        source
+            .lines()
+            .filter(|line| line.chars().next().is_some_and(|c| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split_once('=').map(|(id, _)| id.trim().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_locales_match() {
+        let english = message_ids(SOURCES[0].1);
+        assert!(!english.is_empty());
+        for (code, source) in &SOURCES[1..] {
+            let ids = message_ids(source);
+            for id in &english {
+                assert!(ids.contains(id), "{} is missing {}", code, id);
+            }
+            for id in &ids {
+                assert!(english.contains(id), "{} has {}, which English doesn't", code, id);
+            }
+        }
+        for (code, source) in SOURCES {
+            assert!(FluentResource::try_new(source.to_string()).is_ok(), "{} doesn't parse", code);
+        }
+    }
+
+    #[test]
+    fn test_tr() {
+        set_language(Some("de"));
+        assert_eq!(tr!("chat-send"), "Senden");
+        assert_eq!(tr!("status-agents", count = 2), "Agenten: 2");
+        assert_eq!(translate("no-such-message"), "no-such-message");
+
+        set_language(Some("xx"));
+        assert_eq!(current_language(), "en");
+        assert_eq!(tr!("status-agents", count = 2), "Agents: 2");
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(direction("Hello"), Direction::LeftToRight);
+        assert_eq!(direction("שלום עולם"), Direction::RightToLeft);
+        assert_eq!(direction("  123 مرحبا"), Direction::RightToLeft);
+        assert_eq!(direction("OK: مرحبا"), Direction::LeftToRight);
+        assert_eq!(direction("42"), Direction::LeftToRight);
+    }
+}
@@ -0,0 +1,227 @@
+//! The dashboard's log, kept in memory for the Logs window and crash reports.
+//!
+//! Records still go to stderr as RUST_LOG says, through env_logger. Independently of that,
+//! records at or above the capture level (info unless the Logs window asks for more) go
+//! into a ring buffer, so there's no need to restart with RUST_LOG to see what happened.
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use log::{Level, LevelFilter, Log};
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Records kept in memory, oldest dropped first
+pub const MAX_ENTRIES: usize = 5000;
+
+static BUFFER: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+/// Most verbose level captured, as a `LevelFilter` number
+static CAPTURE_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
+/// Most verbose level RUST_LOG lets through to stderr
+static PRINT_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Error as usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Increases by one per captured record
+    pub seq: u64,
+    pub at: DateTime<Local>,
+    pub level: Level,
+    /// Module the record came from, e.g. `agent_dashboard::mcp`
+    pub target: String,
+    pub message: String,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:<5} {}: {}", self.at.format("%H:%M:%S%.3f"), self.level, self.target, self.message)
+    }
+}
+
+fn level_filter(value: usize) -> LevelFilter {
+    LevelFilter::iter().find(|level| *level as usize == value).unwrap_or(LevelFilter::Info)
+}
+
+pub fn capture_level() -> LevelFilter {
+    level_filter(CAPTURE_LEVEL.load(Ordering::Relaxed))
+}
+
+/// Capture records down to `level`, e.g. debug while diagnosing something
+pub fn set_capture_level(level: LevelFilter) {
+    CAPTURE_LEVEL.store(level as usize, Ordering::Relaxed);
+    log::set_max_level(level.max(level_filter(PRINT_LEVEL.load(Ordering::Relaxed))));
+}
+
+/// Sends records to env_logger and to the in-memory buffer
+struct DashboardLogger {
+    stderr: env_logger::Logger,
+}
+
+impl Log for DashboardLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= capture_level() || self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.stderr.matches(record) {
+            self.stderr.log(record);
+        }
+        if record.level() <= capture_level() {
+            push(record.level(), record.target(), record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+    }
+}
+
+/// Set up logging in place of `env_logger::init()`
+pub fn init() {
+    let stderr = env_logger::Builder::from_default_env().build();
+    PRINT_LEVEL.store(stderr.filter() as usize, Ordering::Relaxed);
+    if log::set_boxed_logger(Box::new(DashboardLogger { stderr })).is_ok() {
+        set_capture_level(capture_level());
+    }
+}
+
+fn push(level: Level, target: &str, message: String) {
+    let Ok(mut buffer) = BUFFER.lock() else {
+        return;
+    };
+    if buffer.len() == MAX_ENTRIES {
+        buffer.pop_front();
+    }
+    // Numbered under the lock, so the buffer stays in order
+    let seq = NEXT_SEQ.fetch_add(1, Ordering::Relaxed);
+    buffer.push_back(LogEntry { seq, at: Local::now(), level, target: target.to_string(), message });
+}
+
+/// Captured records numbered `seq` and later, oldest first
+pub fn entries_since(seq: u64) -> Vec<LogEntry> {
+    let Ok(buffer) = BUFFER.lock() else {
+        return Vec::new();
+    };
+    let start = buffer.partition_point(|entry| entry.seq < seq);
+    buffer.range(start..).cloned().collect()
+}
+
+/// The last `count` records at info level or above, one per line. Doesn't wait for the
+/// buffer, since it's called from the panic hook and the panicking thread may hold it.
+pub fn recent_text(count: usize) -> Option<String> {
+    let buffer = BUFFER.try_lock().ok()?;
+    let lines: Vec<String> = buffer.iter().filter(|entry| entry.level <= Level::Info).map(LogEntry::to_string).collect();
+    Some(lines[lines.len().saturating_sub(count)..].join("\n"))
+}
+
+/// Which records the Logs window shows
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    /// Most verbose level shown
+    pub level: LevelFilter,
+    /// Comma-separated module prefixes; one starting with `-` hides that module instead.
+    /// Empty shows every module.
+    pub modules: String,
+    /// Text the message or module must contain, ignoring case
+    pub search: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self { level: LevelFilter::Trace, modules: String::new(), search: String::new() }
+    }
+}
+
+impl LogFilter {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if entry.level > self.level {
+            return false;
+        }
+
+        let mut include = Vec::new();
+        for module in self.modules.split(',').map(str::trim).filter(|module| !module.is_empty()) {
+            match module.strip_prefix('-') {
+                Some(excluded) if entry.target.starts_with(excluded.trim()) => return false,
+                Some(_) => {}
+                None => include.push(module),
+            }
+        }
+        if !include.is_empty() && !include.iter().any(|module| entry.target.starts_with(module)) {
+            return false;
+        }
+
+        let search = self.search.trim().to_lowercase();
+        search.is_empty()
+            || entry.message.to_lowercase().contains(&search)
+            || entry.target.to_lowercase().contains(&search)
+    }
+}
+
+/// Where the Logs window saves exports
+pub fn export_dir() -> Result<PathBuf> {
+    Ok(crate::config::AppConfig::config_dir()?.join("logs"))
+}
+
+/// Write records to a text file, one per line
+pub fn export<'a>(entries: impl IntoIterator<Item = &'a LogEntry>, path: &Path) -> Result<usize> {
+    let mut text = String::new();
+    let mut count = 0;
+    for entry in entries {
+        text.push_str(&entry.to_string());
+        text.push('\n');
+        count += 1;
+    }
+    std::fs::write(path, text)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: Level, target: &str, message: &str) -> LogEntry {
+        LogEntry { seq: 0, at: Local::now(), level, target: target.to_string(), message: message.to_string() }
+    }
+
+    #[test]
+    fn test_filter() {
+        let mcp = entry(Level::Debug, "agent_dashboard::mcp", "Listening on 127.0.0.1:8765");
+        let wgpu = entry(Level::Info, "wgpu_core::device", "Created buffer");
+
+        assert!(LogFilter::default().matches(&mcp));
+        let info = LogFilter { level: LevelFilter::Info, ..LogFilter::default() };
+        assert!(!info.matches(&mcp) && info.matches(&wgpu));
+
+        let ours = LogFilter { modules: "agent_dashboard".to_string(), ..LogFilter::default() };
+        assert!(ours.matches(&mcp) && !ours.matches(&wgpu));
+        let quiet = LogFilter { modules: "-wgpu".to_string(), ..LogFilter::default() };
+        assert!(quiet.matches(&mcp) && !quiet.matches(&wgpu));
+
+        let search = LogFilter { search: "LISTENING".to_string(), ..LogFilter::default() };
+        assert!(search.matches(&mcp) && !search.matches(&wgpu));
+        let by_module = LogFilter { search: "wgpu".to_string(), ..LogFilter::default() };
+        assert!(by_module.matches(&wgpu));
+    }
+
+    #[test]
+    fn test_buffer() {
+        let start = NEXT_SEQ.load(Ordering::Relaxed);
+        push(Level::Warn, "test", "first".to_string());
+        push(Level::Debug, "test", "second".to_string());
+
+        let entries: Vec<LogEntry> = entries_since(start).into_iter().filter(|entry| entry.target == "test").collect();
+        assert_eq!(entries.iter().map(|entry| entry.message.as_str()).collect::<Vec<_>>(), ["first", "second"]);
+        let later: Vec<LogEntry> = entries_since(entries[1].seq).into_iter().filter(|entry| entry.target == "test").collect();
+        assert_eq!(later, entries[1..]);
+
+        let recent = recent_text(MAX_ENTRIES).unwrap();
+        assert!(recent.contains("test: first") && !recent.contains("test: second"));
+
+        let path = std::env::temp_dir().join(format!("dashboard-log-{}.txt", uuid::Uuid::new_v4().simple()));
+        assert_eq!(export(&entries, &path).unwrap(), 2);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+        let _ = std::fs::remove_file(path);
+    }
+}
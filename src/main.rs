@@ -1,10 +1,32 @@
 mod agent;
+mod approval;
+mod assistant;
+mod blackboard;
+mod clipboard;
 mod config;
+mod crash;
+mod i18n;
+mod logs;
+mod mcp;
+mod monitor;
+mod onboarding;
+mod pipeline;
 mod plan;
+mod sandbox;
+mod scripting;
+mod session;
+mod snippets;
+mod sources;
 mod storage;
+mod sync_api;
+mod telegram;
+mod terminal;
 mod toolcall;
 mod tts;
 mod ui;
+mod updates;
+mod voice;
+mod workspace;
 
 use anyhow::Result;
 use std::env;
@@ -12,10 +34,15 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use ui::DashboardApp;
 
+/// Messages `--stress-chat` generates when not given a number
+const DEFAULT_STRESS_MESSAGES: usize = 10_000;
+
 pub struct TestMode {
     pub enabled: bool,
     pub timeout_secs: u64,
     pub exit_message: Option<String>,
+    /// Fill the chat with this many messages to measure frame times (`--stress-chat [N]`)
+    pub stress_messages: Option<usize>,
     pub start_time: Instant,
     pub log_buffer: Arc<Mutex<Vec<String>>>,
 }
@@ -36,10 +63,15 @@ impl TestMode {
             .and_then(|i| args.get(i + 1))
             .map(|s| s.to_string());
 
+        let stress_messages = args.iter().position(|arg| arg == "--stress-chat").map(|i| {
+            args.get(i + 1).and_then(|s| s.parse().ok()).unwrap_or(DEFAULT_STRESS_MESSAGES)
+        });
+
         Self {
             enabled,
             timeout_secs,
             exit_message,
+            stress_messages,
             start_time: Instant::now(),
             log_buffer: Arc::new(Mutex::new(Vec::new())),
         }
@@ -79,7 +111,9 @@ impl TestMode {
 }
 
 fn main() -> Result<()> {
-    env_logger::init();
+    logs::init();
+    crash::install();
+    config::paths::prepare();
 
     let test_mode = TestMode::from_args();
 
@@ -91,13 +125,20 @@ fn main() -> Result<()> {
         }
     }
 
+    let stress_messages = test_mode.stress_messages;
     let test_mode = Arc::new(Mutex::new(test_mode));
     let test_mode_clone = Arc::clone(&test_mode);
 
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size([1200.0, 800.0])
+        .with_min_inner_size([800.0, 600.0]);
+    match eframe::icon_data::from_png_bytes(include_bytes!("../resources/icons/icon-256.png")) {
+        Ok(icon) => viewport = viewport.with_icon(icon),
+        Err(e) => log::warn!("Failed to load the window icon: {}", e),
+    }
+
     let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1200.0, 800.0])
-            .with_min_inner_size([800.0, 600.0]),
+        viewport,
         ..Default::default()
     };
 
@@ -107,6 +148,9 @@ fn main() -> Result<()> {
         Box::new(move |cc| {
             let mut app = DashboardApp::new(cc);
             app.test_mode = Some(test_mode_clone);
+            if let Some(count) = stress_messages {
+                app.start_chat_stress(count);
+            }
             Ok(Box::new(app))
         }),
     )
@@ -0,0 +1,236 @@
+use anyhow::{Context, Result};
+use chat::mcp_server::ChatMcpServer;
+use chat::{SourcesConfig, SourcesManager};
+use serde::{Deserialize, Serialize};
+use std::sync::{mpsc, Arc};
+use tokio::sync::oneshot;
+
+/// Settings for the chat MCP server the dashboard runs for its agents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// Run the server while the dashboard is open
+    pub enabled: bool,
+    /// Local port it listens on
+    pub port: u16,
+    /// Agents can read chats but never send, draft or mark read
+    pub read_only: bool,
+}
+
+impl Default for McpServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7411,
+            read_only: true,
+        }
+    }
+}
+
+impl McpServerConfig {
+    /// Loopback address the server listens on
+    pub fn address(&self) -> String {
+        format!("127.0.0.1:{}", self.port)
+    }
+}
+
+/// MCP client settings for a server at `address`, in the `mcpServers` format the coding
+/// CLIs read. Clients launch `chat-mcp-server --connect` (the bundled copy when there is
+/// one), which only relays stdio to it.
+pub fn client_config(address: &str) -> serde_json::Value {
+    serde_json::json!({
+        "mcpServers": {
+            "chat": {
+                "command": crate::config::paths::helper("chat-mcp-server"),
+                "args": ["--connect", address],
+            }
+        }
+    })
+}
+
+/// The chat sources configured in sources.toml, for the dashboard to hold and serve from
+pub fn load_sources() -> Result<Arc<SourcesManager>> {
+    Ok(Arc::new(chat::commands::create_manager()?))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McpServerStatus {
+    Starting,
+    Running,
+    /// Stopped, with the reason
+    Stopped(String),
+}
+
+/// What the server thread reports
+enum ServerEvent {
+    Listening,
+    Stopped(String),
+}
+
+/// The chat MCP server, hosted in the dashboard on a runtime thread of its own. It serves
+/// from the dashboard's sources manager, which holds the source connections, and every
+/// agent the dashboard launches shares them over the local port.
+pub struct McpServer {
+    address: String,
+    events: mpsc::Receiver<ServerEvent>,
+    status: McpServerStatus,
+    stop: Option<oneshot::Sender<()>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl McpServer {
+    /// Start serving the chats of `sources`, the dashboard's sources manager, repainting
+    /// `ctx` when the server's status changes. The sources stay connected when it stops.
+    pub fn start(config: &McpServerConfig, sources: Arc<SourcesManager>, ctx: egui::Context) -> Result<Self> {
+        let listener = std::net::TcpListener::bind(config.address())
+            .with_context(|| format!("Failed to listen on {}", config.address()))?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?.to_string();
+
+        let sources_config = SourcesConfig::load()?;
+        // Read-only here can't be turned off by sources.toml, like `chat-mcp-server --read-only`.
+        // Set either way, as a previous server may have left the shared manager read-only.
+        sources.set_read_only(config.read_only || sources_config.read_only);
+        let server = ChatMcpServer::new(SourcesManager::clone(&sources))
+            .with_guardrails(sources_config.mcp)
+            .with_policy_reload(SourcesConfig::default_path()?, config.read_only);
+
+        let (tx, events) = mpsc::channel();
+        let (stop, stopped) = oneshot::channel();
+        let thread = std::thread::Builder::new()
+            .name("chat-mcp-server".to_string())
+            .spawn(move || {
+                let report = |event| {
+                    let _ = tx.send(event);
+                    ctx.request_repaint();
+                };
+                let result = tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(anyhow::Error::from)
+                    .and_then(|runtime| {
+                        runtime.block_on(async {
+                            let listener = tokio::net::TcpListener::from_std(listener)?;
+                            report(ServerEvent::Listening);
+                            tokio::select! {
+                                result = server.serve_tcp(listener) => result,
+                                _ = stopped => Ok(()),
+                            }
+                        })
+                    });
+                report(ServerEvent::Stopped(match result {
+                    Ok(()) => "stopped".to_string(),
+                    Err(e) => e.to_string(),
+                }));
+            })
+            .context("Failed to start the chat MCP server thread")?;
+
+        Ok(Self {
+            address,
+            events,
+            status: McpServerStatus::Starting,
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn status(&mut self) -> McpServerStatus {
+        for event in self.events.try_iter() {
+            self.status = match event {
+                ServerEvent::Listening => McpServerStatus::Running,
+                ServerEvent::Stopped(reason) => McpServerStatus::Stopped(reason),
+            };
+        }
+        self.status.clone()
+    }
+
+    /// Address agents should connect to, once the server is accepting connections
+    pub fn running_address(&mut self) -> Option<String> {
+        (self.status() == McpServerStatus::Running).then(|| self.address.clone())
+    }
+}
+
+impl Drop for McpServer {
+    /// Stop serving and wait for the port to be released, so a restart can bind it again
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_config() {
+        let config = McpServerConfig { port: 9000, ..McpServerConfig::default() };
+        assert_eq!(config.address(), "127.0.0.1:9000");
+
+        let client = client_config(&config.address());
+        assert_eq!(client["mcpServers"]["chat"]["command"], "chat-mcp-server");
+        assert_eq!(client["mcpServers"]["chat"]["args"][1], "127.0.0.1:9000");
+    }
+
+    #[test]
+    fn test_serves_shared_sources() {
+        use chat::recording::{RecordedCall, SourceRecording};
+        use chat::{Chat, ChatId, ChatType, ReplaySource};
+        use std::io::{BufRead, BufReader, Write};
+
+        let recording = SourceRecording {
+            source_name: "Mock".to_string(),
+            calls: vec![RecordedCall::ListChats {
+                filter: None,
+                response: Ok(vec![Chat {
+                    id: ChatId::new("1"),
+                    title: Some("Standup".to_string()),
+                    chat_type: ChatType::Group,
+                    participant_count: None,
+                    topic: None,
+                }]),
+            }],
+            ..SourceRecording::default()
+        };
+        let sources = Arc::new(SourcesManager::new());
+        sources.register(Box::new(ReplaySource::new("mock", recording))).unwrap();
+
+        let config = McpServerConfig { enabled: true, port: 0, read_only: true };
+        let mut server = McpServer::start(&config, Arc::clone(&sources), egui::Context::default()).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let address = loop {
+            if let Some(address) = server.running_address() {
+                break address;
+            }
+            assert!(std::time::Instant::now() < deadline, "server did not start: {:?}", server.status());
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+
+        let mut stream = std::net::TcpStream::connect(&address).unwrap();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": "list_chats", "arguments": { "source": "mock" } }
+        });
+        writeln!(stream, "{}", request).unwrap();
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).unwrap();
+
+        let response: serde_json::Value = serde_json::from_str(&line).unwrap();
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("Standup"), "{}", response);
+
+        // Stopping the server leaves the dashboard's sources registered
+        drop(stream);
+        drop(server);
+        assert!(sources.has_source("mock"));
+    }
+}
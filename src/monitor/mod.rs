@@ -0,0 +1,343 @@
+//! CPU and memory use of agent processes and the Term tab, each counted with the
+//! processes it started, sampled on a background thread.
+
+use crate::agent::AgentId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// Samples kept per process for the sparklines
+pub const HISTORY_LEN: usize = 60;
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_interval_secs() -> u64 {
+    2
+}
+
+/// What happens when a process tree goes over its memory budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitAction {
+    #[default]
+    Warn,
+    Kill,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    /// Memory budget per agent or terminal, its child processes included; 0 for none
+    #[serde(default)]
+    pub memory_limit_mb: u64,
+    #[serde(default)]
+    pub on_memory_limit: LimitAction,
+    /// Warn when CPU use stays above this percentage of one core for two samples; 0 never
+    #[serde(default)]
+    pub cpu_warn_percent: u32,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: default_interval_secs(),
+            memory_limit_mb: 0,
+            on_memory_limit: LimitAction::default(),
+            cpu_warn_percent: 0,
+        }
+    }
+}
+
+/// What is watched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    Agent(AgentId),
+    Terminal,
+}
+
+/// One sample of a process and everything it started
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    /// Percent of one core, so above 100 on several cores
+    pub cpu: f32,
+    pub memory: u64,
+    /// Processes below the root
+    pub children: usize,
+}
+
+impl Usage {
+    pub fn memory_mb(&self) -> f32 {
+        self.memory as f32 / (1024.0 * 1024.0)
+    }
+
+    /// e.g. "CPU 12% · 340 MB · 3 children"
+    pub fn summary(&self) -> String {
+        let children = match self.children {
+            0 => String::new(),
+            1 => " · 1 child".to_string(),
+            n => format!(" · {} children", n),
+        };
+        format!("CPU {:.0}% · {:.0} MB{}", self.cpu, self.memory_mb(), children)
+    }
+}
+
+/// A limit reached, reported once until usage drops back under it
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitEvent {
+    OverMemory { target: Target, usage: Usage, limit_mb: u64, killed: bool },
+    HighCpu { target: Target, usage: Usage },
+}
+
+/// What the sampler needs from a process
+#[derive(Debug, Clone, Copy)]
+struct ProcessInfo {
+    pid: u32,
+    parent: Option<u32>,
+    cpu: f32,
+    memory: u64,
+}
+
+/// `root` and its descendants, root first; empty if `root` isn't running
+fn process_tree(processes: &[ProcessInfo], root: u32) -> Vec<ProcessInfo> {
+    let mut children: HashMap<u32, Vec<&ProcessInfo>> = HashMap::new();
+    for process in processes {
+        if let Some(parent) = process.parent {
+            children.entry(parent).or_default().push(process);
+        }
+    }
+
+    let mut tree: Vec<ProcessInfo> = processes.iter().filter(|process| process.pid == root).copied().collect();
+    let mut next = 0;
+    while let Some(process) = tree.get(next) {
+        let pid = process.pid;
+        tree.extend(children.get(&pid).into_iter().flatten().map(|child| **child));
+        next += 1;
+    }
+    tree
+}
+
+fn tree_usage(tree: &[ProcessInfo]) -> Option<Usage> {
+    (!tree.is_empty()).then(|| Usage {
+        cpu: tree.iter().map(|process| process.cpu).sum(),
+        memory: tree.iter().map(|process| process.memory).sum(),
+        children: tree.len() - 1,
+    })
+}
+
+/// Which limits `usage` is over, given the previous sample for the CPU check
+fn over_limits(config: &MonitorConfig, usage: &Usage, previous: Option<&Usage>) -> (bool, bool) {
+    let memory = config.memory_limit_mb > 0 && usage.memory > config.memory_limit_mb * 1024 * 1024;
+    let cpu_limit = config.cpu_warn_percent as f32;
+    let cpu = config.cpu_warn_percent > 0 && usage.cpu > cpu_limit && previous.is_some_and(|p| p.cpu > cpu_limit);
+    (memory, cpu)
+}
+
+/// Samples the targets every `interval_secs` until dropped
+pub struct ResourceMonitor {
+    config: Arc<Mutex<MonitorConfig>>,
+    targets: Arc<Mutex<HashMap<Target, u32>>>,
+    history: Arc<Mutex<HashMap<Target, VecDeque<Usage>>>>,
+    events: mpsc::Receiver<LimitEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ResourceMonitor {
+    pub fn start(config: &MonitorConfig, ctx: egui::Context) -> Self {
+        let config = Arc::new(Mutex::new(config.clone()));
+        let targets = Arc::new(Mutex::new(HashMap::new()));
+        let history = Arc::new(Mutex::new(HashMap::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (tx, events) = mpsc::channel();
+
+        let sampler = Sampler {
+            config: Arc::clone(&config),
+            system: System::new(),
+            targets: Arc::clone(&targets),
+            history: Arc::clone(&history),
+            over: HashMap::new(),
+            events: tx,
+        };
+        let thread_stop = Arc::clone(&stop);
+        std::thread::spawn(move || sampler.run(&thread_stop, &ctx));
+
+        Self { config, targets, history, events, stop }
+    }
+
+    /// Use new limits and interval from the next sample on
+    pub fn set_config(&self, config: &MonitorConfig) {
+        if let Ok(mut current) = self.config.lock() {
+            *current = config.clone();
+        }
+    }
+
+    /// The root process of each target; targets not listed are no longer sampled
+    pub fn set_targets(&self, targets: HashMap<Target, u32>) {
+        if let Ok(mut current) = self.targets.lock() {
+            *current = targets;
+        }
+    }
+
+    /// Samples for `target`, oldest first
+    pub fn history(&self, target: Target) -> Vec<Usage> {
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        history.get(&target).map(|samples| samples.iter().copied().collect()).unwrap_or_default()
+    }
+
+    /// Limits reached since the last call
+    pub fn events(&self) -> Vec<LimitEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+impl Drop for ResourceMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+struct Sampler {
+    config: Arc<Mutex<MonitorConfig>>,
+    system: System,
+    targets: Arc<Mutex<HashMap<Target, u32>>>,
+    history: Arc<Mutex<HashMap<Target, VecDeque<Usage>>>>,
+    /// Limits each target is over, so each breach is reported once
+    over: HashMap<Target, (bool, bool)>,
+    events: mpsc::Sender<LimitEvent>,
+}
+
+impl Sampler {
+    fn run(mut self, stop: &AtomicBool, ctx: &egui::Context) {
+        while !stop.load(Ordering::SeqCst) {
+            let config = self.config.lock().map(|config| config.clone()).unwrap_or_default();
+            self.sample(&config);
+            ctx.request_repaint();
+
+            // Sleep in steps so dropping the monitor ends the thread soon
+            let mut slept = Duration::ZERO;
+            let interval = Duration::from_secs(config.interval_secs.max(1));
+            while slept < interval && !stop.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(200));
+                slept += Duration::from_millis(200);
+            }
+        }
+    }
+
+    fn sample(&mut self, config: &MonitorConfig) {
+        let targets = self.targets.lock().map(|targets| targets.clone()).unwrap_or_default();
+        if targets.is_empty() {
+            self.history.lock().unwrap_or_else(|e| e.into_inner()).clear();
+            return;
+        }
+
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new().with_cpu().with_memory(),
+        );
+        let processes: Vec<ProcessInfo> = self
+            .system
+            .processes()
+            .values()
+            // Threads are listed as processes on Linux; their use is already the process's
+            .filter(|process| process.thread_kind().is_none())
+            .map(|process| ProcessInfo {
+                pid: process.pid().as_u32(),
+                parent: process.parent().map(Pid::as_u32),
+                cpu: process.cpu_usage(),
+                memory: process.memory(),
+            })
+            .collect();
+
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        history.retain(|target, _| targets.contains_key(target));
+        self.over.retain(|target, _| targets.contains_key(target));
+
+        for (target, root) in targets {
+            let tree = process_tree(&processes, root);
+            let Some(usage) = tree_usage(&tree) else {
+                continue;
+            };
+            let samples = history.entry(target).or_default();
+            let (memory, cpu) = over_limits(config, &usage, samples.back());
+            samples.push_back(usage);
+            if samples.len() > HISTORY_LEN {
+                samples.pop_front();
+            }
+
+            let (was_memory, was_cpu) = self.over.get(&target).copied().unwrap_or_default();
+            if memory && !was_memory {
+                let killed = config.on_memory_limit == LimitAction::Kill && self.kill(&tree);
+                let limit_mb = config.memory_limit_mb;
+                let _ = self.events.send(LimitEvent::OverMemory { target, usage, limit_mb, killed });
+            }
+            if cpu && !was_cpu {
+                let _ = self.events.send(LimitEvent::HighCpu { target, usage });
+            }
+            self.over.insert(target, (memory, cpu));
+        }
+    }
+
+    /// Kill the children before the root so none are left behind
+    fn kill(&self, tree: &[ProcessInfo]) -> bool {
+        let mut killed = false;
+        for process in tree.iter().rev() {
+            if let Some(process) = self.system.process(Pid::from_u32(process.pid)) {
+                killed |= process.kill();
+            }
+        }
+        killed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, parent: Option<u32>, cpu: f32, memory_mb: u64) -> ProcessInfo {
+        ProcessInfo { pid, parent, cpu, memory: memory_mb * 1024 * 1024 }
+    }
+
+    #[test]
+    fn test_tree_usage() {
+        let processes = [
+            process(1, None, 1.0, 10),
+            process(100, Some(1), 5.0, 100),
+            process(101, Some(100), 50.0, 200),
+            process(102, Some(101), 25.0, 300),
+            process(200, Some(1), 90.0, 1000),
+        ];
+        let tree = process_tree(&processes, 100);
+        assert_eq!(tree.iter().map(|p| p.pid).collect::<Vec<_>>(), [100, 101, 102]);
+
+        let usage = tree_usage(&tree).unwrap();
+        assert_eq!(usage.cpu, 80.0);
+        assert_eq!(usage.memory_mb(), 600.0);
+        assert_eq!(usage.children, 2);
+        assert_eq!(usage.summary(), "CPU 80% · 600 MB · 2 children");
+
+        assert!(tree_usage(&process_tree(&processes, 999)).is_none());
+    }
+
+    #[test]
+    fn test_over_limits() {
+        let config = MonitorConfig { memory_limit_mb: 500, cpu_warn_percent: 90, ..MonitorConfig::default() };
+        let usage = |cpu, memory_mb: u64| Usage { cpu, memory: memory_mb * 1024 * 1024, children: 0 };
+
+        assert_eq!(over_limits(&config, &usage(10.0, 400), None), (false, false));
+        assert_eq!(over_limits(&config, &usage(10.0, 600), None), (true, false));
+        // One busy sample is a spike, two in a row a runaway
+        assert_eq!(over_limits(&config, &usage(150.0, 100), Some(&usage(10.0, 100))), (false, false));
+        assert_eq!(over_limits(&config, &usage(150.0, 100), Some(&usage(120.0, 100))), (false, true));
+
+        let unlimited = MonitorConfig::default();
+        assert_eq!(over_limits(&unlimited, &usage(400.0, 90_000), Some(&usage(400.0, 90_000))), (false, false));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::agent::{AgentConfig, AgentId, AgentType};
+use crate::config::AppConfig;
+use crate::telegram::TelegramCredentials;
+use crate::tts::TTSConfig;
+use anyhow::Result;
+use std::path::Path;
+
+/// Spoken when testing a voice
+pub const TEST_PHRASE: &str = "Hello! This is how your agents will sound.";
+
+/// Whether the dashboard has never been configured
+pub fn needs_setup() -> bool {
+    AppConfig::config_file().map_or(true, |file| !file.exists())
+}
+
+/// Piper voices in `model_dir`, by model file name
+pub fn list_voices(model_dir: &Path) -> Vec<String> {
+    let mut voices: Vec<String> = std::fs::read_dir(model_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "onnx"))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    voices.sort();
+
+    if voices.is_empty() {
+        voices.push(TTSConfig::default().selected_voice);
+    }
+    voices
+}
+
+/// The first agent, as entered in the wizard
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentDraft {
+    pub name: String,
+    pub host: String,
+    pub model: String,
+}
+
+impl Default for AgentDraft {
+    fn default() -> Self {
+        Self {
+            name: "Assistant".to_string(),
+            host: "http://localhost:11434".to_string(),
+            model: "llama3.2".to_string(),
+        }
+    }
+}
+
+impl AgentDraft {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Give the agent a name".to_string());
+        }
+        if !self.host.starts_with("http://") && !self.host.starts_with("https://") {
+            return Err("Ollama host must be an http:// or https:// URL".to_string());
+        }
+        if self.model.trim().is_empty() {
+            return Err("Choose a model".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn to_config(&self) -> AgentConfig {
+        AgentConfig {
+            id: AgentId::new(),
+            name: self.name.trim().to_string(),
+            agent_type: AgentType::Ollama,
+            config_data: serde_json::json!({
+                "host": self.host.trim().trim_end_matches('/'),
+                "model": self.model.trim(),
+            }),
+            sandbox: Default::default(),
+            context: Default::default(),
+        }
+    }
+}
+
+/// Everything collected by the setup wizard
+#[derive(Debug, Clone)]
+pub struct Setup {
+    /// Credentials to save for the chat CLI, unless Telegram was skipped
+    pub telegram: Option<TelegramCredentials>,
+    pub tts: TTSConfig,
+    /// The first agent, unless skipped
+    pub agent: Option<AgentDraft>,
+}
+
+impl Setup {
+    /// Apply the setup to `config`, returning the created agent
+    pub fn apply(&self, config: &mut AppConfig) -> Option<AgentConfig> {
+        let mut tts = self.tts.clone();
+        tts.validate();
+        config.tts = tts;
+
+        let agent = self.agent.as_ref().map(AgentDraft::to_config);
+        if let Some(agent) = &agent {
+            config.add_agent(agent.clone());
+        }
+        agent
+    }
+
+    /// Write the Telegram and dashboard config files
+    pub fn write(&self, config: &AppConfig) -> Result<()> {
+        if let Some(telegram) = &self.telegram {
+            telegram.save()?;
+        }
+        config.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_voices() {
+        let dir = std::env::temp_dir().join(format!("dashboard-voices-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(list_voices(&dir), vec!["default".to_string()]);
+
+        for file in ["en_US-lessac-medium.onnx", "en_US-lessac-medium.onnx.json", "de_DE-thorsten-low.onnx"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        assert_eq!(list_voices(&dir), vec!["de_DE-thorsten-low", "en_US-lessac-medium"]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_agent_draft() {
+        let mut draft = AgentDraft {
+            host: "http://gpu-box:11434/".to_string(),
+            ..AgentDraft::default()
+        };
+        assert!(draft.validate().is_ok());
+        let config = draft.to_config();
+        assert_eq!(config.agent_type, AgentType::Ollama);
+        assert_eq!(config.config_data["host"], "http://gpu-box:11434");
+
+        draft.host = "gpu-box".to_string();
+        assert!(draft.validate().is_err());
+    }
+
+    #[test]
+    fn test_apply_setup() {
+        let setup = Setup {
+            telegram: None,
+            tts: TTSConfig {
+                enabled: true,
+                playback_speed: 5.0,
+                ..TTSConfig::default()
+            },
+            agent: Some(AgentDraft::default()),
+        };
+        let mut config = AppConfig::default();
+        let agent = setup.apply(&mut config).unwrap();
+
+        assert!(config.tts.enabled);
+        assert_eq!(config.tts.playback_speed, 2.0);
+        assert_eq!(config.agents.len(), 1);
+        assert_eq!(config.agents[0].id, agent.id);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::sources::{message_body, message_sender, EVENT_SCHEMA_VERSION};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+
+/// A notify or tts dispatch printed by `chat pipeline run --emit`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PipelineEvent {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub pipeline: String,
+    /// "notify" or "tts"
+    pub route: String,
+    #[serde(default)]
+    pub critical: bool,
+    #[serde(default)]
+    pub voice: Option<String>,
+    /// `source:chat_id` key used for per-chat quiet hours
+    pub chat: String,
+    #[serde(default)]
+    pub chat_title: Option<String>,
+    pub text: String,
+    /// The dispatched chat message as serialized by the chat crate
+    #[serde(default)]
+    pub message: serde_json::Value,
+}
+
+impl PipelineEvent {
+    /// Sender display name, falling back to the username
+    pub fn sender(&self) -> String {
+        message_sender(&self.message)
+    }
+
+    /// Message text or media caption, empty for other content
+    pub fn body(&self) -> String {
+        message_body(&self.message)
+    }
+}
+
+/// Runs the message pipelines from `~/.config/chat/pipelines.yaml` through the chat CLI.
+///
+/// Filters, transforms, webhook and store routes run in the child process; notify and
+/// tts routes come back as events for the dashboard to show and speak.
+pub struct PipelineRunner {
+    child: Child,
+    events: mpsc::Receiver<PipelineEvent>,
+}
+
+impl PipelineRunner {
+    /// Start `chat pipeline run --emit`, repainting `ctx` whenever an event arrives
+    pub fn start(ctx: egui::Context) -> Result<Self> {
+        let mut child = Command::new(crate::config::paths::helper("chat"))
+            .args(["pipeline", "run", "--emit"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .context("Failed to run chat CLI")?;
+
+        let stdout = child.stdout.take().context("chat CLI has no stdout")?;
+        let (tx, events) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                match serde_json::from_str::<PipelineEvent>(&line) {
+                    Ok(event) if event.schema_version > EVENT_SCHEMA_VERSION => {
+                        log::warn!("Ignoring pipeline event with newer schema version {}", event.schema_version);
+                    }
+                    Ok(event) => {
+                        if tx.send(event).is_err() {
+                            break;
+                        }
+                        ctx.request_repaint();
+                    }
+                    Err(e) => log::warn!("Ignoring pipeline output '{}': {}", line, e),
+                }
+            }
+        });
+
+        Ok(Self { child, events })
+    }
+
+    /// Events received since the last call
+    pub fn take_events(&self) -> Vec<PipelineEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// The exit status if the child process has stopped
+    pub fn exited(&mut self) -> Option<std::process::ExitStatus> {
+        self.child.try_wait().ok().flatten()
+    }
+}
+
+impl Drop for PipelineRunner {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
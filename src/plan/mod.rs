@@ -1,3 +1,4 @@
+pub mod todos;
 pub mod types;
 
 pub use types::Plan;
@@ -0,0 +1,148 @@
+//! Action items the chat CLI found in chats (`chat todos`), turned into plan steps so
+//! agents can pick them up
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::mpsc;
+
+use super::types::{Plan, PlanStep};
+use crate::agent::AgentId;
+
+/// Title of the plan holding an agent's chat todos
+pub const PLAN_TITLE: &str = "Chat todos";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatTodo {
+    pub id: String,
+    pub chat: String,
+    #[serde(default)]
+    pub chat_title: Option<String>,
+    pub sender: String,
+    pub task: String,
+    pub text: String,
+    pub sent: DateTime<Utc>,
+    #[serde(default)]
+    pub done: Option<DateTime<Utc>>,
+}
+
+impl ChatTodo {
+    /// What an agent is sent when it picks up the todo
+    pub fn agent_message(&self) -> String {
+        format!(
+            "{} asked in {} on {}:\n\n\"{}\"\n\nTask: {}",
+            self.sender,
+            self.chat_title.as_deref().unwrap_or(&self.chat),
+            self.sent.format("%Y-%m-%d"),
+            self.text,
+            self.task
+        )
+    }
+}
+
+/// Todos file written by the chat CLI (`~/.config/chat/todos.json`)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChatTodos {
+    #[serde(default)]
+    todos: Vec<ChatTodo>,
+}
+
+impl ChatTodos {
+    pub fn load() -> Self {
+        dirs::config_dir()
+            .map(|d| Self::load_from(&d.join("chat").join("todos.json")))
+            .unwrap_or_default()
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ChatTodo> {
+        self.todos.iter().find(|todo| todo.id == id)
+    }
+
+    /// A plan for `agent` with a step per open todo. Steps already in `previous`, the
+    /// agent's last chat todos plan, keep their status.
+    pub fn plan(&self, agent: AgentId, previous: Option<&Plan>) -> Plan {
+        let mut plan = Plan::new(
+            agent,
+            PLAN_TITLE.to_string(),
+            "Things people asked for in chats, found by `chat todos`".to_string(),
+        );
+        for todo in self.todos.iter().filter(|todo| todo.done.is_none()) {
+            let mut step = PlanStep::new(format!("{} ({})", todo.task, todo.sender));
+            step.todo_id = Some(todo.id.clone());
+            if let Some(known) = previous
+                .into_iter()
+                .flat_map(|plan| &plan.steps)
+                .find(|known| known.todo_id == step.todo_id)
+            {
+                step.status = known.status.clone();
+            }
+            plan.add_step(step);
+        }
+        plan
+    }
+}
+
+/// Mark a todo done through the chat CLI, in the background
+pub fn mark_done(id: &str) -> mpsc::Receiver<Result<(), String>> {
+    let id = id.to_string();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let result = std::process::Command::new(crate::config::paths::helper("chat"))
+            .args(["todos", "--done", &id])
+            .output()
+            .map_err(|e| format!("Failed to run chat CLI: {}", e))
+            .and_then(|output| {
+                if output.status.success() {
+                    Ok(())
+                } else {
+                    Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+                }
+            });
+        let _ = tx.send(result);
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::types::PlanStepStatus;
+
+    #[test]
+    fn test_plan() {
+        let json = r#"{"todos": [
+            {"id": "aa", "chat": "telegram:1", "chat_title": "Bob", "message_id": "1", "sender": "Bob",
+             "task": "Send the report", "text": "can you send the report?", "sent": "2025-01-15T12:00:00Z"},
+            {"id": "bb", "chat": "telegram:1", "message_id": "2", "sender": "Bob", "task": "Call Ana",
+             "text": "please call Ana", "sent": "2025-01-15T12:05:00Z", "done": "2025-01-16T08:00:00Z"},
+            {"id": "cc", "chat": "telegram:2", "message_id": "3", "sender": "Eve", "task": "Fix the build",
+             "text": "@me please fix the build", "sent": "2025-01-15T13:00:00Z"}
+        ]}"#;
+        let path = std::env::temp_dir().join(format!("todos-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, json).unwrap();
+        let todos = ChatTodos::load_from(&path);
+        std::fs::remove_file(&path).ok();
+
+        let agent = AgentId::new();
+        let mut first = todos.plan(agent, None);
+        assert_eq!(first.steps.len(), 2);
+        assert_eq!(first.steps[0].description, "Send the report (Bob)");
+        assert_eq!(first.steps[1].todo_id.as_deref(), Some("cc"));
+
+        first.steps[1].status = PlanStepStatus::InProgress;
+        let second = todos.plan(agent, Some(&first));
+        assert_eq!(second.steps[0].status, PlanStepStatus::Pending);
+        assert_eq!(second.steps[1].status, PlanStepStatus::InProgress);
+
+        let message = todos.get("aa").unwrap().agent_message();
+        assert!(message.starts_with("Bob asked in Bob on 2025-01-15"));
+        assert!(message.ends_with("Task: Send the report"));
+    }
+}
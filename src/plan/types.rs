@@ -32,6 +32,9 @@ pub struct PlanStep {
     pub description: String,
     pub status: PlanStepStatus,
     pub sub_steps: Vec<PlanStep>,
+    /// Chat todo this step came from (`chat todos`); completing the step marks it done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub todo_id: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -41,6 +44,7 @@ impl PlanStep {
             description,
             status: PlanStepStatus::Pending,
             sub_steps: Vec::new(),
+            todo_id: None,
         }
     }
 }
@@ -0,0 +1,311 @@
+//! Optional sandboxing for the processes the dashboard starts: agents and the Term tab.
+//!
+//! On Linux a process can run under bubblewrap or firejail, with the filesystem read-only
+//! apart from its working directory and chosen paths, and optionally without network.
+//! The environment can be cut down to a few basics on any platform.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Passed on even with a clean environment, as little works without them
+const BASE_ENV: [&str; 8] = ["PATH", "HOME", "USER", "LOGNAME", "LANG", "TERM", "SHELL", "TMPDIR"];
+
+fn default_true() -> bool {
+    true
+}
+
+/// What wraps the process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SandboxBackend {
+    /// `bwrap`, Linux only
+    #[default]
+    Bubblewrap,
+    /// `firejail`, Linux only
+    Firejail,
+    /// No wrapper: only the environment is restricted, on any platform
+    Environment,
+}
+
+impl SandboxBackend {
+    pub const ALL: [SandboxBackend; 3] = [Self::Bubblewrap, Self::Firejail, Self::Environment];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Bubblewrap => "bubblewrap",
+            Self::Firejail => "firejail",
+            Self::Environment => "environment only",
+        }
+    }
+
+    fn binary(self) -> Option<&'static str> {
+        match self {
+            Self::Bubblewrap => Some("bwrap"),
+            Self::Firejail => Some("firejail"),
+            Self::Environment => None,
+        }
+    }
+}
+
+/// How to sandbox a process; off by default
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub backend: SandboxBackend,
+    /// Only writable in the working directory and `writable_paths`
+    #[serde(default = "default_true")]
+    pub confine_to_workspace: bool,
+    /// Writable besides the working directory, e.g. "~/.claude"
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+    /// Read-only even when not confined to the workspace
+    #[serde(default)]
+    pub read_only_paths: Vec<String>,
+    #[serde(default = "default_true")]
+    pub network: bool,
+    /// Start from an environment with only PATH, HOME and a few other basics
+    #[serde(default)]
+    pub clean_env: bool,
+    /// Passed on as well with a clean environment, e.g. "ANTHROPIC_API_KEY"
+    #[serde(default)]
+    pub env_allow: Vec<String>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: SandboxBackend::default(),
+            confine_to_workspace: true,
+            writable_paths: Vec::new(),
+            read_only_paths: Vec::new(),
+            network: true,
+            clean_env: false,
+            env_allow: Vec::new(),
+        }
+    }
+}
+
+/// What to actually run: the program, wrapped when sandboxed, and its environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Launch {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Replaces the inherited environment when set
+    pub env: Option<Vec<(String, String)>>,
+}
+
+impl Launch {
+    pub fn apply_env(&self, command: &mut std::process::Command) {
+        if let Some(env) = &self.env {
+            command.env_clear().envs(env.iter().map(|(key, value)| (key, value)));
+        }
+    }
+
+    pub fn apply_env_pty(&self, command: &mut portable_pty::CommandBuilder) {
+        if let Some(env) = &self.env {
+            command.env_clear();
+            for (key, value) in env {
+                command.env(key, value);
+            }
+        }
+    }
+}
+
+impl SandboxConfig {
+    /// How `program` is launched in `cwd` under this config. Fails when the backend
+    /// isn't installed or doesn't work on this platform, rather than running unconfined.
+    pub fn wrap(&self, program: &str, args: &[String], cwd: Option<&Path>) -> Result<Launch> {
+        if !self.enabled {
+            return Ok(Launch {
+                program: program.to_string(),
+                args: args.to_vec(),
+                env: None,
+            });
+        }
+
+        let env = self.clean_env.then(|| self.environment(std::env::vars()));
+        let Some(binary) = self.backend.binary() else {
+            return Ok(Launch {
+                program: program.to_string(),
+                args: args.to_vec(),
+                env,
+            });
+        };
+        if !cfg!(target_os = "linux") {
+            anyhow::bail!("Sandboxing with {} needs Linux", self.backend.label());
+        }
+        find_in_path(binary).with_context(|| format!("{} is not installed ({} not in PATH)", self.backend.label(), binary))?;
+
+        let mut wrapped = self.wrapper_args(cwd);
+        wrapped.push(program.to_string());
+        wrapped.extend(args.iter().cloned());
+        Ok(Launch {
+            program: binary.to_string(),
+            args: wrapped,
+            env,
+        })
+    }
+
+    /// One line for the UI, e.g. "bubblewrap, workspace only, no network"
+    pub fn summary(&self) -> String {
+        if !self.enabled {
+            return "not sandboxed".to_string();
+        }
+        let mut parts = vec![self.backend.label().to_string()];
+        if self.backend != SandboxBackend::Environment {
+            if self.confine_to_workspace {
+                parts.push("workspace only".to_string());
+            }
+            if !self.network {
+                parts.push("no network".to_string());
+            }
+        }
+        if self.clean_env {
+            parts.push("clean environment".to_string());
+        }
+        parts.join(", ")
+    }
+
+    /// Wrapper options before the program
+    fn wrapper_args(&self, cwd: Option<&Path>) -> Vec<String> {
+        let cwd = cwd.map(|dir| dir.display().to_string());
+        let writable = self.writable_paths.iter().map(|path| expand_home(path));
+        let read_only = self.read_only_paths.iter().map(|path| expand_home(path));
+        let mut args: Vec<String> = Vec::new();
+        let mut push = |items: &[&str]| args.extend(items.iter().map(|item| item.to_string()));
+
+        match self.backend {
+            SandboxBackend::Bubblewrap => {
+                push(&[if self.confine_to_workspace { "--ro-bind" } else { "--bind" }, "/", "/"]);
+                push(&["--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"]);
+                if let Some(cwd) = cwd.as_deref().filter(|_| self.confine_to_workspace) {
+                    push(&["--bind", cwd, cwd]);
+                }
+                for path in writable {
+                    push(&["--bind-try", path.as_str(), path.as_str()]);
+                }
+                for path in read_only {
+                    push(&["--ro-bind-try", path.as_str(), path.as_str()]);
+                }
+                if !self.network {
+                    push(&["--unshare-net"]);
+                }
+                push(&["--die-with-parent"]);
+                if let Some(cwd) = &cwd {
+                    push(&["--chdir", cwd]);
+                }
+                push(&["--"]);
+            }
+            SandboxBackend::Firejail => {
+                push(&["--quiet", "--noprofile", "--private-tmp"]);
+                // firejail can't make / read-only, so confinement covers the home directory
+                if self.confine_to_workspace {
+                    push(&["--read-only=~"]);
+                    if let Some(cwd) = &cwd {
+                        push(&[format!("--read-write={}", cwd).as_str()]);
+                    }
+                }
+                for path in writable {
+                    push(&[format!("--read-write={}", path).as_str()]);
+                }
+                for path in read_only {
+                    push(&[format!("--read-only={}", path).as_str()]);
+                }
+                if !self.network {
+                    push(&["--net=none"]);
+                }
+            }
+            SandboxBackend::Environment => {}
+        }
+        args
+    }
+
+    /// The variables of `vars` a clean environment keeps
+    fn environment(&self, vars: impl Iterator<Item = (String, String)>) -> Vec<(String, String)> {
+        vars.filter(|(key, _)| BASE_ENV.contains(&key.as_str()) || self.env_allow.iter().any(|allowed| allowed == key))
+            .collect()
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).display().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).map(|dir| dir.join(binary)).find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_runs_as_is() {
+        let launch = SandboxConfig::default().wrap("claude", &["--model".to_string()], None).unwrap();
+        assert_eq!(launch.program, "claude");
+        assert_eq!(launch.args, ["--model"]);
+        assert_eq!(launch.env, None);
+    }
+
+    #[test]
+    fn test_bubblewrap_args() {
+        let config = SandboxConfig {
+            enabled: true,
+            network: false,
+            writable_paths: vec!["/var/cache/agent".to_string()],
+            ..SandboxConfig::default()
+        };
+        let args = config.wrapper_args(Some(Path::new("/work/repo")));
+        let joined = args.join(" ");
+        assert!(joined.starts_with("--ro-bind / / --dev /dev --proc /proc --tmpfs /tmp --bind /work/repo /work/repo"));
+        assert!(joined.contains("--bind-try /var/cache/agent /var/cache/agent"));
+        assert!(joined.contains("--unshare-net"));
+        assert!(joined.ends_with("--chdir /work/repo --"));
+
+        let open = SandboxConfig { confine_to_workspace: false, network: true, ..config };
+        let joined = open.wrapper_args(Some(Path::new("/work/repo"))).join(" ");
+        assert!(joined.starts_with("--bind / /"));
+        assert!(!joined.contains("--unshare-net"));
+    }
+
+    #[test]
+    fn test_firejail_args() {
+        let config = SandboxConfig {
+            enabled: true,
+            backend: SandboxBackend::Firejail,
+            network: false,
+            ..SandboxConfig::default()
+        };
+        assert_eq!(
+            config.wrapper_args(Some(Path::new("/work/repo"))),
+            ["--quiet", "--noprofile", "--private-tmp", "--read-only=~", "--read-write=/work/repo", "--net=none"]
+        );
+    }
+
+    #[test]
+    fn test_clean_environment() {
+        let config = SandboxConfig {
+            enabled: true,
+            backend: SandboxBackend::Environment,
+            clean_env: true,
+            env_allow: vec!["ANTHROPIC_API_KEY".to_string()],
+            ..SandboxConfig::default()
+        };
+        let vars = [("PATH", "/bin"), ("ANTHROPIC_API_KEY", "k"), ("AWS_SECRET_ACCESS_KEY", "s")]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+        let kept: Vec<_> = config.environment(vars.into_iter()).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(kept, ["PATH", "ANTHROPIC_API_KEY"]);
+
+        let launch = config.wrap("sh", &[], None).unwrap();
+        assert_eq!(launch.program, "sh");
+        assert!(launch.env.is_some());
+        assert_eq!(config.summary(), "environment only, clean environment");
+    }
+}
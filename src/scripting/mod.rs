@@ -0,0 +1,374 @@
+use anyhow::{Context, Result};
+use rhai::{Dynamic, Engine, ImmutableString, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Events scripts can hook into, each handled by a function of the same name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// A chat message routed to the dashboard by a pipeline
+    Message,
+    /// A notification about to be shown (reminders, pipeline notifications)
+    Alert,
+    /// A reply from an agent
+    AgentOutput,
+}
+
+impl Hook {
+    pub const ALL: [Hook; 3] = [Hook::Message, Hook::Alert, Hook::AgentOutput];
+
+    pub fn function_name(&self) -> &'static str {
+        match self {
+            Hook::Message => "on_message",
+            Hook::Alert => "on_alert",
+            Hook::AgentOutput => "on_agent_output",
+        }
+    }
+}
+
+/// Resource limits applied to every hook call of a script
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScriptLimits {
+    /// Rhai operations before the call is aborted
+    pub max_operations: u64,
+    /// Wall-clock time before the call is aborted
+    pub timeout_ms: u64,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 100_000,
+            timeout_ms: 50,
+        }
+    }
+}
+
+/// What the scripts decided about an event
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookOutcome {
+    /// Drop the event: no notification, speech or unread count
+    pub suppressed: bool,
+    /// Replacement text for the event
+    pub text: Option<String>,
+    /// Texts to read aloud
+    pub speak: Vec<String>,
+    /// Derived notifications to add to the chat panel
+    pub notifications: Vec<String>,
+    /// Scripts that failed, as `name: error`
+    pub errors: Vec<String>,
+}
+
+/// Actions recorded by the script API functions during a call
+#[derive(Debug, Default)]
+struct Actions {
+    suppressed: bool,
+    text: Option<String>,
+    speak: Vec<String>,
+    notifications: Vec<String>,
+}
+
+struct Script {
+    name: String,
+    ast: AST,
+    hooks: Vec<Hook>,
+}
+
+/// A script that failed to load, shown in the editor panel
+#[derive(Debug, Clone)]
+pub struct ScriptError {
+    pub name: String,
+    pub error: String,
+}
+
+/// Sandboxed Rhai engine running user scripts from the scripts directory.
+///
+/// Scripts can only call the functions registered here (`suppress`, `set_text`,
+/// `speak`, `notify`, `log`); Rhai itself has no file, network or process access.
+pub struct ScriptHost {
+    engine: Engine,
+    scripts: Vec<Script>,
+    errors: Vec<ScriptError>,
+    limits: BTreeMap<String, ScriptLimits>,
+    actions: Arc<Mutex<Actions>>,
+    deadline: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ScriptHost {
+    /// Directory holding `*.rhai` scripts (`~/.config/agent-dashboard/scripts`)
+    pub fn scripts_dir() -> Result<PathBuf> {
+        Ok(crate::config::AppConfig::config_dir()?.join("scripts"))
+    }
+
+    pub fn new(limits: BTreeMap<String, ScriptLimits>) -> Self {
+        let actions = Arc::new(Mutex::new(Actions::default()));
+        let deadline: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+
+        let mut engine = Engine::new();
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(64 * 1024);
+        engine.set_max_array_size(10_000);
+        engine.set_max_map_size(10_000);
+        engine.disable_symbol("eval");
+        engine.on_print(|text| log::info!("script: {}", text));
+        engine.on_debug(|text, _, _| log::debug!("script: {}", text));
+
+        let progress_deadline = Arc::clone(&deadline);
+        engine.on_progress(move |operations| {
+            if operations % 1024 != 0 {
+                return None;
+            }
+            let deadline = progress_deadline.lock().ok().and_then(|d| *d);
+            match deadline {
+                Some(deadline) if Instant::now() > deadline => Some(Dynamic::UNIT),
+                _ => None,
+            }
+        });
+
+        let a = Arc::clone(&actions);
+        engine.register_fn("suppress", move || {
+            if let Ok(mut actions) = a.lock() {
+                actions.suppressed = true;
+            }
+        });
+        let a = Arc::clone(&actions);
+        engine.register_fn("set_text", move |text: ImmutableString| {
+            if let Ok(mut actions) = a.lock() {
+                actions.text = Some(text.to_string());
+            }
+        });
+        let a = Arc::clone(&actions);
+        engine.register_fn("speak", move |text: ImmutableString| {
+            if let Ok(mut actions) = a.lock() {
+                actions.speak.push(text.to_string());
+            }
+        });
+        let a = Arc::clone(&actions);
+        engine.register_fn("notify", move |text: ImmutableString| {
+            if let Ok(mut actions) = a.lock() {
+                actions.notifications.push(text.to_string());
+            }
+        });
+        engine.register_fn("log", |text: ImmutableString| log::info!("script: {}", text));
+
+        Self {
+            engine,
+            scripts: Vec::new(),
+            errors: Vec::new(),
+            limits,
+            actions,
+            deadline,
+        }
+    }
+
+    /// Load every script in the scripts directory
+    pub fn load(limits: BTreeMap<String, ScriptLimits>) -> Result<Self> {
+        let mut host = Self::new(limits);
+        host.load_dir(&Self::scripts_dir()?)?;
+        Ok(host)
+    }
+
+    /// Replace the loaded scripts with the `*.rhai` files in `dir`, sorted by name
+    pub fn load_dir(&mut self, dir: &Path) -> Result<()> {
+        self.scripts.clear();
+        self.errors.clear();
+
+        if !dir.exists() {
+            return Ok(());
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read scripts directory: {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let name = script_name(&path);
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| self.add_script(&name, &source));
+            if let Err(error) = result {
+                self.errors.push(ScriptError { name, error });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compile a script and add it; fails if it doesn't parse or defines no hooks
+    pub fn add_script(&mut self, name: &str, source: &str) -> std::result::Result<(), String> {
+        let ast = self.engine.compile(source).map_err(|e| e.to_string())?;
+
+        let hooks: Vec<Hook> = Hook::ALL
+            .into_iter()
+            .filter(|hook| ast.iter_functions().any(|f| f.name == hook.function_name() && f.params.len() == 1))
+            .collect();
+        if hooks.is_empty() {
+            return Err("defines none of on_message(msg), on_alert(alert), on_agent_output(output)".to_string());
+        }
+
+        self.scripts.retain(|s| s.name != name);
+        self.scripts.push(Script {
+            name: name.to_string(),
+            ast,
+            hooks,
+        });
+        Ok(())
+    }
+
+    /// Check that a script compiles, without loading it
+    pub fn check(&self, source: &str) -> std::result::Result<(), String> {
+        self.engine.compile(source).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    /// Scripts that failed to load
+    pub fn errors(&self) -> &[ScriptError] {
+        &self.errors
+    }
+
+    /// Run a hook in every script that defines it, in name order. Later scripts see
+    /// the text set by earlier ones; a suppressed event is not passed on.
+    pub fn run(&mut self, hook: Hook, event: Map) -> HookOutcome {
+        let mut outcome = HookOutcome::default();
+        let mut event = event;
+
+        for script in &self.scripts {
+            if !script.hooks.contains(&hook) {
+                continue;
+            }
+
+            let limits = self.limits.get(&script.name).copied().unwrap_or_default();
+            self.engine.set_max_operations(limits.max_operations);
+            if let Ok(mut deadline) = self.deadline.lock() {
+                *deadline = Some(Instant::now() + Duration::from_millis(limits.timeout_ms));
+            }
+            if let Ok(mut actions) = self.actions.lock() {
+                *actions = Actions::default();
+            }
+
+            let result = self.engine.call_fn::<Dynamic>(
+                &mut Scope::new(),
+                &script.ast,
+                hook.function_name(),
+                (event.clone(),),
+            );
+            if let Err(e) = result {
+                log::warn!("Script {} failed in {}: {}", script.name, hook.function_name(), e);
+                outcome.errors.push(format!("{}: {}", script.name, e));
+                continue;
+            }
+
+            let Ok(mut actions) = self.actions.lock() else {
+                continue;
+            };
+            outcome.speak.append(&mut actions.speak);
+            outcome.notifications.append(&mut actions.notifications);
+            if let Some(text) = actions.text.take() {
+                event.insert("text".into(), text.clone().into());
+                outcome.text = Some(text);
+            }
+            if actions.suppressed {
+                outcome.suppressed = true;
+                break;
+            }
+        }
+
+        outcome
+    }
+}
+
+/// Script name from its file name (`alerts.rhai` -> `alerts`)
+pub fn script_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Build an event map for a hook from string fields
+pub fn event<'a>(fields: impl IntoIterator<Item = (&'a str, Dynamic)>) -> Map {
+    fields.into_iter().map(|(key, value)| (key.into(), value)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(name: &str, source: &str) -> ScriptHost {
+        let mut host = ScriptHost::new(BTreeMap::new());
+        host.add_script(name, source).unwrap();
+        host
+    }
+
+    fn alert(text: &str) -> Map {
+        event([("text", Dynamic::from(text.to_string())), ("kind", Dynamic::from("reminder".to_string()))])
+    }
+
+    #[test]
+    fn test_hook_actions() {
+        let mut host = host(
+            "alerts",
+            r#"
+            fn on_alert(alert) {
+                if alert.text.contains("deploy") {
+                    set_text("🚀 " + alert.text);
+                    speak("Deploy finished");
+                    notify("Derived: " + alert.kind);
+                }
+            }
+            "#,
+        );
+
+        let outcome = host.run(Hook::Alert, alert("deploy done"));
+        assert_eq!(outcome.text.as_deref(), Some("🚀 deploy done"));
+        assert_eq!(outcome.speak, vec!["Deploy finished"]);
+        assert_eq!(outcome.notifications, vec!["Derived: reminder"]);
+        assert!(!outcome.suppressed);
+
+        assert_eq!(host.run(Hook::Alert, alert("lunch")), HookOutcome::default());
+        // Scripts without the hook are skipped
+        assert_eq!(host.run(Hook::AgentOutput, alert("deploy")), HookOutcome::default());
+    }
+
+    #[test]
+    fn test_suppress_stops_later_scripts() {
+        let mut host = host("a", r#"fn on_alert(alert) { suppress(); }"#);
+        host.add_script("b", r#"fn on_alert(alert) { notify("never"); }"#).unwrap();
+
+        let outcome = host.run(Hook::Alert, alert("x"));
+        assert!(outcome.suppressed);
+        assert!(outcome.notifications.is_empty());
+    }
+
+    #[test]
+    fn test_runaway_script_is_stopped() {
+        let spin = |limits: ScriptLimits| {
+            let mut host = ScriptHost::new(BTreeMap::from([("spin".to_string(), limits)]));
+            host.add_script("spin", r#"fn on_alert(alert) { loop { } }"#).unwrap();
+            host
+        };
+
+        let mut host = spin(ScriptLimits { max_operations: 10_000, timeout_ms: 1000 });
+        let started = Instant::now();
+        let outcome = host.run(Hook::Alert, alert("x"));
+        assert_eq!(outcome.errors.len(), 1);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // The time limit applies even when the operation budget is large
+        let mut host = spin(ScriptLimits { max_operations: 0, timeout_ms: 20 });
+        let outcome = host.run(Hook::Alert, alert("x"));
+        assert_eq!(outcome.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_script_without_hooks_rejected() {
+        let mut host = ScriptHost::new(BTreeMap::new());
+        assert!(host.add_script("empty", "let x = 1;").is_err());
+        assert!(host.add_script("broken", "fn on_alert(a) {").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Snapshots of the open session, saved every so often while the dashboard runs and
+//! removed when it closes normally. A snapshot still there at launch means the last
+//! run crashed or was killed, and is offered for restoring.
+
+use crate::agent::branch::Branches;
+use crate::agent::AgentId;
+use crate::config::AppConfig;
+use crate::ui::app::AppTab;
+use crate::ui::chat::ChatMessage;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often the session is saved
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+/// Chat messages kept in a snapshot, newest last
+pub const MAX_MESSAGES: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub saved_at: DateTime<Utc>,
+    pub tab: AppTab,
+    /// Agents that were connected, to connect again
+    pub active_agents: Vec<AgentId>,
+    pub selected_agent: Option<AgentId>,
+    pub broadcast_mode: bool,
+    pub chat_messages: Vec<ChatMessage>,
+    /// Branches of the agents' conversations, and the one shown for each
+    #[serde(default)]
+    pub branches: Branches,
+    /// Unsent text in the chat input
+    pub chat_input: String,
+    pub terminal_startup_command: String,
+    /// Whether the startup command was running, to run it again
+    pub terminal_running: bool,
+    pub terminal_input: String,
+}
+
+impl SessionSnapshot {
+    /// Whether there's anything to restore beyond what a fresh start has
+    pub fn is_worth_restoring(&self) -> bool {
+        !self.active_agents.is_empty()
+            || !self.chat_messages.is_empty()
+            || !self.chat_input.trim().is_empty()
+            || self.terminal_running
+    }
+
+    /// What restoring brings back, e.g. "2 agents, 31 messages, terminal: bash"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        match self.active_agents.len() {
+            0 => {}
+            1 => parts.push("1 agent".to_string()),
+            n => parts.push(format!("{} agents", n)),
+        }
+        match self.chat_messages.len() {
+            0 => {}
+            1 => parts.push("1 message".to_string()),
+            n => parts.push(format!("{} messages", n)),
+        }
+        if !self.chat_input.trim().is_empty() {
+            parts.push("an unsent message".to_string());
+        }
+        if self.terminal_running {
+            parts.push(format!("terminal: {}", self.terminal_startup_command));
+        }
+        parts.join(", ")
+    }
+}
+
+pub fn snapshot_path() -> Result<PathBuf> {
+    Ok(AppConfig::config_dir()?.join("session.json"))
+}
+
+/// Write through a temporary file, so a crash while saving keeps the previous snapshot
+pub fn save(snapshot: &SessionSnapshot, path: &Path) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec(snapshot)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// The snapshot left by a run that didn't close normally, if there is one worth restoring
+pub fn load(path: &Path) -> Result<Option<SessionSnapshot>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let snapshot: SessionSnapshot = serde_json::from_slice(&std::fs::read(path)?)?;
+    Ok(Some(snapshot).filter(SessionSnapshot::is_worth_restoring))
+}
+
+pub fn clear(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> SessionSnapshot {
+        SessionSnapshot {
+            saved_at: Utc::now(),
+            tab: AppTab::Term,
+            active_agents: vec![AgentId::new()],
+            selected_agent: None,
+            broadcast_mode: true,
+            chat_messages: vec![ChatMessage::new_user_message("hello".to_string(), None)],
+            branches: Branches::default(),
+            chat_input: "half a".to_string(),
+            terminal_startup_command: "bash".to_string(),
+            terminal_running: true,
+            terminal_input: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_save_load_clear() {
+        let path = std::env::temp_dir().join(format!("session-{}.json", uuid::Uuid::new_v4()));
+        assert!(load(&path).unwrap().is_none());
+
+        save(&snapshot(), &path).unwrap();
+        let loaded = load(&path).unwrap().unwrap();
+        assert_eq!(loaded.tab, AppTab::Term);
+        assert_eq!(loaded.chat_messages[0].content, "hello");
+        assert_eq!(loaded.summary(), "1 agent, 1 message, an unsent message, terminal: bash");
+
+        let empty = SessionSnapshot {
+            active_agents: Vec::new(),
+            chat_messages: Vec::new(),
+            chat_input: " ".to_string(),
+            terminal_running: false,
+            ..snapshot()
+        };
+        save(&empty, &path).unwrap();
+        assert!(load(&path).unwrap().is_none());
+
+        clear(&path).unwrap();
+        assert!(!path.exists());
+        clear(&path).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+//! Canned replies kept by the chat CLI (`chat snippet`), for the compose box and the
+//! Chats tab
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Snippets file written by the chat CLI (`~/.config/chat/snippets.json`)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Snippets {
+    #[serde(default)]
+    snippets: BTreeMap<String, String>,
+}
+
+impl Snippets {
+    pub fn load() -> Self {
+        let Some(path) = dirs::config_dir().map(|d| d.join("chat").join("snippets.json")) else {
+            return Self::default();
+        };
+
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snippets.is_empty()
+    }
+
+    /// (name, text) sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.snippets.iter().map(|(name, text)| (name.as_str(), text.as_str()))
+    }
+}
+
+/// `{date}` and `{time}` for now, which every snippet can use
+pub fn now_values() -> Vec<(&'static str, String)> {
+    let now = chrono::Local::now();
+    vec![("date", now.format("%Y-%m-%d").to_string()), ("time", now.format("%H:%M").to_string())]
+}
+
+/// `text` with `{name}` replaced where `values` has that name. Other placeholders are
+/// kept so they stand out in the draft, like `chat snippet show` does.
+pub fn render(text: &str, values: &[(&str, String)]) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        let after = &rest[open + 1..];
+        let value = after
+            .find('}')
+            .map(|close| &after[..close])
+            .and_then(|name| values.iter().find(|(key, _)| *key == name).map(|(_, value)| (name, value)));
+        match value {
+            Some((name, value)) => {
+                rendered.push_str(&rest[..open]);
+                rendered.push_str(value);
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                rendered.push_str(&rest[..=open]);
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let values = [("sender", "Ana".to_string()), ("date", "Monday".to_string())];
+        assert_eq!(
+            render("Hi {sender}, see you {date}. {unknown} {\"a\": 1}", &values),
+            "Hi Ana, see you Monday. {unknown} {\"a\": 1}"
+        );
+        assert_eq!(render("{{sender}}", &values), "{Ana}");
+        assert_eq!(render("{sender", &values), "{sender");
+
+        let snippets: Snippets = serde_json::from_str(r#"{"snippets": {"thanks": "Thanks {sender}!"}}"#).unwrap();
+        assert_eq!(snippets.iter().collect::<Vec<_>>(), vec![("thanks", "Thanks {sender}!")]);
+    }
+}
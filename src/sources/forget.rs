@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::IncomingMessage;
+
+/// A chat or person removed from local data with `chat cache forget`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForgetTarget {
+    Chat { source: String, chat_id: String },
+    User { source: String, user_id: String },
+}
+
+impl ForgetTarget {
+    /// Whether a message in `chat` (`source:chat_id`) sent by `sender_id` is the target's
+    pub fn covers(&self, chat: &str, sender_id: Option<&str>) -> bool {
+        let Some((source, chat_id)) = chat.split_once(':') else {
+            return false;
+        };
+        match self {
+            Self::Chat { source: target_source, chat_id: target_chat } => {
+                target_source.eq_ignore_ascii_case(source) && target_chat == chat_id
+            }
+            Self::User { source: target_source, user_id } => {
+                target_source.eq_ignore_ascii_case(source) && sender_id == Some(user_id.as_str())
+            }
+        }
+    }
+
+    pub fn covers_message(&self, message: &IncomingMessage) -> bool {
+        self.covers(&message.chat_key(), message.sender_id().as_deref())
+    }
+}
+
+/// A target as the chat CLI writes it to the log
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LoggedTarget {
+    /// `source:chat_id`
+    Chat(String),
+    User { source: String, user_id: String },
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum LoggedAction {
+    Forget,
+    Unforget,
+}
+
+#[derive(Deserialize)]
+struct LoggedRecord {
+    action: LoggedAction,
+    target: LoggedTarget,
+}
+
+/// The chat CLI's forget log (`<data dir>/chat/forget-log.jsonl`), reread when it changes
+/// so the dashboard can drop forgotten chats and people from what it keeps too
+pub struct ForgetLog {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    forgotten: Vec<ForgetTarget>,
+}
+
+impl ForgetLog {
+    pub fn open_default() -> Result<Self> {
+        let dir = dirs::data_dir().context("Failed to get data directory")?;
+        Ok(Self::open(dir.join("chat").join("forget-log.jsonl")))
+    }
+
+    /// Follow the log at `path`; nothing is read until the first `refresh`
+    pub fn open(path: PathBuf) -> Self {
+        Self { path, modified: None, forgotten: Vec::new() }
+    }
+
+    /// Reread the log if it changed since the last call, returning targets forgotten since
+    pub fn refresh(&mut self) -> Result<Vec<ForgetTarget>> {
+        let modified = match std::fs::metadata(&self.path) {
+            Ok(meta) => Some(meta.modified()?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).context("Failed to read forget log"),
+        };
+        if modified.is_none() || modified == self.modified {
+            return Ok(Vec::new());
+        }
+        self.modified = modified;
+
+        let forgotten = read_log(&self.path)?;
+        let added = forgotten.iter().filter(|target| !self.forgotten.contains(target)).cloned().collect();
+        self.forgotten = forgotten;
+        Ok(added)
+    }
+
+    /// Whether an incoming message is in a forgotten chat or by a forgotten person
+    pub fn covers(&self, message: &IncomingMessage) -> bool {
+        self.forgotten.iter().any(|target| target.covers_message(message))
+    }
+}
+
+/// Targets whose latest record in the log forgets them
+fn read_log(path: &Path) -> Result<Vec<ForgetTarget>> {
+    let contents = std::fs::read_to_string(path).context("Failed to read forget log")?;
+    let mut forgotten = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let record: LoggedRecord = serde_json::from_str(line).context("Failed to parse forget log")?;
+        let target = match record.target {
+            LoggedTarget::Chat(chat) => {
+                let Some((source, chat_id)) = chat.split_once(':') else {
+                    continue;
+                };
+                ForgetTarget::Chat { source: source.to_string(), chat_id: chat_id.to_string() }
+            }
+            LoggedTarget::User { source, user_id } => ForgetTarget::User { source, user_id },
+        };
+        forgotten.retain(|t| *t != target);
+        if record.action == LoggedAction::Forget {
+            forgotten.push(target);
+        }
+    }
+    Ok(forgotten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn incoming(chat_id: &str, sender_id: &str) -> IncomingMessage {
+        serde_json::from_value(serde_json::json!({
+            "source": "telegram",
+            "message": {"id": "1", "chat_id": chat_id, "sender": {"id": sender_id}, "content": {"Text": "hi"}},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_follows_the_log() {
+        let path = std::env::temp_dir().join(format!("forget-log-{}.jsonl", uuid::Uuid::new_v4().simple()));
+        let mut log = ForgetLog::open(path.clone());
+        assert!(log.refresh().unwrap().is_empty());
+
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"at":"2026-10-01T10:00:00Z","action":"forget","target":{"chat":"telegram:-100123"},"removed":{}}"#,
+                "\n",
+                r#"{"at":"2026-10-01T10:01:00Z","action":"forget","target":{"user":{"source":"telegram","user_id":"7"}}}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+        let added = log.refresh().unwrap();
+        assert_eq!(added.len(), 2);
+        assert!(log.covers(&incoming("-100123", "9")));
+        assert!(log.covers(&incoming("-100999", "7")));
+        assert!(!log.covers(&incoming("-100999", "9")));
+        assert!(log.refresh().unwrap().is_empty());
+
+        // Letting the chat back in stops covering it without forgetting anything new
+        let mut contents = std::fs::read_to_string(&path).unwrap();
+        contents.push_str(r#"{"at":"2026-10-02T10:00:00Z","action":"unforget","target":{"chat":"telegram:-100123"}}"#);
+        std::fs::write(&path, contents).unwrap();
+        log.modified = None;
+        assert!(log.refresh().unwrap().is_empty());
+        assert!(!log.covers(&incoming("-100123", "9")));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+
+pub mod forget;
+
+/// A connection event printed by `chat sources --watch`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SourceEvent {
+    pub source: String,
+    #[serde(flatten)]
+    pub kind: SourceEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SourceEventKind {
+    Connected,
+    Disconnected {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    AuthExpired {
+        #[serde(default)]
+        reason: String,
+    },
+    RateLimited {
+        #[serde(default)]
+        retry_after_secs: Option<u64>,
+    },
+}
+
+impl SourceEvent {
+    /// Whether the source can be used after this event
+    pub fn is_connected(&self) -> bool {
+        matches!(self.kind, SourceEventKind::Connected | SourceEventKind::RateLimited { .. })
+    }
+
+    /// Whether the source's session expired and the user has to sign in again
+    pub fn needs_sign_in(&self) -> bool {
+        matches!(self.kind, SourceEventKind::AuthExpired { .. })
+    }
+
+    /// Whether the user should be told about it
+    pub fn is_problem(&self) -> bool {
+        !matches!(self.kind, SourceEventKind::Connected)
+    }
+
+    /// Toast text
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            SourceEventKind::Connected => format!("{} connected", self.source),
+            SourceEventKind::Disconnected { reason: Some(reason) } => {
+                format!("{} disconnected: {}", self.source, reason)
+            }
+            SourceEventKind::Disconnected { reason: None } => format!("{} disconnected", self.source),
+            SourceEventKind::AuthExpired { .. } if self.source == "telegram" => {
+                "Telegram session expired, sign in again".to_string()
+            }
+            SourceEventKind::AuthExpired { .. } => format!("{} session expired, sign in again", self.source),
+            SourceEventKind::RateLimited { retry_after_secs: Some(secs) } => {
+                format!("{} is rate limited, retrying in {}s", self.source, secs)
+            }
+            SourceEventKind::RateLimited { retry_after_secs: None } => format!("{} is rate limited", self.source),
+        }
+    }
+}
+
+/// Follows source connection events through `chat sources --watch`
+pub struct SourceEventWatcher {
+    child: Child,
+    events: mpsc::Receiver<SourceEvent>,
+}
+
+impl SourceEventWatcher {
+    /// Start watching, repainting `ctx` whenever an event arrives
+    pub fn start(ctx: egui::Context) -> Result<Self> {
+        let mut child = Command::new(crate::config::paths::helper("chat"))
+            .args(["sources", "--watch"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to run chat CLI")?;
+
+        let stdout = child.stdout.take().context("chat CLI has no stdout")?;
+        let (tx, events) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                match serde_json::from_str::<SourceEvent>(&line) {
+                    Ok(event) => {
+                        if tx.send(event).is_err() {
+                            break;
+                        }
+                        ctx.request_repaint();
+                    }
+                    Err(e) => log::warn!("Ignoring source event '{}': {}", line, e),
+                }
+            }
+        });
+
+        Ok(Self { child, events })
+    }
+
+    /// Events received since the last call
+    pub fn take_events(&self) -> Vec<SourceEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// The exit status if the child process has stopped
+    pub fn exited(&mut self) -> Option<std::process::ExitStatus> {
+        self.child.try_wait().ok().flatten()
+    }
+}
+
+impl Drop for SourceEventWatcher {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Newest `schema_version` of the chat CLI's event stream this understands
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// A `message` event printed by `chat watch --format json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct IncomingMessage {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub source: String,
+    /// The message as serialized by the chat crate
+    pub message: serde_json::Value,
+}
+
+impl IncomingMessage {
+    /// `source:chat_id` key used for per-chat settings
+    pub fn chat_key(&self) -> String {
+        let chat_id = &self.message["chat_id"];
+        format!("{}:{}", self.source, chat_id.as_str().map(str::to_string).unwrap_or_else(|| chat_id.to_string()))
+    }
+
+    pub fn sender(&self) -> String {
+        message_sender(&self.message)
+    }
+
+    pub fn sender_id(&self) -> Option<String> {
+        json_id(&self.message["sender"]["id"])
+    }
+
+    pub fn body(&self) -> String {
+        message_body(&self.message)
+    }
+}
+
+/// A chat, message or sender ID of a serialized chat message. IDs are strings, but
+/// older sources printed some as numbers.
+pub fn json_id(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(id) => Some(id.clone()),
+        serde_json::Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Sender display name of a serialized chat message, falling back to the username
+pub fn message_sender(message: &serde_json::Value) -> String {
+    let sender = &message["sender"];
+    sender["display_name"]
+        .as_str()
+        .or(sender["username"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Text or media caption of a serialized chat message, empty for other content
+pub fn message_body(message: &serde_json::Value) -> String {
+    let content = &message["content"];
+    content["Text"]
+        .as_str()
+        .or(content["Image"]["caption"].as_str())
+        .or(content["Video"]["caption"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Follows incoming messages from all sources through `chat watch --format json`
+pub struct MessageWatcher {
+    child: Child,
+    messages: mpsc::Receiver<IncomingMessage>,
+}
+
+impl MessageWatcher {
+    /// Start watching, repainting `ctx` whenever a message arrives
+    pub fn start(ctx: egui::Context) -> Result<Self> {
+        let mut child = Command::new(crate::config::paths::helper("chat"))
+            .args(["watch", "--format", "json"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to run chat CLI")?;
+
+        let stdout = child.stdout.take().context("chat CLI has no stdout")?;
+        let (tx, messages) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                match serde_json::from_str::<IncomingMessage>(&line) {
+                    Ok(message) if message.schema_version > EVENT_SCHEMA_VERSION => {
+                        log::warn!("Ignoring message event with newer schema version {}", message.schema_version);
+                    }
+                    Ok(message) => {
+                        if tx.send(message).is_err() {
+                            break;
+                        }
+                        ctx.request_repaint();
+                    }
+                    Err(e) => log::warn!("Ignoring watch output '{}': {}", line, e),
+                }
+            }
+        });
+
+        Ok(Self { child, messages })
+    }
+
+    /// Messages received since the last call
+    pub fn take_messages(&self) -> Vec<IncomingMessage> {
+        self.messages.try_iter().collect()
+    }
+
+    /// The exit status if the child process has stopped
+    pub fn exited(&mut self) -> Option<std::process::ExitStatus> {
+        self.child.try_wait().ok().flatten()
+    }
+}
+
+impl Drop for MessageWatcher {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() {
+        let event: SourceEvent = serde_json::from_str(
+            r#"{"source":"telegram","kind":"rate_limited","retry_after_secs":30,"at":"2024-05-01T10:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(event.kind, SourceEventKind::RateLimited { retry_after_secs: Some(30) });
+        assert!(event.is_connected());
+        assert!(event.is_problem());
+
+        let event: SourceEvent =
+            serde_json::from_str(r#"{"source":"telegram","kind":"auth_expired","reason":"AUTH_KEY_UNREGISTERED"}"#)
+                .unwrap();
+        assert!(!event.is_connected());
+        assert!(event.needs_sign_in());
+        assert!(event.describe().contains("sign in again"));
+
+        let event: SourceEvent = serde_json::from_str(r#"{"source":"whatsapp","kind":"connected"}"#).unwrap();
+        assert!(!event.is_problem());
+        assert_eq!(event.describe(), "whatsapp connected");
+    }
+
+    #[test]
+    fn test_parse_incoming_message() {
+        let message: IncomingMessage = serde_json::from_str(
+            r#"{"type":"message","schema_version":1,"source":"telegram","message":{"id":"42","chat_id":"-100123",
+            "sender":{"id":"7","username":"alice","display_name":null},"content":{"Text":"Hello"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(message.chat_key(), "telegram:-100123");
+        assert_eq!(message.sender(), "alice");
+        assert_eq!(message.body(), "Hello");
+    }
+}
@@ -1,31 +1,49 @@
+use crate::agent::branch::Branch;
 use crate::agent::AgentId;
 use crate::config::AppConfig;
 use crate::ui::chat::ChatMessage;
 use anyhow::Result;
 use rusqlite::{Connection, params};
+use std::path::Path;
 
 pub struct ChatHistoryStore {
-    #[allow(dead_code)]
     conn: Connection,
 }
 
-#[allow(dead_code)]
 impl ChatHistoryStore {
     pub fn new() -> Result<Self> {
-        let db_path = AppConfig::config_dir()?.join("chat_history.db");
-        let conn = Connection::open(db_path)?;
+        Self::open(&AppConfig::config_dir()?.join("chat_history.db"))
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
 
-        conn.execute(
+        conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS messages (
                 id TEXT PRIMARY KEY,
                 agent_id TEXT,
                 content TEXT NOT NULL,
                 timestamp TEXT NOT NULL,
                 direction TEXT NOT NULL,
-                metadata TEXT NOT NULL
-            )",
+                metadata TEXT NOT NULL,
+                branch TEXT
+            );
+            CREATE TABLE IF NOT EXISTS branches (
+                id TEXT PRIMARY KEY,
+                agent_id TEXT NOT NULL,
+                branch TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS branches_by_agent ON branches (agent_id);",
+        )?;
+        // Histories from before conversations could branch
+        let has_branch: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info('messages') WHERE name = 'branch')",
             [],
+            |row| row.get(0),
         )?;
+        if !has_branch {
+            conn.execute("ALTER TABLE messages ADD COLUMN branch TEXT", [])?;
+        }
 
         Ok(Self { conn })
     }
@@ -36,8 +54,8 @@ impl ChatHistoryStore {
         let metadata_str = serde_json::to_string(&message.metadata)?;
 
         self.conn.execute(
-            "INSERT INTO messages (id, agent_id, content, timestamp, direction, metadata)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT OR REPLACE INTO messages (id, agent_id, content, timestamp, direction, metadata, branch)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 message.id.to_string(),
                 agent_id_str,
@@ -45,15 +63,72 @@ impl ChatHistoryStore {
                 message.timestamp.to_rfc3339(),
                 direction_str,
                 metadata_str,
+                message.branch.map(|id| id.to_string()),
             ],
         )?;
 
         Ok(())
     }
 
+    #[allow(dead_code)]
     pub fn load_messages(&self, _agent_id: Option<AgentId>, _limit: usize) -> Result<Vec<ChatMessage>> {
         // Simplified implementation for now - just return empty vector
         // Full deserialization would need more complex logic
         Ok(Vec::new())
     }
+
+    /// Record a branch of an agent's conversation
+    pub fn save_branch(&self, branch: &Branch) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO branches (id, agent_id, branch) VALUES (?1, ?2, ?3)",
+            params![branch.id.to_string(), branch.agent_id.to_string(), serde_json::to_string(branch)?],
+        )?;
+        Ok(())
+    }
+
+    /// The branches of `agent`'s conversation, oldest first
+    #[allow(dead_code)]
+    pub fn load_branches(&self, agent: AgentId) -> Result<Vec<Branch>> {
+        let mut statement = self.conn.prepare("SELECT branch FROM branches WHERE agent_id = ?1")?;
+        let rows = statement.query_map(params![agent.to_string()], |row| row.get::<_, String>(0))?;
+        let mut branches = Vec::new();
+        for row in rows {
+            branches.push(serde_json::from_str::<Branch>(&row?)?);
+        }
+        branches.sort_by_key(|branch| branch.created_at);
+        Ok(branches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::branch::Branches;
+
+    #[test]
+    fn test_branches_are_saved() {
+        let path = std::env::temp_dir().join(format!("chat-history-{}.db", uuid::Uuid::new_v4()));
+        let store = ChatHistoryStore::open(&path).unwrap();
+        let agent = AgentId::new();
+
+        let question = ChatMessage::new_user_message("2+2?".to_string(), Some(agent));
+        store.save_message(&question).unwrap();
+        let mut branches = Branches::default();
+        let mut prompt = ChatMessage::new_user_message("What is 2+2?".to_string(), Some(agent));
+        let branch = branches.fork(&question, prompt.id).unwrap().clone();
+        prompt.branch = Some(branch.id);
+        store.save_message(&prompt).unwrap();
+        store.save_branch(&branch).unwrap();
+
+        assert_eq!(store.load_branches(agent).unwrap(), std::slice::from_ref(&branch));
+        assert!(store.load_branches(AgentId::new()).unwrap().is_empty());
+        let saved: Option<String> = store
+            .conn
+            .query_row("SELECT branch FROM messages WHERE id = ?1", params![prompt.id.to_string()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(saved, Some(branch.id.to_string()));
+
+        drop(store);
+        let _ = std::fs::remove_file(path);
+    }
 }
@@ -0,0 +1,912 @@
+use crate::config::AppConfig;
+use crate::sources::{json_id, message_sender, IncomingMessage};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// A chat message as printed by the chat CLI, reduced to what the cache keeps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedMessage {
+    pub source: String,
+    pub chat_id: String,
+    pub message_id: String,
+    #[serde(default)]
+    pub sender: Option<String>,
+    /// The sender's ID on the source, to forget a person by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_id: Option<String>,
+    pub content: String,
+    pub timestamp: DateTime<Utc>,
+    /// Language detected by the chat CLI at ingest (ISO 639-1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl CachedMessage {
+    /// A message from `chat watch`, or None without a chat and message ID
+    pub fn from_incoming(incoming: &IncomingMessage) -> Option<Self> {
+        let message = &incoming.message;
+        let sender = message_sender(message);
+        Some(Self {
+            source: incoming.source.clone(),
+            chat_id: json_id(&message["chat_id"])?,
+            message_id: json_id(&message["id"])?,
+            sender: (!sender.is_empty()).then_some(sender),
+            sender_id: incoming.sender_id(),
+            content: incoming.body(),
+            timestamp: message["timestamp"]
+                .as_str()
+                .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+                .map_or_else(Utc::now, |timestamp| timestamp.with_timezone(&Utc)),
+            language: message["language"].as_str().map(str::to_string),
+        })
+    }
+}
+
+/// How a journaled message changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    New,
+    Edited,
+    Deleted,
+}
+
+impl ChangeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Edited => "edited",
+            Self::Deleted => "deleted",
+        }
+    }
+
+    fn parse(kind: &str) -> Self {
+        match kind {
+            "new" => Self::New,
+            "edited" => Self::Edited,
+            _ => Self::Deleted,
+        }
+    }
+}
+
+/// Position in the change journal, handed to clients as an opaque string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncToken(i64);
+
+impl std::fmt::Display for SyncToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v1.{}", self.0)
+    }
+}
+
+impl std::str::FromStr for SyncToken {
+    type Err = anyhow::Error;
+
+    fn from_str(token: &str) -> Result<Self> {
+        token
+            .strip_prefix("v1.")
+            .and_then(|seq| seq.parse().ok())
+            .filter(|seq| *seq >= 0)
+            .map(Self)
+            .ok_or_else(|| anyhow::anyhow!("Invalid sync token: {}", token))
+    }
+}
+
+impl Serialize for SyncToken {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A chat a client is subscribed to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatKey {
+    pub source: String,
+    pub chat_id: String,
+}
+
+/// Changes to one chat since a sync token
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChatDelta {
+    #[serde(flatten)]
+    pub chat: ChatKey,
+    pub new: Vec<CachedMessage>,
+    pub edited: Vec<CachedMessage>,
+    /// IDs of deleted messages
+    pub deleted: Vec<String>,
+}
+
+/// Answer to a delta sync request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyncDelta {
+    /// Chats with changes; unchanged chats are left out
+    pub chats: Vec<ChatDelta>,
+    /// Token to send with the next request
+    pub token: SyncToken,
+    /// More changes are waiting; request again right away with `token`
+    pub has_more: bool,
+    /// The token was missing or too old: refetch the chats instead of applying a delta
+    pub reset: bool,
+}
+
+/// SQLite cache of messages from chat sources, keyed by source, chat and message ID.
+///
+/// Every new, edited and deleted message is also recorded in a change journal, so
+/// clients holding a [`SyncToken`] can fetch just what changed with [`MessageCache::delta`].
+/// A full-text index folds case and diacritics for [`MessageCache::search`].
+pub struct MessageCache {
+    conn: Connection,
+}
+
+impl MessageCache {
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(AppConfig::config_dir()?.join("message_cache.db"))
+    }
+
+    /// Open the cache in the config directory
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_path()?)
+    }
+
+    /// Open or create a cache at `path`.
+    ///
+    /// The database runs in WAL mode so readers aren't blocked by ingestion, and with
+    /// `synchronous=NORMAL`: a crash may lose the last committed batches but never
+    /// corrupts the file.
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open message cache: {}", path.display()))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        let has_index: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'messages_fts')",
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS messages (
+                source TEXT NOT NULL,
+                chat_id TEXT NOT NULL,
+                message_id TEXT NOT NULL,
+                sender TEXT,
+                content TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                language TEXT,
+                sender_id TEXT,
+                PRIMARY KEY (source, chat_id, message_id)
+            );
+            CREATE INDEX IF NOT EXISTS messages_by_time ON messages (source, chat_id, timestamp);
+            CREATE TABLE IF NOT EXISTS changes (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                source TEXT NOT NULL,
+                chat_id TEXT NOT NULL,
+                message_id TEXT NOT NULL,
+                kind TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS changes_by_chat ON changes (source, chat_id, seq);
+            -- Row ids follow messages; 'remove_diacritics 2' lets cafe find CAFÉ
+            CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5 (
+                content,
+                sender,
+                tokenize = 'unicode61 remove_diacritics 2'
+            );",
+        )?;
+        // Caches created before language detection
+        let has_language: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info('messages') WHERE name = 'language')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_language {
+            conn.execute("ALTER TABLE messages ADD COLUMN language TEXT", [])?;
+        }
+        // ...and before sender IDs were kept
+        let has_sender_id: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info('messages') WHERE name = 'sender_id')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_sender_id {
+            conn.execute("ALTER TABLE messages ADD COLUMN sender_id TEXT", [])?;
+        }
+        if !has_index {
+            conn.execute(
+                "INSERT INTO messages_fts (rowid, content, sender) SELECT rowid, content, sender FROM messages",
+                [],
+            )?;
+        }
+        Ok(Self { conn })
+    }
+
+    /// Insert or update messages in a single transaction, journaling new and edited ones
+    pub fn insert_batch(&mut self, messages: &[CachedMessage]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut existing = tx.prepare_cached(
+                "SELECT rowid, sender, content FROM messages WHERE source = ?1 AND chat_id = ?2 AND message_id = ?3",
+            )?;
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO messages (source, chat_id, message_id, sender, content, timestamp, language, sender_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let mut journal = tx.prepare_cached(
+                "INSERT INTO changes (source, chat_id, message_id, kind) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            let mut unindex = tx.prepare_cached("DELETE FROM messages_fts WHERE rowid = ?1")?;
+            let mut index =
+                tx.prepare_cached("INSERT INTO messages_fts (rowid, content, sender) VALUES (?1, ?2, ?3)")?;
+            for message in messages {
+                let key = params![message.source, message.chat_id, message.message_id];
+                let previous: Option<(i64, Option<String>, String)> =
+                    existing.query_row(key, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).optional()?;
+                let kind = match previous {
+                    None => ChangeKind::New,
+                    Some((rowid, sender, content)) if sender != message.sender || content != message.content => {
+                        unindex.execute(params![rowid])?;
+                        ChangeKind::Edited
+                    }
+                    // Re-ingesting a message the cache already has isn't a change
+                    Some(_) => continue,
+                };
+
+                insert.execute(params![
+                    message.source,
+                    message.chat_id,
+                    message.message_id,
+                    message.sender,
+                    message.content,
+                    message.timestamp.to_rfc3339(),
+                    message.language,
+                    message.sender_id,
+                ])?;
+                index.execute(params![tx.last_insert_rowid(), message.content, message.sender])?;
+                journal.execute(params![message.source, message.chat_id, message.message_id, kind.as_str()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove a message, journaling the deletion. Sources don't report deletions yet, so
+    /// only tests delete single messages.
+    #[cfg(test)]
+    pub fn delete_message(&mut self, source: &str, chat_id: &str, message_id: &str) -> Result<bool> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM messages_fts WHERE rowid =
+                (SELECT rowid FROM messages WHERE source = ?1 AND chat_id = ?2 AND message_id = ?3)",
+            params![source, chat_id, message_id],
+        )?;
+        let removed = tx.execute(
+            "DELETE FROM messages WHERE source = ?1 AND chat_id = ?2 AND message_id = ?3",
+            params![source, chat_id, message_id],
+        )? > 0;
+        if removed {
+            tx.execute(
+                "INSERT INTO changes (source, chat_id, message_id, kind) VALUES (?1, ?2, ?3, ?4)",
+                params![source, chat_id, message_id, ChangeKind::Deleted.as_str()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Remove a chat's messages or a sender's (by ID) from a source, with their full-text
+    /// entries, journaling each deletion so synced clients drop them too. A dry run only
+    /// counts them.
+    pub fn forget(&mut self, source: &str, chat_id: Option<&str>, sender_id: Option<&str>, dry_run: bool) -> Result<usize> {
+        if chat_id.is_none() && sender_id.is_none() {
+            anyhow::bail!("Forgetting needs a chat or a sender");
+        }
+
+        let tx = self.conn.transaction()?;
+        let found: Vec<(i64, String, String)> = {
+            let mut select = tx.prepare(
+                "SELECT rowid, chat_id, message_id FROM messages
+                 WHERE source = ?1 AND (?2 IS NULL OR chat_id = ?2) AND (?3 IS NULL OR sender_id = ?3)",
+            )?;
+            let rows = select.query_map(params![source, chat_id, sender_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        if dry_run {
+            return Ok(found.len());
+        }
+
+        for (rowid, chat_id, message_id) in &found {
+            tx.execute("DELETE FROM messages_fts WHERE rowid = ?1", params![rowid])?;
+            tx.execute("DELETE FROM messages WHERE rowid = ?1", params![rowid])?;
+            tx.execute(
+                "INSERT INTO changes (source, chat_id, message_id, kind) VALUES (?1, ?2, ?3, ?4)",
+                params![source, chat_id, message_id, ChangeKind::Deleted.as_str()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(found.len())
+    }
+
+    /// Token for the current end of the change journal
+    pub fn current_token(&self) -> Result<SyncToken> {
+        let seq: Option<i64> = self.conn.query_row("SELECT MAX(seq) FROM changes", [], |row| row.get(0))?;
+        Ok(SyncToken(seq.unwrap_or(0)))
+    }
+
+    /// Drop journal entries older than `keep` so the journal doesn't grow forever.
+    /// Clients holding an older token get `reset` on their next delta.
+    pub fn compact_journal(&self, keep: usize) -> Result<usize> {
+        Ok(self.conn.execute(
+            "DELETE FROM changes WHERE seq <= (SELECT MAX(seq) FROM changes) - ?1",
+            params![keep as i64],
+        )?)
+    }
+
+    /// Changes to `chats` since `since`, at most `limit` journal entries at a time.
+    ///
+    /// Without a token, or with one older than the journal, the delta is a `reset`:
+    /// the client should drop what it has and fetch the chats' history again.
+    pub fn delta(&self, since: Option<SyncToken>, chats: &[ChatKey], limit: usize) -> Result<SyncDelta> {
+        let oldest: Option<i64> = self.conn.query_row("SELECT MIN(seq) FROM changes", [], |row| row.get(0))?;
+        let current = self.current_token()?;
+        let since = match since {
+            Some(token) if token.0 >= oldest.unwrap_or(1) - 1 && token.0 <= current.0 => token,
+            _ => {
+                return Ok(SyncDelta {
+                    chats: Vec::new(),
+                    token: current,
+                    has_more: false,
+                    reset: true,
+                })
+            }
+        };
+
+        let mut query = self.conn.prepare_cached(
+            "SELECT seq, message_id, kind FROM changes
+             WHERE source = ?1 AND chat_id = ?2 AND seq > ?3 ORDER BY seq LIMIT ?4",
+        )?;
+
+        // Entries of every requested chat, merged in journal order and cut at `limit`
+        let mut entries = Vec::new();
+        for (index, chat) in chats.iter().enumerate() {
+            let rows = query.query_map(params![chat.source, chat.chat_id, since.0, limit as i64 + 1], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?;
+            for row in rows {
+                let (seq, message_id, kind) = row?;
+                entries.push((seq, index, message_id, ChangeKind::parse(&kind)));
+            }
+        }
+        entries.sort_by_key(|(seq, ..)| *seq);
+        let has_more = entries.len() > limit;
+        entries.truncate(limit);
+        let token = match (has_more, entries.last()) {
+            (true, Some((seq, ..))) => SyncToken(*seq),
+            _ => current,
+        };
+
+        // Only the last change of each message matters, except that a message the client
+        // never saw stays new when it's edited
+        let mut changes: Vec<BTreeMap<String, ChangeKind>> = vec![BTreeMap::new(); chats.len()];
+        for (_, index, message_id, kind) in entries {
+            let entry = changes[index].entry(message_id).or_insert(kind);
+            if !(*entry == ChangeKind::New && kind == ChangeKind::Edited) {
+                *entry = kind;
+            }
+        }
+
+        let mut delta = SyncDelta {
+            chats: Vec::new(),
+            token,
+            has_more,
+            reset: false,
+        };
+        for (chat, changes) in chats.iter().zip(changes) {
+            if changes.is_empty() {
+                continue;
+            }
+            let mut chat_delta = ChatDelta {
+                chat: chat.clone(),
+                new: Vec::new(),
+                edited: Vec::new(),
+                deleted: Vec::new(),
+            };
+            for (message_id, kind) in changes {
+                let message = match kind {
+                    ChangeKind::Deleted => None,
+                    _ => self.message(&chat.source, &chat.chat_id, &message_id)?,
+                };
+                match (kind, message) {
+                    (ChangeKind::New, Some(message)) => chat_delta.new.push(message),
+                    (ChangeKind::Edited, Some(message)) => chat_delta.edited.push(message),
+                    // Deleted, or gone since the change was journaled
+                    _ => chat_delta.deleted.push(message_id),
+                }
+            }
+            delta.chats.push(chat_delta);
+        }
+        Ok(delta)
+    }
+
+    fn message(&self, source: &str, chat_id: &str, message_id: &str) -> Result<Option<CachedMessage>> {
+        let mut query = self.conn.prepare_cached(
+            "SELECT m.* FROM messages m WHERE source = ?1 AND chat_id = ?2 AND message_id = ?3",
+        )?;
+        Ok(read_messages(&mut query, params![source, chat_id, message_id])?.pop())
+    }
+
+    /// Messages whose text or sender contains `query`, newest first.
+    ///
+    /// By default this goes through the full-text index, which ignores case and accents
+    /// and matches whole words or word prefixes. With `exact` it's a plain case- and
+    /// accent-sensitive substring match over the text instead.
+    pub fn search(&self, query: &str, exact: bool, limit: usize) -> Result<Vec<CachedMessage>> {
+        let sql = if exact {
+            "SELECT m.* FROM messages m
+             WHERE instr(content, ?1) > 0 OR instr(sender, ?1) > 0
+             ORDER BY timestamp DESC LIMIT ?2"
+        } else {
+            "SELECT m.* FROM messages_fts JOIN messages m ON m.rowid = messages_fts.rowid
+             WHERE messages_fts MATCH ?1 ORDER BY m.timestamp DESC LIMIT ?2"
+        };
+        let pattern = if exact {
+            query.to_string()
+        } else {
+            // One quoted phrase, so the query's own punctuation isn't FTS syntax
+            format!("\"{}\"*", query.replace('"', "\"\""))
+        };
+
+        let mut statement = self.conn.prepare_cached(sql)?;
+        read_messages(&mut statement, params![pattern, limit as i64])
+    }
+
+    pub fn count(&self) -> Result<u64> {
+        Ok(self.conn.query_row("SELECT COUNT(*) FROM messages", [], |row| row.get(0))?)
+    }
+
+    /// Newest messages of a chat, newest first
+    pub fn recent(&self, source: &str, chat_id: &str, limit: usize) -> Result<Vec<CachedMessage>> {
+        let mut query = self.conn.prepare_cached(
+            "SELECT m.* FROM messages m WHERE source = ?1 AND chat_id = ?2 ORDER BY timestamp DESC LIMIT ?3",
+        )?;
+        read_messages(&mut query, params![source, chat_id, limit as i64])
+    }
+}
+
+/// Read the rows of a `SELECT m.* FROM messages m ...` query
+fn read_messages(statement: &mut rusqlite::Statement, params: impl rusqlite::Params) -> Result<Vec<CachedMessage>> {
+    let rows = statement.query_map(params, |row| {
+        let timestamp: String = row.get("timestamp")?;
+        let timestamp = DateTime::parse_from_rfc3339(&timestamp).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+        Ok(CachedMessage {
+            source: row.get("source")?,
+            chat_id: row.get("chat_id")?,
+            message_id: row.get("message_id")?,
+            sender: row.get("sender")?,
+            sender_id: row.get("sender_id")?,
+            content: row.get("content")?,
+            timestamp: timestamp.with_timezone(&Utc),
+            language: row.get("language")?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Change journal entries kept for delta sync; clients further behind refetch
+pub const JOURNAL_KEEP: usize = 100_000;
+
+/// When the batch writer commits
+#[derive(Debug, Clone, Copy)]
+pub struct BatchConfig {
+    /// Commit once this many messages are buffered
+    pub max_messages: usize,
+    /// Commit buffered messages at least this often
+    pub max_delay: Duration,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_messages: 500,
+            max_delay: Duration::from_millis(250),
+        }
+    }
+}
+
+enum WriterCommand {
+    Message(CachedMessage),
+    /// Commit now and report back
+    Flush(mpsc::Sender<Result<()>>),
+}
+
+/// Counters of a batch writer
+#[derive(Default)]
+struct WriterStats {
+    written: AtomicU64,
+    batches: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Buffers incoming messages on a writer thread and commits them to the cache in
+/// batches, so busy groups don't cost a transaction per message.
+///
+/// Dropping the writer (or calling `shutdown`) commits whatever is still buffered
+/// before the thread exits.
+pub struct BatchWriter {
+    tx: Option<mpsc::SyncSender<WriterCommand>>,
+    thread: Option<JoinHandle<()>>,
+    stats: Arc<WriterStats>,
+}
+
+impl BatchWriter {
+    /// Start writing to the cache in the config directory, first dropping journal
+    /// entries beyond the newest `JOURNAL_KEEP`
+    pub fn start_default() -> Result<Self> {
+        let cache = MessageCache::open_default()?;
+        let compacted = cache.compact_journal(JOURNAL_KEEP)?;
+        log::info!("Message cache holds {} messages ({} old journal entries dropped)", cache.count()?, compacted);
+        Ok(Self::start(cache, BatchConfig::default()))
+    }
+
+    pub fn start(mut cache: MessageCache, config: BatchConfig) -> Self {
+        // Bounded so a stalled disk slows ingestion down instead of growing memory
+        let (tx, rx) = mpsc::sync_channel(config.max_messages.max(1) * 4);
+        let stats = Arc::new(WriterStats::default());
+
+        let thread_stats = Arc::clone(&stats);
+        let thread = std::thread::spawn(move || {
+            let mut buffer: Vec<CachedMessage> = Vec::with_capacity(config.max_messages);
+            let mut oldest: Option<Instant> = None;
+
+            let mut commit = |buffer: &mut Vec<CachedMessage>| -> Result<()> {
+                if buffer.is_empty() {
+                    return Ok(());
+                }
+                let result = cache.insert_batch(buffer);
+                match &result {
+                    Ok(()) => {
+                        thread_stats.written.fetch_add(buffer.len() as u64, Ordering::Relaxed);
+                        thread_stats.batches.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to write {} messages to the cache: {}", buffer.len(), e);
+                        thread_stats.failed.fetch_add(buffer.len() as u64, Ordering::Relaxed);
+                    }
+                }
+                buffer.clear();
+                result
+            };
+
+            loop {
+                let wait = oldest.map_or(config.max_delay, |t| config.max_delay.saturating_sub(t.elapsed()));
+                match rx.recv_timeout(wait) {
+                    Ok(WriterCommand::Message(message)) => {
+                        oldest.get_or_insert_with(Instant::now);
+                        buffer.push(message);
+                        if buffer.len() < config.max_messages {
+                            continue;
+                        }
+                    }
+                    Ok(WriterCommand::Flush(done)) => {
+                        let _ = done.send(commit(&mut buffer));
+                        oldest = None;
+                        continue;
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        let _ = commit(&mut buffer);
+                        return;
+                    }
+                }
+                let _ = commit(&mut buffer);
+                oldest = None;
+            }
+        });
+
+        Self {
+            tx: Some(tx),
+            thread: Some(thread),
+            stats,
+        }
+    }
+
+    /// Queue a message, waiting if the writer is far behind
+    pub fn write(&self, message: CachedMessage) -> Result<()> {
+        self.sender()?
+            .send(WriterCommand::Message(message))
+            .map_err(|_| anyhow::anyhow!("Message cache writer stopped"))
+    }
+
+    /// Commit everything queued so far
+    pub fn flush(&self) -> Result<()> {
+        let (done, result) = mpsc::channel();
+        self.sender()?
+            .send(WriterCommand::Flush(done))
+            .map_err(|_| anyhow::anyhow!("Message cache writer stopped"))?;
+        result.recv().map_err(|_| anyhow::anyhow!("Message cache writer stopped"))?
+    }
+
+    /// Commit everything queued and stop the writer thread
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    /// Messages committed so far
+    pub fn written(&self) -> u64 {
+        self.stats.written.load(Ordering::Relaxed)
+    }
+
+    /// Transactions committed so far
+    pub fn batches(&self) -> u64 {
+        self.stats.batches.load(Ordering::Relaxed)
+    }
+
+    /// Messages lost to failed commits
+    pub fn failed(&self) -> u64 {
+        self.stats.failed.load(Ordering::Relaxed)
+    }
+
+    fn sender(&self) -> Result<&mpsc::SyncSender<WriterCommand>> {
+        self.tx.as_ref().context("Message cache writer stopped")
+    }
+
+    fn stop(&mut self) {
+        // Closing the channel makes the thread commit its buffer and exit
+        self.tx.take();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                log::error!("Message cache writer panicked");
+            }
+        }
+    }
+}
+
+impl Drop for BatchWriter {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("dashboard-cache-{}.db", uuid::Uuid::new_v4().simple()))
+    }
+
+    fn message(chat: usize, n: usize) -> CachedMessage {
+        CachedMessage {
+            source: "telegram".to_string(),
+            chat_id: format!("group-{}", chat),
+            message_id: n.to_string(),
+            sender: Some(format!("user{}", n % 17)),
+            sender_id: Some((n % 17).to_string()),
+            content: format!("message {} in a busy group", n),
+            timestamp: Utc::now() + chrono::Duration::milliseconds(n as i64),
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_batches_and_flushes_on_shutdown() {
+        let path = temp_db();
+        let config = BatchConfig {
+            max_messages: 10,
+            max_delay: Duration::from_secs(60),
+        };
+        let writer = BatchWriter::start(MessageCache::open(&path).unwrap(), config);
+
+        for n in 0..25 {
+            writer.write(message(n % 2, n)).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(writer.written(), 25);
+        assert_eq!(writer.batches(), 3);
+
+        // Buffered messages below the batch size are committed when the writer stops
+        for n in 25..30 {
+            writer.write(message(0, n)).unwrap();
+        }
+        writer.shutdown();
+
+        let cache = MessageCache::open(&path).unwrap();
+        assert_eq!(cache.count().unwrap(), 30);
+        let recent = cache.recent("telegram", "group-0", 2).unwrap();
+        assert_eq!(recent.iter().map(|m| m.message_id.as_str()).collect::<Vec<_>>(), ["29", "28"]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_commits_after_delay() {
+        let path = temp_db();
+        let config = BatchConfig {
+            max_messages: 1000,
+            max_delay: Duration::from_millis(20),
+        };
+        let writer = BatchWriter::start(MessageCache::open(&path).unwrap(), config);
+        writer.write(message(0, 1)).unwrap();
+
+        let started = Instant::now();
+        while writer.written() == 0 && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(writer.written(), 1);
+        drop(writer);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_delta_sync() {
+        let path = temp_db();
+        let mut cache = MessageCache::open(&path).unwrap();
+        let busy = ChatKey { source: "telegram".into(), chat_id: "group-0".into() };
+        let quiet = ChatKey { source: "telegram".into(), chat_id: "group-1".into() };
+        let chats = [busy.clone(), quiet.clone()];
+
+        assert!(cache.delta(None, &chats, 100).unwrap().reset);
+
+        cache.insert_batch(&[message(0, 1), message(0, 2), message(1, 3)]).unwrap();
+        let token = cache.current_token().unwrap();
+        assert_eq!(token.to_string().parse::<SyncToken>().unwrap(), token);
+
+        // Re-ingesting unchanged messages journals nothing
+        cache.insert_batch(&[message(0, 1), message(1, 3)]).unwrap();
+        assert_eq!(cache.current_token().unwrap(), token);
+
+        let mut edited = message(0, 1);
+        edited.content = "edited".to_string();
+        cache.insert_batch(&[edited, message(0, 4), message(1, 5)]).unwrap();
+        let mut unsubscribed = message(0, 6);
+        unsubscribed.chat_id = "group-9".to_string();
+        cache.insert_batch(&[unsubscribed]).unwrap();
+        assert!(cache.delete_message("telegram", "group-0", "2").unwrap());
+
+        let delta = cache.delta(Some(token), &chats, 100).unwrap();
+        assert!(!delta.reset && !delta.has_more);
+        assert_eq!(delta.token, cache.current_token().unwrap());
+        assert_eq!(delta.chats.len(), 2);
+        assert_eq!(delta.chats[0].chat, busy);
+        assert_eq!(delta.chats[0].new.iter().map(|m| m.message_id.as_str()).collect::<Vec<_>>(), ["4"]);
+        assert_eq!(delta.chats[0].edited[0].content, "edited");
+        assert_eq!(delta.chats[0].deleted, ["2"]);
+        assert_eq!(delta.chats[1].new[0].message_id, "5");
+
+        // Paged: the first page ends at the edit, the second picks up from there
+        let first = cache.delta(Some(token), &chats, 1).unwrap();
+        assert!(first.has_more);
+        assert_eq!(first.chats[0].edited.len(), 1);
+        let second = cache.delta(Some(first.token), &chats, 100).unwrap();
+        assert!(!second.has_more);
+        assert_eq!(second.chats.iter().map(|c| c.new.len() + c.deleted.len()).sum::<usize>(), 3);
+
+        // Tokens from before the compacted part of the journal need a full resync
+        cache.compact_journal(1).unwrap();
+        assert!(cache.delta(Some(token), &chats, 100).unwrap().reset);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_forget() {
+        let path = temp_db();
+        let mut cache = MessageCache::open(&path).unwrap();
+        cache.insert_batch(&(1..=40).map(|n| message(n % 2, n)).collect::<Vec<_>>()).unwrap();
+        let token = cache.current_token().unwrap();
+
+        // user1 wrote messages 1, 18 and 35
+        assert_eq!(cache.forget("telegram", None, Some("1"), true).unwrap(), 3);
+        assert_eq!(cache.count().unwrap(), 40);
+        assert_eq!(cache.forget("telegram", None, Some("1"), false).unwrap(), 3);
+        assert_eq!(cache.count().unwrap(), 37);
+        assert!(cache.search("user1", false, 50).unwrap().iter().all(|m| m.sender.as_deref() != Some("user1")));
+
+        let chat = ChatKey { source: "telegram".into(), chat_id: "group-1".into() };
+        let delta = cache.delta(Some(token), &[chat], 100).unwrap();
+        assert_eq!(delta.chats[0].deleted, ["1", "35"]);
+
+        assert_eq!(cache.forget("telegram", Some("group-0"), None, false).unwrap(), 19);
+        assert!(cache.recent("telegram", "group-0", 5).unwrap().is_empty());
+        assert!(cache.forget("telegram", None, None, false).is_err());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_search_folds_case_and_accents() {
+        let path = temp_db();
+        let mut cache = MessageCache::open(&path).unwrap();
+        let texts = ["Meet at the café", "MEET AT THE CAFE", "Встреча в КАФЕ", "Straße gesperrt"];
+        let messages: Vec<_> = texts
+            .iter()
+            .enumerate()
+            .map(|(n, text)| CachedMessage { content: text.to_string(), ..message(0, n) })
+            .collect();
+        cache.insert_batch(&messages).unwrap();
+
+        fn ids(cache: &MessageCache, query: &str, exact: bool) -> Vec<String> {
+            let mut ids: Vec<_> = cache.search(query, exact, 10).unwrap().into_iter().map(|m| m.message_id).collect();
+            ids.sort();
+            ids
+        }
+        assert_eq!(ids(&cache, "Cafe", false), ["0", "1"]);
+        assert_eq!(ids(&cache, "café", false), ["0", "1"]);
+        assert_eq!(ids(&cache, "café", true), ["0"]);
+        assert_eq!(ids(&cache, "кафе", false), ["2"]);
+        assert_eq!(ids(&cache, "straße", false), ["3"]);
+        assert_eq!(ids(&cache, "\"quoted", false), Vec::<String>::new());
+
+        // Edits and deletes keep the index in step
+        let edited = CachedMessage { content: "Meet at the library".to_string(), ..message(0, 0) };
+        cache.insert_batch(&[edited]).unwrap();
+        assert!(cache.delete_message("telegram", "group-0", "1").unwrap());
+        assert!(ids(&cache, "cafe", false).is_empty());
+        assert_eq!(ids(&cache, "library", false), ["0"]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_columns_added_to_old_caches() {
+        let path = temp_db();
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE messages (
+                    source TEXT NOT NULL,
+                    chat_id TEXT NOT NULL,
+                    message_id TEXT NOT NULL,
+                    sender TEXT,
+                    content TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    PRIMARY KEY (source, chat_id, message_id)
+                );",
+            )
+            .unwrap();
+
+        let mut cache = MessageCache::open(&path).unwrap();
+        let finnish = CachedMessage { language: Some("fi".to_string()), ..message(0, 1) };
+        cache.insert_batch(&[finnish, message(0, 2)]).unwrap();
+
+        let recent = cache.recent("telegram", "group-0", 10).unwrap();
+        assert_eq!(recent[0].language, None);
+        assert_eq!(recent[1].language.as_deref(), Some("fi"));
+        assert_eq!(recent[0].sender_id.as_deref(), Some("2"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    /// Sustained ingestion from several busy groups.
+    /// Run with `cargo test --release message_cache -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_sustained_ingestion() {
+        const MESSAGES: usize = 100_000;
+        let path = temp_db();
+        let writer = BatchWriter::start(MessageCache::open(&path).unwrap(), BatchConfig::default());
+
+        let started = Instant::now();
+        for n in 0..MESSAGES {
+            writer.write(message(n % 8, n)).unwrap();
+        }
+        writer.flush().unwrap();
+        let elapsed = started.elapsed();
+
+        let rate = MESSAGES as f64 / elapsed.as_secs_f64();
+        println!(
+            "{} messages in {:.2?} ({:.0} messages/s, {} transactions)",
+            MESSAGES,
+            elapsed,
+            rate,
+            writer.batches()
+        );
+        assert_eq!(writer.written(), MESSAGES as u64);
+        assert!(rate > 5_000.0, "only {:.0} messages/s", rate);
+        drop(writer);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod chat_history;
+pub mod message_cache;
+pub mod reminders;
 
 pub use chat_history::ChatHistoryStore;
+pub use reminders::ReminderStore;
@@ -0,0 +1,96 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A chat message reminder created by `chat remind add`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub source: String,
+    pub message_id: String,
+    #[serde(default)]
+    pub chat_id: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub sender: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub due: DateTime<Utc>,
+    #[serde(default)]
+    pub speak: bool,
+    /// Fires even during do-not-disturb
+    #[serde(default)]
+    pub critical: bool,
+    /// Fields owned by the chat CLI, kept intact when the file is rewritten
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Reminder {
+    /// `source:chat_id` key used for per-chat quiet hours
+    pub fn chat_key(&self) -> Option<String> {
+        self.chat_id.as_ref().map(|chat| format!("{}:{}", self.source, chat))
+    }
+
+    /// Notification text for the chat panel
+    pub fn summary(&self) -> String {
+        let mut text = match (&self.sender, &self.content) {
+            (Some(sender), Some(content)) => format!("{}: {}", sender, content),
+            (None, Some(content)) => content.clone(),
+            _ => format!("Message {} on {}", self.message_id, self.source),
+        };
+
+        if let Some(note) = &self.note {
+            text = format!("{} ({})", text, note);
+        }
+
+        text
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReminderFile {
+    #[serde(default)]
+    reminders: Vec<Reminder>,
+}
+
+/// Reminders shared with the chat CLI (`~/.config/chat/reminders.json`)
+pub struct ReminderStore {
+    path: PathBuf,
+}
+
+impl ReminderStore {
+    pub fn new() -> Result<Self> {
+        let path = dirs::config_dir()
+            .context("Failed to get config directory")?
+            .join("chat")
+            .join("reminders.json");
+
+        Ok(Self { path })
+    }
+
+    /// Remove due reminders from the file and return them
+    pub fn take_due(&self, now: DateTime<Utc>) -> Result<Vec<Reminder>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let contents = std::fs::read_to_string(&self.path)
+            .context("Failed to read reminders file")?;
+        let file: ReminderFile = serde_json::from_str(&contents)
+            .context("Failed to parse reminders file")?;
+
+        let (due, pending): (Vec<_>, Vec<_>) = file.reminders.into_iter().partition(|r| r.due <= now);
+        if due.is_empty() {
+            return Ok(due);
+        }
+
+        let contents = serde_json::to_string_pretty(&ReminderFile { reminders: pending })
+            .context("Failed to serialize reminders")?;
+        std::fs::write(&self.path, contents).context("Failed to write reminders file")?;
+
+        Ok(due)
+    }
+}
@@ -0,0 +1,374 @@
+//! Read-only HTTP API over the message cache, so a phone can follow chats without
+//! fetching whole histories again.
+//!
+//! - `GET /sync?since_token=<token>&chats=telegram:1,telegram:2` answers with the new,
+//!   edited and deleted messages of those chats since the token, and the token to send
+//!   next. Without a token, or with one too old, the answer is a `reset`.
+//! - `GET /messages?chat=telegram:1&limit=50` answers with a chat's newest messages, for
+//!   the first fetch and after a reset.
+//! - `GET /search?q=cafe&limit=20` finds cached messages, ignoring case and accents
+//!   unless `exact=true`.
+//!
+//! Every request needs `Authorization: Bearer <token>` with the token from the config.
+
+use crate::storage::message_cache::{ChatKey, MessageCache, SyncToken};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Journal entries in one delta at most; clients page with `has_more`
+const MAX_DELTA: usize = 1000;
+/// Messages `/messages` returns unless asked for fewer
+const DEFAULT_HISTORY: usize = 100;
+
+/// Settings for the sync API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncApiConfig {
+    /// Serve the API while the dashboard is open
+    #[serde(default)]
+    pub enabled: bool,
+    /// Address it listens on; loopback unless the phone reaches it some other way
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    /// Bearer token clients send; one is generated when the API is first started
+    #[serde(default)]
+    pub token: String,
+}
+
+fn default_listen() -> String {
+    "127.0.0.1:7412".to_string()
+}
+
+impl Default for SyncApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_listen(),
+            token: String::new(),
+        }
+    }
+}
+
+/// The API server, answering one request at a time on its own thread
+pub struct SyncApi {
+    address: SocketAddr,
+    stop: Arc<AtomicBool>,
+}
+
+impl SyncApi {
+    /// Listen on the configured address, serving the cache at `cache_path`
+    pub fn start(config: &SyncApiConfig, cache_path: PathBuf) -> Result<Self> {
+        if config.token.trim().is_empty() {
+            anyhow::bail!("The sync API needs a token");
+        }
+        let listener = TcpListener::bind(&config.listen)
+            .with_context(|| format!("Failed to listen on {}", config.listen))?;
+        let address = listener.local_addr()?;
+        let cache = MessageCache::open(&cache_path)?;
+        let token = config.token.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let stopped = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                match stream {
+                    Ok(stream) => {
+                        if let Err(e) = serve(&cache, &token, stream) {
+                            log::debug!("Sync API request failed: {}", e);
+                        }
+                    }
+                    Err(e) => log::warn!("Sync API connection failed: {}", e),
+                }
+            }
+        });
+
+        Ok(Self { address, stop })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+impl Drop for SyncApi {
+    fn drop(&mut self) {
+        // Wake the accept loop so it sees the flag
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = TcpStream::connect_timeout(&self.address, Duration::from_millis(200));
+    }
+}
+
+/// A parsed request: the path and its query parameters
+#[derive(Debug, Default, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    authorization: Option<String>,
+}
+
+/// Status code and JSON body
+type Response = (u16, serde_json::Value);
+
+fn serve(cache: &MessageCache, token: &str, stream: TcpStream) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let (status, body) = match read_request(&mut reader) {
+        Ok(request) => handle(cache, token, &request),
+        Err(e) => error(400, e),
+    };
+
+    let body = body.to_string();
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("Malformed request line");
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (decode(key), decode(value))
+            })
+            .collect(),
+        authorization: None,
+    };
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                request.authorization = Some(value.trim().to_string());
+            }
+        }
+    }
+    Ok(request)
+}
+
+fn handle(cache: &MessageCache, token: &str, request: &Request) -> Response {
+    if request.authorization.as_deref().and_then(|value| value.strip_prefix("Bearer ")) != Some(token) {
+        return error(401, "Missing or wrong bearer token");
+    }
+    if request.method != "GET" {
+        return error(405, "Only GET is supported");
+    }
+    let result = match request.path.as_str() {
+        "/sync" => sync(cache, &request.query),
+        "/messages" => messages(cache, &request.query),
+        "/search" => search(cache, &request.query),
+        _ => return error(404, format!("No such endpoint: {}", request.path)),
+    };
+    match result {
+        Ok(body) => (200, body),
+        Err(e) => error(400, e),
+    }
+}
+
+fn sync(cache: &MessageCache, query: &HashMap<String, String>) -> Result<serde_json::Value> {
+    let since = query
+        .get("since_token")
+        .filter(|token| !token.is_empty())
+        .map(|token| token.parse::<SyncToken>())
+        .transpose()?;
+    let chats = query
+        .get("chats")
+        .context("Missing 'chats'")?
+        .split(',')
+        .filter(|chat| !chat.is_empty())
+        .map(chat_key)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(serde_json::to_value(cache.delta(since, &chats, MAX_DELTA)?)?)
+}
+
+fn messages(cache: &MessageCache, query: &HashMap<String, String>) -> Result<serde_json::Value> {
+    let chat = chat_key(query.get("chat").context("Missing 'chat'")?)?;
+    let messages = cache.recent(&chat.source, &chat.chat_id, limit(query)?)?;
+    // Clients continue from here with /sync
+    Ok(serde_json::json!({ "messages": messages, "token": cache.current_token()? }))
+}
+
+fn search(cache: &MessageCache, query: &HashMap<String, String>) -> Result<serde_json::Value> {
+    let text = query.get("q").filter(|text| !text.trim().is_empty()).context("Missing 'q'")?;
+    let exact = query.get("exact").is_some_and(|exact| exact == "true");
+    Ok(serde_json::json!({ "messages": cache.search(text, exact, limit(query)?)? }))
+}
+
+/// The `limit` asked for, capped
+fn limit(query: &HashMap<String, String>) -> Result<usize> {
+    let limit = match query.get("limit") {
+        Some(limit) => limit.parse().context("Invalid 'limit'")?,
+        None => DEFAULT_HISTORY,
+    };
+    Ok(limit.min(MAX_DELTA))
+}
+
+/// A chat as `source:chat_id`
+fn chat_key(chat: &str) -> Result<ChatKey> {
+    let (source, chat_id) = chat
+        .split_once(':')
+        .filter(|(source, chat_id)| !source.is_empty() && !chat_id.is_empty())
+        .with_context(|| format!("Chat '{}' should be source:chat_id", chat))?;
+    Ok(ChatKey { source: source.to_string(), chat_id: chat_id.to_string() })
+}
+
+fn error(status: u16, message: impl std::fmt::Display) -> Response {
+    (status, serde_json::json!({ "error": message.to_string() }))
+}
+
+/// Undo URL percent-encoding, with `+` as a space
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::message_cache::CachedMessage;
+    use chrono::Utc;
+    use std::io::Read;
+
+    fn temp_db() -> PathBuf {
+        std::env::temp_dir().join(format!("dashboard-sync-{}.db", uuid::Uuid::new_v4().simple()))
+    }
+
+    fn message(chat: &str, id: &str) -> CachedMessage {
+        CachedMessage {
+            source: "telegram".to_string(),
+            chat_id: chat.to_string(),
+            message_id: id.to_string(),
+            sender: Some("Alice".to_string()),
+            sender_id: Some("7".to_string()),
+            content: format!("message {}", id),
+            timestamp: Utc::now(),
+            language: None,
+        }
+    }
+
+    fn get(target: &str) -> Request {
+        read_request(&mut format!("GET {} HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n", target).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_read_request() {
+        let request = get("/sync?since_token=v1.3&chats=telegram%3A-100%2Ctelegram:2");
+        assert_eq!(request.path, "/sync");
+        assert_eq!(request.query["since_token"], "v1.3");
+        assert_eq!(request.query["chats"], "telegram:-100,telegram:2");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer secret"));
+        assert_eq!(decode("a+b%2"), "a b%2");
+    }
+
+    #[test]
+    fn test_sync_and_history() {
+        let path = temp_db();
+        let mut cache = MessageCache::open(&path).unwrap();
+        cache.insert_batch(&[message("1", "10"), message("2", "20")]).unwrap();
+
+        let (status, _) = handle(&cache, "other", &get("/sync?chats=telegram:1"));
+        assert_eq!(status, 401);
+        let (status, body) = handle(&cache, "secret", &get("/sync?chats=telegram:1"));
+        assert_eq!(status, 200);
+        assert_eq!(body["reset"], true);
+
+        let (status, body) = handle(&cache, "secret", &get("/messages?chat=telegram:1"));
+        assert_eq!(status, 200);
+        assert_eq!(body["messages"][0]["message_id"], "10");
+        let token = body["token"].as_str().unwrap().to_string();
+
+        cache.insert_batch(&[message("1", "11"), message("2", "21")]).unwrap();
+        let (_, body) = handle(&cache, "secret", &get(&format!("/sync?since_token={}&chats=telegram:1", token)));
+        assert_eq!(body["reset"], false);
+        assert_eq!(body["chats"].as_array().unwrap().len(), 1);
+        assert_eq!(body["chats"][0]["new"][0]["message_id"], "11");
+
+        let (_, body) = handle(&cache, "secret", &get("/search?q=MESSAGE+21"));
+        assert_eq!(body["messages"][0]["message_id"], "21");
+
+        assert_eq!(handle(&cache, "secret", &get("/sync?since_token=nope&chats=telegram:1")).0, 400);
+        assert_eq!(handle(&cache, "secret", &get("/messages?chat=telegram")).0, 400);
+        assert_eq!(handle(&cache, "secret", &get("/chats")).0, 404);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_serves_http() {
+        let path = temp_db();
+        MessageCache::open(&path).unwrap().insert_batch(&[message("1", "10")]).unwrap();
+        let config = SyncApiConfig { enabled: true, listen: "127.0.0.1:0".to_string(), token: "secret".to_string() };
+        let api = SyncApi::start(&config, path.clone()).unwrap();
+
+        let mut stream = TcpStream::connect(api.address()).unwrap();
+        stream
+            .write_all(b"GET /messages?chat=telegram:1 HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"message_id\":\"10\""));
+
+        assert!(SyncApi::start(&SyncApiConfig { token: String::new(), ..config }, path.clone()).is_err());
+        drop(api);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// Telegram API credentials from my.telegram.org, as entered in the dashboard
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TelegramCredentials {
+    pub api_id: String,
+    pub api_hash: String,
+    /// Phone number with country code
+    pub phone: String,
+}
+
+impl TelegramCredentials {
+    /// Credentials saved by `chat telegram init`, if any
+    pub fn load() -> Option<Self> {
+        let contents = std::fs::read_to_string(config_file()?).ok()?;
+        let value = |key: &str| {
+            contents
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .find(|(k, _)| k.trim() == key)
+                .map(|(_, v)| v.trim().trim_matches('"').to_string())
+        };
+
+        Some(Self {
+            api_id: value("api_id")?,
+            api_hash: value("api_hash").unwrap_or_default(),
+            phone: value("phone").unwrap_or_default(),
+        })
+    }
+
+    /// Check the fields, returning the parsed API ID
+    pub fn validate(&self) -> Result<i32, String> {
+        let api_id = self
+            .api_id
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| "API ID must be a number".to_string())?;
+        if self.api_hash.trim().len() != 32 || !self.api_hash.trim().chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("API hash must be 32 hex characters".to_string());
+        }
+        let phone = self.phone.trim();
+        if !phone.starts_with('+') || phone.len() < 8 || !phone[1..].chars().all(|c| c.is_ascii_digit() || c == ' ') {
+            return Err("Phone number must start with + and the country code".to_string());
+        }
+        Ok(api_id)
+    }
+
+    /// Contents of the chat CLI's Telegram config file
+    pub fn to_toml(&self) -> String {
+        format!(
+            "api_id = {}\napi_hash = {:?}\nphone = {:?}\n",
+            self.api_id.trim(),
+            self.api_hash.trim(),
+            self.phone.trim()
+        )
+    }
+
+    /// Write the chat CLI's Telegram config file
+    pub fn save(&self) -> Result<()> {
+        let path = config_file().context("Could not determine config directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, self.to_toml()).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// `~/.config/chat/telegram/config.toml`, shared with the chat CLI
+pub fn config_file() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("chat").join("telegram").join("config.toml"))
+}
+
+/// `~/.config/chat/telegram/session.dat`, written once signed in
+pub fn session_file() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("chat").join("telegram").join("session.dat"))
+}
+
+/// Where a login is at
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoginState {
+    /// Connecting and requesting a code
+    Connecting,
+    /// Telegram sent a login code
+    NeedCode,
+    /// The account has two-factor authentication
+    NeedPassword { hint: Option<String> },
+    /// Code or password sent, waiting for Telegram
+    SigningIn,
+    /// Signed in, with the account name if the CLI reported it
+    Done { user: Option<String> },
+    Failed(String),
+}
+
+/// An event printed by `chat telegram init --json`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LoginEvent {
+    CodeRequired,
+    PasswordRequired {
+        #[serde(default)]
+        hint: Option<String>,
+    },
+    SignedIn {
+        #[serde(default)]
+        user: Option<String>,
+        #[serde(default)]
+        username: Option<String>,
+    },
+}
+
+impl LoginEvent {
+    fn is_prompt(&self) -> bool {
+        !matches!(self, LoginEvent::SignedIn { .. })
+    }
+}
+
+/// Login state after `events`, with `answered` prompts already answered
+pub fn login_state(events: &[LoginEvent], answered: usize) -> LoginState {
+    let prompts = events.iter().filter(|e| e.is_prompt()).count();
+    match events.last() {
+        None => LoginState::Connecting,
+        Some(LoginEvent::SignedIn { user, username }) => LoginState::Done {
+            user: user.clone().or_else(|| username.as_ref().map(|u| format!("@{}", u))),
+        },
+        Some(_) if answered >= prompts => LoginState::SigningIn,
+        Some(LoginEvent::CodeRequired) => LoginState::NeedCode,
+        Some(LoginEvent::PasswordRequired { hint }) => LoginState::NeedPassword { hint: hint.clone() },
+    }
+}
+
+/// Runs `chat telegram init --json` in the background, answering its code and password
+/// prompts from the GUI. The CLI saves the session, so the dashboard and CLI share the login.
+pub struct TelegramLogin {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    events: Arc<Mutex<Vec<LoginEvent>>>,
+    errors: Arc<Mutex<String>>,
+    /// Prompts answered so far
+    answered: usize,
+}
+
+impl TelegramLogin {
+    /// Save the credentials and start signing in, repainting `ctx` as the CLI makes progress
+    pub fn start(credentials: &TelegramCredentials, ctx: egui::Context) -> Result<Self> {
+        let mut child = Command::new(crate::config::paths::helper("chat"))
+            .args(["telegram", "init", "--json"])
+            .args(["--api-id", credentials.api_id.trim()])
+            .args(["--api-hash", credentials.api_hash.trim()])
+            .args(["--phone", credentials.phone.trim()])
+            .env("NO_COLOR", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run chat CLI")?;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let errors = Arc::new(Mutex::new(String::new()));
+        let stdout = child.stdout.take().context("chat CLI has no stdout")?;
+        let stderr = child.stderr.take().context("chat CLI has no stderr")?;
+
+        let received = Arc::clone(&events);
+        let repaint = ctx.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                match serde_json::from_str::<LoginEvent>(&line) {
+                    Ok(event) => {
+                        if let Ok(mut events) = received.lock() {
+                            events.push(event);
+                        }
+                        repaint.request_repaint();
+                    }
+                    Err(_) => log::debug!("telegram init: {}", line),
+                }
+            }
+        });
+
+        let collected = Arc::clone(&errors);
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines() {
+                let Ok(line) = line else { break };
+                if let Ok(mut errors) = collected.lock() {
+                    errors.push_str(&line);
+                    errors.push('\n');
+                }
+                ctx.request_repaint();
+            }
+        });
+
+        Ok(Self {
+            stdin: child.stdin.take(),
+            child,
+            events,
+            errors,
+            answered: 0,
+        })
+    }
+
+    /// Answer the current prompt with a login code or 2FA password
+    pub fn submit(&mut self, answer: &str) -> Result<()> {
+        let stdin = self.stdin.as_mut().context("Login has finished")?;
+        writeln!(stdin, "{}", answer.trim())?;
+        stdin.flush()?;
+        self.answered += 1;
+        Ok(())
+    }
+
+    /// Current state, cheap enough to poll every frame
+    pub fn state(&mut self) -> LoginState {
+        let state = match self.events.lock() {
+            Ok(events) => login_state(&events, self.answered),
+            Err(_) => LoginState::Failed("Login output lost".to_string()),
+        };
+        if matches!(state, LoginState::Done { .. }) {
+            return state;
+        }
+
+        match self.child.try_wait() {
+            Ok(Some(status)) if !status.success() => {
+                let errors = self.errors.lock().map(|e| e.clone()).unwrap_or_default();
+                let error = errors
+                    .lines()
+                    .rev()
+                    .find(|line| !line.trim().is_empty())
+                    .map(|line| line.trim().trim_start_matches("Error: ").to_string())
+                    .unwrap_or_else(|| format!("chat telegram init exited with {}", status));
+                LoginState::Failed(error)
+            }
+            // Signed in, but the last event hasn't been read yet
+            Ok(Some(_)) => LoginState::Done { user: None },
+            Ok(None) => state,
+            Err(e) => LoginState::Failed(e.to_string()),
+        }
+    }
+}
+
+impl Drop for TelegramLogin {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_state() {
+        let event = |line: &str| serde_json::from_str::<LoginEvent>(line).unwrap();
+        let mut events = Vec::new();
+        assert_eq!(login_state(&events, 0), LoginState::Connecting);
+
+        events.push(event(r#"{"event":"code_required"}"#));
+        assert_eq!(login_state(&events, 0), LoginState::NeedCode);
+        assert_eq!(login_state(&events, 1), LoginState::SigningIn);
+
+        events.push(event(r#"{"event":"password_required","hint":"cat"}"#));
+        assert_eq!(login_state(&events, 1), LoginState::NeedPassword { hint: Some("cat".to_string()) });
+        assert_eq!(login_state(&events, 2), LoginState::SigningIn);
+
+        events.push(event(r#"{"event":"signed_in","user":null,"username":"ann"}"#));
+        assert_eq!(login_state(&events, 2), LoginState::Done { user: Some("@ann".to_string()) });
+    }
+
+    #[test]
+    fn test_validate_credentials() {
+        let mut credentials = TelegramCredentials {
+            api_id: "12345".to_string(),
+            api_hash: "0123456789abcdef0123456789abcdef".to_string(),
+            phone: "+358 40 1234567".to_string(),
+        };
+        assert_eq!(credentials.validate(), Ok(12345));
+        assert!(credentials.to_toml().contains("api_id = 12345\n"));
+
+        credentials.phone = "0401234567".to_string();
+        assert!(credentials.validate().is_err());
+        credentials.api_id = "abc".to_string();
+        assert!(credentials.validate().is_err());
+    }
+}
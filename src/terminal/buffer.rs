@@ -0,0 +1,292 @@
+use chrono::{DateTime, Local};
+use egui::Color32;
+use std::time::{Duration, Instant};
+
+/// A run of text in one color
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub color: Color32,
+}
+
+/// One line of terminal output, without its newline
+#[derive(Debug, Clone, Default)]
+pub struct Line {
+    pub spans: Vec<Span>,
+    /// Changes whenever the line does, so views can keep its layout until then
+    pub revision: u64,
+}
+
+/// A run of lines that belong together: what a command printed, from the line it was
+/// entered on, or output that came in without one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    /// Unique across the buffer's lifetime, so views can remember blocks they collapsed
+    pub id: u64,
+    pub first_line: usize,
+    /// First line of the command entered, if any
+    pub command: Option<String>,
+    pub started: DateTime<Local>,
+}
+
+/// Term tab output split into lines as it arrives. Only the last line is still open
+/// for more text; earlier lines never change, which keeps their layout reusable.
+/// Lines are also split into [`Block`]s, one per command entered.
+#[derive(Debug, Clone)]
+pub struct ScrollbackBuffer {
+    lines: Vec<Line>,
+    /// Revisions are unique across the buffer's lifetime, also after `clear`
+    next_revision: u64,
+    /// Oldest first, the first starting at line 0
+    blocks: Vec<Block>,
+    /// When output last came in
+    last_output: Option<Instant>,
+}
+
+impl Default for ScrollbackBuffer {
+    fn default() -> Self {
+        let mut buffer = Self {
+            lines: vec![Line::default()],
+            next_revision: 1,
+            blocks: Vec::new(),
+            last_output: None,
+        };
+        buffer.start_block(None);
+        buffer
+    }
+}
+
+impl ScrollbackBuffer {
+    /// Append output, starting a new line after each `\n`
+    pub fn push(&mut self, text: &str, color: Color32) {
+        let mut pieces = text.split('\n');
+        if let Some(first) = pieces.next() {
+            self.append_to_last(first, color);
+        }
+        for piece in pieces {
+            self.lines.push(Line::default());
+            self.append_to_last(piece, color);
+        }
+    }
+
+    fn append_to_last(&mut self, text: &str, color: Color32) {
+        let revision = self.take_revision();
+
+        let line = self.lines.last_mut().expect("buffer always has a line");
+        line.revision = revision;
+        match line.spans.last_mut() {
+            Some(span) if span.color == color => span.text.push_str(text),
+            _ if text.is_empty() => {}
+            _ => line.spans.push(Span {
+                text: text.to_string(),
+                color,
+            }),
+        }
+    }
+
+    fn take_revision(&mut self) -> u64 {
+        let revision = self.next_revision;
+        self.next_revision += 1;
+        revision
+    }
+
+    /// Start a block at the open last line, where the prompt and the echoed command go.
+    /// A block nothing was printed in yet is replaced.
+    fn start_block(&mut self, command: Option<String>) {
+        let first_line = self.lines.len() - 1;
+        if self.blocks.last().is_some_and(|block| block.first_line == first_line) {
+            self.blocks.pop();
+        }
+        let id = self.take_revision();
+        self.blocks.push(Block { id, first_line, command, started: Local::now() });
+    }
+
+    /// A command was entered; what follows is its output
+    pub fn start_command(&mut self, command: &str) {
+        let command = command.lines().find(|line| !line.trim().is_empty()).unwrap_or_default().trim();
+        self.start_block(Some(command.to_string()));
+    }
+
+    /// Output is arriving at `now`. After a pause of at least `gap` it starts a new block,
+    /// unless it belongs to a command, however long that takes.
+    pub fn note_output(&mut self, now: Instant, gap: Option<Duration>) {
+        let paused = match (self.last_output, gap) {
+            (Some(last), Some(gap)) => now.duration_since(last) >= gap,
+            _ => false,
+        };
+        if paused && self.blocks.last().is_some_and(|block| block.command.is_none()) {
+            self.start_block(None);
+        }
+        self.last_output = Some(now);
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Lines of the block at `index`
+    pub fn block_lines(&self, index: usize) -> std::ops::Range<usize> {
+        let start = self.blocks.get(index).map_or(self.lines.len(), |block| block.first_line);
+        let end = self.blocks.get(index + 1).map_or(self.lines.len(), |block| block.first_line);
+        start..end
+    }
+
+    /// Number of lines, counting the open last line
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn line(&self, index: usize) -> Option<&Line> {
+        self.lines.get(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.lines = vec![Line::default()];
+        self.blocks.clear();
+        self.last_output = None;
+        self.start_block(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(buffer: &ScrollbackBuffer, index: usize) -> String {
+        buffer.line(index).unwrap().spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_push_splits_lines() {
+        let mut buffer = ScrollbackBuffer::default();
+        assert_eq!(buffer.len(), 1);
+
+        buffer.push("$ ls\nsrc", Color32::WHITE);
+        buffer.push(" Cargo.toml", Color32::WHITE);
+        buffer.push("\n", Color32::RED);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(text(&buffer, 0), "$ ls");
+        assert_eq!(text(&buffer, 1), "src Cargo.toml");
+        assert_eq!(buffer.line(1).unwrap().spans.len(), 1);
+        assert_eq!(text(&buffer, 2), "");
+    }
+
+    #[test]
+    fn test_revisions() {
+        let mut buffer = ScrollbackBuffer::default();
+        buffer.push("one\ntw", Color32::WHITE);
+        let first = buffer.line(0).unwrap().revision;
+        let open = buffer.line(1).unwrap().revision;
+
+        buffer.push("o", Color32::WHITE);
+        assert_eq!(buffer.line(0).unwrap().revision, first);
+        assert_ne!(buffer.line(1).unwrap().revision, open);
+
+        buffer.clear();
+        buffer.push("one", Color32::WHITE);
+        assert_ne!(buffer.line(0).unwrap().revision, first);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let mut buffer = ScrollbackBuffer::default();
+        let start = Instant::now();
+        let gap = Some(Duration::from_secs(5));
+
+        buffer.note_output(start, gap);
+        buffer.push("welcome\n$ ", Color32::WHITE);
+        buffer.start_command("cargo build\n");
+        buffer.note_output(start + Duration::from_secs(1), gap);
+        buffer.push("cargo build\nCompiling\n", Color32::WHITE);
+        // A slow command keeps its output together
+        buffer.note_output(start + Duration::from_secs(60), gap);
+        buffer.push("Finished\n$ ", Color32::WHITE);
+
+        let blocks = buffer.blocks();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].command.as_deref(), Some("cargo build"));
+        assert_eq!(buffer.block_lines(0), 0..1);
+        assert_eq!(buffer.block_lines(1), 1..buffer.len());
+
+        // Entering commands before any output replaces the empty block
+        buffer.start_command("ls");
+        buffer.start_command("pwd");
+        assert_eq!(buffer.blocks().len(), 3);
+        assert_eq!(buffer.blocks()[2].command.as_deref(), Some("pwd"));
+
+        buffer.clear();
+        buffer.note_output(start, gap);
+        buffer.push("tick\n", Color32::WHITE);
+        buffer.note_output(start + Duration::from_secs(10), gap);
+        buffer.push("tock\n", Color32::WHITE);
+        assert_eq!(buffer.blocks().len(), 2);
+        assert!(buffer.blocks()[1].command.is_none());
+        assert_eq!(buffer.block_lines(1), 1..3);
+    }
+
+    fn push_numbered(buffer: &mut ScrollbackBuffer, n: usize) {
+        let color = if n.is_multiple_of(10) { Color32::GREEN } else { Color32::LIGHT_GRAY };
+        buffer.push(&format!("{:>6}  ", n), Color32::GRAY);
+        buffer.push(&format!("Compiling crate-{} v0.{}.{} (step {})\n", n % 97, n % 13, n % 7, n), color);
+    }
+
+    /// The whole buffer as one layout job, as the Term tab rendered it before `OutputView`
+    fn single_job(buffer: &ScrollbackBuffer, font_id: &egui::FontId) -> egui::text::LayoutJob {
+        let mut job = egui::text::LayoutJob::default();
+        for index in 0..buffer.len() {
+            for span in &buffer.line(index).unwrap().spans {
+                job.append(&span.text, 0.0, egui::TextFormat::simple(font_id.clone(), span.color));
+            }
+            job.append("\n", 0.0, egui::TextFormat::simple(font_id.clone(), Color32::GRAY));
+        }
+        job
+    }
+
+    /// Term tab frame time with 100k lines, as one layout job against `OutputView`, with
+    /// static output and with a line arriving every frame.
+    /// Run with `cargo test --release bench_frame_time -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_frame_time() {
+        const LINES: usize = 100_000;
+        let font_id = egui::FontId::monospace(14.0);
+        let scrollback = || {
+            let mut buffer = ScrollbackBuffer::default();
+            (0..LINES).for_each(|n| push_numbered(&mut buffer, n));
+            buffer
+        };
+
+        for streaming in [false, true] {
+            let mut buffer = scrollback();
+            let before = crate::ui::debug_overlay::frame_time(|ui, frame| {
+                if streaming {
+                    push_numbered(&mut buffer, LINES + frame);
+                }
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| ui.label(single_job(&buffer, &font_id)));
+            });
+
+            let mut buffer = scrollback();
+            let mut view = crate::ui::terminal_output::OutputView::default();
+            let after = crate::ui::debug_overlay::frame_time(|ui, frame| {
+                if streaming {
+                    push_numbered(&mut buffer, LINES + frame);
+                }
+                let height = ui.available_height();
+                view.show(ui, &buffer, &font_id, height, false);
+            });
+
+            println!(
+                "{} lines, {}: one job {:.2?}/frame, virtualized {:.2?}/frame ({:.0}x faster)",
+                LINES,
+                if streaming { "a new line every frame" } else { "static output" },
+                before,
+                after,
+                before.as_secs_f64() / after.as_secs_f64()
+            );
+            assert!(after < before, "virtualized rendering is slower: {:.2?} vs {:.2?}", after, before);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::config::AppConfig;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::TerminalConfig;
+
+/// Lines typed into the Term tab, kept per startup command so `bash` and `python3`
+/// sessions recall their own commands. Saved to `terminal_history.json`.
+pub struct CommandHistory {
+    command: String,
+    /// Oldest first
+    entries: Vec<String>,
+    /// Entry shown while recalling with Up and Down
+    cursor: Option<usize>,
+    /// What was typed before recalling started, given back past the newest entry
+    draft: String,
+}
+
+impl CommandHistory {
+    /// History of a startup command, empty if none was saved
+    pub fn load(command: &str) -> Self {
+        let entries = match load_all() {
+            Ok(mut all) => all.remove(command).unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Failed to load terminal history: {}", e);
+                Vec::new()
+            }
+        };
+
+        Self::with_entries(command, entries)
+    }
+
+    fn with_entries(command: &str, entries: Vec<String>) -> Self {
+        Self {
+            command: command.to_string(),
+            entries,
+            cursor: None,
+            draft: String::new(),
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Remember a sent line unless the config keeps it out or it repeats the last one.
+    /// Returns whether it changed the history.
+    pub fn record(&mut self, line: &str, config: &TerminalConfig) -> bool {
+        self.cursor = None;
+        let line = line.trim_end();
+        if line.trim().is_empty() || config.keeps_out(line) || self.entries.last().is_some_and(|last| last == line) {
+            return false;
+        }
+
+        self.entries.push(line.to_string());
+        let excess = self.entries.len().saturating_sub(config.history_limit);
+        self.entries.drain(..excess);
+        true
+    }
+
+    /// Step back to the previous entry; `current` is kept as the draft when starting
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let index = match self.cursor {
+            None => {
+                self.draft = current.to_string();
+                self.entries.len().checked_sub(1)?
+            }
+            Some(index) => index.saturating_sub(1),
+        };
+        self.cursor = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Step forward to the next entry, or back to the draft after the newest
+    pub fn next(&mut self) -> Option<&str> {
+        let index = self.cursor? + 1;
+        if index >= self.entries.len() {
+            self.cursor = None;
+            return Some(self.draft.as_str());
+        }
+        self.cursor = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Whether Up and Down are currently recalling entries
+    pub fn is_recalling(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// Entries containing the characters of `query` in order, best match first: tighter
+    /// matches before scattered ones, then newer before older. Each line appears once.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&str> {
+        let mut matches: Vec<(usize, usize, &str)> = Vec::new();
+        for (age, entry) in self.entries.iter().rev().enumerate() {
+            if matches.iter().any(|(_, _, seen)| *seen == entry.as_str()) {
+                continue;
+            }
+            if let Some(spread) = fuzzy_spread(entry, query) {
+                matches.push((spread, age, entry.as_str()));
+            }
+        }
+
+        matches.sort_by_key(|(spread, age, _)| (*spread, *age));
+        matches.into_iter().take(limit).map(|(_, _, entry)| entry).collect()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut all = load_all().unwrap_or_default();
+        all.insert(self.command.clone(), self.entries.clone());
+
+        let path = history_file()?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&all)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// How many characters a fuzzy match of `query` spans in `entry`, ignoring case;
+/// `None` if the characters of `query` don't all appear in order
+fn fuzzy_spread(entry: &str, query: &str) -> Option<usize> {
+    let entry: Vec<char> = entry.to_lowercase().chars().collect();
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    // The tightest match can start at any occurrence of the first character
+    (0..entry.len())
+        .filter(|&start| entry[start] == query[0])
+        .filter_map(|start| {
+            let mut matched = 0;
+            for (offset, c) in entry[start..].iter().enumerate() {
+                if *c == query[matched] {
+                    matched += 1;
+                    if matched == query.len() {
+                        return Some(offset + 1);
+                    }
+                }
+            }
+            None
+        })
+        .min()
+}
+
+fn history_file() -> Result<PathBuf> {
+    Ok(AppConfig::config_dir()?.join("terminal_history.json"))
+}
+
+fn load_all() -> Result<BTreeMap<String, Vec<String>>> {
+    let path = history_file()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(entries: &[&str]) -> CommandHistory {
+        CommandHistory::with_entries("bash", entries.iter().map(|e| e.to_string()).collect())
+    }
+
+    #[test]
+    fn test_recall() {
+        let mut history = history(&["ls", "cd src", "cargo build"]);
+        assert_eq!(history.previous("car"), Some("cargo build"));
+        assert_eq!(history.previous(""), Some("cd src"));
+        assert_eq!(history.previous(""), Some("ls"));
+        assert_eq!(history.previous(""), Some("ls"));
+        assert_eq!(history.next(), Some("cd src"));
+        assert_eq!(history.next(), Some("cargo build"));
+        assert_eq!(history.next(), Some("car"));
+        assert!(!history.is_recalling());
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn test_record() {
+        let config = TerminalConfig { history_limit: 2, ..TerminalConfig::default() };
+        let mut history = history(&[]);
+        assert!(history.record("ls", &config));
+        assert!(!history.record("ls", &config));
+        assert!(!history.record("export API_KEY=123", &config));
+        assert!(history.record("pwd", &config));
+        assert!(history.record("whoami", &config));
+        assert_eq!(history.entries, ["pwd", "whoami"]);
+    }
+
+    #[test]
+    fn test_search() {
+        let history = history(&["cargo test", "git status", "cargo build --release", "cat README.md", "cargo test"]);
+        assert_eq!(history.search("cgb", 5), ["cargo build --release"]);
+        assert_eq!(history.search("ca", 5), ["cargo test", "cat README.md", "cargo build --release"]);
+        assert_eq!(history.search("", 2), ["cargo test", "cat README.md"]);
+    }
+}
@@ -0,0 +1,109 @@
+pub mod buffer;
+pub mod history;
+pub mod paste;
+
+use crate::sandbox::SandboxConfig;
+use serde::{Deserialize, Serialize};
+
+pub use history::CommandHistory;
+
+fn default_history_limit() -> usize {
+    1000
+}
+
+fn default_skip_secrets() -> bool {
+    true
+}
+
+fn default_secret_words() -> Vec<String> {
+    ["password", "passwd", "secret", "token", "api_key", "apikey", "private_key", "authorization"]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_paste_confirm_lines() -> usize {
+    5
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Settings for the Term tab
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerminalConfig {
+    /// Commands remembered per startup command
+    #[serde(default = "default_history_limit")]
+    pub history_limit: usize,
+    /// Keep lines that look like they carry a secret out of the history
+    #[serde(default = "default_skip_secrets")]
+    pub skip_secrets: bool,
+    /// A line containing one of these, ignoring case, looks secret
+    #[serde(default = "default_secret_words")]
+    pub secret_words: Vec<String>,
+    /// Pastes of more lines than this wait for confirmation; 0 never asks
+    #[serde(default = "default_paste_confirm_lines")]
+    pub paste_confirm_lines: usize,
+    /// Pastes with escape sequences or other control characters wait for confirmation
+    #[serde(default = "default_true")]
+    pub confirm_control_chars: bool,
+    /// Send multi-line input as a bracketed paste when the program asks for it, so a
+    /// shell doesn't run each line as it arrives
+    #[serde(default = "default_true")]
+    pub bracketed_paste: bool,
+    /// Applies to startup commands, confined to the dashboard's working directory
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            history_limit: default_history_limit(),
+            skip_secrets: default_skip_secrets(),
+            secret_words: default_secret_words(),
+            paste_confirm_lines: default_paste_confirm_lines(),
+            confirm_control_chars: default_true(),
+            bracketed_paste: default_true(),
+            sandbox: SandboxConfig::default(),
+        }
+    }
+}
+
+impl TerminalConfig {
+    /// Whether a line should stay out of the history. Like bash's `ignorespace`, a
+    /// leading space always keeps a line out.
+    pub fn keeps_out(&self, line: &str) -> bool {
+        if line.starts_with(' ') {
+            return true;
+        }
+        if !self.skip_secrets {
+            return false;
+        }
+
+        let line = line.to_lowercase();
+        self.secret_words
+            .iter()
+            .map(|word| word.trim().to_lowercase())
+            .any(|word| !word.is_empty() && line.contains(&word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_out() {
+        let mut config = TerminalConfig::default();
+        assert!(config.keeps_out("export GITHUB_TOKEN=abc"));
+        assert!(config.keeps_out("mysql -u root --password=hunter2"));
+        assert!(config.keeps_out(" ls"));
+        assert!(!config.keeps_out("cargo test"));
+
+        config.skip_secrets = false;
+        assert!(!config.keeps_out("export GITHUB_TOKEN=abc"));
+        assert!(config.keeps_out(" ls"));
+    }
+}
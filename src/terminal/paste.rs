@@ -0,0 +1,102 @@
+//! Pasting into the Term tab: what needs a confirmation first, and bracketed paste
+//! for programs that ask for it
+
+use super::TerminalConfig;
+
+/// Sent before pasted text when the program enabled bracketed paste (`ESC [ ? 2004 h`)
+pub const PASTE_START: &str = "\x1b[200~";
+/// Sent after pasted text
+pub const PASTE_END: &str = "\x1b[201~";
+
+/// Why a paste waits for confirmation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteWarning {
+    pub lines: usize,
+    /// Contains control characters other than newlines and tabs, e.g. escape sequences
+    pub control_chars: bool,
+}
+
+/// A paste held back until confirmed
+#[derive(Debug, Clone)]
+pub struct PendingPaste {
+    pub text: String,
+    pub warning: PasteWarning,
+    /// Character position in the input where it goes
+    pub at: usize,
+}
+
+/// Whether pasting `text` should be confirmed first, per the config
+pub fn check(text: &str, config: &TerminalConfig) -> Option<PasteWarning> {
+    let lines = text.trim_end_matches(['\n', '\r']).lines().count();
+    let control_chars = text.chars().any(is_unsafe_control);
+
+    let too_long = config.paste_confirm_lines > 0 && lines > config.paste_confirm_lines;
+    let risky = config.confirm_control_chars && control_chars;
+    (too_long || risky).then_some(PasteWarning { lines, control_chars })
+}
+
+/// `text` without control characters other than newlines and tabs
+pub fn strip_control(text: &str) -> String {
+    text.replace("\r\n", "\n").chars().filter(|c| !is_unsafe_control(*c)).collect()
+}
+
+/// `text` with control characters made visible, for previews
+pub fn escape_control(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\x1b' => "␛".to_string(),
+            c if is_unsafe_control(c) => format!("\\u{{{:x}}}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Wrap text in bracketed paste markers so the program takes it as one paste rather
+/// than typed lines. An end marker inside the text could end the paste early and run
+/// the rest, so it is removed.
+pub fn bracket(text: &str) -> String {
+    format!("{}{}{}", PASTE_START, text.replace(PASTE_END, ""), PASTE_END)
+}
+
+/// Insert `text` into `input` at character position `at`, or at the end if past it
+pub fn insert_at(input: &mut String, at: usize, text: &str) {
+    let byte = input.char_indices().nth(at).map(|(i, _)| i).unwrap_or(input.len());
+    input.insert_str(byte, text);
+}
+
+fn is_unsafe_control(c: char) -> bool {
+    c.is_control() && !matches!(c, '\n' | '\t' | '\r')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let config = TerminalConfig { paste_confirm_lines: 3, ..TerminalConfig::default() };
+        assert_eq!(check("ls\ncd /\npwd\n", &config), None);
+        assert_eq!(check("a\nb\nc\nd", &config), Some(PasteWarning { lines: 4, control_chars: false }));
+        assert_eq!(check("ls\x1b[201~rm", &config), Some(PasteWarning { lines: 1, control_chars: true }));
+
+        let lenient = TerminalConfig { paste_confirm_lines: 0, confirm_control_chars: false, ..config };
+        assert_eq!(check("a\nb\nc\nd\x07", &lenient), None);
+    }
+
+    #[test]
+    fn test_bracket() {
+        assert_eq!(bracket("echo a\necho b"), "\x1b[200~echo a\necho b\x1b[201~");
+        assert_eq!(bracket("x\x1b[201~rm -rf ~"), "\x1b[200~xrm -rf ~\x1b[201~");
+        assert_eq!(strip_control("a\x1b[31mb\r\nc\t"), "a[31mb\nc\t");
+        assert_eq!(escape_control("a\x1bb\x07"), "a␛b\\u{7}");
+    }
+
+    #[test]
+    fn test_insert_at() {
+        let mut input = "héllo".to_string();
+        insert_at(&mut input, 2, "-");
+        assert_eq!(input, "hé-llo");
+        insert_at(&mut input, 99, "!");
+        assert_eq!(input, "hé-llo!");
+    }
+}
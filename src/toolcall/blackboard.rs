@@ -0,0 +1,124 @@
+use super::types::{Toolcall, ToolcallResult, ToolcallSchema};
+use crate::blackboard::{SharedBlackboard, DEFAULT_NAMESPACE};
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Reads and writes the blackboard the agents share
+pub struct BlackboardToolcall {
+    board: SharedBlackboard,
+}
+
+impl BlackboardToolcall {
+    pub fn new(board: SharedBlackboard) -> Self {
+        Self { board }
+    }
+
+    fn run(&self, parameters: &Value) -> Result<Value> {
+        let text = |name: &str| parameters.get(name).and_then(Value::as_str);
+        let action = text("action").ok_or_else(|| anyhow::anyhow!("Missing 'action' parameter"))?;
+        let namespace = text("namespace").unwrap_or(DEFAULT_NAMESPACE);
+        let key = || text("key").ok_or_else(|| anyhow::anyhow!("Missing 'key' parameter"));
+        let now = chrono::Utc::now();
+        let mut board = self.board.lock().map_err(|_| anyhow::anyhow!("Blackboard lock poisoned"))?;
+
+        match action {
+            "get" => {
+                let key = key()?;
+                let entry = board
+                    .get(namespace, key, now)
+                    .ok_or_else(|| anyhow::anyhow!("Nothing under '{}' in '{}'", key, namespace))?;
+                Ok(serde_json::to_value(entry)?)
+            }
+            "set" => {
+                let value = parameters.get("value").cloned().ok_or_else(|| anyhow::anyhow!("Missing 'value' parameter"))?;
+                let ttl = parameters.get("ttl_seconds").and_then(Value::as_u64).map(Duration::from_secs);
+                board.set(namespace, key()?, value, ttl, now)?;
+                Ok(json!({ "ok": true }))
+            }
+            "delete" => Ok(json!({ "deleted": board.remove(namespace, key()?) })),
+            "list" => {
+                // Without a namespace given, list every one
+                let namespace = text("namespace");
+                let entries = board.list(namespace, text("prefix").unwrap_or(""), now);
+                Ok(serde_json::to_value(entries)?)
+            }
+            other => anyhow::bail!("Unknown action '{}'; use get, set, delete or list", other),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Toolcall for BlackboardToolcall {
+    fn get_schema(&self) -> ToolcallSchema {
+        ToolcallSchema {
+            name: "blackboard".to_string(),
+            description: "Read and write notes shared with the other agents, e.g. a list of failing tests".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "action": {
+                        "type": "string",
+                        "enum": ["get", "set", "delete", "list"]
+                    },
+                    "namespace": {
+                        "type": "string",
+                        "description": "Group of keys, \"shared\" by default; list without one lists every namespace"
+                    },
+                    "key": {
+                        "type": "string",
+                        "description": "Key to get, set or delete"
+                    },
+                    "value": {
+                        "description": "Any JSON value to set"
+                    },
+                    "ttl_seconds": {
+                        "type": "integer",
+                        "description": "Drop the entry this many seconds after setting it (optional)"
+                    },
+                    "prefix": {
+                        "type": "string",
+                        "description": "Only list keys starting with this (optional)"
+                    }
+                },
+                "required": ["action"]
+            }),
+        }
+    }
+
+    async fn execute(&self, parameters: Value) -> Result<ToolcallResult> {
+        Ok(match self.run(&parameters) {
+            Ok(output) => ToolcallResult { success: true, output: output.to_string(), error: None },
+            Err(e) => ToolcallResult { success: false, output: String::new(), error: Some(e.to_string()) },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blackboard::Blackboard;
+
+    #[test]
+    fn test_actions() {
+        let board = Blackboard::shared();
+        let tool = BlackboardToolcall::new(board.clone());
+
+        let set = json!({ "action": "set", "namespace": "ci", "key": "failing", "value": ["a", "b"], "ttl_seconds": 600 });
+        assert_eq!(tool.run(&set).unwrap(), json!({ "ok": true }));
+        let got = tool.run(&json!({ "action": "get", "namespace": "ci", "key": "failing" })).unwrap();
+        assert_eq!(got["value"], json!(["a", "b"]));
+        assert!(got["expires_at"].is_string());
+
+        // Without a namespace, keys go to and come from the shared one
+        tool.run(&json!({ "action": "set", "key": "owner", "value": "reviewer" })).unwrap();
+        assert_eq!(board.lock().unwrap().get(DEFAULT_NAMESPACE, "owner", chrono::Utc::now()).unwrap().value, json!("reviewer"));
+        assert_eq!(tool.run(&json!({ "action": "list" })).unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(tool.run(&json!({ "action": "list", "namespace": "ci" })).unwrap().as_array().unwrap().len(), 1);
+
+        assert_eq!(tool.run(&json!({ "action": "delete", "namespace": "ci", "key": "failing" })).unwrap()["deleted"], true);
+        assert!(tool.run(&json!({ "action": "get", "namespace": "ci", "key": "failing" })).is_err());
+        assert!(tool.run(&json!({ "action": "set", "key": "no value" })).is_err());
+        assert!(tool.run(&json!({ "action": "shout" })).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod blackboard;
+pub mod process;
+pub mod protocol;
 pub mod shell;
 pub mod types;
-
@@ -0,0 +1,188 @@
+use crate::agent::branch::Turn;
+use crate::agent::context::AgentContext;
+use crate::agent::ProcessAgentConfig;
+use crate::approval::AgentApproval;
+use crate::sandbox::SandboxConfig;
+use super::protocol::{Frame, FrameParser, Output};
+use super::types::{ToolcallRegistry, ToolcallRequest, ToolcallResult, ToolcallScope};
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+
+/// A running agent the dashboard exchanges chat text and frames with
+pub trait RunningAgent: Send {
+    /// Send the user's chat text to the agent
+    fn send_message(&self, text: &str) -> Result<()>;
+
+    /// Output received since the last call
+    fn take_output(&self) -> Vec<Output>;
+
+    /// How the agent exited, if it has stopped
+    fn exited(&mut self) -> Option<String>;
+
+    /// Process ID, for resource monitoring
+    fn pid(&self) -> Option<u32>;
+
+    /// Whether the agent is sent the whole conversation with each message, so its
+    /// conversation can branch
+    fn can_branch(&self) -> bool {
+        false
+    }
+
+    /// Go on from `history` instead of the conversation so far
+    fn set_history(&self, _history: Vec<Turn>) -> Result<()> {
+        anyhow::bail!("This agent keeps its own conversation, which can't be replaced")
+    }
+
+    /// Take up a changed system prompt or memory. Agents given a context file read the
+    /// change from there instead.
+    fn set_context(&self, _context: &AgentContext) {}
+}
+
+/// An agent subprocess speaking the frame protocol over stdin and stdout.
+///
+/// Tool calls are run through the registry and answered on the agent's stdin;
+/// every frame and text line, tool calls and results included, is passed on
+/// for the dashboard to show.
+pub struct AgentProcess {
+    child: Child,
+    stdin: Arc<Mutex<ChildStdin>>,
+    output: mpsc::Receiver<Output>,
+}
+
+impl AgentProcess {
+    /// Start the program `config` describes, in its workspace and with `env` added to its
+    /// environment, repainting `ctx` whenever it prints something. With `approval`, shell
+    /// tool calls wait for the agent's approval policy or the user; they run in the
+    /// agent's sandbox either way.
+    pub fn spawn(
+        config: &ProcessAgentConfig,
+        env: &[(&str, String)],
+        sandbox: &SandboxConfig,
+        registry: Arc<ToolcallRegistry>,
+        approval: Option<AgentApproval>,
+        ctx: egui::Context,
+    ) -> Result<Self> {
+        let cwd = config.workspace.as_deref().map(Path::new);
+        let launch = sandbox.wrap(&config.command, &config.args, cwd)?;
+        let mut command = Command::new(&launch.program);
+        if let Some(cwd) = cwd {
+            command.current_dir(cwd);
+        }
+        launch.apply_env(&mut command);
+        let mut child = command
+            .args(&launch.args)
+            .envs(env.iter().map(|(key, value)| (key, value)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to start agent '{}'", config.command))?;
+
+        let stdout = child.stdout.take().context("Agent has no stdout")?;
+        let stdin = Arc::new(Mutex::new(child.stdin.take().context("Agent has no stdin")?));
+        let (tx, output) = mpsc::channel();
+
+        let replies = Arc::clone(&stdin);
+        let scope = ToolcallScope { sandbox: sandbox.clone(), workspace: cwd.map(Path::to_path_buf) };
+        std::thread::spawn(move || {
+            // Tools are async; this thread drives them itself
+            let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    log::error!("Failed to start tool runtime: {}", e);
+                    return;
+                }
+            };
+
+            let mut stdout = stdout;
+            let mut parser = FrameParser::new();
+            let mut buffer = [0u8; 4096];
+
+            loop {
+                let (outputs, done) = match stdout.read(&mut buffer) {
+                    Ok(0) | Err(_) => (parser.finish(), true),
+                    Ok(n) => (parser.push(&buffer[..n]), false),
+                };
+
+                for output in outputs {
+                    let call = match &output {
+                        Output::Frame(Frame::ToolCall { id, name, parameters }) => Some((
+                            id.clone(),
+                            ToolcallRequest { name: name.clone(), parameters: parameters.clone() },
+                        )),
+                        _ => None,
+                    };
+                    if tx.send(output).is_err() {
+                        return;
+                    }
+
+                    if let Some((id, request)) = call {
+                        let denied = approval.as_ref().and_then(|approval| approval.check(&request));
+                        let result = denied.unwrap_or_else(|| {
+                            runtime.block_on(registry.execute_in(request, &scope)).unwrap_or_else(|e| ToolcallResult {
+                                success: false,
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            })
+                        });
+                        let frame = Frame::ToolResult { id, result };
+                        if let Err(e) = write_frame(&replies, &frame) {
+                            log::warn!("Failed to answer agent tool call: {}", e);
+                        }
+                        if tx.send(Output::Frame(frame)).is_err() {
+                            return;
+                        }
+                    }
+                }
+                ctx.request_repaint();
+
+                if done {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self { child, stdin, output })
+    }
+
+    /// Send a frame to the agent
+    pub fn send(&self, frame: &Frame) -> Result<()> {
+        write_frame(&self.stdin, frame)
+    }
+
+}
+
+impl RunningAgent for AgentProcess {
+    fn send_message(&self, text: &str) -> Result<()> {
+        self.send(&Frame::Message { text: text.to_string() })
+    }
+
+    fn take_output(&self) -> Vec<Output> {
+        self.output.try_iter().collect()
+    }
+
+    fn exited(&mut self) -> Option<String> {
+        self.child.try_wait().ok().flatten().map(|status| status.to_string())
+    }
+
+    fn pid(&self) -> Option<u32> {
+        Some(self.child.id())
+    }
+}
+
+impl Drop for AgentProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn write_frame(stdin: &Mutex<ChildStdin>, frame: &Frame) -> Result<()> {
+    let mut stdin = stdin.lock().map_err(|_| anyhow::anyhow!("Agent stdin lock poisoned"))?;
+    stdin.write_all(frame.encode().as_bytes())?;
+    stdin.flush()?;
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use super::types::ToolcallResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Marks a protocol line in an agent's output. Everything else is free-form text.
+pub const FRAME_PREFIX: &str = "@@agent ";
+
+/// One protocol message between the dashboard and an agent process.
+///
+/// Frames are written as a single line: `FRAME_PREFIX` followed by the frame as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Frame {
+    /// The agent asks the dashboard to run a tool
+    ToolCall {
+        id: String,
+        name: String,
+        #[serde(default)]
+        parameters: Value,
+    },
+    /// The dashboard's answer to a tool call with the same id
+    ToolResult {
+        id: String,
+        #[serde(flatten)]
+        result: ToolcallResult,
+    },
+    /// Diagnostics that belong in the log rather than the chat
+    Log {
+        #[serde(default)]
+        level: LogLevel,
+        message: String,
+    },
+    /// The agent's state changed
+    Status {
+        state: AgentState,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    /// Chat text, from the user to the agent or back
+    Message { text: String },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentState {
+    /// Waiting for input
+    Idle,
+    /// Working on a reply
+    Busy,
+    /// Stuck until the user intervenes
+    Error,
+}
+
+impl Frame {
+    /// The frame as a protocol line, newline included
+    pub fn encode(&self) -> String {
+        // Frames only hold strings and JSON values, so serializing can't fail
+        let json = serde_json::to_string(self).unwrap_or_default();
+        format!("{}{}\n", FRAME_PREFIX, json)
+    }
+}
+
+/// A piece of agent output: a protocol frame or a line of free-form text
+#[derive(Debug, Clone, PartialEq)]
+pub enum Output {
+    Frame(Frame),
+    Text(String),
+}
+
+/// Splits raw agent output into frames and text lines.
+///
+/// Output may arrive in arbitrary chunks, split inside a line or a UTF-8 character.
+/// A frame printed after text without a newline (progress output, a prompt) still
+/// parses, and malformed frames come back as text so nothing the agent printed is lost.
+#[derive(Debug, Default)]
+pub struct FrameParser {
+    buffer: Vec<u8>,
+}
+
+impl FrameParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of output, returning every complete line in it
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Output> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut outputs = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            outputs.extend(parse_line(&String::from_utf8_lossy(&line[..end])));
+        }
+        outputs
+    }
+
+    /// Flush a final line that has no newline, e.g. when the process exits
+    pub fn finish(&mut self) -> Vec<Output> {
+        if self.buffer.is_empty() {
+            return Vec::new();
+        }
+        let line = std::mem::take(&mut self.buffer);
+        parse_line(&String::from_utf8_lossy(&line))
+    }
+}
+
+/// Parse one line of output, without its newline
+pub fn parse_line(line: &str) -> Vec<Output> {
+    let line = line.strip_suffix('\r').unwrap_or(line);
+
+    let Some(start) = line.find(FRAME_PREFIX) else {
+        return vec![Output::Text(line.to_string())];
+    };
+
+    match serde_json::from_str::<Frame>(&line[start + FRAME_PREFIX.len()..]) {
+        Ok(frame) => {
+            let before = &line[..start];
+            let mut outputs = Vec::new();
+            if !before.trim().is_empty() {
+                outputs.push(Output::Text(before.to_string()));
+            }
+            outputs.push(Output::Frame(frame));
+            outputs
+        }
+        Err(e) => {
+            log::debug!("Malformed agent frame '{}': {}", line, e);
+            vec![Output::Text(line.to_string())]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let frames = [
+            Frame::ToolCall {
+                id: "1".into(),
+                name: "shell".into(),
+                parameters: serde_json::json!({ "command": "ls" }),
+            },
+            Frame::ToolResult {
+                id: "1".into(),
+                result: ToolcallResult { success: true, output: "Cargo.toml\n".into(), error: None },
+            },
+            Frame::Log { level: LogLevel::Warn, message: "slow".into() },
+            Frame::Status { state: AgentState::Busy, detail: Some("reading files".into()) },
+            Frame::Message { text: "done".into() },
+        ];
+
+        let mut parser = FrameParser::new();
+        let encoded: String = frames.iter().map(Frame::encode).collect();
+        let parsed = parser.push(encoded.as_bytes());
+
+        let expected: Vec<Output> = frames.into_iter().map(Output::Frame).collect();
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn test_interleaved_text() {
+        let mut parser = FrameParser::new();
+        let output = "Compiling...\r\n50% @@agent {\"type\":\"status\",\"state\":\"idle\"}\n@@agent {broken\n";
+
+        let parsed = parser.push(output.as_bytes());
+        assert_eq!(
+            parsed,
+            vec![
+                Output::Text("Compiling...".into()),
+                Output::Text("50% ".into()),
+                Output::Frame(Frame::Status { state: AgentState::Idle, detail: None }),
+                Output::Text("@@agent {broken".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        let mut parser = FrameParser::new();
+        let line = Frame::Message { text: "héllo".into() }.encode();
+        let bytes = line.as_bytes();
+        // Split inside the two-byte 'é'
+        let split = line.find('é').unwrap() + 1;
+
+        assert!(parser.push(&bytes[..split]).is_empty());
+        assert_eq!(parser.push(&bytes[split..]), vec![Output::Frame(Frame::Message { text: "héllo".into() })]);
+
+        assert!(parser.push(b"$ ").is_empty());
+        assert_eq!(parser.finish(), vec![Output::Text("$ ".into())]);
+        assert!(parser.finish().is_empty());
+    }
+}
@@ -1,6 +1,7 @@
-use super::types::{Toolcall, ToolcallResult, ToolcallSchema};
+use super::types::{Toolcall, ToolcallResult, ToolcallSchema, ToolcallScope};
 use anyhow::Result;
 use serde_json::Value;
+use std::path::PathBuf;
 use std::process::Command;
 use std::time::Duration;
 
@@ -52,6 +53,10 @@ impl Toolcall for ShellToolcall {
     }
 
     async fn execute(&self, parameters: Value) -> Result<ToolcallResult> {
+        self.execute_in(parameters, &ToolcallScope::default()).await
+    }
+
+    async fn execute_in(&self, parameters: Value, scope: &ToolcallScope) -> Result<ToolcallResult> {
         let command_str = parameters
             .get("command")
             .and_then(|v| v.as_str())
@@ -63,19 +68,7 @@ impl Toolcall for ShellToolcall {
 
         log::info!("Executing shell command: {}", command_str);
 
-        let mut cmd = if cfg!(target_os = "windows") {
-            let mut c = Command::new("cmd");
-            c.args(["/C", command_str]);
-            c
-        } else {
-            let mut c = Command::new("sh");
-            c.args(["-c", command_str]);
-            c
-        };
-
-        if let Some(dir) = working_dir {
-            cmd.current_dir(dir);
-        }
+        let mut cmd = shell_command(command_str, working_dir, scope)?;
 
         let output = tokio::time::timeout(self.timeout, tokio::task::spawn_blocking(move || {
             cmd.output()
@@ -103,3 +96,79 @@ impl Toolcall for ShellToolcall {
         })
     }
 }
+
+/// The command running `command_str`, in the scope's sandbox when the agent has one.
+/// Fails rather than running unconfined when the sandbox can't be set up.
+fn shell_command(command_str: &str, working_dir: Option<&str>, scope: &ToolcallScope) -> Result<Command> {
+    let (program, mut args) = if cfg!(target_os = "windows") {
+        ("cmd", vec!["/C".to_string(), command_str.to_string()])
+    } else {
+        ("sh", vec!["-c".to_string(), command_str.to_string()])
+    };
+
+    if !scope.sandbox.enabled {
+        let mut cmd = Command::new(program);
+        cmd.args(&args);
+        if let Some(dir) = working_dir {
+            cmd.current_dir(dir);
+        }
+        return Ok(cmd);
+    }
+
+    // bubblewrap starts in the workspace, so change directory inside the sandbox
+    if let (Some(dir), false) = (working_dir, cfg!(target_os = "windows")) {
+        args = ["-c", "cd -- \"$1\" && eval \"$2\"", "sh", dir, command_str].map(String::from).to_vec();
+    }
+    let launch = scope.sandbox.wrap(program, &args, scope.workspace.as_deref())?;
+    let mut cmd = Command::new(&launch.program);
+    cmd.args(&launch.args);
+    launch.apply_env(&mut cmd);
+    if let Some(dir) = working_dir.map(PathBuf::from).or_else(|| scope.workspace.clone()) {
+        cmd.current_dir(dir);
+    }
+    Ok(cmd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::{SandboxBackend, SandboxConfig};
+
+    #[test]
+    fn test_sandboxed_call_is_wrapped() {
+        let scope = ToolcallScope {
+            sandbox: SandboxConfig { enabled: true, network: false, ..SandboxConfig::default() },
+            workspace: Some(PathBuf::from("/work/repo")),
+        };
+        // Without bubblewrap installed the call fails instead of running unconfined
+        if let Ok(cmd) = shell_command("make test", None, &scope) {
+            assert_eq!(cmd.get_program(), "bwrap");
+            let args: Vec<_> = cmd.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+            assert!(args.contains(&"--unshare-net".to_string()));
+            assert!(args.ends_with(&["sh".to_string(), "-c".to_string(), "make test".to_string()]));
+        }
+
+        let open = shell_command("make test", None, &ToolcallScope::default()).unwrap();
+        assert_ne!(open.get_program(), "bwrap");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_sandboxed_working_dir() {
+        let scope = ToolcallScope {
+            sandbox: SandboxConfig {
+                enabled: true,
+                backend: SandboxBackend::Environment,
+                clean_env: true,
+                ..SandboxConfig::default()
+            },
+            workspace: Some(std::env::temp_dir()),
+        };
+        let result = ShellToolcall::new()
+            .execute_in(serde_json::json!({ "command": "pwd; echo ${CARGO_PKG_NAME:-clean}", "working_dir": "/" }), &scope)
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.output.lines().collect::<Vec<_>>(), ["/", "clean"]);
+    }
+}
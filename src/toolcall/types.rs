@@ -1,7 +1,9 @@
+use crate::sandbox::SandboxConfig;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
@@ -18,7 +20,7 @@ pub struct ToolcallRequest {
     pub parameters: Value,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct ToolcallResult {
     pub success: bool,
@@ -26,11 +28,24 @@ pub struct ToolcallResult {
     pub error: Option<String>,
 }
 
+/// Where an agent's tool calls run: under the agent's sandbox, in its workspace
+#[derive(Debug, Clone, Default)]
+pub struct ToolcallScope {
+    pub sandbox: SandboxConfig,
+    pub workspace: Option<PathBuf>,
+}
+
 #[async_trait::async_trait]
 #[allow(dead_code)]
 pub trait Toolcall: Send + Sync {
     fn get_schema(&self) -> ToolcallSchema;
     async fn execute(&self, parameters: Value) -> Result<ToolcallResult>;
+
+    /// Run for an agent in `scope`. Tools that start processes run them in the agent's
+    /// sandbox; the rest work inside the dashboard and run as usual.
+    async fn execute_in(&self, parameters: Value, _scope: &ToolcallScope) -> Result<ToolcallResult> {
+        self.execute(parameters).await
+    }
 }
 
 #[allow(dead_code)]
@@ -63,6 +78,15 @@ impl ToolcallRegistry {
 
         tool.execute(request.parameters).await
     }
+
+    pub async fn execute_in(&self, request: ToolcallRequest, scope: &ToolcallScope) -> Result<ToolcallResult> {
+        let tool = self
+            .tools
+            .get(&request.name)
+            .ok_or_else(|| anyhow::anyhow!("Toolcall '{}' not found", request.name))?;
+
+        tool.execute_in(request.parameters, scope).await
+    }
 }
 
 impl Default for ToolcallRegistry {
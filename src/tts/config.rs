@@ -1,6 +1,7 @@
-/// TTS configuration and data types
+//! TTS configuration and data types
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Voice identifier (model name)
@@ -33,6 +34,14 @@ pub struct VoiceMetadata {
     pub config_path: PathBuf,
 }
 
+fn default_history_max_age_days() -> u32 {
+    7
+}
+
+fn default_history_max_size_mb() -> u64 {
+    200
+}
+
 /// TTS configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TTSConfig {
@@ -48,13 +57,24 @@ pub struct TTSConfig {
     pub audio_device: Option<String>,
     /// Directory containing Piper voice models
     pub model_directory: PathBuf,
+    /// Days to keep synthesized audio for replay (0 = no limit)
+    #[serde(default = "default_history_max_age_days")]
+    pub history_max_age_days: u32,
+    /// Total size of kept audio in megabytes (0 = no limit)
+    #[serde(default = "default_history_max_size_mb")]
+    pub history_max_size_mb: u64,
+    /// Voice for each speaker in exported conversations, by agent name ("You" for
+    /// your own messages)
+    #[serde(default)]
+    pub speaker_voices: BTreeMap<String, VoiceId>,
 }
 
 impl Default for TTSConfig {
     fn default() -> Self {
         // Use XDG config directory or fallback to ~/.config
         let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("~/.config"))
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+            .unwrap_or_else(|| PathBuf::from(".config"))
             .join("agent-dashboard")
             .join("tts")
             .join("models");
@@ -66,6 +86,9 @@ impl Default for TTSConfig {
             playback_speed: 1.0,
             audio_device: None,
             model_directory: config_dir,
+            history_max_age_days: default_history_max_age_days(),
+            history_max_size_mb: default_history_max_size_mb(),
+            speaker_voices: BTreeMap::new(),
         }
     }
 }
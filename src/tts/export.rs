@@ -0,0 +1,261 @@
+//! Narrated audio exports
+//!
+//! Renders a conversation into a single WAV file to listen to like a podcast: each
+//! message in its speaker's voice, silence between messages, and a chapter marker per
+//! message. Chapters are stored as `cue ` points labelled in a `LIST`/`adtl` chunk,
+//! which audio editors and podcast tools show as markers. OGG isn't offered, as there
+//! is no Vorbis encoder among the dependencies.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::tts::{config::TTSConfig, model::ModelCache, synthesis, VoiceId};
+
+/// Silence between messages
+const GAP: Duration = Duration::from_millis(800);
+
+/// One message of the conversation
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub speaker: String,
+    pub text: String,
+    /// Chapter title, e.g. the time and speaker
+    pub title: String,
+}
+
+/// A finished export
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub path: PathBuf,
+    pub duration: Duration,
+    pub chapters: usize,
+}
+
+struct Chapter {
+    title: String,
+    /// Sample where it starts
+    start: u32,
+}
+
+/// Where exports are written unless asked otherwise
+pub fn export_dir() -> Result<PathBuf> {
+    use crate::config::AppConfig;
+    Ok(AppConfig::config_dir()?.join("tts").join("exports"))
+}
+
+/// Synthesize `segments` into one WAV file at `path`. Speakers take their voice from
+/// `speaker_voices` in the config, or else get one of the installed voices in turn.
+/// A speaker's name is read out whenever the speaker changes.
+pub fn export(config: &TTSConfig, segments: &[Segment], path: &Path) -> Result<Export> {
+    if segments.is_empty() {
+        anyhow::bail!("Nothing to export");
+    }
+
+    let mut models = ModelCache::new();
+    models.scan_models(&config.model_directory)?;
+    let installed: Vec<VoiceId> = models.list_voices().into_iter().map(|voice| voice.id).collect();
+    let voices = assign_voices(config, &installed, segments);
+
+    let mut samples = Vec::new();
+    let mut chapters = Vec::new();
+    let mut sample_rate = None;
+    let mut previous_speaker = None;
+    for segment in segments {
+        let model = models.get_or_load(&voices[&segment.speaker])?;
+        let text = if previous_speaker == Some(&segment.speaker) {
+            segment.text.clone()
+        } else {
+            format!("{}. {}", segment.speaker, segment.text)
+        };
+        previous_speaker = Some(&segment.speaker);
+
+        let audio = model
+            .synthesize(&synthesis::preprocess_text(&text))
+            .with_context(|| format!("Failed to synthesize \"{}\"", segment.title))?;
+        let rate = *sample_rate.get_or_insert(model.sample_rate);
+        if !samples.is_empty() {
+            samples.extend(std::iter::repeat_n(0.0, (GAP.as_secs_f32() * rate as f32) as usize));
+        }
+        chapters.push(Chapter {
+            title: segment.title.clone(),
+            start: samples.len() as u32,
+        });
+        samples.extend(resample(&audio, model.sample_rate, rate));
+    }
+
+    let sample_rate = sample_rate.unwrap_or(22050);
+    write_wav(path, &samples, sample_rate, &chapters)?;
+    log::info!("Exported {} messages of narration to {:?}", chapters.len(), path);
+
+    Ok(Export {
+        path: path.to_path_buf(),
+        duration: Duration::from_secs_f64(samples.len() as f64 / sample_rate as f64),
+        chapters: chapters.len(),
+    })
+}
+
+/// Voice for each speaker: the configured one, else the selected voice for the first
+/// speaker and the other installed voices in turn for the rest
+fn assign_voices(config: &TTSConfig, installed: &[VoiceId], segments: &[Segment]) -> HashMap<String, VoiceId> {
+    let mut others: Vec<&VoiceId> = installed.iter().filter(|voice| **voice != config.selected_voice).collect();
+    others.sort();
+    let mut rotation = std::iter::once(&config.selected_voice).chain(others).cycle();
+
+    let mut voices = HashMap::new();
+    for segment in segments {
+        if voices.contains_key(&segment.speaker) {
+            continue;
+        }
+        let voice = match config.speaker_voices.get(&segment.speaker) {
+            Some(voice) => voice.clone(),
+            None => rotation.next().cloned().unwrap_or_else(|| config.selected_voice.clone()),
+        };
+        voices.insert(segment.speaker.clone(), voice);
+    }
+    voices
+}
+
+/// Linear resampling, for voices that don't share a sample rate
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let ratio = from as f64 / to as f64;
+    let len = (samples.len() as f64 / ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position as usize;
+            let next = samples.get(index + 1).unwrap_or(&samples[index]);
+            let weight = (position - index as f64) as f32;
+            samples[index] * (1.0 - weight) + next * weight
+        })
+        .collect()
+}
+
+/// Write 16-bit mono WAV with the chapters as labelled cue points
+fn write_wav(path: &Path, samples: &[f32], sample_rate: u32, chapters: &[Chapter]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).context("Failed to create export directory")?;
+    }
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).context("Failed to create WAV file")?;
+    for &sample in samples {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .context("Failed to write WAV sample")?;
+    }
+    writer.finalize().context("Failed to finalize WAV file")?;
+
+    // hound doesn't write extra chunks, so append them and fix up the RIFF size
+    let mut file = OpenOptions::new().read(true).write(true).open(path).context("Failed to reopen WAV file")?;
+    file.seek(SeekFrom::End(0))?;
+    file.write_all(&chapter_chunks(chapters)).context("Failed to write chapters")?;
+    let riff_size = file.stream_position()? - 8;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&(riff_size as u32).to_le_bytes())?;
+    Ok(())
+}
+
+/// A `cue ` chunk with a point per chapter and a `LIST` chunk labelling them
+fn chapter_chunks(chapters: &[Chapter]) -> Vec<u8> {
+    let mut cue = Vec::new();
+    cue.extend((chapters.len() as u32).to_le_bytes());
+    let mut labels = b"adtl".to_vec();
+    for (index, chapter) in chapters.iter().enumerate() {
+        let id = index as u32 + 1;
+        cue.extend(id.to_le_bytes());
+        cue.extend(chapter.start.to_le_bytes());
+        cue.extend(b"data");
+        cue.extend(0u32.to_le_bytes());
+        cue.extend(0u32.to_le_bytes());
+        cue.extend(chapter.start.to_le_bytes());
+
+        let mut label = id.to_le_bytes().to_vec();
+        label.extend(chapter.title.as_bytes());
+        label.push(0);
+        labels.extend(chunk(b"labl", &label));
+    }
+
+    let mut chunks = chunk(b"cue ", &cue);
+    chunks.extend(chunk(b"LIST", &labels));
+    chunks
+}
+
+/// A RIFF chunk, padded to an even length
+fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut chunk = id.to_vec();
+    chunk.extend((data.len() as u32).to_le_bytes());
+    chunk.extend(data);
+    if data.len() % 2 == 1 {
+        chunk.push(0);
+    }
+    chunk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(speaker: &str) -> Segment {
+        Segment {
+            speaker: speaker.to_string(),
+            text: "Hello there".to_string(),
+            title: speaker.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_assign_voices() {
+        let mut config = TTSConfig::default();
+        config.speaker_voices.insert("Reviewer".to_string(), "en-high".to_string());
+        let installed = ["default", "en-low", "en-high"].map(String::from);
+        let segments = ["You", "Coder", "Reviewer", "Tester", "You"].map(segment);
+
+        let voices = assign_voices(&config, &installed, &segments);
+        assert_eq!(voices["You"], "default");
+        assert_eq!(voices["Coder"], "en-high");
+        assert_eq!(voices["Reviewer"], "en-high");
+        assert_eq!(voices["Tester"], "en-low");
+
+        let voices = assign_voices(&config, &[], &segments);
+        assert_eq!(voices["Coder"], "default");
+    }
+
+    #[test]
+    fn test_resample() {
+        assert_eq!(resample(&[0.0, 1.0, 0.0, 1.0], 2, 1), vec![0.0, 0.0]);
+        assert_eq!(resample(&[0.0, 1.0], 1, 2), vec![0.0, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_write_wav_with_chapters() {
+        let path = std::env::temp_dir().join(format!("dashboard-export-{}.wav", uuid::Uuid::new_v4().simple()));
+        let chapters = [
+            Chapter { title: "09:00 You".to_string(), start: 0 },
+            Chapter { title: "09:01 Coder".to_string(), start: 100 },
+        ];
+        write_wav(&path, &[0.25; 200], 16000, &chapters).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_size, bytes.len() - 8);
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("cue ") && text.contains("09:01 Coder\0"));
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.len(), 200);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+//! History of synthesized audio
+//!
+//! Each WAV the player writes is recorded with the message, text and voice it came
+//! from in `history.json` next to the audio, so it can be replayed later. Audio past
+//! the configured age or total size is deleted, oldest first.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::tts::{TTSRequest, VoiceId};
+
+/// A synthesized audio file and what it says
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: Uuid,
+    /// Chat message spoken, or the request's own id
+    pub message_id: Uuid,
+    pub text: String,
+    pub voice_id: VoiceId,
+    pub speed: f32,
+    pub path: PathBuf,
+    pub created: DateTime<Utc>,
+    /// Length of the audio, at the speed it was saved with
+    pub duration_secs: f32,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default)]
+    entries: Vec<HistoryEntry>,
+}
+
+/// How much audio to keep; 0 means no limit
+#[derive(Debug, Clone, Copy)]
+pub struct Retention {
+    pub max_age_days: u32,
+    pub max_size_mb: u64,
+}
+
+/// Synthesized audio kept for replay, oldest first
+///
+/// Cloning yields a handle to the same history.
+#[derive(Clone)]
+pub struct TTSHistory {
+    audio_dir: PathBuf,
+    entries: Arc<Mutex<Vec<HistoryEntry>>>,
+}
+
+impl TTSHistory {
+    /// Load the history kept in `audio_dir`. A damaged index is logged and started over,
+    /// leaving its audio to the age limit.
+    pub fn open(audio_dir: &Path) -> Self {
+        let path = audio_dir.join("history.json");
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str::<HistoryFile>(&contents) {
+                Ok(file) => file.entries,
+                Err(e) => {
+                    log::warn!("Failed to parse TTS history {:?}: {}", path, e);
+                    Vec::new()
+                }
+            },
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            audio_dir: audio_dir.to_path_buf(),
+            entries: Arc::new(Mutex::new(entries)),
+        }
+    }
+
+    /// Entries, newest first
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.entries.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<HistoryEntry> {
+        self.entries.lock().unwrap().iter().find(|entry| entry.id == id).cloned()
+    }
+
+    /// Record audio saved at `path` for `request`
+    pub fn record(&self, request: &TTSRequest, path: PathBuf, duration: Duration) -> Result<()> {
+        let bytes = fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+        let mut entries = self.entries.lock().unwrap();
+        entries.push(HistoryEntry {
+            id: Uuid::new_v4(),
+            message_id: request.message_id,
+            text: request.text.clone(),
+            voice_id: request.voice_id.clone(),
+            speed: request.speed,
+            path,
+            created: Utc::now(),
+            duration_secs: duration.as_secs_f32(),
+            bytes,
+        });
+        self.save(&entries)
+    }
+
+    /// Delete an entry and its audio
+    pub fn remove(&self, id: Uuid) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(index) = entries.iter().position(|entry| entry.id == id) else {
+            return Ok(());
+        };
+        let entry = entries.remove(index);
+        remove_audio(&entry.path);
+        self.save(&entries)
+    }
+
+    /// Delete audio beyond `retention`: entries older than the age limit or whose file
+    /// is gone, then the oldest until the rest fit the size limit. WAVs in the audio
+    /// directory without an entry, e.g. from before the history, go by the age limit.
+    /// Returns how many files were deleted.
+    pub fn prune(&self, retention: Retention, now: DateTime<Utc>) -> Result<usize> {
+        let max_age = (retention.max_age_days > 0).then(|| chrono::Duration::days(retention.max_age_days as i64));
+        let max_bytes = retention.max_size_mb * 1024 * 1024;
+
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        let mut expired = Vec::new();
+        entries.retain(|entry| {
+            let keep = entry.path.exists() && max_age.is_none_or(|age| now - entry.created <= age);
+            if !keep {
+                expired.push(entry.path.clone());
+            }
+            keep
+        });
+        if max_bytes > 0 {
+            let mut total: u64 = entries.iter().map(|entry| entry.bytes).sum();
+            let over = entries
+                .iter()
+                .take_while(|entry| {
+                    let over = total > max_bytes;
+                    total = total.saturating_sub(entry.bytes);
+                    over
+                })
+                .count();
+            expired.extend(entries.drain(..over).map(|entry| entry.path));
+        }
+        let mut deleted = expired.iter().filter(|path| remove_audio(path)).count();
+
+        if let Some(age) = max_age.and_then(|age| age.to_std().ok()) {
+            deleted += self.remove_untracked(&entries, age)?;
+        }
+        if entries.len() != before {
+            self.save(&entries)?;
+        }
+        Ok(deleted)
+    }
+
+    fn remove_untracked(&self, entries: &[HistoryEntry], max_age: Duration) -> Result<usize> {
+        let Ok(dir) = fs::read_dir(&self.audio_dir) else { return Ok(0) };
+        let mut deleted = 0;
+        for file in dir.flatten() {
+            let path = file.path();
+            if path.extension().is_none_or(|ext| ext != "wav") || entries.iter().any(|entry| entry.path == path) {
+                continue;
+            }
+            let age = file.metadata().and_then(|meta| meta.modified()).ok().and_then(|at| at.elapsed().ok());
+            if age.is_some_and(|age| age > max_age) && remove_audio(&path) {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    fn save(&self, entries: &[HistoryEntry]) -> Result<()> {
+        let file = HistoryFile { entries: entries.to_vec() };
+        let contents = serde_json::to_string_pretty(&file).context("Failed to serialize TTS history")?;
+        fs::write(self.audio_dir.join("history.json"), contents).context("Failed to write TTS history")
+    }
+}
+
+/// Delete an audio file, returning whether it was there
+fn remove_audio(path: &Path) -> bool {
+    match fs::remove_file(path) {
+        Ok(()) => true,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        Err(e) => {
+            log::warn!("Failed to delete TTS audio {:?}: {}", path, e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dashboard-tts-history-{}", Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn record(history: &TTSHistory, dir: &Path, name: &str, bytes: usize) -> Uuid {
+        let path = dir.join(name);
+        fs::write(&path, vec![0u8; bytes]).unwrap();
+        let request = TTSRequest::new(name.to_string(), "default".to_string(), 1.0);
+        history.record(&request, path, Duration::from_secs(1)).unwrap();
+        history.entries()[0].id
+    }
+
+    #[test]
+    fn test_record_and_reopen() {
+        let dir = temp_dir();
+        let history = TTSHistory::open(&dir);
+        record(&history, &dir, "a.wav", 10);
+        let b = record(&history, &dir, "b.wav", 10);
+
+        let reopened = TTSHistory::open(&dir);
+        let texts: Vec<_> = reopened.entries().into_iter().map(|entry| entry.text).collect();
+        assert_eq!(texts, ["b.wav", "a.wav"]);
+        assert_eq!(reopened.get(b).unwrap().bytes, 10);
+
+        reopened.remove(b).unwrap();
+        assert!(!dir.join("b.wav").exists());
+        assert_eq!(TTSHistory::open(&dir).entries().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = temp_dir();
+        let history = TTSHistory::open(&dir);
+        let mb = 1024 * 1024;
+        record(&history, &dir, "old.wav", 10);
+        record(&history, &dir, "big.wav", mb);
+        record(&history, &dir, "new.wav", mb / 2);
+        fs::write(dir.join("untracked.wav"), b"").unwrap();
+        history.entries.lock().unwrap()[0].created -= chrono::Duration::days(8);
+
+        // Past the age limit, then the oldest over 1 MB; the untracked file is too new
+        let retention = Retention { max_age_days: 7, max_size_mb: 1 };
+        assert_eq!(history.prune(retention, Utc::now()).unwrap(), 2);
+        let texts: Vec<_> = history.entries().into_iter().map(|entry| entry.text).collect();
+        assert_eq!(texts, ["new.wav"]);
+        assert!(dir.join("untracked.wav").exists());
+
+        let unlimited = Retention { max_age_days: 0, max_size_mb: 0 };
+        assert_eq!(history.prune(unlimited, Utc::now() + chrono::Duration::days(365)).unwrap(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,9 +1,11 @@
-/// Text-to-Speech module for Ollama chat interface
-///
-/// This module provides TTS capabilities using Candle (Rust ML framework)
-/// and Piper TTS models for converting agent messages to speech.
+//! Text-to-Speech module for Ollama chat interface
+//!
+//! This module provides TTS capabilities using Candle (Rust ML framework)
+//! and Piper TTS models for converting agent messages to speech.
 
 pub mod config;
+pub mod export;
+pub mod history;
 pub mod model;
 pub mod synthesis;
 pub mod playback;
@@ -13,13 +15,23 @@ pub mod service;
 pub use config::{TTSConfig, VoiceId};
 pub use service::TTSService;
 
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Which requests go first; within a level they play in the order they came
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Priority {
+    /// Spoken without being asked: auto-speak, announcements, pipelines, scripts
+    #[default]
+    Auto,
+    /// The user asked for it, e.g. with a speak button
+    User,
+}
+
 /// TTS request for synthesizing and playing text
 #[derive(Debug, Clone)]
 pub struct TTSRequest {
-    /// Unique identifier for this request
-    #[allow(dead_code)]
+    /// Unique identifier for this request, or of the chat message it speaks
     pub message_id: Uuid,
     /// Text to synthesize
     pub text: String,
@@ -27,6 +39,7 @@ pub struct TTSRequest {
     pub voice_id: VoiceId,
     /// Playback speed (0.5 to 2.0)
     pub speed: f32,
+    pub priority: Priority,
 }
 
 impl TTSRequest {
@@ -36,30 +49,52 @@ impl TTSRequest {
             text,
             voice_id,
             speed: speed.clamp(0.5, 2.0),
+            priority: Priority::default(),
+        }
+    }
+
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Speak a chat message, so playback progress can be matched up with it
+    pub fn for_message(mut self, message_id: Uuid) -> Self {
+        self.message_id = message_id;
+        self
+    }
+}
+
+/// How far playback of the current request has got
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlaybackProgress {
+    pub position: Duration,
+    /// Length of the request's audio at its playback speed
+    pub duration: Duration,
+}
+
+impl PlaybackProgress {
+    /// Share of the audio played, from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 0.0;
         }
+        (self.position.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
     }
 }
 
 /// Current status of the TTS queue
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[allow(dead_code)]
 pub struct QueueStatus {
     /// Currently playing request
     pub current: Option<TTSRequest>,
-    /// Number of queued requests
+    /// Number of requests waiting to play, including ones already being synthesized
     pub queue_length: usize,
     /// Whether audio is currently playing
     pub playing: bool,
-}
-
-impl Default for QueueStatus {
-    fn default() -> Self {
-        Self {
-            current: None,
-            queue_length: 0,
-            playing: false,
-        }
-    }
+    /// Where playback of `current` is
+    pub progress: PlaybackProgress,
 }
 
 /// Commands that can be sent to the TTS service
@@ -76,6 +111,12 @@ pub enum TTSCommand {
     ClearQueue,
     /// Get current status
     GetStatus,
+    /// Play on another output device (`None` for the default), keeping the queue
+    SetDevice(Option<String>),
+    /// Jump to a position in the current request
+    Seek(Duration),
+    /// Play a history entry again, ahead of the queue
+    Replay(Uuid),
     /// Shutdown the service
     Shutdown,
 }
@@ -1,7 +1,7 @@
-/// Piper TTS model loading and management
-///
-/// This module handles loading Piper ONNX models using Candle and managing
-/// the model cache for efficient inference.
+//! Piper TTS model loading and management
+//!
+//! This module handles loading Piper ONNX models using Candle and managing
+//! the model cache for efficient inference.
 
 use anyhow::{Context, Result};
 use std::path::PathBuf;
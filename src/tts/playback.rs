@@ -1,28 +1,57 @@
-/// Audio playback with WAV file generation
-///
-/// This implementation saves generated audio to WAV files for playback.
-/// Files are saved to ~/.config/agent-dashboard/tts/audio/
-///
-/// Future enhancement: Use rodio (optional feature) for direct playback
+//! Audio playback
+//!
+//! Generated audio is always saved to WAV files in ~/.config/agent-dashboard/tts/audio/.
+//! With the `tts` feature it is also played through rodio on the configured output
+//! device, following hotplug: see [`output`].
 
 use anyhow::{Result, Context};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use std::path::PathBuf;
+#[cfg(feature = "tts")]
+use std::sync::atomic::AtomicU64;
+#[cfg(not(feature = "tts"))]
+use std::{sync::Mutex, time::Instant};
+use std::path::{Path, PathBuf};
 use std::fs;
 use hound;
 
-/// Audio playback manager (stub implementation)
+/// Names of the audio output devices, for picking one in the TTS panel. Empty when
+/// built without the `tts` feature, which leaves only the default.
+pub fn output_devices() -> Vec<String> {
+    #[cfg(feature = "tts")]
+    {
+        output::device_names()
+    }
+    #[cfg(not(feature = "tts"))]
+    {
+        Vec::new()
+    }
+}
+
+/// How often [`AudioPlayer::wait_for_completion`] reports the position
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Audio playback manager
 #[derive(Clone)]
 pub struct AudioPlayer {
     playing: Arc<AtomicBool>,
+    /// Microseconds into the audio playing, kept up by the output thread
+    #[cfg(feature = "tts")]
+    position: Arc<AtomicU64>,
+    #[cfg(feature = "tts")]
+    output: std::sync::mpsc::Sender<output::Command>,
+    /// Without an output, playback is timed as if there were one: when it started and
+    /// how long the audio is
+    #[cfg(not(feature = "tts"))]
+    simulated: Arc<Mutex<Option<(Instant, Duration)>>>,
 }
 
 impl AudioPlayer {
-    /// Create a new audio player
-    pub fn new() -> Result<Self> {
-        log::info!("TTS Audio Player initialized (WAV file output mode)");
+    /// Create a new audio player on the named output device, or the default one
+    pub fn new(device: Option<String>) -> Result<Self> {
+        #[cfg(not(feature = "tts"))]
+        log::info!("TTS Audio Player initialized (WAV file output mode, device {:?} unused)", device);
 
         // Create audio output directory
         let audio_dir = Self::get_audio_dir()?;
@@ -32,22 +61,49 @@ impl AudioPlayer {
             log::info!("Created audio directory: {:?}", audio_dir);
         }
 
+        let playing = Arc::new(AtomicBool::new(false));
+        #[cfg(feature = "tts")]
+        let position = Arc::new(AtomicU64::new(0));
+        #[cfg(feature = "tts")]
+        let output = {
+            let (tx, rx) = std::sync::mpsc::channel();
+            let thread_playing = playing.clone();
+            let thread_position = position.clone();
+            std::thread::Builder::new()
+                .name("tts-output".to_string())
+                .spawn(move || output::run(device, rx, thread_playing, thread_position))
+                .context("Failed to start audio output thread")?;
+            tx
+        };
+
         Ok(Self {
-            playing: Arc::new(AtomicBool::new(false)),
+            playing,
+            #[cfg(feature = "tts")]
+            position,
+            #[cfg(feature = "tts")]
+            output,
+            #[cfg(not(feature = "tts"))]
+            simulated: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Switch to another output device, or the default for `None`. Audio that is
+    /// playing carries on from where it was on the new device.
+    pub fn set_device(&self, device: Option<String>) {
+        log::info!("TTS output device: {}", device.as_deref().unwrap_or("system default"));
+        #[cfg(feature = "tts")]
+        let _ = self.output.send(output::Command::SetDevice(device));
+    }
+
     /// Get the audio output directory
-    fn get_audio_dir() -> Result<PathBuf> {
+    pub fn get_audio_dir() -> Result<PathBuf> {
         use crate::config::AppConfig;
         let config_dir = AppConfig::config_dir()?;
         Ok(config_dir.join("tts").join("audio"))
     }
 
-    /// Play audio samples by saving to WAV file
-    pub fn play(&self, samples: Vec<f32>, sample_rate: u32, speed: f32) -> Result<()> {
-        self.playing.store(true, Ordering::SeqCst);
-
+    /// Play audio samples by saving to WAV file, returning its path
+    pub fn play(&self, samples: Vec<f32>, sample_rate: u32, speed: f32) -> Result<PathBuf> {
         // Apply speed adjustment by resampling
         let adjusted_samples = if (speed - 1.0).abs() > 0.01 {
             Self::adjust_speed(&samples, speed)
@@ -76,9 +132,40 @@ impl AudioPlayer {
         Self::save_wav(&audio_path, &adjusted_samples, sample_rate)?;
         log::info!("Saved TTS audio to: {:?}", audio_path);
 
+        self.play_samples(adjusted_samples, sample_rate)?;
+        Ok(audio_path)
+    }
+
+    /// Play audio as it is, without saving it, e.g. a replay from the history
+    pub fn play_samples(&self, samples: Vec<f32>, sample_rate: u32) -> Result<()> {
+        self.playing.store(true, Ordering::SeqCst);
+
+        #[cfg(feature = "tts")]
+        self.output
+            .send(output::Command::Play { samples, sample_rate })
+            .context("Audio output thread stopped")?;
+        #[cfg(not(feature = "tts"))]
+        {
+            let duration = Duration::from_secs_f32(samples.len() as f32 / sample_rate as f32);
+            *self.simulated.lock().unwrap() = Some((Instant::now(), duration));
+        }
+
         Ok(())
     }
 
+    /// Read a WAV file written by [`Self::play`] back into samples and sample rate
+    pub fn load_wav(path: &Path) -> Result<(Vec<f32>, u32)> {
+        let mut reader = hound::WavReader::open(path)
+            .with_context(|| format!("Failed to open WAV file {:?}", path))?;
+        let sample_rate = reader.spec().sample_rate;
+        let samples = reader
+            .samples::<i16>()
+            .map(|sample| sample.map(|s| s as f32 / i16::MAX as f32))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to read WAV samples")?;
+        Ok((samples, sample_rate))
+    }
+
     /// Save audio samples to WAV file
     fn save_wav(path: &PathBuf, samples: &[f32], sample_rate: u32) -> Result<()> {
         let spec = hound::WavSpec {
@@ -93,7 +180,7 @@ impl AudioPlayer {
 
         // Convert f32 samples (-1.0 to 1.0) to i16 samples
         for &sample in samples {
-            let amplitude = (sample.max(-1.0).min(1.0) * i16::MAX as f32) as i16;
+            let amplitude = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
             writer.write_sample(amplitude)
                 .context("Failed to write WAV sample")?;
         }
@@ -126,6 +213,8 @@ impl AudioPlayer {
     /// Stop playback
     pub fn stop(&self) {
         log::debug!("TTS playback stopped");
+        #[cfg(feature = "tts")]
+        let _ = self.output.send(output::Command::Stop);
         self.playing.store(false, Ordering::SeqCst);
     }
 
@@ -146,19 +235,234 @@ impl AudioPlayer {
         self.playing.load(Ordering::SeqCst)
     }
 
-    /// Wait for playback to complete (simulated)
-    pub fn wait_for_completion(&self) {
-        if self.is_playing() {
-            // Simulate a brief playback duration
-            std::thread::sleep(Duration::from_millis(500));
-            self.playing.store(false, Ordering::SeqCst);
+    /// How far into the audio playback is, at its playback speed
+    #[cfg(feature = "tts")]
+    pub fn position(&self) -> Duration {
+        Duration::from_micros(self.position.load(Ordering::SeqCst))
+    }
+
+    /// How far into the audio playback is, at its playback speed
+    #[cfg(not(feature = "tts"))]
+    pub fn position(&self) -> Duration {
+        self.simulated
+            .lock()
+            .unwrap()
+            .map_or(Duration::ZERO, |(started, length)| started.elapsed().min(length))
+    }
+
+    /// Continue playback from `position` into the audio
+    pub fn seek(&self, position: Duration) {
+        log::debug!("TTS playback seeking to {:.2?}", position);
+        #[cfg(feature = "tts")]
+        let _ = self.output.send(output::Command::Seek(position));
+        #[cfg(not(feature = "tts"))]
+        if let Some((started, length)) = self.simulated.lock().unwrap().as_mut() {
+            *started = Instant::now().checked_sub(position.min(*length)).unwrap_or(*started);
+        }
+    }
+
+    /// Wait for playback to complete, passing the position to `on_progress` as it goes
+    pub fn wait_for_completion(&self, mut on_progress: impl FnMut(Duration)) {
+        while self.is_playing() {
+            #[cfg(not(feature = "tts"))]
+            if self.simulated.lock().unwrap().is_none_or(|(started, length)| started.elapsed() >= length) {
+                self.playing.store(false, Ordering::SeqCst);
+                break;
+            }
+            on_progress(self.position());
+            std::thread::sleep(PROGRESS_INTERVAL);
         }
     }
 }
 
 impl Default for AudioPlayer {
     fn default() -> Self {
-        Self::new().expect("Failed to create audio player")
+        Self::new(None).expect("Failed to create audio player")
+    }
+}
+
+/// What is left of `samples` after `played` of them went out at `sample_rate`
+#[cfg_attr(not(feature = "tts"), allow(dead_code))]
+fn remaining(samples: &[f32], sample_rate: u32, played: Duration) -> &[f32] {
+    let offset = (played.as_secs_f64() * sample_rate as f64) as usize;
+    &samples[offset.min(samples.len())..]
+}
+
+/// The output stream lives on its own thread: cpal streams can't move between threads,
+/// while the player is shared with synthesis tasks.
+///
+/// The thread checks the devices every couple of seconds. When the one to use changes,
+/// e.g. the selected device was unplugged and playback falls back to the default, or
+/// headphones became the default, the stream is opened again and the utterance that was
+/// playing continues from its position. The TTS queue itself is not touched.
+#[cfg(feature = "tts")]
+mod output {
+    use rodio::cpal::traits::{DeviceTrait, HostTrait};
+    use rodio::{cpal, OutputStream, OutputStreamBuilder, Sink};
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::mpsc::{Receiver, RecvTimeoutError};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    /// How often devices are checked for hotplug
+    const DEVICE_POLL: Duration = Duration::from_secs(2);
+    /// How often a playing utterance's position is updated and checked for the end
+    const PLAYING_POLL: Duration = Duration::from_millis(50);
+
+    pub enum Command {
+        Play { samples: Vec<f32>, sample_rate: u32 },
+        Stop,
+        SetDevice(Option<String>),
+        Seek(Duration),
+    }
+
+    struct Utterance {
+        samples: Vec<f32>,
+        sample_rate: u32,
+        /// Samples played on earlier streams
+        offset: usize,
+    }
+
+    struct Output {
+        device: String,
+        // Kept alive for the sink
+        _stream: OutputStream,
+        sink: Sink,
+    }
+
+    pub fn device_names() -> Vec<String> {
+        match cpal::default_host().output_devices() {
+            Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+            Err(e) => {
+                log::warn!("Failed to list audio output devices: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// The wanted device if it's there, otherwise the default
+    fn find_device(wanted: Option<&str>) -> Option<cpal::Device> {
+        let host = cpal::default_host();
+        wanted
+            .and_then(|name| {
+                host.output_devices()
+                    .ok()?
+                    .find(|device| device.name().is_ok_and(|n| n == name))
+            })
+            .or_else(|| host.default_output_device())
+    }
+
+    fn open(wanted: Option<&str>) -> Option<Output> {
+        let device = find_device(wanted)?;
+        let name = device.name().unwrap_or_default();
+        let opened = OutputStreamBuilder::from_device(device).and_then(|builder| builder.open_stream_or_fallback());
+        match opened {
+            Ok(mut stream) => {
+                stream.log_on_drop(false);
+                let sink = Sink::connect_new(stream.mixer());
+                log::info!("TTS playing on {}", name);
+                Some(Output {
+                    device: name,
+                    _stream: stream,
+                    sink,
+                })
+            }
+            Err(e) => {
+                log::warn!("Failed to open audio output {}: {}", name, e);
+                None
+            }
+        }
+    }
+
+    fn append(output: &Output, utterance: &Utterance) {
+        let samples = utterance.samples[utterance.offset.min(utterance.samples.len())..].to_vec();
+        output
+            .sink
+            .append(rodio::buffer::SamplesBuffer::new(1, utterance.sample_rate, samples));
+    }
+
+    pub fn run(
+        mut wanted: Option<String>,
+        commands: Receiver<Command>,
+        playing: Arc<AtomicBool>,
+        position: Arc<AtomicU64>,
+    ) {
+        let mut output = open(wanted.as_deref());
+        let mut current: Option<Utterance> = None;
+        let mut checked = Instant::now();
+
+        loop {
+            let timeout = if current.is_some() { PLAYING_POLL } else { DEVICE_POLL };
+            let mut reopen = false;
+            match commands.recv_timeout(timeout) {
+                Ok(Command::Play { samples, sample_rate }) => {
+                    let utterance = Utterance { samples, sample_rate, offset: 0 };
+                    if let Some(output) = &output {
+                        output.sink.clear();
+                        append(output, &utterance);
+                        output.sink.play();
+                    }
+                    current = Some(utterance);
+                    playing.store(true, Ordering::SeqCst);
+                    position.store(0, Ordering::SeqCst);
+                }
+                Ok(Command::Stop) => {
+                    if let Some(output) = &output {
+                        output.sink.clear();
+                    }
+                    current = None;
+                }
+                Ok(Command::SetDevice(device)) => {
+                    wanted = device;
+                    reopen = true;
+                }
+                Ok(Command::Seek(to)) => {
+                    if let Some(utterance) = &mut current {
+                        let sample = (to.as_secs_f64() * utterance.sample_rate as f64) as usize;
+                        utterance.offset = sample.min(utterance.samples.len());
+                        if let Some(output) = &output {
+                            output.sink.clear();
+                            append(output, utterance);
+                            output.sink.play();
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            if checked.elapsed() >= DEVICE_POLL {
+                checked = Instant::now();
+                let device = find_device(wanted.as_deref()).and_then(|device| device.name().ok());
+                reopen |= device != output.as_ref().map(|output| output.device.clone());
+            }
+
+            if reopen {
+                if let (Some(old), Some(utterance)) = (&output, &mut current) {
+                    let played = old.sink.get_pos();
+                    let left = super::remaining(&utterance.samples[utterance.offset..], utterance.sample_rate, played);
+                    utterance.offset = utterance.samples.len() - left.len();
+                }
+                // Close the old stream before opening one on the same device again
+                drop(output.take());
+                output = open(wanted.as_deref());
+                if let (Some(output), Some(utterance)) = (&output, &current) {
+                    append(output, utterance);
+                }
+            }
+
+            if let (Some(output), Some(utterance)) = (&output, &current) {
+                let offset = Duration::from_secs_f64(utterance.offset as f64 / utterance.sample_rate as f64);
+                position.store((offset + output.sink.get_pos()).as_micros() as u64, Ordering::SeqCst);
+            }
+
+            // Nothing can play without an output, so don't keep the queue waiting
+            let finished = output.as_ref().map_or(true, |output| output.sink.empty());
+            if current.is_some() && finished {
+                current = None;
+                playing.store(false, Ordering::SeqCst);
+            }
+        }
     }
 }
 
@@ -168,17 +472,49 @@ mod tests {
 
     #[test]
     fn test_audio_player_creation() {
-        let player = AudioPlayer::new().unwrap();
+        let player = AudioPlayer::new(None).unwrap();
         assert!(!player.is_playing());
     }
 
+    #[test]
+    #[cfg(not(feature = "tts"))]
+    fn test_simulated_position() {
+        let player = AudioPlayer::new(None).unwrap();
+        player.play(vec![0.0; 22050 * 10], 22050, 1.0).unwrap();
+        player.seek(Duration::from_secs(4));
+        let position = player.position();
+        assert!(position >= Duration::from_secs(4) && position < Duration::from_secs(5));
+        player.seek(Duration::from_secs(60));
+        assert_eq!(player.position(), Duration::from_secs(10));
+    }
+
     #[test]
     fn test_play_and_stop() {
-        let player = AudioPlayer::new().unwrap();
+        let player = AudioPlayer::new(None).unwrap();
         let samples = vec![0.0; 1000];
-        player.play(samples, 22050, 1.0).unwrap();
+        let path = player.play(samples, 22050, 1.0).unwrap();
         assert!(player.is_playing());
         player.stop();
         assert!(!player.is_playing());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_wav() {
+        let path = std::env::temp_dir().join(format!("dashboard-tts-{}.wav", uuid::Uuid::new_v4().simple()));
+        AudioPlayer::save_wav(&path, &[0.0, 0.5, -1.0], 16000).unwrap();
+        let (samples, sample_rate) = AudioPlayer::load_wav(&path).unwrap();
+        assert_eq!(sample_rate, 16000);
+        assert_eq!(samples.len(), 3);
+        assert!((samples[1] - 0.5).abs() < 0.001);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_remaining() {
+        let samples: Vec<f32> = (0..100).map(|n| n as f32).collect();
+        assert_eq!(remaining(&samples, 10, Duration::from_secs(2)).len(), 80);
+        assert_eq!(remaining(&samples, 10, Duration::from_secs(2))[0], 20.0);
+        assert!(remaining(&samples, 10, Duration::from_secs(60)).is_empty());
     }
 }
@@ -1,16 +1,23 @@
-/// TTS request queue management
+//! TTS request queue management
 
 use std::collections::VecDeque;
 use std::sync::{Arc, RwLock};
-use crate::tts::{TTSRequest, QueueStatus};
+use crate::tts::{PlaybackProgress, TTSRequest, QueueStatus};
+use std::time::Duration;
 
 const MAX_QUEUE_SIZE: usize = 50;
 
-/// Thread-safe TTS queue
+/// Thread-safe TTS queue, ordered by priority and then arrival
+///
+/// Cloning yields a handle to the same underlying queue.
+#[derive(Clone)]
 pub struct TTSQueue {
     queue: Arc<RwLock<VecDeque<TTSRequest>>>,
     current: Arc<RwLock<Option<TTSRequest>>>,
     playing: Arc<RwLock<bool>>,
+    /// Requests taken off the queue for synthesis that haven't started playing
+    prepared: Arc<RwLock<usize>>,
+    progress: Arc<RwLock<PlaybackProgress>>,
 }
 
 impl TTSQueue {
@@ -19,6 +26,8 @@ impl TTSQueue {
             queue: Arc::new(RwLock::new(VecDeque::new())),
             current: Arc::new(RwLock::new(None)),
             playing: Arc::new(RwLock::new(false)),
+            prepared: Arc::new(RwLock::new(0)),
+            progress: Arc::new(RwLock::new(PlaybackProgress::default())),
         }
     }
 
@@ -31,25 +40,49 @@ impl TTSQueue {
             return Err(format!("Queue is full (max {})", MAX_QUEUE_SIZE));
         }
 
-        queue.push_back(request);
+        // Ahead of every request of lower priority, behind the rest
+        let position = queue
+            .iter()
+            .position(|queued| queued.priority < request.priority)
+            .unwrap_or(queue.len());
+        queue.insert(position, request);
         log::debug!("Request added to queue. Queue length: {}", queue.len());
 
         Ok(())
     }
 
-    /// Get the next request from the queue
+    /// Take the next request from the queue, e.g. to synthesize it
     pub fn dequeue(&self) -> Option<TTSRequest> {
         let mut queue = self.queue.write().unwrap();
         let request = queue.pop_front();
 
-        if let Some(ref req) = request {
+        if request.is_some() {
             log::debug!("Request dequeued. Remaining: {}", queue.len());
-            *self.current.write().unwrap() = Some(req.clone());
         }
 
         request
     }
 
+    /// Mark a request as the one playing, with `duration` of audio
+    pub fn start(&self, request: TTSRequest, duration: Duration) {
+        *self.current.write().unwrap() = Some(request);
+        *self.progress.write().unwrap() = PlaybackProgress {
+            position: Duration::ZERO,
+            duration,
+        };
+        self.set_playing(true);
+    }
+
+    /// Record how far the current request has played
+    pub fn set_position(&self, position: Duration) {
+        self.progress.write().unwrap().position = position;
+    }
+
+    /// Set how many requests are synthesized or synthesizing but not yet playing
+    pub fn set_prepared(&self, count: usize) {
+        *self.prepared.write().unwrap() = count;
+    }
+
     /// Clear all queued requests
     pub fn clear(&self) {
         let mut queue = self.queue.write().unwrap();
@@ -61,13 +94,15 @@ impl TTSQueue {
     /// Get the current queue status
     pub fn status(&self) -> QueueStatus {
         let current = self.current.read().unwrap().clone();
-        let queue_length = self.queue.read().unwrap().len();
+        let queue_length = self.queue.read().unwrap().len() + *self.prepared.read().unwrap();
         let playing = *self.playing.read().unwrap();
+        let progress = *self.progress.read().unwrap();
 
         QueueStatus {
             current,
             queue_length,
             playing,
+            progress,
         }
     }
 
@@ -77,6 +112,7 @@ impl TTSQueue {
     }
 
     /// Check if queue is empty
+    #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
         self.queue.read().unwrap().is_empty()
     }
@@ -90,6 +126,7 @@ impl TTSQueue {
     /// Mark current request as complete
     pub fn complete_current(&self) {
         *self.current.write().unwrap() = None;
+        *self.progress.write().unwrap() = PlaybackProgress::default();
         self.set_playing(false);
     }
 }
@@ -103,6 +140,7 @@ impl Default for TTSQueue {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tts::Priority;
 
     fn create_test_request(text: &str) -> TTSRequest {
         TTSRequest::new(text.to_string(), "test-voice".to_string(), 1.0)
@@ -135,6 +173,19 @@ mod tests {
         assert!(queue.is_empty());
     }
 
+    #[test]
+    fn test_priority_order() {
+        let queue = TTSQueue::new();
+        queue.enqueue(create_test_request("auto 1")).unwrap();
+        queue.enqueue(create_test_request("auto 2")).unwrap();
+        queue.enqueue(create_test_request("user 1").with_priority(Priority::User)).unwrap();
+        queue.enqueue(create_test_request("user 2").with_priority(Priority::User)).unwrap();
+        queue.enqueue(create_test_request("auto 3")).unwrap();
+
+        let order: Vec<String> = std::iter::from_fn(|| queue.dequeue()).map(|r| r.text).collect();
+        assert_eq!(order, ["user 1", "user 2", "auto 1", "auto 2", "auto 3"]);
+    }
+
     #[test]
     fn test_queue_limit() {
         let queue = TTSQueue::new();
@@ -175,5 +226,20 @@ mod tests {
 
         assert_eq!(status.queue_length, 1);
         assert!(!status.playing);
+
+        let req = queue.dequeue().unwrap();
+        queue.set_prepared(1);
+        assert_eq!(queue.status().queue_length, 1);
+        queue.set_prepared(0);
+        queue.start(req, Duration::from_secs(4));
+        queue.set_position(Duration::from_secs(1));
+        let status = queue.status();
+        assert_eq!(status.queue_length, 0);
+        assert!(status.playing);
+        assert_eq!(status.progress.fraction(), 0.25);
+        assert_eq!(status.current.unwrap().text, "Test");
+
+        queue.complete_current();
+        assert_eq!(queue.status().progress, PlaybackProgress::default());
     }
 }
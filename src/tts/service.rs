@@ -1,15 +1,20 @@
-/// TTS service - main facade for text-to-speech functionality
-///
-/// This service coordinates model loading, synthesis, playback, and queue management.
+//! TTS service - main facade for text-to-speech functionality
+//!
+//! This service coordinates model loading, synthesis, playback, and queue management.
 
 use anyhow::{Context, Result};
+use chrono::Utc;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::task;
+use uuid::Uuid;
 
 use crate::tts::{
-    TTSCommand, TTSRequest, TTSResponse,
+    Priority, QueueStatus, TTSCommand, TTSRequest, TTSResponse,
     config::TTSConfig,
+    history::{Retention, TTSHistory},
     model::ModelCache,
     playback::AudioPlayer,
     queue::TTSQueue,
@@ -22,17 +27,23 @@ pub struct TTSService {
     command_tx: mpsc::Sender<TTSCommand>,
     #[allow(dead_code)]
     response_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<TTSResponse>>>,
+    queue: TTSQueue,
+    history: TTSHistory,
 }
 
 impl TTSService {
-    /// Create and start the TTS service
-    pub fn start(config: TTSConfig) -> Result<Self> {
+    /// Create and start the TTS service; `ctx` is woken whenever the queue changes
+    pub fn start(config: TTSConfig, ctx: egui::Context) -> Result<Self> {
         let (command_tx, command_rx) = mpsc::channel(32);
         let (response_tx, response_rx) = mpsc::channel(32);
+        let queue = TTSQueue::new();
+        let history = TTSHistory::open(&AudioPlayer::get_audio_dir()?);
 
         // Spawn the service task
+        let service_queue = queue.clone();
+        let service_history = history.clone();
         task::spawn(async move {
-            if let Err(e) = run_service(config, command_rx, response_tx).await {
+            if let Err(e) = run_service(config, service_queue, service_history, command_rx, response_tx, ctx).await {
                 log::error!("TTS service error: {}", e);
             }
         });
@@ -40,9 +51,21 @@ impl TTSService {
         Ok(Self {
             command_tx,
             response_rx: Arc::new(tokio::sync::Mutex::new(response_rx)),
+            queue,
+            history,
         })
     }
 
+    /// Audio synthesized so far, for listing and deleting entries
+    pub fn history(&self) -> &TTSHistory {
+        &self.history
+    }
+
+    /// Snapshot of the queue state, readable without going through the command channel
+    pub fn status(&self) -> QueueStatus {
+        self.queue.status()
+    }
+
     /// Send a command to the TTS service
     pub async fn send_command(&self, command: TTSCommand) -> Result<()> {
         self.command_tx
@@ -78,6 +101,21 @@ impl TTSService {
         self.send_command(TTSCommand::ClearQueue).await
     }
 
+    /// Jump to a position in the request playing
+    pub async fn seek(&self, position: Duration) -> Result<()> {
+        self.send_command(TTSCommand::Seek(position)).await
+    }
+
+    /// Play a history entry again
+    pub async fn replay(&self, entry_id: Uuid) -> Result<()> {
+        self.send_command(TTSCommand::Replay(entry_id)).await
+    }
+
+    /// Switch the output device without restarting the service or losing the queue
+    pub async fn set_device(&self, device: Option<String>) -> Result<()> {
+        self.send_command(TTSCommand::SetDevice(device)).await
+    }
+
     /// Get current status
     #[allow(dead_code)]
     pub async fn get_status(&self) -> Result<()> {
@@ -90,11 +128,32 @@ impl TTSService {
     }
 }
 
+/// Synthesis tasks running at once. Each works on a request ahead of the one playing,
+/// so the next one is ready when playback finishes.
+const SYNTHESIS_WORKERS: usize = 2;
+
+/// A request with its audio, waiting for its turn to play
+struct Synthesized {
+    request: TTSRequest,
+    samples: Vec<f32>,
+    sample_rate: u32,
+    /// Replayed from the history: played as is, and not recorded again
+    replay: bool,
+}
+
 /// Main service loop
+///
+/// Requests leave the queue in priority order and get a sequence number. Up to
+/// [`SYNTHESIS_WORKERS`] are synthesized in parallel on blocking tasks, and their audio
+/// plays strictly in sequence order, each starting when the previous one reports back
+/// that it finished. Replays skip the queue and take the next sequence number.
 async fn run_service(
     config: TTSConfig,
+    queue: TTSQueue,
+    history: TTSHistory,
     mut command_rx: mpsc::Receiver<TTSCommand>,
     response_tx: mpsc::Sender<TTSResponse>,
+    ctx: egui::Context,
 ) -> Result<()> {
     log::info!("TTS service starting...");
 
@@ -102,54 +161,110 @@ async fn run_service(
     let mut model_cache = ModelCache::new();
     model_cache.scan_models(&config.model_directory)?;
 
-    let audio_player = AudioPlayer::new()
+    let audio_player = AudioPlayer::new(config.audio_device.clone())
         .context("Failed to initialize audio player")?;
 
-    let queue = TTSQueue::new();
-
     log::info!("TTS service initialized with {} voices", model_cache.list_voices().len());
 
-    let mut processing = false;
+    let retention = Retention {
+        max_age_days: config.history_max_age_days,
+        max_size_mb: config.history_max_size_mb,
+    };
+    match history.prune(retention, Utc::now()) {
+        Ok(0) => {}
+        Ok(deleted) => log::info!("Deleted {} old TTS audio files", deleted),
+        Err(e) => log::warn!("Failed to clean up TTS history: {}", e),
+    }
+
+    let (synthesized_tx, mut synthesized_rx) = mpsc::unbounded_channel::<(u64, Result<Synthesized>)>();
+    let (finished_tx, mut finished_rx) = mpsc::unbounded_channel::<u64>();
 
+    // Sequence numbers: the next to hand out, the next to play, and the first that
+    // counts after the queue was cleared
+    let mut next_seq = 0u64;
+    let mut next_play = 0u64;
+    let mut valid_from = 0u64;
+    let mut synthesizing = 0usize;
+    let mut ready: BTreeMap<u64, Result<Synthesized>> = BTreeMap::new();
+    let mut playing = false;
+
+    // Responses are only read when someone asks, so they are dropped rather than
+    // waited on once the channel is full
     loop {
-        // Process commands
         tokio::select! {
-            Some(command) = command_rx.recv() => {
+            command = command_rx.recv() => {
+                let Some(command) = command else { break };
                 match command {
                     TTSCommand::Speak(request) => {
                         log::debug!("Received speak command for: {}", request.text);
                         if let Err(e) = queue.enqueue(request) {
-                            let _ = response_tx.send(TTSResponse::Error(e)).await;
+                            let _ = response_tx.try_send(TTSResponse::Error(e));
                         } else {
-                            let _ = response_tx.send(TTSResponse::Ok).await;
+                            let _ = response_tx.try_send(TTSResponse::Ok);
                         }
                     }
 
+                    // Ends the current request; the playback task then reports it finished
                     TTSCommand::Stop => {
                         log::debug!("Stop command received");
                         audio_player.stop();
-                        queue.complete_current();
-                        processing = false;
-                        let _ = response_tx.send(TTSResponse::Ok).await;
+                        let _ = response_tx.try_send(TTSResponse::Ok);
                     }
 
                     TTSCommand::Skip => {
                         log::debug!("Skip command received");
                         audio_player.stop();
-                        queue.complete_current();
-                        processing = false;
-                        let _ = response_tx.send(TTSResponse::Ok).await;
+                        let _ = response_tx.try_send(TTSResponse::Ok);
                     }
 
                     TTSCommand::ClearQueue => {
                         log::debug!("Clear queue command received");
                         queue.clear();
-                        let _ = response_tx.send(TTSResponse::Ok).await;
+                        // Audio being synthesized is dropped when it arrives
+                        ready.clear();
+                        valid_from = next_seq;
+                        next_play = next_seq;
+                        let _ = response_tx.try_send(TTSResponse::Ok);
+                    }
+
+                    TTSCommand::Seek(position) => {
+                        audio_player.seek(position);
+                        let _ = response_tx.try_send(TTSResponse::Ok);
+                    }
+
+                    TTSCommand::Replay(entry_id) => {
+                        let Some(entry) = history.get(entry_id) else {
+                            let _ = response_tx.try_send(TTSResponse::Error("No such TTS history entry".to_string()));
+                            continue;
+                        };
+                        let seq = next_seq;
+                        next_seq += 1;
+                        synthesizing += 1;
+                        let synthesized_tx = synthesized_tx.clone();
+                        task::spawn_blocking(move || {
+                            // The audio already has the speed it was saved with
+                            let request = TTSRequest::new(entry.text, entry.voice_id, 1.0)
+                                .for_message(entry.message_id)
+                                .with_priority(Priority::User);
+                            let result = AudioPlayer::load_wav(&entry.path).map(|(samples, sample_rate)| Synthesized {
+                                request,
+                                samples,
+                                sample_rate,
+                                replay: true,
+                            });
+                            let _ = synthesized_tx.send((seq, result));
+                        });
+                        let _ = response_tx.try_send(TTSResponse::Ok);
+                    }
+
+                    TTSCommand::SetDevice(device) => {
+                        audio_player.set_device(device);
+                        let _ = response_tx.try_send(TTSResponse::Ok);
                     }
 
                     TTSCommand::GetStatus => {
                         let status = queue.status();
-                        let _ = response_tx.send(TTSResponse::Status(status)).await;
+                        let _ = response_tx.try_send(TTSResponse::Status(status));
                     }
 
                     TTSCommand::Shutdown => {
@@ -160,62 +275,102 @@ async fn run_service(
                 }
             }
 
-            _ = tokio::time::sleep(tokio::time::Duration::from_millis(100)), if !processing && !queue.is_empty() => {
-                // Process next item in queue
-                processing = true;
-
-                if let Some(request) = queue.dequeue() {
-                    queue.set_playing(true);
-
-                    // Clone data for async task
-                    let text = request.text.clone();
-                    let voice_id = request.voice_id.clone();
-                    let speed = request.speed;
-                    let player = audio_player.clone();
-
-                    // Get or load model
-                    let model = match model_cache.get_or_load(&voice_id) {
-                        Ok(m) => m,
-                        Err(e) => {
-                            log::error!("Failed to load model: {}", e);
-                            queue.complete_current();
-                            processing = false;
-                            let _ = response_tx.send(TTSResponse::Error(e.to_string())).await;
-                            continue;
+            Some((seq, result)) = synthesized_rx.recv() => {
+                synthesizing -= 1;
+                if seq >= valid_from {
+                    ready.insert(seq, result);
+                }
+            }
+
+            Some(seq) = finished_rx.recv() => {
+                log::debug!("Finished playing request {}", seq);
+                queue.complete_current();
+                playing = false;
+            }
+        }
+
+        // Keep the workers busy with what comes next
+        while synthesizing < SYNTHESIS_WORKERS {
+            let Some(request) = queue.dequeue() else { break };
+            let seq = next_seq;
+            next_seq += 1;
+
+            let model = match model_cache.get_or_load(&request.voice_id) {
+                Ok(model) => model,
+                Err(e) => {
+                    log::error!("Failed to load model: {}", e);
+                    ready.insert(seq, Err(e));
+                    continue;
+                }
+            };
+            synthesizing += 1;
+            let synthesized_tx = synthesized_tx.clone();
+            task::spawn_blocking(move || {
+                let processed = synthesis::preprocess_text(&request.text);
+                let result = model.synthesize(&processed).map(|samples| Synthesized {
+                    request,
+                    samples,
+                    sample_rate: model.sample_rate,
+                    replay: false,
+                });
+                let _ = synthesized_tx.send((seq, result));
+            });
+        }
+
+        // Play the next in sequence once it's ready, skipping failed ones
+        while !playing {
+            let Some(result) = ready.remove(&next_play) else { break };
+            let seq = next_play;
+            next_play += 1;
+
+            let synthesized = match result {
+                Ok(synthesized) => synthesized,
+                Err(e) => {
+                    log::error!("Synthesis error: {}", e);
+                    let _ = response_tx.try_send(TTSResponse::Error(e.to_string()));
+                    continue;
+                }
+            };
+
+            let speed = synthesized.request.speed;
+            let duration = synthesized.samples.len() as f64 / synthesized.sample_rate as f64 / speed as f64;
+            let duration = Duration::from_secs_f64(duration);
+            let request = synthesized.request.clone();
+            queue.start(synthesized.request, duration);
+            playing = true;
+            let player = audio_player.clone();
+            let finished_tx = finished_tx.clone();
+            let progress_queue = queue.clone();
+            let history = history.clone();
+            let repaint = ctx.clone();
+            task::spawn_blocking(move || {
+                let played = if synthesized.replay {
+                    player.play_samples(synthesized.samples, synthesized.sample_rate)
+                } else {
+                    player.play(synthesized.samples, synthesized.sample_rate, speed).map(|path| {
+                        if let Err(e) = history.record(&request, path, duration) {
+                            log::warn!("Failed to record TTS history: {}", e);
                         }
-                    };
-
-                    // Synthesize and play in blocking task
-                    task::spawn_blocking(move || {
-                        // Preprocess text
-                        let processed = synthesis::preprocess_text(&text);
-
-                        // Synthesize
-                        match model.synthesize(&processed) {
-                            Ok(samples) => {
-                                // Play audio
-                                if let Err(e) = player.play(samples, model.sample_rate, speed) {
-                                    log::error!("Playback error: {}", e);
-                                } else {
-                                    // Wait for completion
-                                    player.wait_for_completion();
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("Synthesis error: {}", e);
-                            }
+                        if let Err(e) = history.prune(retention, Utc::now()) {
+                            log::warn!("Failed to clean up TTS history: {}", e);
                         }
-                    });
-
-                    // Mark as complete after synthesis task finishes
-                    // Note: In a real implementation, we'd wait for the spawned task
-                    // For now, we'll just mark it complete after a delay
-                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                    queue.complete_current();
-                    processing = false;
+                    })
+                };
+                match played {
+                    // Stream the position to the UI for highlighting the spoken words
+                    Ok(()) => player.wait_for_completion(|position| {
+                        progress_queue.set_position(position);
+                        repaint.request_repaint();
+                    }),
+                    Err(e) => log::error!("Playback error: {}", e),
                 }
-            }
+                let _ = finished_tx.send(seq);
+            });
         }
+
+        queue.set_prepared(synthesizing + ready.len());
+        // Every pass through the loop may have changed what the status bar shows
+        ctx.request_repaint();
     }
 
     log::info!("TTS service stopped");
@@ -229,7 +384,7 @@ mod tests {
     #[tokio::test]
     async fn test_service_creation() {
         let config = TTSConfig::default();
-        match TTSService::start(config) {
+        match TTSService::start(config, egui::Context::default()) {
             Ok(service) => {
                 // Service should be created successfully
                 assert!(service.shutdown().await.is_ok());
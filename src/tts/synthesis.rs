@@ -1,6 +1,7 @@
-/// Text preprocessing and synthesis coordination
+//! Text preprocessing and synthesis coordination
 
 use anyhow::Result;
+use std::ops::Range;
 
 /// Preprocess text for TTS synthesis
 pub fn preprocess_text(text: &str) -> String {
@@ -101,6 +102,95 @@ pub fn split_sentences(text: &str) -> Vec<String> {
     sentences
 }
 
+/// Estimated timing of the words of a text, for following playback word by word.
+///
+/// Words are timed like the tone synthesizer voices them: a share growing with their
+/// length, capped for long words, plus a pause. Neural voices land close enough for
+/// highlighting. The timing is of the text as written, before preprocessing.
+pub struct WordTimeline {
+    /// Byte range of each word with the fraction of the audio where it starts
+    words: Vec<(Range<usize>, f32)>,
+}
+
+impl WordTimeline {
+    pub fn new(text: &str) -> Self {
+        let mut words = Vec::new();
+        let mut start = None;
+        for (index, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+            match (c.is_whitespace(), start) {
+                (false, None) => start = Some(index),
+                (true, Some(from)) => {
+                    words.push(from..index);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        let weight = |range: &Range<usize>| {
+            let chars = text[range.clone()].chars().count() as f32;
+            (0.12 + chars * 0.04).min(0.6) + 0.06
+        };
+        let total: f32 = words.iter().map(weight).sum();
+        let mut elapsed = 0.0;
+        let words = words
+            .into_iter()
+            .map(|range| {
+                let starts = elapsed / total;
+                elapsed += weight(&range);
+                (range, starts)
+            })
+            .collect();
+
+        Self { words }
+    }
+
+    /// Byte range of the word being spoken `fraction` of the way through the audio
+    pub fn word_at(&self, fraction: f32) -> Option<Range<usize>> {
+        let index = self.words.partition_point(|(_, starts)| *starts <= fraction);
+        self.words.get(index.checked_sub(1)?).map(|(range, _)| range.clone())
+    }
+
+    /// Fraction of the way through the audio where the first word at or after byte
+    /// `offset` starts
+    pub fn fraction_at(&self, offset: usize) -> f32 {
+        self.words
+            .iter()
+            .find(|(range, _)| range.end > offset)
+            .map_or(1.0, |(_, starts)| *starts)
+    }
+}
+
+/// Byte ranges of the sentences of `text`, including their closing punctuation, for
+/// seeking to one. Splits like [`split_sentences`].
+pub fn sentence_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let ends = matches!(c, '.' | '!' | '?')
+            && chars.peek().is_none_or(|(_, next)| next.is_whitespace() || next.is_uppercase());
+        if ends {
+            ranges.push(start..index + c.len_utf8());
+            start = index + c.len_utf8();
+        }
+    }
+    if !text[start..].trim().is_empty() {
+        ranges.push(start..text.len());
+    }
+
+    // Leave out the whitespace between sentences
+    ranges
+        .into_iter()
+        .filter_map(|range| {
+            let piece = &text[range.clone()];
+            let leading = piece.len() - piece.trim_start().len();
+            let trimmed = piece.trim();
+            (!trimmed.is_empty()).then(|| range.start + leading..range.start + leading + trimmed.len())
+        })
+        .collect()
+}
+
 /// Synthesize long text by chunking
 #[allow(dead_code)]
 pub async fn synthesize_long_text(
@@ -161,4 +251,27 @@ mod tests {
         assert_eq!(sentences[1], "How are you?");
         assert_eq!(sentences[2], "I am fine!");
     }
+
+    #[test]
+    fn test_sentence_ranges() {
+        let text = "Hello world. How are you?  I am fine";
+        let sentences: Vec<&str> = sentence_ranges(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(sentences, ["Hello world.", "How are you?", "I am fine"]);
+    }
+
+    #[test]
+    fn test_word_timeline() {
+        let text = "Hi there,  wonderful world";
+        let timeline = WordTimeline::new(text);
+        assert_eq!(timeline.word_at(0.0).map(|r| &text[r]), Some("Hi"));
+        assert_eq!(timeline.word_at(0.99).map(|r| &text[r]), Some("world"));
+
+        let there = timeline.fraction_at(3);
+        assert!(there > 0.0 && there < 0.5);
+        assert_eq!(timeline.word_at(there).map(|r| &text[r]), Some("there,"));
+        // Between words the next one counts
+        assert_eq!(timeline.fraction_at(10), timeline.fraction_at(11));
+        assert_eq!(timeline.fraction_at(text.len()), 1.0);
+        assert_eq!(WordTimeline::new("").word_at(0.5), None);
+    }
 }
@@ -1,21 +1,76 @@
-use crate::agent::AgentId;
-use crate::config::AppConfig;
+use crate::agent::branch::{BranchId, Branches};
+use crate::agent::coding_cli::CodingCliAgent;
+use crate::agent::context;
+use crate::agent::ollama::{OllamaChat, OllamaConfig};
+use crate::agent::{AgentConfig, AgentId, AgentType, CodingCli, CodingCliConfig, ProcessAgentConfig};
+use crate::approval::{AgentApproval, ApprovalGate};
+use crate::assistant::Assistant;
+use crate::blackboard::{Blackboard, SharedBlackboard};
+use crate::clipboard::{Clip, ClipKind, ClipTarget, ClipboardWatcher};
+use crate::crash;
+use crate::config::{AppConfig, Announcer};
+use crate::i18n::{self, tr};
+use crate::mcp::{self, McpServer};
+use crate::monitor::{LimitEvent, ResourceMonitor, Target};
+use crate::onboarding::{self, Setup};
+use crate::pipeline::PipelineRunner;
 use crate::plan::Plan;
-use crate::storage::ChatHistoryStore;
-use crate::tts::{TTSConfig, TTSService, TTSRequest};
-use crate::ui::chat::{ChatMessage, MessageId};
+use crate::scripting::{self, Hook, HookOutcome, ScriptHost};
+use crate::session::{self, SessionSnapshot};
+use crate::snippets::Snippets;
+use crate::sync_api::SyncApi;
+use crate::sources::forget::{ForgetLog, ForgetTarget};
+use crate::sources::{IncomingMessage, MessageWatcher, SourceEventWatcher};
+use crate::storage::message_cache::{BatchWriter, CachedMessage, MessageCache};
+use crate::storage::{ChatHistoryStore, ReminderStore};
+use crate::terminal::paste::{self, PendingPaste};
+use crate::terminal::buffer::ScrollbackBuffer;
+use crate::terminal::CommandHistory;
+use crate::toolcall::process::{AgentProcess, RunningAgent};
+use crate::toolcall::protocol::{AgentState, Frame, Output};
+use crate::toolcall::blackboard::BlackboardToolcall;
+use crate::toolcall::shell::ShellToolcall;
+use crate::toolcall::types::{ToolcallRegistry, ToolcallRequest, ToolcallResult, ToolcallScope};
+use crate::tts::export::{self, Export, Segment};
+use crate::updates::Updater;
+use crate::tts::{Priority, TTSConfig, TTSService, TTSRequest};
+use crate::ui::blackboard_panel::BlackboardPanelState;
+use crate::ui::chat::{BranchView, ChatLogAction, ChatLogView, ChatMessage, MessageDirection, MessageId, SpokenMessage, ToolCall};
+use crate::ui::chats_tab::{ChatsTabAction, ChatsTabState};
+use crate::ui::debug_overlay::DebugOverlay;
+use crate::ui::logs_panel::LogsPanelState;
+use crate::ui::shortcuts::Action;
+use crate::ui::triage::TriageState;
+use crate::ui::identities::IdentitiesState;
+use crate::ui::onboarding::OnboardingState;
+use crate::ui::scripts_panel::ScriptsPanelState;
+use crate::ui::status_bar::SourceIndicator;
+use crate::ui::telegram_login::TelegramLoginPanel;
+use crate::ui::terminal::HistorySearch;
+use crate::ui::terminal_output::OutputView;
+use crate::ui::toasts::Toasts;
+use crate::ui::workspace::WorkspaceUi;
+use crate::voice::intent::{self, Intent, Tab};
+use crate::voice::{self, VoiceEvent, VoiceListener};
+use crate::workspace::WorkspaceTracker;
+use chat::SourcesManager;
 use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
-use std::collections::HashMap;
+use rhai::Dynamic;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 use vte::{Params, Parser, Perform};
 
 // Re-export TestMode type from main
 pub type TestModeHandle = Arc<Mutex<crate::TestMode>>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AppTab {
     Home,
+    Chats,
     Term,
 }
 
@@ -42,7 +97,7 @@ pub enum AnsiColor {
 }
 
 impl AnsiColor {
-    fn to_egui_color(&self) -> egui::Color32 {
+    fn to_egui_color(self) -> egui::Color32 {
         match self {
             AnsiColor::Black => egui::Color32::from_rgb(0, 0, 0),
             AnsiColor::Red => egui::Color32::from_rgb(205, 49, 49),
@@ -107,15 +162,17 @@ struct TerminalPerformer {
     current_text: String,
     current_style: TextStyle,
     pending_cr: bool,  // Track if we just saw a \r without \n
+    bracketed_paste: Arc<AtomicBool>,  // Set while the program has bracketed paste on
 }
 
 impl TerminalPerformer {
-    fn new(output: Arc<Mutex<Vec<StyledText>>>) -> Self {
+    fn new(output: Arc<Mutex<Vec<StyledText>>>, bracketed_paste: Arc<AtomicBool>) -> Self {
         Self {
             output,
             current_text: String::new(),
             current_style: TextStyle::default(),
             pending_cr: false,
+            bracketed_paste,
         }
     }
 
@@ -176,8 +233,12 @@ impl Perform for TerminalPerformer {
 
     fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {}
 
-    fn csi_dispatch(&mut self, params: &Params, _intermediates: &[u8], _ignore: bool, action: char) {
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, action: char) {
         match action {
+            // DEC private modes; 2004 is bracketed paste
+            'h' | 'l' if intermediates == [b'?'] && params.iter().any(|p| p[0] == 2004) => {
+                self.bracketed_paste.store(action == 'h', Ordering::Relaxed);
+            }
             'm' => {
                 // SGR - Select Graphic Rendition (colors and styling)
                 self.flush_current_text();
@@ -253,62 +314,309 @@ impl Perform for TerminalPerformer {
 pub struct DashboardApp {
     pub config: AppConfig,
     pub active_agents: HashMap<AgentId, String>,
+    /// How each agent process started in a sandbox is confined, for the agents panel
+    pub agent_sandboxes: HashMap<AgentId, String>,
     pub selected_agent: Option<AgentId>,
     pub broadcast_mode: bool,
     pub chat_messages: Vec<ChatMessage>,
+    /// Draws `chat_messages`, keeping what it laid out between frames
+    pub chat_log: ChatLogView,
+    /// Branches of the agents' conversations, and the one shown for each
+    pub branches: Branches,
     pub chat_input: String,
     pub show_config_panel: bool,
     pub show_plan_panel: bool,
     pub show_tts_panel: bool,
     pub plans: Vec<Plan>,
-    #[allow(dead_code)]
+    /// Chat todos being marked done through the chat CLI
+    pub todo_marks: Vec<mpsc::Receiver<Result<(), String>>>,
     pub chat_history_store: Option<ChatHistoryStore>,
     pub tts_config: TTSConfig,
     pub tts_service: Option<TTSService>,
-    pub speak_message_id: Option<MessageId>,
+    /// Output devices listed in the TTS panel, listed when it is first shown
+    pub tts_devices: Option<Vec<String>>,
+    /// Narrated audio export in progress
+    pub tts_export: Option<mpsc::Receiver<anyhow::Result<Export>>>,
     pub current_tab: AppTab,
-    pub terminal_output: Vec<OutputLine>,
+    pub terminal_output: ScrollbackBuffer,
+    pub terminal_output_view: OutputView,
     pub terminal_input: String,
     pub terminal_startup_command: String,
+    /// Lines sent with the current startup command, for Up/Down and Ctrl+R
+    pub terminal_history: CommandHistory,
+    pub terminal_history_search: Option<HistorySearch>,
+    /// Paste waiting for confirmation in the Term tab
+    pub terminal_pending_paste: Option<PendingPaste>,
+    /// Whether the program in the terminal turned bracketed paste on
+    pub terminal_bracketed_paste: Arc<AtomicBool>,
+    /// How the running startup command is sandboxed, if it is
+    pub terminal_sandbox: Option<String>,
+    /// Process ID of the startup command while it runs, 0 otherwise
+    pub terminal_pid: Arc<AtomicU32>,
     pub terminal_stdin_tx: Option<mpsc::Sender<String>>,
     pub terminal_stdout_rx: Option<mpsc::Receiver<OutputLine>>,
     pub terminal_pty_master: Option<Box<dyn MasterPty + Send>>,
     pub terminal_pty_size: PtySize,
     pub test_mode: Option<TestModeHandle>,
+    /// Repainting without pause over generated messages, to measure chat frame times
+    pub chat_stress: bool,
+    pub source_indicators: Vec<SourceIndicator>,
+    pub unread_count: usize,
+    pub last_error: Option<String>,
+    pub chats_tab: ChatsTabState,
+    pub reminder_store: Option<ReminderStore>,
+    pub last_reminder_check: Option<Instant>,
+    pub pipeline_runner: Option<PipelineRunner>,
+    pub script_host: Option<ScriptHost>,
+    pub show_scripts_panel: bool,
+    pub scripts_panel: ScriptsPanelState,
+    pub show_logs_panel: bool,
+    pub logs_panel: LogsPanelState,
+    /// Notes the agents share, through the `blackboard` tool call
+    pub blackboard: SharedBlackboard,
+    pub show_blackboard: bool,
+    pub blackboard_panel: BlackboardPanelState,
+    pub clipboard_watcher: Option<ClipboardWatcher>,
+    /// Latest clipboard text waiting for the user to act on it
+    pub clip: Option<Clip>,
+    /// Records and transcribes spoken commands
+    pub voice: Option<VoiceListener>,
+    /// Session left by a run that didn't close normally, until restored or discarded
+    pub session_recovery: Option<SessionSnapshot>,
+    /// Crash reports from earlier runs the user hasn't seen yet, newest first
+    pub crash_reports: Vec<crash::ReportBundle>,
+    /// When the session was last saved for crash recovery
+    pub session_saved_at: Instant,
+    /// chat CLI update checks and installs
+    pub updater: Updater,
+    pub show_update_window: bool,
+    /// Keyboard shortcut list, toggled with F1
+    pub show_shortcuts: bool,
+    /// Move keyboard focus to the message or command input on the next frame
+    pub focus_input: bool,
+    /// Snippets while the compose box's snippet menu is open
+    pub snippets: Option<Snippets>,
+    /// Setup wizard, shown on first run
+    pub onboarding: Option<OnboardingState>,
+    pub telegram_login: TelegramLoginPanel,
+    /// Source whose session expired, while the sign-in dialog is open
+    pub reauth_source: Option<String>,
+    /// Source connection events from the chat CLI, shown as toasts
+    pub source_events: Option<SourceEventWatcher>,
+    pub toasts: Toasts,
+    pub debug_overlay: DebugOverlay,
+    /// Incoming messages from the chat CLI, for chats that are read aloud
+    pub message_watcher: Option<MessageWatcher>,
+    /// Writes watched messages to the message cache in batches
+    pub message_cache: Option<BatchWriter>,
+    /// Chats and people forgotten with `chat cache forget`, kept out of the cache and views
+    pub forget_log: Option<ForgetLog>,
+    pub last_forget_check: Option<Instant>,
+    /// Serves the message cache to a phone
+    pub sync_api: Option<SyncApi>,
+    pub announcer: Announcer,
+    /// Routes chosen chats to the assistant agent and sends its approved replies
+    pub assistant: Assistant,
+    pub show_assistant: bool,
+    /// Stepping through unread messages with single-key actions
+    pub triage: TriageState,
+    pub show_triage: bool,
+    pub identities: IdentitiesState,
+    pub show_identities: bool,
+    /// Chat sources, connected once and served to the coding CLI agents
+    pub chat_sources: Arc<SourcesManager>,
+    /// Chat MCP server the coding CLI agents are connected to
+    pub mcp_server: Option<McpServer>,
+    /// Running process and coding CLI agents, by the agent they were started for
+    pub agent_processes: HashMap<AgentId, Box<dyn RunningAgent>>,
+    /// CPU and memory of the agent processes and the Term tab
+    pub monitor: Option<ResourceMonitor>,
+    /// Last status each process agent reported
+    pub agent_states: HashMap<AgentId, (AgentState, Option<String>)>,
+    /// Tools process agents may call
+    pub tools: Arc<ToolcallRegistry>,
+    /// Tool calls run again from the chat log, by the message showing each
+    pub tool_reruns: Vec<(MessageId, mpsc::Receiver<ToolcallResult>)>,
+    /// Shell commands from process agents waiting for approval
+    pub approvals: Arc<ApprovalGate>,
+    /// Git status of each active agent's workspace
+    pub workspaces: WorkspaceTracker,
+    pub workspace_ui: WorkspaceUi,
 }
 
 impl DashboardApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let config = AppConfig::load().unwrap_or_default();
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut config = AppConfig::load().unwrap_or_default();
+        i18n::set_language(config.language.as_deref());
+        super::appearance::apply(&cc.egui_ctx, &config.appearance);
         let chat_history_store = ChatHistoryStore::new().ok();
+        let session_recovery = session::snapshot_path().and_then(|path| session::load(&path)).unwrap_or_else(|e| {
+            log::warn!("Failed to read the previous session: {}", e);
+            None
+        });
+        crash::set_enabled(config.crash_reports.enabled);
+        let crash_reports = crash::reports_dir().and_then(|dir| crash::pending(&dir)).unwrap_or_else(|e| {
+            log::warn!("Failed to read crash reports: {}", e);
+            Vec::new()
+        });
 
         // Initialize TTS from saved config
         let tts_config = config.tts.clone();
+        let mut last_error = None;
         let tts_service = if tts_config.enabled {
-            TTSService::start(tts_config.clone()).ok()
+            match TTSService::start(tts_config.clone(), cc.egui_ctx.clone()) {
+                Ok(service) => Some(service),
+                Err(e) => {
+                    last_error = Some(format!("Failed to start TTS: {}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let pipeline_runner = if config.pipelines_enabled {
+            match PipelineRunner::start(cc.egui_ctx.clone()) {
+                Ok(runner) => Some(runner),
+                Err(e) => {
+                    last_error = Some(format!("Failed to start pipelines: {}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let chat_sources = mcp::load_sources().unwrap_or_else(|e| {
+            last_error = Some(format!("Failed to load chat sources: {}", e));
+            Arc::new(SourcesManager::new())
+        });
+        let mcp_server = if config.mcp_server.enabled {
+            match McpServer::start(&config.mcp_server, Arc::clone(&chat_sources), cc.egui_ctx.clone()) {
+                Ok(server) => Some(server),
+                Err(e) => {
+                    last_error = Some(format!("Failed to start chat MCP server: {}", e));
+                    None
+                }
+            }
         } else {
             None
         };
 
+        let clipboard_watcher = if config.clipboard.enabled {
+            match ClipboardWatcher::start(&config.clipboard, cc.egui_ctx.clone()) {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    last_error = Some(format!("Failed to watch clipboard: {}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let voice = if config.voice.enabled {
+            match VoiceListener::start(&config.voice, cc.egui_ctx.clone()) {
+                Ok(listener) => Some(listener),
+                Err(e) => {
+                    last_error = Some(format!("Failed to start voice commands: {}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Checks are turned off in the chat CLI's config, which answers accordingly
+        let mut updater = Updater::default();
+        updater.check(&cc.egui_ctx);
+
+        let monitor = config.monitor.enabled.then(|| ResourceMonitor::start(&config.monitor, cc.egui_ctx.clone()));
+
+        let script_host = match ScriptHost::load(config.script_limits.clone()) {
+            Ok(host) => Some(host),
+            Err(e) => {
+                last_error = Some(format!("Failed to load scripts: {}", e));
+                None
+            }
+        };
+
+        let onboarding = onboarding::needs_setup().then(|| OnboardingState::new(&config));
+
+        let mut tools = ToolcallRegistry::new();
+        tools.register(Box::new(ShellToolcall::new()));
+        let blackboard = Blackboard::shared();
+        tools.register(Box::new(BlackboardToolcall::new(Arc::clone(&blackboard))));
+        let approvals = ApprovalGate::new(config.approvals.clone(), cc.egui_ctx.clone());
+
+        // The chat CLI is optional, so failing to start it is not an error
+        let source_events = match SourceEventWatcher::start(cc.egui_ctx.clone()) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                log::warn!("Not watching source events: {}", e);
+                None
+            }
+        };
+
+        let sync_api = if config.sync_api.enabled { start_sync_api(&mut config) } else { None };
+        let message_watcher = if config.announce.chats.is_empty() && !config.assistant.is_active() && !config.sync_api.enabled {
+            None
+        } else {
+            match MessageWatcher::start(cc.egui_ctx.clone()) {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    log::warn!("Not watching messages: {}", e);
+                    None
+                }
+            }
+        };
+
+        let message_cache = match BatchWriter::start_default() {
+            Ok(writer) => Some(writer),
+            Err(e) => {
+                log::warn!("Not caching messages: {}", e);
+                None
+            }
+        };
+        let forget_log = match ForgetLog::open_default() {
+            Ok(forget_log) => Some(forget_log),
+            Err(e) => {
+                log::warn!("Not following the forget log: {}", e);
+                None
+            }
+        };
+
         Self {
             config,
             active_agents: HashMap::new(),
+            agent_sandboxes: HashMap::new(),
             selected_agent: None,
             broadcast_mode: false,
             chat_messages: Vec::new(),
+            chat_log: ChatLogView::default(),
+            branches: Branches::default(),
             chat_input: String::new(),
             show_config_panel: false,
             show_plan_panel: false,
             show_tts_panel: false,
             plans: Vec::new(),
+            todo_marks: Vec::new(),
             chat_history_store,
             tts_config,
             tts_service,
-            speak_message_id: None,
+            tts_devices: None,
+            tts_export: None,
             current_tab: AppTab::Term,
-            terminal_output: Vec::new(),
+            terminal_output: ScrollbackBuffer::default(),
+            terminal_output_view: OutputView::default(),
             terminal_input: String::new(),
             terminal_startup_command: String::from("bash"),
+            terminal_history: CommandHistory::load("bash"),
+            terminal_history_search: None,
+            terminal_pending_paste: None,
+            terminal_bracketed_paste: Arc::new(AtomicBool::new(false)),
+            terminal_sandbox: None,
+            terminal_pid: Arc::new(AtomicU32::new(0)),
             terminal_stdin_tx: None,
             terminal_stdout_rx: None,
             terminal_pty_master: None,
@@ -319,13 +627,1191 @@ impl DashboardApp {
                 pixel_height: 0,
             },
             test_mode: None,
+            chat_stress: false,
+            source_indicators: Vec::new(),
+            unread_count: 0,
+            last_error,
+            chats_tab: ChatsTabState::default(),
+            reminder_store: ReminderStore::new().ok(),
+            last_reminder_check: None,
+            pipeline_runner,
+            script_host,
+            show_scripts_panel: false,
+            scripts_panel: ScriptsPanelState::default(),
+            show_logs_panel: false,
+            logs_panel: LogsPanelState::default(),
+            blackboard,
+            show_blackboard: false,
+            blackboard_panel: BlackboardPanelState::default(),
+            clipboard_watcher,
+            clip: None,
+            voice,
+            session_recovery,
+            crash_reports,
+            session_saved_at: Instant::now(),
+            updater,
+            show_update_window: false,
+            show_shortcuts: false,
+            focus_input: false,
+            snippets: None,
+            onboarding,
+            telegram_login: TelegramLoginPanel::default(),
+            reauth_source: None,
+            source_events,
+            toasts: Toasts::default(),
+            debug_overlay: DebugOverlay::default(),
+            message_watcher,
+            message_cache,
+            forget_log,
+            last_forget_check: None,
+            sync_api,
+            announcer: Announcer::default(),
+            assistant: Assistant::default(),
+            show_assistant: false,
+            triage: TriageState::default(),
+            show_triage: false,
+            identities: IdentitiesState::default(),
+            show_identities: false,
+            chat_sources,
+            mcp_server,
+            agent_processes: HashMap::new(),
+            monitor,
+            agent_states: HashMap::new(),
+            tools: Arc::new(tools),
+            tool_reruns: Vec::new(),
+            approvals,
+            workspaces: WorkspaceTracker::new(Duration::from_secs(10)),
+            workspace_ui: WorkspaceUi::default(),
+        }
+    }
+
+    pub fn save_tts_config(&mut self) {
+        self.config.tts = self.tts_config.clone();
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save TTS config: {}", e);
+            self.last_error = Some(format!("Failed to save TTS config: {}", e));
+        }
+    }
+
+    /// Append a message to the chat, counting agent replies as unread while the chat is not visible
+    pub fn push_chat_message(&mut self, mut message: ChatMessage) {
+        let agent_output = message
+            .agent_id
+            .filter(|_| message.direction == MessageDirection::FromAgent && !message.metadata.is_toolcall);
+
+        let outcome = match agent_output {
+            Some(agent_id) => {
+                let agent = self
+                    .active_agents
+                    .get(&agent_id)
+                    .cloned()
+                    .unwrap_or_else(|| agent_id.to_string());
+                self.run_hook(
+                    Hook::AgentOutput,
+                    scripting::event([
+                        ("agent", Dynamic::from(agent)),
+                        ("text", Dynamic::from(message.content.clone())),
+                    ]),
+                )
+            }
+            None => HookOutcome::default(),
+        };
+        if let Some(text) = &outcome.text {
+            message.content = text.clone();
+        }
+
+        // Replies silenced by a script stay in the log without alerting
+        if !outcome.suppressed {
+            if message.direction == MessageDirection::FromAgent && self.current_tab != AppTab::Home {
+                self.unread_count += 1;
+            }
+
+            // Auto-speak replies from agents, not dashboard notifications
+            if agent_output.is_some()
+                && self.tts_config.auto_speak
+                && self.alert_allowed(None, false, &message.content)
+            {
+                self.speak_message(&message, Priority::Auto);
+            }
+        }
+
+        // Messages to and from an agent belong to the branch of its conversation shown
+        if message.branch.is_none() {
+            message.branch = message.agent_id.and_then(|agent| self.branches.active(agent));
+        }
+        if let Some(store) = &self.chat_history_store {
+            if let Err(e) = store.save_message(&message) {
+                log::warn!("Failed to save chat message: {}", e);
+            }
+        }
+
+        self.chat_messages.push(message);
+        self.apply_hook_effects(outcome);
+    }
+
+    /// Send `text` in place of the message `fork_at`, in a new branch of its conversation
+    fn fork_conversation(&mut self, fork_at: MessageId, text: String) {
+        let Some(message) = self.chat_messages.iter().find(|m| m.id == fork_at).cloned() else {
+            return;
+        };
+        let Some(agent) = message.agent_id else {
+            return;
+        };
+        let history = self.branches.history(&self.chat_messages, agent, Some(fork_at));
+        let Some(process) = self.agent_processes.get(&agent) else {
+            self.last_error = Some("The agent isn't running, so its conversation can't branch".to_string());
+            return;
+        };
+        if let Err(e) = process.set_history(history) {
+            self.last_error = Some(format!("Failed to branch the conversation: {}", e));
+            return;
+        }
+
+        let mut prompt = ChatMessage::new_user_message(text, Some(agent));
+        if let Some(branch) = self.branches.fork(&message, prompt.id) {
+            prompt.branch = Some(branch.id);
+            if let Some(store) = &self.chat_history_store {
+                if let Err(e) = store.save_branch(branch) {
+                    log::warn!("Failed to save conversation branch: {}", e);
+                }
+            }
+        }
+        self.deliver_to_agents(&prompt.content, Some(agent));
+        self.push_chat_message(prompt);
+    }
+
+    /// Ask again for the agent's reply `reply`, by sending the message before it in a new branch
+    fn regenerate_reply(&mut self, reply: MessageId) {
+        let shown = self.branches.visible(&self.chat_messages);
+        let Some(index) = shown.iter().position(|m| m.id == reply) else {
+            return;
+        };
+        let agent = shown[index].agent_id;
+        let prompt = shown[..index]
+            .iter()
+            .rev()
+            .find(|m| m.agent_id == agent && m.direction == MessageDirection::ToAgent && !m.metadata.is_error)
+            .map(|m| (m.id, m.content.clone()));
+        match prompt {
+            Some((id, text)) => self.fork_conversation(id, text),
+            None => self.toasts.info("There's no message to this agent before that reply to send again"),
+        }
+    }
+
+    /// Show another branch of `agent`'s conversation, which the agent goes on from
+    fn switch_branch(&mut self, agent: AgentId, branch: Option<BranchId>) {
+        self.branches.switch(agent, branch);
+        self.sync_history(agent);
+    }
+
+    /// Hand a branching agent the conversation shown for it
+    fn sync_history(&mut self, agent: AgentId) {
+        let Some(process) = self.agent_processes.get(&agent).filter(|process| process.can_branch()) else {
+            return;
+        };
+        let history = self.branches.history(&self.chat_messages, agent, None);
+        if let Err(e) = process.set_history(history) {
+            self.last_error = Some(format!("Failed to switch the conversation: {}", e));
+        }
+    }
+
+    /// Run a script hook, reporting script failures in the status bar
+    fn run_hook(&mut self, hook: Hook, event: rhai::Map) -> HookOutcome {
+        let Some(host) = &mut self.script_host else {
+            return HookOutcome::default();
+        };
+
+        let outcome = host.run(hook, event);
+        if let Some(error) = outcome.errors.last() {
+            self.last_error = Some(format!("Script error: {}", error));
+        }
+        outcome
+    }
+
+    /// Speak and show what scripts asked for
+    fn apply_hook_effects(&mut self, outcome: HookOutcome) {
+        for text in outcome.speak {
+            if self.alert_allowed(None, false, &text) {
+                self.speak(text, Priority::Auto);
+            }
+        }
+        for text in outcome.notifications {
+            self.push_chat_message(ChatMessage::new_notification(format!("📜 {}", text)));
+        }
+    }
+
+    /// Show a notification, letting scripts rewrite or drop it, and holding it back during do-not-disturb
+    fn raise_alert(&mut self, kind: &str, text: String, chat: Option<String>, critical: bool, speak: bool) {
+        let outcome = self.run_hook(
+            Hook::Alert,
+            scripting::event([
+                ("kind", Dynamic::from(kind.to_string())),
+                ("text", Dynamic::from(text.clone())),
+                ("chat", Dynamic::from(chat.clone().unwrap_or_default())),
+                ("critical", Dynamic::from(critical)),
+            ]),
+        );
+        let text = outcome.text.clone().unwrap_or(text);
+
+        if outcome.suppressed {
+            log::info!("Script suppressed alert: {}", text);
+        } else if self.alert_allowed(chat.as_deref(), critical, &text) {
+            if speak {
+                self.speak(text.clone(), Priority::Auto);
+            }
+            self.push_chat_message(ChatMessage::new_notification(text));
+        } else {
+            // Kept in the chat log without raising the unread count
+            self.chat_messages.push(ChatMessage::new_notification(format!("🔕 {}", text)));
         }
+
+        self.apply_hook_effects(outcome);
+    }
+
+    /// Reload scripts from disk, e.g. after editing them
+    pub fn reload_scripts(&mut self) {
+        match ScriptHost::load(self.config.script_limits.clone()) {
+            Ok(host) => self.script_host = Some(host),
+            Err(e) => self.last_error = Some(format!("Failed to load scripts: {}", e)),
+        }
+    }
+
+    /// Check do-not-disturb before alerting; suppressed alerts are logged instead
+    fn alert_allowed(&self, chat: Option<&str>, critical: bool, text: &str) -> bool {
+        let suppressed = self.config.dnd.suppresses(chat, critical, chrono::Local::now());
+        if suppressed {
+            log::info!("DND: suppressed alert: {}", text);
+        }
+        !suppressed
     }
 
-    pub fn save_tts_config(&mut self) {
-        self.config.tts = self.tts_config.clone();
-        if let Err(e) = self.config.save() {
-            log::error!("Failed to save TTS config: {}", e);
+    /// Queue text for TTS playback if the service is running
+    fn speak(&self, text: String, priority: Priority) {
+        self.speak_request(self.tts_request(text).with_priority(priority));
+    }
+
+    /// Queue a chat message for TTS playback, so the chat can follow along as it's spoken
+    fn speak_message(&self, message: &ChatMessage, priority: Priority) {
+        let request = self.tts_request(message.content.clone());
+        self.speak_request(request.with_priority(priority).for_message(message.id.as_uuid()));
+    }
+
+    fn tts_request(&self, text: String) -> TTSRequest {
+        TTSRequest::new(text, self.tts_config.selected_voice.clone(), self.tts_config.playback_speed)
+    }
+
+    fn speak_request(&self, request: TTSRequest) {
+        if let Some(ref service) = self.tts_service {
+            let service = service.clone();
+            tokio::spawn(async move {
+                if let Err(e) = service.speak(request).await {
+                    log::error!("TTS speak error: {}", e);
+                }
+            });
+        }
+    }
+
+    /// Segments for narrating the Home chat: the selected agent's conversation, or every
+    /// agent's in broadcast mode. Errors and tool calls are left out.
+    fn conversation_segments(&self) -> Vec<Segment> {
+        let agent = if self.broadcast_mode { None } else { self.selected_agent };
+        self.branches
+            .visible(&self.chat_messages)
+            .into_iter()
+            .filter(|message| !message.metadata.is_error && !message.metadata.is_toolcall)
+            .filter(|message| agent.is_none() || message.agent_id == agent)
+            .map(|message| {
+                let speaker = match message.direction {
+                    MessageDirection::FromAgent => message
+                        .agent_id
+                        .and_then(|id| self.active_agents.get(&id).cloned())
+                        .unwrap_or_else(|| "Agent".to_string()),
+                    MessageDirection::ToAgent | MessageDirection::Broadcast => "You".to_string(),
+                };
+                let time = message.timestamp.with_timezone(&chrono::Local).format("%H:%M");
+                Segment {
+                    title: format!("{} {}", time, speaker),
+                    speaker,
+                    text: message.content.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Render `segments` to a WAV file in the export directory in the background
+    fn export_audio(&mut self, ctx: &egui::Context, name: &str, segments: Vec<Segment>) {
+        if self.tts_export.is_some() {
+            self.toasts.info("An audio export is already running");
+            return;
+        }
+        let path = match export::export_dir() {
+            Ok(dir) => dir.join(format!("{}-{}.wav", name, chrono::Local::now().format("%Y%m%d-%H%M%S"))),
+            Err(e) => {
+                self.toasts.warning(format!("Audio export failed: {}", e));
+                return;
+            }
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let config = self.tts_config.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(export::export(&config, &segments, &path));
+            ctx.request_repaint();
+        });
+        self.tts_export = Some(rx);
+    }
+
+    fn check_tts_export(&mut self) {
+        let Some(result) = self.tts_export.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return;
+        };
+        self.tts_export = None;
+        match result {
+            Ok(export) => self.toasts.info(format!(
+                "Saved {} chapters ({}:{:02}) to {}",
+                export.chapters,
+                export.duration.as_secs() / 60,
+                export.duration.as_secs() % 60,
+                export.path.display()
+            )),
+            Err(e) => self.toasts.warning(format!("Audio export failed: {}", e)),
+        }
+    }
+
+    /// Switch manual do-not-disturb on or off and persist it
+    pub fn toggle_dnd(&mut self) {
+        self.config.dnd.manual = !self.config.dnd.manual;
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save DND setting: {}", e);
+            self.last_error = Some(format!("Failed to save DND setting: {}", e));
+        }
+    }
+
+    /// Fire chat reminders that have come due, at most every 30 seconds
+    pub fn check_reminders(&mut self) {
+        if self
+            .last_reminder_check
+            .is_some_and(|t| t.elapsed() < Duration::from_secs(30))
+        {
+            return;
+        }
+        self.last_reminder_check = Some(Instant::now());
+
+        let Some(store) = &self.reminder_store else {
+            return;
+        };
+
+        let reminders = match store.take_due(chrono::Utc::now()) {
+            Ok(reminders) => reminders,
+            Err(e) => {
+                log::error!("Failed to check reminders: {}", e);
+                self.last_error = Some(format!("Failed to check reminders: {}", e));
+                return;
+            }
+        };
+
+        for reminder in reminders {
+            let text = format!("⏰ Reminder: {}", reminder.summary());
+            self.raise_alert("reminder", text, reminder.chat_key(), reminder.critical, reminder.speak);
+        }
+    }
+
+    /// Start or stop the message pipelines and persist the choice
+    pub fn set_pipelines_enabled(&mut self, ctx: &egui::Context, enabled: bool) {
+        self.config.pipelines_enabled = enabled;
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save pipeline setting: {}", e);
+            self.last_error = Some(format!("Failed to save pipeline setting: {}", e));
+        }
+
+        if !enabled {
+            self.pipeline_runner = None;
+        } else if self.pipeline_runner.is_none() {
+            match PipelineRunner::start(ctx.clone()) {
+                Ok(runner) => self.pipeline_runner = Some(runner),
+                Err(e) => self.last_error = Some(format!("Failed to start pipelines: {}", e)),
+            }
+        }
+    }
+
+    /// Apply changed chat MCP server settings: save them and start, stop or restart the server.
+    /// Agents already running keep the address they were started with.
+    pub fn apply_mcp_server_config(&mut self, ctx: &egui::Context) {
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save MCP server setting: {}", e);
+            self.last_error = Some(format!("Failed to save MCP server setting: {}", e));
+        }
+
+        self.mcp_server = None;
+        if self.config.mcp_server.enabled {
+            match McpServer::start(&self.config.mcp_server, Arc::clone(&self.chat_sources), ctx.clone()) {
+                Ok(server) => self.mcp_server = Some(server),
+                Err(e) => self.last_error = Some(format!("Failed to start chat MCP server: {}", e)),
+            }
+        }
+    }
+
+    /// Switch reading a chat's messages aloud on or off, watching messages only while some chat is announced
+    pub fn set_announce(&mut self, ctx: &egui::Context, chat: &str, enabled: bool) {
+        self.config.announce.set_enabled(chat, enabled);
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save announcement setting: {}", e);
+            self.last_error = Some(format!("Failed to save announcement setting: {}", e));
+        }
+
+        self.update_message_watcher(ctx);
+    }
+
+    /// Switch the assistant on or off; switching it off throws away replies not sent yet
+    pub fn set_assistant_enabled(&mut self, ctx: &egui::Context, enabled: bool) {
+        self.config.assistant.enabled = enabled;
+        if !enabled {
+            self.assistant.stop();
+        }
+        self.save_assistant_config(ctx);
+    }
+
+    /// Switch the assistant answering a chat on or off
+    pub fn set_assist_chat(&mut self, ctx: &egui::Context, chat: &str, enabled: bool) {
+        self.config.assistant.set_enabled(chat, enabled);
+        self.save_assistant_config(ctx);
+    }
+
+    pub fn save_assistant_config(&mut self, ctx: &egui::Context) {
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save assistant settings: {}", e);
+            self.last_error = Some(format!("Failed to save assistant settings: {}", e));
+        }
+        self.update_message_watcher(ctx);
+    }
+
+    /// Watch messages only while some chat is announced, answered by the assistant or
+    /// tailed in the Chats tab, or the sync API serves them
+    pub fn update_message_watcher(&mut self, ctx: &egui::Context) {
+        if self.config.announce.chats.is_empty()
+            && !self.config.assistant.is_active()
+            && !self.chats_tab.is_tailing()
+            && self.sync_api.is_none()
+        {
+            self.message_watcher = None;
+        } else if self.message_watcher.is_none() {
+            match MessageWatcher::start(ctx.clone()) {
+                Ok(watcher) => self.message_watcher = Some(watcher),
+                Err(e) => self.last_error = Some(format!("Failed to watch messages: {}", e)),
+            }
+        }
+    }
+
+    /// Read aloud new messages from announced chats, within the rate limits, pass
+    /// messages in the assistant's chats to its agent and show them in the Chats tab's tail
+    pub fn check_announcements(&mut self) {
+        let Some(watcher) = &mut self.message_watcher else {
+            return;
+        };
+
+        let messages = watcher.take_messages();
+        if let Some(status) = watcher.exited() {
+            log::warn!("Message watcher stopped ({})", status);
+            self.message_watcher = None;
+        }
+
+        for message in messages {
+            if self.forget_log.as_ref().is_some_and(|forget_log| forget_log.covers(&message)) {
+                continue;
+            }
+            if let (Some(writer), Some(cached)) = (&self.message_cache, CachedMessage::from_incoming(&message)) {
+                if let Err(e) = writer.write(cached) {
+                    log::warn!("Failed to cache message: {}", e);
+                }
+            }
+            self.chats_tab.push_live(&message);
+            self.route_to_assistant(&message);
+            let chat = message.chat_key();
+            let Some(text) = self.announcer.announce(
+                &self.config.announce,
+                &chat,
+                &message.sender(),
+                &message.body(),
+                Instant::now(),
+            ) else {
+                continue;
+            };
+            if self.alert_allowed(Some(&chat), false, &text) {
+                self.speak(text, Priority::Auto);
+            }
+        }
+    }
+
+    /// Drop chats and people forgotten with `chat cache forget` from the message cache,
+    /// the Chats tab's tail and the triage queue, checking the log at most every 5 seconds
+    pub fn check_forgotten(&mut self) {
+        if self.last_forget_check.is_some_and(|t| t.elapsed() < Duration::from_secs(5)) {
+            return;
+        }
+        self.last_forget_check = Some(Instant::now());
+
+        let Some(forget_log) = &mut self.forget_log else {
+            return;
+        };
+        let targets = match forget_log.refresh() {
+            Ok(targets) => targets,
+            Err(e) => {
+                log::warn!("Failed to read the forget log: {}", e);
+                return;
+            }
+        };
+        if targets.is_empty() {
+            return;
+        }
+
+        if let Some(writer) = &self.message_cache {
+            // Commit what's buffered first, so none of the target's messages land afterwards
+            let removed = writer.flush().and_then(|()| {
+                let mut cache = MessageCache::open_default()?;
+                targets.iter().try_fold(0, |removed, target| {
+                    let (source, chat_id, sender_id) = match target {
+                        ForgetTarget::Chat { source, chat_id } => (source, Some(chat_id.as_str()), None),
+                        ForgetTarget::User { source, user_id } => (source, None, Some(user_id.as_str())),
+                    };
+                    Ok(removed + cache.forget(source, chat_id, sender_id, false)?)
+                })
+            });
+            match removed {
+                Ok(removed) => log::info!("Removed {} forgotten messages from the message cache", removed),
+                Err(e) => {
+                    log::error!("Failed to remove forgotten messages from the cache: {}", e);
+                    self.last_error = Some(format!("Failed to remove forgotten messages from the cache: {}", e));
+                }
+            }
+        }
+        for target in &targets {
+            self.chats_tab.forget(target);
+            self.triage.forget(target);
+        }
+    }
+
+    /// Pass a message to the assistant agent if it answers the chat and is running
+    fn route_to_assistant(&mut self, message: &IncomingMessage) {
+        let running = self.config.assistant.agent.is_some_and(|agent| self.agent_processes.contains_key(&agent));
+        if !running {
+            return;
+        }
+        if let Some((agent, prompt)) = self.assistant.route(&self.config.assistant, message) {
+            self.deliver_to_agents(&prompt, Some(agent));
+        }
+    }
+
+    /// Report replies the assistant finished sending
+    pub fn check_assistant_sends(&mut self) {
+        for (reply, result) in self.assistant.poll() {
+            match result {
+                Ok(()) => self.toasts.info(format!("Sent {}'s reply to {}", reply.agent_name, reply.chat)),
+                Err(e) => {
+                    self.toasts.warning(format!("Failed to send {}'s reply to {}: {}", reply.agent_name, reply.chat, e))
+                }
+            }
+        }
+    }
+
+    /// Show and speak messages routed to the dashboard by the pipelines
+    pub fn check_pipeline_events(&mut self) {
+        let Some(runner) = &mut self.pipeline_runner else {
+            return;
+        };
+
+        let events = runner.take_events();
+        if let Some(status) = runner.exited() {
+            self.last_error = Some(format!("Message pipelines stopped ({})", status));
+            self.pipeline_runner = None;
+        }
+
+        for event in events {
+            let outcome = self.run_hook(
+                Hook::Message,
+                scripting::event([
+                    ("pipeline", Dynamic::from(event.pipeline.clone())),
+                    ("route", Dynamic::from(event.route.clone())),
+                    ("chat", Dynamic::from(event.chat.clone())),
+                    ("chat_title", Dynamic::from(event.chat_title.clone().unwrap_or_default())),
+                    ("sender", Dynamic::from(event.sender())),
+                    ("body", Dynamic::from(event.body())),
+                    ("text", Dynamic::from(event.text.clone())),
+                ]),
+            );
+            if outcome.suppressed {
+                self.apply_hook_effects(outcome);
+                continue;
+            }
+            let message_text = outcome.text.clone().unwrap_or_else(|| event.text.clone());
+
+            match event.route.as_str() {
+                "notify" => {
+                    let text = format!("⚙ {}: {}", event.pipeline, message_text);
+                    self.raise_alert("pipeline", text, Some(event.chat.clone()), event.critical, false);
+                }
+                "tts" => {
+                    if self.alert_allowed(Some(&event.chat), event.critical, &message_text) {
+                        if let Some(ref service) = self.tts_service {
+                            let voice = event
+                                .voice
+                                .clone()
+                                .unwrap_or_else(|| self.tts_config.selected_voice.clone());
+                            let request = TTSRequest::new(message_text, voice, self.tts_config.playback_speed);
+                            let service = service.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = service.speak(request).await {
+                                    log::error!("TTS speak error: {}", e);
+                                }
+                            });
+                        }
+                    }
+                }
+                other => log::warn!("Unknown pipeline route '{}'", other),
+            }
+            self.apply_hook_effects(outcome);
+        }
+    }
+
+    /// Save what the setup wizard collected and start using it
+    pub fn finish_onboarding(&mut self, ctx: &egui::Context, setup: Setup) {
+        let agent = setup.apply(&mut self.config);
+        if let Err(e) = setup.write(&self.config) {
+            log::error!("Failed to save setup: {}", e);
+            self.last_error = Some(format!("Failed to save setup: {}", e));
+            return;
+        }
+
+        self.tts_config = self.config.tts.clone();
+        self.tts_service = None;
+        if self.tts_config.enabled {
+            match TTSService::start(self.tts_config.clone(), ctx.clone()) {
+                Ok(service) => self.tts_service = Some(service),
+                Err(e) => self.last_error = Some(format!("Failed to start TTS: {}", e)),
+            }
+        }
+
+        if let Some(agent) = agent {
+            self.active_agents.insert(agent.id, agent.name.clone());
+            self.selected_agent = Some(agent.id);
+        }
+
+        self.onboarding = None;
+        self.current_tab = AppTab::Home;
+    }
+
+    /// Close the setup wizard without changes; it won't open again on its own
+    pub fn skip_onboarding(&mut self) {
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save config: {}", e);
+        }
+        self.onboarding = None;
+    }
+
+    /// Start or stop the clipboard watcher and persist the choice
+    pub fn set_clipboard_enabled(&mut self, ctx: &egui::Context, enabled: bool) {
+        self.config.clipboard.enabled = enabled;
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save clipboard setting: {}", e);
+            self.last_error = Some(format!("Failed to save clipboard setting: {}", e));
+        }
+
+        if !enabled {
+            self.clipboard_watcher = None;
+            self.clip = None;
+        } else if self.clipboard_watcher.is_none() {
+            match ClipboardWatcher::start(&self.config.clipboard, ctx.clone()) {
+                Ok(watcher) => self.clipboard_watcher = Some(watcher),
+                Err(e) => self.last_error = Some(format!("Failed to watch clipboard: {}", e)),
+            }
+        }
+    }
+
+    /// Start or restart voice commands with the current settings, or stop them
+    pub fn apply_voice_config(&mut self, ctx: &egui::Context) {
+        self.voice = None;
+        if !self.config.voice.enabled {
+            return;
+        }
+        match VoiceListener::start(&self.config.voice, ctx.clone()) {
+            Ok(listener) => self.voice = Some(listener),
+            Err(e) => self.last_error = Some(format!("Failed to start voice commands: {}", e)),
+        }
+    }
+
+    fn session_snapshot(&self) -> SessionSnapshot {
+        let skip = self.chat_messages.len().saturating_sub(session::MAX_MESSAGES);
+        SessionSnapshot {
+            saved_at: chrono::Utc::now(),
+            tab: self.current_tab,
+            active_agents: self.active_agents.keys().copied().collect(),
+            selected_agent: self.selected_agent,
+            broadcast_mode: self.broadcast_mode,
+            chat_messages: self.chat_messages[skip..].to_vec(),
+            branches: self.branches.clone(),
+            chat_input: self.chat_input.clone(),
+            terminal_startup_command: self.terminal_startup_command.clone(),
+            terminal_running: self.terminal_stdin_tx.is_some(),
+            terminal_input: self.terminal_input.clone(),
+        }
+    }
+
+    /// Replace the chat with `count` generated messages and keep repainting, with the
+    /// frame time overlay open, to see how the chat log copes with a long conversation
+    pub fn start_chat_stress(&mut self, count: usize) {
+        self.chat_messages = super::chat::stress_messages(count);
+        self.branches = Branches::default();
+        self.chat_stress = true;
+        self.debug_overlay.visible = true;
+        log::info!("Chat stress test with {} messages", count);
+    }
+
+    /// Save the session for crash recovery every `SNAPSHOT_INTERVAL`. Not while the
+    /// previous session is waiting to be restored, which would overwrite it.
+    fn save_session_if_due(&mut self) {
+        let test_mode = self.test_mode.as_ref().is_some_and(|mode| mode.lock().is_ok_and(|mode| mode.enabled));
+        if test_mode || self.chat_stress || self.session_recovery.is_some() || self.session_saved_at.elapsed() < session::SNAPSHOT_INTERVAL {
+            return;
+        }
+        self.session_saved_at = Instant::now();
+        let snapshot = self.session_snapshot();
+        if let Err(e) = session::snapshot_path().and_then(|path| session::save(&snapshot, &path)) {
+            log::warn!("Failed to save the session: {}", e);
+        }
+    }
+
+    /// Bring back a crashed session: its tab, chat, agents and terminal command
+    pub fn restore_session(&mut self, ctx: &egui::Context, snapshot: SessionSnapshot) {
+        let mut messages = snapshot.chat_messages;
+        messages.append(&mut self.chat_messages);
+        self.chat_messages = messages;
+        self.branches = snapshot.branches;
+        if self.chat_input.is_empty() {
+            self.chat_input = snapshot.chat_input;
+        }
+
+        let agents: Vec<AgentConfig> = self
+            .config
+            .agents
+            .iter()
+            .filter(|agent| snapshot.active_agents.contains(&agent.id))
+            .cloned()
+            .collect();
+        for agent in &agents {
+            self.connect_agent(ctx, agent);
+            // Agents that are sent the whole conversation pick it up where it was
+            self.sync_history(agent.id);
+        }
+        self.selected_agent = snapshot.selected_agent.filter(|id| self.active_agents.contains_key(id));
+        self.broadcast_mode = snapshot.broadcast_mode;
+
+        self.terminal_startup_command = snapshot.terminal_startup_command;
+        if snapshot.terminal_running {
+            self.reset_terminal(ctx);
+        } else if self.terminal_history.command() != self.terminal_startup_command {
+            self.terminal_history = CommandHistory::load(&self.terminal_startup_command);
+        }
+        self.terminal_input = snapshot.terminal_input;
+        self.current_tab = snapshot.tab;
+    }
+
+    /// Announce an update found by a check, unless the update window shows it already
+    fn check_updates(&mut self) {
+        let Some(status) = self.updater.poll() else {
+            return;
+        };
+        if !self.show_update_window {
+            let latest = status.latest.clone().unwrap_or_default();
+            self.toasts.info(format!("chat {} is available; see Updates to install it", latest));
+        }
+    }
+
+    /// Act on keyboard shortcuts, so every panel can be reached without a mouse
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        for action in super::shortcuts::read(ctx) {
+            match action {
+                Action::ShowTab(tab) => {
+                    if tab == AppTab::Chats && self.current_tab != AppTab::Chats {
+                        self.chats_tab.refresh();
+                    }
+                    self.current_tab = tab;
+                }
+                // These windows belong to the Home tab
+                Action::ToggleConfig => {
+                    self.current_tab = AppTab::Home;
+                    self.show_config_panel = !self.show_config_panel;
+                }
+                Action::TogglePlans => {
+                    self.current_tab = AppTab::Home;
+                    self.show_plan_panel = !self.show_plan_panel;
+                }
+                Action::ToggleTts => {
+                    self.current_tab = AppTab::Home;
+                    self.show_tts_panel = !self.show_tts_panel;
+                }
+                Action::ToggleScripts => {
+                    self.current_tab = AppTab::Home;
+                    self.show_scripts_panel = !self.show_scripts_panel;
+                }
+                Action::ToggleUpdates => self.show_update_window = !self.show_update_window,
+                Action::ToggleLogs => self.show_logs_panel = !self.show_logs_panel,
+                Action::ToggleBlackboard => self.show_blackboard = !self.show_blackboard,
+                Action::ToggleTriage => self.toggle_triage(),
+                Action::FocusInput => match self.current_tab {
+                    AppTab::Chats => self.chats_tab.focus_search = true,
+                    AppTab::Home | AppTab::Term => self.focus_input = true,
+                },
+                Action::PreviousAgent | Action::NextAgent => {
+                    // Same order as the agents panel lists them
+                    let agents: Vec<AgentId> = self.active_agents.keys().copied().collect();
+                    if agents.is_empty() {
+                        continue;
+                    }
+                    let current = self.selected_agent.and_then(|id| agents.iter().position(|agent| *agent == id));
+                    let index = match (action, current) {
+                        (Action::NextAgent, Some(index)) => (index + 1) % agents.len(),
+                        (_, Some(index)) => (index + agents.len() - 1) % agents.len(),
+                        (Action::NextAgent, None) => 0,
+                        (_, None) => agents.len() - 1,
+                    };
+                    self.selected_agent = Some(agents[index]);
+                    self.broadcast_mode = false;
+                    self.current_tab = AppTab::Home;
+                }
+                Action::CloseWindows => {
+                    self.show_config_panel = false;
+                    self.show_plan_panel = false;
+                    self.show_tts_panel = false;
+                    self.show_scripts_panel = false;
+                    self.show_update_window = false;
+                    self.show_logs_panel = false;
+                    self.show_blackboard = false;
+                    self.show_assistant = false;
+                    self.show_triage = false;
+                    self.show_identities = false;
+                    self.show_shortcuts = false;
+                }
+                Action::ToggleHelp => self.show_shortcuts = !self.show_shortcuts,
+            }
+        }
+    }
+
+    /// Open or close the Triage window, loading unread messages the first time it opens
+    pub fn toggle_triage(&mut self) {
+        self.show_triage = !self.show_triage;
+        if self.show_triage && self.triage.current().is_none() {
+            self.triage.load();
+        }
+    }
+
+    /// Start or restart resource monitoring with the current settings, or stop it
+    pub fn apply_monitor_config(&mut self, ctx: &egui::Context) {
+        self.monitor = self.config.monitor.enabled.then(|| ResourceMonitor::start(&self.config.monitor, ctx.clone()));
+    }
+
+    /// Point the monitor at the running processes and report limits they reached
+    fn check_resources(&mut self) {
+        let Some(monitor) = &self.monitor else {
+            return;
+        };
+        let mut targets: HashMap<Target, u32> = self
+            .agent_processes
+            .iter()
+            .filter_map(|(id, process)| Some((Target::Agent(*id), process.pid()?)))
+            .collect();
+        let terminal_pid = self.terminal_pid.load(Ordering::Relaxed);
+        if terminal_pid != 0 {
+            targets.insert(Target::Terminal, terminal_pid);
+        }
+        monitor.set_targets(targets);
+
+        for event in monitor.events() {
+            let name = |target: Target| match target {
+                Target::Agent(id) => self.active_agents.get(&id).cloned().unwrap_or_else(|| id.to_string()),
+                Target::Terminal => "Terminal".to_string(),
+            };
+            let text = match event {
+                LimitEvent::OverMemory { target, usage, limit_mb, killed } => format!(
+                    "{} {} using {:.0} MB, over its {} MB budget",
+                    name(target),
+                    if killed { "was killed for" } else { "is" },
+                    usage.memory_mb(),
+                    limit_mb
+                ),
+                LimitEvent::HighCpu { target, usage } => format!("{} is busy: {}", name(target), usage.summary()),
+            };
+            log::warn!("{}", text);
+            self.toasts.warning(text);
+        }
+    }
+
+    /// Start listening on the hotkey and act on what was heard
+    pub fn check_voice_commands(&mut self, ctx: &egui::Context) {
+        let Some(listener) = &self.voice else {
+            return;
+        };
+        if let Some(shortcut) = voice::parse_hotkey(&self.config.voice.hotkey) {
+            if ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+                listener.listen();
+            }
+        }
+
+        for event in listener.events() {
+            match event {
+                VoiceEvent::Awake => self.speak("Yes?".to_string(), Priority::User),
+                VoiceEvent::Heard(text) => self.run_voice_command(&text),
+                VoiceEvent::Failed(e) => self.toasts.warning(format!("Voice command: {}", e)),
+            }
+        }
+    }
+
+    /// Carry out a transcribed command and confirm it out loud
+    fn run_voice_command(&mut self, text: &str) {
+        log::info!("Voice command: {}", text);
+        let agents: Vec<String> = self.active_agents.values().cloned().collect();
+        let Some(intent) = intent::parse(text, &agents) else {
+            self.toasts.warning(format!("🎙 Didn't understand \"{}\"", text));
+            self.speak("Sorry, I didn't understand that".to_string(), Priority::User);
+            return;
+        };
+
+        match &intent {
+            Intent::AskAgent { agent, message } => {
+                let Some(id) = self.active_agents.iter().find(|(_, name)| *name == agent).map(|(id, _)| *id) else {
+                    return;
+                };
+                self.deliver_to_agents(message, Some(id));
+                self.push_chat_message(ChatMessage::new_user_message(message.clone(), Some(id)));
+            }
+            Intent::Broadcast(message) => {
+                self.deliver_to_agents(message, None);
+                self.push_chat_message(ChatMessage::new_user_message(message.clone(), None));
+            }
+            Intent::StopSpeaking | Intent::ClearQueue => {
+                if let Some(service) = self.tts_service.clone() {
+                    let clear = intent == Intent::ClearQueue;
+                    tokio::spawn(async move {
+                        let _ = if clear { service.clear_queue().await } else { service.stop().await };
+                    });
+                }
+            }
+            Intent::ReadLast => {
+                let shown = self.branches.visible(&self.chat_messages);
+                let last = shown.into_iter().rev().find(|m| m.direction == MessageDirection::FromAgent).cloned();
+                match last {
+                    Some(message) => self.speak_message(&message, Priority::User),
+                    None => self.speak("No replies yet".to_string(), Priority::User),
+                }
+            }
+            Intent::SwitchTab(tab) => {
+                self.current_tab = match tab {
+                    Tab::Home => AppTab::Home,
+                    Tab::Chats => AppTab::Chats,
+                    Tab::Term => AppTab::Term,
+                };
+                if *tab == Tab::Chats {
+                    self.chats_tab.refresh();
+                }
+            }
+            Intent::DoNotDisturb(on) => {
+                if self.config.dnd.manual != *on {
+                    self.toggle_dnd();
+                }
+            }
+        }
+
+        let confirmation = intent.confirmation();
+        self.toasts.info(format!("🎙 {}", confirmation));
+        // Speaking over a stop would undo it
+        if intent != Intent::StopSpeaking && intent != Intent::ReadLast {
+            self.speak(confirmation, Priority::User);
+        }
+    }
+
+    /// Update the source indicators and toast connection changes
+    pub fn check_source_events(&mut self) {
+        let Some(watcher) = &mut self.source_events else {
+            return;
+        };
+
+        let events = watcher.take_events();
+        if let Some(status) = watcher.exited() {
+            log::warn!("Source event watcher stopped ({})", status);
+            self.source_events = None;
+        }
+
+        for event in events {
+            let connected = event.is_connected();
+            let auth_required = event.needs_sign_in();
+            let known = self.source_indicators.iter_mut().find(|s| s.id == event.source);
+
+            // The first report of a healthy source says nothing new
+            let changed = known.as_ref().is_some_and(|s| s.is_connected != connected);
+            match known {
+                Some(indicator) => {
+                    indicator.is_connected = connected;
+                    // Rate limits and disconnects say nothing about the session
+                    if auth_required || connected {
+                        indicator.auth_required = auth_required;
+                    }
+                }
+                None => {
+                    self.source_indicators.push(SourceIndicator {
+                        id: event.source.clone(),
+                        name: event.source.clone(),
+                        is_connected: connected,
+                        auth_required,
+                        lag: 0,
+                        dropped: 0,
+                    });
+                    self.source_indicators.sort_by(|a, b| a.id.cmp(&b.id));
+                }
+            }
+
+            if auth_required {
+                self.prompt_sign_in(&event.source);
+            }
+
+            if changed || event.is_problem() {
+                let text = event.describe();
+                log::info!("Source event: {}", text);
+                if event.is_problem() {
+                    self.toasts.warning(text);
+                } else {
+                    self.toasts.info(text);
+                }
+            }
+        }
+    }
+
+    /// Open the sign-in dialog for a source whose session expired. Only Telegram can be
+    /// signed into from the dashboard; other sources are left to their toast.
+    pub fn prompt_sign_in(&mut self, source: &str) {
+        if source != "telegram" || self.reauth_source.is_some() {
+            return;
+        }
+        // A login finished earlier in the config panel must not close the dialog at once
+        if !self.telegram_login.in_progress() {
+            self.telegram_login.reset();
+        }
+        self.reauth_source = Some(source.to_string());
+    }
+
+    /// Restart everything holding a chat CLI session, so a new sign-in takes effect
+    pub fn restart_source_watchers(&mut self, ctx: &egui::Context) {
+        self.source_events = None;
+        match SourceEventWatcher::start(ctx.clone()) {
+            Ok(watcher) => self.source_events = Some(watcher),
+            Err(e) => log::warn!("Not watching source events: {}", e),
+        }
+
+        if self.message_watcher.is_some() {
+            self.message_watcher = None;
+            match MessageWatcher::start(ctx.clone()) {
+                Ok(watcher) => self.message_watcher = Some(watcher),
+                Err(e) => self.last_error = Some(format!("Failed to watch messages: {}", e)),
+            }
+        }
+
+        // The sources reconnect with the new session, then the server serves from them
+        match mcp::load_sources() {
+            Ok(sources) => self.chat_sources = sources,
+            Err(e) => log::warn!("Keeping the chat sources as they were: {}", e),
+        }
+        if self.mcp_server.is_some() {
+            self.mcp_server = None;
+            match McpServer::start(&self.config.mcp_server, Arc::clone(&self.chat_sources), ctx.clone()) {
+                Ok(server) => self.mcp_server = Some(server),
+                Err(e) => self.last_error = Some(format!("Failed to start chat MCP server: {}", e)),
+            }
+        }
+    }
+
+    /// Offer actions for clipboard text copied since the last frame
+    pub fn check_clipboard(&mut self) {
+        if let Some(clip) = self.clipboard_watcher.as_ref().and_then(|w| w.latest()) {
+            self.clip = Some(clip);
+        }
+    }
+
+    /// Send the pending clip to the selected agent (or all agents in broadcast mode)
+    pub fn send_clip_to_agent(&mut self) {
+        let Some(clip) = self.clip.take() else {
+            return;
+        };
+
+        let recipient = if self.broadcast_mode { None } else { self.selected_agent };
+        let content = clip.agent_message();
+        self.deliver_to_agents(&content, recipient);
+        self.push_chat_message(ChatMessage::new_user_message(content, recipient));
+        self.current_tab = AppTab::Home;
+    }
+
+    /// Search chat history for the pending clip
+    pub fn search_chats_for_clip(&mut self) {
+        let Some(clip) = self.clip.take() else {
+            return;
+        };
+
+        self.chats_tab.search(clip.search_query());
+        self.current_tab = AppTab::Chats;
+    }
+
+    fn render_clipboard_bar(&mut self, ctx: &egui::Context) {
+        let Some(clip) = &self.clip else {
+            return;
+        };
+
+        let can_send = self.broadcast_mode || self.selected_agent.is_some();
+        let agent_label = if self.broadcast_mode {
+            "Send to all agents".to_string()
+        } else {
+            match self.selected_agent.and_then(|id| self.active_agents.get(&id)) {
+                Some(name) => format!("Send to {}", name),
+                None => "Send to agent".to_string(),
+            }
+        };
+        let suggested = clip.suggested_target();
+        let kind = match clip.kind {
+            ClipKind::Url => "🔗 Link",
+            ClipKind::Code => "⌨ Code",
+            ClipKind::Text => "📋 Text",
+        };
+        let preview = clip.preview();
+
+        let mut action = None;
+        egui::TopBottomPanel::bottom("clipboard_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(kind).strong());
+                ui.label(egui::RichText::new(preview).monospace());
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                        action = Some(None);
+                    }
+
+                    let search = egui::Button::new("Search chats").selected(suggested == ClipTarget::ChatSearch);
+                    if ui.add(search).clicked() {
+                        action = Some(Some(ClipTarget::ChatSearch));
+                    }
+
+                    let send = egui::Button::new(agent_label).selected(suggested == ClipTarget::Agent);
+                    if ui
+                        .add_enabled(can_send, send)
+                        .on_disabled_hover_text("Select an agent first")
+                        .clicked()
+                    {
+                        action = Some(Some(ClipTarget::Agent));
+                    }
+                });
+            });
+        });
+
+        match action {
+            Some(Some(ClipTarget::Agent)) => self.send_clip_to_agent(),
+            Some(Some(ClipTarget::ChatSearch)) => self.search_chats_for_clip(),
+            Some(None) => self.clip = None,
+            None => {}
         }
     }
 
@@ -341,21 +1827,327 @@ impl DashboardApp {
             self.selected_agent
         };
 
+        self.deliver_to_agents(&content, recipient);
         let message = ChatMessage::new_user_message(content, recipient);
-        self.chat_messages.push(message);
+        self.push_chat_message(message);
 
         self.chat_input.clear();
     }
 
-    pub fn spawn_terminal(&mut self) {
+    /// Pass user text to the recipient's process, or every process when broadcasting
+    pub fn deliver_to_agents(&mut self, content: &str, recipient: Option<AgentId>) {
+        let mut failed = Vec::new();
+        for (id, process) in &self.agent_processes {
+            if recipient.is_some_and(|r| r != *id) {
+                continue;
+            }
+            if let Err(e) = process.send_message(content) {
+                failed.push(format!("{}: {}", self.active_agents.get(id).cloned().unwrap_or_default(), e));
+            }
+        }
+        if !failed.is_empty() {
+            self.last_error = Some(format!("Failed to send to agent {}", failed.join(", ")));
+        }
+    }
+
+    /// Mark an agent active, starting its process for process and coding CLI agents
+    pub fn connect_agent(&mut self, ctx: &egui::Context, agent: &AgentConfig) {
+        if !self.agent_processes.contains_key(&agent.id) {
+            // Agents other than LLMs read their system prompt and memory from a file
+            let context_file = match agent.agent_type {
+                AgentType::Ollama => None,
+                _ => context::write_context_file(agent.id, &agent.context).unwrap_or_else(|e| {
+                    log::warn!("Failed to write the context file for {}: {}", agent.name, e);
+                    None
+                }),
+            };
+            let mut sandbox = agent.sandbox.clone();
+            if let Some(path) = &context_file {
+                sandbox.read_only_paths.push(path.display().to_string());
+            }
+
+            let started: anyhow::Result<Option<Box<dyn RunningAgent>>> = match agent.agent_type {
+                AgentType::Ollama => serde_json::from_value::<OllamaConfig>(agent.config_data.clone())
+                    .map_err(anyhow::Error::from)
+                    .map(|config| {
+                        Some(Box::new(OllamaChat::start(config, &agent.context, ctx.clone())) as Box<dyn RunningAgent>)
+                    }),
+                AgentType::Process => serde_json::from_value::<ProcessAgentConfig>(agent.config_data.clone())
+                    .map_err(anyhow::Error::from)
+                    .and_then(|config| {
+                        let approval = AgentApproval {
+                            gate: Arc::clone(&self.approvals),
+                            agent: agent.id,
+                            agent_name: agent.name.clone(),
+                            cwd: config.workspace.clone(),
+                        };
+                        let env: Vec<(&str, String)> = context_file
+                            .iter()
+                            .map(|path| (context::CONTEXT_FILE_ENV, path.display().to_string()))
+                            .collect();
+                        AgentProcess::spawn(
+                            &config,
+                            &env,
+                            &sandbox,
+                            Arc::clone(&self.tools),
+                            Some(approval),
+                            ctx.clone(),
+                        )
+                    })
+                    .map(|process| Some(Box::new(process) as Box<dyn RunningAgent>)),
+                AgentType::ClaudeCode | AgentType::Codex => {
+                    let cli = CodingCli::for_agent_type(&agent.agent_type).expect("coding CLI agent type");
+                    let chat_mcp = self.mcp_server.as_mut().and_then(|server| server.running_address());
+                    serde_json::from_value::<CodingCliConfig>(agent.config_data.clone())
+                        .map_err(anyhow::Error::from)
+                        .and_then(|config| {
+                            CodingCliAgent::spawn(
+                                cli,
+                                &config,
+                                &sandbox,
+                                chat_mcp.as_deref(),
+                                context_file.as_deref(),
+                                ctx.clone(),
+                            )
+                        })
+                        .map(|process| Some(Box::new(process) as Box<dyn RunningAgent>))
+                }
+            };
+            match started {
+                Ok(Some(process)) => {
+                    self.agent_processes.insert(agent.id, process);
+                    if agent.sandbox.enabled {
+                        self.agent_sandboxes.insert(agent.id, agent.sandbox.summary());
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    self.last_error = Some(format!("Failed to start {}: {:#}", agent.name, e));
+                    return;
+                }
+            }
+        }
+
+        self.active_agents.insert(agent.id, agent.name.clone());
+    }
+
+    /// Pass an agent's changed system prompt and memory on to it, if it's running
+    pub fn apply_agent_context(&self, id: AgentId) {
+        let (Some(agent), Some(process)) =
+            (self.config.agents.iter().find(|agent| agent.id == id), self.agent_processes.get(&id))
+        else {
+            return;
+        };
+        if agent.agent_type != AgentType::Ollama {
+            if let Err(e) = context::write_context_file(agent.id, &agent.context) {
+                log::warn!("Failed to write the context file for {}: {}", agent.name, e);
+            }
+        }
+        process.set_context(&agent.context);
+    }
+
+    /// Workspace directories of the active agents
+    pub fn agent_workspaces(&self) -> Vec<(AgentId, std::path::PathBuf)> {
+        self.config
+            .agents
+            .iter()
+            .filter(|agent| self.active_agents.contains_key(&agent.id))
+            .filter_map(|agent| Some((agent.id, crate::workspace::agent_workspace(agent)?)))
+            .collect()
+    }
+
+    /// Stop an agent's process and drop it from the active agents
+    pub fn disconnect_agent(&mut self, id: &AgentId) {
+        self.agent_processes.remove(id);
+        self.agent_states.remove(id);
+        self.agent_sandboxes.remove(id);
+        self.active_agents.remove(id);
+    }
+
+    /// Turn process agent output into chat messages, tool call notes and log lines
+    pub fn check_agent_output(&mut self) {
+        let mut outputs = Vec::new();
+        let mut stopped = Vec::new();
+        for (id, process) in &mut self.agent_processes {
+            outputs.push((*id, process.take_output()));
+            if let Some(status) = process.exited() {
+                stopped.push((*id, status));
+            }
+        }
+
+        for (id, output) in outputs {
+            // Free-form lines between frames are shown as one message
+            let mut text = Vec::new();
+            for item in output {
+                match item {
+                    Output::Text(line) => text.push(line),
+                    Output::Frame(frame) => {
+                        self.flush_agent_text(id, &mut text);
+                        self.apply_agent_frame(id, frame);
+                    }
+                }
+            }
+            self.flush_agent_text(id, &mut text);
+        }
+
+        for (id, status) in stopped {
+            let name = self.active_agents.get(&id).cloned().unwrap_or_else(|| id.to_string());
+            self.push_chat_message(ChatMessage::new_notification(format!("{} stopped ({})", name, status)));
+            self.disconnect_agent(&id);
+        }
+    }
+
+    fn flush_agent_text(&mut self, id: AgentId, lines: &mut Vec<String>) {
+        let text = lines.join("\n");
+        lines.clear();
+        if !text.trim().is_empty() && !self.take_assistant_reply(id, &text) && !super::workspace::take_commit_draft(self, id, &text) {
+            self.push_chat_message(ChatMessage::new_agent_message(id, text.trim_end().to_string()));
+        }
+    }
+
+    /// Put an agent's reply to a routed chat message in the assistant's outbox
+    fn take_assistant_reply(&mut self, id: AgentId, text: &str) -> bool {
+        let name = self.active_agents.get(&id).cloned().unwrap_or_else(|| id.to_string());
+        if !self.assistant.take_reply(id, &name, text) {
+            return false;
+        }
+        self.toasts.info(format!("{} drafted a reply; approve it in the Assistant window", name));
+        true
+    }
+
+    fn apply_agent_frame(&mut self, id: AgentId, frame: Frame) {
+        let name = self.active_agents.get(&id).cloned().unwrap_or_else(|| id.to_string());
+        match frame {
+            Frame::Message { text } => {
+                if !self.take_assistant_reply(id, &text) && !super::workspace::take_commit_draft(self, id, &text) {
+                    self.push_chat_message(ChatMessage::new_agent_message(id, text));
+                }
+            }
+            Frame::ToolCall { id: call_id, name: tool, parameters } => {
+                self.push_chat_message(ChatMessage::new_tool_call(id, ToolCall::new(call_id, tool, parameters)));
+            }
+            Frame::ToolResult { id: call_id, result } => {
+                let call = self.chat_messages.iter().rposition(|message| {
+                    message.agent_id == Some(id)
+                        && message.metadata.tool_call.as_ref().is_some_and(|call| call.call_id == call_id && !call.is_done())
+                });
+                match call {
+                    Some(index) => self.finish_tool_call(index, &result),
+                    None => {
+                        let text = match result.error {
+                            None => format!("🔧 {} tool finished", name),
+                            Some(error) => format!("🔧 {} tool failed: {}", name, error),
+                        };
+                        self.chat_messages.push(ChatMessage::new_notification(text));
+                    }
+                }
+            }
+            Frame::Log { level, message } => log::log!(level.into(), "{}: {}", name, message),
+            Frame::Status { state, detail } => {
+                if state == AgentState::Error {
+                    let detail = detail.clone().unwrap_or_else(|| "unknown error".to_string());
+                    self.last_error = Some(format!("{}: {}", name, detail));
+                }
+                self.agent_states.insert(id, (state, detail));
+            }
+        }
+    }
+
+    /// Record the result of the tool call in `chat_messages[index]`, timed from when it was called
+    fn finish_tool_call(&mut self, index: usize, result: &ToolcallResult) {
+        let message = &mut self.chat_messages[index];
+        let Some(call) = &mut message.metadata.tool_call else {
+            return;
+        };
+        call.finish(result, (chrono::Utc::now() - message.timestamp).to_std().unwrap_or_default());
+        if let Some(store) = &self.chat_history_store {
+            if let Err(e) = store.save_message(message) {
+                log::warn!("Failed to save chat message: {}", e);
+            }
+        }
+    }
+
+    /// Run the tool call `message` again with the same arguments, as a new call in the log.
+    /// Shell commands go through the agent's approval policy and sandbox as they did the
+    /// first time, so calls of agents no longer configured aren't run again.
+    fn rerun_tool(&mut self, ctx: &egui::Context, message: MessageId) {
+        let Some((agent, call)) = self
+            .chat_messages
+            .iter()
+            .find(|m| m.id == message)
+            .and_then(|m| Some((m.agent_id?, m.metadata.tool_call.clone()?)))
+        else {
+            return;
+        };
+        let Some(config) = self.config.agents.iter().find(|config| config.id == agent) else {
+            self.last_error = Some(format!("Can't run {} again: its agent is no longer configured", call.name));
+            return;
+        };
+        let workspace = crate::workspace::agent_workspace(config);
+        let scope = ToolcallScope { sandbox: config.sandbox.clone(), workspace: workspace.clone() };
+        let approval = AgentApproval {
+            gate: Arc::clone(&self.approvals),
+            agent,
+            agent_name: config.name.clone(),
+            cwd: workspace.map(|path| path.display().to_string()),
+        };
+        let request = ToolcallRequest { name: call.name.clone(), parameters: call.arguments.clone() };
+        let rerun = ChatMessage::new_tool_call(agent, ToolCall::new(format!("rerun-{}", call.call_id), call.name, call.arguments));
+
+        let (tx, rx) = mpsc::channel();
+        let tools = Arc::clone(&self.tools);
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = approval.check(&request).unwrap_or_else(|| {
+                let failed = |e: String| ToolcallResult { success: false, output: String::new(), error: Some(e) };
+                match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                    Ok(runtime) => runtime.block_on(tools.execute_in(request, &scope)).unwrap_or_else(|e| failed(e.to_string())),
+                    Err(e) => failed(format!("Failed to start tool runtime: {}", e)),
+                }
+            });
+            let _ = tx.send(result);
+            ctx.request_repaint();
+        });
+
+        self.tool_reruns.push((rerun.id, rx));
+        self.push_chat_message(rerun);
+    }
+
+    fn check_tool_reruns(&mut self) {
+        let mut finished = Vec::new();
+        self.tool_reruns.retain(|(id, rx)| match rx.try_recv() {
+            Ok(result) => {
+                finished.push((*id, result));
+                false
+            }
+            Err(mpsc::TryRecvError::Empty) => true,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        });
+        for (id, result) in finished {
+            if let Some(index) = self.chat_messages.iter().position(|m| m.id == id) {
+                self.finish_tool_call(index, &result);
+            }
+        }
+    }
+
+    /// Start the startup command in a PTY; `ctx` is woken when output arrives
+    pub fn spawn_terminal(&mut self, ctx: &egui::Context) {
         if self.terminal_stdin_tx.is_some() {
             return; // Already spawned
         }
 
         let command = self.terminal_startup_command.clone();
         let pty_size = self.terminal_pty_size;
+        let repaint = ctx.clone();
         let (stdin_tx, stdin_rx) = mpsc::channel::<String>();
         let (output_tx, output_rx) = mpsc::channel::<OutputLine>();
+        // A new program starts with bracketed paste off
+        let bracketed_paste = Arc::new(AtomicBool::new(false));
+        self.terminal_bracketed_paste = bracketed_paste.clone();
+        let sandbox = self.config.terminal.sandbox.clone();
+        self.terminal_sandbox = sandbox.enabled.then(|| sandbox.summary());
+        let terminal_pid = Arc::new(AtomicU32::new(0));
+        self.terminal_pid = terminal_pid.clone();
 
         std::thread::spawn(move || {
             // Initialize PTY system
@@ -373,10 +2165,17 @@ impl DashboardApp {
             let master = pair.master;
             let slave = pair.slave;
 
-            // Parse command using shell-words for proper argument handling
-            let mut cmd = CommandBuilder::new("sh");
-            cmd.arg("-c");
-            cmd.arg(&command);
+            let cwd = std::env::current_dir().ok();
+            let launch = match sandbox.wrap("sh", &["-c".to_string(), command.clone()], cwd.as_deref()) {
+                Ok(launch) => launch,
+                Err(e) => {
+                    let _ = output_tx.send(OutputLine::Stderr(format!("Failed to sandbox process: {:#}\n", e)));
+                    return;
+                }
+            };
+            let mut cmd = CommandBuilder::new(&launch.program);
+            cmd.args(&launch.args);
+            launch.apply_env_pty(&mut cmd);
 
             // Spawn child process with PTY
             let mut child = match slave.spawn_command(cmd) {
@@ -387,6 +2186,7 @@ impl DashboardApp {
                     return;
                 }
             };
+            terminal_pid.store(child.process_id().unwrap_or(0), Ordering::Relaxed);
 
             // Clone master for reader thread
             let reader = match master.try_clone_reader() {
@@ -399,15 +2199,14 @@ impl DashboardApp {
 
             // Spawn PTY reader thread with ANSI parser
             let output_tx_clone = output_tx.clone();
+            let reader_repaint = repaint.clone();
             std::thread::spawn(move || {
-                use std::time::{Duration, Instant};
-
                 let mut reader = reader;
                 let mut buffer = [0u8; 1024];
 
                 // Create VTE parser and performer
                 let styled_segments = Arc::new(Mutex::new(Vec::new()));
-                let mut performer = TerminalPerformer::new(styled_segments.clone());
+                let mut performer = TerminalPerformer::new(styled_segments.clone(), bracketed_paste);
                 let mut parser = Parser::new();
                 let mut last_output_time = Instant::now();
                 let flush_delay = Duration::from_millis(10);  // Small delay to batch rapid updates
@@ -422,6 +2221,7 @@ impl DashboardApp {
                             if !segments.is_empty() {
                                 let _ = output_tx_clone.send(OutputLine::Styled(segments));
                             }
+                            reader_repaint.request_repaint();
                             break;
                         }
                         Ok(n) => {
@@ -443,6 +2243,7 @@ impl DashboardApp {
                                     }
                                     segments_guard.clear();
                                     last_output_time = now;
+                                    reader_repaint.request_repaint();
                                 }
                             }
                         }
@@ -468,26 +2269,126 @@ impl DashboardApp {
 
             // Wait for child process
             let _ = child.wait();
+            terminal_pid.store(0, Ordering::Relaxed);
         });
 
         self.terminal_stdin_tx = Some(stdin_tx);
         self.terminal_stdout_rx = Some(output_rx);
     }
 
-    pub fn reset_terminal(&mut self) {
+    pub fn reset_terminal(&mut self, ctx: &egui::Context) {
         // Drop existing channels and PTY
         self.terminal_stdin_tx = None;
         self.terminal_stdout_rx = None;
         self.terminal_pty_master = None;
         self.terminal_output.clear();
+        if self.terminal_history.command() != self.terminal_startup_command {
+            self.terminal_history = CommandHistory::load(&self.terminal_startup_command);
+        }
+        self.terminal_history_search = None;
+        self.terminal_pending_paste = None;
 
         // Spawn new terminal with updated command
-        self.spawn_terminal();
+        self.spawn_terminal(ctx);
+    }
+}
+
+/// Segments for narrating a chat summary, a chapter per paragraph
+/// Start the sync API, generating its token the first time
+fn start_sync_api(config: &mut AppConfig) -> Option<SyncApi> {
+    if config.sync_api.token.is_empty() {
+        config.sync_api.token = uuid::Uuid::new_v4().simple().to_string();
+        if let Err(e) = config.save() {
+            log::error!("Failed to save the sync API token: {}", e);
+        }
+    }
+    match MessageCache::default_path().and_then(|path| SyncApi::start(&config.sync_api, path)) {
+        Ok(api) => {
+            log::info!("Sync API listening on {}", api.address());
+            Some(api)
+        }
+        Err(e) => {
+            log::warn!("Not serving the sync API: {}", e);
+            None
+        }
     }
 }
 
+fn summary_segments(summary: &str) -> Vec<Segment> {
+    summary
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| Segment {
+            speaker: "Catch-up".to_string(),
+            text: paragraph.to_string(),
+            title: paragraph.lines().next().unwrap_or_default().chars().take(60).collect(),
+        })
+        .collect()
+}
+
+/// Spoken audio in the TTS panel, newest first, to replay or delete
+fn render_tts_history(ui: &mut egui::Ui, service: &TTSService) {
+    let entries = service.history().entries();
+    egui::CollapsingHeader::new(format!("History ({})", entries.len()))
+        .id_salt("tts_history")
+        .show(ui, |ui| {
+            if entries.is_empty() {
+                ui.label("Nothing spoken yet");
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                for entry in &entries {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("▶").on_hover_text("Play again").clicked() {
+                            let service = service.clone();
+                            let id = entry.id;
+                            tokio::spawn(async move {
+                                if let Err(e) = service.replay(id).await {
+                                    log::error!("TTS replay error: {}", e);
+                                }
+                            });
+                        }
+                        if ui.small_button("🗑").on_hover_text("Delete the audio").clicked() {
+                            if let Err(e) = service.history().remove(entry.id) {
+                                log::error!("Failed to delete TTS history entry: {}", e);
+                            }
+                        }
+                        let created = entry.created.with_timezone(&chrono::Local);
+                        ui.label(
+                            egui::RichText::new(format!("{} {:.0}s", created.format("%m-%d %H:%M"), entry.duration_secs))
+                                .small()
+                                .weak(),
+                        );
+                        ui.add(egui::Label::new(entry.text.as_str()).truncate())
+                            .on_hover_text(format!("{}\n\nVoice: {} at {}x", entry.text, entry.voice_id, entry.speed));
+                    });
+                }
+            });
+        });
+}
+
 impl eframe::App for DashboardApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Commit the messages still buffered before the process goes
+        if let Some(writer) = self.message_cache.take() {
+            log::info!(
+                "Cached {} messages in {} batches ({} failed)",
+                writer.written(),
+                writer.batches(),
+                writer.failed()
+            );
+            writer.shutdown();
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.debug_overlay.record(ctx, frame.info().cpu_usage);
+        if self.chat_stress {
+            ctx.request_repaint();
+        }
+        self.handle_shortcuts(ctx);
+
         // Check test mode exit conditions
         if let Some(ref test_mode) = self.test_mode {
             if test_mode.lock().unwrap().should_exit() {
@@ -497,69 +2398,207 @@ impl eframe::App for DashboardApp {
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                ui.heading("Agent Dashboard");
+                ui.heading(tr!("app-title"));
                 ui.separator();
 
                 // Tab selector
-                ui.selectable_value(&mut self.current_tab, AppTab::Home, "Home");
-                ui.selectable_value(&mut self.current_tab, AppTab::Term, "Term");
+                ui.selectable_value(&mut self.current_tab, AppTab::Home, tr!("tab-home"));
+                if ui
+                    .selectable_value(&mut self.current_tab, AppTab::Chats, tr!("tab-chats"))
+                    .clicked()
+                {
+                    self.chats_tab.refresh();
+                }
+                ui.selectable_value(&mut self.current_tab, AppTab::Term, tr!("tab-term"));
 
                 ui.separator();
 
                 // Show buttons only on Home tab
                 if self.current_tab == AppTab::Home {
-                    if ui.button("Config").clicked() {
+                    if ui.button(tr!("button-config")).clicked() {
                         self.show_config_panel = !self.show_config_panel;
                     }
 
-                    if ui.button("Plans").clicked() {
+                    if ui.button(tr!("button-plans")).clicked() {
                         self.show_plan_panel = !self.show_plan_panel;
                     }
 
-                    if ui.button("TTS").clicked() {
+                    if ui.button(tr!("button-tts")).clicked() {
                         self.show_tts_panel = !self.show_tts_panel;
                     }
+
+                    if ui.button(tr!("button-scripts")).clicked() {
+                        self.show_scripts_panel = !self.show_scripts_panel;
+                    }
+                }
+
+                let update_available = self.updater.status.as_ref().is_some_and(|status| status.update_available);
+                let label = if update_available { tr!("button-update-available") } else { tr!("button-updates") };
+                if ui.button(label).on_hover_text(tr!("updates-hover")).clicked() {
+                    self.show_update_window = !self.show_update_window;
+                }
+
+                if ui.button(tr!("button-logs")).clicked() {
+                    self.show_logs_panel = !self.show_logs_panel;
+                }
+
+                if ui.button(tr!("button-blackboard")).clicked() {
+                    self.show_blackboard = !self.show_blackboard;
+                }
+
+                if ui.button(tr!("button-assistant")).clicked() {
+                    self.show_assistant = !self.show_assistant;
+                }
+
+                if ui.button(tr!("button-triage")).on_hover_text(tr!("triage-hover")).clicked() {
+                    self.toggle_triage();
+                }
+
+                if ui.button(tr!("button-identities")).on_hover_text(tr!("identities-hover")).clicked() {
+                    self.show_identities = !self.show_identities;
+                    if self.show_identities && !self.identities.is_loaded() {
+                        self.identities.load();
+                    }
                 }
             });
         });
 
+        self.check_reminders();
+        self.check_pipeline_events();
+        self.check_forgotten();
+        self.check_announcements();
+        self.check_assistant_sends();
+        self.check_clipboard();
+        self.check_source_events();
+        self.check_agent_output();
+        self.check_tts_export();
+        self.check_tool_reruns();
+        self.check_voice_commands(ctx);
+        self.check_resources();
+        self.save_session_if_due();
+        self.check_updates();
+        let workspaces = self.agent_workspaces();
+        self.workspaces.refresh_if_due(workspaces, ctx);
+        // Background work wakes the UI itself; this is only a fallback
+        ctx.request_repaint_after(Duration::from_secs(30));
+
+        super::status_bar::show_status_bar(ctx, self);
+        self.render_clipboard_bar(ctx);
+
         // Show content based on current tab
         match self.current_tab {
             AppTab::Home => self.render_home_tab(ctx),
+            AppTab::Chats => {
+                match super::chats_tab::render_chats_tab(
+                    ctx,
+                    &mut self.chats_tab,
+                    &self.config.announce,
+                    &self.config.assistant,
+                ) {
+                    Some(ChatsTabAction::Announce { chat, enabled }) => self.set_announce(ctx, &chat, enabled),
+                    Some(ChatsTabAction::Assist { chat, enabled }) => self.set_assist_chat(ctx, &chat, enabled),
+                    Some(ChatsTabAction::Speak(text)) => self.speak(text, Priority::User),
+                    Some(ChatsTabAction::ExportAudio(summary)) => {
+                        self.export_audio(ctx, "catch-up", summary_segments(&summary))
+                    }
+                    Some(ChatsTabAction::Tail) => self.update_message_watcher(ctx),
+                    None => {}
+                }
+            }
             AppTab::Term => self.render_term_tab(ctx),
         }
+
+        super::onboarding::show_onboarding(ctx, self);
+        super::session::show_recovery_prompt(ctx, self);
+        super::crash::show_crash_prompt(ctx, self);
+        if self.show_update_window {
+            super::updates::show_update_window(ctx, self);
+        }
+        if self.show_logs_panel {
+            super::logs_panel::show_logs_panel(ctx, self);
+        }
+        if self.show_blackboard {
+            super::blackboard_panel::show_blackboard_panel(ctx, self);
+        }
+        if self.show_assistant {
+            super::assistant::show_assistant_window(ctx, self);
+        }
+        if self.show_triage {
+            super::triage::show_triage_window(ctx, self);
+        }
+        if self.show_identities {
+            super::identities::show_identities_window(ctx, self);
+        }
+        super::approval::show_approval_prompt(ctx, self);
+        super::telegram_login::show_reauth_dialog(ctx, self);
+        if self.show_shortcuts {
+            super::shortcuts::show_help(ctx, &mut self.show_shortcuts);
+        }
+        self.toasts.show(ctx);
+        self.debug_overlay.show(ctx);
     }
 }
 
 impl DashboardApp {
     fn render_home_tab(&mut self, ctx: &egui::Context) {
+        // Chat is visible, so everything has been seen
+        self.unread_count = 0;
+
+        let mut workspace_action = None;
+        let workspaces: HashMap<AgentId, std::path::PathBuf> = self.agent_workspaces().into_iter().collect();
+
         egui::SidePanel::left("agents_panel")
             .resizable(true)
             .default_width(200.0)
             .show(ctx, |ui| {
-                ui.heading("Active Agents");
+                ui.heading(tr!("agents-heading"));
                 ui.separator();
 
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     if self.active_agents.is_empty() {
-                        ui.label("No active agents");
+                        ui.label(tr!("agents-none"));
                     } else {
                         for (agent_id, agent_name) in &self.active_agents {
                             let is_selected = self.selected_agent.as_ref() == Some(agent_id);
-                            if ui
-                                .selectable_label(is_selected, agent_name)
-                                .clicked()
-                            {
+                            let (label, detail) = match self.agent_states.get(agent_id) {
+                                Some((AgentState::Busy, detail)) => (format!("⏳ {}", agent_name), detail.clone()),
+                                Some((AgentState::Error, detail)) => (format!("⚠ {}", agent_name), detail.clone()),
+                                _ => (agent_name.clone(), None),
+                            };
+                            let (label, detail) = match self.agent_sandboxes.get(agent_id) {
+                                Some(sandbox) => {
+                                    let note = tr!("agent-sandboxed", sandbox = sandbox.as_str());
+                                    let detail = detail.map_or_else(|| note.clone(), |detail| format!("{}\n{}", detail, note));
+                                    (format!("🔒 {}", label), Some(detail))
+                                }
+                                None => (label, detail),
+                            };
+                            let response = ui.selectable_label(is_selected, label);
+                            let response = match detail {
+                                Some(detail) => response.on_hover_text(detail),
+                                None => response,
+                            };
+                            if response.clicked() {
                                 self.selected_agent = Some(*agent_id);
                                 self.broadcast_mode = false;
                             }
+                            if let Some(dir) = workspaces.get(agent_id) {
+                                let status = self.workspaces.status(agent_id);
+                                if let Some(action) = super::workspace::agent_workspace_row(ui, &response, dir, status) {
+                                    workspace_action = Some((*agent_id, dir.clone(), action));
+                                }
+                            }
+                            if let Some(monitor) = &self.monitor {
+                                let limit = self.config.monitor.memory_limit_mb;
+                                super::resources::usage_row(ui, monitor, Target::Agent(*agent_id), limit);
+                            }
                         }
                     }
 
                     ui.separator();
 
                     if ui
-                        .selectable_label(self.broadcast_mode, "📢 Broadcast")
+                        .selectable_label(self.broadcast_mode, tr!("agents-broadcast"))
                         .clicked()
                     {
                         self.broadcast_mode = true;
@@ -568,31 +2607,21 @@ impl DashboardApp {
                 });
             });
 
+        if let Some((agent, dir, action)) = workspace_action {
+            super::workspace::handle_action(ctx, self, agent, dir, action);
+        }
+        super::workspace::show_workspace_windows(ctx, self);
+
         if self.show_config_panel {
             super::config_panel::show_config_panel(ctx, self);
         }
 
-        if self.show_plan_panel {
-            egui::SidePanel::right("plans_panel")
-                .resizable(true)
-                .default_width(300.0)
-                .show(ctx, |ui| {
-                    ui.heading("Plans");
-                    ui.separator();
+        if self.show_scripts_panel {
+            super::scripts_panel::show_scripts_panel(ctx, self);
+        }
 
-                    egui::ScrollArea::vertical().show(ui, |ui| {
-                        if self.plans.is_empty() {
-                            ui.label("No active plans");
-                        } else {
-                            for plan in &self.plans {
-                                ui.group(|ui| {
-                                    ui.label(&plan.title);
-                                    ui.label(format!("Steps: {}", plan.steps.len()));
-                                });
-                            }
-                        }
-                    });
-                });
+        if self.show_plan_panel {
+            super::plans_panel::show_plans_panel(ctx, self);
         }
 
         if self.show_tts_panel {
@@ -615,18 +2644,67 @@ impl DashboardApp {
 
                         ui.separator();
 
+                        ui.label("Output Device:");
+                        let devices = self.tts_devices.get_or_insert_with(crate::tts::playback::output_devices);
+                        let before = self.tts_config.audio_device.clone();
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_id_salt("tts_output_device")
+                                .selected_text(self.tts_config.audio_device.as_deref().unwrap_or("System default"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.tts_config.audio_device, None, "System default");
+                                    for device in devices.iter() {
+                                        ui.selectable_value(&mut self.tts_config.audio_device, Some(device.clone()), device);
+                                    }
+                                });
+                            if ui.button("⟳").on_hover_text("List devices again").clicked() {
+                                *devices = crate::tts::playback::output_devices();
+                            }
+                        });
+                        if devices.is_empty() {
+                            ui.label("No devices listed; playing on a device needs the tts feature");
+                        } else if let Some(device) = self.tts_config.audio_device.as_ref().filter(|d| !devices.contains(d)) {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(255, 165, 0),
+                                format!("{} is not connected; playing on the default until it is", device),
+                            );
+                        }
+                        if self.tts_config.audio_device != before {
+                            self.save_tts_config();
+                            // Switch the running service over, keeping what is queued
+                            if let Some(ref service) = self.tts_service {
+                                let service = service.clone();
+                                let device = self.tts_config.audio_device.clone();
+                                tokio::spawn(async move {
+                                    let _ = service.set_device(device).await;
+                                });
+                            }
+                        }
+
+                        ui.separator();
+
                         ui.label("Voice Model:");
                         ui.text_edit_singleline(&mut self.tts_config.selected_voice);
 
                         ui.separator();
 
+                        ui.label("Keep spoken audio (0 = no limit):");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut self.tts_config.history_max_age_days).suffix(" days"));
+                            ui.add(egui::DragValue::new(&mut self.tts_config.history_max_size_mb).suffix(" MB"));
+                        });
+
+                        ui.separator();
+
                         if ui.button("Apply Settings").clicked() {
                             self.tts_config.validate();
                             // Save config
                             self.save_tts_config();
                             // Restart TTS service with new config
-                            if let Ok(service) = TTSService::start(self.tts_config.clone()) {
-                                self.tts_service = Some(service);
+                            match TTSService::start(self.tts_config.clone(), ctx.clone()) {
+                                Ok(service) => self.tts_service = Some(service),
+                                Err(e) => {
+                                    self.last_error = Some(format!("Failed to start TTS: {}", e));
+                                }
                             }
                         }
 
@@ -647,10 +2725,28 @@ impl DashboardApp {
                                     let _ = service.clear_queue().await;
                                 });
                             }
+
+                            ui.separator();
+                            render_tts_history(ui, service);
                         } else {
                             ui.separator();
                             ui.label("TTS Service: Stopped");
                         }
+
+                        ui.separator();
+                        if self.tts_export.is_some() {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Exporting audio...");
+                            });
+                        } else if ui
+                            .button("Export conversation audio")
+                            .on_hover_text("Save the chat with the selected agent, or all agents when broadcasting, as one narrated WAV file with a chapter per message")
+                            .clicked()
+                        {
+                            let segments = self.conversation_segments();
+                            self.export_audio(ctx, "conversation", segments);
+                        }
                     } else {
                         if let Some(ref service) = self.tts_service {
                             let service_clone = service.clone();
@@ -674,58 +2770,103 @@ impl DashboardApp {
             ui.vertical(|ui| {
                 let available_height = ui.available_height();
 
-                egui::ScrollArea::vertical()
-                    .auto_shrink([false, false])
-                    .stick_to_bottom(true)
-                    .max_height(available_height - 80.0)
-                    .show(ui, |ui| {
-                        super::chat::render_chat_messages(ui, &self.chat_messages, &mut self.speak_message_id);
-                    });
+                // The chat message being read out, if any, to highlight as it plays
+                let tts_status = self.tts_service.as_ref().map(|service| service.status());
+                let spoken = tts_status.as_ref().and_then(|status| {
+                    let current = status.current.as_ref()?;
+                    let message = self.chat_messages.iter().find(|m| m.id.as_uuid() == current.message_id)?;
+                    Some(SpokenMessage {
+                        id: message.id,
+                        fraction: status.progress.fraction(),
+                    })
+                });
 
-                // Handle speak requests
-                if let Some(msg_id) = self.speak_message_id.take() {
-                    if let Some(message) = self.chat_messages.iter().find(|m| m.id == msg_id) {
-                        if let Some(ref service) = self.tts_service {
-                            let request = TTSRequest::new(
-                                message.content.clone(),
-                                self.tts_config.selected_voice.clone(),
-                                self.tts_config.playback_speed,
-                            );
+                let branchable: HashSet<AgentId> = self
+                    .agent_processes
+                    .iter()
+                    .filter(|(_, process)| process.can_branch())
+                    .map(|(id, _)| *id)
+                    .collect();
+                let shown = self.branches.visible(&self.chat_messages);
+                let action = self.chat_log.show(
+                    ui,
+                    &shown,
+                    available_height - 80.0,
+                    spoken.as_ref(),
+                    BranchView { branches: &self.branches, branchable: &branchable },
+                    &self.config.grouping,
+                );
+
+                match action {
+                    Some(ChatLogAction::Speak(id)) => {
+                        if let Some(message) = self.chat_messages.iter().find(|m| m.id == id) {
+                            // Asked for with the speak button, so ahead of auto-spoken messages
+                            self.speak_message(message, Priority::User);
+                        }
+                    }
+                    // Clicked a sentence of the message being spoken
+                    Some(ChatLogAction::Seek(fraction)) => {
+                        if let (Some(service), Some(status)) = (&self.tts_service, &tts_status) {
+                            let position = status.progress.duration.mul_f32(fraction);
                             let service = service.clone();
                             tokio::spawn(async move {
-                                if let Err(e) = service.speak(request).await {
-                                    log::error!("TTS speak error: {}", e);
+                                if let Err(e) = service.seek(position).await {
+                                    log::error!("TTS seek error: {}", e);
                                 }
                             });
                         }
                     }
+                    Some(ChatLogAction::Fork { message, text }) => self.fork_conversation(message, text),
+                    Some(ChatLogAction::Regenerate(reply)) => self.regenerate_reply(reply),
+                    Some(ChatLogAction::SwitchBranch { agent, branch }) => self.switch_branch(agent, branch),
+                    Some(ChatLogAction::RerunTool(message)) => self.rerun_tool(ui.ctx(), message),
+                    None => {}
                 }
 
                 ui.separator();
 
                 ui.horizontal(|ui| {
-                    ui.label("To:");
+                    ui.label(tr!("chat-to"));
                     if self.broadcast_mode {
-                        ui.label("All agents");
+                        ui.label(tr!("chat-to-all"));
                     } else if let Some(agent_id) = self.selected_agent {
                         if let Some(name) = self.active_agents.get(&agent_id) {
                             ui.label(name);
                         } else {
-                            ui.label("None");
+                            ui.label(tr!("chat-to-none"));
                         }
                     } else {
-                        ui.label("None (select an agent)");
+                        ui.label(tr!("chat-to-select"));
+                    }
+
+                    let agent = self.selected_agent.and_then(|id| self.active_agents.get(&id)).cloned();
+                    let values = [("agent", agent.unwrap_or_default())];
+                    if let Some(text) = super::snippets::snippet_menu(ui, &mut self.snippets, &values) {
+                        if !self.chat_input.is_empty() && !self.chat_input.ends_with(char::is_whitespace) {
+                            self.chat_input.push(' ');
+                        }
+                        self.chat_input.push_str(&text);
+                        self.focus_input = true;
                     }
                 });
 
                 ui.horizontal(|ui| {
+                    // Right-to-left input lines up on the right, like the messages
+                    let align = match i18n::direction(&self.chat_input) {
+                        i18n::Direction::RightToLeft => egui::Align::RIGHT,
+                        i18n::Direction::LeftToRight => egui::Align::LEFT,
+                    };
                     let text_edit = egui::TextEdit::multiline(&mut self.chat_input)
                         .desired_width(f32::INFINITY)
-                        .desired_rows(2);
+                        .desired_rows(2)
+                        .horizontal_align(align);
 
-                    ui.add(text_edit);
+                    let response = ui.add(text_edit);
+                    if std::mem::take(&mut self.focus_input) {
+                        response.request_focus();
+                    }
 
-                    if ui.button("Send").clicked()
+                    if ui.button(tr!("chat-send")).clicked()
                         || (ui.input(|i| {
                             i.key_pressed(egui::Key::Enter)
                                 && !i.modifiers.shift
@@ -741,12 +2882,15 @@ impl DashboardApp {
     fn render_term_tab(&mut self, ctx: &egui::Context) {
         // Spawn terminal process if not already running
         if self.terminal_stdin_tx.is_none() {
-            self.spawn_terminal();
+            self.spawn_terminal(ctx);
         }
 
         // Poll for output updates
         if let Some(ref mut output_rx) = self.terminal_stdout_rx {
+            let terminal_gap = self.config.grouping.terminal_gap();
             while let Ok(line) = output_rx.try_recv() {
+                self.terminal_output.note_output(Instant::now(), terminal_gap);
+
                 // Log to test mode if enabled
                 if let Some(ref test_mode) = self.test_mode {
                     match &line {
@@ -760,7 +2904,16 @@ impl DashboardApp {
                     }
                 }
 
-                self.terminal_output.push(line);
+                match line {
+                    OutputLine::Styled(segments) => {
+                        for segment in segments {
+                            self.terminal_output.push(&segment.text, segment.style.fg_color.to_egui_color());
+                        }
+                    }
+                    OutputLine::Stderr(text) => {
+                        self.terminal_output.push(&text, egui::Color32::from_rgb(255, 80, 80));
+                    }
+                }
             }
         }
 
@@ -769,6 +2922,9 @@ impl DashboardApp {
                 // Startup command field at the top
                 ui.horizontal(|ui| {
                     ui.label("Startup Command:");
+                    if let Some(sandbox) = &self.terminal_sandbox {
+                        ui.label("🔒").on_hover_text(tr!("agent-sandboxed", sandbox = sandbox.as_str()));
+                    }
                     let cmd_response = ui.add(
                         egui::TextEdit::singleline(&mut self.terminal_startup_command)
                             .desired_width(f32::INFINITY)
@@ -777,73 +2933,85 @@ impl DashboardApp {
 
                     // Reset terminal when Enter is pressed on startup command
                     if cmd_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                        self.reset_terminal();
+                        self.reset_terminal(ctx);
                         cmd_response.request_focus();
                     }
                 });
+                if self.config.grouping.terminal_by_command {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Collapse all").clicked() {
+                            self.terminal_output_view.collapse_all(&self.terminal_output);
+                        }
+                        if ui.small_button("Expand all").clicked() {
+                            self.terminal_output_view.expand_all();
+                        }
+                    });
+                }
+                if let Some(monitor) = &self.monitor {
+                    super::resources::usage_row(ui, monitor, Target::Terminal, self.config.monitor.memory_limit_mb);
+                }
 
                 ui.separator();
 
                 // Calculate available height for output and input
                 let spacing = ui.spacing().item_spacing.y;
                 let available_height = ui.available_height();
-                let input_height = 80.0; // Height for input area
+                // Height for input area, with room for history search results when open
+                let input_height = if self.terminal_history_search.is_some() { 300.0 } else { 80.0 };
                 let separator_height = spacing * 2.0;
                 let output_height = available_height - input_height - separator_height;
 
                 // Terminal output area - fills remaining vertical space
-                egui::ScrollArea::vertical()
-                    .auto_shrink([false, false])
-                    .stick_to_bottom(true)
-                    .max_height(output_height)
-                    .show(ui, |ui| {
-                        ui.style_mut().override_font_id = Some(egui::FontId::monospace(14.0));
-
-                        // Build a LayoutJob to combine all styled segments without extra newlines
-                        let mut layout_job = egui::text::LayoutJob::default();
-
-                        for output_line in &self.terminal_output {
-                            match output_line {
-                                OutputLine::Styled(segments) => {
-                                    for segment in segments {
-                                        let fg_color = segment.style.fg_color.to_egui_color();
-
-                                        let format = egui::TextFormat {
-                                            font_id: egui::FontId::monospace(14.0),
-                                            color: fg_color,
-                                            ..Default::default()
-                                        };
-
-                                        layout_job.append(&segment.text, 0.0, format);
-                                    }
-                                }
-                                OutputLine::Stderr(text) => {
-                                    let format = egui::TextFormat {
-                                        font_id: egui::FontId::monospace(14.0),
-                                        color: egui::Color32::from_rgb(255, 80, 80),
-                                        ..Default::default()
-                                    };
-                                    layout_job.append(text, 0.0, format);
-                                }
-                            }
-                        }
-
-                        // Render as a single label with styled text
-                        ui.label(layout_job);
-                    });
+                self.terminal_output_view.show(
+                    ui,
+                    &self.terminal_output,
+                    &super::appearance::terminal_font(&self.config.appearance),
+                    output_height,
+                    self.config.grouping.terminal_by_command,
+                );
 
                 ui.separator();
 
                 // Terminal input at the bottom - multiline with Enter to send
                 ui.vertical(|ui| {
-                    ui.label("Input (Shift+Enter for newline, Enter to send):");
+                    ui.label("Input (Shift+Enter for newline, Enter to send, Up/Down for history, Ctrl+R to search it):");
+
+                    let input_id = ui.make_persistent_id("terminal_input");
+                    if let Some(line) = super::terminal::render_history_search(
+                        ui,
+                        &mut self.terminal_history_search,
+                        &self.terminal_history,
+                    ) {
+                        self.terminal_input = line;
+                        ui.memory_mut(|m| m.request_focus(input_id));
+                        super::terminal::move_cursor_to_end(ui.ctx(), input_id, &self.terminal_input);
+                    }
+                    super::terminal::handle_history_keys(
+                        ui,
+                        input_id,
+                        &mut self.terminal_history,
+                        &mut self.terminal_input,
+                        &mut self.terminal_history_search,
+                    );
+                    if let Some((text, at)) = super::terminal::render_paste_confirm(ui.ctx(), &mut self.terminal_pending_paste) {
+                        paste::insert_at(&mut self.terminal_input, at, &text);
+                        ui.memory_mut(|m| m.request_focus(input_id));
+                        super::terminal::move_cursor_to_end(ui.ctx(), input_id, &self.terminal_input);
+                    }
+                    if self.terminal_pending_paste.is_none() {
+                        self.terminal_pending_paste = super::terminal::intercept_paste(ui, input_id, &self.config.terminal);
+                    }
 
                     let text_edit = egui::TextEdit::multiline(&mut self.terminal_input)
+                        .id(input_id)
                         .desired_width(f32::INFINITY)
                         .desired_rows(2)
-                        .font(egui::TextStyle::Monospace);
+                        .font(super::appearance::terminal_font(&self.config.appearance));
 
                     let response = ui.add(text_edit);
+                    if std::mem::take(&mut self.focus_input) {
+                        response.request_focus();
+                    }
 
                     // Check if Enter was pressed without Shift
                     if response.has_focus() {
@@ -854,10 +3022,28 @@ impl DashboardApp {
                         if enter_pressed {
                             // Send command to terminal stdin
                             if !self.terminal_input.trim().is_empty() {
+                                if self.config.grouping.terminal_by_command {
+                                    self.terminal_output.start_command(&self.terminal_input);
+                                }
                                 if let Some(ref stdin_tx) = self.terminal_stdin_tx {
-                                    let command = format!("{}\n", self.terminal_input);
+                                    // Several lines go as one bracketed paste when the program
+                                    // supports it, rather than running line by line
+                                    let body = self.terminal_input.trim_end_matches(['\n', '\r']);
+                                    let command = if body.contains('\n')
+                                        && self.config.terminal.bracketed_paste
+                                        && self.terminal_bracketed_paste.load(Ordering::Relaxed)
+                                    {
+                                        format!("{}\n", paste::bracket(body))
+                                    } else {
+                                        format!("{}\n", self.terminal_input)
+                                    };
                                     let _ = stdin_tx.send(command);
                                 }
+                                if self.terminal_history.record(&self.terminal_input, &self.config.terminal) {
+                                    if let Err(e) = self.terminal_history.save() {
+                                        log::warn!("Failed to save terminal history: {}", e);
+                                    }
+                                }
                             } else {
                                 // Send just newline for empty input
                                 if let Some(ref stdin_tx) = self.terminal_stdin_tx {
@@ -872,7 +3058,21 @@ impl DashboardApp {
             });
         });
 
-        // Request continuous repaint to update terminal output
-        ctx.request_repaint();
+        // Output wakes the UI from the reader thread; this catches what doesn't, like
+        // the spawn failing
+        ctx.request_repaint_after(Duration::from_secs(1));
+    }
+}
+
+impl Drop for DashboardApp {
+    /// A normal close leaves nothing to recover. A panic unwinding through here, or a
+    /// recovery prompt not yet answered, keeps the snapshot for the next launch.
+    fn drop(&mut self) {
+        if std::thread::panicking() || self.session_recovery.is_some() {
+            return;
+        }
+        if let Err(e) = session::snapshot_path().and_then(|path| session::clear(&path)) {
+            log::warn!("Failed to remove the session snapshot: {}", e);
+        }
     }
 }
@@ -0,0 +1,77 @@
+use crate::config::AppearanceConfig;
+use egui::{Color32, FontData, FontDefinitions, FontFamily, FontId, Stroke, Theme};
+
+/// Font family of the Term tab: the configured font, then the built-in monospace ones
+const TERMINAL_FAMILY: &str = "terminal";
+
+/// Apply fonts, text sizes and contrast to both the dark and the light theme
+pub fn apply(ctx: &egui::Context, config: &AppearanceConfig) {
+    ctx.set_fonts(font_definitions(config));
+    for theme in [Theme::Dark, Theme::Light] {
+        let mut style = theme.default_style();
+        for font_id in style.text_styles.values_mut() {
+            font_id.size *= config.scale();
+        }
+        if config.high_contrast {
+            high_contrast(&mut style.visuals, theme);
+        }
+        ctx.set_style_of(theme, style);
+    }
+}
+
+/// Font for Term tab output and input
+pub fn terminal_font(config: &AppearanceConfig) -> FontId {
+    FontId::new(config.terminal_size(), FontFamily::Name(TERMINAL_FAMILY.into()))
+}
+
+fn font_definitions(config: &AppearanceConfig) -> FontDefinitions {
+    let mut fonts = FontDefinitions::default();
+    let mut family = fonts.families.get(&FontFamily::Monospace).cloned().unwrap_or_default();
+    if let Some(path) = &config.terminal_font {
+        match std::fs::read(path) {
+            // egui panics on data it can't parse, so only hand it what looks like a font
+            Ok(data) if is_font(&data) => {
+                fonts.font_data.insert(TERMINAL_FAMILY.to_string(), FontData::from_owned(data));
+                family.insert(0, TERMINAL_FAMILY.to_string());
+            }
+            Ok(_) => log::warn!("{} is not a TrueType or OpenType font", path.display()),
+            Err(e) => log::warn!("Failed to read the terminal font {}: {}", path.display(), e),
+        }
+    }
+    fonts.families.insert(FontFamily::Name(TERMINAL_FAMILY.into()), family);
+    fonts
+}
+
+fn is_font(data: &[u8]) -> bool {
+    matches!(data.get(..4), Some([0, 1, 0, 0] | b"OTTO" | b"true" | b"ttcf"))
+}
+
+/// Plain backgrounds, text in full black or white and outlines thick enough to spot
+/// which widget has keyboard focus
+fn high_contrast(visuals: &mut egui::Visuals, theme: Theme) {
+    let (background, text, accent) = match theme {
+        Theme::Dark => (Color32::BLACK, Color32::WHITE, Color32::from_rgb(255, 215, 0)),
+        Theme::Light => (Color32::WHITE, Color32::BLACK, Color32::from_rgb(0, 60, 200)),
+    };
+
+    visuals.override_text_color = Some(text);
+    visuals.panel_fill = background;
+    visuals.window_fill = background;
+    visuals.faint_bg_color = background;
+    visuals.extreme_bg_color = background;
+    visuals.window_stroke = Stroke::new(2.0, text);
+    visuals.hyperlink_color = accent;
+    visuals.selection.bg_fill = accent;
+    visuals.selection.stroke = Stroke::new(2.0, text);
+
+    let widgets = &mut visuals.widgets;
+    for state in [&mut widgets.noninteractive, &mut widgets.inactive, &mut widgets.open] {
+        state.fg_stroke = Stroke::new(1.5, text);
+        state.bg_stroke = Stroke::new(1.0, text);
+    }
+    widgets.noninteractive.bg_fill = background;
+    for state in [&mut widgets.hovered, &mut widgets.active] {
+        state.fg_stroke = Stroke::new(2.0, text);
+        state.bg_stroke = Stroke::new(2.0, accent);
+    }
+}
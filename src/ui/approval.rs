@@ -0,0 +1,163 @@
+use crate::agent::AgentId;
+use crate::approval::{ApprovalConfig, ApprovalGate, ApprovalPolicy, Decision};
+use crate::ui::app::DashboardApp;
+use std::time::Duration;
+
+/// Prompt for the oldest shell command waiting for approval
+pub fn show_approval_prompt(ctx: &egui::Context, app: &mut DashboardApp) {
+    let Some((id, agent, agent_name, command, cwd, remaining)) = app.approvals.with_oldest(|p| {
+        (p.id, p.agent, p.agent_name.clone(), p.command.clone(), p.cwd.clone(), p.remaining())
+    }) else {
+        return;
+    };
+    let waiting = app.approvals.pending_count();
+
+    let mut answer = None;
+    let mut always_allow = false;
+    egui::Window::new("Run shell command?")
+        .id(egui::Id::new("approval_prompt"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            ui.label(format!("{} wants to run:", agent_name));
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.add(egui::Label::new(egui::RichText::new(&command).monospace()).wrap());
+            });
+            ui.label(
+                egui::RichText::new(format!("in {}", cwd.as_deref().unwrap_or("the agent's current directory")))
+                    .monospace()
+                    .size(11.0),
+            );
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new(format!("Denied automatically in {}s", remaining.as_secs()))
+                    .color(egui::Color32::GRAY),
+            );
+            if waiting > 1 {
+                ui.label(egui::RichText::new(format!("{} more waiting", waiting - 1)).color(egui::Color32::GRAY));
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Approve").clicked() {
+                    answer = Some(true);
+                }
+                if ui.button("Deny").clicked() {
+                    answer = Some(false);
+                }
+                if ui
+                    .button("Always allow")
+                    .on_hover_text(format!("Add this exact command to {}'s allowlist", agent_name))
+                    .clicked()
+                {
+                    answer = Some(true);
+                    always_allow = true;
+                }
+            });
+        });
+
+    if always_allow {
+        allow_command(&mut app.config.approvals, agent, &command);
+        app.approvals.set_config(app.config.approvals.clone());
+        if let Err(e) = app.config.save() {
+            log::error!("Failed to save approval policy: {}", e);
+        }
+    }
+    match answer {
+        Some(approve) => app.approvals.answer(id, approve),
+        // Keep the countdown moving
+        None => ctx.request_repaint_after(Duration::from_secs(1)),
+    }
+}
+
+/// Add `command` to an agent's allowlist, switching it to an allowlist if it always asked
+fn allow_command(config: &mut ApprovalConfig, agent: AgentId, command: &str) {
+    let policy = config.policies.entry(agent).or_default();
+    match policy {
+        ApprovalPolicy::Allowlist { patterns } => patterns.push(command.trim().to_string()),
+        ApprovalPolicy::AlwaysAsk => {
+            *policy = ApprovalPolicy::Allowlist { patterns: vec![command.trim().to_string()] };
+        }
+        ApprovalPolicy::FullAuto => {}
+    }
+}
+
+/// Policy selector and allowlist patterns for one agent; returns whether they changed
+pub fn approval_policy_settings(ui: &mut egui::Ui, agent: AgentId, config: &mut ApprovalConfig) -> bool {
+    let mut policy = config.policy(&agent);
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label("Shell commands:");
+        let selected = match policy {
+            ApprovalPolicy::AlwaysAsk => "Always ask",
+            ApprovalPolicy::Allowlist { .. } => "Allowlist",
+            ApprovalPolicy::FullAuto => "Full auto",
+        };
+        egui::ComboBox::from_id_salt(("approval_policy", agent))
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                let options = [
+                    ApprovalPolicy::AlwaysAsk,
+                    ApprovalPolicy::Allowlist { patterns: Vec::new() },
+                    ApprovalPolicy::FullAuto,
+                ];
+                for (option, label) in options.into_iter().zip(["Always ask", "Allowlist", "Full auto"]) {
+                    let is_selected = std::mem::discriminant(&option) == std::mem::discriminant(&policy);
+                    if ui.selectable_label(is_selected, label).clicked() && !is_selected {
+                        policy = option;
+                        changed = true;
+                    }
+                }
+            });
+    });
+
+    if let ApprovalPolicy::Allowlist { patterns } = &mut policy {
+        let mut text = patterns.join("\n");
+        if ui
+            .add(
+                egui::TextEdit::multiline(&mut text)
+                    .hint_text("One pattern per line, e.g. cargo *")
+                    .desired_rows(3)
+                    .code_editor(),
+            )
+            .on_hover_text("`*` matches anything. Commands that chain, pipe or redirect always ask.")
+            .changed()
+        {
+            *patterns = text.lines().map(str::to_string).collect();
+            changed = true;
+        }
+    }
+
+    if changed {
+        config.policies.insert(agent, policy);
+    }
+    changed
+}
+
+/// Recent entries of the approval audit log
+pub fn audit_log(ui: &mut egui::Ui, gate: &ApprovalGate) {
+    let entries = gate.recent_entries(20);
+    if entries.is_empty() {
+        ui.label("No shell commands yet");
+        return;
+    }
+
+    egui::Grid::new("approval_audit_log").striped(true).show(ui, |ui| {
+        for entry in entries {
+            let (label, color) = match entry.decision {
+                Decision::AutoApproved => ("auto", egui::Color32::GRAY),
+                Decision::Approved => ("approved", egui::Color32::GREEN),
+                Decision::Denied => ("denied", egui::Color32::RED),
+                Decision::TimedOut => ("timed out", egui::Color32::RED),
+            };
+            ui.label(entry.at.with_timezone(&chrono::Local).format("%m-%d %H:%M:%S").to_string());
+            ui.label(&entry.agent);
+            ui.colored_label(color, label);
+            ui.label(egui::RichText::new(&entry.command).monospace())
+                .on_hover_text(entry.cwd.unwrap_or_default());
+            ui.end_row();
+        }
+    });
+}
@@ -0,0 +1,159 @@
+use crate::assistant::AuditEvent;
+use crate::i18n::tr;
+use crate::ui::app::DashboardApp;
+
+/// Audit log entries shown at the bottom of the window
+const RECENT_ENTRIES: usize = 20;
+
+enum AssistantAction {
+    Enable(bool),
+    Agent(Option<crate::agent::AgentId>),
+    StopAnswering(String),
+    Send(u64),
+    Discard(u64),
+}
+
+pub fn show_assistant_window(ctx: &egui::Context, app: &mut DashboardApp) {
+    if app.assistant.is_sending() {
+        ctx.request_repaint_after(std::time::Duration::from_millis(200));
+    }
+
+    let mut open = app.show_assistant;
+    let mut action = None;
+    let config = app.config.assistant.clone();
+    let agents: Vec<_> = app.config.agents.iter().map(|agent| (agent.id, agent.name.clone())).collect();
+    let recent = app.assistant.recent_entries(RECENT_ENTRIES);
+
+    egui::Window::new(tr!("button-assistant"))
+        .id(egui::Id::new("assistant_window"))
+        .open(&mut open)
+        .default_width(600.0)
+        .default_height(500.0)
+        .show(ctx, |ui| {
+            let mut enabled = config.enabled;
+            if ui
+                .checkbox(&mut enabled, tr!("assistant-enabled"))
+                .on_hover_text(tr!("assistant-enabled-hint"))
+                .changed()
+            {
+                action = Some(AssistantAction::Enable(enabled));
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(tr!("assistant-agent"));
+                let selected = config
+                    .agent
+                    .and_then(|id| agents.iter().find(|(agent, _)| *agent == id))
+                    .map(|(_, name)| name.clone())
+                    .unwrap_or_else(|| tr!("assistant-no-agent"));
+                let mut agent = config.agent;
+                egui::ComboBox::from_id_salt("assistant_agent").selected_text(selected).show_ui(ui, |ui| {
+                    ui.selectable_value(&mut agent, None, tr!("assistant-no-agent"));
+                    for (id, name) in &agents {
+                        ui.selectable_value(&mut agent, Some(*id), name);
+                    }
+                });
+                if agent != config.agent {
+                    action = Some(AssistantAction::Agent(agent));
+                }
+            });
+            if config.agent.is_some_and(|agent| !app.agent_processes.contains_key(&agent)) {
+                ui.colored_label(egui::Color32::YELLOW, tr!("assistant-agent-stopped"));
+            }
+
+            ui.separator();
+            ui.label(egui::RichText::new(tr!("tab-chats")).strong());
+            if config.chats.is_empty() {
+                ui.label(
+                    egui::RichText::new(tr!("assistant-no-chats"))
+                        .color(egui::Color32::GRAY),
+                );
+            }
+            for chat in &config.chats {
+                ui.horizontal(|ui| {
+                    ui.label(chat);
+                    if ui.small_button(tr!("assistant-stop")).clicked() {
+                        action = Some(AssistantAction::StopAnswering(chat.clone()));
+                    }
+                });
+            }
+
+            ui.separator();
+            ui.label(egui::RichText::new(tr!("assistant-outbox")).strong());
+            if app.assistant.outbox.is_empty() {
+                ui.label(egui::RichText::new(tr!("assistant-outbox-empty")).color(egui::Color32::GRAY));
+            }
+            egui::ScrollArea::vertical().id_salt("assistant_outbox").max_height(250.0).show(ui, |ui| {
+                for reply in &mut app.assistant.outbox {
+                    ui.group(|ui| {
+                        ui.label(
+                            egui::RichText::new(tr!(
+                                "assistant-incoming",
+                                sender = reply.sender.clone(),
+                                chat = reply.chat.clone(),
+                                text = reply.incoming.clone()
+                            ))
+                                .color(egui::Color32::GRAY),
+                        );
+                        ui.add(egui::TextEdit::multiline(&mut reply.text).desired_rows(2).desired_width(f32::INFINITY));
+                        ui.horizontal(|ui| {
+                            let send = ui
+                                .add_enabled(config.enabled, egui::Button::new(tr!("assistant-send")))
+                                .on_disabled_hover_text(tr!("assistant-off"));
+                            if send.clicked() {
+                                action = Some(AssistantAction::Send(reply.id));
+                            }
+                            if ui.button(tr!("assistant-discard")).clicked() {
+                                action = Some(AssistantAction::Discard(reply.id));
+                            }
+                            ui.label(egui::RichText::new(tr!("assistant-from", agent = reply.agent_name.clone())).color(egui::Color32::GRAY));
+                        });
+                    });
+                }
+            });
+
+            ui.separator();
+            ui.label(egui::RichText::new(tr!("assistant-audit")).strong());
+            egui::ScrollArea::vertical().id_salt("assistant_audit").show(ui, |ui| {
+                for entry in &recent {
+                    let (what, text) = match &entry.event {
+                        AuditEvent::Routed { sender, text, .. } => {
+                            (tr!("assistant-audit-routed", sender = sender.clone()), text)
+                        }
+                        AuditEvent::Drafted { text } => (tr!("assistant-audit-drafted"), text),
+                        AuditEvent::Sent { text, edited: true, .. } => (tr!("assistant-audit-sent-edited"), text),
+                        AuditEvent::Sent { text, .. } => (tr!("assistant-audit-sent"), text),
+                        AuditEvent::Failed { text, error } => (tr!("assistant-audit-failed", error = error.clone()), text),
+                        AuditEvent::Discarded { text } => (tr!("assistant-audit-discarded"), text),
+                    };
+                    ui.label(format!(
+                        "{} {} {} {}: {}",
+                        entry.at.with_timezone(&chrono::Local).format("%m-%d %H:%M"),
+                        entry.chat,
+                        entry.agent,
+                        what,
+                        text
+                    ));
+                }
+            });
+        });
+    app.show_assistant = open;
+
+    match action {
+        Some(AssistantAction::Enable(enabled)) => app.set_assistant_enabled(ctx, enabled),
+        Some(AssistantAction::Agent(agent)) => {
+            app.config.assistant.agent = agent;
+            // Replies already asked of the old agent are no longer expected
+            app.assistant.stop();
+            app.save_assistant_config(ctx);
+        }
+        Some(AssistantAction::StopAnswering(chat)) => app.set_assist_chat(ctx, &chat, false),
+        Some(AssistantAction::Send(id)) => {
+            if let Err(e) = app.assistant.approve(&app.config.assistant, id) {
+                app.toasts.warning(e);
+            }
+        }
+        Some(AssistantAction::Discard(id)) => app.assistant.discard(id),
+        None => {}
+    }
+}
@@ -0,0 +1,150 @@
+use crate::i18n::tr;
+use crate::blackboard::DEFAULT_NAMESPACE;
+use crate::ui::app::DashboardApp;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest value shown in a row; the rest is in its tooltip
+const PREVIEW_CHARS: usize = 120;
+
+/// State of the Blackboard window
+#[derive(Default)]
+pub struct BlackboardPanelState {
+    /// Namespace shown; every one when `None`
+    pub namespace: Option<String>,
+    /// The entry being written from the window
+    new_namespace: String,
+    new_key: String,
+    new_value: String,
+    new_ttl: String,
+}
+
+fn preview(value: &Value) -> String {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    match text.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+pub fn show_blackboard_panel(ctx: &egui::Context, app: &mut DashboardApp) {
+    // Expiry counts down, and agents write at any time
+    ctx.request_repaint_after(Duration::from_secs(1));
+
+    let shared = Arc::clone(&app.blackboard);
+    let Ok(mut board) = shared.lock() else {
+        return;
+    };
+    let now = chrono::Utc::now();
+    board.purge_expired(now);
+
+    let state = &mut app.blackboard_panel;
+    let mut error = None;
+    egui::Window::new(tr!("button-blackboard"))
+        .id(egui::Id::new("blackboard_window"))
+        .open(&mut app.show_blackboard)
+        .default_width(700.0)
+        .default_height(400.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("blackboard-namespace"));
+                egui::ComboBox::from_id_salt("blackboard_namespace")
+                    .selected_text(state.namespace.clone().unwrap_or_else(|| tr!("blackboard-all")))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut state.namespace, None, tr!("blackboard-all"));
+                        for namespace in board.namespaces(now) {
+                            ui.selectable_value(&mut state.namespace, Some(namespace.to_string()), namespace);
+                        }
+                    });
+                let clear = match &state.namespace {
+                    Some(namespace) => tr!("blackboard-clear", namespace = namespace.clone()),
+                    None => tr!("blackboard-clear-all"),
+                };
+                if ui.button(clear).clicked() {
+                    board.clear(state.namespace.as_deref());
+                }
+            });
+            ui.separator();
+
+            let mut delete = None;
+            let entries = board.list(state.namespace.as_deref(), "", now);
+            if entries.is_empty() {
+                ui.label(
+                    egui::RichText::new(tr!("blackboard-empty"))
+                        .color(egui::Color32::GRAY),
+                );
+            }
+            egui::ScrollArea::vertical().max_height(ui.available_height() - 80.0).show(ui, |ui| {
+                egui::Grid::new("blackboard_entries").num_columns(5).striped(true).show(ui, |ui| {
+                    for entry in &entries {
+                        ui.label(egui::RichText::new(format!("{}/{}", entry.namespace, entry.key)).monospace());
+                        let full = serde_json::to_string_pretty(&entry.value).unwrap_or_default();
+                        ui.label(preview(&entry.value)).on_hover_text(&full);
+                        let updated = entry.updated_at.with_timezone(&chrono::Local).format("%H:%M:%S");
+                        ui.label(egui::RichText::new(updated.to_string()).size(11.0).color(egui::Color32::GRAY));
+                        match entry.expires_at {
+                            Some(at) => ui.label(tr!("blackboard-expires", seconds = (at - now).num_seconds().max(0))),
+                            None => ui.label(""),
+                        };
+                        ui.horizontal(|ui| {
+                            if ui.small_button("📋").on_hover_text(tr!("blackboard-copy")).clicked() {
+                                ctx.copy_text(full.clone());
+                            }
+                            if ui.small_button("🗑").on_hover_text(tr!("blackboard-delete")).clicked() {
+                                delete = Some((entry.namespace.clone(), entry.key.clone()));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+            if let Some((namespace, key)) = delete {
+                board.remove(&namespace, &key);
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut state.new_namespace).hint_text(DEFAULT_NAMESPACE).desired_width(90.0));
+                ui.add(egui::TextEdit::singleline(&mut state.new_key).hint_text(tr!("blackboard-key")).desired_width(120.0));
+                ui.add(egui::TextEdit::singleline(&mut state.new_value).hint_text(tr!("blackboard-value")).desired_width(220.0));
+                ui.add(egui::TextEdit::singleline(&mut state.new_ttl).hint_text(tr!("blackboard-ttl")).desired_width(50.0))
+                    .on_hover_text(tr!("blackboard-ttl-hint"));
+                if ui.button(tr!("blackboard-set")).clicked() {
+                    let namespace = Some(state.new_namespace.trim()).filter(|namespace| !namespace.is_empty());
+                    let value = serde_json::from_str(&state.new_value).unwrap_or_else(|_| Value::String(state.new_value.clone()));
+                    let ttl = state.new_ttl.trim().parse().ok().map(Duration::from_secs);
+                    match board.set(namespace.unwrap_or(DEFAULT_NAMESPACE), &state.new_key, value, ttl, now) {
+                        Ok(()) => {
+                            state.new_key.clear();
+                            state.new_value.clear();
+                        }
+                        Err(e) => error = Some(e.to_string()),
+                    }
+                }
+            });
+        });
+
+    drop(board);
+    if let Some(error) = error {
+        app.toasts.warning(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview(&json!("plain text")), "plain text");
+        assert_eq!(preview(&json!(["a", 1])), "[\"a\",1]");
+        let long = preview(&json!("é".repeat(PREVIEW_CHARS + 5)));
+        assert_eq!(long.chars().count(), PREVIEW_CHARS + 1);
+        assert!(long.ends_with('…'));
+    }
+}
@@ -1,7 +1,17 @@
+use crate::agent::branch::{BranchId, Branches};
 use crate::agent::AgentId;
+use crate::config::GroupingConfig;
+use crate::i18n::{self, tr};
+use crate::toolcall::types::ToolcallResult;
+use crate::tts::synthesis::{sentence_ranges, WordTimeline};
 use chrono::{DateTime, Utc};
+use egui::text::LayoutJob;
+use egui::{FontId, Galley};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -11,6 +21,10 @@ impl MessageId {
     pub fn new() -> Self {
         Self(Uuid::new_v4())
     }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
 }
 
 impl Default for MessageId {
@@ -32,21 +46,67 @@ pub enum MessageDirection {
     Broadcast,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MessageMetadata {
     pub is_toolcall: bool,
     pub is_error: bool,
     pub error_message: Option<String>,
+    /// The call, for messages about a tool an agent ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call: Option<ToolCall>,
 }
 
-impl Default for MessageMetadata {
-    fn default() -> Self {
-        Self {
-            is_toolcall: false,
-            is_error: false,
-            error_message: None,
-        }
+/// A tool an agent ran, and what came of it once it's done
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// The agent's id for the call, which its result comes back under
+    pub call_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+    /// Output, or the error if the tool failed; `None` until it's done
+    #[serde(default)]
+    pub result: Option<String>,
+    #[serde(default)]
+    pub success: Option<bool>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+impl ToolCall {
+    pub fn new(call_id: String, name: String, arguments: serde_json::Value) -> Self {
+        Self { call_id, name, arguments, result: None, success: None, duration_ms: None }
     }
+
+    pub fn is_done(&self) -> bool {
+        self.success.is_some()
+    }
+
+    /// Record the tool's result, `duration` after it was called
+    pub fn finish(&mut self, result: &ToolcallResult, duration: std::time::Duration) {
+        let text = match &result.error {
+            Some(error) if result.output.is_empty() => error.clone(),
+            Some(error) => format!("{}\n{}", result.output, error),
+            None => result.output.clone(),
+        };
+        self.result = Some(text);
+        self.success = Some(result.success && result.error.is_none());
+        self.duration_ms = Some(duration.as_millis().min(u64::MAX as u128) as u64);
+    }
+
+    /// One line for the log and for searching: the tool and its arguments
+    pub fn summary(&self) -> String {
+        format!("🔧 {}({})", self.name, self.arguments)
+    }
+}
+
+/// `text` pretty-printed if it's JSON, as it is otherwise
+pub fn pretty_json(text: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .filter(|value| value.is_object() || value.is_array())
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| text.to_string())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +117,9 @@ pub struct ChatMessage {
     pub timestamp: DateTime<Utc>,
     pub direction: MessageDirection,
     pub metadata: MessageMetadata,
+    /// Branch of the agent's conversation it's in; `None` for the original conversation
+    #[serde(default)]
+    pub branch: Option<BranchId>,
 }
 
 impl ChatMessage {
@@ -74,10 +137,36 @@ impl ChatMessage {
             timestamp: Utc::now(),
             direction,
             metadata: MessageMetadata::default(),
+            branch: None,
+        }
+    }
+
+    /// A notification from the dashboard itself (e.g. a fired reminder)
+    pub fn new_notification(content: String) -> Self {
+        Self {
+            id: MessageId::new(),
+            agent_id: None,
+            content,
+            timestamp: Utc::now(),
+            direction: MessageDirection::FromAgent,
+            metadata: MessageMetadata::default(),
+            branch: None,
+        }
+    }
+
+    /// An agent calling a tool, finished by [`ToolCall::finish`] when the result arrives
+    pub fn new_tool_call(agent_id: AgentId, call: ToolCall) -> Self {
+        Self {
+            id: MessageId::new(),
+            agent_id: Some(agent_id),
+            content: call.summary(),
+            timestamp: Utc::now(),
+            direction: MessageDirection::FromAgent,
+            metadata: MessageMetadata { is_toolcall: true, tool_call: Some(call), ..Default::default() },
+            branch: None,
         }
     }
 
-    #[allow(dead_code)]
     pub fn new_agent_message(agent_id: AgentId, content: String) -> Self {
         Self {
             id: MessageId::new(),
@@ -86,77 +175,634 @@ impl ChatMessage {
             timestamp: Utc::now(),
             direction: MessageDirection::FromAgent,
             metadata: MessageMetadata::default(),
+            branch: None,
         }
     }
 }
 
-pub fn render_chat_messages(
-    ui: &mut egui::Ui,
-    messages: &[ChatMessage],
-    on_speak: &mut Option<MessageId>,
-) {
-    for message in messages {
-        ui.group(|ui| {
-            ui.horizontal(|ui| {
-                let time_str = message.timestamp.format("%H:%M:%S").to_string();
-                ui.label(
-                    egui::RichText::new(&time_str)
-                        .size(10.0)
-                        .color(egui::Color32::GRAY),
-                );
-
-                match message.direction {
-                    MessageDirection::FromAgent => {
-                        if let Some(agent_id) = message.agent_id {
-                            ui.label(
-                                egui::RichText::new(format!("[Agent: {}]", agent_id))
-                                    .strong()
-                                    .color(egui::Color32::from_rgb(100, 150, 255)),
-                            );
-                        }
+/// A chat message being read out, and how far
+pub struct SpokenMessage {
+    pub id: MessageId,
+    /// Share of its audio played
+    pub fraction: f32,
+}
+
+/// Background of the word being spoken
+const SPOKEN_WORD: egui::Color32 = egui::Color32::from_rgb(255, 215, 0);
+/// Background of the rest of its sentence
+const SPOKEN_SENTENCE: egui::Color32 = egui::Color32::from_rgba_premultiplied(60, 52, 0, 60);
+
+/// Height assumed for a message that hasn't been drawn yet
+const ESTIMATED_HEIGHT: f32 = 56.0;
+/// Messages kept laid out on either side of the visible ones, so scrolling a little
+/// reuses them
+const CACHE_MARGIN: usize = 50;
+
+/// Parts of a message that don't change once it's in the log, laid out when it first
+/// comes into view
+struct MessageLayout {
+    time: String,
+    /// Text, and the error under it, wrapped to `wrap_width`
+    body: Arc<Galley>,
+    wrap_width: f32,
+}
+
+fn layout_message(ui: &egui::Ui, message: &ChatMessage, wrap_width: f32) -> MessageLayout {
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let format = |color| egui::TextFormat { font_id: font_id.clone(), color, ..Default::default() };
+
+    let mut job = LayoutJob::default();
+    job.wrap.max_width = wrap_width;
+    if message.metadata.is_error {
+        job.append(&message.content, 0.0, format(egui::Color32::RED));
+        if let Some(err_msg) = &message.metadata.error_message {
+            job.append("\n", 0.0, format(egui::Color32::RED));
+            job.append(err_msg, 0.0, format(egui::Color32::DARK_RED));
+        }
+    } else if message.metadata.is_toolcall {
+        job.append(&message.content, 0.0, format(egui::Color32::from_rgb(200, 200, 100)));
+    } else {
+        // Drawn in the theme's text color
+        job.append(&message.content, 0.0, format(egui::Color32::PLACEHOLDER));
+    }
+
+    MessageLayout {
+        time: message.timestamp.format("%H:%M:%S").to_string(),
+        body: ui.fonts(|fonts| fonts.layout_job(job)),
+        wrap_width,
+    }
+}
+
+/// What the user asked for in the chat log
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatLogAction {
+    Speak(MessageId),
+    /// Play the message being spoken from this share of its audio
+    Seek(f32),
+    /// Branch the conversation, sending `text` in place of `message`
+    Fork { message: MessageId, text: String },
+    /// Ask again for the agent's reply `message`, in a new branch
+    Regenerate(MessageId),
+    /// Show another branch of an agent's conversation; `None` for the original
+    SwitchBranch { agent: AgentId, branch: Option<BranchId> },
+    /// Run the tool call `message` again with the same arguments
+    RerunTool(MessageId),
+}
+
+/// The branches of the conversations in the log, and the agents whose conversations can branch
+#[derive(Clone, Copy)]
+pub struct BranchView<'a> {
+    pub branches: &'a Branches,
+    pub branchable: &'a HashSet<AgentId>,
+}
+
+/// Consecutive messages from the same sender, each at most `window` after the one
+/// before, as ranges of `messages`. Without a window every message is on its own.
+pub fn group_messages(messages: &[&ChatMessage], window: Option<chrono::Duration>) -> Vec<Range<usize>> {
+    let mut groups: Vec<Range<usize>> = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        let joins = index > 0
+            && window.is_some_and(|window| {
+                let previous = messages[index - 1];
+                same_sender(previous, message) && message.timestamp - previous.timestamp <= window
+            });
+        match groups.last_mut() {
+            Some(group) if joins => group.end = index + 1,
+            _ => groups.push(index..index + 1),
+        }
+    }
+    groups
+}
+
+fn same_sender(a: &ChatMessage, b: &ChatMessage) -> bool {
+    a.direction == b.direction && a.agent_id == b.agent_id && a.branch == b.branch
+}
+
+/// The chat log, drawing only the message blocks in view (see [`group_messages`]).
+/// Blocks are measured as they're drawn; those never drawn count as `ESTIMATED_HEIGHT`
+/// towards the scroll height. A message keeps its layout while it's near the view, and
+/// a block its height, by its first message, for good.
+#[derive(Default)]
+pub struct ChatLogView {
+    heights: HashMap<MessageId, f32>,
+    layouts: HashMap<MessageId, MessageLayout>,
+    /// What the cached heights and layouts were made for
+    font_id: Option<FontId>,
+    pixels_per_point: f32,
+    /// Message being edited to send again, and its new text
+    editing: Option<(MessageId, String)>,
+    /// Blocks expanded or collapsed against the default, by their first message
+    toggled: HashSet<MessageId>,
+}
+
+impl ChatLogView {
+    /// Draw `messages`, returning what the user clicked, if anything
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        messages: &[&ChatMessage],
+        max_height: f32,
+        spoken: Option<&SpokenMessage>,
+        branching: BranchView,
+        grouping: &GroupingConfig,
+    ) -> Option<ChatLogAction> {
+        let font_id = egui::TextStyle::Body.resolve(ui.style());
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        if self.font_id.as_ref() != Some(&font_id) || self.pixels_per_point != pixels_per_point {
+            self.heights.clear();
+            self.layouts.clear();
+            self.font_id = Some(font_id);
+            self.pixels_per_point = pixels_per_point;
+        }
+
+        let groups = group_messages(messages, grouping.chat_window());
+        let mut action = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .max_height(max_height)
+            .show_viewport(ui, |ui, viewport| {
+                let height =
+                    |group: &Range<usize>| self.heights.get(&messages[group.start].id).copied().unwrap_or(ESTIMATED_HEIGHT);
+
+                // The blocks overlapping the view, and the space above them
+                let mut y = 0.0;
+                let mut first = None;
+                let mut end = groups.len();
+                for (index, group) in groups.iter().enumerate() {
+                    let bottom = y + height(group);
+                    if first.is_none() && bottom >= viewport.min.y {
+                        first = Some((index, y));
+                    }
+                    if y > viewport.max.y {
+                        end = index;
+                        break;
                     }
-                    MessageDirection::ToAgent => {
+                    y = bottom;
+                }
+                let (first, above) = first.unwrap_or((groups.len(), y));
+                let below: f32 = groups[end.max(first)..].iter().map(height).sum();
+
+                ui.add_space(above);
+                for group in &groups[first..end.max(first)] {
+                    let members = &messages[group.clone()];
+                    let leader = members[0].id;
+                    let collapsed = members.len() > 1 && grouping.collapse_chat_groups != self.toggled.contains(&leader);
+                    let top = ui.cursor().min.y;
+                    let (clicked, toggle) =
+                        render_group(ui, members, collapsed, &mut self.layouts, spoken, branching, &mut self.editing);
+                    action = action.take().or(clicked);
+                    if toggle && !self.toggled.remove(&leader) {
+                        self.toggled.insert(leader);
+                    }
+                    ui.add_space(4.0);
+                    self.heights.insert(leader, ui.cursor().min.y - top);
+                }
+                ui.add_space(below);
+
+                let keep_groups = &groups[first.saturating_sub(CACHE_MARGIN)..(end + CACHE_MARGIN).min(groups.len())];
+                let keep: HashSet<MessageId> = match (keep_groups.first(), keep_groups.last()) {
+                    (Some(start), Some(last)) => messages[start.start..last.end].iter().map(|message| message.id).collect(),
+                    _ => HashSet::new(),
+                };
+                self.layouts.retain(|id, _| keep.contains(id));
+            });
+        action
+    }
+}
+
+/// A block of messages from one sender: the first with its full header, the others
+/// with just their time and buttons. A collapsed block shows only its first message.
+/// Returns what was clicked, and whether the block was expanded or collapsed.
+fn render_group(
+    ui: &mut egui::Ui,
+    members: &[&ChatMessage],
+    collapsed: bool,
+    layouts: &mut HashMap<MessageId, MessageLayout>,
+    spoken: Option<&SpokenMessage>,
+    branching: BranchView,
+    editing: &mut Option<(MessageId, String)>,
+) -> (Option<ChatLogAction>, bool) {
+    let mut action = None;
+    let mut toggle = false;
+    ui.group(|ui| {
+        let shown = if collapsed { &members[..1] } else { members };
+        for (index, message) in shown.iter().enumerate() {
+            if index > 0 {
+                ui.add_space(2.0);
+            }
+            let mut layout = layouts.remove(&message.id);
+            let clicked = render_message(ui, message, &mut layout, index == 0, spoken, branching, editing);
+            action = action.take().or(clicked);
+            if let Some(layout) = layout {
+                layouts.insert(message.id, layout);
+            }
+        }
+
+        if members.len() > 1 {
+            let label = if collapsed {
+                tr!("chat-group-expand", count = members.len() - 1)
+            } else {
+                tr!("chat-group-collapse")
+            };
+            toggle = ui
+                .add(egui::Label::new(egui::RichText::new(label).size(10.0).color(egui::Color32::GRAY)).sense(egui::Sense::click()))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .clicked();
+        }
+    });
+    (action, toggle)
+}
+
+/// One message: a header with time, sender (unless it continues a block) and buttons,
+/// and the text. `layout` is made or remade as needed; the message being spoken is
+/// drawn without it. In conversations that can branch, messages to the agent can be
+/// edited and replies asked for again, and where there are several branches they can
+/// be switched between.
+fn render_message(
+    ui: &mut egui::Ui,
+    message: &ChatMessage,
+    layout: &mut Option<MessageLayout>,
+    with_sender: bool,
+    spoken: Option<&SpokenMessage>,
+    branching: BranchView,
+    editing: &mut Option<(MessageId, String)>,
+) -> Option<ChatLogAction> {
+    let mut action = None;
+    ui.vertical(|ui| {
+        let wrap_width = ui.available_width();
+        let current = layout.take().filter(|layout| (layout.wrap_width - wrap_width).abs() < 0.5);
+        let current = layout.insert(current.unwrap_or_else(|| layout_message(ui, message, wrap_width)));
+
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(&current.time)
+                    .size(10.0)
+                    .color(egui::Color32::GRAY),
+            );
+
+            match message.direction {
+                _ if !with_sender => {}
+                MessageDirection::FromAgent => {
+                    if let Some(agent_id) = message.agent_id {
                         ui.label(
-                            egui::RichText::new("[You]")
+                            egui::RichText::new(tr!("chat-agent", agent = agent_id.to_string()))
                                 .strong()
-                                .color(egui::Color32::from_rgb(100, 200, 100)),
+                                .color(egui::Color32::from_rgb(100, 150, 255)),
                         );
-                        if let Some(agent_id) = message.agent_id {
-                            ui.label(
-                                egui::RichText::new(format!("→ {}", agent_id))
-                                    .size(10.0)
-                                    .color(egui::Color32::GRAY),
-                            );
-                        }
-                    }
-                    MessageDirection::Broadcast => {
+                    } else {
                         ui.label(
-                            egui::RichText::new("[You → All]")
+                            egui::RichText::new(tr!("chat-notice"))
                                 .strong()
-                                .color(egui::Color32::from_rgb(200, 150, 100)),
+                                .color(egui::Color32::from_rgb(229, 229, 16)),
                         );
                     }
                 }
+                MessageDirection::ToAgent => {
+                    ui.label(
+                        egui::RichText::new(tr!("chat-you"))
+                            .strong()
+                            .color(egui::Color32::from_rgb(100, 200, 100)),
+                    );
+                    if let Some(agent_id) = message.agent_id {
+                        ui.label(
+                            egui::RichText::new(format!("→ {}", agent_id))
+                                .size(10.0)
+                                .color(egui::Color32::GRAY),
+                        );
+                    }
+                }
+                MessageDirection::Broadcast => {
+                    ui.label(
+                        egui::RichText::new(tr!("chat-you-all"))
+                            .strong()
+                            .color(egui::Color32::from_rgb(200, 150, 100)),
+                    );
+                }
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("🔊").on_hover_text(tr!("chat-speak")).clicked() {
+                    action = Some(ChatLogAction::Speak(message.id));
+                }
+                // Calls never answered ran inside the agent, where they can't be repeated
+                if message.metadata.tool_call.as_ref().is_some_and(ToolCall::is_done)
+                    && ui.small_button("↻").on_hover_text(tr!("chat-tool-rerun")).clicked()
+                {
+                    action = Some(ChatLogAction::RerunTool(message.id));
+                }
 
-                // Add TTS speak button
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    if ui.small_button("🔊").on_hover_text("Speak this message").clicked() {
-                        *on_speak = Some(message.id);
+                let Some(agent) = message.agent_id.filter(|agent| branching.branchable.contains(agent)) else {
+                    return;
+                };
+                if !message.metadata.is_error && !message.metadata.is_toolcall {
+                    match message.direction {
+                        MessageDirection::ToAgent => {
+                            if ui.small_button("✏").on_hover_text(tr!("chat-edit")).clicked() {
+                                *editing = Some((message.id, message.content.clone()));
+                            }
+                        }
+                        MessageDirection::FromAgent => {
+                            if ui.small_button("🔄").on_hover_text(tr!("chat-regenerate")).clicked() {
+                                action = Some(ChatLogAction::Regenerate(message.id));
+                            }
+                        }
+                        MessageDirection::Broadcast => {}
                     }
-                });
+                }
+
+                // Right to left, so ▶ comes first
+                if let Some(alternatives) = branching.branches.alternatives(message) {
+                    let count = alternatives.options.len();
+                    let current = alternatives.current;
+                    let mut switch_to = None;
+                    if ui.add_enabled(current + 1 < count, egui::Button::new("▶").small()).clicked() {
+                        switch_to = Some(current + 1);
+                    }
+                    ui.label(egui::RichText::new(format!("{}/{}", current + 1, count)).size(10.0))
+                        .on_hover_text(tr!("chat-branches"));
+                    if ui.add_enabled(current > 0, egui::Button::new("◀").small()).clicked() {
+                        switch_to = Some(current - 1);
+                    }
+                    if let Some(index) = switch_to {
+                        action = Some(ChatLogAction::SwitchBranch { agent, branch: alternatives.options[index] });
+                    }
+                }
             });
+        });
 
-            if message.metadata.is_error {
-                ui.colored_label(egui::Color32::RED, &message.content);
-                if let Some(err_msg) = &message.metadata.error_message {
-                    ui.colored_label(egui::Color32::DARK_RED, err_msg);
+        let plain = !message.metadata.is_error && !message.metadata.is_toolcall;
+        if let Some((_, text)) = editing.as_mut().filter(|(id, _)| *id == message.id) {
+            ui.add(egui::TextEdit::multiline(text).desired_width(f32::INFINITY).desired_rows(2));
+            let mut done = false;
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!text.trim().is_empty(), egui::Button::new(tr!("chat-edit-send"))).clicked() {
+                    action = Some(ChatLogAction::Fork { message: message.id, text: text.trim().to_string() });
+                    done = true;
+                }
+                if ui.button(tr!("chat-edit-cancel")).clicked() {
+                    done = true;
+                }
+            });
+            if done {
+                *editing = None;
+            }
+        } else if let Some(spoken) = spoken.filter(|spoken| plain && spoken.id == message.id) {
+            if let Some(fraction) = render_spoken(ui, &message.content, spoken.fraction) {
+                action = Some(ChatLogAction::Seek(fraction));
+            }
+        } else if let Some(call) = &message.metadata.tool_call {
+            render_tool_call(ui, message.id, call);
+        } else if plain && i18n::direction(&message.content) == i18n::Direction::RightToLeft {
+            // Arabic, Hebrew, ... read from the right, so line them up there
+            ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                ui.label(current.body.clone());
+            });
+        } else {
+            ui.label(current.body.clone());
+        }
+    });
+    action
+}
+
+/// A tool call as a card: the tool, how it went and how long it took, expanding to
+/// its arguments and result
+fn render_tool_call(ui: &mut egui::Ui, id: MessageId, call: &ToolCall) {
+    let (status, color) = match call.success {
+        None => ("…", egui::Color32::GRAY),
+        Some(true) => ("✔", egui::Color32::from_rgb(100, 200, 100)),
+        Some(false) => ("✘", egui::Color32::from_rgb(230, 90, 90)),
+    };
+    let mut title = egui::text::LayoutJob::default();
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let format = |color| egui::TextFormat { font_id: font_id.clone(), color, ..Default::default() };
+    title.append(&format!("🔧 {} ", call.name), 0.0, format(egui::Color32::from_rgb(200, 200, 100)));
+    title.append(status, 0.0, format(color));
+    if let Some(ms) = call.duration_ms {
+        title.append(&format!("  {}", format_duration_ms(ms)), 0.0, format(egui::Color32::GRAY));
+    }
+
+    egui::CollapsingHeader::new(title)
+        .id_salt(("tool-call", id))
+        .show(ui, |ui| {
+            ui.label(egui::RichText::new(tr!("chat-tool-arguments")).size(10.0).color(egui::Color32::GRAY));
+            let arguments = serde_json::to_string_pretty(&call.arguments).unwrap_or_default();
+            ui.add(egui::Label::new(egui::RichText::new(arguments).monospace()).wrap());
+
+            ui.label(egui::RichText::new(tr!("chat-tool-result")).size(10.0).color(egui::Color32::GRAY));
+            match &call.result {
+                Some(result) => {
+                    let text = egui::RichText::new(pretty_json(result)).monospace();
+                    let text = if call.success == Some(false) { text.color(egui::Color32::from_rgb(230, 90, 90)) } else { text };
+                    ui.add(egui::Label::new(text).wrap());
+                }
+                None => {
+                    ui.label(egui::RichText::new(tr!("chat-tool-no-result")).italics().color(egui::Color32::GRAY));
                 }
-            } else if message.metadata.is_toolcall {
-                ui.colored_label(egui::Color32::from_rgb(200, 200, 100), &message.content);
-            } else {
-                ui.label(&message.content);
             }
         });
-        ui.add_space(4.0);
+}
+
+fn format_duration_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{} ms", ms)
+    } else {
+        format!("{:.1} s", ms as f64 / 1000.0)
+    }
+}
+
+/// A long made-up conversation, for `--stress-chat` and the frame time benchmark
+pub fn stress_messages(count: usize) -> Vec<ChatMessage> {
+    const LINES: [&str; 5] = [
+        "Done.",
+        "I've updated the parser so nested lists keep their indentation when they're copied.",
+        "Running the test suite now; the integration tests take a couple of minutes on this machine, so I'll report back once they finish.",
+        "Here's the plan:\n1. Reproduce the crash\n2. Bisect the commits since the last release\n3. Write a regression test",
+        "Can you look at the flaky upload test?",
+    ];
+    let agent = AgentId::new();
+    let start = Utc::now() - chrono::Duration::seconds(count as i64);
+    (0..count)
+        .map(|n| {
+            let content = format!("{} ({})", LINES[n % LINES.len()], n);
+            let mut message = if n % 2 == 0 {
+                ChatMessage::new_user_message(content, Some(agent))
+            } else {
+                ChatMessage::new_agent_message(agent, content)
+            };
+            message.timestamp = start + chrono::Duration::seconds(n as i64);
+            message.metadata.is_toolcall = n % 17 == 0;
+            message
+        })
+        .collect()
+}
+
+/// Message text with the word being spoken and its sentence highlighted, karaoke style.
+/// Clicking a sentence returns the share of the audio where it starts, to play from there.
+fn render_spoken(ui: &mut egui::Ui, content: &str, fraction: f32) -> Option<f32> {
+    let timeline = WordTimeline::new(content);
+    let sentences = sentence_ranges(content);
+    let word = timeline.word_at(fraction);
+    let sentence = word
+        .as_ref()
+        .and_then(|word| sentences.iter().find(|sentence| sentence.contains(&word.start)))
+        .cloned();
+
+    // Cut the text where the highlighting changes
+    let mut cuts = vec![0, content.len()];
+    for range in word.iter().chain(sentence.iter()) {
+        cuts.extend([range.start, range.end]);
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let within = |range: &Option<std::ops::Range<usize>>, start: usize| range.as_ref().is_some_and(|r| r.contains(&start));
+    let mut job = egui::text::LayoutJob::default();
+    job.wrap.max_width = ui.available_width();
+    for cut in cuts.windows(2) {
+        let (color, background) = if within(&word, cut[0]) {
+            (egui::Color32::BLACK, SPOKEN_WORD)
+        } else if within(&sentence, cut[0]) {
+            (text_color, SPOKEN_SENTENCE)
+        } else {
+            (text_color, egui::Color32::TRANSPARENT)
+        };
+        let format = egui::TextFormat {
+            font_id: font_id.clone(),
+            color,
+            background,
+            ..Default::default()
+        };
+        job.append(&content[cut[0]..cut[1]], 0.0, format);
+    }
+
+    let galley = ui.fonts(|fonts| fonts.layout_job(job));
+    let response = ui
+        .add(egui::Label::new(galley.clone()).selectable(false).sense(egui::Sense::click()))
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+        .on_hover_text("Click a sentence to play from there");
+    if let Some(pos) = response.clicked().then(|| response.interact_pointer_pos()).flatten() {
+        let cursor = galley.cursor_from_pos(pos - response.rect.min);
+        let offset = content
+            .char_indices()
+            .nth(cursor.ccursor.index)
+            .map_or(content.len(), |(index, _)| index);
+        if let Some(clicked) = sentences.iter().find(|sentence| sentence.end > offset) {
+            return Some(timeline.fraction_at(clicked.start));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::debug_overlay::frame_time;
+
+    #[test]
+    fn test_only_visible_messages_are_laid_out() {
+        let messages = stress_messages(1000);
+        let shown: Vec<&ChatMessage> = messages.iter().collect();
+        let (branches, branchable) = (Branches::default(), HashSet::new());
+        let branching = BranchView { branches: &branches, branchable: &branchable };
+        let mut view = ChatLogView::default();
+        frame_time(|ui, _| {
+            view.show(ui, &shown, 600.0, None, branching, &GroupingConfig::default());
+        });
+
+        // Only the first frame's top and the bottom it then sticks to were drawn
+        assert!(!view.layouts.is_empty() && view.layouts.len() < 2 * CACHE_MARGIN);
+        assert!(view.heights.contains_key(&messages.last().unwrap().id));
+        assert!(view.heights.len() < messages.len() / 10);
+    }
+
+    #[test]
+    fn test_group_messages() {
+        let agent = AgentId::new();
+        let start = Utc::now();
+        let at = |message: ChatMessage, secs: i64| ChatMessage { timestamp: start + chrono::Duration::seconds(secs), ..message };
+        let messages = [
+            at(ChatMessage::new_agent_message(agent, "Looking".into()), 0),
+            at(ChatMessage::new_agent_message(agent, "Found it".into()), 30),
+            at(ChatMessage::new_agent_message(agent, "Fixed".into()), 100),
+            at(ChatMessage::new_agent_message(agent, "Much later".into()), 1000),
+            at(ChatMessage::new_user_message("Thanks".into(), Some(agent)), 1001),
+            at(ChatMessage::new_agent_message(AgentId::new(), "Other agent".into()), 1002),
+        ];
+        let shown: Vec<&ChatMessage> = messages.iter().collect();
+
+        let window = Some(chrono::Duration::seconds(120));
+        assert_eq!(group_messages(&shown, window), vec![0..3, 3..4, 4..5, 5..6]);
+        assert_eq!(group_messages(&shown, None).len(), messages.len());
+        assert!(group_messages(&[], window).is_empty());
+    }
+
+    #[test]
+    fn test_tool_call_round_trip() {
+        let agent = AgentId::new();
+        let mut call = ToolCall::new("7".into(), "shell".into(), serde_json::json!({ "command": "ls" }));
+        assert!(!call.is_done());
+        call.finish(
+            &ToolcallResult { success: false, output: "partial".into(), error: Some("exit 2".into()) },
+            std::time::Duration::from_millis(1500),
+        );
+        assert_eq!(call.result.as_deref(), Some("partial\nexit 2"));
+        assert_eq!((call.success, call.duration_ms), (Some(false), Some(1500)));
+        assert_eq!(format_duration_ms(1500), "1.5 s");
+
+        let message = ChatMessage::new_tool_call(agent, call.clone());
+        assert!(message.metadata.is_toolcall);
+        assert_eq!(message.content, r#"🔧 shell({"command":"ls"})"#);
+        let saved: MessageMetadata = serde_json::from_str(&serde_json::to_string(&message.metadata).unwrap()).unwrap();
+        assert_eq!(saved.tool_call, Some(call));
+
+        // Metadata saved before tool calls had structure still loads
+        let old: MessageMetadata =
+            serde_json::from_str(r#"{"is_toolcall":true,"is_error":false,"error_message":null}"#).unwrap();
+        assert!(old.tool_call.is_none());
+
+        assert_eq!(pretty_json(r#"{"a":1}"#), "{\n  \"a\": 1\n}");
+        assert_eq!(pretty_json("plain 42"), "plain 42");
+    }
+
+    /// Home tab frame time with 10k chat messages, drawing every message against
+    /// `ChatLogView`. Run with `cargo test --release bench_chat_frame_time -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_chat_frame_time() {
+        const MESSAGES: usize = 10_000;
+        let messages = stress_messages(MESSAGES);
+        let shown: Vec<&ChatMessage> = messages.iter().collect();
+        let (branches, branchable) = (Branches::default(), HashSet::new());
+        let branching = BranchView { branches: &branches, branchable: &branchable };
+
+        let before = frame_time(|ui, _| {
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for message in &messages {
+                        render_group(ui, &[message], false, &mut HashMap::new(), None, branching, &mut None);
+                        ui.add_space(4.0);
+                    }
+                });
+        });
+
+        let mut view = ChatLogView::default();
+        let after = frame_time(|ui, _| {
+            let height = ui.available_height();
+            view.show(ui, &shown, height, None, branching, &GroupingConfig::default());
+        });
+
+        println!(
+            "{} messages: every message {:.2?}/frame, virtualized {:.2?}/frame ({:.0}x faster)",
+            MESSAGES,
+            before,
+            after,
+            before.as_secs_f64() / after.as_secs_f64()
+        );
+        assert!(after < before, "virtualized rendering is slower: {:.2?} vs {:.2?}", after, before);
     }
 }
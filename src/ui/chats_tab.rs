@@ -0,0 +1,1007 @@
+use crate::assistant::AssistantConfig;
+use crate::config::AnnounceConfig;
+use crate::i18n::{self, tr};
+use crate::snippets::{self, Snippets};
+use crate::ui::notes::{NoteEditor, NoteTarget};
+use crate::sources::forget::ForgetTarget;
+use crate::sources::{message_sender, IncomingMessage};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::mpsc;
+
+/// Messages a tail keeps; older ones scroll out
+const TAIL_CAPACITY: usize = 500;
+
+/// A chat as reported by `chat telegram list --format json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatEntry {
+    pub id: String,
+    pub title: Option<String>,
+}
+
+/// Labels file written by the chat CLI (`~/.config/chat/labels.json`)
+#[derive(Debug, Default, Deserialize)]
+struct ChatLabels {
+    #[serde(default)]
+    chats: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ChatLabels {
+    fn load() -> Self {
+        let Some(path) = dirs::config_dir().map(|d| d.join("chat").join("labels.json")) else {
+            return Self::default();
+        };
+
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn labels_for(&self, source_id: &str, chat_id: &str) -> Vec<String> {
+        self.chats
+            .get(&format!("{}:{}", source_id, chat_id))
+            .map(|labels| labels.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Per-chat analysis as reported by `chat analyze --format json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatAnalysis {
+    pub message_count: usize,
+    pub heatmap: ActivityHeatmap,
+    #[serde(default)]
+    pub response_times: Vec<ResponseStats>,
+    pub sentiment: Option<SentimentSummary>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActivityHeatmap {
+    /// Message counts by weekday (Monday first) and hour
+    pub counts: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResponseStats {
+    pub responder: String,
+    pub to: String,
+    pub count: usize,
+    pub median_secs: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SentimentSummary {
+    pub mean: f32,
+    pub positive: usize,
+    pub neutral: usize,
+    pub negative: usize,
+}
+
+/// A message found by `chat messages --search --format json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchHit {
+    pub chat_id: String,
+    pub sender: SearchSender,
+    /// Message content as serialized by the chat crate (`{"Text": "..."}`, `"Sticker"`, ...)
+    pub content: serde_json::Value,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchSender {
+    pub display_name: Option<String>,
+    pub username: Option<String>,
+}
+
+impl SearchHit {
+    fn sender_name(&self) -> &str {
+        self.sender
+            .display_name
+            .as_deref()
+            .or(self.sender.username.as_deref())
+            .unwrap_or("Unknown")
+    }
+
+    fn text(&self) -> String {
+        content_text(&self.content)
+    }
+}
+
+/// Readable text of serialized message content: the text or caption, else the kind of media
+fn content_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::Object(map) => map
+            .get("Text")
+            .and_then(|text| text.as_str())
+            .map(str::to_string)
+            .or_else(|| {
+                map.values()
+                    .find_map(|media| media.get("caption").and_then(|c| c.as_str()))
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| "[media]".to_string()),
+        other => format!("[{}]", other.as_str().unwrap_or("message").to_lowercase()),
+    }
+}
+
+/// A message shown in a tail
+struct TailMessage {
+    /// Arrival order, used to place the new-message divider
+    seq: u64,
+    timestamp: Option<DateTime<Utc>>,
+    sender: String,
+    /// `source:user_id`, for notes on the sender
+    sender_id: Option<String>,
+    text: String,
+    mentions_me: bool,
+}
+
+/// Live view of one chat, fed by `chat watch` like the CLI's watch command
+pub struct ChatTail {
+    /// `telegram:<chat id>`
+    chat: String,
+    messages: VecDeque<TailMessage>,
+    next_seq: u64,
+    /// Keep scrolled to the newest message; off while the user reads further up
+    following: bool,
+    /// First message that arrived while not following
+    divider: Option<u64>,
+    /// Only messages whose sender or text contains this, ignoring case
+    filter: String,
+    mentions_only: bool,
+}
+
+impl ChatTail {
+    fn new(chat_id: &str) -> Self {
+        Self {
+            chat: format!("telegram:{}", chat_id),
+            messages: VecDeque::new(),
+            next_seq: 0,
+            following: true,
+            divider: None,
+            filter: String::new(),
+            mentions_only: false,
+        }
+    }
+
+    fn push(&mut self, message: &IncomingMessage) {
+        if message.chat_key() != self.chat {
+            return;
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if !self.following && self.divider.is_none() {
+            self.divider = Some(seq);
+        }
+
+        self.messages.push_back(TailMessage {
+            seq,
+            timestamp: serde_json::from_value(message.message["timestamp"].clone()).ok(),
+            sender: message_sender(&message.message),
+            sender_id: message.message["sender"]["id"].as_str().map(|id| format!("{}:{}", message.source, id)),
+            text: content_text(&message.message["content"]),
+            mentions_me: message.message["mentions_me"].as_bool().unwrap_or(false),
+        });
+        if self.messages.len() > TAIL_CAPACITY {
+            self.messages.pop_front();
+        }
+    }
+
+    /// Drop messages of a forgotten chat or person
+    fn forget(&mut self, target: &ForgetTarget) {
+        let chat = &self.chat;
+        self.messages.retain(|message| {
+            let sender_id = message.sender_id.as_deref().and_then(|id| id.split_once(':')).map(|(_, id)| id);
+            !target.covers(chat, sender_id)
+        });
+    }
+
+    fn shows(&self, message: &TailMessage) -> bool {
+        if self.mentions_only && !message.mentions_me {
+            return false;
+        }
+        let filter = self.filter.trim().to_lowercase();
+        filter.is_empty()
+            || message.sender.to_lowercase().contains(&filter)
+            || message.text.to_lowercase().contains(&filter)
+    }
+
+    /// Shown messages below the divider
+    fn unseen(&self) -> usize {
+        let Some(divider) = self.divider else {
+            return 0;
+        };
+        self.messages.iter().filter(|m| m.seq >= divider && self.shows(m)).count()
+    }
+
+    fn set_following(&mut self, following: bool) {
+        // A new pause starts a new divider
+        if self.following && !following {
+            self.divider = None;
+        }
+        self.following = following;
+    }
+}
+
+/// Result of `chat catchup --format json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatchUp {
+    pub summary: String,
+    #[serde(default)]
+    pub summarized: bool,
+    #[serde(default)]
+    pub marked_read: bool,
+    #[serde(default)]
+    pub chats: Vec<CatchUpChat>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatchUpChat {
+    pub chat: String,
+    pub title: Option<String>,
+    pub unread: usize,
+    pub urgent: usize,
+}
+
+/// Something the Chats tab asks the app to do
+pub enum ChatsTabAction {
+    /// Switch reading a chat (`source:chat_id`) aloud on or off
+    Announce { chat: String, enabled: bool },
+    /// Switch the assistant answering a chat on or off
+    Assist { chat: String, enabled: bool },
+    Speak(String),
+    /// Save text as narrated audio
+    ExportAudio(String),
+    /// A tail was opened or closed, so incoming messages are needed or not
+    Tail,
+}
+
+/// State for the Chats tab, which groups chats by their local labels
+#[derive(Default)]
+pub struct ChatsTabState {
+    chats: Vec<ChatEntry>,
+    labels: ChatLabels,
+    /// Canned replies offered on tailed messages, reloaded with the chat list
+    snippets: Snippets,
+    loading: bool,
+    error: Option<String>,
+    refresh_rx: Option<mpsc::Receiver<Result<Vec<ChatEntry>, String>>>,
+    selected: Option<String>,
+    analysis: Option<ChatAnalysis>,
+    analysis_error: Option<String>,
+    analysis_rx: Option<mpsc::Receiver<Result<ChatAnalysis, String>>>,
+    search_query: String,
+    search_results: Option<(String, Vec<SearchHit>)>,
+    search_error: Option<String>,
+    search_rx: Option<mpsc::Receiver<Result<Vec<SearchHit>, String>>>,
+    /// Chats ticked for "Catch me up"; none means all listed chats
+    catchup_chats: BTreeSet<String>,
+    catchup_mark_read: bool,
+    catchup: Option<CatchUp>,
+    catchup_error: Option<String>,
+    catchup_rx: Option<mpsc::Receiver<Result<CatchUp, String>>>,
+    /// Live view of the selected chat, replacing its activity panel
+    tail: Option<ChatTail>,
+    /// Note on the selected chat, or on a person picked from its tail
+    note: Option<NoteEditor>,
+    /// Move keyboard focus to the search field on the next frame
+    pub focus_search: bool,
+}
+
+impl ChatsTabState {
+    /// Fetch the chat list through the `chat` CLI in a background thread
+    pub fn refresh(&mut self) {
+        if self.loading {
+            return;
+        }
+
+        self.loading = true;
+        self.error = None;
+        self.labels = ChatLabels::load();
+        self.snippets = Snippets::load();
+
+        let (tx, rx) = mpsc::channel();
+        self.refresh_rx = Some(rx);
+
+        std::thread::spawn(move || {
+            let _ = tx.send(fetch_telegram_chats());
+        });
+    }
+
+    /// Select a chat and analyze its last 30 days in a background thread
+    pub fn select(&mut self, chat_id: String) {
+        self.analysis = None;
+        self.analysis_error = None;
+
+        let (tx, rx) = mpsc::channel();
+        self.analysis_rx = Some(rx);
+
+        let id = chat_id.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(fetch_analysis(&id));
+        });
+
+        // Tailing carries over to the newly selected chat
+        if self.tail.is_some() {
+            self.tail = Some(ChatTail::new(&chat_id));
+        }
+        self.note = Some(NoteEditor::open(NoteTarget::Chat(format!("telegram:{}", chat_id))));
+        self.selected = Some(chat_id);
+    }
+
+    /// Whether a chat is being tailed and needs incoming messages
+    pub fn is_tailing(&self) -> bool {
+        self.tail.is_some()
+    }
+
+    /// Show a message from `chat watch` if it belongs to the tailed chat
+    pub fn push_live(&mut self, message: &IncomingMessage) {
+        if let Some(tail) = &mut self.tail {
+            tail.push(message);
+        }
+    }
+
+    /// Drop a forgotten chat's or person's messages from the tail
+    pub fn forget(&mut self, target: &ForgetTarget) {
+        if let Some(tail) = &mut self.tail {
+            tail.forget(target);
+        }
+    }
+
+    /// Search message text across Telegram chats in a background thread
+    pub fn search(&mut self, query: String) {
+        let query = query.trim().to_string();
+        if query.is_empty() {
+            return;
+        }
+
+        self.search_query = query.clone();
+        self.search_results = None;
+        self.search_error = None;
+
+        let (tx, rx) = mpsc::channel();
+        self.search_rx = Some(rx);
+
+        std::thread::spawn(move || {
+            let _ = tx.send(search_messages(&query));
+        });
+    }
+
+    /// Summarize unread messages of the ticked chats (or all listed chats) in a background thread
+    pub fn catch_up(&mut self) {
+        let chat_ids: Vec<String> = if self.catchup_chats.is_empty() {
+            self.chats.iter().map(|chat| chat.id.clone()).collect()
+        } else {
+            self.catchup_chats.iter().cloned().collect()
+        };
+        if chat_ids.is_empty() {
+            return;
+        }
+
+        self.catchup = None;
+        self.catchup_error = None;
+
+        let (tx, rx) = mpsc::channel();
+        self.catchup_rx = Some(rx);
+
+        let mark_read = self.catchup_mark_read;
+        std::thread::spawn(move || {
+            let _ = tx.send(fetch_catchup(&chat_ids, mark_read));
+        });
+    }
+
+    fn poll(&mut self) {
+        if let Some(rx) = &self.refresh_rx {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(chats) => self.chats = chats,
+                    Err(e) => self.error = Some(e),
+                }
+                self.loading = false;
+                self.refresh_rx = None;
+            }
+        }
+
+        if let Some(rx) = &self.analysis_rx {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(analysis) => self.analysis = Some(analysis),
+                    Err(e) => self.analysis_error = Some(e),
+                }
+                self.analysis_rx = None;
+            }
+        }
+
+        if let Some(rx) = &self.search_rx {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(hits) => self.search_results = Some((self.search_query.clone(), hits)),
+                    Err(e) => self.search_error = Some(e),
+                }
+                self.search_rx = None;
+            }
+        }
+
+        if let Some(rx) = &self.catchup_rx {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(catchup) => self.catchup = Some(catchup),
+                    Err(e) => self.catchup_error = Some(e),
+                }
+                self.catchup_rx = None;
+            }
+        }
+    }
+
+    /// Chats grouped by label; unlabelled chats come last
+    fn grouped(&self) -> Vec<(String, Vec<&ChatEntry>)> {
+        let mut groups: BTreeMap<String, Vec<&ChatEntry>> = BTreeMap::new();
+        let mut unlabelled = Vec::new();
+
+        for chat in &self.chats {
+            let labels = self.labels.labels_for("telegram", &chat.id);
+            if labels.is_empty() {
+                unlabelled.push(chat);
+            }
+            for label in labels {
+                groups.entry(label).or_default().push(chat);
+            }
+        }
+
+        let mut grouped: Vec<_> = groups.into_iter().collect();
+        if !unlabelled.is_empty() {
+            grouped.push((tr!("chats-unlabelled"), unlabelled));
+        }
+        grouped
+    }
+}
+
+/// Run the chat CLI and return the JSON array or object it prints after its progress line
+fn run_chat_cli(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new(crate::config::paths::helper("chat"))
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run chat CLI: {}", e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .find(['[', '{'])
+        .map(|start| stdout[start..].to_string())
+        .ok_or_else(|| "No results returned by chat CLI".to_string())
+}
+
+fn fetch_telegram_chats() -> Result<Vec<ChatEntry>, String> {
+    let json = run_chat_cli(&["telegram", "list", "--format", "json"])?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse chat list: {}", e))
+}
+
+fn search_messages(query: &str) -> Result<Vec<SearchHit>, String> {
+    let json = run_chat_cli(&["messages", "telegram:*", "--search", query, "--limit", "50", "--format", "json"])?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse search results: {}", e))
+}
+
+fn fetch_catchup(chat_ids: &[String], mark_read: bool) -> Result<CatchUp, String> {
+    let filters: Vec<String> = chat_ids.iter().map(|id| format!("telegram:{}", id)).collect();
+    let mut args = vec!["catchup", "--format", "json"];
+    if mark_read {
+        args.push("--mark-read");
+    }
+    args.extend(filters.iter().map(String::as_str));
+
+    let json = run_chat_cli(&args)?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse catch-up: {}", e))
+}
+
+fn fetch_analysis(chat_id: &str) -> Result<ChatAnalysis, String> {
+    let filter = format!("telegram:{}", chat_id);
+    let json = run_chat_cli(&["analyze", &filter, "--since", "30d", "--sentiment", "--format", "json"])?;
+    let analyses: Vec<ChatAnalysis> =
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse analysis: {}", e))?;
+
+    analyses
+        .into_iter()
+        .next()
+        .ok_or_else(|| tr!("chats-no-recent-messages"))
+}
+
+pub fn render_chats_tab(
+    ctx: &egui::Context,
+    state: &mut ChatsTabState,
+    announce: &AnnounceConfig,
+    assistant: &AssistantConfig,
+) -> Option<ChatsTabAction> {
+    state.poll();
+    if state.loading || state.analysis_rx.is_some() || state.search_rx.is_some() || state.catchup_rx.is_some() {
+        ctx.request_repaint_after(std::time::Duration::from_millis(200));
+    }
+
+    let mut action = None;
+    if state.selected.is_some() {
+        egui::SidePanel::right("chat_analysis_panel")
+            .resizable(true)
+            .default_width(360.0)
+            .show(ctx, |ui| action = render_side_panel(ui, state));
+    }
+
+    let mut clicked = None;
+    let mut catchup_toggled = None;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.heading(tr!("tab-chats"));
+            if ui
+                .add_enabled(!state.loading, egui::Button::new(tr!("chats-refresh")))
+                .clicked()
+            {
+                state.refresh();
+            }
+            if state.loading {
+                ui.spinner();
+            }
+
+            ui.separator();
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut state.search_query)
+                    .hint_text(tr!("chats-search-hint"))
+                    .desired_width(200.0),
+            );
+            if std::mem::take(&mut state.focus_search) {
+                response.request_focus();
+            }
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui
+                .add_enabled(state.search_rx.is_none(), egui::Button::new(tr!("chats-search")))
+                .clicked()
+                || submitted
+            {
+                state.search(state.search_query.clone());
+            }
+            if state.search_rx.is_some() {
+                ui.spinner();
+            }
+
+            ui.separator();
+            let label = if state.catchup_chats.is_empty() {
+                tr!("chats-catchup")
+            } else {
+                tr!("chats-catchup-ticked", count = state.catchup_chats.len())
+            };
+            if ui
+                .add_enabled(state.catchup_rx.is_none() && !state.chats.is_empty(), egui::Button::new(label))
+                .on_hover_text(tr!("chats-catchup-hint"))
+                .clicked()
+            {
+                state.catch_up();
+            }
+            ui.checkbox(&mut state.catchup_mark_read, tr!("chats-catchup-mark-read"));
+            if state.catchup_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.label(
+            egui::RichText::new(tr!("chats-labels-hint"))
+                .size(11.0)
+                .color(egui::Color32::GRAY),
+        );
+        ui.separator();
+
+        if let Some(error) = &state.error {
+            ui.colored_label(egui::Color32::from_rgb(255, 80, 80), error);
+            ui.separator();
+        }
+
+        if let Some(error) = &state.search_error {
+            ui.colored_label(egui::Color32::from_rgb(255, 80, 80), error);
+            ui.separator();
+        }
+
+        if let Some(error) = &state.catchup_error {
+            ui.colored_label(egui::Color32::from_rgb(255, 80, 80), error);
+            ui.separator();
+        }
+
+        if let Some(catchup) = &state.catchup {
+            let mut close = false;
+            ui.horizontal(|ui| {
+                ui.strong(tr!("chats-catchup-heading"));
+                for chat in catchup.chats.iter().filter(|chat| chat.urgent > 0) {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 80, 80),
+                        tr!("chats-catchup-urgent", chat = chat.title.as_deref().unwrap_or(&chat.chat).to_string(), count = chat.urgent),
+                    )
+                    .on_hover_text(tr!("chats-catchup-unread", count = chat.unread));
+                }
+                if catchup.marked_read {
+                    ui.label(egui::RichText::new(tr!("chats-catchup-marked-read")).size(11.0).color(egui::Color32::GRAY));
+                }
+                if ui.small_button("🔊").on_hover_text(tr!("chats-read-aloud")).clicked() {
+                    action = Some(ChatsTabAction::Speak(catchup.summary.clone()));
+                }
+                if ui.small_button("💾").on_hover_text(tr!("chats-save-audio")).clicked() {
+                    action = Some(ChatsTabAction::ExportAudio(catchup.summary.clone()));
+                }
+                close = ui.small_button("✖").on_hover_text(tr!("chats-close")).clicked();
+            });
+            if !catchup.summarized && !catchup.chats.is_empty() {
+                ui.label(
+                    egui::RichText::new(tr!("chats-catchup-digest"))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY),
+                );
+            }
+            egui::ScrollArea::vertical()
+                .id_salt("catchup_summary")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    ui.label(&catchup.summary);
+                });
+            if close {
+                state.catchup = None;
+            }
+            ui.separator();
+        }
+
+        if let Some((query, hits)) = &state.search_results {
+            let mut close = false;
+            ui.horizontal(|ui| {
+                ui.strong(tr!("chats-search-results", count = hits.len(), query = query.to_string()));
+                close = ui.small_button("✖").on_hover_text(tr!("chats-close")).clicked();
+            });
+            egui::ScrollArea::vertical()
+                .id_salt("search_results")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    for hit in hits {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .link(format!("{} · {}", hit.timestamp.format("%Y-%m-%d %H:%M"), hit.sender_name()))
+                                .on_hover_text(tr!("chats-show-activity"))
+                                .clicked()
+                            {
+                                clicked = Some(hit.chat_id.clone());
+                            }
+                            ui.label(hit.text());
+                        });
+                    }
+                });
+            if close {
+                state.search_results = None;
+            }
+            ui.separator();
+        }
+
+        if state.chats.is_empty() && !state.loading {
+            ui.label(tr!("chats-none"));
+            return;
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (label, chats) in state.grouped() {
+                egui::CollapsingHeader::new(format!("{} ({})", label, chats.len()))
+                    .id_salt(("chat_group", &label))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for chat in chats {
+                            ui.horizontal(|ui| {
+                                let mut ticked = state.catchup_chats.contains(&chat.id);
+                                if ui.checkbox(&mut ticked, "").on_hover_text(tr!("chats-catchup-include")).changed() {
+                                    catchup_toggled = Some((chat.id.clone(), ticked));
+                                }
+                                let is_selected = state.selected.as_deref() == Some(chat.id.as_str());
+                                if ui
+                                    .selectable_label(is_selected, chat.title.clone().unwrap_or_else(|| tr!("chats-unknown")))
+                                    .clicked()
+                                {
+                                    clicked = Some(chat.id.clone());
+                                }
+                                ui.label(
+                                    egui::RichText::new(&chat.id)
+                                        .size(11.0)
+                                        .color(egui::Color32::GRAY),
+                                );
+                                let key = format!("telegram:{}", chat.id);
+                                let mut announced = announce.is_enabled(&key);
+                                if ui
+                                    .toggle_value(&mut announced, "🔊")
+                                    .on_hover_text(tr!("chats-announce-hint"))
+                                    .changed()
+                                {
+                                    action = Some(ChatsTabAction::Announce { chat: key.clone(), enabled: announced });
+                                }
+                                let mut assisted = assistant.is_enabled(&key);
+                                if ui
+                                    .toggle_value(&mut assisted, "🤖")
+                                    .on_hover_text(tr!("chats-assist-hint"))
+                                    .changed()
+                                {
+                                    action = Some(ChatsTabAction::Assist { chat: key, enabled: assisted });
+                                }
+                            });
+                        }
+                    });
+            }
+        });
+    });
+
+    if let Some(chat_id) = clicked {
+        state.select(chat_id);
+    }
+    if let Some((chat_id, ticked)) = catchup_toggled {
+        if ticked {
+            state.catchup_chats.insert(chat_id);
+        } else {
+            state.catchup_chats.remove(&chat_id);
+        }
+    }
+
+    action
+}
+
+/// Activity of the selected chat, or its live tail
+fn render_side_panel(ui: &mut egui::Ui, state: &mut ChatsTabState) -> Option<ChatsTabAction> {
+    let mut action = None;
+    let mut tailing = state.tail.is_some();
+
+    ui.horizontal(|ui| {
+        ui.heading(if tailing { tr!("chats-live") } else { tr!("chats-activity") });
+        if ui
+            .toggle_value(&mut tailing, tr!("chats-tail"))
+            .on_hover_text(tr!("chats-tail-hint"))
+            .changed()
+        {
+            state.tail = match (tailing, &state.selected) {
+                (true, Some(chat_id)) => Some(ChatTail::new(chat_id)),
+                _ => None,
+            };
+            action = Some(ChatsTabAction::Tail);
+        }
+        if ui.small_button("✖").on_hover_text(tr!("chats-close")).clicked() {
+            state.selected = None;
+            state.analysis = None;
+            state.note = None;
+            if state.tail.take().is_some() {
+                action = Some(ChatsTabAction::Tail);
+            }
+        }
+    });
+
+    if let Some(note) = &mut state.note {
+        let (heading, id, default_open) = match &note.target {
+            NoteTarget::Chat(id) => (tr!("chats-notes"), id.clone(), !note.is_empty()),
+            NoteTarget::Person { id, name } => (tr!("chats-notes-on", name = name.clone()), id.clone(), true),
+        };
+        let mut back = false;
+        egui::CollapsingHeader::new(heading)
+            .id_salt(("chat_note", id))
+            .default_open(default_open)
+            .show(ui, |ui| {
+                note.show(ui);
+                if matches!(note.target, NoteTarget::Person { .. }) {
+                    back = ui.small_button(tr!("chats-notes-back")).clicked();
+                }
+            });
+        if back {
+            state.note = state.selected.as_ref().map(|id| NoteEditor::open(NoteTarget::Chat(format!("telegram:{}", id))));
+        }
+        ui.separator();
+    }
+
+    let chat_name = state
+        .selected
+        .as_ref()
+        .and_then(|id| state.chats.iter().find(|chat| &chat.id == id))
+        .and_then(|chat| chat.title.clone());
+    match &mut state.tail {
+        Some(tail) => {
+            let chat_name = chat_name.unwrap_or_else(|| tail.chat.clone());
+            if let Some(person) = render_tail(ui, tail, &state.snippets, &chat_name) {
+                state.note = Some(NoteEditor::open(person));
+            }
+        }
+        None => render_analysis(ui, state),
+    }
+    action
+}
+
+/// Returns the person whose note was asked for from a message's menu
+fn render_tail(ui: &mut egui::Ui, tail: &mut ChatTail, snippets: &Snippets, chat_name: &str) -> Option<NoteTarget> {
+    let mut open_note = None;
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut tail.filter)
+                .hint_text(tr!("chats-tail-filter"))
+                .desired_width(160.0),
+        );
+        ui.checkbox(&mut tail.mentions_only, tr!("chats-tail-mentions"));
+        if ui.small_button(tr!("chats-tail-clear")).clicked() {
+            tail.messages.clear();
+            tail.divider = None;
+        }
+    });
+    let status = if tail.following {
+        tr!("chats-tail-following")
+    } else {
+        tr!("chats-tail-paused")
+    };
+    ui.label(egui::RichText::new(status).size(11.0).color(egui::Color32::GRAY));
+    ui.separator();
+
+    let unseen = tail.unseen();
+    let jump = !tail.following && unseen > 0 && ui.button(tr!("chats-tail-jump", count = unseen)).clicked();
+
+    let output = egui::ScrollArea::vertical()
+        .id_salt("chat_tail")
+        .auto_shrink([false, false])
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            let mut divider_drawn = false;
+            let mut shown = 0;
+            for message in tail.messages.iter().filter(|m| tail.shows(m)) {
+                if !divider_drawn && tail.divider.is_some_and(|divider| message.seq >= divider) {
+                    divider_drawn = true;
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(255, 80, 80), tr!("chats-tail-new"));
+                        ui.add(egui::Separator::default().horizontal());
+                    });
+                }
+                shown += 1;
+                ui.horizontal_wrapped(|ui| {
+                    if let Some(timestamp) = message.timestamp {
+                        ui.label(
+                            egui::RichText::new(timestamp.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+                                .size(11.0)
+                                .color(egui::Color32::GRAY),
+                        );
+                    }
+                    let sender = egui::RichText::new(&message.sender).strong();
+                    ui.label(if message.mentions_me { sender.color(egui::Color32::YELLOW) } else { sender });
+                    ui.label(&message.text).context_menu(|ui| {
+                        if let Some(id) = &message.sender_id {
+                            if ui.button(tr!("chats-note-on", name = message.sender.clone())).clicked() {
+                                open_note = Some(NoteTarget::Person { id: id.clone(), name: message.sender.clone() });
+                                ui.close_menu();
+                            }
+                        }
+                        if !snippets.is_empty() {
+                            reply_menu(ui, snippets, &message.sender, chat_name);
+                        }
+                    });
+                });
+            }
+            if shown == 0 {
+                ui.label(egui::RichText::new(tr!("chats-tail-waiting")).color(egui::Color32::GRAY));
+            }
+            if jump {
+                ui.scroll_to_cursor(Some(egui::Align::BOTTOM));
+            }
+        });
+
+    let at_bottom = output.state.offset.y + output.inner_rect.height() >= output.content_size.y - 4.0;
+    tail.set_following(at_bottom || jump);
+    open_note
+}
+
+/// Right-click menu on a tailed message: copy a snippet addressed to its sender, to paste
+/// into a reply on the platform
+fn reply_menu(ui: &mut egui::Ui, snippets: &Snippets, sender: &str, chat_name: &str) {
+    ui.label(egui::RichText::new(tr!("chats-copy-reply")).size(11.0).color(egui::Color32::GRAY));
+    for (name, text) in snippets.iter() {
+        if ui.button(name).on_hover_text(text).clicked() {
+            let mut values = vec![("sender", sender.to_string()), ("chat", chat_name.to_string())];
+            values.extend(snippets::now_values());
+            ui.ctx().copy_text(snippets::render(text, &values));
+            ui.close_menu();
+        }
+    }
+}
+
+fn render_analysis(ui: &mut egui::Ui, state: &mut ChatsTabState) {
+    ui.label(
+        egui::RichText::new(tr!("chats-last-30-days"))
+            .size(11.0)
+            .color(egui::Color32::GRAY),
+    );
+    ui.separator();
+
+    if state.analysis_rx.is_some() {
+        ui.spinner();
+        return;
+    }
+
+    if let Some(error) = &state.analysis_error {
+        ui.colored_label(egui::Color32::from_rgb(255, 80, 80), error);
+        return;
+    }
+
+    let Some(analysis) = &state.analysis else {
+        return;
+    };
+
+    ui.label(tr!("chats-message-count", count = analysis.message_count));
+    ui.add_space(4.0);
+    draw_heatmap(ui, &analysis.heatmap);
+
+    if !analysis.response_times.is_empty() {
+        ui.add_space(8.0);
+        ui.strong(tr!("chats-response-times"));
+        for stats in analysis.response_times.iter().take(8) {
+            ui.label(tr!(
+                "chats-response-time",
+                responder = stats.responder.clone(),
+                to = stats.to.clone(),
+                time = format_secs(stats.median_secs),
+                count = stats.count
+            ));
+        }
+    }
+
+    if let Some(sentiment) = &analysis.sentiment {
+        ui.add_space(8.0);
+        ui.strong(tr!("chats-sentiment", mean = format!("{:+.2}", sentiment.mean)))
+            .on_hover_text(tr!("chats-sentiment-hint"));
+        let total = (sentiment.positive + sentiment.neutral + sentiment.negative).max(1) as f32;
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 12.0), egui::Sense::hover());
+        let mut x = rect.left();
+        for (count, color) in [
+            (sentiment.positive, egui::Color32::from_rgb(13, 188, 121)),
+            (sentiment.neutral, egui::Color32::GRAY),
+            (sentiment.negative, egui::Color32::from_rgb(205, 49, 49)),
+        ] {
+            let width = rect.width() * count as f32 / total;
+            let segment = egui::Rect::from_min_size(egui::pos2(x, rect.top()), egui::vec2(width, rect.height()));
+            ui.painter().rect_filled(segment, 0.0, color);
+            x += width;
+        }
+        ui.label(
+            egui::RichText::new(tr!(
+                "chats-sentiment-counts",
+                positive = sentiment.positive,
+                neutral = sentiment.neutral,
+                negative = sentiment.negative
+            ))
+            .size(11.0),
+        );
+    }
+}
+
+/// Hour × weekday grid shaded by message count
+fn draw_heatmap(ui: &mut egui::Ui, heatmap: &ActivityHeatmap) {
+    let weekdays = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"].map(|day| i18n::translate(&format!("weekday-{}", day)));
+    let max = heatmap.counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+    let cell = ((ui.available_width() - 36.0) / 24.0).clamp(6.0, 14.0);
+
+    for (day, hours) in heatmap.counts.iter().enumerate().take(7) {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 1.0;
+            ui.add_sized([32.0, cell], egui::Label::new(egui::RichText::new(&weekdays[day]).size(10.0)));
+            for (hour, &count) in hours.iter().enumerate() {
+                let (rect, response) = ui.allocate_exact_size(egui::vec2(cell, cell), egui::Sense::hover());
+                let intensity = count as f32 / max as f32;
+                let color = if count == 0 {
+                    egui::Color32::from_gray(40)
+                } else {
+                    egui::Color32::from_rgb(30, (80.0 + 150.0 * intensity) as u8, 60)
+                };
+                ui.painter().rect_filled(rect, 1.0, color);
+                response.on_hover_text(tr!("chats-heatmap-cell", day = weekdays[day].clone(), hour = format!("{:02}", hour), count = count));
+            }
+        });
+    }
+}
+
+fn format_secs(secs: i64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
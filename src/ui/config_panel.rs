@@ -1,16 +1,41 @@
-use crate::agent::{AgentConfig, AgentId, AgentType};
+use crate::agent::coding_cli;
+use crate::agent::context::AgentContext;
+use crate::agent::{AgentConfig, AgentId, AgentType, CodingCli, CodingCliConfig, ProcessAgentConfig};
+use crate::config::appearance::FONT_SCALE_RANGE;
+use crate::crash;
+use crate::i18n::{self, tr};
+use crate::mcp::{self, McpServerStatus};
+use crate::monitor::LimitAction;
+use crate::sandbox::{SandboxBackend, SandboxConfig};
+use crate::telegram;
 use crate::ui::app::DashboardApp;
+use crate::ui::approval;
+use crate::ui::onboarding::OnboardingState;
+use std::path::PathBuf;
 
 pub fn show_config_panel(ctx: &egui::Context, app: &mut DashboardApp) {
-    egui::Window::new("Agent Configuration")
+    let mut pipelines_toggled = None;
+    let mut clipboard_toggled = None;
+    let mut run_setup = false;
+    let mut connect = None;
+    let mut to_remove: Option<AgentId> = None;
+    let mut context_changed = Vec::new();
+    let mut settings_changed = false;
+    let mut unannounce = None;
+    let mut restart_mcp = false;
+    let mut restart_voice = false;
+    let mut restart_monitor = false;
+    let mut restyle = false;
+
+    egui::Window::new(tr!("settings-title"))
         .open(&mut app.show_config_panel)
         .default_width(500.0)
         .show(ctx, |ui| {
-            ui.heading("Configured Agents");
+            ui.heading(tr!("settings-agents"));
             ui.separator();
 
             ui.horizontal(|ui| {
-                if ui.button("Add Agent").clicked() {
+                if ui.button(tr!("settings-add-agent")).clicked() {
                     // Create a default Ollama agent config
                     let config = AgentConfig {
                         id: AgentId::new(),
@@ -20,50 +45,675 @@ pub fn show_config_panel(ctx: &egui::Context, app: &mut DashboardApp) {
                             "host": "http://localhost:11434",
                             "model": "llama2"
                         }),
+                        sandbox: SandboxConfig::default(),
+                        context: AgentContext::default(),
                     };
                     app.config.add_agent(config);
                     let _ = app.config.save();
                 }
+
+                if ui
+                    .button(tr!("settings-add-process-agent"))
+                    .on_hover_text(tr!("settings-add-process-agent-hint"))
+                    .clicked()
+                {
+                    let config = AgentConfig {
+                        id: AgentId::new(),
+                        name: format!("Agent {}", app.config.agents.len() + 1),
+                        agent_type: AgentType::Process,
+                        config_data: serde_json::to_value(ProcessAgentConfig {
+                            command: "my-agent".to_string(),
+                            args: Vec::new(),
+                            workspace: None,
+                        })
+                        .unwrap_or_default(),
+                        sandbox: SandboxConfig::default(),
+                        context: AgentContext::default(),
+                    };
+                    app.config.add_agent(config);
+                    let _ = app.config.save();
+                }
+
+                ui.menu_button(tr!("settings-add-coding-cli"), |ui| {
+                    for cli in [CodingCli::ClaudeCode, CodingCli::Codex] {
+                        if ui.button(cli.agent_type().to_string()).clicked() {
+                            let name = format!("{} {}", cli.agent_type(), app.config.agents.len() + 1);
+                            app.config.add_agent(coding_cli::preset(cli, name));
+                            let _ = app.config.save();
+                            ui.close_menu();
+                        }
+                    }
+                });
+
+                if ui.button(tr!("settings-setup-wizard")).clicked() {
+                    run_setup = true;
+                }
             });
 
             ui.separator();
 
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                let mut to_remove: Option<AgentId> = None;
+            let mut pipelines_enabled = app.config.pipelines_enabled;
+            if ui
+                .checkbox(&mut pipelines_enabled, tr!("settings-pipelines"))
+                .on_hover_text(tr!("settings-pipelines-hint"))
+                .changed()
+            {
+                pipelines_toggled = Some(pipelines_enabled);
+            }
+
+            let mut clipboard_enabled = app.config.clipboard.enabled;
+            if ui
+                .checkbox(&mut clipboard_enabled, tr!("settings-clipboard"))
+                .on_hover_text(tr!("settings-clipboard-hint"))
+                .changed()
+            {
+                clipboard_toggled = Some(clipboard_enabled);
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(tr!("settings-language"));
+                let language = &mut app.config.language;
+                let before = language.clone();
+                let selected = match language.as_deref() {
+                    Some(code) => i18n::LANGUAGES.iter().find(|(c, _)| *c == code).map_or(code, |(_, name)| *name).to_string(),
+                    None => tr!("settings-language-system"),
+                };
+                egui::ComboBox::from_id_salt("language")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(language, None, tr!("settings-language-system"));
+                        for (code, name) in i18n::LANGUAGES {
+                            ui.selectable_value(language, Some(code.to_string()), name);
+                        }
+                    });
+                if *language != before {
+                    i18n::set_language(language.as_deref());
+                    settings_changed = true;
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let crash_reports = &mut app.config.crash_reports;
+                if ui
+                    .checkbox(&mut crash_reports.enabled, tr!("settings-crash-reports"))
+                    .on_hover_text(tr!("settings-crash-reports-hint"))
+                    .changed()
+                {
+                    crash::set_enabled(crash_reports.enabled);
+                    settings_changed = true;
+                }
+                if ui.small_button(tr!("settings-crash-reports-folder")).clicked() {
+                    let opened = crash::reports_dir().and_then(|dir| {
+                        std::fs::create_dir_all(&dir)?;
+                        crash::open_folder(&dir)
+                    });
+                    if let Err(e) = opened {
+                        log::warn!("Failed to open the crash reports folder: {}", e);
+                    }
+                }
+            });
+
+            ui.separator();
+
+            egui::CollapsingHeader::new(tr!("settings-appearance")).show(ui, |ui| {
+                let appearance = &mut app.config.appearance;
+                egui::Grid::new("appearance").num_columns(2).show(ui, |ui| {
+                    ui.label(tr!("settings-font-scale"));
+                    restyle |= ui
+                        .add(egui::Slider::new(&mut appearance.font_scale, FONT_SCALE_RANGE).step_by(0.1))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label(tr!("settings-terminal-font"));
+                    let mut path = appearance.terminal_font.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+                    let response = ui
+                        .add(egui::TextEdit::singleline(&mut path).hint_text(tr!("settings-terminal-font-builtin")))
+                        .on_hover_text(tr!("settings-terminal-font-hint"));
+                    if response.changed() {
+                        appearance.terminal_font = (!path.is_empty()).then(|| PathBuf::from(&path));
+                        settings_changed = true;
+                    }
+                    restyle |= response.lost_focus();
+                    ui.end_row();
+
+                    ui.label(tr!("settings-terminal-font-size"));
+                    restyle |= ui
+                        .add(egui::DragValue::new(&mut appearance.terminal_font_size).range(6.0..=48.0).suffix(" pt"))
+                        .changed();
+                    ui.end_row();
+                });
+                restyle |= ui.checkbox(&mut appearance.high_contrast, tr!("settings-high-contrast")).changed();
+                ui.label(
+                    egui::RichText::new(tr!("settings-shortcuts-hint"))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY),
+                );
+            });
+            settings_changed |= restyle;
+
+            egui::CollapsingHeader::new(tr!("settings-approvals")).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("settings-approvals-timeout"));
+                    settings_changed |= ui
+                        .add(egui::DragValue::new(&mut app.config.approvals.timeout_secs).range(5..=600).suffix(" s"))
+                        .changed();
+                });
+                ui.label(egui::RichText::new(tr!("settings-approvals-recent")).strong());
+                approval::audit_log(ui, &app.approvals);
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-announce")).show(ui, |ui| {
+                let announce = &mut app.config.announce;
+                ui.horizontal(|ui| {
+                    ui.label(tr!("settings-announce-template"));
+                    settings_changed |= ui
+                        .text_edit_singleline(&mut announce.template)
+                        .on_hover_text(tr!("settings-announce-template-hint"))
+                        .changed();
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr!("settings-announce-chat-interval"));
+                    settings_changed |= ui
+                        .add(egui::DragValue::new(&mut announce.chat_interval_secs).range(0..=3600).suffix(" s"))
+                        .changed();
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr!("settings-announce-max"));
+                    settings_changed |= ui
+                        .add(egui::DragValue::new(&mut announce.max_per_minute).range(1..=60))
+                        .changed();
+                    ui.label(tr!("settings-announce-per-minute"));
+                });
+                if announce.chats.is_empty() {
+                    ui.label(tr!("settings-announce-none"));
+                }
+                for chat in &announce.chats {
+                    ui.horizontal(|ui| {
+                        ui.label(chat);
+                        if ui.small_button("✖").on_hover_text(tr!("settings-announce-stop")).clicked() {
+                            unannounce = Some(chat.clone());
+                        }
+                    });
+                }
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-mcp")).show(ui, |ui| {
+                let server = &mut app.config.mcp_server;
+                restart_mcp |= ui
+                    .checkbox(&mut server.enabled, tr!("settings-mcp-enabled"))
+                    .on_hover_text(tr!("settings-mcp-enabled-hint"))
+                    .changed();
+                ui.horizontal(|ui| {
+                    ui.label(tr!("settings-mcp-port"));
+                    settings_changed |= ui.add(egui::DragValue::new(&mut server.port).range(1024..=65535)).changed();
+                });
+                settings_changed |= ui
+                    .checkbox(&mut server.read_only, tr!("settings-mcp-read-only"))
+                    .on_hover_text(tr!("settings-mcp-read-only-hint"))
+                    .changed();
+
+                match app.mcp_server.as_mut() {
+                    Some(running) => {
+                        let (text, color) = match running.status() {
+                            McpServerStatus::Starting => (tr!("settings-mcp-starting"), egui::Color32::from_rgb(229, 229, 16)),
+                            McpServerStatus::Running => {
+                                (tr!("settings-mcp-listening", address = running.address().to_string()), egui::Color32::from_rgb(13, 188, 121))
+                            }
+                            McpServerStatus::Stopped(reason) => {
+                                (tr!("settings-mcp-stopped", reason = reason.to_string()), egui::Color32::from_rgb(205, 49, 49))
+                            }
+                        };
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("●").color(color));
+                            ui.label(text).on_hover_text(
+                                serde_json::to_string_pretty(&mcp::client_config(running.address())).unwrap_or_default(),
+                            );
+                        });
+                        if server.enabled && ui.button(tr!("settings-mcp-restart")).on_hover_text(tr!("settings-mcp-restart-hint")).clicked() {
+                            restart_mcp = true;
+                        }
+                    }
+                    None => {
+                        ui.label(tr!("settings-mcp-not-running"));
+                    }
+                }
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-grouping")).show(ui, |ui| {
+                let grouping = &mut app.config.grouping;
+                ui.horizontal(|ui| {
+                    ui.label(tr!("settings-grouping-window"));
+                    settings_changed |= ui
+                        .add(egui::DragValue::new(&mut grouping.chat_window_secs).range(0..=86_400).suffix(" s"))
+                        .on_hover_text(tr!("settings-grouping-window-hint"))
+                        .changed();
+                });
+                settings_changed |= ui
+                    .checkbox(&mut grouping.collapse_chat_groups, tr!("settings-grouping-collapse"))
+                    .changed();
+                settings_changed |= ui
+                    .checkbox(&mut grouping.terminal_by_command, tr!("settings-grouping-terminal"))
+                    .changed();
+                ui.add_enabled_ui(grouping.terminal_by_command, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("settings-grouping-terminal-gap"));
+                        settings_changed |= ui
+                            .add(egui::DragValue::new(&mut grouping.terminal_gap_secs).range(0..=3600).suffix(" s"))
+                            .on_hover_text(tr!("settings-grouping-terminal-gap-hint"))
+                            .changed();
+                    });
+                });
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-history")).show(ui, |ui| {
+                let terminal = &mut app.config.terminal;
+                ui.horizontal(|ui| {
+                    ui.label(tr!("settings-history-remember"));
+                    settings_changed |= ui
+                        .add(egui::DragValue::new(&mut terminal.history_limit).range(0..=100_000))
+                        .changed();
+                    ui.label(tr!("settings-history-per-command"));
+                });
+                settings_changed |= ui
+                    .checkbox(&mut terminal.skip_secrets, tr!("settings-history-skip-secrets"))
+                    .on_hover_text(tr!("settings-history-skip-secrets-hint"))
+                    .changed();
+                ui.add_enabled_ui(terminal.skip_secrets, |ui| {
+                    ui.label(tr!("settings-history-secret-words"));
+                    let mut words = terminal.secret_words.join("\n");
+                    if ui.add(egui::TextEdit::multiline(&mut words).desired_rows(3)).changed() {
+                        terminal.secret_words = words.split('\n').map(str::to_string).collect();
+                        settings_changed = true;
+                    }
+                });
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-paste")).show(ui, |ui| {
+                let terminal = &mut app.config.terminal;
+                ui.horizontal(|ui| {
+                    ui.label(tr!("settings-paste-confirm-lines"));
+                    settings_changed |= ui
+                        .add(egui::DragValue::new(&mut terminal.paste_confirm_lines).range(0..=10_000))
+                        .on_hover_text(tr!("settings-paste-confirm-lines-hint"))
+                        .changed();
+                    ui.label(tr!("settings-paste-lines"));
+                });
+                settings_changed |= ui
+                    .checkbox(&mut terminal.confirm_control_chars, tr!("settings-paste-control-chars"))
+                    .on_hover_text(tr!("settings-paste-control-chars-hint"))
+                    .changed();
+                settings_changed |= ui
+                    .checkbox(&mut terminal.bracketed_paste, tr!("settings-paste-bracketed"))
+                    .on_hover_text(tr!("settings-paste-bracketed-hint"))
+                    .changed();
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-terminal-sandbox")).show(ui, |ui| {
+                ui.label(tr!("settings-terminal-sandbox-note"));
+                settings_changed |= sandbox_settings(ui, "terminal", &mut app.config.terminal.sandbox);
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-limits")).show(ui, |ui| {
+                let monitor = &mut app.config.monitor;
+                if ui.checkbox(&mut monitor.enabled, tr!("settings-limits-monitor")).changed() {
+                    settings_changed = true;
+                    restart_monitor = true;
+                }
+                ui.add_enabled_ui(monitor.enabled, |ui| {
+                    egui::Grid::new("monitor_settings").num_columns(2).show(ui, |ui| {
+                        ui.label(tr!("settings-limits-interval"));
+                        settings_changed |= ui
+                            .add(egui::DragValue::new(&mut monitor.interval_secs).range(1..=60).suffix(" s"))
+                            .changed();
+                        ui.end_row();
+
+                        ui.label(tr!("settings-limits-memory"));
+                        settings_changed |= ui
+                            .add(egui::DragValue::new(&mut monitor.memory_limit_mb).range(0..=1_000_000).suffix(" MB"))
+                            .on_hover_text(tr!("settings-limits-memory-hint"))
+                            .changed();
+                        ui.end_row();
+
+                        ui.label(tr!("settings-limits-over"));
+                        egui::ComboBox::from_id_salt("monitor_on_limit")
+                            .selected_text(match monitor.on_memory_limit {
+                                LimitAction::Warn => tr!("settings-limits-warn"),
+                                LimitAction::Kill => tr!("settings-limits-kill"),
+                            })
+                            .show_ui(ui, |ui| {
+                                settings_changed |=
+                                    ui.selectable_value(&mut monitor.on_memory_limit, LimitAction::Warn, tr!("settings-limits-warn")).changed();
+                                settings_changed |= ui
+                                    .selectable_value(&mut monitor.on_memory_limit, LimitAction::Kill, tr!("settings-limits-kill"))
+                                    .changed();
+                            });
+                        ui.end_row();
+
+                        ui.label(tr!("settings-limits-cpu"));
+                        settings_changed |= ui
+                            .add(egui::DragValue::new(&mut monitor.cpu_warn_percent).range(0..=6400).suffix(" % CPU"))
+                            .on_hover_text(tr!("settings-limits-cpu-hint"))
+                            .changed();
+                        ui.end_row();
+                    });
+                });
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-voice")).show(ui, |ui| {
+                let voice = &mut app.config.voice;
+                if ui
+                    .checkbox(&mut voice.enabled, tr!("settings-voice-enabled"))
+                    .on_hover_text(tr!("settings-voice-enabled-hint"))
+                    .changed()
+                {
+                    settings_changed = true;
+                    restart_voice = true;
+                }
+                egui::Grid::new("voice_settings").num_columns(2).show(ui, |ui| {
+                    ui.label(tr!("settings-voice-hotkey"));
+                    settings_changed |= ui
+                        .text_edit_singleline(&mut voice.hotkey)
+                        .on_hover_text(tr!("settings-voice-hotkey-hint"))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label(tr!("settings-voice-wake-word"));
+                    let mut wake_word = voice.wake_word.clone().unwrap_or_default();
+                    if ui
+                        .text_edit_singleline(&mut wake_word)
+                        .on_hover_text(tr!("settings-voice-wake-word-hint"))
+                        .changed()
+                    {
+                        voice.wake_word = Some(wake_word).filter(|word| !word.trim().is_empty());
+                        settings_changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label(tr!("settings-voice-model"));
+                    let mut model = voice.whisper_model.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+                    if ui.text_edit_singleline(&mut model).changed() {
+                        voice.whisper_model = (!model.trim().is_empty()).then(|| model.trim().into());
+                        settings_changed = true;
+                    }
+                    ui.end_row();
 
-                for agent in &app.config.agents {
+                    ui.label(tr!("settings-voice-language"));
+                    let mut language = voice.language.clone().unwrap_or_default();
+                    if ui.text_edit_singleline(&mut language).on_hover_text(tr!("settings-voice-language-hint")).changed() {
+                        voice.language = Some(language).filter(|language| !language.trim().is_empty());
+                        settings_changed = true;
+                    }
+                    ui.end_row();
+                });
+                if voice.enabled && crate::voice::parse_hotkey(&voice.hotkey).is_none() {
+                    ui.colored_label(egui::Color32::from_rgb(255, 165, 0), tr!("settings-voice-hotkey-unknown"));
+                }
+                ui.label(
+                    egui::RichText::new(tr!("settings-voice-examples"))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY),
+                );
+                restart_voice |= voice.enabled && ui.button(tr!("settings-apply")).clicked();
+            });
+
+            egui::CollapsingHeader::new(tr!("settings-telegram")).show(ui, |ui| {
+                match telegram::session_file().filter(|path| path.exists()) {
+                    Some(path) => ui.label(tr!("settings-telegram-session", path = path.display().to_string())),
+                    None => ui.label(tr!("settings-telegram-signed-out")),
+                };
+                app.telegram_login.show(ui);
+            });
+
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for agent in &mut app.config.agents {
                     ui.group(|ui| {
                         ui.horizontal(|ui| {
                             ui.label(egui::RichText::new(&agent.name).strong());
                             ui.label(format!("({})", agent.agent_type));
+                            if agent.sandbox.enabled && agent.agent_type != AgentType::Ollama {
+                                ui.label("🔒").on_hover_text(tr!("agent-sandboxed", sandbox = agent.sandbox.summary()));
+                            }
 
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                if ui.button("Delete").clicked() {
+                                if ui.button(tr!("settings-agent-delete")).clicked() {
                                     to_remove = Some(agent.id);
                                 }
 
-                                if ui.button("Connect").clicked() {
-                                    app.active_agents
-                                        .insert(agent.id, agent.name.clone());
+                                if ui.button(tr!("settings-agent-connect")).clicked() {
+                                    connect = Some(agent.clone());
                                 }
                             });
                         });
 
-                        ui.label(format!(
-                            "Config: {}",
-                            serde_json::to_string_pretty(&agent.config_data)
-                                .unwrap_or_default()
-                        ));
+                        if CodingCli::for_agent_type(&agent.agent_type).is_some() {
+                            settings_changed |= coding_cli_settings(ui, agent);
+                        } else {
+                            ui.label(tr!(
+                                "settings-agent-config",
+                                config = serde_json::to_string_pretty(&agent.config_data).unwrap_or_default()
+                            ));
+                        }
+
+                        // Coding CLIs run their own tools and ask in their own terminal
+                        if agent.agent_type == AgentType::Process {
+                            settings_changed |=
+                                approval::approval_policy_settings(ui, agent.id, &mut app.config.approvals);
+                        }
+
+                        egui::CollapsingHeader::new(tr!("settings-agent-context", count = agent.context.memory.len()))
+                            .id_salt(("context", agent.id))
+                            .show(ui, |ui| {
+                                if context_settings(ui, agent) {
+                                    context_changed.push(agent.id);
+                                    settings_changed = true;
+                                }
+                            });
+
+                        // Ollama agents are reached over HTTP, there's no process to confine
+                        if agent.agent_type != AgentType::Ollama {
+                            egui::CollapsingHeader::new(tr!("settings-agent-sandbox")).id_salt(("sandbox", agent.id)).show(ui, |ui| {
+                                settings_changed |= sandbox_settings(ui, agent.id, &mut agent.sandbox);
+                            });
+                        }
                     });
 
                     ui.add_space(8.0);
                 }
+            });
+        });
 
-                if let Some(id) = to_remove {
-                    app.config.remove_agent(&id);
-                    app.active_agents.remove(&id);
-                    let _ = app.config.save();
+    if let Some(enabled) = pipelines_toggled {
+        app.set_pipelines_enabled(ctx, enabled);
+    }
+    if let Some(enabled) = clipboard_toggled {
+        app.set_clipboard_enabled(ctx, enabled);
+    }
+    if let Some(chat) = unannounce {
+        app.set_announce(ctx, &chat, false);
+    }
+    if restart_mcp {
+        app.apply_mcp_server_config(ctx);
+    }
+    if restart_voice {
+        app.apply_voice_config(ctx);
+    }
+    if restyle {
+        super::appearance::apply(ctx, &app.config.appearance);
+    }
+    if restart_monitor {
+        app.apply_monitor_config(ctx);
+    } else if let Some(monitor) = app.monitor.as_ref().filter(|_| settings_changed) {
+        monitor.set_config(&app.config.monitor);
+    }
+    if settings_changed {
+        app.approvals.set_config(app.config.approvals.clone());
+        if let Err(e) = app.config.save() {
+            log::error!("Failed to save agent settings: {}", e);
+        }
+    }
+    for id in context_changed {
+        app.apply_agent_context(id);
+    }
+    if let Some(agent) = connect {
+        app.connect_agent(ctx, &agent);
+    }
+    if let Some(id) = to_remove {
+        app.config.remove_agent(&id);
+        app.disconnect_agent(&id);
+        let _ = app.config.save();
+    }
+    if run_setup {
+        app.onboarding = Some(OnboardingState::new(&app.config));
+        app.show_config_panel = false;
+    }
+}
+
+/// Model and workspace fields for a coding CLI agent; returns whether they changed.
+/// Changes apply the next time the agent is connected.
+fn coding_cli_settings(ui: &mut egui::Ui, agent: &mut AgentConfig) -> bool {
+    let mut settings: CodingCliConfig = serde_json::from_value(agent.config_data.clone()).unwrap_or_default();
+    let mut extra_args = settings.extra_args.join(" ");
+    let mut changed = false;
+
+    egui::Grid::new(("coding_cli", agent.id)).num_columns(2).show(ui, |ui| {
+        ui.label(tr!("settings-cli-model"));
+        changed |= ui
+            .add(egui::TextEdit::singleline(&mut settings.model).hint_text(tr!("settings-cli-model-default")))
+            .changed();
+        ui.end_row();
+
+        ui.label(tr!("settings-cli-workspace"));
+        changed |= ui.text_edit_singleline(&mut settings.workspace).changed();
+        ui.end_row();
+
+        ui.label(tr!("settings-cli-extra-args"));
+        if ui.text_edit_singleline(&mut extra_args).changed() {
+            settings.extra_args = extra_args.split_whitespace().map(str::to_string).collect();
+            changed = true;
+        }
+        ui.end_row();
+    });
+
+    if changed {
+        agent.config_data = serde_json::to_value(&settings).unwrap_or_default();
+    }
+    changed
+}
+
+/// An agent's system prompt and memory; returns whether they changed. A running LLM
+/// agent uses changes from its next message, other agents when they read their context
+/// file again.
+fn context_settings(ui: &mut egui::Ui, agent: &mut AgentConfig) -> bool {
+    let context = &mut agent.context;
+    let mut changed = ui
+        .add(
+            egui::TextEdit::multiline(&mut context.system_prompt)
+                .hint_text(tr!("settings-context-prompt-hint"))
+                .desired_rows(3)
+                .desired_width(f32::INFINITY),
+        )
+        .changed();
+
+    let mut forget = None;
+    for (index, entry) in context.memory.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            if ui.small_button("✖").on_hover_text(tr!("settings-context-forget")).clicked() {
+                forget = Some(index);
+            }
+            let added = entry.added_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut entry.text).desired_width(f32::INFINITY))
+                .on_hover_text(tr!("settings-context-since", time = added.to_string()))
+                .changed();
+        });
+    }
+    if let Some(index) = forget {
+        context.memory.remove(index);
+        changed = true;
+    }
+
+    // The fact being typed, kept between frames
+    let id = ui.make_persistent_id(("new_memory", agent.id));
+    let mut text = ui.data_mut(|data| data.get_temp::<String>(id)).unwrap_or_default();
+    ui.horizontal(|ui| {
+        let response = ui.add(egui::TextEdit::singleline(&mut text).hint_text(tr!("settings-context-new")));
+        let entered = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+        if (ui.button(tr!("settings-context-remember")).clicked() || entered) && context.remember(&text) {
+            text.clear();
+            changed = true;
+        }
+        if ui
+            .add_enabled(!context.memory.is_empty(), egui::Button::new(tr!("settings-context-clear")))
+            .on_hover_text(tr!("settings-context-clear-hint"))
+            .clicked()
+        {
+            context.memory.clear();
+            changed = true;
+        }
+    });
+    ui.data_mut(|data| data.insert_temp(id, text));
+    changed
+}
+
+/// Sandbox options for agents and the Term tab; returns whether they changed.
+/// Changes apply the next time the process starts.
+fn sandbox_settings(ui: &mut egui::Ui, id_source: impl std::hash::Hash, sandbox: &mut SandboxConfig) -> bool {
+    let mut changed = ui
+        .checkbox(&mut sandbox.enabled, tr!("settings-sandbox-enabled"))
+        .on_hover_text(tr!("settings-sandbox-enabled-hint"))
+        .changed();
+
+    ui.add_enabled_ui(sandbox.enabled, |ui| {
+        let id = ui.make_persistent_id(id_source);
+        egui::ComboBox::from_id_salt(id.with("backend"))
+            .selected_text(sandbox.backend.label())
+            .show_ui(ui, |ui| {
+                for backend in SandboxBackend::ALL {
+                    changed |= ui.selectable_value(&mut sandbox.backend, backend, backend.label()).changed();
                 }
             });
+
+        let wrapped = sandbox.backend != SandboxBackend::Environment;
+        ui.add_enabled_ui(wrapped, |ui| {
+            changed |= ui
+                .checkbox(&mut sandbox.confine_to_workspace, tr!("settings-sandbox-workspace"))
+                .changed();
+            changed |= ui.checkbox(&mut sandbox.network, tr!("settings-sandbox-network")).changed();
+            egui::Grid::new(id.with("paths")).num_columns(2).show(ui, |ui| {
+                ui.label(tr!("settings-sandbox-writable"));
+                changed |= word_list(ui, &mut sandbox.writable_paths);
+                ui.end_row();
+                ui.label(tr!("settings-sandbox-read-only"));
+                changed |= word_list(ui, &mut sandbox.read_only_paths);
+                ui.end_row();
+            });
         });
+        if !wrapped {
+            ui.label(tr!("settings-sandbox-env-only"));
+        }
+
+        changed |= ui
+            .checkbox(&mut sandbox.clean_env, tr!("settings-sandbox-clean-env"))
+            .on_hover_text(tr!("settings-sandbox-clean-env-hint"))
+            .changed();
+        ui.add_enabled_ui(sandbox.clean_env, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("settings-sandbox-keep-env"));
+                changed |= word_list(ui, &mut sandbox.env_allow);
+            });
+        });
+    });
+    changed
+}
+
+/// A space-separated list in one line
+fn word_list(ui: &mut egui::Ui, items: &mut Vec<String>) -> bool {
+    let mut text = items.join(" ");
+    let changed = ui.text_edit_singleline(&mut text).changed();
+    if changed {
+        *items = text.split_whitespace().map(str::to_string).collect();
+    }
+    changed
 }
@@ -0,0 +1,69 @@
+use crate::crash;
+use crate::i18n::tr;
+use crate::ui::app::DashboardApp;
+
+/// Tell the user about a crash report from an earlier run and offer to file it
+pub fn show_crash_prompt(ctx: &egui::Context, app: &mut DashboardApp) {
+    // One dialog at a time; the session prompt comes first
+    if app.session_recovery.is_some() {
+        return;
+    }
+    let Some(bundle) = app.crash_reports.first() else {
+        return;
+    };
+
+    let mut done = false;
+    egui::Window::new(tr!("crash-title"))
+        .id(egui::Id::new("crash_report"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .default_width(460.0)
+        .show(ctx, |ui| {
+            let report = &bundle.report;
+            let at = report.at.with_timezone(&chrono::Local);
+            ui.label(tr!("crash-body", time = at.format("%Y-%m-%d %H:%M").to_string()));
+            ui.add_space(4.0);
+            ui.label(egui::RichText::new(&report.message).strong().monospace());
+            if let Some(location) = &report.location {
+                ui.label(egui::RichText::new(location).size(11.0).monospace());
+            }
+            if app.crash_reports.len() > 1 {
+                ui.label(tr!("crash-more", count = app.crash_reports.len() - 1));
+            }
+            ui.label(
+                egui::RichText::new(tr!("crash-privacy", path = bundle.dir.display().to_string()))
+                    .size(11.0)
+                    .color(egui::Color32::GRAY),
+            );
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr!("crash-open-issue")).on_hover_text(tr!("crash-open-issue-hint")).clicked() {
+                    ctx.copy_text(report.markdown());
+                    ctx.open_url(egui::OpenUrl::new_tab(report.issue_url()));
+                    if let Err(e) = crash::open_folder(&bundle.dir) {
+                        log::warn!("Failed to open the crash report folder: {}", e);
+                    }
+                    done = true;
+                }
+                if ui.button(tr!("crash-show-folder")).clicked() {
+                    if let Err(e) = crash::open_folder(&bundle.dir) {
+                        app.toasts.warning(e.to_string());
+                    }
+                }
+                if ui.button(tr!("crash-dismiss")).clicked() {
+                    done = true;
+                }
+            });
+        });
+
+    if !done {
+        return;
+    }
+    // Later crashes usually repeat the first, so dealing with one deals with all
+    for bundle in app.crash_reports.drain(..) {
+        if let Err(e) = bundle.mark_reviewed() {
+            log::warn!("Failed to mark crash report {} as seen: {}", bundle.dir.display(), e);
+        }
+    }
+}
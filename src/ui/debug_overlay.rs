@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Frames counted towards the rates shown
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Frame rate and UI thread time, toggled with F12. Frames only happen on input or a
+/// repaint request, so an idle dashboard should show close to 0 fps here.
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    /// When each recent frame started and how long its update took, in seconds
+    frames: VecDeque<(Instant, f32)>,
+    /// Why the latest frames were painted
+    causes: Vec<String>,
+}
+
+impl DebugOverlay {
+    /// Count the frame being drawn; `cpu_usage` is eframe's time for the previous one
+    pub fn record(&mut self, ctx: &egui::Context, cpu_usage: Option<f32>) {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F12)) {
+            self.visible = !self.visible;
+        }
+
+        let now = Instant::now();
+        self.frames.push_back((now, cpu_usage.unwrap_or(0.0)));
+        while self.frames.front().is_some_and(|(at, _)| now.duration_since(*at) > WINDOW) {
+            self.frames.pop_front();
+        }
+        if self.visible {
+            self.causes = ctx.repaint_causes().iter().map(ToString::to_string).collect();
+        }
+    }
+
+    pub fn show(&self, ctx: &egui::Context) {
+        if !self.visible {
+            return;
+        }
+
+        let fps = self.frames.len() as f32 / WINDOW.as_secs_f32();
+        let busy: f32 = self.frames.iter().map(|(_, cpu)| cpu).sum();
+        let last = self.frames.back().map_or(0.0, |(_, cpu)| *cpu);
+
+        egui::Area::new(egui::Id::new("debug_overlay"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 40.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let text = |s: String| egui::RichText::new(s).monospace().size(11.0);
+                    ui.label(text(format!("{:>5.1} fps", fps)));
+                    ui.label(text(format!("{:>5.1} ms last frame", last * 1000.0)));
+                    ui.label(text(format!("{:>5.1} % UI thread busy", busy / WINDOW.as_secs_f32() * 100.0)));
+                    for cause in &self.causes {
+                        ui.label(text(format!("repaint: {}", cause)).color(egui::Color32::GRAY));
+                    }
+                });
+            });
+    }
+}
+
+/// Average headless frame time, tessellation included, after two warm-up frames. For
+/// benchmarks of how long a view takes to draw.
+#[cfg(test)]
+pub fn frame_time(mut content: impl FnMut(&mut egui::Ui, usize)) -> std::time::Duration {
+    const FRAMES: u32 = 20;
+    let ctx = egui::Context::default();
+    let mut run = |frame: usize| {
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1280.0, 800.0))),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| content(ui, frame));
+        });
+        ctx.tessellate(output.shapes, output.pixels_per_point);
+    };
+
+    run(0);
+    run(1);
+    let started = std::time::Instant::now();
+    for frame in 2..2 + FRAMES as usize {
+        run(frame);
+    }
+    started.elapsed() / FRAMES
+}
@@ -0,0 +1,383 @@
+use crate::i18n::{self, tr};
+use crate::ui::app::DashboardApp;
+use serde::Deserialize;
+use std::sync::mpsc;
+
+/// A person as `chat identities list --format json` gives them
+#[derive(Debug, Clone, Deserialize)]
+pub struct Person {
+    pub name: String,
+    /// `platform:id`
+    #[serde(default)]
+    pub accounts: Vec<String>,
+}
+
+impl Person {
+    fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || self.name.to_lowercase().contains(&filter)
+            || self.accounts.iter().any(|account| account.to_lowercase().contains(&filter))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RowError {
+    line: usize,
+    reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Conflict {
+    line: usize,
+    account: String,
+    linked_to: String,
+    wanted: String,
+}
+
+/// Result of `chat identities import --format json`
+#[derive(Debug, Clone, Deserialize)]
+struct ImportReport {
+    added: usize,
+    unchanged: usize,
+    #[serde(default)]
+    moved: usize,
+    #[serde(default)]
+    invalid: Vec<RowError>,
+    #[serde(default)]
+    conflicts: Vec<Conflict>,
+}
+
+impl ImportReport {
+    fn summary(&self, dry_run: bool) -> String {
+        let id = if dry_run { "identities-import-checked" } else { "identities-import-done" };
+        i18n::translate_args(
+            id,
+            &[
+                ("added", self.added.into()),
+                ("unchanged", self.unchanged.into()),
+                ("moved", self.moved.into()),
+                ("conflicts", self.conflicts.len().into()),
+                ("invalid", self.invalid.len().into()),
+            ],
+        )
+    }
+}
+
+/// What a finished chat CLI call reports
+enum Outcome {
+    Edited,
+    Imported { report: ImportReport, dry_run: bool },
+}
+
+/// State of the Identities window: people and their accounts on each platform, kept
+/// by the chat CLI in `~/.config/chat/identities.json`
+#[derive(Default)]
+pub struct IdentitiesState {
+    people: Vec<Person>,
+    pub filter: String,
+    /// A new link: the person and the `platform:id` account
+    new_name: String,
+    new_account: String,
+    /// Person being renamed, and the new name
+    renaming: Option<(String, String)>,
+    import_path: String,
+    /// Move conflicting accounts to the person named last
+    force: bool,
+    loading: Option<mpsc::Receiver<Result<Vec<Person>, String>>>,
+    running: Option<mpsc::Receiver<Result<Outcome, String>>>,
+    error: Option<String>,
+    /// Report of the last import, kept to show its conflicts
+    import: Option<(ImportReport, bool)>,
+}
+
+impl IdentitiesState {
+    /// Fetch the identities in a background thread
+    pub fn load(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        self.loading = Some(rx);
+        std::thread::spawn(move || {
+            let _ = tx.send(fetch_people());
+        });
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        !self.people.is_empty() || self.loading.is_some()
+    }
+
+    fn busy(&self) -> bool {
+        self.loading.is_some() || self.running.is_some()
+    }
+
+    /// Run `chat identities edit <name> <args>`
+    fn edit(&mut self, name: &str, args: &[&str]) {
+        let mut command = vec!["identities".to_string(), "edit".to_string(), name.to_string()];
+        command.extend(args.iter().map(|arg| arg.to_string()));
+        if self.force {
+            command.push("--force".to_string());
+        }
+        self.run(command, None);
+    }
+
+    fn import(&mut self, dry_run: bool) {
+        let mut command = vec![
+            "identities".to_string(),
+            "import".to_string(),
+            self.import_path.trim().to_string(),
+            "--format".to_string(),
+            "json".to_string(),
+        ];
+        if self.force {
+            command.push("--force".to_string());
+        }
+        if dry_run {
+            command.push("--dry-run".to_string());
+        }
+        self.run(command, Some(dry_run));
+    }
+
+    /// Run the chat CLI in a background thread; `import` says it is an import, and
+    /// whether a dry run
+    fn run(&mut self, args: Vec<String>, import: Option<bool>) {
+        let (tx, rx) = mpsc::channel();
+        self.running = Some(rx);
+        self.error = None;
+        std::thread::spawn(move || {
+            let result = run_chat(&args).and_then(|stdout| match import {
+                Some(dry_run) => serde_json::from_str(&stdout)
+                    .map(|report| Outcome::Imported { report, dry_run })
+                    .map_err(|e| format!("Failed to parse the import report: {}", e)),
+                None => Ok(Outcome::Edited),
+            });
+            let _ = tx.send(result);
+        });
+    }
+
+    /// Check background work, returning a message to show when an import finished
+    fn poll(&mut self) -> Option<String> {
+        if let Some(rx) = &self.loading {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(people) => self.people = people,
+                    Err(e) => self.error = Some(e),
+                }
+                self.loading = None;
+            }
+        }
+
+        let result = self.running.as_ref()?.try_recv().ok()?;
+        self.running = None;
+        match result {
+            Ok(Outcome::Edited) => {
+                self.load();
+                None
+            }
+            Ok(Outcome::Imported { report, dry_run }) => {
+                let summary = report.summary(dry_run);
+                self.import = Some((report, dry_run));
+                if !dry_run {
+                    self.load();
+                }
+                Some(summary)
+            }
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+}
+
+fn fetch_people() -> Result<Vec<Person>, String> {
+    let stdout = run_chat(&["identities".into(), "list".into(), "--format".into(), "json".into()])?;
+    serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse identities: {}", e))
+}
+
+fn run_chat(args: &[String]) -> Result<String, String> {
+    let output = std::process::Command::new(crate::config::paths::helper("chat"))
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run chat CLI: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+pub fn show_identities_window(ctx: &egui::Context, app: &mut DashboardApp) {
+    if let Some(summary) = app.identities.poll() {
+        app.toasts.info(summary);
+    }
+    if app.identities.busy() {
+        ctx.request_repaint_after(std::time::Duration::from_millis(200));
+    }
+
+    let state = &mut app.identities;
+    let mut open = app.show_identities;
+    egui::Window::new(tr!("button-identities"))
+        .id(egui::Id::new("identities_window"))
+        .open(&mut open)
+        .default_width(500.0)
+        .default_height(450.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("identities-import-csv"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.import_path)
+                        .hint_text("aliases.csv")
+                        .desired_width(200.0),
+                )
+                .on_hover_text(tr!("identities-import-hint"));
+                let ready = !state.busy() && !state.import_path.trim().is_empty();
+                if ui.add_enabled(ready, egui::Button::new(tr!("identities-check"))).on_hover_text(tr!("identities-check-hint")).clicked() {
+                    state.import(true);
+                }
+                if ui.add_enabled(ready, egui::Button::new(tr!("identities-import"))).clicked() {
+                    state.import(false);
+                }
+            });
+            ui.checkbox(&mut state.force, tr!("identities-force"));
+
+            if let Some((report, dry_run)) = &state.import {
+                ui.label(report.summary(*dry_run));
+                if !report.conflicts.is_empty() || !report.invalid.is_empty() {
+                    egui::ScrollArea::vertical().id_salt("identity_import").max_height(100.0).show(ui, |ui| {
+                        for conflict in &report.conflicts {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                tr!(
+                                    "identities-conflict",
+                                    line = conflict.line,
+                                    account = conflict.account.clone(),
+                                    linked = conflict.linked_to.clone(),
+                                    wanted = conflict.wanted.clone()
+                                ),
+                            );
+                        }
+                        for row in &report.invalid {
+                            ui.colored_label(egui::Color32::RED, tr!("identities-invalid-row", line = row.line, reason = row.reason.clone()));
+                        }
+                    });
+                }
+            }
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut state.new_name).hint_text(tr!("identities-name")).desired_width(120.0));
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.new_account)
+                        .hint_text("platform:id")
+                        .desired_width(180.0),
+                );
+                let ready = !state.busy() && !state.new_name.trim().is_empty() && state.new_account.contains(':');
+                if ui.add_enabled(ready, egui::Button::new(tr!("identities-link"))).clicked() {
+                    let (name, account) = (state.new_name.trim().to_string(), state.new_account.trim().to_string());
+                    state.edit(&name, &["--link", &account]);
+                    state.new_account.clear();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("identities-filter"));
+                ui.add(egui::TextEdit::singleline(&mut state.filter).desired_width(200.0));
+                if ui.add_enabled(!state.busy(), egui::Button::new(tr!("identities-reload"))).clicked() {
+                    state.load();
+                }
+                if state.busy() {
+                    ui.spinner();
+                }
+            });
+            if let Some(error) = &state.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            ui.separator();
+
+            if state.people.is_empty() && state.loading.is_none() {
+                ui.label(egui::RichText::new(tr!("identities-empty")).color(egui::Color32::GRAY));
+            }
+
+            // Edits are collected and run after the list is drawn
+            let mut edit: Option<(String, Vec<String>)> = None;
+            egui::ScrollArea::vertical().id_salt("identities").show(ui, |ui| {
+                for person in state.people.iter().filter(|person| person.matches(&state.filter)) {
+                    ui.horizontal(|ui| {
+                        match &mut state.renaming {
+                            Some((name, new_name)) if *name == person.name => {
+                                let response = ui.add(egui::TextEdit::singleline(new_name).desired_width(150.0));
+                                if ui.button("✔").clicked()
+                                    || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+                                {
+                                    edit = Some((person.name.clone(), vec!["--rename".into(), new_name.clone()]));
+                                }
+                            }
+                            _ => {
+                                ui.strong(&person.name);
+                                if ui.small_button("✏").on_hover_text(tr!("identities-rename-hint")).clicked() {
+                                    state.renaming = Some((person.name.clone(), person.name.clone()));
+                                }
+                            }
+                        }
+                        if ui.small_button("🗑").on_hover_text(tr!("identities-remove-hint")).clicked() {
+                            edit = Some((person.name.clone(), vec!["--remove".into()]));
+                        }
+                        if ui.small_button("+").on_hover_text(tr!("identities-link-hint")).clicked() {
+                            state.new_name = person.name.clone();
+                        }
+                    });
+                    ui.indent(&person.name, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            for account in &person.accounts {
+                                if ui.small_button(format!("{} ✖", account)).on_hover_text(tr!("identities-unlink")).clicked() {
+                                    edit = Some((person.name.clone(), vec!["--unlink".into(), account.clone()]));
+                                }
+                            }
+                        });
+                    });
+                }
+            });
+
+            if let Some((name, args)) = edit {
+                if !state.busy() {
+                    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                    state.edit(&name, &args);
+                    state.renaming = None;
+                }
+            }
+        });
+    app.show_identities = open;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_people_and_filter() {
+        let people: Vec<Person> = serde_json::from_value(serde_json::json!([
+            { "name": "Ana", "accounts": ["matrix:@ana:example.org", "telegram:100"] },
+            { "name": "Ben", "accounts": ["signal:+4917012345"] }
+        ]))
+        .unwrap();
+
+        let names = |filter: &str| -> Vec<&str> {
+            people.iter().filter(|p| p.matches(filter)).map(|p| p.name.as_str()).collect()
+        };
+        assert_eq!(names(""), vec!["Ana", "Ben"]);
+        assert_eq!(names("ben"), vec!["Ben"]);
+        assert_eq!(names(" TELEGRAM "), vec!["Ana"]);
+        assert!(names("whatsapp").is_empty());
+    }
+
+    #[test]
+    fn test_import_report() {
+        let report: ImportReport = serde_json::from_value(serde_json::json!({
+            "added": 2, "unchanged": 1, "moved": 0,
+            "invalid": [{ "line": 8, "reason": "id is empty" }],
+            "conflicts": [{ "line": 6, "account": "telegram:100", "linked_to": "Ana", "wanted": "Ben" }]
+        }))
+        .unwrap();
+        assert_eq!((report.added, report.unchanged, report.moved), (2, 1, 0));
+        assert_eq!(report.conflicts[0].linked_to, "Ana");
+        assert_eq!(report.invalid[0].line, 8);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::i18n::tr;
+use crate::logs::{self, LogEntry, LogFilter};
+use crate::ui::app::DashboardApp;
+use log::{Level, LevelFilter};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How often new records are picked up while the window is open
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// State of the Logs window
+pub struct LogsPanelState {
+    /// Records taken from the log so far, oldest first
+    entries: VecDeque<LogEntry>,
+    next_seq: u64,
+    pub filter: LogFilter,
+    /// Stop taking new records, to read without the list moving
+    pub paused: bool,
+    /// Keep the newest record in view
+    pub follow: bool,
+}
+
+impl Default for LogsPanelState {
+    fn default() -> Self {
+        Self { entries: VecDeque::new(), next_seq: 0, filter: LogFilter::default(), paused: false, follow: true }
+    }
+}
+
+impl LogsPanelState {
+    /// Take the records logged since the last call, unless paused
+    fn poll(&mut self) {
+        if self.paused {
+            return;
+        }
+        let new = logs::entries_since(self.next_seq);
+        if let Some(last) = new.last() {
+            self.next_seq = last.seq + 1;
+        }
+        self.entries.extend(new);
+        while self.entries.len() > logs::MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+}
+
+fn level_color(level: Level) -> egui::Color32 {
+    match level {
+        Level::Error => egui::Color32::from_rgb(205, 49, 49),
+        Level::Warn => egui::Color32::from_rgb(229, 180, 16),
+        Level::Info => egui::Color32::from_rgb(13, 188, 121),
+        Level::Debug => egui::Color32::from_rgb(36, 114, 200),
+        Level::Trace => egui::Color32::GRAY,
+    }
+}
+
+fn level_picker(ui: &mut egui::Ui, id: &str, level: &mut LevelFilter) -> bool {
+    let before = *level;
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(level.as_str())
+        .width(70.0)
+        .show_ui(ui, |ui| {
+            for option in LevelFilter::iter().skip(1) {
+                ui.selectable_value(level, option, option.as_str());
+            }
+        });
+    *level != before
+}
+
+pub fn show_logs_panel(ctx: &egui::Context, app: &mut DashboardApp) {
+    let state = &mut app.logs_panel;
+    state.poll();
+    if !state.paused {
+        ctx.request_repaint_after(REFRESH_INTERVAL);
+    }
+
+    let mut export = false;
+    egui::Window::new(tr!("button-logs"))
+        .id(egui::Id::new("logs_window"))
+        .open(&mut app.show_logs_panel)
+        .default_width(800.0)
+        .default_height(450.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("logs-show"));
+                level_picker(ui, "logs_show_level", &mut state.filter.level);
+
+                ui.label(tr!("logs-record"));
+                let mut capture = logs::capture_level();
+                if level_picker(ui, "logs_capture_level", &mut capture) {
+                    logs::set_capture_level(capture);
+                }
+                ui.label("ⓘ").on_hover_text(tr!("logs-record-hint"));
+
+                ui.separator();
+                let pause = if state.paused { tr!("logs-resume") } else { tr!("logs-pause") };
+                if ui.button(pause).clicked() {
+                    state.paused = !state.paused;
+                }
+                if ui.button(tr!("logs-clear")).clicked() {
+                    state.entries.clear();
+                }
+                if ui.button(tr!("logs-export")).on_hover_text(tr!("logs-export-hint")).clicked() {
+                    export = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.filter.modules)
+                        .hint_text(tr!("logs-modules"))
+                        .desired_width(260.0),
+                )
+                .on_hover_text(tr!("logs-modules-hint"));
+                ui.add(egui::TextEdit::singleline(&mut state.filter.search).hint_text(tr!("logs-search")).desired_width(200.0));
+                ui.checkbox(&mut state.follow, tr!("logs-follow"));
+            });
+            ui.separator();
+
+            let shown: Vec<&LogEntry> = state.entries.iter().filter(|entry| state.filter.matches(entry)).collect();
+            ui.label(
+                egui::RichText::new(tr!("logs-count", shown = shown.len(), total = state.entries.len()))
+                    .size(11.0)
+                    .color(egui::Color32::GRAY),
+            );
+
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            egui::ScrollArea::both()
+                .auto_shrink([false, false])
+                .stick_to_bottom(state.follow)
+                .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                    for entry in &shown[rows] {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(entry.at.format("%H:%M:%S%.3f").to_string())
+                                    .monospace()
+                                    .color(egui::Color32::GRAY),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!("{:<5}", entry.level))
+                                    .monospace()
+                                    .color(level_color(entry.level)),
+                            );
+                            ui.label(egui::RichText::new(&entry.target).monospace().color(egui::Color32::GRAY));
+                            ui.add(egui::Label::new(egui::RichText::new(&entry.message).monospace()).extend());
+                        });
+                    }
+                });
+        });
+
+    if export {
+        let state = &app.logs_panel;
+        let shown = state.entries.iter().filter(|entry| state.filter.matches(entry));
+        let result = logs::export_dir().and_then(|dir| {
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(format!("dashboard-{}.log", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+            Ok((logs::export(shown, &path)?, path))
+        });
+        match result {
+            Ok((count, path)) => app.toasts.info(tr!("logs-exported", count = count, path = path.display().to_string())),
+            Err(e) => app.toasts.warning(tr!("logs-export-failed", error = e.to_string())),
+        }
+    }
+}
@@ -1,6 +1,31 @@
 pub mod app;
+pub mod appearance;
+pub mod approval;
+pub mod assistant;
+pub mod blackboard_panel;
 pub mod chat;
+pub mod chats_tab;
 pub mod config_panel;
+pub mod crash;
+pub mod debug_overlay;
+pub mod identities;
+pub mod logs_panel;
+pub mod notes;
+pub mod onboarding;
+pub mod plans_panel;
+pub mod resources;
+pub mod scripts_panel;
+pub mod session;
+pub mod shortcuts;
 pub mod sidebar;
+pub mod snippets;
+pub mod status_bar;
+pub mod telegram_login;
+pub mod terminal;
+pub mod terminal_output;
+pub mod toasts;
+pub mod triage;
+pub mod updates;
+pub mod workspace;
 
 pub use app::DashboardApp;
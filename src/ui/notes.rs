@@ -0,0 +1,202 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+
+/// A note as the chat CLI stores it (`~/.config/chat/notes.json`)
+#[derive(Debug, Clone, Default, Deserialize)]
+struct StoredNote {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+}
+
+/// Notes file written by `chat note`, keyed like the CLI: `source:chat_id` and `source:user_id`
+#[derive(Debug, Default, Deserialize)]
+struct StoredNotes {
+    #[serde(default)]
+    chats: BTreeMap<String, StoredNote>,
+    #[serde(default)]
+    users: BTreeMap<String, StoredNote>,
+}
+
+impl StoredNotes {
+    fn load() -> Self {
+        let Some(path) = dirs::config_dir().map(|d| d.join("chat").join("notes.json")) else {
+            return Self::default();
+        };
+
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// What a note is about
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoteTarget {
+    /// `source:chat_id`
+    Chat(String),
+    /// `source:user_id`, with the name the chat shows for them
+    Person { id: String, name: String },
+}
+
+/// Editing the note on a chat or person; saving goes through `chat note set` so the
+/// CLI stays the only writer of its notes file
+pub struct NoteEditor {
+    pub target: NoteTarget,
+    name: String,
+    text: String,
+    fields: Vec<(String, String)>,
+    /// Fields the note had when opened, so ones deleted here are cleared on save
+    stored_fields: Vec<String>,
+    saving: Option<mpsc::Receiver<Result<(), String>>>,
+    error: Option<String>,
+    dirty: bool,
+}
+
+impl NoteEditor {
+    pub fn open(target: NoteTarget) -> Self {
+        let notes = StoredNotes::load();
+        let stored = match &target {
+            NoteTarget::Chat(id) => notes.chats.get(id),
+            NoteTarget::Person { id, .. } => notes.users.get(id),
+        }
+        .cloned()
+        .unwrap_or_default();
+
+        let name = match &target {
+            NoteTarget::Person { name, .. } => stored.name.clone().unwrap_or_else(|| name.clone()),
+            NoteTarget::Chat(_) => String::new(),
+        };
+        Self {
+            target,
+            name,
+            text: stored.text,
+            stored_fields: stored.fields.keys().cloned().collect(),
+            fields: stored.fields.into_iter().collect(),
+            saving: None,
+            error: None,
+            dirty: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.fields.is_empty()
+    }
+
+    /// Arguments for `chat note set` that store what the editor shows
+    fn set_args(&self) -> Vec<String> {
+        let mut args = vec!["note".to_string(), "set".to_string()];
+        match &self.target {
+            NoteTarget::Chat(id) => args.push(id.clone()),
+            NoteTarget::Person { id, .. } => {
+                args.extend([id.clone(), "--user".to_string(), "--name".to_string(), self.name.trim().to_string()]);
+            }
+        }
+        args.extend(["--text".to_string(), self.text.clone()]);
+
+        let kept: Vec<&str> = self
+            .fields
+            .iter()
+            .filter(|(key, _)| !key.trim().is_empty())
+            .map(|(key, _)| key.trim())
+            .collect();
+        for (key, value) in self.fields.iter().filter(|(key, _)| !key.trim().is_empty()) {
+            args.extend(["--field".to_string(), format!("{}={}", key.trim(), value)]);
+        }
+        for key in self.stored_fields.iter().filter(|key| !kept.contains(&key.as_str())) {
+            args.extend(["--field".to_string(), format!("{}=", key)]);
+        }
+        args
+    }
+
+    fn save(&mut self) {
+        let args = self.set_args();
+        let (tx, rx) = mpsc::channel();
+        self.saving = Some(rx);
+        self.error = None;
+
+        std::thread::spawn(move || {
+            let result = std::process::Command::new(crate::config::paths::helper("chat"))
+                .args(&args)
+                .output()
+                .map_err(|e| format!("Failed to run chat CLI: {}", e))
+                .and_then(|output| {
+                    if output.status.success() {
+                        Ok(())
+                    } else {
+                        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+                    }
+                });
+            let _ = tx.send(result);
+        });
+    }
+
+    fn poll(&mut self) {
+        if let Some(rx) = &self.saving {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(()) => {
+                        self.dirty = false;
+                        self.stored_fields = self.fields.iter().map(|(key, _)| key.trim().to_string()).collect();
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                self.saving = None;
+            }
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        self.poll();
+
+        if let NoteTarget::Person { id, .. } = &self.target {
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                self.dirty |= ui.text_edit_singleline(&mut self.name).on_hover_text(id.as_str()).changed();
+            });
+        }
+        self.dirty |= ui
+            .add(
+                egui::TextEdit::multiline(&mut self.text)
+                    .hint_text("Anything worth remembering, also given to agents asking about this")
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY),
+            )
+            .changed();
+
+        let mut remove = None;
+        egui::Grid::new("note_fields").num_columns(3).show(ui, |ui| {
+            for (index, (key, value)) in self.fields.iter_mut().enumerate() {
+                self.dirty |= ui.add(egui::TextEdit::singleline(key).hint_text("field").desired_width(90.0)).changed();
+                self.dirty |= ui.add(egui::TextEdit::singleline(value).desired_width(140.0)).changed();
+                if ui.small_button("✖").on_hover_text("Remove field").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = remove {
+            self.fields.remove(index);
+            self.dirty = true;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.small_button("+ Field").on_hover_text("e.g. birthday, project").clicked() {
+                self.fields.push((String::new(), String::new()));
+            }
+            if self.saving.is_some() {
+                ui.spinner();
+            } else if ui.add_enabled(self.dirty, egui::Button::new("Save")).clicked() {
+                self.save();
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::from_rgb(255, 80, 80), error);
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use crate::config::AppConfig;
+use crate::i18n::tr;
+use crate::onboarding::{self, AgentDraft, Setup, TEST_PHRASE};
+use crate::telegram;
+use crate::tts::{TTSConfig, TTSRequest, TTSService};
+use crate::ui::app::DashboardApp;
+use crate::ui::telegram_login::TelegramLoginPanel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Welcome,
+    Telegram,
+    Voice,
+    Agent,
+    Finish,
+}
+
+/// Progress through the first-run setup wizard
+pub struct OnboardingState {
+    step: Step,
+    telegram: TelegramLoginPanel,
+    skip_telegram: bool,
+    tts: TTSConfig,
+    voices: Vec<String>,
+    agent: AgentDraft,
+    skip_agent: bool,
+    error: Option<String>,
+}
+
+impl OnboardingState {
+    pub fn new(config: &AppConfig) -> Self {
+        let tts = TTSConfig {
+            enabled: true,
+            auto_speak: true,
+            ..config.tts.clone()
+        };
+        Self {
+            step: Step::Welcome,
+            telegram: TelegramLoginPanel::default(),
+            skip_telegram: false,
+            voices: onboarding::list_voices(&tts.model_directory),
+            tts,
+            agent: AgentDraft::default(),
+            skip_agent: false,
+            error: None,
+        }
+    }
+
+    fn setup(&self) -> Setup {
+        Setup {
+            telegram: (!self.skip_telegram).then(|| self.telegram.credentials.clone()),
+            tts: self.tts.clone(),
+            agent: (!self.skip_agent).then(|| self.agent.clone()),
+        }
+    }
+}
+
+enum WizardAction {
+    Finish(Box<Setup>),
+    Skip,
+}
+
+pub fn show_onboarding(ctx: &egui::Context, app: &mut DashboardApp) {
+    let Some(state) = app.onboarding.as_mut() else {
+        return;
+    };
+
+    let mut action = None;
+
+    egui::Window::new(tr!("onboarding-title"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .default_width(480.0)
+        .show(ctx, |ui| {
+            match state.step {
+                Step::Welcome => show_welcome(ui),
+                Step::Telegram => show_telegram(ui, state),
+                Step::Voice => show_voice(ui, state),
+                Step::Agent => show_agent(ui, state),
+                Step::Finish => show_finish(ui, state),
+            }
+
+            if let Some(error) = &state.error {
+                ui.add_space(4.0);
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            ui.add_space(8.0);
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if state.step == Step::Welcome {
+                    if ui.button(tr!("onboarding-skip-setup")).clicked() {
+                        action = Some(WizardAction::Skip);
+                    }
+                } else if ui.button(tr!("onboarding-back")).clicked() {
+                    state.error = None;
+                    state.step = match state.step {
+                        Step::Telegram => Step::Welcome,
+                        Step::Voice => Step::Telegram,
+                        Step::Agent => Step::Voice,
+                        _ => Step::Agent,
+                    };
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if state.step == Step::Finish {
+                        if ui.button(tr!("onboarding-finish")).clicked() {
+                            action = Some(WizardAction::Finish(Box::new(state.setup())));
+                        }
+                    } else if ui.button(tr!("onboarding-next")).clicked() {
+                        next_step(state);
+                    }
+                });
+            });
+        });
+
+    match action {
+        Some(WizardAction::Finish(setup)) => app.finish_onboarding(ctx, *setup),
+        Some(WizardAction::Skip) => app.skip_onboarding(),
+        None => {}
+    }
+}
+
+/// Move on if the current step is complete, otherwise explain what's missing
+fn next_step(state: &mut OnboardingState) {
+    state.error = None;
+    state.step = match state.step {
+        Step::Welcome => Step::Telegram,
+        Step::Telegram if state.skip_telegram || state.telegram.signed_in() => Step::Voice,
+        Step::Telegram => {
+            state.error = Some(tr!("onboarding-telegram-required"));
+            Step::Telegram
+        }
+        Step::Voice => Step::Agent,
+        Step::Agent => match state.agent.validate() {
+            Err(e) if !state.skip_agent => {
+                state.error = Some(e);
+                Step::Agent
+            }
+            _ => Step::Finish,
+        },
+        Step::Finish => Step::Finish,
+    };
+}
+
+fn show_welcome(ui: &mut egui::Ui) {
+    ui.heading(tr!("onboarding-welcome"));
+    ui.add_space(4.0);
+    ui.label(tr!("onboarding-welcome-intro"));
+    ui.label(tr!("onboarding-welcome-telegram"));
+    ui.label(tr!("onboarding-welcome-voice"));
+    ui.label(tr!("onboarding-welcome-agent"));
+    ui.add_space(4.0);
+    ui.label(tr!("onboarding-welcome-saving"));
+}
+
+fn show_telegram(ui: &mut egui::Ui, state: &mut OnboardingState) {
+    ui.heading(tr!("onboarding-telegram"));
+    ui.checkbox(&mut state.skip_telegram, tr!("onboarding-skip-telegram"));
+    if !state.skip_telegram {
+        state.telegram.show(ui);
+    }
+}
+
+fn show_voice(ui: &mut egui::Ui, state: &mut OnboardingState) {
+    ui.heading(tr!("onboarding-voice"));
+    ui.checkbox(&mut state.tts.enabled, tr!("onboarding-read-aloud"));
+    if !state.tts.enabled {
+        return;
+    }
+    state.tts.auto_speak = true;
+
+    egui::ComboBox::from_label(tr!("onboarding-voice"))
+        .selected_text(state.tts.selected_voice.clone())
+        .show_ui(ui, |ui| {
+            for voice in &state.voices {
+                ui.selectable_value(&mut state.tts.selected_voice, voice.clone(), voice);
+            }
+        });
+    ui.add(egui::Slider::new(&mut state.tts.playback_speed, 0.5..=2.0).text(tr!("onboarding-speed")));
+
+    if ui.button(tr!("onboarding-test-voice")).clicked() {
+        match TTSService::start(state.tts.clone(), ui.ctx().clone()) {
+            Ok(service) => {
+                let request = TTSRequest::new(
+                    TEST_PHRASE.to_string(),
+                    state.tts.selected_voice.clone(),
+                    state.tts.playback_speed,
+                );
+                tokio::spawn(async move {
+                    if let Err(e) = service.speak(request).await {
+                        log::error!("TTS speak error: {}", e);
+                    }
+                });
+            }
+            Err(e) => state.error = Some(tr!("onboarding-tts-failed", error = e.to_string())),
+        }
+    }
+
+    ui.add_space(4.0);
+    ui.label(tr!(
+        "onboarding-voices-location",
+        path = state.tts.model_directory.display().to_string()
+    ));
+}
+
+fn show_agent(ui: &mut egui::Ui, state: &mut OnboardingState) {
+    ui.heading(tr!("onboarding-agent"));
+    ui.checkbox(&mut state.skip_agent, tr!("onboarding-skip-agent"));
+    if state.skip_agent {
+        return;
+    }
+
+    egui::Grid::new("first_agent").num_columns(2).show(ui, |ui| {
+        ui.label(tr!("onboarding-agent-name"));
+        ui.text_edit_singleline(&mut state.agent.name);
+        ui.end_row();
+        ui.label(tr!("onboarding-agent-host"));
+        ui.text_edit_singleline(&mut state.agent.host);
+        ui.end_row();
+        ui.label(tr!("onboarding-agent-model"));
+        ui.text_edit_singleline(&mut state.agent.model);
+        ui.end_row();
+    });
+}
+
+fn show_finish(ui: &mut egui::Ui, state: &OnboardingState) {
+    ui.heading(tr!("onboarding-ready"));
+    ui.label(if state.skip_telegram {
+        tr!("onboarding-summary-telegram-skipped")
+    } else {
+        tr!("onboarding-summary-telegram", phone = state.telegram.credentials.phone.trim().to_string())
+    });
+    ui.label(if state.tts.enabled {
+        tr!(
+            "onboarding-summary-voice",
+            voice = state.tts.selected_voice.clone(),
+            speed = format!("{:.1}", state.tts.playback_speed)
+        )
+    } else {
+        tr!("onboarding-summary-voice-off")
+    });
+    ui.label(if state.skip_agent {
+        tr!("onboarding-summary-agent-skipped")
+    } else {
+        tr!(
+            "onboarding-summary-agent",
+            name = state.agent.name.trim().to_string(),
+            model = state.agent.model.trim().to_string()
+        )
+    });
+
+    ui.add_space(4.0);
+    ui.label(tr!("onboarding-writes"));
+    if !state.skip_telegram {
+        if let Some(path) = telegram::config_file() {
+            ui.monospace(path.display().to_string());
+        }
+    }
+    if let Ok(path) = AppConfig::config_file() {
+        ui.monospace(path.display().to_string());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::i18n::tr;
+use crate::plan::todos::{self, ChatTodos, PLAN_TITLE};
+use crate::plan::types::PlanStepStatus;
+use crate::ui::app::DashboardApp;
+use crate::ui::chat::ChatMessage;
+
+enum PanelAction {
+    ImportTodos,
+    /// Send step `.1` of plan `.0` to the plan's agent
+    Send(usize, usize),
+    Complete(usize, usize),
+}
+
+pub fn show_plans_panel(ctx: &egui::Context, app: &mut DashboardApp) {
+    app.todo_marks.retain(|rx| match rx.try_recv() {
+        Ok(Ok(())) => false,
+        Ok(Err(e)) => {
+            app.last_error = Some(format!("Failed to mark todo done: {}", e));
+            false
+        }
+        Err(std::sync::mpsc::TryRecvError::Empty) => true,
+        Err(std::sync::mpsc::TryRecvError::Disconnected) => false,
+    });
+
+    let mut action = None;
+    egui::SidePanel::right("plans_panel")
+        .resizable(true)
+        .default_width(300.0)
+        .show(ctx, |ui| {
+            ui.heading(tr!("button-plans"));
+            let import = ui
+                .add_enabled(app.selected_agent.is_some(), egui::Button::new(tr!("plans-import-todos")))
+                .on_hover_text(tr!("plans-import-todos-hint"))
+                .on_disabled_hover_text(tr!("plans-select-agent"));
+            if import.clicked() {
+                action = Some(PanelAction::ImportTodos);
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                if app.plans.is_empty() {
+                    ui.label(tr!("plans-empty"));
+                }
+                for (p, plan) in app.plans.iter().enumerate() {
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.strong(&plan.title);
+                            if let Some(agent) = app.active_agents.get(&plan.agent_id) {
+                                ui.weak(agent);
+                            }
+                        });
+                        for (s, step) in plan.steps.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let icon = match step.status {
+                                    PlanStepStatus::Pending => "○",
+                                    PlanStepStatus::InProgress => "◐",
+                                    PlanStepStatus::Completed => "●",
+                                    PlanStepStatus::Failed => "✖",
+                                };
+                                ui.label(icon);
+                                ui.label(&step.description);
+                                if step.todo_id.is_none() || step.status == PlanStepStatus::Completed {
+                                    return;
+                                }
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    if ui.small_button("✔").on_hover_text(tr!("plans-step-done")).clicked() {
+                                        action = Some(PanelAction::Complete(p, s));
+                                    }
+                                    if ui.small_button("▶").on_hover_text(tr!("plans-step-send")).clicked() {
+                                        action = Some(PanelAction::Send(p, s));
+                                    }
+                                });
+                            });
+                        }
+                    });
+                }
+            });
+        });
+
+    match action {
+        Some(PanelAction::ImportTodos) => import_todos(app),
+        Some(PanelAction::Send(p, s)) => send_step(app, p, s),
+        Some(PanelAction::Complete(p, s)) => {
+            let step = &mut app.plans[p].steps[s];
+            step.status = PlanStepStatus::Completed;
+            if let Some(id) = &step.todo_id {
+                app.todo_marks.push(todos::mark_done(id));
+            }
+        }
+        None => {}
+    }
+}
+
+/// Replace the selected agent's chat todos plan with the open todos from the chat CLI
+fn import_todos(app: &mut DashboardApp) {
+    let Some(agent) = app.selected_agent else {
+        return;
+    };
+
+    let previous = app.plans.iter().position(|plan| plan.agent_id == agent && plan.title == PLAN_TITLE);
+    let plan = ChatTodos::load().plan(agent, previous.map(|i| &app.plans[i]));
+    match previous {
+        Some(i) => app.plans[i] = plan,
+        None => app.plans.push(plan),
+    }
+}
+
+/// Hand a step to the plan's agent: the whole todo and its message when the step came
+/// from one, otherwise the step description
+fn send_step(app: &mut DashboardApp, p: usize, s: usize) {
+    let agent = app.plans[p].agent_id;
+    let step = &mut app.plans[p].steps[s];
+    step.status = PlanStepStatus::InProgress;
+    let content = step
+        .todo_id
+        .as_deref()
+        .and_then(|id| ChatTodos::load().get(id).map(|todo| todo.agent_message()))
+        .unwrap_or_else(|| step.description.clone());
+
+    app.deliver_to_agents(&content, Some(agent));
+    app.push_chat_message(ChatMessage::new_user_message(content, Some(agent)));
+}
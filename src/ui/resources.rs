@@ -0,0 +1,48 @@
+use crate::monitor::{ResourceMonitor, Target, Usage, HISTORY_LEN};
+
+const SPARKLINE_SIZE: egui::Vec2 = egui::vec2(60.0, 14.0);
+const MEMORY_COLOR: egui::Color32 = egui::Color32::from_rgb(100, 170, 255);
+const CPU_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 170, 60);
+
+/// CPU and memory sparklines with the latest numbers, under an agent in the agent list
+/// or in the Term tab. Shows nothing until the first sample.
+pub fn usage_row(ui: &mut egui::Ui, monitor: &ResourceMonitor, target: Target, memory_limit_mb: u64) {
+    let history = monitor.history(target);
+    let Some(latest) = history.last() else {
+        return;
+    };
+
+    let over_budget = memory_limit_mb > 0 && latest.memory_mb() > memory_limit_mb as f32;
+    let text_color = if over_budget { egui::Color32::from_rgb(255, 80, 80) } else { egui::Color32::GRAY };
+    ui.horizontal(|ui| {
+        let cpu: Vec<f32> = history.iter().map(|usage| usage.cpu).collect();
+        let memory: Vec<f32> = history.iter().map(Usage::memory_mb).collect();
+        // Scale CPU to at least one core and memory to at least the budget, so a flat
+        // line means "well within" rather than "constant"
+        sparkline(ui, &cpu, 100.0, CPU_COLOR);
+        sparkline(ui, &memory, memory_limit_mb as f32, MEMORY_COLOR);
+        ui.label(egui::RichText::new(latest.summary()).size(11.0).color(text_color));
+    })
+    .response
+    .on_hover_text(format!("Last {} samples of CPU (orange) and memory (blue)", HISTORY_LEN));
+}
+
+fn sparkline(ui: &mut egui::Ui, values: &[f32], min_scale: f32, color: egui::Color32) {
+    let (rect, _) = ui.allocate_exact_size(SPARKLINE_SIZE, egui::Sense::hover());
+    if !ui.is_rect_visible(rect) {
+        return;
+    }
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let scale = values.iter().copied().fold(min_scale, f32::max).max(f32::EPSILON);
+    let step = rect.width() / (HISTORY_LEN - 1) as f32;
+    // Newest sample at the right edge
+    let start = rect.right() - step * (values.len().saturating_sub(1)) as f32;
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| egui::pos2(start + step * i as f32, rect.bottom() - rect.height() * (value / scale)))
+        .collect();
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+}
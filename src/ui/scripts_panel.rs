@@ -0,0 +1,270 @@
+use crate::scripting::{script_name, ScriptHost, ScriptLimits};
+use crate::ui::app::DashboardApp;
+
+/// Template for new scripts
+const NEW_SCRIPT: &str = r#"// Hooks receive an event map and may call:
+//   suppress()        drop the event
+//   set_text(text)    rewrite the event's text
+//   speak(text)       read text aloud
+//   notify(text)      add a notification to the chat panel
+//   log(text)         write to the dashboard log
+
+fn on_message(msg) {
+    // msg: pipeline, route, chat, chat_title, sender, body, text
+}
+
+fn on_alert(alert) {
+    // alert: kind, text, chat, critical
+}
+
+fn on_agent_output(output) {
+    // output: agent, text
+}
+"#;
+
+/// Editor state for the Scripts window
+#[derive(Default)]
+pub struct ScriptsPanelState {
+    /// Script files in the scripts directory, refreshed on demand
+    pub files: Option<Vec<String>>,
+    pub selected: Option<String>,
+    pub source: String,
+    pub limits: ScriptLimits,
+    pub new_name: String,
+    /// Result of the last action, and whether it succeeded
+    pub status: Option<(bool, String)>,
+}
+
+enum PanelAction {
+    Select(String),
+    Create,
+    Save,
+    Check,
+    Delete,
+    Reload,
+}
+
+pub fn show_scripts_panel(ctx: &egui::Context, app: &mut DashboardApp) {
+    if app.scripts_panel.files.is_none() {
+        app.scripts_panel.files = Some(list_scripts());
+    }
+
+    let mut action = None;
+    let state = &mut app.scripts_panel;
+    let host = app.script_host.as_ref();
+
+    egui::Window::new("Scripts")
+        .open(&mut app.show_scripts_panel)
+        .default_width(700.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut state.new_name).hint_text("new-script").desired_width(150.0));
+                if ui.button("New").clicked() {
+                    action = Some(PanelAction::Create);
+                }
+                if ui.button("Reload all").clicked() {
+                    action = Some(PanelAction::Reload);
+                }
+            });
+
+            ui.separator();
+
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(150.0);
+                    let files = state.files.as_deref().unwrap_or_default();
+                    if files.is_empty() {
+                        ui.label("No scripts");
+                    }
+                    for name in files {
+                        let failed = host.is_some_and(|h| h.errors().iter().any(|e| &e.name == name));
+                        let label = if failed { format!("⚠ {}", name) } else { name.clone() };
+                        if ui.selectable_label(state.selected.as_ref() == Some(name), label).clicked() {
+                            action = Some(PanelAction::Select(name.clone()));
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                ui.vertical(|ui| {
+                    let Some(selected) = &state.selected else {
+                        ui.label("Select or create a script");
+                        return;
+                    };
+                    ui.label(egui::RichText::new(format!("{}.rhai", selected)).strong());
+
+                    egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut state.source)
+                                .code_editor()
+                                .desired_rows(20)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Max operations:");
+                        ui.add(egui::DragValue::new(&mut state.limits.max_operations).speed(1000).range(1000..=10_000_000));
+                        ui.label("Timeout (ms):");
+                        ui.add(egui::DragValue::new(&mut state.limits.timeout_ms).range(1..=5000));
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Save").clicked() {
+                            action = Some(PanelAction::Save);
+                        }
+                        if ui.button("Check").clicked() {
+                            action = Some(PanelAction::Check);
+                        }
+                        if ui.button("Delete").clicked() {
+                            action = Some(PanelAction::Delete);
+                        }
+                    });
+
+                    if let Some(error) = host.and_then(|h| h.errors().iter().find(|e| &e.name == selected)) {
+                        ui.colored_label(egui::Color32::RED, &error.error);
+                    }
+                });
+            });
+
+            if let Some((ok, message)) = &state.status {
+                ui.separator();
+                let color = if *ok { egui::Color32::GREEN } else { egui::Color32::RED };
+                ui.colored_label(color, message);
+            }
+        });
+
+    if let Some(action) = action {
+        handle_action(app, action);
+    }
+}
+
+fn handle_action(app: &mut DashboardApp, action: PanelAction) {
+    let dir = match ScriptHost::scripts_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            app.scripts_panel.status = Some((false, e.to_string()));
+            return;
+        }
+    };
+
+    match action {
+        PanelAction::Select(name) => match std::fs::read_to_string(dir.join(format!("{}.rhai", name))) {
+            Ok(source) => {
+                let state = &mut app.scripts_panel;
+                state.source = source;
+                state.limits = app.config.script_limits.get(&name).copied().unwrap_or_default();
+                state.selected = Some(name);
+                state.status = None;
+            }
+            Err(e) => app.scripts_panel.status = Some((false, format!("Failed to read script: {}", e))),
+        },
+        PanelAction::Create => {
+            let name = app.scripts_panel.new_name.trim().to_string();
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                app.scripts_panel.status = Some((false, "Use letters, digits, - and _ for script names".to_string()));
+                return;
+            }
+            let path = dir.join(format!("{}.rhai", name));
+            if path.exists() {
+                app.scripts_panel.status = Some((false, format!("{} already exists", name)));
+                return;
+            }
+
+            let result = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, NEW_SCRIPT));
+            match result {
+                Ok(()) => {
+                    let state = &mut app.scripts_panel;
+                    state.files = Some(list_scripts());
+                    state.source = NEW_SCRIPT.to_string();
+                    state.limits = ScriptLimits::default();
+                    state.selected = Some(name);
+                    state.new_name.clear();
+                    state.status = None;
+                    app.reload_scripts();
+                }
+                Err(e) => app.scripts_panel.status = Some((false, format!("Failed to create script: {}", e))),
+            }
+        }
+        PanelAction::Save => {
+            let Some(name) = app.scripts_panel.selected.clone() else {
+                return;
+            };
+            if let Err(e) = std::fs::write(dir.join(format!("{}.rhai", name)), &app.scripts_panel.source) {
+                app.scripts_panel.status = Some((false, format!("Failed to save script: {}", e)));
+                return;
+            }
+
+            app.config.script_limits.insert(name.clone(), app.scripts_panel.limits);
+            if let Err(e) = app.config.save() {
+                log::error!("Failed to save script limits: {}", e);
+            }
+            app.reload_scripts();
+
+            let error = app
+                .script_host
+                .as_ref()
+                .and_then(|h| h.errors().iter().find(|e| e.name == name))
+                .map(|e| e.error.clone());
+            app.scripts_panel.status = Some(match error {
+                None => (true, format!("Saved and loaded {}", name)),
+                Some(error) => (false, format!("Saved, but not loaded: {}", error)),
+            });
+        }
+        PanelAction::Check => {
+            let result = match &app.script_host {
+                Some(host) => host.check(&app.scripts_panel.source),
+                None => Err("Scripting is unavailable".to_string()),
+            };
+            app.scripts_panel.status = Some(match result {
+                Ok(()) => (true, "No syntax errors".to_string()),
+                Err(e) => (false, e),
+            });
+        }
+        PanelAction::Delete => {
+            let Some(name) = app.scripts_panel.selected.take() else {
+                return;
+            };
+            if let Err(e) = std::fs::remove_file(dir.join(format!("{}.rhai", name))) {
+                app.scripts_panel.status = Some((false, format!("Failed to delete script: {}", e)));
+                return;
+            }
+
+            app.config.script_limits.remove(&name);
+            let _ = app.config.save();
+            app.scripts_panel.files = Some(list_scripts());
+            app.scripts_panel.source.clear();
+            app.scripts_panel.status = Some((true, format!("Deleted {}", name)));
+            app.reload_scripts();
+        }
+        PanelAction::Reload => {
+            app.scripts_panel.files = Some(list_scripts());
+            app.reload_scripts();
+            let failed = app.script_host.as_ref().map_or(0, |h| h.errors().len());
+            app.scripts_panel.status = Some(if failed == 0 {
+                (true, "Reloaded scripts".to_string())
+            } else {
+                (false, format!("{} scripts failed to load", failed))
+            });
+        }
+    }
+}
+
+/// Names of the `*.rhai` files in the scripts directory
+fn list_scripts() -> Vec<String> {
+    let Ok(dir) = ScriptHost::scripts_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+        .map(|path| script_name(&path))
+        .collect();
+    names.sort();
+    names
+}
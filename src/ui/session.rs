@@ -0,0 +1,49 @@
+use crate::i18n::tr;
+use crate::session;
+use crate::ui::app::DashboardApp;
+
+/// Offer to restore the session of a run that didn't close normally
+pub fn show_recovery_prompt(ctx: &egui::Context, app: &mut DashboardApp) {
+    let Some(snapshot) = &app.session_recovery else {
+        return;
+    };
+
+    let mut restore = None;
+    egui::Window::new(tr!("recovery-title"))
+        .id(egui::Id::new("session_recovery"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            let saved_at = snapshot.saved_at.with_timezone(&chrono::Local);
+            ui.label(tr!("recovery-body", time = saved_at.format("%Y-%m-%d %H:%M").to_string()));
+            ui.add_space(4.0);
+            ui.label(egui::RichText::new(snapshot.summary()).strong());
+            ui.label(
+                egui::RichText::new(tr!("recovery-note"))
+                    .size(11.0)
+                    .color(egui::Color32::GRAY),
+            );
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr!("recovery-restore")).clicked() {
+                    restore = Some(true);
+                }
+                if ui.button(tr!("recovery-discard")).clicked() {
+                    restore = Some(false);
+                }
+            });
+        });
+
+    let Some(restore) = restore else {
+        return;
+    };
+    if let Some(snapshot) = app.session_recovery.take() {
+        if restore {
+            app.restore_session(ctx, snapshot);
+        } else if let Err(e) = session::snapshot_path().and_then(|path| session::clear(&path)) {
+            log::warn!("Failed to remove the session snapshot: {}", e);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::ui::app::AppTab;
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+/// Something the keyboard can do from anywhere in the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    ShowTab(AppTab),
+    ToggleConfig,
+    TogglePlans,
+    ToggleTts,
+    ToggleScripts,
+    ToggleUpdates,
+    ToggleLogs,
+    ToggleBlackboard,
+    ToggleTriage,
+    /// Put the cursor in the current tab's text input
+    FocusInput,
+    PreviousAgent,
+    NextAgent,
+    /// Close every open window, when no text field has focus
+    CloseWindows,
+    ToggleHelp,
+}
+
+const fn shortcut(modifiers: Modifiers, key: Key) -> KeyboardShortcut {
+    KeyboardShortcut::new(modifiers, key)
+}
+
+const COMMAND_SHIFT: Modifiers = Modifiers::COMMAND.plus(Modifiers::SHIFT);
+
+/// egui also matches a shortcut when extra Shift or Alt is held, so those with more
+/// modifiers come first
+pub const SHORTCUTS: [(KeyboardShortcut, Action, &str); 16] = [
+    (shortcut(COMMAND_SHIFT, Key::P), Action::TogglePlans, "Plans"),
+    (shortcut(COMMAND_SHIFT, Key::T), Action::ToggleTts, "TTS panel"),
+    (shortcut(COMMAND_SHIFT, Key::S), Action::ToggleScripts, "Scripts"),
+    (shortcut(COMMAND_SHIFT, Key::U), Action::ToggleUpdates, "Updates"),
+    (shortcut(COMMAND_SHIFT, Key::L), Action::ToggleLogs, "Logs"),
+    (shortcut(COMMAND_SHIFT, Key::B), Action::ToggleBlackboard, "Blackboard"),
+    (shortcut(COMMAND_SHIFT, Key::I), Action::ToggleTriage, "Triage unread messages"),
+    (shortcut(Modifiers::COMMAND, Key::Num1), Action::ShowTab(AppTab::Home), "Home tab"),
+    (shortcut(Modifiers::COMMAND, Key::Num2), Action::ShowTab(AppTab::Chats), "Chats tab"),
+    (shortcut(Modifiers::COMMAND, Key::Num3), Action::ShowTab(AppTab::Term), "Term tab"),
+    (shortcut(Modifiers::COMMAND, Key::Comma), Action::ToggleConfig, "Configuration"),
+    (shortcut(Modifiers::COMMAND, Key::L), Action::FocusInput, "Message, search or command input"),
+    (shortcut(Modifiers::ALT, Key::ArrowUp), Action::PreviousAgent, "Select the previous agent"),
+    (shortcut(Modifiers::ALT, Key::ArrowDown), Action::NextAgent, "Select the next agent"),
+    (shortcut(Modifiers::NONE, Key::Escape), Action::CloseWindows, "Close windows"),
+    (shortcut(Modifiers::NONE, Key::F1), Action::ToggleHelp, "This list"),
+];
+
+/// Take this frame's shortcuts out of the input, before any widget sees them
+pub fn read(ctx: &egui::Context) -> Vec<Action> {
+    let typing = ctx.memory(|memory| memory.focused().is_some());
+    SHORTCUTS
+        .iter()
+        .filter(|(_, action, _)| !(typing && *action == Action::CloseWindows))
+        .filter(|(shortcut, _, _)| ctx.input_mut(|input| input.consume_shortcut(shortcut)))
+        .map(|(_, action, _)| *action)
+        .collect()
+}
+
+/// The shortcuts, and how to get around with Tab
+pub fn show_help(ctx: &egui::Context, open: &mut bool) {
+    egui::Window::new("Keyboard shortcuts").open(open).resizable(false).show(ctx, |ui| {
+        egui::Grid::new("shortcuts").num_columns(2).striped(true).show(ui, |ui| {
+            for (shortcut, _, description) in &SHORTCUTS {
+                ui.label(egui::RichText::new(ctx.format_shortcut(shortcut)).monospace());
+                ui.label(*description);
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        ui.label("Tab and Shift+Tab move between buttons and fields; Space or Enter presses the focused one.");
+    });
+}
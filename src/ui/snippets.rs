@@ -0,0 +1,35 @@
+use crate::snippets::{self, Snippets};
+
+/// Snippet picker: a menu of the chat CLI's snippets, each filled in with `values` (plus
+/// `{date}` and `{time}`) when picked. Loads the snippets when opened, so ones added
+/// with `chat snippet add` meanwhile show up.
+pub fn snippet_menu(ui: &mut egui::Ui, cache: &mut Option<Snippets>, values: &[(&str, String)]) -> Option<String> {
+    let response = ui.menu_button("📝", |ui| {
+        let snippets = cache.get_or_insert_with(Snippets::load);
+        if snippets.is_empty() {
+            ui.label(egui::RichText::new("No snippets yet. Add one with: chat snippet add <name> <text>").color(egui::Color32::GRAY));
+            return None;
+        }
+
+        let mut picked = None;
+        for (name, text) in snippets.iter() {
+            if ui.button(name).on_hover_text(text).clicked() {
+                let mut values = values.to_vec();
+                values.extend(snippets::now_values());
+                picked = Some(snippets::render(text, &values));
+                ui.close_menu();
+            }
+        }
+        picked
+    });
+
+    response.response.on_hover_text("Insert a snippet");
+    match response.inner {
+        Some(picked) => picked,
+        None => {
+            // Closed, so the next open loads them again
+            *cache = None;
+            None
+        }
+    }
+}
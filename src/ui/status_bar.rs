@@ -0,0 +1,199 @@
+use crate::config::DndStatus;
+use crate::i18n::tr;
+use crate::mcp::McpServerStatus;
+use crate::ui::app::{AppTab, DashboardApp};
+use crate::voice::VoiceState;
+
+/// Connection indicator for a chat source shown in the status bar
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct SourceIndicator {
+    pub id: String,
+    pub name: String,
+    pub is_connected: bool,
+    /// The session expired; clicking the source offers to sign in again
+    pub auth_required: bool,
+    /// Messages buffered but not yet consumed by the dashboard
+    pub lag: usize,
+    /// Messages dropped because the subscription buffer overflowed
+    pub dropped: u64,
+}
+
+pub fn show_status_bar(ctx: &egui::Context, app: &mut DashboardApp) {
+    egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            // Per-source connection dots
+            if app.source_indicators.is_empty() {
+                ui.label(
+                    egui::RichText::new(tr!("status-no-sources"))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY),
+                );
+            } else {
+                let mut sign_in = None;
+                for source in &app.source_indicators {
+                    let (color, state) = if source.auth_required {
+                        (egui::Color32::from_rgb(229, 229, 16), tr!("source-session-expired"))
+                    } else if source.is_connected {
+                        (egui::Color32::from_rgb(13, 188, 121), tr!("source-connected"))
+                    } else {
+                        (egui::Color32::from_rgb(205, 49, 49), tr!("source-disconnected"))
+                    };
+                    ui.label(egui::RichText::new("●").color(color))
+                        .on_hover_text(format!("{} ({})", source.name, state));
+                    if source.auth_required {
+                        if ui
+                            .link(egui::RichText::new(&source.name).size(11.0))
+                            .on_hover_text(tr!("source-sign-in"))
+                            .clicked()
+                        {
+                            sign_in = Some(source.id.clone());
+                        }
+                    } else {
+                        ui.label(egui::RichText::new(&source.name).size(11.0));
+                    }
+                    if source.lag > 0 || source.dropped > 0 {
+                        let color = if source.dropped > 0 {
+                            egui::Color32::from_rgb(255, 80, 80)
+                        } else {
+                            egui::Color32::from_rgb(229, 229, 16)
+                        };
+                        ui.label(
+                            egui::RichText::new(tr!("status-lag", lag = source.lag))
+                                .size(10.0)
+                                .color(color),
+                        )
+                        .on_hover_text(tr!("status-lag-hover", lag = source.lag, dropped = source.dropped));
+                    }
+                }
+                if let Some(source) = sign_in {
+                    app.prompt_sign_in(&source);
+                }
+            }
+
+            // Chat MCP server shared by the agents
+            if let Some(server) = &mut app.mcp_server {
+                let (color, state) = match server.status() {
+                    McpServerStatus::Starting => (egui::Color32::from_rgb(229, 229, 16), tr!("mcp-starting")),
+                    McpServerStatus::Running => (egui::Color32::from_rgb(13, 188, 121), tr!("mcp-running", address = server.address().to_string())),
+                    McpServerStatus::Stopped(reason) => (egui::Color32::from_rgb(205, 49, 49), tr!("mcp-stopped", reason = reason.to_string())),
+                };
+                ui.separator();
+                ui.label(egui::RichText::new("●").color(color))
+                    .on_hover_text(tr!("mcp-hover", state = state));
+                if ui.link(egui::RichText::new("MCP").size(11.0)).clicked() {
+                    app.show_config_panel = true;
+                }
+            }
+
+            ui.separator();
+
+            // TTS playback state
+            let tts_text = match &app.tts_service {
+                Some(service) => {
+                    let status = service.status();
+                    if status.playing {
+                        tr!("tts-playing", queued = status.queue_length)
+                    } else if status.queue_length > 0 {
+                        tr!("tts-queued", queued = status.queue_length)
+                    } else {
+                        tr!("tts-idle")
+                    }
+                }
+                None => tr!("tts-off"),
+            };
+            if ui.link(egui::RichText::new(tts_text).size(11.0)).clicked() {
+                app.current_tab = AppTab::Home;
+                app.show_tts_panel = true;
+            }
+
+            ui.separator();
+
+            // Do-not-disturb (click to toggle manual DND)
+            let (dnd_text, dnd_color, dnd_hover) = match app.config.dnd.status(chrono::Local::now()) {
+                DndStatus::Off => (
+                    tr!("dnd-off"),
+                    egui::Color32::GRAY,
+                    tr!("dnd-off-hover"),
+                ),
+                DndStatus::Manual => (
+                    tr!("dnd-on"),
+                    egui::Color32::from_rgb(229, 229, 16),
+                    tr!("dnd-on-hover"),
+                ),
+                DndStatus::QuietHours { until } => (
+                    tr!("dnd-quiet-hours"),
+                    egui::Color32::from_rgb(229, 229, 16),
+                    tr!("dnd-quiet-hours-hover", until = until.format("%H:%M").to_string()),
+                ),
+            };
+            if ui
+                .link(egui::RichText::new(dnd_text).size(11.0).color(dnd_color))
+                .on_hover_text(dnd_hover)
+                .clicked()
+            {
+                app.toggle_dnd();
+            }
+
+            ui.separator();
+
+            // Voice commands (click to listen, like the hotkey)
+            if let Some(listener) = &app.voice {
+                let (voice_text, voice_color) = match listener.state() {
+                    VoiceState::Idle => (tr!("voice-ready"), egui::Color32::GRAY),
+                    VoiceState::WaitingForWakeWord => (tr!("voice-waiting"), egui::Color32::GRAY),
+                    VoiceState::Listening => (tr!("voice-listening"), egui::Color32::from_rgb(255, 80, 80)),
+                    VoiceState::Transcribing => (tr!("voice-transcribing"), egui::Color32::from_rgb(229, 229, 16)),
+                };
+                if ui
+                    .link(egui::RichText::new(voice_text).size(11.0).color(voice_color))
+                    .on_hover_text(tr!("voice-hover", hotkey = app.config.voice.hotkey.as_str()))
+                    .clicked()
+                {
+                    listener.listen();
+                }
+
+                ui.separator();
+            }
+
+            // Running agents
+            let agents_text = tr!("status-agents", count = app.active_agents.len());
+            if ui.link(egui::RichText::new(agents_text).size(11.0)).clicked() {
+                app.current_tab = AppTab::Home;
+                app.show_config_panel = true;
+            }
+
+            ui.separator();
+
+            // Unread agent messages
+            let unread_text = tr!("status-unread", count = app.unread_count);
+            let unread_color = if app.unread_count > 0 {
+                egui::Color32::from_rgb(229, 229, 16)
+            } else {
+                egui::Color32::GRAY
+            };
+            if ui
+                .link(egui::RichText::new(unread_text).size(11.0).color(unread_color))
+                .clicked()
+            {
+                app.current_tab = AppTab::Home;
+            }
+
+            // Last error (click to dismiss)
+            if let Some(error) = app.last_error.clone() {
+                ui.separator();
+                if ui
+                    .link(
+                        egui::RichText::new(format!("⚠ {}", error))
+                            .size(11.0)
+                            .color(egui::Color32::from_rgb(255, 80, 80)),
+                    )
+                    .on_hover_text(tr!("status-dismiss"))
+                    .clicked()
+                {
+                    app.last_error = None;
+                }
+            }
+        });
+    });
+}
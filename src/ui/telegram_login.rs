@@ -0,0 +1,201 @@
+use crate::i18n::tr;
+use crate::telegram::{LoginState, TelegramCredentials, TelegramLogin};
+use crate::ui::app::DashboardApp;
+
+/// Telegram credentials form and sign-in flow, used by the setup wizard and the config panel
+pub struct TelegramLoginPanel {
+    pub credentials: TelegramCredentials,
+    login: Option<TelegramLogin>,
+    state: Option<LoginState>,
+    /// Login code or 2FA password being typed
+    answer: String,
+    error: Option<String>,
+}
+
+impl Default for TelegramLoginPanel {
+    fn default() -> Self {
+        Self {
+            credentials: TelegramCredentials::load().unwrap_or_default(),
+            login: None,
+            state: None,
+            answer: String::new(),
+            error: None,
+        }
+    }
+}
+
+impl TelegramLoginPanel {
+    /// Whether a login started here has completed
+    pub fn signed_in(&self) -> bool {
+        matches!(self.state, Some(LoginState::Done { .. }))
+    }
+
+    /// Whether a login is waiting on the user or on Telegram
+    pub fn in_progress(&self) -> bool {
+        self.login.is_some() && !matches!(self.state, Some(LoginState::Done { .. } | LoginState::Failed(_)))
+    }
+
+    /// Forget a finished or failed login, back to the "Sign in" button
+    pub fn reset(&mut self) {
+        self.login = None;
+        self.state = None;
+        self.answer.clear();
+        self.error = None;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        if let Some(login) = &mut self.login {
+            self.state = Some(login.state());
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr!("telegram-create-app"));
+            ui.hyperlink("https://my.telegram.org");
+            ui.label(tr!("telegram-create-app-purpose"));
+        });
+
+        ui.add_enabled_ui(!self.in_progress(), |ui| {
+            egui::Grid::new(ui.next_auto_id()).num_columns(2).show(ui, |ui| {
+                ui.label(tr!("telegram-api-id"));
+                ui.text_edit_singleline(&mut self.credentials.api_id);
+                ui.end_row();
+                ui.label(tr!("telegram-api-hash"));
+                ui.add(egui::TextEdit::singleline(&mut self.credentials.api_hash).password(true));
+                ui.end_row();
+                ui.label(tr!("telegram-phone"));
+                ui.add(egui::TextEdit::singleline(&mut self.credentials.phone).hint_text("+358401234567"));
+                ui.end_row();
+            });
+        });
+        ui.add_space(4.0);
+
+        let mut submit = false;
+        match self.state.clone() {
+            None => {
+                if ui.button(tr!("telegram-sign-in")).clicked() {
+                    self.start(ui.ctx());
+                }
+            }
+            Some(LoginState::Connecting) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(tr!("telegram-requesting-code"));
+                });
+            }
+            Some(LoginState::NeedCode) => {
+                ui.label(tr!("telegram-enter-code"));
+                ui.horizontal(|ui| {
+                    let response = ui.text_edit_singleline(&mut self.answer);
+                    submit = ui.button(tr!("telegram-submit")).clicked()
+                        || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                });
+            }
+            Some(LoginState::NeedPassword { hint }) => {
+                match hint {
+                    Some(hint) => ui.label(tr!("telegram-password-hint", hint = hint)),
+                    None => ui.label(tr!("telegram-password")),
+                };
+                ui.horizontal(|ui| {
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.answer).password(true));
+                    submit = ui.button(tr!("telegram-submit")).clicked()
+                        || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                });
+            }
+            Some(LoginState::SigningIn) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(tr!("telegram-signing-in"));
+                });
+            }
+            Some(LoginState::Done { user }) => {
+                let text = match user {
+                    Some(user) => tr!("telegram-signed-in-as", user = user),
+                    None => tr!("telegram-signed-in"),
+                };
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::GREEN, text);
+                    if ui.button(tr!("telegram-sign-in-again")).clicked() {
+                        self.start(ui.ctx());
+                    }
+                });
+            }
+            Some(LoginState::Failed(error)) => {
+                ui.colored_label(egui::Color32::RED, tr!("telegram-sign-in-failed", error = error));
+                if ui.button(tr!("telegram-try-again")).clicked() {
+                    self.start(ui.ctx());
+                }
+            }
+        }
+
+        if self.in_progress() && ui.button(tr!("telegram-cancel")).clicked() {
+            self.login = None;
+            self.state = None;
+        }
+
+        if submit && !self.answer.trim().is_empty() {
+            let answer = std::mem::take(&mut self.answer);
+            if let Some(login) = &mut self.login {
+                if let Err(e) = login.submit(&answer) {
+                    self.error = Some(tr!("telegram-submit-failed", error = e.to_string()));
+                }
+            }
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+
+    fn start(&mut self, ctx: &egui::Context) {
+        self.error = None;
+        self.login = None;
+        self.state = None;
+        self.answer.clear();
+
+        if let Err(e) = self.credentials.validate() {
+            self.error = Some(e);
+            return;
+        }
+
+        match TelegramLogin::start(&self.credentials, ctx.clone()) {
+            Ok(login) => {
+                self.login = Some(login);
+                self.state = Some(LoginState::Connecting);
+            }
+            Err(e) => self.error = Some(tr!("telegram-start-failed", error = e.to_string())),
+        }
+    }
+}
+
+/// Ask to sign in again after the chat CLI reported an expired Telegram session
+pub fn show_reauth_dialog(ctx: &egui::Context, app: &mut DashboardApp) {
+    if app.reauth_source.is_none() {
+        return;
+    }
+
+    let mut later = false;
+    egui::Window::new(tr!("telegram-reauth-title"))
+        .id(egui::Id::new("telegram_reauth"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            ui.label(tr!("telegram-reauth-reason"));
+            ui.label(tr!("telegram-reauth-impact"));
+            ui.add_space(4.0);
+            app.telegram_login.show(ui);
+            ui.add_space(4.0);
+            if !app.telegram_login.in_progress() && ui.button(tr!("telegram-later")).clicked() {
+                later = true;
+            }
+        });
+
+    if app.telegram_login.signed_in() {
+        app.reauth_source = None;
+        app.toasts.info(tr!("telegram-reauth-done"));
+        app.restart_source_watchers(ctx);
+    } else if later {
+        app.reauth_source = None;
+    }
+}
@@ -0,0 +1,185 @@
+use crate::terminal::paste::{self, PendingPaste};
+use crate::terminal::{CommandHistory, TerminalConfig};
+
+/// Matches listed by Ctrl+R at most
+const SEARCH_RESULTS: usize = 8;
+
+/// Ctrl+R search over the command history
+#[derive(Default)]
+pub struct HistorySearch {
+    pub query: String,
+    selected: usize,
+}
+
+/// Recall history into the input with Up and Down, and open the search with Ctrl+R.
+/// Call before adding the input widget `input_id`, so it doesn't see the keys too.
+pub fn handle_history_keys(
+    ui: &mut egui::Ui,
+    input_id: egui::Id,
+    history: &mut CommandHistory,
+    input: &mut String,
+    search: &mut Option<HistorySearch>,
+) {
+    if search.is_some() || !ui.memory(|m| m.has_focus(input_id)) {
+        return;
+    }
+
+    if ui.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::R)) {
+        *search = Some(HistorySearch::default());
+        return;
+    }
+
+    // Multi-line input keeps Up and Down for moving between its lines, unless recalling
+    if input.contains('\n') && !history.is_recalling() {
+        return;
+    }
+    let recalled = if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)) {
+        history.previous(input).map(str::to_string)
+    } else if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
+        history.next().map(str::to_string)
+    } else {
+        None
+    };
+
+    if let Some(line) = recalled {
+        *input = line;
+        move_cursor_to_end(ui.ctx(), input_id, input);
+    }
+}
+
+/// Search box with the best matches. Returns the line picked with Enter or a click;
+/// Escape closes the search and `search` is cleared either way.
+pub fn render_history_search(
+    ui: &mut egui::Ui,
+    search: &mut Option<HistorySearch>,
+    history: &CommandHistory,
+) -> Option<String> {
+    let state = search.as_mut()?;
+    let matches = history.search(&state.query, SEARCH_RESULTS);
+    state.selected = state.selected.min(matches.len().saturating_sub(1));
+
+    let (escape, enter, up, down) = ui.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+        )
+    });
+    if up {
+        state.selected = state.selected.saturating_sub(1);
+    }
+    if down && state.selected + 1 < matches.len() {
+        state.selected += 1;
+    }
+
+    let mut picked = enter.then(|| matches.get(state.selected).map(|line| line.to_string())).flatten();
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("History search:");
+            ui.add(
+                egui::TextEdit::singleline(&mut state.query)
+                    .hint_text("type to filter, Enter to use, Esc to cancel")
+                    .desired_width(f32::INFINITY)
+                    .font(egui::TextStyle::Monospace),
+            )
+            .request_focus();
+        });
+        if matches.is_empty() {
+            ui.label(egui::RichText::new("No matching commands").color(egui::Color32::GRAY));
+        }
+        for (index, line) in matches.iter().enumerate() {
+            let text = egui::RichText::new(*line).monospace();
+            if ui.selectable_label(index == state.selected, text).clicked() {
+                picked = Some(line.to_string());
+            }
+        }
+    });
+
+    if escape || picked.is_some() {
+        *search = None;
+    }
+    picked
+}
+
+/// Hold back a paste into the input widget `input_id` that the config wants confirmed,
+/// so the widget doesn't see it. Call before adding the widget.
+pub fn intercept_paste(ui: &mut egui::Ui, input_id: egui::Id, config: &TerminalConfig) -> Option<PendingPaste> {
+    if !ui.memory(|m| m.has_focus(input_id)) {
+        return None;
+    }
+
+    let mut pending = None;
+    ui.input_mut(|i| {
+        i.events.retain(|event| match event {
+            egui::Event::Paste(text) if pending.is_none() => match paste::check(text, config) {
+                Some(warning) => {
+                    pending = Some((text.clone(), warning));
+                    false
+                }
+                None => true,
+            },
+            _ => true,
+        })
+    });
+
+    let (text, warning) = pending?;
+    let at = egui::TextEdit::load_state(ui.ctx(), input_id)
+        .and_then(|state| state.cursor.char_range())
+        .map(|range| range.primary.index)
+        .unwrap_or(usize::MAX);
+    Some(PendingPaste { text, warning, at })
+}
+
+/// Dialog asking whether to go ahead with a held back paste. Returns the text to
+/// insert once confirmed; `pending` is cleared when confirmed or cancelled.
+pub fn render_paste_confirm(ctx: &egui::Context, pending: &mut Option<PendingPaste>) -> Option<(String, usize)> {
+    let paste = pending.as_ref()?;
+    let mut confirmed = None;
+    let mut cancelled = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape));
+
+    egui::Window::new("Confirm paste")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(format!("This paste has {} lines.", paste.warning.lines));
+            if paste.warning.control_chars {
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 165, 0),
+                    "It contains control characters (shown as ␛ or \\u{..}), which a terminal may act on.",
+                );
+            }
+
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                    ui.label(egui::RichText::new(paste::escape_control(&paste.text)).monospace());
+                });
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Paste").clicked() {
+                    confirmed = Some(paste.text.clone());
+                }
+                if paste.warning.control_chars && ui.button("Paste without control characters").clicked() {
+                    confirmed = Some(paste::strip_control(&paste.text));
+                }
+                cancelled |= ui.button("Cancel").clicked();
+            });
+        });
+
+    let at = paste.at;
+    if confirmed.is_some() || cancelled {
+        *pending = None;
+    }
+    confirmed.map(|text| (text, at))
+}
+
+/// Put the text cursor of a text edit after its last character
+pub fn move_cursor_to_end(ctx: &egui::Context, id: egui::Id, text: &str) {
+    if let Some(mut state) = egui::TextEdit::load_state(ctx, id) {
+        let end = egui::text::CCursor::new(text.chars().count());
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+        state.store(ctx, id);
+    }
+}
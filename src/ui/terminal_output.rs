@@ -0,0 +1,190 @@
+use crate::terminal::buffer::{Line, ScrollbackBuffer};
+use egui::text::{LayoutJob, TextWrapping};
+use egui::{FontId, Galley};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Lines kept laid out on either side of the visible ones, so scrolling a little
+/// reuses them
+const CACHE_MARGIN: usize = 200;
+
+/// A row of the view: the header of a block, by its index, or a line
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Row {
+    Header(usize),
+    Line(usize),
+}
+
+/// Scrollback shown one row per line, laying out only the visible lines. A line keeps
+/// its galley until it changes, so a frame with new output lays out just the open
+/// last line. Lines don't wrap; long ones scroll sideways. Grouped, each command's
+/// block gets a header row that collapses it.
+#[derive(Default)]
+pub struct OutputView {
+    galleys: HashMap<usize, (u64, Arc<Galley>)>,
+    /// What the cached galleys were laid out for
+    font_id: Option<FontId>,
+    pixels_per_point: f32,
+    /// Blocks collapsed to their header, by block ID
+    collapsed: HashSet<u64>,
+    /// Rows when grouped, and the lines they cover
+    rows: Vec<Row>,
+    rows_lines: usize,
+    /// What `rows` was made for: number of blocks, last block and collapsed blocks
+    rows_for: Option<(usize, u64, usize)>,
+    /// Counts changes to `collapsed`
+    generation: usize,
+}
+
+impl OutputView {
+    pub fn show(&mut self, ui: &mut egui::Ui, buffer: &ScrollbackBuffer, font_id: &FontId, max_height: f32, grouped: bool) {
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        if self.font_id.as_ref() != Some(font_id) || self.pixels_per_point != pixels_per_point {
+            self.galleys.clear();
+            self.font_id = Some(font_id.clone());
+            self.pixels_per_point = pixels_per_point;
+        }
+        let row_count = if grouped {
+            self.update_rows(buffer);
+            self.rows.len()
+        } else {
+            buffer.len()
+        };
+
+        let row_height = ui.fonts(|fonts| fonts.row_height(font_id));
+        let mut toggle = None;
+        ui.scope(|ui| {
+            ui.spacing_mut().item_spacing.y = 0.0;
+            egui::ScrollArea::both()
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .max_height(max_height)
+                .show_rows(ui, row_height, row_count, |ui, rows| {
+                    let mut shown_lines = None::<(usize, usize)>;
+                    for row in rows.clone() {
+                        let row = if grouped { self.rows.get(row).copied() } else { Some(Row::Line(row)) };
+                        let index = match row {
+                            Some(Row::Line(index)) => index,
+                            Some(Row::Header(block)) => {
+                                if block_header(ui, buffer, block, self.collapsed.contains(&buffer.blocks()[block].id), font_id) {
+                                    toggle = Some(buffer.blocks()[block].id);
+                                }
+                                continue;
+                            }
+                            None => break,
+                        };
+                        let Some(line) = buffer.line(index) else { break };
+                        let galley = match self.galleys.get(&index) {
+                            Some((revision, galley)) if *revision == line.revision => galley.clone(),
+                            _ => {
+                                let galley = layout_line(ui, line, font_id);
+                                self.galleys.insert(index, (line.revision, galley.clone()));
+                                galley
+                            }
+                        };
+                        ui.label(galley);
+                        shown_lines = Some(shown_lines.map_or((index, index), |(first, _)| (first, index)));
+                    }
+
+                    if let Some((first, last)) = shown_lines {
+                        let keep = first.saturating_sub(CACHE_MARGIN)..last + 1 + CACHE_MARGIN;
+                        self.galleys.retain(|index, _| keep.contains(index));
+                    }
+                });
+        });
+
+        if let Some(block) = toggle {
+            if !self.collapsed.remove(&block) {
+                self.collapsed.insert(block);
+            }
+            self.generation += 1;
+        }
+    }
+
+    /// Collapse every block with a header
+    pub fn collapse_all(&mut self, buffer: &ScrollbackBuffer) {
+        self.collapsed.extend(buffer.blocks().iter().map(|block| block.id));
+        self.generation += 1;
+    }
+
+    pub fn expand_all(&mut self) {
+        self.collapsed.clear();
+        self.generation += 1;
+    }
+
+    /// Rebuild the rows when blocks started or were collapsed; new lines in the last
+    /// block are only appended
+    fn update_rows(&mut self, buffer: &ScrollbackBuffer) {
+        let blocks = buffer.blocks();
+        let key = (blocks.len(), blocks.last().map_or(0, |block| block.id), self.generation);
+        if self.rows_for == Some(key) && buffer.len() >= self.rows_lines {
+            let last_collapsed = blocks.last().is_some_and(|block| self.collapsed.contains(&block.id));
+            if !last_collapsed {
+                self.rows.extend((self.rows_lines..buffer.len()).map(Row::Line));
+            }
+            self.rows_lines = buffer.len();
+            return;
+        }
+
+        self.rows.clear();
+        for (index, block) in blocks.iter().enumerate() {
+            // Output from before the first command needs no header
+            if index > 0 || block.command.is_some() {
+                self.rows.push(Row::Header(index));
+            }
+            if !self.collapsed.contains(&block.id) {
+                self.rows.extend(buffer.block_lines(index).map(Row::Line));
+            }
+        }
+        self.rows_lines = buffer.len();
+        self.rows_for = Some(key);
+        // Blocks cleared or trimmed from the scrollback can't be expanded again
+        self.collapsed.retain(|id| blocks.iter().any(|block| block.id == *id));
+    }
+}
+
+/// A block's header row: its command, or when its output came, and how many lines it
+/// has. Returns whether it was clicked, to collapse or expand the block.
+fn block_header(ui: &mut egui::Ui, buffer: &ScrollbackBuffer, index: usize, collapsed: bool, font_id: &FontId) -> bool {
+    let block = &buffer.blocks()[index];
+    let lines = buffer.block_lines(index).len();
+    let title = match &block.command {
+        Some(command) => format!("$ {}", command),
+        None => format!("output at {}", block.started.format("%H:%M:%S")),
+    };
+    let text = format!(
+        "{} {}  ({} line{}, {})",
+        if collapsed { "▸" } else { "▾" },
+        title,
+        lines,
+        if lines == 1 { "" } else { "s" },
+        block.started.format("%H:%M:%S")
+    );
+    ui.add(
+        egui::Label::new(egui::RichText::new(text).font(font_id.clone()).color(egui::Color32::from_rgb(120, 170, 255)))
+            .sense(egui::Sense::click()),
+    )
+    .on_hover_cursor(egui::CursorIcon::PointingHand)
+    .clicked()
+}
+
+fn layout_line(ui: &egui::Ui, line: &Line, font_id: &FontId) -> Arc<Galley> {
+    let format = |color| egui::TextFormat {
+        font_id: font_id.clone(),
+        color,
+        ..Default::default()
+    };
+
+    let mut job = LayoutJob {
+        wrap: TextWrapping::no_max_width(),
+        ..Default::default()
+    };
+    for span in &line.spans {
+        job.append(&span.text, 0.0, format(span.color));
+    }
+    if job.sections.is_empty() {
+        // An empty line still takes a row of this font's height
+        job.append("", 0.0, format(egui::Color32::TRANSPARENT));
+    }
+    ui.fonts(|fonts| fonts.layout_job(job))
+}
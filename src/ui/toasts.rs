@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+/// How long a toast stays up
+const INFO_DURATION: Duration = Duration::from_secs(5);
+const WARNING_DURATION: Duration = Duration::from_secs(15);
+/// Older toasts are dropped when more than this many are up
+const MAX_TOASTS: usize = 5;
+
+struct Toast {
+    text: String,
+    warning: bool,
+    shown_at: Instant,
+}
+
+impl Toast {
+    fn duration(&self) -> Duration {
+        if self.warning {
+            WARNING_DURATION
+        } else {
+            INFO_DURATION
+        }
+    }
+}
+
+/// Short-lived messages stacked in the bottom-right corner
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(text.into(), false);
+    }
+
+    pub fn warning(&mut self, text: impl Into<String>) {
+        self.push(text.into(), true);
+    }
+
+    fn push(&mut self, text: String, warning: bool) {
+        self.toasts.push(Toast { text, warning, shown_at: Instant::now() });
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+    }
+
+    /// Draw the toasts that haven't expired; clicking one dismisses it
+    pub fn show(&mut self, ctx: &egui::Context) {
+        self.toasts.retain(|toast| toast.shown_at.elapsed() < toast.duration());
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -36.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for (i, toast) in self.toasts.iter().enumerate() {
+                    let color = if toast.warning {
+                        egui::Color32::from_rgb(229, 229, 16)
+                    } else {
+                        egui::Color32::from_rgb(13, 188, 121)
+                    };
+                    let response = egui::Frame::popup(ui.style())
+                        .show(ui, |ui| {
+                            ui.set_max_width(320.0);
+                            ui.colored_label(color, &toast.text);
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text("Click to dismiss");
+                    if response.clicked() {
+                        dismissed = Some(i);
+                    }
+                }
+            });
+
+        if let Some(i) = dismissed {
+            self.toasts.remove(i);
+        }
+
+        // Repaint when the next toast expires
+        if let Some(remaining) = self
+            .toasts
+            .iter()
+            .map(|toast| toast.duration().saturating_sub(toast.shown_at.elapsed()))
+            .min()
+        {
+            ctx.request_repaint_after(remaining);
+        }
+    }
+}
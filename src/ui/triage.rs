@@ -0,0 +1,533 @@
+use crate::i18n::{self, tr};
+use crate::sources::forget::ForgetTarget;
+use crate::sources::{json_id, message_body, message_sender};
+use crate::ui::app::DashboardApp;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::mpsc;
+
+/// How long "remind me later" waits
+const REMIND_IN: &str = "1d";
+
+/// Result of `chat catchup --messages --format json`
+#[derive(Deserialize)]
+struct CatchUp {
+    #[serde(default)]
+    chats: Vec<UnreadChat>,
+}
+
+#[derive(Deserialize)]
+struct UnreadChat {
+    /// `source:chat_id`
+    chat: String,
+    title: Option<String>,
+    #[serde(default)]
+    messages: Vec<Value>,
+}
+
+/// What has been done with a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Read,
+    Bookmarked,
+    Reminded,
+    SentToAgent,
+}
+
+impl Step {
+    fn badge(self) -> String {
+        match self {
+            Step::Read => tr!("triage-step-read"),
+            Step::Bookmarked => tr!("triage-step-bookmarked"),
+            Step::Reminded => tr!("triage-step-reminded"),
+            Step::SentToAgent => tr!("triage-step-sent"),
+        }
+    }
+}
+
+/// An unread message in the triage queue
+#[derive(Debug, Clone)]
+pub struct TriageItem {
+    /// `source:chat_id`
+    pub chat: String,
+    pub title: String,
+    pub message: Value,
+    pub steps: Vec<Step>,
+}
+
+impl TriageItem {
+    fn source(&self) -> &str {
+        self.chat.split_once(':').map_or(self.chat.as_str(), |(source, _)| source)
+    }
+
+    fn chat_id(&self) -> &str {
+        self.chat.split_once(':').map_or("", |(_, id)| id)
+    }
+
+    fn message_id(&self) -> String {
+        match &self.message["id"] {
+            Value::String(id) => id.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        !self.steps.is_empty()
+    }
+}
+
+/// Key to press, and the message describing what it does
+const KEYS: [(egui::Key, &str); 9] = [
+    (egui::Key::J, "triage-key-next"),
+    (egui::Key::K, "triage-key-previous"),
+    (egui::Key::R, "triage-key-read"),
+    (egui::Key::A, "triage-key-read-chat"),
+    (egui::Key::B, "triage-key-bookmark"),
+    (egui::Key::L, "triage-key-remind"),
+    (egui::Key::S, "triage-key-summarize"),
+    (egui::Key::D, "triage-key-draft"),
+    (egui::Key::N, "triage-key-next-chat"),
+];
+
+/// State of the Triage window: unread messages, urgent chats first and oldest first in
+/// each chat, stepped through one at a time
+pub struct TriageState {
+    /// How far back to look in chats never read before
+    pub since: String,
+    items: Vec<TriageItem>,
+    index: usize,
+    loading: Option<mpsc::Receiver<Result<Vec<TriageItem>, String>>>,
+    error: Option<String>,
+    /// Chat CLI calls still running, by what they do
+    running: Vec<(String, mpsc::Receiver<Result<(), String>>)>,
+}
+
+impl Default for TriageState {
+    fn default() -> Self {
+        Self {
+            since: "14d".to_string(),
+            items: Vec::new(),
+            index: 0,
+            loading: None,
+            error: None,
+            running: Vec::new(),
+        }
+    }
+}
+
+impl TriageState {
+    /// Fetch the unread messages in a background thread
+    pub fn load(&mut self) {
+        let since = self.since.trim().to_string();
+        let (tx, rx) = mpsc::channel();
+        self.loading = Some(rx);
+        self.error = None;
+        std::thread::spawn(move || {
+            let _ = tx.send(fetch_unread(&since));
+        });
+    }
+
+    fn set_items(&mut self, items: Vec<TriageItem>) {
+        self.items = items;
+        self.index = 0;
+    }
+
+    pub fn current(&self) -> Option<&TriageItem> {
+        self.items.get(self.index)
+    }
+
+    /// Drop queued messages of a forgotten chat or person, staying on the current message
+    pub fn forget(&mut self, target: &ForgetTarget) {
+        let covers = |item: &TriageItem| target.covers(&item.chat, json_id(&item.message["sender"]["id"]).as_deref());
+        let before = self.items.iter().take(self.index).filter(|item| covers(item)).count();
+        self.items.retain(|item| !covers(item));
+        self.index = (self.index - before).min(self.items.len());
+    }
+
+    /// Messages nothing has been done with yet
+    pub fn remaining(&self) -> usize {
+        self.items.iter().filter(|item| !item.is_done()).count()
+    }
+
+    pub fn next(&mut self) {
+        self.index = (self.index + 1).min(self.items.len());
+    }
+
+    pub fn previous(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    /// Skip to the first message of the next chat
+    pub fn next_chat(&mut self) {
+        let Some(chat) = self.current().map(|item| item.chat.clone()) else {
+            return;
+        };
+        while self.current().is_some_and(|item| item.chat == chat) {
+            self.index += 1;
+        }
+    }
+
+    /// Record a step on the current message
+    fn mark(&mut self, step: Step) {
+        if let Some(item) = self.items.get_mut(self.index) {
+            if !item.steps.contains(&step) {
+                item.steps.push(step);
+            }
+        }
+    }
+
+    /// Marking read covers the chat's earlier messages too, as the read cursor does
+    fn mark_read_through_current(&mut self) {
+        let Some(current) = self.current().cloned() else {
+            return;
+        };
+        for item in &mut self.items[..=self.index] {
+            if item.chat == current.chat && !item.steps.contains(&Step::Read) {
+                item.steps.push(Step::Read);
+            }
+        }
+    }
+
+    fn mark_chat_read(&mut self) {
+        let Some(chat) = self.current().map(|item| item.chat.clone()) else {
+            return;
+        };
+        for item in self.items.iter_mut().filter(|item| item.chat == chat) {
+            if !item.steps.contains(&Step::Read) {
+                item.steps.push(Step::Read);
+            }
+        }
+    }
+
+    /// The current message and the chat's unread messages before it, for agent prompts
+    fn conversation(&self) -> String {
+        let Some(current) = self.current() else {
+            return String::new();
+        };
+        self.items[..=self.index]
+            .iter()
+            .filter(|item| item.chat == current.chat)
+            .map(|item| format!("{}: {}", message_sender(&item.message), message_body(&item.message)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn run(&mut self, description: String, args: Vec<String>) {
+        let (tx, rx) = mpsc::channel();
+        self.running.push((description, rx));
+        std::thread::spawn(move || {
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let _ = tx.send(run_chat(&args));
+        });
+    }
+
+    /// Check background work, returning failed actions to report
+    fn poll(&mut self) -> Vec<String> {
+        if let Some(rx) = &self.loading {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(items) => self.set_items(items),
+                    Err(e) => self.error = Some(e),
+                }
+                self.loading = None;
+            }
+        }
+
+        let mut failed = Vec::new();
+        self.running.retain(|(description, rx)| match rx.try_recv() {
+            Ok(Ok(())) => false,
+            Ok(Err(e)) => {
+                failed.push(tr!("triage-action-failed", action = description.clone(), error = e));
+                false
+            }
+            Err(mpsc::TryRecvError::Empty) => true,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        });
+        failed
+    }
+}
+
+fn fetch_unread(since: &str) -> Result<Vec<TriageItem>, String> {
+    let output = std::process::Command::new(crate::config::paths::helper("chat"))
+        .args(["catchup", "--no-llm", "--messages", "--since", since, "--format", "json"])
+        .output()
+        .map_err(|e| format!("Failed to run chat CLI: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let catchup: CatchUp =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("Failed to parse unread messages: {}", e))?;
+    Ok(queue(catchup))
+}
+
+/// One item per message, keeping the catch-up's order of chats and messages
+fn queue(catchup: CatchUp) -> Vec<TriageItem> {
+    catchup
+        .chats
+        .into_iter()
+        .flat_map(|chat| {
+            let title = chat.title.unwrap_or_else(|| chat.chat.clone());
+            let key = chat.chat;
+            chat.messages.into_iter().map(move |message| TriageItem {
+                chat: key.clone(),
+                title: title.clone(),
+                message,
+                steps: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+fn run_chat(args: &[&str]) -> Result<(), String> {
+    let output = std::process::Command::new(crate::config::paths::helper("chat"))
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run chat CLI: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Which single-key action was pressed this frame, if no text field has focus
+fn pressed_key(ctx: &egui::Context) -> Option<egui::Key> {
+    if ctx.memory(|memory| memory.focused().is_some()) {
+        return None;
+    }
+    KEYS.iter()
+        .map(|(key, _)| *key)
+        .chain([egui::Key::ArrowRight, egui::Key::ArrowLeft, egui::Key::Space])
+        .find(|key| ctx.input_mut(|input| input.consume_key(egui::Modifiers::NONE, *key)))
+}
+
+pub fn show_triage_window(ctx: &egui::Context, app: &mut DashboardApp) {
+    for failure in app.triage.poll() {
+        app.toasts.warning(failure);
+    }
+    if app.triage.loading.is_some() || !app.triage.running.is_empty() {
+        ctx.request_repaint_after(std::time::Duration::from_millis(200));
+    }
+
+    if let Some(key) = pressed_key(ctx) {
+        handle_key(app, key);
+    }
+
+    let state = &mut app.triage;
+    let mut open = app.show_triage;
+    egui::Window::new(tr!("button-triage"))
+        .id(egui::Id::new("triage_window"))
+        .open(&mut open)
+        .default_width(600.0)
+        .default_height(400.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("triage-since"));
+                ui.add(egui::TextEdit::singleline(&mut state.since).desired_width(60.0));
+                let label = if state.items.is_empty() { tr!("triage-load") } else { tr!("triage-reload") };
+                if ui.add_enabled(state.loading.is_none(), egui::Button::new(label)).clicked() {
+                    state.load();
+                }
+                if state.loading.is_some() {
+                    ui.spinner();
+                }
+            });
+            if let Some(error) = &state.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            ui.separator();
+
+            if state.items.is_empty() {
+                if state.loading.is_none() {
+                    ui.label(egui::RichText::new(tr!("triage-nothing-loaded")).color(egui::Color32::GRAY));
+                }
+            } else {
+                ui.label(tr!(
+                    "triage-progress",
+                    position = (state.index + 1).min(state.items.len()),
+                    total = state.items.len(),
+                    remaining = state.remaining()
+                ));
+                match state.current() {
+                    Some(item) => {
+                        ui.heading(&item.title);
+                        let time = item.message["timestamp"].as_str().unwrap_or_default();
+                        ui.label(
+                            egui::RichText::new(format!("{} · {}", message_sender(&item.message), time))
+                                .color(egui::Color32::GRAY),
+                        );
+                        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                            ui.label(message_body(&item.message));
+                        });
+                        if item.is_done() {
+                            let badges: Vec<_> = item.steps.iter().map(|step| step.badge()).collect();
+                            ui.colored_label(egui::Color32::GREEN, badges.join("  "));
+                        }
+                    }
+                    None => {
+                        ui.heading(tr!("triage-done"));
+                    }
+                }
+            }
+
+            ui.separator();
+            ui.horizontal_wrapped(|ui| {
+                for (key, description) in KEYS {
+                    ui.label(egui::RichText::new(key.name()).monospace().strong());
+                    ui.label(egui::RichText::new(i18n::translate(description)).color(egui::Color32::GRAY));
+                }
+            });
+        });
+    app.show_triage = open;
+}
+
+fn handle_key(app: &mut DashboardApp, key: egui::Key) {
+    let state = &mut app.triage;
+    match key {
+        egui::Key::J | egui::Key::ArrowRight | egui::Key::Space => return state.next(),
+        egui::Key::K | egui::Key::ArrowLeft => return state.previous(),
+        egui::Key::N => return state.next_chat(),
+        _ => {}
+    }
+    let Some(item) = state.current().cloned() else {
+        return;
+    };
+    let reference = format!("{}:{}", item.source(), item.message_id());
+
+    match key {
+        egui::Key::R => {
+            state.run(
+                tr!("triage-action-read", chat = item.title.clone()),
+                vec!["mark-read".into(), item.chat.clone(), "--through".into(), item.message_id()],
+            );
+            state.mark_read_through_current();
+            state.next();
+        }
+        egui::Key::A => {
+            state.run(tr!("triage-action-read", chat = item.title.clone()), vec!["mark-read".into(), item.chat.clone()]);
+            state.mark_chat_read();
+            state.next_chat();
+        }
+        egui::Key::B => {
+            state.run(
+                tr!("triage-action-bookmark"),
+                vec!["bookmark".into(), "add".into(), reference, "--chat".into(), item.chat_id().to_string()],
+            );
+            state.mark(Step::Bookmarked);
+            state.next();
+        }
+        egui::Key::L => {
+            state.run(
+                tr!("triage-action-remind"),
+                vec![
+                    "remind".into(),
+                    "add".into(),
+                    reference,
+                    "--in".into(),
+                    REMIND_IN.into(),
+                    "--chat".into(),
+                    item.chat_id().to_string(),
+                ],
+            );
+            state.mark(Step::Reminded);
+            state.next();
+        }
+        egui::Key::S | egui::Key::D => {
+            let Some(agent) = app.selected_agent.filter(|agent| app.agent_processes.contains_key(agent)) else {
+                app.toasts.warning(tr!("triage-select-agent"));
+                return;
+            };
+            let conversation = state.conversation();
+            let prompt = if key == egui::Key::S {
+                format!("Summarize these unread messages from {} in a few lines:\n\n{}", item.title, conversation)
+            } else {
+                format!(
+                    "Draft a short reply to the last of these messages from {}:\n\n{}\n\nReply with only the draft.",
+                    item.title, conversation
+                )
+            };
+            state.mark(Step::SentToAgent);
+            state.next();
+            app.deliver_to_agents(&prompt, Some(agent));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> TriageState {
+        let catchup: CatchUp = serde_json::from_value(serde_json::json!({
+            "summary": "Work: 2 unread",
+            "chats": [
+                { "chat": "telegram:1", "title": "Work", "unread": 2, "urgent": 1, "messages": [
+                    { "id": "10", "sender": { "display_name": "Ana" }, "content": { "Text": "Deadline moved" } },
+                    { "id": "11", "sender": { "id": "5", "display_name": "Ben" }, "content": { "Text": "To Friday" } }
+                ] },
+                { "chat": "telegram:2", "title": null, "unread": 1, "urgent": 0, "messages": [
+                    { "id": "20", "sender": { "username": "cy" }, "content": { "Text": "Hi" } }
+                ] }
+            ]
+        }))
+        .unwrap();
+        let mut state = TriageState::default();
+        state.set_items(queue(catchup));
+        state
+    }
+
+    #[test]
+    fn test_queue_and_navigation() {
+        let mut state = state();
+        assert_eq!(state.items.len(), 3);
+        assert_eq!(state.items[2].title, "telegram:2");
+        assert_eq!(state.current().unwrap().message_id(), "10");
+
+        state.next_chat();
+        assert_eq!(state.current().unwrap().chat_id(), "2");
+        state.previous();
+        state.previous();
+        state.previous();
+        assert_eq!(state.index, 0);
+
+        state.next_chat();
+        state.next_chat();
+        assert!(state.current().is_none());
+        state.next();
+        assert!(state.current().is_none());
+    }
+
+    #[test]
+    fn test_marking_read() {
+        let mut state = state();
+        state.next();
+        assert_eq!(state.conversation(), "Ana: Deadline moved\nBen: To Friday");
+
+        // Reading a message reads the chat's earlier messages too
+        state.mark_read_through_current();
+        assert!(state.items[0].steps.contains(&Step::Read));
+        assert!(state.items[1].steps.contains(&Step::Read));
+        assert_eq!(state.remaining(), 1);
+
+        state.next();
+        state.mark(Step::Bookmarked);
+        state.mark(Step::Bookmarked);
+        assert_eq!(state.items[2].steps, vec![Step::Bookmarked]);
+        assert_eq!(state.remaining(), 0);
+    }
+
+    #[test]
+    fn test_forget() {
+        let mut state = state();
+        state.next_chat();
+        state.forget(&ForgetTarget::User { source: "telegram".into(), user_id: "5".into() });
+        assert_eq!(state.items.len(), 2);
+        assert_eq!(state.current().unwrap().message_id(), "20");
+
+        state.forget(&ForgetTarget::Chat { source: "telegram".into(), chat_id: "2".into() });
+        assert_eq!(state.items.len(), 1);
+        assert!(state.current().is_none());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::i18n::tr;
+use crate::ui::app::DashboardApp;
+use crate::updates::Task;
+
+/// The chat CLI's version, what's new in the latest release and a button to install it
+pub fn show_update_window(ctx: &egui::Context, app: &mut DashboardApp) {
+    let mut open = app.show_update_window;
+    let mut check = false;
+    let mut install = false;
+
+    let window = egui::Window::new(tr!("updates-title")).id(egui::Id::new("update_window"));
+    window.open(&mut open).default_width(460.0).show(ctx, |ui| {
+        let updater = &app.updater;
+        match &updater.status {
+            Some(status) if status.disabled => {
+                ui.label(format!("chat {}", status.current));
+                ui.label(
+                    egui::RichText::new(tr!("updates-disabled"))
+                        .color(egui::Color32::GRAY),
+                );
+            }
+            Some(status) if status.update_available => {
+                ui.label(
+                    egui::RichText::new(tr!(
+                        "updates-available",
+                        latest = status.latest.as_deref().unwrap_or("?"),
+                        current = status.current.as_str()
+                    ))
+                    .strong(),
+                );
+                if !status.changelog.is_empty() {
+                    ui.separator();
+                    egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                        ui.label(&status.changelog);
+                    });
+                }
+                if !status.url.is_empty() {
+                    ui.hyperlink_to(tr!("updates-release-page"), &status.url);
+                }
+            }
+            Some(status) => {
+                ui.label(tr!("updates-up-to-date", current = status.current.as_str()));
+            }
+            None if updater.running() == Some(Task::Check) => {}
+            None => {
+                ui.label(tr!("updates-not-checked"));
+            }
+        }
+
+        if let Some(error) = &updater.error {
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+        }
+        if let Some(log) = &updater.install_log {
+            ui.separator();
+            ui.label(egui::RichText::new(log.trim()).monospace().size(11.0));
+            ui.label(tr!("updates-restart"));
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| match updater.running() {
+            Some(Task::Check) => {
+                ui.spinner();
+                ui.label(tr!("updates-checking"));
+            }
+            Some(Task::Install) => {
+                ui.spinner();
+                ui.label(tr!("updates-installing"));
+            }
+            None => {
+                check = ui.button(tr!("updates-check")).clicked();
+                let available = updater.status.as_ref().is_some_and(|status| status.update_available);
+                install = ui
+                    .add_enabled(available, egui::Button::new(tr!("updates-install")))
+                    .on_hover_text(tr!("updates-install-hover"))
+                    .clicked();
+            }
+        });
+    });
+
+    app.show_update_window = open;
+    if check {
+        app.updater.check(ctx);
+    }
+    if install {
+        app.updater.install(ctx);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::agent::AgentId;
+use crate::ui::app::{AppTab, DashboardApp};
+use crate::workspace::{self, WorkspaceStatus};
+use std::path::{Path, PathBuf};
+
+/// Quick actions on an agent's workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceAction {
+    OpenTerminal,
+    ShowDiff,
+    Commit,
+}
+
+/// Commit approval dialog; the agent drafts the message, the user edits and approves it
+pub struct CommitDialog {
+    pub agent: AgentId,
+    pub dir: PathBuf,
+    pub message: String,
+    /// Waiting for the agent's draft
+    pub drafting: bool,
+    pub error: Option<String>,
+}
+
+/// Open workspace windows
+#[derive(Default)]
+pub struct WorkspaceUi {
+    /// Window title and diff text
+    pub diff: Option<(String, String)>,
+    pub commit: Option<CommitDialog>,
+}
+
+/// Branch and dirty state under an agent in the agent list, with its quick actions menu
+pub fn agent_workspace_row(
+    ui: &mut egui::Ui,
+    response: &egui::Response,
+    dir: &Path,
+    status: Option<Result<WorkspaceStatus, String>>,
+) -> Option<WorkspaceAction> {
+    match &status {
+        Some(Ok(status)) => {
+            let color = if status.is_dirty() {
+                egui::Color32::from_rgb(229, 229, 16)
+            } else {
+                egui::Color32::GRAY
+            };
+            let kind = if status.linked_worktree { "worktree" } else { "checkout" };
+            ui.label(egui::RichText::new(status.summary()).size(11.0).color(color))
+                .on_hover_text(format!("{} ({})\n{} changed files", dir.display(), kind, status.changed));
+        }
+        Some(Err(error)) => {
+            ui.label(egui::RichText::new("⎇ not a git repo").size(11.0).color(egui::Color32::GRAY))
+                .on_hover_text(format!("{}: {}", dir.display(), error));
+        }
+        None => {}
+    }
+
+    let is_repo = matches!(status, Some(Ok(_)));
+    let mut action = None;
+    response.context_menu(|ui| {
+        if ui.button("Open terminal here").clicked() {
+            action = Some(WorkspaceAction::OpenTerminal);
+            ui.close_menu();
+        }
+        if ui.add_enabled(is_repo, egui::Button::new("Show diff")).clicked() {
+            action = Some(WorkspaceAction::ShowDiff);
+            ui.close_menu();
+        }
+        if ui.add_enabled(is_repo, egui::Button::new("Commit…")).clicked() {
+            action = Some(WorkspaceAction::Commit);
+            ui.close_menu();
+        }
+    });
+    action
+}
+
+/// Run a quick action for an agent's workspace
+pub fn handle_action(ctx: &egui::Context, app: &mut DashboardApp, agent: AgentId, dir: PathBuf, action: WorkspaceAction) {
+    let name = app.active_agents.get(&agent).cloned().unwrap_or_default();
+
+    match action {
+        WorkspaceAction::OpenTerminal => {
+            let dir = dir.display().to_string().replace('\'', r"'\''");
+            app.terminal_startup_command = format!("cd '{}' && exec ${{SHELL:-bash}}", dir);
+            app.reset_terminal(ctx);
+            app.current_tab = AppTab::Term;
+        }
+        WorkspaceAction::ShowDiff => {
+            let text = workspace::diff(&dir).unwrap_or_else(|e| format!("Failed to read diff: {}", e));
+            app.workspace_ui.diff = Some((format!("Diff: {}", name), text));
+        }
+        WorkspaceAction::Commit => {
+            let mut dialog = CommitDialog {
+                agent,
+                dir: dir.clone(),
+                message: String::new(),
+                drafting: false,
+                error: None,
+            };
+
+            // Agents without a running process leave the message to the user
+            if let Some(process) = app.agent_processes.get(&agent) {
+                let stat = workspace::diff_stat(&dir).unwrap_or_default();
+                match process.send_message(&workspace::commit_message_prompt(&stat)) {
+                    Ok(()) => dialog.drafting = true,
+                    Err(e) => dialog.error = Some(format!("Couldn't ask {} for a message: {}", name, e)),
+                }
+            }
+            app.workspace_ui.commit = Some(dialog);
+        }
+    }
+}
+
+/// Fill the commit dialog with an agent's reply if it's the draft being waited for
+pub fn take_commit_draft(app: &mut DashboardApp, agent: AgentId, text: &str) -> bool {
+    match &mut app.workspace_ui.commit {
+        Some(dialog) if dialog.drafting && dialog.agent == agent => {
+            dialog.message = text.trim().trim_matches('`').trim().to_string();
+            dialog.drafting = false;
+            true
+        }
+        _ => false,
+    }
+}
+
+pub fn show_workspace_windows(ctx: &egui::Context, app: &mut DashboardApp) {
+    if let Some((title, text)) = &app.workspace_ui.diff {
+        let mut open = true;
+        egui::Window::new(title.as_str())
+            .id(egui::Id::new("workspace_diff"))
+            .open(&mut open)
+            .default_size([700.0, 500.0])
+            .show(ctx, |ui| {
+                egui::ScrollArea::both().show(ui, |ui| {
+                    ui.add(egui::Label::new(egui::RichText::new(text).monospace()).extend());
+                });
+            });
+        if !open {
+            app.workspace_ui.diff = None;
+        }
+    }
+
+    let Some(dialog) = &mut app.workspace_ui.commit else {
+        return;
+    };
+    let name = app.active_agents.get(&dialog.agent).cloned().unwrap_or_default();
+
+    let mut commit = false;
+    let mut cancel = false;
+    egui::Window::new(format!("Commit changes from {}", name))
+        .id(egui::Id::new("workspace_commit"))
+        .collapsible(false)
+        .default_width(500.0)
+        .show(ctx, |ui| {
+            ui.label(egui::RichText::new(dialog.dir.display().to_string()).monospace().size(11.0));
+            if dialog.drafting {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Waiting for {} to draft a message…", name));
+                });
+            }
+            ui.add(
+                egui::TextEdit::multiline(&mut dialog.message)
+                    .hint_text("Commit message")
+                    .desired_rows(6)
+                    .desired_width(f32::INFINITY),
+            );
+            if let Some(error) = &dialog.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            ui.horizontal(|ui| {
+                let ready = !dialog.message.trim().is_empty();
+                if ui.add_enabled(ready, egui::Button::new("Commit all changes")).clicked() {
+                    commit = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if cancel {
+        app.workspace_ui.commit = None;
+    } else if commit {
+        match workspace::commit(&dialog.dir, &dialog.message) {
+            Ok(summary) => {
+                app.workspace_ui.commit = None;
+                app.toasts.info(format!("{}: {}", name, summary));
+                let workspaces = app.agent_workspaces();
+                app.workspaces.refresh(workspaces, ctx);
+            }
+            Err(e) => dialog.error = Some(e.to_string()),
+        }
+    }
+}
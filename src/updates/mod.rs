@@ -0,0 +1,144 @@
+//! Updating the chat CLI from the dashboard, through `chat self-update`
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::process::Command;
+use std::sync::mpsc;
+
+/// What `chat self-update --check --format json` prints
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UpdateCheck {
+    pub current: String,
+    pub latest: Option<String>,
+    pub update_available: bool,
+    #[serde(default)]
+    pub changelog: String,
+    #[serde(default)]
+    pub url: String,
+    /// Turned off with `[updates] check = false` in the chat CLI's sources.toml
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    Check,
+    Install,
+}
+
+/// Runs update checks and installs in the background, one at a time
+#[derive(Default)]
+pub struct Updater {
+    pub status: Option<UpdateCheck>,
+    pub error: Option<String>,
+    /// What the last install printed
+    pub install_log: Option<String>,
+    running: Option<(Task, mpsc::Receiver<Result<String>>)>,
+}
+
+impl Updater {
+    pub fn running(&self) -> Option<Task> {
+        self.running.as_ref().map(|(task, _)| *task)
+    }
+
+    pub fn check(&mut self, ctx: &egui::Context) {
+        self.start(Task::Check, &["self-update", "--check", "--format", "json"], ctx);
+    }
+
+    pub fn install(&mut self, ctx: &egui::Context) {
+        self.start(Task::Install, &["self-update"], ctx);
+    }
+
+    fn start(&mut self, task: Task, args: &'static [&'static str], ctx: &egui::Context) {
+        if self.running.is_some() {
+            return;
+        }
+        self.error = None;
+        let (tx, rx) = mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            // Installing reports progress on stderr; a check's JSON is all on stdout
+            let _ = tx.send(run_chat(args, task == Task::Install));
+            ctx.request_repaint();
+        });
+        self.running = Some((task, rx));
+    }
+
+    /// Take the result of a finished run. Returns the new status when a check just
+    /// found an update.
+    pub fn poll(&mut self) -> Option<&UpdateCheck> {
+        let (task, result) = match &self.running {
+            Some((task, rx)) => match rx.try_recv() {
+                Ok(result) => (*task, result),
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Err(mpsc::TryRecvError::Disconnected) => (*task, Err(anyhow::anyhow!("Update task stopped"))),
+            },
+            None => return None,
+        };
+        self.running = None;
+
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                self.error = Some(format!("{:#}", e));
+                return None;
+            }
+        };
+        match task {
+            Task::Check => match serde_json::from_str::<UpdateCheck>(output.trim()) {
+                Ok(status) => {
+                    self.status = Some(status);
+                    self.status.as_ref().filter(|status| status.update_available)
+                }
+                Err(e) => {
+                    self.error = Some(format!("Unexpected output from chat self-update: {}", e));
+                    None
+                }
+            },
+            Task::Install => {
+                self.install_log = Some(output);
+                // What's installed now is the latest
+                if let Some(status) = &mut self.status {
+                    status.update_available = false;
+                    status.current = status.latest.clone().unwrap_or_else(|| status.current.clone());
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Run the chat CLI, returning its output, with what it printed to stderr if `with_stderr`
+fn run_chat(args: &[&str], with_stderr: bool) -> Result<String> {
+    let output = Command::new(crate::config::paths::helper("chat"))
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .context("Failed to run chat CLI")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        anyhow::bail!("{}", stderr.trim());
+    }
+    Ok(if with_stderr { format!("{}{}", stdout, stderr) } else { stdout.into_owned() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_check() {
+        let status: UpdateCheck = serde_json::from_str(
+            r#"{"current":"0.1.0","latest":"0.2.0","update_available":true,"changelog":"- Faster sync","url":"https://example.com","disabled":false}"#,
+        )
+        .unwrap();
+        assert!(status.update_available);
+        assert_eq!(status.latest.as_deref(), Some("0.2.0"));
+
+        let disabled: UpdateCheck =
+            serde_json::from_str(r#"{"current":"0.1.0","latest":null,"update_available":false,"disabled":true}"#).unwrap();
+        assert!(disabled.disabled);
+        assert_eq!(disabled.changelog, "");
+    }
+}
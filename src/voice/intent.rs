@@ -0,0 +1,173 @@
+//! Small grammar mapping spoken commands to dashboard actions
+//!
+//! Matching is on words, ignoring case and punctuation, so it copes with how Whisper
+//! writes things down ("Ask Coder agent, to run the tests."). Text meant for an agent
+//! keeps its original spelling.
+
+/// Tabs a command can switch to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Home,
+    Chats,
+    Term,
+}
+
+/// What a spoken command asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intent {
+    /// Send a message to the named agent, as listed among the active agents
+    AskAgent { agent: String, message: String },
+    /// Send a message to every agent
+    Broadcast(String),
+    StopSpeaking,
+    ClearQueue,
+    /// Read the latest agent reply aloud
+    ReadLast,
+    SwitchTab(Tab),
+    /// Turn do-not-disturb on or off
+    DoNotDisturb(bool),
+}
+
+impl Intent {
+    /// Spoken back to confirm the command was understood
+    pub fn confirmation(&self) -> String {
+        match self {
+            Intent::AskAgent { agent, message } => format!("Asking {}: {}", agent, message),
+            Intent::Broadcast(message) => format!("Telling all agents: {}", message),
+            Intent::StopSpeaking => "Stopping".to_string(),
+            Intent::ClearQueue => "Cleared the speech queue".to_string(),
+            Intent::ReadLast => "Reading the last reply".to_string(),
+            Intent::SwitchTab(Tab::Home) => "Opening home".to_string(),
+            Intent::SwitchTab(Tab::Chats) => "Opening chats".to_string(),
+            Intent::SwitchTab(Tab::Term) => "Opening the terminal".to_string(),
+            Intent::DoNotDisturb(true) => "Do not disturb is on".to_string(),
+            Intent::DoNotDisturb(false) => "Do not disturb is off".to_string(),
+        }
+    }
+}
+
+/// A word as heard and as matched
+struct Word<'a> {
+    original: &'a str,
+    key: String,
+}
+
+/// Work out what `text` asks for; `agents` are the names of the active agents
+pub fn parse(text: &str, agents: &[String]) -> Option<Intent> {
+    let words: Vec<Word> = text
+        .split_whitespace()
+        .map(|original| Word {
+            original,
+            key: normalize(original),
+        })
+        .filter(|word| !word.key.is_empty())
+        .collect();
+    let keys: Vec<&str> = words.iter().map(|word| word.key.as_str()).collect();
+    // Everything from word `from` on, as spoken
+    let rest = |from: usize| -> Option<String> {
+        let rest = words.get(from..)?.iter().map(|word| word.original).collect::<Vec<_>>().join(" ");
+        let rest = rest.trim_matches(|c: char| c.is_ascii_punctuation() || c.is_whitespace());
+        (!rest.is_empty()).then(|| rest.to_string())
+    };
+
+    match keys.as_slice() {
+        ["stop"] | ["stop", "speaking" | "talking" | "reading"] | ["be", "quiet"] | ["quiet"] | ["silence"] => {
+            Some(Intent::StopSpeaking)
+        }
+        ["clear", "the", "queue"] | ["clear", "queue"] | ["clear", "the", "speech", "queue"] => Some(Intent::ClearQueue),
+        ["read" | "repeat", "the", "last", "message" | "reply"] | ["read" | "repeat", "last", "message" | "reply"] => {
+            Some(Intent::ReadLast)
+        }
+        ["open" | "show", ..] | ["go" | "switch", "to", ..] => {
+            let tab = keys.iter().find_map(|key| match *key {
+                "home" => Some(Tab::Home),
+                "chats" | "chat" => Some(Tab::Chats),
+                "term" | "terminal" => Some(Tab::Term),
+                _ => None,
+            })?;
+            Some(Intent::SwitchTab(tab))
+        }
+        ["mute"] | ["do", "not", "disturb", "on"] | ["turn", "on", "do", "not", "disturb"] => {
+            Some(Intent::DoNotDisturb(true))
+        }
+        ["unmute"] | ["do", "not", "disturb", "off"] | ["turn", "off", "do", "not", "disturb"] => {
+            Some(Intent::DoNotDisturb(false))
+        }
+        ["broadcast", ..] => rest(1 + skip_filler(&keys[1..])).map(Intent::Broadcast),
+        ["tell" | "ask", "everyone" | "everybody", ..] => rest(2 + skip_filler(&keys[2..])).map(Intent::Broadcast),
+        ["tell" | "ask", "all", "agents", ..] => rest(3 + skip_filler(&keys[3..])).map(Intent::Broadcast),
+        ["tell" | "ask", ..] => {
+            let start = if keys.get(1) == Some(&"the") { 2 } else { 1 };
+            let (agent, len) = match_agent(&keys[start..], agents)?;
+            let mut next = start + len;
+            if keys.get(next) == Some(&"agent") {
+                next += 1;
+            }
+            let message = rest(next + skip_filler(&keys[next..]))?;
+            Some(Intent::AskAgent { agent, message })
+        }
+        _ => None,
+    }
+}
+
+/// Lowercase letters and digits of a word
+fn normalize(word: &str) -> String {
+    word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Words like "to" between the addressee and the message
+fn skip_filler(keys: &[&str]) -> usize {
+    match keys.first() {
+        Some(&"to" | &"that" | &"about") => 1,
+        _ => 0,
+    }
+}
+
+/// The agent whose name the words start with, preferring the longest name, and how
+/// many words it took
+fn match_agent(keys: &[&str], agents: &[String]) -> Option<(String, usize)> {
+    agents
+        .iter()
+        .filter_map(|agent| {
+            let name: Vec<String> = agent.split_whitespace().map(normalize).filter(|w| !w.is_empty()).collect();
+            let matches = !name.is_empty() && keys.len() >= name.len() && name.iter().zip(keys).all(|(a, b)| a == b);
+            matches.then(|| (agent.clone(), name.len()))
+        })
+        .max_by_key(|(_, len)| *len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agents() -> Vec<String> {
+        vec!["Coder".to_string(), "Code Reviewer".to_string()]
+    }
+
+    #[test]
+    fn test_ask_agent() {
+        assert_eq!(
+            parse("Ask coder agent to run the tests.", &agents()),
+            Some(Intent::AskAgent { agent: "Coder".to_string(), message: "run the tests".to_string() })
+        );
+        assert_eq!(
+            parse("tell the code reviewer, Look at PR 12", &agents()),
+            Some(Intent::AskAgent { agent: "Code Reviewer".to_string(), message: "Look at PR 12".to_string() })
+        );
+        assert_eq!(parse("ask nobody to do it", &agents()), None);
+        assert_eq!(parse("ask coder", &agents()), None);
+    }
+
+    #[test]
+    fn test_other_commands() {
+        assert_eq!(parse("Broadcast: pull main", &agents()), Some(Intent::Broadcast("pull main".to_string())));
+        assert_eq!(parse("tell everyone to stop", &agents()), Some(Intent::Broadcast("stop".to_string())));
+        assert_eq!(parse("Stop speaking!", &agents()), Some(Intent::StopSpeaking));
+        assert_eq!(parse("clear the queue", &agents()), Some(Intent::ClearQueue));
+        assert_eq!(parse("Read the last message.", &agents()), Some(Intent::ReadLast));
+        assert_eq!(parse("switch to the terminal tab", &agents()), Some(Intent::SwitchTab(Tab::Term)));
+        assert_eq!(parse("open chats", &agents()), Some(Intent::SwitchTab(Tab::Chats)));
+        assert_eq!(parse("Do not disturb, on.", &agents()), Some(Intent::DoNotDisturb(true)));
+        assert_eq!(parse("what's the weather", &agents()), None);
+    }
+}
@@ -0,0 +1,374 @@
+//! Voice commands: a hotkey, or a wake word while listening continuously, records a
+//! spoken command, Whisper transcribes it and [`intent`] maps it to a dashboard action.
+//!
+//! Recording goes through an external recorder (SoX `rec` by default, which stops
+//! after a pause) and transcription through the whisper.cpp command line tool, the same
+//! one the chat CLI uses for voice notes.
+
+pub mod intent;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+/// How often a running recorder is checked for having finished or being stopped
+const RECORDER_POLL: Duration = Duration::from_millis(100);
+
+fn default_hotkey() -> String {
+    "Ctrl+Shift+Space".to_string()
+}
+
+fn default_record_command() -> Vec<String> {
+    // 16 kHz mono as Whisper wants it; starts on sound, stops after 1.2 s of quiet or 15 s
+    "rec -q -c 1 -r 16000 -b 16 {file} silence 1 0.1 2% 1 1.2 2% trim 0 15"
+        .split(' ')
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_whisper_binary() -> String {
+    "whisper-cli".to_string()
+}
+
+/// Voice command settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoiceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Starts listening for one command, e.g. "Ctrl+Shift+Space"
+    #[serde(default = "default_hotkey")]
+    pub hotkey: String,
+    /// Listen all the time and take what follows this as a command
+    #[serde(default)]
+    pub wake_word: Option<String>,
+    /// Records one utterance into `{file}` as 16 kHz WAV
+    #[serde(default = "default_record_command")]
+    pub record_command: Vec<String>,
+    /// whisper.cpp binary
+    #[serde(default = "default_whisper_binary")]
+    pub whisper_binary: String,
+    /// Path of the whisper.cpp ggml model file
+    #[serde(default)]
+    pub whisper_model: Option<PathBuf>,
+    /// Spoken language, e.g. "en"; detected by Whisper when None
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl Default for VoiceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hotkey: default_hotkey(),
+            wake_word: None,
+            record_command: default_record_command(),
+            whisper_binary: default_whisper_binary(),
+            whisper_model: None,
+            language: None,
+        }
+    }
+}
+
+/// Parse a hotkey like "Ctrl+Shift+Space" or "F9"
+pub fn parse_hotkey(hotkey: &str) -> Option<egui::KeyboardShortcut> {
+    let mut modifiers = egui::Modifiers::NONE;
+    let mut key = None;
+    for part in hotkey.split('+').map(str::trim) {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "control" => modifiers = modifiers | egui::Modifiers::CTRL,
+            "shift" => modifiers = modifiers | egui::Modifiers::SHIFT,
+            "alt" | "option" => modifiers = modifiers | egui::Modifiers::ALT,
+            "cmd" | "command" | "super" => modifiers = modifiers | egui::Modifiers::COMMAND,
+            _ if key.is_none() => key = Some(egui::Key::from_name(part)?),
+            _ => return None,
+        }
+    }
+    Some(egui::KeyboardShortcut::new(modifiers, key?))
+}
+
+/// What the listener is doing, for the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceState {
+    Idle,
+    /// Listening continuously for the wake word
+    WaitingForWakeWord,
+    /// Recording a command
+    Listening,
+    Transcribing,
+}
+
+impl VoiceState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::WaitingForWakeWord,
+            2 => Self::Listening,
+            3 => Self::Transcribing,
+            _ => Self::Idle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VoiceEvent {
+    /// The wake word alone; the next utterance is the command
+    Awake,
+    /// A command was heard
+    Heard(String),
+    Failed(String),
+}
+
+/// Records and transcribes voice commands on background threads
+pub struct VoiceListener {
+    config: VoiceConfig,
+    ctx: egui::Context,
+    state: Arc<AtomicU8>,
+    /// Set while a command is being recorded or transcribed
+    busy: Arc<AtomicBool>,
+    /// With a wake word: take the next utterance as a command without it
+    armed: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    events_tx: mpsc::Sender<VoiceEvent>,
+    events: mpsc::Receiver<VoiceEvent>,
+}
+
+impl VoiceListener {
+    /// Check the setup and, with a wake word, start listening for it
+    pub fn start(config: &VoiceConfig, ctx: egui::Context) -> Result<Self> {
+        let model = config.whisper_model.as_ref().context("Voice commands need a Whisper model")?;
+        if !model.exists() {
+            anyhow::bail!("Whisper model {} not found", model.display());
+        }
+        if !config.record_command.iter().any(|arg| arg.contains("{file}")) {
+            anyhow::bail!("The record command needs a {{file}} argument");
+        }
+
+        let (events_tx, events) = mpsc::channel();
+        let listener = Self {
+            config: config.clone(),
+            ctx,
+            state: Arc::new(AtomicU8::new(VoiceState::Idle as u8)),
+            busy: Arc::new(AtomicBool::new(false)),
+            armed: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(AtomicBool::new(false)),
+            events_tx,
+            events,
+        };
+        if let Some(wake_word) = config.wake_word.clone().filter(|word| !word.trim().is_empty()) {
+            listener.spawn_wake_loop(wake_word);
+        }
+        Ok(listener)
+    }
+
+    pub fn state(&self) -> VoiceState {
+        VoiceState::from_u8(self.state.load(Ordering::SeqCst))
+    }
+
+    /// Events since the last call
+    pub fn events(&self) -> Vec<VoiceEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// Record one command now, as for the hotkey. While listening for the wake word,
+    /// the next utterance counts as a command instead.
+    pub fn listen(&self) {
+        if self.config.wake_word.as_ref().is_some_and(|word| !word.trim().is_empty()) {
+            self.armed.store(true, Ordering::SeqCst);
+            return;
+        }
+        if self.busy.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let (config, ctx, state, busy, tx) = self.handles();
+        let stop = Arc::clone(&self.stop);
+        std::thread::spawn(move || {
+            let event = match utterance(&config, &state, &ctx, VoiceState::Listening, &stop) {
+                Ok(text) if text.is_empty() => VoiceEvent::Failed("Didn't hear anything".to_string()),
+                Ok(text) => VoiceEvent::Heard(text),
+                Err(e) => VoiceEvent::Failed(e.to_string()),
+            };
+            state.store(VoiceState::Idle as u8, Ordering::SeqCst);
+            busy.store(false, Ordering::SeqCst);
+            let _ = tx.send(event);
+            ctx.request_repaint();
+        });
+    }
+
+    fn spawn_wake_loop(&self, wake_word: String) {
+        let (config, ctx, state, _, tx) = self.handles();
+        let armed = Arc::clone(&self.armed);
+        let stop = Arc::clone(&self.stop);
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                let recording = if armed.load(Ordering::SeqCst) {
+                    VoiceState::Listening
+                } else {
+                    VoiceState::WaitingForWakeWord
+                };
+                let event = match utterance(&config, &state, &ctx, recording, &stop) {
+                    Ok(text) if text.is_empty() => None,
+                    Ok(text) if armed.swap(false, Ordering::SeqCst) => Some(VoiceEvent::Heard(text)),
+                    Ok(text) => match after_wake_word(&text, &wake_word) {
+                        Some(command) if command.is_empty() => {
+                            armed.store(true, Ordering::SeqCst);
+                            Some(VoiceEvent::Awake)
+                        }
+                        Some(command) => Some(VoiceEvent::Heard(command)),
+                        None => None,
+                    },
+                    Err(_) if stop.load(Ordering::SeqCst) => None,
+                    Err(e) => {
+                        let _ = tx.send(VoiceEvent::Failed(e.to_string()));
+                        ctx.request_repaint();
+                        // Don't spin on a missing recorder
+                        std::thread::sleep(Duration::from_secs(10));
+                        None
+                    }
+                };
+                if let Some(event) = event {
+                    if tx.send(event).is_err() {
+                        break;
+                    }
+                    ctx.request_repaint();
+                }
+            }
+            state.store(VoiceState::Idle as u8, Ordering::SeqCst);
+        });
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn handles(&self) -> (VoiceConfig, egui::Context, Arc<AtomicU8>, Arc<AtomicBool>, mpsc::Sender<VoiceEvent>) {
+        (
+            self.config.clone(),
+            self.ctx.clone(),
+            Arc::clone(&self.state),
+            Arc::clone(&self.busy),
+            self.events_tx.clone(),
+        )
+    }
+}
+
+impl Drop for VoiceListener {
+    fn drop(&mut self) {
+        // Ends the recording in progress, and with it the wake word loop
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Record one utterance and transcribe it
+fn utterance(
+    config: &VoiceConfig,
+    state: &AtomicU8,
+    ctx: &egui::Context,
+    recording: VoiceState,
+    stop: &AtomicBool,
+) -> Result<String> {
+    let file = std::env::temp_dir().join(format!("dashboard-voice-{}.wav", uuid::Uuid::new_v4().simple()));
+    state.store(recording as u8, Ordering::SeqCst);
+    ctx.request_repaint();
+    let result = record(config, &file, stop).and_then(|()| {
+        state.store(VoiceState::Transcribing as u8, Ordering::SeqCst);
+        ctx.request_repaint();
+        transcribe(config, &file)
+    });
+    let _ = std::fs::remove_file(&file);
+    result
+}
+
+/// Run the recorder until it stops by itself, or kill it once `stop` is set
+fn record(config: &VoiceConfig, file: &Path, stop: &AtomicBool) -> Result<()> {
+    let (program, args) = config.record_command.split_first().context("No record command set")?;
+    let file = file.to_string_lossy();
+    let mut child = Command::new(program)
+        .args(args.iter().map(|arg| arg.replace("{file}", &file)))
+        .spawn()
+        .with_context(|| format!("Failed to run {}; is it installed?", program))?;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if stop.load(Ordering::SeqCst) {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("Stopped listening");
+        }
+        std::thread::sleep(RECORDER_POLL);
+    };
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program, status);
+    }
+    Ok(())
+}
+
+fn transcribe(config: &VoiceConfig, file: &Path) -> Result<String> {
+    let model = config.whisper_model.as_ref().context("No Whisper model set")?;
+    let mut command = Command::new(&config.whisper_binary);
+    command.arg("-m").arg(model).arg("-f").arg(file).args(["-nt", "-np"]);
+    if let Some(language) = &config.language {
+        command.args(["-l", language]);
+    }
+    let output = command
+        .output()
+        .with_context(|| format!("Failed to run {}; is whisper.cpp installed?", config.whisper_binary))?;
+    if !output.status.success() {
+        anyhow::bail!("{} failed: {}", config.whisper_binary, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(normalize_transcript(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Whisper's output as one line, without markers for silence and noise like
+/// `[BLANK_AUDIO]` or `(wind blowing)`
+fn normalize_transcript(raw: &str) -> String {
+    let mut text = String::new();
+    let mut depth = 0usize;
+    for c in raw.chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The command following the wake word at the start of `text`, ignoring case and
+/// punctuation, or None if it doesn't start with the wake word
+fn after_wake_word(text: &str, wake_word: &str) -> Option<String> {
+    let key = |word: &str| word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect::<String>();
+    let wake: Vec<String> = wake_word.split_whitespace().map(key).collect();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.len() < wake.len() || !wake.iter().zip(&words).all(|(w, word)| *w == key(word)) {
+        return None;
+    }
+    let rest = words[wake.len()..].join(" ");
+    Some(rest.trim_matches(|c: char| c.is_ascii_punctuation() || c.is_whitespace()).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hotkey() {
+        let shortcut = parse_hotkey("Ctrl+Shift+Space").unwrap();
+        assert_eq!(shortcut.modifiers, egui::Modifiers::CTRL | egui::Modifiers::SHIFT);
+        assert_eq!(shortcut.logical_key, egui::Key::Space);
+        assert_eq!(parse_hotkey("F9").unwrap().logical_key, egui::Key::F9);
+        assert!(parse_hotkey("Ctrl+Nope").is_none());
+        assert!(parse_hotkey("Ctrl").is_none());
+    }
+
+    #[test]
+    fn test_transcripts() {
+        assert_eq!(normalize_transcript("[BLANK_AUDIO]\n"), "");
+        assert_eq!(normalize_transcript(" Ask coder\n (keyboard clicking) to run the tests.\n"), "Ask coder to run the tests.");
+        assert_eq!(after_wake_word("Hey, Dashboard! Stop.", "hey dashboard"), Some("Stop".to_string()));
+        assert_eq!(after_wake_word("Hey dashboard.", "hey dashboard"), Some(String::new()));
+        assert_eq!(after_wake_word("Hey there", "hey dashboard"), None);
+    }
+}
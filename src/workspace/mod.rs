@@ -0,0 +1,258 @@
+use crate::agent::{AgentConfig, AgentId};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Git status of an agent's workspace
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WorkspaceStatus {
+    /// Branch name, None when HEAD is detached
+    pub branch: Option<String>,
+    /// Short commit hash of HEAD, None before the first commit
+    pub head: Option<String>,
+    /// Commits ahead of and behind the upstream branch
+    pub ahead: u32,
+    pub behind: u32,
+    /// Changed, staged and untracked files
+    pub changed: usize,
+    /// Whether this is a linked worktree rather than the main checkout
+    pub linked_worktree: bool,
+}
+
+impl WorkspaceStatus {
+    pub fn is_dirty(&self) -> bool {
+        self.changed > 0
+    }
+
+    /// Short label for the agent list, e.g. "⎇ feature ●3 ↑1"
+    pub fn summary(&self) -> String {
+        let mut summary = match (&self.branch, &self.head) {
+            (Some(branch), _) => format!("⎇ {}", branch),
+            (None, Some(head)) => format!("⎇ {} (detached)", head),
+            (None, None) => "⎇ ?".to_string(),
+        };
+        if self.changed > 0 {
+            summary.push_str(&format!(" ●{}", self.changed));
+        }
+        if self.ahead > 0 {
+            summary.push_str(&format!(" ↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            summary.push_str(&format!(" ↓{}", self.behind));
+        }
+        summary
+    }
+}
+
+/// Parse `git status --porcelain=v2 --branch` output
+pub fn parse_status(output: &str) -> WorkspaceStatus {
+    let mut status = WorkspaceStatus::default();
+
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => status.head = Some(value.chars().take(8).collect()),
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split_whitespace() {
+                        if let Some(n) = part.strip_prefix('+') {
+                            status.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            status.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        } else if !line.is_empty() && !line.starts_with('!') {
+            // "1 ", "2 " (renames), "u " (unmerged) and "? " (untracked) entries
+            status.changed += 1;
+        }
+    }
+
+    status
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{}", stderr.trim().trim_start_matches("fatal: "));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Read the git status of a directory
+pub fn status(dir: &Path) -> Result<WorkspaceStatus> {
+    let mut status = parse_status(&git(dir, &["status", "--porcelain=v2", "--branch"])?);
+
+    // A linked worktree has its own git dir inside the main repo's common dir
+    let dirs = git(dir, &["rev-parse", "--git-dir", "--git-common-dir"])?;
+    let mut dirs = dirs.lines().map(|d| dir.join(d.trim()));
+    if let (Some(git_dir), Some(common_dir)) = (dirs.next(), dirs.next()) {
+        status.linked_worktree = git_dir.canonicalize().ok() != common_dir.canonicalize().ok();
+    }
+
+    Ok(status)
+}
+
+/// Uncommitted changes against HEAD, with untracked files listed after the diff
+pub fn diff(dir: &Path) -> Result<String> {
+    let mut diff = git(dir, &["diff", "HEAD", "--stat", "--patch"])
+        .or_else(|_| git(dir, &["diff", "--cached", "--stat", "--patch"]))?;
+
+    let untracked = git(dir, &["ls-files", "--others", "--exclude-standard"])?;
+    if !untracked.trim().is_empty() {
+        diff.push_str("\nUntracked files:\n");
+        for file in untracked.lines() {
+            diff.push_str(&format!("  {}\n", file));
+        }
+    }
+
+    if diff.trim().is_empty() {
+        diff = "No changes".to_string();
+    }
+    Ok(diff)
+}
+
+/// Summary of uncommitted changes, one line per file
+pub fn diff_stat(dir: &Path) -> Result<String> {
+    git(dir, &["diff", "HEAD", "--stat"])
+}
+
+/// Stage everything and commit it, returning git's summary line
+pub fn commit(dir: &Path, message: &str) -> Result<String> {
+    if message.trim().is_empty() {
+        anyhow::bail!("Commit message is empty");
+    }
+    git(dir, &["add", "-A"])?;
+    let output = git(dir, &["commit", "-m", message.trim()])?;
+    Ok(output.lines().next().unwrap_or_default().to_string())
+}
+
+/// The workspace directory from an agent's settings, if it has one
+pub fn agent_workspace(agent: &AgentConfig) -> Option<PathBuf> {
+    agent.config_data["workspace"]
+        .as_str()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Prompt asking an agent for a commit message
+pub fn commit_message_prompt(diff_stat: &str) -> String {
+    format!(
+        "Write a commit message for the uncommitted changes in your workspace. \
+         Reply with only the message: a short summary line, then a blank line and details if needed.\n\n{}",
+        diff_stat.trim()
+    )
+}
+
+/// Git status of every agent workspace, refreshed in the background
+pub struct WorkspaceTracker {
+    statuses: Arc<Mutex<HashMap<AgentId, std::result::Result<WorkspaceStatus, String>>>>,
+    last_refresh: Option<Instant>,
+    interval: Duration,
+}
+
+impl WorkspaceTracker {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            statuses: Arc::new(Mutex::new(HashMap::new())),
+            last_refresh: None,
+            interval,
+        }
+    }
+
+    /// Refresh in a background thread unless the last refresh is recent
+    pub fn refresh_if_due(&mut self, workspaces: Vec<(AgentId, PathBuf)>, ctx: &egui::Context) {
+        if self.last_refresh.is_some_and(|t| t.elapsed() < self.interval) {
+            return;
+        }
+        self.refresh(workspaces, ctx);
+    }
+
+    /// Refresh now in a background thread
+    pub fn refresh(&mut self, workspaces: Vec<(AgentId, PathBuf)>, ctx: &egui::Context) {
+        self.last_refresh = Some(Instant::now());
+
+        let statuses = Arc::clone(&self.statuses);
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let fresh: HashMap<_, _> = workspaces
+                .into_iter()
+                .map(|(id, dir)| (id, status(&dir).map_err(|e| e.to_string())))
+                .collect();
+            if let Ok(mut statuses) = statuses.lock() {
+                *statuses = fresh;
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    pub fn status(&self, id: &AgentId) -> Option<std::result::Result<WorkspaceStatus, String>> {
+        self.statuses.lock().ok()?.get(id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let output = "\
+# branch.oid 4be796e1d2c3b4a5
+# branch.head agent/refactor
+# branch.upstream origin/agent/refactor
+# branch.ab +2 -1
+1 .M N... 100644 100644 100644 abc abc src/main.rs
+2 R. N... 100644 100644 100644 abc abc R100 new.rs\told.rs
+? notes.txt
+! target/
+";
+        let status = parse_status(output);
+        assert_eq!(status.branch.as_deref(), Some("agent/refactor"));
+        assert_eq!(status.head.as_deref(), Some("4be796e1"));
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert_eq!(status.changed, 3);
+        assert_eq!(status.summary(), "⎇ agent/refactor ●3 ↑2 ↓1");
+    }
+
+    #[test]
+    fn test_parse_detached_and_initial() {
+        let detached = parse_status("# branch.oid 0123456789ab\n# branch.head (detached)\n");
+        assert_eq!(detached.branch, None);
+        assert!(!detached.is_dirty());
+        assert_eq!(detached.summary(), "⎇ 01234567 (detached)");
+
+        let initial = parse_status("# branch.oid (initial)\n# branch.head main\n");
+        assert_eq!(initial.head, None);
+        assert_eq!(initial.summary(), "⎇ main");
+    }
+
+    #[test]
+    fn test_agent_workspace() {
+        let mut agent = AgentConfig {
+            id: AgentId::new(),
+            name: "Ollama".to_string(),
+            agent_type: crate::agent::AgentType::Ollama,
+            config_data: serde_json::json!({ "host": "http://localhost:11434" }),
+            sandbox: Default::default(),
+            context: Default::default(),
+        };
+        assert_eq!(agent_workspace(&agent), None);
+
+        agent.config_data = serde_json::json!({ "workspace": "/src/app-wt1" });
+        assert_eq!(agent_workspace(&agent), Some(PathBuf::from("/src/app-wt1")));
+    }
+}